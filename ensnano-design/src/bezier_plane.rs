@@ -33,6 +33,10 @@ pub use import_from_svg::*;
 pub struct BezierPlaneDescriptor {
     pub position: Vec3,
     pub orientation: Rotor3,
+    /// The name given to the plane by the user. An empty name means that the plane has not been
+    /// named.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
 }
 
 impl Default for BezierPlaneDescriptor {
@@ -40,6 +44,7 @@ impl Default for BezierPlaneDescriptor {
         Self {
             position: Vec3::zero(),
             orientation: Rotor3::identity(),
+            name: String::new(),
         }
     }
 }
@@ -69,6 +74,16 @@ impl BezierPlanes {
 }
 
 impl BezierPlaneDescriptor {
+    /// The name of the plane, or a default name derived from its identifier if it has not been
+    /// named.
+    pub fn name_or_default(&self, id: BezierPlaneId) -> String {
+        if self.name.is_empty() {
+            format!("Plane {}", id.0)
+        } else {
+            self.name.clone()
+        }
+    }
+
     pub fn ray_intersection(
         &self,
         origin: Vec3,
@@ -146,7 +161,8 @@ pub struct BezierPlanesMut<'a> {
 }
 
 impl<'a> BezierPlanesMut<'a> {
-    pub fn push(&mut self, desc: BezierPlaneDescriptor) {
+    /// Add a new plane and return its identifier.
+    pub fn push(&mut self, desc: BezierPlaneDescriptor) -> BezierPlaneId {
         let new_key = self
             .new_map
             .keys()
@@ -154,6 +170,16 @@ impl<'a> BezierPlanesMut<'a> {
             .map(|m| BezierPlaneId(m.0 + 1))
             .unwrap_or_default();
         self.new_map.insert(new_key, Arc::new(desc));
+        new_key
+    }
+
+    pub fn get_mut(&mut self, id: &BezierPlaneId) -> Option<&mut BezierPlaneDescriptor> {
+        self.new_map.get_mut(id).map(Arc::make_mut)
+    }
+
+    #[must_use]
+    pub fn remove(&mut self, id: &BezierPlaneId) -> Option<()> {
+        self.new_map.remove(id).map(|_| ())
     }
 }
 
@@ -191,6 +217,23 @@ impl BezierPaths {
             source: self,
         }
     }
+
+    /// The vertices of all the paths that are positioned on the plane `plane_id`.
+    pub fn vertices_on_plane(&self, plane_id: BezierPlaneId) -> Vec<BezierVertexId> {
+        self.0
+            .iter()
+            .flat_map(|(path_id, path)| {
+                path.vertices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| v.plane_id == plane_id)
+                    .map(move |(vertex_id, _)| BezierVertexId {
+                        path_id: *path_id,
+                        vertex_id,
+                    })
+            })
+            .collect()
+    }
 }
 
 impl<'a> BezierPathsMut<'a> {
@@ -245,6 +288,10 @@ impl<'a> Drop for BezierPathsMut<'a> {
     }
 }
 
+/// The number of points of a segment that are sampled to find the point of the segment that is
+/// the closest to a given position
+const NB_SAMPLES_VERTEX_INSERTION: usize = 200;
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct BezierPath {
     vertices: Vec<BezierVertex>,
@@ -281,6 +328,74 @@ impl BezierPath {
         }
     }
 
+    /// Insert a vertex at `position` on the segment of the path that starts at vertex
+    /// `after_vertex`, and return the identifier of the new vertex.
+    ///
+    /// The segment is split with de Casteljau's algorithm at the point of the segment that is the
+    /// closest to `position`, so that the shape of the curve is preserved when `position` is on
+    /// the curve. The tangents of the extremities of the segment are fixed to their current value
+    /// since their default value depends on their neighbours.
+    ///
+    /// Return `None` if the path has no segment starting at `after_vertex`.
+    pub fn insert_vertex(&mut self, after_vertex: usize, position: Vec2) -> Option<usize> {
+        let nb_vertices = self.vertices.len();
+        let next_vertex = if after_vertex + 1 < nb_vertices {
+            after_vertex + 1
+        } else if self.cyclic && after_vertex + 1 == nb_vertices && nb_vertices > 1 {
+            0
+        } else {
+            return None;
+        };
+        let ends = self.instantiate()?.ends;
+        let to_vec2 = |v: Vec3| Vec2::new(v.x, v.y);
+        let p0 = to_vec2(ends[after_vertex].position);
+        let c1 = p0 + to_vec2(ends[after_vertex].vector_out);
+        let p3 = to_vec2(ends[next_vertex].position);
+        let c2 = p3 - to_vec2(ends[next_vertex].vector_in);
+
+        let lerp = |a: Vec2, b: Vec2, t: f32| a + (b - a) * t;
+        let de_casteljau = |t: f32| {
+            let q0 = lerp(p0, c1, t);
+            let q1 = lerp(c1, c2, t);
+            let q2 = lerp(c2, p3, t);
+            let r0 = lerp(q0, q1, t);
+            let r1 = lerp(q1, q2, t);
+            (q0, r0, lerp(r0, r1, t), r1, q2)
+        };
+        let t = (0..=NB_SAMPLES_VERTEX_INSERTION)
+            .map(|i| i as f32 / NB_SAMPLES_VERTEX_INSERTION as f32)
+            .min_by(|t1, t2| {
+                let d1 = (de_casteljau(*t1).2 - position).mag_sq();
+                let d2 = (de_casteljau(*t2).2 - position).mag_sq();
+                d1.total_cmp(&d2)
+            })?;
+        let (q0, r0, split_point, r1, q2) = de_casteljau(t);
+
+        for (v_id, end) in [
+            (after_vertex, &ends[after_vertex]),
+            (next_vertex, &ends[next_vertex]),
+        ] {
+            let vertex = &mut self.vertices[v_id];
+            vertex.position_in = Some(vertex.position - to_vec2(end.vector_in));
+            vertex.position_out = Some(vertex.position + to_vec2(end.vector_out));
+        }
+        self.vertices[after_vertex].position_out = Some(q0);
+        self.vertices[next_vertex].position_in = Some(q2);
+
+        let previous = self.vertices[after_vertex];
+        let next = self.vertices[next_vertex];
+        let new_vertex = BezierVertex {
+            plane_id: previous.plane_id,
+            position,
+            position_in: Some(position - (split_point - r0)),
+            position_out: Some(position + (r1 - split_point)),
+            grid_translation: previous.grid_translation * (1. - t) + next.grid_translation * t,
+            angle_with_plane: previous.angle_with_plane * (1. - t) + next.angle_with_plane * t,
+        };
+        self.vertices.insert(after_vertex + 1, new_vertex);
+        Some(after_vertex + 1)
+    }
+
     pub fn set_vector_out(&mut self, i: usize, vector_out: Vec3, planes: &BezierPlanes) {
         if let Some(v) = self.vertices_mut().get_mut(i) {
             v.set_vector_out(vector_out, planes)
@@ -540,6 +655,43 @@ impl InstanciatedPath {
             .unwrap_or(&[])
     }
 
+    /// Return the index of the segment of the path, drawn on its bezier planes, that is the
+    /// closest to `point` together with the distance between `point` and this segment.
+    ///
+    /// Segment `i` is the segment that starts at vertex `i`.
+    pub fn closest_segment(&self, point: Vec3) -> Option<(usize, f32)> {
+        let ends = self.bezier_controls();
+        let nb_segments = if self.source_path.cyclic {
+            ends.len()
+        } else {
+            ends.len().saturating_sub(1)
+        };
+        if ends.len() < 2 {
+            return None;
+        }
+        (0..nb_segments)
+            .map(|i| {
+                let (start, end) = (&ends[i], &ends[(i + 1) % ends.len()]);
+                let p0 = start.position;
+                let c1 = start.position + start.vector_out;
+                let c2 = end.position - end.vector_in;
+                let p3 = end.position;
+                let distance = (0..=NB_SAMPLES_VERTEX_INSERTION)
+                    .map(|s| {
+                        let t = s as f32 / NB_SAMPLES_VERTEX_INSERTION as f32;
+                        let u = 1. - t;
+                        let position = p0 * (u * u * u)
+                            + c1 * (3. * u * u * t)
+                            + c2 * (3. * u * t * t)
+                            + p3 * (t * t * t);
+                        (position - point).mag()
+                    })
+                    .fold(f32::INFINITY, f32::min);
+                (i, distance)
+            })
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+    }
+
     pub fn get_curve_points(&self) -> &[DVec3] {
         self.curve_2d
             .as_ref()
@@ -690,6 +842,8 @@ impl BezierPathData {
                                 orientation: self.orientation_vertex(vertex_id)?,
                                 position: self.position_vertex_2d(vertex_id)? + v.grid_translation,
                                 bezier_vertex: Some(vertex_id),
+                                bounds: None,
+                                phantom_length: None,
                             };
                             Some((GridId::BezierPathGrid(vertex_id), desc))
                         })
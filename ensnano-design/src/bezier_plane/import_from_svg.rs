@@ -19,50 +19,124 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use std::path::Path as StdPath;
 
 use svg::node::element::path::{Command, Data, Position};
+use svg::node::element::tag;
 use svg::parser::Event;
 
 use super::*;
 
-const SCALE: Vec2 = Vec2 { x: 0.1, y: 0.1 };
-const ORIGIN: Vec2 = Vec2 {
-    x: 134.23425,
-    y: 13.5557,
-};
+/// The default length, in nanometers, of one unit of an imported svg file
+pub const DEFAULT_SVG_IMPORT_SCALE: f32 = 0.1;
 
-pub fn read_first_svg_path(file_path: &StdPath) -> Result<BezierPath, SvgImportError> {
+/// The parameters of the import of the paths of an svg file.
+#[derive(Debug, Clone)]
+pub struct SvgImportParameters {
+    /// The length, in nanometers, of one unit of the svg file
+    pub scale: f32,
+    /// The plane on which the paths are created
+    pub plane: SvgImportPlane,
+    /// The position, in the coordinates of the plane, of the center of the imported paths
+    pub position: Vec2,
+}
+
+impl Default for SvgImportParameters {
+    fn default() -> Self {
+        Self {
+            scale: DEFAULT_SVG_IMPORT_SCALE,
+            plane: SvgImportPlane::New(Default::default()),
+            position: Vec2::zero(),
+        }
+    }
+}
+
+/// The plane on which the paths of an svg file are imported.
+#[derive(Debug, Clone)]
+pub enum SvgImportPlane {
+    Existing(BezierPlaneId),
+    /// A new plane, created with the imported paths
+    New(BezierPlaneDescriptor),
+}
+
+impl SvgImportParameters {
+    /// Scale the paths read by [read_svg_paths] and put them on the plane `plane_id`, so that the
+    /// center of their bounding box is at `self.position`.
+    ///
+    /// The y axis of svg files points downward, so the paths are mirrored to be drawn the right
+    /// way up on the plane.
+    pub fn place_paths(&self, paths: &mut [BezierPath], plane_id: BezierPlaneId) {
+        let (min, max) = paths.iter().flat_map(|p| p.vertices.iter()).fold(
+            (
+                Vec2::broadcast(f32::INFINITY),
+                Vec2::broadcast(f32::NEG_INFINITY),
+            ),
+            |(min, max), v| {
+                (
+                    min.min_by_component(v.position),
+                    max.max_by_component(v.position),
+                )
+            },
+        );
+        let center = (min + max) / 2.;
+        let place =
+            |p: Vec2| self.position + self.scale * Vec2::new(p.x - center.x, center.y - p.y);
+        for vertex in paths.iter_mut().flat_map(|p| p.vertices.iter_mut()) {
+            vertex.plane_id = plane_id;
+            vertex.position = place(vertex.position);
+            vertex.position_in = vertex.position_in.map(place);
+            vertex.position_out = vertex.position_out.map(place);
+        }
+    }
+}
+
+/// Read the paths of an svg file. Each subpath becomes a [BezierPath] whose vertices are in the
+/// coordinates of the svg file, see [SvgImportParameters::place_paths].
+pub fn read_svg_paths(file_path: &StdPath) -> Result<Vec<BezierPath>, SvgImportError> {
     let mut content = String::new();
     let events = svg::open(file_path, &mut content)?;
+    paths_of_events(events)
+}
 
+fn paths_of_events<'a>(
+    events: impl Iterator<Item = Event<'a>>,
+) -> Result<Vec<BezierPath>, SvgImportError> {
+    let mut paths = Vec::new();
     for event in events {
-        if let Event::Tag(_, _, attributes) = event {
+        if let Event::Tag(tag::Path, tag_type, attributes) = event {
+            if let tag::Type::End = tag_type {
+                continue;
+            }
             let data = attributes
                 .get("d")
                 .ok_or_else(|| SvgImportError::AttributeNotFound(String::from("d")))?;
             let data = Data::parse(data)?;
 
-            let mut ret = PathBuilder::default();
+            let mut builder = PathBuilder::default();
             for command in data.iter() {
                 match command {
-                    Command::Close => return Ok(ret.close()),
+                    Command::Close => paths.extend(builder.close()),
                     Command::Move(Position::Absolute, parameters) => {
                         if parameters.len() != 2 {
                             return Err(SvgImportError::BadParameters);
                         } else {
-                            let at = Vec2::new(parameters[0], parameters[1]);
-                            ret.start(at)?;
+                            // A move starts a new subpath
+                            paths.extend(builder.finish());
+                            builder.start(Vec2::new(parameters[0], parameters[1]));
                         }
                     }
                     Command::CubicCurve(Position::Absolute, parameters) => {
                         let arg = MoveToParameter::from_svg_paramter(parameters)?;
-                        ret.move_to(arg)?
+                        builder.move_to(arg)?
                     }
                     _ => (),
                 }
             }
-            return Ok(ret.finish());
+            paths.extend(builder.finish());
         }
     }
-    Err(SvgImportError::NoPathFound)
+    if paths.is_empty() {
+        Err(SvgImportError::NoPathFound)
+    } else {
+        Ok(paths)
+    }
 }
 
 #[derive(Default)]
@@ -71,21 +145,19 @@ struct PathBuilder {
 }
 
 impl PathBuilder {
-    fn start(&mut self, at: Vec2) -> Result<(), SvgImportError> {
-        if self.vertices.is_empty() {
-            self.vertices = vec![BezierVertex {
-                plane_id: BezierPlaneId(0),
-                position: SCALE * at - ORIGIN,
-                position_in: None,
-                position_out: None,
-                grid_translation: Vec3::zero(),
-                angle_with_plane: 0.,
-            }]
-        } else {
-            return Err(SvgImportError::UnexpectedCommand(String::from("Move")));
+    fn vertex(position: Vec2) -> BezierVertex {
+        BezierVertex {
+            plane_id: BezierPlaneId(0),
+            position,
+            position_in: None,
+            position_out: None,
+            grid_translation: Vec3::zero(),
+            angle_with_plane: 0.,
         }
+    }
 
-        Ok(())
+    fn start(&mut self, at: Vec2) {
+        self.vertices = vec![Self::vertex(at)];
     }
 
     fn move_to(&mut self, parameters: MoveToParameter) -> Result<(), SvgImportError> {
@@ -93,35 +165,42 @@ impl PathBuilder {
             .vertices
             .last_mut()
             .ok_or_else(|| SvgImportError::UnexpectedCommand(String::from("CubicCurve")))?;
-        prev_vertex.position_out = Some(SCALE * parameters.control_1 - ORIGIN);
+        prev_vertex.position_out = Some(parameters.control_1);
 
-        let new_vertex = BezierVertex {
-            plane_id: BezierPlaneId(0),
-            position: SCALE * parameters.position - ORIGIN,
-            position_out: None,
-            position_in: Some(SCALE * parameters.control_2 - ORIGIN),
-            grid_translation: Vec3::zero(),
-            angle_with_plane: 0.,
-        };
-        self.vertices.push(new_vertex);
+        self.vertices.push(BezierVertex {
+            position_in: Some(parameters.control_2),
+            ..Self::vertex(parameters.position)
+        });
 
         Ok(())
     }
 
-    fn close(self) -> BezierPath {
-        BezierPath {
-            vertices: self.vertices,
+    /// The subpath being built, made cyclic.
+    fn close(&mut self) -> Option<BezierPath> {
+        let mut vertices = std::mem::take(&mut self.vertices);
+        // The last segment of closed subpaths usually ends on the first vertex, which must not
+        // be duplicated
+        if vertices.len() > 2
+            && (vertices[0].position - vertices[vertices.len() - 1].position).mag() < 1e-3
+        {
+            let last = vertices.pop()?;
+            vertices[0].position_in = last.position_in;
+        }
+        (!vertices.is_empty()).then(|| BezierPath {
+            vertices,
             cyclic: true,
             grid_type: None,
-        }
+        })
     }
 
-    fn finish(self) -> BezierPath {
-        BezierPath {
-            vertices: self.vertices,
+    /// The subpath being built, if any.
+    fn finish(&mut self) -> Option<BezierPath> {
+        let vertices = std::mem::take(&mut self.vertices);
+        (!vertices.is_empty()).then(|| BezierPath {
+            vertices,
             cyclic: false,
             grid_type: None,
-        }
+        })
     }
 }
 
@@ -167,3 +246,39 @@ impl From<svg::parser::Error> for SvgImportError {
         Self::SvgParserError(e)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_SUBPATHS: &str = r#"<svg xmlns="http://www.w3.org/2000/svg">
+        <path d="M 0 0 C 10 0 20 10 20 20 C 20 30 10 40 0 40 Z M 100 0 C 110 0 120 10 120 20" />
+    </svg>"#;
+
+    #[test]
+    fn each_subpath_is_a_path() {
+        let paths = paths_of_events(svg::read(TWO_SUBPATHS).unwrap()).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].cyclic);
+        assert_eq!(paths[0].vertices().len(), 3);
+        assert!(!paths[1].cyclic);
+        assert_eq!(paths[1].vertices().len(), 2);
+    }
+
+    #[test]
+    fn paths_are_centered_on_the_position() {
+        let mut paths = paths_of_events(svg::read(TWO_SUBPATHS).unwrap()).unwrap();
+        let parameters = SvgImportParameters {
+            scale: 0.5,
+            plane: SvgImportPlane::Existing(BezierPlaneId(2)),
+            position: Vec2::new(1., 2.),
+        };
+        parameters.place_paths(&mut paths, BezierPlaneId(2));
+        let first = paths[0].vertices()[0];
+        assert_eq!(first.plane_id, BezierPlaneId(2));
+        // The bounding box of the positions of the vertices is [0, 120] x [0, 40]
+        assert_eq!(first.position, Vec2::new(1. - 30., 2. + 10.));
+        let last = paths[1].vertices()[1];
+        assert_eq!(last.position, Vec2::new(1. + 30., 2.));
+    }
+}
@@ -0,0 +1,415 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Import of cadnano designs.
+//!
+//! In a cadnano file, each helix (called a virtual strand) stores, for both the scaffold and the
+//! staples, an array whose `i`-th entry gives the 5' and 3' neighbours of the nucleotide at
+//! position `i`. The strands are reconstructed by following these links. Loops and skips are
+//! mapped onto ENSnano insertions and onto a shift of the nucleotides positions. Skipped
+//! nucleotides are dropped rather than imported as ENSnano deletions, which are only markers and
+//! would not shorten the strands.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use super::grid::{Grid, GridDescriptor, GridId, GridTypeDescr};
+use super::{read_junctions, sanitize_domains, Domain, Helix, HelixInterval, Strand};
+use ultraviolet::{Rotor3, Vec3};
+
+/// The length of the arrays of honeycomb designs is a multiple of this value.
+const HONEYCOMB_STEP: usize = 21;
+/// The length of the arrays of square designs is a multiple of this value.
+const SQUARE_STEP: usize = 32;
+/// The value of an array entry that has no 5' or 3' neighbour.
+const NO_NEIGHBOUR: [isize; 4] = [-1, -1, -1, -1];
+
+const SCAFFOLD_COLOR: u32 = 0xFF_3498DB;
+/// The color given by cadnano to the staples that have no color.
+const DEFAULT_STAPLE_COLOR: u32 = 0xFF_888888;
+
+#[derive(Serialize, Deserialize)]
+pub struct CadnanoDesign {
+    #[serde(default)]
+    pub name: String,
+    pub vstrands: Vec<CadnanoVStrand>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CadnanoVStrand {
+    pub num: isize,
+    pub row: isize,
+    pub col: isize,
+    pub scaf: Vec<[isize; 4]>,
+    pub stap: Vec<[isize; 4]>,
+    #[serde(rename = "loop", default)]
+    pub loop_: Vec<isize>,
+    #[serde(default)]
+    pub skip: Vec<isize>,
+    /// The colors of the staples, given as pairs `[position of the 5' end, rgb color]`.
+    #[serde(default)]
+    pub stap_colors: Vec<[isize; 2]>,
+}
+
+#[derive(Debug)]
+pub enum CadnanoImportError {
+    NoHelices,
+    InvalidHelixNumber(isize),
+    DuplicatedHelixNumber(isize),
+    /// The scaffold and staple arrays of a helix do not have the same length
+    InconsistentLength(isize),
+    UnknownHelix(isize),
+    InvalidPosition {
+        helix: isize,
+        position: isize,
+    },
+}
+
+impl CadnanoDesign {
+    /// The grid on which all the helices are placed.
+    ///
+    /// Cadnano does not store the lattice type. It is deduced from the length of the arrays. If
+    /// this length is compatible with both lattices, the helices are put on a honeycomb grid.
+    pub fn grid_descriptor(&self) -> Result<GridDescriptor, CadnanoImportError> {
+        let length = self
+            .vstrands
+            .first()
+            .map(|v| v.scaf.len())
+            .ok_or(CadnanoImportError::NoHelices)?;
+        let grid_type = if length % SQUARE_STEP == 0 && length % HONEYCOMB_STEP != 0 {
+            GridTypeDescr::Square { twist: None }
+        } else {
+            GridTypeDescr::Honeycomb { twist: None }
+        };
+        Ok(GridDescriptor {
+            position: Vec3::zero(),
+            orientation: Rotor3::identity(),
+            grid_type,
+            invisible: false,
+            bezier_vertex: None,
+            bounds: None,
+            phantom_length: None,
+        })
+    }
+
+    /// The helices of the design, identified by their cadnano number.
+    pub(super) fn helices(
+        &self,
+        grid: &Grid,
+    ) -> Result<BTreeMap<usize, Arc<Helix>>, CadnanoImportError> {
+        let mut ret = BTreeMap::new();
+        for v in self.vstrands.iter() {
+            let helix = Helix::new_on_grid(grid, v.col, v.row, GridId::FreeGrid(0));
+            if ret.insert(v.helix_id()?, Arc::new(helix)).is_some() {
+                return Err(CadnanoImportError::DuplicatedHelixNumber(v.num));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// The strands of the design, and the identifier of the scaffold.
+    ///
+    /// If the scaffold is made of several fragments, they are all imported, no scaffold is set
+    /// and a warning is emitted.
+    pub(super) fn strands(
+        &self,
+    ) -> Result<(BTreeMap<usize, Strand>, Option<usize>), CadnanoImportError> {
+        let mut strands = BTreeMap::new();
+        let scaffold_reader = StrandReader::new(&self.vstrands, true)?;
+        for strand in scaffold_reader.read_strands()? {
+            strands.insert(strands.len(), strand);
+        }
+        let nb_scaffold_fragments = strands.len();
+        let staple_reader = StrandReader::new(&self.vstrands, false)?;
+        for strand in staple_reader.read_strands()? {
+            strands.insert(strands.len(), strand);
+        }
+        let scaffold_id = if nb_scaffold_fragments == 1 {
+            Some(0)
+        } else {
+            if nb_scaffold_fragments > 1 {
+                log::warn!(
+                    "The scaffold of the cadnano design is made of {} fragments",
+                    nb_scaffold_fragments
+                );
+            }
+            None
+        };
+        Ok((strands, scaffold_id))
+    }
+}
+
+impl CadnanoVStrand {
+    fn helix_id(&self) -> Result<usize, CadnanoImportError> {
+        if self.num < 0 {
+            Err(CadnanoImportError::InvalidHelixNumber(self.num))
+        } else {
+            Ok(self.num as usize)
+        }
+    }
+
+    fn entries(&self, scaffold: bool) -> &[[isize; 4]] {
+        if scaffold {
+            &self.scaf
+        } else {
+            &self.stap
+        }
+    }
+
+    fn is_skipped(&self, position: usize) -> bool {
+        self.skip.get(position).cloned().unwrap_or_default() < 0
+    }
+
+    fn loop_length(&self, position: usize) -> usize {
+        self.loop_.get(position).cloned().unwrap_or_default().max(0) as usize
+    }
+
+    /// In cadnano, the scaffold goes from left to right on even helices, and the staples go from
+    /// left to right on odd helices.
+    fn forward(&self, scaffold: bool) -> bool {
+        (self.num % 2 == 0) == scaffold
+    }
+}
+
+/// A nucleotide of a cadnano design, identified by the index of its virtual strand and its
+/// position in the arrays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct CadnanoNucl {
+    vstrand: usize,
+    position: usize,
+}
+
+impl CadnanoNucl {
+    fn is_adjacent_to(&self, other: &Self) -> bool {
+        self.vstrand == other.vstrand
+            && (self.position as isize - other.position as isize).abs() == 1
+    }
+}
+
+/// Reconstructs either the scaffold or the staples of a cadnano design.
+struct StrandReader<'a> {
+    vstrands: &'a [CadnanoVStrand],
+    scaffold: bool,
+    num_to_vstrand: HashMap<isize, usize>,
+    /// For each virtual strand, the ENSnano position of each nucleotide. Skipped nucleotides are
+    /// given the same position as their left neighbour.
+    ensnano_positions: Vec<Vec<isize>>,
+    stap_colors: HashMap<CadnanoNucl, u32>,
+}
+
+impl<'a> StrandReader<'a> {
+    fn new(vstrands: &'a [CadnanoVStrand], scaffold: bool) -> Result<Self, CadnanoImportError> {
+        let mut num_to_vstrand = HashMap::new();
+        let mut ensnano_positions = Vec::with_capacity(vstrands.len());
+        let mut stap_colors = HashMap::new();
+        for (i, v) in vstrands.iter().enumerate() {
+            if v.scaf.len() != v.stap.len() {
+                return Err(CadnanoImportError::InconsistentLength(v.num));
+            }
+            num_to_vstrand.insert(v.num, i);
+            let mut nb_skipped = 0;
+            let positions = (0..v.scaf.len())
+                .map(|position| {
+                    if v.is_skipped(position) {
+                        nb_skipped += 1;
+                    }
+                    position as isize - nb_skipped
+                })
+                .collect();
+            ensnano_positions.push(positions);
+            for [position, color] in v.stap_colors.iter() {
+                let nucl = CadnanoNucl {
+                    vstrand: i,
+                    position: (*position).max(0) as usize,
+                };
+                stap_colors.insert(nucl, 0xFF_00_00_00 | (*color as u32 & 0xFF_FF_FF));
+            }
+        }
+        Ok(Self {
+            vstrands,
+            scaffold,
+            num_to_vstrand,
+            ensnano_positions,
+            stap_colors,
+        })
+    }
+
+    fn entry(&self, nucl: CadnanoNucl) -> [isize; 4] {
+        self.vstrands[nucl.vstrand].entries(self.scaffold)[nucl.position]
+    }
+
+    fn nucl(&self, num: isize, position: isize) -> Result<Option<CadnanoNucl>, CadnanoImportError> {
+        if num < 0 || position < 0 {
+            return Ok(None);
+        }
+        let vstrand = *self
+            .num_to_vstrand
+            .get(&num)
+            .ok_or(CadnanoImportError::UnknownHelix(num))?;
+        if position as usize >= self.vstrands[vstrand].scaf.len() {
+            return Err(CadnanoImportError::InvalidPosition {
+                helix: num,
+                position,
+            });
+        }
+        Ok(Some(CadnanoNucl {
+            vstrand,
+            position: position as usize,
+        }))
+    }
+
+    fn prime5_neighbour(
+        &self,
+        nucl: CadnanoNucl,
+    ) -> Result<Option<CadnanoNucl>, CadnanoImportError> {
+        let [num, position, _, _] = self.entry(nucl);
+        self.nucl(num, position)
+    }
+
+    fn prime3_neighbour(
+        &self,
+        nucl: CadnanoNucl,
+    ) -> Result<Option<CadnanoNucl>, CadnanoImportError> {
+        let [_, _, num, position] = self.entry(nucl);
+        self.nucl(num, position)
+    }
+
+    fn read_strands(&self) -> Result<Vec<Strand>, CadnanoImportError> {
+        let mut ret = Vec::new();
+        let mut seen = HashSet::new();
+        for (vstrand, v) in self.vstrands.iter().enumerate() {
+            for position in 0..v.scaf.len() {
+                let nucl = CadnanoNucl { vstrand, position };
+                if self.entry(nucl) != NO_NEIGHBOUR && !seen.contains(&nucl) {
+                    let (nucls, cyclic) = self.strand_nucls(nucl)?;
+                    seen.extend(nucls.iter().cloned());
+                    ret.push(self.make_strand(&nucls, cyclic));
+                }
+            }
+        }
+        Ok(ret)
+    }
+
+    /// The nucleotides of the strand going through `nucl`, from 5' to 3', and a boolean
+    /// indicating whether the strand is cyclic.
+    fn strand_nucls(
+        &self,
+        nucl: CadnanoNucl,
+    ) -> Result<(Vec<CadnanoNucl>, bool), CadnanoImportError> {
+        let mut prime5 = nucl;
+        let mut visited = HashSet::new();
+        visited.insert(nucl);
+        let mut cyclic = false;
+        while let Some(neighbour) = self.prime5_neighbour(prime5)? {
+            if !visited.insert(neighbour) {
+                cyclic = true;
+                break;
+            }
+            prime5 = neighbour;
+        }
+
+        let mut ret = vec![prime5];
+        let mut visited = HashSet::new();
+        visited.insert(prime5);
+        let mut current = prime5;
+        while let Some(neighbour) = self.prime3_neighbour(current)? {
+            if !visited.insert(neighbour) {
+                break;
+            }
+            ret.push(neighbour);
+            current = neighbour;
+        }
+
+        if cyclic {
+            // Start the strand at the beginning of a domain so that no domain is split between
+            // the end and the begining of the strand.
+            let len = ret.len();
+            if let Some(start) =
+                (0..len).find(|i| !ret[(i + len - 1) % len].is_adjacent_to(&ret[*i]))
+            {
+                ret.rotate_left(start);
+            }
+        }
+        Ok((ret, cyclic))
+    }
+
+    fn make_strand(&self, nucls: &[CadnanoNucl], cyclic: bool) -> Strand {
+        let mut domains = Vec::new();
+        let mut current_domain: Vec<CadnanoNucl> = Vec::new();
+        for nucl in nucls.iter() {
+            if let Some(last) = current_domain.last() {
+                if !last.is_adjacent_to(nucl) {
+                    domains.extend(self.helix_domain(&current_domain));
+                    current_domain.clear();
+                }
+            }
+            current_domain.push(*nucl);
+            let loop_length = self.vstrands[nucl.vstrand].loop_length(nucl.position);
+            if loop_length > 0 {
+                domains.extend(self.helix_domain(&current_domain));
+                current_domain.clear();
+                domains.push(Domain::new_insertion(loop_length));
+            }
+        }
+        domains.extend(self.helix_domain(&current_domain));
+
+        let color = if self.scaffold {
+            SCAFFOLD_COLOR
+        } else {
+            nucls
+                .iter()
+                .find_map(|n| self.stap_colors.get(n))
+                .cloned()
+                .unwrap_or(DEFAULT_STAPLE_COLOR)
+        };
+        let sane_domains = sanitize_domains(&domains, cyclic);
+        let junctions = read_junctions(&sane_domains, cyclic);
+        Strand {
+            domains: sane_domains,
+            junctions,
+            cyclic,
+            color,
+            ..Default::default()
+        }
+    }
+
+    /// The domain made of the non skipped nucleotides of `nucls`, which must be consecutive
+    /// nucleotides of the same helix.
+    fn helix_domain(&self, nucls: &[CadnanoNucl]) -> Option<Domain> {
+        let vstrand = nucls.first()?.vstrand;
+        let v = &self.vstrands[vstrand];
+        let forward = if let [first, .., last] = nucls {
+            first.position < last.position
+        } else {
+            v.forward(self.scaffold)
+        };
+        let positions: Vec<isize> = nucls
+            .iter()
+            .filter(|n| !v.is_skipped(n.position))
+            .map(|n| self.ensnano_positions[vstrand][n.position])
+            .collect();
+        let start = positions.iter().min()?;
+        let end = positions.iter().max()? + 1;
+        Some(Domain::HelixDomain(HelixInterval {
+            helix: v.num as usize,
+            start: *start,
+            end,
+            forward,
+            sequence: None,
+        }))
+    }
+}
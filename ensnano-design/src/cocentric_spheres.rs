@@ -121,6 +121,9 @@ fn main() {
                         sequence: None,
                     })],
                     name: None,
+                    invisible: false,
+                    note: None,
+                    keep_color: false,
                 };
                 design.strands.push(big_strand);
             }
@@ -223,6 +226,9 @@ fn add_hyperboloid_helices(design: &mut Design, desc: GridDescriptor) {
                         sequence: None,
                     })],
                     name: None,
+                    invisible: false,
+                    note: None,
+                    keep_color: false,
                 };
                 design.strands.push(big_strand);
             }
@@ -50,7 +50,7 @@ pub use bezier::{
 };
 pub use revolution::{InterpolatedCurveDescriptor, InterpolationDescriptor};
 pub use sphere_like_spiral::{SphereLikeSpiralDescriptor, SphereOrientation};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 pub use supertwist::SuperTwist;
 pub use time_nucl_map::AbscissaConverter;
 pub(crate) use time_nucl_map::{PathTimeMaps, RevolutionCurveTimeMaps};
@@ -841,7 +841,25 @@ impl InstanciatedCurveDescriptor {
     }
 
     pub fn make_curve(&self, parameters: &Parameters, cached_curve: &mut CurveCache) -> Arc<Curve> {
-        InstanciatedCurveDescriptor_::clone(&self.instance).into_curve(parameters, cached_curve)
+        let key = self.cache_key(parameters);
+        if let Some(curve) = key.as_ref().and_then(|key| cached_curve.get(key)) {
+            return curve;
+        }
+        let curve = InstanciatedCurveDescriptor_::clone(&self.instance).into_curve(parameters);
+        if let Some(key) = key {
+            cached_curve.insert(key, self.instance.clone(), curve.clone());
+        }
+        curve
+    }
+
+    /// Return the serialized content of the source descriptor and of `parameters`, or `None` if
+    /// the curve also depends on the grids or Bézier paths of the design and must not be cached.
+    fn cache_key(&self, parameters: &Parameters) -> Option<Vec<u8>> {
+        match self.instance {
+            InstanciatedCurveDescriptor_::PiecewiseBezier(_)
+            | InstanciatedCurveDescriptor_::TranslatedBezierPath { .. } => None,
+            _ => serde_json::to_vec(&(self.source.as_ref(), parameters)).ok(),
+        }
     }
 
     pub fn get_bezier_controls(&self) -> Option<CubicBezierConstructor> {
@@ -979,7 +997,7 @@ impl InstanciatedPiecewiseBezierDescriptor {
 }
 
 impl InstanciatedCurveDescriptor_ {
-    pub fn into_curve(self, parameters: &Parameters, cache: &mut CurveCache) -> Arc<Curve> {
+    pub fn into_curve(self, parameters: &Parameters) -> Arc<Curve> {
         match self {
             Self::Bezier(constructor) => {
                 Arc::new(Curve::new(constructor.into_bezier(), parameters))
@@ -993,18 +1011,8 @@ impl InstanciatedCurveDescriptor_ {
             Self::Twist(twist) => Arc::new(Curve::new(twist, parameters)),
             Self::Torus(torus) => Arc::new(Curve::new(torus, parameters)),
             Self::SuperTwist(twist) => Arc::new(Curve::new(twist, parameters)),
-            Self::TwistedTorus(ref desc) => {
-                if let Some(curve) = cache.0.get(desc) {
-                    curve.clone()
-                } else {
-                    let ret = Arc::new(Curve::new(
-                        TwistedTorus::new(desc.clone(), parameters),
-                        parameters,
-                    ));
-                    println!("Number of nucleotides {}", ret.nb_points());
-                    cache.0.insert(desc.clone(), ret.clone());
-                    ret
-                }
+            Self::TwistedTorus(desc) => {
+                Arc::new(Curve::new(TwistedTorus::new(desc, parameters), parameters))
             }
             Self::PiecewiseBezier(instanciated_descriptor) => {
                 Arc::new(Curve::new(instanciated_descriptor.desc, parameters))
@@ -1146,7 +1154,78 @@ impl InstanciatedCurveDescriptor_ {
 
 #[derive(Default, Clone)]
 /// A map from curve descriptor to instanciated curves to avoid duplication of computations
-pub struct CurveCache(pub(crate) HashMap<TwistedTorusDescriptor, Arc<Curve>>);
+///
+/// Entries are keyed by the serialized content of the curve descriptor and of the DNA parameters,
+/// so a curve is not discretized again when its descriptor is rebuilt with the same content. Curves
+/// that depend on the grids or Bézier paths of the design are not cached.
+pub struct CurveCache {
+    curves: HashMap<Vec<u8>, CachedCurve>,
+    /// The number of curves that were found in the cache
+    hits: usize,
+    /// The number of curves that had to be discretized
+    misses: usize,
+}
+
+#[derive(Clone)]
+struct CachedCurve {
+    instance: InstanciatedCurveDescriptor_,
+    curve: Arc<Curve>,
+}
+
+impl CurveCache {
+    fn get(&mut self, key: &[u8]) -> Option<Arc<Curve>> {
+        let ret = self.curves.get(key).map(|cached| cached.curve.clone());
+        if ret.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        ret
+    }
+
+    fn insert(&mut self, key: Vec<u8>, instance: InstanciatedCurveDescriptor_, curve: Arc<Curve>) {
+        self.curves.insert(key, CachedCurve { instance, curve });
+    }
+
+    /// Remove the curves that are not used by any of `helices`.
+    ///
+    /// Because curves are keyed by their content, a curve is only removed when the descriptors of
+    /// all the helices that used it have been modified.
+    pub(crate) fn retain_used_by<'a>(&mut self, helices: impl Iterator<Item = &'a Helix>) {
+        let used: HashSet<*const Curve> = helices
+            .filter_map(|h| h.instanciated_curve.as_ref())
+            .map(|c| Arc::as_ptr(&c.curve))
+            .collect();
+        self.curves
+            .retain(|_, cached| used.contains(&Arc::as_ptr(&cached.curve)));
+    }
+
+    /// The descriptors of the cached twisted tori
+    pub(crate) fn twisted_tori(&self) -> impl Iterator<Item = &TwistedTorusDescriptor> {
+        self.curves.values().filter_map(|cached| {
+            if let InstanciatedCurveDescriptor_::TwistedTorus(desc) = &cached.instance {
+                Some(desc)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub(crate) fn log_statistics(&self) {
+        log::debug!(
+            "Curve cache: {} entries, {} hits, {} misses",
+            self.curves.len(),
+            self.hits,
+            self.misses
+        );
+    }
+
+    /// The number of curves that had to be discretized because they were not in the cache
+    #[cfg(test)]
+    pub(crate) fn nb_misses(&self) -> usize {
+        self.misses
+    }
+}
 
 #[derive(Clone)]
 /// An instanciated curve with pre-computed nucleotides positions and orientations
@@ -1208,11 +1287,12 @@ impl Helix {
     }
 
     pub fn try_update_curve(&mut self, parameters: &Parameters) {
+        let parameters = *self.parameters(parameters);
         if let Some(curve) = self.curve.as_ref() {
             if let Some(desc) = InstanciatedCurveDescriptor::try_instanciate(curve.clone()) {
                 let desc = Arc::new(desc);
                 self.instanciated_descriptor = Some(desc.clone());
-                if let Some(curve) = desc.as_ref().instance.try_into_curve(parameters) {
+                if let Some(curve) = desc.as_ref().instance.try_into_curve(&parameters) {
                     self.instanciated_curve = Some(InstanciatedCurve {
                         curve,
                         source: desc,
@@ -0,0 +1,364 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Detection of the defects of a design that should be fixed before ordering the staples.
+
+use crate::{Design, Domain, HelixInterval, Nucl};
+use std::collections::{BTreeMap, HashSet};
+use ultraviolet::Vec3;
+
+/// The penalty, in nanometers per radian, given to a cross-over whose extremities do not point
+/// toward each other
+const XOVER_ANGLE_STRAIN_WEIGHT: f32 = 0.5;
+
+/// Cross-overs whose [xover_strain] is above this value are considered strained.
+pub const STRAINED_XOVER_THRESHOLD: f32 = 1.0;
+
+/// The thresholds used by [check_design].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckParameters {
+    /// Staple domains strictly shorter than this are reported
+    pub min_domain_length: usize,
+    /// Runs of unpaired scaffold nucleotides strictly longer than this are reported
+    pub max_unpaired_scaffold: usize,
+    /// Cross-overs between the same helices and with the same orientation that are strictly
+    /// closer than this are reported
+    pub min_xover_spacing: usize,
+}
+
+impl Default for CheckParameters {
+    fn default() -> Self {
+        Self {
+            min_domain_length: 7,
+            max_unpaired_scaffold: 8,
+            min_xover_spacing: 5,
+        }
+    }
+}
+
+/// A defect of a design
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    /// A helix domain of a staple is too short to bind reliably
+    ShortDomain {
+        strand_id: usize,
+        prime5: Nucl,
+        prime3: Nucl,
+        length: usize,
+    },
+    /// The 5' and 3' domains of `strand_id` lie on both sides of an inner domain of
+    /// `inner_strand_id`, which can prevent the inner domain from binding.
+    SandwichStrand {
+        strand_id: usize,
+        inner_strand_id: usize,
+        prime5: Nucl,
+        prime3: Nucl,
+    },
+    /// A run of consecutive scaffold nucleotides that are not paired with any staple
+    UnpairedScaffold {
+        first: Nucl,
+        last: Nucl,
+        length: usize,
+    },
+    /// Two cross-overs between the same helices that are too close to each other
+    CloseXovers {
+        xover_1: (Nucl, Nucl),
+        xover_2: (Nucl, Nucl),
+        spacing: usize,
+    },
+}
+
+impl Diagnostic {
+    /// The identifiers of the strands involved in the defect
+    pub fn strand_ids(&self) -> Vec<usize> {
+        match self {
+            Self::ShortDomain { strand_id, .. } => vec![*strand_id],
+            Self::SandwichStrand {
+                strand_id,
+                inner_strand_id,
+                ..
+            } => vec![*strand_id, *inner_strand_id],
+            Self::UnpairedScaffold { .. } | Self::CloseXovers { .. } => vec![],
+        }
+    }
+
+    /// The nucleotides involved in the defect
+    pub fn nucls(&self) -> Vec<Nucl> {
+        match self {
+            Self::ShortDomain { prime5, prime3, .. } => vec![*prime5, *prime3],
+            Self::SandwichStrand { prime5, prime3, .. } => vec![*prime5, *prime3],
+            Self::UnpairedScaffold { first, last, .. } => vec![*first, *last],
+            Self::CloseXovers {
+                xover_1, xover_2, ..
+            } => vec![xover_1.0, xover_1.1, xover_2.0, xover_2.1],
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ShortDomain {
+                strand_id, length, ..
+            } => write!(f, "Strand {}: domain of length {}", strand_id, length),
+            Self::SandwichStrand {
+                strand_id,
+                inner_strand_id,
+                ..
+            } => write!(
+                f,
+                "Strand {} sandwiches strand {}",
+                strand_id, inner_strand_id
+            ),
+            Self::UnpairedScaffold { first, length, .. } => write!(
+                f,
+                "{} unpaired scaffold nt from h{}:nt{}",
+                length, first.helix, first.position
+            ),
+            Self::CloseXovers {
+                xover_1,
+                xover_2,
+                spacing,
+            } => write!(
+                f,
+                "Xovers h{}:nt{} and h{}:nt{} are {} nt apart",
+                xover_1.0.helix, xover_1.0.position, xover_2.0.helix, xover_2.0.position, spacing
+            ),
+        }
+    }
+}
+
+/// Return all the defects of `design`.
+pub fn check_design(design: &Design, parameters: &CheckParameters) -> Vec<Diagnostic> {
+    let mut ret = short_domains(design, parameters.min_domain_length);
+    ret.extend(sandwich_strands(design));
+    ret.extend(unpaired_scaffold(design, parameters.max_unpaired_scaffold));
+    ret.extend(close_xovers(design, parameters.min_xover_spacing));
+    ret
+}
+
+/// The helix domains of each staple of the design, in the 5' -> 3' order.
+fn staple_intervals(design: &Design) -> impl Iterator<Item = (usize, Vec<&HelixInterval>)> {
+    design
+        .strands
+        .iter()
+        .filter(move |(s_id, _)| !design.is_scaffold(**s_id))
+        .map(|(s_id, strand)| {
+            let intervals = strand
+                .domains
+                .iter()
+                .filter_map(|d| {
+                    if let Domain::HelixDomain(interval) = d {
+                        Some(interval)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            (*s_id, intervals)
+        })
+}
+
+fn interval_ends(interval: &HelixInterval) -> (Nucl, Nucl) {
+    let nucl = |position| Nucl {
+        helix: interval.helix,
+        position,
+        forward: interval.forward,
+    };
+    if interval.forward {
+        (nucl(interval.start), nucl(interval.end - 1))
+    } else {
+        (nucl(interval.end - 1), nucl(interval.start))
+    }
+}
+
+fn short_domains(design: &Design, min_domain_length: usize) -> Vec<Diagnostic> {
+    let mut ret = Vec::new();
+    for (strand_id, intervals) in staple_intervals(design) {
+        for interval in intervals {
+            let length = (interval.end - interval.start).max(0) as usize;
+            if length < min_domain_length {
+                let (prime5, prime3) = interval_ends(interval);
+                ret.push(Diagnostic::ShortDomain {
+                    strand_id,
+                    prime5,
+                    prime3,
+                    length,
+                });
+            }
+        }
+    }
+    ret
+}
+
+/// Report the staples whose 5' and 3' domains are on the same helix and lie on both sides of an
+/// inner domain of another staple.
+fn sandwich_strands(design: &Design) -> Vec<Diagnostic> {
+    let staples: Vec<_> = staple_intervals(design).collect();
+    let mut ret = Vec::new();
+    for (strand_id, intervals) in staples.iter() {
+        let (first, last) = match (intervals.first(), intervals.last()) {
+            (Some(first), Some(last)) if intervals.len() > 1 => (first, last),
+            _ => continue,
+        };
+        if first.helix != last.helix || first.forward != last.forward {
+            continue;
+        }
+        let gap_start = first.end.min(last.end);
+        let gap_end = first.start.max(last.start);
+        for (inner_strand_id, inner_intervals) in staples.iter() {
+            if inner_strand_id == strand_id || inner_intervals.len() < 3 {
+                continue;
+            }
+            let sandwiched = inner_intervals[1..inner_intervals.len() - 1]
+                .iter()
+                .any(|inner| {
+                    inner.helix == first.helix
+                        && inner.forward == first.forward
+                        && inner.start >= gap_start
+                        && inner.end <= gap_end
+                });
+            if sandwiched {
+                ret.push(Diagnostic::SandwichStrand {
+                    strand_id: *strand_id,
+                    inner_strand_id: *inner_strand_id,
+                    prime5: interval_ends(first).0,
+                    prime3: interval_ends(last).1,
+                });
+            }
+        }
+    }
+    ret
+}
+
+fn unpaired_scaffold(design: &Design, max_unpaired_scaffold: usize) -> Vec<Diagnostic> {
+    let mut staple_nucls = HashSet::new();
+    for (_, intervals) in staple_intervals(design) {
+        for interval in intervals {
+            for position in interval.iter() {
+                staple_nucls.insert(Nucl {
+                    helix: interval.helix,
+                    position,
+                    forward: interval.forward,
+                });
+            }
+        }
+    }
+
+    let mut ret = Vec::new();
+    let mut report = |run: Option<(Nucl, Nucl, usize)>| {
+        if let Some((first, last, length)) = run {
+            if length > max_unpaired_scaffold {
+                ret.push(Diagnostic::UnpairedScaffold {
+                    first,
+                    last,
+                    length,
+                })
+            }
+        }
+    };
+    for (_, scaffold) in design.scaffolds() {
+        let mut run: Option<(Nucl, Nucl, usize)> = None;
+        for domain in scaffold.domains.iter() {
+            if let Domain::HelixDomain(interval) = domain {
+                for position in interval.iter() {
+                    let nucl = Nucl {
+                        helix: interval.helix,
+                        position,
+                        forward: interval.forward,
+                    };
+                    if staple_nucls.contains(&nucl.compl()) {
+                        report(run.take());
+                    } else if let Some((_, last, length)) = run.as_mut() {
+                        *last = nucl;
+                        *length += 1;
+                    } else {
+                        run = Some((nucl, nucl, 1));
+                    }
+                }
+            }
+        }
+        report(run);
+    }
+    ret
+}
+
+/// Report the pairs of cross-overs between the same helices that have the same orientation and
+/// are too close to each other.
+///
+/// Two cross-overs have the same orientation if they go from the same strand direction of one
+/// helix to the same strand direction of the other. The two cross-overs of a double cross-over
+/// have opposite orientations and are thus not reported.
+fn close_xovers(design: &Design, min_xover_spacing: usize) -> Vec<Diagnostic> {
+    let mut xovers_by_orientation: BTreeMap<(usize, usize, bool, bool), Vec<(Nucl, Nucl)>> =
+        BTreeMap::new();
+    for (n1, n2) in design.strands.get_xovers() {
+        if n1.helix == n2.helix {
+            continue;
+        }
+        // n1 is the 3' end of the first domain and n2 the 5' end of the next one
+        let (n1, n2, from_first_helix) = if n1.helix < n2.helix {
+            (n1, n2, true)
+        } else {
+            (n2, n1, false)
+        };
+        xovers_by_orientation
+            .entry((n1.helix, n2.helix, n1.forward, from_first_helix))
+            .or_default()
+            .push((n1, n2));
+    }
+
+    let mut ret = Vec::new();
+    for xovers in xovers_by_orientation.values_mut() {
+        xovers.sort_by_key(|(n1, _)| n1.position);
+        for pair in xovers.windows(2) {
+            let spacing = (pair[1].0.position - pair[0].0.position) as usize;
+            if spacing < min_xover_spacing {
+                ret.push(Diagnostic::CloseXovers {
+                    xover_1: pair[0],
+                    xover_2: pair[1],
+                    spacing,
+                });
+            }
+        }
+    }
+    ret
+}
+
+/// A score, in nanometers, measuring how far the cross-over from `prime3` to `prime5` is from an
+/// ideal cross-over.
+///
+/// The score is the deviation of the distance between the two backbones from the expected
+/// cross-over length, plus a penalty for each extremity of the cross-over that does not point
+/// toward the other one.
+pub fn xover_strain(design: &Design, prime3: Nucl, prime5: Nucl) -> Option<f32> {
+    let parameters = design.parameters.unwrap_or_default();
+    let pos_3 = design.get_nucl_position(prime3)?;
+    let pos_5 = design.get_nucl_position(prime5)?;
+    let misalignment = |nucl: Nucl, position: Vec3, target: Vec3| -> Option<f32> {
+        let helix = design.helices.get(&nucl.helix)?;
+        let axis = helix.axis_position(&parameters, nucl.position);
+        let tangent = (helix.axis_position(&parameters, nucl.position + 1) - axis).normalized();
+        let orthogonal = |v: Vec3| v - v.dot(tangent) * tangent;
+        let radial = orthogonal(position - axis);
+        let direction = orthogonal(target - position);
+        Some(radial.cross(direction).mag().atan2(radial.dot(direction)))
+    };
+    let length_strain = ((pos_5 - pos_3).mag() - parameters.dist_ac()).abs();
+    let angle_strain = misalignment(prime3, pos_3, pos_5)? + misalignment(prime5, pos_5, pos_3)?;
+    Some(length_strain + XOVER_ANGLE_STRAIN_WEIGHT * angle_strain)
+}
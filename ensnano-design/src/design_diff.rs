@@ -0,0 +1,262 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Comparison of two versions of a design.
+//!
+//! Strands are identified by their id. Because ids are reused when strands are deleted and
+//! created, a strand whose id is still used but that does not share any nucleotide with the
+//! strand that had this id is reported as removed and added, and a strand whose id changed but
+//! whose domains did not is reported as the same strand.
+
+use crate::{Design, Domain, HasMap, HelixCollection, Nucl, Strand};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Helices whose position changed by more than this distance, in nanometers, are reported as
+/// moved.
+pub const HELIX_POSITION_TOLERANCE: f32 = 1e-3;
+/// Helices whose orientation changed by more than this angle, in radians, are reported as moved.
+pub const HELIX_ORIENTATION_TOLERANCE: f32 = 1e-3;
+
+/// The differences between an old and a new version of a design.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DesignDiff {
+    /// Identifiers, in the new design, of the strands that are not in the old design
+    pub added_strands: Vec<usize>,
+    /// Identifiers, in the old design, of the strands that are not in the new design
+    pub removed_strands: Vec<usize>,
+    /// Identifiers, in the new design, of the strands whose domains, sequence or color changed
+    pub modified_strands: Vec<usize>,
+    /// Identifiers of the helices whose position or orientation changed
+    pub moved_helices: Vec<usize>,
+}
+
+impl DesignDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_strands.is_empty()
+            && self.removed_strands.is_empty()
+            && self.modified_strands.is_empty()
+            && self.moved_helices.is_empty()
+    }
+}
+
+/// Compute the differences between `old` and `new`.
+pub fn diff(old: &Design, new: &Design) -> DesignDiff {
+    let mut added_strands = Vec::new();
+    let mut removed_strands = Vec::new();
+    let mut modified_strands = Vec::new();
+    for (s_id, new_strand) in new.strands.iter() {
+        match old.strands.get(s_id) {
+            Some(old_strand) if same_domains(old_strand, new_strand) => {
+                if !same_attributes(old_strand, new_strand) {
+                    modified_strands.push(*s_id);
+                }
+            }
+            Some(old_strand) if share_a_nucleotide(old_strand, new_strand) => {
+                modified_strands.push(*s_id)
+            }
+            Some(_) => {
+                // The id was reused by an unrelated strand
+                removed_strands.push(*s_id);
+                added_strands.push(*s_id);
+            }
+            None => added_strands.push(*s_id),
+        }
+    }
+    removed_strands.extend(
+        old.strands
+            .keys()
+            .filter(|s_id| new.strands.get(s_id).is_none()),
+    );
+
+    // A strand whose id changed is neither added nor removed
+    added_strands.retain(|new_id| {
+        let new_strand = if let Some(strand) = new.strands.get(new_id) {
+            strand
+        } else {
+            return false;
+        };
+        let same_strand = removed_strands.iter().position(|old_id| {
+            old.strands
+                .get(old_id)
+                .map(|old_strand| same_domains(old_strand, new_strand))
+                .unwrap_or(false)
+        });
+        if let Some(pos) = same_strand {
+            let old_id = removed_strands.remove(pos);
+            if old
+                .strands
+                .get(&old_id)
+                .map(|old_strand| !same_attributes(old_strand, new_strand))
+                .unwrap_or(false)
+            {
+                modified_strands.push(*new_id);
+            }
+            false
+        } else {
+            true
+        }
+    });
+    removed_strands.sort_unstable();
+    modified_strands.sort_unstable();
+
+    let mut moved_helices = Vec::new();
+    for (h_id, new_helix) in new.helices.iter() {
+        if let Some(old_helix) = old.helices.get(h_id) {
+            let translation = (new_helix.position - old_helix.position).mag();
+            let rotation = new_helix.orientation * old_helix.orientation.reversed();
+            let angle = 2. * rotation.s.abs().min(1.).acos();
+            if translation > HELIX_POSITION_TOLERANCE || angle > HELIX_ORIENTATION_TOLERANCE {
+                moved_helices.push(*h_id);
+            }
+        }
+    }
+
+    DesignDiff {
+        added_strands,
+        removed_strands,
+        modified_strands,
+        moved_helices,
+    }
+}
+
+/// Return the identifiers of the strands whose representation may differ between `old` and
+/// `new`.
+///
+/// Unlike [`diff`], strands are only identified by their id: a strand is reported if it is in
+/// only one of the two designs, or if its domains, sequence, color or visibility changed.
+pub fn changed_strand_ids(old: &Design, new: &Design) -> BTreeSet<usize> {
+    let mut ret: BTreeSet<usize> = old
+        .strands
+        .keys()
+        .filter(|s_id| new.strands.get(s_id).is_none())
+        .cloned()
+        .collect();
+    for (s_id, new_strand) in new.strands.iter() {
+        let unchanged = old
+            .strands
+            .get(s_id)
+            .map(|old_strand| {
+                same_domains(old_strand, new_strand)
+                    && same_attributes(old_strand, new_strand)
+                    && old_strand.invisible == new_strand.invisible
+            })
+            .unwrap_or(false);
+        if !unchanged {
+            ret.insert(*s_id);
+        }
+    }
+    ret
+}
+
+/// Return the identifiers of the helices that are in only one of `old` and `new`, or that were
+/// mutated between the two designs.
+///
+/// Helices are shared by the successive versions of a design until they are mutated, so this
+/// only compares pointers and may report helices that were mutated without being changed.
+pub fn changed_helix_ids(old: &Design, new: &Design) -> BTreeSet<usize> {
+    let mut ret: BTreeSet<usize> = old
+        .helices
+        .0
+        .keys()
+        .filter(|h_id| !new.helices.0.contains_key(h_id))
+        .cloned()
+        .collect();
+    for (h_id, new_helix) in new.helices.0.iter() {
+        let unchanged = old
+            .helices
+            .0
+            .get(h_id)
+            .map(|old_helix| Arc::ptr_eq(old_helix, new_helix))
+            .unwrap_or(false);
+        if !unchanged {
+            ret.insert(*h_id);
+        }
+    }
+    ret
+}
+
+/// Return true if `old` and `new` share the same grids, Bézier planes and Bézier paths.
+///
+/// When this is not the case, helices that are attached to these objects may have moved even if
+/// they are not reported by [`changed_helix_ids`].
+pub fn same_helix_supports(old: &Design, new: &Design) -> bool {
+    same_items(&old.free_grids, &new.free_grids)
+        && same_items(&old.bezier_planes, &new.bezier_planes)
+        && same_items(&old.bezier_paths, &new.bezier_paths)
+}
+
+fn same_items<M: HasMap>(m1: &M, m2: &M) -> bool {
+    let (m1, m2) = (m1.get_map(), m2.get_map());
+    m1.len() == m2.len()
+        && m1
+            .iter()
+            .zip(m2.iter())
+            .all(|((k1, v1), (k2, v2))| k1 == k2 && Arc::ptr_eq(v1, v2))
+}
+
+fn same_domains(s1: &Strand, s2: &Strand) -> bool {
+    s1.cyclic == s2.cyclic
+        && s1.domains.len() == s2.domains.len()
+        && s1
+            .domains
+            .iter()
+            .zip(s2.domains.iter())
+            .all(|(d1, d2)| same_domain(d1, d2))
+}
+
+fn same_domain(d1: &Domain, d2: &Domain) -> bool {
+    match (d1, d2) {
+        (Domain::HelixDomain(i1), Domain::HelixDomain(i2)) => {
+            i1.helix == i2.helix
+                && i1.start == i2.start
+                && i1.end == i2.end
+                && i1.forward == i2.forward
+                && i1.sequence == i2.sequence
+        }
+        (
+            Domain::Insertion {
+                nb_nucl: n1,
+                sequence: seq1,
+                ..
+            },
+            Domain::Insertion {
+                nb_nucl: n2,
+                sequence: seq2,
+                ..
+            },
+        ) => n1 == n2 && seq1 == seq2,
+        _ => false,
+    }
+}
+
+fn same_attributes(s1: &Strand, s2: &Strand) -> bool {
+    s1.sequence == s2.sequence && s1.color == s2.color
+}
+
+fn share_a_nucleotide(s1: &Strand, s2: &Strand) -> bool {
+    s2.domains.iter().any(|d| match d {
+        Domain::HelixDomain(interval) => interval.iter().any(|position| {
+            s1.has_nucl(&Nucl {
+                helix: interval.helix,
+                position,
+                forward: interval.forward,
+            })
+        }),
+        Domain::Insertion { .. } => false,
+    })
+}
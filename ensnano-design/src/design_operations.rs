@@ -19,14 +19,23 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! The functions that apply thes operations take a mutable reference to the design that they are
 //! modifying and may return an `ErrOperation` if the opperation could not be applied.
 
-use super::{bezier_plane::*, grid::*, CurveDescriptor, Design};
+use super::{
+    bezier_plane::*, elements::DnaElementKey, grid::*, pairs_of_close_nucleotides, read_junctions,
+    Collection, CurveDescriptor, Design, Domain, GroupId, HelixCollection, HelixInterval, Nucl,
+    OrganizerTree, Parameters, Strand,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
-use ultraviolet::{Rotor3, Vec3};
+use ultraviolet::{Bivec3, Mat3, Rotor3, Vec2, Vec3};
 
 /// An error that occured when trying to apply an operation.
 #[derive(Debug)]
 pub enum ErrOperation {
-    NotEnoughHelices { actual: usize, needed: usize },
+    NotEnoughHelices {
+        actual: usize,
+        needed: usize,
+    },
     GridPositionAlreadyUsed,
     HelixDoesNotExists(usize),
     GridDoesNotExist(GridId),
@@ -35,6 +44,31 @@ pub enum ErrOperation {
     HelixIsNotPiecewiseBezier,
     CouldNotGetPath(BezierPathId),
     CouldNotGetVertex(BezierVertexId),
+    InvalidReflectionPlane,
+    HelixCannotBeReflected(usize),
+    GridCannotBeReflected(GridId),
+    StrandPartiallyReflected(usize),
+    StrandDoesNotExist(usize),
+    HelixCannotBeDuplicated(usize),
+    HelixCannotBeAligned(usize),
+    /// The lattice of the grid cannot be changed to or from an hyperboloid
+    GridTypeCannotBeChanged(GridId),
+    /// The pairs of helices that would be mapped to the same position of the new lattice
+    GridPositionCollisions(Vec<(usize, usize)>),
+    /// The helix already carries strands
+    HelixIsNotEmpty(usize),
+    /// The helix appears several times in the route of a scaffold
+    HelixVisitedTwice(usize),
+    /// The cross-over windows at both ends of the helices would overlap
+    InvalidScaffoldRoutingParameters,
+    /// The design has no scaffold
+    NoScaffold,
+    /// The staple lengths or the domain lengths are not compatible
+    InvalidStapleFillingParameters,
+    /// There is no group with this identifier in the organizer
+    GroupDoesNotExist(GroupId),
+    /// The list of colors of a palette is empty
+    EmptyPalette,
 }
 
 /// The minimum number of helices requiered to infer a grid
@@ -104,6 +138,131 @@ pub fn attach_object_to_grid(
     }
 }
 
+/// Change the lattice of a free grid.
+///
+/// The helices attached to the grid are moved to the position of the new lattice that is the
+/// closest to their current position. If two helices would be moved to the same position, no
+/// modification is made and the colliding helices are returned in the error.
+pub fn set_grid_type(
+    design: &mut Design,
+    grid_id: GridId,
+    grid_type: GridTypeDescr,
+) -> Result<(), ErrOperation> {
+    let free_id = FreeGridId::try_from_grid_id(grid_id)
+        .ok_or(ErrOperation::GridTypeCannotBeChanged(grid_id))?;
+    let old_type = design
+        .free_grids
+        .get(&free_id)
+        .ok_or(ErrOperation::GridDoesNotExist(grid_id))?
+        .grid_type;
+    let is_hyperboloid = |t: &GridTypeDescr| matches!(t, GridTypeDescr::Hyperboloid { .. });
+    if is_hyperboloid(&old_type) || is_hyperboloid(&grid_type) {
+        return Err(ErrOperation::GridTypeCannotBeChanged(grid_id));
+    }
+    let parameters = design.parameters.unwrap_or_default();
+    let old_lattice = old_type.to_concrete();
+    let new_lattice = grid_type.to_concrete();
+
+    let mut new_positions: BTreeMap<(isize, isize), usize> = BTreeMap::new();
+    let mut collisions = Vec::new();
+    for (h_id, h) in design.helices.iter() {
+        if let Some(position) = h.grid_position.filter(|p| p.grid == grid_id) {
+            let origin = old_lattice.origin_helix(&parameters, position.x, position.y);
+            let new_position = new_lattice.interpolate(&parameters, origin.x, origin.y);
+            if let Some(other) = new_positions.insert(new_position, *h_id) {
+                collisions.push((other, *h_id));
+            }
+        }
+    }
+    if !collisions.is_empty() {
+        return Err(ErrOperation::GridPositionCollisions(collisions));
+    }
+
+    let mut helices_mut = design.helices.make_mut();
+    for ((x, y), h_id) in new_positions.into_iter() {
+        if let Some(position) = helices_mut
+            .get_mut(&h_id)
+            .and_then(|h| h.grid_position.as_mut())
+        {
+            position.x = x;
+            position.y = y;
+        }
+    }
+    drop(helices_mut);
+    let mut grids_mut = design.free_grids.make_mut();
+    if let Some(grid) = grids_mut.get_mut(&free_id) {
+        grid.grid_type = grid_type;
+    }
+    Ok(())
+}
+
+/// Insert a vertex at `position` on the segment of a bezier path that starts at vertex
+/// `after_vertex`, and return the identifier of the new vertex.
+///
+/// The vertices that follow the new vertex are shifted, and so are the grids that are generated
+/// from them. The helices that are attached to these grids are updated accordingly.
+pub fn insert_vertex_in_bezier_path(
+    design: &mut Design,
+    path_id: BezierPathId,
+    after_vertex: usize,
+    position: Vec2,
+) -> Result<BezierVertexId, ErrOperation> {
+    let mut paths_mut = design.bezier_paths.make_mut();
+    let path = paths_mut
+        .get_mut(&path_id)
+        .ok_or(ErrOperation::CouldNotGetPath(path_id))?;
+    let vertex_id =
+        path.insert_vertex(after_vertex, position)
+            .ok_or(ErrOperation::CouldNotGetVertex(BezierVertexId {
+                path_id,
+                vertex_id: after_vertex,
+            }))?;
+    drop(paths_mut);
+
+    let shifted_grid = |grid: GridId| match grid {
+        GridId::BezierPathGrid(v) if v.path_id == path_id && v.vertex_id >= vertex_id => {
+            Some(GridId::BezierPathGrid(BezierVertexId {
+                path_id,
+                vertex_id: v.vertex_id + 1,
+            }))
+        }
+        _ => None,
+    };
+    let helices_to_update: Vec<usize> = design
+        .helices
+        .iter()
+        .filter(|(_, h)| {
+            h.grid_position.and_then(|p| shifted_grid(p.grid)).is_some()
+                || h.curve.as_ref().map_or(false, |c| {
+                    c.grid_positions_involved()
+                        .any(|p| shifted_grid(p.grid).is_some())
+                })
+        })
+        .map(|(h_id, _)| *h_id)
+        .collect();
+    let mut helices_mut = design.helices.make_mut();
+    for h_id in helices_to_update {
+        if let Some(helix) = helices_mut.get_mut(&h_id) {
+            if let Some(position) = helix.grid_position.as_mut() {
+                if let Some(grid) = shifted_grid(position.grid) {
+                    position.grid = grid;
+                }
+            }
+            if let Some(curve) = helix.curve.as_mut() {
+                if let CurveDescriptor::PiecewiseBezier { points, .. } = Arc::make_mut(curve) {
+                    for point in points.iter_mut() {
+                        if let Some(grid) = shifted_grid(point.position.grid) {
+                            point.position.grid = grid;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    drop(helices_mut);
+    Ok(BezierVertexId { path_id, vertex_id })
+}
+
 /// Translate helices by a given translation.
 ///
 /// If snap is true, the helices are mapped to grid position.
@@ -134,3 +293,1289 @@ pub fn rotate_helices_3d(
     let mut helices_translator = HelicesTranslator::from_design(design);
     helices_translator.rotate_helices_3d(snap, helices, rotation, origin)
 }
+
+/// Reflect helices and grids across the plane going through `plane_origin` with normal vector
+/// `plane_normal`. The helices that are on a reflected grid are reflected with it.
+///
+/// The mirror image of a right-handed double helix is left-handed. The reflected helices are
+/// thus right-handed helices whose nucleotides coincide with the mirror image at position 0, and
+/// the strands lying on them are reversed so that they stay antiparallel with their
+/// complementary strands.
+pub fn reflect_helices(
+    design: &mut Design,
+    helices: Vec<usize>,
+    grids: Vec<GridId>,
+    plane_origin: Vec3,
+    plane_normal: Vec3,
+) -> Result<(), ErrOperation> {
+    if plane_normal.mag_sq() < 1e-6 {
+        return Err(ErrOperation::InvalidReflectionPlane);
+    }
+    let reflection = Reflection {
+        origin: plane_origin,
+        normal: plane_normal.normalized(),
+    };
+    let groove_angle = design.parameters.unwrap_or_default().groove_angle;
+
+    let grids: BTreeSet<GridId> = grids.into_iter().collect();
+    let mut reflected_helices: BTreeSet<usize> = helices.into_iter().collect();
+    for (h_id, h) in design.helices.iter() {
+        if h.grid_position
+            .filter(|gp| grids.contains(&gp.grid))
+            .is_some()
+        {
+            reflected_helices.insert(*h_id);
+        }
+    }
+
+    for (s_id, strand) in design.strands.iter() {
+        let mut on_reflected = false;
+        let mut on_other = false;
+        for domain in strand.domains.iter() {
+            if let Domain::HelixDomain(interval) = domain {
+                if reflected_helices.contains(&interval.helix) {
+                    on_reflected = true;
+                } else {
+                    on_other = true;
+                }
+            }
+        }
+        if on_reflected && on_other {
+            return Err(ErrOperation::StrandPartiallyReflected(*s_id));
+        }
+    }
+
+    let mut new_grids = design.free_grids.make_mut();
+    for g_id in grids.iter() {
+        let desc = new_grids
+            .get_mut_g_id(g_id)
+            .filter(|desc| desc.bezier_vertex.is_none())
+            .ok_or(ErrOperation::GridCannotBeReflected(*g_id))?;
+        desc.grid_type = match desc.grid_type {
+            GridTypeDescr::Square { twist } => GridTypeDescr::Square {
+                twist: twist.map(|t| -t),
+            },
+            GridTypeDescr::Honeycomb { twist } => GridTypeDescr::Honeycomb {
+                twist: twist.map(|t| -t),
+            },
+            GridTypeDescr::Hyperboloid { .. } => {
+                return Err(ErrOperation::GridCannotBeReflected(*g_id))
+            }
+        };
+        desc.position = reflection.point(desc.position);
+        desc.orientation = reflection.orientation(desc.orientation);
+    }
+    drop(new_grids);
+
+    let mut new_helices = design.helices.make_mut();
+    for h_id in reflected_helices.iter() {
+        let helix = new_helices
+            .get_mut(h_id)
+            .ok_or(ErrOperation::HelixDoesNotExists(*h_id))?;
+        match helix.grid_position.as_mut() {
+            Some(grid_position) if grids.contains(&grid_position.grid) => {
+                // Square and honeycomb grids are symmetric with respect to their local z axis,
+                // which is mapped to the `x` coordinate of the grid positions.
+                grid_position.x = -grid_position.x;
+                grid_position.roll = -grid_position.roll;
+            }
+            _ => {
+                if helix.curve.is_some() {
+                    return Err(ErrOperation::HelixCannotBeReflected(*h_id));
+                }
+                helix.grid_position = None;
+                helix.position = reflection.point(helix.position);
+                helix.orientation = reflection.orientation(helix.orientation);
+            }
+        }
+        // Exchanging the forward and backward strands shifts the nucleotides by the groove angle
+        helix.roll = -helix.roll - groove_angle;
+    }
+    drop(new_helices);
+
+    for strand in design.strands.values_mut() {
+        let reflected = strand.domains.iter().any(|d| {
+            matches!(d, Domain::HelixDomain(interval) if reflected_helices.contains(&interval.helix))
+        });
+        if reflected {
+            strand.reverse();
+        }
+    }
+
+    let reflect_nucl = |nucl: &Nucl| {
+        if reflected_helices.contains(&nucl.helix) {
+            Nucl {
+                forward: !nucl.forward,
+                ..*nucl
+            }
+        } else {
+            *nucl
+        }
+    };
+    design.anchors = design.anchors.iter().map(reflect_nucl).collect();
+    design.deletions = design.deletions.iter().map(reflect_nucl).collect();
+    design.modifications = std::mem::take(&mut design.modifications)
+        .into_iter()
+        .map(|(nucl, modification)| (reflect_nucl(&nucl), modification))
+        .collect();
+    for selection in design.named_selections.iter_mut() {
+        for element in selection.elements.iter_mut() {
+            if let DnaElementKey::Nucleotide { helix, forward, .. } = element {
+                if reflected_helices.contains(helix) {
+                    *forward ^= true;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A reflection across a plane
+struct Reflection {
+    origin: Vec3,
+    /// A unit vector normal to the plane
+    normal: Vec3,
+}
+
+impl Reflection {
+    fn vector(&self, v: Vec3) -> Vec3 {
+        v - 2. * v.dot(self.normal) * self.normal
+    }
+
+    fn point(&self, p: Vec3) -> Vec3 {
+        self.origin + self.vector(p - self.origin)
+    }
+
+    /// The reflection of a frame is an indirect frame, so the local z axis of the reflected frame
+    /// is flipped to get a rotation.
+    fn orientation(&self, orientation: Rotor3) -> Rotor3 {
+        let x = self.vector(Vec3::unit_x().rotated_by(orientation));
+        let y = self.vector(Vec3::unit_y().rotated_by(orientation));
+        let z = -self.vector(Vec3::unit_z().rotated_by(orientation));
+        Mat3::new(x, y, z).into_rotor3().normalized()
+    }
+}
+
+/// The parameters of a duplication of helices and strands in a periodic pattern.
+///
+/// Each step of the pattern is a rotation around an axis going through the center of the
+/// duplicated helices, followed by a translation. The n-th copy is obtained by applying n steps to
+/// the duplicated elements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DuplicationPattern {
+    pub nb_copies: usize,
+    pub translation: Vec3,
+    pub rotation_axis: Vec3,
+    pub rotation_angle: f32,
+    /// If this is `Some(d)`, the strand ends of consecutive copies that are at distance at most `d`
+    /// are connected.
+    pub connection_distance: Option<f32>,
+}
+
+impl DuplicationPattern {
+    fn step_rotation(&self) -> Rotor3 {
+        if self.rotation_axis.mag_sq() < 1e-6 {
+            Rotor3::identity()
+        } else {
+            Rotor3::from_angle_plane(
+                self.rotation_angle,
+                Bivec3::from_normalized_axis(self.rotation_axis.normalized()),
+            )
+        }
+    }
+}
+
+/// Create copies of helices and strands according to `pattern`. The helices on which the strands
+/// lie are duplicated with them. The copied helices are not attached to any grid.
+///
+/// If `strands` is empty, the strands whose helix domains all lie on the duplicated helices are
+/// duplicated. Return, for the original elements and for each copy, the identifiers of the
+/// corresponding strands.
+pub fn duplicate_in_pattern(
+    design: &mut Design,
+    helices: Vec<usize>,
+    strands: Vec<usize>,
+    pattern: &DuplicationPattern,
+) -> Result<Vec<Vec<usize>>, ErrOperation> {
+    let mut helices: BTreeSet<usize> = helices.into_iter().collect();
+    let strands: Vec<usize> = if strands.is_empty() {
+        design
+            .strands
+            .iter()
+            .filter(|(_, s)| {
+                s.domains.iter().any(|d| d.helix().is_some())
+                    && s.domains
+                        .iter()
+                        .filter_map(|d| d.helix())
+                        .all(|h| helices.contains(&h))
+            })
+            .map(|(s_id, _)| *s_id)
+            .collect()
+    } else {
+        strands
+    };
+    let mut strand_templates = Vec::with_capacity(strands.len());
+    for s_id in strands.iter() {
+        let strand = design
+            .strands
+            .get(s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(*s_id))?;
+        helices.extend(strand.domains.iter().filter_map(|d| d.helix()));
+        strand_templates.push(strand.clone());
+    }
+
+    let mut originals = Vec::with_capacity(helices.len());
+    for h_id in helices.iter() {
+        let helix = design
+            .helices
+            .get(h_id)
+            .ok_or(ErrOperation::HelixDoesNotExists(*h_id))?;
+        if helix.curve.is_some() {
+            return Err(ErrOperation::HelixCannotBeDuplicated(*h_id));
+        }
+        originals.push((*h_id, helix.detached_copy()));
+    }
+
+    let center = if originals.is_empty() {
+        Vec3::zero()
+    } else {
+        originals
+            .iter()
+            .map(|(_, h)| h.position)
+            .fold(Vec3::zero(), |acc, p| acc + p)
+            / originals.len() as f32
+    };
+    let rotation = pattern.step_rotation();
+
+    let mut ret = vec![strands];
+    // The helices of the previous copy, with the identifier of the helix that they are copying
+    let mut copies = originals;
+    for _ in 0..pattern.nb_copies {
+        let mut new_ids = BTreeMap::new();
+        let mut helices_mut = design.helices.make_mut();
+        for (original_id, helix) in copies.iter_mut() {
+            helix.rotate_arround(rotation, center);
+            helix.translate(pattern.translation);
+            let copy_id = helices_mut.push_helix(helix.clone());
+            new_ids.insert(*original_id, copy_id);
+        }
+        for (_, copy_id) in new_ids.iter() {
+            let helix = helices_mut
+                .get_mut(copy_id)
+                .ok_or(ErrOperation::HelixDoesNotExists(*copy_id))?;
+            helix.support_helix = helix
+                .support_helix
+                .map(|support| new_ids.get(&support).cloned().unwrap_or(support));
+        }
+        drop(helices_mut);
+
+        let mut copied_strands = Vec::with_capacity(strand_templates.len());
+        for template in strand_templates.iter() {
+            let mut domains = template.domains.clone();
+            for domain in domains.iter_mut() {
+                match domain {
+                    Domain::HelixDomain(interval) => {
+                        interval.helix = *new_ids
+                            .get(&interval.helix)
+                            .ok_or(ErrOperation::HelixDoesNotExists(interval.helix))?;
+                    }
+                    Domain::Insertion { instanciation, .. } => *instanciation = None,
+                }
+            }
+            let junctions = read_junctions(&domains, template.cyclic);
+            let copy = Strand {
+                domains,
+                junctions,
+                name: None,
+                ..template.clone()
+            };
+            let s_id = design.strands.keys().max().map(|m| m + 1).unwrap_or(0);
+            design.strands.insert(s_id, copy);
+            copied_strands.push(s_id);
+        }
+        ret.push(copied_strands);
+    }
+    Ok(ret)
+}
+
+/// Rotate helices so that their axis becomes parallel or antiparallel, whichever is closer, to
+/// the axis of the `reference` helix.
+///
+/// Each helix is rotated around the middle of the portion of its axis that is used by strands.
+/// Helices attached to a grid are not rotated because their orientation is dictated by the grid.
+pub fn align_helices(
+    design: &mut Design,
+    helices: Vec<usize>,
+    reference: usize,
+) -> Result<(), ErrOperation> {
+    let reference_helix = design
+        .helices
+        .get(&reference)
+        .ok_or(ErrOperation::HelixDoesNotExists(reference))?;
+    if reference_helix.curve.is_some() {
+        return Err(ErrOperation::HelixCannotBeAligned(reference));
+    }
+    let reference_axis = Vec3::unit_x().rotated_by(reference_helix.orientation);
+
+    let mut aligned_helices = BTreeSet::new();
+    for h_id in helices.into_iter().filter(|h_id| *h_id != reference) {
+        let helix = design
+            .helices
+            .get(&h_id)
+            .ok_or(ErrOperation::HelixDoesNotExists(h_id))?;
+        if helix.curve.is_some() {
+            return Err(ErrOperation::HelixCannotBeAligned(h_id));
+        } else if helix.grid_position.is_some() {
+            log::warn!(
+                "Helix {} is attached to a grid and will not be aligned",
+                h_id
+            );
+        } else {
+            aligned_helices.insert(h_id);
+        }
+    }
+
+    // The range of nucleotide positions used by strands on each aligned helix
+    let mut ranges: BTreeMap<usize, (isize, isize)> = BTreeMap::new();
+    for strand in design.strands.values() {
+        for domain in strand.domains.iter() {
+            if let Domain::HelixDomain(interval) = domain {
+                if aligned_helices.contains(&interval.helix) {
+                    let range = ranges
+                        .entry(interval.helix)
+                        .or_insert((interval.start, interval.end));
+                    range.0 = range.0.min(interval.start);
+                    range.1 = range.1.max(interval.end);
+                }
+            }
+        }
+    }
+
+    let parameters = design.parameters.unwrap_or_default();
+    let mut helices_mut = design.helices.make_mut();
+    for h_id in aligned_helices.iter() {
+        let helix = helices_mut
+            .get_mut(h_id)
+            .ok_or(ErrOperation::HelixDoesNotExists(*h_id))?;
+        let axis = Vec3::unit_x().rotated_by(helix.orientation);
+        let target = if axis.dot(reference_axis) < 0. {
+            -reference_axis
+        } else {
+            reference_axis
+        };
+        let center = ranges
+            .get(h_id)
+            .map(|(start, end)| {
+                let first = helix.axis_position(&parameters, *start);
+                let last = helix.axis_position(&parameters, *end - 1);
+                (first + last) / 2.
+            })
+            .unwrap_or(helix.position);
+        helix.rotate_arround(Rotor3::from_rotation_between(axis, target), center);
+    }
+    Ok(())
+}
+
+/// The order in which the helices of a grid are visited by a routed scaffold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaffoldRoutingPattern {
+    /// Row by row, each row being the positions with the same `x` coordinate. The direction in
+    /// which the rows are traversed alternates.
+    Serpentine,
+    /// Clockwise around the grid, from the outermost positions to the center.
+    Spiral,
+}
+
+/// The helices through which a scaffold is routed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScaffoldRoute {
+    /// The helices, in the order in which they are visited
+    Helices(Vec<usize>),
+    /// The helices attached to a grid, in the order given by a pattern
+    Grid {
+        grid_id: GridId,
+        pattern: ScaffoldRoutingPattern,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaffoldRoutingParameters {
+    /// The position of the first nucleotide of the scaffold on each helix
+    pub start: isize,
+    /// The number of nucleotides of the scaffold on each helix
+    pub length: usize,
+    /// The number of positions, at each end of the helices, among which the cross-overs are
+    /// placed
+    pub xover_window: usize,
+    /// If true, the strands lying on the helices are deleted. Otherwise, the operation fails if
+    /// one of the helices carries a strand.
+    pub clear_existing: bool,
+}
+
+impl Default for ScaffoldRoutingParameters {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            length: 64,
+            xover_window: 11,
+            clear_existing: false,
+        }
+    }
+}
+
+/// Create a scaffold strand going through the helices of `route`, add it to the scaffolds of the
+/// design and return its identifier.
+///
+/// The scaffold goes forward on the first helix and changes direction on each helix. The
+/// cross-over between two consecutive helices joins the two closest nucleotides among the
+/// `xover_window` last positions of the first helix. When `clear_existing` is true, the strands
+/// that have a domain on one of the helices are entirely deleted.
+pub fn route_scaffold(
+    design: &mut Design,
+    route: &ScaffoldRoute,
+    parameters: &ScaffoldRoutingParameters,
+    color: u32,
+) -> Result<usize, ErrOperation> {
+    let helices = match route {
+        ScaffoldRoute::Helices(helices) => helices.clone(),
+        ScaffoldRoute::Grid { grid_id, pattern } => helices_of_grid(design, *grid_id, *pattern),
+    };
+    if helices.is_empty() {
+        return Err(ErrOperation::NotEnoughHelices {
+            actual: 0,
+            needed: 1,
+        });
+    }
+    let window = parameters.xover_window;
+    if window == 0 || 2 * window > parameters.length {
+        return Err(ErrOperation::InvalidScaffoldRoutingParameters);
+    }
+    let mut visited = BTreeSet::new();
+    for h_id in helices.iter() {
+        if design.helices.get(h_id).is_none() {
+            return Err(ErrOperation::HelixDoesNotExists(*h_id));
+        }
+        if !visited.insert(*h_id) {
+            return Err(ErrOperation::HelixVisitedTwice(*h_id));
+        }
+    }
+
+    let occupying_strands: Vec<usize> = design
+        .strands
+        .iter()
+        .filter(|(_, s)| {
+            s.domains
+                .iter()
+                .any(|d| d.helix().map(|h| visited.contains(&h)).unwrap_or(false))
+        })
+        .map(|(s_id, _)| *s_id)
+        .collect();
+    if let Some(s_id) = occupying_strands.first() {
+        if parameters.clear_existing {
+            for s_id in occupying_strands.iter() {
+                design.strands.remove(s_id);
+                design.scaffold_ids.retain(|id| id != s_id);
+                design.scaffold_sequences.remove(s_id);
+            }
+        } else {
+            let h_id = design
+                .strands
+                .get(s_id)
+                .and_then(|s| {
+                    s.domains
+                        .iter()
+                        .filter_map(|d| d.helix())
+                        .find(|h| visited.contains(h))
+                })
+                .unwrap_or(helices[0]);
+            return Err(ErrOperation::HelixIsNotEmpty(h_id));
+        }
+    }
+
+    let first = parameters.start;
+    let last = parameters.start + parameters.length as isize - 1;
+    let window = window as isize;
+    let mut domains = Vec::with_capacity(helices.len());
+    let mut prime5 = first;
+    for (i, h_id) in helices.iter().enumerate() {
+        let forward = i % 2 == 0;
+        let (prime3, next_prime5) = if let Some(next_h_id) = helices.get(i + 1) {
+            // The scaffold leaves the helix at the end towards which it goes
+            let candidates = if forward {
+                (last - window + 1)..=last
+            } else {
+                first..=(first + window - 1)
+            };
+            closest_xover(design, (*h_id, *next_h_id), forward, candidates)
+        } else {
+            (if forward { last } else { first }, first)
+        };
+        let (start, end) = if forward {
+            (prime5, prime3 + 1)
+        } else {
+            (prime3, prime5 + 1)
+        };
+        domains.push(Domain::HelixDomain(HelixInterval {
+            helix: *h_id,
+            start,
+            end,
+            forward,
+            sequence: None,
+        }));
+        prime5 = next_prime5;
+    }
+
+    let junctions = read_junctions(&domains, false);
+    let s_id = design.strands.keys().max().map(|m| m + 1).unwrap_or(0);
+    design.strands.insert(
+        s_id,
+        Strand {
+            domains,
+            junctions,
+            color,
+            ..Default::default()
+        },
+    );
+    design.scaffold_ids.push(s_id);
+    Ok(s_id)
+}
+
+/// Return the positions of the two closest nucleotides, among `candidates`, going in direction
+/// `forward` on the first helix and in the opposite direction on the second helix.
+fn closest_xover(
+    design: &Design,
+    (helix_1, helix_2): (usize, usize),
+    forward: bool,
+    candidates: std::ops::RangeInclusive<isize>,
+) -> (isize, isize) {
+    let mut ret = (*candidates.end(), *candidates.end());
+    let mut best_dist = f32::INFINITY;
+    for p1 in candidates.clone() {
+        let pos1 = design.get_nucl_position(Nucl {
+            helix: helix_1,
+            position: p1,
+            forward,
+        });
+        for p2 in candidates.clone() {
+            let pos2 = design.get_nucl_position(Nucl {
+                helix: helix_2,
+                position: p2,
+                forward: !forward,
+            });
+            if let Some(dist) = pos1.zip(pos2).map(|(a, b)| (a - b).mag()) {
+                if dist < best_dist {
+                    best_dist = dist;
+                    ret = (p1, p2);
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// Return the helices attached to the grid `grid_id`, in the order defined by `pattern`.
+fn helices_of_grid(
+    design: &Design,
+    grid_id: GridId,
+    pattern: ScaffoldRoutingPattern,
+) -> Vec<usize> {
+    let positions: BTreeMap<(isize, isize), usize> = design
+        .helices
+        .iter()
+        .filter_map(|(h_id, h)| {
+            h.grid_position
+                .filter(|p| p.grid == grid_id)
+                .map(|p| ((p.x, p.y), *h_id))
+        })
+        .collect();
+    match pattern {
+        ScaffoldRoutingPattern::Serpentine => {
+            let mut rows: BTreeMap<isize, Vec<usize>> = BTreeMap::new();
+            for ((x, _), h_id) in positions.iter() {
+                rows.entry(*x).or_default().push(*h_id);
+            }
+            rows.into_values()
+                .enumerate()
+                .flat_map(|(i, mut row)| {
+                    if i % 2 == 1 {
+                        row.reverse();
+                    }
+                    row
+                })
+                .collect()
+        }
+        ScaffoldRoutingPattern::Spiral => {
+            let (mut x_min, mut x_max, mut y_min, mut y_max) = if let Some(bounds) = positions
+                .keys()
+                .fold(None, |acc: Option<(isize, isize, isize, isize)>, (x, y)| {
+                    Some(acc.map_or((*x, *x, *y, *y), |(x0, x1, y0, y1)| {
+                        (x0.min(*x), x1.max(*x), y0.min(*y), y1.max(*y))
+                    }))
+                }) {
+                bounds
+            } else {
+                return vec![];
+            };
+            let mut ret = Vec::with_capacity(positions.len());
+            let mut push = |x: isize, y: isize| {
+                if let Some(h_id) = positions.get(&(x, y)) {
+                    ret.push(*h_id);
+                }
+            };
+            while x_min <= x_max && y_min <= y_max {
+                for y in y_min..=y_max {
+                    push(x_min, y);
+                }
+                for x in (x_min + 1)..=x_max {
+                    push(x, y_max);
+                }
+                if x_min < x_max && y_min < y_max {
+                    for y in (y_min..y_max).rev() {
+                        push(x_max, y);
+                    }
+                    for x in ((x_min + 1)..x_max).rev() {
+                        push(x, y_min);
+                    }
+                }
+                x_min += 1;
+                x_max -= 1;
+                y_min += 1;
+                y_max -= 1;
+            }
+            ret
+        }
+    }
+}
+
+/// The parameters of the automatic creation of staples on the scaffold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StapleFillingParameters {
+    pub min_length: usize,
+    pub max_length: usize,
+    /// The target lengths of the successive domains of a staple, e.g. `[16, 16]` or `[8, 16, 8]`.
+    ///
+    /// Consecutive staple cross-overs on a helix are at least `first + last` nucleotides apart,
+    /// and a staple is nicked `last` nucleotides after its last cross-over.
+    pub domain_lengths: Vec<usize>,
+    /// The maximal distance between the two nucleotides of a staple cross-over
+    pub max_xover_distance: f32,
+    /// Staple nucleotides that must be the 3' end of a staple
+    pub nicks: Vec<Nucl>,
+}
+
+impl Default for StapleFillingParameters {
+    fn default() -> Self {
+        Self {
+            min_length: 20,
+            max_length: 60,
+            domain_lengths: vec![16, 16],
+            max_xover_distance: 1.5,
+            nicks: Vec::new(),
+        }
+    }
+}
+
+/// Create staples on the nucleotides of the scaffolds that are not paired with a nucleotide of an
+/// other strand, and return the identifiers of the new strands.
+///
+/// The staples are linked by pairs of adjacent cross-overs between neighbouring helices, and the
+/// resulting paths are nicked according to `parameters`. Regions that are too short to hold a
+/// staple of `parameters.min_length` nucleotides are left uncovered.
+pub fn fill_staples<F>(
+    design: &mut Design,
+    parameters: &StapleFillingParameters,
+    mut new_color: F,
+) -> Result<Vec<usize>, ErrOperation>
+where
+    F: FnMut() -> u32,
+{
+    let min_length = parameters.min_length;
+    if min_length == 0
+        || min_length > parameters.max_length
+        || parameters.domain_lengths.is_empty()
+        || parameters.domain_lengths.contains(&0)
+    {
+        return Err(ErrOperation::InvalidStapleFillingParameters);
+    }
+    let scaffolds: Vec<&Strand> = design.scaffolds().map(|(_, s)| s).collect();
+    if scaffolds.is_empty() {
+        return Err(ErrOperation::NoScaffold);
+    }
+
+    let mut occupied = HashSet::new();
+    for (_, strand) in design
+        .strands
+        .iter()
+        .filter(|(s_id, _)| !design.is_scaffold(**s_id))
+    {
+        for domain in strand.domains.iter() {
+            if let Domain::HelixDomain(interval) = domain {
+                occupied.extend(interval.iter().map(|position| Nucl {
+                    helix: interval.helix,
+                    position,
+                    forward: interval.forward,
+                }));
+            }
+        }
+    }
+    // The nucleotides of the new staples, in the order of their partners on the scaffolds
+    let mut track = Vec::new();
+    let mut track_set = HashSet::new();
+    for domain in scaffolds.iter().flat_map(|s| s.domains.iter()) {
+        if let Domain::HelixDomain(interval) = domain {
+            for position in interval.iter() {
+                let nucl = Nucl {
+                    helix: interval.helix,
+                    position,
+                    forward: !interval.forward,
+                };
+                if !occupied.contains(&nucl) && track_set.insert(nucl) {
+                    track.push(nucl);
+                }
+            }
+        }
+    }
+
+    let mut double_xovers = staple_double_xovers(design, &track, &track_set, parameters);
+    let last_domain = parameters.domain_lengths[parameters.domain_lengths.len() - 1];
+    let components = loop {
+        let layout = StapleLayout::new(&track, &track_set, &double_xovers);
+        let components = layout.components(&track, last_domain);
+        // A short path or cycle that goes through a cross-over is merged with its neighbours by
+        // removing the cross-over.
+        let to_remove = components
+            .iter()
+            .filter(|component| component.len() < min_length)
+            .find_map(|component| {
+                component
+                    .iter()
+                    .filter_map(|nucl| layout.xover_entries.get(nucl))
+                    .min()
+                    .cloned()
+            });
+        if let Some(idx) = to_remove {
+            double_xovers.remove(idx);
+        } else {
+            break components;
+        }
+    };
+
+    let nicks: HashSet<Nucl> = parameters.nicks.iter().cloned().collect();
+    let xover_entries: HashSet<Nucl> = double_xovers
+        .iter()
+        .flat_map(|(a, b)| vec![*b, a.prime3()])
+        .collect();
+    let mut new_strands = Vec::new();
+    for component in components.iter() {
+        for staple in nick_staple_path(component, &xover_entries, &nicks, parameters) {
+            let domains = domains_of_nucls(&staple);
+            let junctions = read_junctions(&domains, false);
+            let s_id = design.strands.keys().max().map(|m| m + 1).unwrap_or(0);
+            design.strands.insert(
+                s_id,
+                Strand {
+                    domains,
+                    junctions,
+                    color: new_color(),
+                    ..Default::default()
+                },
+            );
+            new_strands.push(s_id);
+        }
+    }
+    Ok(new_strands)
+}
+
+/// Return the pairs `(a, b)` of staple nucleotides such that the cross-overs `a -> b` and
+/// `b.prime5() -> a.prime3()` are both shorter than `parameters.max_xover_distance`, selected
+/// greedily by increasing length and so that the cross-overs on a helix are far enough apart.
+fn staple_double_xovers(
+    design: &Design,
+    track: &[Nucl],
+    track_set: &HashSet<Nucl>,
+    parameters: &StapleFillingParameters,
+) -> Vec<(Nucl, Nucl)> {
+    let domain_lengths = &parameters.domain_lengths;
+    if domain_lengths.len() < 2 {
+        return Vec::new();
+    }
+    let spacing = (domain_lengths[0] + domain_lengths[domain_lengths.len() - 1]) as isize;
+    let max_distance = parameters.max_xover_distance;
+
+    let positions: Vec<(Nucl, Vec3)> = track
+        .iter()
+        .filter_map(|n| design.get_nucl_position(*n).map(|p| (*n, p)))
+        .collect();
+    let direction = |n: Nucl| {
+        design
+            .get_nucl_position(n)
+            .zip(design.get_nucl_position(n.prime3()))
+            .map(|(p, next)| next - p)
+    };
+    let xover_length = |n1: Nucl, n2: Nucl| {
+        design
+            .get_nucl_position(n1)
+            .zip(design.get_nucl_position(n2))
+            .map(|(p1, p2)| (p1 - p2).mag())
+    };
+    let mut candidates = Vec::new();
+    for (n1, n2, _) in pairs_of_close_nucleotides(&positions, max_distance) {
+        let antiparallel = direction(n1)
+            .zip(direction(n2))
+            .map(|(d1, d2)| d1.dot(d2) < 0.)
+            .unwrap_or(false);
+        if !antiparallel {
+            continue;
+        }
+        for (a, b) in [(n1, n2), (n2, n1)].iter().cloned() {
+            if !track_set.contains(&a.prime3()) || !track_set.contains(&b.prime5()) {
+                continue;
+            }
+            let lengths = xover_length(a, b).zip(xover_length(b.prime5(), a.prime3()));
+            if let Some((l1, l2)) = lengths.filter(|(_, l2)| *l2 < max_distance) {
+                candidates.push((l1 + l2, (a, b)));
+            }
+        }
+    }
+    candidates.sort_by(|(l1, x1), (l2, x2)| {
+        l1.partial_cmp(l2)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| x1.cmp(x2))
+    });
+
+    let mut ret = Vec::new();
+    let mut used = HashSet::new();
+    let mut xover_positions: HashMap<(usize, bool), Vec<isize>> = HashMap::new();
+    for (_, (a, b)) in candidates.into_iter() {
+        let nucls = [a, b, b.prime5(), a.prime3()];
+        let far_enough = |n: &Nucl| {
+            xover_positions
+                .get(&(n.helix, n.forward))
+                .map(|positions| positions.iter().all(|p| (p - n.position).abs() >= spacing))
+                .unwrap_or(true)
+        };
+        if nucls.iter().any(|n| used.contains(n)) || !far_enough(&a) || !far_enough(&b) {
+            continue;
+        }
+        used.extend(nucls.iter().cloned());
+        for n in [a, b].iter() {
+            xover_positions
+                .entry((n.helix, n.forward))
+                .or_default()
+                .push(n.position);
+        }
+        ret.push((a, b));
+    }
+    ret
+}
+
+/// The links between consecutive staple nucleotides.
+struct StapleLayout {
+    next: HashMap<Nucl, Nucl>,
+    prev: HashMap<Nucl, Nucl>,
+    /// Maps the nucleotides that are reached through a cross-over to the index of the double
+    /// cross-over
+    xover_entries: HashMap<Nucl, usize>,
+}
+
+impl StapleLayout {
+    fn new(track: &[Nucl], track_set: &HashSet<Nucl>, double_xovers: &[(Nucl, Nucl)]) -> Self {
+        let mut next = HashMap::new();
+        let mut prev = HashMap::new();
+        for nucl in track.iter() {
+            let successor = nucl.prime3();
+            if track_set.contains(&successor) {
+                next.insert(*nucl, successor);
+                prev.insert(successor, *nucl);
+            }
+        }
+        let mut xover_entries = HashMap::new();
+        for (idx, (a, b)) in double_xovers.iter().enumerate() {
+            for (source, target) in [(*a, *b), (b.prime5(), a.prime3())].iter() {
+                if let Some(old_target) = next.remove(source) {
+                    prev.remove(&old_target);
+                }
+                if let Some(old_source) = prev.remove(target) {
+                    next.remove(&old_source);
+                }
+                next.insert(*source, *target);
+                prev.insert(*target, *source);
+                xover_entries.insert(*target, idx);
+            }
+        }
+        Self {
+            next,
+            prev,
+            xover_entries,
+        }
+    }
+
+    /// Return the paths and cycles of staple nucleotides.
+    ///
+    /// Paths start at the nucleotides that have no predecessor. Cycles start `last_domain`
+    /// nucleotides after one of their cross-overs so that they are nicked in the middle of a
+    /// domain.
+    fn components(&self, track: &[Nucl], last_domain: usize) -> Vec<Vec<Nucl>> {
+        let mut visited = HashSet::new();
+        let mut ret = Vec::new();
+        let walk_from = |start: Nucl, visited: &mut HashSet<Nucl>| {
+            let mut component = Vec::new();
+            let mut nucl = Some(start);
+            while let Some(n) = nucl.filter(|n| visited.insert(*n)) {
+                component.push(n);
+                nucl = self.next.get(&n).cloned();
+            }
+            component
+        };
+        for start in track.iter().filter(|n| !self.prev.contains_key(*n)) {
+            if !visited.contains(start) {
+                ret.push(walk_from(*start, &mut visited));
+            }
+        }
+        for nucl in track.iter() {
+            if visited.contains(nucl) {
+                continue;
+            }
+            let mut start = *nucl;
+            for _ in 0..track.len() {
+                if self.xover_entries.contains_key(&start) {
+                    break;
+                }
+                start = self.next[&start];
+            }
+            for _ in 0..last_domain {
+                start = self.next[&start];
+            }
+            ret.push(walk_from(start, &mut visited));
+        }
+        ret
+    }
+}
+
+/// Cut a path of staple nucleotides into staples.
+fn nick_staple_path(
+    path: &[Nucl],
+    xover_entries: &HashSet<Nucl>,
+    nicks: &HashSet<Nucl>,
+    parameters: &StapleFillingParameters,
+) -> Vec<Vec<Nucl>> {
+    let domain_lengths = &parameters.domain_lengths;
+    let last_domain = domain_lengths[domain_lengths.len() - 1];
+    let mut pieces: Vec<Vec<Nucl>> = Vec::new();
+    let mut current = Vec::new();
+    let mut nb_xovers = 0;
+    let mut domain_length = 0;
+    for nucl in path.iter() {
+        if !current.is_empty() && xover_entries.contains(nucl) {
+            nb_xovers += 1;
+            domain_length = 0;
+        }
+        current.push(*nucl);
+        domain_length += 1;
+        let pattern_done = nb_xovers + 1 >= domain_lengths.len()
+            && domain_length >= last_domain
+            && current.len() >= parameters.min_length;
+        if current.len() >= parameters.max_length || nicks.contains(nucl) || pattern_done {
+            pieces.push(std::mem::take(&mut current));
+            nb_xovers = 0;
+            domain_length = 0;
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+
+    // Short pieces are merged with the previous one unless they are separated by a nick
+    let mut merged: Vec<Vec<Nucl>> = Vec::new();
+    for piece in pieces.into_iter() {
+        match merged.last_mut() {
+            Some(previous)
+                if (previous.len() < parameters.min_length
+                    || piece.len() < parameters.min_length)
+                    && !previous.last().map(|n| nicks.contains(n)).unwrap_or(false) =>
+            {
+                previous.extend(piece)
+            }
+            _ => merged.push(piece),
+        }
+    }
+    let mut ret = Vec::with_capacity(merged.len());
+    for mut staple in merged.into_iter() {
+        if staple.len() > parameters.max_length && staple.len() >= 2 * parameters.min_length {
+            let second_half = staple.split_off(staple.len() / 2);
+            ret.push(staple);
+            ret.push(second_half);
+        } else if staple.len() >= parameters.min_length {
+            ret.push(staple);
+        }
+    }
+    ret
+}
+
+/// Group consecutive nucleotides into domains.
+fn domains_of_nucls(nucls: &[Nucl]) -> Vec<Domain> {
+    let mut ret = Vec::new();
+    let mut current: Option<HelixInterval> = None;
+    for nucl in nucls.iter() {
+        if let Some(interval) = current
+            .as_mut()
+            .filter(|i| i.forward == nucl.forward && i.prime3().prime3() == *nucl)
+        {
+            if nucl.forward {
+                interval.end += 1;
+            } else {
+                interval.start -= 1;
+            }
+        } else {
+            ret.extend(current.take().map(Domain::HelixDomain));
+            current = Some(HelixInterval {
+                helix: nucl.helix,
+                start: nucl.position,
+                end: nucl.position + 1,
+                forward: nucl.forward,
+                sequence: None,
+            });
+        }
+    }
+    ret.extend(current.map(Domain::HelixDomain));
+    ret
+}
+
+/// Set the parameters that define the geometry of `helices`. If `parameters` is `None`, the
+/// helices use the parameters of the design.
+pub fn set_helices_parameters(
+    design: &mut Design,
+    helices: &[usize],
+    parameters: Option<Parameters>,
+) -> Result<(), ErrOperation> {
+    let mut helices_mut = design.helices.make_mut();
+    for h_id in helices.iter() {
+        let helix = helices_mut
+            .get_mut(h_id)
+            .ok_or(ErrOperation::HelixDoesNotExists(*h_id))?;
+        helix.parameters_override = parameters;
+        // The curve of the helix must be discretized with the new parameters
+        helix.instanciated_curve = None;
+    }
+    Ok(())
+}
+
+/// The strands whose color is changed by a recoloring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecolorScope {
+    /// All the strands of the design
+    AllStaples,
+    /// The strands with the given identifiers
+    Strands(Vec<usize>),
+    /// The strands of a group of the organizer, including those of its sub-groups
+    Group(GroupId),
+}
+
+/// The colors given to the recolored strands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecolorPalette {
+    /// Random colors, chosen so that strands with consecutive identifiers look different
+    RandomDistinct,
+    /// Hues going from red to purple along the scaffold
+    Rainbow,
+    /// One random color per helix. A strand gets the color of the helix of its 5' end.
+    ByHelix,
+    /// The given colors, cycled along the scaffold
+    Fixed(Vec<u32>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecolorParameters {
+    pub scope: RecolorScope,
+    pub palette: RecolorPalette,
+    /// The seed of the random choices of the palette. Recoloring a design twice with the same
+    /// parameters gives the same colors.
+    pub seed: u64,
+}
+
+impl Default for RecolorParameters {
+    fn default() -> Self {
+        Self {
+            scope: RecolorScope::AllStaples,
+            palette: RecolorPalette::RandomDistinct,
+            seed: 0,
+        }
+    }
+}
+
+/// The fraction of the color wheel covered by the rainbow palette. It stops before going back to
+/// red so that the first and last strands of the scaffold can be told apart.
+const RAINBOW_HUE_RANGE: f64 = 0.8;
+
+/// Change the color of the strands of `parameters.scope` and return the identifiers of the
+/// recolored strands.
+///
+/// The scaffold and the strands whose `keep_color` flag is set are never recolored.
+pub fn recolor_strands(
+    design: &mut Design,
+    parameters: &RecolorParameters,
+) -> Result<Vec<usize>, ErrOperation> {
+    let candidates: BTreeSet<usize> = match &parameters.scope {
+        RecolorScope::AllStaples => design.strands.keys().cloned().collect(),
+        RecolorScope::Strands(strands) => strands.iter().cloned().collect(),
+        RecolorScope::Group(group_id) => design
+            .organizer_tree
+            .as_ref()
+            .and_then(|tree| strands_of_group(tree, *group_id))
+            .ok_or(ErrOperation::GroupDoesNotExist(*group_id))?,
+    };
+    let recolored: Vec<usize> = candidates
+        .into_iter()
+        .filter(|s_id| !design.is_scaffold(*s_id))
+        .filter(|s_id| {
+            design
+                .strands
+                .get(s_id)
+                .map(|strand| !strand.keep_color)
+                .unwrap_or(false)
+        })
+        .collect();
+
+    let mut rng = StdRng::seed_from_u64(parameters.seed);
+    let colors: Vec<(usize, u32)> = match &parameters.palette {
+        RecolorPalette::RandomDistinct => {
+            let first_idx: u16 = rng.gen();
+            recolored
+                .iter()
+                .enumerate()
+                .map(|(i, s_id)| (*s_id, golden_ratio_color(first_idx as usize + i)))
+                .collect()
+        }
+        RecolorPalette::Rainbow => {
+            let ordered = in_scaffold_order(design, &recolored);
+            let nb_strands = ordered.len().max(1) as f64;
+            ordered
+                .into_iter()
+                .enumerate()
+                .map(|(i, s_id)| {
+                    let hue = RAINBOW_HUE_RANGE * i as f64 / nb_strands;
+                    (s_id, hsv_color(hue, 1., 0.9))
+                })
+                .collect()
+        }
+        RecolorPalette::ByHelix => {
+            let first_idx: u16 = rng.gen();
+            recolored
+                .iter()
+                .map(|s_id| {
+                    let helix = design
+                        .strands
+                        .get(s_id)
+                        .and_then(|strand| strand.get_5prime())
+                        .map(|nucl| nucl.helix)
+                        .unwrap_or(0);
+                    (*s_id, golden_ratio_color(first_idx as usize + helix))
+                })
+                .collect()
+        }
+        RecolorPalette::Fixed(palette) => {
+            if palette.is_empty() {
+                return Err(ErrOperation::EmptyPalette);
+            }
+            in_scaffold_order(design, &recolored)
+                .into_iter()
+                .zip(palette.iter().cycle())
+                .map(|(s_id, color)| (s_id, 0xFF_00_00_00 | color))
+                .collect()
+        }
+    };
+    for (s_id, color) in colors.into_iter() {
+        if let Some(strand) = design.strands.get_mut(&s_id) {
+            strand.color = color;
+        }
+    }
+    Ok(recolored)
+}
+
+/// The strands in the group `group_id` of `tree` or in one of its sub-groups, or `None` if there
+/// is no such group.
+pub fn strands_of_group(
+    tree: &OrganizerTree<DnaElementKey>,
+    group_id: GroupId,
+) -> Option<BTreeSet<usize>> {
+    fn add_strands(tree: &OrganizerTree<DnaElementKey>, strands: &mut BTreeSet<usize>) {
+        match tree {
+            OrganizerTree::Leaf(DnaElementKey::Strand(s_id)) => {
+                strands.insert(*s_id);
+            }
+            OrganizerTree::Leaf(_) => (),
+            OrganizerTree::Node { childrens, .. } => {
+                for c in childrens.iter() {
+                    add_strands(c, strands)
+                }
+            }
+        }
+    }
+    match tree {
+        OrganizerTree::Leaf(_) => None,
+        OrganizerTree::Node { id, .. } if *id == Some(group_id) => {
+            let mut strands = BTreeSet::new();
+            add_strands(tree, &mut strands);
+            Some(strands)
+        }
+        OrganizerTree::Node { childrens, .. } => {
+            childrens.iter().find_map(|c| strands_of_group(c, group_id))
+        }
+    }
+}
+
+/// Sort `strands` by the first nucleotide of the scaffolds to which they are paired, the
+/// scaffolds being taken in order. The strands that are not paired with a scaffold come last, by
+/// increasing identifier.
+fn in_scaffold_order(design: &Design, strands: &[usize]) -> Vec<usize> {
+    let mut rank: HashMap<Nucl, usize> = HashMap::new();
+    for (_, scaffold) in design.scaffolds() {
+        for nucl in scaffold.nucls() {
+            let next_rank = rank.len();
+            rank.entry(nucl.compl()).or_insert(next_rank);
+        }
+    }
+    let mut ret = strands.to_vec();
+    ret.sort_by_key(|s_id| {
+        design
+            .strands
+            .get(s_id)
+            .and_then(|strand| {
+                strand
+                    .nucls()
+                    .iter()
+                    .filter_map(|n| rank.get(n))
+                    .min()
+                    .cloned()
+            })
+            .unwrap_or(usize::MAX)
+    });
+    ret
+}
+
+/// The `idx`-th color of a sequence in which consecutive colors have well separated hues.
+fn golden_ratio_color(idx: usize) -> u32 {
+    let golden_ratio = (1. + 5f64.sqrt()) / 2.;
+    let hue = (idx as f64 * golden_ratio).fract();
+    let saturation = (idx as f64 * 7. * golden_ratio).fract() * 0.25 + 0.75;
+    let value = (idx as f64 * 11. * golden_ratio).fract() * 0.5 + 0.5;
+    hsv_color(hue, saturation, value)
+}
+
+/// The opaque color with the given hue, saturation and value, which are all between 0 and 1.
+fn hsv_color(hue: f64, saturation: f64, value: f64) -> u32 {
+    let sector = hue.rem_euclid(1.) * 6.;
+    let chroma = value * saturation;
+    let x = chroma * (1. - (sector % 2. - 1.).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    let min = value - chroma;
+    let channel = |c: f64| ((c + min) * 255.).round() as u32;
+    0xFF_00_00_00 | (channel(r) << 16) | (channel(g) << 8) | channel(b)
+}
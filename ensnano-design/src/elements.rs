@@ -31,6 +31,11 @@ pub enum DnaElement {
         id: usize,
         length: usize,
         domain_lengths: Vec<usize>,
+        name: Option<String>,
+        visible: bool,
+        /// True if all the nucleotides of the strand are anchors
+        anchored: bool,
+        color: u32,
     },
     Helix {
         id: usize,
@@ -130,6 +135,11 @@ impl OrganizerElement for DnaElement {
     fn display_name(&self) -> String {
         match self {
             DnaElement::Grid { id, .. } => format!("Grid {}", id),
+            DnaElement::Strand {
+                id,
+                name: Some(name),
+                ..
+            } => format!("Strand {}: {}", id, name),
             DnaElement::Strand { id, .. } => format!("Strand {}", id),
             DnaElement::Helix { id, .. } => format!("Helix {}", id),
             DnaElement::Nucleotide {
@@ -168,10 +178,27 @@ impl OrganizerElement for DnaElement {
                 DnaAttribute::LockedForSimulations(*locked),
             ],
             DnaElement::Grid { visible, .. } => vec![DnaAttribute::Visible(*visible)],
+            DnaElement::Strand {
+                visible, anchored, ..
+            } => vec![
+                DnaAttribute::Visible(*visible),
+                DnaAttribute::Anchored(*anchored),
+            ],
             _ => vec![],
         }
     }
 
+    fn is_renamable(&self) -> bool {
+        matches!(self, DnaElement::Strand { .. })
+    }
+
+    fn custom_name(&self) -> Option<String> {
+        match self {
+            DnaElement::Strand { name, .. } => name.clone(),
+            _ => None,
+        }
+    }
+
     fn auto_groups(&self) -> Vec<Self::AutoGroup> {
         match self {
             DnaElement::Strand {
@@ -191,6 +218,31 @@ impl OrganizerElement for DnaElement {
             _ => vec![],
         }
     }
+
+    fn sort_value(&self) -> Option<f32> {
+        match self {
+            DnaElement::Strand { length, .. } => Some(*length as f32),
+            _ => None,
+        }
+    }
+
+    fn color(&self) -> Option<u32> {
+        match self {
+            DnaElement::Strand { color, .. } => Some(*color),
+            _ => None,
+        }
+    }
+
+    fn weight(&self) -> Option<usize> {
+        match self {
+            DnaElement::Strand { length, .. } => Some(*length),
+            _ => None,
+        }
+    }
+
+    fn weight_unit() -> &'static str {
+        "nt"
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
@@ -247,6 +299,7 @@ pub enum DnaAttribute {
     Visible(bool),
     XoverGroup(Option<bool>),
     LockedForSimulations(bool),
+    Anchored(bool),
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, Ord, Eq, TryFromPrimitive, IntoPrimitive)]
@@ -255,12 +308,14 @@ pub enum DnaAttributeRepr {
     Visible,
     XoverGroup,
     LockedForSimulations,
+    Anchored,
 }
 
-const ALL_DNA_ATTRIBUTE_REPR: [DnaAttributeRepr; 3] = [
+const ALL_DNA_ATTRIBUTE_REPR: [DnaAttributeRepr; 4] = [
     DnaAttributeRepr::Visible,
     DnaAttributeRepr::XoverGroup,
     DnaAttributeRepr::LockedForSimulations,
+    DnaAttributeRepr::Anchored,
 ];
 
 impl OrganizerAttributeRepr for DnaAttributeRepr {
@@ -277,6 +332,7 @@ impl OrganizerAttribute for DnaAttribute {
             DnaAttribute::Visible(_) => DnaAttributeRepr::Visible,
             DnaAttribute::XoverGroup(_) => DnaAttributeRepr::XoverGroup,
             DnaAttribute::LockedForSimulations(_) => DnaAttributeRepr::LockedForSimulations,
+            DnaAttribute::Anchored(_) => DnaAttributeRepr::Anchored,
         }
     }
 
@@ -288,6 +344,9 @@ impl OrganizerAttribute for DnaAttribute {
             DnaAttribute::LockedForSimulations(b) => AttributeWidget::FlipButton {
                 value_if_pressed: DnaAttribute::LockedForSimulations(!b),
             },
+            DnaAttribute::Anchored(b) => AttributeWidget::FlipButton {
+                value_if_pressed: DnaAttribute::Anchored(!b),
+            },
             DnaAttribute::XoverGroup(None) => AttributeWidget::FlipButton {
                 value_if_pressed: DnaAttribute::XoverGroup(Some(false)),
             },
@@ -324,6 +383,13 @@ impl OrganizerAttribute for DnaAttribute {
                 };
                 AttributeDisplay::Icon(c)
             }
+            DnaAttribute::Anchored(b) => {
+                if *b {
+                    AttributeDisplay::Text("A".to_owned())
+                } else {
+                    AttributeDisplay::Text("\u{2205}".to_owned())
+                }
+            }
         }
     }
 }
@@ -21,20 +21,50 @@ use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use ultraviolet::{Rotor3, Vec3};
+use ultraviolet::{Mat4, Rotor3, Vec3};
 
+use crate::grid::GridId;
+use crate::utils::is_false;
 use crate::Collection;
 
 const DEFAULT_COLOR: u32 = 0xdb5530; // orange/red
 
 /// An external object to be drawn in the scene
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct External3DObject {
     opacity: f32,
     color: u32,
     position: Vec3,
     orientation: Rotor3,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    #[serde(default, skip_serializing_if = "is_false")]
+    hidden: bool,
     source_file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    attachment: Option<Attachment>,
+}
+
+/// An element of the design to which an external object can be attached. The object then follows
+/// the frame of its anchor when the anchor is moved.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum External3DObjectAnchor {
+    /// A position on a grid, the frame is that of a helix placed at that position.
+    Grid(GridId, isize, isize),
+    /// A nucleotide position on a helix axis.
+    Helix(usize, isize),
+}
+
+/// The transform of an attached object, expressed in the frame of its anchor.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Attachment {
+    anchor: External3DObjectAnchor,
+    local_position: Vec3,
+    local_orientation: Rotor3,
+}
+
+fn default_scale() -> f32 {
+    1.
 }
 
 pub struct External3DObjectDescriptor<P1: AsRef<Path>, P2: AsRef<Path>> {
@@ -58,7 +88,10 @@ impl External3DObject {
                 color: DEFAULT_COLOR,
                 position: Vec3::zero(),
                 orientation: Rotor3::identity(),
+                scale: default_scale(),
+                hidden: false,
                 source_file: rel_path.to_string(),
+                attachment: None,
             })
         } else {
             log::error!(
@@ -69,6 +102,89 @@ impl External3DObject {
             None
         }
     }
+
+    /// The name of the source file of the object
+    pub fn name(&self) -> String {
+        RelativePathBuf::from(&self.source_file)
+            .file_name()
+            .unwrap_or(&self.source_file)
+            .to_string()
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn orientation(&self) -> Rotor3 {
+        self.orientation
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn is_visible(&self) -> bool {
+        !self.hidden
+    }
+
+    pub fn set_transform(&mut self, position: Vec3, orientation: Rotor3, scale: f32) {
+        self.position = position;
+        self.orientation = orientation;
+        self.scale = scale;
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.hidden = !visible;
+    }
+
+    pub fn anchor(&self) -> Option<External3DObjectAnchor> {
+        self.attachment.as_ref().map(|a| a.anchor)
+    }
+
+    /// Attach the object to `anchor` whose current frame is `frame`. The world transform of the
+    /// object is left unchanged.
+    pub fn attach(&mut self, anchor: External3DObjectAnchor, frame: (Vec3, Rotor3)) {
+        self.attachment = Some(Attachment {
+            anchor,
+            local_position: Vec3::zero(),
+            local_orientation: Rotor3::identity(),
+        });
+        self.update_local_offset(frame);
+    }
+
+    /// Detach the object from its anchor. The object keeps its current world transform.
+    pub fn detach(&mut self) {
+        self.attachment = None;
+    }
+
+    /// Recompute the offset of the object relative to its anchor, so that its current world
+    /// transform is preserved when the anchor's frame is `frame`.
+    pub fn update_local_offset(&mut self, frame: (Vec3, Rotor3)) {
+        let (position, orientation) = (self.position, self.orientation);
+        if let Some(attachment) = self.attachment.as_mut() {
+            let inverse = frame.1.reversed();
+            attachment.local_position = (position - frame.0).rotated_by(inverse);
+            attachment.local_orientation = inverse * orientation;
+        }
+    }
+
+    /// The world transform of the object if its anchor's frame is `frame`, or `None` if the object
+    /// is not attached.
+    fn world_transform(&self, frame: (Vec3, Rotor3)) -> Option<(Vec3, Rotor3)> {
+        self.attachment.as_ref().map(|attachment| {
+            (
+                frame.0 + attachment.local_position.rotated_by(frame.1),
+                frame.1 * attachment.local_orientation,
+            )
+        })
+    }
+
+    /// The matrix mapping the coordinates of the source file to the world coordinates
+    pub fn model_matrix(&self) -> Mat4 {
+        Mat4::from_translation(self.position)
+            * self.orientation.into_matrix().into_homogeneous()
+            * Mat4::from_scale(self.scale)
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
@@ -128,9 +244,94 @@ impl External3DObjects {
         let key = self
             .0
             .keys()
-            .min_by_key(|k| k.0)
+            .max_by_key(|k| k.0)
             .map(|k| External3DObjectId(k.0 + 1))
             .unwrap_or(External3DObjectId(0));
         Arc::make_mut(&mut self.0).insert(key, object);
     }
+
+    pub fn get_mut(&mut self, id: &External3DObjectId) -> Option<&mut External3DObject> {
+        Arc::make_mut(&mut self.0).get_mut(id)
+    }
+
+    /// Move the attached objects according to the current frame of their anchor, as given by
+    /// `anchor_frame`. The collection is only modified if an object actually moved.
+    pub(crate) fn follow_anchors<F>(&mut self, anchor_frame: F)
+    where
+        F: Fn(External3DObjectAnchor) -> Option<(Vec3, Rotor3)>,
+    {
+        let moved: Vec<_> = self
+            .0
+            .iter()
+            .filter_map(|(id, object)| {
+                let frame = anchor_frame(object.anchor()?)?;
+                object
+                    .world_transform(frame)
+                    .filter(|(position, orientation)| {
+                        *position != object.position || *orientation != object.orientation
+                    })
+                    .map(|transform| (*id, transform))
+            })
+            .collect();
+        if !moved.is_empty() {
+            let objects = Arc::make_mut(&mut self.0);
+            for (id, (position, orientation)) in moved {
+                if let Some(object) = objects.get_mut(&id) {
+                    object.position = position;
+                    object.orientation = orientation;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object_at(position: Vec3) -> External3DObject {
+        let mut object = External3DObject::new(External3DObjectDescriptor {
+            object_path: "/designs/object.stl",
+            design_path: "/designs",
+        })
+        .unwrap();
+        object.set_transform(position, Rotor3::identity(), 1.);
+        object
+    }
+
+    #[test]
+    fn attached_object_follows_its_anchor() {
+        let anchor = External3DObjectAnchor::Helix(0, 0);
+        let mut objects = External3DObjects::default();
+        let mut object = object_at(Vec3::new(1., 2., 0.));
+        object.attach(anchor, (Vec3::new(1., 0., 0.), Rotor3::identity()));
+        objects.add_object(object);
+
+        let rotation = Rotor3::from_rotation_xy(std::f32::consts::FRAC_PI_2);
+        objects.follow_anchors(|_| Some((Vec3::new(0., 0., 5.), rotation)));
+        let object = objects.get(&External3DObjectId(0)).unwrap();
+        let expected = Vec3::new(0., 0., 5.) + Vec3::new(0., 2., 0.).rotated_by(rotation);
+        assert!((object.position() - expected).mag() < 1e-5);
+        assert!(object.position().y.abs() < 1e-5);
+        assert_eq!(object.anchor(), Some(anchor));
+    }
+
+    #[test]
+    fn detached_object_keeps_its_position() {
+        let mut objects = External3DObjects::default();
+        let mut object = object_at(Vec3::new(1., 2., 0.));
+        object.attach(
+            External3DObjectAnchor::Helix(0, 0),
+            (Vec3::zero(), Rotor3::identity()),
+        );
+        object.detach();
+        objects.add_object(object);
+        let stamp = objects.was_updated(None);
+        objects.follow_anchors(|_| Some((Vec3::new(0., 0., 5.), Rotor3::identity())));
+        assert!(objects.was_updated(stamp).is_none());
+        assert_eq!(
+            objects.get(&External3DObjectId(0)).unwrap().position(),
+            Vec3::new(1., 2., 0.)
+        );
+    }
 }
@@ -0,0 +1,84 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! The fog of the 3D view, saved with the design so that it is restored when the design is
+//! opened again.
+
+use ultraviolet::Vec3;
+
+/// The point around which the fog is centered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FogCenter {
+    Camera,
+    Pivot,
+    /// The centroid of the selected elements, updated when the selection changes
+    Selection,
+}
+
+impl Default for FogCenter {
+    fn default() -> Self {
+        Self::Camera
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FogStyle {
+    Transparent,
+    Dark,
+    Reversed,
+}
+
+impl Default for FogStyle {
+    fn default() -> Self {
+        Self::Transparent
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FogSettings {
+    pub enabled: bool,
+    pub style: FogStyle,
+    /// The distance to the center under which the elements are not hidden by the fog
+    pub radius: f32,
+    /// The distance over which the fog goes from clear to opaque
+    pub softness: f32,
+    pub center: FogCenter,
+    /// The position of the center of the fog when the design was saved. It is used until a
+    /// new pivot is set or the selection changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center_position: Option<Vec3>,
+}
+
+impl Default for FogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            style: FogStyle::Transparent,
+            radius: 10.,
+            softness: 10.,
+            center: FogCenter::Camera,
+            center_position: None,
+        }
+    }
+}
+
+impl FogSettings {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
@@ -25,8 +25,8 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use super::{
     curves,
     design_operations::{ErrOperation, MIN_HELICES_TO_MAKE_GRID},
-    twist_to_omega, Axis, BezierControlPoint, Collection, Design, Helices, Helix, HelixCollection,
-    Parameters, Twist,
+    twist_to_omega, Axis, BezierControlPoint, Collection, Design, External3DObjectAnchor, Helices,
+    Helix, HelixCollection, Parameters, Twist,
 };
 use curves::{
     CurveCache, CurveInstantiator, InstanciatedCurve, InstanciatedCurveDescriptor, PathTimeMaps,
@@ -58,6 +58,8 @@ pub struct Grid {
     pub parameters: Parameters,
     pub grid_type: GridType,
     pub invisible: bool,
+    pub bounds: Option<GridBounds>,
+    pub phantom_length: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -69,6 +71,23 @@ pub struct GridDescriptor {
     pub invisible: bool, // by default grids are visible so we store a "negative attribute"
     #[serde(default)]
     pub bezier_vertex: Option<BezierVertexId>,
+    /// The part of the grid that is drawn in the 3D view. If `None`, the drawn part is adjusted
+    /// to the positions that are used by the helices.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bounds: Option<GridBounds>,
+    /// The number of phantom nucleotides that are drawn on each side of the origin of the
+    /// helices of the grid. If `None`, the default length is used.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub phantom_length: Option<usize>,
+}
+
+/// The range of grid coordinates that is drawn in the 3D view
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct GridBounds {
+    pub min_x: isize,
+    pub max_x: isize,
+    pub min_y: isize,
+    pub max_y: isize,
 }
 
 #[derive(Debug, Clone, Copy, Serialize)]
@@ -109,6 +128,8 @@ impl GridDescriptor {
             grid_type: hyperboloid.desc(),
             invisible: false,
             bezier_vertex: None,
+            bounds: None,
+            phantom_length: None,
         }
     }
 
@@ -119,6 +140,8 @@ impl GridDescriptor {
             invisible: self.invisible,
             grid_type: self.grid_type.to_concrete(),
             parameters,
+            bounds: self.bounds,
+            phantom_length: self.phantom_length,
         }
     }
 }
@@ -142,7 +165,7 @@ impl GridTypeDescr {
         }
     }
 
-    fn to_concrete(self) -> GridType {
+    pub(crate) fn to_concrete(self) -> GridType {
         match self {
             Self::Square { twist } => GridType::square(twist),
             Self::Honeycomb { twist } => GridType::honneycomb(twist),
@@ -292,6 +315,8 @@ impl Grid {
             parameters,
             grid_type,
             invisible: false,
+            bounds: None,
+            phantom_length: None,
         }
     }
 
@@ -416,6 +441,8 @@ impl Grid {
             grid_type: self.grid_type.descr(),
             invisible: self.invisible,
             bezier_vertex: None,
+            bounds: self.bounds,
+            phantom_length: self.phantom_length,
         }
     }
 
@@ -813,9 +840,29 @@ impl GridData {
         ret.update_all_curves(Arc::make_mut(&mut design.cached_curve));
         ret.update_support_helices();
         design.helices = ret.source_helices.clone();
+        design
+            .external_3d_objects
+            .follow_anchors(|anchor| ret.external_object_anchor_frame(anchor));
         ret
     }
 
+    /// The current frame of an element to which an external 3D object can be attached.
+    pub fn external_object_anchor_frame(
+        &self,
+        anchor: External3DObjectAnchor,
+    ) -> Option<(Vec3, Rotor3)> {
+        match anchor {
+            External3DObjectAnchor::Grid(g_id, x, y) => self
+                .grids
+                .get(&g_id)
+                .map(|grid| (grid.position_helix(x, y), grid.orientation_helix(x, y))),
+            External3DObjectAnchor::Helix(h_id, n) => self
+                .source_helices
+                .get(&h_id)
+                .map(|h| (h.axis_position(&self.parameters, n), h.axis_orientation(n))),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn get_empty_grids_id(&self) -> HashSet<GridId> {
         let mut ret: HashSet<GridId> = self.grids.keys().cloned().collect();
@@ -890,6 +937,8 @@ impl GridData {
         for h in new_helices.make_mut().values_mut() {
             self.update_curve(h, cached_curve);
         }
+        cached_curve.retain_used_by(new_helices.values());
+        cached_curve.log_statistics();
         let helices: Vec<(usize, &Helix)> =
             new_helices.iter().map(|(h_id, h)| (*h_id, h)).collect();
         if let Some(paths_data) = self.paths_data.as_ref() {
@@ -901,7 +950,7 @@ impl GridData {
         }
         {
             let maps_mut = Arc::make_mut(&mut self.revolution_curve_time_maps);
-            for k in cached_curve.0.keys() {
+            for k in cached_curve.twisted_tori() {
                 let curve_time_map = RevolutionCurveTimeMaps::new(&k.curve, &helices);
                 maps_mut.insert(k.curve.clone(), Arc::new(curve_time_map));
             }
@@ -1050,6 +1099,8 @@ impl GridData {
                 grid_type: GridTypeDescr::Square { twist: None },
                 invisible: square_grid.invisible,
                 bezier_vertex: None,
+                bounds: None,
+                phantom_length: None,
             }
         } else {
             GridDescriptor {
@@ -1058,6 +1109,8 @@ impl GridData {
                 grid_type: GridTypeDescr::Honeycomb { twist: None },
                 invisible: hex_grid.invisible,
                 bezier_vertex: None,
+                bounds: None,
+                phantom_length: None,
             }
         }
     }
@@ -1128,7 +1181,7 @@ impl GridData {
     pub fn get_persistent_phantom_helices_id(&self) -> HashSet<u32> {
         self.pos_to_object
             .iter()
-            .filter(|(k, _)| !self.no_phantoms.contains(&k.grid))
+            .filter(|(k, _)| !self.no_phantoms.contains(&k.grid) && self.get_visibility(k.grid))
             .map(|(_, v)| match v {
                 GridObject::Helix(h) => *h as u32,
                 GridObject::BezierPoint { helix_id, .. } => *helix_id as u32,
@@ -1413,8 +1466,9 @@ impl GridData {
             .map(|p| helix.need_curve_update(&self.source_free_grids, p))
             .unwrap_or(true)
         {
+            let parameters = *helix.parameters(&self.parameters);
             if let Some(desc) = helix.instanciated_descriptor.as_ref() {
-                let curve = desc.make_curve(&self.parameters, cached_curve);
+                let curve = desc.make_curve(&parameters, cached_curve);
                 curve.update_additional_segments(&mut helix.additonal_isometries);
                 helix.instanciated_curve = Some(InstanciatedCurve {
                     curve,
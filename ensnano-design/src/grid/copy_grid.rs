@@ -108,6 +108,8 @@ impl Design {
                 grid_type: source_grid.grid_type,
                 invisible: false,
                 bezier_vertex: None,
+                bounds: source_grid.bounds,
+                phantom_length: source_grid.phantom_length,
             };
 
             let new_grid_id = new_grids.push(new_grid);
@@ -226,6 +228,9 @@ impl Design {
                 .name
                 .as_ref()
                 .map(|n| Cow::from(format!("{}_copy", n))),
+            invisible: source_strand.invisible,
+            note: source_strand.note.clone(),
+            keep_color: source_strand.keep_color,
         })
     }
 }
@@ -22,6 +22,12 @@ use ultraviolet::{Rotor3, Vec3};
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GroupAttribute {
     pub pivot: Option<GroupPivot>,
+    /// The color used to display the members of the group
+    #[serde(default)]
+    pub color: Option<u32>,
+    /// If true, the operations that would modify the members of the group are rejected
+    #[serde(default)]
+    pub locked: bool,
 }
 
 /// The position and orientation of the pivot used to rotate/translate the group
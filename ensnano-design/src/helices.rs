@@ -29,7 +29,7 @@ use super::{
 };
 use std::collections::BTreeMap;
 use std::sync::Arc;
-use ultraviolet::{DRotor3, DVec3, Isometry2, Mat4, Rotor3, Vec2, Vec3};
+use ultraviolet::{DRotor3, DVec3, Isometry2, Mat3, Mat4, Rotor3, Vec2, Vec3};
 
 /// A structure maping helices identifier to `Helix` objects
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -236,6 +236,11 @@ pub struct Helix {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) path_id: Option<BezierPathId>,
+
+    /// Parameters of the helix that are used instead of the parameters of the design, e.g. to
+    /// have A-form RNA helices in a DNA design.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters_override: Option<Parameters>,
 }
 
 impl Helix {
@@ -280,6 +285,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: None,
         }
     }
 
@@ -363,6 +369,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: None,
         })
     }
 
@@ -390,6 +397,19 @@ impl Helix {
             })
         }
     }
+
+    /// Return a copy of self that is not attached to any grid and that has no 2D representation.
+    pub fn detached_copy(&self) -> Self {
+        Self {
+            instanciated_curve: None,
+            instanciated_descriptor: None,
+            grid_position: None,
+            isometry2d: None,
+            additonal_isometries: Vec::new(),
+            path_id: None,
+            ..self.clone()
+        }
+    }
 }
 
 impl Helix {
@@ -411,6 +431,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: None,
         }
     }
 
@@ -439,6 +460,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: None,
         }
     }
 
@@ -460,6 +482,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: None,
         }
     }
 
@@ -481,6 +504,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: None,
         }
     }
 
@@ -502,6 +526,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: None,
         }
     }
 
@@ -598,6 +623,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: None,
         };
         // we can use a fake cache because we don't need it for bezier curves.
         let mut fake_cache = Default::default();
@@ -641,6 +667,7 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: Some(path_id),
+            parameters_override: None,
         };
         let mut fake_cache = Default::default();
         grid_manager.update_curve(&mut ret, &mut fake_cache);
@@ -654,8 +681,17 @@ impl Helix {
             .unwrap_or(0)
     }
 
+    /// The parameters that define the geometry of the helix: its own parameters if it has some,
+    /// and `design_parameters` otherwise.
+    pub fn parameters<'a>(&'a self, design_parameters: &'a Parameters) -> &'a Parameters {
+        self.parameters_override
+            .as_ref()
+            .unwrap_or(design_parameters)
+    }
+
     pub fn roll_at_pos(&self, n: isize, cst: &Parameters) -> f32 {
         use std::f32::consts::PI;
+        let cst = self.parameters(cst);
         let bbpt = cst.bases_per_turn + self.delta_bbpt;
         let beta = 2. * PI / bbpt;
         self.roll - n as f32 * beta // Beta is positive but helix turn clockwise when n increases
@@ -664,6 +700,7 @@ impl Helix {
     /// Angle of base number `n` around this helix.
     pub fn theta(&self, n: isize, forward: bool, cst: &Parameters) -> f32 {
         use std::f32::consts::PI;
+        let cst = self.parameters(cst);
         // The groove_angle goes from the backward strand to the forward strand
         let shift = if forward { cst.groove_angle } else { 0. };
         let bbpt = cst.bases_per_turn + self.delta_bbpt;
@@ -727,6 +764,7 @@ impl Helix {
     }
 
     pub fn shifted_space_pos(&self, p: &Parameters, n: isize, forward: bool, shift: f32) -> Vec3 {
+        let p = self.parameters(p);
         let n = self.initial_nt_index + n;
         let theta = self.theta(n, forward, p) + shift;
         self.theta_n_to_space_pos(p, n, theta, forward)
@@ -758,10 +796,12 @@ impl Helix {
             initial_nt_index: 0,
             support_helix: None,
             path_id: None,
+            parameters_override: self.parameters_override,
         }
     }
 
     fn position_ideal_neighbour(&self, n: isize, forward: bool, p: &Parameters) -> Vec3 {
+        let p = self.parameters(p);
         let axis_pos = self.axis_position(p, n);
         let my_nucl_pos = self.space_pos(p, n, forward);
         let direction = (my_nucl_pos - axis_pos).normalized();
@@ -810,7 +850,30 @@ impl Helix {
         }
     }
 
+    /// The orientation of the frame of the helix axis at position `n`. The x axis of this frame
+    /// is tangent to the helix axis.
+    pub fn axis_orientation(&self, n: isize) -> Rotor3 {
+        let n = n + self.initial_nt_index;
+        if let Some(curve) = self.instanciated_curve.as_ref().map(|s| &s.curve) {
+            if let Some(axis) = curve.axis_at_pos(n, true) {
+                let frame = Mat3::new(
+                    dvec_to_vec(axis[2]),
+                    dvec_to_vec(axis[1]),
+                    -dvec_to_vec(axis[0]),
+                )
+                .into_rotor3();
+                return if curve.as_ref().has_its_own_encoded_frame() {
+                    frame
+                } else {
+                    self.orientation * frame
+                };
+            }
+        }
+        self.orientation
+    }
+
     pub fn axis_position(&self, p: &Parameters, n: isize) -> Vec3 {
+        let p = self.parameters(p);
         let n = n + self.initial_nt_index;
         if let Some(curve) = self.instanciated_curve.as_ref().map(|s| &s.curve) {
             if let Some(point) = curve.axis_pos(n).map(dvec_to_vec) {
@@ -829,6 +892,21 @@ impl Helix {
         ret
     }
 
+    /// The angle, in radians, by which the strand going in direction `forward` turns around the
+    /// axis of the helix between the nucleotides `n` and `n + 1`.
+    pub fn twist_at(&self, p: &Parameters, n: isize, forward: bool) -> f32 {
+        let axis_0 = self.axis_position(p, n);
+        let axis_1 = self.axis_position(p, n + 1);
+        let tangent = (axis_1 - axis_0).normalized();
+        let radial = |nucl: Vec3, axis: Vec3| {
+            let v = nucl - axis;
+            v - v.dot(tangent) * tangent
+        };
+        let r0 = radial(self.space_pos(p, n, forward), axis_0);
+        let r1 = radial(self.space_pos(p, n + 1, forward), axis_1);
+        r0.cross(r1).mag().atan2(r0.dot(r1))
+    }
+
     pub fn rotate_point(&self, ret: Vec3) -> Vec3 {
         ret.rotated_by(self.orientation)
     }
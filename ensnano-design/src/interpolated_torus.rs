@@ -61,6 +61,9 @@ fn main() {
                 sequence: None,
             })],
             name: None,
+            invisible: false,
+            note: None,
+            keep_color: false,
         };
         let backward_strand = Strand {
             cyclic: false,
@@ -75,6 +78,9 @@ fn main() {
                 sequence: None,
             })],
             name: None,
+            invisible: false,
+            note: None,
+            keep_color: false,
         };
         design.strands.push(forward_strand);
         design.strands.push(backward_strand);
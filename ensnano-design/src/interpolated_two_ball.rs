@@ -118,6 +118,9 @@ fn json_to_ens(path: &Path) {
                 sequence: None,
             })],
             name: None,
+            invisible: false,
+            note: None,
+            keep_color: false,
         };
         let len = helices_length_backward[len_idx];
         let backward_strand = Strand {
@@ -133,6 +136,9 @@ fn json_to_ens(path: &Path) {
                 sequence: None,
             })],
             name: None,
+            invisible: false,
+            note: None,
+            keep_color: false,
         };
         design.strands.push(forward_strand);
         design.strands.push(backward_strand);
@@ -28,10 +28,12 @@ pub use bezier_plane::*;
 pub use ultraviolet;
 use ultraviolet::{Rotor3, Vec3};
 
+pub mod cadnano;
 pub mod codenano;
 pub mod grid;
 use grid::{FreeGrids, GridData, GridDescriptor, GridId};
 pub mod scadnano;
+use cadnano::{CadnanoDesign, CadnanoImportError};
 pub use ensnano_organizer::{GroupId, OrganizerTree};
 use scadnano::*;
 pub mod elements;
@@ -48,7 +50,10 @@ pub use helices::*;
 mod curves;
 pub use curves::*;
 mod collection;
+pub mod design_check;
+pub mod design_diff;
 pub mod design_operations;
+pub mod staple_report;
 pub mod utils;
 pub use collection::{Collection, HasMap};
 
@@ -60,12 +65,19 @@ pub use ultraviolet::*;
 
 mod bezier_plane;
 mod external_3d_objects;
+mod fog;
+pub use fog::{FogCenter, FogSettings, FogStyle};
 mod insertions;
+pub mod modifications;
+mod scaffolds;
+use modifications::Modification;
+pub use scaffolds::ScaffoldSequence;
 #[cfg(test)]
 mod tests;
 pub use external_3d_objects::*;
 
 /// The `ensnano` Design structure.
+#[serde_as]
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Design {
     /// The collection of all helices used in this design. Helices have a
@@ -84,18 +96,26 @@ pub struct Design {
     )]
     pub parameters: Option<Parameters>,
 
-    /// The strand that is the scaffold if the design is an origami
-    #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub scaffold_id: Option<usize>,
+    /// The strands that are scaffolds if the design is an origami
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        alias = "scaffold_id",
+        deserialize_with = "scaffolds::deserialize_scaffold_ids"
+    )]
+    pub scaffold_ids: Vec<usize>,
 
-    /// The sequence of the scaffold if the design is an origami
-    #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub scaffold_sequence: Option<String>,
+    /// The sequences of the scaffolds, indexed by the identifier of the scaffold strand
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub scaffold_sequences: BTreeMap<usize, ScaffoldSequence>,
 
-    /// The shifting of the scaffold if the design is an origami. This is used to reduce the number
-    /// of anti-patern in the stapples sequences
-    #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub scaffold_shift: Option<usize>,
+    /// The sequence of the scaffold in designs that could only have one scaffold. It is moved to
+    /// `scaffold_sequences` by `update_version`.
+    #[serde(default, skip_serializing, rename = "scaffold_sequence")]
+    old_scaffold_sequence: Option<String>,
+
+    #[serde(default, skip_serializing, rename = "scaffold_shift")]
+    old_scaffold_shift: Option<usize>,
 
     #[serde(default)]
     pub free_grids: FreeGrids,
@@ -127,6 +147,20 @@ pub struct Design {
     #[serde(skip_serializing_if = "HashSet::is_empty", default)]
     pub anchors: HashSet<Nucl>,
 
+    /// The set of nucleotides that are marked as deleted, similar to the skips of cadnano.
+    ///
+    /// Deletions are annotation-only markers: a deleted nucleotide is still part of its strand,
+    /// and the strand lengths, the sequences, the 3D geometry and the exports ignore them. Only
+    /// the design summary leaves them out of its nucleotide count.
+    #[serde(skip_serializing_if = "HashSet::is_empty", default)]
+    pub deletions: HashSet<Nucl>,
+
+    /// The chemical modifications carried by nucleotides. They are saved as a list of pairs
+    /// because the keys of a json map must be strings.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub modifications: HashMap<Nucl, Modification>,
+
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub organizer_tree: Option<Arc<OrganizerTree<DnaElementKey>>>,
 
@@ -145,9 +179,17 @@ pub struct Design {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     saved_camera: Option<Camera>,
 
+    /// The fog of the 3D view when the design was saved
+    #[serde(default, skip_serializing_if = "FogSettings::is_default")]
+    pub fog: FogSettings,
+
     #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     pub checked_xovers: HashSet<usize>,
 
+    /// Sets of elements saved under a name so that they can be selected again
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub named_selections: Vec<NamedSelection>,
+
     /// True if the colors of the scaffold's nucleotides should make a rainbow
     #[serde(default)]
     pub rainbow_scaffold: bool,
@@ -282,6 +324,14 @@ impl Design {
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct CameraId(u64);
 
+/// A set of elements of the design, that can be a mix of strands, helices and single
+/// nucleotides, saved under a name so that it can be selected again later.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NamedSelection {
+    pub name: String,
+    pub elements: Vec<DnaElementKey>,
+}
+
 use serde_with::{serde_as, DefaultOnError};
 /// A saved camera position. This can be use to register intresting point of views of the design.
 #[serde_as]
@@ -341,20 +391,25 @@ impl Design {
             strands: Default::default(),
             parameters: Some(Parameters::DEFAULT),
             free_grids: Default::default(),
-            scaffold_id: None,
-            scaffold_sequence: None,
-            scaffold_shift: None,
+            scaffold_ids: Vec::new(),
+            scaffold_sequences: Default::default(),
+            old_scaffold_sequence: None,
+            old_scaffold_shift: None,
             groups: Default::default(),
             small_spheres: Default::default(),
             no_phantoms: Default::default(),
             anchors: Default::default(),
+            deletions: Default::default(),
+            modifications: Default::default(),
             organizer_tree: None,
             ensnano_version: ensnano_version(),
             group_attributes: Default::default(),
             cameras: Default::default(),
             favorite_camera: None,
             saved_camera: None,
+            fog: Default::default(),
             checked_xovers: Default::default(),
+            named_selections: Vec::new(),
             rainbow_scaffold: false,
             instanciated_grid_data: None,
             cached_curve: Default::default(),
@@ -377,6 +432,17 @@ impl Design {
         }
         drop(grids_mut);
 
+        if let Some(sequence) = self.old_scaffold_sequence.take() {
+            if let Some(s_id) = self.scaffold_ids.first() {
+                let shift = self.old_scaffold_shift.unwrap_or(0);
+                self.scaffold_sequences
+                    .insert(*s_id, ScaffoldSequence { sequence, shift });
+            } else {
+                log::warn!("Ignoring the scaffold sequence of a design without scaffold");
+            }
+        }
+        self.old_scaffold_shift = None;
+
         if version_compare::compare(&self.ensnano_version, "0.5.0") == Ok(version_compare::Cmp::Lt)
         {
             // For legacy reason, the version of curved design must be set to a value >= 0.5.0
@@ -404,32 +470,114 @@ impl Design {
     /// Return a list of tuples (n1, n2, M) where n1 and n2 are nuclotides that are not on the same
     /// helix and whose distance is at most `epsilon` and M is the middle of the segment between
     /// the two positions of n1 and n2.
+    ///
+    /// The order of the pairs, and the order of the nucleotides in a pair, are not specified.
     pub fn get_pairs_of_close_nucleotides(&self, epsilon: f32) -> Vec<(Nucl, Nucl, Vec3)> {
-        let mut ret = Vec::new();
+        use rayon::prelude::*;
         let mut nucls = Vec::new();
-        let parameters = self.parameters.unwrap_or_default();
         for s in self.strands.values() {
             for d in s.domains.iter() {
                 if let Domain::HelixDomain(interval) = d {
-                    for i in interval.iter() {
-                        let nucl = Nucl {
-                            helix: interval.helix,
-                            forward: interval.forward,
-                            position: i,
-                        };
-                        if let Some(h) = self.helices.get(&interval.helix) {
-                            let space_position =
-                                h.space_pos(&parameters, nucl.position, nucl.forward);
-                            nucls.push((nucl, space_position));
-                        }
-                    }
+                    nucls.extend(interval.iter().map(|i| Nucl {
+                        helix: interval.helix,
+                        forward: interval.forward,
+                        position: i,
+                    }));
                 }
             }
         }
-        for (n_id, n1) in nucls.iter().enumerate() {
-            for n2 in nucls.iter().skip(n_id + 1) {
-                if n1.0.helix != n2.0.helix && (n1.1 - n2.1).mag() < epsilon {
-                    ret.push((n1.0, n2.0, ((n1.1 + n2.1) / 2.)));
+        let parameters = self.parameters.unwrap_or_default();
+        let helices = &self.helices;
+        let nucls: Vec<(Nucl, Vec3)> = nucls
+            .into_par_iter()
+            .filter_map(|nucl| {
+                let h = helices.get(&nucl.helix)?;
+                Some((nucl, h.space_pos(&parameters, nucl.position, nucl.forward)))
+            })
+            .collect();
+        pairs_of_close_nucleotides(&nucls, epsilon)
+    }
+
+    /// Return a list of cross-overs between nucleotides of `helix_1` and `helix_2` that satisfy
+    /// the constraints given by `parameters`.
+    ///
+    /// Each nucleotide of `helix_1` is paired with the closest nucleotide of `helix_2` whose
+    /// 5' -> 3' direction is opposite to its own. Pairs are then selected along `helix_1`, keeping
+    /// the shortest cross-over at each position.
+    pub fn xovers_between_helices(
+        &self,
+        helix_1: usize,
+        helix_2: usize,
+        parameters: &XoverPlacementParameters,
+    ) -> Vec<(Nucl, Nucl)> {
+        let nucls_1 = self.nucls_on_helix_for_xovers(helix_1, parameters);
+        let nucls_2 = self.nucls_on_helix_for_xovers(helix_2, parameters);
+
+        // The closest partner of each nucleotide of helix_1, at each position of helix_1
+        let mut best_pairs: BTreeMap<isize, (Nucl, Nucl, f32)> = BTreeMap::new();
+        for (n1, position_1, direction_1) in nucls_1.iter() {
+            for (n2, position_2, direction_2) in nucls_2.iter() {
+                let distance = (*position_1 - *position_2).mag();
+                if distance < parameters.max_distance
+                    && direction_1.dot(*direction_2) < 0.
+                    && best_pairs
+                        .get(&n1.position)
+                        .map(|(_, _, d)| distance < *d)
+                        .unwrap_or(true)
+                {
+                    best_pairs.insert(n1.position, (*n1, *n2, distance));
+                }
+            }
+        }
+
+        let mut ret = Vec::new();
+        let mut used = HashSet::new();
+        let mut last_position: Option<isize> = None;
+        for (position, (n1, n2, _)) in best_pairs.into_iter() {
+            let far_enough = last_position
+                .map(|last| (position - last).abs() as usize >= parameters.min_spacing)
+                .unwrap_or(true);
+            let n1_end = self.strands.is_strand_end(&n1).to_opt();
+            let n2_end = self.strands.is_strand_end(&n2).to_opt();
+            let same_end_type = matches!(n1_end.zip(n2_end), Some((a, b)) if a == b);
+            if far_enough && !same_end_type && !used.contains(&n2) {
+                ret.push((n1, n2));
+                used.insert(n2);
+                last_position = Some(position);
+            }
+        }
+        ret
+    }
+
+    /// Return the nucleotides of the strands on `h_id` together with their position and the
+    /// direction from their position to the one of the next nucleotide in the 5' -> 3' direction.
+    fn nucls_on_helix_for_xovers(
+        &self,
+        h_id: usize,
+        parameters: &XoverPlacementParameters,
+    ) -> Vec<(Nucl, Vec3, Vec3)> {
+        let mut ret = Vec::new();
+        for (s_id, strand) in self.strands.iter() {
+            if parameters.protect_scaffold && self.is_scaffold(*s_id) {
+                continue;
+            }
+            for domain in strand.domains.iter() {
+                if let Domain::HelixDomain(interval) = domain {
+                    if interval.helix == h_id {
+                        for position in interval.iter() {
+                            let nucl = Nucl {
+                                helix: h_id,
+                                position,
+                                forward: interval.forward,
+                            };
+                            if let Some((space_pos, next_pos)) = self
+                                .get_nucl_position(nucl)
+                                .zip(self.get_nucl_position(nucl.prime3()))
+                            {
+                                ret.push((nucl, space_pos, next_pos - space_pos));
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -469,6 +617,37 @@ impl Design {
         }
     }
 
+    /// Save `elements` under the name `name`, replacing the named selection with the same name
+    /// if there is one.
+    pub fn add_named_selection(&mut self, name: String, elements: Vec<DnaElementKey>) {
+        if let Some(selection) = self.named_selections.iter_mut().find(|s| s.name == name) {
+            selection.elements = elements;
+        } else {
+            self.named_selections
+                .push(NamedSelection { name, elements });
+        }
+    }
+
+    /// Rename the named selection at position `idx`. Return false if there is no such selection.
+    pub fn rename_named_selection(&mut self, idx: usize, name: String) -> bool {
+        if let Some(selection) = self.named_selections.get_mut(idx) {
+            selection.name = name;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Delete the named selection at position `idx`. Return false if there is no such selection.
+    pub fn rm_named_selection(&mut self, idx: usize) -> bool {
+        if idx < self.named_selections.len() {
+            self.named_selections.remove(idx);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn get_camera_mut(&mut self, cam_id: CameraId) -> Option<&mut Camera> {
         self.cameras.get_mut(&cam_id)
     }
@@ -505,8 +684,23 @@ impl Design {
         self.cameras.iter()
     }
 
+    /// Return true if the strand `s_id` is one of the scaffolds of the design
+    pub fn is_scaffold(&self, s_id: usize) -> bool {
+        self.scaffold_ids.contains(&s_id)
+    }
+
+    /// Return the scaffolds of the design that are strands of the design
+    pub fn scaffolds(&self) -> impl Iterator<Item = (usize, &Strand)> {
+        self.scaffold_ids
+            .iter()
+            .filter_map(|s_id| self.strands.get(s_id).map(|s| (*s_id, s)))
+    }
+
     pub fn prepare_for_save(&mut self, saving_information: SavingInformation) {
         self.saved_camera = saving_information.camera;
+        if let Some(fog) = saving_information.fog {
+            self.fog = fog;
+        }
     }
 
     pub fn get_nucl_position(&self, nucl: Nucl) -> Option<Vec3> {
@@ -608,6 +802,7 @@ pub struct MutStrandAndData<'a> {
 
 pub struct SavingInformation {
     pub camera: Option<Camera>,
+    pub fog: Option<FogSettings>,
 }
 
 impl Design {
@@ -651,13 +846,12 @@ impl Design {
             helices: Helices(Arc::new(helices)),
             strands: Strands(strands),
             small_spheres: Default::default(),
-            scaffold_id: None, //TODO determine this value
-            scaffold_sequence: None,
-            scaffold_shift: None,
+            scaffold_ids: Vec::new(), //TODO determine this value
             groups: Default::default(),
             no_phantoms: Default::default(),
             parameters: Some(Parameters::DEFAULT),
             anchors: Default::default(),
+            deletions: Default::default(),
             organizer_tree: None,
             ensnano_version: ensnano_version(),
             group_attributes: Default::default(),
@@ -666,6 +860,21 @@ impl Design {
         })
     }
 
+    pub fn from_cadnano(cadnano: &CadnanoDesign) -> Result<Self, CadnanoImportError> {
+        let grid = cadnano.grid_descriptor()?;
+        let helices = cadnano.helices(&grid.to_grid(Parameters::DEFAULT))?;
+        let (strands, scaffold_id) = cadnano.strands()?;
+        Ok(Self {
+            free_grids: FreeGrids::from_vec(vec![grid]),
+            helices: Helices(Arc::new(helices)),
+            strands: Strands(strands),
+            scaffold_ids: scaffold_id.into_iter().collect(),
+            parameters: Some(Parameters::DEFAULT),
+            ensnano_version: ensnano_version(),
+            ..Default::default()
+        })
+    }
+
     pub fn _set_helices(&mut self, helices: BTreeMap<usize, Arc<Helix>>) {
         self.helices = Helices(Arc::new(helices));
     }
@@ -706,6 +915,82 @@ where
     Some(())
 }
 
+/// The constraints on the cross-overs placed by [Design::xovers_between_helices]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XoverPlacementParameters {
+    /// The maximal distance between the two nucleotides of a cross-over
+    pub max_distance: f32,
+    /// The minimal number of nucleotides between two consecutive cross-overs
+    pub min_spacing: usize,
+    /// If true, no cross-over is placed on the scaffold
+    pub protect_scaffold: bool,
+}
+
+impl Default for XoverPlacementParameters {
+    fn default() -> Self {
+        Self {
+            max_distance: 1.2,
+            min_spacing: 7,
+            protect_scaffold: true,
+        }
+    }
+}
+
+/// Return a list of tuples (n1, n2, M) where n1 and n2 are nucleotides of `nucls` that are not on
+/// the same helix and whose distance is at most `epsilon` and M is the middle of the segment
+/// between the two positions of n1 and n2.
+///
+/// The positions are put in a grid of cubes of side `epsilon`, so that each nucleotide is only
+/// compared to the nucleotides of the neighbouring cubes. The order of the pairs, and the order of
+/// the nucleotides in a pair, are not specified.
+pub fn pairs_of_close_nucleotides(nucls: &[(Nucl, Vec3)], epsilon: f32) -> Vec<(Nucl, Nucl, Vec3)> {
+    use rayon::prelude::*;
+    if epsilon.is_nan() || epsilon <= 0. {
+        return Vec::new();
+    }
+    let cube = |position: Vec3| {
+        (
+            (position.x / epsilon).floor() as isize,
+            (position.y / epsilon).floor() as isize,
+            (position.z / epsilon).floor() as isize,
+        )
+    };
+    let mut cubes: HashMap<(isize, isize, isize), Vec<usize>> = HashMap::new();
+    for (n_id, (_, position)) in nucls.iter().enumerate() {
+        cubes.entry(cube(*position)).or_default().push(n_id);
+    }
+    nucls
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(n_id, (n1, p1))| {
+            let (x, y, z) = cube(*p1);
+            let mut neighbours = Vec::new();
+            for i in -1..=1 {
+                for j in -1..=1 {
+                    for k in -1..=1 {
+                        let neighbour_cube = (
+                            x.saturating_add(i),
+                            y.saturating_add(j),
+                            z.saturating_add(k),
+                        );
+                        if let Some(ids) = cubes.get(&neighbour_cube) {
+                            neighbours.extend(ids.iter().cloned().filter(|id| *id > n_id));
+                        }
+                    }
+                }
+            }
+            // Cubes on the boundary of the representable coordinates are visited several times
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            neighbours.into_iter().filter_map(move |id| {
+                let (n2, p2) = &nucls[id];
+                (n1.helix != n2.helix && (*p1 - *p2).mag() < epsilon)
+                    .then(|| (*n1, *n2, (*p1 + *p2) / 2.))
+            })
+        })
+        .collect()
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
 pub struct Nucl {
     pub helix: usize,
@@ -0,0 +1,201 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Chemical modifications carried by specific nucleotides, such as a biotin at the 5' end of a
+//! staple or an internal fluorophore.
+//!
+//! When ordering the strands, the modifications are written in the sequence with the codes used
+//! by IDT. These codes depend on whether the modified nucleotide is the 5' end, the 3' end or an
+//! internal nucleotide of its strand.
+
+use crate::{Design, Nucl};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModificationKind {
+    Biotin,
+    Amino,
+    Thiol,
+    Phosphate,
+    Cy3,
+    Cy5,
+    Fam,
+    /// A modification that is not in the list, ordered with the code given in the text of the
+    /// modification
+    Custom,
+}
+
+pub const ALL_MODIFICATION_KINDS: [ModificationKind; 8] = [
+    ModificationKind::Biotin,
+    ModificationKind::Amino,
+    ModificationKind::Thiol,
+    ModificationKind::Phosphate,
+    ModificationKind::Cy3,
+    ModificationKind::Cy5,
+    ModificationKind::Fam,
+    ModificationKind::Custom,
+];
+
+impl std::fmt::Display for ModificationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Biotin => "Biotin",
+            Self::Amino => "Amino",
+            Self::Thiol => "Thiol",
+            Self::Phosphate => "Phosphate",
+            Self::Cy3 => "Cy3",
+            Self::Cy5 => "Cy5",
+            Self::Fam => "FAM",
+            Self::Custom => "Custom",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The position of a modified nucleotide on its strand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModificationPosition {
+    Prime5,
+    Internal,
+    Prime3,
+}
+
+/// A modification attached to a nucleotide
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Modification {
+    pub kind: ModificationKind,
+    /// A free text describing the modification. For custom modifications, this is the code used
+    /// to order the modification.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+}
+
+impl Modification {
+    pub fn new(kind: ModificationKind) -> Self {
+        Self {
+            kind,
+            text: String::new(),
+        }
+    }
+
+    /// The IDT code of the modification at `position`, or `None` if the modification cannot be
+    /// ordered at this position.
+    pub fn idt_code(&self, position: ModificationPosition) -> Option<String> {
+        use ModificationKind::*;
+        use ModificationPosition::*;
+        let code = match (self.kind, position) {
+            (Biotin, Prime5) => "/5Biosg/",
+            (Biotin, Internal) => "/iBiodT/",
+            (Biotin, Prime3) => "/3Bio/",
+            (Amino, Prime5) => "/5AmMC6/",
+            (Amino, Internal) => "/iAmMC6T/",
+            (Amino, Prime3) => "/3AmMO/",
+            (Thiol, Prime5) => "/5ThioMC6-D/",
+            (Thiol, Internal) => "/iThioMC6-D/",
+            (Thiol, Prime3) => "/3ThioMC3-D/",
+            (Phosphate, Prime5) => "/5Phos/",
+            (Phosphate, Internal) => return None,
+            (Phosphate, Prime3) => "/3Phos/",
+            (Cy3, Prime5) => "/5Cy3/",
+            (Cy3, Internal) => "/iCy3/",
+            (Cy3, Prime3) => "/3Cy3Sp/",
+            (Cy5, Prime5) => "/5Cy5/",
+            (Cy5, Internal) => "/iCy5/",
+            (Cy5, Prime3) => "/3Cy5Sp/",
+            (Fam, Prime5) => "/56-FAM/",
+            (Fam, Internal) => "/iFluorT/",
+            (Fam, Prime3) => "/36-FAM/",
+            (Custom, _) => {
+                return Some(self.text.trim().to_string()).filter(|code| !code.is_empty())
+            }
+        };
+        Some(code.to_string())
+    }
+
+    /// True if the internal code of the modification designates a modified thymine, that replaces
+    /// the base of the nucleotide in the sequence.
+    fn replaces_base(&self, position: ModificationPosition) -> bool {
+        position == ModificationPosition::Internal
+            && matches!(
+                self.kind,
+                ModificationKind::Biotin | ModificationKind::Amino | ModificationKind::Fam
+            )
+    }
+
+    /// A short description of the modification
+    pub fn description(&self) -> String {
+        if self.text.is_empty() {
+            self.kind.to_string()
+        } else {
+            format!("{} ({})", self.kind, self.text)
+        }
+    }
+}
+
+/// The sequence of a strand in which the IDT codes of the modifications have been inserted.
+///
+/// `bases` are the bases of the strand in the 5' to 3' direction, together with the nucleotide
+/// that they are attached to, if any. The codes of 5' modifications are written before the base,
+/// and the other codes are written after the base, except for the internal modified thymines
+/// which replace the base.
+pub fn idt_sequence(
+    bases: &[(Option<Nucl>, char)],
+    modifications: &HashMap<Nucl, Modification>,
+) -> String {
+    let mut ret = String::with_capacity(bases.len());
+    for (idx, (nucl, base)) in bases.iter().enumerate() {
+        let position = if idx == 0 {
+            ModificationPosition::Prime5
+        } else if idx == bases.len() - 1 {
+            ModificationPosition::Prime3
+        } else {
+            ModificationPosition::Internal
+        };
+        let modification = nucl.as_ref().and_then(|n| modifications.get(n));
+        let code = modification.and_then(|m| m.idt_code(position));
+        match (modification, code) {
+            (Some(modification), Some(code)) => {
+                if modification.replaces_base(position) {
+                    ret.push_str(&code);
+                } else if position == ModificationPosition::Prime5 {
+                    ret.push_str(&code);
+                    ret.push(*base);
+                } else {
+                    ret.push(*base);
+                    ret.push_str(&code);
+                }
+            }
+            _ => ret.push(*base),
+        }
+    }
+    ret
+}
+
+impl Design {
+    /// Remove the modifications attached to nucleotides that do not belong to any strand.
+    pub fn remove_orphan_modifications(&mut self) {
+        if self.modifications.is_empty() {
+            return;
+        }
+        let used_nucls: HashSet<Nucl> = self
+            .strands
+            .values()
+            .flat_map(|s| s.nucls().into_iter())
+            .collect();
+        self.modifications.retain(|n, _| used_nucls.contains(n));
+    }
+}
@@ -21,7 +21,7 @@ use super::codenano;
 use std::f32::consts::{PI, SQRT_2, TAU};
 
 /// DNA geometric parameters.
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Parameters {
     /// Distance between two consecutive bases along the axis of a
     /// helix, in nanometers.
@@ -55,6 +55,8 @@ impl ScadnanoDesign {
             grid_type,
             invisible: false,
             bezier_vertex: None,
+            bounds: None,
+            phantom_length: None,
         })
     }
 }
@@ -94,6 +96,8 @@ impl ScadnanoGroup {
             position: self.position,
             invisible: false,
             bezier_vertex: None,
+            bounds: None,
+            phantom_length: None,
         })
     }
 }
@@ -0,0 +1,63 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! The scaffolds of a design and their sequences.
+//!
+//! A design may have several scaffolds. Designs saved before this was possible have a single
+//! `scaffold_id` that may be `null`, it is read as a list of zero or one scaffold.
+
+use serde::{Deserialize, Deserializer};
+
+/// The sequence of a scaffold strand.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScaffoldSequence {
+    pub sequence: String,
+    /// The shifting of the sequence on the scaffold strand. This is used to reduce the number of
+    /// anti-patern in the stapples sequences
+    #[serde(default)]
+    pub shift: usize,
+}
+
+impl ScaffoldSequence {
+    /// The number of bases of the sequence
+    pub fn len(&self) -> usize {
+        self.sequence.chars().filter(|c| c.is_alphabetic()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScaffoldIds {
+    Several(Vec<usize>),
+    /// The format of the designs that could only have one scaffold
+    AtMostOne(Option<usize>),
+}
+
+pub(super) fn deserialize_scaffold_ids<'de, D>(deserializer: D) -> Result<Vec<usize>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match ScaffoldIds::deserialize(deserializer)? {
+        ScaffoldIds::Several(ids) => ids,
+        ScaffoldIds::AtMostOne(id) => id.into_iter().collect(),
+    })
+}
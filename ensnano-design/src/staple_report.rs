@@ -0,0 +1,185 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Quality report on the staples of a design.
+//!
+//! For each non-scaffold strand, the report gives its length, GC content, an estimation of its
+//! melting temperature and the length of its domains, and flags the values that fall outside of
+//! user defined bounds.
+
+use crate::{Design, Domain, Nucl};
+
+/// The bounds outside of which the statistics of a staple are flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StapleBounds {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub min_domain_length: usize,
+}
+
+impl Default for StapleBounds {
+    fn default() -> Self {
+        Self {
+            min_length: 18,
+            max_length: 60,
+            min_domain_length: 7,
+        }
+    }
+}
+
+/// A value of a [StapleStats] that falls outside of the [StapleBounds].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StapleWarning {
+    TooShort,
+    TooLong,
+    ShortDomain,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StapleStats {
+    pub strand_id: usize,
+    pub name: Option<String>,
+    pub length: usize,
+    /// The sequence of the staple read in the 5' -> 3' direction. Nucleotides with no assigned
+    /// basis are represented by a '?'.
+    pub sequence: String,
+    /// The proportion of G and C among the known bases of the staple
+    pub gc_content: Option<f32>,
+    /// The estimated melting temperature in °C
+    pub melting_temperature: Option<f32>,
+    /// The number of domains of the staple that are on a helix
+    pub nb_domains: usize,
+    pub shortest_domain: Option<usize>,
+    pub warnings: Vec<StapleWarning>,
+}
+
+impl StapleStats {
+    pub fn is_flagged(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+/// Compute the statistics of all the non-scaffold strands of `design`.
+///
+/// `basis` gives the basis of each nucleotide of the design if it is known. Insertions use their
+/// own sequence when they have one.
+pub fn staple_stats<F>(design: &Design, basis: F, bounds: &StapleBounds) -> Vec<StapleStats>
+where
+    F: Fn(&Nucl) -> Option<char>,
+{
+    let mut ret = Vec::new();
+    for (s_id, strand) in design.strands.iter() {
+        if strand.length() == 0 || design.is_scaffold(*s_id) {
+            continue;
+        }
+        let mut sequence = String::with_capacity(strand.length());
+        let mut domain_lengths = Vec::new();
+        for domain in strand.domains.iter() {
+            match domain {
+                Domain::HelixDomain(interval) => {
+                    domain_lengths.push(domain.length());
+                    for position in interval.iter() {
+                        let nucl = Nucl {
+                            helix: interval.helix,
+                            position,
+                            forward: interval.forward,
+                        };
+                        sequence.push(basis(&nucl).unwrap_or('?'));
+                    }
+                }
+                Domain::Insertion {
+                    nb_nucl,
+                    sequence: insertion_sequence,
+                    ..
+                } => {
+                    let mut chars = insertion_sequence.as_deref().unwrap_or("").chars();
+                    for _ in 0..*nb_nucl {
+                        sequence.push(chars.next().unwrap_or('?'));
+                    }
+                }
+            }
+        }
+        let length = strand.length();
+        let shortest_domain = domain_lengths.iter().min().cloned();
+        let mut warnings = Vec::new();
+        if length < bounds.min_length {
+            warnings.push(StapleWarning::TooShort);
+        }
+        if length > bounds.max_length {
+            warnings.push(StapleWarning::TooLong);
+        }
+        if shortest_domain
+            .map(|l| l < bounds.min_domain_length)
+            .unwrap_or(false)
+        {
+            warnings.push(StapleWarning::ShortDomain);
+        }
+        ret.push(StapleStats {
+            strand_id: *s_id,
+            name: strand.name.as_ref().map(|n| n.to_string()),
+            length,
+            gc_content: gc_content(&sequence),
+            melting_temperature: melting_temperature(&sequence),
+            sequence,
+            nb_domains: domain_lengths.len(),
+            shortest_domain,
+            warnings,
+        });
+    }
+    ret
+}
+
+/// Count the A/T and G/C bases of `sequence`. Other characters are ignored.
+fn count_bases(sequence: &str) -> (usize, usize) {
+    let mut at = 0;
+    let mut gc = 0;
+    for c in sequence.chars() {
+        match c.to_ascii_uppercase() {
+            'A' | 'T' => at += 1,
+            'G' | 'C' => gc += 1,
+            _ => (),
+        }
+    }
+    (at, gc)
+}
+
+/// The proportion of G and C among the known bases of `sequence`.
+pub fn gc_content(sequence: &str) -> Option<f32> {
+    let (at, gc) = count_bases(sequence);
+    if at + gc == 0 {
+        None
+    } else {
+        Some(gc as f32 / (at + gc) as f32)
+    }
+}
+
+/// An estimation of the melting temperature of `sequence` in °C.
+///
+/// The Wallace rule is used for sequences shorter than 14 nucleotides, and the GC content
+/// formula is used for longer sequences. Only the known bases of `sequence` are taken into
+/// account.
+pub fn melting_temperature(sequence: &str) -> Option<f32> {
+    let (at, gc) = count_bases(sequence);
+    let n = at + gc;
+    if n == 0 {
+        None
+    } else if n < 14 {
+        Some((2 * at + 4 * gc) as f32)
+    } else {
+        Some(64.9 + 41. * (gc as f32 - 16.4) / n as f32)
+    }
+}
@@ -256,6 +256,18 @@ pub struct Strand {
     /// will be given a name corresponding to the position of its 5' nucleotide
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub name: Option<Cow<'static, str>>,
+    /// True if the strand is hidden in the 3D and 2D views. Hidden strands are still exported.
+    /// Can be skipped (and defaults to `false`) in the serialization.
+    #[serde(skip_serializing_if = "is_false", default)]
+    pub invisible: bool,
+    /// A free-text note attached to the strand, for documentation purposes. It is displayed when
+    /// hovering the strand in the 3D view and included in the exports.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub note: Option<String>,
+    /// True if the colour of the strand must be preserved when staples are recolored. Can be
+    /// skipped (and defaults to `false`) in the serialization.
+    #[serde(skip_serializing_if = "is_false", default)]
+    pub keep_color: bool,
 }
 
 struct InsertionAccumulator {
@@ -447,6 +459,51 @@ impl Strand {
         self.domains.iter().map(|d| d.length()).sum()
     }
 
+    /// Reverse the 5' to 3' direction of the strand.
+    ///
+    /// The nucleotides keep their position along the helices but are moved to the other strand
+    /// of the helix, i.e. forward domains become backward domains and vice versa. Each nucleotide
+    /// keeps its base.
+    pub fn reverse(&mut self) {
+        fn reversed(sequence: &str) -> Cow<'static, str> {
+            Cow::Owned(sequence.chars().rev().collect())
+        }
+
+        self.domains.reverse();
+        for domain in self.domains.iter_mut() {
+            match domain {
+                Domain::HelixDomain(interval) => {
+                    interval.forward ^= true;
+                    interval.sequence = interval.sequence.as_deref().map(reversed);
+                }
+                Domain::Insertion {
+                    instanciation,
+                    sequence,
+                    attached_to_prime3,
+                    ..
+                } => {
+                    *instanciation = None;
+                    *sequence = sequence.as_deref().map(reversed);
+                    *attached_to_prime3 ^= true;
+                }
+            }
+        }
+
+        // The junction after domain i is now the junction before it. The last junction is either
+        // the 3' end or the junction closing a cycle, which stay at the end.
+        if let Some(last_junction) = self.junctions.pop() {
+            self.junctions.reverse();
+            self.junctions.push(last_junction);
+        }
+
+        // Only the prefix of the sequence that is used by the nucleotides is kept.
+        let length = self.length();
+        self.sequence = self.sequence.as_deref().map(|s| {
+            let used: String = s.chars().take(length).collect();
+            reversed(&used)
+        });
+    }
+
     /// Merge all consecutive domains that are on the same helix
     pub fn merge_consecutive_domains(&mut self) {
         let mut to_merge = vec![];
@@ -589,6 +646,22 @@ impl Strand {
         None
     }
 
+    /// Return the nucleotides of the strand, from its 5' end to its 3' end. The nucleotides of
+    /// the insertions are not included.
+    pub fn nucls(&self) -> Vec<Nucl> {
+        let mut ret = Vec::with_capacity(self.length());
+        for d in self.domains.iter() {
+            if let Domain::HelixDomain(d) = d {
+                ret.extend(d.iter().map(|position| Nucl {
+                    position,
+                    helix: d.helix,
+                    forward: d.forward,
+                }));
+            }
+        }
+        ret
+    }
+
     pub fn insertion_points(&self) -> Vec<(Option<Nucl>, Option<Nucl>)> {
         let mut ret = Vec::new();
         let mut prev_prime3 = if self.cyclic {
@@ -117,6 +117,96 @@ fn scadnano_import_one_loopout() {
     assert_good_strand(&strand, "[H0: 8 -> 15] [@5] [H1: 8 <- 15]")
 }
 
+/// The array of `helix` for a strand going through the nucleotides of `path`, from 5' to 3'.
+fn cadnano_array(helix: isize, path: &[(isize, isize)]) -> Vec<[isize; 4]> {
+    let mut ret = vec![[-1, -1, -1, -1]; 32];
+    for (i, (h, position)) in path.iter().enumerate() {
+        if *h == helix {
+            let (prev_h, prev_p) = if i > 0 { path[i - 1] } else { (-1, -1) };
+            let (next_h, next_p) = path.get(i + 1).cloned().unwrap_or((-1, -1));
+            ret[*position as usize] = [prev_h, prev_p, next_h, next_p];
+        }
+    }
+    ret
+}
+
+/// Two helices of a square lattice. The scaffold goes right on helix 0 and comes back on helix 1,
+/// the staple goes right on helix 1 and comes back on helix 0. Helix 0 has a skip and helix 1
+/// has a loop.
+fn two_helices_cadnano_design() -> super::cadnano::CadnanoDesign {
+    use super::cadnano::{CadnanoDesign, CadnanoVStrand};
+    let scaffold: Vec<(isize, isize)> = (0..8)
+        .map(|j| (0, j))
+        .chain((0..8).rev().map(|j| (1, j)))
+        .collect();
+    let staple: Vec<(isize, isize)> = (0..8)
+        .map(|j| (1, j))
+        .chain((0..8).rev().map(|j| (0, j)))
+        .collect();
+    let mut skip_0 = vec![0; 32];
+    skip_0[3] = -1;
+    let mut loop_1 = vec![0; 32];
+    loop_1[4] = 2;
+    CadnanoDesign {
+        name: String::new(),
+        vstrands: vec![
+            CadnanoVStrand {
+                num: 0,
+                row: 0,
+                col: 0,
+                scaf: cadnano_array(0, &scaffold),
+                stap: cadnano_array(0, &staple),
+                loop_: vec![0; 32],
+                skip: skip_0,
+                stap_colors: vec![],
+            },
+            CadnanoVStrand {
+                num: 1,
+                row: 0,
+                col: 1,
+                scaf: cadnano_array(1, &scaffold),
+                stap: cadnano_array(1, &staple),
+                loop_: loop_1,
+                skip: vec![0; 32],
+                stap_colors: vec![[0, 0xFF0000]],
+            },
+        ],
+    }
+}
+
+#[test]
+fn cadnano_import_loops_and_skips() {
+    let cadnano_design = two_helices_cadnano_design();
+    let ensnano_design =
+        Design::from_cadnano(&cadnano_design).expect("Could not convert to ensnano");
+    assert_eq!(ensnano_design.helices.len(), 2);
+    assert_eq!(ensnano_design.strands.len(), 2);
+    assert_eq!(ensnano_design.scaffold_ids, vec![0]);
+
+    let scaffold = ensnano_design.strands.get(&0).unwrap();
+    assert_eq!(scaffold.domains.len(), 4);
+    assert_good_strand(scaffold, "[H0: 0 -> 6] [H1: 4 <- 7] [@2] [H1: 0 <- 3]");
+
+    let staple = ensnano_design.strands.get(&1).unwrap();
+    assert_eq!(staple.domains.len(), 4);
+    assert_good_strand(staple, "[H1: 0 -> 4] [@2] [H1: 5 -> 7] [H0: 0 <- 6]");
+    assert_eq!(staple.color, 0xFF_FF0000);
+}
+
+#[test]
+fn cadnano_import_multiple_scaffold_fragments() {
+    let mut cadnano_design = two_helices_cadnano_design();
+    // Remove the scaffold cross-over
+    cadnano_design.vstrands[0].scaf[7][2] = -1;
+    cadnano_design.vstrands[0].scaf[7][3] = -1;
+    cadnano_design.vstrands[1].scaf[7][0] = -1;
+    cadnano_design.vstrands[1].scaf[7][1] = -1;
+    let ensnano_design =
+        Design::from_cadnano(&cadnano_design).expect("Could not convert to ensnano");
+    assert_eq!(ensnano_design.strands.len(), 3);
+    assert!(ensnano_design.scaffold_ids.is_empty());
+}
+
 fn assert_good_strand<S: std::ops::Deref<Target = str>>(strand: &Strand, objective: S) {
     use regex::Regex;
     let re = Regex::new(r#"\[[^\]]*\]"#).unwrap();
@@ -627,6 +717,303 @@ fn test_insertion_right_to_left() {
     assert_good_strand(&strand, objective);
 }
 
+#[test]
+fn reverse_strand_with_insertion() {
+    let mut strand = strand_with_insertion();
+    strand.domains = sanitize_domains(&strand.domains, strand.cyclic);
+    strand.junctions = read_junctions(&strand.domains, strand.cyclic);
+    strand.reverse();
+    assert_good_strand(&strand, "[H2: 0 -> 7] [@5] [H1: 4 <- 7] [@8] [H1: 0 <- 3]");
+    assert_eq!(
+        strand.junctions,
+        read_junctions(&strand.domains, strand.cyclic)
+    );
+}
+
+#[test]
+fn duplicate_in_translation_pattern() {
+    use design_operations::{duplicate_in_pattern, DuplicationPattern};
+    let mut design = Design::new();
+    let h_id = design
+        .helices
+        .make_mut()
+        .push_helix(Helix::new(Vec3::zero(), Rotor3::identity()));
+    design.strands.push(Strand::init(h_id, 0, true, 0));
+    let pattern = DuplicationPattern {
+        nb_copies: 3,
+        translation: 2. * Vec3::unit_y(),
+        rotation_axis: Vec3::zero(),
+        rotation_angle: 0.,
+        connection_distance: None,
+    };
+    let copies = duplicate_in_pattern(&mut design, vec![h_id], vec![], &pattern)
+        .expect("Could not duplicate");
+    assert_eq!(copies.len(), 4);
+    assert_eq!(design.helices.len(), 4);
+    assert_eq!(design.strands.len(), 4);
+    for (n, strands) in copies.iter().enumerate() {
+        let nucl = design
+            .strands
+            .get(&strands[0])
+            .unwrap()
+            .get_5prime()
+            .unwrap();
+        let position = design.helices.get(&nucl.helix).unwrap().position;
+        assert!((position - 2. * n as f32 * Vec3::unit_y()).mag() < 1e-5);
+    }
+}
+
+#[test]
+fn align_helices_on_reference() {
+    use design_operations::align_helices;
+    let mut design = Design::new();
+    let mut helices = design.helices.make_mut();
+    let reference = helices.push_helix(Helix::new(Vec3::zero(), Rotor3::identity()));
+    let tilted = helices.push_helix(Helix::new(
+        5. * Vec3::unit_y(),
+        Rotor3::from_rotation_xy(0.3),
+    ));
+    let reversed = helices.push_helix(Helix::new(
+        -5. * Vec3::unit_y(),
+        Rotor3::from_rotation_xy(std::f32::consts::PI - 0.2),
+    ));
+    drop(helices);
+    align_helices(&mut design, vec![tilted, reversed, reference], reference)
+        .expect("Could not align helices");
+    let axis = |h_id| Vec3::unit_x().rotated_by(design.helices.get(&h_id).unwrap().orientation);
+    assert!((axis(tilted) - Vec3::unit_x()).mag() < 1e-5);
+    assert!((axis(reversed) + Vec3::unit_x()).mag() < 1e-5);
+}
+
+fn two_parallel_helices_with_strands() -> Design {
+    let mut design = Design::new();
+    let mut helices = design.helices.make_mut();
+    let h0 = helices.push_helix(Helix::new(Vec3::zero(), Rotor3::identity()));
+    let h1 = helices.push_helix(Helix::new(
+        Parameters::INTER_CENTER_GAP * Vec3::unit_y(),
+        Rotor3::identity(),
+    ));
+    drop(helices);
+    for (h_id, forward) in [(h0, true), (h1, false)].iter() {
+        let mut strand = Strand::init(*h_id, 0, *forward, 0);
+        strand.domains = vec![Domain::HelixDomain(HelixInterval {
+            helix: *h_id,
+            start: 0,
+            end: 42,
+            forward: *forward,
+            sequence: None,
+        })];
+        design.strands.push(strand);
+    }
+    design
+}
+
+#[test]
+fn xovers_between_helices_are_spaced() {
+    let design = two_parallel_helices_with_strands();
+    let parameters = XoverPlacementParameters {
+        protect_scaffold: false,
+        ..Default::default()
+    };
+    let xovers = design.xovers_between_helices(0, 1, &parameters);
+    assert!(!xovers.is_empty());
+    for (n1, n2) in xovers.iter() {
+        assert_eq!(n1.helix, 0);
+        assert_eq!(n2.helix, 1);
+        let distance =
+            (design.get_nucl_position(*n1).unwrap() - design.get_nucl_position(*n2).unwrap()).mag();
+        assert!(distance < parameters.max_distance);
+    }
+    for pair in xovers.windows(2) {
+        assert!(pair[1].0.position - pair[0].0.position >= parameters.min_spacing as isize);
+    }
+}
+
+#[test]
+fn xovers_between_helices_protect_scaffold() {
+    let mut design = two_parallel_helices_with_strands();
+    design.scaffold_ids = vec![0];
+    let xovers = design.xovers_between_helices(0, 1, &Default::default());
+    assert!(xovers.is_empty());
+}
+
+#[test]
+fn staple_report_flags_short_staples() {
+    use staple_report::{staple_stats, StapleBounds, StapleWarning};
+    let mut design = two_parallel_helices_with_strands();
+    design.scaffold_ids = vec![0];
+    design.strands.push(Strand::init(0, 50, true, 0));
+    let short = 2;
+    let stats = staple_stats(&design, |_| Some('G'), &StapleBounds::default());
+    assert_eq!(stats.len(), 2);
+    let long_staple = stats.iter().find(|s| s.strand_id != short).unwrap();
+    assert_eq!(long_staple.length, 42);
+    assert_eq!(long_staple.nb_domains, 1);
+    assert_eq!(long_staple.gc_content, Some(1.));
+    assert!(long_staple.warnings.is_empty());
+    let short_staple = stats.iter().find(|s| s.strand_id == short).unwrap();
+    assert_eq!(
+        short_staple.warnings,
+        vec![StapleWarning::TooShort, StapleWarning::ShortDomain]
+    );
+    assert_eq!(short_staple.melting_temperature, Some(4.));
+}
+
+/// A strand made of the helix intervals (helix, start, end, forward)
+fn strand_from_intervals(intervals: &[(usize, isize, isize, bool)]) -> Strand {
+    let domains: Vec<Domain> = intervals
+        .iter()
+        .map(|(helix, start, end, forward)| {
+            Domain::HelixDomain(HelixInterval {
+                helix: *helix,
+                start: *start,
+                end: *end,
+                forward: *forward,
+                sequence: None,
+            })
+        })
+        .collect();
+    let junctions = read_junctions(&domains, false);
+    Strand {
+        domains,
+        junctions,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn design_check_short_domain() {
+    use design_check::{check_design, Diagnostic};
+    let mut design = Design::new();
+    design
+        .strands
+        .push(strand_from_intervals(&[(0, 0, 5, true)]));
+    let diagnostics = check_design(&design, &Default::default());
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::ShortDomain {
+            strand_id: 0,
+            prime5: Nucl::new(0, 0, true),
+            prime3: Nucl::new(0, 4, true),
+            length: 5,
+        }]
+    );
+}
+
+#[test]
+fn design_check_sandwich_strand() {
+    use design_check::{check_design, Diagnostic};
+    let mut design = Design::new();
+    design.strands.push(strand_from_intervals(&[
+        (0, 0, 8, true),
+        (1, 0, 16, false),
+        (0, 24, 32, true),
+    ]));
+    design.strands.push(strand_from_intervals(&[
+        (2, 0, 8, false),
+        (0, 10, 20, true),
+        (2, 8, 16, false),
+    ]));
+    let sandwiches: Vec<_> = check_design(&design, &Default::default())
+        .into_iter()
+        .filter(|d| matches!(d, Diagnostic::SandwichStrand { .. }))
+        .collect();
+    assert_eq!(
+        sandwiches,
+        vec![Diagnostic::SandwichStrand {
+            strand_id: 0,
+            inner_strand_id: 1,
+            prime5: Nucl::new(0, 0, true),
+            prime3: Nucl::new(0, 31, true),
+        }]
+    );
+}
+
+#[test]
+fn design_check_unpaired_scaffold() {
+    use design_check::{check_design, Diagnostic};
+    let mut design = Design::new();
+    design
+        .strands
+        .push(strand_from_intervals(&[(0, 0, 20, true)]));
+    design.scaffold_ids = vec![0];
+    design
+        .strands
+        .push(strand_from_intervals(&[(0, 0, 5, false)]));
+    let unpaired: Vec<_> = check_design(&design, &Default::default())
+        .into_iter()
+        .filter(|d| matches!(d, Diagnostic::UnpairedScaffold { .. }))
+        .collect();
+    assert_eq!(
+        unpaired,
+        vec![Diagnostic::UnpairedScaffold {
+            first: Nucl::new(0, 5, true),
+            last: Nucl::new(0, 19, true),
+            length: 15,
+        }]
+    );
+}
+
+#[test]
+fn design_check_unpaired_scaffold_on_backward_domain() {
+    use design_check::{check_design, Diagnostic};
+    let mut design = Design::new();
+    design
+        .strands
+        .push(strand_from_intervals(&[(0, 0, 20, false)]));
+    design.scaffold_ids = vec![0];
+    design
+        .strands
+        .push(strand_from_intervals(&[(0, 15, 20, true)]));
+    let unpaired: Vec<_> = check_design(&design, &Default::default())
+        .into_iter()
+        .filter(|d| matches!(d, Diagnostic::UnpairedScaffold { .. }))
+        .collect();
+    assert_eq!(
+        unpaired,
+        vec![Diagnostic::UnpairedScaffold {
+            first: Nucl::new(0, 14, false),
+            last: Nucl::new(0, 0, false),
+            length: 15,
+        }]
+    );
+}
+
+#[test]
+fn design_check_close_xovers() {
+    use design_check::{check_design, Diagnostic};
+    let mut design = Design::new();
+    design.strands.push(strand_from_intervals(&[
+        (0, 0, 10, true),
+        (1, 0, 10, false),
+    ]));
+    design.strands.push(strand_from_intervals(&[
+        (0, 10, 12, true),
+        (1, 10, 12, false),
+    ]));
+    // A double cross-over, whose cross-overs are one nucleotide apart
+    design.strands.push(strand_from_intervals(&[
+        (1, 20, 30, false),
+        (0, 20, 30, true),
+    ]));
+    design.strands.push(strand_from_intervals(&[
+        (0, 15, 22, true),
+        (1, 15, 22, false),
+    ]));
+    let close_xovers: Vec<_> = check_design(&design, &Default::default())
+        .into_iter()
+        .filter(|d| matches!(d, Diagnostic::CloseXovers { .. }))
+        .collect();
+    assert_eq!(
+        close_xovers,
+        vec![Diagnostic::CloseXovers {
+            xover_1: (Nucl::new(0, 9, true), Nucl::new(1, 9, false)),
+            xover_2: (Nucl::new(0, 11, true), Nucl::new(1, 11, false)),
+            spacing: 2,
+        }]
+    );
+}
+
 /// A strand whose inital topology is [H1: 0 -> 3] [@5] [@3] [H1: 4 -> 7] [@5] [H2: 0 <- 7]
 fn strand_with_insertion() -> Strand {
     let strand_str = include_str!("./strand_with_insertion.json");
@@ -647,3 +1034,1058 @@ fn check_formated_strand_with_insertion() {
     let strand = strand_with_insertion();
     assert_good_strand(&strand, formated_strand_with_insertion())
 }
+
+#[test]
+fn deletions_survive_serialization() {
+    let mut design = Design::new();
+    let deleted = Nucl::new(0, 3, true);
+    design.deletions.insert(deleted);
+    let json = serde_json::to_string(&design).expect("Could not serialize design");
+    let read: Design = serde_json::from_str(&json).expect("Could not parse design");
+    assert!(read.deletions.contains(&deleted));
+    assert_eq!(read.deletions.len(), 1);
+
+    let json = serde_json::to_string(&Design::new()).expect("Could not serialize design");
+    assert!(!json.contains("deletions"));
+}
+
+#[test]
+fn anchors_survive_serialization() {
+    let mut design = two_parallel_helices_with_strands();
+    let anchors: Vec<Nucl> = design.strands.get(&0).unwrap().nucls()[..3].to_vec();
+    design.anchors.extend(anchors.iter().cloned());
+    let json = serde_json::to_string(&design).expect("Could not serialize design");
+    let read: Design = serde_json::from_str(&json).expect("Could not parse design");
+    assert_eq!(read.anchors, design.anchors);
+
+    let json = serde_json::to_string(&Design::new()).expect("Could not serialize design");
+    assert!(!json.contains("anchors"));
+}
+
+#[test]
+fn fog_settings_survive_serialization() {
+    let mut design = Design::new();
+    let fog = FogSettings {
+        enabled: true,
+        style: FogStyle::Dark,
+        center: FogCenter::Pivot,
+        center_position: Some(Vec3::new(1., 2., 3.)),
+        ..Default::default()
+    };
+    design.prepare_for_save(SavingInformation {
+        camera: None,
+        fog: Some(fog.clone()),
+    });
+    let json = serde_json::to_string(&design).expect("Could not serialize design");
+    let read: Design = serde_json::from_str(&json).expect("Could not parse design");
+    assert_eq!(read.fog, fog);
+
+    let json = serde_json::to_string(&Design::new()).expect("Could not serialize design");
+    assert!(!json.contains("\"fog\""));
+}
+
+#[test]
+fn single_scaffold_of_old_designs_is_read() {
+    let mut json = serde_json::to_value(&Design::new()).expect("Could not serialize design");
+    json["scaffold_id"] = serde_json::json!(3);
+    json["scaffold_sequence"] = serde_json::json!("ATGC");
+    json["scaffold_shift"] = serde_json::json!(2);
+    let mut design: Design = serde_json::from_value(json).expect("Could not parse design");
+    design.update_version();
+    assert_eq!(design.scaffold_ids, vec![3]);
+    assert_eq!(
+        design.scaffold_sequences.get(&3),
+        Some(&ScaffoldSequence {
+            sequence: "ATGC".to_owned(),
+            shift: 2,
+        })
+    );
+
+    let mut json = serde_json::to_value(&Design::new()).expect("Could not serialize design");
+    json["scaffold_id"] = serde_json::Value::Null;
+    let design: Design = serde_json::from_value(json).expect("Could not parse design");
+    assert!(design.scaffold_ids.is_empty());
+
+    let mut design = Design::new();
+    design.scaffold_ids = vec![1, 0];
+    let json = serde_json::to_string(&design).expect("Could not serialize design");
+    let read: Design = serde_json::from_str(&json).expect("Could not parse design");
+    assert_eq!(read.scaffold_ids, vec![1, 0]);
+}
+
+#[test]
+fn strand_nucls_go_from_5prime_to_3prime() {
+    let strand = strand_from_intervals(&[(0, 0, 2, true), (1, 0, 2, false)]);
+    assert_eq!(
+        strand.nucls(),
+        vec![
+            Nucl::new(0, 0, true),
+            Nucl::new(0, 1, true),
+            Nucl::new(1, 1, false),
+            Nucl::new(1, 0, false),
+        ]
+    );
+}
+
+#[test]
+fn straight_helix_has_relaxed_twist() {
+    let p = Parameters::GEARY_2014_DNA;
+    let helix = Helix::new(Vec3::new(1., 2., 3.), Rotor3::from_rotation_xz(0.7));
+    let relaxed = 2. * std::f32::consts::PI / p.bases_per_turn;
+    for n in -5..5 {
+        for forward in [true, false] {
+            let twist = helix.twist_at(&p, n, forward);
+            assert!((twist - relaxed).abs() < 1e-4, "{} != {}", twist, relaxed);
+        }
+    }
+}
+
+#[test]
+fn misplaced_xover_is_more_strained() {
+    use design_check::xover_strain;
+    let design = two_parallel_helices_with_strands();
+    let (n1, n2) = design.xovers_between_helices(0, 1, &Default::default())[0];
+    let good_strain = xover_strain(&design, n1, n2).unwrap();
+    let shifted = Nucl {
+        position: n2.position + 5,
+        ..n2
+    };
+    let bad_strain = xover_strain(&design, n1, shifted).unwrap();
+    assert!(
+        good_strain < bad_strain,
+        "{} >= {}",
+        good_strain,
+        bad_strain
+    );
+}
+
+#[test]
+fn set_grid_type_moves_helices_to_new_lattice() {
+    use design_operations::set_grid_type;
+    use grid::{GridDescriptor, GridTypeDescr};
+    let mut design = Design::new();
+    let descriptor = GridDescriptor {
+        position: Vec3::zero(),
+        orientation: Rotor3::identity(),
+        grid_type: GridTypeDescr::Square { twist: None },
+        invisible: false,
+        bezier_vertex: None,
+        bounds: None,
+        phantom_length: None,
+    };
+    let grid = descriptor.to_grid(Parameters::DEFAULT);
+    let g_id = design.free_grids.make_mut().push(descriptor);
+    let mut helices = design.helices.make_mut();
+    for (x, y) in [(0, 0), (2, 0), (0, 2), (2, 2)] {
+        helices.push_helix(Helix::new_on_grid(&grid, x, y, g_id));
+    }
+    drop(helices);
+
+    set_grid_type(&mut design, g_id, GridTypeDescr::Honeycomb { twist: None }).unwrap();
+    assert!(matches!(
+        design.free_grids.get_from_g_id(&g_id).unwrap().grid_type,
+        GridTypeDescr::Honeycomb { .. }
+    ));
+    let positions: HashSet<(isize, isize)> = design
+        .helices
+        .values()
+        .map(|h| {
+            let position = h.grid_position.unwrap();
+            (position.x, position.y)
+        })
+        .collect();
+    assert_eq!(positions.len(), 4);
+    assert!(positions.contains(&(0, 0)));
+}
+
+#[test]
+fn inserting_bezier_vertex_preserves_curve_and_shifts_grids() {
+    use design_operations::insert_vertex_in_bezier_path;
+    use grid::{GridDescriptor, GridTypeDescr};
+    fn cubic(ends: &[BezierEndCoordinates], i: usize, t: f32) -> Vec3 {
+        let (p0, p3) = (ends[i].position, ends[i + 1].position);
+        let (c1, c2) = (p0 + ends[i].vector_out, p3 - ends[i + 1].vector_in);
+        let s = 1. - t;
+        p0 * (s * s * s) + c1 * (3. * s * s * t) + c2 * (3. * s * t * t) + p3 * (t * t * t)
+    }
+
+    let mut design = Design::new();
+    let plane = BezierPlaneId(0);
+    let mut paths = design.bezier_paths.make_mut();
+    let path_id = paths.create_path(BezierVertex::new(plane, Vec2::new(0., 0.)));
+    let path = paths.get_mut(&path_id).unwrap();
+    path.add_vertex(BezierVertex::new(plane, Vec2::new(10., 5.)));
+    path.add_vertex(BezierVertex::new(plane, Vec2::new(20., 0.)));
+    drop(paths);
+    let old_ends = design
+        .bezier_paths
+        .get(&path_id)
+        .unwrap()
+        .to_instanciated_path_2d()
+        .unwrap()
+        .ends;
+
+    let descriptor = GridDescriptor {
+        position: Vec3::zero(),
+        orientation: Rotor3::identity(),
+        grid_type: GridTypeDescr::Square { twist: None },
+        invisible: false,
+        bezier_vertex: None,
+        bounds: None,
+        phantom_length: None,
+    };
+    let grid = descriptor.to_grid(Parameters::DEFAULT);
+    let last_vertex = BezierVertexId {
+        path_id,
+        vertex_id: 2,
+    };
+    let h_id = design.helices.make_mut().push_helix(Helix::new_on_grid(
+        &grid,
+        0,
+        0,
+        GridId::BezierPathGrid(last_vertex),
+    ));
+
+    let middle = cubic(&old_ends, 0, 0.5);
+    let new_vertex =
+        insert_vertex_in_bezier_path(&mut design, path_id, 0, Vec2::new(middle.x, middle.y))
+            .unwrap();
+    assert_eq!(new_vertex.vertex_id, 1);
+
+    let path = design.bezier_paths.get(&path_id).unwrap();
+    assert_eq!(path.vertices().len(), 4);
+    let new_ends = path.to_instanciated_path_2d().unwrap().ends;
+    for t in [0.1, 0.3, 0.5, 0.7, 0.9] {
+        let (segment, local_t) = if t < 0.5 {
+            (0, 2. * t)
+        } else {
+            (1, 2. * t - 1.)
+        };
+        let expected = cubic(&old_ends, 0, t);
+        assert!((cubic(&new_ends, segment, local_t) - expected).mag() < 1e-2);
+        // The segment that was not split is unchanged
+        assert!((cubic(&new_ends, 2, t) - cubic(&old_ends, 1, t)).mag() < 1e-4);
+    }
+
+    assert_eq!(
+        design
+            .helices
+            .get(&h_id)
+            .unwrap()
+            .grid_position
+            .unwrap()
+            .grid,
+        GridId::BezierPathGrid(BezierVertexId {
+            path_id,
+            vertex_id: 3
+        })
+    );
+}
+
+#[test]
+fn design_diff_reports_modified_strands_and_moved_helices() {
+    use design_diff::diff;
+    let old = two_parallel_helices_with_strands();
+    let mut new = old.clone();
+    assert!(diff(&old, &new).is_empty());
+
+    new.strands.iter_mut().next().unwrap().1.color = 0xFF_00_00;
+    new.strands
+        .push(strand_from_intervals(&[(0, 50, 60, true)]));
+    let mut helices = new.helices.make_mut();
+    helices.get_mut(&1).unwrap().position += Vec3::unit_z();
+    drop(helices);
+
+    let changes = diff(&old, &new);
+    assert_eq!(changes.modified_strands, vec![0]);
+    assert_eq!(changes.added_strands, vec![2]);
+    assert!(changes.removed_strands.is_empty());
+    assert_eq!(changes.moved_helices, vec![1]);
+}
+
+#[test]
+fn design_diff_handles_reused_strand_ids() {
+    use design_diff::diff;
+    let old = two_parallel_helices_with_strands();
+
+    // The id of a deleted strand is given to an unrelated strand
+    let mut new = old.clone();
+    new.strands.remove(&1);
+    new.strands
+        .push(strand_from_intervals(&[(0, 50, 60, true)]));
+    let changes = diff(&old, &new);
+    assert_eq!(changes.removed_strands, vec![1]);
+    assert_eq!(changes.added_strands, vec![1]);
+    assert!(changes.modified_strands.is_empty());
+
+    // A strand that was given a new id is not reported
+    let mut new = old.clone();
+    let strand = new.strands.remove(&0).unwrap();
+    new.strands.insert(2, strand);
+    assert!(diff(&old, &new).is_empty());
+}
+
+#[test]
+fn changed_ids_only_report_edited_objects() {
+    use design_diff::{changed_helix_ids, changed_strand_ids};
+    let old = two_parallel_helices_with_strands();
+    let mut new = old.clone();
+    assert!(changed_strand_ids(&old, &new).is_empty());
+    assert!(changed_helix_ids(&old, &new).is_empty());
+
+    new.strands.get_mut(&1).unwrap().color = 0xFF_00_00;
+    new.strands.get_mut(&0).unwrap().invisible = true;
+    let mut helices = new.helices.make_mut();
+    helices.get_mut(&1).unwrap().roll += 1.;
+    drop(helices);
+    assert_eq!(
+        changed_strand_ids(&old, &new)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![0, 1]
+    );
+    assert_eq!(
+        changed_helix_ids(&old, &new)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![1]
+    );
+
+    // Moving a strand to a new id changes both ids
+    let mut new = old.clone();
+    let strand = new.strands.remove(&0).unwrap();
+    new.strands.insert(2, strand);
+    assert_eq!(
+        changed_strand_ids(&old, &new)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![0, 2]
+    );
+}
+
+#[test]
+fn named_selections_survive_serialization() {
+    use elements::DnaElementKey;
+    let mut design = two_parallel_helices_with_strands();
+    let elements = vec![
+        DnaElementKey::Strand(0),
+        DnaElementKey::Helix(1),
+        DnaElementKey::Nucleotide {
+            helix: 0,
+            position: 3,
+            forward: true,
+        },
+    ];
+    design.add_named_selection("mixed".to_string(), elements.clone());
+    let json = serde_json::to_string(&design).expect("Could not serialize design");
+    let read: Design = serde_json::from_str(&json).expect("Could not parse design");
+    assert_eq!(read.named_selections, design.named_selections);
+    assert_eq!(read.named_selections[0].elements, elements);
+
+    let json = serde_json::to_string(&Design::new()).expect("Could not serialize design");
+    assert!(!json.contains("named_selections"));
+}
+
+#[test]
+fn adding_a_named_selection_with_an_existing_name_replaces_it() {
+    use elements::DnaElementKey;
+    let mut design = Design::new();
+    design.add_named_selection("a".to_string(), vec![DnaElementKey::Strand(0)]);
+    design.add_named_selection("b".to_string(), vec![DnaElementKey::Strand(1)]);
+    design.add_named_selection("a".to_string(), vec![DnaElementKey::Helix(2)]);
+    assert_eq!(design.named_selections.len(), 2);
+    assert_eq!(
+        design.named_selections[0].elements,
+        vec![DnaElementKey::Helix(2)]
+    );
+
+    assert!(design.rename_named_selection(1, "c".to_string()));
+    assert_eq!(design.named_selections[1].name, "c");
+    assert!(design.rm_named_selection(0));
+    assert!(!design.rm_named_selection(1));
+    assert_eq!(design.named_selections.len(), 1);
+}
+
+/// The pairs of close nucleotides computed by comparing all the pairs of nucleotides
+fn brute_force_close_nucleotides(nucls: &[(Nucl, Vec3)], epsilon: f32) -> Vec<(Nucl, Nucl)> {
+    let mut ret = Vec::new();
+    for (n_id, n1) in nucls.iter().enumerate() {
+        for n2 in nucls.iter().skip(n_id + 1) {
+            if n1.0.helix != n2.0.helix && (n1.1 - n2.1).mag() < epsilon {
+                ret.push(ordered_pair(n1.0, n2.0));
+            }
+        }
+    }
+    ret.sort();
+    ret
+}
+
+fn ordered_pair(n1: Nucl, n2: Nucl) -> (Nucl, Nucl) {
+    if n1 < n2 {
+        (n1, n2)
+    } else {
+        (n2, n1)
+    }
+}
+
+fn random_design(rng: &mut impl rand::Rng) -> Design {
+    let mut design = Design::new();
+    let nb_helices = rng.gen_range(2..6);
+    let mut helices = design.helices.make_mut();
+    for _ in 0..nb_helices {
+        let origin = Vec3::new(
+            rng.gen_range(-5.0..5.0),
+            rng.gen_range(-5.0..5.0),
+            rng.gen_range(-5.0..5.0),
+        );
+        let orientation = Rotor3::from_euler_angles(
+            rng.gen_range(-3.0..3.0),
+            rng.gen_range(-3.0..3.0),
+            rng.gen_range(-3.0..3.0),
+        );
+        helices.push_helix(Helix::new(origin, orientation));
+    }
+    drop(helices);
+    for h_id in 0..nb_helices {
+        for forward in [true, false].iter() {
+            let start = rng.gen_range(-20..0);
+            let end = rng.gen_range(1..20);
+            design
+                .strands
+                .push(strand_from_intervals(&[(h_id, start, end, *forward)]));
+        }
+    }
+    design
+}
+
+#[test]
+fn close_nucleotides_match_brute_force() {
+    use rand::{Rng, SeedableRng};
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    for _ in 0..20 {
+        let design = random_design(&mut rng);
+        let epsilon = rng.gen_range(0.5..3.0);
+        let parameters = design.parameters.unwrap_or_default();
+        let mut nucls = Vec::new();
+        for strand in design.strands.values() {
+            for domain in strand.domains.iter() {
+                if let Domain::HelixDomain(interval) = domain {
+                    let helix = design.helices.get(&interval.helix).unwrap();
+                    for position in interval.iter() {
+                        let nucl = Nucl::new(interval.helix, position, interval.forward);
+                        nucls.push((nucl, helix.space_pos(&parameters, position, nucl.forward)));
+                    }
+                }
+            }
+        }
+        let expected = brute_force_close_nucleotides(&nucls, epsilon);
+        let close = design.get_pairs_of_close_nucleotides(epsilon);
+        for (n1, n2, middle) in close.iter() {
+            let p1 = nucls.iter().find(|n| n.0 == *n1).unwrap().1;
+            let p2 = nucls.iter().find(|n| n.0 == *n2).unwrap().1;
+            assert!((*middle - (p1 + p2) / 2.).mag() < 1e-5);
+        }
+        let mut close: Vec<_> = close
+            .into_iter()
+            .map(|(n1, n2, _)| ordered_pair(n1, n2))
+            .collect();
+        close.sort();
+        assert_eq!(close, expected);
+    }
+}
+
+#[test]
+fn moving_a_straight_helix_does_not_rediscretize_curves() {
+    use grid::{GridDescriptor, GridTypeDescr};
+    let mut design = Design::new();
+    let descriptor = GridDescriptor {
+        position: Vec3::zero(),
+        orientation: Rotor3::identity(),
+        grid_type: GridTypeDescr::Square { twist: Some(0.5) },
+        invisible: false,
+        bezier_vertex: None,
+        bounds: None,
+        phantom_length: None,
+    };
+    let grid = descriptor.to_grid(Parameters::DEFAULT);
+    let g_id = design.free_grids.make_mut().push(descriptor);
+    let mut helices = design.helices.make_mut();
+    for (x, y) in [(0, 0), (1, 0)] {
+        helices.push_helix(Helix::new_on_grid(&grid, x, y, g_id));
+    }
+    let straight = helices.push_helix(Helix::new(10. * Vec3::unit_y(), Rotor3::identity()));
+    drop(helices);
+    for h_id in 0..3 {
+        design
+            .strands
+            .push(strand_from_intervals(&[(h_id, 0, 20, true)]));
+    }
+    let nb_curved = |design: &Design| {
+        design
+            .helices
+            .values()
+            .filter(|h| h.instanciated_curve.is_some())
+            .count()
+    };
+
+    design.get_updated_grid_data();
+    assert_eq!(nb_curved(&design), 2);
+    let nb_misses = design.cached_curve.nb_misses();
+
+    let mut helices = design.helices.make_mut();
+    helices.get_mut(&straight).unwrap().position += Vec3::unit_x();
+    drop(helices);
+    design.get_updated_grid_data();
+    assert_eq!(nb_curved(&design), 2);
+    assert_eq!(design.cached_curve.nb_misses(), nb_misses);
+}
+
+/// Helices parallel to the x axis, whose axis are placed along a line.
+fn helices_in_a_row(nb_helices: usize) -> (Design, Vec<usize>) {
+    let mut design = Design::new();
+    let mut helices = design.helices.make_mut();
+    let ids = (0..nb_helices)
+        .map(|i| {
+            helices.push_helix(Helix::new(
+                i as f32 * Parameters::INTER_CENTER_GAP * Vec3::unit_y(),
+                Rotor3::identity(),
+            ))
+        })
+        .collect();
+    drop(helices);
+    (design, ids)
+}
+
+#[test]
+fn scaffold_routed_through_helices() {
+    use design_operations::{route_scaffold, ScaffoldRoute, ScaffoldRoutingParameters};
+    let (mut design, helices) = helices_in_a_row(4);
+    let parameters = ScaffoldRoutingParameters::default();
+    let s_id = route_scaffold(
+        &mut design,
+        &ScaffoldRoute::Helices(helices.clone()),
+        &parameters,
+        0,
+    )
+    .expect("Could not route scaffold");
+    assert_eq!(design.scaffold_ids, vec![s_id]);
+    let strand = design.strands.get(&s_id).unwrap();
+    let intervals: Vec<&HelixInterval> = strand
+        .domains
+        .iter()
+        .filter_map(|d| match d {
+            Domain::HelixDomain(interval) => Some(interval),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(intervals.len(), helices.len());
+    let end = parameters.start + parameters.length as isize;
+    for (i, (interval, h_id)) in intervals.iter().zip(helices.iter()).enumerate() {
+        assert_eq!(interval.helix, *h_id);
+        assert_eq!(interval.forward, i % 2 == 0);
+        assert!(interval.start >= parameters.start && interval.end <= end);
+    }
+    for pair in intervals.windows(2) {
+        let prime3 = pair[0].prime3();
+        let prime5 = pair[1].prime5();
+        let distance = (design.get_nucl_position(prime3).unwrap()
+            - design.get_nucl_position(prime5).unwrap())
+        .mag();
+        assert!(distance < 1.5, "xover of length {} nm", distance);
+    }
+}
+
+#[test]
+fn scaffold_routing_needs_empty_helices() {
+    use design_operations::{route_scaffold, ScaffoldRoute, ScaffoldRoutingParameters};
+    let (mut design, helices) = helices_in_a_row(2);
+    design.strands.push(Strand::init(helices[1], 3, true, 0));
+    let route = ScaffoldRoute::Helices(helices.clone());
+    let mut parameters = ScaffoldRoutingParameters::default();
+    assert!(matches!(
+        route_scaffold(&mut design, &route, &parameters, 0),
+        Err(design_operations::ErrOperation::HelixIsNotEmpty(h)) if h == helices[1]
+    ));
+    parameters.clear_existing = true;
+    let s_id = route_scaffold(&mut design, &route, &parameters, 0).unwrap();
+    assert_eq!(design.strands.keys().collect::<Vec<_>>(), vec![&s_id]);
+}
+
+#[test]
+fn grid_helices_routed_in_pattern() {
+    use design_operations::{
+        route_scaffold, ScaffoldRoute, ScaffoldRoutingParameters, ScaffoldRoutingPattern,
+    };
+    let grid_id = GridId::FreeGrid(0);
+    let mut design = Design::new();
+    let mut helices = design.helices.make_mut();
+    // A 3x3 square of helices, the helix at (x, y) having identifier 3 * x + y
+    for x in 0..3 {
+        for y in 0..3 {
+            let mut helix = Helix::new(
+                Parameters::INTER_CENTER_GAP * Vec3::new(0., x as f32, y as f32),
+                Rotor3::identity(),
+            );
+            helix.grid_position = Some(grid::HelixGridPosition {
+                grid: grid_id,
+                x,
+                y,
+                axis_pos: 0,
+                roll: 0.,
+            });
+            helices.push_helix(helix);
+        }
+    }
+    drop(helices);
+    let route_order = |design: &mut Design, pattern| {
+        let parameters = ScaffoldRoutingParameters {
+            clear_existing: true,
+            ..Default::default()
+        };
+        let s_id = route_scaffold(
+            design,
+            &ScaffoldRoute::Grid { grid_id, pattern },
+            &parameters,
+            0,
+        )
+        .unwrap();
+        design
+            .strands
+            .get(&s_id)
+            .unwrap()
+            .domains
+            .iter()
+            .filter_map(|d| d.helix())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        route_order(&mut design, ScaffoldRoutingPattern::Serpentine),
+        vec![0, 1, 2, 5, 4, 3, 6, 7, 8]
+    );
+    assert_eq!(
+        route_order(&mut design, ScaffoldRoutingPattern::Spiral),
+        vec![0, 1, 2, 5, 8, 7, 6, 3, 4]
+    );
+}
+
+/// A design with six helices placed around an hexagon, as in a 6 helix bundle, and a scaffold
+/// going through them.
+fn six_helix_bundle_with_scaffold() -> Design {
+    use design_operations::{route_scaffold, ScaffoldRoute, ScaffoldRoutingParameters};
+    let mut design = Design::new();
+    let mut helices = design.helices.make_mut();
+    let ids: Vec<usize> = (0..6)
+        .map(|i| {
+            let angle = i as f32 * std::f32::consts::FRAC_PI_3;
+            helices.push_helix(Helix::new(
+                Parameters::INTER_CENTER_GAP * Vec3::new(0., angle.cos(), angle.sin()),
+                Rotor3::identity(),
+            ))
+        })
+        .collect();
+    drop(helices);
+    let parameters = ScaffoldRoutingParameters {
+        length: 84,
+        ..Default::default()
+    };
+    route_scaffold(&mut design, &ScaffoldRoute::Helices(ids), &parameters, 0).unwrap();
+    design
+}
+
+fn helix_domain_nucls(strand: &Strand) -> Vec<Nucl> {
+    strand
+        .domains
+        .iter()
+        .filter_map(|d| match d {
+            Domain::HelixDomain(interval) => Some(interval),
+            _ => None,
+        })
+        .flat_map(|interval| {
+            interval.iter().map(move |position| Nucl {
+                helix: interval.helix,
+                position,
+                forward: interval.forward,
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn staples_cover_the_scaffold() {
+    use design_operations::{fill_staples, StapleFillingParameters};
+    let initial_design = six_helix_bundle_with_scaffold();
+    let scaffold_id = initial_design.scaffold_ids[0];
+    let scaffold_nucls = helix_domain_nucls(initial_design.strands.get(&scaffold_id).unwrap());
+    for domain_lengths in vec![vec![16, 16], vec![8, 16, 8]].into_iter() {
+        let mut design = initial_design.clone();
+        let parameters = StapleFillingParameters {
+            domain_lengths,
+            ..Default::default()
+        };
+        let staples = fill_staples(&mut design, &parameters, || 0).unwrap();
+        let mut covered = HashMap::new();
+        for s_id in staples.iter() {
+            let strand = design.strands.get(s_id).unwrap();
+            let nucls = helix_domain_nucls(strand);
+            assert!(
+                nucls.len() >= parameters.min_length,
+                "staple of length {}",
+                nucls.len()
+            );
+            for nucl in nucls {
+                *covered.entry(nucl).or_insert(0) += 1;
+            }
+        }
+        for nucl in scaffold_nucls.iter() {
+            assert_eq!(covered.get(&nucl.compl()), Some(&1), "{:?}", nucl);
+        }
+        assert_eq!(covered.len(), scaffold_nucls.len());
+        assert!(staples
+            .iter()
+            .any(|s_id| design.strands.get(s_id).unwrap().domains.len() > 1));
+    }
+}
+
+#[test]
+fn staple_filling_keeps_existing_staples() {
+    use design_operations::{fill_staples, StapleFillingParameters};
+    let mut design = six_helix_bundle_with_scaffold();
+    let scaffold_id = design.scaffold_ids[0];
+    let scaffold_nucls = helix_domain_nucls(design.strands.get(&scaffold_id).unwrap());
+    // A staple paired with the 30 first nucleotides of the scaffold
+    let existing_nucls: Vec<Nucl> = scaffold_nucls[..30].iter().map(|n| n.compl()).collect();
+    let existing = Strand {
+        domains: vec![Domain::HelixDomain(HelixInterval {
+            helix: existing_nucls[0].helix,
+            start: existing_nucls[0].position,
+            end: existing_nucls[29].position + 1,
+            forward: existing_nucls[0].forward,
+            sequence: None,
+        })],
+        ..Default::default()
+    };
+    design.strands.push(existing.clone());
+    let existing_id = *design.strands.keys().max().unwrap();
+
+    let parameters = StapleFillingParameters::default();
+    let staples = fill_staples(&mut design, &parameters, || 0).unwrap();
+    assert_eq!(
+        helix_domain_nucls(design.strands.get(&existing_id).unwrap()),
+        helix_domain_nucls(&existing)
+    );
+    let new_nucls: Vec<Nucl> = staples
+        .iter()
+        .flat_map(|s_id| helix_domain_nucls(design.strands.get(s_id).unwrap()))
+        .collect();
+    let new_set: HashSet<Nucl> = new_nucls.iter().cloned().collect();
+    assert_eq!(new_set.len(), new_nucls.len());
+    for nucl in scaffold_nucls[..30].iter() {
+        assert!(!new_set.contains(&nucl.compl()));
+    }
+    for nucl in scaffold_nucls[30..].iter() {
+        assert!(new_set.contains(&nucl.compl()), "{:?}", nucl);
+    }
+
+    let mut no_scaffold = Design::new();
+    assert!(matches!(
+        fill_staples(&mut no_scaffold, &parameters, || 0),
+        Err(design_operations::ErrOperation::NoScaffold)
+    ));
+}
+
+#[test]
+fn helix_parameters_override_design_parameters() {
+    use design_operations::{set_helices_parameters, ErrOperation};
+    let (mut design, helices) = helices_in_a_row(2);
+    set_helices_parameters(&mut design, &helices[1..], Some(Parameters::GEARY_2014_RNA)).unwrap();
+
+    let rna_helix = Helix::new(
+        design.helices.get(&helices[1]).unwrap().position,
+        Rotor3::identity(),
+    );
+    for position in [0, 5, 11].iter().cloned() {
+        assert_eq!(
+            design.get_nucl_position(Nucl::new(helices[1], position, true)),
+            Some(rna_helix.space_pos(&Parameters::GEARY_2014_RNA, position, true))
+        );
+    }
+    // The other helix still uses the parameters of the design
+    let dna_helix = Helix::new(
+        design.helices.get(&helices[0]).unwrap().position,
+        Rotor3::identity(),
+    );
+    assert_eq!(
+        design.get_nucl_position(Nucl::new(helices[0], 5, true)),
+        Some(dna_helix.space_pos(&Parameters::DEFAULT, 5, true))
+    );
+
+    set_helices_parameters(&mut design, &helices[1..], None).unwrap();
+    assert!(design
+        .helices
+        .get(&helices[1])
+        .unwrap()
+        .parameters_override
+        .is_none());
+    assert!(matches!(
+        set_helices_parameters(&mut design, &[42], None),
+        Err(ErrOperation::HelixDoesNotExists(42))
+    ));
+}
+
+/// The six helix bundle with its scaffold, and the identifiers of staples of color 0 covering the
+/// scaffold
+fn six_helix_bundle_with_staples() -> (Design, Vec<usize>) {
+    use design_operations::{fill_staples, StapleFillingParameters};
+    let mut design = six_helix_bundle_with_scaffold();
+    let staples = fill_staples(&mut design, &StapleFillingParameters::default(), || 0).unwrap();
+    (design, staples)
+}
+
+#[test]
+fn recoloring_is_deterministic_and_preserves_excluded_strands() {
+    use design_operations::{recolor_strands, RecolorParameters};
+    let (mut design, staples) = six_helix_bundle_with_staples();
+    design.strands.get_mut(&staples[0]).unwrap().keep_color = true;
+    let scaffold_id = design.scaffold_ids[0];
+    let scaffold_color = design.strands.get(&scaffold_id).unwrap().color;
+    let mut other_design = design.clone();
+
+    let parameters = RecolorParameters {
+        seed: 42,
+        ..Default::default()
+    };
+    let recolored = recolor_strands(&mut design, &parameters).unwrap();
+    recolor_strands(&mut other_design, &parameters).unwrap();
+    assert_eq!(recolored, staples[1..].to_vec());
+    for s_id in staples[1..].iter() {
+        let color = design.strands.get(s_id).unwrap().color;
+        assert_ne!(color, 0);
+        assert_eq!(color, other_design.strands.get(s_id).unwrap().color);
+    }
+    assert_eq!(design.strands.get(&staples[0]).unwrap().color, 0);
+    assert_eq!(
+        design.strands.get(&scaffold_id).unwrap().color,
+        scaffold_color
+    );
+}
+
+#[test]
+fn fixed_palette_is_cycled_on_the_strands_of_a_group() {
+    use design_operations::{
+        recolor_strands, ErrOperation, RecolorPalette, RecolorParameters, RecolorScope,
+    };
+    let (mut design, staples) = six_helix_bundle_with_staples();
+    let group_id: GroupId = rand::random();
+    let group = OrganizerTree::Node {
+        name: "group".to_owned(),
+        childrens: staples[..3]
+            .iter()
+            .map(|s_id| OrganizerTree::Leaf(DnaElementKey::Strand(*s_id)))
+            .collect(),
+        expanded: false,
+        id: Some(group_id),
+    };
+    design.organizer_tree = Some(Arc::new(OrganizerTree::Node {
+        name: "root".to_owned(),
+        childrens: vec![group],
+        expanded: true,
+        id: None,
+    }));
+
+    let mut parameters = RecolorParameters {
+        scope: RecolorScope::Group(group_id),
+        palette: RecolorPalette::Fixed(vec![0xFF_00_00, 0x00_FF_00]),
+        seed: 0,
+    };
+    let recolored = recolor_strands(&mut design, &parameters).unwrap();
+    assert_eq!(recolored, staples[..3].to_vec());
+    let mut colors: Vec<u32> = recolored
+        .iter()
+        .map(|s_id| design.strands.get(s_id).unwrap().color)
+        .collect();
+    colors.sort();
+    assert_eq!(colors, vec![0xFF_00_FF_00, 0xFF_FF_00_00, 0xFF_FF_00_00]);
+    for s_id in staples[3..].iter() {
+        assert_eq!(design.strands.get(s_id).unwrap().color, 0);
+    }
+
+    parameters.palette = RecolorPalette::Fixed(Vec::new());
+    assert!(matches!(
+        recolor_strands(&mut design, &parameters),
+        Err(ErrOperation::EmptyPalette)
+    ));
+    parameters.scope = RecolorScope::Group(rand::random());
+    assert!(matches!(
+        recolor_strands(&mut design, &parameters),
+        Err(ErrOperation::GroupDoesNotExist(_))
+    ));
+}
+
+/// Add `helix` to `design` with a duplex on its nucleotides 0 to 9.
+fn push_duplex(design: &mut Design, helix: Helix) -> usize {
+    let h_id = design.helices.make_mut().push_helix(helix);
+    for forward in [true, false].iter() {
+        let mut strand = Strand::init(h_id, 0, *forward, 0);
+        strand.domains = vec![Domain::HelixDomain(HelixInterval {
+            helix: h_id,
+            start: 0,
+            end: 10,
+            forward: *forward,
+            sequence: None,
+        })];
+        design.strands.push(strand);
+    }
+    h_id
+}
+
+/// Check that the duplex on helix `h_id` of `reflected` is still paired, and that its
+/// nucleotides at position 0 are the images by `mirror` of those of `original`.
+///
+/// The reflected helix is right-handed, so its nucleotides only coincide with the mirror image at
+/// position 0, up to the inclination since the forward and backward strands are exchanged.
+fn assert_reflected_duplex(
+    original: &Design,
+    reflected: &Design,
+    h_id: usize,
+    mirror: impl Fn(Vec3) -> Vec3,
+) {
+    let parameters = original.parameters.unwrap_or_default();
+    let nucl = |position, forward| Nucl {
+        helix: h_id,
+        position,
+        forward,
+    };
+    let pair_distance = |design: &Design, position| {
+        (design.get_nucl_position(nucl(position, true)).unwrap()
+            - design.get_nucl_position(nucl(position, false)).unwrap())
+        .mag()
+    };
+    let expected_distance = pair_distance(original, 0);
+    for position in 0..10 {
+        let prime5 = reflected.strands.get_strand_nucl(&nucl(position, true));
+        let prime3 = reflected.strands.get_strand_nucl(&nucl(position, false));
+        assert!(prime5.is_some() && prime3.is_some() && prime5 != prime3);
+        assert!((pair_distance(reflected, position) - expected_distance).abs() < 1e-4);
+    }
+    for forward in [true, false].iter() {
+        let image = mirror(original.get_nucl_position(nucl(0, *forward)).unwrap());
+        let reflected_position = reflected.get_nucl_position(nucl(0, !*forward)).unwrap();
+        assert!(
+            (reflected_position - image).mag() < parameters.inclination.abs() + 1e-4,
+            "{:?} is not the mirror image {:?}",
+            reflected_position,
+            image
+        );
+    }
+}
+
+/// A reflection across the plane going through `origin` with normal vector `normal`
+fn mirror(origin: Vec3, normal: Vec3) -> impl Fn(Vec3) -> Vec3 {
+    let normal = normal.normalized();
+    move |p| p - 2. * (p - origin).dot(normal) * normal
+}
+
+#[test]
+fn reflecting_a_free_helix_keeps_its_duplex_paired() {
+    use design_operations::reflect_helices;
+    let mut design = Design::new();
+    let mut helix = Helix::new(Vec3::new(1., -2., 0.5), Rotor3::from_rotation_xy(0.3));
+    helix.roll = 0.7;
+    let h_id = push_duplex(&mut design, helix);
+    let original = design.clone();
+
+    let (origin, normal) = (Vec3::new(2., 0., 1.), Vec3::new(1., 2., 0.5));
+    reflect_helices(&mut design, vec![h_id], vec![], origin, normal).unwrap();
+    assert_reflected_duplex(&original, &design, h_id, mirror(origin, normal));
+}
+
+#[test]
+fn reflecting_a_grid_reflects_the_duplex_of_its_helices() {
+    use design_operations::reflect_helices;
+    use grid::{GridDescriptor, GridTypeDescr};
+    let mut design = Design::new();
+    let descriptor = GridDescriptor {
+        position: Vec3::new(1., 2., 3.),
+        orientation: Rotor3::from_rotation_xz(0.3),
+        grid_type: GridTypeDescr::Square { twist: None },
+        invisible: false,
+        bezier_vertex: None,
+        bounds: None,
+        phantom_length: None,
+    };
+    let grid = descriptor.to_grid(Parameters::DEFAULT);
+    let g_id = design.free_grids.make_mut().push(descriptor);
+    let mut helix = Helix::new_on_grid(&grid, 1, 2, g_id);
+    // The roll of the helix and the roll of its grid position must not compensate each other
+    helix.roll = 0.7;
+    if let Some(grid_position) = helix.grid_position.as_mut() {
+        grid_position.roll = 0.4;
+    }
+    let h_id = push_duplex(&mut design, helix);
+    design.get_updated_grid_data();
+    let original = design.clone();
+
+    let (origin, normal) = (Vec3::new(2., 0., 1.), Vec3::new(1., 2., 0.5));
+    reflect_helices(&mut design, vec![], vec![g_id], origin, normal).unwrap();
+    design.get_updated_grid_data();
+    let grid_position = design.helices.get(&h_id).unwrap().grid_position.unwrap();
+    assert_eq!((grid_position.x, grid_position.y), (-1, 2));
+    assert_reflected_duplex(&original, &design, h_id, mirror(origin, normal));
+}
+
+#[test]
+fn reflecting_a_helix_moves_its_deletions_with_their_strand() {
+    use design_operations::reflect_helices;
+    let mut design = Design::new();
+    let h_id = push_duplex(&mut design, Helix::new(Vec3::zero(), Rotor3::identity()));
+    let deleted = Nucl {
+        helix: h_id,
+        position: 3,
+        forward: true,
+    };
+    design.deletions.insert(deleted);
+    let s_id = design.strands.get_strand_nucl(&deleted).unwrap();
+
+    let (origin, normal) = (Vec3::new(2., 0., 1.), Vec3::new(1., 2., 0.5));
+    reflect_helices(&mut design, vec![h_id], vec![], origin, normal).unwrap();
+    let reflected = Nucl {
+        forward: false,
+        ..deleted
+    };
+    assert_eq!(design.deletions.len(), 1);
+    assert!(design.deletions.contains(&reflected));
+    assert_eq!(design.strands.get_strand_nucl(&reflected), Some(s_id));
+}
+
+#[test]
+fn reflecting_a_helix_moves_the_nucleotides_of_named_selections() {
+    use design_operations::reflect_helices;
+    use elements::DnaElementKey;
+    let mut design = Design::new();
+    let h_id = push_duplex(&mut design, Helix::new(Vec3::zero(), Rotor3::identity()));
+    let other_id = push_duplex(&mut design, Helix::new(Vec3::unit_y(), Rotor3::identity()));
+    let nucl_key = |helix, forward| DnaElementKey::Nucleotide {
+        helix,
+        position: 3,
+        forward,
+    };
+    design.add_named_selection(
+        "nucls".to_string(),
+        vec![
+            nucl_key(h_id, true),
+            nucl_key(other_id, true),
+            DnaElementKey::Helix(h_id),
+        ],
+    );
+
+    let (origin, normal) = (Vec3::new(2., 0., 1.), Vec3::new(1., 2., 0.5));
+    reflect_helices(&mut design, vec![h_id], vec![], origin, normal).unwrap();
+    assert_eq!(
+        design.named_selections[0].elements,
+        vec![
+            nucl_key(h_id, false),
+            nucl_key(other_id, true),
+            DnaElementKey::Helix(h_id),
+        ]
+    );
+}
@@ -0,0 +1,295 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Export of the strands sequences to a csv file, with one row per strand.
+
+use super::*;
+use ensnano_design::{
+    modifications::{idt_sequence, Modification},
+    staple_report::{gc_content, melting_temperature},
+    Domain, Strand,
+};
+use std::io::Write;
+
+const CSV_HEADER: &[&str] = &[
+    "strand id",
+    "role",
+    "name",
+    "length",
+    "color",
+    "5' helix",
+    "5' position",
+    "3' helix",
+    "3' position",
+    "sequence",
+    "sequence with modifications",
+    "gc content (%)",
+    "melting temperature (°C)",
+    "note",
+];
+
+/// A row of the exported csv file
+struct CsvRow {
+    strand_id: usize,
+    is_scaffold: bool,
+    name: String,
+    length: usize,
+    color: u32,
+    prime5: Option<Nucl>,
+    prime3: Option<Nucl>,
+    sequence: String,
+    /// The sequence in which the IDT codes of the modifications have been inserted
+    ordering_sequence: String,
+    note: String,
+}
+
+impl CsvRow {
+    fn new(
+        strand_id: usize,
+        strand: &Strand,
+        basis_mapper: &BasisMapper,
+        modifications: &HashMap<Nucl, Modification>,
+    ) -> Self {
+        let bases = strand_bases(strand_id, strand, basis_mapper);
+        Self {
+            strand_id,
+            is_scaffold: false,
+            name: strand
+                .name
+                .as_ref()
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            length: strand.length(),
+            color: strand.color & 0xFFFFFF,
+            prime5: strand.get_5prime(),
+            prime3: strand.get_3prime(),
+            sequence: bases.iter().map(|(_, base)| *base).collect(),
+            ordering_sequence: idt_sequence(&bases, modifications),
+            note: strand.note.clone().unwrap_or_default(),
+        }
+    }
+
+    fn fields(&self) -> Vec<String> {
+        let helix = |n: Option<Nucl>| n.map(|n| n.helix.to_string()).unwrap_or_default();
+        let position = |n: Option<Nucl>| n.map(|n| n.position.to_string()).unwrap_or_default();
+        let one_decimal = |x: Option<f32>| x.map(|x| format!("{:.1}", x)).unwrap_or_default();
+        vec![
+            self.strand_id.to_string(),
+            if self.is_scaffold {
+                "scaffold".to_owned()
+            } else {
+                "staple".to_owned()
+            },
+            self.name.clone(),
+            self.length.to_string(),
+            format!("#{:06X}", self.color),
+            helix(self.prime5),
+            position(self.prime5),
+            helix(self.prime3),
+            position(self.prime3),
+            self.sequence.clone(),
+            self.ordering_sequence.clone(),
+            one_decimal(gc_content(&self.sequence).map(|gc| 100. * gc)),
+            one_decimal(melting_temperature(&self.sequence)),
+            self.note.clone(),
+        ]
+    }
+}
+
+/// The bases of a strand, read in the 5' to 3' direction, together with the nucleotide that
+/// they belong to. The bases of the insertions do not belong to a nucleotide of a helix.
+///
+/// Nucleotides that have no assigned basis are given the same basis as in the oxDNA export.
+pub(super) fn strand_bases(
+    strand_id: usize,
+    strand: &Strand,
+    basis_mapper: &BasisMapper,
+) -> Vec<(Option<Nucl>, char)> {
+    let mut ret = Vec::with_capacity(strand.length());
+    for (domain_id, d) in strand.domains.iter().enumerate() {
+        match d {
+            Domain::HelixDomain(dom) => {
+                for position in dom.iter() {
+                    let nucl = Nucl {
+                        position,
+                        helix: dom.helix,
+                        forward: dom.forward,
+                    };
+                    ret.push((Some(nucl), basis_mapper.get_basis(&nucl, 'T')));
+                }
+            }
+            Domain::Insertion {
+                nb_nucl, sequence, ..
+            } => {
+                for position in 0..*nb_nucl {
+                    let basis = basis_mapper.get_insertion_basis(
+                        strand_id,
+                        domain_id,
+                        position,
+                        sequence.as_deref(),
+                        'T',
+                    );
+                    ret.push((None, basis));
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// Quote a field if it contains characters that have a special meaning in csv files.
+fn escape_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn to_csv(design: &Design, basis_mapper: &BasisMapper) -> String {
+    let mut lines = vec![CSV_HEADER.join(",")];
+    for (s_id, strand) in design.strands.iter() {
+        let row = CsvRow {
+            is_scaffold: design.is_scaffold(*s_id),
+            ..CsvRow::new(*s_id, strand, basis_mapper, &design.modifications)
+        };
+        let fields: Vec<String> = row.fields().iter().map(|f| escape_field(f)).collect();
+        lines.push(fields.join(","));
+    }
+    lines.join("\n")
+}
+
+pub(super) fn csv_export(
+    design: &Design,
+    basis_mapper: BasisMapper,
+    out_path: &PathBuf,
+) -> Result<(), std::io::Error> {
+    let content = to_csv(design, &basis_mapper);
+    let mut out_file = std::fs::File::create(out_path)?;
+    writeln!(&mut out_file, "{content}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forward_strand(start: isize, end: isize) -> Strand {
+        let mut strand = Strand::init(0, start, true, 0xFF0000);
+        if let Some(Domain::HelixDomain(dom)) = strand.domains.get_mut(0) {
+            dom.end = end;
+        }
+        strand
+    }
+
+    fn strand_sequence(strand_id: usize, strand: &Strand, basis_mapper: &BasisMapper) -> String {
+        strand_bases(strand_id, strand, basis_mapper)
+            .into_iter()
+            .map(|(_, base)| base)
+            .collect()
+    }
+
+    #[test]
+    fn csv_sequence_contains_insertions() {
+        let mut strand = forward_strand(0, 4);
+        strand.domains.push(Domain::Insertion {
+            nb_nucl: 3,
+            instanciation: None,
+            sequence: Some("GGG".into()),
+            attached_to_prime3: false,
+        });
+        strand.name = Some("my, staple".into());
+        let mut map: HashMap<Nucl, char, ahash::RandomState> = Default::default();
+        for position in 0..4 {
+            map.insert(Nucl::new(0, position, true), 'A');
+        }
+        let mapper = BasisMapper::new(Some(&map));
+        let row = CsvRow::new(0, &strand, &mapper, &HashMap::new());
+        assert_eq!(row.sequence, "AAAAGGG");
+        assert_eq!(row.length, 7);
+        assert_eq!(escape_field(&row.name), "\"my, staple\"");
+    }
+
+    #[test]
+    fn csv_rows_contain_the_note() {
+        let mut strand = forward_strand(0, 4);
+        strand.note = Some("biotin-modified, HPLC".into());
+        let row = CsvRow::new(0, &strand, &BasisMapper::new(None), &HashMap::new());
+        let fields = row.fields();
+        assert_eq!(fields.len(), CSV_HEADER.len());
+        assert_eq!(
+            escape_field(fields.last().unwrap()),
+            "\"biotin-modified, HPLC\""
+        );
+    }
+
+    #[test]
+    fn csv_ordering_sequence_contains_idt_codes() {
+        use ensnano_design::modifications::ModificationKind;
+        let strand = forward_strand(0, 4);
+        let mut map: HashMap<Nucl, char, ahash::RandomState> = Default::default();
+        for (position, base) in "ACGT".chars().enumerate() {
+            map.insert(Nucl::new(0, position as isize, true), base);
+        }
+        let mut modifications = HashMap::new();
+        modifications.insert(
+            Nucl::new(0, 0, true),
+            Modification::new(ModificationKind::Biotin),
+        );
+        modifications.insert(
+            Nucl::new(0, 2, true),
+            Modification::new(ModificationKind::Cy3),
+        );
+        let row = CsvRow::new(0, &strand, &BasisMapper::new(Some(&map)), &modifications);
+        assert_eq!(row.sequence, "ACGT");
+        assert_eq!(row.ordering_sequence, "/5Biosg/ACG/iCy3/T");
+    }
+
+    #[test]
+    fn csv_filling_is_consistent_and_paired() {
+        let mapper = BasisMapper::new(None);
+        let forward = forward_strand(0, 20);
+        let mut backward = Strand::init(0, 0, false, 0);
+        if let Some(Domain::HelixDomain(dom)) = backward.domains.get_mut(0) {
+            dom.end = 20;
+        }
+        let forward_seq = strand_sequence(0, &forward, &mapper);
+        assert_eq!(
+            forward_seq,
+            strand_sequence(0, &forward, &BasisMapper::new(None))
+        );
+        let backward_seq = strand_sequence(1, &backward, &mapper);
+        for (f, b) in forward_seq.chars().zip(backward_seq.chars().rev()) {
+            assert_eq!(compl(f, 'T'), b);
+        }
+    }
+
+    #[test]
+    fn csv_rows_contain_the_role_of_the_strands() {
+        let mut design = Design::new();
+        for s_id in 0..3 {
+            design.strands.insert(s_id, forward_strand(0, 4));
+        }
+        design.scaffold_ids = vec![2, 0];
+        let csv = to_csv(&design, &BasisMapper::new(None));
+        let roles: Vec<&str> = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(1).unwrap())
+            .collect();
+        assert_eq!(roles, vec!["scaffold", "staple", "scaffold"]);
+    }
+}
@@ -0,0 +1,149 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Formatting of the sequences of a set of strands as plain text, e.g. to copy them to the
+//! clipboard.
+
+use super::*;
+use ensnano_design::{Domain, Strand};
+
+/// The sequences of a set of strands, formatted as text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequencesText {
+    pub text: String,
+    /// The number of sequences in `text`
+    pub nb_sequences: usize,
+}
+
+/// The sequences of `strands`, read in the 5' to 3' direction.
+///
+/// A single sequence is returned as is. Several sequences are formatted as FASTA records whose
+/// header is the name of the strand. The bases are the same as in the csv export.
+///
+/// Strands that are not in the design and strands none of whose bases is known are skipped.
+/// Return `None` if all the strands are skipped.
+pub fn sequences_text(
+    design: &Design,
+    strands: &[usize],
+    basis_map: Option<&dyn BasisMap>,
+) -> Option<SequencesText> {
+    let basis_mapper = BasisMapper::new(basis_map);
+    let records: Vec<(String, String)> = strands
+        .iter()
+        .filter_map(|s_id| {
+            let strand = design.strands.get(s_id)?;
+            if !has_known_basis(strand, basis_map) {
+                return None;
+            }
+            let sequence = csv::strand_bases(*s_id, strand, &basis_mapper)
+                .into_iter()
+                .map(|(_, base)| base)
+                .collect();
+            Some((header(*s_id, strand), sequence))
+        })
+        .collect();
+    let text = match records.as_slice() {
+        [] => return None,
+        [(_, sequence)] => sequence.clone(),
+        _ => records
+            .iter()
+            .map(|(header, sequence)| format!(">{header}\n{sequence}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    Some(SequencesText {
+        text,
+        nb_sequences: records.len(),
+    })
+}
+
+/// The name of the strand, or a description of the position of its ends if it has no name.
+fn header(s_id: usize, strand: &Strand) -> String {
+    if let Some(name) = strand.name.as_ref() {
+        name.to_string()
+    } else if let Some((prime5, prime3)) = strand.get_5prime().zip(strand.get_3prime()) {
+        format!(
+            "Strand {:04}; 5':h{}:nt{}>3':h{}:nt{}",
+            s_id, prime5.helix, prime5.position, prime3.helix, prime3.position
+        )
+    } else {
+        format!("Strand {:04}", s_id)
+    }
+}
+
+/// True if the basis of at least one nucleotide of `strand` is given by the map, or if one of
+/// its insertions has a sequence.
+fn has_known_basis(strand: &Strand, basis_map: Option<&dyn BasisMap>) -> bool {
+    strand.domains.iter().any(|d| match d {
+        Domain::HelixDomain(dom) => basis_map
+            .map(|map| {
+                dom.iter().any(|position| {
+                    let nucl = Nucl {
+                        position,
+                        helix: dom.helix,
+                        forward: dom.forward,
+                    };
+                    map.get(&nucl).is_some() || map.get(&nucl.compl()).is_some()
+                })
+            })
+            .unwrap_or(false),
+        Domain::Insertion { sequence, .. } => sequence.is_some(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forward_strand(start: isize, end: isize) -> Strand {
+        let mut strand = Strand::init(0, start, true, 0xFF0000);
+        if let Some(Domain::HelixDomain(dom)) = strand.domains.get_mut(0) {
+            dom.end = end;
+        }
+        strand
+    }
+
+    #[test]
+    fn several_sequences_are_formatted_as_fasta() {
+        let mut design = Design::new();
+        let mut named = forward_strand(0, 4);
+        named.name = Some("my staple".into());
+        design.strands.insert(0, named);
+        design.strands.insert(1, forward_strand(4, 6));
+        design.strands.insert(2, forward_strand(10, 12));
+        let mut map: HashMap<Nucl, char, ahash::RandomState> = Default::default();
+        for (position, base) in "ACGTTG".chars().enumerate() {
+            map.insert(Nucl::new(0, position as isize, true), base);
+        }
+
+        assert_eq!(
+            sequences_text(&design, &[0], Some(&map))
+                .map(|s| s.text)
+                .as_deref(),
+            Some("ACGT")
+        );
+        assert_eq!(
+            sequences_text(&design, &[0, 1, 2], Some(&map)),
+            Some(SequencesText {
+                text: ">my staple\nACGT\n>Strand 0001; 5':h0:nt4>3':h0:nt5\nTG".into(),
+                nb_sequences: 2,
+            })
+        );
+        assert_eq!(sequences_text(&design, &[2], Some(&map)), None);
+        assert_eq!(sequences_text(&design, &[0], None), None);
+    }
+}
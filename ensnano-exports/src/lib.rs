@@ -21,13 +21,16 @@ use strum::Display;
 
 pub mod cadnano;
 pub mod cando;
+pub mod csv;
+pub mod fasta;
+pub mod obj;
 pub mod oxdna;
 pub mod pdb;
 use cadnano::CadnanoError;
 use cando::CanDoError;
 use ensnano_design::{ultraviolet, Design, Nucl};
 use pdb::PdbError;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 /// The file formats to which an export is implemented
@@ -37,6 +40,8 @@ pub enum ExportType {
     Cando,
     Pdb,
     Oxdna,
+    Csv,
+    Obj(obj::ObjParameters),
 }
 
 /// A value returned by the export functions when exports was successfull.
@@ -50,6 +55,11 @@ pub enum ExportSuccess {
         topology: PathBuf,
         configuration: PathBuf,
     },
+    Csv(PathBuf),
+    Obj {
+        mesh: PathBuf,
+        materials: PathBuf,
+    },
 }
 
 const SUCCESSFUL_EXPORT_MSG_PREFIX: &str = "Succussfully exported to";
@@ -62,6 +72,7 @@ impl ExportSuccess {
             Self::Cadnano(p) => format!("{SUCCESSFUL_EXPORT_MSG_PREFIX}\n{}", p.to_string_lossy()),
             Self::Cando(p) => format!("{SUCCESSFUL_EXPORT_MSG_PREFIX}\n{}", p.to_string_lossy()),
             Self::Pdb(p) => format!("{SUCCESSFUL_EXPORT_MSG_PREFIX}\n{}", p.to_string_lossy()),
+            Self::Csv(p) => format!("{SUCCESSFUL_EXPORT_MSG_PREFIX}\n{}", p.to_string_lossy()),
             Self::Oxdna {
                 topology,
                 configuration,
@@ -70,6 +81,11 @@ impl ExportSuccess {
                 configuration.to_string_lossy(),
                 topology.to_string_lossy()
             ),
+            Self::Obj { mesh, materials } => format!(
+                "{SUCCESSFUL_EXPORT_MSG_PREFIX}\n{}\n{}",
+                mesh.to_string_lossy(),
+                materials.to_string_lossy()
+            ),
         }
     }
 }
@@ -81,6 +97,8 @@ pub enum ExportError {
     PdbConversion(PdbError),
     IOError(std::io::Error),
     NotImplemented,
+    /// None of the strands that were asked to be exported are in the design
+    EmptySelection,
 }
 
 impl From<CadnanoError> for ExportError {
@@ -117,31 +135,89 @@ impl BasisMap for HashMap<Nucl, char, ahash::RandomState> {
 
 struct BasisMapper<'a> {
     map: Option<&'a dyn BasisMap>,
-    alternative: HashMap<Nucl, char>,
 }
 
 impl<'a> BasisMapper<'a> {
-    fn get_basis(&mut self, nucl: &Nucl, compl_a: char) -> char {
+    /// Return the basis of `nucl`.
+    ///
+    /// The basis given to `nucl` by the map takes precedence over the complement of the basis of
+    /// its partner. The map given by the design reader contains the basis of the explicit
+    /// sequences of the strands and, for the nucleotides without an explicit basis, the basis
+    /// derived from the scaffold sequence, so explicit sequences are always preferred over
+    /// derived ones. The nucleotides whose basis cannot be determined get a filling basis.
+    fn get_basis(&self, nucl: &Nucl, compl_a: char) -> char {
         if let Some(c) = self.map.and_then(|m| m.get(nucl)) {
             *c
         } else if let Some(c) = self.map.and_then(|m| m.get(&nucl.compl())) {
             compl(*c, compl_a)
-        } else if let Some(c) = self.alternative.get(nucl) {
-            *c
         } else {
-            let base = rand_base();
-            self.alternative.insert(nucl.clone(), base);
-            self.alternative.insert(nucl.compl(), compl(base, compl_a));
-            base
+            filling_base(nucl, compl_a)
         }
     }
 
-    fn new(map: Option<&'a dyn BasisMap>) -> Self {
-        Self {
-            map,
-            alternative: HashMap::new(),
+    /// Return the basis of the `position`-th nucleotide of the `domain_id`-th domain of strand
+    /// `strand_id`, assuming that this domain is an insertion.
+    fn get_insertion_basis(
+        &self,
+        strand_id: usize,
+        domain_id: usize,
+        position: usize,
+        sequence: Option<&str>,
+        compl_a: char,
+    ) -> char {
+        if let Some(c) = sequence.and_then(|s| s.chars().nth(position)) {
+            c
+        } else {
+            let hash = stable_hash(&[strand_id as u64, domain_id as u64, position as u64]);
+            pseudo_random_base(hash, compl_a)
         }
     }
+
+    fn new(map: Option<&'a dyn BasisMap>) -> Self {
+        Self { map }
+    }
+}
+
+/// The basis given to a nucleotide that has no assigned basis.
+///
+/// The choice is pseudo-random but only depends on the nucleotide, so that all exports of the
+/// same design agree with each other and that complementary nucleotides are paired.
+fn filling_base(nucl: &Nucl, compl_a: char) -> char {
+    let hash = stable_hash(&[nucl.helix as u64, nucl.position as i64 as u64]);
+    let base = pseudo_random_base(hash, compl_a);
+    if nucl.forward {
+        base
+    } else {
+        compl(base, compl_a)
+    }
+}
+
+/// The seed of `stable_hash`
+const FILLING_SEED: u64 = 0x454E_534E_414E_4F00;
+
+/// A hash of `values` that does not depend on the platform nor on the version of the standard
+/// library, so that the filling bases of a design are the same in every version of ENSnano.
+fn stable_hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(FILLING_SEED, |state, value| split_mix64(state ^ value))
+}
+
+/// The output function of the SplitMix64 generator
+fn split_mix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn pseudo_random_base(hash: u64, compl_a: char) -> char {
+    match hash % 4 {
+        0 => 'A',
+        1 => compl_a,
+        2 => 'G',
+        _ => 'C',
+    }
 }
 
 fn compl(c: char, compl_a: char) -> char {
@@ -154,15 +230,6 @@ fn compl(c: char, compl_a: char) -> char {
     }
 }
 
-fn rand_base() -> char {
-    match rand::random::<u8>() % 4 {
-        0 => 'A',
-        1 => 'T',
-        2 => 'G',
-        _ => 'C',
-    }
-}
-
 fn rand_pick(list: &[char]) -> char {
     let idx = rand::random::<usize>() % list.len();
     list[idx]
@@ -192,12 +259,24 @@ fn rand_base_from_symbol(symbol: char, compl_a: char) -> char {
     }
 }
 
+/// Export `design` to `export_path`.
+///
+/// If `strands` is `Some`, only the strands whose identifier is in the set, and the helices that
+/// they use, are exported.
 pub fn export(
     design: &Design,
     export_type: ExportType,
     basis_map: Option<&dyn BasisMap>,
     export_path: &PathBuf,
+    strands: Option<&HashSet<usize>>,
 ) -> Result<ExportSuccess, ExportError> {
+    let restricted_design;
+    let design = if let Some(strands) = strands {
+        restricted_design = restrict_to_strands(design, strands)?;
+        &restricted_design
+    } else {
+        design
+    };
     let basis_mapper = BasisMapper::new(basis_map);
     match export_type {
         ExportType::Oxdna => {
@@ -223,8 +302,142 @@ pub fn export(
             writeln!(&mut out_file, "{cadnano_content}")?;
             Ok(ExportSuccess::Cadnano(export_path.clone()))
         }
+        ExportType::Csv => {
+            csv::csv_export(design, basis_mapper, export_path)?;
+            Ok(ExportSuccess::Csv(export_path.clone()))
+        }
+        ExportType::Obj(parameters) => {
+            let mut materials = export_path.clone();
+            materials.set_extension("mtl");
+            obj::obj_export(design, parameters, export_path, &materials)?;
+            Ok(ExportSuccess::Obj {
+                mesh: export_path.clone(),
+                materials,
+            })
+        }
         _ => Err(ExportError::NotImplemented),
     }
 }
 
+/// Return a copy of `design` that only contains the strands whose identifier is in `strands` and
+/// the helices that they use.
+fn restrict_to_strands(design: &Design, strands: &HashSet<usize>) -> Result<Design, ExportError> {
+    use ensnano_design::HelixCollection;
+    let mut ret = design.clone();
+    let removed_strands: Vec<usize> = ret
+        .strands
+        .keys()
+        .filter(|s_id| !strands.contains(s_id))
+        .cloned()
+        .collect();
+    for s_id in removed_strands {
+        ret.strands.remove(&s_id);
+    }
+    if ret.strands.len() == 0 {
+        return Err(ExportError::EmptySelection);
+    }
+    let unused_helices: Vec<usize> = ret
+        .helices
+        .keys()
+        .filter(|h_id| !ret.strands.uses_helix(**h_id))
+        .cloned()
+        .collect();
+    let mut helices = ret.helices.make_mut();
+    for h_id in unused_helices {
+        helices.remove(&h_id);
+    }
+    drop(helices);
+    ret.scaffold_ids.retain(|s_id| strands.contains(s_id));
+    ret.scaffold_sequences
+        .retain(|s_id, _| strands.contains(s_id));
+    Ok(ret)
+}
+
 pub type ExportResult = Result<ExportSuccess, ExportError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ensnano_design::{Helix, HelixCollection, Strand};
+    use ultraviolet::{Rotor3, Vec3};
+
+    fn two_helices_design() -> Design {
+        let mut design = Design::new();
+        let mut helices = design.helices.make_mut();
+        for h_id in 0..2 {
+            helices.insert(h_id, Helix::new(Vec3::zero(), Rotor3::identity()));
+        }
+        drop(helices);
+        design.strands.insert(0, Strand::init(0, 0, true, 0));
+        design.strands.insert(1, Strand::init(1, 0, true, 0));
+        design.scaffold_ids = vec![1];
+        design
+    }
+
+    #[test]
+    fn restricting_to_strands_removes_unused_helices() {
+        let design = two_helices_design();
+        let strands: HashSet<usize> = [0].into_iter().collect();
+        let restricted = restrict_to_strands(&design, &strands).unwrap();
+        assert_eq!(restricted.strands.keys().collect::<Vec<_>>(), vec![&0]);
+        assert_eq!(restricted.helices.keys().collect::<Vec<_>>(), vec![&0]);
+        assert!(restricted.scaffold_ids.is_empty());
+    }
+
+    #[test]
+    fn explicit_basis_takes_precedence_over_partner() {
+        let nucl = Nucl {
+            helix: 0,
+            position: 3,
+            forward: true,
+        };
+        let lonely_nucl = Nucl {
+            position: 4,
+            ..nucl
+        };
+        let mut map: HashMap<Nucl, char, ahash::RandomState> = Default::default();
+        // The two nucleotides of the pair have non complementary explicit basis
+        map.insert(nucl, 'G');
+        map.insert(nucl.compl(), 'A');
+        map.insert(lonely_nucl.compl(), 'A');
+        let mapper = BasisMapper::new(Some(&map));
+        assert_eq!(mapper.get_basis(&nucl, 'T'), 'G');
+        assert_eq!(mapper.get_basis(&nucl.compl(), 'T'), 'A');
+        assert_eq!(mapper.get_basis(&lonely_nucl, 'T'), 'T');
+        assert_eq!(mapper.get_basis(&lonely_nucl, 'U'), 'U');
+    }
+
+    #[test]
+    fn filling_bases_are_stable() {
+        let nucl = |helix, position| Nucl {
+            helix,
+            position,
+            forward: true,
+        };
+        let bases: String = [(0, 0), (0, 1), (0, 2), (0, 3), (3, -5), (7, 42)]
+            .iter()
+            .map(|(helix, position)| filling_base(&nucl(*helix, *position), 'T'))
+            .collect();
+        assert_eq!(bases, "ACCTGT");
+        // Complementary nucleotides are paired
+        assert_eq!(filling_base(&nucl(3, -5).compl(), 'T'), 'C');
+        assert_eq!(filling_base(&nucl(7, 42).compl(), 'U'), 'A');
+
+        let mapper = BasisMapper::new(None);
+        let insertion_bases: String = [(0, 1, 0), (0, 1, 1), (2, 3, 4), (5, 1, 2)]
+            .iter()
+            .map(|(s_id, d_id, n)| mapper.get_insertion_basis(*s_id, *d_id, *n, None, 'T'))
+            .collect();
+        assert_eq!(insertion_bases, "AGTC");
+    }
+
+    #[test]
+    fn restricting_to_no_strand_is_an_error() {
+        let design = two_helices_design();
+        let strands: HashSet<usize> = [2].into_iter().collect();
+        assert!(matches!(
+            restrict_to_strands(&design, &strands),
+            Err(ExportError::EmptySelection)
+        ));
+    }
+}
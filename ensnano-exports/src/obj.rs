@@ -0,0 +1,386 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Export of the 3D representation of a design (spheres and tubes) as a Wavefront OBJ mesh.
+//!
+//! Each strand is written as a separate object whose material, defined in a companion .mtl
+//! file, has the color of the strand.
+
+use super::*;
+use ensnano_design::{Domain, HelixCollection, Parameters, Strand};
+use std::io::Write;
+use std::path::Path;
+use ultraviolet::Vec3;
+
+/// Radius of the spheres representing nucleotides. This is the radius used in the 3D scene.
+const NUCL_RADIUS: f32 = 0.2;
+/// Radius of the tubes representing bonds between nucleotides. This is the radius used in the 3D
+/// scene.
+const BOND_RADIUS: f32 = 0.06;
+/// Number of vertices on the circular sections of the tubes and cylinders
+const NB_RAY_TUBE: usize = 12;
+
+/// The largest sphere subdivision level that can be asked for
+pub const MAX_SPHERE_SUBDIVISION: u32 = 5;
+
+/// The parameters of an OBJ export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjParameters {
+    /// The subdivision level of the spheres representing nucleotides.
+    ///
+    /// At level `n` the spheres have `4 * (n + 1)` sectors and `2 * (n + 1)` stacks, so level
+    /// 0 represents nucleotides as octahedrons.
+    pub sphere_subdivision: u32,
+    /// If true, the helix domains of the strands are exported as cylinders along the axis of
+    /// their helix instead of one sphere per nucleotide.
+    pub helices_as_cylinders: bool,
+}
+
+impl Default for ObjParameters {
+    fn default() -> Self {
+        Self {
+            sphere_subdivision: 2,
+            helices_as_cylinders: false,
+        }
+    }
+}
+
+/// An indexed triangle mesh
+#[derive(Default)]
+struct Mesh {
+    vertices: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl Mesh {
+    fn push_vertex(&mut self, position: Vec3, normal: Vec3) -> u32 {
+        self.vertices.push(position);
+        self.normals.push(normal);
+        self.vertices.len() as u32 - 1
+    }
+
+    /// Add a UV sphere centered on `center`.
+    fn push_sphere(&mut self, center: Vec3, radius: f32, subdivision: u32) {
+        let nb_sector = 4 * (subdivision as usize + 1);
+        let nb_stack = 2 * (subdivision as usize + 1);
+
+        let north = self.push_vertex(center + radius * Vec3::unit_z(), Vec3::unit_z());
+        let first_ring = self.vertices.len() as u32;
+        for i in 1..nb_stack {
+            let stack_angle = std::f32::consts::PI * (0.5 - i as f32 / nb_stack as f32);
+            for j in 0..nb_sector {
+                let sector_angle = std::f32::consts::TAU * j as f32 / nb_sector as f32;
+                let normal = Vec3::new(
+                    stack_angle.cos() * sector_angle.cos(),
+                    stack_angle.cos() * sector_angle.sin(),
+                    stack_angle.sin(),
+                );
+                self.push_vertex(center + radius * normal, normal);
+            }
+        }
+        let south = self.push_vertex(center - radius * Vec3::unit_z(), -Vec3::unit_z());
+
+        let ring_vertex =
+            |ring: usize, j: usize| first_ring + (ring * nb_sector + j % nb_sector) as u32;
+        for j in 0..nb_sector {
+            self.triangles
+                .push([north, ring_vertex(0, j), ring_vertex(0, j + 1)]);
+            self.triangles.push([
+                south,
+                ring_vertex(nb_stack - 2, j + 1),
+                ring_vertex(nb_stack - 2, j),
+            ]);
+        }
+        for ring in 0..(nb_stack - 2) {
+            for j in 0..nb_sector {
+                let a = ring_vertex(ring, j);
+                let b = ring_vertex(ring + 1, j);
+                let c = ring_vertex(ring + 1, j + 1);
+                let d = ring_vertex(ring, j + 1);
+                self.triangles.push([a, b, c]);
+                self.triangles.push([a, c, d]);
+            }
+        }
+    }
+
+    /// Add an open tube going through all the `points`.
+    ///
+    /// If `capped` is true, the extremities of the tube are closed by disks.
+    fn push_tube(&mut self, points: &[Vec3], radius: f32, capped: bool) {
+        if points.len() < 2 {
+            return;
+        }
+        let tangent = |i: usize| {
+            let before = points[i.saturating_sub(1)];
+            let after = points[(i + 1).min(points.len() - 1)];
+            (after - before).normalized()
+        };
+
+        let first_ring = self.vertices.len() as u32;
+        // The radial directions are parallel transported from one section to the next one so
+        // that the tube does not twist.
+        let mut radial = orthogonal_vector(tangent(0));
+        for (i, point) in points.iter().enumerate() {
+            let t = tangent(i);
+            radial = (radial - t.dot(radial) * t).normalized();
+            let other_radial = t.cross(radial);
+            for j in 0..NB_RAY_TUBE {
+                let angle = std::f32::consts::TAU * j as f32 / NB_RAY_TUBE as f32;
+                let normal = angle.cos() * radial + angle.sin() * other_radial;
+                self.push_vertex(*point + radius * normal, normal);
+            }
+        }
+
+        let ring_vertex =
+            |ring: usize, j: usize| first_ring + (ring * NB_RAY_TUBE + j % NB_RAY_TUBE) as u32;
+        for ring in 0..(points.len() - 1) {
+            for j in 0..NB_RAY_TUBE {
+                let a = ring_vertex(ring, j);
+                let b = ring_vertex(ring, j + 1);
+                let c = ring_vertex(ring + 1, j + 1);
+                let d = ring_vertex(ring + 1, j);
+                self.triangles.push([a, b, c]);
+                self.triangles.push([a, c, d]);
+            }
+        }
+
+        if capped {
+            let last = points.len() - 1;
+            for (ring, point, sign) in [(0, points[0], -1f32), (last, points[last], 1f32)] {
+                let normal = sign * tangent(ring);
+                let center = self.push_vertex(point, normal);
+                let first_cap_vertex = self.vertices.len() as u32;
+                for j in 0..NB_RAY_TUBE {
+                    let position = self.vertices[ring_vertex(ring, j) as usize];
+                    self.push_vertex(position, normal);
+                }
+                for j in 0..NB_RAY_TUBE {
+                    let a = first_cap_vertex + j as u32;
+                    let b = first_cap_vertex + ((j + 1) % NB_RAY_TUBE) as u32;
+                    if sign > 0. {
+                        self.triangles.push([center, a, b]);
+                    } else {
+                        self.triangles.push([center, b, a]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Return a unit vector orthogonal to `direction`.
+fn orthogonal_vector(direction: Vec3) -> Vec3 {
+    let other = if direction.x.abs() < 0.9 {
+        Vec3::unit_x()
+    } else {
+        Vec3::unit_y()
+    };
+    direction.cross(other).normalized()
+}
+
+/// The mesh representing a strand, with one sphere per nucleotide and one tube per bond.
+fn strand_nucleotides_mesh(
+    design: &Design,
+    strand: &Strand,
+    parameters: &Parameters,
+    sphere_subdivision: u32,
+) -> Mesh {
+    let mut positions = Vec::new();
+    for domain in strand.domains.iter() {
+        match domain {
+            Domain::HelixDomain(dom) => {
+                if let Some(helix) = design.helices.get(&dom.helix) {
+                    positions.extend(
+                        dom.iter()
+                            .map(|n| helix.space_pos(parameters, n, dom.forward)),
+                    );
+                }
+            }
+            Domain::Insertion {
+                instanciation: Some(instanciation),
+                ..
+            } => positions.extend(instanciation.pos().iter().cloned()),
+            _ => (),
+        }
+    }
+
+    let mut mesh = Mesh::default();
+    for position in positions.iter() {
+        mesh.push_sphere(*position, NUCL_RADIUS, sphere_subdivision);
+    }
+    for bond in positions.windows(2) {
+        mesh.push_tube(bond, BOND_RADIUS, false);
+    }
+    if strand.cyclic && positions.len() > 2 {
+        mesh.push_tube(
+            &[positions[positions.len() - 1], positions[0]],
+            BOND_RADIUS,
+            false,
+        );
+    }
+    mesh
+}
+
+/// The mesh representing a strand, with one cylinder along the helix axis per helix domain and
+/// one tube per cross-over.
+fn strand_cylinders_mesh(
+    design: &Design,
+    strand: &Strand,
+    parameters: &Parameters,
+    sphere_subdivision: u32,
+) -> Mesh {
+    let mut mesh = Mesh::default();
+    let mut sections: Vec<Vec<Vec3>> = Vec::new();
+    for domain in strand.domains.iter() {
+        if let Domain::HelixDomain(dom) = domain {
+            if let Some(helix) = design.helices.get(&dom.helix) {
+                sections.push(
+                    dom.iter()
+                        .map(|n| helix.axis_position(parameters, n))
+                        .collect(),
+                );
+            }
+        }
+    }
+
+    for section in sections.iter() {
+        if section.len() == 1 {
+            mesh.push_sphere(section[0], NUCL_RADIUS, sphere_subdivision);
+        } else {
+            mesh.push_tube(section, NUCL_RADIUS, true);
+        }
+    }
+    for (prev, next) in sections.iter().zip(sections.iter().skip(1)) {
+        if let (Some(from), Some(to)) = (prev.last(), next.first()) {
+            mesh.push_tube(&[*from, *to], BOND_RADIUS, false);
+        }
+    }
+    if strand.cyclic && sections.len() > 1 {
+        if let (Some(from), Some(to)) = (
+            sections.last().and_then(|s| s.last()),
+            sections.first().and_then(|s| s.first()),
+        ) {
+            mesh.push_tube(&[*from, *to], BOND_RADIUS, false);
+        }
+    }
+    mesh
+}
+
+fn material_name(strand_id: usize) -> String {
+    format!("strand_{strand_id}")
+}
+
+/// Write the mesh representing `design` to `obj_path` and its materials to `mtl_path`.
+pub(super) fn obj_export(
+    design: &Design,
+    obj_parameters: ObjParameters,
+    obj_path: &Path,
+    mtl_path: &Path,
+) -> Result<(), std::io::Error> {
+    let parameters = design.parameters.unwrap_or_default();
+    let sphere_subdivision = obj_parameters
+        .sphere_subdivision
+        .min(MAX_SPHERE_SUBDIVISION);
+
+    let mut obj = std::io::BufWriter::new(std::fs::File::create(obj_path)?);
+    let mut mtl = std::io::BufWriter::new(std::fs::File::create(mtl_path)?);
+    let mtl_name = mtl_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    writeln!(obj, "# Exported from ENSnano")?;
+    writeln!(obj, "mtllib {mtl_name}")?;
+
+    // Vertex indices are global to the file and start at 1.
+    let mut offset = 1;
+    for (s_id, strand) in design.strands.iter() {
+        let mesh = if obj_parameters.helices_as_cylinders {
+            strand_cylinders_mesh(design, strand, &parameters, sphere_subdivision)
+        } else {
+            strand_nucleotides_mesh(design, strand, &parameters, sphere_subdivision)
+        };
+        if mesh.triangles.is_empty() {
+            continue;
+        }
+
+        let material = material_name(*s_id);
+        let color = strand.color & 0xFFFFFF;
+        writeln!(mtl, "newmtl {material}")?;
+        writeln!(
+            mtl,
+            "Kd {:.4} {:.4} {:.4}",
+            (color >> 16) as f32 / 255.,
+            ((color >> 8) & 0xFF) as f32 / 255.,
+            (color & 0xFF) as f32 / 255.
+        )?;
+        writeln!(mtl)?;
+
+        writeln!(obj, "o {material}")?;
+        writeln!(obj, "usemtl {material}")?;
+        for v in mesh.vertices.iter() {
+            writeln!(obj, "v {:.4} {:.4} {:.4}", v.x, v.y, v.z)?;
+        }
+        for n in mesh.normals.iter() {
+            writeln!(obj, "vn {:.4} {:.4} {:.4}", n.x, n.y, n.z)?;
+        }
+        for [a, b, c] in mesh.triangles.iter().map(|t| t.map(|i| i + offset)) {
+            writeln!(obj, "f {a}//{a} {b}//{b} {c}//{c}")?;
+        }
+        offset += mesh.vertices.len() as u32;
+    }
+    obj.flush()?;
+    mtl.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowest_subdivision_sphere_is_an_octahedron() {
+        let mut mesh = Mesh::default();
+        mesh.push_sphere(Vec3::zero(), 1., 0);
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.triangles.len(), 8);
+    }
+
+    #[test]
+    fn sphere_triangles_are_outward_oriented() {
+        let mut mesh = Mesh::default();
+        mesh.push_sphere(Vec3::zero(), 1., 2);
+        for [a, b, c] in mesh.triangles.iter() {
+            let (a, b, c) = (
+                mesh.vertices[*a as usize],
+                mesh.vertices[*b as usize],
+                mesh.vertices[*c as usize],
+            );
+            let normal = (b - a).cross(c - a);
+            assert!(normal.dot(a + b + c) > 0.);
+        }
+    }
+
+    #[test]
+    fn capped_tube_vertex_count() {
+        let mut mesh = Mesh::default();
+        let points = [Vec3::zero(), Vec3::unit_x(), 2. * Vec3::unit_x()];
+        mesh.push_tube(&points, 1., true);
+        assert_eq!(mesh.vertices.len(), 3 * NB_RAY_TUBE + 2 * (NB_RAY_TUBE + 1));
+        assert_eq!(mesh.triangles.len(), 2 * 2 * NB_RAY_TUBE + 2 * NB_RAY_TUBE);
+    }
+}
@@ -22,6 +22,8 @@ use std::mem::ManuallyDrop;
 use std::path::Path;
 use ultraviolet::{Mat3, Rotor3, Vec3};
 
+pub mod import;
+
 pub const OXDNA_LEN_FACTOR: f32 = 1. / 0.8518;
 pub const BACKBONE_TO_CM: f32 = 0.34 * OXDNA_LEN_FACTOR;
 
@@ -55,17 +57,21 @@ pub struct OxDnaConfig {
 impl OxDnaConfig {
     pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
         let mut file = std::fs::File::create(path)?;
+        self.write_to(&mut file)
+    }
+
+    fn write_to<W: Write>(&self, file: &mut W) -> Result<(), std::io::Error> {
         let max = self.boundaries[0].max(self.boundaries[1].max(self.boundaries[2]));
-        writeln!(&mut file, "t = {}", self.time)?;
-        writeln!(&mut file, "b = {} {} {}", max, max, max)?;
+        writeln!(file, "t = {}", self.time)?;
+        writeln!(file, "b = {} {} {}", max, max, max)?;
         writeln!(
-            &mut file,
+            file,
             "E = {} {} {}",
             self.kinetic_energies[0], self.kinetic_energies[1], self.kinetic_energies[2]
         )?;
         for n in self.nucls.iter() {
             writeln!(
-                &mut file,
+                file,
                 "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
                 n.position.x,
                 n.position.y,
@@ -92,15 +98,22 @@ pub struct OxDnaTopology {
     nb_nucl: usize,
     nb_strand: usize,
     bounds: Vec<OxDnaBound>,
+    /// The design nucleotide represented by each oxDNA nucleotide, or `None` for nucleotides of
+    /// insertions. This is not written in the topology file.
+    origins: Vec<Option<Nucl>>,
 }
 
 impl OxDnaTopology {
     pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
         let mut file = std::fs::File::create(path)?;
-        writeln!(&mut file, "{} {}", self.nb_nucl, self.nb_strand)?;
+        self.write_to(&mut file)
+    }
+
+    fn write_to<W: Write>(&self, file: &mut W) -> Result<(), std::io::Error> {
+        writeln!(file, "{} {}", self.nb_nucl, self.nb_strand)?;
         for bound in self.bounds.iter() {
             writeln!(
-                &mut file,
+                file,
                 "{} {} {} {}",
                 bound.strand_id, bound.base, bound.prime5, bound.prime3
             )?;
@@ -122,6 +135,7 @@ pub trait OxDnaHelix {
 
 impl OxDnaHelix for Helix {
     fn ox_dna_nucl(&self, nucl_idx: isize, forward: bool, parameters: &Parameters) -> OxDnaNucl {
+        let parameters = self.parameters(parameters);
         let backbone_position = self.space_pos(parameters, nucl_idx, forward);
         let a1 = {
             let other_base = self.space_pos(parameters, nucl_idx, !forward);
@@ -172,6 +186,7 @@ pub(super) struct OxDnaMaker<'a> {
     boundaries: [f32; 3],
     bounds: Vec<OxDnaBound>,
     nucls: Vec<OxDnaNucl>,
+    origins: Vec<Option<Nucl>>,
     basis_map: BasisMapper<'a>,
     nb_strand: usize,
     parameters: Parameters,
@@ -184,6 +199,7 @@ impl<'a> OxDnaMaker<'a> {
             boundaries: Default::default(),
             bounds: Vec::new(),
             nucls: Vec::new(),
+            origins: Vec::new(),
             basis_map,
             parameters,
             nb_strand: 0,
@@ -207,6 +223,7 @@ impl<'a> OxDnaMaker<'a> {
             bounds: self.bounds,
             nb_strand: self.nb_strand,
             nb_nucl: self.nucl_id as usize,
+            origins: self.origins,
         };
         let config = OxDnaConfig {
             time: 0f32,
@@ -227,20 +244,22 @@ pub struct StrandMaker<'a, 'b> {
 }
 
 impl StrandMaker<'_, '_> {
-    pub fn add_ox_nucl(&mut self, ox_nucl: OxDnaNucl, nucl: Option<Nucl>) {
+    pub fn add_ox_nucl(&mut self, ox_nucl: OxDnaNucl, nucl: Nucl) {
+        let base = self.context.basis_map.get_basis(&nucl, 'T');
+        self.push_ox_nucl(ox_nucl, base, Some(nucl))
+    }
+
+    fn push_ox_nucl(&mut self, ox_nucl: OxDnaNucl, base: char, origin: Option<Nucl>) {
         self.context.boundaries[0] = self.context.boundaries[0].max(4. * ox_nucl.position.x.abs());
         self.context.boundaries[1] = self.context.boundaries[1].max(4. * ox_nucl.position.y.abs());
         self.context.boundaries[2] = self.context.boundaries[2].max(4. * ox_nucl.position.z.abs());
 
         self.previous_position = Some(ox_nucl.position);
         self.context.nucls.push(ox_nucl);
-
-        let base = nucl
-            .as_ref()
-            .map(|nucl| self.context.basis_map.get_basis(&nucl, 'T'));
+        self.context.origins.push(origin);
 
         let bound = OxDnaBound {
-            base: base.unwrap_or(super::rand_base()),
+            base,
             strand_id: self.strand_id,
             prime3: -1,
             prime5: self.prev_nucl.unwrap_or(-1),
@@ -255,14 +274,14 @@ impl StrandMaker<'_, '_> {
         self.context.nucl_id += 1;
     }
 
-    pub fn add_free_nucl(&mut self, position: Vec3, free_idx: usize) {
+    pub fn add_free_nucl(&mut self, position: Vec3, free_idx: usize, base: char) {
         let ox_nucl = free_oxdna_nucl(
             position,
             self.previous_position,
             free_idx,
             &self.context.parameters,
         );
-        self.add_ox_nucl(ox_nucl, None)
+        self.push_ox_nucl(ox_nucl, base, None)
     }
 
     // TODO move the strand maker in a wrapper to force the call to end when droping
@@ -282,10 +301,18 @@ pub(super) fn to_oxdna(design: &Design, basis_map: BasisMapper) -> (OxDnaConfig,
     let parameters = design.parameters.unwrap_or_default();
     let mut maker = OxDnaMaker::new(basis_map, parameters);
 
-    for (strand_id, s) in design.strands.values().enumerate() {
+    // The scaffolds are the first strands of the topology, in the order of `scaffold_ids`
+    let strands = design.scaffolds().chain(
+        design
+            .strands
+            .iter()
+            .filter(|(s_id, _)| !design.is_scaffold(**s_id))
+            .map(|(s_id, s)| (*s_id, s)),
+    );
+    for (strand_id, (s_id, s)) in strands.enumerate() {
         let mut strand_maker = maker.new_strand(strand_id);
 
-        for d in s.domains.iter() {
+        for (domain_id, d) in s.domains.iter().enumerate() {
             if let Domain::HelixDomain(dom) = d {
                 for position in dom.iter() {
                     let ox_nucl = design.helices.get(&dom.helix).unwrap().ox_dna_nucl(
@@ -298,15 +325,23 @@ pub(super) fn to_oxdna(design: &Design, basis_map: BasisMapper) -> (OxDnaConfig,
                         helix: dom.helix,
                         forward: dom.forward,
                     };
-                    strand_maker.add_ox_nucl(ox_nucl, Some(nucl));
+                    strand_maker.add_ox_nucl(ox_nucl, nucl);
                 }
             } else if let Domain::Insertion {
                 instanciation: Some(instanciation),
+                sequence,
                 ..
             } = d
             {
                 for (dom_position, space_position) in instanciation.pos().iter().enumerate() {
-                    strand_maker.add_free_nucl(*space_position, dom_position);
+                    let base = strand_maker.context.basis_map.get_insertion_basis(
+                        s_id,
+                        domain_id,
+                        dom_position,
+                        sequence.as_deref(),
+                        'T',
+                    );
+                    strand_maker.add_free_nucl(*space_position, dom_position, base);
                 }
             }
         }
@@ -316,3 +351,24 @@ pub(super) fn to_oxdna(design: &Design, basis_map: BasisMapper) -> (OxDnaConfig,
 
     maker.end()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helix_parameters_are_used_for_oxdna_nucls() {
+        let mut rna_helix = Helix::new(Vec3::zero(), Rotor3::identity());
+        rna_helix.parameters_override = Some(Parameters::GEARY_2014_RNA);
+        let dna_helix = Helix::new(Vec3::zero(), Rotor3::identity());
+        for position in 0..12 {
+            let from_override = rna_helix.ox_dna_nucl(position, true, &Parameters::GEARY_2014_DNA);
+            let from_design = dna_helix.ox_dna_nucl(position, true, &Parameters::GEARY_2014_RNA);
+            assert!((from_override.position - from_design.position).mag() < 1e-5);
+            assert!((from_override.backbone_base - from_design.backbone_base).mag() < 1e-5);
+        }
+        let dna = dna_helix.ox_dna_nucl(5, true, &Parameters::GEARY_2014_DNA);
+        let rna = rna_helix.ox_dna_nucl(5, true, &Parameters::GEARY_2014_DNA);
+        assert!((dna.position - rna.position).mag() > 0.1);
+    }
+}
@@ -0,0 +1,457 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Import of oxDNA configurations back onto the design that produced them.
+//!
+//! The correspondence between the nucleotides of the oxDNA files and the nucleotides of the design
+//! is not stored in the files, so it is recomputed by exporting the design again. As a
+//! consequence, a configuration can only be mapped onto the design from which it was exported.
+
+use super::*;
+use ensnano_design::AdditionalStructure;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use ultraviolet::Similarity3;
+
+/// An oxDNA configuration mapped onto the nucleotides of a design.
+pub struct OxDnaStructure {
+    /// The position of the backbone of each nucleotide, in the order of the oxDNA files
+    positions: Vec<Vec3>,
+    /// The orientation of each nucleotide, in the order of the oxDNA files
+    orientations: Vec<Rotor3>,
+    /// The index in the oxDNA files of each nucleotide of the design. Nucleotides of insertions
+    /// are not in this map.
+    indices: HashMap<Nucl, usize>,
+    backbone_bonds: Vec<(usize, usize)>,
+    base_pairs: Vec<(usize, usize)>,
+}
+
+impl OxDnaStructure {
+    /// The simulated position of the backbone of a nucleotide of the design
+    pub fn nucl_position(&self, nucl: &Nucl) -> Option<Vec3> {
+        self.indices.get(nucl).map(|i| self.positions[*i])
+    }
+
+    /// The simulated orientation of a nucleotide of the design
+    pub fn nucl_orientation(&self, nucl: &Nucl) -> Option<Rotor3> {
+        self.indices.get(nucl).map(|i| self.orientations[*i])
+    }
+}
+
+impl AdditionalStructure for OxDnaStructure {
+    fn frame(&self) -> Similarity3 {
+        Similarity3::identity()
+    }
+
+    fn position(&self) -> Vec<Vec3> {
+        self.positions.clone()
+    }
+
+    fn right(&self) -> Vec<(usize, usize)> {
+        self.base_pairs.clone()
+    }
+
+    fn next(&self) -> Vec<(usize, usize)> {
+        self.backbone_bonds.clone()
+    }
+
+    fn nt_path(&self) -> Option<Vec<Vec3>> {
+        None
+    }
+
+    fn current_length(&self) -> Option<usize> {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub enum OxDnaImportError {
+    IOError(std::io::Error),
+    /// No topology file could be associated to the configuration file
+    MissingTopology(PathBuf),
+    ParseError {
+        path: PathBuf,
+        line: usize,
+    },
+    /// The configuration file does not contain any nucleotide
+    EmptyConfiguration,
+    /// The topology does not have the same number of nucleotides as the export of the design
+    NucleotideCountMismatch {
+        design: usize,
+        topology: usize,
+    },
+    /// The configuration does not have the same number of nucleotides as the topology
+    ConfigurationLengthMismatch {
+        topology: usize,
+        configuration: usize,
+    },
+    /// The topology does not have the same number of strands as the export of the design
+    StrandCountMismatch {
+        design: usize,
+        topology: usize,
+    },
+    /// A strand of the topology does not have the same length as the corresponding strand of the
+    /// export of the design
+    StrandLengthMismatch {
+        strand: usize,
+        design: usize,
+        topology: usize,
+    },
+}
+
+impl From<std::io::Error> for OxDnaImportError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOError(e)
+    }
+}
+
+impl std::fmt::Display for OxDnaImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(e) => write!(f, "IO error: {e}"),
+            Self::MissingTopology(path) => write!(
+                f,
+                "Could not find the topology file. Expected {}",
+                path.to_string_lossy()
+            ),
+            Self::ParseError { path, line } => write!(
+                f,
+                "Could not parse line {line} of {}",
+                path.to_string_lossy()
+            ),
+            Self::EmptyConfiguration => write!(f, "The configuration contains no nucleotide"),
+            Self::NucleotideCountMismatch { design, topology } => write!(
+                f,
+                "The topology has {topology} nucleotides but the design has {design} nucleotides.
+Was the configuration exported from this design?"
+            ),
+            Self::ConfigurationLengthMismatch {
+                topology,
+                configuration,
+            } => write!(
+                f,
+                "The configuration has {configuration} nucleotides but the topology has {topology} nucleotides"
+            ),
+            Self::StrandCountMismatch { design, topology } => write!(
+                f,
+                "The topology has {topology} strands but the design has {design} strands.
+Was the configuration exported from this design?"
+            ),
+            Self::StrandLengthMismatch {
+                strand,
+                design,
+                topology,
+            } => write!(
+                f,
+                "Strand {strand} of the topology has {topology} nucleotides but the corresponding strand of the design has {design} nucleotides.
+Was the configuration exported from this design?"
+            ),
+        }
+    }
+}
+
+/// Read an oxDNA configuration and map it onto the nucleotides of `design`.
+///
+/// The topology is read from the `.top` file next to the configuration file, or from the only
+/// `.top` file of the configuration's directory. If the configuration file is a trajectory, its
+/// last configuration is used.
+pub fn import_oxdna(
+    design: &Design,
+    configuration: &Path,
+) -> Result<OxDnaStructure, OxDnaImportError> {
+    let topology_path = find_topology(configuration)
+        .ok_or_else(|| OxDnaImportError::MissingTopology(configuration.with_extension("top")))?;
+    let topology = std::fs::read_to_string(&topology_path)?;
+    let strand_ids = parse_topology(&topology, &topology_path)?;
+    let content = std::fs::read_to_string(configuration)?;
+    let nucls = parse_configuration(&content, configuration)?;
+    map_onto_design(design, &strand_ids, nucls)
+}
+
+fn find_topology(configuration: &Path) -> Option<PathBuf> {
+    let next_to_configuration = configuration.with_extension("top");
+    if next_to_configuration.exists() {
+        return Some(next_to_configuration);
+    }
+    let mut candidates = std::fs::read_dir(configuration.parent()?)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|ext| ext == "top").unwrap_or(false));
+    let topology = candidates.next()?;
+    // If there are several candidates, there is no way to know which one is the right one.
+    if candidates.next().is_none() {
+        Some(topology)
+    } else {
+        None
+    }
+}
+
+/// Return the strand identifier of each nucleotide of a topology file
+fn parse_topology(content: &str, path: &Path) -> Result<Vec<isize>, OxDnaImportError> {
+    let parse_error = |line_idx: usize| OxDnaImportError::ParseError {
+        path: path.to_path_buf(),
+        line: line_idx + 1,
+    };
+    let first_value =
+        |line: &str| -> Option<usize> { line.split_whitespace().next()?.parse().ok() };
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let (header_idx, header) = lines.next().ok_or_else(|| parse_error(0))?;
+    let nb_nucl = first_value(header).ok_or_else(|| parse_error(header_idx))?;
+    let mut strand_ids = Vec::with_capacity(nb_nucl);
+    for (line_idx, line) in lines {
+        let strand_id = line
+            .split_whitespace()
+            .next()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| parse_error(line_idx))?;
+        strand_ids.push(strand_id);
+    }
+    if strand_ids.len() != nb_nucl {
+        return Err(parse_error(header_idx));
+    }
+    Ok(strand_ids)
+}
+
+/// Read the nucleotides of the last configuration of a configuration or trajectory file
+fn parse_configuration(content: &str, path: &Path) -> Result<Vec<OxDnaNucl>, OxDnaImportError> {
+    let mut nucls = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('t') {
+            // This is the beginning of a new configuration of a trajectory
+            nucls.clear();
+        } else if !(line.is_empty() || line.starts_with('b') || line.starts_with('E')) {
+            let values: Option<Vec<f32>> = line
+                .split_whitespace()
+                .map(|value| value.parse().ok())
+                .collect();
+            if let Some([px, py, pz, bx, by, bz, nx, ny, nz, ..]) = values.as_deref() {
+                nucls.push(OxDnaNucl {
+                    position: Vec3::new(*px, *py, *pz),
+                    backbone_base: Vec3::new(*bx, *by, *bz),
+                    normal: Vec3::new(*nx, *ny, *nz),
+                    velocity: Vec3::zero(),
+                    angular_velocity: Vec3::zero(),
+                });
+            } else {
+                return Err(OxDnaImportError::ParseError {
+                    path: path.to_path_buf(),
+                    line: line_idx + 1,
+                });
+            }
+        }
+    }
+    if nucls.is_empty() {
+        Err(OxDnaImportError::EmptyConfiguration)
+    } else {
+        Ok(nucls)
+    }
+}
+
+fn map_onto_design(
+    design: &Design,
+    strand_ids: &[isize],
+    nucls: Vec<OxDnaNucl>,
+) -> Result<OxDnaStructure, OxDnaImportError> {
+    // The insertions must be instanciated to be exported
+    let mut design = design.clone();
+    let parameters = design.parameters.unwrap_or_default();
+    for (_, strand) in design.strands.iter_mut() {
+        strand.update_insertions(&design.helices, &parameters);
+    }
+    let (idealized, topology) = to_oxdna(&design, BasisMapper::new(None));
+
+    if topology.nb_nucl != strand_ids.len() {
+        return Err(OxDnaImportError::NucleotideCountMismatch {
+            design: topology.nb_nucl,
+            topology: strand_ids.len(),
+        });
+    }
+    if nucls.len() != strand_ids.len() {
+        return Err(OxDnaImportError::ConfigurationLengthMismatch {
+            topology: strand_ids.len(),
+            configuration: nucls.len(),
+        });
+    }
+    let design_lengths = strand_lengths(topology.bounds.iter().map(|b| b.strand_id as isize));
+    let topology_lengths = strand_lengths(strand_ids.iter().cloned());
+    if design_lengths.len() != topology_lengths.len() {
+        return Err(OxDnaImportError::StrandCountMismatch {
+            design: design_lengths.len(),
+            topology: topology_lengths.len(),
+        });
+    }
+    if let Some((strand, (design_len, topology_len))) = design_lengths
+        .iter()
+        .zip(topology_lengths.iter())
+        .enumerate()
+        .find(|(_, (design_len, topology_len))| design_len != topology_len)
+    {
+        return Err(OxDnaImportError::StrandLengthMismatch {
+            strand,
+            design: *design_len,
+            topology: *topology_len,
+        });
+    }
+
+    let mut positions: Vec<Vec3> = nucls.iter().map(backbone_position).collect();
+    // The simulation may have moved the whole structure, so the configuration is centered on the
+    // idealized design.
+    let shift = centroid(idealized.nucls.iter().map(backbone_position))
+        - centroid(positions.iter().cloned());
+    for position in positions.iter_mut() {
+        *position += shift;
+    }
+    let orientations = nucls.iter().map(OxDnaNucl::get_basis).collect();
+
+    let indices: HashMap<Nucl, usize> = topology
+        .origins
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, origin)| origin.map(|nucl| (nucl, idx)))
+        .collect();
+    let mut base_pairs: Vec<(usize, usize)> = indices
+        .iter()
+        .filter(|(nucl, _)| nucl.forward)
+        .filter_map(|(nucl, idx)| indices.get(&nucl.compl()).map(|compl| (*idx, *compl)))
+        .collect();
+    base_pairs.sort_unstable();
+    let backbone_bonds = topology
+        .bounds
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, bound)| usize::try_from(bound.prime3).ok().map(|next| (idx, next)))
+        .collect();
+
+    Ok(OxDnaStructure {
+        positions,
+        orientations,
+        indices,
+        backbone_bonds,
+        base_pairs,
+    })
+}
+
+/// The lengths of the successive strands, given the strand identifier of each nucleotide
+fn strand_lengths(strand_ids: impl Iterator<Item = isize>) -> Vec<usize> {
+    let mut ret = Vec::new();
+    let mut current_strand = None;
+    for id in strand_ids {
+        if current_strand == Some(id) {
+            if let Some(len) = ret.last_mut() {
+                *len += 1;
+            }
+        } else {
+            current_strand = Some(id);
+            ret.push(1);
+        }
+    }
+    ret
+}
+
+/// Invert the transformation applied by the exporter to get the center of mass of a nucleotide
+fn backbone_position(nucl: &OxDnaNucl) -> Vec3 {
+    (nucl.position - nucl.backbone_base * BACKBONE_TO_CM) / OXDNA_LEN_FACTOR
+}
+
+fn centroid(points: impl Iterator<Item = Vec3>) -> Vec3 {
+    let (sum, nb_points) = points.fold((Vec3::zero(), 0), |(sum, nb), p| (sum + p, nb + 1));
+    sum / nb_points.max(1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ensnano_design::{Domain, Strand};
+
+    fn strand(start: isize, end: isize, forward: bool) -> Strand {
+        let mut strand = Strand::init(0, start, forward, 0xFF0000);
+        if let Some(Domain::HelixDomain(dom)) = strand.domains.get_mut(0) {
+            dom.end = end;
+        }
+        strand
+    }
+
+    fn duplex_design() -> Design {
+        let mut design = Design::new();
+        design
+            .helices
+            .make_mut()
+            .insert(0, Helix::new(Vec3::zero(), Rotor3::identity()));
+        design.strands.insert(0, strand(0, 10, true));
+        design.strands.insert(1, strand(0, 10, false));
+        design
+    }
+
+    fn exported_files(design: &Design) -> (String, String) {
+        let (config, topology) = to_oxdna(design, BasisMapper::new(None));
+        let mut config_content = Vec::new();
+        config.write_to(&mut config_content).unwrap();
+        let mut topology_content = Vec::new();
+        topology.write_to(&mut topology_content).unwrap();
+        (
+            String::from_utf8(config_content).unwrap(),
+            String::from_utf8(topology_content).unwrap(),
+        )
+    }
+
+    #[test]
+    fn exported_configuration_maps_back_onto_design() {
+        let design = duplex_design();
+        let (config, topology) = exported_files(&design);
+        let path = Path::new("test");
+        let strand_ids = parse_topology(&topology, path).unwrap();
+        let nucls = parse_configuration(&config, path).unwrap();
+        let structure = map_onto_design(&design, &strand_ids, nucls).unwrap();
+
+        let helix = design.helices.get(&0).unwrap();
+        let parameters = Parameters::DEFAULT;
+        for position in 0..10 {
+            for forward in [true, false] {
+                let nucl = Nucl::new(0, position, forward);
+                let expected = helix.space_pos(&parameters, position, forward);
+                let imported = structure.nucl_position(&nucl).unwrap();
+                assert!((expected - imported).mag() < 1e-3);
+            }
+        }
+        assert_eq!(structure.right().len(), 10);
+        assert_eq!(structure.next().len(), 18);
+    }
+
+    #[test]
+    fn mismatched_topology_is_rejected() {
+        let (config, topology) = exported_files(&duplex_design());
+        let path = Path::new("test");
+        let strand_ids = parse_topology(&topology, path).unwrap();
+        let nucls = parse_configuration(&config, path).unwrap();
+
+        let mut other_design = duplex_design();
+        other_design.strands.insert(1, strand(0, 9, false));
+        let result = map_onto_design(&other_design, &strand_ids, nucls);
+        assert!(matches!(
+            result,
+            Err(OxDnaImportError::NucleotideCountMismatch {
+                design: 19,
+                topology: 20
+            })
+        ));
+    }
+}
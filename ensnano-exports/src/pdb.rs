@@ -680,7 +680,7 @@ impl PdbStrand<'_> {
 
 pub(super) fn pdb_export(
     design: &Design,
-    mut basis_map: BasisMapper,
+    basis_map: BasisMapper,
     out_path: &PathBuf,
 ) -> Result<(), PdbError> {
     let parameters = design.parameters.unwrap_or_default();
@@ -28,12 +28,15 @@ use super::{
 };
 
 use ensnano_design::ultraviolet;
+use ensnano_interactor::application::Camera2D;
+use ensnano_interactor::keymap::{KeyAction, KeyScope, Keymap};
 use ensnano_utils::winit::event::*;
 use std::cell::RefCell;
+use std::sync::Arc;
 use ultraviolet::Vec2;
 
 mod automata;
-use automata::{ctrl, ControllerState, NormalState, Transition};
+use automata::{ControllerState, NormalState, PanningMinimap, Transition};
 
 pub struct Controller<S: AppState> {
     #[allow(dead_code)]
@@ -49,6 +52,7 @@ pub struct Controller<S: AppState> {
     action_mode: ActionMode,
     modifiers: ModifiersState,
     mouse_position: PhysicalPosition<f64>,
+    keymap: Arc<Keymap>,
 }
 
 #[derive(Debug)]
@@ -64,10 +68,21 @@ pub enum Consequence {
     NewCandidate(Option<FlatNucl>),
     NewHelixCandidate(FlatHelix),
     RmStrand(FlatNucl),
+    InsertionLength {
+        nucl: FlatNucl,
+        length: usize,
+    },
+    FlipDeletion(FlatNucl),
+    ShiftScaffold(isize),
+    ScaffoldShiftEnded,
     RmHelix(FlatHelix),
     FlipVisibility(FlatHelix, bool),
     Built,
     FlipGroup(FlatHelix),
+    ReorderHelix {
+        helix: FlatHelix,
+        new_rank: usize,
+    },
     FollowingSuggestion(FlatNucl, bool),
     Centering(FlatNucl, bool),
     DrawingSelection(PhysicalPosition<f64>, PhysicalPosition<f64>),
@@ -97,6 +112,28 @@ pub enum Consequence {
     PngExport(Vec2, Vec2),
 }
 
+impl Consequence {
+    /// If the consequence modifies the design, a description of the modification, used to
+    /// explain why it is not performed while a simulation is running.
+    pub fn design_edit(&self) -> Option<&'static str> {
+        match self {
+            Self::Xover(..) | Self::FollowingSuggestion(..) => Some("make crossovers"),
+            Self::Cut(_) | Self::CutFreeEnd(..) | Self::CutCross(..) => Some("cut strands"),
+            Self::RmStrand(_) => Some("delete strands"),
+            Self::RmHelix(_) => Some("delete helices"),
+            Self::InsertionLength { .. } | Self::FlipDeletion(_) => Some("edit insertions"),
+            Self::ShiftScaffold(_) => Some("shift the scaffold"),
+            Self::FlipGroup(_) | Self::ReorderHelix { .. } => Some("reorder helices"),
+            Self::InitBuilding(_) | Self::MoveBuilders(_) => Some("build strands"),
+            Self::PasteRequest(_) => Some("paste"),
+            Self::Snap { .. } | Self::Rotation { .. } | Self::Symmetry { .. } => {
+                Some("move helices")
+            }
+            _ => None,
+        }
+    }
+}
+
 impl<S: AppState> Controller<S> {
     pub fn new(
         view: ViewPtr,
@@ -106,6 +143,7 @@ impl<S: AppState> Controller<S> {
         camera_top: CameraPtr,
         camera_bottom: CameraPtr,
         splited: bool,
+        keymap: Arc<Keymap>,
     ) -> Self {
         Self {
             view,
@@ -121,9 +159,16 @@ impl<S: AppState> Controller<S> {
             action_mode: ActionMode::Normal,
             modifiers: ModifiersState::empty(),
             mouse_position: PhysicalPosition::from((0., 0.)),
+            keymap,
         }
     }
 
+    /// The action bound to `key` with the current modifiers.
+    fn key_action(&self, key: VirtualKeyCode) -> Option<KeyAction> {
+        self.keymap
+            .action(key, &self.modifiers, KeyScope::FlatScene)
+    }
+
     pub fn update_modifiers(&mut self, modifiers: ModifiersState) {
         self.modifiers = modifiers;
     }
@@ -204,7 +249,10 @@ impl<S: AppState> Controller<S> {
         position: PhysicalPosition<f64>,
         app_state: &S,
     ) -> Consequence {
-        self.update_hovered_nucl(position);
+        let on_minimap = self.is_on_minimap(position);
+        if !on_minimap {
+            self.update_hovered_nucl(position);
+        }
         self.mouse_position = position;
         let transition = if let WindowEvent::Focused(false) = event {
             Transition {
@@ -213,6 +261,26 @@ impl<S: AppState> Controller<S> {
                 })),
                 consequences: Consequence::Nothing,
             }
+        } else if on_minimap
+            && matches!(
+                event,
+                WindowEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state: ElementState::Pressed,
+                    ..
+                }
+            )
+        {
+            // Clicks on the minimap move the camera instead of being handled by the current tool
+            let bottom = self.is_bottom(position.y);
+            self.center_camera_on_minimap(position, bottom);
+            Transition {
+                new_state: Some(Box::new(PanningMinimap {
+                    mouse_position: position,
+                    bottom,
+                })),
+                consequences: Consequence::Nothing,
+            }
         } else {
             self.state
                 .borrow_mut()
@@ -228,6 +296,24 @@ impl<S: AppState> Controller<S> {
         transition.consequences
     }
 
+    fn is_on_minimap(&self, position: PhysicalPosition<f64>) -> bool {
+        self.view
+            .borrow()
+            .is_on_minimap(position, self.is_bottom(position.y))
+    }
+
+    /// Center the camera of the top or bottom half of the view on the point displayed under
+    /// `position` on its minimap.
+    fn center_camera_on_minimap(&self, position: PhysicalPosition<f64>, bottom: bool) {
+        let center = self.view.borrow().minimap_to_world(position, bottom);
+        let camera = if bottom {
+            &self.camera_bottom
+        } else {
+            &self.camera_top
+        };
+        camera.borrow_mut().set_center(center);
+    }
+
     fn update_hovered_nucl(&self, position: PhysicalPosition<f64>) {
         let (x, y) = self
             .get_camera(position.y)
@@ -257,23 +343,19 @@ impl<S: AppState> Controller<S> {
         } = event
         {
             let camera = self.get_camera(self.mouse_position.y);
-            match *key {
-                VirtualKeyCode::Left if self.modifiers.alt() => {
+            match self.key_action(*key) {
+                Some(KeyAction::TiltLeft) => {
                     camera.borrow_mut().tilt_left();
                 }
-                VirtualKeyCode::Right if self.modifiers.alt() => {
+                Some(KeyAction::TiltRight) => {
                     camera.borrow_mut().tilt_right();
                 }
-                VirtualKeyCode::Left | VirtualKeyCode::Right if ctrl(&self.modifiers) => {
-                    camera.borrow_mut().apply_symettry_x()
-                }
-                VirtualKeyCode::Up | VirtualKeyCode::Down if ctrl(&self.modifiers) => {
-                    camera.borrow_mut().apply_symettry_y()
-                }
-                VirtualKeyCode::J => {
+                Some(KeyAction::SymmetryX) => camera.borrow_mut().apply_symettry_x(),
+                Some(KeyAction::SymmetryY) => camera.borrow_mut().apply_symettry_y(),
+                Some(KeyAction::MoveHelixBackward) => {
                     self.data.borrow_mut().move_helix_backward();
                 }
-                VirtualKeyCode::K => {
+                Some(KeyAction::MoveHelixForward) => {
                     self.data.borrow_mut().move_helix_forward();
                 }
                 _ => (),
@@ -319,6 +401,18 @@ impl<S: AppState> Controller<S> {
             .swap(&mut self.camera_top.borrow_mut())
     }
 
+    pub fn get_cameras_2d(&self) -> [Camera2D; 2] {
+        [
+            self.camera_top.borrow().get_camera_2d(),
+            self.camera_bottom.borrow().get_camera_2d(),
+        ]
+    }
+
+    pub fn teleport_cameras(&mut self, cameras: &[Camera2D; 2]) {
+        self.camera_top.borrow_mut().teleport(&cameras[0]);
+        self.camera_bottom.borrow_mut().teleport(&cameras[1]);
+    }
+
     pub fn get_icon(&self) -> Option<ensnano_interactor::CursorIcon> {
         self.state.borrow().cursor()
     }
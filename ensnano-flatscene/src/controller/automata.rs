@@ -88,6 +88,52 @@ impl<S: AppState> ControllerState<S> for NormalState {
         app_state: &S,
     ) -> Transition<S> {
         match event {
+            WindowEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ElementState::Pressed,
+                ..
+            } if controller.modifiers.alt() && controller.modifiers.shift() => {
+                let (x, y) = controller
+                    .get_camera(position.y)
+                    .borrow()
+                    .screen_to_world(self.mouse_position.x as f32, self.mouse_position.y as f32);
+                let click_result =
+                    controller
+                        .data
+                        .borrow()
+                        .get_click(x, y, &controller.get_camera(position.y));
+                match click_result {
+                    ClickResult::Nucl(nucl) if controller.data.borrow().has_nucl(nucl) => {
+                        let length = controller.data.borrow().insertion_length_after(nucl);
+                        Transition {
+                            new_state: Some(Box::new(EditingInsertion { nucl, length })),
+                            consequences: Consequence::Nothing,
+                        }
+                    }
+                    _ => Transition::nothing(),
+                }
+            }
+            WindowEvent::MouseInput {
+                button: MouseButton::Right,
+                state: ElementState::Pressed,
+                ..
+            } if controller.modifiers.alt() && controller.modifiers.shift() => {
+                let (x, y) = controller
+                    .get_camera(position.y)
+                    .borrow()
+                    .screen_to_world(self.mouse_position.x as f32, self.mouse_position.y as f32);
+                let click_result =
+                    controller
+                        .data
+                        .borrow()
+                        .get_click(x, y, &controller.get_camera(position.y));
+                match click_result {
+                    ClickResult::Nucl(nucl) if controller.data.borrow().has_nucl(nucl) => {
+                        Transition::consequence(Consequence::FlipDeletion(nucl))
+                    }
+                    _ => Transition::nothing(),
+                }
+            }
             WindowEvent::MouseInput {
                 button: MouseButton::Left,
                 state: ElementState::Pressed,
@@ -137,6 +183,13 @@ impl<S: AppState> ControllerState<S> for NormalState {
                             consequences: Consequence::Nothing,
                         }
                     }
+                } else if let ClickResult::CircleWidget { translation_pivot } = click_result {
+                    Transition {
+                        new_state: Some(Box::new(ReorderingHelix {
+                            helix: translation_pivot.helix,
+                        })),
+                        consequences: Consequence::Nothing,
+                    }
                 } else {
                     Transition::nothing()
                 }
@@ -422,6 +475,24 @@ impl<S: AppState> ControllerState<S> for NormalState {
                     .process_scroll(delta, self.mouse_position);
                 Transition::nothing()
             }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state,
+                        ..
+                    },
+                ..
+            } if increment_of_key(key).is_some()
+                && controller.data.borrow().is_editing_scaffold_shift() =>
+            {
+                match (state, increment_of_key(key)) {
+                    (ElementState::Pressed, Some(delta)) => {
+                        Transition::consequence(Consequence::ShiftScaffold(delta))
+                    }
+                    _ => Transition::consequence(Consequence::ScaffoldShiftEnded),
+                }
+            }
             WindowEvent::KeyboardInput { .. } => {
                 controller.process_keyboard(event);
                 Transition::nothing()
@@ -619,6 +690,55 @@ impl<S: AppState> ControllerState<S> for MovingCamera {
     }
 }
 
+/// The camera of one half of the view follows the cursor on the minimap of that half.
+pub struct PanningMinimap {
+    pub mouse_position: PhysicalPosition<f64>,
+    pub bottom: bool,
+}
+
+impl<S: AppState> ControllerState<S> for PanningMinimap {
+    fn display(&self) -> String {
+        String::from("Panning minimap")
+    }
+
+    fn input(
+        &mut self,
+        event: &WindowEvent,
+        position: PhysicalPosition<f64>,
+        controller: &Controller<S>,
+        _: &S,
+    ) -> Transition<S> {
+        match event {
+            WindowEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ElementState::Released,
+                ..
+            } => Transition {
+                new_state: Some(Box::new(NormalState {
+                    mouse_position: self.mouse_position,
+                })),
+                consequences: Consequence::Nothing,
+            },
+            WindowEvent::CursorMoved { .. } => {
+                self.mouse_position = position;
+                controller.center_camera_on_minimap(position, self.bottom);
+                Transition::nothing()
+            }
+            _ => Transition::nothing(),
+        }
+    }
+
+    fn transition_from(&self, controller: &Controller<S>) {
+        controller.end_movement();
+    }
+
+    fn transition_to(&self, _controller: &Controller<S>) {}
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::Grabbing)
+    }
+}
+
 pub struct ReleasedPivot {
     pub mouse_position: PhysicalPosition<f64>,
     pub translation_pivots: Vec<FlatNucl>,
@@ -977,8 +1097,8 @@ impl<S: AppState> ControllerState<S> for ReleasedPivot {
                         ..
                     },
                 ..
-            } => match *key {
-                VirtualKeyCode::Left | VirtualKeyCode::Right if ctrl(&controller.modifiers) => {
+            } => match controller.key_action(*key) {
+                Some(KeyAction::SymmetryX) => {
                     let csq = Consequence::Symmetry {
                         centers: self.rotation_pivots.clone(),
                         helices: self
@@ -991,7 +1111,7 @@ impl<S: AppState> ControllerState<S> for ReleasedPivot {
                     };
                     Transition::consequence(csq)
                 }
-                VirtualKeyCode::Up | VirtualKeyCode::Down if ctrl(&controller.modifiers) => {
+                Some(KeyAction::SymmetryY) => {
                     let csq = Consequence::Symmetry {
                         centers: self.rotation_pivots.clone(),
                         helices: self
@@ -2146,6 +2266,157 @@ impl<S: AppState> ControllerState<S> for RmHelix {
     }
 }
 
+/// The number of nucleotides inserted after `nucl` is being edited with the mouse wheel or the
+/// +/- keys.
+struct EditingInsertion {
+    nucl: FlatNucl,
+    length: usize,
+}
+
+impl EditingInsertion {
+    fn set_length<S: AppState>(&mut self, length: usize) -> Transition<S> {
+        if length == self.length {
+            Transition::nothing()
+        } else {
+            self.length = length;
+            Transition::consequence(Consequence::InsertionLength {
+                nucl: self.nucl,
+                length,
+            })
+        }
+    }
+}
+
+impl<S: AppState> ControllerState<S> for EditingInsertion {
+    fn transition_from(&self, _controller: &Controller<S>) {}
+
+    fn transition_to(&self, _controller: &Controller<S>) {}
+
+    fn display(&self) -> String {
+        format!("Editing insertion of length {}", self.length)
+    }
+
+    fn input(
+        &mut self,
+        event: &WindowEvent,
+        position: PhysicalPosition<f64>,
+        _controller: &Controller<S>,
+        _: &S,
+    ) -> Transition<S> {
+        match event {
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                ..
+            } => Transition {
+                new_state: Some(Box::new(NormalState {
+                    mouse_position: position,
+                })),
+                consequences: Consequence::Nothing,
+            },
+            WindowEvent::MouseWheel { delta, .. } => {
+                let up = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y > 0.,
+                    MouseScrollDelta::PixelDelta(p) => p.y > 0.,
+                };
+                if up {
+                    self.set_length(self.length + 1)
+                } else {
+                    self.set_length(self.length.saturating_sub(1))
+                }
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(increment) = increment_of_key(key) {
+                    self.set_length((self.length as isize + increment).max(0) as usize)
+                } else if let VirtualKeyCode::Escape | VirtualKeyCode::Return = key {
+                    Transition {
+                        new_state: Some(Box::new(NormalState {
+                            mouse_position: position,
+                        })),
+                        consequences: Consequence::Nothing,
+                    }
+                } else {
+                    Transition::nothing()
+                }
+            }
+            _ => Transition::nothing(),
+        }
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::NsResize)
+    }
+}
+
+/// The helix is being dragged vertically to change its rank in the layout.
+struct ReorderingHelix {
+    helix: FlatHelix,
+}
+
+impl<S: AppState> ControllerState<S> for ReorderingHelix {
+    fn transition_from(&self, _controller: &Controller<S>) {}
+
+    fn transition_to(&self, _controller: &Controller<S>) {}
+
+    fn display(&self) -> String {
+        String::from("Reordering helix")
+    }
+
+    fn input(
+        &mut self,
+        event: &WindowEvent,
+        position: PhysicalPosition<f64>,
+        controller: &Controller<S>,
+        _: &S,
+    ) -> Transition<S> {
+        match event {
+            WindowEvent::MouseInput {
+                button: MouseButton::Right,
+                state: ElementState::Released,
+                ..
+            } => {
+                let (_, y) = controller
+                    .get_camera(position.y)
+                    .borrow()
+                    .screen_to_world(position.x as f32, position.y as f32);
+                let new_rank = controller.data.borrow().helix_rank_at(self.helix, y);
+                Transition {
+                    new_state: Some(Box::new(NormalState {
+                        mouse_position: position,
+                    })),
+                    consequences: Consequence::ReorderHelix {
+                        helix: self.helix,
+                        new_rank,
+                    },
+                }
+            }
+            WindowEvent::KeyboardInput { .. } => {
+                controller.process_keyboard(event);
+                Transition::nothing()
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                controller
+                    .get_camera(position.y)
+                    .borrow_mut()
+                    .process_scroll(delta, position);
+                Transition::nothing()
+            }
+            _ => Transition::nothing(),
+        }
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::NsResize)
+    }
+}
+
 struct FlipGroup {
     mouse_position: PhysicalPosition<f64>,
     helix: FlatHelix,
@@ -3142,6 +3413,15 @@ fn position_difference(a: PhysicalPosition<f64>, b: PhysicalPosition<f64>) -> f6
     (a.x - b.x).abs().max((a.y - b.y).abs())
 }
 
+/// Return 1 for the keys that increase a value and -1 for the keys that decrease it.
+fn increment_of_key(key: &VirtualKeyCode) -> Option<isize> {
+    match key {
+        VirtualKeyCode::Plus | VirtualKeyCode::Equals | VirtualKeyCode::NumpadAdd => Some(1),
+        VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => Some(-1),
+        _ => None,
+    }
+}
+
 pub(super) fn ctrl(modifiers: &ModifiersState) -> bool {
     if cfg!(target_os = "macos") {
         modifiers.logo()
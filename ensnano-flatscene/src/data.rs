@@ -23,7 +23,7 @@ use super::{
 use ensnano_design::{ultraviolet, Nucl};
 use ensnano_interactor::{Selection, SelectionMode};
 use std::sync::{Arc, Mutex};
-use ultraviolet::Vec2;
+use ultraviolet::{Isometry2, Vec2};
 
 pub(crate) mod helix;
 pub use helix::{GpuVertex, Helix, HelixHandle, HelixModel, Shift};
@@ -34,11 +34,14 @@ use super::{CameraPtr, FlatHelix, FlatIdx, FlatNucl};
 use crate::FlatHelixMaps;
 use ahash::RandomState;
 use design::{Design2d, Helix2d};
-pub use design::{DesignReader, FlatTorsion, NuclCollection};
+pub use design::{DesignReader, FlatTorsion, NuclCollection, ScaffoldSequenceInfo};
 use ensnano_interactor::consts::*;
 use ensnano_utils::camera2d::FitRectangle;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
+/// The vertical distance between two consecutive helices of a compacted layout.
+const HELIX_SPACING: f32 = 5.;
+
 pub struct Data<R: DesignReader> {
     view: ViewPtr,
     design: Design2d<R>,
@@ -51,6 +54,11 @@ pub struct Data<R: DesignReader> {
     id: u32,
     requests: Arc<Mutex<dyn Requests>>,
     last_click: LastClick,
+    /// Set when the scaffold is the only selected element, in which case the position of its
+    /// sequence can be edited
+    scaffold_shift_edition: Option<ScaffoldSequenceInfo>,
+    /// True if the preview of the strand complementary to the domain being built is drawn
+    building_complement_shown: bool,
 }
 
 impl<R: DesignReader> Data<R> {
@@ -67,6 +75,8 @@ impl<R: DesignReader> Data<R> {
             id,
             requests,
             last_click: Default::default(),
+            scaffold_shift_edition: None,
+            building_complement_shown: false,
         }
     }
 
@@ -87,24 +97,43 @@ impl<R: DesignReader> Data<R> {
             self.view.borrow_mut().reset();
             self.instance_reset = false;
         }
+        self.view
+            .borrow_mut()
+            .set_show_minimap(new_state.show_minimap());
         if new_state.design_was_updated(old_state)
             || new_state.selection_was_updated(old_state)
             || new_state.candidate_was_updated(old_state)
+            || new_state.design_changes_were_updated(old_state)
+            || new_state.strand_coloring_was_updated(old_state)
             || self.instance_update
             || self.view.borrow().needs_redraw()
         {
             log::trace!("updating 2d data");
-            self.design.update(new_state.get_design_reader());
+            self.design.update(
+                new_state.get_design_reader(),
+                new_state.get_strand_coloring(),
+                new_state.get_strand_colormap(),
+            );
             self.fetch_helices(new_state.get_design_reader());
             self.view.borrow_mut().update_helices(&self.helices);
             self.view
                 .borrow_mut()
                 .update_strands(self.design.get_strands(), &self.helices);
+            self.view
+                .borrow_mut()
+                .update_deletions(self.design.get_deletions(), &self.helices);
+            self.view
+                .borrow_mut()
+                .update_modifications(self.design.get_modified_nucls(), &self.helices);
+            self.update_scaffold_shift_edition(new_state);
             self.view
                 .borrow_mut()
                 .update_pasted_strand(self.design.get_pasted_strand(), &self.helices);
             self.update_highlight(new_state);
-            self.update_strand_building_info(new_state.get_building_state());
+        }
+        self.update_strand_building_info(new_state.get_building_state());
+        if self.building_complement_shown || !new_state.get_strand_builders().is_empty() {
+            self.update_building_complement(new_state);
         }
         self.instance_update = false;
     }
@@ -113,6 +142,35 @@ impl<R: DesignReader> Data<R> {
         self.design.id_map()
     }
 
+    fn update_scaffold_shift_edition<S: AppState>(&mut self, new_state: &S) {
+        self.scaffold_shift_edition = match new_state.get_selection() {
+            [Selection::Strand(_, s_id)] => self.design.get_scaffold_sequence_info(*s_id as usize),
+            _ => None,
+        };
+        let seam = self
+            .scaffold_shift_edition
+            .and_then(|info| info.seam)
+            .and_then(|n| FlatNucl::from_real(&n, self.id_map()));
+        let mut view = self.view.borrow_mut();
+        view.set_show_scaffold_sequence(self.scaffold_shift_edition.is_some());
+        view.update_scaffold_seam(seam, &self.helices);
+    }
+
+    /// Return true if a scaffold is the only selected element, in which case the position of
+    /// its sequence can be edited.
+    pub fn is_editing_scaffold_shift(&self) -> bool {
+        self.scaffold_shift_edition.is_some()
+    }
+
+    /// Shift the sequence of the edited scaffold by `delta` bases and return the updated
+    /// informations. The shift wraps around the length of the sequence.
+    pub fn shift_scaffold_sequence(&mut self, delta: isize) -> Option<ScaffoldSequenceInfo> {
+        let info = self.scaffold_shift_edition.as_mut()?;
+        let length = info.sequence_length as isize;
+        info.shift = (info.shift as isize + delta).rem_euclid(length) as usize;
+        Some(*info)
+    }
+
     pub fn update_highlight<S: AppState>(&mut self, new_state: &S) {
         let mut selected_strands = HashSet::new();
         let mut candidate_strands = HashSet::new();
@@ -151,8 +209,8 @@ impl<R: DesignReader> Data<R> {
                         selected_helices.push(flat_helix.flat);
                     }
                 }
-                Selection::Nucleotide(_, n) => {
-                    if let Some(flat_nucl) = FlatNucl::from_real(n, id_map) {
+                Selection::Nucleotide(..) => {
+                    if let FlatSelection::Nucleotide(_, flat_nucl) = self.convert_to_flat(*s) {
                         selected_nucls.push(flat_nucl);
                     }
                 }
@@ -188,8 +246,15 @@ impl<R: DesignReader> Data<R> {
                         candidate_helices.push(flat_helix.flat);
                     }
                 }
-                Selection::Nucleotide(_, n) => {
-                    if let Some(flat_nucl) = FlatNucl::from_real(n, id_map) {
+                Selection::Phantom(phantom) => {
+                    // Phantom nucleotides hovered in the 3D view are highlighted like candidate
+                    // nucleotides.
+                    if let Some(flat_nucl) = FlatNucl::from_real(&phantom.to_nucl(), id_map) {
+                        candidate_nucls.push(flat_nucl);
+                    }
+                }
+                Selection::Nucleotide(..) => {
+                    if let FlatSelection::Nucleotide(_, flat_nucl) = self.convert_to_flat(*c) {
                         candidate_nucls.push(flat_nucl);
                         let mut other = self.get_best_suggestion(flat_nucl);
                         other = other.or_else(|| self.can_make_auto_xover(flat_nucl));
@@ -219,6 +284,43 @@ impl<R: DesignReader> Data<R> {
         for xover in candidate_xovers.iter() {
             candidate_highlight.push(self.design.strand_from_xover(xover, CANDIDATE_COLOR, true));
         }
+        let mut moved_helices = Vec::new();
+        if let Some(changes) = new_state.get_design_changes() {
+            for s in self.design.get_strands().iter() {
+                let color = if changes.added_strands.contains(&s.id) {
+                    ADDED_STRAND_COLOR
+                } else if changes.modified_strands.contains(&s.id) {
+                    MODIFIED_STRAND_COLOR
+                } else {
+                    continue;
+                };
+                candidate_highlight
+                    .push(s.highlighted(color, CANDIDATE_STRAND_HIGHLIGHT_FACTOR_2D));
+            }
+            // The deleted strands are drawn as ghosts on the helices that still exist
+            for removed in changes.removed_strands.iter() {
+                let points = removed
+                    .nucls
+                    .iter()
+                    .filter_map(|n| FlatNucl::from_real(n, id_map))
+                    .collect();
+                candidate_highlight.push(Strand::new(
+                    REMOVED_STRAND_COLOR,
+                    points,
+                    vec![],
+                    0,
+                    Some(CANDIDATE_STRAND_HIGHLIGHT_FACTOR_2D),
+                ));
+            }
+            for h_id in changes.moved_helices.iter() {
+                moved_helices.extend(
+                    self.helices
+                        .iter()
+                        .filter(|h| h.real_id == *h_id)
+                        .map(|h| h.flat_id.flat),
+                );
+            }
+        }
         self.view
             .borrow_mut()
             .update_selection(&selection_highlight, &self.helices);
@@ -231,6 +333,7 @@ impl<R: DesignReader> Data<R> {
         self.view
             .borrow_mut()
             .set_candidate_helices(candidate_helices);
+        self.view.borrow_mut().set_moved_helices(moved_helices);
         self.view.borrow_mut().set_selected_nucls(selected_nucls);
         self.view.borrow_mut().set_candidate_nucls(candidate_nucls);
         self.view.borrow_mut().set_candidate_suggestion(
@@ -246,6 +349,33 @@ impl<R: DesignReader> Data<R> {
             .update_strand_building_info(flat_info);
     }
 
+    /// Draw a faint preview of the nucleotides that would pair with the domains being built.
+    fn update_building_complement<S: AppState>(&mut self, new_state: &S) {
+        let id_map = self.design.id_map();
+        let strands: Vec<Strand> = new_state
+            .get_strand_builders()
+            .iter()
+            .map(|builder| {
+                let points = builder
+                    .get_domain_nucls()
+                    .iter()
+                    .filter_map(|n| FlatNucl::from_real(&n.compl(), id_map))
+                    .collect();
+                Strand::new(
+                    BUILDING_COMPLEMENT_COLOR,
+                    points,
+                    vec![],
+                    0,
+                    Some(CANDIDATE_STRAND_HIGHLIGHT_FACTOR_2D),
+                )
+            })
+            .collect();
+        self.building_complement_shown = !strands.is_empty();
+        self.view
+            .borrow_mut()
+            .update_building_complement(&strands, &self.helices);
+    }
+
     fn fetch_helices(&mut self, design: R) {
         let removed_helices = self.design.get_removed_helices();
         for h in removed_helices.iter().rev() {
@@ -492,6 +622,74 @@ impl<R: DesignReader> Data<R> {
         self.notify_update();
     }
 
+    /// The rank that `helix` would have in the vertical order of the helices if its origin was
+    /// at height `y`.
+    pub fn helix_rank_at(&self, helix: FlatHelix, y: f32) -> usize {
+        self.helices
+            .iter()
+            .filter(|h| h.flat_id != helix && h.isometry.translation.y < y)
+            .count()
+    }
+
+    /// Move `helix` to position `new_rank` in the vertical order of the helices.
+    ///
+    /// The heights occupied by the helices do not change, they are redistributed among the
+    /// helices to match the new order.
+    pub fn reorder_helix(&mut self, helix: FlatHelix, new_rank: usize) {
+        let mut order = self.helices_vertical_order();
+        let heights: Vec<f32> = order
+            .iter()
+            .map(|h| self.helices[h.flat].isometry.translation.y)
+            .collect();
+        if let Some(rank) = order.iter().position(|h| *h == helix) {
+            order.remove(rank);
+            order.insert(new_rank.min(order.len()), helix);
+            self.set_helices_height(order.into_iter().zip(heights));
+        }
+    }
+
+    /// Stack the visible helices with a uniform spacing, keeping their vertical order. Hidden
+    /// helices are not moved.
+    pub fn compact_layout(&mut self) {
+        let order: Vec<FlatHelix> = self
+            .helices_vertical_order()
+            .into_iter()
+            .filter(|h| self.helices[h.flat].visible)
+            .collect();
+        if let Some(first) = order.first() {
+            let top = self.helices[first.flat].isometry.translation.y;
+            let heights = (0..order.len()).map(|i| top + HELIX_SPACING * i as f32);
+            self.set_helices_height(order.into_iter().zip(heights));
+        }
+    }
+
+    /// The helices sorted by the height of their origin.
+    fn helices_vertical_order(&self) -> Vec<FlatHelix> {
+        let mut ret: Vec<FlatHelix> = self.helices.iter().map(|h| h.flat_id).collect();
+        ret.sort_by(|a, b| {
+            let y_a = self.helices[a.flat].isometry.translation.y;
+            let y_b = self.helices[b.flat].isometry.translation.y;
+            y_a.partial_cmp(&y_b).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        ret
+    }
+
+    /// Request a new isometry for each helix whose height must change.
+    fn set_helices_height(&mut self, heights: impl Iterator<Item = (FlatHelix, f32)>) {
+        for (helix, y) in heights {
+            let isometry = &self.helices[helix.flat].isometry;
+            if (isometry.translation.y - y).abs() > f32::EPSILON {
+                let new_isometry =
+                    Isometry2::new(Vec2::new(isometry.translation.x, y), isometry.rotation);
+                self.requests.lock().unwrap().set_isometry(
+                    helix.segment.helix_idx,
+                    helix.segment.segment_idx,
+                    new_isometry,
+                );
+            }
+        }
+    }
+
     /*
     pub fn rotate_helix(&mut self, helix: FlatHelix, pivot: Vec2, angle: f32) {
         self.helices[helix.flat].rotate(pivot, angle);
@@ -577,6 +775,11 @@ impl<R: DesignReader> Data<R> {
         self.design.has_nucl(nucl.to_real())
     }
 
+    /// Return the number of nucleotides inserted right after `nucl` in the 5' -> 3' direction.
+    pub fn insertion_length_after(&self, nucl: FlatNucl) -> usize {
+        self.design.get_insertion_length_after(nucl.to_real())
+    }
+
     pub fn get_strand_id(&self, nucl: FlatNucl) -> Option<usize> {
         let nucl = nucl.to_real();
         self.design.get_strand_id(nucl)
@@ -642,14 +845,7 @@ impl<R: DesignReader> Data<R> {
     }
 
     pub fn get_fit_rectangle(&self) -> FitRectangle {
-        let mut ret = FitRectangle::new();
-        for h in self.helices.iter() {
-            let left = h.get_pivot(h.get_flat_left());
-            ret.add_point(Vec2::new(left.x, left.y));
-            let right = h.get_pivot(h.get_flat_right());
-            ret.add_point(Vec2::new(right.x, right.y));
-        }
-        ret
+        fit_rectangle(&self.helices)
     }
 
     pub fn is_xover_end(&self, nucl: &FlatNucl) -> Option<bool> {
@@ -955,6 +1151,25 @@ impl<R: DesignReader> Data<R> {
                         }
                     }
                 }
+                SelectionMode::Nucleotide => {
+                    // Only nucleotides that belong to a strand can be selected, and repeated
+                    // clicks only cycle between the nucleotide and its cross-over.
+                    if self.get_strand_id(nucl).is_none() {
+                        return;
+                    }
+                    self.last_click.click_on(nucl);
+                    let mut selection_pool = vec![Selection::Nucleotide(self.id, nucl.to_real())];
+                    if let Some(xover) = self.xover_containing_nucl(&nucl) {
+                        selection_pool.push(Selection::Xover(self.id, xover));
+                    }
+                    let selection = self.last_click.select(&mut selection_pool);
+                    if let Some(pos) = new_selection.iter().position(|x| *x == selection) {
+                        new_selection.remove(pos);
+                    } else {
+                        new_selection.retain(|s| !selection_pool.contains(s));
+                        new_selection.push(selection);
+                    }
+                }
                 _ => {
                     self.last_click.click_on(nucl);
                     let mut selection_pool = vec![Selection::Nucleotide(self.id, nucl.to_real())];
@@ -1214,6 +1429,18 @@ impl ToFlatInfo for super::StrandBuildingStatus {
     }
 }
 
+/// Return the smallest rectangle containing all the helices.
+pub fn fit_rectangle(helices: &[Helix]) -> FitRectangle {
+    let mut ret = FitRectangle::new();
+    for h in helices.iter() {
+        let left = h.get_pivot(h.get_flat_left());
+        ret.add_point(Vec2::new(left.x, left.y));
+        let right = h.get_pivot(h.get_flat_right());
+        ret.add_point(Vec2::new(right.x, right.y));
+    }
+    ret
+}
+
 struct LastClick {
     counter: usize,
     last_click_time: std::time::Instant,
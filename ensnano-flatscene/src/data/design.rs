@@ -28,7 +28,11 @@ use ensnano_design::{
 use ensnano_interactor::consts::{
     CANDIDATE_STRAND_HIGHLIGHT_FACTOR_2D, SELECTED_STRAND_HIGHLIGHT_FACTOR_2D,
 };
-use ensnano_interactor::{torsion::Torsion, Referential};
+use ensnano_interactor::{
+    graphics::{Colormap, StrandColoring, StrandColors},
+    torsion::Torsion,
+    Referential,
+};
 use ensnano_utils::full_isometry::FullIsometry;
 use ultraviolet::{Isometry2, Rotor2, Vec2, Vec3};
 
@@ -45,6 +49,10 @@ pub(super) struct Design2d<R: DesignReader> {
     design: R,
     /// The strand being pasted,
     pasted_strands: Vec<Strand>,
+    /// The nucleotides marked as deleted
+    deletions: Vec<FlatNucl>,
+    /// The nucleotides that carry a chemical modification
+    modified_nucls: Vec<FlatNucl>,
     last_flip_other: Option<FlatHelix>,
     removed: BTreeSet<FlatIdx>,
     requests: Arc<Mutex<dyn Requests>>,
@@ -60,6 +68,8 @@ impl<R: DesignReader> Design2d<R> {
             id_map: Default::default(),
             strands: Vec::new(),
             pasted_strands: Vec::new(),
+            deletions: Vec::new(),
+            modified_nucls: Vec::new(),
             last_flip_other: None,
             removed: BTreeSet::new(),
             requests,
@@ -73,15 +83,25 @@ impl<R: DesignReader> Design2d<R> {
         self.id_map = Default::default();
         self.strands = Default::default();
         self.pasted_strands = Default::default();
+        self.deletions = Default::default();
+        self.modified_nucls = Default::default();
         self.last_flip_other = Default::default();
         self.removed = Default::default();
         self.known_helices = Default::default();
         self.known_map = std::ptr::null();
     }
 
-    /// Re-read the design and update the 2d data accordingly
-    pub fn update(&mut self, design: R) {
+    /// Re-read the design and update the 2d data accordingly.
+    ///
+    /// If `coloring` is not `StrandColoring::Stored`, the non-scaffold strands are colored
+    /// according to the property `coloring` with the same colors as in the 3D view.
+    pub fn update(&mut self, design: R, coloring: StrandColoring, colormap: Colormap) {
         self.design = design;
+        let strand_colors = StrandColors::new(
+            coloring,
+            colormap,
+            &self.design.get_strand_property_values(coloring),
+        );
         log::trace!("updating design");
         // At the moment we rebuild the strands from scratch. If needed, this might be an optimisation
         // target
@@ -95,13 +115,20 @@ impl<R: DesignReader> Design2d<R> {
             // Unwrap: `strand_id` is in the list returned by `get_all_strand_ids` so it
             // corresponds to an existing strand id.
             let strand = strand_opt.unwrap();
-            let color = self.design.get_strand_color(*strand_id).unwrap_or_else(|| {
-                log::warn!("Warning: could not find strand color, this is not normal");
-                0
-            });
+            let color = strand_colors
+                .as_ref()
+                .and_then(|colors| colors.strand_color(*strand_id))
+                .or_else(|| self.design.get_strand_color(*strand_id))
+                .unwrap_or_else(|| {
+                    log::warn!("Warning: could not find strand color, this is not normal");
+                    0
+                });
             for nucl in strand.iter() {
                 self.read_nucl(nucl)
             }
+            if self.design.get_visibility_strand(*strand_id) == Some(false) {
+                continue;
+            }
             let flat_strand: Vec<_> = strand
                 .iter()
                 .filter_map(|n| FlatNucl::from_real(n, self.id_map()))
@@ -119,6 +146,18 @@ impl<R: DesignReader> Design2d<R> {
                 None,
             ));
         }
+        self.deletions = self
+            .design
+            .get_deletions()
+            .iter()
+            .filter_map(|n| FlatNucl::from_real(n, self.id_map()))
+            .collect();
+        self.modified_nucls = self
+            .design
+            .get_modified_nucls()
+            .iter()
+            .filter_map(|n| FlatNucl::from_real(n, self.id_map()))
+            .collect();
         let nucls_opt = self.design.get_copy_points();
 
         self.pasted_strands = nucls_opt
@@ -318,6 +357,22 @@ impl<R: DesignReader> Design2d<R> {
         &self.strands
     }
 
+    pub fn get_deletions(&self) -> &[FlatNucl] {
+        &self.deletions
+    }
+
+    pub fn get_modified_nucls(&self) -> &[FlatNucl] {
+        &self.modified_nucls
+    }
+
+    pub fn get_scaffold_sequence_info(&self, scaffold_id: usize) -> Option<ScaffoldSequenceInfo> {
+        self.design.get_scaffold_sequence_info(scaffold_id)
+    }
+
+    pub fn get_insertion_length_after(&self, nucl: Nucl) -> usize {
+        self.design.get_insertion_length_after(&nucl)
+    }
+
     pub fn get_pasted_strand(&self) -> &[Strand] {
         &self.pasted_strands
     }
@@ -519,6 +574,18 @@ impl FlatTorsion {
     }
 }
 
+/// The position of the scaffold sequence on the scaffold strand
+#[derive(Debug, Clone, Copy)]
+pub struct ScaffoldSequenceInfo {
+    pub scaffold_id: usize,
+    /// The shift of the scaffold sequence, reduced modulo the sequence length
+    pub shift: usize,
+    /// The number of bases of the scaffold sequence
+    pub sequence_length: usize,
+    /// The scaffold nucleotide that is assigned the first base of the sequence
+    pub seam: Option<Nucl>,
+}
+
 pub trait DesignReader: 'static {
     type NuclCollection: NuclCollection;
     fn get_all_strand_ids(&self) -> Vec<usize>;
@@ -526,9 +593,23 @@ pub trait DesignReader: 'static {
     /// is no strand with id `s_id` in the design.
     fn get_strand_points(&self, s_id: usize) -> Option<Vec<Nucl>>;
     fn get_strand_color(&self, s_id: usize) -> Option<u32>;
+    /// Return the value of the property `coloring` for each non-scaffold strand for which it is
+    /// known
+    fn get_strand_property_values(&self, coloring: StrandColoring) -> BTreeMap<usize, f32>;
     fn get_insertions(&self, s_id: usize) -> Option<Vec<Nucl>>;
+    /// Return the number of nucleotides inserted right after `nucl` in the 5' -> 3' direction.
+    fn get_insertion_length_after(&self, nucl: &Nucl) -> usize;
+    /// Return the nucleotides that are marked as deleted.
+    fn get_deletions(&self) -> Vec<Nucl>;
+    /// Return the nucleotides that carry a chemical modification.
+    fn get_modified_nucls(&self) -> Vec<Nucl>;
+    /// Return None if `scaffold_id` is not a scaffold or if no sequence is set for it.
+    fn get_scaffold_sequence_info(&self, scaffold_id: usize) -> Option<ScaffoldSequenceInfo>;
     fn get_copy_points(&self) -> Vec<Vec<Nucl>>;
     fn get_visibility_helix(&self, h_id: usize) -> Option<bool>;
+    /// Return false iff strand `s_id` has been hidden by the user. Return None iff there is no
+    /// strand with id `s_id` in the design.
+    fn get_visibility_strand(&self, s_id: usize) -> Option<bool>;
     fn get_suggestions(&self) -> Vec<(Nucl, Nucl)>;
     fn has_helix(&self, h_id: usize) -> bool;
     fn get_isometry(&self, h_id: usize, segment_idx: usize) -> Option<Isometry2>;
@@ -45,6 +45,8 @@ type Vertices = lyon::tessellation::VertexBuffers<GpuVertex, u16>;
 
 const CIRCLE_WIDGET_RADIUS: f32 = 1.5;
 const ZOOM_THRESHOLD: f32 = 7.0;
+/// The zoom level below which the lengths of the domains are not written
+const DOMAIN_LENGTH_ZOOM_THRESHOLD: f32 = 3.0;
 
 #[derive(Debug, Clone)]
 pub struct Helix {
@@ -275,6 +277,20 @@ impl Helix {
                         Vec2::new(0.5, 1.5)
                     }
                 }
+                Shift::Prime5Boundary => {
+                    if nucl.forward {
+                        Vec2::new(0., 0.5)
+                    } else {
+                        Vec2::new(1., 1.5)
+                    }
+                }
+                Shift::Prime3Boundary => {
+                    if nucl.forward {
+                        Vec2::new(1., 0.5)
+                    } else {
+                        Vec2::new(0., 1.5)
+                    }
+                }
             };
         let new_x = self
             .abscissa_converter
@@ -691,6 +707,65 @@ impl Helix {
         })
     }
 
+    pub fn deletion_instance(&self, nucl: &FlatNucl, color: u32) -> InsertionInstance {
+        InsertionInstance::new(InsertionDescriptor {
+            position: self.get_nucl_position(nucl, Shift::No),
+            depth: self.get_depth(),
+            symmetry: self.isometry.symmetry,
+            orientation: self.isometry.rotation,
+            color,
+        })
+    }
+
+    /// An instance marking a modified nucleotide, pointing away from the helix like the
+    /// insertions
+    pub fn modification_instance(&self, nucl: &FlatNucl, color: u32) -> InsertionInstance {
+        let mut orientation = self.isometry.rotation;
+        if nucl.forward {
+            orientation = Rotor2::from_angle(std::f32::consts::PI) * orientation;
+        }
+        InsertionInstance::new(InsertionDescriptor {
+            position: self.get_nucl_position(nucl, Shift::No),
+            depth: self.get_depth(),
+            symmetry: self.isometry.symmetry,
+            orientation,
+            color,
+        })
+    }
+
+    /// An instance marking the boundary between `nucl` and its 5' neighbour
+    pub fn seam_instance(&self, nucl: &FlatNucl, color: u32) -> InsertionInstance {
+        InsertionInstance::new(InsertionDescriptor {
+            position: self.get_nucl_position(nucl, Shift::Prime5Boundary),
+            depth: self.get_depth(),
+            symmetry: self.isometry.symmetry,
+            orientation: self.isometry.rotation,
+            color,
+        })
+    }
+
+    /// An instance marking the boundary between the domain ending at `nucl` and the next or
+    /// previous nucleotide, depending on `prime3`
+    pub fn domain_boundary_instance(
+        &self,
+        nucl: &FlatNucl,
+        prime3: bool,
+        color: u32,
+    ) -> InsertionInstance {
+        let shift = if prime3 {
+            Shift::Prime3Boundary
+        } else {
+            Shift::Prime5Boundary
+        };
+        InsertionInstance::new(InsertionDescriptor {
+            position: self.get_nucl_position(nucl, shift),
+            depth: self.get_depth(),
+            symmetry: self.isometry.symmetry,
+            orientation: self.isometry.rotation,
+            color,
+        })
+    }
+
     fn info_line(&self) -> Line {
         Line {
             origin: self
@@ -826,27 +901,6 @@ impl Helix {
             print_pos(position);
         }
 
-        let mut print_info = |flat_pos: FlatPosition, info: &str| {
-            let sentence = Sentence {
-                text: info,
-                size: size_pos * zoom_font,
-                z_index: self.flat_id.flat.0 as i32,
-                color: [0., 0., 0., 1.].into(),
-                rotation,
-                symetry,
-            };
-            let line = self.info_line();
-            char_collector
-                .text_drawer
-                .add_sentence(sentence, self.info_position(flat_pos), line);
-        };
-
-        if let Some(building) = edition_info {
-            if building.nucl.helix == self.flat_id {
-                print_info(building.nucl.flat_position, &building.to_string());
-            }
-        }
-
         let mut print_basis = |flat_position: FlatPosition, forward: bool| {
             let nucl = FlatNucl {
                 helix: self.flat_id,
@@ -888,6 +942,70 @@ impl Helix {
         }
     }
 
+    /// Write `length` in the middle of the domain going from `prime5` to `prime3`, on the side of
+    /// the helix on which the domain lies.
+    pub fn add_domain_length(
+        &self,
+        text_drawer: &mut TextDrawer,
+        camera: &CameraPtr,
+        (prime5, prime3): (&FlatNucl, &FlatNucl),
+        length: usize,
+    ) {
+        let zoom = camera.borrow().get_globals().zoom;
+        if zoom < DOMAIN_LENGTH_ZOOM_THRESHOLD {
+            return;
+        }
+        let zoom_font = if zoom < 7.0 { 2. } else { 1. };
+        let text = length.to_string();
+        let sentence = Sentence {
+            text: &text,
+            size: 1.4 * zoom_font,
+            z_index: self.flat_id.flat.0 as i32,
+            color: BLACK_VEC4,
+            rotation: camera.borrow().rotation().reversed(),
+            symetry: camera.borrow().get_globals().symetry,
+        };
+        let middle = (prime5.flat_position.0 + prime3.flat_position.0) as f32 / 2. + 0.5;
+        let (y, line) = if prime5.forward {
+            (0., self.top_line())
+        } else {
+            (2., self.bottom_line())
+        };
+        let position = self
+            .isometry
+            .into_homogeneous_matrix()
+            .transform_point2(self.x_conversion(middle) * Vec2::unit_x() + y * Vec2::unit_y());
+        text_drawer.add_sentence(sentence, position, line);
+    }
+
+    /// Write the length of the domain being built under the dragged nucleotide.
+    pub fn add_building_label(
+        &self,
+        text_drawer: &mut TextDrawer,
+        camera: &CameraPtr,
+        info: &EditionInfo,
+    ) {
+        let zoom_font = if camera.borrow().get_globals().zoom < 7.0 {
+            2.
+        } else {
+            1.
+        };
+        let text = info.to_string();
+        let sentence = Sentence {
+            text: &text,
+            size: 1.4 * zoom_font,
+            z_index: self.flat_id.flat.0 as i32,
+            color: [0., 0., 0., 1.].into(),
+            rotation: camera.borrow().rotation().reversed(),
+            symetry: camera.borrow().get_globals().symetry,
+        };
+        text_drawer.add_sentence(
+            sentence,
+            self.info_position(info.nucl.flat_position),
+            self.info_line(),
+        );
+    }
+
     pub fn get_flat_left(&self) -> FlatPosition {
         FlatPosition::from_real(self.get_left(), self.flat_id.segment_left)
     }
@@ -1107,6 +1225,10 @@ pub enum Shift {
     Prime3,
     /// The returned point will be slightly shifted in the 5' direction outside the helix
     Prime5Outsided,
+    /// The returned point will be on the boundary between the nucleotide and its 5' neighbour
+    Prime5Boundary,
+    /// The returned point will be on the boundary between the nucleotide and its 3' neighbour
+    Prime3Boundary,
     /// The returned point will be slightly shifted in the 3' direction outside the helix
     Prime3Outsided,
 }
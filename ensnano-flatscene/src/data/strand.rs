@@ -144,6 +144,19 @@ impl Strand {
         ret
     }
 
+    /// The 5' end, the 3' end and the number of nucleotides of each domain of the strand.
+    pub fn domains(&self) -> Vec<(FlatNucl, FlatNucl, usize)> {
+        self.points
+            .chunks_exact(2)
+            .filter(|ends| ends[0].helix == ends[1].helix && ends[0].forward == ends[1].forward)
+            .map(|ends| {
+                let length =
+                    (ends[1].to_real().position - ends[0].to_real().position).unsigned_abs() + 1;
+                (ends[0], ends[1], length)
+            })
+            .collect()
+    }
+
     pub fn indication(nucl1: FlatNucl, nucl2: FlatNucl, helices: &[Helix]) -> Vertices {
         let mut vertices = Vertices::new();
         let mut builder = Path::builder_with_attributes(2);
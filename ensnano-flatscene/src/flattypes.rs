@@ -370,6 +370,7 @@ impl FlatSelection {
                 Selection::BezierControlPoint { .. } => Self::Nothing,
                 Selection::BezierTengent { .. } => Self::Nothing,
                 Selection::BezierVertex(_) => Self::Nothing,
+                Selection::External3DObject(_) => Self::Nothing,
             }
         } else {
             Self::Nothing
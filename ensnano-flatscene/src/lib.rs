@@ -19,11 +19,13 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use ensnano_design::Nucl;
 use ensnano_interactor::{
-    application::{AppId, Application, Duration, Notification},
-    graphics::DrawArea,
+    application::{AppId, Application, Camera2D, Duration, Notification},
+    graphics::{Colormap, DesignChanges, DrawArea, StrandColoring},
+    keymap::Keymap,
     operation::*,
-    ActionMode, DesignOperation, PhantomElement, Selection, SelectionMode, StrandBuilder,
-    StrandBuildingStatus,
+    toasts::{self, OverlayMessage, Toasts},
+    ActionMode, CandidatesFilter, DesignOperation, InsertionPoint, PhantomElement, Selection,
+    SelectionMode, SimulationState, StrandBuilder, StrandBuildingStatus,
 };
 use ensnano_utils::wgpu;
 use ensnano_utils::winit;
@@ -44,7 +46,7 @@ mod view;
 use camera::{Camera, FitRectangle, Globals};
 use controller::Controller;
 use data::Data;
-pub use data::{DesignReader, NuclCollection};
+pub use data::{DesignReader, NuclCollection, ScaffoldSequenceInfo};
 use flattypes::*;
 use std::time::Instant;
 use view::View;
@@ -78,6 +80,14 @@ pub struct FlatScene<S: AppState> {
     splited: bool,
     old_state: S,
     requests: Arc<Mutex<dyn Requests>>,
+    /// The keyboard shortcuts, given to the controllers
+    keymap: Arc<Keymap>,
+    /// Avoid requesting the same candidates at each mouse movement
+    candidates_filter: CandidatesFilter,
+    /// The messages explaining why the last actions of the user were not performed
+    toasts: Toasts,
+    /// The messages that are currently drawn on top of the view
+    overlay_messages: Vec<OverlayMessage>,
 }
 
 impl<S: AppState> FlatScene<S> {
@@ -88,6 +98,7 @@ impl<S: AppState> FlatScene<S> {
         area: DrawArea,
         requests: Arc<Mutex<dyn Requests>>,
         initial_state: S,
+        keymap: Arc<Keymap>,
     ) -> Self {
         let mut ret = Self {
             view: Vec::new(),
@@ -102,6 +113,10 @@ impl<S: AppState> FlatScene<S> {
             splited: false,
             old_state: initial_state.clone(),
             requests: requests.clone(),
+            keymap,
+            candidates_filter: Default::default(),
+            toasts: Default::default(),
+            overlay_messages: Vec::new(),
         };
         ret.add_design(initial_state.get_design_reader(), requests);
         ret
@@ -144,6 +159,7 @@ impl<S: AppState> FlatScene<S> {
             camera_top,
             camera_bottom,
             self.splited,
+            self.keymap.clone(),
         );
         if !self.view.is_empty() {
             self.view[0] = view;
@@ -196,6 +212,14 @@ impl<S: AppState> FlatScene<S> {
     fn read_consequence(&mut self, consequence: controller::Consequence, new_state: Option<&S>) {
         let app_state = new_state.unwrap_or(&self.old_state);
         use controller::Consequence;
+        if app_state.get_simulation_state().is_runing() {
+            if let Some(edit) = consequence.design_edit() {
+                // The design cannot be modified during simulations, the request is not sent
+                self.toasts
+                    .push(toasts::blocked_during_simulation(edit), Instant::now());
+                return;
+            }
+        }
         match consequence {
             Consequence::Xover(nucl1, nucl2) => {
                 let (prime5_id, prime3_id) =
@@ -239,7 +263,12 @@ impl<S: AppState> FlatScene<S> {
                 self.data[self.selected_design]
                     .borrow_mut()
                     .set_free_end(free_end);
-                self.requests.lock().unwrap().new_candidates(candidates);
+                if let Some(candidates) = self
+                    .candidates_filter
+                    .filter(candidates, app_state.get_candidates())
+                {
+                    self.requests.lock().unwrap().new_candidates(candidates);
+                }
             }
             Consequence::CutFreeEnd(nucl, free_end) => {
                 let strand_id = self.data[self.selected_design].borrow().get_strand_id(nucl);
@@ -299,10 +328,12 @@ impl<S: AppState> FlatScene<S> {
                 } else {
                     phantom.map(Selection::Phantom)
                 };
-                self.requests
-                    .lock()
-                    .unwrap()
-                    .new_candidates(candidate.iter().cloned().collect())
+                if let Some(candidates) = self
+                    .candidates_filter
+                    .filter(candidate.into_iter().collect(), app_state.get_candidates())
+                {
+                    self.requests.lock().unwrap().new_candidates(candidates);
+                }
             }
             Consequence::Built => {
                 self.requests.lock().unwrap().suspend_op();
@@ -313,6 +344,9 @@ impl<S: AppState> FlatScene<S> {
             Consequence::FlipGroup(helix) => self.data[self.selected_design]
                 .borrow_mut()
                 .flip_group(helix),
+            Consequence::ReorderHelix { helix, new_rank } => self.data[self.selected_design]
+                .borrow_mut()
+                .reorder_helix(helix, new_rank),
             Consequence::FollowingSuggestion(nucl, double) => {
                 let nucl2 = self.data[self.selected_design]
                     .borrow()
@@ -452,17 +486,59 @@ impl<S: AppState> FlatScene<S> {
                     .lock()
                     .unwrap()
                     .apply_design_operation(DesignOperation::MoveBuilders(n));
-                self.requests.lock().unwrap().new_candidates(vec![]);
+                if let Some(candidates) = self
+                    .candidates_filter
+                    .filter(vec![], app_state.get_candidates())
+                {
+                    self.requests.lock().unwrap().new_candidates(candidates);
+                }
             }
-            Consequence::NewHelixCandidate(flat_helix) => self
-                .requests
-                .lock()
-                .unwrap()
-                .new_candidates(vec![Selection::Helix {
+            Consequence::NewHelixCandidate(flat_helix) => {
+                let candidate = Selection::Helix {
                     design_id: self.selected_design as u32,
                     helix_id: flat_helix.segment.helix_idx,
                     segment_id: flat_helix.segment.segment_idx,
-                }]),
+                };
+                if let Some(candidates) = self
+                    .candidates_filter
+                    .filter(vec![candidate], app_state.get_candidates())
+                {
+                    self.requests.lock().unwrap().new_candidates(candidates);
+                }
+            }
+            Consequence::ShiftScaffold(delta) => {
+                let info = self.data[self.selected_design]
+                    .borrow_mut()
+                    .shift_scaffold_sequence(delta);
+                if let Some(info) = info {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .update_opperation(Arc::new(ScaffoldShiftUpdate {
+                            scaffold_id: info.scaffold_id,
+                            shift: info.shift,
+                        }))
+                }
+            }
+            Consequence::ScaffoldShiftEnded => self.requests.lock().unwrap().suspend_op(),
+            Consequence::InsertionLength { nucl, length } => {
+                self.requests.lock().unwrap().apply_design_operation(
+                    DesignOperation::SetInsertionLength {
+                        insertion_point: InsertionPoint {
+                            nucl: nucl.to_real(),
+                            nucl_is_prime5_of_insertion: true,
+                        },
+                        length,
+                    },
+                )
+            }
+            Consequence::FlipDeletion(nucl) => {
+                self.requests.lock().unwrap().apply_design_operation(
+                    DesignOperation::FlipDeletions {
+                        nucls: vec![nucl.to_real()],
+                    },
+                )
+            }
             Consequence::PngExport(corner1, corner2) => {
                 let glob_png = Globals::from_selection_rectangle(corner1, corner2);
                 use chrono::Utc;
@@ -498,6 +574,13 @@ impl<S: AppState> FlatScene<S> {
             self.data[self.selected_design]
                 .borrow_mut()
                 .perform_update(&new_state, &self.old_state);
+            let overlay_messages = self
+                .toasts
+                .overlay_messages(new_state.get_simulation_state().is_runing(), Instant::now());
+            if overlay_messages != self.overlay_messages {
+                self.overlay_messages = overlay_messages.clone();
+                view.borrow_mut().set_overlay_messages(overlay_messages);
+            }
             self.old_state = new_state;
             let ret = view.borrow().needs_redraw();
             if ret {
@@ -569,7 +652,6 @@ impl<S: AppState> FlatScene<S> {
         let device = self.device.as_ref();
         let queue = self.queue.as_ref();
         println!("export to {png_name}");
-        use ensnano_utils::BufferDimensions;
         use std::io::Write;
 
         let size = wgpu::Extent3d {
@@ -588,67 +670,8 @@ impl<S: AppState> FlatScene<S> {
             .borrow_mut()
             .draw(&mut encoder, &texture_view, Some(PNG_SIZE), Some(glob));
 
-        // create a buffer and fill it with the texture
-        let extent = wgpu::Extent3d {
-            width: size.width,
-            height: size.height,
-            depth_or_array_layers: 1,
-        };
-        let buffer_dimensions =
-            BufferDimensions::new(extent.width as usize, extent.height as usize);
-        let buf_size = buffer_dimensions.padded_bytes_per_row * buffer_dimensions.height;
-        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            size: buf_size as u64,
-            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-            label: Some("staging_buffer"),
-        });
-        let buffer_copy_view = wgpu::ImageCopyBuffer {
-            buffer: &staging_buffer,
-            layout: wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: (buffer_dimensions.padded_bytes_per_row as u32)
-                    .try_into()
-                    .ok(),
-                rows_per_image: None,
-            },
-        };
-        let origin = wgpu::Origin3d { x: 0, y: 0, z: 0 };
-        let texture_copy_view = wgpu::ImageCopyTexture {
-            texture: &texture,
-            mip_level: 0,
-            origin,
-            aspect: Default::default(),
-        };
-
-        encoder.copy_texture_to_buffer(texture_copy_view, buffer_copy_view, extent);
-        queue.submit(Some(encoder.finish()));
-
-        let buffer_slice = staging_buffer.slice(..);
-        let buffer_future = buffer_slice.map_async(wgpu::MapMode::Read);
-        device.poll(wgpu::Maintain::Wait);
-
-        let pixels = async {
-            if let Ok(()) = buffer_future.await {
-                let pixels_slice = buffer_slice.get_mapped_range();
-                let mut pixels = Vec::with_capacity((size.height * size.width) as usize);
-                for chunck in pixels_slice.chunks(buffer_dimensions.padded_bytes_per_row) {
-                    for chunk in chunck.chunks(4) {
-                        // convert Bgra to Rgba
-                        pixels.push(chunk[2]);
-                        pixels.push(chunk[1]);
-                        pixels.push(chunk[0]);
-                        pixels.push(chunk[3]);
-                    }
-                }
-                drop(pixels_slice);
-                staging_buffer.unmap();
-                pixels
-            } else {
-                panic!("could not read fake texture");
-            }
-        };
-        let pixels = futures::executor::block_on(pixels);
+        let pixels =
+            ensnano_utils::readback::read_texture_as_rgba(device, queue, encoder, &texture, size);
         let mut png_encoder = png::Encoder::new(
             std::fs::File::create(png_name).unwrap(),
             PNG_SIZE.width,
@@ -660,7 +683,7 @@ impl<S: AppState> FlatScene<S> {
         let mut png_writer = png_encoder
             .write_header()
             .unwrap()
-            .into_stream_writer_with_size(buffer_dimensions.unpadded_bytes_per_row)
+            .into_stream_writer_with_size(4 * PNG_SIZE.width as usize)
             .unwrap();
 
         png_writer.write_all(pixels.as_slice()).unwrap();
@@ -681,6 +704,13 @@ impl<S: AppState> Application for FlatScene<S> {
                     v.borrow_mut().set_show_torsion(b);
                 }
             }
+            Notification::TorsionRange(_) => (),
+            Notification::ShowStrain(_) => (),
+            Notification::ShowDomainLengths(b) => {
+                for v in self.view.iter() {
+                    v.borrow_mut().set_show_domain_lengths(b);
+                }
+            }
             Notification::CameraTarget(_) => (),
             Notification::ClearDesigns => self.data[0].borrow_mut().clear_design(),
             Notification::Centering(_, _) => (),
@@ -718,13 +748,29 @@ impl<S: AppState> Application for FlatScene<S> {
                     .borrow_mut()
                     .redim_helices(selection)
             }
+            Notification::Compact2dLayout => self.data[self.selected_design]
+                .borrow_mut()
+                .compact_layout(),
             Notification::Fog(_) => (),
             Notification::WindowFocusLost => (),
             Notification::TeleportCamera(_) => (),
+            Notification::SmoothTeleportCamera(_) => (),
             Notification::NewStereographicCamera(_) => (),
             Notification::FlipSplitViews => self.controller[0].flip_split_views(),
+            Notification::ToggleSplitScene3D => (),
             Notification::HorizonAligned => (),
-            Notification::ScreenShot3D => (),
+            Notification::ScreenShot3D(_) => (),
+            Notification::ToggleOrthographicProjection => (),
+            Notification::CameraModeChanged(_) => (),
+            Notification::FlyMode(_) => (),
+            Notification::ExportAllCameras(_) => (),
+            Notification::TeleportCameras2D(cameras) => {
+                if let Some(controller) = self.controller.get_mut(self.selected_design) {
+                    controller.teleport_cameras(&cameras)
+                }
+            }
+            Notification::PlaceGroupPivot(_) => (),
+            Notification::IsolateSelection(_) => (),
         }
     }
 
@@ -764,6 +810,12 @@ impl<S: AppState> Application for FlatScene<S> {
     fn is_splited(&self) -> bool {
         self.splited
     }
+
+    fn get_cameras_2d(&self) -> Option<[Camera2D; 2]> {
+        self.controller
+            .get(self.selected_design)
+            .map(Controller::get_cameras_2d)
+    }
 }
 
 pub trait AppState: Clone {
@@ -779,6 +831,24 @@ pub trait AppState: Clone {
     fn is_changing_color(&self) -> bool;
     fn is_pasting(&self) -> bool;
     fn get_building_state(&self) -> Option<StrandBuildingStatus>;
+    /// Return true if the overview of the design must be drawn in a corner of the view
+    fn show_minimap(&self) -> bool;
+    /// The changes made to the design since it was last saved, if they must be highlighted
+    fn get_design_changes(&self) -> Option<&DesignChanges>;
+    fn get_simulation_state(&self) -> SimulationState;
+
+    /// The property according to which the non-scaffold strands are colored
+    fn get_strand_coloring(&self) -> StrandColoring;
+    fn get_strand_colormap(&self) -> Colormap;
+
+    fn design_changes_were_updated(&self, other: &Self) -> bool {
+        self.get_design_changes() != other.get_design_changes()
+    }
+
+    fn strand_coloring_was_updated(&self, other: &Self) -> bool {
+        self.get_strand_coloring() != other.get_strand_coloring()
+            || self.get_strand_colormap() != other.get_strand_colormap()
+    }
 }
 
 use ensnano_design::ultraviolet::Isometry2;
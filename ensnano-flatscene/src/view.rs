@@ -21,7 +21,7 @@ use super::data::{
 };
 use super::{CameraPtr, FlatIdx, FlatNucl, NuclCollection};
 use crate::{DrawArea, PhySize};
-use ensnano_design::Nucl;
+use ensnano_design::{ultraviolet::Vec2, Nucl};
 use ensnano_utils::bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
 use ensnano_utils::camera2d::Globals;
 use ensnano_utils::texture::Texture;
@@ -34,6 +34,8 @@ mod helix_view;
 use helix_view::{HelixView, StrandView};
 mod background;
 mod insertion;
+mod minimap;
+mod overlay_messages;
 mod rectangle;
 use super::FlatSelection;
 use ahash::RandomState;
@@ -41,11 +43,17 @@ use background::Background;
 pub use chars::TextDrawer;
 pub use circles::CircleInstance;
 use circles::{CircleDrawer, CircleKind};
-use ensnano_interactor::consts::SAMPLE_COUNT;
+use ensnano_interactor::consts::{
+    DELETION_COLOR, DOMAIN_BOUNDARY_COLOR, MODIFICATION_COLOR, MOVED_HELIX_COLOR, SAMPLE_COUNT,
+    SCAFFOLD_SEAM_COLOR,
+};
+use ensnano_interactor::toasts::OverlayMessage;
 use ensnano_utils::winit::dpi::PhysicalPosition;
 use ensnano_utils::{chars2d as chars, circles2d as circles};
-use insertion::InsertionDrawer;
 pub use insertion::{InsertionDescriptor, InsertionInstance};
+use insertion::{InsertionDrawer, InsertionGlyph};
+use minimap::{Minimap, MinimapArea};
+use overlay_messages::OverlayMessagesDrawer;
 use rectangle::Rectangle;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
@@ -63,6 +71,8 @@ pub struct View {
     helices_background: Vec<HelixView>,
     strands: Vec<StrandView>,
     pasted_strands: Vec<StrandView>,
+    /// The preview of the strands complementary to the domains being built
+    building_complement: Vec<StrandView>,
     helices_model: Vec<HelixModel>,
     models: DynamicBindGroup,
     globals_top: UniformBindGroup,
@@ -82,15 +92,34 @@ pub struct View {
     nucl_highlighter_bottom: CircleDrawer,
     rotation_widget: CircleDrawer,
     insertion_drawer: InsertionDrawer,
+    deletion_drawer: InsertionDrawer,
+    /// Marks the nucleotides that carry a chemical modification
+    modification_drawer: InsertionDrawer,
+    /// Marks the nucleotide receiving the first base of the scaffold sequence
+    seam_drawer: InsertionDrawer,
+    /// Marks the boundaries of the domains when `show_domain_lengths` is true
+    domain_boundary_drawer: InsertionDrawer,
+    /// The 5' end, the 3' end and the length of the domains of the visible strands
+    domains: Vec<(FlatNucl, FlatNucl, usize)>,
+    show_domain_lengths: bool,
     text_drawer_top: TextDrawer,
     text_drawer_bottom: TextDrawer,
+    /// The length of the domain being built. It is drawn separately from the other texts so that
+    /// it does not appear on exported png.
+    building_label_top: TextDrawer,
+    building_label_bottom: TextDrawer,
     show_sec: bool,
+    /// True when the position of the scaffold sequence is being edited, in which case the
+    /// sequence is shown even if `show_sec` is false
+    show_scaffold_sequence: bool,
     suggestions: Vec<(FlatNucl, FlatNucl)>,
     suggestions_view: Vec<StrandView>,
     selected_strands: Vec<StrandView>,
     candidate_strands: Vec<StrandView>,
     selected_helices: Vec<FlatIdx>,
     candidate_helices: Vec<FlatIdx>,
+    /// The helices that moved since the design was last saved
+    moved_helices: Vec<FlatIdx>,
     candidate_nucl: Vec<FlatNucl>,
     selected_nucl: Vec<FlatNucl>,
     suggestion_candidate: Option<(FlatNucl, FlatNucl)>,
@@ -102,6 +131,10 @@ pub struct View {
     nucl_collection: Arc<dyn NuclCollection>,
     edition_info: Option<EditionInfo>,
     hovered_nucl: Option<FlatNucl>,
+    minimap: Minimap,
+    show_minimap: bool,
+    /// The simulation banner and the messages explaining why an action was blocked
+    overlay_messages: OverlayMessagesDrawer,
 }
 
 impl NuclCollection for () {
@@ -205,6 +238,8 @@ impl View {
             CircleKind::RotationWidget,
         );
         let rectangle = Rectangle::new(&device, queue.clone());
+        let minimap = Minimap::new(device.clone(), queue.clone());
+        let overlay_messages = OverlayMessagesDrawer::new(device.clone(), queue.clone());
 
         let text_drawer_top = TextDrawer::new(
             ensnano_interactor::consts::PRINTABLE_CHARS,
@@ -218,12 +253,57 @@ impl View {
             queue.clone(),
             globals_bottom.get_layout(),
         );
+        let building_label_top = TextDrawer::new(
+            ensnano_interactor::consts::BUILDING_LABEL_CHARS,
+            device.clone(),
+            queue.clone(),
+            globals_top.get_layout(),
+        );
+        let building_label_bottom = TextDrawer::new(
+            ensnano_interactor::consts::BUILDING_LABEL_CHARS,
+            device.clone(),
+            queue.clone(),
+            globals_bottom.get_layout(),
+        );
 
         let insertion_drawer = InsertionDrawer::new(
+            device.clone(),
+            queue.clone(),
+            globals_top.get_layout(),
+            depth_stencil_state.clone(),
+            InsertionGlyph::Loop,
+        );
+
+        let deletion_drawer = InsertionDrawer::new(
+            device.clone(),
+            queue.clone(),
+            globals_top.get_layout(),
+            depth_stencil_state.clone(),
+            InsertionGlyph::Cross,
+        );
+
+        let modification_drawer = InsertionDrawer::new(
+            device.clone(),
+            queue.clone(),
+            globals_top.get_layout(),
+            depth_stencil_state.clone(),
+            InsertionGlyph::Pin,
+        );
+
+        let seam_drawer = InsertionDrawer::new(
+            device.clone(),
+            queue.clone(),
+            globals_top.get_layout(),
+            depth_stencil_state.clone(),
+            InsertionGlyph::Seam,
+        );
+
+        let domain_boundary_drawer = InsertionDrawer::new(
             device.clone(),
             queue.clone(),
             globals_top.get_layout(),
             depth_stencil_state,
+            InsertionGlyph::Seam,
         );
 
         Self {
@@ -234,6 +314,7 @@ impl View {
             helices_view: Vec::new(),
             strands: Vec::new(),
             pasted_strands: Vec::new(),
+            building_complement: Vec::new(),
             helices_model: Vec::new(),
             helices_background: Vec::new(),
             models,
@@ -255,18 +336,28 @@ impl View {
             rotation_widget,
             text_drawer_bottom,
             text_drawer_top,
+            building_label_top,
+            building_label_bottom,
             show_sec: false,
+            show_scaffold_sequence: false,
             suggestions: vec![],
             suggestions_view: vec![],
             selected_strands: vec![],
             candidate_strands: vec![],
             selected_helices: vec![],
             candidate_helices: vec![],
+            moved_helices: vec![],
             suggestion_candidate: None,
             torsions: HashMap::new(),
             show_torsion: false,
             rectangle,
             insertion_drawer,
+            deletion_drawer,
+            modification_drawer,
+            seam_drawer,
+            domain_boundary_drawer,
+            domains: vec![],
+            show_domain_lengths: false,
             groups: Default::default(),
             basis_map: Default::default(),
             nucl_collection: Arc::new(()),
@@ -274,6 +365,9 @@ impl View {
             selected_nucl: vec![],
             candidate_nucl: vec![],
             hovered_nucl: None,
+            minimap,
+            show_minimap: false,
+            overlay_messages,
         }
     }
 
@@ -282,16 +376,54 @@ impl View {
         self.was_updated = true;
     }
 
+    pub fn set_show_scaffold_sequence(&mut self, show: bool) {
+        if self.show_scaffold_sequence != show {
+            self.show_scaffold_sequence = show;
+            self.was_updated = true;
+        }
+    }
+
     pub fn set_show_torsion(&mut self, show: bool) {
         self.show_torsion = show;
         self.was_updated = true;
     }
 
+    pub fn set_show_domain_lengths(&mut self, show: bool) {
+        self.show_domain_lengths = show;
+        self.was_updated = true;
+    }
+
     pub fn set_splited(&mut self, splited: bool) {
         self.was_updated = true;
         self.splited = splited;
     }
 
+    pub fn set_overlay_messages(&mut self, messages: Vec<OverlayMessage>) {
+        self.overlay_messages.update(messages);
+        self.was_updated = true;
+    }
+
+    pub fn set_show_minimap(&mut self, show: bool) {
+        if self.show_minimap != show {
+            self.show_minimap = show;
+            self.was_updated = true;
+        }
+    }
+
+    /// Return true if the minimap of the top or bottom half of the view is displayed under
+    /// `position`.
+    pub fn is_on_minimap(&self, position: PhysicalPosition<f64>, bottom: bool) -> bool {
+        self.show_minimap
+            && MinimapArea::new(self.area_size, self.splited, bottom).contains(position)
+    }
+
+    /// Return the point of the design displayed under `position` on the minimap of the top or
+    /// bottom half of the view.
+    pub fn minimap_to_world(&self, position: PhysicalPosition<f64>, bottom: bool) -> Vec2 {
+        let area = MinimapArea::new(self.area_size, self.splited, bottom);
+        self.minimap.to_world(area, position)
+    }
+
     pub fn update_strand_building_info(&mut self, info: Option<EditionInfo>) {
         if info.as_ref().map(|i| i.nucl) != self.edition_info.as_ref().map(|i| i.nucl) {
             self.was_updated = true;
@@ -359,6 +491,8 @@ impl View {
         }
         self.models.update(self.helices_model.as_slice());
         self.helices = helices.to_vec();
+        self.minimap
+            .set_design_rectangle(super::data::fit_rectangle(helices));
         self.was_updated = true;
     }
 
@@ -415,6 +549,53 @@ impl View {
             }
         }
         self.insertion_drawer.new_instances(insertions);
+        self.domains = strands.iter().flat_map(|s| s.domains()).collect();
+        let boundaries = self
+            .domains
+            .iter()
+            .flat_map(|(prime5, prime3, _)| {
+                [
+                    helices[prime5.helix].domain_boundary_instance(
+                        prime5,
+                        false,
+                        DOMAIN_BOUNDARY_COLOR,
+                    ),
+                    helices[prime3.helix].domain_boundary_instance(
+                        prime3,
+                        true,
+                        DOMAIN_BOUNDARY_COLOR,
+                    ),
+                ]
+            })
+            .collect();
+        self.domain_boundary_drawer.new_instances(boundaries);
+        self.was_updated = true;
+    }
+
+    pub fn update_deletions(&mut self, deletions: &[FlatNucl], helices: &[Helix]) {
+        let instances = deletions
+            .iter()
+            .map(|n| helices[n.helix].deletion_instance(n, DELETION_COLOR))
+            .collect();
+        self.deletion_drawer.new_instances(instances);
+        self.was_updated = true;
+    }
+
+    pub fn update_modifications(&mut self, modifications: &[FlatNucl], helices: &[Helix]) {
+        let instances = modifications
+            .iter()
+            .map(|n| helices[n.helix].modification_instance(n, MODIFICATION_COLOR))
+            .collect();
+        self.modification_drawer.new_instances(instances);
+        self.was_updated = true;
+    }
+
+    pub fn update_scaffold_seam(&mut self, seam: Option<FlatNucl>, helices: &[Helix]) {
+        let instances = seam
+            .iter()
+            .map(|n| helices[n.helix].seam_instance(n, SCAFFOLD_SEAM_COLOR))
+            .collect();
+        self.seam_drawer.new_instances(instances);
         self.was_updated = true;
     }
 
@@ -449,11 +630,17 @@ impl View {
     }
 
     pub fn set_candidate_nucls(&mut self, nucls: Vec<FlatNucl>) {
-        self.candidate_nucl = nucls;
+        if self.candidate_nucl != nucls {
+            self.candidate_nucl = nucls;
+            self.was_updated = true;
+        }
     }
 
     pub fn set_selected_nucls(&mut self, nucls: Vec<FlatNucl>) {
-        self.selected_nucl = nucls;
+        if self.selected_nucl != nucls {
+            self.selected_nucl = nucls;
+            self.was_updated = true;
+        }
     }
 
     pub fn update_pasted_strand(&mut self, strand: &[Strand], helices: &[Helix]) {
@@ -473,6 +660,24 @@ impl View {
             .collect();
     }
 
+    pub fn update_building_complement(&mut self, strands: &[Strand], helices: &[Helix]) {
+        self.building_complement = strands
+            .iter()
+            .map(|strand| {
+                let mut complement = StrandView::new(self.device.clone(), self.queue.clone());
+                complement.update(
+                    strand,
+                    helices,
+                    &None,
+                    &self.camera_top,
+                    &self.camera_bottom,
+                );
+                complement
+            })
+            .collect();
+        self.was_updated = true;
+    }
+
     pub fn set_free_end(&mut self, free_end: Option<FreeEnd>) {
         self.free_end = free_end;
     }
@@ -495,6 +700,13 @@ impl View {
         self.candidate_helices = selection;
     }
 
+    pub fn set_moved_helices(&mut self, helices: Vec<FlatIdx>) {
+        if self.moved_helices != helices {
+            self.moved_helices = helices;
+            self.was_updated = true;
+        }
+    }
+
     pub fn center_selection(&mut self, selection: FlatSelection) -> Option<(FlatNucl, FlatNucl)> {
         self.camera_top.borrow_mut().zoom_closer();
         self.was_updated = true;
@@ -534,6 +746,8 @@ impl View {
                 },
             ) => {
                 self.helices[helix].make_visible(position, self.camera_top.clone());
+                let center = self.helices[helix].get_pivot(position);
+                self.camera_top.borrow_mut().set_center(center);
                 None
             }
             _ => None,
@@ -776,7 +990,16 @@ impl View {
         render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
         self.circle_drawer_top.draw(&mut render_pass);
         self.text_drawer_top.draw(&mut render_pass);
+        if !exporting_png {
+            self.building_label_top.draw(&mut render_pass);
+        }
         self.insertion_drawer.draw(&mut render_pass);
+        self.deletion_drawer.draw(&mut render_pass);
+        self.modification_drawer.draw(&mut render_pass);
+        self.seam_drawer.draw(&mut render_pass);
+        if self.show_domain_lengths {
+            self.domain_boundary_drawer.draw(&mut render_pass);
+        }
         render_pass.set_pipeline(&self.strand_pipeline);
         log::trace!("Draw strands..");
         for strand in self.strands.iter() {
@@ -787,6 +1010,9 @@ impl View {
         for strand in self.pasted_strands.iter() {
             strand.draw(&mut render_pass, bottom);
         }
+        for strand in self.building_complement.iter() {
+            strand.draw(&mut render_pass, bottom);
+        }
         log::trace!("..OK");
         log::trace!("Draw suggestion..");
         for suggestion in self.suggestions_view.iter() {
@@ -856,6 +1082,9 @@ impl View {
         for strand in self.pasted_strands.iter() {
             strand.draw_split(&mut render_pass, bottom);
         }
+        for strand in self.building_complement.iter() {
+            strand.draw_split(&mut render_pass, bottom);
+        }
         for suggestion in self.suggestions_view.iter() {
             suggestion.draw_split(&mut render_pass, bottom);
         }
@@ -959,7 +1188,14 @@ impl View {
             render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
             self.circle_drawer_bottom.draw(&mut render_pass);
             self.text_drawer_bottom.draw(&mut render_pass);
+            self.building_label_bottom.draw(&mut render_pass);
             self.insertion_drawer.draw(&mut render_pass);
+            self.deletion_drawer.draw(&mut render_pass);
+            self.modification_drawer.draw(&mut render_pass);
+            self.seam_drawer.draw(&mut render_pass);
+            if self.show_domain_lengths {
+                self.domain_boundary_drawer.draw(&mut render_pass);
+            }
             render_pass.set_pipeline(&self.strand_pipeline);
             for strand in self.strands.iter() {
                 strand.draw(&mut render_pass, bottom);
@@ -967,6 +1203,9 @@ impl View {
             for strand in self.pasted_strands.iter() {
                 strand.draw(&mut render_pass, bottom);
             }
+            for strand in self.building_complement.iter() {
+                strand.draw(&mut render_pass, bottom);
+            }
             for suggestion in self.suggestions_view.iter() {
                 suggestion.draw(&mut render_pass, bottom);
             }
@@ -1026,6 +1265,9 @@ impl View {
             for strand in self.pasted_strands.iter() {
                 strand.draw_split(&mut render_pass, bottom);
             }
+            for strand in self.building_complement.iter() {
+                strand.draw_split(&mut render_pass, bottom);
+            }
             for suggestion in self.suggestions_view.iter() {
                 suggestion.draw_split(&mut render_pass, bottom);
             }
@@ -1036,6 +1278,24 @@ impl View {
                 highlight.draw_split(&mut render_pass, bottom);
             }
         }
+        if self.show_minimap && !exporting_png {
+            self.draw_minimap(
+                encoder,
+                attachment,
+                resolve_target,
+                depth_texture_view,
+                false,
+            );
+            if self.splited {
+                self.draw_minimap(
+                    encoder,
+                    attachment,
+                    resolve_target,
+                    depth_texture_view,
+                    true,
+                );
+            }
+        }
         if !exporting_png {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
@@ -1060,10 +1320,74 @@ impl View {
                 }),
             });
             self.rectangle.draw(&mut render_pass);
+            self.overlay_messages.draw(&mut render_pass, self.area_size);
         }
         self.was_updated = false;
     }
 
+    /// Draw the minimap of the top or bottom half of the view.
+    fn draw_minimap(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        attachment: &wgpu::TextureView,
+        resolve_target: Option<&wgpu::TextureView>,
+        depth_texture_view: &wgpu::TextureView,
+        bottom: bool,
+    ) {
+        let area = MinimapArea::new(self.area_size, self.splited, bottom);
+        if area.is_empty() {
+            return;
+        }
+        self.minimap.update_camera(area);
+        let view_camera = if bottom {
+            self.camera_bottom.clone()
+        } else {
+            self.camera_top.clone()
+        };
+        self.minimap.update_viewport(&view_camera.borrow(), bottom);
+
+        let mut render_pass = minimap_render_pass(
+            encoder,
+            attachment,
+            resolve_target,
+            depth_texture_view,
+            area,
+        );
+        self.minimap.background_mut().draw(&mut render_pass);
+        drop(render_pass);
+
+        let mut render_pass = minimap_render_pass(
+            encoder,
+            attachment,
+            resolve_target,
+            depth_texture_view,
+            area,
+        );
+        render_pass.set_bind_group(0, self.minimap.get_globals().get_bindgroup(), &[]);
+        render_pass.set_bind_group(1, self.models.get_bindgroup(), &[]);
+        render_pass.set_pipeline(&self.helices_pipeline);
+        for background in self.helices_background.iter() {
+            background.draw(&mut render_pass);
+        }
+        for helix in self.helices_view.iter() {
+            helix.draw(&mut render_pass);
+        }
+        render_pass.set_pipeline(&self.strand_pipeline);
+        for strand in self.strands.iter() {
+            strand.draw(&mut render_pass, false);
+        }
+        drop(render_pass);
+
+        let mut render_pass = minimap_render_pass(
+            encoder,
+            attachment,
+            resolve_target,
+            depth_texture_view,
+            area,
+        );
+        self.minimap.viewport_mut(bottom).draw(&mut render_pass);
+    }
+
     /// Return all the circles that must be displayed to represent the flatscene.
     ///
     /// Currently these circles are:
@@ -1096,6 +1420,17 @@ impl View {
                 circles.push(circle)
             }
         }
+        for h_id in self.moved_helices.iter() {
+            if let Some(mut circle) = self
+                .helices
+                .get(h_id.0)
+                .and_then(|h| h.get_circle(camera, self.groups.as_ref()))
+            {
+                circle.set_radius(circle.radius * 1.2);
+                circle.set_color(MOVED_HELIX_COLOR);
+                circles.push(circle);
+            }
+        }
         for h_id in self
             .selected_helices
             .iter()
@@ -1244,7 +1579,7 @@ impl View {
                 text_drawer: &mut self.text_drawer_top,
                 groups: self.groups.as_ref(),
                 basis_map: self.basis_map.as_ref(),
-                show_seq: self.show_sec,
+                show_seq: self.show_sec || self.show_scaffold_sequence,
                 edition_info: &self.edition_info,
                 hovered_nucl: &self.hovered_nucl,
                 nucl_collection: self.nucl_collection.as_ref(),
@@ -1254,12 +1589,40 @@ impl View {
                 text_drawer: &mut self.text_drawer_bottom,
                 groups: self.groups.as_ref(),
                 basis_map: self.basis_map.as_ref(),
-                show_seq: self.show_sec,
+                show_seq: self.show_sec || self.show_scaffold_sequence,
                 edition_info: &self.edition_info,
                 hovered_nucl: &self.hovered_nucl,
                 nucl_collection: self.nucl_collection.as_ref(),
             })
         }
+
+        if self.show_domain_lengths {
+            for (prime5, prime3, length) in self.domains.iter() {
+                if let Some(h) = self.helices.get(prime5.helix.flat.0) {
+                    h.add_domain_length(
+                        &mut self.text_drawer_top,
+                        &self.camera_top,
+                        (prime5, prime3),
+                        *length,
+                    );
+                    h.add_domain_length(
+                        &mut self.text_drawer_bottom,
+                        &self.camera_bottom,
+                        (prime5, prime3),
+                        *length,
+                    );
+                }
+            }
+        }
+
+        self.building_label_top.clear();
+        self.building_label_bottom.clear();
+        if let Some(info) = self.edition_info.as_ref() {
+            if let Some(h) = self.helices.get(info.nucl.helix.flat.0) {
+                h.add_building_label(&mut self.building_label_top, &self.camera_top, info);
+                h.add_building_label(&mut self.building_label_bottom, &self.camera_bottom, info);
+            }
+        }
     }
 
     pub fn set_wheels(&mut self, wheels: Vec<CircleInstance>) {
@@ -1390,6 +1753,46 @@ fn strand_pipeline_descr(
     device.create_render_pipeline(&desc)
 }
 
+/// Begin a render pass that draws in the area of a minimap.
+fn minimap_render_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    attachment: &'a wgpu::TextureView,
+    resolve_target: Option<&'a wgpu::TextureView>,
+    depth_texture_view: &'a wgpu::TextureView,
+    area: MinimapArea,
+) -> wgpu::RenderPass<'a> {
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("minimap"),
+        color_attachments: &[wgpu::RenderPassColorAttachment {
+            view: attachment,
+            resolve_target,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: depth_texture_view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.),
+                store: true,
+            }),
+            stencil_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(0),
+                store: true,
+            }),
+        }),
+    });
+    render_pass.set_viewport(area.x, area.y, area.width, area.height, 0., 1.);
+    render_pass.set_scissor_rect(
+        area.x as u32,
+        area.y as u32,
+        area.width as u32,
+        area.height as u32,
+    );
+    render_pass
+}
+
 fn torsion_color(strength: f32) -> u32 {
     const RED_HUE: f32 = 0.;
     const BLUE_HUE: f32 = 240.;
@@ -16,9 +16,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use super::{CameraPtr, FlatNucl, FreeEnd, Helix, Strand};
+use ensnano_utils::bindgroup_manager::DynamicBuffer;
 use ensnano_utils::wgpu;
 use std::rc::Rc;
-use wgpu::{Buffer, Device, Queue, RenderPass};
+use wgpu::{Device, Queue, RenderPass};
 
 pub struct HelixView {
     vertex_buffer: DynamicBuffer,
@@ -233,65 +234,3 @@ impl StrandView {
         }
     }
 }
-
-struct DynamicBuffer {
-    buffer: Buffer,
-    capacity: usize,
-    length: u64,
-    device: Rc<Device>,
-    queue: Rc<Queue>,
-    usage: wgpu::BufferUsages,
-}
-
-impl DynamicBuffer {
-    pub fn new(
-        device: Rc<Device>,
-        queue: Rc<Queue>,
-        usage: wgpu::BufferUsages,
-        label: &str,
-    ) -> Self {
-        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some(label),
-            size: 0,
-            usage: usage | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-        let capacity = 0;
-        let length = 0;
-
-        Self {
-            device,
-            queue,
-            buffer,
-            capacity,
-            length,
-            usage,
-        }
-    }
-
-    /// Replace the data of the associated buffer.
-    pub fn update<I: bytemuck::Pod>(&mut self, data: &[I]) {
-        let mut bytes: Vec<u8> = bytemuck::cast_slice(data).into();
-        let length = bytes.len();
-        while bytes.len() % 4 != 0 {
-            bytes.push(0)
-        }
-        if self.capacity < bytes.len() {
-            self.length = length as u64;
-            self.buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some(&format!("capacity = {}", 2 * bytes.len())),
-                size: 2 * bytes.len() as u64,
-                usage: self.usage | wgpu::BufferUsages::COPY_DST,
-                mapped_at_creation: false,
-            });
-            self.capacity = 2 * bytes.len();
-        } else if self.length != length as u64 {
-            self.length = length as u64;
-        }
-        self.queue.write_buffer(&self.buffer, 0, bytes.as_slice());
-    }
-
-    pub fn get_slice(&self) -> wgpu::BufferSlice {
-        self.buffer.slice(..self.length)
-    }
-}
@@ -36,12 +36,28 @@ pub struct InsertionDrawer {
     number_instances: usize,
 }
 
+/// The shape drawn at each instance of an [InsertionDrawer]
+#[derive(Clone, Copy, Debug)]
+pub enum InsertionGlyph {
+    /// A loop, representing inserted nucleotides
+    Loop,
+    /// A cross, representing a deleted nucleotide
+    Cross,
+    /// A bar across a strand, marking the nucleotide that receives the first base of the
+    /// scaffold sequence
+    Seam,
+    /// A pin pointing away from the helix, marking a nucleotide that carries a chemical
+    /// modification
+    Pin,
+}
+
 impl InsertionDrawer {
     pub fn new(
         device: Rc<Device>,
         queue: Rc<Queue>,
         globals: &BindGroupLayout,
         depth_stencil_state: Option<DepthStencilState>,
+        glyph: InsertionGlyph,
     ) -> Self {
         let instances = DynamicBindGroup::new(device.clone(), queue.clone(), "insertion instances");
         let pipeline = insertion_pipeline(
@@ -50,7 +66,7 @@ impl InsertionDrawer {
             instances.get_layout(),
             depth_stencil_state,
         );
-        let vertices = make_vertices();
+        let vertices = make_vertices(glyph);
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: None,
             contents: bytemuck::cast_slice(&vertices.vertices),
@@ -151,18 +167,48 @@ impl InsertionInstance {
 
 type Vertices = lyon::tessellation::VertexBuffers<InsertionVertex, u16>;
 
-fn make_vertices() -> Vertices {
+fn make_vertices(glyph: InsertionGlyph) -> Vertices {
     let mut vertices = Vertices::new();
     let mut builder = Path::builder();
-    let origin = Point::new(0., 0.);
-    let left = Point::new(-1., 1.);
-    let right = Point::new(1., 1.);
+    match glyph {
+        InsertionGlyph::Loop => {
+            let origin = Point::new(0., 0.);
+            let left = Point::new(-1., 1.);
+            let right = Point::new(1., 1.);
 
-    builder.begin(origin);
-    builder.cubic_bezier_to(left, right, origin);
+            builder.begin(origin);
+            builder.cubic_bezier_to(left, right, origin);
+            builder.end(false);
+        }
+        InsertionGlyph::Cross => {
+            const HALF_WIDTH: f32 = 0.35;
+            builder.begin(Point::new(-HALF_WIDTH, -HALF_WIDTH));
+            builder.line_to(Point::new(HALF_WIDTH, HALF_WIDTH));
+            builder.end(false);
+            builder.begin(Point::new(-HALF_WIDTH, HALF_WIDTH));
+            builder.line_to(Point::new(HALF_WIDTH, -HALF_WIDTH));
+            builder.end(false);
+        }
+        InsertionGlyph::Seam => {
+            builder.begin(Point::new(0., -0.7));
+            builder.line_to(Point::new(0., 0.7));
+            builder.end(false);
+        }
+        InsertionGlyph::Pin => {
+            const HEAD_START: f32 = 1.1;
+            const HEAD_HALF_WIDTH: f32 = 0.3;
+            builder.begin(Point::new(0., 0.5));
+            builder.line_to(Point::new(0., HEAD_START));
+            builder.end(false);
+            builder.begin(Point::new(0., HEAD_START));
+            builder.line_to(Point::new(HEAD_HALF_WIDTH, HEAD_START + HEAD_HALF_WIDTH));
+            builder.line_to(Point::new(0., HEAD_START + 2. * HEAD_HALF_WIDTH));
+            builder.line_to(Point::new(-HEAD_HALF_WIDTH, HEAD_START + HEAD_HALF_WIDTH));
+            builder.end(true);
+        }
+    }
     let mut stroke_tess = lyon::tessellation::StrokeTessellator::new();
 
-    builder.end(false);
     let path = builder.build();
     stroke_tess
         .tessellate_path(
@@ -0,0 +1,190 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The minimap is an overview of the whole design drawn in the bottom right corner of the 2D
+//! view, on which the area seen by the camera is shown as a rectangle.
+//!
+//! The minimap has its own camera, fitted to the design. In split mode, each half of the view has
+//! its own minimap, showing the area seen by the camera of that half. Both minimaps share the
+//! same camera since the two halves have the same size.
+
+use super::rectangle::Rectangle;
+use super::{PhySize, Rc};
+use ensnano_design::ultraviolet::Vec2;
+use ensnano_utils::bindgroup_manager::UniformBindGroup;
+use ensnano_utils::camera2d::{Camera, FitRectangle, Globals};
+use ensnano_utils::wgpu::{Device, Queue};
+use ensnano_utils::winit::dpi::PhysicalPosition;
+use ensnano_utils::Ndc;
+
+/// The fraction of the width and of the height of a view that is occupied by its minimap.
+const MINIMAP_RATIO: f32 = 0.25;
+/// The distance, in pixels, between the minimap and the borders of the view.
+const MINIMAP_MARGIN: f32 = 10.;
+const MINIMAP_BACKGROUND_COLOR: [f32; 4] = [0.85, 0.85, 0.85, 0.9];
+const MINIMAP_VIEWPORT_COLOR: [f32; 4] = [0.26, 0.64, 0.85, 0.35];
+
+/// The area of the drawing surface occupied by a minimap, in pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct MinimapArea {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl MinimapArea {
+    /// The area of the minimap of the top or bottom half of a view of size `area_size`.
+    pub fn new(area_size: PhySize, splited: bool, bottom: bool) -> Self {
+        let view_height = if splited {
+            area_size.height as f32 / 2.
+        } else {
+            area_size.height as f32
+        };
+        let view_top = if splited && bottom { view_height } else { 0. };
+        let width = area_size.width as f32 * MINIMAP_RATIO;
+        let height = view_height * MINIMAP_RATIO;
+        Self {
+            x: area_size.width as f32 - width - MINIMAP_MARGIN,
+            y: view_top + view_height - height - MINIMAP_MARGIN,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x < 0. || self.y < 0. || self.width < 1. || self.height < 1.
+    }
+
+    pub fn contains(&self, position: PhysicalPosition<f64>) -> bool {
+        let x = position.x as f32;
+        let y = position.y as f32;
+        !self.is_empty()
+            && x >= self.x
+            && x <= self.x + self.width
+            && y >= self.y
+            && y <= self.y + self.height
+    }
+
+    /// The normalized coordinates of `position` in the minimap. The position is clamped to the
+    /// minimap so that the camera keeps following the cursor when it is dragged out of it.
+    fn normalized(&self, position: PhysicalPosition<f64>) -> (f32, f32) {
+        let x = (position.x as f32 - self.x) / self.width;
+        let y = (position.y as f32 - self.y) / self.height;
+        (x.max(0.).min(1.), y.max(0.).min(1.))
+    }
+}
+
+pub struct Minimap {
+    camera: Camera,
+    globals: UniformBindGroup,
+    background: Rectangle,
+    viewport_top: Rectangle,
+    viewport_bottom: Rectangle,
+    design_rectangle: FitRectangle,
+    need_fit: bool,
+}
+
+impl Minimap {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        let camera = Camera::new(Globals::default([1., 1.]), false);
+        let globals = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            camera.get_globals(),
+            "globals minimap",
+        );
+        let mut background =
+            Rectangle::with_color(&device, queue.clone(), MINIMAP_BACKGROUND_COLOR);
+        background.update_corners(Some([Ndc { x: -1., y: -1. }, Ndc { x: 1., y: 1. }]));
+        Self {
+            camera,
+            globals,
+            background,
+            viewport_top: Rectangle::with_color(&device, queue.clone(), MINIMAP_VIEWPORT_COLOR),
+            viewport_bottom: Rectangle::with_color(&device, queue, MINIMAP_VIEWPORT_COLOR),
+            design_rectangle: FitRectangle::INITIAL_RECTANGLE,
+            need_fit: true,
+        }
+    }
+
+    pub fn set_design_rectangle(&mut self, rectangle: FitRectangle) {
+        self.design_rectangle = rectangle;
+        self.need_fit = true;
+    }
+
+    /// Fit the camera of the minimap to the design, and send its globals to the GPU if they
+    /// were modified.
+    pub fn update_camera(&mut self, area: MinimapArea) {
+        let resolution = self.camera.get_globals().resolution;
+        if resolution != [area.width, area.height] {
+            self.camera.resize(area.width, area.height);
+            self.need_fit = true;
+        }
+        if self.need_fit {
+            self.camera.fit_center(self.design_rectangle);
+            self.need_fit = false;
+        }
+        if let Some(globals) = self.camera.update() {
+            self.globals.update(globals);
+        }
+    }
+
+    /// Update the rectangle showing the area seen by `view_camera`.
+    pub fn update_viewport(&mut self, view_camera: &Camera, bottom: bool) {
+        let mut corners = FitRectangle::new();
+        for (x, y) in [(0., 0.), (1., 0.), (0., 1.), (1., 1.)] {
+            let (x_world, y_world) = view_camera.norm_screen_to_world(x, y);
+            let (x_minimap, y_minimap) = self.camera.world_to_norm_screen(x_world, y_world);
+            corners.add_point(Vec2::new(2. * x_minimap - 1., 1. - 2. * y_minimap));
+        }
+        let ndc_corners = corners
+            .min_x
+            .zip(corners.min_y)
+            .zip(corners.max_x.zip(corners.max_y))
+            .map(|((min_x, min_y), (max_x, max_y))| {
+                [Ndc { x: min_x, y: min_y }, Ndc { x: max_x, y: max_y }]
+            });
+        if bottom {
+            self.viewport_bottom.update_corners(ndc_corners);
+        } else {
+            self.viewport_top.update_corners(ndc_corners);
+        }
+    }
+
+    /// The point of the design that is displayed under `position` on the minimap.
+    pub fn to_world(&self, area: MinimapArea, position: PhysicalPosition<f64>) -> Vec2 {
+        let (x, y) = area.normalized(position);
+        self.camera.norm_screen_to_world(x, y).into()
+    }
+
+    pub fn get_globals(&self) -> &UniformBindGroup {
+        &self.globals
+    }
+
+    pub fn background_mut(&mut self) -> &mut Rectangle {
+        &mut self.background
+    }
+
+    pub fn viewport_mut(&mut self, bottom: bool) -> &mut Rectangle {
+        if bottom {
+            &mut self.viewport_bottom
+        } else {
+            &mut self.viewport_top
+        }
+    }
+}
@@ -0,0 +1,174 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Draws the messages shown on top of the view: the banner displayed while a simulation is
+//! running and the transient messages explaining why an action was blocked.
+//!
+//! The messages are stacked at the top of the drawing area, centered horizontally. They are drawn
+//! with their own globals, for which world coordinates are pixels from the top left corner of the
+//! drawing area.
+
+use super::chars::{Line, Sentence, TextDrawer};
+use super::rectangle::Rectangle;
+use super::{Device, Queue, Rc, UniformBindGroup};
+use crate::PhySize;
+use ensnano_design::ultraviolet::{Rotor2, Vec2, Vec4};
+use ensnano_interactor::consts::{MAX_TOASTS, SIMULATION_BANNER_COLOR, TOAST_BACKGROUND_COLOR};
+use ensnano_interactor::toasts::{OverlayMessage, OverlayMessageKind};
+use ensnano_utils::camera2d::Globals;
+use ensnano_utils::{wgpu, Ndc};
+
+/// The distance, in pixels, between the top of the drawing area and the first message
+const TOP_MARGIN: f32 = 10.;
+/// The vertical space, in pixels, between two messages
+const SPACING: f32 = 6.;
+/// The distance, in pixels, between the text and the borders of a message
+const PADDING: f32 = 6.;
+const TEXT_SIZE: f32 = 16.;
+/// An estimation of the width of a character, relatively to the size of the text
+const CHAR_WIDTH_RATIO: f32 = 0.6;
+/// The maximum number of messages drawn at the same time: the banner and the toasts
+const MAX_MESSAGES: usize = MAX_TOASTS + 1;
+
+pub struct OverlayMessagesDrawer {
+    messages: Vec<OverlayMessage>,
+    /// The size of the drawing area for which the messages were last laid out
+    area_size: Option<PhySize>,
+    need_update: bool,
+    globals: UniformBindGroup,
+    backgrounds: Vec<Rectangle>,
+    text_drawer: TextDrawer,
+}
+
+impl OverlayMessagesDrawer {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        let globals = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            &screen_space_globals(1., 1.),
+            "globals overlay messages",
+        );
+        let backgrounds = (0..MAX_MESSAGES)
+            .map(|_| Rectangle::with_color(&device, queue.clone(), TOAST_BACKGROUND_COLOR))
+            .collect();
+        // The messages are written in plain English, any printable ascii character may appear
+        let chars: Vec<char> = (' '..='~').collect();
+        let text_drawer = TextDrawer::new(&chars, device, queue, globals.get_layout());
+        Self {
+            messages: Vec::new(),
+            area_size: None,
+            need_update: false,
+            globals,
+            backgrounds,
+            text_drawer,
+        }
+    }
+
+    /// Set the messages to be drawn, from top to bottom.
+    pub fn update(&mut self, mut messages: Vec<OverlayMessage>) {
+        messages.truncate(MAX_MESSAGES);
+        if self.messages != messages {
+            self.messages = messages;
+            self.need_update = true;
+        }
+    }
+
+    /// Draw the messages in a drawing area of size `area_size`.
+    ///
+    /// The viewport of `render_pass` must be the whole drawing area.
+    pub fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>, area_size: PhySize) {
+        if self.messages.is_empty() {
+            return;
+        }
+        if self.need_update || self.area_size != Some(area_size) {
+            self.layout(area_size);
+            self.need_update = false;
+            self.area_size = Some(area_size);
+        }
+        for background in self.backgrounds.iter_mut().take(self.messages.len()) {
+            background.draw(render_pass);
+        }
+        render_pass.set_bind_group(0, self.globals.get_bindgroup(), &[]);
+        self.text_drawer.draw(render_pass);
+    }
+
+    /// Compute the corners of the backgrounds and the position of the texts of the messages.
+    fn layout(&mut self, area_size: PhySize) {
+        let (width, height) = (area_size.width as f32, area_size.height as f32);
+        self.globals.update(&screen_space_globals(width, height));
+        self.text_drawer.clear();
+
+        // Coordinates in pixels, with the origin at the top left corner of the drawing area
+        let mut top = TOP_MARGIN;
+        for (message, background) in self.messages.iter().zip(self.backgrounds.iter_mut()) {
+            let (background_color, text_color) = match message.kind {
+                OverlayMessageKind::Banner => (SIMULATION_BANNER_COLOR, Vec4::new(0., 0., 0., 1.)),
+                OverlayMessageKind::Toast => (TOAST_BACKGROUND_COLOR, Vec4::new(1., 1., 1., 1.)),
+            };
+            let background_color = Vec4::from(background_color);
+            let opacity = message.opacity.max(0.).min(1.);
+            background.set_color([
+                background_color.x,
+                background_color.y,
+                background_color.z,
+                background_color.w * opacity,
+            ]);
+            // The characters are opaque, they fade out by taking the color of the background
+            let text_color = background_color * (1. - opacity) + text_color * opacity;
+
+            let nb_chars = message.text.chars().count() as f32;
+            let box_width = 2. * PADDING + CHAR_WIDTH_RATIO * TEXT_SIZE * nb_chars;
+            let box_height = 2. * PADDING + TEXT_SIZE;
+            let (left, right) = ((width - box_width) / 2., (width + box_width) / 2.);
+            let bottom = top + box_height;
+            let to_ndc = |x: f32, y: f32| Ndc {
+                x: 2. * x / width - 1.,
+                y: 1. - 2. * y / height,
+            };
+            background.update_corners(Some([to_ndc(left, top), to_ndc(right, bottom)]));
+
+            let sentence = Sentence {
+                text: &message.text,
+                size: TEXT_SIZE,
+                z_index: 0,
+                color: Vec4::new(text_color.x, text_color.y, text_color.z, 1.),
+                rotation: Rotor2::identity(),
+                symetry: Vec2::one(),
+            };
+            let center = Vec2::new(width / 2., (top + bottom) / 2.);
+            // A line without direction does not constrain the position of the text
+            let no_bound = Line {
+                origin: center,
+                direction: Vec2::zero(),
+            };
+            self.text_drawer.add_sentence(sentence, center, no_bound);
+            top = bottom + SPACING;
+        }
+    }
+}
+
+/// Globals for which the world coordinates are pixels from the top left corner of a drawing area
+/// of size `width` x `height`.
+fn screen_space_globals(width: f32, height: f32) -> Globals {
+    Globals {
+        resolution: [width, height],
+        scroll_offset: [width / 2., height / 2.],
+        zoom: 1.,
+        tilt: 0.,
+        symetry: Vec2::one(),
+    }
+}
@@ -23,6 +23,15 @@ pub const NB_TURN_STEP: f32 = 0.05;
 
 pub const NB_TURN_SLIDER_SPACING: u16 = 3;
 
+/// The number of phantom nucleotides drawn on each side of the origin of the helices of a grid
+/// that uses the default length
+pub const DEFAULT_PHANTOM_LENGTH: usize = 100;
+pub const MAX_PHANTOM_LENGTH: f32 = ensnano_interactor::PHANTOM_RANGE as f32;
+pub const PHANTOM_LENGTH_STEP: f32 = 10.;
+
+/// The number of cross-overs listed in the strain report
+pub const NB_STRAIN_REPORT_XOVERS: usize = 20;
+
 use iced::Color;
 pub const fn innactive_color() -> Color {
     Color::from_rgb(0.6, 0.6, 0.6)
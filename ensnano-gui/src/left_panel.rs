@@ -15,8 +15,14 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use ensnano_interactor::{graphics::HBoundDisplay, EquadiffSolvingMethod};
+use ensnano_interactor::{
+    graphics::{
+        CameraMode, Colormap, HBondFilter, HBoundDisplay, IsolateSelection, StrandColoring,
+    },
+    EquadiffSolvingMethod,
+};
 use ensnano_organizer::{Organizer, OrganizerMessage, OrganizerTree};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use iced::{
@@ -31,11 +37,19 @@ use iced_winit::winit::{
     dpi::{LogicalPosition, LogicalSize},
     event::ModifiersState,
 };
-use ultraviolet::Vec3;
+use ultraviolet::{Isometry3, Rotor3, Vec3};
 
 use ensnano_design::{
+    design_check::CheckParameters,
+    design_operations::{
+        DuplicationPattern, RecolorPalette, RecolorScope, ScaffoldRoute, ScaffoldRoutingParameters,
+        ScaffoldRoutingPattern, StapleFillingParameters,
+    },
     elements::{DnaElement, DnaElementKey},
-    BezierPathId, CameraId,
+    modifications::{Modification, ModificationKind},
+    staple_report::StapleBounds,
+    BezierPathId, BezierPlaneId, CameraId, External3DObjectAnchor, External3DObjectId, Nucl,
+    XoverPlacementParameters,
 };
 use ensnano_interactor::{
     graphics::{Background3D, RenderingMode},
@@ -50,10 +64,14 @@ use super::{
         dark_icon as icon, icon_to_char, LightIcon as MaterialIcon, DARK_ICONFONT as ICONFONT,
     },
     slider_style::DesactivatedSlider,
-    text_btn, AppState, FogParameters as Fog, OverlayType, Requests, UiSize,
+    text_btn, AppState, FogParameters as Fog, OverlayType, RecentFile, Requests, UiSize,
+    UndoHistory,
 };
 
-use ensnano_design::{grid::GridTypeDescr, ultraviolet, NamedParameter};
+use ensnano_design::{
+    grid::{GridId, GridTypeDescr},
+    ultraviolet, NamedParameter,
+};
 mod color_picker;
 use color_picker::ColorPicker;
 mod sequence_input;
@@ -65,14 +83,16 @@ mod tabs;
 use crate::{consts::*, left_panel::tabs::RevolutionParameterId};
 mod contextual_panel;
 mod export_menu;
+mod quick_open;
 use contextual_panel::{ContextualPanel, InstanciatedValue, ValueKind};
 use export_menu::ExportMenu;
+use quick_open::QuickOpen;
 
 use ensnano_interactor::{CheckXoversParameter, HyperboloidRequest, Selection};
 pub use tabs::revolution_tab::*;
 use tabs::{
-    CameraShortcut, CameraTab, EditionTab, GridTab, ParametersTab, PenTab, SequenceTab,
-    SimulationTab,
+    CameraShortcut, CameraTab, EditionTab, GridTab, ParametersTab, PenTab, RecolorPaletteChoice,
+    RecolorScopeChoice, SequenceTab, SimulationTab,
 };
 
 pub(super) const ENSNANO_FONT: iced::Font = iced::Font::External {
@@ -88,10 +108,7 @@ pub struct LeftPanel<R: Requests, S: AppState> {
     logical_position: LogicalPosition<f64>,
     #[allow(dead_code)]
     open_color: button::State,
-    sequence_input: SequenceInput,
     requests: Arc<Mutex<R>>,
-    #[allow(dead_code)]
-    show_torsion: bool,
     selected_tab: usize,
     organizer: Organizer<DnaElement>,
     ui_size: UiSize,
@@ -107,6 +124,7 @@ pub struct LeftPanel<R: Requests, S: AppState> {
     camera_shortcut: CameraShortcut,
     application_state: S,
     exports_menu: ExportMenu,
+    quick_open: QuickOpen,
 }
 
 #[derive(Debug, Clone)]
@@ -117,9 +135,17 @@ pub enum Message<S: AppState> {
     MakeGrids,
     SequenceChanged(String),
     SequenceFileRequested,
+    /// Apply the sequence of the sequence input despite its mismatches with the scaffold
+    ForceSequence,
+    ClearSequence,
+    /// The result of the last attempt to apply the sequence: the number of mismatches with the
+    /// scaffold if the sequence was refused, `None` if it was applied
+    SequenceChecked(Option<usize>),
     ColorPicked(Color),
     HsvSatValueChanged(f64, f64),
     StrandNameChanged(usize, String),
+    StrandNoteChanged(usize, String),
+    StrandKeepColorChanged(usize, bool),
     FinishChangingColor,
     HueChanged(f64),
     NewGrid(GridTypeDescr),
@@ -128,16 +154,23 @@ pub enum Message<S: AppState> {
     PositionHelicesChanged(String),
     LengthHelicesChanged(String),
     ScaffoldPositionInput(String),
-    #[allow(dead_code)]
     ShowTorsion(bool),
+    TorsionRange(Option<f32>),
+    ShowStrain(bool),
+    ShowDomainLengths(bool),
     FogRadius(f32),
     FogLength(f32),
+    FogFollowSelection(bool),
+    /// Fog parameters that were set without using the panel, for example with a preset
+    NewFogParameters(Fog),
     SimRequest,
     DescreteValue {
         factory_id: FactoryId,
         value_id: ValueId,
         value: f32,
     },
+    /// The slider of a value of the factory was released
+    DescreteValueReleased(FactoryId),
     NewHyperboloid,
     FinalizeHyperboloid,
     RollTargeted(bool),
@@ -146,6 +179,7 @@ pub enum Message<S: AppState> {
     VolumeExclusion(bool),
     TabSelected(usize),
     OrganizerMessage(OrganizerMessage<DnaElement>),
+    OrganizerKeyPressed(iced::keyboard::KeyCode),
     ModifiersChanged(ModifiersState),
     UiSizeChanged(UiSize),
     UiSizePicked(UiSize),
@@ -158,25 +192,43 @@ pub enum Message<S: AppState> {
     ToggleVisibility(bool),
     AllVisible,
     Redim2dHelices(bool),
+    Compact2dLayout,
+    PivotOnSelection,
     InvertScroll(bool),
+    RememberWorkspace(bool),
     BrownianMotion(bool),
     Nothing,
     CancelHyperboloid,
     SelectionValueChanged(usize, String),
     SetSmallSpheres(bool),
-    ScaffoldIdSet(usize, bool),
+    SetGridVisibility(bool),
+    SoloGrids,
+    /// Give or remove the scaffold role to a strand
+    ScaffoldRoleSet(usize, bool),
     //NewScaffoldInfo(Option<ScaffoldInfo>),
     SelectScaffold,
+    /// Chose the scaffold targeted by the operations of the sequence tab
+    ScaffoldTargetPicked(usize),
     ForceHelp,
     ShowTutorial,
     RenderingMode(RenderingMode),
     Background3D(Background3D),
     OpenLink(&'static str),
     NewApplicationState(S),
+    NewUndoHistory(UndoHistory),
+    NewRecentFiles(Vec<RecentFile>),
+    ToggleQuickOpen,
+    QuickOpenFilterInput(String),
+    /// Open the first recent design matching the filter
+    QuickOpenSubmitted,
+    OpenRecentFile(PathBuf),
+    /// Undo `-n` operations if `n` is negative, or redo `n` operations otherwise
+    HistoryEntryClicked(isize),
     FogChoice(tabs::FogChoice),
     SetScaffoldSeqButtonPressed,
     OptimizeScaffoldShiftPressed,
     ResetSimulation,
+    StepSimulation,
     EditCameraName(String),
     SubmitCameraName,
     StartEditCameraName(CameraId),
@@ -191,14 +243,31 @@ pub enum Message<S: AppState> {
     FollowStereographicCamera(bool),
     ShowStereographicCamera(bool),
     ShowHBonds(HBoundDisplay),
+    HBondFilter(HBondFilter),
+    Show2dMinimap(bool),
+    ShowAnchors(bool),
+    LocalPhantoms(bool),
+    ShowNucleotideRuler(bool),
+    FullDetail(bool),
+    StrandColoring(StrandColoring),
+    StrandColormap(Colormap),
     RainbowScaffold(bool),
     StopSimulation,
     FinishRelaxation,
     StartTwist,
+    SetGridType(GridTypeDescr),
     NewDnaParameters(NamedParameter),
     SetExpandInsertions(bool),
+    IsolateSelection(IsolateSelection),
+    CameraMode(CameraMode),
     InsertionLengthInput(String),
     InsertionLengthSubmitted,
+    ModificationKindPicked(Nucl, ModificationKind),
+    ModificationTextInput(String),
+    ModificationTextSubmitted(Nucl),
+    RemoveModification(Nucl),
+    SetExternal3DObjectVisibility(External3DObjectId, bool),
+    AttachExternal3DObject(External3DObjectId, Option<External3DObjectAnchor>),
     NewBezierPlane,
     StartBezierPath,
     TurnPathIntoGrid {
@@ -210,6 +279,15 @@ pub enum Message<S: AppState> {
         path_id: BezierPathId,
         cyclic: bool,
     },
+    StartEditBezierPlaneName(BezierPlaneId),
+    EditBezierPlaneName(String),
+    SubmitBezierPlaneName,
+    DeleteBezierPlane(BezierPlaneId),
+    DuplicateBezierPlane {
+        plane_id: BezierPlaneId,
+        offset: Vec3,
+    },
+    SetCopyPathsOfDuplicatedPlane(bool),
     Export(ExportType),
     CurveBuilderPicked(CurveDescriptorBuilder<S>),
     RevolutionEquadiffSolvingMethodPicked(EquadiffSolvingMethod),
@@ -219,8 +297,52 @@ pub enum Message<S: AppState> {
     },
     InitRevolutionRelaxation,
     CancelExport,
+    ExportSelectionOnly(bool),
+    ExportObj,
+    ObjSphereSubdivision(u32),
+    DuplicateInPattern,
+    AlignHelices,
+    ProtectScaffoldFromXovers(bool),
+    PlaceXoversBetweenHelices,
+    ScaffoldRoutingSpiral(bool),
+    ScaffoldRoutingClearExisting(bool),
+    RouteScaffold,
+    StapleDomainLengthsInput(String),
+    NickAtSelectedNucls(bool),
+    FillStaples,
+    RecolorScopePicked(RecolorScopeChoice),
+    RecolorPalettePicked(RecolorPaletteChoice),
+    RecolorColorsInput(String),
+    RecolorStaples,
+    SetSelectedHelicesParameters(NamedParameter),
+    ResetSelectedHelicesParameters,
+    AnchorStrandEndsOnly(bool),
+    AnchorSelectedStrands,
+    NamedSelectionNameInput(String),
+    SaveNamedSelection,
+    RecallNamedSelection(usize),
+    RenameNamedSelection(usize),
+    DeleteNamedSelection(usize),
+    UpdateStapleReport,
+    SortStapleReport(tabs::StapleReportColumn),
+    StapleReportRowClicked(usize),
+    CheckDesign,
+    DiagnosticClicked(usize),
+    StrainedXoverClicked(usize),
+    ToggleXoverChecked(usize),
     LoadSvgFile,
+    ImportOxDnaConfiguration,
+    HideIdealizedDesign(bool),
+    LoadReferenceDesign,
+    RemoveReferenceDesign,
+    ShowReferenceDesign(bool),
+    ShowDesignChanges(bool),
     ScreenShot3D,
+    PngWidthInput(String),
+    PngHeightInput(String),
+    TransparentPng(bool),
+    ExportAllCameras,
+    ToggleOrthographicProjection,
     IncrRevolutionShift,
     DecrRevolutionShift,
 }
@@ -251,9 +373,7 @@ impl<R: Requests, S: AppState> LeftPanel<R, S> {
             logical_size,
             logical_position,
             open_color: Default::default(),
-            sequence_input: SequenceInput::new(),
             requests,
-            show_torsion: false,
             selected_tab,
             organizer,
             ui_size,
@@ -269,6 +389,7 @@ impl<R: Requests, S: AppState> LeftPanel<R, S> {
             camera_shortcut: CameraShortcut::new(),
             application_state: state.clone(),
             exports_menu: Default::default(),
+            quick_open: Default::default(),
         }
     }
 
@@ -311,6 +432,27 @@ impl<R: Requests, S: AppState> LeftPanel<R, S> {
             OrganizerMessage::NewTree(tree) => {
                 self.requests.lock().unwrap().update_organizer_tree(tree)
             }
+            OrganizerMessage::NewGroupColor { group_id, color } => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_group_color(group_id, color),
+            OrganizerMessage::NewGroupLock { group_id, locked } => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_group_locked(group_id, locked),
+            OrganizerMessage::NewPinnedGroups(group_ids) => {
+                let sim_state = self.application_state.get_simulation_state();
+                if sim_state.simulating_helices() || sim_state.is_paused() {
+                    self.requests.lock().unwrap().set_pinned_groups(group_ids)
+                }
+            }
+            OrganizerMessage::NewElementName(key, name) => {
+                if let DnaElementKey::Strand(s_id) = key {
+                    self.requests.lock().unwrap().set_strand_name(s_id, name)
+                }
+            }
             OrganizerMessage::Candidates(candidates) => self
                 .requests
                 .lock()
@@ -337,12 +479,23 @@ impl<R: Requests, S: AppState> LeftPanel<R, S> {
     }
 
     pub fn has_keyboard_priority(&self) -> bool {
-        self.sequence_input.has_keyboard_priority()
+        self.edition_tab.has_keyboard_priority()
             || self.contextual_panel.has_keyboard_priority()
             || self.organizer.has_keyboard_priority()
             || self.sequence_tab.has_keyboard_priority()
             || self.camera_shortcut.has_keyboard_priority()
+            || self.pen_tab.has_keyboard_priority()
             || self.revolution_tab.has_keyboard_priority()
+            || self.quick_open.has_keyboard_priority()
+    }
+
+    fn update_staple_report(&mut self) {
+        let bounds = self.sequence_tab.get_staple_bounds();
+        let report = self
+            .application_state
+            .get_reader()
+            .get_staple_stats(&bounds);
+        self.sequence_tab.set_staple_report(report);
     }
 }
 
@@ -356,12 +509,37 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 self.requests
                     .lock()
                     .unwrap()
-                    .set_selected_strand_sequence(s.clone());
-                self.sequence_input.update_sequence(s);
+                    .set_selected_strand_sequence(s.clone(), false);
+                self.edition_tab.update_sequence(s);
+            }
+            Message::ForceSequence => {
+                self.edition_tab.force_sequence();
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_selected_strand_sequence(self.edition_tab.get_sequence(), true);
+            }
+            Message::ClearSequence => {
+                self.edition_tab.update_sequence(String::new());
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .clear_selected_strands_sequence();
+            }
+            Message::SequenceChecked(nb_mismatches) => {
+                self.edition_tab.set_sequence_check(nb_mismatches)
             }
             Message::StrandNameChanged(s_id, name) => {
                 self.requests.lock().unwrap().set_strand_name(s_id, name)
             }
+            Message::StrandNoteChanged(s_id, note) => {
+                self.requests.lock().unwrap().set_strand_note(s_id, note)
+            }
+            Message::StrandKeepColorChanged(s_id, keep_color) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_strand_keep_color(s_id, keep_color),
             Message::SequenceFileRequested => {
                 let dialog = rfd::AsyncFileDialog::new().pick_file();
                 let requests = self.requests.clone();
@@ -374,7 +552,7 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                                 requests
                                     .lock()
                                     .unwrap()
-                                    .set_selected_strand_sequence(content);
+                                    .set_selected_strand_sequence(content, false);
                             }
                         }
                     };
@@ -446,12 +624,32 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
             }
             Message::ScaffoldPositionInput(position_str) => {
                 if let Some(n) = self.sequence_tab.update_pos_str(position_str) {
-                    self.requests.lock().unwrap().set_scaffold_shift(n);
+                    if let Some(scaffold_id) = self.sequence_tab.get_target_scaffold() {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .set_scaffold_shift(scaffold_id, n);
+                    }
                 }
             }
             Message::ShowTorsion(b) => {
                 self.requests.lock().unwrap().set_torsion_visibility(b);
-                self.show_torsion = b;
+                self.camera_tab.show_torsion = b;
+            }
+            Message::TorsionRange(range) => {
+                self.camera_tab.set_torsion_range(range);
+                self.requests.lock().unwrap().set_torsion_range(range);
+            }
+            Message::ShowStrain(b) => {
+                self.requests.lock().unwrap().set_strain_visibility(b);
+                self.camera_tab.show_strain = b;
+            }
+            Message::ShowDomainLengths(b) => {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_domain_lengths_visibility(b);
+                self.camera_tab.show_domain_lengths = b;
             }
             Message::FogLength(length) => {
                 self.camera_tab.fog_length(length);
@@ -463,6 +661,12 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 let request = self.camera_tab.get_fog_request();
                 self.requests.lock().unwrap().set_fog_parameters(request);
             }
+            Message::FogFollowSelection(follow_selection) => {
+                self.camera_tab.fog_follow_selection(follow_selection);
+                let request = self.camera_tab.get_fog_request();
+                self.requests.lock().unwrap().set_fog_parameters(request);
+            }
+            Message::NewFogParameters(fog) => self.camera_tab.set_fog(&fog),
             Message::SimRequest => {
                 if self.application_state.get_simulation_state().is_rolling() {
                     self.requests.lock().unwrap().stop_simulations()
@@ -496,6 +700,45 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                             .update_scroll_sensitivity(request);
                     }
                 }
+                FactoryId::CameraAnimation => {
+                    let mut request = None;
+                    self.parameters_tab.update_camera_animation_request(
+                        value_id,
+                        value,
+                        &mut request,
+                    );
+                    if let Some(request) = request {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .update_camera_animation_duration(request);
+                    }
+                }
+                FactoryId::Autosave => {
+                    let mut request = None;
+                    self.parameters_tab
+                        .update_autosave_request(value_id, value, &mut request);
+                    if let Some(request) = request {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .update_autosave_interval(request);
+                    }
+                }
+                FactoryId::UndoHistoryLength => {
+                    let mut request = None;
+                    self.parameters_tab.update_undo_history_length_request(
+                        value_id,
+                        value,
+                        &mut request,
+                    );
+                    if let Some(request) = request {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .update_undo_history_length(request);
+                    }
+                }
                 FactoryId::HelixRoll => {
                     let mut request = None;
                     self.edition_tab
@@ -518,6 +761,16 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                             .update_current_hyperboloid(request);
                     }
                 }
+                FactoryId::HyperboloidEdition => {
+                    if let Some((grid_id, request)) =
+                        self.grid_tab.update_hyperboloid_edition(value_id, value)
+                    {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .update_hyperboloid(grid_id, request);
+                    }
+                }
                 FactoryId::RigidBody => {
                     let mut request = None;
                     self.simulation_tab
@@ -540,7 +793,200 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                             .update_rigid_body_simulation_parameters(request);
                     }
                 }
+                FactoryId::DuplicationPattern => {
+                    self.edition_tab.update_duplication_pattern(value_id, value)
+                }
+                FactoryId::XoverPlacement => {
+                    self.edition_tab.update_xover_placement(value_id, value)
+                }
+                FactoryId::ScaffoldRouting => {
+                    self.edition_tab.update_scaffold_routing(value_id, value)
+                }
+                FactoryId::StapleFilling => self.edition_tab.update_staple_filling(value_id, value),
+                FactoryId::DesignCheck => {
+                    self.sequence_tab.update_check_parameters(value_id, value)
+                }
+                FactoryId::ReferenceDesignIsometry => {
+                    let mut request = None;
+                    self.camera_tab
+                        .update_reference_isometry(value_id, value, &mut request);
+                    if let Some(request) = request {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .set_reference_design_isometry(request);
+                    }
+                }
+                FactoryId::StapleBounds => {
+                    self.sequence_tab.update_staple_bounds(value_id, value);
+                    if self.sequence_tab.has_staple_report() {
+                        self.update_staple_report();
+                    }
+                }
             },
+            Message::DescreteValueReleased(factory_id) => {
+                if factory_id == FactoryId::HyperboloidEdition {
+                    if let Some(grid_id) = self.grid_tab.edited_hyperboloid() {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .finish_hyperboloid_update(grid_id);
+                    }
+                }
+            }
+            Message::AlignHelices => {
+                let helices =
+                    ensnano_interactor::extract_helices(self.application_state.get_selection());
+                if let Some(reference) = helices.last().cloned() {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .align_helices(helices, reference);
+                }
+            }
+            Message::DuplicateInPattern => {
+                let selection = self.application_state.get_selection();
+                let helices = ensnano_interactor::extract_helices(selection);
+                let strands = ensnano_interactor::extract_strands_from_selection(selection);
+                let mut request = None;
+                self.edition_tab
+                    .make_duplication_pattern_request(&mut request);
+                if let Some(pattern) = request {
+                    if !helices.is_empty() || !strands.is_empty() {
+                        self.requests
+                            .lock()
+                            .unwrap()
+                            .duplicate_in_pattern(helices, strands, pattern);
+                    }
+                }
+            }
+            Message::ProtectScaffoldFromXovers(b) => self.edition_tab.set_protect_scaffold(b),
+            Message::PlaceXoversBetweenHelices => {
+                let mut request = None;
+                self.edition_tab.make_xover_placement_request(&mut request);
+                if let Some(parameters) = request {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .place_xovers_between_selected_helices(parameters);
+                }
+            }
+            Message::ScaffoldRoutingSpiral(spiral) => {
+                self.edition_tab.set_scaffold_routing_spiral(spiral)
+            }
+            Message::ScaffoldRoutingClearExisting(clear) => {
+                self.edition_tab.set_scaffold_routing_clear_existing(clear)
+            }
+            Message::RouteScaffold => {
+                let selection = self.application_state.get_selection();
+                let helices = ensnano_interactor::extract_helices(selection);
+                let route = if !helices.is_empty() {
+                    Some(ScaffoldRoute::Helices(helices))
+                } else {
+                    ensnano_interactor::extract_grids(selection)
+                        .first()
+                        .map(|grid_id| ScaffoldRoute::Grid {
+                            grid_id: *grid_id,
+                            pattern: self.edition_tab.scaffold_routing_pattern(),
+                        })
+                };
+                let mut request = None;
+                self.edition_tab.make_scaffold_routing_request(&mut request);
+                if let Some((route, parameters)) = route.zip(request) {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .route_scaffold(route, parameters);
+                }
+            }
+            Message::StapleDomainLengthsInput(text) => {
+                self.edition_tab.set_staple_domain_lengths(text)
+            }
+            Message::NickAtSelectedNucls(b) => self.edition_tab.set_nick_at_selected_nucls(b),
+            Message::FillStaples => {
+                let mut request = None;
+                self.edition_tab.make_staple_filling_request(&mut request);
+                if let Some(mut parameters) = request {
+                    if self.edition_tab.nick_at_selected_nucls() {
+                        // The selected nucleotides are on the scaffold, the nicks are on their
+                        // partners
+                        parameters.nicks = ensnano_interactor::extract_nucls_from_selection(
+                            self.application_state.get_selection(),
+                        )
+                        .into_iter()
+                        .map(|nucl| nucl.compl())
+                        .collect();
+                    }
+                    self.requests.lock().unwrap().fill_staples(parameters);
+                }
+            }
+            Message::RecolorScopePicked(scope) => self.edition_tab.set_recolor_scope(scope),
+            Message::RecolorPalettePicked(palette) => self.edition_tab.set_recolor_palette(palette),
+            Message::RecolorColorsInput(text) => self.edition_tab.set_recolor_colors(text),
+            Message::RecolorStaples => {
+                let scope = match self.edition_tab.recolor_scope() {
+                    RecolorScopeChoice::AllStaples => Some(RecolorScope::AllStaples),
+                    RecolorScopeChoice::SelectedStrands => Some(RecolorScope::Strands(
+                        ensnano_interactor::extract_strands_from_selection(
+                            self.application_state.get_selection(),
+                        ),
+                    )),
+                    RecolorScopeChoice::SelectedGroup => self
+                        .application_state
+                        .get_selected_group()
+                        .map(RecolorScope::Group),
+                };
+                if let Some((scope, palette)) = scope.zip(self.edition_tab.recolor_palette()) {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .recolor_staples(scope, palette);
+                }
+            }
+            Message::SetSelectedHelicesParameters(parameters) => {
+                let helices =
+                    ensnano_interactor::extract_helices(self.application_state.get_selection());
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_helices_parameters(helices, Some(parameters.value));
+            }
+            Message::ResetSelectedHelicesParameters => {
+                let helices =
+                    ensnano_interactor::extract_helices(self.application_state.get_selection());
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .set_helices_parameters(helices, None);
+            }
+            Message::AnchorStrandEndsOnly(b) => self.edition_tab.set_anchor_strand_ends_only(b),
+            Message::AnchorSelectedStrands => {
+                let ends_only = self.edition_tab.anchor_strand_ends_only();
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .anchor_selected_strands(ends_only);
+            }
+            Message::NamedSelectionNameInput(name) => {
+                self.edition_tab.set_named_selection_name(name)
+            }
+            Message::SaveNamedSelection => {
+                let name = self.edition_tab.take_named_selection_name();
+                self.requests.lock().unwrap().save_named_selection(name);
+            }
+            Message::RecallNamedSelection(idx) => {
+                self.requests.lock().unwrap().recall_named_selection(idx)
+            }
+            Message::RenameNamedSelection(idx) => {
+                let name = self.edition_tab.take_named_selection_name();
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .rename_named_selection(idx, name);
+            }
+            Message::DeleteNamedSelection(idx) => {
+                self.requests.lock().unwrap().delete_named_selection(idx)
+            }
             Message::VolumeExclusion(b) => {
                 self.simulation_tab.set_volume_exclusion(b);
                 let mut request: Option<RigidBodyParametersRequest> = None;
@@ -595,10 +1041,12 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                     let mut request: Option<RigidBodyParametersRequest> = None;
                     self.simulation_tab.make_rigid_body_request(&mut request);
                     if let Some(request) = request {
-                        self.requests
-                            .lock()
-                            .unwrap()
-                            .update_rigid_helices_simulation(request);
+                        let mut requests = self.requests.lock().unwrap();
+                        requests.update_rigid_helices_simulation(request);
+                        let pinned_groups = self.organizer.pinned_groups();
+                        if !pinned_groups.is_empty() {
+                            requests.set_pinned_groups(pinned_groups);
+                        }
                     }
                 } else {
                     self.requests.lock().unwrap().stop_simulations();
@@ -648,21 +1096,69 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                     self.update(message);
                 }
             }
+            Message::OrganizerKeyPressed(key_code) => {
+                if !self.has_keyboard_priority() {
+                    let selection = self
+                        .application_state
+                        .get_selection()
+                        .iter()
+                        .filter_map(|s| DnaElementKey::from_selection(s, 0))
+                        .collect();
+                    if let Some(message) = self.organizer.keyboard_event(key_code, &selection) {
+                        self.update(Message::OrganizerMessage(message));
+                    }
+                }
+            }
             Message::ModifiersChanged(modifiers) => self
                 .organizer
                 .new_modifiers(iced_winit::conversion::modifiers(modifiers)),
             Message::UiSizePicked(ui_size) => self.requests.lock().unwrap().set_ui_size(ui_size),
             Message::UiSizeChanged(ui_size) => self.ui_size = ui_size,
             Message::SetScaffoldSeqButtonPressed => {
-                self.requests
-                    .lock()
-                    .unwrap()
-                    .set_scaffold_sequence(self.sequence_tab.get_scaffold_shift());
+                if let Some(scaffold_id) = self.sequence_tab.get_target_scaffold() {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .set_scaffold_sequence(scaffold_id, self.sequence_tab.get_scaffold_shift());
+                }
             }
             Message::OptimizeScaffoldShiftPressed => {
-                self.requests.lock().unwrap().optimize_scaffold_shift();
+                if let Some(scaffold_id) = self.sequence_tab.get_target_scaffold() {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .optimize_scaffold_shift(scaffold_id);
+                }
             }
             Message::StapplesRequested => self.requests.lock().unwrap().download_stapples(),
+            Message::UpdateStapleReport => self.update_staple_report(),
+            Message::SortStapleReport(column) => self.sequence_tab.sort_staple_report_by(column),
+            Message::StapleReportRowClicked(s_id) => {
+                self.requests.lock().unwrap().select_strand(s_id)
+            }
+            Message::CheckDesign => {
+                let parameters = self.sequence_tab.get_check_parameters();
+                let diagnostics = self
+                    .application_state
+                    .get_reader()
+                    .check_design(&parameters);
+                self.sequence_tab.set_diagnostics(diagnostics);
+            }
+            Message::DiagnosticClicked(n) => {
+                if let Some(diagnostic) = self.sequence_tab.get_diagnostic(n) {
+                    let selection = if let Some(s_id) = diagnostic.strand_ids().first() {
+                        Some(Selection::Strand(0, *s_id as u32))
+                    } else {
+                        diagnostic
+                            .nucls()
+                            .first()
+                            .map(|nucl| Selection::Nucleotide(0, *nucl))
+                    };
+                    if let Some(selection) = selection {
+                        self.requests.lock().unwrap().select_and_center(selection);
+                    }
+                }
+            }
             Message::ToggleText(b) => {
                 self.requests
                     .lock()
@@ -682,9 +1178,14 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
             Message::ToggleVisibility(b) => self.requests.lock().unwrap().toggle_visibility(b),
             Message::AllVisible => self.requests.lock().unwrap().make_all_elements_visible(),
             Message::Redim2dHelices(b) => self.requests.lock().unwrap().resize_2d_helices(b),
+            Message::Compact2dLayout => self.requests.lock().unwrap().compact_2d_layout(),
+            Message::PivotOnSelection => self.requests.lock().unwrap().place_pivot_on_selection(),
             Message::InvertScroll(b) => {
                 self.requests.lock().unwrap().invert_scroll(b);
             }
+            Message::RememberWorkspace(b) => {
+                self.requests.lock().unwrap().set_remember_workspace(b);
+            }
             Message::CancelHyperboloid => {
                 self.requests.lock().unwrap().cancel_hyperboloid();
             }
@@ -696,10 +1197,15 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 self.contextual_panel
                     .set_small_sphere(b, self.requests.clone());
             }
-            Message::ScaffoldIdSet(n, b) => {
+            Message::SetGridVisibility(visible) => {
+                self.requests.lock().unwrap().set_grid_visibility(visible)
+            }
+            Message::SoloGrids => self.requests.lock().unwrap().solo_selected_grids(),
+            Message::ScaffoldRoleSet(n, b) => {
                 self.contextual_panel
-                    .scaffold_id_set(n, b, self.requests.clone());
+                    .scaffold_role_set(n, b, self.requests.clone());
             }
+            Message::ScaffoldTargetPicked(s_id) => self.sequence_tab.set_target_scaffold(s_id),
             Message::SelectScaffold => self.requests.lock().unwrap().set_scaffold_from_selection(),
             Message::RenderingMode(mode) => {
                 self.requests.lock().unwrap().change_3d_rendering_mode(mode);
@@ -721,10 +1227,31 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 // ATM we continue even in case of error, later any error will be promted to user
                 let _ = open::that(link);
             }
+            Message::NewUndoHistory(history) => self.edition_tab.set_undo_history(history),
+            Message::NewRecentFiles(recent_files) => self.quick_open.set_recent_files(recent_files),
+            Message::ToggleQuickOpen => self.quick_open.toggle(),
+            Message::QuickOpenFilterInput(filter) => self.quick_open.set_filter(filter),
+            Message::QuickOpenSubmitted => {
+                if let Some(path) = self.quick_open.first_match() {
+                    self.quick_open.toggle();
+                    self.requests.lock().unwrap().open_recent_file(path);
+                }
+            }
+            Message::OpenRecentFile(path) => {
+                self.quick_open.toggle();
+                self.requests.lock().unwrap().open_recent_file(path);
+            }
+            Message::HistoryEntryClicked(steps) => {
+                self.requests.lock().unwrap().jump_in_history(steps)
+            }
             Message::NewApplicationState(state) => {
                 if state.design_was_modified(&self.application_state) {
                     let reader = state.get_reader();
                     self.organizer.update_elements(reader.get_dna_elements());
+                    self.organizer
+                        .update_group_colors(reader.get_group_colors());
+                    self.organizer
+                        .update_locked_groups(reader.get_locked_groups());
                     self.contextual_panel.state_updated();
                     let unrooted_surface = self
                         .revolution_tab
@@ -750,6 +1277,7 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 self.requests.lock().unwrap().finish_changing_color();
             }
             Message::ResetSimulation => self.requests.lock().unwrap().reset_simulations(),
+            Message::StepSimulation => self.requests.lock().unwrap().step_simulation(),
             Message::Nothing => (),
             Message::SubmitCameraName => {
                 if let Some((id, name)) = self.camera_shortcut.stop_editing() {
@@ -794,6 +1322,14 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 .lock()
                 .unwrap()
                 .set_check_xover_parameters(parameters),
+            Message::StrainedXoverClicked(xover_id) => self
+                .requests
+                .lock()
+                .unwrap()
+                .select_and_center(Selection::Xover(0, xover_id)),
+            Message::ToggleXoverChecked(xover_id) => {
+                self.requests.lock().unwrap().check_xovers(vec![xover_id])
+            }
             Message::FollowStereographicCamera(b) => {
                 self.requests.lock().unwrap().follow_stereographic_camera(b)
             }
@@ -806,6 +1342,22 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
             Message::ShowHBonds(b) => {
                 self.requests.lock().unwrap().set_show_h_bonds(b);
             }
+            Message::HBondFilter(filter) => {
+                self.requests.lock().unwrap().set_h_bond_filter(filter);
+            }
+            Message::Show2dMinimap(b) => self.requests.lock().unwrap().set_show_2d_minimap(b),
+            Message::ShowAnchors(b) => self.requests.lock().unwrap().set_show_anchors(b),
+            Message::LocalPhantoms(b) => self.requests.lock().unwrap().set_local_phantoms(b),
+            Message::ShowNucleotideRuler(b) => {
+                self.requests.lock().unwrap().set_show_nucleotide_ruler(b)
+            }
+            Message::FullDetail(b) => self.requests.lock().unwrap().set_full_detail(b),
+            Message::StrandColoring(coloring) => {
+                self.requests.lock().unwrap().set_strand_coloring(coloring)
+            }
+            Message::StrandColormap(colormap) => {
+                self.requests.lock().unwrap().set_strand_colormap(colormap)
+            }
             Message::RainbowScaffold(b) => self.requests.lock().unwrap().set_rainbow_scaffold(b),
             Message::StopSimulation => self.requests.lock().unwrap().stop_simulations(),
             Message::StartTwist => {
@@ -815,6 +1367,16 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                     self.requests.lock().unwrap().start_twist_simulation(*g_id)
                 }
             }
+            Message::SetGridType(grid_type) => {
+                if let Some(Selection::Grid(_, g_id)) =
+                    self.application_state.get_selection().get(0)
+                {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .set_grid_type(*g_id, grid_type)
+                }
+            }
             Message::OrigamisRequested => self.requests.lock().unwrap().download_origamis(),
             Message::NewDnaParameters(parameters) => self
                 .requests
@@ -824,6 +1386,14 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
             Message::SetExpandInsertions(b) => {
                 self.requests.lock().unwrap().set_expand_insertions(b)
             }
+            Message::IsolateSelection(isolate_selection) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_isolate_selection(isolate_selection),
+            Message::CameraMode(camera_mode) => {
+                self.requests.lock().unwrap().set_camera_mode(camera_mode)
+            }
             Message::InsertionLengthInput(s) => {
                 self.contextual_panel.update_insertion_length_input(s);
             }
@@ -843,6 +1413,48 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                     }
                 }
             }
+            Message::ModificationKindPicked(nucl, kind) => {
+                let text = self
+                    .application_state
+                    .get_reader()
+                    .nucl_modification(nucl)
+                    .map(|m| m.text)
+                    .unwrap_or_default();
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .add_modification(nucl, Modification { kind, text });
+            }
+            Message::ModificationTextInput(s) => {
+                self.contextual_panel.update_modification_text_input(s);
+            }
+            Message::ModificationTextSubmitted(nucl) => {
+                let modification = self.application_state.get_reader().nucl_modification(nucl);
+                if let Some((modification, text)) =
+                    modification.zip(self.contextual_panel.get_modification_text())
+                {
+                    self.requests.lock().unwrap().add_modification(
+                        nucl,
+                        Modification {
+                            kind: modification.kind,
+                            text,
+                        },
+                    );
+                }
+            }
+            Message::RemoveModification(nucl) => {
+                self.requests.lock().unwrap().rm_modification(nucl);
+            }
+            Message::SetExternal3DObjectVisibility(object_id, visible) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_external_3d_object_visibility(object_id, visible),
+            Message::AttachExternal3DObject(object_id, anchor) => self
+                .requests
+                .lock()
+                .unwrap()
+                .attach_external_3d_object(object_id, anchor),
             Message::NewBezierPlane => {
                 self.requests.lock().unwrap().create_bezier_plane();
             }
@@ -866,8 +1478,52 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                     .unwrap()
                     .make_bezier_path_cyclic(path_id, cyclic);
             }
+            Message::StartEditBezierPlaneName(plane_id) => self.pen_tab.start_editing(plane_id),
+            Message::EditBezierPlaneName(name) => self.pen_tab.set_plane_input_name(name),
+            Message::SubmitBezierPlaneName => {
+                if let Some((plane_id, name)) = self.pen_tab.stop_editing() {
+                    self.requests
+                        .lock()
+                        .unwrap()
+                        .rename_bezier_plane(plane_id, name);
+                }
+            }
+            Message::DeleteBezierPlane(plane_id) => {
+                self.requests.lock().unwrap().delete_bezier_plane(plane_id)
+            }
+            Message::DuplicateBezierPlane { plane_id, offset } => {
+                let copy_paths = self.pen_tab.copy_paths_of_duplicated_plane;
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .duplicate_bezier_plane(plane_id, offset, copy_paths);
+            }
+            Message::SetCopyPathsOfDuplicatedPlane(copy_paths) => {
+                self.pen_tab.copy_paths_of_duplicated_plane = copy_paths
+            }
             Message::Export(export_type) => {
-                self.requests.lock().unwrap().export(export_type);
+                if self.exports_menu.selection_only {
+                    self.requests.lock().unwrap().export_selection(export_type);
+                } else {
+                    self.requests.lock().unwrap().export(export_type);
+                }
+            }
+            Message::ExportSelectionOnly(selection_only) => {
+                self.exports_menu.selection_only = selection_only
+            }
+            Message::ExportObj => {
+                let parameters = self
+                    .exports_menu
+                    .obj_parameters(self.application_state.want_thick_helices());
+                let export_type = ExportType::Obj(parameters);
+                if self.exports_menu.selection_only {
+                    self.requests.lock().unwrap().export_selection(export_type);
+                } else {
+                    self.requests.lock().unwrap().export(export_type);
+                }
+            }
+            Message::ObjSphereSubdivision(subdivision) => {
+                self.exports_menu.sphere_subdivision = subdivision
             }
             Message::CancelExport => {
                 self.requests.lock().unwrap().set_exporting(false);
@@ -931,8 +1587,44 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
                 .unwrap()
                 .finish_revolutiion_relaxation(),
             Message::LoadSvgFile => self.requests.lock().unwrap().load_svg(),
+            Message::ImportOxDnaConfiguration => {
+                self.requests.lock().unwrap().import_oxdna_configuration()
+            }
+            Message::HideIdealizedDesign(hide) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_hide_idealized_design(hide),
+            Message::LoadReferenceDesign => self.requests.lock().unwrap().load_reference_design(),
+            Message::RemoveReferenceDesign => {
+                self.camera_tab.reset_reference_isometry();
+                self.requests.lock().unwrap().remove_reference_design()
+            }
+            Message::ShowReferenceDesign(visible) => self
+                .requests
+                .lock()
+                .unwrap()
+                .set_reference_design_visibility(visible),
+            Message::ShowDesignChanges(show) => {
+                self.requests.lock().unwrap().set_show_design_changes(show)
+            }
             Message::ScreenShot3D => {
-                self.requests.lock().unwrap().request_screenshot_3d();
+                let options = self.camera_shortcut.png_export_options();
+                self.requests.lock().unwrap().request_screenshot_3d(options);
+            }
+            Message::PngWidthInput(width) => self.camera_shortcut.set_png_width(width),
+            Message::PngHeightInput(height) => self.camera_shortcut.set_png_height(height),
+            Message::TransparentPng(transparent) => {
+                self.camera_shortcut.set_transparent_png(transparent)
+            }
+            Message::ExportAllCameras => {
+                self.requests.lock().unwrap().request_export_all_cameras();
+            }
+            Message::ToggleOrthographicProjection => {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .toggle_orthographic_projection();
             }
             Message::IncrRevolutionShift => self.revolution_tab.shift_idx += 1,
             Message::DecrRevolutionShift => self.revolution_tab.shift_idx -= 1,
@@ -1026,6 +1718,8 @@ impl<R: Requests, S: AppState> Program for LeftPanel<R, S> {
 
         let first_container = if self.application_state.is_exporting() {
             Container::new(self.exports_menu.view()).height(Length::FillPortion(2))
+        } else if self.quick_open.visible {
+            Container::new(self.quick_open.view(self.ui_size)).height(Length::FillPortion(2))
         } else {
             Container::new(tabs).height(Length::FillPortion(2))
         };
@@ -1403,9 +2097,11 @@ impl Requestable for ScrollSentivity {
     }
 }
 
-struct HelixRoll {}
+struct CameraAnimationDuration {
+    initial_value: f32,
+}
 
-impl Requestable for HelixRoll {
+impl Requestable for CameraAnimationDuration {
     type Request = f32;
     fn request_from_values(&self, values: &[f32]) -> f32 {
         values[0]
@@ -1414,9 +2110,154 @@ impl Requestable for HelixRoll {
         1
     }
     fn initial_value(&self, n: usize) -> f32 {
-        match n {
-            0 => 0f32,
-            _ => unreachable!(),
+        if n == 0 {
+            self.initial_value
+        } else {
+            unreachable!()
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            0f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            2f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            0.1f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        if n == 0 {
+            String::from("Duration (s)")
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+struct UndoHistoryLength {
+    initial_value: usize,
+}
+
+impl Requestable for UndoHistoryLength {
+    type Request = usize;
+    fn request_from_values(&self, values: &[f32]) -> usize {
+        values[0].round() as usize
+    }
+    fn nb_values(&self) -> usize {
+        1
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        if n == 0 {
+            self.initial_value as f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            1f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            200f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            1f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        if n == 0 {
+            String::from("Listed operations")
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+struct AutosaveInterval {
+    initial_value: f32,
+}
+
+impl Requestable for AutosaveInterval {
+    type Request = f32;
+    fn request_from_values(&self, values: &[f32]) -> f32 {
+        values[0]
+    }
+    fn nb_values(&self) -> usize {
+        1
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        if n == 0 {
+            self.initial_value
+        } else {
+            unreachable!()
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            1f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            60f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        if n == 0 {
+            1f32
+        } else {
+            unreachable!()
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        if n == 0 {
+            String::from("Interval (min)")
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+struct HelixRoll {}
+
+impl Requestable for HelixRoll {
+    type Request = f32;
+    fn request_from_values(&self, values: &[f32]) -> f32 {
+        values[0]
+    }
+    fn nb_values(&self) -> usize {
+        1
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        match n {
+            0 => 0f32,
+            _ => unreachable!(),
         }
     }
     fn min_val(&self, n: usize) -> f32 {
@@ -1447,6 +2288,400 @@ impl Requestable for HelixRoll {
     }
 }
 
+struct DuplicationPatternFactory {}
+
+impl Requestable for DuplicationPatternFactory {
+    type Request = DuplicationPattern;
+    fn request_from_values(&self, values: &[f32]) -> DuplicationPattern {
+        DuplicationPattern {
+            nb_copies: values[0] as usize,
+            translation: Vec3::new(values[1], values[2], values[3]),
+            rotation_angle: values[4].to_radians(),
+            rotation_axis: Vec3::new(values[5], values[6], values[7]),
+            connection_distance: Some(values[8]).filter(|d| *d > 0.),
+        }
+    }
+    fn nb_values(&self) -> usize {
+        9
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        match n {
+            0 => 1.,
+            1..=6 => 0.,
+            7 => 1.,
+            8 => 0.,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 1.,
+            1..=3 => -50.,
+            4 => -180.,
+            5..=7 => -1.,
+            8 => 0.,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 50.,
+            1..=3 => 50.,
+            4 => 180.,
+            5..=7 => 1.,
+            8 => 2.,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 1.,
+            1..=3 => 0.5,
+            4 => 1.,
+            5..=7 => 0.1,
+            8 => 0.1,
+            _ => unreachable!(),
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("Copies"),
+            1 => String::from("Shift x (nm)"),
+            2 => String::from("Shift y (nm)"),
+            3 => String::from("Shift z (nm)"),
+            4 => String::from("Angle (°)"),
+            5 => String::from("Axis x"),
+            6 => String::from("Axis y"),
+            7 => String::from("Axis z"),
+            8 => String::from("Connect ends (nm)"),
+            _ => unreachable!(),
+        }
+    }
+}
+
+struct CheckParametersFactory {}
+
+impl Requestable for CheckParametersFactory {
+    type Request = CheckParameters;
+    fn request_from_values(&self, values: &[f32]) -> CheckParameters {
+        CheckParameters {
+            min_domain_length: values[0] as usize,
+            max_unpaired_scaffold: values[1] as usize,
+            min_xover_spacing: values[2] as usize,
+        }
+    }
+    fn nb_values(&self) -> usize {
+        3
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        let default = CheckParameters::default();
+        match n {
+            0 => default.min_domain_length as f32,
+            1 => default.max_unpaired_scaffold as f32,
+            2 => default.min_xover_spacing as f32,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0 | 1 | 2 => 0.,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0 | 1 | 2 => 50.,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        match n {
+            0 | 1 | 2 => 1.,
+            _ => unreachable!(),
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("Min domain length"),
+            1 => String::from("Max unpaired scaffold"),
+            2 => String::from("Min xover spacing"),
+            _ => unreachable!(),
+        }
+    }
+}
+
+struct ReferenceIsometryFactory {}
+
+impl Requestable for ReferenceIsometryFactory {
+    type Request = Isometry3;
+    fn request_from_values(&self, values: &[f32]) -> Isometry3 {
+        Isometry3::new(
+            Vec3::new(values[0], values[1], values[2]),
+            Rotor3::from_euler_angles(
+                values[3].to_radians(),
+                values[4].to_radians(),
+                values[5].to_radians(),
+            ),
+        )
+    }
+    fn nb_values(&self) -> usize {
+        6
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        match n {
+            0..=5 => 0.,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0..=2 => -200.,
+            3..=5 => -180.,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0..=2 => 200.,
+            3..=5 => 180.,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        match n {
+            0..=2 => 0.5,
+            3..=5 => 1.,
+            _ => unreachable!(),
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("Shift x (nm)"),
+            1 => String::from("Shift y (nm)"),
+            2 => String::from("Shift z (nm)"),
+            3 => String::from("Roll (°)"),
+            4 => String::from("Pitch (°)"),
+            5 => String::from("Yaw (°)"),
+            _ => unreachable!(),
+        }
+    }
+}
+
+struct StapleBoundsFactory {}
+
+impl Requestable for StapleBoundsFactory {
+    type Request = StapleBounds;
+    fn request_from_values(&self, values: &[f32]) -> StapleBounds {
+        StapleBounds {
+            min_length: values[0] as usize,
+            max_length: values[1] as usize,
+            min_domain_length: values[2] as usize,
+        }
+    }
+    fn nb_values(&self) -> usize {
+        3
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        let default = StapleBounds::default();
+        match n {
+            0 => default.min_length as f32,
+            1 => default.max_length as f32,
+            2 => default.min_domain_length as f32,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0 | 1 | 2 => 1.,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0 | 1 => 200.,
+            2 => 50.,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        match n {
+            0 | 1 | 2 => 1.,
+            _ => unreachable!(),
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("Min length"),
+            1 => String::from("Max length"),
+            2 => String::from("Min domain length"),
+            _ => unreachable!(),
+        }
+    }
+}
+
+struct XoverPlacementFactory {}
+
+impl Requestable for XoverPlacementFactory {
+    type Request = XoverPlacementParameters;
+    fn request_from_values(&self, values: &[f32]) -> XoverPlacementParameters {
+        XoverPlacementParameters {
+            max_distance: values[0],
+            min_spacing: values[1] as usize,
+            ..Default::default()
+        }
+    }
+    fn nb_values(&self) -> usize {
+        2
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        let default = XoverPlacementParameters::default();
+        match n {
+            0 => default.max_distance,
+            1 => default.min_spacing as f32,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 0.5,
+            1 => 1.,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 3.,
+            1 => 42.,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 0.1,
+            1 => 1.,
+            _ => unreachable!(),
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("Max distance (nm)"),
+            1 => String::from("Min spacing (nt)"),
+            _ => unreachable!(),
+        }
+    }
+}
+
+struct ScaffoldRoutingFactory {}
+
+impl Requestable for ScaffoldRoutingFactory {
+    type Request = ScaffoldRoutingParameters;
+    fn request_from_values(&self, values: &[f32]) -> ScaffoldRoutingParameters {
+        ScaffoldRoutingParameters {
+            start: values[0] as isize,
+            length: values[1] as usize,
+            xover_window: values[2] as usize,
+            ..Default::default()
+        }
+    }
+    fn nb_values(&self) -> usize {
+        3
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        let default = ScaffoldRoutingParameters::default();
+        match n {
+            0 => default.start as f32,
+            1 => default.length as f32,
+            2 => default.xover_window as f32,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0 => -1000.,
+            1 => 2.,
+            2 => 1.,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 1000.,
+            1 => 2000.,
+            2 => 42.,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, _n: usize) -> f32 {
+        1.
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("Start (nt)"),
+            1 => String::from("Length (nt)"),
+            2 => String::from("Xover window (nt)"),
+            _ => unreachable!(),
+        }
+    }
+}
+
+struct StapleFillingFactory {}
+
+impl Requestable for StapleFillingFactory {
+    type Request = StapleFillingParameters;
+    fn request_from_values(&self, values: &[f32]) -> StapleFillingParameters {
+        StapleFillingParameters {
+            min_length: values[0] as usize,
+            max_length: values[1] as usize,
+            max_xover_distance: values[2],
+            ..Default::default()
+        }
+    }
+    fn nb_values(&self) -> usize {
+        3
+    }
+    fn initial_value(&self, n: usize) -> f32 {
+        let default = StapleFillingParameters::default();
+        match n {
+            0 => default.min_length as f32,
+            1 => default.max_length as f32,
+            2 => default.max_xover_distance,
+            _ => unreachable!(),
+        }
+    }
+    fn min_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 1.,
+            1 => 1.,
+            2 => 0.5,
+            _ => unreachable!(),
+        }
+    }
+    fn max_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 200.,
+            1 => 200.,
+            2 => 3.,
+            _ => unreachable!(),
+        }
+    }
+    fn step_val(&self, n: usize) -> f32 {
+        match n {
+            0 => 1.,
+            1 => 1.,
+            2 => 0.1,
+            _ => unreachable!(),
+        }
+    }
+    fn name_val(&self, n: usize) -> String {
+        match n {
+            0 => String::from("Min length (nt)"),
+            1 => String::from("Max length (nt)"),
+            2 => String::from("Max xover length (nm)"),
+            _ => unreachable!(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct RigidBodyParametersRequest {
     pub k_springs: f32,
@@ -15,10 +15,14 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use super::super::DesignReader;
+use super::super::{DesignReader, External3DObjectEntry};
 use super::*;
-use ensnano_design::{grid::GridId, BezierVertexId};
-use ensnano_interactor::{Selection, SimulationState};
+use ensnano_design::{
+    grid::GridId,
+    modifications::{ModificationKind, ALL_MODIFICATION_KINDS},
+    BezierVertexId, External3DObjectAnchor,
+};
+use ensnano_interactor::{extract_external_3d_object_anchor, Selection, SimulationState};
 use iced::{scrollable, Scrollable};
 
 mod value_constructor;
@@ -39,6 +43,10 @@ pub enum ValueRequest {
         grid_id: GridId,
         nb_turn: f32,
     },
+    GridPhantomLength {
+        grid_id: GridId,
+        length: Option<usize>,
+    },
     BezierVertexPosition {
         vertex_id: BezierVertexId,
         position: Vec2,
@@ -81,6 +89,17 @@ impl ValueRequest {
                     None
                 }
             }
+            InstanciatedValue::GridPhantomLength(length) => {
+                if let Selection::Grid(_, g_id) = selection {
+                    Some(Self::GridPhantomLength {
+                        grid_id: *g_id,
+                        length,
+                    })
+                } else {
+                    log::error!("Recieved value {:?} with selection {:?}", value, selection);
+                    None
+                }
+            }
             InstanciatedValue::BezierVertexPosition(pos) => {
                 if let Selection::BezierVertex(vertex_id) = selection {
                     Some(Self::BezierVertexPosition {
@@ -111,6 +130,10 @@ impl ValueRequest {
             Self::GridNbTurn { grid_id, nb_turn } => {
                 request.lock().unwrap().set_nb_turn(*grid_id, *nb_turn)
             }
+            Self::GridPhantomLength { grid_id, length } => request
+                .lock()
+                .unwrap()
+                .set_grid_phantom_length(*grid_id, *length),
             Self::BezierVertexPosition {
                 vertex_id,
                 position,
@@ -186,9 +209,15 @@ pub(super) struct ContextualPanel<S: AppState> {
     ens_nano_website: button::State,
     add_strand_menu: AddStrandMenu,
     strand_name_state: text_input::State,
+    strand_note_state: text_input::State,
     builder: Option<InstantiatedBuilder<S>>,
     twist_button: button::State,
+    square_lattice_button: button::State,
+    honeycomb_lattice_button: button::State,
+    solo_grid_button: button::State,
     insertion_length_state: InsertionLengthState,
+    modification_state: ModificationState,
+    external_object_buttons: Vec<button::State>,
 }
 
 impl<S: AppState> ContextualPanel<S> {
@@ -202,9 +231,15 @@ impl<S: AppState> ContextualPanel<S> {
             ens_nano_website: Default::default(),
             add_strand_menu: Default::default(),
             strand_name_state: Default::default(),
+            strand_note_state: Default::default(),
             builder: None,
             twist_button: Default::default(),
+            square_lattice_button: Default::default(),
+            honeycomb_lattice_button: Default::default(),
+            solo_grid_button: Default::default(),
             insertion_length_state: Default::default(),
+            modification_state: Default::default(),
+            external_object_buttons: Vec::new(),
         }
     }
 
@@ -259,6 +294,7 @@ impl<S: AppState> ContextualPanel<S> {
             .and_then(|id| app_state.get_reader().xover_length(id));
 
         self.insertion_length_state.update_selection(selection);
+        self.modification_state.update_selection(selection);
         let info_values = values_of_selection(selection, app_state.get_reader().as_ref());
         if self.show_tutorial {
             column = column.push(
@@ -323,19 +359,28 @@ impl<S: AppState> ContextualPanel<S> {
                         ui_size,
                         &mut self.twist_button,
                         twisting,
+                        (
+                            &mut self.square_lattice_button,
+                            &mut self.honeycomb_lattice_button,
+                        ),
+                        &mut self.solo_grid_button,
                     )
                 }
                 Selection::Strand(_, _) => {
                     column = add_strand_content(
                         column,
-                        &mut self.strand_name_state,
+                        (&mut self.strand_name_state, &mut self.strand_note_state),
                         info_values.as_slice(),
                         ui_size,
                     )
                 }
-                Selection::Nucleotide(_, _) => {
+                Selection::Nucleotide(_, nucl) => {
                     let anchor = info_values[0].clone();
                     column = column.push(Text::new(format!("Anchor {}", anchor)));
+                    let modification = app_state.get_reader().nucl_modification(*nucl);
+                    column =
+                        self.modification_state
+                            .view(column, *nucl, modification.as_ref(), ui_size);
                 }
                 Selection::Xover(_, _) => {
                     if xover_len.is_none() {
@@ -363,6 +408,23 @@ impl<S: AppState> ContextualPanel<S> {
             }
         }
 
+        let external_objects = app_state.get_reader().get_external_3d_objects();
+        if !external_objects.is_empty()
+            && !self.show_tutorial
+            && !app_state.get_action_mode().is_build()
+        {
+            let anchor = extract_external_3d_object_anchor(app_state.get_selection());
+            self.external_object_buttons
+                .resize_with(external_objects.len(), Default::default);
+            column = add_external_objects_content(
+                column,
+                external_objects,
+                anchor,
+                &mut self.external_object_buttons,
+                ui_size,
+            );
+        }
+
         if let Some(len) = app_state.get_reader().get_insertion_length(selection) {
             let real_len_string = len.to_string();
             let text_input_content = self
@@ -404,12 +466,8 @@ impl<S: AppState> ContextualPanel<S> {
         requests.lock().unwrap().set_small_sphere(b);
     }
 
-    pub fn scaffold_id_set<R: Requests>(&mut self, n: usize, b: bool, requests: Arc<Mutex<R>>) {
-        if b {
-            requests.lock().unwrap().set_scaffold_id(Some(n))
-        } else {
-            requests.lock().unwrap().set_scaffold_id(None)
-        }
+    pub fn scaffold_role_set<R: Requests>(&mut self, n: usize, b: bool, requests: Arc<Mutex<R>>) {
+        requests.lock().unwrap().set_scaffold_role(n, b)
     }
 
     pub fn state_updated(&mut self) {
@@ -428,8 +486,10 @@ impl<S: AppState> ContextualPanel<S> {
     pub fn has_keyboard_priority(&self) -> bool {
         self.add_strand_menu.has_keyboard_priority()
             || self.strand_name_state.is_focused()
+            || self.strand_note_state.is_focused()
             || self.builder_has_keyboard_priority()
             || self.insertion_length_state.has_keyboard_priority()
+            || self.modification_state.has_keyboard_priority()
     }
 
     fn builder_has_keyboard_priority(&self) -> bool {
@@ -485,6 +545,15 @@ impl<S: AppState> ContextualPanel<S> {
         self.insertion_length_state.input_str = Some(input);
     }
 
+    pub fn update_modification_text_input(&mut self, input: String) {
+        self.modification_state.input_str = Some(input);
+    }
+
+    /// Return the text of the modification that was typed by the user, if it was edited
+    pub fn get_modification_text(&self) -> Option<String> {
+        self.modification_state.input_str.clone()
+    }
+
     pub fn get_insertion_request(&self) -> Option<InsertionRequest> {
         let length = self
             .insertion_length_state
@@ -510,6 +579,8 @@ fn add_grid_content<'a, S: AppState, I: std::ops::Deref<Target = str>>(
     ui_size: UiSize,
     twist_button: &'a mut button::State,
     twisting: TwistStatus,
+    lattice_buttons: (&'a mut button::State, &'a mut button::State),
+    solo_button: &'a mut button::State,
 ) -> Column<'a, Message<S>> {
     let twist_button = match twisting {
         TwistStatus::Twisting => {
@@ -521,6 +592,18 @@ fn add_grid_content<'a, S: AppState, I: std::ops::Deref<Target = str>>(
         TwistStatus::CannotTwist => text_btn(twist_button, "Twist", ui_size),
     };
     column = column.push(twist_button);
+    column = column.push(
+        Row::new()
+            .spacing(5)
+            .push(Text::new("Lattice").size(ui_size.main_text()))
+            .push(
+                text_btn(lattice_buttons.0, "Square", ui_size)
+                    .on_press(Message::SetGridType(GridTypeDescr::Square { twist: None })),
+            )
+            .push(text_btn(lattice_buttons.1, "Honeycomb", ui_size).on_press(
+                Message::SetGridType(GridTypeDescr::Honeycomb { twist: None }),
+            )),
+    );
     column = column.push(
         Checkbox::new(
             info_values[0].parse::<bool>().unwrap(),
@@ -537,12 +620,24 @@ fn add_grid_content<'a, S: AppState, I: std::ops::Deref<Target = str>>(
         .size(ui_size.checkbox())
         .text_size(ui_size.main_text()),
     );
+    column = column.push(
+        Row::new()
+            .spacing(5)
+            .push(
+                Checkbox::new(info_values[2].parse::<bool>().unwrap(), "Visible", |b| {
+                    Message::SetGridVisibility(b)
+                })
+                .size(ui_size.checkbox())
+                .text_size(ui_size.main_text()),
+            )
+            .push(text_btn(solo_button, "Solo", ui_size).on_press(Message::SoloGrids)),
+    );
     column
 }
 
 fn add_strand_content<'a, S: AppState, I: std::ops::Deref<Target = str>>(
     mut column: Column<'a, Message<S>>,
-    strand_name_state: &'a mut text_input::State,
+    (strand_name_state, strand_note_state): (&'a mut text_input::State, &'a mut text_input::State),
     info_values: &[I],
     ui_size: UiSize,
 ) -> Column<'a, Message<S>> {
@@ -559,17 +654,71 @@ fn add_strand_content<'a, S: AppState, I: std::ops::Deref<Target = str>>(
             .size(ui_size.main_text()),
         );
     column = column.push(name_row);
+    let note_row = Row::new()
+        .push(Text::new("Note").size(ui_size.main_text()))
+        .push(
+            TextInput::new(
+                strand_note_state,
+                "Note",
+                &info_values[5],
+                move |new_note| Message::StrandNoteChanged(s_id, new_note),
+            )
+            .size(ui_size.main_text()),
+        );
+    column = column.push(note_row);
     column = column
         .push(Text::new(format!("length {}", info_values[0].deref())).size(ui_size.main_text()));
     column = column.push(Checkbox::new(
         info_values[1].parse().unwrap(),
         "Scaffold",
-        move |b| Message::ScaffoldIdSet(s_id, b),
+        move |b| Message::ScaffoldRoleSet(s_id, b),
+    ));
+    column = column.push(Checkbox::new(
+        info_values[6].parse().unwrap(),
+        "Keep color when recoloring",
+        move |b| Message::StrandKeepColorChanged(s_id, b),
     ));
     column = column.push(Text::new(info_values[3].deref()).size(ui_size.main_text()));
     column
 }
 
+/// Add a checkbox showing or hiding each external 3D object, next to a button attaching the
+/// object to the selected element or detaching it
+fn add_external_objects_content<'a, S: AppState>(
+    mut column: Column<'a, Message<S>>,
+    external_objects: Vec<External3DObjectEntry>,
+    anchor: Option<External3DObjectAnchor>,
+    buttons: &'a mut [button::State],
+    ui_size: UiSize,
+) -> Column<'a, Message<S>> {
+    column = column.push(Text::new("3D objects").size(ui_size.intermediate_text()));
+    for (object, button_state) in external_objects.into_iter().zip(buttons.iter_mut()) {
+        let object_id = object.id;
+        let mut row = Row::new().spacing(5).push(
+            Checkbox::new(object.visible, object.name, move |b| {
+                Message::SetExternal3DObjectVisibility(object_id, b)
+            })
+            .size(ui_size.checkbox())
+            .text_size(ui_size.main_text())
+            .width(Length::Fill),
+        );
+        if object.attached {
+            row = row.push(
+                text_btn(button_state, "Detach", ui_size)
+                    .on_press(Message::AttachExternal3DObject(object_id, None)),
+            );
+        } else {
+            let mut button = text_btn(button_state, "Attach to selection", ui_size);
+            if anchor.is_some() {
+                button = button.on_press(Message::AttachExternal3DObject(object_id, anchor));
+            }
+            row = row.push(button);
+        }
+        column = column.push(row);
+    }
+    column
+}
+
 fn bool_to_string(b: bool) -> String {
     if b {
         String::from("true")
@@ -640,6 +789,10 @@ fn view_3d_help() -> Vec<(String, String)> {
             format!("{}+{}", SHIFT, LCLICK),
             "Multiple select".to_owned(),
         ),
+        (
+            format!("{}+{} Drag", SHIFT, LCLICK),
+            "Rectangle select".to_owned(),
+        ),
         (String::new(), String::new()),
         (
             format!("2x{}", LCLICK),
@@ -669,6 +822,23 @@ fn view_3d_help() -> Vec<(String, String)> {
             format!("{}+{} Drag", SHIFT, RCLICK),
             "Tilt camera".to_owned(),
         ),
+        (
+            format!("{} + 1..9", CTRL),
+            "Go to the nth saved camera".to_owned(),
+        ),
+        (
+            "PageUp / PageDown".to_owned(),
+            "Previous / next saved camera".to_owned(),
+        ),
+        ("F".to_owned(), "Toggle fly mode".to_owned()),
+        (
+            "W A S D / Q E (fly mode)".to_owned(),
+            "Move forward, left, backward, right / down, up".to_owned(),
+        ),
+        (
+            "Scroll (fly mode)".to_owned(),
+            "Change the flying speed".to_owned(),
+        ),
         (
             "⎵ (with cursor over the 3D scene)".to_owned(),
             "Export the current view in png format".to_owned(),
@@ -685,12 +855,36 @@ fn view_3d_help() -> Vec<(String, String)> {
             format!("{} on handle", LCLICK),
             "Move selected object".to_owned(),
         ),
+        (
+            format!("{} Drag pivot near nt", LCLICK),
+            "Snap the pivot on the nucleotide".to_owned(),
+        ),
+        (
+            "P".to_owned(),
+            "Place the pivot on the pointed nucleotide or at the center of the selection"
+                .to_owned(),
+        ),
+        (
+            "Arrows / PageUp PageDown".to_owned(),
+            format!(
+                "Move the selected helices or grids by 0.5 nm (0.1 nm with {}, 5 nm with {})",
+                SHIFT, CTRL
+            ),
+        ),
         (String::new(), String::new()),
         (format!("When in 3D {} mode", ROTCHAR), String::new()),
         (
             format!("{} on handle", LCLICK),
             "Rotate selected object".to_owned(),
         ),
+        (
+            format!("{}/{}/both + {} on handle", SHIFT, CTRL, LCLICK),
+            "Snap the rotation to 5°/15°/90° steps".to_owned(),
+        ),
+        (
+            "Type an angle + Enter".to_owned(),
+            "Rotate by this angle around the pointed circle of the widget".to_owned(),
+        ),
     ]
 }
 
@@ -707,6 +901,7 @@ fn view_2d_3d_help() -> Vec<(String, String)> {
         (String::new(), String::new()),
         (format!("{} + S", CTRL), "Save design".to_owned()),
         (format!("{} + O", CTRL), "Open design".to_owned()),
+        (format!("{} + P", CTRL), "Open recent design".to_owned()),
         (format!("{} + Z", CTRL), "Undo".to_owned()),
         (format!("{} + R", CTRL), "Redo".to_owned()),
         (String::new(), String::new()),
@@ -783,6 +978,17 @@ fn view_2d_help() -> Vec<(String, String)> {
             format!("{} + {}", CTRL, LCLICK),
             "Make suggested crossover".to_owned(),
         ),
+        (
+            format!("{} + {} + {}", ALT, SHIFT, LCLICK),
+            "Edit insertion (scroll or +/-)".to_owned(),
+        ),
+        (
+            format!("{} + {} + {}", ALT, SHIFT, RCLICK),
+            "Set/Unset deletion mark (annotation only)".to_owned(),
+        ),
+        (String::new(), String::new()),
+        ("With a scaffold selected".to_owned(), String::new()),
+        ("+/-".to_owned(), "Shift scaffold sequence".to_owned()),
     ]
 }
 
@@ -809,7 +1015,8 @@ fn values_of_selection(selection: &Selection, reader: &dyn DesignReader) -> Vec<
         Selection::Grid(_, g_id) => {
             let b1 = reader.grid_has_persistent_phantom(*g_id);
             let b2 = reader.grid_has_small_spheres(*g_id);
-            let mut ret: Vec<String> = vec![b1, b2]
+            let b3 = reader.grid_is_visible(*g_id);
+            let mut ret: Vec<String> = vec![b1, b2, b3]
                 .iter()
                 .map(|b| {
                     if *b {
@@ -833,6 +1040,8 @@ fn values_of_selection(selection: &Selection, reader: &dyn DesignReader) -> Vec<
             s_id.to_string(),
             reader.length_decomposition(*s_id as usize),
             reader.strand_name(*s_id as usize),
+            reader.strand_note(*s_id as usize).unwrap_or_default(),
+            reader.strand_keeps_color(*s_id as usize).to_string(),
         ],
         Selection::Nucleotide(_, nucl) => {
             vec![format!("{}", reader.nucl_is_anchor(*nucl))]
@@ -970,6 +1179,12 @@ impl AddStrandMenu {
                     .push(length_input),
             );
         ret = ret.push(row);
+        if self.text_inputs_are_active {
+            ret = ret.push(
+                Text::new("Click on an existing helix to add the strands on it")
+                    .size(ui_size.main_text()),
+            );
+        }
         ret.into()
     }
 }
@@ -1003,6 +1218,86 @@ impl InsertionLengthState {
     }
 }
 
+/// The widgets editing the chemical modification of the selected nucleotide
+struct ModificationState {
+    kind_picklist: pick_list::State<ModificationKind>,
+    text_state: text_input::State,
+    remove_button: button::State,
+    selection: Selection,
+    input_str: Option<String>,
+}
+
+impl Default for ModificationState {
+    fn default() -> Self {
+        Self {
+            kind_picklist: Default::default(),
+            text_state: Default::default(),
+            remove_button: Default::default(),
+            selection: Selection::Nothing,
+            input_str: None,
+        }
+    }
+}
+
+impl ModificationState {
+    fn update_selection(&mut self, selection: &Selection) {
+        if selection != &self.selection {
+            self.input_str = None;
+            self.selection = *selection;
+        }
+    }
+
+    fn has_keyboard_priority(&self) -> bool {
+        self.text_state.is_focused()
+    }
+
+    fn view<'a, S: AppState>(
+        &'a mut self,
+        mut column: Column<'a, Message<S>>,
+        nucl: Nucl,
+        modification: Option<&Modification>,
+        ui_size: UiSize,
+    ) -> Column<'a, Message<S>> {
+        column = column.push(
+            Row::new()
+                .push(Text::new("Modification").size(ui_size.main_text()))
+                .push(
+                    PickList::new(
+                        &mut self.kind_picklist,
+                        &ALL_MODIFICATION_KINDS[..],
+                        modification.map(|m| m.kind),
+                        move |kind| Message::ModificationKindPicked(nucl, kind),
+                    )
+                    .placeholder("None")
+                    .text_size(ui_size.main_text()),
+                ),
+        );
+        if let Some(modification) = modification {
+            let placeholder = if modification.kind == ModificationKind::Custom {
+                "IDT code"
+            } else {
+                "Description"
+            };
+            let text = self.input_str.as_ref().unwrap_or(&modification.text);
+            column = column.push(
+                TextInput::new(
+                    &mut self.text_state,
+                    placeholder,
+                    text,
+                    Message::ModificationTextInput,
+                )
+                .on_submit(Message::ModificationTextSubmitted(nucl))
+                .size(ui_size.main_text()),
+            );
+            column = column.push(
+                text_btn(&mut self.remove_button, "Remove modification", ui_size)
+                    .on_press(Message::RemoveModification(nucl)),
+            );
+        }
+        column
+    }
+}
+
 pub(super) struct InsertionRequest {
     pub selection: Selection,
     pub length: usize,
@@ -19,7 +19,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::{Selection, UiSize};
 
 use iced_native::{
-    widget::{slider, text_input, Column, Row, Slider, Text, TextInput},
+    widget::{button, slider, text_input, Column, Row, Slider, Text, TextInput},
     Element,
 };
 use iced_wgpu::Renderer;
@@ -187,6 +187,8 @@ pub enum InstanciatedValue {
     HelixGridPosition(Vec3),
     GridOrientation(Rotor3),
     GridNbTurn(f32),
+    /// The phantom length of a grid, or `None` to use the default length
+    GridPhantomLength(Option<usize>),
     BezierVertexPosition(Vec2),
 }
 
@@ -325,6 +327,8 @@ pub struct GridBuilder {
     position_builder: GridPositionBuilder,
     orientation_builder: GridOrientationBuilder,
     nb_turn_slider: slider::State,
+    phantom_length_slider: slider::State,
+    default_phantom_length_button: button::State,
 }
 
 impl GridBuilder {
@@ -333,6 +337,49 @@ impl GridBuilder {
             position_builder: GridPositionBuilder::new_cartesian(position),
             orientation_builder: GridOrientationBuilder::new_direction_angle(orientation),
             nb_turn_slider: Default::default(),
+            phantom_length_slider: Default::default(),
+            default_phantom_length_button: Default::default(),
+        }
+    }
+
+    fn phantom_length_row<'a, S: AppState>(
+        slider: &'a mut slider::State,
+        default_button: &'a mut button::State,
+        ui_size: UiSize,
+        app_state: &S,
+        selection: &Selection,
+    ) -> Option<Element<'a, super::Message<S>, Renderer>> {
+        use crate::consts;
+        if let Selection::Grid(_, g_id) = selection {
+            let length = app_state.get_reader().get_grid_phantom_length(*g_id);
+            let row = Row::new()
+                .spacing(consts::NB_TURN_SLIDER_SPACING)
+                .push(Text::new(
+                    length.unwrap_or(consts::DEFAULT_PHANTOM_LENGTH).to_string(),
+                ))
+                .push(
+                    Slider::new(
+                        slider,
+                        0f32..=consts::MAX_PHANTOM_LENGTH,
+                        length.unwrap_or(consts::DEFAULT_PHANTOM_LENGTH) as f32,
+                        |x| {
+                            super::Message::InstanciatedValueSubmitted(
+                                InstanciatedValue::GridPhantomLength(Some(x.round() as usize)),
+                            )
+                        },
+                    )
+                    .step(consts::PHANTOM_LENGTH_STEP),
+                )
+                .push(
+                    crate::text_btn(default_button, "Default", ui_size).on_press(
+                        super::Message::InstanciatedValueSubmitted(
+                            InstanciatedValue::GridPhantomLength(None),
+                        ),
+                    ),
+                );
+            Some(row.into())
+        } else {
+            None
         }
     }
 
@@ -385,6 +432,16 @@ impl<S: AppState> Builder<S> for GridBuilder {
         if let Some(row) = Self::nb_turn_row(&mut self.nb_turn_slider, app_state, selection) {
             ret = ret.push(row)
         }
+        ret = ret.push(Text::new("Phantom length").size(ui_size.intermediate_text()));
+        if let Some(row) = Self::phantom_length_row(
+            &mut self.phantom_length_slider,
+            &mut self.default_phantom_length_button,
+            ui_size,
+            app_state,
+            selection,
+        ) {
+            ret = ret.push(row)
+        }
         ret.into()
     }
 
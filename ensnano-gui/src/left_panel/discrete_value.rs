@@ -53,9 +53,20 @@ pub struct RequestFactory<R: Requestable> {
 pub enum FactoryId {
     HelixRoll,
     Hyperboloid,
+    HyperboloidEdition,
     Scroll,
+    CameraAnimation,
+    Autosave,
     RigidBody,
     Brownian,
+    DuplicationPattern,
+    XoverPlacement,
+    ScaffoldRouting,
+    StapleFilling,
+    StapleBounds,
+    DesignCheck,
+    ReferenceDesignIsometry,
+    UndoHistoryLength,
 }
 
 impl<R: Requestable> RequestFactory<R> {
@@ -196,6 +207,7 @@ impl DiscreteValue {
                     value,
                 },
             )
+            .on_release(Message::DescreteValueReleased(factory_id))
             .step(self.step)
         } else {
             Slider::new(
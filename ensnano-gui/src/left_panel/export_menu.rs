@@ -17,24 +17,62 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 
 use super::*;
-use iced_native::widget::scrollable;
+use ensnano_exports::obj::{ObjParameters, MAX_SPHERE_SUBDIVISION};
+use iced_native::widget::{scrollable, slider};
 
-#[derive(Default)]
 pub struct ExportMenu {
     scroll: scrollable::State,
     button_cancel: button::State,
     button_oxdna: button::State,
     button_pdb: button::State,
     button_cadnano: button::State,
+    button_csv: button::State,
+    button_obj: button::State,
+    subdivision_slider: slider::State,
+    /// The subdivision level of the spheres in OBJ exports
+    pub sphere_subdivision: u32,
+    /// Only export the strands of the current selection
+    pub selection_only: bool,
+}
+
+impl Default for ExportMenu {
+    fn default() -> Self {
+        Self {
+            scroll: Default::default(),
+            button_cancel: Default::default(),
+            button_oxdna: Default::default(),
+            button_pdb: Default::default(),
+            button_cadnano: Default::default(),
+            button_csv: Default::default(),
+            button_obj: Default::default(),
+            subdivision_slider: Default::default(),
+            sphere_subdivision: ObjParameters::default().sphere_subdivision,
+            selection_only: false,
+        }
+    }
 }
 
 impl ExportMenu {
+    /// The parameters of an OBJ export. If helices are not drawn thick, they are exported as
+    /// cylinders.
+    pub fn obj_parameters(&self, thick_helices: bool) -> ObjParameters {
+        ObjParameters {
+            sphere_subdivision: self.sphere_subdivision,
+            helices_as_cylinders: !thick_helices,
+        }
+    }
+
     pub fn view<'a, S: AppState>(&'a mut self) -> Element<'a, Message<S>> {
         let ret = Column::new()
             .push(
                 Button::new(&mut self.button_cancel, Text::new("Cancel"))
                     .on_press(Message::CancelExport),
             )
+            .push(Checkbox::new(
+                self.selection_only,
+                "Selection only",
+                Message::ExportSelectionOnly,
+            ))
             .push(
                 Button::new(&mut self.button_oxdna, Text::new("Oxdna"))
                     .on_press(Message::Export(ExportType::Oxdna)),
@@ -46,7 +84,25 @@ impl ExportMenu {
             .push(
                 Button::new(&mut self.button_cadnano, Text::new("Cadnano"))
                     .on_press(Message::Export(ExportType::Cadnano)),
-            );
+            )
+            .push(
+                Button::new(&mut self.button_csv, Text::new("Csv"))
+                    .on_press(Message::Export(ExportType::Csv)),
+            )
+            .push(
+                Button::new(&mut self.button_obj, Text::new("Obj mesh"))
+                    .on_press(Message::ExportObj),
+            )
+            .push(Text::new(format!(
+                "Sphere subdivision: {}",
+                self.sphere_subdivision
+            )))
+            .push(Slider::new(
+                &mut self.subdivision_slider,
+                0..=MAX_SPHERE_SUBDIVISION,
+                self.sphere_subdivision,
+                Message::ObjSphereSubdivision,
+            ));
 
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
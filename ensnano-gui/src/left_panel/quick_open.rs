@@ -0,0 +1,132 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! A menu listing the recently opened designs, filtered by the text typed by the user.
+
+use super::*;
+use crate::RecentFile;
+use iced_native::widget::scrollable;
+use std::path::PathBuf;
+
+const MISSING_FILE_COLOR: Color = Color::from_rgb(0.5, 0.5, 0.5);
+
+#[derive(Default)]
+pub struct QuickOpen {
+    /// True if the menu is shown in place of the tabs
+    pub visible: bool,
+    scroll: scrollable::State,
+    button_cancel: button::State,
+    filter_input: text_input::State,
+    filter: String,
+    recent_files: Vec<RecentFile>,
+    file_buttons: Vec<button::State>,
+}
+
+impl QuickOpen {
+    pub fn toggle(&mut self) {
+        self.visible ^= true;
+        self.filter.clear();
+        self.filter_input = if self.visible {
+            text_input::State::focused()
+        } else {
+            Default::default()
+        };
+    }
+
+    pub fn set_recent_files(&mut self, recent_files: Vec<RecentFile>) {
+        self.file_buttons = recent_files.iter().map(|_| Default::default()).collect();
+        self.recent_files = recent_files;
+    }
+
+    pub fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+    }
+
+    pub fn has_keyboard_priority(&self) -> bool {
+        self.visible && self.filter_input.is_focused()
+    }
+
+    fn matches_filter(&self, file: &RecentFile) -> bool {
+        file.path
+            .to_string_lossy()
+            .to_lowercase()
+            .contains(&self.filter.to_lowercase())
+    }
+
+    /// The first file of the list that matches the filter and exists
+    pub fn first_match(&self) -> Option<PathBuf> {
+        self.recent_files
+            .iter()
+            .find(|f| f.exists && self.matches_filter(f))
+            .map(|f| f.path.clone())
+    }
+
+    pub fn view<'a, S: AppState>(&'a mut self, ui_size: UiSize) -> Element<'a, Message<S>> {
+        let mut ret = Column::new()
+            .spacing(5)
+            .push(
+                Button::new(&mut self.button_cancel, Text::new("Cancel"))
+                    .on_press(Message::ToggleQuickOpen),
+            )
+            .push(Text::new("Recent designs").size(ui_size.head_text()))
+            .push(
+                TextInput::new(
+                    &mut self.filter_input,
+                    "Filter",
+                    &self.filter,
+                    Message::QuickOpenFilterInput,
+                )
+                .on_submit(Message::QuickOpenSubmitted)
+                .size(ui_size.main_text()),
+            );
+        let filter = self.filter.to_lowercase();
+        let mut nb_shown = 0;
+        for (file, button_state) in self.recent_files.iter().zip(self.file_buttons.iter_mut()) {
+            if !file.path.to_string_lossy().to_lowercase().contains(&filter) {
+                continue;
+            }
+            nb_shown += 1;
+            let name = file
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let directory = file
+                .path
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mut name = Text::new(name).size(ui_size.main_text());
+            let mut directory = Text::new(directory).size(ui_size.smaller_text());
+            if !file.exists {
+                name = name.color(MISSING_FILE_COLOR);
+                directory = directory.color(MISSING_FILE_COLOR);
+            }
+            ret = ret.push(
+                Button::new(button_state, Column::new().push(name).push(directory))
+                    .width(Length::Fill)
+                    .on_press(Message::OpenRecentFile(file.path.clone())),
+            );
+        }
+        if nb_shown == 0 {
+            ret = ret.push(Text::new("No recent design").size(ui_size.main_text()));
+        }
+
+        Scrollable::new(&mut self.scroll).push(ret).into()
+    }
+}
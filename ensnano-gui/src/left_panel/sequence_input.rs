@@ -15,14 +15,20 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use super::{AppState, Message};
-use iced::{button, text_input, Button, Row, Text, TextInput};
+use super::{text_btn, AppState, Message, UiSize};
+use iced::{button, text_input, Button, Column, Row, Text, TextInput};
 
 pub struct SequenceInput {
     input: text_input::State,
-    #[allow(dead_code)]
     button_state: button::State,
+    force_button: button::State,
+    clear_button: button::State,
     sequence: String,
+    /// The number of mismatches with the scaffold found when the sequence was last applied, if
+    /// it was refused because of them
+    nb_mismatches: Option<usize>,
+    /// Whether the sequence was applied despite its mismatches
+    forced: bool,
 }
 
 impl SequenceInput {
@@ -31,30 +37,83 @@ impl SequenceInput {
             input: Default::default(),
             sequence: String::new(),
             button_state: Default::default(),
+            force_button: Default::default(),
+            clear_button: Default::default(),
+            nb_mismatches: None,
+            forced: false,
         }
     }
 
-    #[allow(dead_code)]
-    pub fn view<S: AppState>(&mut self) -> Row<Message<S>> {
+    pub fn view<S: AppState>(&mut self, ui_size: UiSize) -> Column<Message<S>> {
         let sequence_input = Row::new()
             .spacing(5)
-            .push(TextInput::new(
-                &mut self.input,
-                "Sequence",
-                &self.sequence,
-                Message::SequenceChanged,
-            ))
+            .push(
+                TextInput::new(
+                    &mut self.input,
+                    "Sequence",
+                    &self.sequence,
+                    Message::SequenceChanged,
+                )
+                .size(ui_size.intermediate_text()),
+            )
             .push(
                 Button::new(&mut self.button_state, Text::new("Load File"))
                     .on_press(Message::SequenceFileRequested),
             );
-        sequence_input
+        let mut ret = Column::new().spacing(5).push(sequence_input);
+        match self.nb_mismatches {
+            Some(nb_mismatches) if self.forced => {
+                ret = ret.push(
+                    Text::new(format!(
+                        "Applied despite {} mismatches with the scaffold",
+                        nb_mismatches
+                    ))
+                    .size(ui_size.intermediate_text()),
+                )
+            }
+            Some(nb_mismatches) => {
+                ret = ret
+                    .push(
+                        Text::new(format!("{} mismatches with the scaffold", nb_mismatches))
+                            .size(ui_size.intermediate_text()),
+                    )
+                    .push(
+                        text_btn(&mut self.force_button, "Apply anyway", ui_size)
+                            .on_press(Message::ForceSequence),
+                    )
+            }
+            None => (),
+        }
+        ret.push(
+            text_btn(&mut self.clear_button, "Clear sequence", ui_size)
+                .on_press(Message::ClearSequence),
+        )
     }
 
     pub fn update_sequence(&mut self, sequence: String) {
         self.sequence = sequence;
     }
 
+    pub fn get_sequence(&self) -> String {
+        self.sequence.clone()
+    }
+
+    /// Record the result of the last attempt to apply the sequence. `nb_mismatches` is `None` if
+    /// the sequence was applied.
+    pub fn set_sequence_check(&mut self, nb_mismatches: Option<usize>) {
+        if nb_mismatches.is_some() {
+            self.nb_mismatches = nb_mismatches;
+            self.forced = false;
+        } else if !self.forced {
+            self.nb_mismatches = None;
+        }
+    }
+
+    /// Mark the sequence as beeing applied despite its mismatches
+    pub fn force(&mut self) {
+        self.forced = true;
+    }
+
     pub fn has_keyboard_priority(&self) -> bool {
         self.input.is_focused()
     }
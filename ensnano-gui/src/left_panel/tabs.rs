@@ -69,7 +69,7 @@ macro_rules! extra_jump {
 }
 
 mod edition_tab;
-pub use edition_tab::EditionTab;
+pub use edition_tab::{EditionTab, RecolorPaletteChoice, RecolorScopeChoice};
 mod grids_tab;
 pub use grids_tab::GridTab;
 mod camera_shortcut;
@@ -81,7 +81,7 @@ pub use simulation_tab::SimulationTab;
 mod parameters_tab;
 pub use parameters_tab::ParametersTab;
 mod sequence_tab;
-pub use sequence_tab::SequenceTab;
+pub use sequence_tab::{SequenceTab, StapleReportColumn};
 mod pen_tab;
 pub use pen_tab::PenTab;
 pub(super) mod revolution_tab;
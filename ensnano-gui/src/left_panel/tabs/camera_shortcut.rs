@@ -16,6 +16,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use super::*;
+use ensnano_interactor::graphics::PngExportOptions;
 
 struct TargetShortcut {
     name: &'static str,
@@ -134,9 +135,60 @@ macro_rules! add_screenshot_button {
         )
         .on_press(Message::ScreenShot3D)
         .width(Length::Units($ui_size.button()));
+        let all_cameras_button = Button::new(
+            &mut $self.all_cameras_screenshot_button,
+            Text::new("All cameras").size($ui_size.main_text()),
+        )
+        .on_press(Message::ExportAllCameras);
+
+        let width_input = TextInput::new(
+            &mut $self.png_width_input,
+            "width",
+            &$self.png_width,
+            Message::PngWidthInput,
+        )
+        .width(Length::Units(2 * $ui_size.button()));
+        let height_input = TextInput::new(
+            &mut $self.png_height_input,
+            "height",
+            &$self.png_height,
+            Message::PngHeightInput,
+        )
+        .width(Length::Units(2 * $ui_size.button()));
 
         $ret = $ret.push(Text::new("Screenshot"));
-        $ret = $ret.spacing(5).push(screenshot_button);
+        $ret = $ret.spacing(5).push(
+            Row::new()
+                .spacing(5)
+                .push(screenshot_button)
+                .push(all_cameras_button),
+        );
+        $ret = $ret.push(
+            Row::new()
+                .spacing(5)
+                .push(width_input)
+                .push(Text::new("x"))
+                .push(height_input),
+        );
+        $ret = $ret.push(right_checkbox(
+            $self.transparent_png,
+            "Transparent background",
+            Message::TransparentPng,
+            $ui_size,
+        ));
+    };
+}
+
+macro_rules! add_projection_button {
+    ($ret: ident, $self: ident, $ui_size: ident) => {
+        let projection_button = Button::new(
+            &mut $self.projection_button,
+            Text::new("Perspective/Orthographic").size($ui_size.main_text()),
+        )
+        .on_press(Message::ToggleOrthographicProjection);
+
+        $ret = $ret.push(Text::new("Projection"));
+        $ret = $ret.spacing(5).push(projection_button);
     };
 }
 
@@ -185,6 +237,15 @@ pub struct CameraShortcut {
     new_camera_button: button::State,
     camera_widget_states: Vec<CameraWidgetState>,
     screenshot_button: button::State,
+    all_cameras_screenshot_button: button::State,
+    /// The width of the exported png images. If empty, a default size is used.
+    png_width: String,
+    png_width_input: text_input::State,
+    /// The height of the exported png images. If empty, a default size is used.
+    png_height: String,
+    png_height_input: text_input::State,
+    transparent_png: bool,
+    projection_button: button::State,
 }
 
 impl CameraShortcut {
@@ -202,6 +263,13 @@ impl CameraShortcut {
             new_camera_button: Default::default(),
             camera_widget_states: vec![],
             screenshot_button: Default::default(),
+            all_cameras_screenshot_button: Default::default(),
+            png_width: String::new(),
+            png_width_input: Default::default(),
+            png_height: String::new(),
+            png_height_input: Default::default(),
+            transparent_png: false,
+            projection_button: Default::default(),
         }
     }
 
@@ -248,6 +316,32 @@ impl CameraShortcut {
         self.camera_widget_states
             .iter()
             .any(|s| s.name_input.is_focused())
+            || self.png_width_input.is_focused()
+            || self.png_height_input.is_focused()
+    }
+
+    pub fn set_png_width(&mut self, width: String) {
+        self.png_width = width;
+    }
+
+    pub fn set_png_height(&mut self, height: String) {
+        self.png_height = height;
+    }
+
+    pub fn set_transparent_png(&mut self, transparent: bool) {
+        self.transparent_png = transparent;
+    }
+
+    /// The options of the png export. The default resolution is used unless both the width and
+    /// the height were given.
+    pub fn png_export_options(&self) -> PngExportOptions {
+        let width = self.png_width.trim().parse::<u32>().ok();
+        let height = self.png_height.trim().parse::<u32>().ok();
+        PngExportOptions {
+            resolution: width.zip(height),
+            transparent_background: self.transparent_png,
+            ..Default::default()
+        }
     }
 
     fn set_camera_widget<S: AppState>(&mut self, app: &S) {
@@ -285,6 +379,8 @@ impl CameraShortcut {
 
         add_screenshot_button!(ret, self, ui_size, width);
 
+        add_projection_button!(ret, self, ui_size);
+
         add_custom_camera_row!(ret, self, ui_size);
 
         add_camera_widgets!(ret, self, ui_size);
@@ -18,7 +18,9 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use super::*;
 use ensnano_interactor::graphics::{
-    Background3D, RenderingMode, ALL_BACKGROUND3D, ALL_RENDERING_MODE,
+    Background3D, CameraMode, Colormap, FogCenter, RenderingMode, StrandColoring, ALL_BACKGROUND3D,
+    ALL_CAMERA_MODES, ALL_COLORMAPS, ALL_H_BOND_FILTERS, ALL_ISOLATE_SELECTION, ALL_RENDERING_MODE,
+    ALL_STRAND_COLORINGS,
 };
 
 pub struct CameraTab {
@@ -33,6 +35,23 @@ pub struct CameraTab {
     rendering_mode_picklist: pick_list::State<RenderingMode>,
     check_xover_picklist: pick_list::State<CheckXoversParameter>,
     h_bounds_picklist: pick_list::State<HBoundDisplay>,
+    h_bond_filter_picklist: pick_list::State<HBondFilter>,
+    isolate_selection_picklist: pick_list::State<IsolateSelection>,
+    camera_mode_picklist: pick_list::State<CameraMode>,
+    strand_coloring_picklist: pick_list::State<StrandColoring>,
+    strand_colormap_picklist: pick_list::State<Colormap>,
+    pub show_torsion: bool,
+    /// The last range of the torsion heatmap that was set by the user
+    torsion_range: f32,
+    pub show_strain: bool,
+    /// True if the boundaries and the lengths of the domains are shown in the 2D view
+    pub show_domain_lengths: bool,
+    auto_torsion_range: bool,
+    torsion_range_slider: slider::State,
+    strain_report_buttons: Vec<button::State>,
+    load_reference_button: button::State,
+    remove_reference_button: button::State,
+    reference_isometry_factory: RequestFactory<ReferenceIsometryFactory>,
 }
 
 impl CameraTab {
@@ -49,6 +68,24 @@ impl CameraTab {
             rendering_mode_picklist: Default::default(),
             check_xover_picklist: Default::default(),
             h_bounds_picklist: Default::default(),
+            h_bond_filter_picklist: Default::default(),
+            isolate_selection_picklist: Default::default(),
+            camera_mode_picklist: Default::default(),
+            strand_coloring_picklist: Default::default(),
+            strand_colormap_picklist: Default::default(),
+            show_torsion: false,
+            torsion_range: DEFAULT_TORSION_RANGE,
+            show_strain: false,
+            show_domain_lengths: false,
+            auto_torsion_range: true,
+            torsion_range_slider: Default::default(),
+            strain_report_buttons: Vec::new(),
+            load_reference_button: Default::default(),
+            remove_reference_button: Default::default(),
+            reference_isometry_factory: RequestFactory::new(
+                FactoryId::ReferenceDesignIsometry,
+                ReferenceIsometryFactory {},
+            ),
         }
     }
 
@@ -84,6 +121,13 @@ impl CameraTab {
             )
             .on_press(Message::AllVisible),
         );
+        ret = ret.push(Text::new("Isolate selection").size(ui_size.intermediate_text()));
+        ret = ret.push(PickList::new(
+            &mut self.isolate_selection_picklist,
+            &ALL_ISOLATE_SELECTION[..],
+            Some(app_state.get_isolate_selection()),
+            Message::IsolateSelection,
+        ));
         ret = ret.push(self.fog.view(&ui_size));
 
         let h_bound_column = Column::new()
@@ -98,6 +142,12 @@ impl CameraTab {
                 .as_slice(),
                 Some(app_state.get_h_bounds_display()),
                 Message::ShowHBonds,
+            ))
+            .push(PickList::new(
+                &mut self.h_bond_filter_picklist,
+                &ALL_H_BOND_FILTERS[..],
+                Some(app_state.get_h_bond_filter()),
+                Message::HBondFilter,
             ));
 
         ret = ret.push(h_bound_column);
@@ -116,6 +166,14 @@ impl CameraTab {
             ui_size,
         ));
 
+        ret = ret.push(Text::new("Rotation mode").size(ui_size.intermediate_text()));
+        ret = ret.push(PickList::new(
+            &mut self.camera_mode_picklist,
+            &ALL_CAMERA_MODES[..],
+            Some(app_state.get_camera_mode()),
+            Message::CameraMode,
+        ));
+
         subsection!(ret, ui_size, "Highlight Xovers");
         ret = ret.push(PickList::new(
             &mut self.check_xover_picklist,
@@ -123,6 +181,39 @@ impl CameraTab {
             Some(app_state.get_checked_xovers_parameters()),
             Message::CheckXoversParameter,
         ));
+        if app_state.get_checked_xovers_parameters() != CheckXoversParameter::None {
+            let strains: Vec<_> = app_state
+                .get_reader()
+                .get_xovers_by_strain()
+                .into_iter()
+                .take(NB_STRAIN_REPORT_XOVERS)
+                .collect();
+            self.strain_report_buttons
+                .resize_with(strains.len(), Default::default);
+            ret = ret.push(Text::new("Most strained xovers").size(ui_size.intermediate_text()));
+            for (xover, state) in strains.iter().zip(self.strain_report_buttons.iter_mut()) {
+                let xover_id = xover.xover_id;
+                let (n1, n2) = xover.xover;
+                let description = format!(
+                    "h{}:nt{} -> h{}:nt{}  {:.2} nm",
+                    n1.helix, n1.position, n2.helix, n2.position, xover.strain
+                );
+                ret = ret.push(
+                    Row::new()
+                        .spacing(5)
+                        .push(
+                            Button::new(
+                                state,
+                                Text::new(description).size(ui_size.intermediate_text()),
+                            )
+                            .on_press(Message::StrainedXoverClicked(xover_id)),
+                        )
+                        .push(Checkbox::new(xover.checked, "", move |_| {
+                            Message::ToggleXoverChecked(xover_id)
+                        })),
+                );
+            }
+        }
 
         subsection!(ret, ui_size, "Rendering");
         ret = ret.push(Text::new("Style"));
@@ -144,10 +235,161 @@ impl CameraTab {
             "Expand insertions",
             Message::SetExpandInsertions,
         ));
+        ret = ret.push(Checkbox::new(
+            app_state.show_2d_minimap(),
+            "Show 2D minimap",
+            Message::Show2dMinimap,
+        ));
+        ret = ret.push(Checkbox::new(
+            self.show_domain_lengths,
+            "Show domain lengths in 2D",
+            Message::ShowDomainLengths,
+        ));
+        ret = ret.push(Checkbox::new(
+            app_state.show_anchors(),
+            "Show anchors",
+            Message::ShowAnchors,
+        ));
+        ret = ret.push(Checkbox::new(
+            app_state.local_phantoms(),
+            "Phantoms on pointed grid only",
+            Message::LocalPhantoms,
+        ));
+        ret = ret.push(Checkbox::new(
+            app_state.show_nucleotide_ruler(),
+            "Nucleotide ruler",
+            Message::ShowNucleotideRuler,
+        ));
+        ret = ret.push(Checkbox::new(
+            app_state.full_detail(),
+            "Full detail",
+            Message::FullDetail,
+        ));
+        ret = ret.push(Text::new("Color strands by"));
+        ret = ret.push(PickList::new(
+            &mut self.strand_coloring_picklist,
+            &ALL_STRAND_COLORINGS[..],
+            Some(app_state.get_strand_coloring()),
+            Message::StrandColoring,
+        ));
+        if app_state.get_strand_coloring() != StrandColoring::Stored {
+            ret = ret.push(PickList::new(
+                &mut self.strand_colormap_picklist,
+                &ALL_COLORMAPS[..],
+                Some(app_state.get_strand_colormap()),
+                Message::StrandColormap,
+            ));
+        }
+        ret = ret.push(Checkbox::new(
+            self.show_torsion,
+            "Show torsion heatmap",
+            Message::ShowTorsion,
+        ));
+        if self.show_torsion {
+            let torsion_range = self.torsion_range;
+            ret = ret.push(Checkbox::new(
+                self.auto_torsion_range,
+                "Auto range",
+                move |auto| Message::TorsionRange(if auto { None } else { Some(torsion_range) }),
+            ));
+            let range_text = format!("Range ±{:.0}°", self.torsion_range);
+            let range_slider = if self.auto_torsion_range {
+                Slider::new(
+                    &mut self.torsion_range_slider,
+                    MIN_TORSION_RANGE..=MAX_TORSION_RANGE,
+                    self.torsion_range,
+                    |_| Message::Nothing,
+                )
+                .style(DesactivatedSlider)
+            } else {
+                Slider::new(
+                    &mut self.torsion_range_slider,
+                    MIN_TORSION_RANGE..=MAX_TORSION_RANGE,
+                    self.torsion_range,
+                    |range| Message::TorsionRange(Some(range)),
+                )
+            };
+            ret = ret.push(
+                Row::new()
+                    .spacing(5)
+                    .push(Text::new(range_text))
+                    .push(range_slider),
+            );
+        }
+        ret = ret.push(Checkbox::new(
+            self.show_strain,
+            "Show strain during simulations",
+            Message::ShowStrain,
+        ));
+
+        subsection!(ret, ui_size, "Reference design");
+        ret = ret.push(
+            text_btn(
+                &mut self.load_reference_button,
+                "Load reference design",
+                ui_size.clone(),
+            )
+            .on_press(Message::LoadReferenceDesign),
+        );
+        if let Some(visible) = app_state.get_reference_design_visibility() {
+            ret = ret.push(right_checkbox(
+                visible,
+                "Show reference design",
+                Message::ShowReferenceDesign,
+                ui_size,
+            ));
+            for view in self
+                .reference_isometry_factory
+                .view(true, ui_size.intermediate_text())
+                .into_iter()
+            {
+                ret = ret.push(view);
+            }
+            ret = ret.push(
+                text_btn(
+                    &mut self.remove_reference_button,
+                    "Remove reference design",
+                    ui_size.clone(),
+                )
+                .on_press(Message::RemoveReferenceDesign),
+            );
+        }
+
+        subsection!(ret, ui_size, "Changes since last save");
+        ret = ret.push(right_checkbox(
+            app_state.is_showing_design_changes(),
+            "Highlight changes",
+            Message::ShowDesignChanges,
+            ui_size,
+        ));
 
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
 
+    pub fn update_reference_isometry(
+        &mut self,
+        value_id: ValueId,
+        value: f32,
+        request: &mut Option<Isometry3>,
+    ) {
+        self.reference_isometry_factory
+            .update_request(value_id, value, request);
+    }
+
+    pub fn reset_reference_isometry(&mut self) {
+        self.reference_isometry_factory = RequestFactory::new(
+            FactoryId::ReferenceDesignIsometry,
+            ReferenceIsometryFactory {},
+        );
+    }
+
+    pub fn set_torsion_range(&mut self, range: Option<f32>) {
+        self.auto_torsion_range = range.is_none();
+        if let Some(range) = range {
+            self.torsion_range = range;
+        }
+    }
+
     pub fn fog_visible(&mut self, visible: bool) {
         self.fog.visible = visible
     }
@@ -172,6 +414,22 @@ impl CameraTab {
         self.fog.from_camera = from_camera;
     }
 
+    pub fn fog_follow_selection(&mut self, follow_selection: bool) {
+        self.fog.follow_selection = follow_selection;
+    }
+
+    /// Show fog parameters that were set without using the panel
+    pub fn set_fog(&mut self, fog: &Fog) {
+        use ensnano_interactor::graphics::fog_kind;
+        self.fog.visible = fog.is_visible();
+        self.fog.dark = fog.fog_kind == fog_kind::DARK_FOG;
+        self.fog.reversed = fog.fog_kind == fog_kind::REVERSED_FOG;
+        self.fog.from_camera = fog.center == FogCenter::Camera;
+        self.fog.follow_selection = fog.center == FogCenter::Selection;
+        self.fog.length = fog.length;
+        self.fog.radius = fog.radius;
+    }
+
     pub fn get_fog_request(&self) -> Fog {
         self.fog.request()
     }
@@ -180,6 +438,8 @@ impl CameraTab {
 struct FogParameters {
     visible: bool,
     from_camera: bool,
+    /// True if the fog is centered on the selection instead of the pivot
+    follow_selection: bool,
     dark: bool,
     radius: f32,
     radius_slider: slider::State,
@@ -253,6 +513,13 @@ impl FogParameters {
                     .push(gradient_text)
                     .push(softness_slider),
             );
+        if self.visible && !self.from_camera {
+            column = column.push(Checkbox::new(
+                self.follow_selection,
+                "Follow selection",
+                Message::FogFollowSelection,
+            ));
+        }
         column
     }
 
@@ -267,12 +534,24 @@ impl FogParameters {
             )
             .fog_kind(),
             length: self.length,
-            from_camera: self.from_camera,
+            center: self.center(),
             alt_fog_center: None,
         }
     }
 }
 
+impl FogParameters {
+    fn center(&self) -> FogCenter {
+        if self.from_camera {
+            FogCenter::Camera
+        } else if self.follow_selection {
+            FogCenter::Selection
+        } else {
+            FogCenter::Pivot
+        }
+    }
+}
+
 impl Default for FogParameters {
     fn default() -> Self {
         Self {
@@ -283,6 +562,7 @@ impl Default for FogParameters {
             length_slider: Default::default(),
             radius_slider: Default::default(),
             from_camera: true,
+            follow_selection: false,
             picklist: Default::default(),
             reversed: false,
         }
@@ -305,6 +585,10 @@ impl Default for FogChoice {
     }
 }
 
+const DEFAULT_TORSION_RANGE: f32 = 10.;
+const MIN_TORSION_RANGE: f32 = 1.;
+const MAX_TORSION_RANGE: f32 = 90.;
+
 const ALL_FOG_CHOICE: &'static [FogChoice] = &[
     FogChoice::None,
     FogChoice::FromCamera,
@@ -20,13 +20,54 @@ use super::*;
 pub struct EditionTab<S: AppState> {
     scroll: iced::scrollable::State,
     helix_roll_factory: RequestFactory<HelixRoll>,
+    duplication_pattern_factory: RequestFactory<DuplicationPatternFactory>,
+    duplicate_button: button::State,
+    align_helices_button: button::State,
+    helix_parameters_picklist: pick_list::State<NamedParameter>,
+    reset_helix_parameters_button: button::State,
+    xover_placement_factory: RequestFactory<XoverPlacementFactory>,
+    protect_scaffold_from_xovers: bool,
+    place_xovers_button: button::State,
+    scaffold_routing_factory: RequestFactory<ScaffoldRoutingFactory>,
+    scaffold_routing_spiral: bool,
+    scaffold_routing_clear_existing: bool,
+    route_scaffold_button: button::State,
+    staple_filling_factory: RequestFactory<StapleFillingFactory>,
+    staple_domain_lengths: String,
+    staple_domain_lengths_input: text_input::State,
+    nick_at_selected_nucls: bool,
+    fill_staples_button: button::State,
+    recolor_scope: RecolorScopeChoice,
+    recolor_scope_picklist: pick_list::State<RecolorScopeChoice>,
+    recolor_palette: RecolorPaletteChoice,
+    recolor_palette_picklist: pick_list::State<RecolorPaletteChoice>,
+    recolor_colors: String,
+    recolor_colors_input: text_input::State,
+    recolor_button: button::State,
+    anchor_strand_ends_only: bool,
+    anchor_button: button::State,
     color_picker: ColorPicker,
-    _sequence_input: SequenceInput,
+    sequence_input: SequenceInput,
     redim_helices_button: button::State,
     redim_all_helices_button: button::State,
+    compact_layout_button: button::State,
+    pivot_on_selection_button: button::State,
     roll_target_btn: GoStop<S>,
     color_square_state: ColorState,
     memory_color_squares: VecDeque<MemoryColorSquare>,
+    undo_history: UndoHistory,
+    history_buttons: Vec<button::State>,
+    named_selection_name: String,
+    named_selection_input: text_input::State,
+    save_selection_button: button::State,
+    named_selection_buttons: Vec<NamedSelectionButtons>,
+}
+
+#[derive(Default)]
+struct NamedSelectionButtons {
+    recall: button::State,
+    rename: button::State,
+    delete: button::State,
 }
 
 struct MemoryColorSquare {
@@ -185,16 +226,64 @@ impl<S: AppState> EditionTab<S> {
         Self {
             scroll: Default::default(),
             helix_roll_factory: RequestFactory::new(FactoryId::HelixRoll, HelixRoll {}),
+            duplication_pattern_factory: RequestFactory::new(
+                FactoryId::DuplicationPattern,
+                DuplicationPatternFactory {},
+            ),
+            duplicate_button: Default::default(),
+            align_helices_button: Default::default(),
+            helix_parameters_picklist: Default::default(),
+            reset_helix_parameters_button: Default::default(),
+            xover_placement_factory: RequestFactory::new(
+                FactoryId::XoverPlacement,
+                XoverPlacementFactory {},
+            ),
+            protect_scaffold_from_xovers: XoverPlacementParameters::default().protect_scaffold,
+            anchor_strand_ends_only: false,
+            anchor_button: Default::default(),
+            place_xovers_button: Default::default(),
+            scaffold_routing_factory: RequestFactory::new(
+                FactoryId::ScaffoldRouting,
+                ScaffoldRoutingFactory {},
+            ),
+            scaffold_routing_spiral: false,
+            scaffold_routing_clear_existing: ScaffoldRoutingParameters::default().clear_existing,
+            route_scaffold_button: Default::default(),
+            staple_filling_factory: RequestFactory::new(
+                FactoryId::StapleFilling,
+                StapleFillingFactory {},
+            ),
+            staple_domain_lengths: format_domain_lengths(
+                &StapleFillingParameters::default().domain_lengths,
+            ),
+            staple_domain_lengths_input: Default::default(),
+            nick_at_selected_nucls: false,
+            fill_staples_button: Default::default(),
+            recolor_scope: RecolorScopeChoice::AllStaples,
+            recolor_scope_picklist: Default::default(),
+            recolor_palette: RecolorPaletteChoice::RandomDistinct,
+            recolor_palette_picklist: Default::default(),
+            recolor_colors: String::new(),
+            recolor_colors_input: Default::default(),
+            recolor_button: Default::default(),
             color_picker: ColorPicker::new(),
-            _sequence_input: SequenceInput::new(),
+            sequence_input: SequenceInput::new(),
             redim_helices_button: Default::default(),
             redim_all_helices_button: Default::default(),
+            compact_layout_button: Default::default(),
+            pivot_on_selection_button: Default::default(),
             roll_target_btn: GoStop::new(
                 "Autoroll selected helices".to_owned(),
                 Message::RollTargeted,
             ),
             color_square_state: Default::default(),
             memory_color_squares: VecDeque::new(),
+            undo_history: Default::default(),
+            history_buttons: Vec::new(),
+            named_selection_name: String::new(),
+            named_selection_input: Default::default(),
+            save_selection_button: Default::default(),
+            named_selection_buttons: Vec::new(),
         }
     }
 
@@ -210,13 +299,200 @@ impl<S: AppState> EditionTab<S> {
         section!(ret, ui_size, "Edition");
         add_roll_slider!(ret, self, app_state, ui_size);
         add_autoroll_button!(ret, self, app_state, roll_target_helices);
+        let mut align_helices_button = text_btn(
+            &mut self.align_helices_button,
+            "Align on last selected helix",
+            ui_size,
+        );
+        if roll_target_helices.len() > 1 {
+            align_helices_button = align_helices_button.on_press(Message::AlignHelices);
+        }
+        ret = ret.push(align_helices_button);
+
+        if !roll_target_helices.is_empty() {
+            subsection!(ret, ui_size, "Helix parameters");
+            ret = ret.push(PickList::new(
+                &mut self.helix_parameters_picklist,
+                &ensnano_design::NAMED_DNA_PARAMETERS[..],
+                None,
+                Message::SetSelectedHelicesParameters,
+            ));
+            ret = ret.push(
+                text_btn(
+                    &mut self.reset_helix_parameters_button,
+                    "Use design parameters",
+                    ui_size,
+                )
+                .on_press(Message::ResetSelectedHelicesParameters),
+            );
+        }
 
         let selection_contains_strand =
             ensnano_interactor::extract_strands_from_selection(app_state.get_selection()).len() > 0;
         if selection_contains_strand {
             let color_square = self.color_picker.color_square(&mut self.color_square_state);
             add_color_square!(ret, self, color_square);
+            subsection!(ret, ui_size, "Sequence");
+            ret = ret.push(self.sequence_input.view(ui_size));
+        }
+
+        subsection!(ret, ui_size, "Duplicate in pattern");
+        let can_duplicate = !roll_target_helices.is_empty() || selection_contains_strand;
+        for view in self
+            .duplication_pattern_factory
+            .view(can_duplicate, ui_size.intermediate_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+        let mut duplicate_button = text_btn(&mut self.duplicate_button, "Duplicate", ui_size);
+        if can_duplicate {
+            duplicate_button = duplicate_button.on_press(Message::DuplicateInPattern);
+        }
+        ret = ret.push(duplicate_button);
+
+        subsection!(ret, ui_size, "Xovers between two helices");
+        let can_place_xovers = roll_target_helices.len() == 2;
+        for view in self
+            .xover_placement_factory
+            .view(can_place_xovers, ui_size.intermediate_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+        ret = ret.push(right_checkbox(
+            self.protect_scaffold_from_xovers,
+            "Protect scaffold",
+            Message::ProtectScaffoldFromXovers,
+            ui_size,
+        ));
+        let mut place_xovers_button =
+            text_btn(&mut self.place_xovers_button, "Place xovers", ui_size);
+        if can_place_xovers {
+            place_xovers_button = place_xovers_button.on_press(Message::PlaceXoversBetweenHelices);
+        }
+        ret = ret.push(place_xovers_button);
+
+        subsection!(ret, ui_size, "Scaffold routing");
+        let can_route_scaffold = !roll_target_helices.is_empty()
+            || !ensnano_interactor::extract_grids(app_state.get_selection()).is_empty();
+        for view in self
+            .scaffold_routing_factory
+            .view(can_route_scaffold, ui_size.intermediate_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+        ret = ret.push(right_checkbox(
+            self.scaffold_routing_spiral,
+            "Spiral order on grids",
+            Message::ScaffoldRoutingSpiral,
+            ui_size,
+        ));
+        ret = ret.push(right_checkbox(
+            self.scaffold_routing_clear_existing,
+            "Clear existing strands",
+            Message::ScaffoldRoutingClearExisting,
+            ui_size,
+        ));
+        let mut route_scaffold_button =
+            text_btn(&mut self.route_scaffold_button, "Route scaffold", ui_size);
+        if can_route_scaffold {
+            route_scaffold_button = route_scaffold_button.on_press(Message::RouteScaffold);
+        }
+        ret = ret.push(route_scaffold_button);
+
+        subsection!(ret, ui_size, "Staple filling");
+        for view in self
+            .staple_filling_factory
+            .view(true, ui_size.intermediate_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+        ret = ret.push(
+            TextInput::new(
+                &mut self.staple_domain_lengths_input,
+                "Domain lengths, e.g. 8-16-8",
+                &self.staple_domain_lengths,
+                Message::StapleDomainLengthsInput,
+            )
+            .size(ui_size.intermediate_text()),
+        );
+        ret = ret.push(right_checkbox(
+            self.nick_at_selected_nucls,
+            "Nick at selected nucleotides",
+            Message::NickAtSelectedNucls,
+            ui_size,
+        ));
+        let mut fill_staples_button =
+            text_btn(&mut self.fill_staples_button, "Fill staples", ui_size);
+        if parse_domain_lengths(&self.staple_domain_lengths).is_some() {
+            fill_staples_button = fill_staples_button.on_press(Message::FillStaples);
+        }
+        ret = ret.push(fill_staples_button);
+
+        subsection!(ret, ui_size, "Staple recoloring");
+        ret = ret.push(
+            PickList::new(
+                &mut self.recolor_scope_picklist,
+                &ALL_RECOLOR_SCOPE_CHOICES[..],
+                Some(self.recolor_scope),
+                Message::RecolorScopePicked,
+            )
+            .text_size(ui_size.main_text()),
+        );
+        ret = ret.push(
+            PickList::new(
+                &mut self.recolor_palette_picklist,
+                &ALL_RECOLOR_PALETTE_CHOICES[..],
+                Some(self.recolor_palette),
+                Message::RecolorPalettePicked,
+            )
+            .text_size(ui_size.main_text()),
+        );
+        let palette_is_valid = parse_color_list(&self.recolor_colors).is_some();
+        if self.recolor_palette == RecolorPaletteChoice::Fixed {
+            ret = ret.push(
+                TextInput::new(
+                    &mut self.recolor_colors_input,
+                    "Colors, e.g. #E52B50 #0070BB",
+                    &self.recolor_colors,
+                    Message::RecolorColorsInput,
+                )
+                .style(BadValue(palette_is_valid))
+                .size(ui_size.intermediate_text()),
+            );
+        }
+        let scope_is_valid = match self.recolor_scope {
+            RecolorScopeChoice::AllStaples => true,
+            RecolorScopeChoice::SelectedStrands => selection_contains_strand,
+            RecolorScopeChoice::SelectedGroup => app_state.get_selected_group().is_some(),
+        };
+        let mut recolor_button = text_btn(&mut self.recolor_button, "Recolor staples", ui_size);
+        if scope_is_valid
+            && (palette_is_valid || self.recolor_palette != RecolorPaletteChoice::Fixed)
+        {
+            recolor_button = recolor_button.on_press(Message::RecolorStaples);
+        }
+        ret = ret.push(recolor_button);
+
+        subsection!(ret, ui_size, "Anchors");
+        ret = ret.push(right_checkbox(
+            self.anchor_strand_ends_only,
+            "Only strand ends",
+            Message::AnchorStrandEndsOnly,
+            ui_size,
+        ));
+        let mut anchor_button = text_btn(
+            &mut self.anchor_button,
+            "Flip anchors of selection",
+            ui_size,
+        );
+        if selection_contains_strand || !roll_target_helices.is_empty() {
+            anchor_button = anchor_button.on_press(Message::AnchorSelectedStrands);
         }
+        ret = ret.push(anchor_button);
 
         subsection!(ret, ui_size, "Suggestions Parameters");
         add_suggestion_parameters_checkboxes!(ret, self, app_state, ui_size);
@@ -224,9 +500,136 @@ impl<S: AppState> EditionTab<S> {
         subsection!(ret, ui_size, "Tighten 2D helices");
         add_tighten_helices_button!(ret, self, app_state, ui_size, roll_target_helices);
 
+        subsection!(ret, ui_size, "2D layout");
+        ret = ret.push(
+            text_btn(&mut self.compact_layout_button, "Compact", ui_size)
+                .on_press(Message::Compact2dLayout),
+        );
+
+        subsection!(ret, ui_size, "Group pivot");
+        let mut pivot_on_selection_button = text_btn(
+            &mut self.pivot_on_selection_button,
+            "Center on selection",
+            ui_size,
+        );
+        if !app_state.get_selection().is_empty() {
+            pivot_on_selection_button =
+                pivot_on_selection_button.on_press(Message::PivotOnSelection);
+        }
+        ret = ret.push(pivot_on_selection_button);
+
+        subsection!(ret, ui_size, "Saved selections");
+        let can_save = !self.named_selection_name.is_empty();
+        ret = ret.push(
+            TextInput::new(
+                &mut self.named_selection_input,
+                "Selection name",
+                &self.named_selection_name,
+                Message::NamedSelectionNameInput,
+            )
+            .size(ui_size.intermediate_text()),
+        );
+        let mut save_selection_button =
+            text_btn(&mut self.save_selection_button, "Save selection", ui_size);
+        if can_save && !app_state.get_selection().is_empty() {
+            save_selection_button = save_selection_button.on_press(Message::SaveNamedSelection);
+        }
+        ret = ret.push(save_selection_button);
+        let names: Vec<String> = app_state
+            .get_reader()
+            .get_named_selections()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        self.named_selection_buttons
+            .resize_with(names.len(), Default::default);
+        for (idx, (name, buttons)) in names
+            .into_iter()
+            .zip(self.named_selection_buttons.iter_mut())
+            .enumerate()
+        {
+            let recall_button = light_icon_btn(&mut buttons.recall, LightIcon::Visibility, ui_size)
+                .on_press(Message::RecallNamedSelection(idx));
+            let mut rename_button = light_icon_btn(&mut buttons.rename, LightIcon::Edit, ui_size);
+            if can_save {
+                rename_button = rename_button.on_press(Message::RenameNamedSelection(idx));
+            }
+            let delete_button = light_icon_btn(&mut buttons.delete, LightIcon::Delete, ui_size)
+                .on_press(Message::DeleteNamedSelection(idx));
+            ret = ret.push(
+                Row::new()
+                    .push(Text::new(name).size(ui_size.intermediate_text()))
+                    .push(iced::Space::with_width(Length::Fill))
+                    .push(recall_button)
+                    .push(rename_button)
+                    .push(delete_button),
+            );
+        }
+
+        subsection!(ret, ui_size, "History");
+        let max_len = app_state.get_undo_history_length();
+        for (entry, state) in self
+            .history_entries(max_len)
+            .into_iter()
+            .zip(self.history_buttons.iter_mut())
+        {
+            let (label, steps) = entry;
+            if steps == 0 {
+                ret = ret.push(Text::new(format!("> {}", label)).size(ui_size.intermediate_text()));
+            } else {
+                let mut text = Text::new(label).size(ui_size.intermediate_text());
+                if steps > 0 {
+                    text = text.color([0.6, 0.6, 0.6]);
+                }
+                ret = ret
+                    .push(Button::new(state, text).on_press(Message::HistoryEntryClicked(steps)));
+            }
+        }
+
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
 
+    pub fn set_undo_history(&mut self, history: UndoHistory) {
+        self.undo_history = history;
+        let nb_entries =
+            self.undo_history.undo_labels.len() + self.undo_history.redo_labels.len() + 1;
+        self.history_buttons
+            .resize_with(nb_entries, Default::default);
+    }
+
+    /// The entries of the history panel, from the oldest to the most recent one, with the number
+    /// of operations that must be redone (or undone if negative) to reach them.
+    ///
+    /// At most `max_len` entries are listed on each side of the current state.
+    fn history_entries(&self, max_len: usize) -> Vec<(String, isize)> {
+        let nb_undo = self.undo_history.undo_labels.len();
+        let first_listed = nb_undo.saturating_sub(max_len);
+        let mut ret = Vec::new();
+        if first_listed == 0 {
+            ret.push((String::from("Initial state"), -(nb_undo as isize)));
+        }
+        for (i, label) in self
+            .undo_history
+            .undo_labels
+            .iter()
+            .enumerate()
+            .skip(first_listed)
+        {
+            // The state reached after the operation
+            ret.push((label.clone(), i as isize + 1 - nb_undo as isize));
+        }
+        for (i, label) in self
+            .undo_history
+            .redo_labels
+            .iter()
+            .take(max_len)
+            .enumerate()
+        {
+            ret.push((label.clone(), i as isize + 1));
+        }
+        ret
+    }
+
     fn get_roll_target_helices(&self, selection: &[DnaElementKey]) -> Vec<usize> {
         let mut ret = vec![];
         for s in selection.iter() {
@@ -247,6 +650,130 @@ impl<S: AppState> EditionTab<S> {
             .update_request(value_id, value, request);
     }
 
+    pub fn update_duplication_pattern(&mut self, value_id: ValueId, value: f32) {
+        self.duplication_pattern_factory
+            .update_value(value_id, value);
+    }
+
+    pub fn make_duplication_pattern_request(&self, request: &mut Option<DuplicationPattern>) {
+        self.duplication_pattern_factory.make_request(request)
+    }
+
+    pub fn update_xover_placement(&mut self, value_id: ValueId, value: f32) {
+        self.xover_placement_factory.update_value(value_id, value);
+    }
+
+    pub fn set_protect_scaffold(&mut self, protect_scaffold: bool) {
+        self.protect_scaffold_from_xovers = protect_scaffold;
+    }
+
+    pub fn update_scaffold_routing(&mut self, value_id: ValueId, value: f32) {
+        self.scaffold_routing_factory.update_value(value_id, value);
+    }
+
+    pub fn set_scaffold_routing_spiral(&mut self, spiral: bool) {
+        self.scaffold_routing_spiral = spiral;
+    }
+
+    pub fn set_scaffold_routing_clear_existing(&mut self, clear: bool) {
+        self.scaffold_routing_clear_existing = clear;
+    }
+
+    pub fn update_staple_filling(&mut self, value_id: ValueId, value: f32) {
+        self.staple_filling_factory.update_value(value_id, value);
+    }
+
+    pub fn set_staple_domain_lengths(&mut self, text: String) {
+        self.staple_domain_lengths = text;
+    }
+
+    pub fn set_nick_at_selected_nucls(&mut self, nick: bool) {
+        self.nick_at_selected_nucls = nick;
+    }
+
+    pub fn nick_at_selected_nucls(&self) -> bool {
+        self.nick_at_selected_nucls
+    }
+
+    /// `request` is left unchanged if the domain lengths typed by the user cannot be parsed.
+    pub fn make_staple_filling_request(&self, request: &mut Option<StapleFillingParameters>) {
+        if let Some(domain_lengths) = parse_domain_lengths(&self.staple_domain_lengths) {
+            self.staple_filling_factory.make_request(request);
+            if let Some(parameters) = request.as_mut() {
+                parameters.domain_lengths = domain_lengths;
+            }
+        }
+    }
+
+    pub fn set_recolor_scope(&mut self, scope: RecolorScopeChoice) {
+        self.recolor_scope = scope;
+    }
+
+    pub fn recolor_scope(&self) -> RecolorScopeChoice {
+        self.recolor_scope
+    }
+
+    pub fn set_recolor_palette(&mut self, palette: RecolorPaletteChoice) {
+        self.recolor_palette = palette;
+    }
+
+    pub fn set_recolor_colors(&mut self, text: String) {
+        self.recolor_colors = text;
+    }
+
+    /// The palette chosen by the user, or `None` if the list of colors typed by the user cannot be
+    /// parsed.
+    pub fn recolor_palette(&self) -> Option<RecolorPalette> {
+        match self.recolor_palette {
+            RecolorPaletteChoice::RandomDistinct => Some(RecolorPalette::RandomDistinct),
+            RecolorPaletteChoice::Rainbow => Some(RecolorPalette::Rainbow),
+            RecolorPaletteChoice::ByHelix => Some(RecolorPalette::ByHelix),
+            RecolorPaletteChoice::Fixed => {
+                parse_color_list(&self.recolor_colors).map(RecolorPalette::Fixed)
+            }
+        }
+    }
+
+    /// The order in which the helices of a selected grid are visited by the scaffold
+    pub fn scaffold_routing_pattern(&self) -> ScaffoldRoutingPattern {
+        if self.scaffold_routing_spiral {
+            ScaffoldRoutingPattern::Spiral
+        } else {
+            ScaffoldRoutingPattern::Serpentine
+        }
+    }
+
+    pub fn make_scaffold_routing_request(&self, request: &mut Option<ScaffoldRoutingParameters>) {
+        self.scaffold_routing_factory.make_request(request);
+        if let Some(parameters) = request.as_mut() {
+            parameters.clear_existing = self.scaffold_routing_clear_existing;
+        }
+    }
+
+    pub fn set_anchor_strand_ends_only(&mut self, ends_only: bool) {
+        self.anchor_strand_ends_only = ends_only;
+    }
+
+    pub fn anchor_strand_ends_only(&self) -> bool {
+        self.anchor_strand_ends_only
+    }
+
+    pub fn set_named_selection_name(&mut self, name: String) {
+        self.named_selection_name = name;
+    }
+
+    /// The name typed in the named selection input. The input is cleared.
+    pub fn take_named_selection_name(&mut self) -> String {
+        std::mem::take(&mut self.named_selection_name)
+    }
+
+    pub fn make_xover_placement_request(&self, request: &mut Option<XoverPlacementParameters>) {
+        self.xover_placement_factory.make_request(request);
+        if let Some(parameters) = request.as_mut() {
+            parameters.protect_scaffold = self.protect_scaffold_from_xovers;
+        }
+    }
+
     pub fn get_roll_request(&mut self, selection: &[DnaElementKey]) -> Option<RollRequest> {
         let roll_target_helices = self.get_roll_target_helices(selection);
         if roll_target_helices.len() > 0 {
@@ -260,6 +787,26 @@ impl<S: AppState> EditionTab<S> {
         }
     }
 
+    pub fn update_sequence(&mut self, sequence: String) {
+        self.sequence_input.update_sequence(sequence)
+    }
+
+    pub fn get_sequence(&self) -> String {
+        self.sequence_input.get_sequence()
+    }
+
+    pub fn set_sequence_check(&mut self, nb_mismatches: Option<usize>) {
+        self.sequence_input.set_sequence_check(nb_mismatches)
+    }
+
+    pub fn force_sequence(&mut self) {
+        self.sequence_input.force()
+    }
+
+    pub fn has_keyboard_priority(&self) -> bool {
+        self.sequence_input.has_keyboard_priority()
+    }
+
     pub fn strand_color_change(&mut self) -> u32 {
         let color = self.color_picker.update_color();
         super::color_to_u32(color)
@@ -285,3 +832,87 @@ impl<S: AppState> EditionTab<S> {
         }
     }
 }
+
+/// Parse a list of positive lengths separated by dashes, e.g. "8-16-8".
+fn parse_domain_lengths(text: &str) -> Option<Vec<usize>> {
+    text.split('-')
+        .map(|length| length.trim().parse::<usize>().ok().filter(|l| *l > 0))
+        .collect()
+}
+
+fn format_domain_lengths(lengths: &[usize]) -> String {
+    lengths
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The strands recolored by the staple recoloring.
+#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+pub enum RecolorScopeChoice {
+    AllStaples,
+    SelectedStrands,
+    SelectedGroup,
+}
+
+const ALL_RECOLOR_SCOPE_CHOICES: &[RecolorScopeChoice] = &[
+    RecolorScopeChoice::AllStaples,
+    RecolorScopeChoice::SelectedStrands,
+    RecolorScopeChoice::SelectedGroup,
+];
+
+impl std::fmt::Display for RecolorScopeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::AllStaples => "All staples",
+            Self::SelectedStrands => "Selected strands",
+            Self::SelectedGroup => "Selected group",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// The palette used by the staple recoloring.
+#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+pub enum RecolorPaletteChoice {
+    RandomDistinct,
+    Rainbow,
+    ByHelix,
+    Fixed,
+}
+
+const ALL_RECOLOR_PALETTE_CHOICES: &[RecolorPaletteChoice] = &[
+    RecolorPaletteChoice::RandomDistinct,
+    RecolorPaletteChoice::Rainbow,
+    RecolorPaletteChoice::ByHelix,
+    RecolorPaletteChoice::Fixed,
+];
+
+impl std::fmt::Display for RecolorPaletteChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::RandomDistinct => "Random colors",
+            Self::Rainbow => "Rainbow along scaffold",
+            Self::ByHelix => "One color per helix",
+            Self::Fixed => "List of colors",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// Parse a non-empty list of hexadecimal RGB colors separated by spaces or commas, e.g.
+/// "#E52B50, #0070BB". The leading '#' is optional.
+fn parse_color_list(text: &str) -> Option<Vec<u32>> {
+    let colors: Option<Vec<u32>> = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|color| !color.is_empty())
+        .map(|color| {
+            let hex = color.strip_prefix('#').unwrap_or(color);
+            Some(hex)
+                .filter(|hex| hex.len() == 6)
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        })
+        .collect();
+    colors.filter(|colors| !colors.is_empty())
+}
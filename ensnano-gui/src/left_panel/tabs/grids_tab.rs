@@ -26,6 +26,9 @@ pub struct GridTab {
     hyperboloid_factory: RequestFactory<Hyperboloid_>,
     start_hyperboloid_btn: button::State,
     make_grid_btn: button::State,
+    hyperboloid_edition_factory: RequestFactory<Hyperboloid_>,
+    /// The existing hyperboloid grid whose parameters are shown by `hyperboloid_edition_factory`
+    edited_hyperboloid: Option<GridId>,
 }
 
 macro_rules! add_grid_buttons {
@@ -119,6 +122,11 @@ impl GridTab {
             finalize_hyperboloid_btn: Default::default(),
             start_hyperboloid_btn: Default::default(),
             make_grid_btn: Default::default(),
+            hyperboloid_edition_factory: RequestFactory::new(
+                FactoryId::HyperboloidEdition,
+                Hyperboloid_ {},
+            ),
+            edited_hyperboloid: None,
         }
     }
 
@@ -143,6 +151,20 @@ impl GridTab {
 
         add_hyperboloid_sliders!(ret, self, ui_size, app_state);
 
+        if !app_state.is_building_hyperboloid() {
+            self.update_edited_hyperboloid(app_state);
+            if self.edited_hyperboloid.is_some() {
+                subsection!(ret, ui_size, "Edit nanotube");
+                for view in self
+                    .hyperboloid_edition_factory
+                    .view(true, ui_size.main_text())
+                    .into_iter()
+                {
+                    ret = ret.push(view);
+                }
+            }
+        }
+
         extra_jump!(ret);
 
         subsection!(ret, ui_size, "Guess grid");
@@ -157,6 +179,55 @@ impl GridTab {
         self.hyperboloid_factory.make_request(requests);
     }
 
+    /// Show the parameters of the selected hyperboloid grid, if the selection is made of exactly
+    /// one hyperboloid grid.
+    fn update_edited_hyperboloid<S: AppState>(&mut self, app_state: &S) {
+        let selected_grid = if let [Selection::Grid(_, g_id)] = app_state.get_selection() {
+            Some(*g_id)
+        } else {
+            None
+        };
+        let parameters =
+            selected_grid.and_then(|g_id| app_state.get_reader().get_hyperboloid_parameters(g_id));
+        match (selected_grid, parameters) {
+            (Some(g_id), Some(parameters)) => {
+                if self.edited_hyperboloid != Some(g_id) {
+                    let mut factory =
+                        RequestFactory::new(FactoryId::HyperboloidEdition, Hyperboloid_ {});
+                    let values = [
+                        parameters.radius as f32,
+                        parameters.length,
+                        parameters.shift,
+                        parameters.radius_shift,
+                        parameters.nb_turn as f32,
+                    ];
+                    for (i, value) in values.iter().enumerate() {
+                        factory.update_value(ValueId(i), *value);
+                    }
+                    self.hyperboloid_edition_factory = factory;
+                    self.edited_hyperboloid = Some(g_id);
+                }
+            }
+            _ => self.edited_hyperboloid = None,
+        }
+    }
+
+    pub fn edited_hyperboloid(&self) -> Option<GridId> {
+        self.edited_hyperboloid
+    }
+
+    pub fn update_hyperboloid_edition(
+        &mut self,
+        value_id: ValueId,
+        value: f32,
+    ) -> Option<(GridId, HyperboloidRequest)> {
+        let grid_id = self.edited_hyperboloid?;
+        let request = self
+            .hyperboloid_edition_factory
+            .update_value(value_id, value);
+        Some((grid_id, request))
+    }
+
     pub fn update_hyperboloid_request(
         &mut self,
         value_id: ValueId,
@@ -23,6 +23,9 @@ pub struct ParametersTab {
     size_pick_list: pick_list::State<UiSize>,
     scroll: scrollable::State,
     scroll_sensitivity_factory: RequestFactory<ScrollSentivity>,
+    camera_animation_factory: RequestFactory<CameraAnimationDuration>,
+    autosave_factory: RequestFactory<AutosaveInterval>,
+    undo_history_length_factory: RequestFactory<UndoHistoryLength>,
     dna_parameters_picklist: pick_list::State<NamedParameter>,
     pub invert_y_scroll: bool,
 }
@@ -38,6 +41,24 @@ impl ParametersTab {
                     initial_value: app_state.get_scroll_sensitivity(),
                 },
             ),
+            camera_animation_factory: RequestFactory::new(
+                FactoryId::CameraAnimation,
+                CameraAnimationDuration {
+                    initial_value: app_state.get_camera_animation_duration(),
+                },
+            ),
+            autosave_factory: RequestFactory::new(
+                FactoryId::Autosave,
+                AutosaveInterval {
+                    initial_value: app_state.get_autosave_interval(),
+                },
+            ),
+            undo_history_length_factory: RequestFactory::new(
+                FactoryId::UndoHistoryLength,
+                UndoHistoryLength {
+                    initial_value: app_state.get_undo_history_length(),
+                },
+            ),
             dna_parameters_picklist: Default::default(),
             invert_y_scroll: false,
         }
@@ -76,6 +97,45 @@ impl ParametersTab {
             ui_size.clone(),
         ));
 
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Camera animations");
+        for view in self
+            .camera_animation_factory
+            .view(true, ui_size.main_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Backups");
+        for view in self
+            .autosave_factory
+            .view(true, ui_size.main_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "History");
+        for view in self
+            .undo_history_length_factory
+            .view(true, ui_size.main_text())
+            .into_iter()
+        {
+            ret = ret.push(view);
+        }
+
+        extra_jump!(ret);
+        subsection!(ret, ui_size, "Workspace");
+        ret = ret.push(right_checkbox(
+            app_state.get_remember_workspace(),
+            "Restore layout when opening a design",
+            Message::RememberWorkspace,
+            ui_size.clone(),
+        ));
+
         extra_jump!(10, ret);
         section!(ret, ui_size, "P-stick model");
         ret = ret.push(PickList::new(
@@ -116,4 +176,34 @@ impl ParametersTab {
         self.scroll_sensitivity_factory
             .update_request(value_id, value, request);
     }
+
+    pub fn update_camera_animation_request(
+        &mut self,
+        value_id: ValueId,
+        value: f32,
+        request: &mut Option<f32>,
+    ) {
+        self.camera_animation_factory
+            .update_request(value_id, value, request);
+    }
+
+    pub fn update_autosave_request(
+        &mut self,
+        value_id: ValueId,
+        value: f32,
+        request: &mut Option<f32>,
+    ) {
+        self.autosave_factory
+            .update_request(value_id, value, request);
+    }
+
+    pub fn update_undo_history_length_request(
+        &mut self,
+        value_id: ValueId,
+        value: f32,
+        request: &mut Option<usize>,
+    ) {
+        self.undo_history_length_factory
+            .update_request(value_id, value, request);
+    }
 }
@@ -19,6 +19,8 @@ use super::*;
 
 const NEW_BEZIER_PLANE_ICON: LightIcon = LightIcon::HistoryEdu;
 const EDIT_BEZIER_PATH_ICON: LightIcon = LightIcon::LinearScale;
+/// The distance, in nanometers, between a bezier plane and its copy
+const DUPLICATED_PLANE_OFFSET: f32 = 10.;
 
 #[derive(Default)]
 pub struct PenTab {
@@ -27,6 +29,13 @@ pub struct PenTab {
     make_square_grid_btn: button::State,
     make_honeycomb_grid_btn: button::State,
     load_svg_btn: button::State,
+    plane_widget_states: Vec<PlaneWidgetState>,
+    /// The identifiers of the planes in the order of their widgets
+    plane_ids: Vec<BezierPlaneId>,
+    plane_input_name: Option<String>,
+    plane_being_edited: Option<BezierPlaneId>,
+    /// True if the paths lying on a duplicated plane must be copied with it
+    pub copy_paths_of_duplicated_plane: bool,
 }
 
 macro_rules! add_buttons {
@@ -74,7 +83,73 @@ macro_rules! add_grid_buttons {
     };
 }
 
+macro_rules! add_plane_widgets {
+    ($ret: ident, $self: ident, $ui_size: ident, $app_state: ident) => {
+        let planes = $app_state.get_reader().get_bezier_plane_entries();
+        $self.plane_ids = planes.iter().map(|plane| plane.id).collect();
+        if $self.plane_widget_states.len() < planes.len() {
+            $self
+                .plane_widget_states
+                .resize(planes.len(), Default::default());
+        }
+        for (plane, state) in planes.into_iter().zip($self.plane_widget_states.iter_mut()) {
+            let being_edited = $self.plane_being_edited == Some(plane.id);
+            let name = if being_edited {
+                $self.plane_input_name.clone().unwrap_or(plane.name)
+            } else {
+                plane.name
+            };
+            $ret = $ret.push(plane_widget(
+                plane.id,
+                name,
+                plane.normal,
+                being_edited,
+                $ui_size,
+                state,
+            ));
+        }
+        $ret = $ret.push(Checkbox::new(
+            $self.copy_paths_of_duplicated_plane,
+            "Copy paths when duplicating",
+            Message::SetCopyPathsOfDuplicatedPlane,
+        ));
+    };
+}
+
 impl PenTab {
+    pub fn set_plane_input_name(&mut self, name: String) {
+        self.plane_input_name = Some(name);
+    }
+
+    pub fn start_editing(&mut self, plane_id: BezierPlaneId) {
+        for (id, s) in self
+            .plane_ids
+            .iter()
+            .zip(self.plane_widget_states.iter_mut())
+        {
+            if *id == plane_id {
+                self.plane_being_edited = Some(plane_id);
+                s.name_input.focus();
+                s.name_input.select_all();
+            }
+        }
+    }
+
+    pub fn stop_editing(&mut self) -> Option<(BezierPlaneId, String)> {
+        let name = self.plane_input_name.take();
+        let id = self.plane_being_edited.take();
+        for s in self.plane_widget_states.iter_mut() {
+            s.name_input.unfocus();
+        }
+        id.zip(name)
+    }
+
+    pub fn has_keyboard_priority(&self) -> bool {
+        self.plane_widget_states
+            .iter()
+            .any(|s| s.name_input.is_focused())
+    }
+
     pub fn view<'a, S: AppState>(
         &'a mut self,
         ui_size: UiSize,
@@ -87,6 +162,8 @@ impl PenTab {
                 .on_press(Message::LoadSvgFile),
         );
         add_buttons!(ret, self, ui_size);
+        add_plane_widgets!(ret, self, ui_size, app_state);
+        extra_jump!(ret);
         add_grid_buttons!(ret, self, ui_size, app_state);
         let selected_path_id = app_state.get_selected_bezier_path();
         let path_txt = selected_path_id
@@ -114,3 +191,56 @@ impl PenTab {
         ret.into()
     }
 }
+
+#[derive(Debug, Clone, Default)]
+struct PlaneWidgetState {
+    edit_name_btn: button::State,
+    duplicate_btn: button::State,
+    delete_btn: button::State,
+    name_input: text_input::State,
+}
+
+fn plane_widget<'a, S: AppState>(
+    plane_id: BezierPlaneId,
+    name: String,
+    normal: Vec3,
+    being_edited: bool,
+    ui_size: UiSize,
+    state: &'a mut PlaneWidgetState,
+) -> Element<'a, Message<S>> {
+    let name: Element<Message<S>> = if being_edited {
+        TextInput::new(
+            &mut state.name_input,
+            "Plane name",
+            &name,
+            Message::EditBezierPlaneName,
+        )
+        .on_submit(Message::SubmitBezierPlaneName)
+        .into()
+    } else {
+        Text::new(name).into()
+    };
+
+    let edit_button = light_icon_btn(&mut state.edit_name_btn, LightIcon::Edit, ui_size)
+        .on_press(Message::StartEditBezierPlaneName(plane_id));
+
+    let duplicate_button =
+        light_icon_btn(&mut state.duplicate_btn, LightIcon::ContentCopy, ui_size).on_press(
+            Message::DuplicateBezierPlane {
+                plane_id,
+                offset: normal * DUPLICATED_PLANE_OFFSET,
+            },
+        );
+
+    let delete_button = light_icon_btn(&mut state.delete_btn, LightIcon::Delete, ui_size)
+        .on_press(Message::DeleteBezierPlane(plane_id));
+
+    Row::new()
+        .push(name)
+        .push(iced::Space::with_width(iced::Length::Units(3)))
+        .push(edit_button)
+        .push(iced::Space::with_width(iced::Length::Fill))
+        .push(duplicate_button)
+        .push(delete_button)
+        .into()
+}
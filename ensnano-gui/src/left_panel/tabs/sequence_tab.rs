@@ -18,6 +18,9 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use super::*;
+use ensnano_design::design_check::{CheckParameters, Diagnostic};
+use ensnano_design::staple_report::{StapleBounds, StapleStats};
+use std::cmp::Ordering;
 
 pub struct SequenceTab {
     scroll: scrollable::State,
@@ -30,8 +33,93 @@ pub struct SequenceTab {
     scaffold_input: text_input::State,
     button_selection_from_scaffold: button::State,
     button_selection_to_scaffold: button::State,
+    /// The scaffold targeted by the sequence and shift operations
+    target_scaffold: Option<usize>,
+    scaffold_target_buttons: Vec<button::State>,
     button_show_sequence: button::State,
     button_optimize_shift: button::State,
+    staple_bounds_factory: RequestFactory<StapleBoundsFactory>,
+    button_staple_report: button::State,
+    staple_report: Vec<StapleStats>,
+    /// The column by which the report is sorted, and true if the order is descending
+    staple_report_sort: (StapleReportColumn, bool),
+    staple_report_header: Vec<button::State>,
+    staple_report_rows: Vec<button::State>,
+    check_parameters_factory: RequestFactory<CheckParametersFactory>,
+    button_check_design: button::State,
+    diagnostics: Vec<Diagnostic>,
+    diagnostic_buttons: Vec<button::State>,
+}
+
+/// A column of the staple report table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StapleReportColumn {
+    Strand,
+    Length,
+    GcContent,
+    MeltingTemperature,
+    NbDomains,
+    ShortestDomain,
+}
+
+impl StapleReportColumn {
+    const ALL: [Self; 6] = [
+        Self::Strand,
+        Self::Length,
+        Self::GcContent,
+        Self::MeltingTemperature,
+        Self::NbDomains,
+        Self::ShortestDomain,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Strand => "Strand",
+            Self::Length => "Length",
+            Self::GcContent => "GC %",
+            Self::MeltingTemperature => "Tm",
+            Self::NbDomains => "Dom.",
+            Self::ShortestDomain => "Min dom.",
+        }
+    }
+
+    fn cell(&self, stats: &StapleStats) -> String {
+        let one_decimal = |x: Option<f32>| {
+            x.map(|x| format!("{:.1}", x))
+                .unwrap_or_else(|| String::from("—"))
+        };
+        match self {
+            Self::Strand => stats
+                .name
+                .clone()
+                .unwrap_or_else(|| stats.strand_id.to_string()),
+            Self::Length => stats.length.to_string(),
+            Self::GcContent => one_decimal(stats.gc_content.map(|gc| 100. * gc)),
+            Self::MeltingTemperature => one_decimal(stats.melting_temperature),
+            Self::NbDomains => stats.nb_domains.to_string(),
+            Self::ShortestDomain => stats
+                .shortest_domain
+                .map(|l| l.to_string())
+                .unwrap_or_else(|| String::from("—")),
+        }
+    }
+
+    fn compare(&self, a: &StapleStats, b: &StapleStats) -> Ordering {
+        match self {
+            Self::Strand => a.strand_id.cmp(&b.strand_id),
+            Self::Length => a.length.cmp(&b.length),
+            Self::GcContent => a
+                .gc_content
+                .partial_cmp(&b.gc_content)
+                .unwrap_or(Ordering::Equal),
+            Self::MeltingTemperature => a
+                .melting_temperature
+                .partial_cmp(&b.melting_temperature)
+                .unwrap_or(Ordering::Equal),
+            Self::NbDomains => a.nb_domains.cmp(&b.nb_domains),
+            Self::ShortestDomain => a.shortest_domain.cmp(&b.shortest_domain),
+        }
+    }
 }
 
 macro_rules! add_show_sequence_button {
@@ -48,22 +136,30 @@ macro_rules! add_show_sequence_button {
 }
 
 macro_rules! add_scaffold_from_to_selection_buttons {
-    ($ret: ident, $self:ident, $ui_size: ident, $app_state: ident) => {
+    ($ret: ident, $self:ident, $ui_size: ident, $app_state: ident, $scaffolds: ident) => {
+        let selection = $app_state.get_selection_as_dnaelement();
+        let candidate = Self::get_candidate_scaffold(&selection);
+        let candidate_is_scaffold = candidate
+            .map(|n| $scaffolds.iter().any(|info| info.id == n))
+            .unwrap_or(false);
         let mut button_selection_to_scaffold = text_btn(
             &mut $self.button_selection_to_scaffold,
-            "From selection",
+            if candidate_is_scaffold {
+                "Unset selection"
+            } else {
+                "From selection"
+            },
             $ui_size,
         );
         let mut button_selection_from_scaffold =
             text_btn(&mut $self.button_selection_from_scaffold, "Show", $ui_size);
-        if $app_state.get_scaffold_info().is_some() {
+        if !$scaffolds.is_empty() {
             button_selection_from_scaffold =
                 button_selection_from_scaffold.on_press(Message::SelectScaffold);
         }
-        let selection = $app_state.get_selection_as_dnaelement();
-        if let Some(n) = Self::get_candidate_scaffold(&selection) {
-            button_selection_to_scaffold =
-                button_selection_to_scaffold.on_press(Message::ScaffoldIdSet(n, true));
+        if let Some(n) = candidate {
+            button_selection_to_scaffold = button_selection_to_scaffold
+                .on_press(Message::ScaffoldRoleSet(n, !candidate_is_scaffold));
         }
         $ret = $ret.push(
             Row::new()
@@ -86,9 +182,37 @@ macro_rules! nucl_text_fmt {
     };
 }
 
+macro_rules! add_scaffold_target_buttons {
+    ($ret: ident, $self: ident, $ui_size: ident, $scaffolds: ident) => {
+        if $scaffolds.len() > 1 {
+            $self
+                .scaffold_target_buttons
+                .resize_with($scaffolds.len(), Default::default);
+            let mut row = Row::new();
+            for (info, state) in $scaffolds
+                .iter()
+                .zip($self.scaffold_target_buttons.iter_mut())
+            {
+                let label = if Some(info.id) == $self.target_scaffold {
+                    format!("[#{}]", info.id)
+                } else {
+                    format!("#{}", info.id)
+                };
+                row = row.push(
+                    Button::new(state, Text::new(label).size($ui_size.main_text()))
+                        .height(Length::Units($ui_size.button()))
+                        .on_press(Message::ScaffoldTargetPicked(info.id)),
+                );
+            }
+            $ret = $ret.push(Text::new("Target scaffold").size($ui_size.main_text()));
+            $ret = $ret.push(row);
+        }
+    };
+}
+
 macro_rules! add_scaffold_info {
-    ($ret: ident, $self: ident, $ui_size: ident, $app_state: ident) => {
-        let (scaffold_text, length_text) = if let Some(info) = $app_state.get_scaffold_info() {
+    ($ret: ident, $self: ident, $ui_size: ident, $target_info: ident) => {
+        let (scaffold_text, length_text) = if let Some(info) = $target_info.as_ref() {
             (
                 format!("Strand #{}", info.id),
                 format!(scaffold_length_fmt!(), info.length),
@@ -100,7 +224,7 @@ macro_rules! add_scaffold_info {
             )
         };
         let mut length_text = Text::new(length_text);
-        if $app_state.get_scaffold_info().is_none() {
+        if $target_info.is_none() {
             length_text = length_text.color(innactive_color())
         }
         $ret = $ret.push(Text::new(scaffold_text).size($ui_size.main_text()));
@@ -110,21 +234,24 @@ macro_rules! add_scaffold_info {
 
 macro_rules! add_set_scaffold_sequence_button {
     ($ret: ident, $self: ident, $ui_size: ident) => {
-        let button_scaffold = Button::new(
+        let mut button_scaffold = Button::new(
             &mut $self.button_scaffold,
             iced::Text::new("Set scaffold sequence"),
         )
-        .height(Length::Units($ui_size.button()))
-        .on_press(Message::SetScaffoldSeqButtonPressed);
+        .height(Length::Units($ui_size.button()));
+        if $self.target_scaffold.is_some() {
+            button_scaffold = button_scaffold.on_press(Message::SetScaffoldSeqButtonPressed);
+        }
         $ret = $ret.push(button_scaffold);
     };
 }
 
 macro_rules! show_current_sequence_name {
     ($ret: ident, $self: ident, $app_state: ident ) => {
-        let sequence_name = $app_state
-            .get_reader()
-            .get_scaffold_sequence()
+        let reader = $app_state.get_reader();
+        let sequence_name = $self
+            .target_scaffold
+            .and_then(|s_id| reader.get_scaffold_sequence(s_id))
             .map(get_sequence_name)
             .unwrap_or("None");
         let message = format!("current sequence: {sequence_name}");
@@ -165,22 +292,21 @@ macro_rules! add_scaffold_position_input_row {
 
 macro_rules! add_optimize_scaffold_shift_button {
     ($ret: ident, $self: ident, $ui_size: ident) => {
-        let button_scaffold = Button::new(
+        let mut button_scaffold = Button::new(
             &mut $self.button_optimize_shift,
             iced::Text::new("Optimize starting position"),
         )
-        .height(Length::Units($ui_size.button()))
-        .on_press(Message::OptimizeScaffoldShiftPressed);
+        .height(Length::Units($ui_size.button()));
+        if $self.target_scaffold.is_some() {
+            button_scaffold = button_scaffold.on_press(Message::OptimizeScaffoldShiftPressed);
+        }
         $ret = $ret.push(button_scaffold);
     };
 }
 
 macro_rules! add_scaffold_start_position {
-    ($ret: ident, $ui_size: ident, $app_state: ident) => {
-        let starting_nucl = $app_state
-            .get_scaffold_info()
-            .as_ref()
-            .and_then(|info| info.starting_nucl);
+    ($ret: ident, $ui_size: ident, $target_info: ident) => {
+        let starting_nucl = $target_info.as_ref().and_then(|info| info.starting_nucl);
         let nucl_text = if let Some(nucl) = starting_nucl {
             format!(
                 nucl_text_fmt!(),
@@ -221,6 +347,89 @@ macro_rules! add_download_staples_button {
     };
 }
 
+macro_rules! add_design_check {
+    ($ret: ident, $self: ident, $ui_size: ident) => {
+        for view in $self
+            .check_parameters_factory
+            .view(true, $ui_size.intermediate_text())
+            .into_iter()
+        {
+            $ret = $ret.push(view);
+        }
+        $ret = $ret.push(
+            text_btn(&mut $self.button_check_design, "Check design", $ui_size)
+                .on_press(Message::CheckDesign),
+        );
+        for (n, (diagnostic, state)) in $self
+            .diagnostics
+            .iter()
+            .zip($self.diagnostic_buttons.iter_mut())
+            .enumerate()
+        {
+            $ret = $ret.push(
+                Button::new(
+                    state,
+                    Text::new(diagnostic.to_string()).size($ui_size.intermediate_text()),
+                )
+                .on_press(Message::DiagnosticClicked(n)),
+            );
+        }
+    };
+}
+
+macro_rules! add_staple_report {
+    ($ret: ident, $self: ident, $ui_size: ident) => {
+        for view in $self
+            .staple_bounds_factory
+            .view(true, $ui_size.intermediate_text())
+            .into_iter()
+        {
+            $ret = $ret.push(view);
+        }
+        $ret = $ret.push(
+            text_btn(&mut $self.button_staple_report, "Update report", $ui_size)
+                .on_press(Message::UpdateStapleReport),
+        );
+        let (sort_column, descending) = $self.staple_report_sort;
+        let mut header = Row::new();
+        for (column, state) in StapleReportColumn::ALL
+            .iter()
+            .zip($self.staple_report_header.iter_mut())
+        {
+            let label = if *column == sort_column {
+                format!("{} {}", column.label(), if descending { "↓" } else { "↑" })
+            } else {
+                column.label().to_string()
+            };
+            header = header.push(
+                Button::new(state, Text::new(label).size($ui_size.intermediate_text()))
+                    .on_press(Message::SortStapleReport(*column))
+                    .width(Length::FillPortion(1)),
+            );
+        }
+        $ret = $ret.push(header);
+        for (stats, state) in $self
+            .staple_report
+            .iter()
+            .zip($self.staple_report_rows.iter_mut())
+        {
+            let mut row = Row::new();
+            for column in StapleReportColumn::ALL.iter() {
+                let mut cell = Text::new(column.cell(stats))
+                    .size($ui_size.intermediate_text())
+                    .width(Length::FillPortion(1));
+                if stats.is_flagged() {
+                    cell = cell.color(Color::from_rgb(1., 0.3, 0.3));
+                }
+                row = row.push(cell);
+            }
+            $ret = $ret.push(
+                Button::new(state, row).on_press(Message::StapleReportRowClicked(stats.strand_id)),
+            );
+        }
+    };
+}
+
 macro_rules! add_rainbow_scaffold_checkbox {
     ($ret: ident, $ui_size: ident, $app_state: ident) => {
         $ret = $ret.push(right_checkbox(
@@ -245,8 +454,29 @@ impl SequenceTab {
             scaffold_input: Default::default(),
             button_selection_from_scaffold: Default::default(),
             button_selection_to_scaffold: Default::default(),
+            target_scaffold: None,
+            scaffold_target_buttons: Vec::new(),
             button_show_sequence: Default::default(),
             button_optimize_shift: Default::default(),
+            staple_bounds_factory: RequestFactory::new(
+                FactoryId::StapleBounds,
+                StapleBoundsFactory {},
+            ),
+            button_staple_report: Default::default(),
+            staple_report: Vec::new(),
+            staple_report_sort: (StapleReportColumn::Strand, false),
+            staple_report_header: StapleReportColumn::ALL
+                .iter()
+                .map(|_| Default::default())
+                .collect(),
+            staple_report_rows: Vec::new(),
+            check_parameters_factory: RequestFactory::new(
+                FactoryId::DesignCheck,
+                CheckParametersFactory {},
+            ),
+            button_check_design: Default::default(),
+            diagnostics: Vec::new(),
+            diagnostic_buttons: Vec::new(),
         }
     }
 
@@ -255,8 +485,19 @@ impl SequenceTab {
         ui_size: UiSize,
         app_state: &'a S,
     ) -> Element<'a, Message<S>> {
+        let scaffolds = app_state.get_scaffolds_info();
+        if !scaffolds
+            .iter()
+            .any(|info| Some(info.id) == self.target_scaffold)
+        {
+            self.target_scaffold = scaffolds.first().map(|info| info.id);
+        }
+        let target_info = scaffolds
+            .iter()
+            .find(|info| Some(info.id) == self.target_scaffold)
+            .cloned();
         if !self.scaffold_input.is_focused() {
-            if let Some(n) = app_state.get_scaffold_info().and_then(|info| info.shift) {
+            if let Some(n) = target_info.as_ref().and_then(|info| info.shift) {
                 self.update_pos_str(n.to_string());
             }
         }
@@ -268,9 +509,10 @@ impl SequenceTab {
         extra_jump!(ret);
         section!(ret, ui_size, "Scaffold");
         extra_jump!(ret);
-        add_scaffold_from_to_selection_buttons!(ret, self, ui_size, app_state);
+        add_scaffold_from_to_selection_buttons!(ret, self, ui_size, app_state, scaffolds);
         extra_jump!(ret);
-        add_scaffold_info!(ret, self, ui_size, app_state);
+        add_scaffold_target_buttons!(ret, self, ui_size, scaffolds);
+        add_scaffold_info!(ret, self, ui_size, target_info);
         extra_jump!(ret);
 
         add_rainbow_scaffold_checkbox!(ret, ui_size, app_state);
@@ -282,11 +524,19 @@ impl SequenceTab {
         add_scaffold_position_input_row!(ret, self);
 
         add_optimize_scaffold_shift_button!(ret, self, ui_size);
-        add_scaffold_start_position!(ret, ui_size, app_state);
+        add_scaffold_start_position!(ret, ui_size, target_info);
         extra_jump!(ret);
         section!(ret, ui_size, "Staples");
         extra_jump!(ret);
         add_download_staples_button!(ret, self, ui_size);
+        extra_jump!(ret);
+        section!(ret, ui_size, "Staple report");
+        extra_jump!(ret);
+        add_staple_report!(ret, self, ui_size);
+        extra_jump!(ret);
+        section!(ret, ui_size, "Design check");
+        extra_jump!(ret);
+        add_design_check!(ret, self, ui_size);
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
 
@@ -323,4 +573,71 @@ impl SequenceTab {
     pub fn get_scaffold_shift(&self) -> usize {
         self.scaffold_position
     }
+
+    pub fn get_target_scaffold(&self) -> Option<usize> {
+        self.target_scaffold
+    }
+
+    pub fn set_target_scaffold(&mut self, scaffold_id: usize) {
+        self.target_scaffold = Some(scaffold_id);
+    }
+
+    pub fn update_staple_bounds(&mut self, value_id: ValueId, value: f32) {
+        self.staple_bounds_factory.update_value(value_id, value);
+    }
+
+    pub fn get_staple_bounds(&self) -> StapleBounds {
+        let mut request = None;
+        self.staple_bounds_factory.make_request(&mut request);
+        request.unwrap_or_default()
+    }
+
+    pub fn has_staple_report(&self) -> bool {
+        !self.staple_report.is_empty()
+    }
+
+    pub fn set_staple_report(&mut self, report: Vec<StapleStats>) {
+        self.staple_report_rows = report.iter().map(|_| Default::default()).collect();
+        self.staple_report = report;
+        self.sort_staple_report();
+    }
+
+    /// Sort the report by `column`. If the report is already sorted by `column`, reverse the
+    /// order.
+    pub fn sort_staple_report_by(&mut self, column: StapleReportColumn) {
+        let (sort_column, descending) = self.staple_report_sort;
+        self.staple_report_sort = (column, sort_column == column && !descending);
+        self.sort_staple_report();
+    }
+
+    pub fn update_check_parameters(&mut self, value_id: ValueId, value: f32) {
+        self.check_parameters_factory.update_value(value_id, value);
+    }
+
+    pub fn get_check_parameters(&self) -> CheckParameters {
+        let mut request = None;
+        self.check_parameters_factory.make_request(&mut request);
+        request.unwrap_or_default()
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostic_buttons = diagnostics.iter().map(|_| Default::default()).collect();
+        self.diagnostics = diagnostics;
+    }
+
+    pub fn get_diagnostic(&self, n: usize) -> Option<&Diagnostic> {
+        self.diagnostics.get(n)
+    }
+
+    fn sort_staple_report(&mut self) {
+        let (column, descending) = self.staple_report_sort;
+        self.staple_report.sort_by(|a, b| {
+            let ordering = column.compare(a, b);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
 }
@@ -26,6 +26,8 @@ pub struct SimulationTab<S: AppState> {
     scroll: scrollable::State,
     physical_simulation: PhysicalSimulation,
     reset_state: button::State,
+    step_state: button::State,
+    import_oxdna_button: button::State,
 }
 
 impl<S: AppState> SimulationTab<S> {
@@ -55,6 +57,8 @@ impl<S: AppState> SimulationTab<S> {
             scroll: Default::default(),
             physical_simulation: Default::default(),
             reset_state: Default::default(),
+            step_state: Default::default(),
+            import_oxdna_button: Default::default(),
         }
     }
 
@@ -78,6 +82,7 @@ impl<S: AppState> SimulationTab<S> {
             .push(Self::helix_btns(
                 &mut self.rigid_helices_button,
                 &mut self.reset_state,
+                &mut self.step_state,
                 app_state,
                 ui_size.clone(),
             ));
@@ -112,12 +117,29 @@ impl<S: AppState> SimulationTab<S> {
             ret = ret.push(view);
         }
 
+        subsection!(ret, ui_size, "oxDNA");
+        ret = ret.push(
+            text_btn(
+                &mut self.import_oxdna_button,
+                "Import configuration",
+                ui_size.clone(),
+            )
+            .on_press(Message::ImportOxDnaConfiguration),
+        );
+        ret = ret.push(right_checkbox(
+            app_state.hide_idealized_design(),
+            "Hide idealized design",
+            Message::HideIdealizedDesign,
+            ui_size,
+        ));
+
         Scrollable::new(&mut self.scroll).push(ret).into()
     }
 
     fn helix_btns<'a>(
         go_stop: &'a mut GoStop<S>,
         reset_state: &'a mut button::State,
+        step_state: &'a mut button::State,
         app_state: &S,
         ui_size: UiSize,
     ) -> Element<'a, Message<S>> {
@@ -126,6 +148,9 @@ impl<S: AppState> SimulationTab<S> {
             Row::new()
                 .push(go_stop.view(true, false))
                 .spacing(3)
+                .push(
+                    text_btn(step_state, "Step", ui_size.clone()).on_press(Message::StepSimulation),
+                )
                 .push(text_btn(reset_state, "Reset", ui_size).on_press(Message::ResetSimulation))
                 .into()
         } else {
@@ -51,29 +51,41 @@ extern crate serde_derive;
 use status_bar::StatusBar;
 
 use ensnano_design::{
+    design_check::{CheckParameters, Diagnostic},
+    design_operations::{
+        DuplicationPattern, RecolorPalette, RecolorScope, ScaffoldRoute, ScaffoldRoutingParameters,
+        StapleFillingParameters,
+    },
     elements::{DnaAttribute, DnaElement, DnaElementKey},
     grid::GridTypeDescr,
-    ultraviolet, BezierPathId, BezierVertexId, Nucl, Parameters,
+    modifications::Modification,
+    staple_report::{StapleBounds, StapleStats},
+    ultraviolet, BezierPathId, BezierPlaneId, BezierVertexId, External3DObjectAnchor,
+    External3DObjectId, Nucl, Parameters, XoverPlacementParameters,
 };
+use ensnano_interactor::{design_summary::DesignSummary, operation::Operation, ScaffoldInfo};
 use ensnano_interactor::{
     graphics::{Background3D, DrawArea, ElementType, RenderingMode, SplitMode},
     CheckXoversParameter, InsertionPoint, PastingStatus, Selection, SimulationState,
-    SuggestionParameters, UnrootedRevolutionSurfaceDescriptor, WidgetBasis,
+    SuggestionParameters, UnrootedRevolutionSurfaceDescriptor, WidgetBasis, XoverStrain,
 };
 use ensnano_interactor::{
-    graphics::{FogParameters, HBoundDisplay},
+    graphics::{
+        CameraMode, Colormap, FogParameters, HBondFilter, HBoundDisplay, IsolateSelection,
+        PngExportOptions, StrandColoring,
+    },
     RevolutionSurfaceSystemDescriptor,
 };
-use ensnano_interactor::{operation::Operation, ScaffoldInfo};
 use ensnano_interactor::{ActionMode, HyperboloidRequest, RollRequest, SelectionMode};
 pub use ensnano_organizer::OrganizerTree;
 use iced_native::Event;
 use iced_wgpu::{wgpu, Backend, Renderer, Settings, Viewport};
 use iced_winit::{conversion, program, winit, Debug, Size};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use ultraviolet::{Rotor3, Vec2, Vec3};
+use ultraviolet::{Isometry3, Rotor3, Vec2, Vec3};
 use wgpu::Device;
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
@@ -98,6 +110,10 @@ pub trait Requests: 'static + Send {
     fn invert_scroll(&mut self, invert: bool);
     /// Resize all the 2D helices, or only the selected ones
     fn resize_2d_helices(&mut self, all: bool);
+    /// Stack the visible helices of the 2D view with a uniform spacing
+    fn compact_2d_layout(&mut self);
+    /// Move the pivot of the current group to the center of the selection
+    fn place_pivot_on_selection(&mut self);
     /// Make all elements of the design visible
     fn make_all_elements_visible(&mut self);
     /// Toggle the visibility of the selected elements
@@ -112,9 +128,19 @@ pub trait Requests: 'static + Send {
     fn set_dna_sequences_visibility(&mut self, visible: bool);
     /// Download the stapples as an xlsx file
     fn download_stapples(&mut self);
-    fn set_selected_strand_sequence(&mut self, sequence: String);
-    fn set_scaffold_sequence(&mut self, shift: usize);
-    fn set_scaffold_shift(&mut self, shift: usize);
+    /// Select the strand `s_id`
+    fn select_strand(&mut self, s_id: usize);
+    /// Select `selection` and center it in both views
+    fn select_and_center(&mut self, selection: Selection);
+    /// Toggle the checked status of the cross-overs `xovers`
+    fn check_xovers(&mut self, xovers: Vec<usize>);
+    /// Set the sequence of the selected strands. Unless `force` is true, the sequence is not
+    /// applied if it does not match the complement of the scaffold.
+    fn set_selected_strand_sequence(&mut self, sequence: String, force: bool);
+    /// Remove the sequence of the selected strands
+    fn clear_selected_strands_sequence(&mut self);
+    fn set_scaffold_sequence(&mut self, scaffold_id: usize, shift: usize);
+    fn set_scaffold_shift(&mut self, scaffold_id: usize, shift: usize);
     /// Change the size of the UI components
     fn set_ui_size(&mut self, size: UiSize);
     /// Finalize the currently eddited hyperboloid grid
@@ -133,11 +159,32 @@ pub trait Requests: 'static + Send {
     fn create_new_hyperboloid(&mut self, parameters: HyperboloidRequest);
     /// Update the parameters of the currently eddited hyperboloid grid
     fn update_current_hyperboloid(&mut self, parameters: HyperboloidRequest);
+    /// Change the parameters of the existing hyperboloid grid `grid_id`
+    fn update_hyperboloid(&mut self, grid_id: GridId, parameters: HyperboloidRequest);
+    /// Stop editing the parameters of the hyperboloid grid `grid_id`
+    fn finish_hyperboloid_update(&mut self, grid_id: GridId);
     fn update_roll_of_selected_helices(&mut self, roll: f32);
     fn update_scroll_sensitivity(&mut self, sensitivity: f32);
+    /// Set the duration, in seconds, of the animated camera movements
+    fn update_camera_animation_duration(&mut self, duration: f32);
+    /// Set the number of minutes between two backups of the design
+    fn update_autosave_interval(&mut self, interval: f32);
+    fn update_undo_history_length(&mut self, length: usize);
+    /// Enable or disable the saving and restoration of the layout of the application with the
+    /// design
+    fn set_remember_workspace(&mut self, remember: bool);
     fn set_fog_parameters(&mut self, parameters: FogParameters);
     /// Show/hide the torsion indications
     fn set_torsion_visibility(&mut self, visible: bool);
+    /// Set the torsion, in degrees, mapped to the extreme colors of the torsion heatmap. If
+    /// `None`, the range is adjusted to the torsions of the design.
+    fn set_torsion_range(&mut self, range: Option<f32>);
+    /// Show/hide the strain of the nucleotides during rigid helices simulations
+    fn set_strain_visibility(&mut self, visible: bool);
+    /// Show/hide the boundaries and the lengths of the domains in the 2D view
+    fn set_domain_lengths_visibility(&mut self, visible: bool);
+    /// Copy the summary of the design to the clipboard
+    fn copy_design_summary(&mut self);
     /// Set the direction and up vector of the 3D camera
     fn set_camera_dir_up_vec(&mut self, direction: Vec3, up: Vec3);
     fn perform_camera_rotation(&mut self, xz: f32, yz: f32, xy: f32);
@@ -159,10 +206,16 @@ pub trait Requests: 'static + Send {
     );
     fn change_split_mode(&mut self, split_mode: SplitMode);
     fn export(&mut self, export_type: ensnano_exports::ExportType);
+    /// Export only the strands of the current selection
+    fn export_selection(&mut self, export_type: ensnano_exports::ExportType);
     /// Split/Unsplit the 2D view
     fn toggle_2d_view_split(&mut self);
+    /// Split/Unsplit the 3D view
+    fn toggle_3d_view_split(&mut self);
     fn undo(&mut self);
     fn redo(&mut self);
+    /// Undo `-steps` operations if `steps` is negative, or redo `steps` operations otherwise
+    fn jump_in_history(&mut self, steps: isize);
     /// Display the help message in the contextual panel, regardless of the selection
     fn force_help(&mut self);
     /// Show tutorial in the contextual panel
@@ -171,6 +224,10 @@ pub trait Requests: 'static + Send {
     fn save_as(&mut self);
     fn save(&mut self);
     fn open_file(&mut self);
+    /// Show or hide the list of recently opened designs
+    fn toggle_quick_open(&mut self);
+    /// Load the design stored at `path`
+    fn open_recent_file(&mut self, path: PathBuf);
     /// Adjust the 2D and 3D cameras so that the design fit in screen
     fn fit_design_in_scenes(&mut self);
     /// Update the parameters of the current operation
@@ -178,33 +235,112 @@ pub trait Requests: 'static + Send {
     /// Update the shift of the currently seleced hyperbloid grid
     fn update_hyperboloid_shift(&mut self, shift: f32);
     fn display_error_msg(&mut self, msg: String);
-    /// Set the scaffold to be the some strand with id `s_id`, or none
-    fn set_scaffold_id(&mut self, s_id: Option<usize>);
+    /// Make the strand `s_id` one of the scaffolds of the design, or remove it from the scaffolds
+    fn set_scaffold_role(&mut self, s_id: usize, is_scaffold: bool);
     /// make the spheres of the currently selected grid large/small
     fn toggle_helices_persistance_of_grid(&mut self, persistant: bool);
     /// make the spheres of the currently selected grid large/small
     fn set_small_sphere(&mut self, small: bool);
+    /// Show or hide the currently selected grids
+    fn set_grid_visibility(&mut self, visible: bool);
+    /// Hide all the grids except the currently selected ones
+    fn solo_selected_grids(&mut self);
     fn finish_changing_color(&mut self);
     fn stop_simulations(&mut self);
     fn reset_simulations(&mut self);
+    /// Advance the paused rigid helices simulation by one time step
+    fn step_simulation(&mut self);
+    /// Make the helices of the given groups immobile in the rigid helices simulation
+    fn set_pinned_groups(&mut self, group_ids: Vec<GroupId>);
     fn reload_file(&mut self);
     fn add_double_strand_on_new_helix(&mut self, parameters: Option<(isize, usize)>);
     fn set_strand_name(&mut self, s_id: usize, name: String);
+    /// Set the note of a strand. An empty note removes the note of the strand.
+    fn set_strand_note(&mut self, s_id: usize, note: String);
+    /// Set whether the color of a strand must be preserved when staples are recolored
+    fn set_strand_keep_color(&mut self, s_id: usize, keep_color: bool);
+    /// Attach a chemical modification to a nucleotide
+    fn add_modification(&mut self, nucl: Nucl, modification: Modification);
+    /// Remove the chemical modification carried by a nucleotide
+    fn rm_modification(&mut self, nucl: Nucl);
+    /// Show or hide an external 3D object
+    fn set_external_3d_object_visibility(&mut self, object_id: External3DObjectId, visible: bool);
+    /// Attach an external 3D object to `anchor`, or detach it if `anchor` is `None`
+    fn attach_external_3d_object(
+        &mut self,
+        object_id: External3DObjectId,
+        anchor: Option<External3DObjectAnchor>,
+    );
+    /// Duplicate helices and strands in a periodic pattern
+    fn duplicate_in_pattern(
+        &mut self,
+        helices: Vec<usize>,
+        strands: Vec<usize>,
+        pattern: DuplicationPattern,
+    );
+    /// Rotate helices so that their axis becomes parallel to the one of the reference helix
+    fn align_helices(&mut self, helices: Vec<usize>, reference: usize);
+    /// Create a scaffold strand going through the helices of `route`
+    fn route_scaffold(&mut self, route: ScaffoldRoute, parameters: ScaffoldRoutingParameters);
+    /// Create staples on the parts of the scaffold that are not paired
+    fn fill_staples(&mut self, parameters: StapleFillingParameters);
+    /// Change the colors of the staples of `scope` using the colors of `palette`
+    fn recolor_staples(&mut self, scope: RecolorScope, palette: RecolorPalette);
+    /// Set the parameters of helices, or make them use the parameters of the design if
+    /// `parameters` is `None`
+    fn set_helices_parameters(&mut self, helices: Vec<usize>, parameters: Option<Parameters>);
+    /// Place cross-overs between the two selected helices
+    fn place_xovers_between_selected_helices(&mut self, parameters: XoverPlacementParameters);
+    /// Flip the anchor status of the nucleotides of the selected strands and helices, or only of
+    /// the ends of the selected strands if `ends_only` is true
+    fn anchor_selected_strands(&mut self, ends_only: bool);
+    fn set_group_color(&mut self, group_id: GroupId, color: Option<u32>);
+    /// Lock or unlock the members of a group of the organizer
+    fn set_group_locked(&mut self, group_id: GroupId, locked: bool);
     fn create_new_camera(&mut self);
     fn delete_camera(&mut self, cam_id: CameraId);
     fn select_camera(&mut self, cam_id: CameraId);
     fn set_favourite_camera(&mut self, cam_id: CameraId);
     fn update_camera(&mut self, cam_id: CameraId);
     fn set_camera_name(&mut self, cam_id: CameraId, name: String);
+    /// Save the current selection in the design under the name `name`
+    fn save_named_selection(&mut self, name: String);
+    /// Select the elements of the `idx`-th named selection of the design
+    fn recall_named_selection(&mut self, idx: usize);
+    fn rename_named_selection(&mut self, idx: usize, name: String);
+    fn delete_named_selection(&mut self, idx: usize);
     fn set_suggestion_parameters(&mut self, param: SuggestionParameters);
     fn set_grid_position(&mut self, grid_id: GridId, position: Vec3);
     fn set_grid_orientation(&mut self, grid_id: GridId, orientation: Rotor3);
     fn toggle_2d(&mut self);
     fn set_nb_turn(&mut self, grid_id: GridId, nb_turn: f32);
+    /// Set the number of phantom nucleotides drawn on each side of the origin of the helices of
+    /// the grid `grid_id`. If `length` is `None`, the default length is used.
+    fn set_grid_phantom_length(&mut self, grid_id: GridId, length: Option<usize>);
+    /// Change the lattice of the grid `grid_id`
+    fn set_grid_type(&mut self, grid_id: GridId, grid_type: GridTypeDescr);
     fn set_check_xover_parameters(&mut self, paramters: CheckXoversParameter);
     fn follow_stereographic_camera(&mut self, follow: bool);
     fn set_show_stereographic_camera(&mut self, show: bool);
     fn set_show_h_bonds(&mut self, show: HBoundDisplay);
+    /// Set which hydrogen bonds are drawn in the 3D view
+    fn set_h_bond_filter(&mut self, filter: HBondFilter);
+    /// Show/hide the overview of the design in the 2D view
+    fn set_show_2d_minimap(&mut self, show: bool);
+    /// Show/hide the markers of the anchored nucleotides in the 3D view
+    fn set_show_anchors(&mut self, show: bool);
+    /// Draw the phantom helices only on the grid that is under the cursor or selected in the 3D
+    /// view
+    fn set_local_phantoms(&mut self, local_phantoms: bool);
+    /// Draw the indices of the nucleotides along the selected helices and the helix under the
+    /// cursor in the 3D view
+    fn set_show_nucleotide_ruler(&mut self, show: bool);
+    /// Draw every nucleotide of the 3D view, even the ones of helices far from the camera
+    fn set_full_detail(&mut self, full_detail: bool);
+    /// Set the property according to which the non-scaffold strands are colored
+    fn set_strand_coloring(&mut self, coloring: StrandColoring);
+    /// Set the colormap used when the strands are colored according to a property
+    fn set_strand_colormap(&mut self, colormap: Colormap);
     fn flip_split_views(&mut self);
     fn set_rainbow_scaffold(&mut self, rainbow: bool);
     fn set_thick_helices(&mut self, thick: bool);
@@ -212,23 +348,47 @@ pub trait Requests: 'static + Send {
     fn download_origamis(&mut self);
     fn set_dna_parameters(&mut self, param: Parameters);
     fn set_expand_insertions(&mut self, expand: bool);
+    fn set_isolate_selection(&mut self, isolate_selection: IsolateSelection);
+    /// Set the way mouse drags rotate the 3D camera
+    fn set_camera_mode(&mut self, camera_mode: CameraMode);
     fn set_insertion_length(&mut self, insertion_point: InsertionPoint, length: usize);
     fn create_bezier_plane(&mut self);
     fn turn_path_into_grid(&mut self, path_id: BezierPathId, grid_type: GridTypeDescr);
     fn set_show_bezier_paths(&mut self, show: bool);
     fn make_bezier_path_cyclic(&mut self, path_id: BezierPathId, cyclic: bool);
+    fn rename_bezier_plane(&mut self, plane_id: BezierPlaneId, name: String);
+    fn delete_bezier_plane(&mut self, plane_id: BezierPlaneId);
+    /// Create a copy of a bezier plane translated by `offset`, and of the paths lying on it if
+    /// `copy_paths` is true
+    fn duplicate_bezier_plane(&mut self, plane_id: BezierPlaneId, offset: Vec3, copy_paths: bool);
     fn set_exporting(&mut self, exporting: bool);
     fn import_3d_object(&mut self);
     fn set_position_of_bezier_vertex(&mut self, vertex_id: BezierVertexId, position: Vec2);
-    fn optimize_scaffold_shift(&mut self);
+    fn optimize_scaffold_shift(&mut self, scaffold_id: usize);
     fn start_revolution_relaxation(&mut self, desc: RevolutionSurfaceSystemDescriptor);
     fn finish_revolutiion_relaxation(&mut self);
     fn load_svg(&mut self);
+    /// Show an oxDNA configuration, obtained from an export of the design, on top of the design
+    fn import_oxdna_configuration(&mut self);
+    /// Only draw the imported oxDNA configuration in the 3D view
+    fn set_hide_idealized_design(&mut self, hide: bool);
+    /// Ask for a design to be drawn next to the current one for comparison
+    fn load_reference_design(&mut self);
+    fn remove_reference_design(&mut self);
+    fn set_reference_design_visibility(&mut self, visible: bool);
+    /// Set the isometry applied to the reference design to align it with the current design
+    fn set_reference_design_isometry(&mut self, isometry: Isometry3);
+    /// Highlight the changes made to the design since it was last saved
+    fn set_show_design_changes(&mut self, show: bool);
     fn set_bezier_revolution_radius(&mut self, radius: f64);
     fn set_bezier_revolution_id(&mut self, id: Option<usize>);
     fn set_unrooted_surface(&mut self, surface: Option<UnrootedRevolutionSurfaceDescriptor>);
     /// Make a 3D screenshot
-    fn request_screenshot_3d(&mut self);
+    fn request_screenshot_3d(&mut self, options: PngExportOptions);
+    /// Render one picture per saved camera of the design
+    fn request_export_all_cameras(&mut self);
+    /// Switch between the perspective and the orthographic projection in the 3D view
+    fn toggle_orthographic_projection(&mut self);
     fn notify_revolution_tab(&mut self);
 }
 
@@ -258,6 +418,13 @@ impl<R: Requests, S: AppState> GuiState<R, S> {
                 GuiState::LeftPanel(_) => (),
             }
         } else {
+            if let Event::Keyboard(iced::keyboard::Event::KeyPressed { key_code, .. }) = &event {
+                if let GuiState::LeftPanel(_) = self {
+                    self.queue_left_panel_message(left_panel::Message::OrganizerKeyPressed(
+                        *key_code,
+                    ))
+                }
+            }
             match self {
                 GuiState::TopBar(state) => state.queue_event(event),
                 GuiState::LeftPanel(state) => state.queue_event(event),
@@ -957,12 +1124,19 @@ impl<S: AppState> IcedMessages<S> {
             .push_back(status_bar::Message::Message(Some(message)));
     }
 
-    #[allow(dead_code)]
     pub fn clear_message(&mut self) {
         self.status_bar
             .push_back(status_bar::Message::Message(None));
     }
 
+    pub fn push_pointed_grid_position(
+        &mut self,
+        position: Option<ensnano_design::grid::GridPosition>,
+    ) {
+        self.status_bar
+            .push_back(status_bar::Message::PointedGridPosition(position));
+    }
+
     pub fn push_progress(&mut self, progress_name: String, progress: f32) {
         self.status_bar
             .push_back(status_bar::Message::Progress(Some((
@@ -990,6 +1164,19 @@ impl<S: AppState> IcedMessages<S> {
             .push_back(status_bar::Message::UiSizeChanged(ui_size));
     }
 
+    /// Forward the result of the last attempt to set the sequence of the selected strands.
+    /// `nb_mismatches` is `None` if the sequence was applied.
+    pub fn push_sequence_check(&mut self, nb_mismatches: Option<usize>) {
+        self.left_panel
+            .push_back(left_panel::Message::SequenceChecked(nb_mismatches));
+    }
+
+    /// Show fog parameters that were not set in the left panel
+    pub fn push_fog_parameters(&mut self, fog: FogParameters) {
+        self.left_panel
+            .push_back(left_panel::Message::NewFogParameters(fog));
+    }
+
     pub fn push_show_tutorial(&mut self) {
         self.left_panel.push_back(left_panel::Message::ShowTutorial);
     }
@@ -998,6 +1185,11 @@ impl<S: AppState> IcedMessages<S> {
         self.left_panel.push_back(left_panel::Message::ForceHelp);
     }
 
+    pub fn toggle_quick_open(&mut self) {
+        self.left_panel
+            .push_back(left_panel::Message::ToggleQuickOpen);
+    }
+
     pub fn push_application_state(&mut self, state: S, main_state: MainState) {
         log::trace!("Old ptr {:p}, new ptr {:p}", state, self.application_state);
         self.application_state = state.clone();
@@ -1007,6 +1199,14 @@ impl<S: AppState> IcedMessages<S> {
         if must_update {
             self.left_panel
                 .push_back(left_panel::Message::NewApplicationState(state.clone()));
+            self.left_panel
+                .push_back(left_panel::Message::NewUndoHistory(
+                    main_state.history.clone(),
+                ));
+            self.left_panel
+                .push_back(left_panel::Message::NewRecentFiles(
+                    main_state.recent_files.clone(),
+                ));
             self.top_bar
                 .push_back(top_bar::Message::NewApplicationState(top_bar_main_state(
                     &state, main_state,
@@ -1037,7 +1237,7 @@ pub trait AppState:
     fn get_simulation_state(&self) -> SimulationState;
     fn get_dna_parameters(&self) -> Parameters;
     fn is_building_hyperboloid(&self) -> bool;
-    fn get_scaffold_info(&self) -> Option<ScaffoldInfo>;
+    fn get_scaffolds_info(&self) -> Vec<ScaffoldInfo>;
     fn get_selection(&self) -> &[Selection];
     fn get_selection_as_dnaelement(&self) -> Vec<DnaElementKey>;
     fn can_make_grid(&self) -> bool;
@@ -1052,10 +1252,32 @@ pub trait AppState:
     fn follow_stereographic_camera(&self) -> bool;
     fn show_stereographic_camera(&self) -> bool;
     fn get_h_bounds_display(&self) -> HBoundDisplay;
+    fn get_h_bond_filter(&self) -> HBondFilter;
+    fn show_2d_minimap(&self) -> bool;
+    fn show_anchors(&self) -> bool;
+    fn local_phantoms(&self) -> bool;
+    fn show_nucleotide_ruler(&self) -> bool;
+    fn full_detail(&self) -> bool;
+    fn get_strand_coloring(&self) -> StrandColoring;
+    fn get_strand_colormap(&self) -> Colormap;
     fn get_scroll_sensitivity(&self) -> f32;
     fn get_invert_y_scroll(&self) -> bool;
+    fn get_camera_animation_duration(&self) -> f32;
+    /// The number of minutes between two backups of the design
+    fn get_autosave_interval(&self) -> f32;
+    /// The maximum number of operations listed in the history panel on each side of the current
+    /// state
+    fn get_undo_history_length(&self) -> usize;
+    /// Whether the layout of the application is saved and restored with the design
+    fn get_remember_workspace(&self) -> bool;
     fn want_thick_helices(&self) -> bool;
     fn expand_insertions(&self) -> bool;
+    fn get_isolate_selection(&self) -> IsolateSelection;
+    fn hide_idealized_design(&self) -> bool;
+    /// The visibility of the reference design, or `None` if there is no reference design
+    fn get_reference_design_visibility(&self) -> Option<bool>;
+    fn is_showing_design_changes(&self) -> bool;
+    fn get_camera_mode(&self) -> CameraMode;
     fn get_show_bezier_paths(&self) -> bool;
     fn get_selected_bezier_path(&self) -> Option<BezierPathId>;
     fn is_exporting(&self) -> bool;
@@ -1072,38 +1294,107 @@ pub trait AppState:
 pub trait DesignReader: 'static {
     fn grid_has_persistent_phantom(&self, g_id: GridId) -> bool;
     fn grid_has_small_spheres(&self, g_id: GridId) -> bool;
+    fn grid_is_visible(&self, g_id: GridId) -> bool;
     fn get_grid_shift(&self, g_id: GridId) -> Option<f32>;
     fn get_strand_length(&self, s_id: usize) -> Option<usize>;
     fn is_id_of_scaffold(&self, s_id: usize) -> bool;
     fn length_decomposition(&self, s_id: usize) -> String;
     fn nucl_is_anchor(&self, nucl: Nucl) -> bool;
+    /// Return the chemical modification carried by `nucl`, if any
+    fn nucl_modification(&self, nucl: Nucl) -> Option<Modification>;
+    /// Return a description of each external 3D object
+    fn get_external_3d_objects(&self) -> Vec<External3DObjectEntry>;
     fn get_dna_elements(&self) -> &[DnaElement];
     fn get_organizer_tree(&self) -> Option<Arc<ensnano_design::EnsnTree>>;
+    /// Return the colors that were given to the groups of the organizer
+    fn get_group_colors(&self) -> HashMap<GroupId, u32>;
+    /// Return the groups of the organizer whose members cannot be modified
+    fn get_locked_groups(&self) -> HashSet<GroupId>;
     fn strand_name(&self, s_id: usize) -> String;
+    fn strand_note(&self, s_id: usize) -> Option<String>;
+    /// Return true if the color of the strand must be preserved when staples are recolored
+    fn strand_keeps_color(&self, s_id: usize) -> bool;
     fn get_all_cameras(&self) -> Vec<(CameraId, &str)>;
     fn get_favourite_camera(&self) -> Option<CameraId>;
+    /// Return the names of the named selections of the design
+    fn get_named_selections(&self) -> Vec<&str>;
     fn get_grid_position_and_orientation(&self, g_id: GridId) -> Option<(Vec3, Rotor3)>;
     fn get_grid_nb_turn(&self, g_id: GridId) -> Option<f32>;
+    /// Return the number of phantom nucleotides drawn on each side of the origin of the helices
+    /// of the grid `g_id`, if it is not the default one
+    fn get_grid_phantom_length(&self, g_id: GridId) -> Option<usize>;
+    /// Return the parameters of the grid `g_id` if it is a hyperboloid grid
+    fn get_hyperboloid_parameters(&self, g_id: GridId) -> Option<HyperboloidRequest>;
     fn xover_length(&self, xover_id: usize) -> Option<(f32, Option<f32>)>;
     fn get_id_of_xover_involving_nucl(&self, nucl: Nucl) -> Option<usize>;
     fn rainbow_scaffold(&self) -> bool;
     fn get_insertion_length(&self, selection: &Selection) -> Option<usize>;
     fn get_insertion_point(&self, selection: &Selection) -> Option<InsertionPoint>;
     fn is_bezier_path_cyclic(&self, path_id: BezierPathId) -> Option<bool>;
+    /// Return a description of each bezier plane
+    fn get_bezier_plane_entries(&self) -> Vec<BezierPlaneEntry>;
     fn get_bezier_vertex_position(&self, vertex_id: BezierVertexId) -> Option<Vec2>;
-    fn get_scaffold_sequence(&self) -> Option<&str>;
+    fn get_scaffold_sequence(&self, scaffold_id: usize) -> Option<&str>;
     fn get_current_length_of_relaxed_shape(&self) -> Option<usize>;
+    /// Return the statistics of the staples of the design, flagged according to `bounds`
+    fn get_staple_stats(&self, bounds: &StapleBounds) -> Vec<StapleStats>;
+    /// Return the defects of the design
+    fn check_design(&self, parameters: &CheckParameters) -> Vec<Diagnostic>;
+    /// Return the strain of all the cross-overs of the design, the most strained first
+    fn get_xovers_by_strain(&self) -> Vec<XoverStrain>;
+    fn get_design_summary(&self) -> DesignSummary;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct MainState {
     pub can_undo: bool,
     pub can_redo: bool,
+    pub history: UndoHistory,
     pub need_save: bool,
     pub can_reload: bool,
     pub can_split2d: bool,
     pub can_toggle_2d: bool,
     pub splited_2d: bool,
+    pub can_split3d: bool,
+    pub splited_3d: bool,
+    /// The designs that were recently opened or saved, the most recent one first
+    pub recent_files: Vec<RecentFile>,
+}
+
+/// An entry of the list of external 3D objects
+#[derive(Debug, Clone, PartialEq)]
+pub struct External3DObjectEntry {
+    pub id: External3DObjectId,
+    pub name: String,
+    pub visible: bool,
+    /// True if the object is attached to an element of the design
+    pub attached: bool,
+}
+
+/// An entry of the list of bezier planes
+#[derive(Debug, Clone, PartialEq)]
+pub struct BezierPlaneEntry {
+    pub id: BezierPlaneId,
+    pub name: String,
+    /// The normal vector of the plane
+    pub normal: Vec3,
+}
+
+/// An entry of the list of recently opened designs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    /// False if the file did not exist anymore when the list was read
+    pub exists: bool,
+}
+
+/// The labels of the operations that can be undone and redone
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UndoHistory {
+    /// The operations that can be undone, the most recent one last
+    pub undo_labels: Vec<String>,
+    /// The operations that can be redone, the next one first
+    pub redo_labels: Vec<String>,
 }
 
 fn top_bar_main_state<S: AppState>(app_state: &S, main_state: MainState) -> top_bar::MainState<S> {
@@ -1116,5 +1407,7 @@ fn top_bar_main_state<S: AppState>(app_state: &S, main_state: MainState) -> top_
         can_split2d: main_state.can_split2d,
         can_toggle_2d: main_state.can_toggle_2d,
         splited_2d: main_state.splited_2d,
+        can_split3d: main_state.can_split3d,
+        splited_3d: main_state.splited_3d,
     }
 }
@@ -16,9 +16,11 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 use super::{AppState, Requests, UiSize};
+use ensnano_design::grid::{GridId, GridPosition};
+use ensnano_interactor::design_summary::DesignSummary;
 use ensnano_interactor::operation::{Operation, ParameterField};
 pub use ensnano_interactor::StrandBuildingStatus;
-use iced::{container, slider, Background, Container, Length};
+use iced::{button, container, slider, Background, Button, Container, Length};
 use iced_native::{
     widget::{pick_list, text_input, PickList, TextInput},
     Color,
@@ -96,6 +98,12 @@ pub struct StatusBar<R: Requests, S: AppState> {
     app_state: S,
     ui_size: UiSize,
     message: Option<String>,
+    /// The grid position under the cursor in the 3D view
+    pointed_grid_position: Option<GridPosition>,
+    /// The summary of the design, computed again when it is shown after a modification of the
+    /// design
+    summary: Option<DesignSummary>,
+    copy_summary_button: button::State,
     logical_size: LogicalSize<f64>,
 }
 
@@ -115,6 +123,9 @@ impl<R: Requests, S: AppState> StatusBar<R, S> {
             app_state: state.clone(),
             ui_size,
             message: None,
+            pointed_grid_position: None,
+            summary: None,
+            copy_summary_button: Default::default(),
             logical_size,
         }
     }
@@ -182,7 +193,9 @@ pub enum Message<S: AppState> {
     UiSizeChanged(UiSize),
     TabPressed,
     Message(Option<String>),
+    PointedGridPosition(Option<GridPosition>),
     Resize(LogicalSize<f64>),
+    CopySummary,
 }
 
 impl<R: Requests, S: AppState> Program for StatusBar<R, S> {
@@ -211,11 +224,18 @@ impl<R: Requests, S: AppState> Program for StatusBar<R, S> {
                 self.info_values[2] = f.to_string();
                 self.requests.lock().unwrap().update_hyperboloid_shift(f);
             }
-            Message::NewApplicationState(state) => self.app_state = state,
+            Message::NewApplicationState(state) => {
+                if state.design_was_modified(&self.app_state) {
+                    self.summary = None;
+                }
+                self.app_state = state
+            }
             Message::UiSizeChanged(ui_size) => self.set_ui_size(ui_size),
             Message::TabPressed => self.process_tab(),
             Message::Message(message) => self.message = message,
+            Message::PointedGridPosition(position) => self.pointed_grid_position = position,
             Message::Resize(size) => self.logical_size = size,
+            Message::CopySummary => self.requests.lock().unwrap().copy_design_summary(),
         }
         Command::none()
     }
@@ -253,14 +273,40 @@ impl<R: Requests, S: AppState> Program for StatusBar<R, S> {
             Row::new().into() //TODO
         };
 
+        let grid_position_text = self
+            .pointed_grid_position
+            .map(grid_position_info)
+            .unwrap_or_default();
+
         content = Row::new()
             .push(content)
             .push(Space::with_width(Length::Fill)) // To right align the clipboard text
+            .push(Text::new(grid_position_text))
+            .push(Space::with_width(Length::Units(20)))
             .push(Text::new(clipboard_text))
             .push(Space::with_width(Length::Units(5)))
             .align_items(iced_winit::Alignment::End);
 
+        if self.summary.is_none() {
+            self.summary = Some(self.app_state.get_reader().get_design_summary());
+        }
+        let summary_text = self
+            .summary
+            .as_ref()
+            .map(DesignSummary::short_description)
+            .unwrap_or_default();
+
         let pasting_status_row = Row::new()
+            .push(Space::with_width(Length::Units(5)))
+            .push(Text::new(summary_text).size(self.ui_size.main_text()))
+            .push(Space::with_width(Length::Units(5)))
+            .push(
+                Button::new(
+                    &mut self.copy_summary_button,
+                    Text::new("Copy").size(self.ui_size.main_text()),
+                )
+                .on_press(Message::CopySummary),
+            )
             .push(Space::with_width(Length::Fill))
             .push(Text::new(pasting_text))
             .push(Space::with_width(Length::Units(5)));
@@ -277,6 +323,19 @@ impl<R: Requests, S: AppState> Program for StatusBar<R, S> {
     }
 }
 
+/// The description of a grid position, using the same coordinates as the positions of the
+/// helices on the grid.
+fn grid_position_info(position: GridPosition) -> String {
+    let grid = match position.grid {
+        GridId::FreeGrid(id) => format!("Grid {id}"),
+        GridId::BezierPathGrid(vertex) => format!(
+            "Grid of vertex {} of path {}",
+            vertex.vertex_id, vertex.path_id.0
+        ),
+    };
+    format!("{grid}: ({}, {})", position.x, position.y)
+}
+
 struct StatusBarStyle;
 impl container::StyleSheet for StatusBarStyle {
     fn style(&self) -> container::Style {
@@ -34,6 +34,7 @@ use super::{Requests, SplitMode};
 pub struct TopBar<R: Requests, S: AppState> {
     button_fit: button::State,
     button_add_file: button::State,
+    button_recent_files: button::State,
     button_save_as: button::State,
     button_save: button::State,
     button_undo: button::State,
@@ -43,6 +44,7 @@ pub struct TopBar<R: Requests, S: AppState> {
     button_split: button::State,
     button_oxdna: button::State,
     button_split_2d: button::State,
+    button_split_3d: button::State,
     button_flip_split: button::State,
     button_help: button::State,
     button_tutorial: button::State,
@@ -70,6 +72,8 @@ pub struct MainState<S: AppState> {
     pub can_split2d: bool,
     pub can_toggle_2d: bool,
     pub splited_2d: bool,
+    pub can_split3d: bool,
+    pub splited_3d: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -77,6 +81,7 @@ pub enum Message<S: AppState> {
     SceneFitRequested,
     AlignHorizon,
     OpenFileButtonPressed,
+    RecentFilesButtonPressed,
     FileSaveRequested,
     SaveAsRequested,
     Resize(LogicalSize<f64>),
@@ -84,6 +89,7 @@ pub enum Message<S: AppState> {
     UiSizeChanged(UiSize),
     ExportRequested,
     Split2d,
+    Split3d,
     NewApplicationState(MainState<S>),
     ForceHelp,
     ShowTutorial,
@@ -109,6 +115,7 @@ impl<R: Requests, S: AppState> TopBar<R, S> {
         Self {
             button_fit: Default::default(),
             button_add_file: Default::default(),
+            button_recent_files: Default::default(),
             button_save_as: Default::default(),
             horizon_button: Default::default(),
             button_save: Default::default(),
@@ -119,6 +126,7 @@ impl<R: Requests, S: AppState> TopBar<R, S> {
             button_split: Default::default(),
             button_oxdna: Default::default(),
             button_split_2d: Default::default(),
+            button_split_3d: Default::default(),
             button_flip_split: Default::default(),
             button_help: Default::default(),
             button_tutorial: Default::default(),
@@ -154,6 +162,9 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             Message::SceneFitRequested => {
                 self.requests.lock().unwrap().fit_design_in_scenes();
             }
+            Message::RecentFilesButtonPressed => {
+                self.requests.lock().unwrap().toggle_quick_open();
+            }
             Message::OpenFileButtonPressed => {
                 self.requests.lock().unwrap().open_file();
             }
@@ -168,6 +179,7 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             Message::UiSizeChanged(ui_size) => self.ui_size = ui_size,
             Message::ExportRequested => self.requests.lock().unwrap().set_exporting(true),
             Message::Split2d => self.requests.lock().unwrap().toggle_2d_view_split(),
+            Message::Split3d => self.requests.lock().unwrap().toggle_3d_view_split(),
             Message::NewApplicationState(state) => self.application_state = state,
             Message::Undo => self.requests.lock().unwrap().undo(),
             Message::Redo => self.requests.lock().unwrap().redo(),
@@ -243,6 +255,12 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
         )
         .on_press(Message::OpenFileButtonPressed);
 
+        let button_recent_files = Button::new(
+            &mut self.button_recent_files,
+            light_icon(LightIcon::History, self.ui_size),
+        )
+        .on_press(Message::RecentFilesButtonPressed);
+
         let mut button_reload = Button::new(
             &mut self.button_reload,
             light_icon(LightIcon::RestorePage, self.ui_size),
@@ -359,6 +377,22 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             button_split_2d = button_split_2d.on_press(Message::Split2d);
         }
 
+        let split_3d_icon = if self.application_state.splited_3d {
+            LightIcon::BorderOuter
+        } else {
+            LightIcon::BorderVertical
+        };
+
+        let mut button_split_3d = Button::new(
+            &mut self.button_split_3d,
+            light_icon(split_3d_icon, self.ui_size),
+        )
+        .height(Length::Units(self.ui_size.button()));
+
+        if self.application_state.can_split3d {
+            button_split_3d = button_split_3d.on_press(Message::Split3d);
+        }
+
         let mut button_toggle_2d =
             Button::new(&mut self.button_toggle_2d, iced::Text::new("Toggle 2D"))
                 .height(Length::Units(self.ui_size.button()));
@@ -428,6 +462,7 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             .height(Length::Units(height))
             .push(button_new_empty_design)
             .push(button_add_file)
+            .push(button_recent_files)
             .push(button_reload)
             .push(button_save)
             .push(button_save_as)
@@ -439,6 +474,7 @@ impl<R: Requests, S: AppState> Program for TopBar<R, S> {
             .push(button_2d)
             .push(button_split)
             .push(button_split_2d)
+            .push(button_split_3d)
             .push(button_toggle_2d)
             .push(button_flip_split)
             .push(iced::Space::with_width(Length::Units(10)))
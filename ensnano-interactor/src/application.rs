@@ -22,6 +22,8 @@ use ensnano_design::group_attributes::GroupPivot;
 use ensnano_design::Nucl;
 use iced_wgpu::wgpu;
 use iced_winit::winit;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Arc;
 pub use std::time::Duration;
 use ultraviolet::{Rotor3, Vec3};
@@ -31,7 +33,7 @@ use winit::{
     event::{ModifiersState, WindowEvent},
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Camera3D {
     pub position: Vec3,
     pub orientation: Rotor3,
@@ -48,6 +50,15 @@ impl Default for Camera3D {
     }
 }
 
+/// The position of the camera of one of the views of the 2D scene
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Camera2D {
+    pub scroll_offset: [f32; 2],
+    pub zoom: f32,
+    pub tilt: f32,
+    pub symetry: [f32; 2],
+}
+
 pub trait Application {
     type AppState;
     /// For notification about the data
@@ -80,6 +91,16 @@ pub trait Application {
         None
     }
 
+    /// The fog of the 3D view, saved with the design
+    fn get_fog_parameters(&self) -> Option<FogParameters> {
+        None
+    }
+
+    /// The cameras of the top and bottom views of the 2D scene
+    fn get_cameras_2d(&self) -> Option<[Camera2D; 2]> {
+        None
+    }
+
     fn is_splited(&self) -> bool;
 }
 
@@ -94,19 +115,55 @@ pub enum Notification {
     /// The 3d camera must face a given target
     CameraTarget((Vec3, Vec3)),
     TeleportCamera(Camera3D),
+    /// The 3d camera must move smoothly to a given position and orientation
+    SmoothTeleportCamera(Camera3D),
     CameraRotation(f32, f32, f32),
     Centering(Nucl, usize),
     CenterSelection(Selection, AppId),
     ShowTorsion(bool),
+    /// The torsion, in degrees, mapped to the extreme colors of the torsion heatmap. `None` means
+    /// that the range must be adjusted to the torsions of the design.
+    TorsionRange(Option<f32>),
+    /// Show/hide the strain of the nucleotides during rigid helices simulations
+    ShowStrain(bool),
+    /// Show/hide the boundaries and the lengths of the domains in the 2D view
+    ShowDomainLengths(bool),
     ModifersChanged(ModifiersState),
     Split2d,
     Redim2dHelices(bool),
+    /// Stack the visible helices of the 2D view with a uniform spacing
+    Compact2dLayout,
     Fog(FogParameters),
     WindowFocusLost,
     NewStereographicCamera(Arc<(Camera3D, f32)>),
     FlipSplitViews,
+    /// Split the 3D view into two viewports with independent cameras, or merge them
+    ToggleSplitScene3D,
     HorizonAligned,
-    ScreenShot3D,
+    ScreenShot3D(PngExportOptions),
+    /// Switch between the perspective and the orthographic projection in the 3d view
+    ToggleOrthographicProjection,
+    /// The way the 3d camera is rotated has changed
+    CameraModeChanged(CameraMode),
+    /// Enter or leave the fly-through navigation mode of the 3D view
+    FlyMode(bool),
+    /// Render a png picture from each saved camera of the design, in the given directory
+    ExportAllCameras(PathBuf),
+    /// The cameras of the top and bottom views of the 2D scene must be moved
+    TeleportCameras2D([Camera2D; 2]),
+    /// The pivot of the current group must be moved
+    PlaceGroupPivot(PivotPlacement),
+    /// Only show the selected elements in the 3D view, optionally with their cross-over partners
+    IsolateSelection(IsolateSelection),
+}
+
+/// A position at which the pivot of the current group can be placed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PivotPlacement {
+    /// The position of a nucleotide
+    Nucleotide(Nucl),
+    /// The centroid of the nucleotides of the current selection
+    SelectionCentroid,
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
@@ -31,6 +31,8 @@ pub const BOUND_LENGTH: f32 = 1.;
 pub const NB_RAY_TUBE: usize = 12;
 
 pub const SPHERE_RADIUS: f32 = 0.2;
+/// The distance, in pixels, at which a dragged pivot snaps to a nucleotide
+pub const PIVOT_SNAPPING_RADIUS: f32 = 15.;
 pub const NB_STACK_SPHERE: u16 = 12;
 pub const NB_SECTOR_SPHERE: u16 = 12;
 
@@ -40,6 +42,9 @@ pub const CANDIDATE_SCALE_FACTOR: f32 = 1.3;
 pub const SELECT_SCALE_FACTOR: f32 = 1. + 2. * (CANDIDATE_SCALE_FACTOR - 1.);
 pub const PIVOT_SCALE_FACTOR: f32 = 1.2 * SELECT_SCALE_FACTOR;
 pub const FREE_XOVER_SCALE_FACTOR: f32 = 1.25 * SELECT_SCALE_FACTOR;
+/// The maximal distance, in nanometers, between the cursor and a nucleotide for the target of a
+/// free cross-over to snap to this nucleotide.
+pub const FREE_XOVER_SNAPPING_DISTANCE: f32 = 1.;
 
 pub const RIGHT_HANDLE_ID: u32 = 0;
 pub const UP_HANDLE_ID: u32 = 1;
@@ -54,6 +59,11 @@ pub const BEZIER_CONTROL1_WIDGET_ID: u32 = 8;
 #[allow(dead_code)]
 pub const BEZIER_CONTROL2_WIDGET_ID: u32 = 9;
 pub const BEZIER_END_WIDGET_ID: u32 = 10;
+/// The identifier of the circle used to set the roll of the selected helix. It is the largest
+/// identifier that can be read from the fake texture, so that it does not collide with the
+/// identifiers of the widgets of bezier control points.
+pub const ROLL_CIRCLE_ID: u32 = 0xFF_FF_FF;
+pub const ROLL_CIRCLE_COLOR: u32 = 0xFF_E0_40_FF;
 
 pub fn bezier_widget_id(helix_id: u32, control_point: BezierControlPoint) -> u32 {
     let bezier_id = bezier_control_id(control_point);
@@ -62,6 +72,9 @@ pub fn bezier_widget_id(helix_id: u32, control_point: BezierControlPoint) -> u32
 
 use crate::{BezierControlPoint, RevolutionSimulationParameters};
 pub fn widget_id_to_bezier(id: u32) -> Option<(usize, BezierControlPoint)> {
+    if id == ROLL_CIRCLE_ID {
+        return None;
+    }
     let control = match id & 0xFF {
         n if n > BEZIER_END_WIDGET_ID => Some(BezierControlPoint::PiecewiseBezier(
             (n - 1 - BEZIER_END_WIDGET_ID) as usize,
@@ -111,11 +124,81 @@ pub const SUGGESTION_COLOR: u32 = 0xBF_FF_00_FF;
 pub const PIVOT_SPHERE_COLOR: u32 = 0xBF_FF_FF_00;
 pub const SURFACE_PIVOT_SPHERE_COLOR: u32 = 0xBF_FF_14_B9; // pinkish
 pub const FREE_XOVER_COLOR: u32 = 0xBF_00_00_FF;
+pub const MEASURE_COLOR: u32 = 0xBF_FF_8C_00; // Dark orange
+pub const RULER_COLOR: u32 = 0xFF_30_30_30; // Dark grey
+pub const REFERENCE_DESIGN_COLOR: u32 = 0x60_70_80_90; // Transparent slate grey
+pub const ADDED_STRAND_COLOR: u32 = 0xBF_20_C0_C0; // Teal
+pub const MODIFIED_STRAND_COLOR: u32 = 0xBF_FF_D7_00; // Gold
+pub const MOVED_HELIX_COLOR: u32 = 0xBF_B0_60_FF; // Violet
+pub const REMOVED_STRAND_COLOR: u32 = 0x60_FF_30_30; // Transparent red
+pub const DELETION_COLOR: u32 = 0xFF_D0_10_10;
+pub const ANCHOR_COLOR: u32 = 0xFF_4B_00_82; // Indigo
+pub const MODIFICATION_COLOR: u32 = 0xFF_FF_14_93; // Deep pink
+pub const STRAND_END_HANDLE_COLOR: u32 = 0xFF_1E_90_FF; // Dodger blue
+/// The color of the handles of a strand whose ends meet, and that will be made cyclic when the
+/// handle is released
+pub const CLOSING_STRAND_HANDLE_COLOR: u32 = 0xFF_32_CD_32; // Lime green
+/// The color of the preview of the nucleotides that would pair with the domain being built
+pub const BUILDING_COMPLEMENT_COLOR: u32 = 0x60_00_FF_00; // Transparent green
+/// The color of the existing strands that would pair with the domain being built
+pub const BUILDING_PAIRED_STRAND_COLOR: u32 = 0xBF_00_BF_FF; // Deep sky blue
+
+/// The colors of the diverging colormap used to display the torsion of the helices
+pub const TORSION_UNDERTWIST_COLOR: u32 = 0xFF_3B_4C_C0;
+pub const TORSION_RELAXED_COLOR: u32 = 0xFF_DD_DD_DD;
+pub const TORSION_OVERTWIST_COLOR: u32 = 0xFF_B4_04_26;
+/// The range, in degrees, of the torsion colormap when the design is not twisted at all
+pub const MIN_AUTO_TORSION_RANGE: f32 = 0.1;
+/// The colors of the nucleotides with no strain and with the largest strain of the design
+/// during rigid helices simulations
+pub const STRAIN_RELAXED_COLOR: u32 = TORSION_RELAXED_COLOR;
+pub const STRAIN_MAX_COLOR: u32 = 0xFF_D7_30_27;
+/// The strain mapped to `STRAIN_MAX_COLOR` when the design is not strained at all
+pub const MIN_AUTO_STRAIN_RANGE: f32 = 1e-3;
+pub const SCAFFOLD_SEAM_COLOR: u32 = 0xFF_00_80_FF;
+/// The color of the marks drawn at the boundaries of the domains in the 2D view
+pub const DOMAIN_BOUNDARY_COLOR: u32 = 0xFF_40_40_40;
+/// The radius of the tubes joining measured nucleotides, relative to the radius of the bonds
+pub const MEASURE_TUBE_RADIUS: f32 = 0.3;
+/// The radius of the ticks of the nucleotide ruler, relative to the radius of the bonds
+pub const RULER_TICK_RADIUS: f32 = 0.25;
+/// The number of nucleotides between two consecutive ticks of the nucleotide ruler
+pub const RULER_TICK_PERIOD: isize = 8;
+/// The number of nucleotides between two consecutive labels of the nucleotide ruler, when the
+/// labels are close to the camera
+pub const RULER_LABEL_PERIOD: isize = 32;
+/// Half the length, in nanometers, of the ticks of the nucleotide ruler. The ticks of the
+/// labelled nucleotides are twice as long.
+pub const RULER_TICK_HALF_LENGTH: f32 = 1.5;
+/// The minimal distance between two consecutive labels of the nucleotide ruler, relative to their
+/// distance to the camera
+pub const RULER_LABEL_MIN_SPACING: f32 = 0.15;
 pub const CHECKED_XOVER_COLOR: u32 = 0xBF_3C_B3_71; //Medium sea green
 pub const UNCHECKED_XOVER_COLOR: u32 = 0xCF_FF_14_93; // Deep pink
+/// The colors of the cross-overs with no strain and with a strain of at least
+/// `MAX_XOVER_STRAIN_COLOR` nanometers
+pub const RELAXED_XOVER_COLOR: u32 = 0xFF_1A_98_50;
+pub const STRAINED_XOVER_COLOR: u32 = 0xFF_D7_30_27;
+pub const MAX_XOVER_STRAIN_COLOR: f32 = 2.;
 pub const STEREOGRAPHIC_SPHERE_COLOR: u32 = 0xDD_2F_4F_4F; // Slate grey
 pub const STEREOGRAPHIC_SPHERE_RADIUS: f32 = 2.;
 
+/// Designs with at most this number of nucleotides are always drawn with full detail
+pub const LOD_NUCL_BUDGET: usize = 40_000;
+/// The distance, in nanometers, from the camera above which helices may be drawn as cylinders
+pub const LOD_DISTANCE: f32 = 150.;
+/// The relative margin around the threshold distance within which the representation of a
+/// helix is left unchanged
+pub const LOD_HYSTERESIS: f32 = 0.15;
+/// The distance, in nanometers, that the camera must travel before the level of detail is
+/// updated
+pub const LOD_CAMERA_EPSILON: f32 = 1.;
+/// The maximal number of consecutive positions of a helix drawn as a single cylinder
+pub const LOD_SEGMENT_LENGTH: isize = 21;
+/// The radius, in nanometers, of the cylinders representing helices far from the camera
+pub const LOD_HELIX_RADIUS: f32 = 1.;
+pub const LOD_HELIX_COLOR: u32 = 0xFF_A0_A8_B0;
+
 pub const MAX_ZOOM_2D: f32 = 50.0;
 
 pub const CIRCLE2D_GREY: u32 = 0xFF_4D4D4D;
@@ -180,9 +263,11 @@ pub const CYM_HANDLE_COLORS: [u32; 3] = [0x00FFFF, 0xFF00FF, 0xFFFF00];
 pub const ORIGAMI_EXTENSION: &str = "origami";
 pub const ENS_EXTENSION: &str = "ens";
 pub const ENS_BACKUP_EXTENSION: &str = "ensbackup";
+pub const ENS_WORKSPACE_EXTENSION: &str = "ensworkspace.json";
 pub const ENS_UNNAMED_FILE_NAME: &str = "Unnamed_design";
-pub const CANNOT_OPEN_DEFAULT_DIR: &str = "Unable to open document or home directory.
+pub const CANNOT_OPEN_DEFAULT_DIR: &str = "Unable to open the data directory.
 No backup will be saved for this unnamed design";
+pub const SAVING_THREAD_STOPPED: &str = "The thread that writes the design files has stopped";
 
 pub const NO_DESIGN_TITLE: &str = "New file";
 
@@ -192,11 +277,16 @@ pub const BEZIER_START_COLOR: u32 = 0xFF_B0_21_21;
 pub const BEZIER_END_COLOR: u32 = 0xFF_F0_CA_22;
 pub const BEZIER_CONTROL1_COLOR: u32 = 0xFF_37_85_30;
 pub const BEZIER_CONTROL2_COLOR: u32 = 0xFF_1A_15_70;
-pub const SEC_BETWEEN_BACKUPS: u64 = 60;
+/// The default number of minutes between two backups of the design
+pub const DEFAULT_AUTOSAVE_INTERVAL: f32 = 1.;
+/// The default number of operations listed in the history panel
+pub const DEFAULT_UNDO_HISTORY_LENGTH: usize = 20;
 pub const SEC_PER_YEAR: u64 = 31_536_000;
 
 pub const DEFAULT_STEREOGRAPHIC_ZOOM: f32 = 3.0;
 pub const STEREOGRAPHIC_ZOOM_STEP: f32 = 1.1;
+/// The default duration, in seconds, of the animated camera movements
+pub const DEFAULT_CAMERA_ANIMATION_DURATION: f32 = 0.3;
 pub const PIECEWISE_BEZIER_COLOR: u32 = 0xFF_66_CD_AA; // Medium Aquamarine
 
 pub const UPDATE_VISIBILITY_SIEVE_LABEL: &str = "Update visibility sieve";
@@ -244,6 +334,73 @@ pub const PRINTABLE_CHARS: &[char] = &[
 ];
 pub const NB_PRINTABLE_CHARS: usize = PRINTABLE_CHARS.len();
 
+/// The characters that can appear in the labels of the measurements in the 3D view
+pub const MEASURE_CHARS: &[char] = &[
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '.', ' ', 'n', 'm', '°',
+];
+pub const NB_MEASURE_CHARS: usize = MEASURE_CHARS.len();
+/// The characters that can appear in the label showing the length of a domain being built
+pub const BUILDING_LABEL_CHARS: &[char] = &[
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '.', 'n', 't', 'm', '/',
+];
+pub const NB_BUILDING_LABEL_CHARS: usize = BUILDING_LABEL_CHARS.len();
+/// The characters that can appear in the coordinates labels of the grid cells
+pub const GRID_COORDINATE_CHARS: &[char] = &[
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '-', ',', '(', ')',
+];
+pub const NB_GRID_COORDINATE_CHARS: usize = GRID_COORDINATE_CHARS.len();
+/// The characters that can appear in the label showing the angle of the rotation made with the
+/// rotation widget
+pub const ROTATION_ANGLE_CHARS: &[char] = &[
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '.', '-', '°',
+];
+pub const NB_ROTATION_ANGLE_CHARS: usize = ROTATION_ANGLE_CHARS.len();
+/// The characters that can appear in the labels showing the names of the bezier planes. The other
+/// characters of the names are not drawn.
+pub const BEZIER_PLANE_NAME_CHARS: &[char] = &[
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
+    't', 'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L',
+    'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '0', '1', '2', '3', '4',
+    '5', '6', '7', '8', '9', '-', '_', '.', '(', ')',
+];
+pub const NB_BEZIER_PLANE_NAME_CHARS: usize = BEZIER_PLANE_NAME_CHARS.len();
+/// The characters that can appear in the labels of the nucleotide ruler
+pub const RULER_CHARS: &[char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '-'];
+pub const NB_RULER_CHARS: usize = RULER_CHARS.len();
+/// The characters that can appear in the labels of the legend of the strand colormap
+pub const LEGEND_CHARS: &[char] = &[
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '.', '-', 'n', 't', '°', 'C',
+];
+pub const NB_LEGEND_CHARS: usize = LEGEND_CHARS.len();
+/// The color of the background of the tooltips of the 3D view
+pub const TOOLTIP_BACKGROUND_COLOR: [f32; 4] = [1., 1., 0.9, 0.9];
+/// The maximum number of characters displayed in the tooltips of the 3D view
+pub const TOOLTIP_MAX_CHARS: usize = 60;
+/// The color of the background of the banner shown on the 2D and 3D views during simulations
+pub const SIMULATION_BANNER_COLOR: [f32; 4] = [1., 0.8, 0.4, 0.9];
+/// The color of the background of the transient messages shown on the 2D and 3D views
+pub const TOAST_BACKGROUND_COLOR: [f32; 4] = [0.2, 0.2, 0.2, 0.85];
+/// The maximum number of transient messages shown at the same time
+pub const MAX_TOASTS: usize = 3;
+/// The time during which a transient message is shown, fade-out included
+pub const TOAST_DURATION: std::time::Duration = std::time::Duration::from_millis(3_000);
+/// The duration of the fade-out at the end of the display of a transient message
+pub const TOAST_FADE_DURATION: std::time::Duration = std::time::Duration::from_millis(500);
+/// The coordinates of the grid cells are displayed on the cells whose x and y coordinates are at
+/// most this far from the cell under the cursor
+pub const GRID_COORDINATE_LABEL_RADIUS: isize = 2;
+/// The length, in nanometers, by which the selection is moved at each key press
+pub const NUDGE_STEP: f32 = 0.5;
+/// The length, in nanometers, by which the selection is moved at each key press with Shift held
+pub const NUDGE_FINE_STEP: f32 = 0.1;
+/// The length, in nanometers, by which the selection is moved at each key press with Ctrl held
+pub const NUDGE_COARSE_STEP: f32 = 5.;
+/// The increments, in degrees, to which the rotations made with the widget snap with Shift, Ctrl,
+/// and both of them held
+pub const ROTATION_SNAP_FINE_STEP: f32 = 5.;
+pub const ROTATION_SNAP_STEP: f32 = 15.;
+pub const ROTATION_SNAP_COARSE_STEP: f32 = 90.;
+
 /// The factor by which the width of candidate highlighted strands is multiplied
 pub const CANDIDATE_STRAND_HIGHLIGHT_FACTOR_2D: f32 = 1.7;
 /// The factor by which the width of selected highlighted strands is multiplied
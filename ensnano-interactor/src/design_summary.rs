@@ -0,0 +1,329 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Aggregated statistics of a design, shown in the status bar.
+//!
+//! The summary is updated after each modification of the design, so it is computed from the
+//! domains of the strands without looking at individual nucleotides. The contribution of each
+//! strand is cached so that only the strands that were modified are read again.
+
+use ensnano_design::{Design, Domain, HelixCollection, Strand};
+use std::collections::{BTreeMap, HashMap};
+
+/// The average molar mass of a nucleotide in a DNA strand, in g/mol
+pub const AVERAGE_NUCLEOTIDE_MASS: f64 = 330.;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DesignSummary {
+    pub nb_helices: usize,
+    pub nb_strands: usize,
+    pub nb_staples: usize,
+    pub nb_scaffolds: usize,
+    /// The number of nucleotides of the scaffolds, or `None` if the design has no scaffold
+    pub scaffold_length: Option<usize>,
+    /// The number of nucleotides of the scaffolds that are not paired with another nucleotide
+    pub nb_unpaired_scaffold_nucl: usize,
+    /// The number of nucleotides of all the strands, deleted nucleotides excluded
+    pub nb_nucl: usize,
+    pub nb_xovers: usize,
+}
+
+impl DesignSummary {
+    /// The fraction of the nucleotides of the scaffolds that are paired
+    pub fn scaffold_coverage(&self) -> Option<f64> {
+        self.scaffold_length
+            .filter(|length| *length > 0)
+            .map(|length| 1. - self.nb_unpaired_scaffold_nucl as f64 / length as f64)
+    }
+
+    /// The estimated molar mass of the design, in g/mol
+    pub fn estimated_mass(&self) -> f64 {
+        self.nb_nucl as f64 * AVERAGE_NUCLEOTIDE_MASS
+    }
+
+    /// A description of the summary that fits on one line
+    pub fn short_description(&self) -> String {
+        let mut ret = format!(
+            "{} helices, {} strands, {} nt, {} xovers",
+            self.nb_helices, self.nb_strands, self.nb_nucl, self.nb_xovers
+        );
+        if let Some(coverage) = self.scaffold_coverage() {
+            ret.push_str(&format!(", scaffold {:.1}% covered", coverage * 100.));
+        }
+        ret
+    }
+
+    /// A description of the summary with one statistic per line
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("Helices: {}", self.nb_helices),
+            format!("Strands: {}", self.nb_strands),
+            format!("Staples: {}", self.nb_staples),
+        ];
+        if self.nb_scaffolds > 1 {
+            lines.push(format!("Scaffolds: {}", self.nb_scaffolds));
+        }
+        if let Some(length) = self.scaffold_length {
+            lines.push(format!("Scaffold length: {length} nt"));
+            lines.push(format!(
+                "Unpaired scaffold nucleotides: {}",
+                self.nb_unpaired_scaffold_nucl
+            ));
+        }
+        if let Some(coverage) = self.scaffold_coverage() {
+            lines.push(format!("Scaffold coverage: {:.1}%", coverage * 100.));
+        }
+        lines.push(format!("Nucleotides: {}", self.nb_nucl));
+        lines.push(format!("Cross-overs: {}", self.nb_xovers));
+        lines.push(format!(
+            "Estimated mass: {:.2} MDa",
+            self.estimated_mass() / 1e6
+        ));
+        lines.join("\n")
+    }
+}
+
+/// An interval `[start, end)` of positions on one strand of a helix
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    helix: usize,
+    forward: bool,
+    start: isize,
+    end: isize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct StrandContribution {
+    nb_nucl: usize,
+    nb_xovers: usize,
+    intervals: Vec<Interval>,
+}
+
+impl StrandContribution {
+    fn new(strand: &Strand) -> Self {
+        let intervals = strand
+            .domains
+            .iter()
+            .filter_map(|d| {
+                if let Domain::HelixDomain(interval) = d {
+                    Some(Interval {
+                        helix: interval.helix,
+                        forward: interval.forward,
+                        start: interval.start,
+                        end: interval.end,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        Self {
+            nb_nucl: strand.length(),
+            nb_xovers: if strand.domains.is_empty() {
+                0
+            } else {
+                strand.xovers().len()
+            },
+            intervals,
+        }
+    }
+}
+
+/// The contributions of the strands of a design to its summary.
+#[derive(Debug, Clone, Default)]
+pub struct DesignSummaryCache {
+    strands: BTreeMap<usize, StrandContribution>,
+}
+
+impl DesignSummaryCache {
+    /// Read all the strands of `design`.
+    pub fn reset(&mut self, design: &Design) {
+        self.strands = design
+            .strands
+            .iter()
+            .map(|(s_id, strand)| (*s_id, StrandContribution::new(strand)))
+            .collect();
+    }
+
+    /// Read again the strands whose identifier is in `strand_ids`. The identifiers that are not
+    /// used in `design` anymore are forgotten.
+    pub fn update_strands(&mut self, design: &Design, strand_ids: impl IntoIterator<Item = usize>) {
+        for s_id in strand_ids {
+            if let Some(strand) = design.strands.get(&s_id) {
+                self.strands.insert(s_id, StrandContribution::new(strand));
+            } else {
+                self.strands.remove(&s_id);
+            }
+        }
+    }
+
+    /// The summary of `design`, whose strands must have been read by this cache.
+    pub fn summary(&self, design: &Design) -> DesignSummary {
+        let mut covered: HashMap<(usize, bool), Vec<(isize, isize)>> = HashMap::new();
+        for interval in self.strands.values().flat_map(|s| s.intervals.iter()) {
+            covered
+                .entry((interval.helix, interval.forward))
+                .or_default()
+                .push((interval.start, interval.end));
+        }
+        for intervals in covered.values_mut() {
+            merge_intervals(intervals);
+        }
+        let covered_length = |helix: usize, forward: bool, start: isize, end: isize| {
+            covered
+                .get(&(helix, forward))
+                .map(|intervals| intersection_length(intervals, start, end))
+                .unwrap_or(0)
+        };
+
+        let scaffolds: Vec<&StrandContribution> = design
+            .scaffold_ids
+            .iter()
+            .filter_map(|s_id| self.strands.get(s_id))
+            .collect();
+        let nb_unpaired_scaffold_nucl = scaffolds
+            .iter()
+            .flat_map(|scaffold| scaffold.intervals.iter())
+            .map(|i| {
+                (i.end - i.start).max(0) as usize
+                    - covered_length(i.helix, !i.forward, i.start, i.end)
+            })
+            .sum();
+        let nb_deleted_nucl = design
+            .deletions
+            .iter()
+            .filter(|n| covered_length(n.helix, n.forward, n.position, n.position + 1) > 0)
+            .count();
+        let nb_nucl: usize = self.strands.values().map(|s| s.nb_nucl).sum();
+
+        DesignSummary {
+            nb_helices: design.helices.len(),
+            nb_strands: self.strands.len(),
+            nb_staples: self.strands.len() - scaffolds.len(),
+            nb_scaffolds: scaffolds.len(),
+            scaffold_length: (!scaffolds.is_empty())
+                .then(|| scaffolds.iter().map(|scaffold| scaffold.nb_nucl).sum()),
+            nb_unpaired_scaffold_nucl,
+            nb_nucl: nb_nucl.saturating_sub(nb_deleted_nucl),
+            nb_xovers: self.strands.values().map(|s| s.nb_xovers).sum(),
+        }
+    }
+}
+
+/// Sort `intervals` and merge the ones that overlap.
+fn merge_intervals(intervals: &mut Vec<(isize, isize)>) {
+    intervals.sort_unstable();
+    let mut merged: Vec<(isize, isize)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals.drain(..) {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    *intervals = merged;
+}
+
+/// The number of positions of `[start, end)` that are in one of the sorted disjoint `intervals`.
+fn intersection_length(intervals: &[(isize, isize)], start: isize, end: isize) -> usize {
+    let first = intervals.partition_point(|(_, i_end)| *i_end <= start);
+    intervals[first..]
+        .iter()
+        .take_while(|(i_start, _)| *i_start < end)
+        .map(|(i_start, i_end)| (end.min(*i_end) - start.max(*i_start)).max(0) as usize)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ensnano_design::{read_junctions, HelixInterval, Nucl};
+
+    fn strand(intervals: &[(usize, isize, isize, bool)]) -> Strand {
+        let domains: Vec<Domain> = intervals
+            .iter()
+            .map(|(helix, start, end, forward)| {
+                Domain::HelixDomain(HelixInterval {
+                    helix: *helix,
+                    start: *start,
+                    end: *end,
+                    forward: *forward,
+                    sequence: None,
+                })
+            })
+            .collect();
+        Strand {
+            junctions: read_junctions(&domains, false),
+            domains,
+            ..Default::default()
+        }
+    }
+
+    fn summary(design: &Design) -> DesignSummary {
+        let mut cache = DesignSummaryCache::default();
+        cache.reset(design);
+        cache.summary(design)
+    }
+
+    #[test]
+    fn scaffold_coverage_counts_paired_nucleotides() {
+        let mut design = Design::new();
+        design
+            .strands
+            .insert(0, strand(&[(0, 0, 10, true), (1, 0, 10, false)]));
+        design.strands.insert(1, strand(&[(0, 2, 6, false)]));
+        design.strands.insert(2, strand(&[(0, 4, 8, false)]));
+        design.strands.insert(3, strand(&[(1, 0, 5, true)]));
+        design.scaffold_ids = vec![0];
+        let summary = summary(&design);
+        assert_eq!(summary.nb_strands, 4);
+        assert_eq!(summary.nb_staples, 3);
+        assert_eq!(summary.scaffold_length, Some(20));
+        assert_eq!(summary.nb_unpaired_scaffold_nucl, 20 - 6 - 5);
+        assert_eq!(summary.nb_nucl, 20 + 4 + 4 + 5);
+        assert_eq!(summary.nb_xovers, 1);
+    }
+
+    #[test]
+    fn modified_strands_are_read_again() {
+        let mut design = Design::new();
+        design.strands.insert(0, strand(&[(0, 0, 10, true)]));
+        design.strands.insert(1, strand(&[(0, 0, 10, false)]));
+        let mut cache = DesignSummaryCache::default();
+        cache.reset(&design);
+        assert_eq!(cache.summary(&design).nb_nucl, 20);
+
+        design.strands.insert(1, strand(&[(0, 0, 4, false)]));
+        design.strands.remove(&0);
+        design.deletions.insert(Nucl::new(0, 2, false));
+        cache.update_strands(&design, [0, 1]);
+        let summary = cache.summary(&design);
+        assert_eq!(summary, self::summary(&design));
+        assert_eq!(summary.nb_strands, 1);
+        assert_eq!(summary.nb_nucl, 3);
+        assert_eq!(summary.scaffold_coverage(), None);
+    }
+
+    #[test]
+    fn intersection_of_intervals() {
+        let mut intervals = vec![(5, 8), (0, 3), (2, 4), (10, 12)];
+        merge_intervals(&mut intervals);
+        assert_eq!(intervals, vec![(0, 4), (5, 8), (10, 12)]);
+        assert_eq!(intersection_length(&intervals, 3, 11), 1 + 3 + 1);
+        assert_eq!(intersection_length(&intervals, 12, 20), 0);
+    }
+}
@@ -16,9 +16,13 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub use ensnano_design::FogCenter;
+use ensnano_design::{design_diff, Design, Domain, FogSettings, FogStyle, Nucl, Strand};
 use iced_winit::winit;
 use serde::{Deserialize, Serialize};
-use ultraviolet::Vec3;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use ultraviolet::{Isometry3, Vec3};
 use winit::dpi::{PhysicalPosition, PhysicalSize};
 #[derive(Clone, Debug, PartialEq, Eq, Copy, Serialize, Deserialize)]
 pub enum RenderingMode {
@@ -34,6 +38,29 @@ impl Default for RenderingMode {
     }
 }
 
+/// The parameters of a png export of the 3D view
+#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+pub struct PngExportOptions {
+    /// The width and height of the exported image. If `None`, the largest side of the image is
+    /// 2560 pixels long and the image has the aspect ratio of the 3D view.
+    pub resolution: Option<(u32, u32)>,
+    /// Render the background with a fully transparent color
+    pub transparent_background: bool,
+    /// The rendering mode used for the export. If `None`, the current rendering mode of the 3D
+    /// view is used.
+    pub rendering_mode: Option<RenderingMode>,
+}
+
+impl Default for PngExportOptions {
+    fn default() -> Self {
+        Self {
+            resolution: None,
+            transparent_background: false,
+            rendering_mode: Some(RenderingMode::Cartoon),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Copy, Serialize, Deserialize)]
 pub enum Background3D {
     Sky,
@@ -92,6 +119,281 @@ impl std::fmt::Display for HBoundDisplay {
     }
 }
 
+/// Which hydrogen bonds are drawn in the 3D view
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HBondFilter {
+    /// Do not draw any hydrogen bond
+    None,
+    /// Only draw the hydrogen bonds whose two nucleotides belong to the selected elements
+    SelectionOnly,
+    /// Draw all the hydrogen bonds of the design
+    All,
+}
+
+pub const ALL_H_BOND_FILTERS: [HBondFilter; 3] = [
+    HBondFilter::None,
+    HBondFilter::SelectionOnly,
+    HBondFilter::All,
+];
+
+impl Default for HBondFilter {
+    fn default() -> Self {
+        Self::All
+    }
+}
+
+impl std::fmt::Display for HBondFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::None => "None",
+            Self::SelectionOnly => "Selection only",
+            Self::All => "All",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// Which elements are drawn in the 3D view
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IsolateSelection {
+    /// Draw all the elements of the design
+    No,
+    /// Only draw the selected elements
+    Selection,
+    /// Only draw the selected elements and the strands that are linked to them by a cross-over
+    WithXoverPartners,
+}
+
+pub const ALL_ISOLATE_SELECTION: [IsolateSelection; 3] = [
+    IsolateSelection::No,
+    IsolateSelection::Selection,
+    IsolateSelection::WithXoverPartners,
+];
+
+impl Default for IsolateSelection {
+    fn default() -> Self {
+        Self::No
+    }
+}
+
+impl std::fmt::Display for IsolateSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::No => "No",
+            Self::Selection => "Selection",
+            Self::WithXoverPartners => "Selection + xover partners",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+/// The property according to which the non-scaffold strands are colored
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StrandColoring {
+    /// Use the color of the strands
+    Stored,
+    /// The number of nucleotides of the strand
+    Length,
+    /// The number of domains of the strand that are on a helix
+    NbDomains,
+    /// The estimated melting temperature of the strand
+    MeltingTemperature,
+}
+
+pub const ALL_STRAND_COLORINGS: [StrandColoring; 4] = [
+    StrandColoring::Stored,
+    StrandColoring::Length,
+    StrandColoring::NbDomains,
+    StrandColoring::MeltingTemperature,
+];
+
+impl Default for StrandColoring {
+    fn default() -> Self {
+        Self::Stored
+    }
+}
+
+impl std::fmt::Display for StrandColoring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::Stored => "Strand colors",
+            Self::Length => "Length",
+            Self::NbDomains => "Number of domains",
+            Self::MeltingTemperature => "Melting temperature",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+impl StrandColoring {
+    /// Format a value of the property, with its unit.
+    pub fn format_value(&self, value: f32) -> String {
+        match self {
+            Self::Stored | Self::NbDomains => format!("{}", value.round()),
+            Self::Length => format!("{}nt", value.round()),
+            Self::MeltingTemperature => format!("{:.1}°C", value),
+        }
+    }
+}
+
+/// The colormaps that can be used to color the strands according to a property
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Colormap {
+    Viridis,
+    Rainbow,
+    CoolWarm,
+}
+
+pub const ALL_COLORMAPS: [Colormap; 3] = [Colormap::Viridis, Colormap::Rainbow, Colormap::CoolWarm];
+
+impl Default for Colormap {
+    fn default() -> Self {
+        Self::Viridis
+    }
+}
+
+impl std::fmt::Display for Colormap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::Viridis => "Viridis",
+            Self::Rainbow => "Rainbow",
+            Self::CoolWarm => "Cool-warm",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
+impl Colormap {
+    fn stops(&self) -> &'static [u32] {
+        match self {
+            Self::Viridis => &[
+                0xFF_44_01_54,
+                0xFF_3B_52_8B,
+                0xFF_21_91_8C,
+                0xFF_5E_C9_62,
+                0xFF_FD_E7_25,
+            ],
+            Self::Rainbow => &[
+                0xFF_00_00_FF,
+                0xFF_00_FF_FF,
+                0xFF_00_FF_00,
+                0xFF_FF_FF_00,
+                0xFF_FF_00_00,
+            ],
+            Self::CoolWarm => &[0xFF_3B_4C_C0, 0xFF_DD_DD_DD, 0xFF_B4_04_26],
+        }
+    }
+
+    /// The color mapped to `t`, which is clamped to [0, 1].
+    pub fn color(&self, t: f32) -> u32 {
+        let stops = self.stops();
+        let t = if t.is_nan() { 0. } else { t.clamp(0., 1.) };
+        let x = t * (stops.len() - 1) as f32;
+        let idx = (x.floor() as usize).min(stops.len() - 2);
+        let (from, to) = (stops[idx], stops[idx + 1]);
+        let s = x - idx as f32;
+        let channel = |shift: u32| {
+            let a = ((from >> shift) & 0xFF) as f32;
+            let b = ((to >> shift) & 0xFF) as f32;
+            ((a + s * (b - a)).round() as u32) << shift
+        };
+        channel(24) | channel(16) | channel(8) | channel(0)
+    }
+}
+
+/// The colors of the strands when they are colored according to a property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrandColors {
+    pub coloring: StrandColoring,
+    pub colormap: Colormap,
+    /// The smallest value of the property among the colored strands
+    pub min: f32,
+    /// The largest value of the property among the colored strands
+    pub max: f32,
+    colors: HashMap<usize, u32>,
+}
+
+impl StrandColors {
+    /// Map the values of the property of each strand to the colors of `colormap`.
+    ///
+    /// Return `None` if `coloring` is `StrandColoring::Stored` or if no strand has a value.
+    pub fn new(
+        coloring: StrandColoring,
+        colormap: Colormap,
+        values: &BTreeMap<usize, f32>,
+    ) -> Option<Self> {
+        if coloring == StrandColoring::Stored || values.is_empty() {
+            return None;
+        }
+        let min = values.values().cloned().fold(f32::INFINITY, f32::min);
+        let max = values.values().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let colors = values
+            .iter()
+            .map(|(s_id, v)| {
+                let t = if max > min {
+                    (v - min) / (max - min)
+                } else {
+                    0.5
+                };
+                (*s_id, colormap.color(t))
+            })
+            .collect();
+        Some(Self {
+            coloring,
+            colormap,
+            min,
+            max,
+            colors,
+        })
+    }
+
+    /// The color of the strand `s_id`, or `None` if the strand is not colored according to the
+    /// property.
+    pub fn strand_color(&self, s_id: usize) -> Option<u32> {
+        self.colors.get(&s_id).cloned()
+    }
+}
+
+/// How mouse drags rotate the 3D camera
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CameraMode {
+    /// The camera can rotate freely
+    Free,
+    /// The camera rotates around the world's y axis, and never rolls
+    TurntableY,
+    /// The camera rotates around the world's z axis, and never rolls
+    TurntableZ,
+}
+
+pub const ALL_CAMERA_MODES: [CameraMode; 3] = [
+    CameraMode::Free,
+    CameraMode::TurntableY,
+    CameraMode::TurntableZ,
+];
+
+impl Default for CameraMode {
+    fn default() -> Self {
+        Self::Free
+    }
+}
+
+impl CameraMode {
+    pub fn is_turntable(&self) -> bool {
+        matches!(self, Self::TurntableY | Self::TurntableZ)
+    }
+}
+
+impl std::fmt::Display for CameraMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ret = match self {
+            Self::Free => "Free",
+            Self::TurntableY => "Turntable (Y up)",
+            Self::TurntableZ => "Turntable (Z up)",
+        };
+        write!(f, "{}", ret)
+    }
+}
+
 pub mod fog_kind {
     pub const NO_FOG: u32 = 0;
     pub const TRANSPARENT_FOG: u32 = 1;
@@ -104,23 +406,89 @@ pub struct FogParameters {
     pub radius: f32,
     pub length: f32,
     pub fog_kind: u32,
-    pub from_camera: bool,
+    pub center: FogCenter,
     pub alt_fog_center: Option<Vec3>,
 }
 
+/// The clear radius of the fog of the "focus" preset
+const FOCUS_FOG_LENGTH: f32 = 5.;
+/// The softness of the fog of the "focus" preset
+const FOCUS_FOG_RADIUS: f32 = 3.;
+
 impl FogParameters {
     pub fn new() -> Self {
         Self {
             radius: 10.,
             length: 10.,
             fog_kind: fog_kind::NO_FOG,
-            from_camera: true,
+            center: FogCenter::Camera,
             alt_fog_center: None,
         }
     }
+
+    /// The "focus" preset: a tight fog around the pivot.
+    pub fn focus() -> Self {
+        Self {
+            radius: FOCUS_FOG_RADIUS,
+            length: FOCUS_FOG_LENGTH,
+            fog_kind: fog_kind::TRANSPARENT_FOG,
+            center: FogCenter::Pivot,
+            alt_fog_center: None,
+        }
+    }
+
+    /// The "off" preset. The dimensions of the fog are kept so that they are the same when the
+    /// fog is enabled again.
+    pub fn off(&self) -> Self {
+        Self {
+            fog_kind: fog_kind::NO_FOG,
+            ..self.clone()
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.fog_kind != fog_kind::NO_FOG
+    }
+
+    pub fn from_settings(settings: &FogSettings) -> Self {
+        let fog_kind = if settings.enabled {
+            match settings.style {
+                FogStyle::Transparent => fog_kind::TRANSPARENT_FOG,
+                FogStyle::Dark => fog_kind::DARK_FOG,
+                FogStyle::Reversed => fog_kind::REVERSED_FOG,
+            }
+        } else {
+            fog_kind::NO_FOG
+        };
+        Self {
+            radius: settings.softness,
+            length: settings.radius,
+            fog_kind,
+            center: settings.center,
+            alt_fog_center: settings.center_position,
+        }
+    }
+
+    pub fn to_settings(&self) -> FogSettings {
+        let style = match self.fog_kind {
+            fog_kind::DARK_FOG => FogStyle::Dark,
+            fog_kind::REVERSED_FOG => FogStyle::Reversed,
+            _ => FogStyle::Transparent,
+        };
+        FogSettings {
+            enabled: self.is_visible(),
+            style,
+            radius: self.length,
+            softness: self.radius,
+            center: self.center,
+            center_position: self
+                .alt_fog_center
+                .filter(|_| self.center != FogCenter::Camera),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SplitMode {
     Flat,
     Scene3D,
@@ -193,3 +561,190 @@ pub struct LoopoutBond {
     /// The identifier of the bond representing the whole loopout involving this bond
     pub repr_bond_identifier: u32,
 }
+
+/// A design loaded next to the edited one, to compare two versions of a structure.
+///
+/// The reference design is only drawn in the 3D view, with a single semi-transparent color, and
+/// it can neither be selected nor modified.
+#[derive(Clone, Debug)]
+pub struct ReferenceDesign {
+    /// The positions of the nucleotides of the reference design
+    pub nucleotides: Arc<Vec<Vec3>>,
+    /// The bonds between consecutive nucleotides of the strands of the reference design
+    pub bonds: Arc<Vec<(Vec3, Vec3)>>,
+    /// The barycenter of the nucleotides, around which the rotation of `isometry` is applied
+    pub center: Vec3,
+    /// The isometry applied to the reference design to align it with the edited design
+    pub isometry: Isometry3,
+    pub visible: bool,
+}
+
+impl ReferenceDesign {
+    /// Read the position of the nucleotides of the strands of `design`.
+    ///
+    /// The helices of `design` must have been instanciated so that the nucleotides of curved
+    /// helices are correctly positioned.
+    pub fn new(design: &Design) -> Self {
+        let mut nucleotides = Vec::new();
+        let mut bonds = Vec::new();
+        for strand in design.strands.values() {
+            let positions: Vec<Vec3> = helix_nucls(strand)
+                .filter_map(|nucl| design.get_nucl_position(nucl))
+                .collect();
+            bonds.extend(positions.windows(2).map(|w| (w[0], w[1])));
+            if strand.cyclic && positions.len() > 2 {
+                bonds.push((positions[positions.len() - 1], positions[0]));
+            }
+            nucleotides.extend(positions);
+        }
+        let center = if nucleotides.is_empty() {
+            Vec3::zero()
+        } else {
+            nucleotides.iter().fold(Vec3::zero(), |acc, p| acc + *p) / nucleotides.len() as f32
+        };
+        Self {
+            nucleotides: Arc::new(nucleotides),
+            bonds: Arc::new(bonds),
+            center,
+            isometry: Isometry3::identity(),
+            visible: true,
+        }
+    }
+
+    /// The position at which `point`, a position in the reference design, is drawn.
+    pub fn transform(&self, point: Vec3) -> Vec3 {
+        self.isometry.transform_vec(point - self.center) + self.center
+    }
+}
+
+/// The nucleotides of the helix domains of `strand`, in the 5' -> 3' direction.
+fn helix_nucls(strand: &Strand) -> impl Iterator<Item = Nucl> + '_ {
+    strand
+        .domains
+        .iter()
+        .filter_map(|d| match d {
+            Domain::HelixDomain(interval) => Some(interval),
+            Domain::Insertion { .. } => None,
+        })
+        .flat_map(|interval| {
+            interval.iter().map(move |position| Nucl {
+                helix: interval.helix,
+                position,
+                forward: interval.forward,
+            })
+        })
+}
+
+impl PartialEq for ReferenceDesign {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.nucleotides, &other.nucleotides)
+            && Arc::ptr_eq(&self.bonds, &other.bonds)
+            && self.isometry == other.isometry
+            && self.visible == other.visible
+    }
+}
+
+/// The changes made to the design since it was last saved, highlighted in the views.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DesignChanges {
+    /// Identifiers of the strands that were created since the last save
+    pub added_strands: Vec<usize>,
+    /// Identifiers of the strands whose domains, sequence or color changed since the last save
+    pub modified_strands: Vec<usize>,
+    /// Identifiers of the helices whose position or orientation changed since the last save
+    pub moved_helices: Vec<usize>,
+    /// The strands of the saved design that were deleted, drawn as ghosts
+    pub removed_strands: Vec<RemovedStrand>,
+}
+
+/// A strand of the saved design that is not in the current design.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemovedStrand {
+    pub nucls: Vec<Nucl>,
+    /// The positions of the nucleotides in the saved design
+    pub positions: Vec<Vec3>,
+    pub cyclic: bool,
+}
+
+impl DesignChanges {
+    /// Compute the changes made to `saved_design` to obtain `current_design`.
+    ///
+    /// The helices of `saved_design` must have been instanciated so that the removed strands can
+    /// be positioned.
+    pub fn new(saved_design: &Design, current_design: &Design) -> Self {
+        let diff = design_diff::diff(saved_design, current_design);
+        let removed_strands = diff
+            .removed_strands
+            .iter()
+            .filter_map(|s_id| saved_design.strands.get(s_id))
+            .map(|strand| {
+                let nucls: Vec<Nucl> = helix_nucls(strand).collect();
+                let positions = nucls
+                    .iter()
+                    .filter_map(|nucl| saved_design.get_nucl_position(*nucl))
+                    .collect();
+                RemovedStrand {
+                    nucls,
+                    positions,
+                    cyclic: strand.cyclic,
+                }
+            })
+            .collect();
+        Self {
+            added_strands: diff.added_strands,
+            modified_strands: diff.modified_strands,
+            moved_helices: diff.moved_helices,
+            removed_strands,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strand_colors_span_the_colormap() {
+        let values: BTreeMap<usize, f32> = [(0, 20.), (3, 30.), (5, 40.)].into_iter().collect();
+        let colormap = Colormap::Viridis;
+        let colors = StrandColors::new(StrandColoring::Length, colormap, &values).unwrap();
+        assert_eq!(colors.min, 20.);
+        assert_eq!(colors.max, 40.);
+        assert_eq!(colors.strand_color(0), Some(colormap.color(0.)));
+        assert_eq!(colors.strand_color(3), Some(colormap.color(0.5)));
+        assert_eq!(colors.strand_color(5), Some(colormap.color(1.)));
+        assert_eq!(colors.strand_color(1), None);
+    }
+
+    #[test]
+    fn stored_coloring_has_no_strand_colors() {
+        let values: BTreeMap<usize, f32> = [(0, 20.)].into_iter().collect();
+        assert!(StrandColors::new(StrandColoring::Stored, Colormap::Rainbow, &values).is_none());
+        assert!(
+            StrandColors::new(StrandColoring::Length, Colormap::Rainbow, &BTreeMap::new())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn fog_parameters_survive_settings_round_trip() {
+        let fog = FogParameters {
+            fog_kind: fog_kind::DARK_FOG,
+            center: FogCenter::Selection,
+            alt_fog_center: Some(Vec3::unit_x()),
+            ..FogParameters::focus()
+        };
+        let restored = FogParameters::from_settings(&fog.to_settings());
+        assert_eq!(restored.radius, fog.radius);
+        assert_eq!(restored.length, fog.length);
+        assert_eq!(restored.fog_kind, fog_kind::DARK_FOG);
+        assert_eq!(restored.center, FogCenter::Selection);
+        assert_eq!(restored.alt_fog_center, Some(Vec3::unit_x()));
+    }
+
+    #[test]
+    fn disabled_fog_is_saved_as_default() {
+        assert!(FogParameters::new().to_settings().is_default());
+        assert!(!FogParameters::focus().off().to_settings().is_default());
+    }
+}
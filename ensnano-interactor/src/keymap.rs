@@ -0,0 +1,578 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Keyboard shortcuts.
+//!
+//! The controllers do not match raw key codes; they ask the [`Keymap`] which [`KeyAction`] is
+//! bound to the key chord that was pressed. A keymap is built from one of the built-in
+//! [presets](KeymapPreset), on top of which the user can override the bindings of some actions
+//! in a [`KeymapConfig`].
+//!
+//! The camera controls that are active as long as a key is held (arrows, fly mode), the nudging
+//! of the selected helices and grids (arrows, PageUp/PageDown, see [`crate::snapping`]), the
+//! entry of rotation angles (digits, Enter) and the favorite cameras shortcuts (digits) are not
+//! part of the keymap.
+
+use iced_winit::winit::event::{ModifiersState, VirtualKeyCode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// An action that can be triggered by a keyboard shortcut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    ToggleFlyMode,
+    NormalMode,
+    MakeXovers,
+    MakeDoubledXovers,
+    ToggleThickHelices,
+    Undo,
+    Redo,
+    Copy,
+    /// Copy the sequences of the selected strands to the system clipboard
+    CopySequences,
+    Paste,
+    Duplicate,
+    Anchor,
+    RotationMode,
+    TranslationMode,
+    NickMode,
+    LigateMode,
+    SelectNucleotides,
+    SelectHelices,
+    SelectStrands,
+    Save,
+    Open,
+    /// Show the list of recent designs
+    QuickOpen,
+    Quit,
+    NextCamera,
+    PreviousCamera,
+    RecolorStaples,
+    DeleteSelection,
+    ToggleSplit,
+    AlignWithStereo,
+    CheckXovers,
+    PivotCenter,
+    ToggleWidget,
+    ReverseSurfaceDirection,
+    ReflectSelection,
+    PlacePivot,
+    /// Apply the "focus" fog preset: a tight fog around the pivot
+    FocusFog,
+    /// Remove the fog of the 3D view
+    RemoveFog,
+    TiltLeft,
+    TiltRight,
+    SymmetryX,
+    SymmetryY,
+    MoveHelixBackward,
+    MoveHelixForward,
+}
+
+/// The part of the application in which a shortcut is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyScope {
+    /// The shortcut is active whatever the element that has the focus
+    Global,
+    /// The shortcut is active when the 3D view has the focus
+    Scene,
+    /// The shortcut is active when the 2D view has the focus
+    FlatScene,
+}
+
+impl KeyAction {
+    pub fn scope(self) -> KeyScope {
+        use KeyAction::*;
+        match self {
+            AlignWithStereo
+            | CheckXovers
+            | PivotCenter
+            | ToggleWidget
+            | ReverseSurfaceDirection
+            | ReflectSelection
+            | PlacePivot
+            | FocusFog
+            | RemoveFog => KeyScope::Scene,
+            TiltLeft | TiltRight | SymmetryX | SymmetryY | MoveHelixBackward | MoveHelixForward => {
+                KeyScope::FlatScene
+            }
+            _ => KeyScope::Global,
+        }
+    }
+
+    /// Two actions may not share a chord if one of them can be triggered whenever the other one
+    /// can.
+    fn overlaps(self, other: Self) -> bool {
+        let (s1, s2) = (self.scope(), other.scope());
+        s1 == s2 || s1 == KeyScope::Global || s2 == KeyScope::Global
+    }
+}
+
+/// A key pressed together with some modifiers.
+///
+/// `ctrl` stands for the command key on macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct KeyChord {
+    pub key: VirtualKeyCode,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl KeyChord {
+    pub const fn key(key: VirtualKeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    pub const fn ctrl(key: VirtualKeyCode) -> Self {
+        Self {
+            ctrl: true,
+            ..Self::key(key)
+        }
+    }
+
+    pub const fn alt(key: VirtualKeyCode) -> Self {
+        Self {
+            alt: true,
+            ..Self::key(key)
+        }
+    }
+
+    pub const fn with_shift(self) -> Self {
+        Self {
+            shift: true,
+            ..self
+        }
+    }
+
+    pub fn from_event(key: VirtualKeyCode, modifiers: &ModifiersState) -> Self {
+        let ctrl = if cfg!(target_os = "macos") {
+            modifiers.logo()
+        } else {
+            modifiers.ctrl()
+        };
+        Self {
+            key,
+            ctrl,
+            alt: modifiers.alt(),
+            shift: modifiers.shift(),
+        }
+    }
+}
+
+/// The keys that can be used in a chord, designated by their `Debug` representation.
+const SUPPORTED_KEYS: &[VirtualKeyCode] = {
+    use VirtualKeyCode::*;
+    &[
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Key0, Key1,
+        Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10,
+        F11, F12, Escape, Space, Return, Tab, Back, Delete, Insert, Home, End, PageUp, PageDown,
+        Left, Right, Up, Down, Plus, Minus, Equals, Comma, Period, Slash, Semicolon,
+    ]
+};
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{:?}", self.key)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyChordError(String);
+
+impl fmt::Display for ParseKeyChordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid key chord \"{}\"", self.0)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = ParseKeyChordError;
+
+    /// Parse chords of the form `"Ctrl+Shift+Z"`. The modifiers may be given in any order and are
+    /// case insensitive. `"Cmd"` is accepted as a synonym of `"Ctrl"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseKeyChordError(s.to_string());
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key_name = parts.pop().ok_or_else(err)?;
+        let key = SUPPORTED_KEYS
+            .iter()
+            .find(|k| format!("{k:?}").eq_ignore_ascii_case(key_name))
+            .cloned()
+            .ok_or_else(err)?;
+        let mut chord = Self::key(key);
+        for modifier in parts {
+            let flag = match modifier.to_lowercase().as_str() {
+                "ctrl" | "cmd" => &mut chord.ctrl,
+                "alt" => &mut chord.alt,
+                "shift" => &mut chord.shift,
+                _ => return Err(err()),
+            };
+            if *flag {
+                return Err(err());
+            }
+            *flag = true;
+        }
+        Ok(chord)
+    }
+}
+
+/// A built-in set of bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeymapPreset {
+    /// The historical ENSnano shortcuts
+    Ensnano,
+    /// Shortcuts closer to those of cadnano
+    Cadnano,
+}
+
+impl Default for KeymapPreset {
+    fn default() -> Self {
+        Self::Ensnano
+    }
+}
+
+impl KeymapPreset {
+    pub fn bindings(self) -> BTreeMap<KeyAction, Vec<KeyChord>> {
+        use KeyAction::*;
+        use VirtualKeyCode as K;
+        let mut ret: BTreeMap<KeyAction, Vec<KeyChord>> = [
+            (ToggleFlyMode, vec![KeyChord::key(K::F)]),
+            (NormalMode, vec![KeyChord::key(K::Escape)]),
+            (MakeXovers, vec![KeyChord::alt(K::X)]),
+            (MakeDoubledXovers, vec![KeyChord::alt(K::X).with_shift()]),
+            (ToggleThickHelices, vec![KeyChord::key(K::X)]),
+            (Undo, vec![KeyChord::ctrl(K::Z)]),
+            (
+                Redo,
+                vec![KeyChord::ctrl(K::R), KeyChord::ctrl(K::Z).with_shift()],
+            ),
+            (Copy, vec![KeyChord::ctrl(K::C)]),
+            (CopySequences, vec![KeyChord::ctrl(K::C).with_shift()]),
+            (Paste, vec![KeyChord::ctrl(K::V)]),
+            (Duplicate, vec![KeyChord::ctrl(K::J)]),
+            (Anchor, vec![KeyChord::ctrl(K::L)]),
+            (RotationMode, vec![KeyChord::key(K::R)]),
+            (TranslationMode, vec![KeyChord::key(K::T)]),
+            (NickMode, vec![KeyChord::alt(K::N)]),
+            (LigateMode, vec![KeyChord::alt(K::L)]),
+            (SelectNucleotides, vec![KeyChord::key(K::N)]),
+            (SelectHelices, vec![KeyChord::key(K::H)]),
+            (SelectStrands, vec![KeyChord::key(K::S)]),
+            (Save, vec![KeyChord::ctrl(K::S)]),
+            (Open, vec![KeyChord::ctrl(K::O)]),
+            (QuickOpen, vec![KeyChord::ctrl(K::P)]),
+            (
+                Quit,
+                if cfg!(target_os = "macos") {
+                    vec![KeyChord::ctrl(K::Q)]
+                } else {
+                    vec![]
+                },
+            ),
+            (NextCamera, vec![KeyChord::key(K::PageDown)]),
+            (PreviousCamera, vec![KeyChord::key(K::PageUp)]),
+            (RecolorStaples, vec![KeyChord::key(K::K)]),
+            (
+                DeleteSelection,
+                vec![KeyChord::key(K::Delete), KeyChord::key(K::Back)],
+            ),
+            (ToggleSplit, vec![]),
+            (AlignWithStereo, vec![KeyChord::key(K::A)]),
+            (CheckXovers, vec![KeyChord::key(K::C)]),
+            (PivotCenter, vec![KeyChord::key(K::Q)]),
+            (ToggleWidget, vec![KeyChord::key(K::Space)]),
+            (ReverseSurfaceDirection, vec![KeyChord::key(K::W)]),
+            (ReflectSelection, vec![KeyChord::key(K::M)]),
+            (PlacePivot, vec![KeyChord::key(K::P)]),
+            (FocusFog, vec![KeyChord::alt(K::F)]),
+            (RemoveFog, vec![KeyChord::alt(K::F).with_shift()]),
+            (TiltLeft, vec![KeyChord::alt(K::Left)]),
+            (TiltRight, vec![KeyChord::alt(K::Right)]),
+            (
+                SymmetryX,
+                vec![KeyChord::ctrl(K::Left), KeyChord::ctrl(K::Right)],
+            ),
+            (
+                SymmetryY,
+                vec![KeyChord::ctrl(K::Up), KeyChord::ctrl(K::Down)],
+            ),
+            (MoveHelixBackward, vec![KeyChord::key(K::J)]),
+            // K recolors the staples
+            (MoveHelixForward, vec![]),
+        ]
+        .into_iter()
+        .collect();
+        if self == Self::Cadnano {
+            ret.insert(
+                Redo,
+                vec![KeyChord::ctrl(K::Z).with_shift(), KeyChord::ctrl(K::Y)],
+            );
+            ret.insert(SelectStrands, vec![KeyChord::key(K::V)]);
+            ret.insert(MakeXovers, vec![KeyChord::ctrl(K::X).with_shift()]);
+            ret.insert(ToggleSplit, vec![KeyChord::key(K::Tab)]);
+        }
+        ret
+    }
+}
+
+/// The keymap settings stored in the user configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeymapConfig {
+    pub preset: KeymapPreset,
+    /// Bindings that replace those of the preset. An action mapped to an empty list is unbound.
+    pub bindings: BTreeMap<KeyAction, Vec<String>>,
+}
+
+/// A problem found while building a keymap. The faulty bindings are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapWarning {
+    InvalidChord {
+        action: KeyAction,
+        chord: String,
+    },
+    Conflict {
+        chord: KeyChord,
+        actions: Vec<KeyAction>,
+    },
+}
+
+impl fmt::Display for KeymapWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChord { action, chord } => {
+                write!(f, "Invalid key chord \"{chord}\" for {action:?}")
+            }
+            Self::Conflict { chord, actions } => {
+                let actions: Vec<String> = actions.iter().map(|a| format!("{a:?}")).collect();
+                write!(f, "{chord} is bound to {}", actions.join(", "))
+            }
+        }
+    }
+}
+
+/// A map from key chords to actions.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    actions: HashMap<KeyChord, Vec<KeyAction>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_bindings(KeymapPreset::default().bindings()).0
+    }
+}
+
+impl Keymap {
+    /// Build the keymap described by `config`, together with the problems found in the
+    /// configuration.
+    pub fn from_config(config: &KeymapConfig) -> (Self, Vec<KeymapWarning>) {
+        let mut bindings = config.preset.bindings();
+        let mut warnings = Vec::new();
+        for (action, chords) in config.bindings.iter() {
+            let mut parsed = Vec::with_capacity(chords.len());
+            for chord in chords.iter() {
+                match chord.parse() {
+                    Ok(c) => parsed.push(c),
+                    Err(_) => warnings.push(KeymapWarning::InvalidChord {
+                        action: *action,
+                        chord: chord.clone(),
+                    }),
+                }
+            }
+            bindings.insert(*action, parsed);
+        }
+        let (keymap, conflicts) = Self::from_bindings(bindings);
+        warnings.extend(conflicts);
+        (keymap, warnings)
+    }
+
+    fn from_bindings(bindings: BTreeMap<KeyAction, Vec<KeyChord>>) -> (Self, Vec<KeymapWarning>) {
+        let mut actions: HashMap<KeyChord, Vec<KeyAction>> = HashMap::new();
+        for (action, chords) in bindings {
+            for chord in chords {
+                actions.entry(chord).or_default().push(action);
+            }
+        }
+        let mut conflicts: Vec<KeymapWarning> = actions
+            .iter()
+            .filter_map(|(chord, actions)| {
+                let clashing: Vec<KeyAction> = actions
+                    .iter()
+                    .filter(|a| actions.iter().any(|b| b != *a && a.overlaps(*b)))
+                    .cloned()
+                    .collect();
+                (!clashing.is_empty()).then(|| KeymapWarning::Conflict {
+                    chord: *chord,
+                    actions: clashing,
+                })
+            })
+            .collect();
+        conflicts.sort_by_key(|w| match w {
+            KeymapWarning::Conflict { chord, .. } => Some(*chord),
+            KeymapWarning::InvalidChord { .. } => None,
+        });
+        // Among conflicting actions, the first one in the order of `KeyAction` wins
+        for actions in actions.values_mut() {
+            let mut kept: Vec<KeyAction> = Vec::with_capacity(actions.len());
+            for a in actions.iter() {
+                if !kept.iter().any(|b| a.overlaps(*b)) {
+                    kept.push(*a);
+                }
+            }
+            *actions = kept;
+        }
+        (Self { actions }, conflicts)
+    }
+
+    /// The action bound to `key` pressed with `modifiers` in `scope`. The actions of
+    /// `KeyScope::Global` are available in every scope.
+    pub fn action(
+        &self,
+        key: VirtualKeyCode,
+        modifiers: &ModifiersState,
+        scope: KeyScope,
+    ) -> Option<KeyAction> {
+        let actions = self.actions.get(&KeyChord::from_event(key, modifiers))?;
+        actions
+            .iter()
+            .find(|a| a.scope() == scope)
+            .or_else(|| actions.iter().find(|a| a.scope() == KeyScope::Global))
+            .cloned()
+    }
+
+    /// The chords bound to `action`.
+    pub fn chords(&self, action: KeyAction) -> Vec<KeyChord> {
+        let mut ret: Vec<KeyChord> = self
+            .actions
+            .iter()
+            .filter(|(_, actions)| actions.contains(&action))
+            .map(|(chord, _)| *chord)
+            .collect();
+        ret.sort();
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_chords_round_trip() {
+        for s in ["Ctrl+Shift+Z", "Alt+Left", "Delete", "Ctrl+Key1"] {
+            let chord: KeyChord = s.parse().unwrap();
+            assert_eq!(chord.to_string(), s);
+        }
+        assert_eq!(
+            "shift+cmd+z".parse::<KeyChord>(),
+            Ok(KeyChord::ctrl(VirtualKeyCode::Z).with_shift())
+        );
+        assert!("Ctrl+Ctrl+Z".parse::<KeyChord>().is_err());
+        assert!("Hyper+Z".parse::<KeyChord>().is_err());
+        assert!("Ctrl+".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn presets_have_no_conflicts() {
+        for preset in [KeymapPreset::Ensnano, KeymapPreset::Cadnano] {
+            let config = KeymapConfig {
+                preset,
+                ..Default::default()
+            };
+            let (_, warnings) = Keymap::from_config(&config);
+            assert_eq!(warnings, vec![], "{preset:?}");
+        }
+    }
+
+    #[test]
+    fn conflicts_are_reported() {
+        let mut config = KeymapConfig::default();
+        config
+            .bindings
+            .insert(KeyAction::Copy, vec!["Ctrl+Z".into(), "Nope".into()]);
+        let (keymap, warnings) = Keymap::from_config(&config);
+        assert_eq!(
+            warnings,
+            vec![
+                KeymapWarning::InvalidChord {
+                    action: KeyAction::Copy,
+                    chord: "Nope".into()
+                },
+                KeymapWarning::Conflict {
+                    chord: KeyChord::ctrl(VirtualKeyCode::Z),
+                    actions: vec![KeyAction::Undo, KeyAction::Copy],
+                },
+            ]
+        );
+        let modifiers = if cfg!(target_os = "macos") {
+            ModifiersState::LOGO
+        } else {
+            ModifiersState::CTRL
+        };
+        assert_eq!(
+            keymap.action(VirtualKeyCode::Z, &modifiers, KeyScope::Global),
+            Some(KeyAction::Undo)
+        );
+    }
+
+    #[test]
+    fn scoped_actions_may_share_chords() {
+        let mut config = KeymapConfig::default();
+        config
+            .bindings
+            .insert(KeyAction::MoveHelixForward, vec!["A".into()]);
+        let (keymap, warnings) = Keymap::from_config(&config);
+        assert_eq!(warnings, vec![]);
+        let none = ModifiersState::empty();
+        assert_eq!(
+            keymap.action(VirtualKeyCode::A, &none, KeyScope::Scene),
+            Some(KeyAction::AlignWithStereo)
+        );
+        assert_eq!(
+            keymap.action(VirtualKeyCode::A, &none, KeyScope::FlatScene),
+            Some(KeyAction::MoveHelixForward)
+        );
+        assert_eq!(
+            keymap.action(VirtualKeyCode::A, &none, KeyScope::Global),
+            None
+        );
+        assert_eq!(
+            keymap.action(VirtualKeyCode::K, &none, KeyScope::FlatScene),
+            Some(KeyAction::RecolorStaples)
+        );
+    }
+}
@@ -22,11 +22,17 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use std::path::PathBuf;
 
 use ensnano_design::{
+    design_operations::{
+        DuplicationPattern, RecolorParameters, ScaffoldRoute, ScaffoldRoutingParameters,
+        StapleFillingParameters,
+    },
     elements::{DnaAttribute, DnaElementKey},
     grid::{GridDescriptor, GridId, GridObject, GridTypeDescr, HelixGridPosition, Hyperboloid},
     group_attributes::GroupPivot,
+    modifications::Modification,
     BezierPathId, BezierPlaneDescriptor, BezierPlaneId, BezierVertex, BezierVertexId,
-    CurveDescriptor2D, Isometry3, Nucl, Parameters,
+    CurveDescriptor2D, External3DObjectAnchor, External3DObjectId, Isometry3, Nucl, Parameters,
+    SvgImportParameters,
 };
 use serde::{Deserialize, Serialize};
 use ultraviolet::{Isometry2, Rotor3, Vec2, Vec3};
@@ -39,9 +45,14 @@ pub mod operation;
 mod strand_builder;
 pub use strand_builder::*;
 pub mod consts;
+pub mod design_summary;
+pub mod keymap;
+pub mod snapping;
+pub mod toasts;
 pub mod torsion;
 use ensnano_organizer::GroupId;
 mod operation_labels;
+mod operation_targets;
 mod surfaces;
 pub use surfaces::*;
 
@@ -93,6 +104,13 @@ pub enum DesignOperation {
         start: isize,
         length: usize,
     },
+    /// Add a forward and a backward strand covering the positions `start..start + length` of an
+    /// existing helix
+    MakeDuplex {
+        helix: usize,
+        start: isize,
+        length: usize,
+    },
     AddTwoPointsBezier {
         start: HelixGridPosition,
         end: HelixGridPosition,
@@ -135,28 +153,48 @@ pub enum DesignOperation {
     AddGrid(GridDescriptor),
     /// Remove a grid
     RmGrid(usize),
-    /// Pick a new color at random for all the strands that are not the scaffold
-    RecolorStaples,
-    /// Set the sequence of a set of strands
+    /// Change the color of a set of strands. The scaffold and the strands whose color must be kept
+    /// are not recolored.
+    RecolorStaples(RecolorParameters),
+    /// Set the sequence of a set of strands.
+    ///
+    /// Unless `force` is true, the operation fails if the sequence of a staple does not match the
+    /// complement of the scaffold at the positions where the staple is paired to the scaffold.
     ChangeSequence {
         sequence: String,
         strands: Vec<usize>,
+        force: bool,
+    },
+    /// Remove the sequence of a set of strands, so that their basis are derived from the
+    /// scaffold's sequence
+    ClearSequence {
+        strands: Vec<usize>,
     },
     /// Change the color of a set of strands
     ChangeColor {
         color: u32,
         strands: Vec<usize>,
     },
-    /// Set the strand with a given id as the scaffold
-    SetScaffoldId(Option<usize>),
-    /// Change the shift of the scaffold without changing the sequence
-    SetScaffoldShift(usize),
-    /// Change the sequence and the shift of the scaffold
+    /// Set the strands with the given ids as the scaffolds
+    SetScaffoldIds(Vec<usize>),
+    /// Change the shift of a scaffold without changing its sequence
+    SetScaffoldShift {
+        scaffold_id: usize,
+        shift: usize,
+    },
+    /// Change the sequence and the shift of a scaffold
     SetScaffoldSequence {
+        scaffold_id: usize,
         sequence: String,
         shift: usize,
     },
     HyperboloidOperation(HyperboloidOperation),
+    /// Change the parameters of an existing hyperboloid grid. The helices attached to the grid
+    /// are moved accordingly and their strands are preserved.
+    UpdateHyperboloid {
+        grid_id: GridId,
+        request: HyperboloidRequest,
+    },
     CleanDesign,
     HelicesToGrid(Vec<Selection>),
     SetHelicesPersistance {
@@ -171,6 +209,16 @@ pub enum DesignOperation {
         grid_ids: Vec<GridId>,
         small: bool,
     },
+    /// Show or hide grids. The helices of a hidden grid are still drawn, but the grid and the
+    /// phantom helices on it are not.
+    SetGridVisibility {
+        grid_ids: Vec<GridId>,
+        visible: bool,
+    },
+    /// Show the grids `grid_ids` and hide all the other grids.
+    SoloGrids {
+        grid_ids: Vec<GridId>,
+    },
     /// Apply a translation to the 2d representation of helices holding each pivot
     SnapHelices {
         pivots: Vec<(Nucl, usize)>,
@@ -203,12 +251,32 @@ pub enum DesignOperation {
         helix: usize,
         visible: bool,
     },
+    /// Show or hide strands in the 3D and 2D views
+    SetVisibilityStrands {
+        strand_ids: Vec<usize>,
+        visible: bool,
+    },
     FlipHelixGroup {
         helix: usize,
     },
     FlipAnchors {
         nucls: Vec<Nucl>,
     },
+    /// Mark the nucleotides as deleted, or unmark them if they are all already deleted. The marks
+    /// are annotations only and do not remove the nucleotides from their strands.
+    FlipDeletions {
+        nucls: Vec<Nucl>,
+    },
+    /// Attach a chemical modification to a nucleotide, replacing its previous modification if
+    /// it had one
+    AddModification {
+        nucl: Nucl,
+        modification: Modification,
+    },
+    /// Remove the chemical modifications carried by the nucleotides
+    RmModifications {
+        nucls: Vec<Nucl>,
+    },
     AttachObject {
         object: GridObject,
         grid: GridId,
@@ -220,9 +288,31 @@ pub enum DesignOperation {
         s_id: usize,
         name: String,
     },
+    /// Set the note of a strand, or remove it if `note` is `None`
+    SetStrandNote {
+        s_id: usize,
+        note: Option<String>,
+    },
+    /// Set whether the color of a strand must be preserved when staples are recolored
+    SetStrandKeepColor {
+        s_id: usize,
+        keep_color: bool,
+    },
     SetGroupPivot {
         group_id: GroupId,
         pivot: GroupPivot,
+        /// If false, the pivot is only set if the group does not have one yet
+        replace: bool,
+    },
+    SetGroupColor {
+        group_id: GroupId,
+        color: Option<u32>,
+    },
+    /// Lock or unlock the members of a group. The operations that would modify the members of a
+    /// locked group are rejected.
+    SetGroupLocked {
+        group_id: GroupId,
+        locked: bool,
     },
     DeleteCamera(ensnano_design::CameraId),
     CreateNewCamera {
@@ -240,6 +330,16 @@ pub enum DesignOperation {
         camera_id: ensnano_design::CameraId,
         name: String,
     },
+    /// Save `elements` under the name `name`
+    CreateNamedSelection {
+        name: String,
+        elements: Vec<DnaElementKey>,
+    },
+    RenameNamedSelection {
+        idx: usize,
+        name: String,
+    },
+    DeleteNamedSelection(usize),
     SetGridPosition {
         grid_id: GridId,
         position: Vec3,
@@ -252,6 +352,26 @@ pub enum DesignOperation {
         grid_id: GridId,
         nb_turn: f32,
     },
+    /// Change the lattice of a grid, moving the helices attached to it to the closest position
+    /// of the new lattice
+    SetGridType {
+        grid_id: GridId,
+        grid_type: GridTypeDescr,
+    },
+    /// Set the part of a grid that is drawn in the 3D view
+    SetGridBounds {
+        grid_id: GridId,
+        min_x: isize,
+        max_x: isize,
+        min_y: isize,
+        max_y: isize,
+    },
+    /// Set the number of phantom nucleotides drawn on each side of the origin of the helices of
+    /// a grid. If `length` is `None`, the default length is used.
+    SetGridPhantomLength {
+        grid_id: GridId,
+        length: Option<usize>,
+    },
     MakeSeveralXovers {
         xovers: Vec<(Nucl, Nucl)>,
         doubled: bool,
@@ -277,6 +397,13 @@ pub enum DesignOperation {
         path_id: BezierPathId,
         vertex: BezierVertex,
     },
+    /// Split the segment of a path that starts at vertex `after_vertex` by inserting a vertex at
+    /// `position`
+    InsertVertexInPath {
+        path_id: BezierPathId,
+        after_vertex: usize,
+        position: Vec2,
+    },
     /// Move the first vertex to `position` and apply the same translation to the other vertices
     MoveBezierVertex {
         vertices: Vec<BezierVertexId>,
@@ -293,6 +420,22 @@ pub enum DesignOperation {
     ApplyHomothethyOnBezierPlane {
         homothethy: BezierPlaneHomothethy,
     },
+    RenameBezierPlane {
+        plane_id: BezierPlaneId,
+        name: String,
+    },
+    /// Remove a bezier plane. The operation fails if some vertices of bezier paths are positioned
+    /// on the plane.
+    DeleteBezierPlane {
+        plane_id: BezierPlaneId,
+    },
+    /// Create a copy of a bezier plane translated by `offset`. If `copy_paths` is true, the paths
+    /// lying entirely on the plane are copied on the new plane.
+    DuplicateBezierPlane {
+        plane_id: BezierPlaneId,
+        offset: Vec3,
+        copy_paths: bool,
+    },
     SetVectorOfBezierTengent(NewBezierTengentVector),
     MakeBezierPathCyclic {
         path_id: BezierPathId,
@@ -308,8 +451,64 @@ pub enum DesignOperation {
         file_path: PathBuf,
         design_path: PathBuf,
     },
+    /// Place an external 3D object in the scene
+    SetExternal3DObjectTransform {
+        object_id: External3DObjectId,
+        position: Vec3,
+        orientation: Rotor3,
+        scale: f32,
+    },
+    SetExternal3DObjectVisibility {
+        object_id: External3DObjectId,
+        visible: bool,
+    },
+    /// Attach an external 3D object to an element of the design so that it follows that element
+    /// when it is moved. If `anchor` is `None`, the object is detached and keeps its current
+    /// position.
+    AttachExternal3DObject {
+        object_id: External3DObjectId,
+        anchor: Option<External3DObjectAnchor>,
+    },
+    /// Create a bezier path for each subpath of an svg file
     ImportSvgPath {
         path: PathBuf,
+        parameters: SvgImportParameters,
+    },
+    /// Show an oxDNA configuration obtained from an export of the design
+    ImportOxDnaConfiguration {
+        path: PathBuf,
+    },
+    /// Reflect helices and grids across a plane. The strands lying on the reflected helices are
+    /// reversed.
+    ReflectSelection {
+        helices: Vec<usize>,
+        grids: Vec<GridId>,
+        plane_origin: Vec3,
+        plane_normal: Vec3,
+    },
+    /// Create copies of helices and of the strands lying on them, arranged in a periodic pattern
+    DuplicateInPattern {
+        helices: Vec<usize>,
+        strands: Vec<usize>,
+        pattern: DuplicationPattern,
+    },
+    /// Rotate helices so that their axis becomes parallel to the one of the reference helix
+    AlignHelices {
+        helices: Vec<usize>,
+        reference: usize,
+    },
+    /// Create a scaffold strand going through a sequence of helices
+    RouteScaffold {
+        route: ScaffoldRoute,
+        parameters: ScaffoldRoutingParameters,
+    },
+    /// Create staples on the parts of the scaffold that are not paired
+    FillStaples(StapleFillingParameters),
+    /// Set the parameters that define the geometry of helices. If `parameters` is `None`, the
+    /// helices use the parameters of the design.
+    SetHelixParameters {
+        helices: Vec<usize>,
+        parameters: Option<Parameters>,
     },
 }
 
@@ -648,6 +847,15 @@ impl CheckXoversParameter {
     }
 }
 
+/// The strain of a cross-over, as computed by [ensnano_design::design_check::xover_strain].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XoverStrain {
+    pub xover_id: usize,
+    pub xover: (Nucl, Nucl),
+    pub strain: f32,
+    pub checked: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct BezierPlaneHomothethy {
     pub plane_id: BezierPlaneId,
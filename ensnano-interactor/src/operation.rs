@@ -19,7 +19,11 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use crate::BezierControlPoint;
 
 use super::{DesignOperation, DesignRotation, DesignTranslation, GroupId, IsometryTarget};
-use ensnano_design::{grid::*, BezierPlaneId, BezierVertexId, Nucl};
+use ensnano_design::{
+    grid::*, BezierPlaneDescriptor, BezierPlaneId, BezierVertexId, External3DObjectId, Nucl,
+    SvgImportParameters, SvgImportPlane,
+};
+use std::path::PathBuf;
 use ultraviolet::{Bivec3, Rotor3, Vec2, Vec3};
 
 pub enum ParameterField {
@@ -410,6 +414,111 @@ impl Operation for TranslateBezierSheetCorner {
     }
 }
 
+/// The import of the paths of an svg file. The scale, the position and the plane of the paths
+/// can be modified until the operation is finished.
+#[derive(Debug, Clone)]
+pub struct SvgPathImport {
+    pub file_path: PathBuf,
+    pub parameters: SvgImportParameters,
+    /// The existing planes on which the paths can be imported
+    pub available_planes: Vec<BezierPlaneId>,
+    /// The plane that is created if the paths are not imported on an existing plane
+    pub new_plane: BezierPlaneDescriptor,
+    pub replace: bool,
+}
+
+const NEW_PLANE_CHOICE: &str = "New plane";
+
+fn plane_choice(plane_id: BezierPlaneId) -> String {
+    format!("Plane {}", plane_id.0)
+}
+
+impl Operation for SvgPathImport {
+    fn parameters(&self) -> Vec<Parameter> {
+        let mut planes = vec![String::from(NEW_PLANE_CHOICE)];
+        planes.extend(self.available_planes.iter().cloned().map(plane_choice));
+        vec![
+            Parameter {
+                field: ParameterField::Value,
+                name: String::from("scale"),
+            },
+            Parameter {
+                field: ParameterField::Value,
+                name: String::from("x"),
+            },
+            Parameter {
+                field: ParameterField::Value,
+                name: String::from("y"),
+            },
+            Parameter {
+                field: ParameterField::Choice(planes),
+                name: String::from("plane"),
+            },
+        ]
+    }
+
+    fn values(&self) -> Vec<String> {
+        let plane = match self.parameters.plane {
+            SvgImportPlane::Existing(plane_id) => plane_choice(plane_id),
+            SvgImportPlane::New(_) => String::from(NEW_PLANE_CHOICE),
+        };
+        vec![
+            self.parameters.scale.to_string(),
+            self.parameters.position.x.to_string(),
+            self.parameters.position.y.to_string(),
+            plane,
+        ]
+    }
+
+    fn effect(&self) -> DesignOperation {
+        DesignOperation::ImportSvgPath {
+            path: self.file_path.clone(),
+            parameters: self.parameters.clone(),
+        }
+    }
+
+    fn description(&self) -> String {
+        let file_name = self
+            .file_path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        format!("Import {file_name}")
+    }
+
+    fn with_new_value(&self, n: usize, val: String) -> Option<Arc<dyn Operation>> {
+        let mut ret = self.clone();
+        match n {
+            0 => {
+                let scale: f32 = val.parse().ok()?;
+                if scale <= 0. {
+                    return None;
+                }
+                ret.parameters.scale = scale
+            }
+            1 => ret.parameters.position.x = val.parse().ok()?,
+            2 => ret.parameters.position.y = val.parse().ok()?,
+            3 if val == NEW_PLANE_CHOICE => {
+                ret.parameters.plane = SvgImportPlane::New(self.new_plane.clone())
+            }
+            3 => {
+                let plane_id = self
+                    .available_planes
+                    .iter()
+                    .find(|plane_id| plane_choice(**plane_id) == val)?;
+                ret.parameters.plane = SvgImportPlane::Existing(*plane_id)
+            }
+            _ => return None,
+        }
+        ret.replace = true;
+        Some(Arc::new(ret))
+    }
+
+    fn replace_previous(&self) -> bool {
+        self.replace
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HelixTranslation {
     pub design_id: usize,
@@ -585,6 +694,62 @@ impl Operation for GridTranslation {
     }
 }
 
+/// A modification of the position, orientation or size of an external 3D object.
+#[derive(Clone, Debug)]
+pub struct External3DObjectTransformation {
+    pub object_id: External3DObjectId,
+    pub position: Vec3,
+    pub orientation: Rotor3,
+    pub scale: f32,
+}
+
+impl Operation for External3DObjectTransformation {
+    fn parameters(&self) -> Vec<Parameter> {
+        ["x", "y", "z", "scale"]
+            .iter()
+            .map(|name| Parameter {
+                field: ParameterField::Value,
+                name: String::from(*name),
+            })
+            .collect()
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.position.x.to_string(),
+            self.position.y.to_string(),
+            self.position.z.to_string(),
+            self.scale.to_string(),
+        ]
+    }
+
+    fn effect(&self) -> DesignOperation {
+        DesignOperation::SetExternal3DObjectTransform {
+            object_id: self.object_id,
+            position: self.position,
+            orientation: self.orientation,
+            scale: self.scale,
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Move 3D object {}", self.object_id.0)
+    }
+
+    fn with_new_value(&self, n: usize, val: String) -> Option<Arc<dyn Operation>> {
+        let val: f32 = val.parse().ok()?;
+        let mut ret = self.clone();
+        match n {
+            0 => ret.position.x = val,
+            1 => ret.position.y = val,
+            2 => ret.position.z = val,
+            3 if val > 0. => ret.scale = val,
+            _ => return None,
+        }
+        Some(Arc::new(ret))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GridHelixCreation {
     pub design_id: usize,
@@ -671,6 +836,35 @@ impl Operation for Cut {
     }
 }
 
+/// Set the position of the scaffold sequence on the scaffold strand.
+///
+/// Successive updates replace each other, so that the shift can be adjusted interactively.
+#[derive(Clone, Debug)]
+pub struct ScaffoldShiftUpdate {
+    pub scaffold_id: usize,
+    pub shift: usize,
+}
+
+impl Operation for ScaffoldShiftUpdate {
+    fn effect(&self) -> DesignOperation {
+        DesignOperation::SetScaffoldShift {
+            scaffold_id: self.scaffold_id,
+            shift: self.shift,
+        }
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Set shift of scaffold {} to {}",
+            self.scaffold_id, self.shift
+        )
+    }
+
+    fn replace_previous(&self) -> bool {
+        true
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Xover {
     pub prime5_id: usize,
@@ -795,6 +989,8 @@ impl Operation for CreateGrid {
             grid_type: self.grid_type,
             invisible: false,
             bezier_vertex: None,
+            bounds: None,
+            phantom_length: None,
         })
     }
 
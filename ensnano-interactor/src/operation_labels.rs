@@ -28,6 +28,7 @@ impl DesignOperation {
                 format!("Translation of {}", translation.target.to_string()).into()
             }
             Self::AddGridHelix { .. } => "Helix creation".into(),
+            Self::MakeDuplex { .. } => "Duplex creation".into(),
             Self::AddTwoPointsBezier { .. } => "Bezier curve creation".into(),
             Self::RmHelices { .. } => "Helix deletion".into(),
             Self::RmXovers { .. } => "Xover deletion".into(),
@@ -40,12 +41,14 @@ impl DesignOperation {
             Self::RmStrands { .. } => "Strand deletion".into(),
             Self::AddGrid(_) => "Grid creation".into(),
             Self::RmGrid(_) => "Grid delection".into(),
-            Self::RecolorStaples => "Staple recoloring".into(),
+            Self::RecolorStaples(_) => "Staple recoloring".into(),
             Self::ChangeSequence { .. } => "Sequence update".into(),
+            Self::ClearSequence { .. } => "Sequence removal".into(),
             Self::ChangeColor { .. } => "Color modification".into(),
-            Self::SetScaffoldId(_) => "Scaffold setting".into(),
+            Self::SetScaffoldIds(_) => "Scaffold setting".into(),
             Self::SetScaffoldSequence { .. } => "Scaffold sequence setting".into(),
             Self::HyperboloidOperation(_) => "Nanotube operation".into(),
+            Self::UpdateHyperboloid { .. } => "Nanotube edition".into(),
             Self::CleanDesign => "Clean design".into(),
             Self::HelicesToGrid(_) => "Grid creation from helices".into(),
             Self::SetHelicesPersistance {
@@ -57,6 +60,9 @@ impl DesignOperation {
             Self::UpdateAttribute { .. } => "Update attribute from organizer".into(),
             Self::SetSmallSpheres { small: true, .. } => "Hide nucleotides".into(),
             Self::SetSmallSpheres { small: false, .. } => "Show nucleotides".into(),
+            Self::SetGridVisibility { visible: true, .. } => "Show grids".into(),
+            Self::SetGridVisibility { visible: false, .. } => "Hide grids".into(),
+            Self::SoloGrids { .. } => "Solo grids".into(),
             Self::SnapHelices { .. } => "Move 2D helices".into(),
             Self::RotateHelices { .. } => "Translate 2D helices".into(),
             Self::SetIsometry { .. } => "Set isometry of helices".into(),
@@ -65,17 +71,48 @@ impl DesignOperation {
             Self::SetRollHelices { .. } => "Set roll of helix".into(),
             Self::SetVisibilityHelix { visible: true, .. } => "Make helices visible".into(),
             Self::SetVisibilityHelix { visible: false, .. } => "Make helices invisible".into(),
+            Self::SetVisibilityStrands { visible: true, .. } => "Make strands visible".into(),
+            Self::SetVisibilityStrands { visible: false, .. } => "Make strands invisible".into(),
             Self::FlipHelixGroup { .. } => "Change xover group of helices".into(),
             Self::FlipAnchors { .. } => "Set/Unset nucl anchor".into(),
+            Self::FlipDeletions { .. } => "Set/Unset nucl deletion mark".into(),
+            Self::AddModification { .. } => "Add modification".into(),
+            Self::RmModifications { .. } => "Remove modifications".into(),
             Self::AttachObject { .. } => "Move grid object".into(),
             Self::SetOrganizerTree(_) => "Update organizer tree".into(),
             Self::SetStrandName { .. } => "Update name of strand".into(),
+            Self::SetStrandNote { .. } => "Update note of strand".into(),
+            Self::SetStrandKeepColor { .. } => "Update color preservation of strand".into(),
             Self::SetGroupPivot { .. } => "Set group pivot".into(),
+            Self::SetGroupColor { .. } => "Set group color".into(),
+            Self::SetGroupLocked { locked: true, .. } => "Lock group".into(),
+            Self::SetGroupLocked { locked: false, .. } => "Unlock group".into(),
             Self::DeleteCamera(_) => "Delete camera".into(),
             Self::CreateNewCamera { .. } => "Create camera shortcut".into(),
+            Self::CreateNamedSelection { .. } => "Save selection".into(),
+            Self::RenameNamedSelection { .. } => "Rename saved selection".into(),
+            Self::DeleteNamedSelection(_) => "Delete saved selection".into(),
             Self::SetGridPosition { .. } => "Set grid position".into(),
             Self::SetGridOrientation { .. } => "Set grid orientation".into(),
+            Self::SetGridType { .. } => "Change grid type".into(),
+            Self::SetGridBounds { .. } => "Resize grid".into(),
+            Self::SetGridPhantomLength { .. } => "Set phantom length".into(),
+            Self::InsertVertexInPath { .. } => "Insert vertex in path".into(),
             Self::MakeSeveralXovers { .. } => "Multiple xovers".into(),
+            Self::RouteScaffold { .. } => "Scaffold routing".into(),
+            Self::FillStaples(_) => "Staple filling".into(),
+            Self::SetHelixParameters { .. } => "Set helix parameters".into(),
+            Self::SetExternal3DObjectTransform { .. } => "Move 3D object".into(),
+            Self::SetExternal3DObjectVisibility { visible: true, .. } => "Show 3D object".into(),
+            Self::SetExternal3DObjectVisibility { visible: false, .. } => "Hide 3D object".into(),
+            Self::AttachExternal3DObject {
+                anchor: Some(_), ..
+            } => "Attach 3D object".into(),
+            Self::AttachExternal3DObject { anchor: None, .. } => "Detach 3D object".into(),
+            Self::ImportSvgPath { .. } => "Import svg paths".into(),
+            Self::RenameBezierPlane { .. } => "Rename bezier plane".into(),
+            Self::DeleteBezierPlane { .. } => "Delete bezier plane".into(),
+            Self::DuplicateBezierPlane { .. } => "Duplicate bezier plane".into(),
             _ => "Unamed operation".into(),
         }
     }
@@ -0,0 +1,296 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The elements of the design that are modified by an operation.
+
+use super::*;
+use ensnano_design::design_operations::RecolorScope;
+
+impl DesignOperation {
+    /// Return the keys of the elements that are directly modified by the operation.
+    ///
+    /// Operations on nucleotides return the keys of these nucleotides and of their helices, it is
+    /// up to the caller to resolve the strands containing them. Operations that do not modify the
+    /// members of the groups of the organizer, for example the operations on cameras, on the
+    /// organizer itself, or the operations that only create new elements, return an empty
+    /// vector.
+    ///
+    /// Some operations modify elements that can only be found by looking at the design, for
+    /// example the helices attached to the grids of a bezier path. These elements are not
+    /// returned and must be resolved by the caller.
+    pub fn affected_elements(&self) -> Vec<DnaElementKey> {
+        match self {
+            Self::Rotation(DesignRotation { target, .. })
+            | Self::Translation(DesignTranslation { target, .. }) => isometry_target_keys(target),
+            Self::MakeDuplex { helix, .. }
+            | Self::SetIsometry { helix, .. }
+            | Self::FlipHelixGroup { helix } => vec![DnaElementKey::Helix(*helix)],
+            Self::RmHelices { h_ids: helices }
+            | Self::SetRollHelices { helices, .. }
+            | Self::RotateHelices { helices, .. }
+            | Self::ApplySymmetryToHelices { helices, .. }
+            | Self::AlignHelices { helices, .. }
+            | Self::SetHelixParameters { helices, .. } => {
+                helices.iter().cloned().map(DnaElementKey::Helix).collect()
+            }
+            Self::RmXovers { xovers } | Self::MakeSeveralXovers { xovers, .. } => xovers
+                .iter()
+                .flat_map(|(n1, n2)| nucl_keys(n1).into_iter().chain(nucl_keys(n2)))
+                .collect(),
+            Self::Cut { nucl, s_id } => std::iter::once(DnaElementKey::Strand(*s_id))
+                .chain(nucl_keys(nucl))
+                .collect(),
+            Self::GeneralXover { source, target } => nucl_keys(source)
+                .into_iter()
+                .chain(nucl_keys(target))
+                .collect(),
+            // The helices of the ends of the strands are resolved by the caller
+            Self::Xover {
+                prime5_id,
+                prime3_id,
+            } => vec![
+                DnaElementKey::Strand(*prime5_id),
+                DnaElementKey::Strand(*prime3_id),
+            ],
+            Self::CrossCut {
+                source_id,
+                target_id,
+                nucl,
+                ..
+            } => vec![
+                DnaElementKey::Strand(*source_id),
+                DnaElementKey::Strand(*target_id),
+            ]
+            .into_iter()
+            .chain(nucl_keys(nucl))
+            .collect(),
+            Self::RequestStrandBuilders { nucls }
+            | Self::FlipAnchors { nucls }
+            | Self::FlipDeletions { nucls }
+            | Self::RmModifications { nucls } => nucls.iter().flat_map(nucl_keys).collect(),
+            Self::AddModification { nucl, .. } => nucl_keys(nucl),
+            Self::SetInsertionLength {
+                insertion_point, ..
+            } => nucl_keys(&insertion_point.nucl),
+            Self::SnapHelices { pivots, .. } => pivots
+                .iter()
+                .map(|(nucl, _)| DnaElementKey::Helix(nucl.helix))
+                .collect(),
+            Self::HelicesToGrid(selection) => selection
+                .iter()
+                .filter_map(|s| match s {
+                    Selection::Helix { helix_id, .. } => Some(DnaElementKey::Helix(*helix_id)),
+                    _ => None,
+                })
+                .collect(),
+            Self::AttachObject { object, grid, .. } => {
+                let helix = match object {
+                    GridObject::Helix(helix) => *helix,
+                    GridObject::BezierPoint { helix_id, .. } => *helix_id,
+                };
+                std::iter::once(DnaElementKey::Helix(helix))
+                    .chain(grid_key(grid))
+                    .collect()
+            }
+            Self::RmStrands { strand_ids }
+            | Self::RecolorStaples(RecolorParameters {
+                scope: RecolorScope::Strands(strand_ids),
+                ..
+            })
+            | Self::ChangeSequence {
+                strands: strand_ids,
+                ..
+            }
+            | Self::ClearSequence {
+                strands: strand_ids,
+            }
+            | Self::ChangeColor {
+                strands: strand_ids,
+                ..
+            }
+            | Self::SetScaffoldIds(strand_ids) => strand_ids
+                .iter()
+                .cloned()
+                .map(DnaElementKey::Strand)
+                .collect(),
+            Self::SetScaffoldShift { scaffold_id, .. }
+            | Self::SetScaffoldSequence { scaffold_id, .. } => {
+                vec![DnaElementKey::Strand(*scaffold_id)]
+            }
+            Self::SetStrandName { s_id, .. }
+            | Self::SetStrandNote { s_id, .. }
+            | Self::SetStrandKeepColor { s_id, .. } => vec![DnaElementKey::Strand(*s_id)],
+            // The strands of the scope are resolved by the caller
+            Self::RecolorStaples(RecolorParameters {
+                scope: RecolorScope::AllStaples | RecolorScope::Group(_),
+                ..
+            }) => vec![],
+            Self::RmGrid(grid_id) => vec![DnaElementKey::Grid(*grid_id)],
+            Self::RmFreeGrids { grid_ids } => {
+                grid_ids.iter().cloned().map(DnaElementKey::Grid).collect()
+            }
+            Self::UpdateHyperboloid { grid_id, .. }
+            | Self::SetGridPosition { grid_id, .. }
+            | Self::SetGridOrientation { grid_id, .. }
+            | Self::SetGridNbTurn { grid_id, .. }
+            | Self::SetGridType { grid_id, .. } => grid_key(grid_id).into_iter().collect(),
+            Self::ReflectSelection { helices, grids, .. } => helices
+                .iter()
+                .cloned()
+                .map(DnaElementKey::Helix)
+                .chain(grids.iter().filter_map(grid_key))
+                .collect(),
+            // The visibility is not a modification of the elements
+            Self::UpdateAttribute {
+                attribute: DnaAttribute::Visible(_),
+                ..
+            } => vec![],
+            Self::UpdateAttribute { elements, .. } => elements.clone(),
+            Self::RouteScaffold {
+                route: ScaffoldRoute::Helices(helices),
+                parameters,
+            } if parameters.clear_existing => {
+                helices.iter().cloned().map(DnaElementKey::Helix).collect()
+            }
+            // The helices of the grid are resolved by the caller
+            Self::RouteScaffold { .. } => vec![],
+            // These operations move the grids of bezier paths, the helices attached to these
+            // grids are resolved by the caller
+            Self::AppendVertexToPath { .. }
+            | Self::InsertVertexInPath { .. }
+            | Self::MoveBezierVertex { .. }
+            | Self::SetBezierVertexPosition { .. }
+            | Self::TurnPathVerticesIntoGrid { .. }
+            | Self::ApplyHomothethyOnBezierPlane { .. }
+            | Self::SetVectorOfBezierTengent(_)
+            | Self::MakeBezierPathCyclic { .. }
+            | Self::RmBezierVertices { .. } => vec![],
+            // These operations only create new elements
+            Self::AddGridHelix { .. }
+            | Self::AddTwoPointsBezier { .. }
+            | Self::AddGrid(_)
+            | Self::HyperboloidOperation(_)
+            | Self::MoveBuilders(_)
+            | Self::AddBezierPlane { .. }
+            | Self::CreateBezierPath { .. }
+            | Self::DuplicateBezierPlane { .. }
+            | Self::ImportSvgPath { .. }
+            | Self::DuplicateInPattern { .. }
+            | Self::FillStaples(_) => vec![],
+            // These operations only change how the elements are drawn
+            Self::SetHelicesPersistance { .. }
+            | Self::SetSmallSpheres { .. }
+            | Self::SetGridVisibility { .. }
+            | Self::SoloGrids { .. }
+            | Self::SetVisibilityHelix { .. }
+            | Self::SetVisibilityStrands { .. }
+            | Self::SetGridBounds { .. }
+            | Self::SetGridPhantomLength { .. }
+            | Self::CheckXovers { .. }
+            | Self::SetRainbowScaffold(_)
+            | Self::ImportOxDnaConfiguration { .. } => vec![],
+            // These operations do not modify the members of the groups of the organizer
+            Self::CleanDesign
+            | Self::SetOrganizerTree(_)
+            | Self::SetGroupPivot { .. }
+            | Self::SetGroupColor { .. }
+            | Self::SetGroupLocked { .. }
+            | Self::DeleteCamera(_)
+            | Self::CreateNewCamera { .. }
+            | Self::SetFavouriteCamera(_)
+            | Self::UpdateCamera { .. }
+            | Self::SetCameraName { .. }
+            | Self::CreateNamedSelection { .. }
+            | Self::RenameNamedSelection { .. }
+            | Self::DeleteNamedSelection(_)
+            | Self::SetDnaParameters { .. }
+            | Self::RenameBezierPlane { .. }
+            | Self::DeleteBezierPlane { .. }
+            | Self::Add3DObject { .. }
+            | Self::SetExternal3DObjectTransform { .. }
+            | Self::SetExternal3DObjectVisibility { .. }
+            | Self::AttachExternal3DObject { .. } => vec![],
+        }
+    }
+}
+
+fn isometry_target_keys(target: &IsometryTarget) -> Vec<DnaElementKey> {
+    match target {
+        IsometryTarget::Helices(helices, _) => {
+            helices.iter().cloned().map(DnaElementKey::Helix).collect()
+        }
+        IsometryTarget::Grids(grids) => grids.iter().filter_map(grid_key).collect(),
+        IsometryTarget::ControlPoint(controls) => controls
+            .iter()
+            .map(|(h_id, _)| DnaElementKey::Helix(*h_id))
+            .collect(),
+        IsometryTarget::Design | IsometryTarget::GroupPivot(_) => vec![],
+    }
+}
+
+/// The keys of a nucleotide and of the helix holding it
+fn nucl_keys(nucl: &Nucl) -> Vec<DnaElementKey> {
+    vec![
+        DnaElementKey::Nucleotide {
+            helix: nucl.helix,
+            position: nucl.position,
+            forward: nucl.forward,
+        },
+        DnaElementKey::Helix(nucl.helix),
+    ]
+}
+
+fn grid_key(grid_id: &GridId) -> Option<DnaElementKey> {
+    if let GridId::FreeGrid(id) = grid_id {
+        Some(DnaElementKey::Grid(*id))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_operations_do_not_affect_elements() {
+        let op = DesignOperation::SetFavouriteCamera(Default::default());
+        assert!(op.affected_elements().is_empty());
+    }
+
+    #[test]
+    fn cut_affects_strand_nucleotide_and_helix() {
+        let nucl = Nucl {
+            helix: 1,
+            position: 4,
+            forward: true,
+        };
+        let keys = DesignOperation::Cut { nucl, s_id: 3 }.affected_elements();
+        assert_eq!(
+            keys,
+            vec![
+                DnaElementKey::Strand(3),
+                DnaElementKey::Nucleotide {
+                    helix: 1,
+                    position: 4,
+                    forward: true,
+                },
+                DnaElementKey::Helix(1),
+            ]
+        );
+    }
+}
@@ -18,9 +18,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 pub use ensnano_design::BezierControlPoint;
 use ensnano_design::{
     grid::{GridId, HelixGridPosition},
-    BezierPathId, BezierVertexId,
+    BezierPathId, BezierVertexId, External3DObjectAnchor, External3DObjectId,
 };
 use ensnano_design::{Nucl, Strand};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 
 pub const PHANTOM_RANGE: i32 = 1000;
@@ -48,6 +49,7 @@ pub enum Selection {
         vertex_id: BezierVertexId,
         inward: bool,
     },
+    External3DObject(External3DObjectId),
     Nothing,
 }
 
@@ -91,6 +93,7 @@ impl Selection {
             Selection::Xover(d, _) => Some(*d),
             Selection::BezierTengent { .. } => Some(0),
             Selection::BezierVertex(_) => Some(0),
+            Selection::External3DObject(_) => Some(0),
         }
     }
 
@@ -118,6 +121,7 @@ impl Selection {
             Self::BezierControlPoint { .. } => None,
             Self::BezierTengent { .. } => None,
             Self::BezierVertex(_) => None,
+            Self::External3DObject(_) => None,
         }
     }
 
@@ -182,6 +186,38 @@ fn extract_one_strand(selection: &Selection) -> Option<usize> {
     }
 }
 
+/// Return the identifiers of the strands that are selected, or that have a domain on a selected
+/// helix or grid. If the whole design is selected, return all the strands of the design.
+pub fn strands_of_selection(selection: &[Selection], reader: &dyn DesignReader) -> Vec<usize> {
+    let mut ret: BTreeSet<usize> = extract_strands_from_selection(selection)
+        .into_iter()
+        .collect();
+    let whole_design = selection.iter().any(|s| matches!(s, Selection::Design(_)));
+    let helices: BTreeSet<usize> = extract_helices(selection).into_iter().collect();
+    let grids: BTreeSet<GridId> = extract_grids(selection).into_iter().collect();
+    let on_selection = |h_id: usize| {
+        helices.contains(&h_id)
+            || reader
+                .get_helix_grid(h_id)
+                .map(|g_id| grids.contains(&g_id))
+                .unwrap_or(false)
+    };
+    for s_id in reader.get_all_strand_ids() {
+        if let Some(strand) = reader.get_strand_with_id(s_id) {
+            if whole_design
+                || strand
+                    .domains
+                    .iter()
+                    .filter_map(|d| d.helix())
+                    .any(on_selection)
+            {
+                ret.insert(s_id);
+            }
+        }
+    }
+    ret.into_iter().collect()
+}
+
 pub fn extract_grids(selection: &[Selection]) -> Vec<GridId> {
     selection.iter().filter_map(extract_one_grid).collect()
 }
@@ -380,6 +416,29 @@ pub fn extract_control_points(selection: &[Selection]) -> Vec<(usize, BezierCont
     ret
 }
 
+/// Return the external 3D object that is selected, if it is the only selected element.
+pub fn extract_external_3d_object(selection: &[Selection]) -> Option<External3DObjectId> {
+    if let [Selection::External3DObject(object_id)] = selection {
+        Some(*object_id)
+    } else {
+        None
+    }
+}
+
+/// Return the element of the selection to which an external 3D object can be attached, if any.
+pub fn extract_external_3d_object_anchor(
+    selection: &[Selection],
+) -> Option<External3DObjectAnchor> {
+    selection.iter().find_map(|s| match s {
+        Selection::Nucleotide(_, nucl) => {
+            Some(External3DObjectAnchor::Helix(nucl.helix, nucl.position))
+        }
+        Selection::Helix { helix_id, .. } => Some(External3DObjectAnchor::Helix(*helix_id, 0)),
+        Selection::Grid(_, g_id) => Some(External3DObjectAnchor::Grid(*g_id, 0, 0)),
+        _ => None,
+    })
+}
+
 pub fn set_of_helices_containing_selection(
     selection: &[Selection],
     reader: &dyn DesignReader,
@@ -450,7 +509,36 @@ pub fn extract_nucls_from_selection(selection: &[Selection]) -> Vec<Nucl> {
     ret
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// Remembers the last candidates requested by an application, so that the same candidates are
+/// not requested again at each movement of the cursor.
+#[derive(Debug, Default)]
+pub struct CandidatesFilter {
+    last_request: Option<Vec<Selection>>,
+}
+
+impl CandidatesFilter {
+    /// Return the candidates that must be requested, or `None` if `candidates` were the last
+    /// requested candidates and are already the `current` candidates of the app state.
+    ///
+    /// A request is still made when the candidates were changed by another application, or when
+    /// the last request has not been applied yet.
+    pub fn filter(
+        &mut self,
+        mut candidates: Vec<Selection>,
+        current: &[Selection],
+    ) -> Option<Vec<Selection>> {
+        candidates.sort();
+        candidates.dedup();
+        if self.last_request.as_ref() == Some(&candidates) && candidates.as_slice() == current {
+            None
+        } else {
+            self.last_request = Some(candidates.clone());
+            Some(candidates)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum SelectionMode {
     Nucleotide,
     Strand,
@@ -489,7 +577,7 @@ impl SelectionMode {
 }
 
 /// Describe the action currently done by the user when they click left
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ActionMode {
     /// User is moving the camera
     Normal,
@@ -505,6 +593,9 @@ pub enum ActionMode {
     BuildHelix { position: isize, length: usize },
     /// User can cut strands
     Cut,
+    /// User can join the 3' end of a strand to the 5' end of an other strand that follows it on
+    /// the same helix
+    Ligate,
     /// User is drawing a bezier path
     EditBezierPath,
 }
@@ -527,6 +618,7 @@ impl std::fmt::Display for ActionMode {
                 ActionMode::Build(_) => "Build",
                 ActionMode::BuildHelix { .. } => "Build",
                 ActionMode::Cut => "Cut",
+                ActionMode::Ligate => "Ligate",
                 ActionMode::EditBezierPath { .. } => "Edit path",
             }
         )
@@ -627,6 +719,7 @@ pub trait DesignReader {
     fn get_strand_with_id(&self, id: usize) -> Option<&Strand>;
     fn get_helix_grid(&self, h_id: usize) -> Option<GridId>;
     fn get_domain_ends(&self, s_id: usize) -> Option<Vec<Nucl>>;
+    fn get_all_strand_ids(&self) -> Vec<usize>;
 }
 
 pub trait SelectionConversion: Sized {
@@ -669,6 +762,7 @@ impl SelectionConversion for DnaElementKey {
                 Selection::BezierControlPoint { .. } => None, //TODO make DNAelement out of these
                 Selection::BezierVertex(_) => None,
                 Selection::BezierTengent { .. } => None,
+                Selection::External3DObject(_) => None,
             }
         } else {
             None
@@ -700,3 +794,43 @@ impl SelectionConversion for DnaElementKey {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strand(s_id: u32) -> Selection {
+        Selection::Strand(0, s_id)
+    }
+
+    #[test]
+    fn same_candidates_are_requested_once() {
+        let mut filter = CandidatesFilter::default();
+        let current = filter.filter(vec![strand(1)], &[]).unwrap();
+        assert_eq!(current, vec![strand(1)]);
+        assert!(filter.filter(vec![strand(1)], &current).is_none());
+        assert!(filter
+            .filter(vec![strand(1), strand(1)], &current)
+            .is_none());
+    }
+
+    #[test]
+    fn pending_request_is_repeated() {
+        let mut filter = CandidatesFilter::default();
+        filter.filter(vec![strand(1)], &[]);
+        // The app state has not been updated yet
+        assert!(filter.filter(vec![strand(1)], &[]).is_some());
+    }
+
+    #[test]
+    fn candidates_changed_by_another_application_are_requested_again() {
+        let mut filter = CandidatesFilter::default();
+        filter.filter(vec![strand(1)], &[]);
+        assert!(filter.filter(vec![strand(1)], &[strand(1)]).is_none());
+        // Another application replaced the candidates
+        assert_eq!(
+            filter.filter(vec![strand(1)], &[strand(2)]),
+            Some(vec![strand(1)])
+        );
+    }
+}
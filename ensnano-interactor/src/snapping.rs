@@ -0,0 +1,283 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The fixed steps by which the selection can be moved or rotated.
+//!
+//! The selected helices and grids can be nudged with the arrow keys (and PageUp/PageDown for the
+//! third axis) along the axes of the widget. The length of the step depends on the modifiers
+//! that are held when the key is pressed, so that the same modifiers can later select the
+//! snapping step of the translation widget.
+//!
+//! The rotations made with the rotation widget snap to multiples of an [`AngleStep`] while a
+//! modifier is held, and a rotation of a given angle can be typed on the keyboard. Both follow
+//! the sign convention of the rotation widget, described in [`signed_angle_plane`].
+
+use crate::consts::{
+    NUDGE_COARSE_STEP, NUDGE_FINE_STEP, NUDGE_STEP, ROTATION_SNAP_COARSE_STEP,
+    ROTATION_SNAP_FINE_STEP, ROTATION_SNAP_STEP,
+};
+use crate::{extract_grids, extract_helices, ActionMode, Selection};
+use iced_winit::winit::event::{ModifiersState, VirtualKeyCode};
+use std::fmt;
+use ultraviolet::{Bivec3, Rotor3, Vec3};
+
+fn ctrl(modifiers: &ModifiersState) -> bool {
+    if cfg!(target_os = "macos") {
+        modifiers.logo()
+    } else {
+        modifiers.ctrl()
+    }
+}
+
+/// The length of the translations applied by the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranslationStep {
+    Fine,
+    Normal,
+    Coarse,
+}
+
+impl Default for TranslationStep {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl TranslationStep {
+    /// The step selected by `modifiers`. Shift selects the fine step and Ctrl (Cmd on macOS) the
+    /// coarse one.
+    pub fn from_modifiers(modifiers: &ModifiersState) -> Self {
+        if modifiers.shift() {
+            Self::Fine
+        } else if ctrl(modifiers) {
+            Self::Coarse
+        } else {
+            Self::Normal
+        }
+    }
+
+    /// The length of the step, in nanometers.
+    pub fn length(self) -> f32 {
+        match self {
+            Self::Fine => NUDGE_FINE_STEP,
+            Self::Normal => NUDGE_STEP,
+            Self::Coarse => NUDGE_COARSE_STEP,
+        }
+    }
+}
+
+impl fmt::Display for TranslationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} nm", self.length())
+    }
+}
+
+/// The direction, in the basis of the widget, in which `key` nudges the selection.
+pub fn nudge_direction(key: VirtualKeyCode) -> Option<Vec3> {
+    match key {
+        VirtualKeyCode::Right => Some(Vec3::unit_x()),
+        VirtualKeyCode::Left => Some(-Vec3::unit_x()),
+        VirtualKeyCode::Up => Some(Vec3::unit_y()),
+        VirtualKeyCode::Down => Some(-Vec3::unit_y()),
+        VirtualKeyCode::PageUp => Some(Vec3::unit_z()),
+        VirtualKeyCode::PageDown => Some(-Vec3::unit_z()),
+        _ => None,
+    }
+}
+
+/// True if the keyboard nudges `selection` when the action mode is `action_mode`: the
+/// translation widget must be shown and helices or grids must be selected.
+pub fn can_nudge_selection(selection: &[Selection], action_mode: ActionMode) -> bool {
+    action_mode == ActionMode::Translate
+        && !(extract_helices(selection).is_empty() && extract_grids(selection).is_empty())
+}
+
+/// The increment to which the rotations made with the rotation widget snap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AngleStep {
+    Fine,
+    Normal,
+    Coarse,
+}
+
+impl AngleStep {
+    /// The step selected by `modifiers`, or `None` if the rotation must not snap. Shift selects
+    /// the fine step, Ctrl (Cmd on macOS) the normal one and both of them the coarse one.
+    pub fn from_modifiers(modifiers: &ModifiersState) -> Option<Self> {
+        match (modifiers.shift(), ctrl(modifiers)) {
+            (true, true) => Some(Self::Coarse),
+            (true, false) => Some(Self::Fine),
+            (false, true) => Some(Self::Normal),
+            (false, false) => None,
+        }
+    }
+
+    /// The value of the step, in degrees.
+    pub fn degrees(self) -> f32 {
+        match self {
+            Self::Fine => ROTATION_SNAP_FINE_STEP,
+            Self::Normal => ROTATION_SNAP_STEP,
+            Self::Coarse => ROTATION_SNAP_COARSE_STEP,
+        }
+    }
+
+    /// The multiple of the step that is the closest to `angle`. Both angles are in radians.
+    pub fn snap(self, angle: f32) -> f32 {
+        let step = self.degrees().to_radians();
+        (angle / step).round() * step
+    }
+}
+
+/// True if the rotation `rotation`, computed by the rotation widget, is a rotation by a positive
+/// angle around `normal`, the normal of the circle of the widget that is being dragged.
+pub fn is_positive_rotation(rotation: Rotor3, normal: Vec3) -> bool {
+    let axis = {
+        let plane = rotation.bv.normalized();
+        Vec3::new(plane.yz, plane.xz, plane.xy)
+    };
+    normal.dot(axis) >= 0.
+}
+
+/// The angle, in radians, and the plane of `rotation`. The plane is oriented so that the angle
+/// is positive if and only if `positive` is true, where `positive` is given by
+/// [`is_positive_rotation`]. The angle is the one that is displayed next to the rotation widget.
+pub fn signed_angle_plane(rotation: Rotor3, positive: bool) -> (f32, Bivec3) {
+    let (mut angle, mut plane) = rotation.into_angle_plane();
+    if !positive {
+        angle *= -1.;
+        plane *= -1.;
+    }
+    (angle, plane)
+}
+
+/// The angle and plane of the rotation by `angle` radians around `normal`, with the same
+/// convention as [`signed_angle_plane`]: the rotation is the one that the rotation widget applies
+/// when its displayed angle is `angle`.
+pub fn rotation_around(normal: Vec3, angle: f32) -> (f32, Bivec3) {
+    let normal = normal.normalized();
+    let plane = Bivec3::new(normal.z, normal.y, normal.x);
+    let probe = Rotor3::from_angle_plane(std::f32::consts::FRAC_PI_2, plane);
+    let (probe_angle, _) = signed_angle_plane(probe, is_positive_rotation(probe, normal));
+    if probe_angle < 0. {
+        (angle, -plane)
+    } else {
+        (angle, plane)
+    }
+}
+
+/// The character typed by `key` in the entry of a rotation angle.
+pub fn angle_entry_char(key: VirtualKeyCode) -> Option<char> {
+    use VirtualKeyCode::*;
+    match key {
+        Key0 | Numpad0 => Some('0'),
+        Key1 | Numpad1 => Some('1'),
+        Key2 | Numpad2 => Some('2'),
+        Key3 | Numpad3 => Some('3'),
+        Key4 | Numpad4 => Some('4'),
+        Key5 | Numpad5 => Some('5'),
+        Key6 | Numpad6 => Some('6'),
+        Key7 | Numpad7 => Some('7'),
+        Key8 | Numpad8 => Some('8'),
+        Key9 | Numpad9 => Some('9'),
+        Period | NumpadDecimal => Some('.'),
+        Minus | NumpadSubtract => Some('-'),
+        _ => None,
+    }
+}
+
+/// True if `key` validates the entry of a rotation angle.
+pub fn is_angle_entry_validation(key: VirtualKeyCode) -> bool {
+    matches!(key, VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter)
+}
+
+/// True if a rotation angle can be typed when the action mode is `action_mode`: the rotation
+/// widget must be shown, which requires a selection.
+pub fn can_enter_rotation_angle(selection: &[Selection], action_mode: ActionMode) -> bool {
+    action_mode == ActionMode::Rotate && !selection.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ensnano_design::grid::GridId;
+
+    #[test]
+    fn modifiers_select_the_step() {
+        let ctrl = if cfg!(target_os = "macos") {
+            ModifiersState::LOGO
+        } else {
+            ModifiersState::CTRL
+        };
+        assert_eq!(
+            TranslationStep::from_modifiers(&ModifiersState::empty()).length(),
+            0.5
+        );
+        assert_eq!(
+            TranslationStep::from_modifiers(&ModifiersState::SHIFT).length(),
+            0.1
+        );
+        assert_eq!(TranslationStep::from_modifiers(&ctrl).length(), 5.);
+        assert_eq!(TranslationStep::Fine.to_string(), "0.1 nm");
+    }
+
+    #[test]
+    fn angles_snap_to_the_closest_step() {
+        let snapped = AngleStep::Normal.snap(20f32.to_radians());
+        assert!((snapped.to_degrees() - 15.).abs() < 1e-3);
+        let snapped = AngleStep::Coarse.snap(-50f32.to_radians());
+        assert!((snapped.to_degrees() + 90.).abs() < 1e-3);
+        let snapped = AngleStep::Fine.snap(2f32.to_radians());
+        assert_eq!(snapped, 0.);
+        assert_eq!(AngleStep::from_modifiers(&ModifiersState::empty()), None);
+        assert_eq!(
+            AngleStep::from_modifiers(&ModifiersState::SHIFT),
+            Some(AngleStep::Fine)
+        );
+    }
+
+    #[test]
+    fn typed_rotations_follow_the_widget_convention() {
+        let normal = Vec3::new(0.3, -1., 0.5).normalized();
+        for degrees in [30f32, -45., 120.] {
+            let (angle, plane) = rotation_around(normal, degrees.to_radians());
+            let rotation = Rotor3::from_angle_plane(angle, plane);
+            let (signed_angle, _) =
+                signed_angle_plane(rotation, is_positive_rotation(rotation, normal));
+            assert!(
+                (signed_angle.to_degrees() - degrees).abs() < 1e-2,
+                "{signed_angle} {degrees}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_helices_and_grids_are_nudged() {
+        let helix = Selection::Helix {
+            design_id: 0,
+            helix_id: 3,
+            segment_id: 0,
+        };
+        let grid = Selection::Grid(0, GridId::FreeGrid(1));
+        assert!(can_nudge_selection(&[helix], ActionMode::Translate));
+        assert!(can_nudge_selection(&[grid], ActionMode::Translate));
+        assert!(!can_nudge_selection(&[helix], ActionMode::Rotate));
+        assert!(!can_nudge_selection(
+            &[Selection::Strand(0, 1)],
+            ActionMode::Translate
+        ));
+    }
+}
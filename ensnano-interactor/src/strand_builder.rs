@@ -409,6 +409,24 @@ impl StrandBuilder {
         self.identifier
     }
 
+    /// Return the nucleotides of the domain being built, from its fixed end to its moving end.
+    pub fn get_domain_nucls(&self) -> Vec<Nucl> {
+        let fixed_end = self.fixed_end.unwrap_or(self.initial_position);
+        let moving_end = self.moving_end.position;
+        let positions: Vec<isize> = if fixed_end <= moving_end {
+            (fixed_end..=moving_end).collect()
+        } else {
+            (moving_end..=fixed_end).rev().collect()
+        };
+        positions
+            .into_iter()
+            .map(|position| Nucl {
+                position,
+                ..self.moving_end
+            })
+            .collect()
+    }
+
     pub fn get_timestamp(&self) -> std::time::SystemTime {
         self.timestamp
     }
@@ -0,0 +1,164 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Messages drawn on top of the 2D and 3D views: a persistent banner while a simulation is
+//! running, and transient messages (toasts) explaining why an action was not performed.
+//!
+//! Each view owns its own queue of toasts, since the messages are produced by the controller of
+//! the view in which the user attempted the action.
+
+use crate::consts::{MAX_TOASTS, TOAST_DURATION, TOAST_FADE_DURATION};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// The text of the banner shown while a simulation is running
+pub const SIMULATION_RUNNING_BANNER: &str = "Simulation running - editing disabled";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayMessageKind {
+    Banner,
+    Toast,
+}
+
+/// A message to be drawn on top of a view
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlayMessage {
+    pub text: String,
+    pub kind: OverlayMessageKind,
+    /// Goes from 1 to 0 during the fade-out of a toast
+    pub opacity: f32,
+}
+
+/// The message explaining that an action was blocked because a simulation is running. `action`
+/// describes the action, e.g. "make crossovers".
+pub fn blocked_during_simulation(action: &str) -> String {
+    format!("Cannot {action} while a simulation is running")
+}
+
+struct Toast {
+    text: String,
+    shown_at: Instant,
+}
+
+/// A queue of toasts. At most `MAX_TOASTS` toasts are shown at the same time, the oldest ones
+/// are dropped when new toasts are pushed.
+#[derive(Default)]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Show `text` in a new toast. If the most recent toast has the same text, its display is
+    /// extended instead, so that repeated attempts (e.g. during a drag) do not fill the queue.
+    pub fn push(&mut self, text: String, now: Instant) {
+        if let Some(last) = self.toasts.back_mut().filter(|t| t.text == text) {
+            last.shown_at = now;
+            return;
+        }
+        self.toasts.push_back(Toast {
+            text,
+            shown_at: now,
+        });
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    /// The messages to be drawn at time `now`, from top to bottom. The banner comes first if
+    /// `simulation_running` is true. Expired toasts are removed from the queue.
+    pub fn overlay_messages(
+        &mut self,
+        simulation_running: bool,
+        now: Instant,
+    ) -> Vec<OverlayMessage> {
+        self.toasts
+            .retain(|t| now.saturating_duration_since(t.shown_at) < TOAST_DURATION);
+        let banner = simulation_running.then(|| OverlayMessage {
+            text: SIMULATION_RUNNING_BANNER.to_string(),
+            kind: OverlayMessageKind::Banner,
+            opacity: 1.,
+        });
+        banner
+            .into_iter()
+            .chain(self.toasts.iter().map(|t| OverlayMessage {
+                text: t.text.clone(),
+                kind: OverlayMessageKind::Toast,
+                opacity: toast_opacity(now.saturating_duration_since(t.shown_at)),
+            }))
+            .collect()
+    }
+}
+
+fn toast_opacity(age: std::time::Duration) -> f32 {
+    let fade_start = TOAST_DURATION.saturating_sub(TOAST_FADE_DURATION);
+    if age <= fade_start {
+        1.
+    } else {
+        let fade = (age - fade_start).as_secs_f32() / TOAST_FADE_DURATION.as_secs_f32();
+        (1. - fade).max(0.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn at_most_three_toasts() {
+        let now = Instant::now();
+        let mut toasts = Toasts::default();
+        for i in 0..5 {
+            toasts.push(format!("toast {i}"), now);
+        }
+        let messages = toasts.overlay_messages(false, now);
+        let texts: Vec<_> = messages.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["toast 2", "toast 3", "toast 4"]);
+    }
+
+    #[test]
+    fn repeated_toast_is_not_stacked() {
+        let now = Instant::now();
+        let mut toasts = Toasts::default();
+        toasts.push("blocked".into(), now);
+        toasts.push("blocked".into(), now + Duration::from_millis(100));
+        assert_eq!(toasts.overlay_messages(false, now).len(), 1);
+    }
+
+    #[test]
+    fn toasts_fade_out_and_expire() {
+        let now = Instant::now();
+        let mut toasts = Toasts::default();
+        toasts.push("blocked".into(), now);
+        let fading = now + TOAST_DURATION - TOAST_FADE_DURATION / 2;
+        let opacity = toasts.overlay_messages(false, fading)[0].opacity;
+        assert!(opacity > 0. && opacity < 1.);
+        assert!(toasts
+            .overlay_messages(false, now + TOAST_DURATION)
+            .is_empty());
+    }
+
+    #[test]
+    fn banner_is_shown_first() {
+        let now = Instant::now();
+        let mut toasts = Toasts::default();
+        toasts.push("blocked".into(), now);
+        let messages = toasts.overlay_messages(true, now);
+        assert_eq!(messages[0].kind, OverlayMessageKind::Banner);
+        assert_eq!(messages[1].kind, OverlayMessageKind::Toast);
+    }
+}
@@ -6,8 +6,14 @@ use iced_native::{
 
 #[derive(Debug, Clone, PartialOrd, PartialEq, Eq, Ord)]
 pub(super) enum Identifier<K, AutoGroup> {
-    Group { id: super::NodeId<AutoGroup> },
-    Section { key: K },
+    Group {
+        id: super::NodeId<AutoGroup>,
+    },
+    Section {
+        key: K,
+    },
+    /// The end of the list of top-level groups
+    Root,
 }
 
 pub(super) struct DragDropTarget<'a, Message, K, E> {
@@ -20,6 +26,8 @@ pub(super) struct DragDropTarget<'a, Message, K, E> {
     vertical_alignment: Alignment,
     content: Container<'a, Message>,
     identifier: Identifier<K, E>,
+    on_escape: Option<Message>,
+    drop_indicator: Option<iced::Color>,
 }
 
 impl<'a, Message, K, E> DragDropTarget<'a, Message, K, E> {
@@ -38,9 +46,24 @@ impl<'a, Message, K, E> DragDropTarget<'a, Message, K, E> {
             vertical_alignment: Alignment::Start,
             content: Container::new(content).width(Length::Fill),
             identifier,
+            on_escape: None,
+            drop_indicator: None,
         }
     }
 
+    /// Sets the message that is produced when the escape key is pressed.
+    pub fn on_escape(mut self, message: Message) -> Self {
+        self.on_escape = Some(message);
+        self
+    }
+
+    /// Sets the color of the border that is drawn around the target when it is hovered. If
+    /// `color` is None, no border is drawn.
+    pub fn drop_indicator(mut self, color: Option<iced::Color>) -> Self {
+        self.drop_indicator = color;
+        self
+    }
+
     /// Sets the width of the [`Container`] contained in self.
     pub fn width(mut self, width: Length) -> Self {
         self.width = width.clone();
@@ -114,6 +137,15 @@ impl<'a, E: super::OrganizerElement> Widget<OrganizerMessage<E>, Renderer>
                 }
                 return event::Status::Captured;
             }
+            Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::Escape,
+                ..
+            }) => {
+                if let Some(message) = self.on_escape.clone() {
+                    shell.publish(message);
+                    return event::Status::Captured;
+                }
+            }
             _ => (),
         };
         status
@@ -133,7 +165,21 @@ impl<'a, E: super::OrganizerElement> Widget<OrganizerMessage<E>, Renderer>
             layout.children().next().unwrap(),
             cursor_position,
             viewport,
-        )
+        );
+        if let Some(color) = self.drop_indicator {
+            if layout.bounds().contains(cursor_position) {
+                iced_native::Renderer::fill_quad(
+                    renderer,
+                    iced_native::renderer::Quad {
+                        bounds: layout.bounds(),
+                        border_radius: 0.,
+                        border_width: 2.,
+                        border_color: color,
+                    },
+                    iced::Background::Color(iced::Color::TRANSPARENT),
+                );
+            }
+        }
     }
 
     fn overlay(
@@ -38,11 +38,42 @@ pub trait OrganizerElement: Clone + std::fmt::Debug + 'static {
 
     fn attributes(&self) -> Vec<Self::Attribute>;
 
+    /// Return true iff the element can be renamed from the organizer
+    fn is_renamable(&self) -> bool {
+        false
+    }
+
+    /// The name that was given to the element, if any
+    fn custom_name(&self) -> Option<String> {
+        None
+    }
+
     fn all_repr() -> &'static [<Self::Attribute as OrganizerAttribute>::Repr] {
         Self::Attribute::all_repr()
     }
 
     fn auto_groups(&self) -> Vec<Self::AutoGroup>;
+
+    /// A numeric value by which the elements of a section can be sorted, if the element has one
+    fn sort_value(&self) -> Option<f32> {
+        None
+    }
+
+    /// The color of the element, used to display elements with the same color next to each other
+    fn color(&self) -> Option<u32> {
+        None
+    }
+
+    /// A quantity that is summed over the elements of each group and displayed on the group
+    /// header, if the element has one
+    fn weight(&self) -> Option<usize> {
+        None
+    }
+
+    /// The unit displayed after the sum of the weights of the elements of a group
+    fn weight_unit() -> &'static str {
+        ""
+    }
 }
 
 pub trait OrganizerAttributeRepr:
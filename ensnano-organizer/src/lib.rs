@@ -1,12 +1,13 @@
 use iced::{
-    button, scrollable, text_input, tooltip, Button, Column, Container, Element, Row, Scrollable,
-    Space, TextInput, Tooltip,
+    button, pick_list, scrollable, text_input, tooltip, Button, Column, Container, Element,
+    PickList, Row, Scrollable, Space, TextInput, Tooltip,
 };
 pub use iced_aw::Icon;
-use iced_native::keyboard::Modifiers;
+use iced_native::keyboard::{KeyCode, Modifiers};
 use iced_native::{text::Renderer, widget::Text};
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryInto;
+use std::time::{Duration, Instant};
 
 #[macro_use]
 extern crate serde_derive;
@@ -15,11 +16,13 @@ extern crate serde;
 mod drag_drop_target;
 pub mod element;
 mod hoverable_button;
+mod sort;
 pub mod theme;
 mod tree;
 
 pub use element::*;
 use rand::{rngs::ThreadRng, Rng};
+pub use sort::SortCriterion;
 use theme::Theme;
 pub use tree::{GroupId, OrganizerTree};
 
@@ -30,6 +33,22 @@ use hoverable_button::HoverableContainer;
 const LEVEL0_SPACING: u16 = 3;
 const LEVELS_SPACING: u16 = 2;
 const ICON_SIZE: u16 = 10;
+/// The maximal delay between two clicks on an element for them to be a double click
+/// The colors that can be given to groups. Pressing the color button of a group cycles through
+/// these colors.
+const GROUP_COLORS: [u32; 8] = [
+    0xE74C3C, 0xE67E22, 0xF1C40F, 0x2ECC71, 0x1ABC9C, 0x3498DB, 0x9B59B6, 0x7F8C8D,
+];
+const DOUBLE_CLICK_DELAY: Duration = Duration::from_millis(400);
+/// The number of element names listed in the tooltip of the summary of a group
+const SUMMARY_NB_NAMES: usize = 5;
+const SUMMARY_TEXT_SIZE: u16 = 14;
+const SUMMARY_TEXT_COLOR: iced::Color = iced::Color {
+    r: 0.6,
+    g: 0.6,
+    b: 0.6,
+    a: 1.,
+};
 
 #[derive(Clone, Debug)]
 pub enum OrganizerMessage<E: OrganizerElement> {
@@ -39,6 +58,18 @@ pub enum OrganizerMessage<E: OrganizerElement> {
     ElementUpdate(Vec<BTreeMap<E::Key, E>>),
     NewAttribute(E::Attribute, Vec<E::Key>),
     NewTree(OrganizerTree<E::Key>),
+    NewElementName(E::Key, String),
+    NewGroupColor {
+        group_id: GroupId,
+        color: Option<u32>,
+    },
+    /// The set of pinned groups has changed
+    NewPinnedGroups(Vec<GroupId>),
+    /// The lock button of a group was pressed
+    NewGroupLock {
+        group_id: GroupId,
+        locked: bool,
+    },
     NewGroup {
         group_id: GroupId,
         elements_selected: Vec<E::Key>,
@@ -67,6 +98,15 @@ impl<E: std::fmt::Debug> NodeId<E> {
     }
 }
 
+/// A row of the organizer that can be under the keyboard cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum CursorRow<K, AutoGroupId> {
+    /// The title row of a group or section, or a leaf of a group
+    Node(NodeId<AutoGroupId>),
+    /// An element displayed in a section
+    Element { parent: NodeId<AutoGroupId>, key: K },
+}
+
 fn get_group_id<E>(id: &NodeId<E>) -> Option<&[usize]> {
     if let NodeId::TreeId(id) = id {
         Some(id)
@@ -75,6 +115,20 @@ fn get_group_id<E>(id: &NodeId<E>) -> Option<&[usize]> {
     }
 }
 
+/// Return true iff `ancestor` is the identifier of a strict ancestor of `id`
+fn is_prefix(ancestor: &[usize], id: &[usize]) -> bool {
+    ancestor.len() < id.len() && id[..ancestor.len()] == ancestor[..]
+}
+
+/// Return true iff the name of `element` contains `filter`, ignoring case.
+///
+/// `filter` must be in lowercase.
+fn element_matches<E: OrganizerElement>(element: &E, filter: Option<&str>) -> bool {
+    filter
+        .map(|f| element.display_name().to_lowercase().contains(f))
+        .unwrap_or(true)
+}
+
 fn get_section_id<E>(id: &NodeId<E>) -> Option<usize> {
     if let NodeId::SectionId(n) = id {
         Some(*n)
@@ -130,6 +184,24 @@ impl<E: OrganizerElement> OrganizerMessage<E> {
         Self::InternalMessage(InternalMessage(OrganizerMessage_::StopEddit))
     }
 
+    fn eddit_element(identifier: Identifier<E::Key, E::AutoGroup>) -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::EdditElement {
+            identifier,
+        }))
+    }
+
+    fn element_name_input(name: String) -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::ElementNameInput {
+            name,
+        }))
+    }
+
+    fn stop_element_eddit(commit: bool) -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::StopElementEddit {
+            commit,
+        }))
+    }
+
     fn element_selected(key: E::Key) -> Self {
         Self::InternalMessage(InternalMessage(OrganizerMessage_::ElementSelected { key }))
     }
@@ -138,6 +210,26 @@ impl<E: OrganizerElement> OrganizerMessage<E> {
         Self::InternalMessage(InternalMessage(OrganizerMessage_::NewGroup))
     }
 
+    fn filter_input(text: String) -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::FilterInput { text }))
+    }
+
+    fn group_color_pressed(id: NodeId<E::AutoGroup>) -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::GroupColorPressed { id }))
+    }
+
+    fn group_pin_pressed(id: NodeId<E::AutoGroup>) -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::GroupPinPressed { id }))
+    }
+
+    fn group_lock_pressed(id: NodeId<E::AutoGroup>) -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::GroupLockPressed { id }))
+    }
+
+    fn undo_delete() -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::UndoDelete))
+    }
+
     fn dragging(key: Identifier<E::Key, E::AutoGroup>) -> Self {
         Self::InternalMessage(InternalMessage(OrganizerMessage_::Dragging(key)))
     }
@@ -146,6 +238,13 @@ impl<E: OrganizerElement> OrganizerMessage<E> {
         Self::InternalMessage(InternalMessage(OrganizerMessage_::DragDropped(key)))
     }
 
+    fn sort_section(section: usize, criterion: SortCriterion) -> Self {
+        Self::InternalMessage(InternalMessage(OrganizerMessage_::SortSection {
+            section,
+            criterion,
+        }))
+    }
+
     fn attribute_selected(attribute: E::Attribute, id: NodeId<E::AutoGroup>) -> Self {
         Self::InternalMessage(InternalMessage(OrganizerMessage_::AttributeSelected {
             attribute,
@@ -181,16 +280,42 @@ enum OrganizerMessage_<E: OrganizerElement> {
     NameInput {
         name: String,
     },
+    EdditElement {
+        identifier: Identifier<E::Key, E::AutoGroup>,
+    },
+    ElementNameInput {
+        name: String,
+    },
+    StopElementEddit {
+        commit: bool,
+    },
     NewGroup,
     Delete {
         id: NodeId<E::AutoGroup>,
     },
+    UndoDelete,
+    GroupColorPressed {
+        id: NodeId<E::AutoGroup>,
+    },
+    GroupPinPressed {
+        id: NodeId<E::AutoGroup>,
+    },
+    GroupLockPressed {
+        id: NodeId<E::AutoGroup>,
+    },
+    FilterInput {
+        text: String,
+    },
     DragDropped(Identifier<E::Key, E::AutoGroup>),
     Dragging(Identifier<E::Key, E::AutoGroup>),
     AttributeSelected {
         attribute: E::Attribute,
         id: NodeId<E::AutoGroup>,
     },
+    SortSection {
+        section: usize,
+        criterion: SortCriterion,
+    },
 }
 
 pub struct Organizer<E: OrganizerElement> {
@@ -202,6 +327,8 @@ pub struct Organizer<E: OrganizerElement> {
     theme: Theme,
     width: iced::Length,
     edditing: Option<GroupId>,
+    edditing_element: Option<Identifier<E::Key, E::AutoGroup>>,
+    last_click: Option<(Identifier<E::Key, E::AutoGroup>, Instant)>,
     modifiers: Modifiers,
     selected_nodes: BTreeSet<NodeId<E::AutoGroup>>,
     dragging: BTreeSet<Identifier<E::Key, E::AutoGroup>>,
@@ -210,6 +337,105 @@ pub struct Organizer<E: OrganizerElement> {
     last_read_tree: *const OrganizerTree<E::Key>,
     must_update_tree: bool,
     group_to_node: HashMap<GroupId, NodeId<E::AutoGroup>>,
+    /// The contents that were deleted with the trash button, the most recent being the last one
+    deleted_contents: Vec<DeletedContent<E>>,
+    undo_delete_button: button::State,
+    root_drop_target: button::State,
+    /// Only the elements whose name contains this text are displayed
+    filter: String,
+    filter_input: text_input::State,
+    /// The row highlighted by the keyboard cursor
+    cursor: Option<CursorRow<E::Key, E::AutoGroup>>,
+    group_marks: GroupMarks,
+}
+
+/// The attributes of the groups that are displayed next to their names.
+///
+/// They are stored by group identifier so that they are not lost when the tree is rebuilt.
+#[derive(Default)]
+struct GroupMarks {
+    colors: HashMap<GroupId, u32>,
+    /// The groups whose helices are immobile during simulations
+    pinned: HashSet<GroupId>,
+    /// The groups whose members cannot be modified
+    locked: HashSet<GroupId>,
+}
+
+impl GroupMarks {
+    fn of_group(&self, group_id: &GroupId) -> NodeMarks {
+        NodeMarks {
+            color: self.colors.get(group_id).cloned(),
+            pinned: self.pinned.contains(group_id),
+            locked: self.locked.contains(group_id),
+        }
+    }
+}
+
+/// The attributes displayed next to the name of one group
+#[derive(Clone, Copy, Default)]
+struct NodeMarks {
+    color: Option<u32>,
+    pinned: bool,
+    locked: bool,
+}
+
+/// A description of the elements below a group, displayed on the right of its title row
+struct NodeSummary {
+    nb_elements: usize,
+    /// The sum of the weights of the elements, if at least one of them has a weight
+    weight: Option<usize>,
+    /// The names of the first elements
+    names: Vec<String>,
+}
+
+impl NodeSummary {
+    fn new<E: OrganizerElement>(elements: &BTreeSet<E::Key>, sections: &[Section<E>]) -> Self {
+        let elements_below: Vec<&E> = elements
+            .iter()
+            .filter_map(|k| get_element(sections, k))
+            .collect();
+        let weight = elements_below
+            .iter()
+            .filter_map(|e| e.weight())
+            .fold(None, |sum: Option<usize>, w| Some(sum.unwrap_or(0) + w));
+        Self {
+            nb_elements: elements.len(),
+            weight,
+            names: elements_below
+                .iter()
+                .take(SUMMARY_NB_NAMES)
+                .map(|e| e.display_name())
+                .collect(),
+        }
+    }
+
+    fn text(&self, unit: &str) -> String {
+        if let Some(weight) = self.weight {
+            format!("{} · {} {}", self.nb_elements, weight, unit)
+        } else {
+            self.nb_elements.to_string()
+        }
+    }
+
+    fn tooltip(&self) -> String {
+        let mut ret = self.names.join("\n");
+        if self.nb_elements > self.names.len() {
+            ret.push_str(&format!(
+                "\n… and {} more",
+                self.nb_elements - self.names.len()
+            ));
+        }
+        ret
+    }
+}
+
+/// A sub-tree that was removed from the organizer and that can be restored.
+struct DeletedContent<E: OrganizerElement> {
+    /// The identifier that the content had before being deleted
+    id: NodeId<E::AutoGroup>,
+    /// The group that contained the content, or None if it was at the root level
+    parent: Option<GroupId>,
+    content: GroupContent<E>,
 }
 
 impl<E: OrganizerElement> Organizer<E> {
@@ -234,6 +460,8 @@ impl<E: OrganizerElement> Organizer<E> {
             theme: Theme::grey(),
             width: iced::Length::Units(300),
             edditing: None,
+            edditing_element: None,
+            last_click: None,
             modifiers: Modifiers::default(),
             selected_nodes: BTreeSet::new(),
             dragging: BTreeSet::new(),
@@ -242,27 +470,59 @@ impl<E: OrganizerElement> Organizer<E> {
             last_read_tree: std::ptr::null(),
             must_update_tree: false,
             group_to_node: HashMap::new(),
+            deleted_contents: Vec::new(),
+            undo_delete_button: Default::default(),
+            root_drop_target: Default::default(),
+            filter: String::new(),
+            filter_input: Default::default(),
+            cursor: None,
+            group_marks: Default::default(),
         }
     }
 
     pub fn reset(&mut self) {
-        self.groups = vec![]
+        self.groups = vec![];
+        self.deleted_contents.clear();
+        self.group_marks.pinned.clear();
     }
 
     pub fn new_modifiers(&mut self, modifiers: Modifiers) {
         self.modifiers = modifiers;
     }
 
+    /// Set the colors that are displayed next to the groups names
+    pub fn update_group_colors(&mut self, group_colors: HashMap<GroupId, u32>) {
+        self.group_marks.colors = group_colors;
+    }
+
+    /// Set the groups whose members cannot be modified
+    pub fn update_locked_groups(&mut self, locked_groups: HashSet<GroupId>) {
+        self.group_marks.locked = locked_groups;
+    }
+
+    /// The groups whose helices must be immobile during simulations
+    pub fn pinned_groups(&self) -> Vec<GroupId> {
+        self.group_marks.pinned.iter().cloned().collect()
+    }
+
     pub fn set_width(&mut self, width: u16) {
         self.width = iced::Length::Units(width);
     }
 
     pub fn view(&mut self, selection: BTreeSet<E::Key>) -> Element<OrganizerMessage<E>> {
         self.hovered_in = None;
+        let dragging = !self.dragging.is_empty();
+        self.theme.set_dragging(dragging);
+        let filter = self.lowercase_filter();
+        let filter = filter.as_deref();
+        let cursor = self.cursor.as_ref();
         let mut ret = Scrollable::new(&mut self.scroll_state)
             .width(self.width)
             .spacing(LEVEL0_SPACING);
         for c in self.groups.iter_mut() {
+            if !c.matches_filter(&self.sections, filter) {
+                continue;
+            }
             ret = ret.push(
                 Row::new().push(tabulation()).push(
                     c.view(
@@ -270,23 +530,46 @@ impl<E: OrganizerElement> Organizer<E> {
                         &self.sections,
                         &selection,
                         &self.selected_nodes,
+                        filter,
+                        cursor,
+                        &self.group_marks,
                     )
                     .width(iced::Length::FillPortion(8)),
                 ),
             )
         }
+        if dragging {
+            // Dropping elements here moves them at the end of the top-level groups
+            let root_button =
+                Button::new(&mut self.root_drop_target, Text::new("Move to top level"))
+                    .width(iced::Length::Fill)
+                    .style(self.theme.level(0));
+            ret = ret.push(
+                Row::new().push(tabulation()).push(
+                    DragDropTarget::new(root_button, Identifier::Root)
+                        .drop_indicator(self.theme.drop_indicator())
+                        .width(iced::Length::FillPortion(8)),
+                ),
+            )
+        }
         for s in self.sections.iter_mut() {
+            if !s.matches_filter(filter) {
+                continue;
+            }
             ret = ret.push(
                 Row::new().push(tabulation()).push(
-                    s.view(&self.theme, &selection)
+                    s.view(&self.theme, &selection, filter, cursor)
                         .width(iced::Length::FillPortion(8)),
                 ),
             )
         }
         for s in self.auto_groups.values_mut() {
+            if !s.matches_filter(filter) {
+                continue;
+            }
             ret = ret.push(
                 Row::new().push(tabulation()).push(
-                    s.view(&self.theme, &selection)
+                    s.view(&self.theme, &selection, filter, cursor)
                         .width(iced::Length::FillPortion(8)),
                 ),
             )
@@ -300,8 +583,24 @@ impl<E: OrganizerElement> Organizer<E> {
             "Create new_group from selection",
             tooltip::Position::FollowCursor,
         );
-        let title_row = Row::new().push(new_group_tooltip);
-        let column = Column::new().push(title_row).push(ret);
+        let mut undo_delete_button =
+            Button::new(&mut self.undo_delete_button, Text::new("Undo delete"));
+        if !self.deleted_contents.is_empty() {
+            undo_delete_button = undo_delete_button.on_press(OrganizerMessage::undo_delete());
+        }
+        let undo_delete_tooltip = Tooltip::new(
+            undo_delete_button,
+            "Restore the last deleted group",
+            tooltip::Position::FollowCursor,
+        );
+        let title_row = Row::new().push(new_group_tooltip).push(undo_delete_tooltip);
+        let filter_input = TextInput::new(
+            &mut self.filter_input,
+            "Search...",
+            &self.filter,
+            OrganizerMessage::filter_input,
+        );
+        let column = Column::new().push(title_row).push(filter_input).push(ret);
         Container::new(column).style(self.theme.level(0)).into()
     }
 
@@ -375,14 +674,70 @@ impl<E: OrganizerElement> Organizer<E> {
             }
             OrganizerMessage_::Delete { id } => {
                 self.stop_edditing();
-                self.pop_id(id);
+                self.delete_id(id);
+                self.update_attributes();
                 return Some(OrganizerMessage::NewTree(self.tree()));
             }
+            OrganizerMessage_::UndoDelete => return self.restore_last_deleted(),
+            OrganizerMessage_::GroupColorPressed { id } => {
+                if let Some(group_id) = self.get_group(id).and_then(|g| g.get_group_id()) {
+                    let color = next_group_color(self.group_marks.colors.get(&group_id).cloned());
+                    return Some(OrganizerMessage::NewGroupColor { group_id, color });
+                }
+            }
+            OrganizerMessage_::GroupPinPressed { id } => {
+                if let Some(group_id) = self.get_group(id).and_then(|g| g.get_group_id()) {
+                    if !self.group_marks.pinned.remove(&group_id) {
+                        self.group_marks.pinned.insert(group_id);
+                    }
+                    return Some(OrganizerMessage::NewPinnedGroups(self.pinned_groups()));
+                }
+            }
+            OrganizerMessage_::GroupLockPressed { id } => {
+                if let Some(group_id) = self.get_group(id).and_then(|g| g.get_group_id()) {
+                    let locked = !self.group_marks.locked.contains(&group_id);
+                    return Some(OrganizerMessage::NewGroupLock { group_id, locked });
+                }
+            }
+            OrganizerMessage_::FilterInput { text } => self.filter = text.clone(),
+            OrganizerMessage_::Dragging(Identifier::Root) => (),
             OrganizerMessage_::Dragging(k) => {
-                self.dragging.clear();
-                self.dragging.insert(k.clone());
+                self.cursor = Some(match k {
+                    Identifier::Group { id } => CursorRow::Node(id.clone()),
+                    Identifier::Section { key } => CursorRow::Element {
+                        parent: NodeId::SectionId(key.section().into()),
+                        key: key.clone(),
+                    },
+                    Identifier::Root => unreachable!(),
+                });
+                if self.is_double_click(k) {
+                    self.start_element_edition(k)
+                }
+                self.start_dragging(k, selection)
+            }
+            OrganizerMessage_::EdditElement { identifier } => {
+                if self.edditing_element.as_ref() == Some(identifier) {
+                    return self.stop_element_edition(true);
+                } else {
+                    self.start_element_edition(identifier)
+                }
+            }
+            OrganizerMessage_::ElementNameInput { name } => {
+                if let Some(view) = self
+                    .edditing_element
+                    .clone()
+                    .and_then(|id| self.get_element_view_mut(&id))
+                {
+                    view.eddit_name(name.clone())
+                }
+            }
+            OrganizerMessage_::StopElementEddit { commit } => {
+                return self.stop_element_edition(*commit)
+            }
+            OrganizerMessage_::DragDropped(k) => {
+                self.drag_drop(k);
+                self.update_attributes();
             }
-            OrganizerMessage_::DragDropped(k) => self.drag_drop(k),
             OrganizerMessage_::NodeHovered { id, hovered_in } => {
                 return self.hover(id, *hovered_in)
             }
@@ -393,10 +748,172 @@ impl<E: OrganizerElement> Organizer<E> {
                 let keys = self.get_keys_below(id);
                 return Some(OrganizerMessage::NewAttribute(attribute.clone(), keys));
             }
+            OrganizerMessage_::SortSection { section, criterion } => {
+                if let Some(s) = self.sections.get_mut(*section) {
+                    s.set_sort_criterion(*criterion)
+                }
+            }
+        }
+        None
+    }
+
+    /// Handle a key pressed while the organizer has the focus.
+    ///
+    /// Up and Down move the keyboard cursor through the displayed rows, Right expands the node
+    /// under the cursor and Left collapses it or moves the cursor to its parent. Space selects the
+    /// row under the cursor and Enter starts renaming the group under the cursor.
+    ///
+    /// Keys are ignored if the cursor has not been placed on a row by clicking it.
+    pub fn keyboard_event(
+        &mut self,
+        key_code: KeyCode,
+        selection: &BTreeSet<E::Key>,
+    ) -> Option<OrganizerMessage<E>> {
+        let cursor = self.cursor.clone()?;
+        match key_code {
+            KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Down => self.move_cursor(1),
+            KeyCode::Right => {
+                if let CursorRow::Node(id) = cursor {
+                    if self.node_expanded(&id) == Some(false) {
+                        let message = OrganizerMessage_::Expand { id, expanded: true };
+                        return self.message(&InternalMessage(message), selection);
+                    }
+                }
+            }
+            KeyCode::Left => match cursor {
+                CursorRow::Node(id) if self.node_expanded(&id) == Some(true) => {
+                    let message = OrganizerMessage_::Expand {
+                        id,
+                        expanded: false,
+                    };
+                    return self.message(&InternalMessage(message), selection);
+                }
+                CursorRow::Node(NodeId::TreeId(id)) if id.len() > 1 => {
+                    let parent = NodeId::TreeId(id[..id.len() - 1].to_vec());
+                    self.cursor = Some(CursorRow::Node(parent));
+                }
+                CursorRow::Element { parent, .. } => self.cursor = Some(CursorRow::Node(parent)),
+                _ => (),
+            },
+            KeyCode::Space => {
+                let message = match cursor {
+                    CursorRow::Node(id) => {
+                        if let Some(key) = self.get_group(&id).and_then(GroupContent::leaf_key) {
+                            OrganizerMessage_::ElementSelected { key: key.clone() }
+                        } else {
+                            OrganizerMessage_::NodeSelected { id }
+                        }
+                    }
+                    CursorRow::Element { key, .. } => OrganizerMessage_::ElementSelected { key },
+                };
+                return self.message(&InternalMessage(message), selection);
+            }
+            KeyCode::Enter => {
+                if let CursorRow::Node(id) = cursor {
+                    if let Some(group_id) = self.get_group(&id).and_then(|g| g.get_group_id()) {
+                        self.start_edditing(group_id)
+                    }
+                }
+            }
+            KeyCode::Escape => self.cursor = None,
+            _ => (),
         }
         None
     }
 
+    fn move_cursor(&mut self, delta: isize) {
+        let rows = self.visible_rows();
+        if rows.is_empty() {
+            self.cursor = None;
+            return;
+        }
+        let position = self
+            .cursor
+            .as_ref()
+            .and_then(|cursor| rows.iter().position(|r| r == cursor));
+        let new_position = match position {
+            Some(p) => (p as isize + delta).max(0).min(rows.len() as isize - 1) as usize,
+            None => 0,
+        };
+        self.cursor = Some(rows[new_position].clone());
+    }
+
+    /// The rows that are displayed by the organizer, in the order in which they are displayed
+    fn visible_rows(&self) -> Vec<CursorRow<E::Key, E::AutoGroup>> {
+        let filter = self.lowercase_filter();
+        let filter = filter.as_deref();
+        let mut rows = Vec::new();
+        for g in self.groups.iter() {
+            if g.matches_filter(&self.sections, filter) {
+                g.push_visible_rows(&self.sections, filter, &mut rows);
+            }
+        }
+        for s in self.sections.iter().chain(self.auto_groups.values()) {
+            if s.matches_filter(filter) {
+                s.push_visible_rows(filter, &mut rows);
+            }
+        }
+        rows
+    }
+
+    /// Return Some(expanded) if `id` identifies a node that can be expanded, and None otherwise
+    fn node_expanded(&self, id: &NodeId<E::AutoGroup>) -> Option<bool> {
+        match id {
+            NodeId::TreeId(_) => match self.get_group(id) {
+                Some(GroupContent::Node { expanded, .. }) => Some(*expanded),
+                _ => None,
+            },
+            NodeId::SectionId(s_id) => self.sections.get(*s_id).map(|s| s.expanded),
+            NodeId::AutoGroupId(name) => self.auto_groups.get(name).map(|s| s.expanded),
+        }
+    }
+
+    /// A description of the position of the cursor in the tree that does not depend on the
+    /// node identifiers. It is used to move the cursor to the same row when the tree is rebuilt.
+    ///
+    /// Return the identifier of the group under the cursor, or of the parent of the leaf under the
+    /// cursor together with the key of the leaf.
+    fn cursor_anchor(&self) -> Option<(GroupId, Option<E::Key>)> {
+        let id = match self.cursor.as_ref() {
+            Some(CursorRow::Node(id)) => id,
+            _ => return None,
+        };
+        let tree_id = get_group_id(id)?;
+        match self.get_group(id)? {
+            GroupContent::Node { group_id, .. } => Some((*group_id, None)),
+            GroupContent::Leaf { element, .. } if tree_id.len() > 1 => {
+                let parent = NodeId::TreeId(tree_id[..tree_id.len() - 1].to_vec());
+                let parent_id = self.get_group(&parent)?.get_group_id()?;
+                Some((parent_id, Some(element.clone())))
+            }
+            _ => None,
+        }
+    }
+
+    fn resolve_cursor_anchor(&mut self, anchor: Option<(GroupId, Option<E::Key>)>) {
+        let node = anchor
+            .as_ref()
+            .and_then(|(group_id, _)| self.group_to_node.get(group_id))
+            .cloned();
+        self.cursor = match (node, anchor.and_then(|(_, key)| key)) {
+            (Some(node), None) => Some(CursorRow::Node(node)),
+            (Some(mut node), Some(key)) => self
+                .get_group(&node)
+                .and_then(|g| g.child_with_key(&key))
+                .map(|i| {
+                    node.push(i);
+                    CursorRow::Node(node)
+                }),
+            (None, _) => None,
+        };
+    }
+
+    /// The filter in lowercase, or None if there is no filter
+    fn lowercase_filter(&self) -> Option<String> {
+        Some(self.filter.to_lowercase()).filter(|f| !f.is_empty())
+    }
+
     fn hover(
         &mut self,
         id: &NodeId<E::AutoGroup>,
@@ -539,7 +1056,84 @@ impl<E: OrganizerElement> Organizer<E> {
     }
 
     pub fn has_keyboard_priority(&self) -> bool {
-        self.edditing.is_some()
+        self.edditing.is_some() || self.edditing_element.is_some() || self.filter_input.is_focused()
+    }
+
+    /// Register a click on `identifier` and return true iff it is the second click of a double
+    /// click.
+    fn is_double_click(&mut self, identifier: &Identifier<E::Key, E::AutoGroup>) -> bool {
+        let now = Instant::now();
+        let ret = self
+            .last_click
+            .as_ref()
+            .map(|(id, time)| id == identifier && now - *time < DOUBLE_CLICK_DELAY)
+            .unwrap_or(false);
+        self.last_click = if ret {
+            None
+        } else {
+            Some((identifier.clone(), now))
+        };
+        ret
+    }
+
+    fn get_element_key(&self, identifier: &Identifier<E::Key, E::AutoGroup>) -> Option<E::Key> {
+        match identifier {
+            Identifier::Section { key } => Some(key.clone()),
+            Identifier::Group { id } => {
+                self.get_group(id).and_then(GroupContent::leaf_key).cloned()
+            }
+            Identifier::Root => None,
+        }
+    }
+
+    fn get_element_view_mut(
+        &mut self,
+        identifier: &Identifier<E::Key, E::AutoGroup>,
+    ) -> Option<&mut ElementView<E>> {
+        match identifier {
+            Identifier::Section { key } => {
+                let s_id: usize = key.section().into();
+                self.sections
+                    .get_mut(s_id)
+                    .and_then(|s| s.elements.get_mut(key))
+            }
+            Identifier::Group { id } => get_group_id(id).and_then(|id| {
+                self.groups
+                    .get_mut(id[0])
+                    .and_then(|g| g.get_leaf_view_mut(&id[1..]))
+            }),
+            Identifier::Root => None,
+        }
+    }
+
+    fn start_element_edition(&mut self, identifier: &Identifier<E::Key, E::AutoGroup>) {
+        self.stop_element_edition(false);
+        let element = self
+            .get_element_key(identifier)
+            .and_then(|k| get_element(&self.sections, &k).cloned())
+            .filter(|e| e.is_renamable());
+        if let Some(element) = element {
+            if let Some(view) = self.get_element_view_mut(identifier) {
+                view.start_edditing(element.custom_name().unwrap_or_default());
+                self.edditing_element = Some(identifier.clone());
+            }
+        }
+    }
+
+    /// Stop renaming the element being eddited. If `commit` is true, return a message
+    /// notifying the new name of the element.
+    fn stop_element_edition(&mut self, commit: bool) -> Option<OrganizerMessage<E>> {
+        let identifier = self.edditing_element.take()?;
+        let key = self.get_element_key(&identifier);
+        let name = self
+            .get_element_view_mut(&identifier)
+            .and_then(|view| view.stop_edditing());
+        if commit {
+            key.zip(name)
+                .map(|(key, name)| OrganizerMessage::NewElementName(key, name))
+        } else {
+            None
+        }
     }
 
     fn stop_edditing(&mut self) {
@@ -601,6 +1195,8 @@ impl<E: OrganizerElement> Organizer<E> {
     pub fn read_tree(&mut self, tree: &OrganizerTree<E::Key>) -> bool {
         if self.last_read_tree != tree {
             self.last_read_tree = tree;
+            let cursor_in_tree = matches!(self.cursor, Some(CursorRow::Node(NodeId::TreeId(_))));
+            let cursor_anchor = self.cursor_anchor();
             if let OrganizerTree::Node { childrens, .. } = tree {
                 self.groups = childrens
                     .iter()
@@ -612,6 +1208,14 @@ impl<E: OrganizerElement> Organizer<E> {
                 self.groups = vec![];
             }
             self.recompute_id();
+            self.forget_restored_contents();
+            if let Some(Identifier::Group { .. }) = self.edditing_element {
+                // The views of the leaves have been rebuilt
+                self.edditing_element = None;
+            }
+            if cursor_in_tree {
+                self.resolve_cursor_anchor(cursor_anchor);
+            }
             self.update_attributes();
             if let Some(group_id) = self.edditing {
                 self.start_edditing(group_id)
@@ -643,6 +1247,80 @@ impl<E: OrganizerElement> Organizer<E> {
         }
     }
 
+    /// Remove a content from the tree and keep it in the buffer of deleted contents.
+    fn delete_id(&mut self, id: &NodeId<E::AutoGroup>) {
+        let parent = get_group_id(id)
+            .filter(|id| id.len() > 1)
+            .and_then(|id| self.get_group(&NodeId::TreeId(id[..id.len() - 1].to_vec())))
+            .and_then(|g| g.get_group_id());
+        if let Some(content) = self.pop_id(id) {
+            self.deleted_contents.push(DeletedContent {
+                id: id.clone(),
+                parent,
+                content,
+            })
+        }
+    }
+
+    /// Reinsert the last deleted content at its previous location, or at the root level if its
+    /// parent group does not exist anymore. Elements cannot be at the root level, so an element
+    /// whose parent group does not exist anymore is restored in a new group.
+    ///
+    /// Return a message notifying the new tree if a content was restored.
+    pub fn restore_last_deleted(&mut self) -> Option<OrganizerMessage<E>> {
+        let deleted = self.deleted_contents.pop()?;
+        let position = get_group_id(&deleted.id)
+            .and_then(|id| id.last().cloned())
+            .unwrap_or(self.groups.len());
+        let parent_id = deleted
+            .parent
+            .and_then(|g_id| self.group_to_node.get(&g_id))
+            .and_then(get_group_id)
+            .map(|id| id.to_vec());
+        let content = match (deleted.parent, parent_id) {
+            (Some(_), Some(parent_id)) => {
+                self.groups[parent_id[0]].insert_child(&parent_id[1..], position, deleted.content)
+            }
+            (Some(_), None) => Some(deleted.content),
+            (None, _) => {
+                let position = position.min(self.groups.len());
+                self.groups.insert(position, deleted.content);
+                None
+            }
+        };
+        if let Some(content) = content {
+            // The parent group was deleted, so we restore the content at the root level
+            let content = if let Some(key) = content.leaf_key() {
+                GroupContent::new(
+                    vec![key.clone()],
+                    String::from("New group"),
+                    NodeId::TreeId(vec![]),
+                    &mut self.rng_thread,
+                )
+            } else {
+                content
+            };
+            self.groups.push(content);
+        }
+        self.recompute_id();
+        self.update_attributes();
+        Some(OrganizerMessage::NewTree(self.tree()))
+    }
+
+    /// Forget the deleted contents containing groups that are present in the tree. This happens
+    /// when the tree was modified from outside of the organizer, for example when undoing the
+    /// deletion in the design.
+    fn forget_restored_contents(&mut self) {
+        let group_to_node = &self.group_to_node;
+        self.deleted_contents.retain(|deleted| {
+            let mut group_ids = Vec::new();
+            deleted.content.collect_group_ids(&mut group_ids);
+            !group_ids
+                .iter()
+                .any(|g_id| group_to_node.contains_key(g_id))
+        });
+    }
+
     fn pop_id_no_recompute(&mut self, id: &[usize]) -> Option<GroupContent<E>> {
         let ret;
         if id.len() < 2 {
@@ -705,24 +1383,144 @@ impl<E: OrganizerElement> Organizer<E> {
         }
     }
 
+    /// Set the identifiers that are being dragged.
+    ///
+    /// If the drag starts on an element or a node that is part of the current selection, all the
+    /// selected nodes and keys are dragged together.
+    fn start_dragging(
+        &mut self,
+        identifier: &Identifier<E::Key, E::AutoGroup>,
+        selection: &BTreeSet<E::Key>,
+    ) {
+        self.dragging.clear();
+        self.dragging.insert(identifier.clone());
+        let drag_selection = match identifier {
+            Identifier::Group { id } => {
+                self.selected_nodes.contains(id)
+                    || self
+                        .get_group(id)
+                        .and_then(GroupContent::leaf_key)
+                        .map(|k| selection.contains(k))
+                        .unwrap_or(false)
+            }
+            Identifier::Section { key } => selection.contains(key),
+            Identifier::Root => false,
+        };
+        if drag_selection {
+            for id in self.selected_nodes.iter() {
+                if get_group_id(id).is_some() {
+                    self.dragging.insert(Identifier::Group { id: id.clone() });
+                }
+            }
+            // Keys that are below a dragged group are moved with their group
+            let keys_in_groups: BTreeSet<E::Key> = self
+                .dragging
+                .iter()
+                .filter_map(|d| match d {
+                    Identifier::Group { id } => Some(self.get_keys_below(id)),
+                    Identifier::Section { .. } | Identifier::Root => None,
+                })
+                .flatten()
+                .collect();
+            for key in selection.iter() {
+                if !keys_in_groups.contains(key) {
+                    self.dragging
+                        .insert(Identifier::Section { key: key.clone() });
+                }
+            }
+        }
+    }
+
     fn drag_drop(&mut self, k: &Identifier<E::Key, E::AutoGroup>) {
+        let dragging = std::mem::take(&mut self.dragging);
         match k {
             Identifier::Group { id: id_dest } => {
-                if let Some(identifer) = self.dragging.iter().next().cloned() {
-                    match identifer {
-                        id if id == k.clone() => (),
-                        Identifier::Group { id } => self.move_id(&id, id_dest),
-                        Identifier::Section { key } => {
-                            if let Some(id) = get_group_id(id_dest) {
-                                self.add_key_at(key, id)
-                            }
-                        }
+                if let Some(dest) = get_group_id(id_dest) {
+                    if !dragging.contains(k) {
+                        self.move_identifiers(&dragging, Some(dest))
                     }
                 }
             }
-            _ => (),
+            Identifier::Root => self.move_identifiers(&dragging, None),
+            Identifier::Section { .. } => (),
+        }
+    }
+
+    /// Move all the dragged groups and keys at the position of `dest`, preserving their relative
+    /// order. If `dest` is None, they are moved at the end of the top-level groups.
+    ///
+    /// Keys that are moved at the top level are wrapped in a new group.
+    ///
+    /// Nothing is moved if one of the dragged groups is an ancestor of `dest`.
+    fn move_identifiers(
+        &mut self,
+        identifiers: &BTreeSet<Identifier<E::Key, E::AutoGroup>>,
+        dest: Option<&[usize]>,
+    ) {
+        let mut sources: Vec<&[usize]> = Vec::new();
+        let mut keys = Vec::new();
+        for identifier in identifiers.iter() {
+            match identifier {
+                Identifier::Group { id } => {
+                    if let Some(id) = get_group_id(id) {
+                        sources.push(id)
+                    }
+                }
+                Identifier::Section { key } => keys.push(key.clone()),
+                Identifier::Root => (),
+            }
+        }
+        if let Some(dest) = dest {
+            if sources.iter().any(|s| is_prefix(s, dest)) {
+                log::info!("Cannot move a group inside itself");
+                return;
+            }
+        }
+        sources.sort();
+        // The descendants of a moved group are moved with it
+        let mut roots: Vec<&[usize]> = Vec::new();
+        for s in sources {
+            if !roots.iter().any(|r| is_prefix(r, s)) {
+                roots.push(s)
+            }
+        }
+        let from_top = dest
+            .and_then(|dest| roots.first().map(|s| *s <= dest))
+            .unwrap_or(false);
+
+        // Popped contents are replaced by placeholders so that the identifiers stay valid until
+        // the call to recompute_id
+        let mut contents: Vec<GroupContent<E>> = roots
+            .iter()
+            .filter_map(|s| self.pop_id_no_recompute(s))
+            .collect();
+        if !keys.is_empty() {
+            if dest.map(|d| d.len() >= 2).unwrap_or(false) {
+                contents.extend(keys.into_iter().map(|k| GroupContent::leaf(k, vec![])));
+            } else {
+                // Keys cannot be at the top level so they are wrapped in a new group
+                contents.push(GroupContent::new(
+                    keys,
+                    String::from("New group"),
+                    NodeId::TreeId(vec![]),
+                    &mut self.rng_thread,
+                ));
+            }
+        }
+        if contents.is_empty() {
+            return;
+        }
+        if let Some(dest) = dest {
+            // Insert in reverse order at the same position to preserve the relative order of the
+            // moved contents
+            for content in contents.into_iter().rev() {
+                self.add_at_id(content, dest, from_top);
+            }
+        } else {
+            self.groups.extend(contents);
         }
-        self.dragging = BTreeSet::new();
+        self.recompute_id();
+        self.must_update_tree = true;
     }
 
     pub fn merge_ids(&mut self, id0: &[usize], id1: &[usize]) {
@@ -748,37 +1546,9 @@ impl<E: OrganizerElement> Organizer<E> {
         }
     }
 
-    fn move_id(&mut self, source: &NodeId<E::AutoGroup>, dest: &NodeId<E::AutoGroup>) {
-        if let (NodeId::TreeId(source), NodeId::TreeId(dest)) = (source, dest) {
-            if source.len() < dest.len() && dest[..source.len()] == source[..] {
-                println!("prefix");
-                return;
-            }
-            let from_top = source <= dest;
-            if let Some(content) = self.pop_id_no_recompute(source) {
-                self.add_at_id(content, dest, from_top);
-                self.recompute_id()
-            }
-            self.must_update_tree = true;
-        }
-    }
-
-    fn add_key_at(&mut self, key: E::Key, dest: &[usize]) {
-        if dest.len() < 2 {
-            println!("I have not decided what to do when moving a key at the root level of the organizer");
-        } else {
-            if let Some(group) = self.groups.get_mut(dest[0]) {
-                group.add_key_at(key, &dest[1..])
-            }
-        }
-        self.recompute_id();
-        self.must_update_tree = true;
-    }
-
     /// Update the elements in the tree and return true if the tree graph was modified
     pub fn update_elements(&mut self, elements: &[E]) -> bool {
         for s in self.sections.iter_mut() {
-            s.elements.clear();
             s.content.clear();
         }
         for g in self.auto_groups.values_mut() {
@@ -796,6 +1566,19 @@ impl<E: OrganizerElement> Organizer<E> {
                     .add_element(e.clone())
             }
         }
+        for s in self.sections.iter_mut() {
+            // Keep the views of the elements that still exist so that they are not reset while
+            // being eddited
+            let content = &s.content;
+            s.elements.retain(|k, _| content.contains_key(k));
+        }
+        for s in self
+            .sections
+            .iter_mut()
+            .chain(self.auto_groups.values_mut())
+        {
+            s.update_order();
+        }
         self.auto_groups.retain(|_, g| g.elements.len() > 0);
         let ret = self.delete_useless_leaves(elements.iter().map(|e| e.key()).collect());
         self.update_attributes();
@@ -819,6 +1602,10 @@ struct Section<E: OrganizerElement> {
     expanded: bool,
     view: NodeView<E>,
     elements: BTreeMap<E::Key, ElementView<E>>,
+    /// The keys of `content` in the order in which they are displayed
+    order: Vec<E::Key>,
+    sort_criterion: SortCriterion,
+    sort_picklist: pick_list::State<SortCriterion>,
 }
 
 impl<E: OrganizerElement> Section<E> {
@@ -830,6 +1617,9 @@ impl<E: OrganizerElement> Section<E> {
             expanded: false,
             view: NodeView::new_section(),
             elements: BTreeMap::new(),
+            order: Vec::new(),
+            sort_criterion: Default::default(),
+            sort_picklist: Default::default(),
         }
     }
 
@@ -837,19 +1627,65 @@ impl<E: OrganizerElement> Section<E> {
         self.expanded = expanded
     }
 
+    fn set_sort_criterion(&mut self, criterion: SortCriterion) {
+        self.sort_criterion = criterion;
+        self.update_order();
+    }
+
+    fn update_order(&mut self) {
+        self.order = sort::sorted_keys(&self.content, self.sort_criterion);
+    }
+
     fn view(
         &mut self,
         theme: &Theme,
         selection: &BTreeSet<E::Key>,
+        filter: Option<&str>,
+        cursor: Option<&CursorRow<E::Key, E::AutoGroup>>,
     ) -> Container<OrganizerMessage<E>> {
-        let title_row = self
-            .view
-            .view(theme, &self.name, self.id.clone(), self.expanded, false);
-        let mut ret = Column::new()
-            .spacing(LEVELS_SPACING)
-            .push(Element::new(title_row));
-        if self.expanded {
-            for (e_id, e) in self.elements.iter_mut() {
+        // Sections are expanded while the organizer is filtered
+        let expanded = self.expanded || filter.is_some();
+        let title_cursor = cursor == Some(&CursorRow::Node(self.id.clone()));
+        let title_row = self.view.view(
+            theme,
+            &self.name,
+            self.id.clone(),
+            expanded,
+            false,
+            title_cursor,
+            NodeMarks::default(),
+        );
+        let title_row: Element<OrganizerMessage<E>> =
+            if let Some(section) = get_section_id(&self.id) {
+                let sort_picklist = PickList::new(
+                    &mut self.sort_picklist,
+                    SortCriterion::ALL,
+                    Some(self.sort_criterion),
+                    move |criterion| OrganizerMessage::sort_section(section, criterion),
+                );
+                Row::new()
+                    .push(Element::new(title_row))
+                    .push(sort_picklist)
+                    .into()
+            } else {
+                Element::new(title_row)
+            };
+        let mut ret = Column::new().spacing(LEVELS_SPACING).push(title_row);
+        if expanded {
+            for e_id in self.order.iter() {
+                if !element_matches(&self.content[e_id], filter) {
+                    continue;
+                }
+                let e = if let Some(e) = self.elements.get_mut(e_id) {
+                    e
+                } else {
+                    continue;
+                };
+                let element_cursor = cursor
+                    == Some(&CursorRow::Element {
+                        parent: self.id.clone(),
+                        key: e_id.clone(),
+                    });
                 ret = ret.push(
                     Row::new().push(tabulation()).push(
                         Container::new(Element::new(e.view(
@@ -857,6 +1693,7 @@ impl<E: OrganizerElement> Section<E> {
                             &self.content[e_id],
                             selection,
                             None,
+                            element_cursor,
                         )))
                         .style(theme.level(1))
                         .width(iced::Length::FillPortion(8)),
@@ -870,7 +1707,7 @@ impl<E: OrganizerElement> Section<E> {
     fn add_element(&mut self, element: E) {
         let key = element.key();
         self.content.insert(key.clone(), element);
-        self.elements.insert(key, ElementView::new());
+        self.elements.entry(key).or_insert_with(ElementView::new);
     }
 
     fn update_attributes(&mut self) {
@@ -884,6 +1721,29 @@ impl<E: OrganizerElement> Section<E> {
     fn get_all_keys(&self) -> Vec<E::Key> {
         self.content.keys().cloned().collect()
     }
+
+    fn push_visible_rows(
+        &self,
+        filter: Option<&str>,
+        rows: &mut Vec<CursorRow<E::Key, E::AutoGroup>>,
+    ) {
+        rows.push(CursorRow::Node(self.id.clone()));
+        if self.expanded || filter.is_some() {
+            for key in self.order.iter() {
+                if element_matches(&self.content[key], filter) {
+                    rows.push(CursorRow::Element {
+                        parent: self.id.clone(),
+                        key: key.clone(),
+                    })
+                }
+            }
+        }
+    }
+
+    /// Return true iff the section contains an element that matches the filter
+    fn matches_filter(&self, filter: Option<&str>) -> bool {
+        filter.is_none() || self.content.values().any(|e| element_matches(e, filter))
+    }
 }
 
 /// A data structure whose view displays information about an element.
@@ -892,6 +1752,9 @@ struct ElementView<E: OrganizerElement + 'static> {
     hovering_state: hoverable_button::State,
     button_state: button::State,
     delete_button_state: button::State,
+    eddit_button_state: button::State,
+    /// The state of the name input and the name being typed, while the element is renamed
+    name_input: Option<(text_input::State, String)>,
 }
 
 impl<E: OrganizerElement> ElementView<E> {
@@ -901,25 +1764,64 @@ impl<E: OrganizerElement> ElementView<E> {
             hovering_state: Default::default(),
             button_state: Default::default(),
             delete_button_state: Default::default(),
+            eddit_button_state: Default::default(),
+            name_input: None,
         }
     }
+
+    fn start_edditing(&mut self, name: String) {
+        let mut input = text_input::State::focused();
+        input.select_all();
+        self.name_input = Some((input, name));
+    }
+
+    fn eddit_name(&mut self, name: String) {
+        if let Some((_, current_name)) = self.name_input.as_mut() {
+            *current_name = name;
+        }
+    }
+
+    /// Stop edditing the name of the element and return the name that was typed
+    fn stop_edditing(&mut self) -> Option<String> {
+        self.name_input.take().map(|(_, name)| name)
+    }
+
     fn view(
         &mut self,
         theme: &Theme,
         element: &E,
         selection: &BTreeSet<E::Key>,
         deletable: Option<NodeId<E::AutoGroup>>,
+        cursor: bool,
     ) -> DragDropTarget<OrganizerMessage<E>, E::Key, E::AutoGroup> {
         let selected = selection.contains(&element.key());
-        let mut content = Row::new()
-            .push(Text::new(element.display_name()))
-            .push(Space::with_width(iced::Length::Fill));
         let identifier = match deletable.as_ref() {
             Some(id) => Identifier::Group { id: id.clone() },
             None => Identifier::Section {
                 key: element.key().clone(),
             },
         };
+        let edditing = self.name_input.is_some();
+        let mut content = if let Some((input, name)) = self.name_input.as_mut() {
+            Row::new().push(
+                TextInput::new(
+                    input,
+                    "New name...",
+                    name,
+                    OrganizerMessage::element_name_input,
+                )
+                .on_submit(OrganizerMessage::stop_element_eddit(true)),
+            )
+        } else {
+            Row::new().push(Text::new(element.display_name()))
+        }
+        .push(Space::with_width(iced::Length::Fill));
+        if element.is_renamable() {
+            content = content.push(
+                Button::new(&mut self.eddit_button_state, eddit_icon())
+                    .on_press(OrganizerMessage::eddit_element(identifier.clone())),
+            );
+        }
         for ad in self.attribute_displayers.iter_mut() {
             if let Some(view) = ad.view() {
                 let mut elt = BTreeSet::new();
@@ -941,7 +1843,7 @@ impl<E: OrganizerElement> ElementView<E> {
             Button::new(&mut self.button_state, content)
                 .on_press(OrganizerMessage::element_selected(element.key().clone()))
                 .width(iced::Length::Fill)
-                .style(theme.selected(selected)),
+                .style(theme.selected(selected).cursor(cursor)),
         );
         if let Some(id) = deletable {
             button = button
@@ -952,7 +1854,13 @@ impl<E: OrganizerElement> ElementView<E> {
                 .on_hovered_in(OrganizerMessage::key_hovered(element.key(), true))
                 .on_hovered_out(OrganizerMessage::key_hovered(element.key(), false))
         }
-        DragDropTarget::new(button, identifier).width(iced::Length::Fill)
+        let mut target = DragDropTarget::new(button, identifier)
+            .drop_indicator(theme.drop_indicator())
+            .width(iced::Length::Fill);
+        if edditing {
+            target = target.on_escape(OrganizerMessage::stop_element_eddit(false));
+        }
+        target
     }
 
     fn update_attributes(&mut self, attributes: &[Option<E::Attribute>]) {
@@ -973,8 +1881,12 @@ struct NodeView<E: OrganizerElement> {
     expansion_btn_state: button::State,
     title_button_hovering_state: hoverable_button::State,
     title_button_state: button::State,
+    color_button_state: button::State,
+    pin_button_state: button::State,
+    lock_button_state: button::State,
     state: GroupState,
     attribute_displayers: Vec<AttributeDisplayer<E::Attribute>>,
+    summary: Option<NodeSummary>,
 }
 
 impl<E: OrganizerElement> NodeView<E> {
@@ -983,11 +1895,15 @@ impl<E: OrganizerElement> NodeView<E> {
             expansion_btn_state: Default::default(),
             title_button_state: Default::default(),
             title_button_hovering_state: Default::default(),
+            color_button_state: Default::default(),
+            pin_button_state: Default::default(),
+            lock_button_state: Default::default(),
             state: GroupState::Iddle {
                 eddit_button: Default::default(),
                 delete_button: Default::default(),
             },
             attribute_displayers: vec![AttributeDisplayer::new(); E::all_repr().len()],
+            summary: None,
         }
     }
 
@@ -996,8 +1912,12 @@ impl<E: OrganizerElement> NodeView<E> {
             expansion_btn_state: Default::default(),
             title_button_state: Default::default(),
             title_button_hovering_state: Default::default(),
+            color_button_state: Default::default(),
+            pin_button_state: Default::default(),
+            lock_button_state: Default::default(),
             state: GroupState::NotEdditable,
             attribute_displayers: vec![],
+            summary: None,
         }
     }
 
@@ -1027,8 +1947,11 @@ impl<E: OrganizerElement> NodeView<E> {
         id: NodeId<E::AutoGroup>,
         expanded: bool,
         selected: bool,
+        cursor: bool,
+        marks: NodeMarks,
     ) -> DragDropTarget<OrganizerMessage<E>, E::Key, E::AutoGroup> {
         let level = get_group_id(&id).map(|v| v.len()).unwrap_or(0);
+        let drop_indicator = theme.drop_indicator();
         let title_row = match &mut self.state {
             GroupState::Iddle {
                 eddit_button,
@@ -1042,6 +1965,24 @@ impl<E: OrganizerElement> NodeView<E> {
                 row = row
                     .push(Text::new(name.clone()))
                     .push(Space::with_width(iced::Length::Fill));
+                if let Some(summary) = self.summary.as_ref() {
+                    row = row.push(summary_view(summary, E::weight_unit()));
+                }
+
+                row = row.push(
+                    Button::new(&mut self.color_button_state, color_icon(marks.color))
+                        .on_press(OrganizerMessage::group_color_pressed(id.clone())),
+                );
+
+                row = row.push(
+                    Button::new(&mut self.pin_button_state, pin_icon(marks.pinned))
+                        .on_press(OrganizerMessage::group_pin_pressed(id.clone())),
+                );
+
+                row = row.push(
+                    Button::new(&mut self.lock_button_state, lock_icon(marks.locked))
+                        .on_press(OrganizerMessage::group_lock_pressed(id.clone())),
+                );
 
                 row = row.push(
                     Button::new(eddit_button, eddit_icon())
@@ -1081,6 +2022,9 @@ impl<E: OrganizerElement> NodeView<E> {
                         .on_submit(OrganizerMessage::stop_eddit()),
                     )
                     .push(Space::with_width(iced::Length::Fill));
+                if let Some(summary) = self.summary.as_ref() {
+                    row = row.push(summary_view(summary, E::weight_unit()));
+                }
 
                 row = row.push(
                     Button::new(eddit_button, eddit_icon())
@@ -1114,7 +2058,8 @@ impl<E: OrganizerElement> NodeView<E> {
             theme.level_selected(level)
         } else {
             theme.level(level)
-        };
+        }
+        .cursor(cursor);
         let button = HoverableContainer::new(
             &mut self.title_button_hovering_state,
             Button::new(&mut self.title_button_state, title_row)
@@ -1126,7 +2071,9 @@ impl<E: OrganizerElement> NodeView<E> {
         .on_hovered_out(OrganizerMessage::node_hovered(id.clone(), false))
         .width(iced::Length::Fill)
         .style(theme);
-        DragDropTarget::new(button, Identifier::Group { id: id.clone() }).width(iced::Length::Fill)
+        DragDropTarget::new(button, Identifier::Group { id: id.clone() })
+            .drop_indicator(drop_indicator)
+            .width(iced::Length::Fill)
     }
 
     fn update_attributes(&mut self, attributes: &[Option<E::Attribute>]) {
@@ -1134,6 +2081,23 @@ impl<E: OrganizerElement> NodeView<E> {
             self.attribute_displayers[i].update_attribute(a.clone())
         }
     }
+
+    fn update_summary(&mut self, summary: NodeSummary) {
+        self.summary = Some(summary);
+    }
+}
+
+fn summary_view<'a, E: OrganizerElement>(
+    summary: &NodeSummary,
+    unit: &str,
+) -> Tooltip<'a, OrganizerMessage<E>> {
+    Tooltip::new(
+        Text::new(summary.text(unit))
+            .size(SUMMARY_TEXT_SIZE)
+            .color(SUMMARY_TEXT_COLOR),
+        summary.tooltip(),
+        tooltip::Position::FollowCursor,
+    )
 }
 
 enum GroupContent<E: OrganizerElement> {
@@ -1176,6 +2140,9 @@ impl<E: OrganizerElement> GroupContent<E> {
         sections: &[Section<E>],
         selection: &BTreeSet<E::Key>,
         selected_nodes: &BTreeSet<NodeId<E::AutoGroup>>,
+        filter: Option<&str>,
+        cursor: Option<&CursorRow<E::Key, E::AutoGroup>>,
+        group_marks: &GroupMarks,
     ) -> Container<OrganizerMessage<E>> {
         let level;
         let colummn = match self {
@@ -1185,6 +2152,7 @@ impl<E: OrganizerElement> GroupContent<E> {
                 childrens,
                 view,
                 id,
+                group_id,
                 ..
             } => {
                 level = if let NodeId::TreeId(id) = id {
@@ -1193,16 +2161,38 @@ impl<E: OrganizerElement> GroupContent<E> {
                     0
                 };
                 let selected = selected_nodes.contains(&id);
-                let title_row = view.view(theme, name, id.clone(), *expanded, selected);
+                // Groups are expanded while the organizer is filtered
+                let expanded = *expanded || filter.is_some();
+                let is_cursor = cursor == Some(&CursorRow::Node(id.clone()));
+                let title_row = view.view(
+                    theme,
+                    name,
+                    id.clone(),
+                    expanded,
+                    selected,
+                    is_cursor,
+                    group_marks.of_group(group_id),
+                );
                 let mut ret = Column::new()
                     .spacing(LEVELS_SPACING)
                     .push(Element::new(title_row));
-                if *expanded {
+                if expanded {
                     for c in childrens.iter_mut() {
+                        if !c.matches_filter(sections, filter) {
+                            continue;
+                        }
                         ret = ret.push(
                             Row::new().push(tabulation()).push(
-                                c.view(theme, sections, selection, selected_nodes)
-                                    .width(iced::Length::FillPortion(8)),
+                                c.view(
+                                    theme,
+                                    sections,
+                                    selection,
+                                    selected_nodes,
+                                    filter,
+                                    cursor,
+                                    group_marks,
+                                )
+                                .width(iced::Length::FillPortion(8)),
                             ),
                         )
                     }
@@ -1217,6 +2207,7 @@ impl<E: OrganizerElement> GroupContent<E> {
                 } else {
                     0
                 };
+                let is_cursor = cursor == Some(&CursorRow::Node(id.clone()));
                 if let Some(element) = get_element(sections, element) {
                     Column::new()
                         .spacing(LEVELS_SPACING)
@@ -1225,6 +2216,7 @@ impl<E: OrganizerElement> GroupContent<E> {
                             element,
                             selection,
                             Some(id.clone()),
+                            is_cursor,
                         )))
                 } else {
                     println!("WARNING viewing leaf owning deleted element");
@@ -1236,6 +2228,58 @@ impl<E: OrganizerElement> GroupContent<E> {
         Container::new(colummn).style(theme.level(level))
     }
 
+    fn push_visible_rows(
+        &self,
+        sections: &[Section<E>],
+        filter: Option<&str>,
+        rows: &mut Vec<CursorRow<E::Key, E::AutoGroup>>,
+    ) {
+        match self {
+            Self::Leaf { id, .. } => rows.push(CursorRow::Node(id.clone())),
+            Self::Node {
+                id,
+                expanded,
+                childrens,
+                ..
+            } => {
+                rows.push(CursorRow::Node(id.clone()));
+                if *expanded || filter.is_some() {
+                    for c in childrens.iter() {
+                        if c.matches_filter(sections, filter) {
+                            c.push_visible_rows(sections, filter, rows);
+                        }
+                    }
+                }
+            }
+            Self::Placeholder => (),
+        }
+    }
+
+    /// The position of the leaf owning `key` in the childrens of self
+    fn child_with_key(&self, key: &E::Key) -> Option<usize> {
+        match self {
+            Self::Node { childrens, .. } => childrens.iter().position(|c| c.is_leaf_key(key)),
+            _ => None,
+        }
+    }
+
+    /// Return true iff self is a leaf whose element matches the filter or a group containing
+    /// such a leaf.
+    fn matches_filter(&self, sections: &[Section<E>], filter: Option<&str>) -> bool {
+        if filter.is_none() {
+            return true;
+        }
+        match self {
+            Self::Leaf { element, .. } => get_element(sections, element)
+                .map(|e| element_matches(e, filter))
+                .unwrap_or(false),
+            Self::Node { childrens, .. } => {
+                childrens.iter().any(|c| c.matches_filter(sections, filter))
+            }
+            Self::Placeholder => false,
+        }
+    }
+
     fn leaf(key: E::Key, id: Vec<usize>) -> Self {
         Self::Leaf {
             id: NodeId::TreeId(id),
@@ -1495,26 +2539,69 @@ impl<E: OrganizerElement> GroupContent<E> {
         }
     }
 
-    fn add_key_at(&mut self, key: E::Key, id: &[usize]) {
-        let has_key = self.has_key_no_rec(&key);
+    fn has_key_no_rec(&self, key: &E::Key) -> bool {
+        match self {
+            Self::Node { childrens, .. } => childrens.iter().any(|c| c.is_leaf_key(key)),
+            _ => false,
+        }
+    }
+
+    fn get_leaf_view_mut(&mut self, id: &[usize]) -> Option<&mut ElementView<E>> {
+        match self {
+            Self::Leaf { view, .. } if id.is_empty() => Some(view),
+            Self::Node { childrens, .. } if !id.is_empty() => childrens
+                .get_mut(id[0])
+                .and_then(|c| c.get_leaf_view_mut(&id[1..])),
+            _ => None,
+        }
+    }
+
+    /// Insert `content` at the given position in the childrens of the group identified by `id`.
+    ///
+    /// If there is no such group, `content` is returned.
+    fn insert_child(&mut self, id: &[usize], position: usize, content: Self) -> Option<Self> {
         match self {
             Self::Node { childrens, .. } => {
-                if id.len() > 1 {
-                    childrens[id[0]].add_key_at(key, &id[1..])
-                } else if !has_key {
-                    let leaf = Self::leaf(key, vec![]);
-                    childrens.insert(id[0], leaf);
+                if id.len() > 0 {
+                    if let Some(child) = childrens.get_mut(id[0]) {
+                        child.insert_child(&id[1..], position, content)
+                    } else {
+                        Some(content)
+                    }
+                } else {
+                    let already_present = content
+                        .leaf_key()
+                        .map(|k| childrens.iter().any(|c| c.is_leaf_key(k)))
+                        .unwrap_or(false);
+                    if !already_present {
+                        let position = position.min(childrens.len());
+                        childrens.insert(position, content);
+                    }
+                    None
                 }
             }
-            Self::Leaf { .. } => unreachable!("Add key at Id on Leaf"),
-            Self::Placeholder => unreachable!("Add key Id on Placeholder"),
+            _ => Some(content),
         }
     }
 
-    fn has_key_no_rec(&self, key: &E::Key) -> bool {
+    fn collect_group_ids(&self, group_ids: &mut Vec<GroupId>) {
+        if let Self::Node {
+            childrens,
+            group_id,
+            ..
+        } = self
+        {
+            group_ids.push(*group_id);
+            for c in childrens.iter() {
+                c.collect_group_ids(group_ids);
+            }
+        }
+    }
+
+    fn leaf_key(&self) -> Option<&E::Key> {
         match self {
-            Self::Node { childrens, .. } => childrens.iter().any(|c| c.is_leaf_key(key)),
-            _ => false,
+            Self::Leaf { element, .. } => Some(element),
+            _ => None,
         }
     }
 
@@ -1567,6 +2654,7 @@ impl<E: OrganizerElement> GroupContent<E> {
                 *attributes = merge_attributes(attr_children.as_slice());
                 //}
                 view.update_attributes(attributes);
+                view.update_summary(NodeSummary::new(elements_below, sections));
             }
             Self::Placeholder => (),
         }
@@ -1713,6 +2801,55 @@ where
     }
 }
 
+/// A square filled with `color`, or an empty square if there is no color
+fn color_icon<R: Renderer>(color: Option<u32>) -> Text<R>
+where
+    <R as iced_native::text::Renderer>::Font: From<iced::Font>,
+{
+    if let Some(color) = color {
+        icon(Icon::SquareFill.into()).color(iced::Color::from_rgb8(
+            (color >> 16) as u8,
+            (color >> 8) as u8,
+            color as u8,
+        ))
+    } else {
+        icon(Icon::Square.into())
+    }
+}
+
+/// A filled pin if the group is pinned, an empty one otherwise
+fn pin_icon<R: Renderer>(pinned: bool) -> Text<R>
+where
+    <R as iced_native::text::Renderer>::Font: From<iced::Font>,
+{
+    if pinned {
+        icon(Icon::PinFill.into())
+    } else {
+        icon(Icon::Pin.into())
+    }
+}
+
+fn lock_icon<R: Renderer>(locked: bool) -> Text<R>
+where
+    <R as iced_native::text::Renderer>::Font: From<iced::Font>,
+{
+    if locked {
+        icon(Icon::LockFill.into())
+    } else {
+        icon(Icon::Unlock.into())
+    }
+}
+
+/// The color that follows `color` in GROUP_COLORS. After the last color, the group has no color
+/// anymore.
+fn next_group_color(color: Option<u32>) -> Option<u32> {
+    match color.and_then(|c| GROUP_COLORS.iter().position(|x| *x == c)) {
+        Some(n) => GROUP_COLORS.get(n + 1).cloned(),
+        None if color.is_some() => None,
+        None => GROUP_COLORS.first().cloned(),
+    }
+}
+
 fn eddit_icon<R: Renderer>() -> Text<R>
 where
     <R as iced_native::text::Renderer>::Font: From<iced::Font>,
@@ -1760,3 +2897,259 @@ fn merge_opt<T: Ord + Clone>(a: &Option<T>, b: &Option<T>) -> Option<T> {
         _ => a.clone().or(b.clone()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    struct TestKey(usize);
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, IntoPrimitive, TryFromPrimitive)]
+    #[repr(usize)]
+    enum TestSection {
+        Elements,
+    }
+
+    impl ElementKey for TestKey {
+        type Section = TestSection;
+
+        fn name(_section: TestSection) -> String {
+            "Elements".to_owned()
+        }
+
+        fn section(&self) -> TestSection {
+            TestSection::Elements
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct TestAttribute;
+
+    impl std::fmt::Display for TestAttribute {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "attribute")
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, IntoPrimitive, TryFromPrimitive)]
+    #[repr(usize)]
+    enum TestAttributeRepr {
+        Attribute,
+    }
+
+    impl OrganizerAttributeRepr for TestAttributeRepr {
+        fn all_repr() -> &'static [Self] {
+            &[TestAttributeRepr::Attribute]
+        }
+    }
+
+    impl OrganizerAttribute for TestAttribute {
+        type Repr = TestAttributeRepr;
+
+        fn repr(&self) -> TestAttributeRepr {
+            TestAttributeRepr::Attribute
+        }
+
+        fn widget(&self) -> AttributeWidget<Self> {
+            AttributeWidget::FlipButton {
+                value_if_pressed: TestAttribute,
+            }
+        }
+
+        fn char_repr(&self) -> AttributeDisplay {
+            AttributeDisplay::Text(self.to_string())
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestElement(usize);
+
+    impl OrganizerElement for TestElement {
+        type Attribute = TestAttribute;
+        type Key = TestKey;
+        type AutoGroup = String;
+
+        fn display_name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn key(&self) -> TestKey {
+            TestKey(self.0)
+        }
+
+        fn attributes(&self) -> Vec<TestAttribute> {
+            vec![]
+        }
+
+        fn auto_groups(&self) -> Vec<String> {
+            vec![]
+        }
+    }
+
+    fn root(childrens: Vec<OrganizerTree<TestKey>>) -> OrganizerTree<TestKey> {
+        OrganizerTree::Node {
+            name: "root".to_owned(),
+            childrens,
+            expanded: true,
+            id: None,
+        }
+    }
+
+    #[test]
+    fn restored_element_whose_group_was_deleted_is_put_in_a_new_group() {
+        let mut organizer: Organizer<TestElement> = Organizer::new();
+        let tree = root(vec![OrganizerTree::Node {
+            name: "group".to_owned(),
+            childrens: vec![
+                OrganizerTree::Leaf(TestKey(1)),
+                OrganizerTree::Leaf(TestKey(2)),
+            ],
+            expanded: true,
+            id: Some(rand::random()),
+        }]);
+        let _ = organizer.read_tree(&tree);
+        organizer.delete_id(&NodeId::TreeId(vec![0, 0]));
+        // The group is removed from outside of the organizer, for example by undoing its creation
+        let empty_tree = root(vec![]);
+        let _ = organizer.read_tree(&empty_tree);
+
+        assert!(organizer.restore_last_deleted().is_some());
+        let groups = match organizer.tree() {
+            OrganizerTree::Node { childrens, .. } => childrens,
+            OrganizerTree::Leaf(_) => panic!("The root of the tree is a leaf"),
+        };
+        match groups.as_slice() {
+            [OrganizerTree::Node { childrens, .. }] => {
+                assert!(matches!(
+                    childrens.as_slice(),
+                    [OrganizerTree::Leaf(TestKey(1))]
+                ))
+            }
+            _ => panic!("Expected a single group, got {:?}", groups),
+        }
+    }
+
+    fn group(name: &str, childrens: Vec<OrganizerTree<TestKey>>) -> OrganizerTree<TestKey> {
+        OrganizerTree::Node {
+            name: name.to_owned(),
+            childrens,
+            expanded: true,
+            id: Some(rand::random()),
+        }
+    }
+
+    fn leaf(key: usize) -> OrganizerTree<TestKey> {
+        OrganizerTree::Leaf(TestKey(key))
+    }
+
+    /// A compact description of the groups of `tree`, for example `A[C[1],2] B[3]`
+    fn shape(tree: &OrganizerTree<TestKey>) -> String {
+        fn node_shape(tree: &OrganizerTree<TestKey>) -> String {
+            match tree {
+                OrganizerTree::Leaf(TestKey(k)) => k.to_string(),
+                OrganizerTree::Node {
+                    name, childrens, ..
+                } => format!(
+                    "{}[{}]",
+                    name,
+                    childrens
+                        .iter()
+                        .map(node_shape)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+            }
+        }
+        match tree {
+            OrganizerTree::Node { childrens, .. } => childrens
+                .iter()
+                .map(node_shape)
+                .collect::<Vec<_>>()
+                .join(" "),
+            OrganizerTree::Leaf(_) => panic!("The root of the tree is a leaf"),
+        }
+    }
+
+    fn section(key: usize) -> Identifier<TestKey, String> {
+        Identifier::Section { key: TestKey(key) }
+    }
+
+    fn tree_id(id: &[usize]) -> Identifier<TestKey, String> {
+        Identifier::Group {
+            id: NodeId::TreeId(id.to_vec()),
+        }
+    }
+
+    #[test]
+    fn dragged_keys_already_in_the_destination_group_are_not_duplicated() {
+        let mut organizer: Organizer<TestElement> = Organizer::new();
+        let tree = root(vec![
+            group("A", vec![leaf(1), leaf(2)]),
+            group("B", vec![leaf(3)]),
+        ]);
+        let _ = organizer.read_tree(&tree);
+
+        let selection: BTreeSet<TestKey> = vec![TestKey(1), TestKey(3)].into_iter().collect();
+        organizer.start_dragging(&section(1), &selection);
+        organizer.drag_drop(&tree_id(&[1, 0]));
+        assert_eq!(shape(&organizer.tree()), "A[1,2] B[1,3]");
+    }
+
+    #[test]
+    fn dropping_a_group_in_its_own_child_does_nothing() {
+        let mut organizer: Organizer<TestElement> = Organizer::new();
+        let tree = root(vec![
+            group("A", vec![group("C", vec![leaf(1)]), leaf(2)]),
+            group("B", vec![leaf(3)]),
+        ]);
+        let _ = organizer.read_tree(&tree);
+
+        organizer.start_dragging(&tree_id(&[0]), &BTreeSet::new());
+        organizer.drag_drop(&tree_id(&[0, 0, 0]));
+        assert_eq!(shape(&organizer.tree()), "A[C[1],2] B[3]");
+        assert!(!organizer.must_update_tree);
+    }
+
+    #[test]
+    fn dropping_at_the_root_appends_groups_and_wraps_keys() {
+        let mut organizer: Organizer<TestElement> = Organizer::new();
+        let tree = root(vec![
+            group("A", vec![group("C", vec![leaf(1)]), leaf(2)]),
+            group("B", vec![leaf(3)]),
+        ]);
+        let _ = organizer.read_tree(&tree);
+
+        // The key 1 is moved with its group, and the key 3 is wrapped in a new group even though
+        // it is already in a top-level group
+        organizer.selected_nodes.insert(NodeId::TreeId(vec![0, 0]));
+        let selection: BTreeSet<TestKey> = vec![TestKey(1), TestKey(3)].into_iter().collect();
+        organizer.start_dragging(&tree_id(&[0, 0]), &selection);
+        organizer.drag_drop(&Identifier::Root);
+        let moved = organizer.tree();
+        assert_eq!(shape(&moved), "A[2] B[3] C[1] New group[3]");
+
+        // The new order survives a round trip through the tree
+        let mut reread: Organizer<TestElement> = Organizer::new();
+        let _ = reread.read_tree(&moved);
+        assert_eq!(shape(&reread.tree()), shape(&moved));
+    }
+
+    #[test]
+    fn dropping_a_group_with_its_descendant_at_the_root_moves_them_together() {
+        let mut organizer: Organizer<TestElement> = Organizer::new();
+        let tree = root(vec![
+            group("A", vec![group("C", vec![leaf(1)]), leaf(2)]),
+            group("B", vec![leaf(3)]),
+        ]);
+        let _ = organizer.read_tree(&tree);
+
+        // Dragging a group together with one of its descendants moves the descendant with it
+        organizer.selected_nodes.insert(NodeId::TreeId(vec![0]));
+        organizer.selected_nodes.insert(NodeId::TreeId(vec![0, 0]));
+        organizer.start_dragging(&tree_id(&[0]), &BTreeSet::new());
+        organizer.drag_drop(&Identifier::Root);
+        assert_eq!(shape(&organizer.tree()), "B[3] A[C[1],2]");
+    }
+}
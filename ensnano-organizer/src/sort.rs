@@ -0,0 +1,143 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! The orders in which the elements of a section can be displayed.
+
+use super::OrganizerElement;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortCriterion {
+    /// The order of the keys of the elements
+    Key,
+    /// The natural order of the display names, so that "Strand 2" comes before "Strand 10"
+    Name,
+    /// The order of the values returned by `OrganizerElement::sort_value`
+    Value,
+    /// Elements with the same color are displayed next to each other
+    Color,
+}
+
+impl SortCriterion {
+    pub const ALL: &'static [Self] = &[Self::Key, Self::Name, Self::Value, Self::Color];
+}
+
+impl Default for SortCriterion {
+    fn default() -> Self {
+        Self::Key
+    }
+}
+
+impl std::fmt::Display for SortCriterion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Key => "Default order",
+            Self::Name => "By name",
+            Self::Value => "By value",
+            Self::Color => "By color",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Return the keys of `content` in the order defined by `criterion`. Elements that are equal
+/// for `criterion` are in the order of their keys.
+pub(crate) fn sorted_keys<E: OrganizerElement>(
+    content: &BTreeMap<E::Key, E>,
+    criterion: SortCriterion,
+) -> Vec<E::Key> {
+    let mut elements: Vec<(&E::Key, &E)> = content.iter().collect();
+    match criterion {
+        SortCriterion::Key => (),
+        SortCriterion::Name => {
+            let mut named: Vec<_> = elements
+                .into_iter()
+                .map(|(k, e)| (e.display_name(), k))
+                .collect();
+            named.sort_by(|(n1, _), (n2, _)| natural_cmp(n1, n2));
+            return named.into_iter().map(|(_, k)| k.clone()).collect();
+        }
+        // Elements without a value or a color are displayed last
+        SortCriterion::Value => {
+            elements.sort_by(
+                |(_, e1), (_, e2)| match (e1.sort_value(), e2.sort_value()) {
+                    (Some(v1), Some(v2)) => v1.partial_cmp(&v2).unwrap_or(Ordering::Equal),
+                    (v1, v2) => v2.is_some().cmp(&v1.is_some()),
+                },
+            )
+        }
+        SortCriterion::Color => {
+            elements.sort_by(|(_, e1), (_, e2)| match (e1.color(), e2.color()) {
+                (Some(c1), Some(c2)) => c1.cmp(&c2),
+                (c1, c2) => c2.is_some().cmp(&c1.is_some()),
+            })
+        }
+    }
+    elements.into_iter().map(|(k, _)| k.clone()).collect()
+}
+
+/// Compare two strings, treating the sequences of digits as numbers and ignoring case.
+fn natural_cmp(s1: &str, s2: &str) -> Ordering {
+    let mut chunks1 = Chunks(s1);
+    let mut chunks2 = Chunks(s2);
+    loop {
+        match (chunks1.next(), chunks2.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(c1), Some(c2)) => {
+                let ord = compare_chunks(c1, c2);
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+}
+
+fn compare_chunks(c1: &str, c2: &str) -> Ordering {
+    let is_number = |c: &str| c.starts_with(|c: char| c.is_ascii_digit());
+    if is_number(c1) && is_number(c2) {
+        // Compare the numbers without parsing them so that there is no overflow
+        let n1 = c1.trim_start_matches('0');
+        let n2 = c2.trim_start_matches('0');
+        n1.len().cmp(&n2.len()).then_with(|| n1.cmp(n2))
+    } else {
+        c1.to_lowercase().cmp(&c2.to_lowercase())
+    }
+}
+
+/// An iterator over the maximal substrings of a string that are either made only of digits or
+/// without any digit.
+struct Chunks<'a>(&'a str);
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let first = self.0.chars().next()?;
+        let digit = first.is_ascii_digit();
+        let end = self
+            .0
+            .find(|c: char| c.is_ascii_digit() != digit)
+            .unwrap_or(self.0.len());
+        let (chunk, rest) = self.0.split_at(end);
+        self.0 = rest;
+        Some(chunk)
+    }
+}
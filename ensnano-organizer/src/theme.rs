@@ -69,6 +69,12 @@ pub struct Theme {
     gradient: ColorGradient,
     text_color: Color,
     border_color: Color,
+    /// The background color of the row under the keyboard cursor
+    cursor_color: Color,
+    /// The color of the border drawn around the target on which a dragged element would be
+    /// dropped
+    drop_indicator_color: Color,
+    dragging: bool,
     max_level: usize,
 }
 
@@ -79,6 +85,8 @@ pub(super) struct ThemeLevel {
     border_color: Color,
     gradient_value: f32,
     selected: bool,
+    cursor: bool,
+    cursor_color: Color,
 }
 
 pub(super) struct ThemeSelection {
@@ -86,6 +94,30 @@ pub(super) struct ThemeSelection {
     text_color: Color,
     selected_color: Color,
     border_color: Color,
+    cursor: bool,
+    cursor_color: Color,
+}
+
+fn cursor_background(cursor: bool, color: Color) -> Option<iced::Background> {
+    if cursor {
+        Some(iced::Background::Color(color))
+    } else {
+        None
+    }
+}
+
+impl ThemeSelection {
+    /// Highlight the row if it is under the keyboard cursor
+    pub(super) fn cursor(self, cursor: bool) -> Self {
+        Self { cursor, ..self }
+    }
+}
+
+impl ThemeLevel {
+    /// Highlight the row if it is under the keyboard cursor
+    pub(super) fn cursor(self, cursor: bool) -> Self {
+        Self { cursor, ..self }
+    }
 }
 
 impl iced::button::StyleSheet for ThemeSelection {
@@ -98,7 +130,7 @@ impl iced::button::StyleSheet for ThemeSelection {
         };
         ButtonStyle {
             shadow_offset: iced::Vector::new(0., 0.),
-            background: None,
+            background: cursor_background(self.cursor, self.cursor_color),
             border_radius: 0.,
             border_width,
             border_color: self.border_color,
@@ -125,7 +157,7 @@ impl iced::button::StyleSheet for ThemeLevel {
         let border_width = if self.selected { 4. } else { 0. };
         ButtonStyle {
             shadow_offset: iced::Vector::new(0., 0.),
-            background: None,
+            background: cursor_background(self.cursor, self.cursor_color),
             border_radius: 0.,
             border_width,
             border_color: self.border_color,
@@ -160,6 +192,15 @@ impl iced::container::StyleSheet for ThemeLevel {
 }
 
 impl Theme {
+    pub(super) fn set_dragging(&mut self, dragging: bool) {
+        self.dragging = dragging;
+    }
+
+    /// The color of the drop indicator if an element is being dragged
+    pub(super) fn drop_indicator(&self) -> Option<Color> {
+        Some(self.drop_indicator_color).filter(|_| self.dragging)
+    }
+
     pub(super) fn level(&self, n: usize) -> ThemeLevel {
         ThemeLevel {
             gradient: self.gradient.clone(),
@@ -167,6 +208,8 @@ impl Theme {
             border_color: self.border_color.clone(),
             gradient_value: n as f32 / self.max_level as f32,
             selected: false,
+            cursor: false,
+            cursor_color: self.cursor_color,
         }
     }
 
@@ -177,6 +220,8 @@ impl Theme {
             border_color: self.border_color.clone(),
             gradient_value: n as f32 / self.max_level as f32,
             selected: true,
+            cursor: false,
+            cursor_color: self.cursor_color,
         }
     }
 
@@ -186,6 +231,8 @@ impl Theme {
             text_color: self.text_color.clone(),
             selected_color: self.border_color.clone(),
             border_color: self.border_color.clone(),
+            cursor: false,
+            cursor_color: self.cursor_color,
         }
     }
 
@@ -194,6 +241,9 @@ impl Theme {
             gradient: moon_gradient(),
             text_color: Color::WHITE,
             border_color: Color::from_rgb8(0x83, 0x1a, 0x1a),
+            cursor_color: Color::from_rgba8(0xff, 0xff, 0xff, 0.2),
+            drop_indicator_color: Color::from_rgb8(0xf1, 0xc4, 0x0f),
+            dragging: false,
             max_level: 5,
         }
     }
@@ -203,6 +253,9 @@ impl Theme {
             gradient: grey_gradient(),
             text_color: Color::WHITE,
             border_color: Color::from_rgb8(0x83, 0x1a, 0x1a),
+            cursor_color: Color::from_rgba8(0xff, 0xff, 0xff, 0.2),
+            drop_indicator_color: Color::from_rgb8(0xf1, 0xc4, 0x0f),
+            dragging: false,
             max_level: 5,
         }
     }
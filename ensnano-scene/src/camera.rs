@@ -18,18 +18,32 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::maths_3d;
 use super::{controller::Data as SurfaceInfoProvider, ClickMode, PhySize, Stereography};
 use ensnano_design::{ultraviolet, SurfaceInfo, SurfacePoint};
+use ensnano_interactor::graphics::CameraMode;
 use ensnano_utils::winit;
 use std::cell::RefCell;
 use std::f32::consts::{FRAC_PI_2, PI};
 use std::rc::Rc;
 use std::time::Duration;
-use ultraviolet::{Mat3, Mat4, Rotor3, Vec3};
+use ultraviolet::{Mat3, Mat4, Rotor3, Slerp, Vec3};
 use winit::dpi::PhysicalPosition;
 use winit::event::*;
 
 const DEFAULT_DIST_TO_SURFACE: f32 = 20.;
 const SURFACE_ABSCISSA_FACTOR: f64 = 1.;
 const SURFACE_REVOLUTION_ANGLE_FACTOR: f64 = 1.;
+/// The factor by which the height of the orthographic view is multiplied when scrolling one line
+const ORTHOGRAPHIC_ZOOM_STEP: f32 = 1.1;
+/// The maximal angle between the direction of the camera and the horizon in turntable mode
+const TURNTABLE_MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+/// The initial speed of the camera in fly mode, in nm per second
+const DEFAULT_FLY_SPEED: f32 = 10.;
+/// The factor by which the speed of the camera in fly mode is multiplied when scrolling one line
+const FLY_SPEED_STEP: f32 = 1.25;
+const MIN_FLY_SPEED: f32 = 0.5;
+const MAX_FLY_SPEED: f32 = 500.;
+/// The time, in seconds, needed by the camera to reach approximately 63% of its target speed in
+/// fly mode
+const FLY_SMOOTHING_TIME: f32 = 0.1;
 
 #[derive(Debug, Clone)]
 pub struct Camera {
@@ -79,7 +93,7 @@ impl Camera {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// This structure holds the information needed to compute the projection matrix.
 pub struct Projection {
     aspect: f32,
@@ -88,6 +102,10 @@ pub struct Projection {
     znear: f32,
     zfar: f32,
     pub stereographic_zoom: f32,
+    /// True iff the projection is orthographic
+    orthographic: bool,
+    /// The height of the area seen by the camera when the projection is orthographic
+    orthographic_height: f32,
 }
 
 pub type ProjectionPtr = Rc<RefCell<Projection>>;
@@ -100,6 +118,8 @@ impl Projection {
             znear,
             zfar,
             stereographic_zoom: ensnano_interactor::consts::DEFAULT_STEREOGRAPHIC_ZOOM,
+            orthographic: false,
+            orthographic_height: 2. * (fovy / 2.).tan(),
         }
     }
 
@@ -109,6 +129,24 @@ impl Projection {
 
     /// Computes the projection matrix.
     pub fn calc_matrix(&self) -> Mat4 {
+        if self.orthographic {
+            let half_height = self.orthographic_height / 2.;
+            let half_width = half_height * self.aspect;
+            ultraviolet::projection::rh_yup::orthographic_wgpu_dx(
+                -half_width,
+                half_width,
+                -half_height,
+                half_height,
+                self.znear,
+                self.zfar,
+            )
+        } else {
+            self.perspective_matrix()
+        }
+    }
+
+    /// Computes the perspective projection matrix, regardless of the current projection mode.
+    pub fn perspective_matrix(&self) -> Mat4 {
         ultraviolet::projection::rh_yup::perspective_wgpu_dx(
             self.fovy,
             self.aspect,
@@ -117,6 +155,35 @@ impl Projection {
         )
     }
 
+    pub fn is_orthographic(&self) -> bool {
+        self.orthographic
+    }
+
+    /// Switch between the perspective and the orthographic projection.
+    ///
+    /// When switching to the orthographic projection, the height of the view is chosen so that
+    /// objects at distance `dist` from the camera keep the same size on the screen.
+    pub fn set_orthographic(&mut self, orthographic: bool, dist: f32) {
+        if orthographic {
+            self.orthographic_height = 2. * dist * (self.fovy / 2.).tan();
+        }
+        self.orthographic = orthographic;
+    }
+
+    /// Multiply the height of the orthographic view by `factor`.
+    pub fn scale_orthographic_height(&mut self, factor: f32) {
+        self.orthographic_height *= factor;
+    }
+
+    /// Half the height of the area seen by the camera, at distance `dist` from the camera.
+    pub fn half_height_at(&self, dist: f32) -> f32 {
+        if self.orthographic {
+            self.orthographic_height / 2.
+        } else {
+            dist * (self.fovy / 2.).tan()
+        }
+    }
+
     pub fn get_fovy(&self) -> f32 {
         self.fovy
     }
@@ -135,6 +202,8 @@ struct ConstrainedRotation {
     theta: f32,
     horizon_x: Vec3,
     horizon_z: Vec3,
+    /// If true, phi stays in [-TURNTABLE_MAX_PITCH, TURNTABLE_MAX_PITCH]
+    clamp_pitch: bool,
 }
 
 impl ConstrainedRotation {
@@ -178,6 +247,7 @@ impl ConstrainedRotation {
                 theta: theta * upside_down,
                 horizon_x: upside_down * horizon_x,
                 horizon_z,
+                clamp_pitch: false,
             }
         } else {
             let horizon_x = current_rotor.reversed() * Vec3::unit_x();
@@ -206,16 +276,51 @@ impl ConstrainedRotation {
                 theta,
                 horizon_x,
                 horizon_z,
+                clamp_pitch: false,
             }
         }
     }
 
+    /// A rotation around the fixed up axis `horizon_z.cross(horizon_x)`, that never rolls the
+    /// camera and that stops before the poles.
+    fn turntable(current_rotor: Rotor3, horizon_x: Vec3, horizon_z: Vec3) -> Self {
+        let up = horizon_z.cross(horizon_x);
+        let current_pos_on_sphere = (current_rotor.reversed() * Vec3::unit_z()).normalized();
+
+        let theta = if current_pos_on_sphere.cross(up).mag() > 1e-3 {
+            current_pos_on_sphere
+                .dot(horizon_x)
+                .atan2(current_pos_on_sphere.dot(horizon_z))
+        } else {
+            // We are on a pole, the right vector of the camera is used to determine theta
+            let current_right = current_rotor.reversed() * Vec3::unit_x();
+            current_right
+                .dot(-horizon_z)
+                .atan2(current_right.dot(horizon_x))
+        };
+        let phi = current_pos_on_sphere
+            .dot(up)
+            .asin()
+            .clamp(-TURNTABLE_MAX_PITCH, TURNTABLE_MAX_PITCH);
+
+        Self {
+            phi,
+            theta,
+            horizon_x,
+            horizon_z,
+            clamp_pitch: true,
+        }
+    }
+
     fn add_angle_xz(&mut self, delta_xz: f32) {
         self.theta += delta_xz;
     }
 
     fn add_angle_yz(&mut self, delta_yz: f32) {
         self.phi += delta_yz;
+        if self.clamp_pitch {
+            self.phi = self.phi.clamp(-TURNTABLE_MAX_PITCH, TURNTABLE_MAX_PITCH);
+        }
     }
 
     fn compute_rotor(&self) -> Rotor3 {
@@ -264,6 +369,74 @@ pub struct CameraController {
     surface_point: Option<SurfacePoint>,
     surface_point0: Option<SurfacePoint>,
     dist_to_surface: Option<f32>,
+    camera_mode: CameraMode,
+    transition: Option<CameraTransition>,
+    /// The state of the fly-through navigation, `None` when not in fly mode
+    fly_state: Option<FlyState>,
+    fly_speed: f32,
+}
+
+/// The state of the movement keys and the velocity of the camera in fly mode.
+#[derive(Default)]
+struct FlyState {
+    forward: bool,
+    backward: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+    /// The velocity of the camera, expressed in the camera's basis (right, up, direction)
+    velocity: Vec3,
+}
+
+impl FlyState {
+    /// The direction in which the movement keys ask to move, in the camera's basis
+    fn key_direction(&self) -> Vec3 {
+        let axis = |positive: bool, negative: bool| positive as u8 as f32 - negative as u8 as f32;
+        Vec3::new(
+            axis(self.right, self.left),
+            axis(self.up, self.down),
+            axis(self.forward, self.backward),
+        )
+    }
+
+    fn is_moving(&self) -> bool {
+        self.key_direction().mag_sq() > 0. || self.velocity.mag_sq() > 0.
+    }
+}
+
+/// A smooth movement of the camera towards a target position and orientation.
+struct CameraTransition {
+    start_position: Vec3,
+    start_rotor: Rotor3,
+    target_position: Vec3,
+    target_rotor: Rotor3,
+    elapsed: Duration,
+    duration: Duration,
+}
+
+impl CameraTransition {
+    /// Advance the transition by `dt` and return the new position and orientation of the
+    /// camera, and wether the transition is over.
+    fn step(&mut self, dt: Duration) -> (Vec3, Rotor3, bool) {
+        self.elapsed += dt;
+        let t = (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.);
+        // ease in and out so that the camera does not start or stop abruptly
+        let t = t * t * (3. - 2. * t);
+        let position = self.start_position + (self.target_position - self.start_position) * t;
+        let rotor = self.start_rotor.slerp(self.target_rotor, t).normalized();
+        (position, rotor, self.elapsed >= self.duration)
+    }
+}
+
+/// The horizon vectors (x, z) of a turntable camera mode. The up axis of the turntable is
+/// `z.cross(x)`.
+fn turntable_horizon(camera_mode: CameraMode) -> Option<(Vec3, Vec3)> {
+    match camera_mode {
+        CameraMode::Free => None,
+        CameraMode::TurntableY => Some((Vec3::unit_x(), Vec3::unit_z())),
+        CameraMode::TurntableZ => Some((Vec3::unit_x(), -Vec3::unit_y())),
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -319,6 +492,31 @@ impl CameraController {
             surface_point: None,
             surface_point0: None,
             dist_to_surface: None,
+            camera_mode: CameraMode::default(),
+            transition: None,
+            fly_state: None,
+            fly_speed: DEFAULT_FLY_SPEED,
+        }
+    }
+
+    pub fn is_turntable(&self) -> bool {
+        self.camera_mode.is_turntable()
+    }
+
+    pub fn get_camera_mode(&self) -> CameraMode {
+        self.camera_mode
+    }
+
+    /// Set the camera mode. When switching to a turntable mode, the camera is rotated so that it
+    /// does not roll.
+    pub fn set_camera_mode(&mut self, camera_mode: CameraMode) {
+        self.camera_mode = camera_mode;
+        if let Some((horizon_x, horizon_z)) = turntable_horizon(camera_mode) {
+            let rotor =
+                ConstrainedRotation::turntable(self.camera.borrow().rotor, horizon_x, horizon_z)
+                    .compute_rotor();
+            let position = self.camera.borrow().position;
+            self.teleport_camera(position, rotor);
         }
     }
 
@@ -328,6 +526,21 @@ impl CameraController {
         } else {
             0.0
         };
+        if state == ElementState::Pressed
+            && matches!(
+                key,
+                VirtualKeyCode::Up
+                    | VirtualKeyCode::Down
+                    | VirtualKeyCode::Left
+                    | VirtualKeyCode::Right
+                    | VirtualKeyCode::H
+                    | VirtualKeyCode::L
+                    | VirtualKeyCode::J
+                    | VirtualKeyCode::K
+            )
+        {
+            self.cancel_transition();
+        }
         match key {
             VirtualKeyCode::Up => {
                 self.amount_up = amount;
@@ -386,6 +599,12 @@ impl CameraController {
     }
 
     pub fn is_moving(&self) -> bool {
+        self.is_moved_by_keys_or_scroll()
+            || self.transition.is_some()
+            || self.fly_state.as_ref().map(FlyState::is_moving) == Some(true)
+    }
+
+    fn is_moved_by_keys_or_scroll(&self) -> bool {
         self.amount_down > 0.
             || self.amount_up > 0.
             || self.amount_right > 0.
@@ -398,6 +617,72 @@ impl CameraController {
         self.amount_right = 0.;
         self.amount_up = 0.;
         self.amount_down = 0.;
+        if let Some(fly_state) = self.fly_state.as_mut() {
+            *fly_state = Default::default();
+        }
+    }
+
+    pub fn is_fly_mode(&self) -> bool {
+        self.fly_state.is_some()
+    }
+
+    pub fn set_fly_mode(&mut self, fly_mode: bool) {
+        self.cancel_transition();
+        self.stop_camera_movement();
+        self.fly_state = if fly_mode {
+            Some(Default::default())
+        } else {
+            None
+        };
+    }
+
+    /// Update the state of the movement keys of the fly mode. Return true iff `key` is one of
+    /// them.
+    pub fn process_fly_keyboard(&mut self, key: VirtualKeyCode, state: ElementState) -> bool {
+        let pressed = state == ElementState::Pressed;
+        if let Some(fly_state) = self.fly_state.as_mut() {
+            match key {
+                VirtualKeyCode::W => fly_state.forward = pressed,
+                VirtualKeyCode::S => fly_state.backward = pressed,
+                VirtualKeyCode::A => fly_state.left = pressed,
+                VirtualKeyCode::D => fly_state.right = pressed,
+                VirtualKeyCode::E => fly_state.up = pressed,
+                VirtualKeyCode::Q => fly_state.down = pressed,
+                _ => return false,
+            }
+        } else {
+            return false;
+        }
+        if pressed {
+            self.cancel_transition();
+        }
+        true
+    }
+
+    /// Move the camera according to the movement keys of the fly mode. The velocity of the camera
+    /// converges smoothly towards the speed asked by the keys.
+    fn fly(&mut self, dt: Duration) {
+        if let Some(fly_state) = self.fly_state.as_mut().filter(|s| s.is_moving()) {
+            let dt = dt.as_secs_f32();
+            let key_direction = fly_state.key_direction();
+            let target_velocity = if key_direction.mag_sq() > 0. {
+                key_direction.normalized() * self.fly_speed
+            } else {
+                Vec3::zero()
+            };
+            let alpha = 1. - (-dt / FLY_SMOOTHING_TIME).exp();
+            fly_state.velocity += (target_velocity - fly_state.velocity) * alpha;
+            if key_direction.mag_sq() == 0. && fly_state.velocity.mag() < 1e-2 {
+                fly_state.velocity = Vec3::zero();
+            }
+            let velocity = fly_state.velocity;
+            let mut camera = self.camera.borrow_mut();
+            let translation = velocity.x * camera.right_vec()
+                + velocity.y * camera.up_vec()
+                + velocity.z * camera.direction();
+            camera.position += translation * dt;
+            self.cam0 = camera.clone();
+        }
     }
 
     pub fn set_pivot_point(&mut self, point: Option<FiniteVec3>) {
@@ -435,6 +720,7 @@ impl CameraController {
     }
 
     pub fn process_mouse(&mut self, mouse_dx: f64, mouse_dy: f64) {
+        self.cancel_transition();
         self.mouse_horizontal = -mouse_dx as f32;
         self.mouse_vertical = -mouse_dy as f32;
         self.processed_move = true;
@@ -447,15 +733,24 @@ impl CameraController {
         y_cursor: f32,
         sensitivity: f32,
     ) {
-        self.x_scroll = x_cursor;
-        self.y_scroll = y_cursor;
-        self.scroll = match delta {
+        self.cancel_transition();
+        let scroll = match delta {
             // I'm assuming a line is about 100 pixels
             MouseScrollDelta::LineDelta(_, scroll) => scroll.min(1.).max(-1.),
             MouseScrollDelta::PixelDelta(PhysicalPosition { y: scroll, .. }) => {
                 scroll.signum() as f32
             }
-        } * sensitivity;
+        };
+        if self.is_fly_mode() {
+            // In fly mode, scrolling changes the speed of the camera
+            self.fly_speed = (self.fly_speed * FLY_SPEED_STEP.powf(scroll))
+                .max(MIN_FLY_SPEED)
+                .min(MAX_FLY_SPEED);
+            return;
+        }
+        self.x_scroll = x_cursor;
+        self.y_scroll = y_cursor;
+        self.scroll = scroll * sensitivity;
     }
 
     pub fn update_stereographic_zoom(&mut self, delta: &MouseScrollDelta) {
@@ -516,6 +811,13 @@ impl CameraController {
                 10.
             };
 
+            // With the orthographic projection, the displacement does not depend on the distance
+            // to the camera
+            let scale = {
+                let projection = self.projection.borrow();
+                projection.half_height_at(scale) / (projection.get_fovy() / 2.).tan()
+            };
+
             let right_vec =
                 self.camera.borrow().right_vec() * scale * self.projection.borrow().get_ratio();
             let up_vec = self.camera.borrow().up_vec() * scale;
@@ -648,6 +950,8 @@ impl CameraController {
                     + self.dist_to_surface.unwrap_or(DEFAULT_DIST_TO_SURFACE)
                         * Vec3::unit_z().rotated_by(surface_info.local_frame);
                 self.teleport_camera(cam_pos, surface_info.local_frame.reversed());
+            } else if self.projection.borrow().is_orthographic() {
+                self.zoom_orthographic();
             } else {
                 let mut camera = self.camera.borrow_mut();
                 camera.position += scrollward * self.scroll * self.speed * 3.0;
@@ -657,6 +961,17 @@ impl CameraController {
         self.scroll = 0.;
     }
 
+    /// Scale the height of the orthographic view according to self.scroll, keeping the point
+    /// under the cursor at the same position on the screen.
+    fn zoom_orthographic(&mut self) {
+        let (origin_before, _) = self.ray(self.x_scroll, self.y_scroll);
+        self.projection
+            .borrow_mut()
+            .scale_orthographic_height(ORTHOGRAPHIC_ZOOM_STEP.powf(-self.scroll));
+        let (origin_after, _) = self.ray(self.x_scroll, self.y_scroll);
+        self.camera.borrow_mut().position += origin_before - origin_after;
+    }
+
     pub(super) fn update_camera(
         &mut self,
         dt: Duration,
@@ -670,12 +985,54 @@ impl CameraController {
                 ClickMode::TranslateCam => self.translate_camera(surface_info_provider),
             }
         }
-        if self.is_moving() {
-            self.move_camera(dt, modifier, surface_info_provider);
+        if self.transition.is_some() {
+            self.step_transition(dt);
+        } else {
+            self.fly(dt);
+            if self.is_moved_by_keys_or_scroll() {
+                self.move_camera(dt, modifier, surface_info_provider);
+            }
+        }
+    }
+
+    /// Move the camera towards `position` and `rotation` over `duration` instead of teleporting
+    /// it. The transition is canceled by any user input moving the camera.
+    pub fn smooth_teleport_camera(&mut self, position: Vec3, rotation: Rotor3, duration: Duration) {
+        if duration.as_secs_f32() <= 0. {
+            self.teleport_camera(position, rotation);
+            return;
+        }
+        let camera = self.camera.borrow();
+        self.transition = Some(CameraTransition {
+            start_position: camera.position,
+            start_rotor: camera.rotor,
+            target_position: position,
+            target_rotor: rotation,
+            elapsed: Duration::from_secs(0),
+            duration,
+        });
+    }
+
+    fn step_transition(&mut self, dt: Duration) {
+        if let Some(mut transition) = self.transition.take() {
+            let (position, rotor, over) = transition.step(dt);
+            self.teleport_camera(position, rotor);
+            if !over {
+                self.transition = Some(transition);
+            }
+        }
+    }
+
+    /// Stop the current transition, leaving the camera where it currently is.
+    fn cancel_transition(&mut self) {
+        if self.transition.take().is_some() {
+            self.last_rotor = self.camera.borrow().rotor;
+            self.cam0 = self.camera.borrow().clone();
         }
     }
 
     pub fn init_movement(&mut self, along_surface: bool) {
+        self.cancel_transition();
         self.processed_move = false;
         if !along_surface {
             log::info!("Setting info to None");
@@ -684,11 +1041,16 @@ impl CameraController {
         }
     }
 
+    /// Initialise the rotation used to swing the camera. In turntable mode, `force_horizon` is
+    /// ignored.
     pub fn init_constrained_rotation(&mut self, force_horizon: bool) {
-        self.current_constrained_rotation = Some(ConstrainedRotation::init(
-            self.camera.borrow().rotor,
-            force_horizon,
-        ));
+        let rotor = self.camera.borrow().rotor;
+        self.current_constrained_rotation =
+            if let Some((horizon_x, horizon_z)) = turntable_horizon(self.camera_mode) {
+                Some(ConstrainedRotation::turntable(rotor, horizon_x, horizon_z))
+            } else {
+                Some(ConstrainedRotation::init(rotor, force_horizon))
+            };
     }
 
     pub fn end_constrained_rotation(&mut self) {
@@ -714,6 +1076,7 @@ impl CameraController {
     }
 
     pub fn teleport_camera(&mut self, position: Vec3, rotation: Rotor3) {
+        self.transition = None;
         let mut camera = self.camera.borrow_mut();
         camera.position = position;
         camera.rotor = rotation;
@@ -728,10 +1091,15 @@ impl CameraController {
     }
 
     pub fn set_surface_point(&mut self, info: SurfaceInfo) {
+        self.animate_to_surface_point(info, Duration::from_secs(0))
+    }
+
+    /// Set the surface point and move the camera towards it over `duration`.
+    pub fn animate_to_surface_point(&mut self, info: SurfaceInfo, duration: Duration) {
         let cam_pos =
             info.position + DEFAULT_DIST_TO_SURFACE * Vec3::unit_z().rotated_by(info.local_frame);
         self.dist_to_surface = self.dist_to_surface.or(Some(DEFAULT_DIST_TO_SURFACE));
-        self.teleport_camera(cam_pos, info.local_frame.reversed());
+        self.smooth_teleport_camera(cam_pos, info.local_frame.reversed(), duration);
         self.surface_point0 = Some(info.point.clone());
         self.surface_point = Some(info.point);
     }
@@ -787,12 +1155,17 @@ impl CameraController {
     /// Swing the camera arrond `self.pivot_point`. Assumes that the pivot_point is where the
     /// camera points at.
     pub fn swing(&mut self, x: f64, y: f64) {
+        self.cancel_transition();
         let new_angle_yz = -((y + 1.).rem_euclid(2.) - 1.) as f32 * PI;
         let new_angle_xz = ((x + 1.).rem_euclid(2.) - 1.) as f32 * PI;
         let delta_angle_yz = new_angle_yz - self.free_yz_angle;
         let delta_angle_xz = new_angle_xz - self.free_xz_angle;
         if let Some(pivot) = self.pivot_point {
             self.rotate_camera_around(delta_angle_xz, delta_angle_yz, pivot);
+        } else if self.is_turntable() || self.is_fly_mode() {
+            // Without pivot, the camera turns around itself
+            let position = FiniteVec3(self.camera.borrow().position);
+            self.rotate_camera_around(delta_angle_xz, delta_angle_yz, position);
         } else {
             self.small_rotate_camera(new_angle_xz, new_angle_yz, None);
         }
@@ -908,10 +1281,11 @@ impl CameraController {
         self.cam0.rotor = self.camera.borrow().rotor;
     }
 
-    pub fn center_camera(&mut self, center: Vec3) {
+    /// Move the camera over `duration` so that it looks at `center`.
+    pub fn center_camera(&mut self, center: Vec3, duration: Duration) {
         let new_position = center - 5. * self.camera.borrow().direction();
         let orientation = self.camera.borrow().rotor;
-        self.teleport_camera(new_position, orientation);
+        self.smooth_teleport_camera(new_position, orientation, duration);
     }
 
     pub fn ray(&self, x_ndc: f32, y_ndc: f32) -> (Vec3, Vec3) {
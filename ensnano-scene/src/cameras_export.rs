@@ -0,0 +1,115 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Export of one png picture per saved camera of the design.
+//!
+//! The pictures are rendered one per frame so that the progress of the export can be displayed.
+
+use ensnano_interactor::application::Camera3D;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+pub(super) struct CamerasExport {
+    /// The directory in which the pictures are written
+    directory: PathBuf,
+    /// The name and position of the cameras from which the pictures are taken
+    cameras: Vec<(String, Camera3D)>,
+    nb_exported: usize,
+    aborted: bool,
+    /// The file names that have already been used during this export
+    used_names: HashSet<String>,
+    /// The camera of the scene before the export started. It is restored once all the pictures
+    /// have been rendered.
+    pub initial_camera: Camera3D,
+}
+
+impl CamerasExport {
+    pub fn new(
+        directory: PathBuf,
+        cameras: Vec<(String, Camera3D)>,
+        initial_camera: Camera3D,
+    ) -> Self {
+        Self {
+            directory,
+            cameras,
+            nb_exported: 0,
+            aborted: false,
+            used_names: HashSet::new(),
+            initial_camera,
+        }
+    }
+
+    /// Return the camera from which the next picture must be taken, and the path to which the
+    /// picture must be written.
+    pub fn next_picture(&mut self) -> Option<(Camera3D, PathBuf)> {
+        let (name, camera) = self.cameras.get(self.nb_exported)?.clone();
+        self.nb_exported += 1;
+        let file_name = self.file_name(&name);
+        Some((camera, self.directory.join(file_name)))
+    }
+
+    /// Do not render the remaining pictures.
+    pub fn abort(&mut self) {
+        self.aborted = true;
+        self.nb_exported = self.cameras.len();
+    }
+
+    pub fn was_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The fraction of the pictures that have been rendered.
+    pub fn progress(&self) -> f32 {
+        if self.cameras.is_empty() {
+            1.
+        } else {
+            self.nb_exported as f32 / self.cameras.len() as f32
+        }
+    }
+
+    /// A file name derived from the name of the camera, that has not been used yet during this
+    /// export.
+    fn file_name(&mut self, camera_name: &str) -> String {
+        let sanitized: String = camera_name
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let base = if sanitized.is_empty() {
+            String::from("camera")
+        } else {
+            sanitized
+        };
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !self.used_names.insert(name.clone()) {
+            name = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        format!("{name}.png")
+    }
+}
@@ -20,16 +20,21 @@ use super::{
     camera, ultraviolet, Duration, ElementSelector, HandleDir, SceneElement, Stereography, ViewPtr,
     WidgetRotationMode as RotationMode,
 };
+use crate::data::StrandEnd;
 use crate::{PhySize, PhysicalPosition, WindowEvent};
 use ensnano_design::grid::{GridId, GridObject, GridPosition, HelixGridPosition};
 use ensnano_design::{
     BezierPathId, BezierPlaneId, BezierVertex, BezierVertexId, Nucl, SurfaceInfo, SurfacePoint,
 };
 use ensnano_interactor::consts::*;
-use ensnano_interactor::Selection;
+use ensnano_interactor::graphics::CameraMode;
+use ensnano_interactor::keymap::{KeyAction, KeyScope, Keymap};
+use ensnano_interactor::snapping::{self, AngleStep, TranslationStep};
+use ensnano_interactor::{ActionMode, Selection};
 use ensnano_utils::winit::event::*;
 use std::cell::RefCell;
 use std::ops::Deref;
+use std::sync::Arc;
 use ultraviolet::{Rotor3, Vec2, Vec3};
 
 use super::AppState;
@@ -59,6 +64,9 @@ pub struct Controller<S: AppState> {
     data: DataPtr,
     /// The event that modify the camera are forwarded to the camera_controller
     camera_controller: CameraController,
+    /// The controller of the camera of the viewport that is not under the cursor, when the 3D
+    /// view is split
+    inactive_camera_controller: Option<CameraController>,
     /// The size of the window
     window_size: PhySize,
     /// The size of the drawing area
@@ -71,6 +79,46 @@ pub struct Controller<S: AppState> {
     stereography: Option<Stereography>,
     /// The origin of the two points bezier curve being created.
     bezier_curve_origin: Option<HelixGridPosition>,
+    /// The elements that can be picked by clicking again at the position of the last click.
+    pick_cycle: RefCell<Option<PickCycle>>,
+    /// The keyboard shortcuts
+    keymap: Arc<Keymap>,
+    /// The rotation angle, in degrees, that is being typed
+    angle_entry: String,
+}
+
+/// The maximal number of characters of a typed rotation angle.
+const MAX_ANGLE_ENTRY_LEN: usize = 8;
+
+/// The maximal distance, in pixels, between two clicks for the second one to pick the element
+/// that is behind the one picked by the first click.
+const PICK_CYCLE_TOLERANCE: f64 = 4.;
+
+/// The elements under the cursor at the position of the last click.
+///
+/// Repeated clicks at the same position cycle through these elements, from the front-most one to
+/// the farthest one.
+struct PickCycle {
+    position: PhysicalPosition<f64>,
+    candidates: Vec<SceneElement>,
+    index: usize,
+}
+
+impl PickCycle {
+    fn is_near(&self, position: PhysicalPosition<f64>) -> bool {
+        let dx = self.position.x - position.x;
+        let dy = self.position.y - position.y;
+        dx * dx + dy * dy <= PICK_CYCLE_TOLERANCE * PICK_CYCLE_TOLERANCE
+    }
+
+    fn current(&self) -> Option<SceneElement> {
+        self.candidates.get(self.index).cloned()
+    }
+
+    fn next(&mut self) -> Option<SceneElement> {
+        self.index = (self.index + 1) % self.candidates.len().max(1);
+        self.current()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -83,19 +131,61 @@ pub enum Consequence {
         doubled: bool,
     },
     Translation(HandleDir, f64, f64, WidgetTarget),
+    /// Move the selected helices or grids by `step` in `direction`, given in the basis of the
+    /// widget
+    Nudge {
+        direction: Vec3,
+        step: TranslationStep,
+    },
     MovementEnded,
-    Rotation(f64, f64, WidgetTarget),
+    /// Rotate the selection so that it follows the cursor. If a step is given, the angle of the
+    /// rotation is a multiple of this step.
+    Rotation(f64, f64, WidgetTarget, Option<AngleStep>),
+    /// The rotation angle, in degrees, that is being typed has changed
+    AngleEntry(String),
+    /// Rotate the selection by the typed angle, in degrees, around the highlighted axis of the
+    /// rotation widget
+    TypedRotation(String),
     InitRotation(RotationMode, f64, f64, WidgetTarget),
     InitTranslation(f64, f64, WidgetTarget),
+    /// Start modifying the roll of the helix on which the roll widget is
+    InitRoll(f64, f64),
+    /// Set the roll of the helix on which the roll widget is so that it follows the cursor. If
+    /// `snap` is true, the variation of the roll is a multiple of the twist between two
+    /// consecutive nucleotides.
+    Roll {
+        x: f64,
+        y: f64,
+        snap: bool,
+    },
     Swing(f64, f64),
     Tilt(f64, f64),
     Nothing,
     ToggleWidget,
     BuildEnded,
+    /// The handle of a strand end was released. This ends the building of the strand, and makes
+    /// the strand cyclic if its ends meet.
+    StrandEndReleased,
     Building(isize),
     Undo,
     Redo,
     Candidate(Option<super::SceneElement>),
+    /// The element under the cursor in ligation mode, and the end of the strand with which it
+    /// can be ligated if it is a strand end.
+    LigationCandidate {
+        element: Option<super::SceneElement>,
+        partner: Option<Nucl>,
+    },
+    /// Cut the strand `strand_id` after `nucl`
+    Nick {
+        nucl: Nucl,
+        strand_id: usize,
+    },
+    /// Join the 3' end of the strand `prime5_id` to the 5' end of the strand `prime3_id`
+    Ligate {
+        prime5_id: usize,
+        prime3_id: usize,
+    },
     PivotElement(Option<super::SceneElement>),
     ElementSelected(Option<super::SceneElement>, bool),
     MoveFreeXover(Option<super::SceneElement>, Vec3),
@@ -130,6 +220,12 @@ pub enum Consequence {
         /// new path is being created
         path: Option<BezierPathId>,
     },
+    /// Insert a vertex in the segment of a bezier path that starts at vertex `after_vertex`
+    InsertBezierVertex {
+        path_id: BezierPathId,
+        after_vertex: usize,
+        position: Vec2,
+    },
     MoveBezierVertex {
         x: f32,
         y: f32,
@@ -152,7 +248,53 @@ pub enum Consequence {
         new_vector: Vec2,
     },
     ReverseSurfaceDirection,
+    /// Reflect the selected helices or grids across the plane spanned by the y and z axes of the
+    /// widget
+    ReflectSelection,
+    /// Move the pivot of the current group to the pointed nucleotide, or to the center of the
+    /// selection if no nucleotide is pointed
+    PlacePivot,
+    /// Replace the fog of the 3D view by a tight fog around the pivot
+    FocusFog,
+    RemoveFog,
     SetRevolutionAxisPosition(f32),
+    /// The user is drawing a selection rectangle. The corners are given in normalized coordinates
+    DrawingRectangleSelection(PhysicalPosition<f64>, PhysicalPosition<f64>),
+    /// The user has released a selection rectangle. The corners are given in normalized
+    /// coordinates
+    ReleasedRectangleSelection(PhysicalPosition<f64>, PhysicalPosition<f64>),
+}
+
+impl Consequence {
+    /// If the consequence modifies the design, a description of the modification, used to
+    /// explain why it is not performed while a simulation is running.
+    pub fn design_edit(&self) -> Option<&'static str> {
+        match self {
+            Self::XoverAtempt(..) | Self::QuickXoverAttempt { .. } | Self::EndFreeXover => {
+                Some("make crossovers")
+            }
+            Self::Translation(_, _, _, WidgetTarget::Object)
+            | Self::Rotation(_, _, WidgetTarget::Object, _)
+            | Self::TypedRotation(_)
+            | Self::Nudge { .. }
+            | Self::ObjectTranslated { .. } => Some("move elements"),
+            Self::Roll { .. } => Some("roll helices"),
+            Self::InitBuild(_) | Self::Building(_) | Self::StrandEndReleased => {
+                Some("build strands")
+            }
+            Self::Nick { .. } => Some("cut strands"),
+            Self::Ligate { .. } => Some("ligate strands"),
+            Self::BuildHelix { .. } => Some("add helices"),
+            Self::Paste(_) => Some("paste"),
+            Self::CreateBezierVertex { .. }
+            | Self::InsertBezierVertex { .. }
+            | Self::MoveBezierVertex { .. }
+            | Self::MoveBezierCorner { .. }
+            | Self::MoveBezierTengent { .. } => Some("edit bezier paths"),
+            Self::ReflectSelection => Some("reflect the selection"),
+            _ => None,
+        }
+    }
 }
 
 enum TransistionConsequence {
@@ -173,6 +315,7 @@ impl<S: AppState> Controller<S> {
         data: DataPtr,
         window_size: PhySize,
         area_size: PhySize,
+        keymap: Arc<Keymap>,
     ) -> Self {
         let camera_controller = {
             let view = view.borrow();
@@ -182,6 +325,7 @@ impl<S: AppState> Controller<S> {
             view,
             data,
             camera_controller,
+            inactive_camera_controller: None,
             window_size,
             area_size,
             current_modifiers: ModifiersState::empty(),
@@ -189,9 +333,18 @@ impl<S: AppState> Controller<S> {
             state: automata::initial_state(),
             stereography: None,
             bezier_curve_origin: None,
+            pick_cycle: RefCell::new(None),
+            keymap,
+            angle_entry: String::new(),
         }
     }
 
+    /// Forget the elements that were under the cursor at the last click. The next click will
+    /// pick the front-most element under the cursor.
+    pub fn reset_pick_cycle(&self) {
+        self.pick_cycle.borrow_mut().take();
+    }
+
     pub fn set_setreography(&mut self, stereography: Option<Stereography>) {
         self.stereography = stereography;
     }
@@ -204,14 +357,43 @@ impl<S: AppState> Controller<S> {
         }
     }
 
+    /// Control the camera of the second viewport of a split view.
+    pub fn split_camera(&mut self, camera: camera::CameraPtr, projection: camera::ProjectionPtr) {
+        let mut controller = CameraController::new(4.0, camera, projection);
+        controller.set_camera_mode(self.camera_controller.get_camera_mode());
+        self.inactive_camera_controller = Some(controller);
+    }
+
+    /// Forget the camera of the viewport that is not under the cursor.
+    pub fn merge_cameras(&mut self) {
+        self.inactive_camera_controller = None;
+    }
+
+    /// Exchange the controllers of the cameras of a split view, when the cursor moves to the
+    /// other viewport.
+    pub fn swap_cameras(&mut self) {
+        if let Some(inactive) = self.inactive_camera_controller.as_mut() {
+            self.camera_controller.stop_camera_movement();
+            std::mem::swap(&mut self.camera_controller, inactive);
+            self.reset_pick_cycle();
+        }
+    }
+
     /// Replace the camera by a new one.
     pub fn teleport_camera(&mut self, position: Vec3, rotation: Rotor3) {
         self.camera_controller.teleport_camera(position, rotation);
         self.end_movement();
     }
 
-    pub fn set_surface_point(&mut self, info: SurfaceInfo) {
-        self.camera_controller.set_surface_point(info);
+    /// Move the camera smoothly to a new position and orientation.
+    pub fn smooth_teleport_camera(&mut self, position: Vec3, rotation: Rotor3, duration: Duration) {
+        self.camera_controller
+            .smooth_teleport_camera(position, rotation, duration);
+    }
+
+    pub fn set_surface_point(&mut self, info: SurfaceInfo, duration: Duration) {
+        self.camera_controller
+            .animate_to_surface_point(info, duration);
         self.end_movement();
     }
 
@@ -232,8 +414,8 @@ impl<S: AppState> Controller<S> {
     }
 
     /// Keep the camera orientation and make it face a given point.
-    pub fn center_camera(&mut self, center: Vec3) {
-        self.camera_controller.center_camera(center)
+    pub fn center_camera(&mut self, center: Vec3, duration: Duration) {
+        self.camera_controller.center_camera(center, duration)
     }
 
     pub fn check_timers(&mut self) -> Consequence {
@@ -345,33 +527,59 @@ impl<S: AppState> Controller<S> {
             ..
         } = event
         {
-            let csq = match *key {
-                VirtualKeyCode::A if *state == ElementState::Pressed => {
-                    Consequence::AlignWithStereo
-                }
-                VirtualKeyCode::C if *state == ElementState::Pressed => Consequence::CheckXovers,
-                VirtualKeyCode::Z
-                    if ctrl(&self.current_modifiers) && *state == ElementState::Pressed =>
-                {
-                    Consequence::Undo
-                }
-                VirtualKeyCode::R
-                    if ctrl(&self.current_modifiers) && *state == ElementState::Pressed =>
-                {
-                    Consequence::Redo
-                }
-                VirtualKeyCode::Q => Consequence::PivotCenter,
-                VirtualKeyCode::Space if *state == ElementState::Pressed => {
-                    Consequence::ToggleWidget
+            let angle_entry = Some(*state)
+                .filter(|s| *s == ElementState::Pressed && !ctrl(&self.current_modifiers))
+                .filter(|_| {
+                    snapping::can_enter_rotation_angle(
+                        app_state.get_selection(),
+                        app_state.get_action_mode().0,
+                    )
+                })
+                .and_then(|_| self.type_angle(*key));
+            let nudge_direction = snapping::nudge_direction(*key)
+                .filter(|_| *state == ElementState::Pressed)
+                .filter(|_| {
+                    snapping::can_nudge_selection(
+                        app_state.get_selection(),
+                        app_state.get_action_mode().0,
+                    )
+                });
+            let csq = if let Some(csq) = angle_entry {
+                csq
+            } else if let Some(direction) = nudge_direction {
+                Consequence::Nudge {
+                    direction,
+                    step: TranslationStep::from_modifiers(&self.current_modifiers),
                 }
-                VirtualKeyCode::W if *state == ElementState::Pressed => {
-                    Consequence::ReverseSurfaceDirection
-                }
-                _ => {
-                    if self.camera_controller.process_keyboard(*key, *state) {
-                        Consequence::CameraMoved
-                    } else {
-                        Consequence::Nothing
+            } else if self.camera_controller.process_fly_keyboard(*key, *state) {
+                Consequence::CameraMoved
+            } else {
+                let action = Some(*state)
+                    .filter(|s| *s == ElementState::Pressed)
+                    .and_then(|_| {
+                        self.keymap
+                            .action(*key, &self.current_modifiers, KeyScope::Scene)
+                    });
+                match action {
+                    Some(KeyAction::AlignWithStereo) => Consequence::AlignWithStereo,
+                    Some(KeyAction::CheckXovers) => Consequence::CheckXovers,
+                    Some(KeyAction::Undo) => Consequence::Undo,
+                    Some(KeyAction::Redo) => Consequence::Redo,
+                    Some(KeyAction::PivotCenter) => Consequence::PivotCenter,
+                    Some(KeyAction::ToggleWidget) => Consequence::ToggleWidget,
+                    Some(KeyAction::ReverseSurfaceDirection) => {
+                        Consequence::ReverseSurfaceDirection
+                    }
+                    Some(KeyAction::ReflectSelection) => Consequence::ReflectSelection,
+                    Some(KeyAction::PlacePivot) => Consequence::PlacePivot,
+                    Some(KeyAction::FocusFog) => Consequence::FocusFog,
+                    Some(KeyAction::RemoveFog) => Consequence::RemoveFog,
+                    _ => {
+                        if self.camera_controller.process_keyboard(*key, *state) {
+                            Consequence::CameraMoved
+                        } else {
+                            Consequence::Nothing
+                        }
                     }
                 }
             };
@@ -395,6 +603,30 @@ impl<S: AppState> Controller<S> {
         transition.consequences
     }
 
+    /// Add the character typed by `key` to the rotation angle being typed, or validate the
+    /// angle if `key` is Enter.
+    fn type_angle(&mut self, key: VirtualKeyCode) -> Option<Consequence> {
+        if let Some(c) = snapping::angle_entry_char(key) {
+            if self.angle_entry.len() < MAX_ANGLE_ENTRY_LEN {
+                self.angle_entry.push(c);
+            }
+            Some(Consequence::AngleEntry(self.angle_entry.clone()))
+        } else if snapping::is_angle_entry_validation(key) && !self.angle_entry.is_empty() {
+            Some(Consequence::TypedRotation(std::mem::take(
+                &mut self.angle_entry,
+            )))
+        } else {
+            None
+        }
+    }
+
+    /// Forget the rotation angle being typed, if any, and return true if there was one.
+    pub fn cancel_angle_entry(&mut self) -> bool {
+        let cancelled = !self.angle_entry.is_empty();
+        self.angle_entry.clear();
+        cancelled
+    }
+
     fn transition_consequence(&mut self, csq: TransistionConsequence) {
         match csq {
             TransistionConsequence::Nothing => (),
@@ -453,6 +685,9 @@ impl<S: AppState> Controller<S> {
         self.window_size = window_size;
         self.area_size = area_size;
         self.camera_controller.resize(area_size);
+        if let Some(inactive) = self.inactive_camera_controller.as_mut() {
+            inactive.resize(area_size);
+        }
         // the view needs the window size to build a depth texture
         self.view
             .borrow_mut()
@@ -465,7 +700,9 @@ impl<S: AppState> Controller<S> {
 
     fn init_movement(&mut self, along_surface: bool) {
         self.camera_controller.init_movement(along_surface);
-        if !ctrl(&self.current_modifiers) {
+        if self.camera_controller.is_turntable() || self.camera_controller.is_fly_mode() {
+            self.camera_controller.init_constrained_rotation(true)
+        } else if !ctrl(&self.current_modifiers) {
             self.camera_controller
                 .init_constrained_rotation(!self.current_modifiers.alt())
         }
@@ -493,6 +730,34 @@ impl<S: AppState> Controller<S> {
         self.shift_cam();
     }
 
+    /// True if the translation handles must scale the selected external 3D object instead of
+    /// moving it.
+    pub fn is_scaling(&self) -> bool {
+        ctrl(&self.current_modifiers)
+    }
+
+    pub fn is_fly_mode(&self) -> bool {
+        self.camera_controller.is_fly_mode()
+    }
+
+    /// Enter or leave the fly mode, in which the camera is moved with the WASD and QE keys.
+    pub fn set_fly_mode(&mut self, fly_mode: bool) {
+        self.camera_controller.set_fly_mode(fly_mode);
+        self.camera_controller.set_pivot_point(None);
+        self.end_movement();
+    }
+
+    pub fn set_camera_mode(&mut self, camera_mode: CameraMode) {
+        self.camera_controller.set_camera_mode(camera_mode);
+        if let Some(inactive) = self.inactive_camera_controller.as_mut() {
+            inactive.set_camera_mode(camera_mode);
+        }
+    }
+
+    pub fn is_turntable(&self) -> bool {
+        self.camera_controller.is_turntable()
+    }
+
     pub fn continuous_tilt(&mut self, angle: f32) {
         self.camera_controller.continuous_tilt(angle);
     }
@@ -536,8 +801,14 @@ impl<S: AppState> Controller<S> {
         }
     }
 
-    pub fn get_icon(&self) -> Option<ensnano_interactor::CursorIcon> {
-        self.state.borrow().cursor()
+    /// The cursor icon of the current state, or the one of the tool in use if the state does not
+    /// have one.
+    pub fn get_icon(&self, action_mode: ActionMode) -> Option<ensnano_interactor::CursorIcon> {
+        self.state.borrow().cursor().or(match action_mode {
+            ActionMode::Cut => Some(ensnano_interactor::CursorIcon::Crosshair),
+            ActionMode::Ligate => Some(ensnano_interactor::CursorIcon::Alias),
+            _ => None,
+        })
     }
 }
 
@@ -556,6 +827,10 @@ pub(super) trait Data {
         non_phantom: bool,
     ) -> Option<(Nucl, usize)>;
     fn get_nucl_position(&self, nucl: Nucl, d_id: usize) -> Option<Vec3>;
+    fn element_to_nick(&self, element: &Option<SceneElement>) -> Option<(Nucl, usize)>;
+    fn element_to_strand_end(&self, element: &Option<SceneElement>) -> Option<StrandEnd>;
+    fn get_ligation_partner(&self, end: StrandEnd) -> Option<StrandEnd>;
+    fn get_strand_end_handle_nucl(&self, element: &Option<SceneElement>) -> Option<Nucl>;
     fn attempt_xover(
         &self,
         source: &Option<SceneElement>,
@@ -123,6 +123,14 @@ impl<S: AppState> ControllerState<S> for NormalState {
                 let element = context.convert_grid_to_grid_disc(element);
                 Transition::consequence(Consequence::PasteCandidate(element))
             }
+            WindowEvent::CursorMoved { .. } if context.get_action_mode() == ActionMode::Ligate => {
+                self.mouse_position = context.cursor_position;
+                let element = context.get_element_under_cursor();
+                let partner = context
+                    .get_ligation(&element)
+                    .map(|(_, partner)| partner.nucl);
+                Transition::consequence(Consequence::LigationCandidate { element, partner })
+            }
             WindowEvent::CursorMoved { .. } => {
                 self.mouse_position = context.cursor_position;
                 let element = context.get_element_under_cursor();
@@ -167,10 +175,46 @@ impl<S: AppState> ControllerState<S> for NormalState {
                 state: ElementState::Pressed,
                 button: MouseButton::Left,
                 ..
-            } => {
+            } if context.get_action_mode() == ActionMode::Cut => {
+                let element = context.pick_element_under_cursor();
+                if let Some((nucl, strand_id)) = context.get_nick(&element) {
+                    Transition::consequence(Consequence::Nick { nucl, strand_id })
+                } else {
+                    Transition::nothing()
+                }
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if context.get_action_mode() == ActionMode::Ligate => {
                 let element = context.get_element_under_cursor();
+                if let Some((end, partner)) = context.get_ligation(&element) {
+                    let (prime5_id, prime3_id) = end.ligation_ids(&partner);
+                    Transition::consequence(Consequence::Ligate {
+                        prime5_id,
+                        prime3_id,
+                    })
+                } else {
+                    Transition::nothing()
+                }
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let element = context.pick_element_under_cursor();
                 log::info!("Clicked on {:?}", element);
-                if let Some(SceneElement::PlaneCorner {
+                if let Some(nucl) = context.get_strand_end_handle_nucl(&element) {
+                    let click_info = ClickInfo::new(MouseButton::Left, context.cursor_position);
+                    return Transition {
+                        new_state: Some(Box::new(dragging_state::moving_strand_end(
+                            click_info, nucl,
+                        ))),
+                        consequences: Consequence::Nothing,
+                    };
+                } else if let Some(SceneElement::PlaneCorner {
                     plane_id,
                     corner_type,
                 }) = element
@@ -409,6 +453,19 @@ impl<S: AppState> ControllerState<S> for NormalState {
                                     ),
                                 }
                             }
+                            ROLL_CIRCLE_ID => {
+                                let click_info =
+                                    ClickInfo::new(MouseButton::Left, context.cursor_position);
+                                Transition {
+                                    new_state: Some(Box::new(dragging_state::rolling_helix(
+                                        click_info,
+                                    ))),
+                                    consequences: Consequence::InitRoll(
+                                        normalized_cursor_position.x,
+                                        normalized_cursor_position.y,
+                                    ),
+                                }
+                            }
                             _ => {
                                 println!("WARNING UNEXPECTED WIDGET ID");
                                 Transition::nothing()
@@ -428,6 +485,22 @@ impl<S: AppState> ControllerState<S> for NormalState {
                             Transition::nothing()
                         }
                     }
+                    None if context.is_editing_bezier_path()
+                        && context.get_bezier_segment_under_cursor().is_some() =>
+                    {
+                        let (path_id, after_vertex, position) =
+                            context.get_bezier_segment_under_cursor().unwrap();
+                        let new_state = PointAndClicking::clicking_bezier_segment(
+                            context.cursor_position,
+                            path_id,
+                            after_vertex,
+                            position,
+                        );
+                        Transition {
+                            new_state: Some(Box::new(new_state)),
+                            consequences: Consequence::Nothing,
+                        }
+                    }
                     None if context.is_editing_bezier_path() => {
                         // path_id is either:
                         // - the id of the currently selected bezier vertex, in
@@ -482,6 +555,18 @@ impl<S: AppState> ControllerState<S> for NormalState {
                             consequences: Consequence::Nothing,
                         }
                     }
+                    _ if context.get_modifiers().shift()
+                        && context.get_action_mode() == ActionMode::Normal =>
+                    {
+                        let new_state = PointAndClicking::selecting_with_rectangle(
+                            context.cursor_position,
+                            element,
+                        );
+                        Transition {
+                            new_state: Some(Box::new(new_state)),
+                            consequences: Consequence::Nothing,
+                        }
+                    }
                     _ => {
                         let adding =
                             context.get_modifiers().shift() || ctrl(context.get_modifiers());
@@ -375,6 +375,54 @@ impl DraggingTransitionTable for TiltingCamera {
 
 dragging_state_constructor! {tilting_camera, TiltingCamera}
 
+/// The user is drawing a rectangle to select all the nucleotides inside it
+///
+/// Cursor movements move the corner of the rectangle opposite to the clicked position
+#[derive(Default)]
+pub(super) struct SelectingRectangle {
+    /// The normalized positions of the clicked corner and of the current corner
+    corners: Option<(PhysicalPosition<f64>, PhysicalPosition<f64>)>,
+}
+
+impl DraggingTransitionTable for SelectingRectangle {
+    fn description() -> &'static str {
+        "Selecting Rectangle"
+    }
+
+    fn on_cursor_moved<S: AppState>(
+        &mut self,
+        cursor: DraggedCursor<'_, '_, S>,
+    ) -> Option<Consequence> {
+        let clicked_corner = cursor.context.normalize_position(cursor.clicked_position);
+        let current_corner = cursor.normalized_position;
+        self.corners = Some((clicked_corner, current_corner));
+        Some(Consequence::DrawingRectangleSelection(
+            clicked_corner,
+            current_corner,
+        ))
+    }
+
+    fn on_button_released(&self) -> Option<Consequence> {
+        self.corners
+            .map(|(c1, c2)| Consequence::ReleasedRectangleSelection(c1, c2))
+    }
+
+    no_csq_leaving_or_entering! {}
+
+    fn cursor() -> Option<ensnano_interactor::CursorIcon> {
+        Some(CursorIcon::Crosshair)
+    }
+}
+
+pub(super) fn selecting_rectangle(click: ClickInfo) -> DraggingState<SelectingRectangle> {
+    DraggingState {
+        current_cursor_position: click.current_position,
+        clicked_button: click.button,
+        clicked_position: click.clicked_position,
+        transition_table: Default::default(),
+    }
+}
+
 /// The user is making a cross-over
 ///
 /// Cursor movement set the xover target
@@ -500,6 +548,57 @@ pub(super) fn building_strands(
     }
 }
 
+/// The user is moving the end of a strand with its handle
+pub(super) struct MovingStrandEnd {
+    to_initialize: Option<Nucl>,
+}
+
+impl DraggingTransitionTable for MovingStrandEnd {
+    fn description() -> &'static str {
+        "Moving strand end"
+    }
+
+    fn on_cursor_moved<S: AppState>(
+        &mut self,
+        cursor: DraggedCursor<'_, '_, S>,
+    ) -> Option<Consequence> {
+        if let Some(nucl) = self.to_initialize.take() {
+            Some(Consequence::InitBuild(vec![nucl]))
+        } else {
+            cursor
+                .context
+                .get_new_build_position()
+                .map(|p| Consequence::Building(p))
+        }
+    }
+
+    fn on_button_released(&self) -> Option<Consequence> {
+        Some(Consequence::StrandEndReleased)
+    }
+
+    no_csq_leaving_or_entering!();
+
+    fn cursor() -> Option<ensnano_interactor::CursorIcon> {
+        Some(CursorIcon::Grabbing)
+    }
+}
+
+pub(super) fn moving_strand_end(
+    click_info: ClickInfo,
+    nucl: Nucl,
+) -> DraggingState<MovingStrandEnd> {
+    let transition_table = MovingStrandEnd {
+        to_initialize: Some(nucl),
+    };
+
+    DraggingState {
+        current_cursor_position: click_info.current_position,
+        clicked_position: click_info.current_position,
+        clicked_button: click_info.button,
+        transition_table,
+    }
+}
+
 pub(super) struct TranslatingWidget {
     direction: HandleDir,
     translation_target: WidgetTarget,
@@ -632,6 +731,7 @@ impl DraggingTransitionTable for RotatingWidget {
             cursor.normalized_position.x,
             cursor.normalized_position.y,
             self.target,
+            AngleStep::from_modifiers(cursor.context.get_modifiers()),
         ))
     }
 
@@ -654,6 +754,33 @@ pub(super) fn rotating_widget(
     }
 }
 
+pub(super) struct RollingHelix;
+
+impl DraggingTransitionTable for RollingHelix {
+    fn description() -> &'static str {
+        "Rolling helix"
+    }
+
+    fn on_button_released(&self) -> Option<Consequence> {
+        Some(Consequence::MovementEnded)
+    }
+
+    no_csq_leaving_or_entering!();
+
+    fn on_cursor_moved<S: AppState>(
+        &mut self,
+        cursor: DraggedCursor<'_, '_, S>,
+    ) -> Option<Consequence> {
+        Some(Consequence::Roll {
+            x: cursor.normalized_position.x,
+            y: cursor.normalized_position.y,
+            snap: cursor.context.get_modifiers().shift(),
+        })
+    }
+}
+
+dragging_state_constructor! {rolling_helix, RollingHelix}
+
 pub(super) enum MovingBezierVertex {
     New {
         plane_id: BezierPlaneId,
@@ -22,6 +22,9 @@ use super::*;
 use ensnano_design::{Axis, BezierPlaneIntersection};
 
 const REVOLUTION_AXIS_WIDTH: f32 = 1.;
+/// The maximal distance between the cursor and a segment of a bezier path for the segment to be
+/// considered under the cursor
+const BEZIER_SEGMENT_PICKING_DISTANCE: f32 = 1.;
 
 /// The context in which an event took place.
 pub struct EventContext<'a, S: AppState> {
@@ -69,9 +72,62 @@ impl<'a, S: AppState> EventContext<'a, S> {
     }
 
     pub fn get_element_under_cursor(&mut self) -> Option<SceneElement> {
+        {
+            let mut pick_cycle = self.controller.pick_cycle.borrow_mut();
+            match pick_cycle.as_ref() {
+                Some(cycle) if cycle.is_near(self.cursor_position) => return cycle.current(),
+                _ => *pick_cycle = None,
+            }
+        }
         self.pixel_reader.set_selected_id(self.cursor_position)
     }
 
+    /// Return the element that is picked by a click at the current cursor position.
+    ///
+    /// Repeated clicks at the same position cycle through the elements under the cursor, ordered
+    /// by depth.
+    pub fn pick_element_under_cursor(&mut self) -> Option<SceneElement> {
+        if let Some(cycle) = self
+            .controller
+            .pick_cycle
+            .borrow_mut()
+            .as_mut()
+            .filter(|cycle| cycle.is_near(self.cursor_position))
+        {
+            return cycle.next();
+        }
+        let candidates = self.get_candidates_under_cursor();
+        let element = candidates.first().cloned();
+        *self.controller.pick_cycle.borrow_mut() = Some(PickCycle {
+            position: self.cursor_position,
+            candidates,
+            index: 0,
+        });
+        element
+    }
+
+    /// Return the elements under the cursor. The first one is the front-most element, it is
+    /// followed by the nucleotides hit by the ray shot from the cursor, ordered by depth, and by
+    /// the other elements drawn near the cursor.
+    fn get_candidates_under_cursor(&mut self) -> Vec<SceneElement> {
+        let mut candidates = self.pixel_reader.get_candidates(self.cursor_position);
+        if self.controller.stereography.is_none() {
+            let (origin, direction) = self.shoot_ray(self.cursor_position);
+            let on_ray = self
+                .controller
+                .data
+                .borrow()
+                .get_nucl_elements_on_ray(origin, direction);
+            let insertion_point = candidates.len().min(1);
+            for element in on_ray.into_iter().rev() {
+                if !candidates.contains(&element) {
+                    candidates.insert(insertion_point, element);
+                }
+            }
+        }
+        candidates
+    }
+
     pub fn shoot_ray(&self, point: PhysicalPosition<f64>) -> (Vec3, Vec3) {
         let normalized_point = self.normalize_position(point);
         self.controller
@@ -132,6 +188,28 @@ impl<'a, S: AppState> EventContext<'a, S> {
         self.controller.data.borrow().can_start_builder(element)
     }
 
+    /// If `element` is a nucleotide or a bond of a strand, return the nucleotide after which the
+    /// strand must be nicked and the identifier of the strand.
+    pub fn get_nick(&self, element: &Option<SceneElement>) -> Option<(Nucl, usize)> {
+        self.controller.data.borrow().element_to_nick(element)
+    }
+
+    /// If `element` is a strand end that can be ligated with the end of an other strand, return
+    /// these two ends.
+    pub fn get_ligation(&self, element: &Option<SceneElement>) -> Option<(StrandEnd, StrandEnd)> {
+        let data = self.controller.data.borrow();
+        let end = data.element_to_strand_end(element)?;
+        data.get_ligation_partner(end).map(|partner| (end, partner))
+    }
+
+    /// If `element` is the handle of a strand end, return the nucleotide on which it is drawn.
+    pub fn get_strand_end_handle_nucl(&self, element: &Option<SceneElement>) -> Option<Nucl> {
+        self.controller
+            .data
+            .borrow()
+            .get_strand_end_handle_nucl(element)
+    }
+
     /// Project the current cursor position on an axis
     pub fn get_projection_on_axis(&self, axis: Axis<'_>) -> Option<isize> {
         let normalized_cursor_position = self.normalized_cursor_position();
@@ -245,6 +323,32 @@ impl<'a, S: AppState> EventContext<'a, S> {
         )
     }
 
+    /// If the cursor is on a segment of a bezier path drawn on the bezier plane under the cursor,
+    /// return the identifier of the path, the vertex at which the segment starts and the
+    /// projection of the cursor on the plane.
+    pub fn get_bezier_segment_under_cursor(&self) -> Option<(BezierPathId, usize, Vec2)> {
+        let (plane_id, intersection) = self.get_plane_under_cursor()?;
+        let reader = self.app_state.get_design_reader();
+        let point = reader
+            .get_bezier_planes()
+            .get(&plane_id)?
+            .space_position_of_point2d(intersection.position());
+        reader
+            .get_bezier_paths()?
+            .iter()
+            .filter_map(|(path_id, path)| {
+                let (segment, distance) = path.closest_segment(point)?;
+                let on_plane = reader
+                    .get_bezier_vertex(*path_id, segment)
+                    .map(|v| v.plane_id == plane_id)
+                    .unwrap_or(false);
+                (on_plane && distance < BEZIER_SEGMENT_PICKING_DISTANCE)
+                    .then_some((*path_id, segment, distance))
+            })
+            .min_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2))
+            .map(|(path_id, segment, _)| (path_id, segment, intersection.position()))
+    }
+
     pub fn cursor_is_on_revolution_axis(&self) -> bool {
         self.get_plane_under_cursor()
             .and_then(|(_, intersection)| {
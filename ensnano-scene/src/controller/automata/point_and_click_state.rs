@@ -65,12 +65,16 @@ impl<S: AppState> Default for OptionalTransitionPtr<S> {
 
 impl<S: AppState> OptionalTransitionPtr<S> {
     fn double_clicking(element: Option<SceneElement>) -> Self {
+        Self::double_clicking_with(Consequence::DoubleClick(element))
+    }
+
+    fn double_clicking_with(consequence: Consequence) -> Self {
         let now = Instant::now();
         Self::Owned(Box::new(move |info| {
             Some(Box::new(PointAndClicking::double_clicking(
                 info.clicked_position,
                 now,
-                element,
+                consequence.clone(),
             )))
         }))
     }
@@ -310,6 +314,19 @@ fn leaving_selection<'a, S: AppState>(
     }
 }
 
+/// When leaving a rubber band selection, strands are still built from the clicked nucleotide if
+/// possible.
+fn leaving_rectangle_selection<'a, S: AppState>(
+    context: &'a EventContext<'a, S>,
+    element: Option<SceneElement>,
+) -> Box<dyn OptionalTransition<S>> {
+    if let Some(nucl) = context.can_start_builder(element) {
+        Box::new(move |click_info| build_strand(click_info, Some(nucl)))
+    } else {
+        Box::new(|click_info| Some(Box::new(dragging_state::selecting_rectangle(click_info))))
+    }
+}
+
 fn build_strand<S: AppState>(
     click: ClickInfo,
     nucl: Option<Nucl>,
@@ -346,6 +363,30 @@ impl<S: AppState> PointAndClicking<S> {
         }
     }
 
+    /// A state in which the user is adding an element to the selection.
+    ///
+    /// If the cursor is moved away from its initial position, the controller's automata
+    /// transitions to the `SelectingRectangle` state.
+    pub(super) fn selecting_with_rectangle(
+        clicked_position: PhysicalPosition<f64>,
+        element: Option<SceneElement>,
+    ) -> Self {
+        Self {
+            away_state: Default::default(),
+            away_state_maker: Some(ContextDependentTransitionPtr::Owned(Box::new(
+                move |context, _| leaving_rectangle_selection(context, element),
+            ))),
+            clicked_date: Instant::now(),
+            clicked_position,
+            description: "Selecting with rectangle",
+            pressed_button: MouseButton::Left,
+            release_consequences: Consequence::ElementSelected(element, true),
+            release_transition: Default::default(),
+            long_hold_state: None,
+            long_hold_state_maker: None,
+        }
+    }
+
     #[allow(dead_code)] // was used to make it possible to reverse surface direction with a double
                         // click. We may want to use it again in the future.
     pub(super) fn reversing_surface_direction(
@@ -369,11 +410,11 @@ impl<S: AppState> PointAndClicking<S> {
     /// A state in which the user may be performing a double click
     ///
     /// If the user clicks on the element a second time in a short (i.e. < `LONG_HOLDING_TIME` )
-    /// time interval, this triggers a "double click" consequence.
+    /// time interval, this triggers `consequence`.
     fn double_clicking(
         clicked_position: PhysicalPosition<f64>,
         clicked_date: Instant,
-        element: Option<SceneElement>,
+        consequence: Consequence,
     ) -> Self {
         Self {
             away_state: Default::default(),
@@ -381,7 +422,7 @@ impl<S: AppState> PointAndClicking<S> {
             clicked_date,
             description: "Waiting for double click",
             pressed_button: MouseButton::Left,
-            release_consequences: Consequence::DoubleClick(element),
+            release_consequences: consequence,
             release_transition: Default::default(),
             long_hold_state: Some(Default::default()),
             clicked_position,
@@ -389,6 +430,35 @@ impl<S: AppState> PointAndClicking<S> {
         }
     }
 
+    /// A state in which the user is clicking on a segment of a bezier path.
+    ///
+    /// Double clicking on the segment inserts a new vertex at `position`.
+    pub(super) fn clicking_bezier_segment(
+        clicked_position: PhysicalPosition<f64>,
+        path_id: BezierPathId,
+        after_vertex: usize,
+        position: Vec2,
+    ) -> Self {
+        Self {
+            away_state: Default::default(),
+            away_state_maker: None,
+            clicked_date: Instant::now(),
+            clicked_position,
+            description: "Clicking bezier path",
+            pressed_button: MouseButton::Left,
+            release_consequences: Consequence::Nothing,
+            release_transition: OptionalTransitionPtr::double_clicking_with(
+                Consequence::InsertBezierVertex {
+                    path_id,
+                    after_vertex,
+                    position,
+                },
+            ),
+            long_hold_state: None,
+            long_hold_state_maker: None,
+        }
+    }
+
     /// A state in which the user is pasting something
     pub(super) fn pasting(
         clicked_position: PhysicalPosition<f64>,
@@ -18,7 +18,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! This modules handles internal informations about the scene, such as the selected objects etc..
 //! It also communicates with the desgings to get the position of the objects to draw on the scene.
 
-use crate::view::AvailableRotationAxes;
+use crate::view::{AvailableRotationAxes, RollWidgetDescriptor};
 
 use super::view::{
     GridDisc, HandleColors, Instanciable, RawDnaInstance, StereographicSphereAndPlane,
@@ -28,14 +28,14 @@ use super::{
     RotationWidgetDescriptor, RotationWidgetOrientation, SceneElement, View, ViewUpdate,
 };
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::Arc;
 
 use ensnano_design::grid::GridObject;
 use ensnano_design::{BezierVertexId, Collection};
-use ensnano_interactor::graphics::HBoundDisplay;
-use ultraviolet::{Rotor3, Vec3};
+use ensnano_interactor::graphics::{HBondFilter, HBoundDisplay, IsolateSelection};
+use ultraviolet::{Rotor3, Vec2, Vec3};
 
 use super::view::Mesh;
 use ensnano_design::{
@@ -44,19 +44,34 @@ use ensnano_design::{
 };
 use ensnano_interactor::consts::*;
 use ensnano_interactor::{
-    ActionMode, CenterOfSelection, ObjectType, PhantomElement, Referential, Selection,
-    SelectionMode,
+    ActionMode, CenterOfSelection, CheckXoversParameter, ObjectType, PhantomElement, Referential,
+    Selection, SelectionMode,
 };
 
 use super::AppState;
+use crate::PhysicalPosition;
 
 type ViewPtr = Rc<RefCell<View>>;
 
 /// A module that handles the instantiation of designs as 3D geometric objects
 mod design3d;
-use design3d::Design3D;
-pub use design3d::{DesignReader, HBond, HalfHBond, SurfaceInfo, SurfacePoint};
+use design3d::{Design3D, HBoundsInstances, InstancesParameters};
+pub use design3d::{
+    DesignReader, HBond, HalfHBond, ModifiedObjects, StrandEnd, SurfaceInfo, SurfacePoint,
+};
+/// A module that computes distances and angles between selected nucleotides
+mod measurement;
 use ensnano_design::External3DObjectsStamp;
+use measurement::Measurement;
+/// A module that computes the ticks and labels of the nucleotide ruler
+mod ruler;
+use ruler::{Graduation, RulerInstances};
+/// A module that decides which helices are drawn with full detail
+mod level_of_detail;
+use level_of_detail::LevelOfDetail;
+/// A module that keeps the instances of the strands between two updates of the design
+mod instance_cache;
+use instance_cache::{CachedInstances, InstanceCache};
 
 pub struct Data<R: DesignReader> {
     view: ViewPtr,
@@ -86,13 +101,56 @@ pub struct Data<R: DesignReader> {
     stereographic_camera: Arc<(Camera3D, f32)>,
     stereographic_camera_need_update: bool,
     external_3d_objects_stamps: Option<External3DObjectsStamp>,
+    /// The distances and angle between the selected nucleotides
+    measurement: Option<Measurement>,
+    /// Set to true when the description of `measurement` must be sent to the status bar
+    measurement_info_update: bool,
+    /// True while the length of the domain being built is displayed next to the dragged
+    /// nucleotide
+    building_label_shown: bool,
+    /// True if the preview of the strand complementary to the domain being built is drawn
+    building_complement_shown: bool,
+    /// The instances representing the hydrogen bonds. They are only recomputed when the design
+    /// or the set of bonds to draw are modified.
+    h_bonds: Option<HBoundsInstances>,
+    /// True if the nucleotides must be colored according to the torsion of their helix
+    show_torsion: bool,
+    /// The torsion, in degrees, mapped to the extreme colors of the torsion heatmap. If `None`,
+    /// the range is adjusted to the torsions of the design.
+    torsion_range: Option<f32>,
+    torsion_heatmap_update: bool,
+    /// True if the nucleotides must be colored according to their strain during rigid helices
+    /// simulations
+    show_strain: bool,
+    /// The grid position under the cursor
+    pointed_grid_position: Option<GridPosition>,
+    /// Set to true when the coordinates labels of the grid cells must be recomputed
+    grid_coordinate_labels_update: bool,
+    /// The graduations of the nucleotide ruler of the selected helices and of the helix under the
+    /// cursor
+    ruler_graduations: Vec<Graduation>,
+    /// Set to true when the nucleotide ruler must be drawn again, even if the camera did not move
+    ruler_update: bool,
+    /// The position and orientation of the camera when the nucleotide ruler was last drawn
+    ruler_camera: Option<(Vec3, Rotor3)>,
+    /// The helices that are drawn as cylinders because they are far from the camera
+    level_of_detail: LevelOfDetail,
+    /// The design that was drawn at the previous frame
+    previous_reader: Option<R>,
+    /// The parameters with which the instances of the strands were last computed
+    instances_parameters: Option<InstancesParameters>,
+    sphere_instances: InstanceCache,
+    tube_instances: InstanceCache,
 }
 
 impl<R: DesignReader> Data<R> {
     pub fn new(reader: R, view: ViewPtr) -> Self {
+        let design = Design3D::new(reader, 0);
+        let mut level_of_detail = LevelOfDetail::default();
+        level_of_detail.set_segments(design.get_helix_segments());
         Self {
             view,
-            designs: vec![Design3D::new(reader, 0)],
+            designs: vec![design],
             candidate_element: None,
             sub_selection_mode: SelectionMode::Nucleotide,
             selected_position: None,
@@ -109,6 +167,57 @@ impl<R: DesignReader> Data<R> {
             stereographic_camera_need_update: false,
             external_3d_objects_stamps: None,
             surface_pivot_position: None,
+            measurement: None,
+            measurement_info_update: false,
+            building_label_shown: false,
+            building_complement_shown: false,
+            h_bonds: None,
+            show_torsion: false,
+            torsion_range: None,
+            torsion_heatmap_update: false,
+            show_strain: false,
+            pointed_grid_position: None,
+            grid_coordinate_labels_update: false,
+            ruler_graduations: Vec::new(),
+            ruler_update: false,
+            ruler_camera: None,
+            level_of_detail,
+            previous_reader: None,
+            instances_parameters: None,
+            sphere_instances: Default::default(),
+            tube_instances: Default::default(),
+        }
+    }
+
+    pub fn set_show_torsion(&mut self, show: bool) {
+        if self.show_torsion != show {
+            self.show_torsion = show;
+            self.torsion_heatmap_update = true;
+        }
+    }
+
+    pub fn set_show_strain(&mut self, show: bool) {
+        if self.show_strain != show {
+            self.show_strain = show;
+            self.torsion_heatmap_update = true;
+        }
+    }
+
+    pub fn set_torsion_range(&mut self, range: Option<f32>) {
+        if self.torsion_range != range {
+            self.torsion_range = range;
+            self.torsion_heatmap_update = true;
+        }
+    }
+
+    /// Set the grid position under the cursor. Return true if it was modified.
+    pub fn set_pointed_grid_position(&mut self, position: Option<GridPosition>) -> bool {
+        if self.pointed_grid_position != position {
+            self.pointed_grid_position = position;
+            self.grid_coordinate_labels_update = true;
+            true
+        } else {
+            false
         }
     }
 
@@ -121,7 +230,8 @@ impl<R: DesignReader> Data<R> {
 
     /// Add a new design to be drawn
     pub fn update_design(&mut self, design: R) {
-        self.designs[0] = Design3D::new(design, 0);
+        let previous = std::mem::replace(&mut self.designs[0], Design3D::new(design, 0));
+        self.previous_reader = Some(previous.into_reader());
     }
 
     /// Remove all designs to be drawn
@@ -142,18 +252,54 @@ impl<R: DesignReader> Data<R> {
         if self.discs_need_update(app_state, older_app_state) {
             self.update_discs(app_state);
         }
-        if app_state.design_was_modified(older_app_state)
+        let level_of_detail_changed = self.update_level_of_detail(app_state, older_app_state);
+        if level_of_detail_changed
+            || app_state.design_was_modified(older_app_state)
             || app_state.suggestion_parameters_were_updated(older_app_state)
             || app_state.draw_options_were_updated(older_app_state)
             || app_state.insertion_bond_display_was_modified(older_app_state)
             || app_state.selection_was_updated(older_app_state)
             || app_state.revolution_bezier_updated(older_app_state)
+            || self.torsion_heatmap_update
+            || app_state.get_simulation_state() != older_app_state.get_simulation_state()
+            || app_state.get_check_xover_parameters()
+                != older_app_state.get_check_xover_parameters()
         {
+            let isolated_elements = self.get_isolated_elements(app_state).map(Rc::new);
+            let show_torsion = self.show_torsion;
+            let torsion_range = self.torsion_range;
+            let simulation_state = app_state.get_simulation_state();
+            let show_strain = self.show_strain
+                && (simulation_state.simulating_helices() || simulation_state.is_paused());
+            let color_xovers_by_strain =
+                app_state.get_check_xover_parameters() != CheckXoversParameter::None;
+            let strand_coloring = app_state.get_draw_options().strand_coloring;
+            let strand_colormap = app_state.get_draw_options().strand_colormap;
+            let reduced_helices = self.level_of_detail.reduced_helices();
             for d in self.designs.iter_mut() {
+                d.reduced_helices = reduced_helices.clone();
                 d.thick_helices = app_state.get_draw_options().thick_helices;
-            }
+                d.isolated_elements = isolated_elements.clone();
+                d.hide_idealized_design = app_state.get_draw_options().hide_idealized_design;
+                d.torsion_heatmap = show_torsion.then(|| d.make_torsion_heatmap(torsion_range));
+                d.strain_heatmap = show_strain.then(|| d.make_strain_heatmap()).flatten();
+                d.color_xovers_by_strain = color_xovers_by_strain;
+                d.strand_colors = d.make_strand_colors(strand_coloring, strand_colormap);
+            }
+            self.torsion_heatmap_update = false;
+            self.view
+                .borrow_mut()
+                .update(ViewUpdate::StrandColorsLegend(
+                    self.designs[0].strand_colors.clone(),
+                ));
             self.update_instances(app_state);
         }
+        if self.h_bonds_need_update(app_state, older_app_state) {
+            self.h_bonds = None;
+        }
+        if self.h_bonds.is_none() || app_state.draw_options_were_updated(older_app_state) {
+            self.update_h_bonds(app_state);
+        }
 
         if self.stereographic_camera_need_update {
             self.update_stereographic_sphere();
@@ -170,10 +316,45 @@ impl<R: DesignReader> Data<R> {
         {
             self.update_selection(app_state.get_selection(), app_state);
         }
+        if app_state.selection_was_updated(older_app_state)
+            || app_state.design_was_modified(older_app_state)
+        {
+            self.update_measurement(app_state);
+            self.update_strand_end_handles(app_state);
+        }
+        if app_state.reference_design_was_updated(older_app_state) {
+            self.update_reference_design(app_state);
+        }
+        if app_state.design_changes_were_updated(older_app_state)
+            || (app_state.get_design_changes().is_some()
+                && app_state.design_was_modified(older_app_state))
+        {
+            self.update_design_changes(app_state);
+        }
+        if self.building_label_shown || !app_state.get_strand_builders().is_empty() {
+            self.update_building_label(app_state);
+        }
+        if self.building_complement_shown || !app_state.get_strand_builders().is_empty() {
+            self.update_building_complement(app_state);
+        }
+        // The labels follow the grids when they are moved or rotated
+        if self.grid_coordinate_labels_update || app_state.design_was_modified(older_app_state) {
+            self.update_grid_coordinate_labels();
+            self.grid_coordinate_labels_update = false;
+        }
         self.handle_need_opdate |= app_state.design_was_modified(older_app_state)
             || app_state.selection_was_updated(older_app_state)
             || app_state.get_action_mode() != older_app_state.get_action_mode();
 
+        if app_state.design_was_modified(older_app_state)
+            || app_state.draw_options_were_updated(older_app_state)
+            || app_state.selection_was_updated(older_app_state)
+            || app_state.candidates_set_was_updated(older_app_state)
+        {
+            self.update_ruler_graduations(app_state);
+        }
+        // The ticks face the camera and the density of the labels depends on its position
+        self.update_ruler();
         if self.handle_need_opdate {
             self.update_bezier(app_state);
             self.update_handle(app_state);
@@ -182,6 +363,14 @@ impl<R: DesignReader> Data<R> {
         if app_state.candidates_set_was_updated(older_app_state) {
             self.update_candidate(app_state.get_candidates(), app_state);
         }
+        // The phantoms drawn locally follow the grid under the cursor
+        if (app_state.get_draw_options().local_phantoms
+            && app_state.candidates_set_was_updated(older_app_state))
+            || app_state.get_draw_options().local_phantoms
+                != older_app_state.get_draw_options().local_phantoms
+        {
+            self.update_phantoms(app_state);
+        }
         if self.pivot_update {
             self.update_pivot();
             self.pivot_update = false;
@@ -198,6 +387,129 @@ impl<R: DesignReader> Data<R> {
         self.update_external_3d_objects(app_state);
     }
 
+    /// Update the set of helices that are drawn as cylinders. Return true if it was modified.
+    fn update_level_of_detail<S: AppState>(&mut self, app_state: &S, older_app_state: &S) -> bool {
+        if app_state.design_was_modified(older_app_state) {
+            self.level_of_detail
+                .set_segments(self.designs[0].get_helix_segments());
+        }
+        if app_state.design_was_modified(older_app_state)
+            || app_state.selection_was_updated(older_app_state)
+            || app_state.candidates_set_was_updated(older_app_state)
+        {
+            let protected_helices = self.get_lod_protected_helices(app_state);
+            self.level_of_detail
+                .set_protected_helices(protected_helices);
+        }
+        self.level_of_detail
+            .set_full_detail(app_state.get_draw_options().full_detail);
+        let camera_position = self.view.borrow().get_camera().borrow().position;
+        self.level_of_detail.update(camera_position)
+    }
+
+    /// Return the helices containing selected or candidate elements. They are always drawn with
+    /// full detail.
+    fn get_lod_protected_helices<S: AppState>(&self, app_state: &S) -> HashSet<usize> {
+        let mut ret = HashSet::new();
+        for selection in app_state
+            .get_selection()
+            .iter()
+            .chain(app_state.get_candidates().iter())
+        {
+            match selection {
+                Selection::Helix { helix_id, .. } => {
+                    ret.insert(*helix_id);
+                }
+                // Drawing every helix with full detail would defeat the purpose of the level of
+                // detail
+                Selection::Design(_) => (),
+                selection => ret.extend(
+                    self.get_group_member(selection)
+                        .into_iter()
+                        .filter_map(|id| self.designs[0].get_helix(id)),
+                ),
+            }
+        }
+        ret
+    }
+
+    /// Return the set of elements that must be drawn when the selection is isolated, or `None` if
+    /// all elements must be drawn.
+    ///
+    /// If the selection is empty, all elements are drawn.
+    fn get_isolated_elements<S: AppState>(&self, app_state: &S) -> Option<HashSet<u32>> {
+        let isolate_selection = app_state.get_draw_options().isolate_selection;
+        if isolate_selection == IsolateSelection::No {
+            return None;
+        }
+        let mut ret = HashSet::new();
+        for selection in app_state.get_selection().iter() {
+            ret.extend(self.get_group_member(selection));
+        }
+        if isolate_selection == IsolateSelection::WithXoverPartners {
+            let partners = self.designs[0].get_xover_partners(&ret);
+            ret.extend(partners);
+        }
+        // The extremities of the drawn bonds must be drawn as well
+        let bond_extremities: Vec<u32> = ret
+            .iter()
+            .filter_map(|id| match self.designs[0].get_element_type(*id) {
+                Some(ObjectType::Bound(n1, n2)) => Some([n1, n2]),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        ret.extend(bond_extremities);
+        Some(ret).filter(|elements| !elements.is_empty())
+    }
+
+    fn h_bonds_need_update<S: AppState>(&self, app_state: &S, older_app_state: &S) -> bool {
+        let draw_options = app_state.get_draw_options();
+        let older_draw_options = older_app_state.get_draw_options();
+        let selection_matters = draw_options.h_bond_display == HBondFilter::SelectionOnly
+            || draw_options.isolate_selection != IsolateSelection::No;
+        let hidden = |h_bonds: HBoundDisplay| h_bonds == HBoundDisplay::No;
+        app_state.design_was_modified(older_app_state)
+            || hidden(draw_options.h_bonds) != hidden(older_draw_options.h_bonds)
+            || draw_options.h_bond_display != older_draw_options.h_bond_display
+            || draw_options.isolate_selection != older_draw_options.isolate_selection
+            || (selection_matters && app_state.selection_was_updated(older_app_state))
+    }
+
+    /// Send the instances representing the hydrogen bonds to the view, computing them if they
+    /// are not cached.
+    fn update_h_bonds<S: AppState>(&mut self, app_state: &S) {
+        let draw_options = app_state.get_draw_options();
+        if self.h_bonds.is_none() {
+            let bonds = match draw_options.h_bond_display {
+                _ if draw_options.h_bonds == HBoundDisplay::No => Default::default(),
+                HBondFilter::None => Default::default(),
+                HBondFilter::All => self.designs[0].get_hbonds(None),
+                HBondFilter::SelectionOnly => {
+                    let mut selected = HashSet::new();
+                    for selection in app_state.get_selection().iter() {
+                        selected.extend(self.get_group_member(selection));
+                    }
+                    self.designs[0].get_hbonds(Some(&selected))
+                }
+            };
+            self.h_bonds = Some(bonds);
+        }
+        if let Some(bonds) = self.h_bonds.as_ref() {
+            let h_bonds = if draw_options.h_bonds == HBoundDisplay::Ellipsoid {
+                bonds.partial_h_bonds.clone()
+            } else {
+                bonds.full_h_bonds.clone()
+            };
+            let mut view = self.view.borrow_mut();
+            view.update(ViewUpdate::RawDna(Mesh::HBond, h_bonds));
+            view.update(ViewUpdate::RawDna(
+                Mesh::BaseEllipsoid,
+                bonds.ellipsoids.clone(),
+            ));
+        }
+    }
+
     fn update_stereographic_sphere(&self) {
         let instances = Rc::new(vec![StereographicSphereAndPlane {
             position: self.stereographic_camera.0.position,
@@ -273,8 +585,8 @@ impl<R: DesignReader> Data<R> {
             .update(ViewUpdate::RawDna(Mesh::BezierSqueleton, Rc::new(tubes)));
     }
 
-    fn update_handle<S: AppState>(&self, app_state: &S) {
-        log::debug!("updating handle {:?} ", self.selected_element(app_state));
+    /// Return the origin and orientation of the handles and of the rotation widget.
+    pub fn get_widget_frame<S: AppState>(&self, app_state: &S) -> (Option<Vec3>, Option<Rotor3>) {
         let pivot = app_state.get_current_group_pivot();
         let origin = pivot
             .as_ref()
@@ -287,6 +599,12 @@ impl<R: DesignReader> Data<R> {
                 .map(|p| p.orientation)
                 .or_else(|| self.get_widget_basis(app_state))
         });
+        (origin, orientation)
+    }
+
+    fn update_handle<S: AppState>(&self, app_state: &S) {
+        log::debug!("updating handle {:?} ", self.selected_element(app_state));
+        let (origin, orientation) = self.get_widget_frame(app_state);
         let handle_descr = if app_state.get_action_mode().0.wants_handle() || self.rotating_pivot {
             let colors = if self.rotating_pivot {
                 HandleColors::Rgb
@@ -331,6 +649,32 @@ impl<R: DesignReader> Data<R> {
         self.view
             .borrow_mut()
             .update(ViewUpdate::RotationWidget(rotation_widget_descr));
+        let roll_widget_descr = self.roll_widget_descriptor(app_state);
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RollWidget(roll_widget_descr));
+    }
+
+    /// The roll widget is shown when exactly one helix is selected. It is placed on the axis of
+    /// the helix, at the projection of the selected position.
+    fn roll_widget_descriptor<S: AppState>(&self, app_state: &S) -> Option<RollWidgetDescriptor> {
+        let helix_id = match app_state.get_selection() {
+            [Selection::Helix { helix_id, .. }] => *helix_id,
+            _ => return None,
+        };
+        let design = &self.designs[0];
+        let (axis_point, axis) = design.get_helix_axis(helix_id)?;
+        let origin = if let Some(position) = self.get_selected_position() {
+            axis_point + axis * axis.dot(position - axis_point)
+        } else {
+            axis_point
+        };
+        Some(RollWidgetDescriptor {
+            helix_id,
+            origin,
+            axis,
+            radius: 2. * design.get_parameters().helix_radius,
+        })
     }
 }
 
@@ -352,6 +696,18 @@ impl<R: DesignReader> Data<R> {
         self.pivot_update = true;
     }
 
+    /// The position that was set as pivot, regardless of the selection.
+    pub fn get_explicit_pivot_position(&self) -> Option<Vec3> {
+        self.pivot_position
+    }
+
+    /// Set the pivot position to a value previously returned by
+    /// `get_explicit_pivot_position`.
+    pub fn restore_pivot_position(&mut self, position: Option<Vec3>) {
+        self.pivot_position = position;
+        self.pivot_update = true;
+    }
+
     #[allow(dead_code)]
     fn get_element_design(&self, element: &SceneElement) -> u32 {
         match element {
@@ -745,6 +1101,7 @@ impl<R: DesignReader> Data<R> {
             Selection::Phantom(_) => HashSet::new(),
             Selection::BezierTengent { .. } => HashSet::new(),
             Selection::BezierVertex(_) => HashSet::new(),
+            Selection::External3DObject(_) => HashSet::new(),
             Selection::Nothing => HashSet::new(),
             Selection::Design(d_id) => self.designs[*d_id as usize].get_all_elements(),
         }
@@ -783,6 +1140,12 @@ impl<R: DesignReader> Data<R> {
         self.selected_position
     }
 
+    /// Return the position of the element corresponding to a nucleotide or bond selection.
+    pub fn get_selection_position(&self, selection: Selection) -> Option<Vec3> {
+        let element = self.selection_to_element(selection)?;
+        self.get_element_position(&element, Referential::World, SelectionMode::Nucleotide)
+    }
+
     pub fn try_update_pivot_position<S: AppState>(&mut self, app_state: &S) {
         if self.pivot_element.is_none() {
             self.pivot_element = self.selected_element(app_state);
@@ -846,6 +1209,115 @@ impl<R: DesignReader> Data<R> {
         Some(selection).filter(|s| *s != Selection::Nothing)
     }
 
+    /// Return the selection obtained by adding to `selection` all the drawn nucleotides whose
+    /// projection on the screen is inside the rectangle with corners `c1` and `c2`, given in
+    /// normalized coordinates.
+    ///
+    /// The added elements are promoted according to the current selection mode.
+    pub fn add_rectangle_to_selection<S: AppState>(
+        &self,
+        c1: PhysicalPosition<f64>,
+        c2: PhysicalPosition<f64>,
+        selection: &[Selection],
+        app_state: &S,
+    ) -> Vec<Selection> {
+        let to_ndc =
+            |c: PhysicalPosition<f64>| Vec2::new(2. * c.x as f32 - 1., 1. - 2. * c.y as f32);
+        let view_proj = {
+            let view = self.view.borrow();
+            let projection = view.get_projection().borrow().calc_matrix();
+            let camera = view.get_camera().borrow().calc_matrix();
+            projection * camera
+        };
+        let selection_mode = app_state.get_selection_mode();
+        let mut new_selection = selection.to_vec();
+        let mut already_selected: BTreeSet<Selection> = selection.iter().cloned().collect();
+        for (d_id, design) in self.designs.iter().enumerate() {
+            for id in design.get_nucl_ids_in_rectangle(view_proj, to_ndc(c1), to_ndc(c2)) {
+                let element = SceneElement::DesignElement(d_id as u32, id);
+                let selected = self.element_to_selection(&element, selection_mode);
+                if selected != Selection::Nothing && already_selected.insert(selected) {
+                    new_selection.push(selected);
+                }
+            }
+        }
+        new_selection
+    }
+
+    /// Return the drawn nucleotide whose projection on the screen is the closest to the cursor if
+    /// it is at less than `PIVOT_SNAPPING_RADIUS` pixels from it, together with its design and its
+    /// position.
+    pub fn get_nucl_near_cursor(
+        &self,
+        cursor: PhysicalPosition<f64>,
+        area_width: u32,
+        area_height: u32,
+    ) -> Option<(u32, Nucl, Vec3)> {
+        let point = Vec2::new(2. * cursor.x as f32 - 1., 1. - 2. * cursor.y as f32);
+        let scale = Vec2::new(area_width as f32 / 2., area_height as f32 / 2.);
+        let view_proj = {
+            let view = self.view.borrow();
+            let projection = view.get_projection().borrow().calc_matrix();
+            let camera = view.get_camera().borrow().calc_matrix();
+            projection * camera
+        };
+        let (d_id, id, _) = self
+            .designs
+            .iter()
+            .enumerate()
+            .filter_map(|(d_id, design)| {
+                design
+                    .get_closest_nucl_id_on_screen(view_proj, point, scale)
+                    .map(|(id, distance)| (d_id, id, distance))
+            })
+            .filter(|(_, _, distance)| *distance <= PIVOT_SNAPPING_RADIUS)
+            .min_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2))?;
+        let design = &self.designs[d_id];
+        let nucl = design.get_nucl(id)?;
+        let position = design.get_design_element_position(id, Referential::World)?;
+        Some((d_id as u32, nucl, position))
+    }
+
+    /// Return the center of mass of the nucleotides in the selection.
+    pub fn get_selection_centroid<S: AppState>(&self, app_state: &S) -> Option<Vec3> {
+        let mut sum = Vec3::zero();
+        let mut nb_nucl = 0;
+        for selection in app_state.get_selection().iter() {
+            let design = if let Some(design) = selection
+                .get_design()
+                .and_then(|d_id| self.designs.get(d_id as usize))
+            {
+                design
+            } else {
+                continue;
+            };
+            for id in self.get_group_member(selection) {
+                if design.get_nucl(id).is_some() {
+                    if let Some(position) =
+                        design.get_design_element_position(id, Referential::World)
+                    {
+                        sum += position;
+                        nb_nucl += 1;
+                    }
+                }
+            }
+        }
+        (nb_nucl > 0).then(|| sum / nb_nucl as f32)
+    }
+
+    /// Return the nucleotides that are hit by the ray, sorted by increasing distance to the
+    /// origin of the ray.
+    pub fn get_nucl_elements_on_ray(&self, origin: Vec3, direction: Vec3) -> Vec<SceneElement> {
+        let mut hits: Vec<(SceneElement, f32)> = Vec::new();
+        for (d_id, design) in self.designs.iter().enumerate() {
+            for (id, depth) in design.get_nucl_ids_on_ray(origin, direction, SPHERE_RADIUS) {
+                hits.push((SceneElement::DesignElement(d_id as u32, id), depth));
+            }
+        }
+        hits.sort_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+        hits.into_iter().map(|(element, _)| element).collect()
+    }
+
     pub fn add_to_selection<S: AppState>(
         &mut self,
         element: Option<SceneElement>,
@@ -1004,13 +1476,7 @@ impl<R: DesignReader> Data<R> {
         self.view
             .borrow_mut()
             .update(ViewUpdate::RawDna(Mesh::SelectedSphere, Rc::new(sphere)));
-        let (sphere, vec) = self.get_phantom_instances(app_state);
-        self.view
-            .borrow_mut()
-            .update(ViewUpdate::RawDna(Mesh::PhantomSphere, sphere));
-        self.view
-            .borrow_mut()
-            .update(ViewUpdate::RawDna(Mesh::PhantomTube, vec));
+        self.update_phantoms(app_state);
         let mut grids =
             if let Some(SceneElement::Grid(d_id, g_id)) = self.selected_element(app_state) {
                 vec![(d_id as usize, g_id)]
@@ -1023,6 +1489,129 @@ impl<R: DesignReader> Data<R> {
             }
         }
         self.view.borrow_mut().set_selected_grid(grids);
+        let objects = selection
+            .iter()
+            .filter_map(|s| {
+                if let Selection::External3DObject(object_id) = s {
+                    Some(*object_id)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.view
+            .borrow_mut()
+            .set_selected_external_objects(objects);
+    }
+
+    /// Notify the view that the handles on the ends of the selected strands and of the strands
+    /// being built have changed.
+    ///
+    /// The handles of a strand being built whose ends meet are highlighted, since releasing them
+    /// makes the strand cyclic.
+    fn update_strand_end_handles<S: AppState>(&mut self, app_state: &S) {
+        let design = &self.designs[0];
+        let built_strands: BTreeSet<usize> = app_state
+            .get_strand_builders()
+            .iter()
+            .map(|b| b.get_strand_id())
+            .collect();
+        let mut strand_ids: BTreeSet<usize> = app_state
+            .get_selection()
+            .iter()
+            .filter_map(|s| {
+                if let Selection::Strand(_, s_id) = s {
+                    Some(*s_id as usize)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        strand_ids.extend(built_strands.iter().cloned());
+        let instances: Vec<RawDnaInstance> = strand_ids
+            .into_iter()
+            .flat_map(|s_id| {
+                let color = if built_strands.contains(&s_id) && design.strand_ends_meet(s_id) {
+                    CLOSING_STRAND_HANDLE_COLOR
+                } else {
+                    STRAND_END_HANDLE_COLOR
+                };
+                design
+                    .get_strand_ends(s_id)
+                    .into_iter()
+                    .filter_map(move |end| design.make_strand_end_handle(end, color))
+            })
+            .collect();
+        self.view.borrow_mut().update(ViewUpdate::RawDna(
+            Mesh::StrandEndHandle,
+            Rc::new(instances),
+        ));
+    }
+
+    /// Return the strand that can be made cyclic by linking its ends after its end has been moved
+    /// with a handle.
+    pub fn get_strand_to_close<S: AppState>(&self, app_state: &S) -> Option<usize> {
+        app_state
+            .get_strand_builders()
+            .iter()
+            .map(|b| b.get_strand_id())
+            .find(|s_id| self.designs[0].strand_ends_meet(*s_id))
+    }
+
+    /// Return the nucleotide on which the strand end handle `element` is drawn.
+    pub fn get_strand_end_handle_nucl(&self, element: &Option<SceneElement>) -> Option<Nucl> {
+        if let Some(SceneElement::StrandEndHandle { strand_id, prime3 }) = element {
+            self.designs[0]
+                .get_strand_ends(*strand_id)
+                .into_iter()
+                .find(|end| end.prime3 == *prime3)
+                .map(|end| end.nucl)
+        } else {
+            None
+        }
+    }
+
+    /// Notify the view that the instances of the phantom helices have changed
+    fn update_phantoms<S: AppState>(&mut self, app_state: &S) {
+        let (sphere, vec) = self.get_phantom_instances(app_state);
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::PhantomSphere, sphere));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::PhantomTube, vec));
+    }
+
+    /// Return the grids that are under the cursor or selected. When phantoms are drawn locally,
+    /// the persistent phantoms of the other grids are hidden.
+    fn local_phantom_grids<S: AppState>(&self, app_state: &S) -> HashSet<(u32, GridId)> {
+        self.candidate_element
+            .iter()
+            .cloned()
+            .chain(self.selected_element(app_state))
+            .filter_map(|element| self.grid_of_element(&element))
+            .collect()
+    }
+
+    /// Return the grid that contains `element` or the helix to which `element` belongs.
+    fn grid_of_element(&self, element: &SceneElement) -> Option<(u32, GridId)> {
+        match element {
+            SceneElement::Grid(d_id, g_id) => Some((*d_id, *g_id)),
+            SceneElement::GridCircle(d_id, position) => Some((*d_id, position.grid)),
+            SceneElement::DesignElement(d_id, e_id) => {
+                let design = self.designs.get(*d_id as usize)?;
+                let h_id = design.get_helix(*e_id)?;
+                design
+                    .get_helix_grid_position(h_id as u32)
+                    .map(|position| (*d_id, position.grid))
+            }
+            SceneElement::PhantomElement(phantom_element) => self
+                .designs
+                .get(phantom_element.design_id as usize)?
+                .get_helix_grid_position(phantom_element.helix_id)
+                .map(|position| (phantom_element.design_id, position.grid)),
+            _ => None,
+        }
     }
 
     /// Return the sets of elements of the phantom helix
@@ -1054,11 +1643,26 @@ impl<R: DesignReader> Data<R> {
     ) -> HashMap<u32, HashMap<u32, bool>> {
         let mut ret = HashMap::new();
 
+        let local_grids = if app_state.get_draw_options().local_phantoms {
+            Some(self.local_phantom_grids(app_state))
+        } else {
+            None
+        };
         for (d_id, design) in self.designs.iter().enumerate() {
             let new_helices = design.get_persistent_phantom_helices();
             let set = ret.entry(d_id as u32).or_insert_with(HashMap::new);
             for h_id in new_helices.iter() {
-                set.insert(*h_id, true);
+                let drawn = if let Some(grids) = local_grids.as_ref() {
+                    design
+                        .get_helix_grid_position(*h_id)
+                        .map(|position| grids.contains(&(d_id as u32, position.grid)))
+                        .unwrap_or(false)
+                } else {
+                    true
+                };
+                if drawn {
+                    set.insert(*h_id, true);
+                }
             }
         }
         if self.must_draw_phantom(app_state) {
@@ -1099,9 +1703,23 @@ impl<R: DesignReader> Data<R> {
                     SceneElement::BezierVertex { .. } => (),
                     SceneElement::BezierTengent { .. } => (),
                     SceneElement::PlaneCorner { .. } => (),
+                    SceneElement::StrandEndHandle { .. } => (),
+                    SceneElement::External3DObject(_) => (),
                 }
             }
         }
+        // The helices of hidden grids are drawn, but not their phantoms
+        for (d_id, set) in ret.iter_mut() {
+            if let Some(design) = self.designs.get(*d_id as usize) {
+                let grids = design.get_grid();
+                set.retain(|h_id, _| {
+                    design
+                        .get_helix_grid_position(*h_id)
+                        .map(|position| grids.contains_key(&position.grid))
+                        .unwrap_or(true)
+                });
+            }
+        }
         ret
     }
 
@@ -1126,6 +1744,16 @@ impl<R: DesignReader> Data<R> {
     ) -> Selection {
         match element {
             SceneElement::DesignElement(design_id, element_id) => {
+                // Picking a helix drawn as cylinders selects the whole helix
+                let selection_mode = if self.designs[*design_id as usize]
+                    .get_helix(*element_id)
+                    .map(|h_id| self.level_of_detail.is_reduced(h_id))
+                    .unwrap_or(false)
+                {
+                    SelectionMode::Helix
+                } else {
+                    selection_mode
+                };
                 if let Some(group_id) =
                     self.get_group_identifier(*design_id, *element_id, selection_mode)
                 {
@@ -1195,6 +1823,9 @@ impl<R: DesignReader> Data<R> {
                 helix_id: *helix_id,
             },
             SceneElement::PlaneCorner { .. } => Selection::Nothing,
+            SceneElement::StrandEndHandle { strand_id, .. } => {
+                Selection::Strand(0, *strand_id as u32)
+            }
             SceneElement::BezierVertex { path_id, vertex_id } => {
                 Selection::BezierVertex(BezierVertexId {
                     path_id: *path_id,
@@ -1202,6 +1833,7 @@ impl<R: DesignReader> Data<R> {
                 })
             }
             SceneElement::WidgetElement(_) => Selection::Nothing,
+            SceneElement::External3DObject(object_id) => Selection::External3DObject(*object_id),
             SceneElement::BezierTengent {
                 path_id, vertex_id, ..
             } => Selection::BezierVertex(BezierVertexId {
@@ -1315,6 +1947,19 @@ impl<R: DesignReader> Data<R> {
             }
         }
         self.view.borrow_mut().set_candidate_grid(grids);
+        let objects = candidates
+            .iter()
+            .filter_map(|c| {
+                if let Selection::External3DObject(object_id) = c {
+                    Some(*object_id)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.view
+            .borrow_mut()
+            .set_candidate_external_objects(objects);
     }
 
     fn update_pivot(&mut self) {
@@ -1370,6 +2015,361 @@ impl<R: DesignReader> Data<R> {
             .update(ViewUpdate::RawDna(Mesh::XoverTube, Rc::new(tubes)));
     }
 
+    /// Draw the reference design, if there is one and it is visible.
+    fn update_reference_design<S: AppState>(&self, app_state: &S) {
+        let reference = app_state.get_reference_design().filter(|r| r.visible);
+        let spheres: Vec<_> = reference
+            .iter()
+            .flat_map(|r| {
+                r.nucleotides
+                    .iter()
+                    .map(|p| Design3D::<R>::ghost_sphere(r.transform(*p), REFERENCE_DESIGN_COLOR))
+            })
+            .collect();
+        let tubes: Vec<_> = reference
+            .iter()
+            .flat_map(|r| {
+                r.bonds.iter().map(|(p1, p2)| {
+                    Design3D::<R>::ghost_tube(
+                        r.transform(*p1),
+                        r.transform(*p2),
+                        REFERENCE_DESIGN_COLOR,
+                    )
+                })
+            })
+            .collect();
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::ReferenceSphere, Rc::new(spheres)));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::ReferenceTube, Rc::new(tubes)));
+    }
+
+    /// Highlight the elements that changed since the design was last saved, and draw the deleted
+    /// strands as ghosts.
+    fn update_design_changes<S: AppState>(&self, app_state: &S) {
+        let mut spheres = Vec::new();
+        let mut tubes = Vec::new();
+        if let Some(changes) = app_state.get_design_changes() {
+            let highlights = changes
+                .added_strands
+                .iter()
+                .map(|s_id| (Selection::Strand(0, *s_id as u32), ADDED_STRAND_COLOR))
+                .chain(
+                    changes
+                        .modified_strands
+                        .iter()
+                        .map(|s_id| (Selection::Strand(0, *s_id as u32), MODIFIED_STRAND_COLOR)),
+                )
+                .chain(changes.moved_helices.iter().map(|h_id| {
+                    (
+                        Selection::Helix {
+                            design_id: 0,
+                            helix_id: *h_id,
+                            segment_id: 0,
+                        },
+                        MOVED_HELIX_COLOR,
+                    )
+                }));
+            for (selection, color) in highlights {
+                spheres.extend(self.get_highlight_instances(
+                    &selection,
+                    color,
+                    ObjectType::Nucleotide(0),
+                    app_state,
+                ));
+                tubes.extend(self.get_highlight_instances(
+                    &selection,
+                    color,
+                    ObjectType::Bound(0, 0),
+                    app_state,
+                ));
+            }
+            for strand in changes.removed_strands.iter() {
+                spheres.extend(
+                    strand
+                        .positions
+                        .iter()
+                        .map(|p| Design3D::<R>::ghost_sphere(*p, REMOVED_STRAND_COLOR)),
+                );
+                tubes.extend(
+                    strand
+                        .positions
+                        .windows(2)
+                        .map(|w| Design3D::<R>::ghost_tube(w[0], w[1], REMOVED_STRAND_COLOR)),
+                );
+                if strand.cyclic && strand.positions.len() > 2 {
+                    tubes.push(Design3D::<R>::ghost_tube(
+                        strand.positions[strand.positions.len() - 1],
+                        strand.positions[0],
+                        REMOVED_STRAND_COLOR,
+                    ));
+                }
+            }
+        }
+        self.view.borrow_mut().update(ViewUpdate::RawDna(
+            Mesh::DesignChangesSphere,
+            Rc::new(spheres),
+        ));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::DesignChangesTube, Rc::new(tubes)));
+    }
+
+    /// Return the instances of the nucleotides or bonds of `selection`, enlarged and colored with
+    /// `color`.
+    fn get_highlight_instances<S: AppState>(
+        &self,
+        selection: &Selection,
+        color: u32,
+        object_type: ObjectType,
+        app_state: &S,
+    ) -> Vec<RawDnaInstance> {
+        let expand_with = if let ObjectType::Nucleotide(_) = object_type {
+            design3d::ExpandWith::Spheres
+        } else {
+            design3d::ExpandWith::Tubes
+        };
+        let mut ret = Vec::new();
+        for element in self.expand_selection(object_type, selection).iter() {
+            if let SceneElement::DesignElement(d_id, id) = element {
+                if let Some(design) = self.designs.get(*d_id as usize) {
+                    ret.extend(design.make_instance(
+                        *id,
+                        color,
+                        CANDIDATE_SCALE_FACTOR,
+                        Some(expand_with).filter(|_| !app_state.show_insertion_representents()),
+                    ));
+                }
+            }
+        }
+        ret
+    }
+
+    /// Draw the distance between two selected nucleotides, and the angle formed by three selected
+    /// nucleotides.
+    fn update_measurement<S: AppState>(&mut self, app_state: &S) {
+        let positions: Option<Vec<Vec3>> = app_state
+            .get_selection()
+            .iter()
+            .map(|s| match s {
+                Selection::Nucleotide(d_id, nucl) => self.get_nucl_position(*nucl, *d_id as usize),
+                _ => None,
+            })
+            .collect();
+        let measurement = positions.and_then(Measurement::new);
+        let tubes: Vec<_> = measurement
+            .iter()
+            .flat_map(|m| m.segments())
+            .map(|(pos1, pos2)| Design3D::<R>::measure_tube(pos1, pos2))
+            .collect();
+        let letters = if let Some(measurement) = measurement.as_ref() {
+            let right = self.view.borrow().get_camera().borrow().right_vec();
+            let up = self.view.borrow().get_camera().borrow().up_vec();
+            measurement.letter_instances(right, up)
+        } else {
+            vec![vec![]; NB_MEASURE_CHARS]
+        };
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::MeasureTube, Rc::new(tubes)));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::MeasureLetter(letters));
+        if measurement != self.measurement {
+            self.measurement = measurement;
+            self.measurement_info_update = true;
+        }
+    }
+
+    /// Draw the length of the domain being built next to the dragged nucleotide.
+    fn update_building_label<S: AppState>(&mut self, app_state: &S) {
+        let letters = app_state.get_building_state().and_then(|status| {
+            let position = self.get_nucl_position(status.dragged_nucl, 0)?;
+            let right = self.view.borrow().get_camera().borrow().right_vec();
+            let up = self.view.borrow().get_camera().borrow().up_vec();
+            Some(measurement::building_label_instances(
+                &status, position, right, up,
+            ))
+        });
+        self.building_label_shown = letters.is_some();
+        self.view.borrow_mut().update(ViewUpdate::BuildingLetter(
+            letters.unwrap_or_else(|| vec![vec![]; NB_BUILDING_LABEL_CHARS]),
+        ));
+    }
+
+    /// Draw the nucleotides that would pair with the domains being built, and highlight the
+    /// existing strands that they would pair with.
+    fn update_building_complement<S: AppState>(&mut self, app_state: &S) {
+        let mut spheres = Vec::new();
+        let mut tubes = Vec::new();
+        let mut paired_strands = BTreeSet::new();
+        for builder in app_state.get_strand_builders().iter() {
+            let design = if let Some(design) = self.designs.get(builder.get_design_id() as usize) {
+                design
+            } else {
+                continue;
+            };
+            let mut positions = Vec::new();
+            for nucl in builder.get_domain_nucls() {
+                let complement = nucl.compl();
+                if let Some(position) = design.get_nucl_position(complement) {
+                    positions.push(position);
+                    if let Some(s_id) = design
+                        .get_identifier_nucl(&complement)
+                        .and_then(|e_id| design.get_strand(e_id))
+                    {
+                        paired_strands.insert(s_id);
+                    } else {
+                        spheres.push(Design3D::<R>::ghost_sphere(
+                            position,
+                            BUILDING_COMPLEMENT_COLOR,
+                        ));
+                    }
+                }
+            }
+            tubes.extend(
+                positions
+                    .windows(2)
+                    .map(|w| Design3D::<R>::ghost_tube(w[0], w[1], BUILDING_COMPLEMENT_COLOR)),
+            );
+        }
+        for s_id in paired_strands {
+            let selection = Selection::Strand(0, s_id as u32);
+            spheres.extend(self.get_highlight_instances(
+                &selection,
+                BUILDING_PAIRED_STRAND_COLOR,
+                ObjectType::Nucleotide(0),
+                app_state,
+            ));
+            tubes.extend(self.get_highlight_instances(
+                &selection,
+                BUILDING_PAIRED_STRAND_COLOR,
+                ObjectType::Bound(0, 0),
+                app_state,
+            ));
+        }
+        self.building_complement_shown = !app_state.get_strand_builders().is_empty();
+        self.view.borrow_mut().update(ViewUpdate::RawDna(
+            Mesh::BuildingComplementSphere,
+            Rc::new(spheres),
+        ));
+        self.view.borrow_mut().update(ViewUpdate::RawDna(
+            Mesh::BuildingComplementTube,
+            Rc::new(tubes),
+        ));
+    }
+
+    /// Draw `text` next to the rotation widget, or erase the label if `text` is `None`.
+    pub fn update_rotation_angle_label(&mut self, text: Option<&str>) {
+        let letters = text.and_then(|text| {
+            let position = self.view.borrow().get_rotation_label_position()?;
+            let right = self.view.borrow().get_camera().borrow().right_vec();
+            let up = self.view.borrow().get_camera().borrow().up_vec();
+            Some(measurement::rotation_angle_label_instances(
+                text, position, right, up,
+            ))
+        });
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RotationAngleLetter(
+                letters.unwrap_or_else(|| vec![vec![]; NB_ROTATION_ANGLE_CHARS]),
+            ));
+    }
+
+    /// Draw the coordinates of the cells of the grid under the cursor that are near the cursor.
+    fn update_grid_coordinate_labels(&mut self) {
+        let letters = self.pointed_grid_position.and_then(|position| {
+            let grids = self.designs[0].get_grid();
+            let grid = grids.get(&position.grid).filter(|g| g.visible)?;
+            let right = self.view.borrow().get_camera().borrow().right_vec();
+            let up = self.view.borrow().get_camera().borrow().up_vec();
+            Some(measurement::grid_coordinate_label_instances(
+                grid, position, right, up,
+            ))
+        });
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::GridCoordinateLetter(
+                letters.unwrap_or_else(|| vec![vec![]; NB_GRID_COORDINATE_CHARS]),
+            ));
+    }
+
+    /// Compute the graduations of the nucleotide ruler of the selected helices and of the helix
+    /// under the cursor.
+    fn update_ruler_graduations<S: AppState>(&mut self, app_state: &S) {
+        let graduations: Vec<Graduation> = if app_state.get_draw_options().show_nucleotide_ruler {
+            self.get_ruled_helices(app_state)
+                .into_iter()
+                .flat_map(|h_id| self.designs[0].get_ruler_graduations(h_id))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if graduations != self.ruler_graduations {
+            self.ruler_graduations = graduations;
+            self.ruler_update = true;
+        }
+    }
+
+    /// The helices along which the nucleotide ruler is drawn: the selected helices and the helix
+    /// under the cursor.
+    fn get_ruled_helices<S: AppState>(&self, app_state: &S) -> BTreeSet<usize> {
+        let selected = app_state.get_selection().iter().filter_map(|s| match s {
+            Selection::Helix { helix_id, .. } => Some(*helix_id),
+            _ => None,
+        });
+        let pointed = app_state.get_candidates().iter().filter_map(|s| match s {
+            Selection::Helix { helix_id, .. } => Some(*helix_id),
+            Selection::Nucleotide(_, nucl) => Some(nucl.helix),
+            _ => None,
+        });
+        selected.chain(pointed).collect()
+    }
+
+    /// Draw the ticks and the labels of the nucleotide ruler if they were modified or if the
+    /// camera moved.
+    fn update_ruler(&mut self) {
+        let camera = self.view.borrow().get_camera();
+        let camera = camera.borrow();
+        let camera_moved = self.ruler_camera != Some((camera.position, camera.rotor));
+        if !self.ruler_update && !(camera_moved && !self.ruler_graduations.is_empty()) {
+            return;
+        }
+        let instances = RulerInstances::new(
+            &self.ruler_graduations,
+            camera.position,
+            camera.right_vec(),
+            camera.up_vec(),
+            self.designs[0].get_parameters().z_step,
+        );
+        self.ruler_camera = Some((camera.position, camera.rotor));
+        self.ruler_update = false;
+        let ticks: Vec<_> = instances
+            .ticks
+            .into_iter()
+            .map(|(pos1, pos2)| Design3D::<R>::ruler_tick(pos1, pos2))
+            .collect();
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::RulerTick, Rc::new(ticks)));
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RulerLetter(instances.letters));
+    }
+
+    /// If the measurement has changed since the last call to this method, return the new
+    /// description of the measurement.
+    pub fn take_measurement_info_update(&mut self) -> Option<Option<String>> {
+        if self.measurement_info_update {
+            self.measurement_info_update = false;
+            Some(self.measurement.as_ref().map(Measurement::info))
+        } else {
+            None
+        }
+    }
+
     fn convert_free_end(
         &self,
         free_end: &FreeXoverEnd,
@@ -1386,8 +2386,60 @@ impl<R: DesignReader> Data<R> {
 
     /// Notify the view that the set of instances have been modified.
     fn update_instances<S: AppState>(&mut self, app_state: &S) {
-        let mut spheres = Vec::with_capacity(10_000);
-        let mut tubes = Vec::with_capacity(10_000);
+        let show_insertion_representents = app_state.show_insertion_representents();
+        let design = &self.designs[0];
+        let instances_parameters = design.get_instances_parameters(show_insertion_representents);
+        // If the way the strands are drawn changed, all their instances must be recomputed
+        let modified_strands = if self.instances_parameters.as_ref() == Some(&instances_parameters)
+        {
+            self.previous_reader
+                .as_ref()
+                .and_then(|older| design.get_modified_strands(older))
+        } else {
+            None
+        };
+        self.instances_parameters = Some(instances_parameters);
+
+        let (sphere_ids, orphan_spheres) = design.get_sphere_ids_by_strand();
+        let mut sphere_tail = design.id_to_raw_instances(orphan_spheres);
+        sphere_tail.extend(design.get_additional_spheres_raw(show_insertion_representents));
+        let (tube_ids, orphan_tubes) = design.get_tube_ids_by_strand(show_insertion_representents);
+        let mut tube_tail: Vec<_> = orphan_tubes
+            .into_iter()
+            .filter_map(|id| design.make_tube_raw_instance(id))
+            .collect();
+        tube_tail.extend(design.get_additional_tubes_raw(show_insertion_representents));
+        tube_tail.extend(self.level_of_detail.cylinders());
+        let sheet_instances = if app_state.show_bezier_paths() {
+            let (bezier_spheres, bezier_tubes) = design.get_bezier_paths_elements(app_state);
+            sphere_tail.extend(bezier_spheres);
+            tube_tail.extend(bezier_tubes);
+            let (sheet_instances, corner_spheres) = design.get_bezier_sheets(app_state);
+            sphere_tail.extend(corner_spheres);
+            sheet_instances
+        } else {
+            Default::default()
+        };
+        let plane_name_letters = measurement::bezier_plane_name_label_instances(
+            design.get_bezier_plane_names(&sheet_instances),
+        );
+        let spheres = self.sphere_instances.update(
+            sphere_ids,
+            modified_strands.as_ref(),
+            sphere_tail,
+            |id| design.make_raw_instance(id),
+        );
+        let tubes =
+            self.tube_instances
+                .update(tube_ids, modified_strands.as_ref(), tube_tail, |id| {
+                    design.make_tube_raw_instance(id)
+                });
+        log::debug!(
+            "Recomputed {} sphere and {} tube instances",
+            spheres.nb_regenerated,
+            tubes.nb_regenerated
+        );
+
         let mut suggested_spheres = Vec::with_capacity(1000);
         let mut suggested_tubes = Vec::with_capacity(1000);
         let mut pasted_spheres = Vec::with_capacity(1000);
@@ -1396,25 +2448,12 @@ impl<R: DesignReader> Data<R> {
         let mut letters = Vec::new();
         let mut grids = BTreeMap::new();
         let mut cones = Vec::new();
+        let mut anchor_cones = Vec::new();
+        let mut modification_spheres = Vec::new();
+        let mut modification_tubes = Vec::new();
+        let show_anchors = app_state.get_draw_options().show_anchors;
         for design in self.designs.iter() {
-            for sphere in design
-                .get_spheres_raw(app_state.show_insertion_representents())
-                .iter()
-            {
-                spheres.push(*sphere);
-            }
-            for tube in design
-                .get_tubes_raw(app_state.show_insertion_representents())
-                .iter()
-            {
-                tubes.push(*tube);
-            }
-            if app_state.show_bezier_paths() {
-                let (bezier_spheres, bezier_tubes) = design.get_bezier_paths_elements(app_state);
-                spheres.extend(bezier_spheres);
-                tubes.extend(bezier_tubes);
-            }
-            letters = design.get_letter_instances(app_state.show_insertion_representents());
+            letters = design.get_letter_instances(show_insertion_representents);
             for (grid_id, grid) in design.get_grid().iter().filter(|g| g.1.visible) {
                 grids.insert(*grid_id, grid.clone());
             }
@@ -1431,26 +2470,25 @@ impl<R: DesignReader> Data<R> {
             for tube in tubes {
                 pasted_tubes.push(tube);
             }
-            for cone in design.get_cones_raw(app_state.show_insertion_representents()) {
+            for cone in design.get_cones_raw(show_insertion_representents) {
                 cones.push(cone);
             }
+            if show_anchors {
+                anchor_cones.extend(design.get_anchor_cones());
+            }
+            let (spheres, tubes) = design.get_modification_markers();
+            modification_spheres.extend(spheres);
+            modification_tubes.extend(tubes);
         }
         self.update_free_xover(app_state.get_candidates());
-        let (sheet_instances, corner_spheres) = if app_state.show_bezier_paths() {
-            self.designs[0].get_bezier_sheets(app_state)
-        } else {
-            (Default::default(), Default::default())
-        };
-        spheres.extend(corner_spheres);
         self.view
             .borrow_mut()
             .update(ViewUpdate::BezierSheets(sheet_instances));
         self.view
             .borrow_mut()
-            .update(ViewUpdate::RawDna(Mesh::Tube, Rc::new(tubes)));
-        self.view
-            .borrow_mut()
-            .update(ViewUpdate::RawDna(Mesh::Sphere, Rc::new(spheres)));
+            .update(ViewUpdate::BezierPlaneNameLetter(plane_name_letters));
+        self.send_cached_instances(Mesh::Tube, tubes);
+        self.send_cached_instances(Mesh::Sphere, spheres);
         self.view.borrow_mut().update(ViewUpdate::RawDna(
             Mesh::SuggestionSphere,
             Rc::new(suggested_spheres),
@@ -1471,23 +2509,29 @@ impl<R: DesignReader> Data<R> {
         self.view
             .borrow_mut()
             .update(ViewUpdate::RawDna(Mesh::Prime3Cone, Rc::new(cones)));
-        let bonds = self.designs[0].get_all_hbond();
-        if app_state.get_draw_options().h_bonds == HBoundDisplay::Ellipsoid {
-            self.view.borrow_mut().update(ViewUpdate::RawDna(
-                Mesh::HBond,
-                Rc::new(bonds.partial_h_bonds),
-            ));
-        } else {
-            self.view
-                .borrow_mut()
-                .update(ViewUpdate::RawDna(Mesh::HBond, Rc::new(bonds.full_h_bonds)));
-        }
+        self.view
+            .borrow_mut()
+            .update(ViewUpdate::RawDna(Mesh::AnchorCone, Rc::new(anchor_cones)));
+        self.view.borrow_mut().update(ViewUpdate::RawDna(
+            Mesh::ModificationSphere,
+            Rc::new(modification_spheres),
+        ));
         self.view.borrow_mut().update(ViewUpdate::RawDna(
-            Mesh::BaseEllipsoid,
-            Rc::new(bonds.ellipsoids),
+            Mesh::ModificationTube,
+            Rc::new(modification_tubes),
         ));
     }
 
+    /// Notify the view that the instances of `mesh` have been modified, only writing the modified
+    /// ranges if possible.
+    fn send_cached_instances(&self, mesh: Mesh, cached: CachedInstances) {
+        let update = match cached.modified_ranges {
+            Some(ranges) => ViewUpdate::RawDnaRanges(mesh, cached.instances, ranges),
+            None => ViewUpdate::RawDna(mesh, cached.instances),
+        };
+        self.view.borrow_mut().update(update);
+    }
+
     fn update_discs<S: AppState>(&mut self, app_state: &S) {
         let mut discs = Vec::new();
         let mut letters: Vec<Vec<LetterInstance>> = vec![vec![]; 10];
@@ -1650,6 +2694,9 @@ impl<R: DesignReader> Data<R> {
                 helix_id,
                 bezier_control,
             }) => self.designs[0].get_bezier_control_basis(helix_id, bezier_control),
+            Some(SceneElement::External3DObject(object_id)) => {
+                self.designs[0].get_external_object_basis(object_id)
+            }
             _ => None,
         };
         let from_selection = match app_state.get_selection().get(0) {
@@ -1710,6 +2757,27 @@ impl<R: DesignReader> Data<R> {
         design.get_nucl_position(nucl)
     }
 
+    /// If `element` is a nucleotide or a bond of a strand, return the nucleotide after which the
+    /// strand must be nicked and the identifier of the strand.
+    pub fn element_to_nick(&self, element: &Option<SceneElement>) -> Option<(Nucl, usize)> {
+        if let Some(SceneElement::DesignElement(d_id, e_id)) = element {
+            self.designs.get(*d_id as usize)?.get_nick(*e_id)
+        } else {
+            None
+        }
+    }
+
+    /// If `element` is a nucleotide at the end of a strand, return this end.
+    pub fn element_to_strand_end(&self, element: &Option<SceneElement>) -> Option<StrandEnd> {
+        let (nucl, d_id) = self.element_to_nucl(element, true)?;
+        self.designs.get(d_id)?.get_strand_end(nucl)
+    }
+
+    /// Return the end of an other strand with which `end` can be ligated.
+    pub fn get_ligation_partner(&self, end: StrandEnd) -> Option<StrandEnd> {
+        self.designs.get(0)?.get_ligation_partner(end)
+    }
+
     /*
     /// Set the selection to a given nucleotide if it exists in the design.
     pub fn select_nucl(&mut self, nucl: Nucl, design_id: usize) {
@@ -1719,7 +2787,6 @@ impl<R: DesignReader> Data<R> {
         }
     }*/
 
-    #[allow(dead_code)]
     pub fn get_candidate_nucl(&self) -> Option<Nucl> {
         match self.candidate_element.as_ref() {
             None => None,
@@ -1746,7 +2813,15 @@ impl<R: DesignReader> Data<R> {
         if let Some(free_xover) = self.free_xover.as_mut() {
             free_xover.target = FreeXoverEnd::Free(position);
             if let FreeXoverEnd::Nucl(origin_nucl) = free_xover.source {
-                if let Some((nucl, _)) = nucl.filter(|n| n.1 == free_xover.design_id) {
+                let design_id = free_xover.design_id;
+                // If there is no nucleotide under the cursor, snap to the closest one
+                let nucl = nucl.filter(|n| n.1 == design_id).map(|n| n.0).or_else(|| {
+                    self.designs
+                        .get(design_id)?
+                        .closest_nucl(position, FREE_XOVER_SNAPPING_DISTANCE)
+                        .filter(|n| *n != origin_nucl)
+                });
+                if let Some(nucl) = nucl {
                     if !self.designs[free_xover.design_id].both_prime3(origin_nucl, nucl)
                         && !self.designs[free_xover.design_id].both_prime5(origin_nucl, nucl)
                     {
@@ -1757,6 +2832,36 @@ impl<R: DesignReader> Data<R> {
         }
     }
 
+    /// Return the note of the strand containing the candidate element, if it has one.
+    pub fn get_candidate_note(&self) -> Option<String> {
+        if let Some(SceneElement::DesignElement(d_id, e_id)) = self.candidate_element {
+            let design = self.designs.get(d_id as usize)?;
+            design.get_strand_note(design.get_strand(e_id)?)
+        } else {
+            None
+        }
+    }
+
+    /// Return the identifier of the helix at a grid position, if there is one.
+    pub fn get_helix_at_grid_position(&self, d_id: u32, position: GridPosition) -> Option<usize> {
+        self.designs
+            .get(d_id as usize)
+            .and_then(|d| d.get_helix_grid(position))
+            .map(|h_id| h_id as usize)
+    }
+
+    /// Return the source, target and design of the free cross-over if both its ends are
+    /// nucleotides.
+    pub fn get_free_xover_nucls(&self) -> Option<(Nucl, Nucl, usize)> {
+        let free_xover = self.free_xover.as_ref()?;
+        match (&free_xover.source, &free_xover.target) {
+            (FreeXoverEnd::Nucl(source), FreeXoverEnd::Nucl(target)) => {
+                Some((*source, *target, free_xover.design_id))
+            }
+            _ => None,
+        }
+    }
+
     pub fn end_free_xover(&mut self) {
         self.free_xover_update = true;
         self.free_xover = None;
@@ -1872,6 +2977,9 @@ impl<R: DesignReader> Data<R> {
             Selection::Xover(d_id, xover_id) => design
                 .get_element_identifier_from_xover_id(*xover_id)
                 .map(|e_id| SceneElement::DesignElement(*d_id, e_id)),
+            Selection::External3DObject(object_id) => {
+                Some(SceneElement::External3DObject(*object_id))
+            }
             _ => None,
         }
     }
@@ -1929,6 +3037,8 @@ impl<R: DesignReader> Data<R> {
             }
             SceneElement::BezierTengent { .. } => None,
             SceneElement::PlaneCorner { .. } => None,
+            SceneElement::StrandEndHandle { .. } => None,
+            SceneElement::External3DObject(_) => None,
         }
     }
 
@@ -2011,6 +3121,22 @@ impl<R: DesignReader> ControllerData for Data<R> {
         self.get_nucl_position(nucl, design_id)
     }
 
+    fn element_to_nick(&self, element: &Option<SceneElement>) -> Option<(Nucl, usize)> {
+        self.element_to_nick(element)
+    }
+
+    fn element_to_strand_end(&self, element: &Option<SceneElement>) -> Option<StrandEnd> {
+        self.element_to_strand_end(element)
+    }
+
+    fn get_ligation_partner(&self, end: StrandEnd) -> Option<StrandEnd> {
+        self.get_ligation_partner(end)
+    }
+
+    fn get_strand_end_handle_nucl(&self, element: &Option<SceneElement>) -> Option<Nucl> {
+        self.get_strand_end_handle_nucl(element)
+    }
+
     fn attempt_xover(
         &self,
         source: &Option<SceneElement>,
@@ -20,18 +20,21 @@ use super::super::view::{
     ConeInstance, Ellipsoid, Instanciable, RawDnaInstance, Sheet2D, SphereInstance, TubeInstance,
 };
 use super::super::GridInstance;
+use super::level_of_detail::HelixSegment;
+use super::ruler::{self, Graduation};
 use super::{ultraviolet, LetterInstance, SceneElement};
 use ensnano_design::grid::{GridId, GridObject, GridPosition};
 use ensnano_design::{grid::HelixGridPosition, Nucl};
 use ensnano_design::{
     AdditionalStructure, BezierPathId, BezierPlaneDescriptor, BezierPlaneId, BezierVertex,
-    Collection, CubicBezierConstructor, CurveDescriptor, External3DObjects, InstanciatedPath,
-    Parameters,
+    Collection, CubicBezierConstructor, CurveDescriptor, External3DObjectId, External3DObjects,
+    InstanciatedPath, Parameters,
 };
 pub use ensnano_design::{SurfaceInfo, SurfacePoint};
 use ensnano_interactor::consts::*;
 use ensnano_interactor::{
-    graphics::{LoopoutBond, LoopoutNucl},
+    application::Camera3D,
+    graphics::{Colormap, LoopoutBond, LoopoutNucl, StrandColoring, StrandColors},
     phantom_helix_encoder_bound, phantom_helix_encoder_nucl, BezierControlPoint, ObjectType,
     PhantomElement, Referential, PHANTOM_RANGE,
 };
@@ -49,6 +52,77 @@ pub struct Design3D<R: DesignReader> {
     id: u32,
     symbol_map: HashMap<char, usize>,
     pub thick_helices: bool,
+    /// If this is not `None`, only the elements whose identifier is in the set are drawn.
+    pub isolated_elements: Option<Rc<HashSet<u32>>>,
+    /// If true and the design has an additional structure, only the additional structure is
+    /// drawn
+    pub hide_idealized_design: bool,
+    /// If this is not `None`, the elements are colored according to the torsion of the helices
+    pub torsion_heatmap: Option<TorsionHeatmap>,
+    /// If this is not `None`, the nucleotides are colored according to their strain in the
+    /// running simulation
+    pub strain_heatmap: Option<StrainHeatmap>,
+    /// If true, the cross-overs are colored according to their strain
+    pub color_xovers_by_strain: bool,
+    /// If this is not `None`, the non-scaffold strands are colored according to a property
+    pub strand_colors: Option<StrandColors>,
+    /// The helices that are drawn as cylinders instead of nucleotides and bonds
+    pub reduced_helices: Rc<HashSet<usize>>,
+}
+
+/// The attributes of a `Design3D` that can modify the instances of all the strands.
+#[derive(Clone, PartialEq)]
+pub(super) struct InstancesParameters {
+    thick_helices: bool,
+    isolated_elements: Option<Rc<HashSet<u32>>>,
+    hide_idealized_design: bool,
+    torsion_heatmap: Option<TorsionHeatmap>,
+    strain_heatmap: Option<StrainHeatmap>,
+    color_xovers_by_strain: bool,
+    strand_colors: Option<StrandColors>,
+    reduced_helices: Rc<HashSet<usize>>,
+    show_insertion_representents: bool,
+}
+
+/// A diverging colormap mapping the torsion of the helices to colors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TorsionHeatmap {
+    /// The torsion, in degrees, that is mapped to the extreme colors of the colormap
+    pub range: f32,
+}
+
+impl TorsionHeatmap {
+    fn color(&self, torsion: f32) -> u32 {
+        let t = (torsion / self.range).clamp(-1., 1.);
+        if t < 0. {
+            lerp_color(TORSION_RELAXED_COLOR, TORSION_UNDERTWIST_COLOR, -t)
+        } else {
+            lerp_color(TORSION_RELAXED_COLOR, TORSION_OVERTWIST_COLOR, t)
+        }
+    }
+}
+
+/// A sequential colormap mapping the strain of the nucleotides to colors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrainHeatmap {
+    /// The strain that is mapped to the most strained color of the colormap
+    pub range: f32,
+}
+
+impl StrainHeatmap {
+    fn color(&self, strain: f32) -> u32 {
+        let t = (strain / self.range).clamp(0., 1.);
+        lerp_color(STRAIN_RELAXED_COLOR, STRAIN_MAX_COLOR, t)
+    }
+}
+
+fn lerp_color(from: u32, to: u32, t: f32) -> u32 {
+    let channel = |shift: u32| {
+        let a = ((from >> shift) & 0xFF) as f32;
+        let b = ((to >> shift) & 0xFF) as f32;
+        ((a + t * (b - a)).round() as u32) << shift
+    };
+    channel(24) | channel(16) | channel(8) | channel(0)
 }
 
 impl<R: DesignReader> Design3D<R> {
@@ -62,9 +136,199 @@ impl<R: DesignReader> Design3D<R> {
             id,
             symbol_map,
             thick_helices: true,
+            isolated_elements: None,
+            hide_idealized_design: false,
+            torsion_heatmap: None,
+            strain_heatmap: None,
+            color_xovers_by_strain: false,
+            strand_colors: None,
+            reduced_helices: Default::default(),
         }
     }
 
+    pub fn into_reader(self) -> R {
+        self.design
+    }
+
+    pub(super) fn get_instances_parameters(
+        &self,
+        show_insertion_representents: bool,
+    ) -> InstancesParameters {
+        InstancesParameters {
+            thick_helices: self.thick_helices,
+            isolated_elements: self.isolated_elements.clone(),
+            hide_idealized_design: self.hide_idealized_design,
+            torsion_heatmap: self.torsion_heatmap,
+            strain_heatmap: self.strain_heatmap,
+            color_xovers_by_strain: self.color_xovers_by_strain,
+            strand_colors: self.strand_colors.clone(),
+            reduced_helices: self.reduced_helices.clone(),
+            show_insertion_representents,
+        }
+    }
+
+    /// Return the identifiers of the strands whose instances may differ between `older` and the
+    /// design of `self`, or `None` if the instances of all the strands must be recomputed.
+    pub fn get_modified_strands(&self, older: &R) -> Option<HashSet<usize>> {
+        let modified = self.design.get_modified_objects(older)?;
+        let mut ret = modified.strands;
+        if !modified.helices.is_empty() {
+            for id in self.design.get_all_nucl_ids() {
+                let on_modified_helix = self
+                    .design
+                    .get_id_of_helix_containing(id)
+                    .map(|h_id| modified.helices.contains(&h_id))
+                    .unwrap_or(false);
+                if on_modified_helix {
+                    ret.extend(self.design.get_id_of_strand_containing(id));
+                }
+            }
+        }
+        Some(ret)
+    }
+
+    /// Return the colors of the strands when they are colored according to `coloring`.
+    pub fn make_strand_colors(
+        &self,
+        coloring: StrandColoring,
+        colormap: Colormap,
+    ) -> Option<StrandColors> {
+        if coloring == StrandColoring::Stored {
+            return None;
+        }
+        let values = self.design.get_strand_property_values(coloring);
+        StrandColors::new(coloring, colormap, &values)
+    }
+
+    /// Return the colormap used to display the torsion of the helices.
+    ///
+    /// If `range` is `None`, the range of the colormap is the largest absolute torsion among
+    /// the visible nucleotides.
+    pub fn make_torsion_heatmap(&self, range: Option<f32>) -> TorsionHeatmap {
+        let range = range.unwrap_or_else(|| {
+            self.design
+                .get_all_visible_nucl_ids()
+                .into_iter()
+                .filter_map(|id| self.design.get_torsion(id))
+                .fold(MIN_AUTO_TORSION_RANGE, |max, t| max.max(t.abs()))
+        });
+        TorsionHeatmap { range }
+    }
+
+    /// Return the colormap used to display the strain of the nucleotides, or `None` if the
+    /// design does not have strain data.
+    ///
+    /// The range of the colormap is the largest strain among the visible nucleotides.
+    pub fn make_strain_heatmap(&self) -> Option<StrainHeatmap> {
+        let strains: Vec<f32> = self
+            .design
+            .get_all_visible_nucl_ids()
+            .into_iter()
+            .filter_map(|id| self.design.get_strain(id))
+            .collect();
+        if strains.is_empty() {
+            None
+        } else {
+            let range = strains.into_iter().fold(MIN_AUTO_STRAIN_RANGE, f32::max);
+            Some(StrainHeatmap { range })
+        }
+    }
+
+    /// Return true if the element with identifier `id` must be drawn
+    fn is_drawn(&self, id: u32) -> bool {
+        if self.hide_idealized_design && self.design.get_additional_structure().is_some() {
+            return false;
+        }
+        self.isolated_elements
+            .as_ref()
+            .map(|elements| elements.contains(&id))
+            .unwrap_or(true)
+    }
+
+    /// Return true if the element with identifier `id` belongs to a helix that is drawn as
+    /// cylinders. A bond is reduced only if both its nucleotides are.
+    fn is_reduced(&self, id: u32) -> bool {
+        if self.reduced_helices.is_empty() {
+            return false;
+        }
+        let nucl_is_reduced = |id: u32| {
+            self.design
+                .get_id_of_helix_containing(id)
+                .map(|h_id| self.reduced_helices.contains(&h_id))
+                .unwrap_or(false)
+        };
+        match self.design.get_object_type(id) {
+            Some(ObjectType::Nucleotide(_)) => nucl_is_reduced(id),
+            Some(ObjectType::Bound(id1, id2)) => nucl_is_reduced(id1) && nucl_is_reduced(id2),
+            None => false,
+        }
+    }
+
+    /// Return the segments of helices that are drawn as single cylinders when their helix is
+    /// far from the camera.
+    ///
+    /// A segment is a run of at most `LOD_SEGMENT_LENGTH` consecutive occupied positions of a
+    /// helix, so that the cylinders follow curved helices.
+    pub fn get_helix_segments(&self) -> Vec<HelixSegment> {
+        // For each helix, the number of nucleotides at each occupied position and the
+        // identifier of one of them
+        let mut positions: BTreeMap<usize, BTreeMap<isize, (u32, usize)>> = BTreeMap::new();
+        for id in self.design.get_all_visible_nucl_ids() {
+            if let Some(nucl) = self.design.get_nucl_with_id(id) {
+                let entry = positions
+                    .entry(nucl.helix)
+                    .or_default()
+                    .entry(nucl.position)
+                    .or_insert((id, 0));
+                entry.1 += 1;
+            }
+        }
+        let axis_position = |helix: usize, position: isize| {
+            self.design.get_position_of_nucl_on_helix(
+                Nucl {
+                    helix,
+                    position,
+                    forward: true,
+                },
+                Referential::World,
+                true,
+            )
+        };
+        let mut ret = Vec::new();
+        for (helix, positions) in positions {
+            let mut runs: Vec<Vec<(isize, u32, usize)>> = Vec::new();
+            for (position, (nucl_id, nb_nucl)) in positions {
+                let extends_last_run = runs
+                    .last()
+                    .and_then(|run| run.last().map(|last| (run.len(), last.0)))
+                    .map(|(len, last)| last + 1 == position && (len as isize) < LOD_SEGMENT_LENGTH)
+                    .unwrap_or(false);
+                if extends_last_run {
+                    runs.last_mut().unwrap().push((position, nucl_id, nb_nucl));
+                } else {
+                    runs.push(vec![(position, nucl_id, nb_nucl)]);
+                }
+            }
+            for run in runs {
+                let first = run[0].0;
+                let last = run[run.len() - 1].0;
+                // The cylinder covers the whole width of the last position
+                if let Some((start, end)) =
+                    axis_position(helix, first).zip(axis_position(helix, last + 1))
+                {
+                    ret.push(HelixSegment {
+                        helix,
+                        start,
+                        end,
+                        nucl_id: run[run.len() / 2].1,
+                        nb_nucl: run.iter().map(|r| r.2).sum(),
+                    })
+                }
+            }
+        }
+        ret
+    }
+
     /// Convert a list of ids into a list of instances
     pub fn id_to_raw_instances(&self, ids: Vec<u32>) -> Vec<RawDnaInstance> {
         let mut ret = Vec::new();
@@ -76,12 +340,43 @@ impl<R: DesignReader> Design3D<R> {
         ret
     }
 
-    /// Return the list of raw sphere instances to be displayed to represent the design
-    pub fn get_spheres_raw(&self, show_insertion_representents: bool) -> Rc<Vec<RawDnaInstance>> {
-        let ids = self.design.get_all_visible_nucl_ids();
-        let mut ret = self.id_to_raw_instances(ids);
+    /// Group `ids` by the strand containing them. Return the groups and the identifiers that
+    /// do not belong to a strand.
+    fn group_by_strand(&self, ids: Vec<u32>) -> (BTreeMap<usize, Vec<u32>>, Vec<u32>) {
+        let mut groups: BTreeMap<usize, Vec<u32>> = BTreeMap::new();
+        let mut orphans = Vec::new();
+        for id in ids {
+            if let Some(s_id) = self.design.get_id_of_strand_containing(id) {
+                groups.entry(s_id).or_default().push(id);
+            } else {
+                orphans.push(id);
+            }
+        }
+        (groups, orphans)
+    }
+
+    /// Return the identifiers of the nucleotides that are drawn as spheres, grouped by strand,
+    /// and the identifiers of those that do not belong to a strand.
+    pub fn get_sphere_ids_by_strand(&self) -> (BTreeMap<usize, Vec<u32>>, Vec<u32>) {
+        let mut ids = self.design.get_all_visible_nucl_ids();
+        ids.retain(|id| self.is_drawn(*id) && !self.is_reduced(*id));
+        self.group_by_strand(ids)
+    }
+
+    /// Return the sphere instances representing the nucleotides of the loopouts and the
+    /// additional structure of the design
+    pub fn get_additional_spheres_raw(
+        &self,
+        show_insertion_representents: bool,
+    ) -> Vec<RawDnaInstance> {
+        let mut ret = Vec::new();
         if !show_insertion_representents {
-            for loopout_nucl in self.design.get_all_loopout_nucl() {
+            for loopout_nucl in self
+                .design
+                .get_all_loopout_nucl()
+                .iter()
+                .filter(|n| self.is_drawn(n.repr_bond_identifier))
+            {
                 ret.push(
                     SphereInstance {
                         position: loopout_nucl.position,
@@ -120,7 +415,7 @@ impl<R: DesignReader> Design3D<R> {
                 }
             }
         }
-        Rc::new(ret)
+        ret
     }
 
     pub fn get_pasted_strand(&self) -> (Vec<RawDnaInstance>, Vec<RawDnaInstance>) {
@@ -160,7 +455,10 @@ impl<R: DesignReader> Design3D<R> {
     ) -> Vec<Vec<LetterInstance>> {
         let ids = self.design.get_all_nucl_ids();
         let mut vecs = vec![Vec::new(); NB_PRINTABLE_CHARS];
-        for id in ids {
+        for id in ids
+            .into_iter()
+            .filter(|id| self.is_drawn(*id) && !self.is_reduced(*id))
+        {
             let pos = self.design.get_symbol_position(id);
             let symbol = self.design.get_symbol(id);
             if let Some((pos, symbol)) = pos.zip(symbol) {
@@ -177,7 +475,12 @@ impl<R: DesignReader> Design3D<R> {
             }
         }
         if !show_insertion_representents {
-            for loopout_nucl in self.design.get_all_loopout_nucl() {
+            for loopout_nucl in self
+                .design
+                .get_all_loopout_nucl()
+                .iter()
+                .filter(|n| self.is_drawn(n.repr_bond_identifier))
+            {
                 if let Some(symbol) = loopout_nucl.basis {
                     let pos = loopout_nucl.position;
                     if let Some(id) = self.symbol_map.get(&symbol) {
@@ -198,6 +501,7 @@ impl<R: DesignReader> Design3D<R> {
 
     pub fn get_cones_raw(&self, show_insertion_representents: bool) -> Vec<RawDnaInstance> {
         let mut ids = self.design.get_all_visible_bound_ids();
+        ids.retain(|id| self.is_drawn(*id) && !self.is_reduced(*id));
         if !show_insertion_representents {
             ids.retain(|id| self.design.get_insertion_length(*id) == 0);
         }
@@ -209,24 +513,49 @@ impl<R: DesignReader> Design3D<R> {
         vec
     }
 
-    /// Return the list of tube instances to be displayed to represent the design
-    pub fn get_tubes_raw(&self, show_insertion_representents: bool) -> Rc<Vec<RawDnaInstance>> {
+    /// Return the identifiers of the bonds that are drawn as tubes, grouped by strand, and the
+    /// identifiers of those that do not belong to a strand.
+    pub fn get_tube_ids_by_strand(
+        &self,
+        show_insertion_representents: bool,
+    ) -> (BTreeMap<usize, Vec<u32>>, Vec<u32>) {
         let mut ids = self.design.get_all_visible_bound_ids();
+        ids.retain(|id| self.is_drawn(*id) && !self.is_reduced(*id));
         if !show_insertion_representents {
             ids.retain(|id| self.design.get_insertion_length(*id) == 0);
         }
-        let expected_length = if self.thick_helices {
+        self.group_by_strand(ids)
+    }
+
+    fn expected_bond_length(&self) -> f32 {
+        if self.thick_helices {
             self.design.get_expected_bond_length()
         } else {
             Parameters::INTER_CENTER_GAP
-        };
-        let mut ret: Vec<_> = self
-            .id_to_raw_instances(ids)
-            .into_iter()
-            .map(|x| x.with_expected_length(expected_length))
-            .collect();
+        }
+    }
+
+    /// Return the tube instance representing the bond with identifier `id`
+    pub fn make_tube_raw_instance(&self, id: u32) -> Option<RawDnaInstance> {
+        self.make_raw_instance(id)
+            .map(|x| x.with_expected_length(self.expected_bond_length()))
+    }
+
+    /// Return the tube instances representing the bonds of the loopouts and the additional
+    /// structure of the design
+    pub fn get_additional_tubes_raw(
+        &self,
+        show_insertion_representents: bool,
+    ) -> Vec<RawDnaInstance> {
+        let expected_length = self.expected_bond_length();
+        let mut ret = Vec::new();
         if !show_insertion_representents {
-            for loopout_bond in self.design.get_all_loopout_bonds() {
+            for loopout_bond in self
+                .design
+                .get_all_loopout_bonds()
+                .iter()
+                .filter(|b| self.is_drawn(b.repr_bond_identifier))
+            {
                 ret.push(
                     create_dna_bound(
                         loopout_bond.position_prime5,
@@ -261,7 +590,7 @@ impl<R: DesignReader> Design3D<R> {
                 )
             }
         }
-        Rc::new(ret)
+        ret
     }
 
     pub fn get_model_matrix(&self) -> Mat4 {
@@ -272,6 +601,16 @@ impl<R: DesignReader> Design3D<R> {
         self.design.get_bezier_grid_used_by_helix(h_id)
     }
 
+    /// Return the nucleotide that is the closest to `position`, in world coordinates, if it is
+    /// at distance at most `max_dist` from it.
+    pub fn closest_nucl(&self, position: Vec3, max_dist: f32) -> Option<Nucl> {
+        let model_position = self
+            .get_model_matrix()
+            .inversed()
+            .transform_point3(position);
+        self.design.closest_nucl(model_position, max_dist)
+    }
+
     /// Convert return an instance representing the object with identifier `id` and custom
     /// color and radius.
     pub(super) fn make_instance(
@@ -385,11 +724,22 @@ impl<R: DesignReader> Design3D<R> {
     }
 
     /// Return (h bonds instances, ellipoids instances)
-    pub(super) fn get_all_hbond(&self) -> HBoundsInstances {
+    /// Return the instances representing the hydrogen bonds of the design.
+    ///
+    /// If `restriction` is not `None`, only the bonds whose two nucleotides belong to
+    /// `restriction` are represented.
+    pub(super) fn get_hbonds(&self, restriction: Option<&HashSet<u32>>) -> HBoundsInstances {
         let mut full_h_bonds = Vec::new();
         let mut partial_h_bonds = Vec::new();
         let mut ellipsoids = Vec::new();
-        for hbond in self.design.get_all_h_bonds() {
+        let in_restriction = |id: u32| restriction.map(|r| r.contains(&id)).unwrap_or(true);
+        for hbond in self
+            .design
+            .get_all_h_bonds()
+            .iter()
+            .filter(|b| self.is_drawn(b.forward.nucl_id) || self.is_drawn(b.backward.nucl_id))
+            .filter(|b| in_restriction(b.forward.nucl_id) && in_restriction(b.backward.nucl_id))
+        {
             let forward_bond = create_dna_bound(
                 hbond.forward.backbone,
                 hbond.forward.center_of_mass,
@@ -440,9 +790,9 @@ impl<R: DesignReader> Design3D<R> {
             full_h_bonds.push(full_bond.to_raw_instance());
         }
         HBoundsInstances {
-            partial_h_bonds,
-            full_h_bonds,
-            ellipsoids,
+            partial_h_bonds: Rc::new(partial_h_bonds),
+            full_h_bonds: Rc::new(full_h_bonds),
+            ellipsoids: Rc::new(ellipsoids),
         }
     }
 
@@ -646,11 +996,17 @@ impl<R: DesignReader> Design3D<R> {
         let mut spheres = Vec::new();
         let mut tubes = Vec::new();
         for (helix_id, short) in helix_ids.iter() {
-            let range_phantom = if *short {
-                PHANTOM_RANGE / 10
-            } else {
-                PHANTOM_RANGE
-            } as isize;
+            // The length is bounded by PHANTOM_RANGE so that the phantom nucleotides can still be
+            // identified when picking them
+            let range_phantom = self
+                .design
+                .get_helix_phantom_length(*helix_id as usize)
+                .map(|length| length.min(PHANTOM_RANGE as usize) as isize)
+                .unwrap_or(if *short {
+                    PHANTOM_RANGE as isize / 10
+                } else {
+                    PHANTOM_RANGE as isize
+                });
             for forward in [false, true].iter() {
                 let mut previous_nucl = None;
                 let range = self
@@ -729,10 +1085,22 @@ impl<R: DesignReader> Design3D<R> {
             SceneElement::BezierVertex { path_id, vertex_id } => {
                 self.get_bezier_vertex_position(*path_id, *vertex_id)
             }
+            SceneElement::External3DObject(object_id) => self
+                .design
+                .get_external_objects()
+                .get(object_id)
+                .map(|o| o.position()),
             _ => None,
         }
     }
 
+    pub fn get_external_object_basis(&self, object_id: External3DObjectId) -> Option<Rotor3> {
+        self.design
+            .get_external_objects()
+            .get(&object_id)
+            .map(|o| o.orientation())
+    }
+
     fn get_graphic_element_position(&self, element: &SceneElement) -> Option<Vec3> {
         if self.thick_helices {
             self.get_element_position(element, Referential::World)
@@ -759,7 +1127,9 @@ impl<R: DesignReader> Design3D<R> {
             | SceneElement::BezierVertex { .. }
             | SceneElement::GridCircle(_, _)
             | SceneElement::PlaneCorner { .. }
-            | SceneElement::BezierTengent { .. } => None,
+            | SceneElement::BezierTengent { .. }
+            | SceneElement::StrandEndHandle { .. }
+            | SceneElement::External3DObject(_) => None,
         }
     }
 
@@ -767,6 +1137,79 @@ impl<R: DesignReader> Design3D<R> {
         self.design.get_element_position(id, referential)
     }
 
+    /// Return the identifiers of the drawn nucleotides whose projection by `view_proj` is inside
+    /// the rectangle with corners `c1` and `c2`, given in normalized device coordinates.
+    pub fn get_nucl_ids_in_rectangle(&self, view_proj: Mat4, c1: Vec2, c2: Vec2) -> Vec<u32> {
+        let min = c1.min_by_component(c2);
+        let max = c1.max_by_component(c2);
+        self.design
+            .get_all_visible_nucl_ids()
+            .into_iter()
+            .filter(|id| self.is_drawn(*id))
+            .filter(|id| {
+                self.get_design_element_position(*id, Referential::World)
+                    .map(|position| {
+                        let clip = view_proj * position.into_homogeneous_point();
+                        if clip.w <= 0. {
+                            // The nucleotide is behind the camera
+                            return false;
+                        }
+                        let ndc = Vec2::new(clip.x / clip.w, clip.y / clip.w);
+                        ndc.x >= min.x && ndc.x <= max.x && ndc.y >= min.y && ndc.y <= max.y
+                    })
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Return the identifier of the drawn nucleotide whose projection by `view_proj` is the
+    /// closest to `point`, given in normalized device coordinates, together with that distance
+    /// once multiplied by `scale`.
+    pub fn get_closest_nucl_id_on_screen(
+        &self,
+        view_proj: Mat4,
+        point: Vec2,
+        scale: Vec2,
+    ) -> Option<(u32, f32)> {
+        self.design
+            .get_all_visible_nucl_ids()
+            .into_iter()
+            .filter(|id| self.is_drawn(*id))
+            .filter_map(|id| {
+                let position = self.get_design_element_position(id, Referential::World)?;
+                let clip = view_proj * position.into_homogeneous_point();
+                if clip.w <= 0. {
+                    // The nucleotide is behind the camera
+                    return None;
+                }
+                let ndc = Vec2::new(clip.x / clip.w, clip.y / clip.w);
+                Some((id, ((ndc - point) * scale).mag()))
+            })
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+    }
+
+    /// Return the identifiers of the visible nucleotides that are at distance at most `radius`
+    /// of the ray, together with the distance from `origin` at which the ray reaches them.
+    pub fn get_nucl_ids_on_ray(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        radius: f32,
+    ) -> Vec<(u32, f32)> {
+        let direction = direction.normalized();
+        self.design
+            .get_all_visible_nucl_ids()
+            .into_iter()
+            .filter(|id| self.is_drawn(*id))
+            .filter_map(|id| {
+                let position = self.get_design_element_position(id, Referential::World)?;
+                let depth = (position - origin).dot(direction);
+                let distance_to_ray = (position - origin - depth * direction).mag();
+                (depth > 0. && distance_to_ray <= radius).then_some((id, depth))
+            })
+            .collect()
+    }
+
     pub fn get_design_element_axis_position(
         &self,
         id: u32,
@@ -776,7 +1219,62 @@ impl<R: DesignReader> Design3D<R> {
     }
 
     fn get_color(&self, id: u32) -> Option<u32> {
-        self.design.get_color(id)
+        self.strain_heatmap
+            .and_then(|heatmap| self.get_strain_color(id, &heatmap))
+            .or_else(|| {
+                self.torsion_heatmap
+                    .and_then(|heatmap| self.get_torsion_color(id, &heatmap))
+            })
+            .or_else(|| self.get_xover_strain_color(id))
+            .or_else(|| self.get_property_color(id))
+            .or_else(|| self.design.get_group_color(id))
+            .or_else(|| self.design.get_color(id))
+    }
+
+    fn get_torsion_color(&self, id: u32, heatmap: &TorsionHeatmap) -> Option<u32> {
+        let torsion = match self.get_object_type(id)? {
+            ObjectType::Nucleotide(id) => self.design.get_torsion(id)?,
+            ObjectType::Bound(id1, id2) => {
+                (self.design.get_torsion(id1)? + self.design.get_torsion(id2)?) / 2.
+            }
+        };
+        Some(heatmap.color(torsion))
+    }
+
+    fn get_strain_color(&self, id: u32, heatmap: &StrainHeatmap) -> Option<u32> {
+        let strain = match self.get_object_type(id)? {
+            ObjectType::Nucleotide(id) => self.design.get_strain(id)?,
+            ObjectType::Bound(id1, id2) => self
+                .design
+                .get_strain(id1)?
+                .max(self.design.get_strain(id2)?),
+        };
+        Some(heatmap.color(strain))
+    }
+
+    fn get_property_color(&self, id: u32) -> Option<u32> {
+        let strand_colors = self.strand_colors.as_ref()?;
+        let s_id = self.design.get_id_of_strand_containing(id)?;
+        strand_colors.strand_color(s_id)
+    }
+
+    fn get_xover_strain_color(&self, id: u32) -> Option<u32> {
+        if !self.color_xovers_by_strain {
+            return None;
+        }
+        if let ObjectType::Bound(id1, id2) = self.get_object_type(id)? {
+            let n1 = self.design.get_nucl_with_id(id1)?;
+            let n2 = self.design.get_nucl_with_id(id2)?;
+            let xover_id = self.design.get_xover_id(&(n1, n2))?;
+            let strain = self.design.get_xover_strain(xover_id)?;
+            Some(lerp_color(
+                RELAXED_XOVER_COLOR,
+                STRAINED_XOVER_COLOR,
+                (strain / MAX_XOVER_STRAIN_COLOR).min(1.),
+            ))
+        } else {
+            None
+        }
     }
 
     /// Return the middle point of `self` in the world coordinates
@@ -931,6 +1429,10 @@ impl<R: DesignReader> Design3D<R> {
         self.design.get_id_of_strand_containing(element_id)
     }
 
+    pub fn get_strand_note(&self, s_id: usize) -> Option<String> {
+        self.design.get_strand_note(s_id)
+    }
+
     pub fn get_helix(&self, element_id: u32) -> Option<usize> {
         self.design.get_id_of_helix_containing(element_id)
     }
@@ -946,6 +1448,28 @@ impl<R: DesignReader> Design3D<R> {
         self.design.get_object_type(e_id)
     }
 
+    /// Return the elements of the strands that are linked by a cross-over to a nucleotide of
+    /// `elements`.
+    pub fn get_xover_partners(&self, elements: &HashSet<u32>) -> HashSet<u32> {
+        let mut ret = HashSet::new();
+        for nucl in elements.iter().filter_map(|e_id| self.get_nucl(*e_id)) {
+            let xover = self
+                .design
+                .get_id_of_xover_involving_nucl(nucl)
+                .and_then(|xover_id| self.design.get_xover_with_id(xover_id));
+            if let Some((n1, n2)) = xover {
+                let partner = if n1 == nucl { n2 } else { n1 };
+                if let Some(s_id) = self
+                    .get_identifier_nucl(&partner)
+                    .and_then(|id| self.get_strand(id))
+                {
+                    ret.extend(self.get_strand_elements(s_id as u32));
+                }
+            }
+        }
+        ret
+    }
+
     pub fn get_helix_elements(&self, helix_id: u32) -> HashSet<u32> {
         self.design
             .get_ids_of_elements_belonging_to_helix(helix_id as usize)
@@ -957,6 +1481,26 @@ impl<R: DesignReader> Design3D<R> {
         self.design.get_helix_basis(h_id)
     }
 
+    /// Return a point of the axis of helix `h_id` and the direction of this axis, in the world
+    /// referential.
+    pub fn get_helix_axis(&self, h_id: usize) -> Option<(Vec3, Vec3)> {
+        let direction = Vec3::unit_x().rotated_by(self.get_helix_basis(h_id as u32)?);
+        let point = self.design.get_position_of_nucl_on_helix(
+            Nucl {
+                helix: h_id,
+                position: 0,
+                forward: true,
+            },
+            Referential::World,
+            true,
+        )?;
+        Some((point, direction))
+    }
+
+    pub fn get_parameters(&self) -> Parameters {
+        self.design.get_parameters()
+    }
+
     pub fn get_identifier_nucl(&self, nucl: &Nucl) -> Option<u32> {
         self.design.get_identifier_nucl(nucl)
     }
@@ -1094,6 +1638,62 @@ impl<R: DesignReader> Design3D<R> {
         create_dna_bound(pos1, pos2, FREE_XOVER_COLOR, 0, true).to_raw_instance()
     }
 
+    pub fn measure_tube(pos1: Vec3, pos2: Vec3) -> RawDnaInstance {
+        let mut tube = create_dna_bound(pos1, pos2, MEASURE_COLOR, 0, true);
+        tube.radius = MEASURE_TUBE_RADIUS;
+        tube.to_raw_instance()
+    }
+
+    pub fn ruler_tick(pos1: Vec3, pos2: Vec3) -> RawDnaInstance {
+        let mut tube = create_dna_bound(pos1, pos2, RULER_COLOR, 0, true);
+        tube.radius = RULER_TICK_RADIUS;
+        tube.to_raw_instance()
+    }
+
+    /// The graduations of the nucleotide ruler of helix `h_id`. They span the whole curve of
+    /// curved helices, and the nucleotides of the helix otherwise.
+    pub fn get_ruler_graduations(&self, h_id: usize) -> Vec<Graduation> {
+        let range = self.design.get_curve_range(h_id).or_else(|| {
+            let positions: Vec<isize> = self
+                .design
+                .get_ids_of_elements_belonging_to_helix(h_id)
+                .into_iter()
+                .filter_map(|id| self.design.get_nucl_with_id(id))
+                .map(|nucl| nucl.position)
+                .collect();
+            Some(*positions.iter().min()?..=*positions.iter().max()?)
+        });
+        let axis_position = |position| {
+            let nucl = Nucl {
+                helix: h_id,
+                position,
+                forward: true,
+            };
+            self.design
+                .get_position_of_nucl_on_helix(nucl, Referential::World, true)
+        };
+        range
+            .map(|range| ruler::graduations(range, axis_position))
+            .unwrap_or_default()
+    }
+
+    /// A nucleotide that is not part of the edited design, such as a nucleotide of the
+    /// reference design or of a deleted strand.
+    pub fn ghost_sphere(position: Vec3, color: u32) -> RawDnaInstance {
+        SphereInstance {
+            position,
+            id: 0,
+            radius: 1.,
+            color: Instance::color_from_au32(color),
+        }
+        .to_raw_instance()
+    }
+
+    /// A bond between two nucleotides that are not part of the edited design.
+    pub fn ghost_tube(pos1: Vec3, pos2: Vec3, color: u32) -> RawDnaInstance {
+        create_dna_bound(pos1, pos2, color, 0, true).to_raw_instance()
+    }
+
     pub fn has_nucl(&self, nucl: &Nucl) -> bool {
         self.design.get_identifier_nucl(nucl).is_some()
     }
@@ -1110,6 +1710,124 @@ impl<R: DesignReader> Design3D<R> {
         prime5_1.and(prime5_2).is_some()
     }
 
+    /// If `nucl` is the 3' or the 5' end of a strand, return this end. The 3' end is returned for
+    /// strands made of a single nucleotide.
+    pub fn get_strand_end(&self, nucl: Nucl) -> Option<StrandEnd> {
+        if let Some(strand_id) = self.design.prime3_of_which_strand(nucl) {
+            Some(StrandEnd {
+                nucl,
+                strand_id,
+                prime3: true,
+            })
+        } else {
+            self.design
+                .prime5_of_which_strand(nucl)
+                .map(|strand_id| StrandEnd {
+                    nucl,
+                    strand_id,
+                    prime3: false,
+                })
+        }
+    }
+
+    /// Return the end of an other strand that is next to `end` on its helix and that can be
+    /// ligated with it.
+    pub fn get_ligation_partner(&self, end: StrandEnd) -> Option<StrandEnd> {
+        let nucl = end.ligation_neighbour();
+        let strand_id = if end.prime3 {
+            self.design.prime5_of_which_strand(nucl)
+        } else {
+            self.design.prime3_of_which_strand(nucl)
+        }?;
+        (strand_id != end.strand_id).then(|| StrandEnd {
+            nucl,
+            strand_id,
+            prime3: !end.prime3,
+        })
+    }
+
+    /// If `e_id` is the identifier of a nucleotide or of a bond, return the nucleotide after
+    /// which its strand must be nicked, together with the identifier of the strand.
+    ///
+    /// For a bond, this is the 5' nucleotide of the bond. Nothing is returned for the 3' end of a
+    /// strand since there is nothing to cut after it.
+    pub fn get_nick(&self, e_id: u32) -> Option<(Nucl, usize)> {
+        let nucl = self.get_nucl_relax(e_id)?;
+        let strand_id = self.get_strand(e_id)?;
+        if self.design.prime3_of_which_strand(nucl).is_some() {
+            None
+        } else {
+            Some((nucl, strand_id))
+        }
+    }
+
+    /// Return the ends of the strand `s_id`. A cyclic strand has no end.
+    pub fn get_strand_ends(&self, s_id: usize) -> Vec<StrandEnd> {
+        let strand = if let Some(strand) = self.design.get_strand_with_id(s_id) {
+            strand
+        } else {
+            return vec![];
+        };
+        if strand.cyclic {
+            return vec![];
+        }
+        let prime5 = strand.get_5prime().map(|nucl| StrandEnd {
+            nucl,
+            strand_id: s_id,
+            prime3: false,
+        });
+        let prime3 = strand.get_3prime().map(|nucl| StrandEnd {
+            nucl,
+            strand_id: s_id,
+            prime3: true,
+        });
+        prime5.into_iter().chain(prime3).collect()
+    }
+
+    /// Return true if the 3' end of the strand `s_id` is right before its 5' end, in which case
+    /// linking its two ends makes the strand cyclic.
+    pub fn strand_ends_meet(&self, s_id: usize) -> bool {
+        self.design
+            .get_strand_with_id(s_id)
+            .filter(|strand| !strand.cyclic)
+            .and_then(|strand| Some(strand.get_3prime()?.prime3() == strand.get_5prime()?))
+            .unwrap_or(false)
+    }
+
+    /// Return the handle drawn on `end`: a cone pointing away from the strand along the axis of
+    /// the helix.
+    pub fn make_strand_end_handle(&self, end: StrandEnd, color: u32) -> Option<RawDnaInstance> {
+        let drawn = self
+            .design
+            .get_identifier_nucl(&end.nucl)
+            .map(|id| self.is_drawn(id))
+            .unwrap_or(false);
+        if !drawn {
+            return None;
+        }
+        let position = self.get_nucl_position(end.nucl)?;
+        let axis = self
+            .design
+            .get_position_of_nucl_on_helix(end.nucl, Referential::World, true)?;
+        let next_axis = self.design.get_position_of_nucl_on_helix(
+            end.ligation_neighbour(),
+            Referential::World,
+            true,
+        )?;
+        let direction = (next_axis - axis).normalized();
+        Some(
+            ConeInstance {
+                position: position + SPHERE_RADIUS * direction,
+                length: 2. * SPHERE_RADIUS,
+                rotor: Rotor3::from_rotation_between(Vec3::unit_x(), direction),
+                color: Instance::color_from_au32(color),
+                id: crate::element_selector::strand_end_handle_id(end.strand_id, end.prime3),
+                radius: SPHERE_RADIUS,
+            }
+            .to_raw_instance(),
+        )
+    }
+
     #[allow(dead_code)]
     pub fn get_all_prime3_cone(&self) -> Vec<RawDnaInstance> {
         if !self.thick_helices {
@@ -1123,6 +1841,49 @@ impl<R: DesignReader> Design3D<R> {
         ret
     }
 
+    /// Return the markers of the drawn nucleotides that are anchors
+    pub fn get_anchor_cones(&self) -> Vec<RawDnaInstance> {
+        self.design
+            .get_anchors()
+            .into_iter()
+            .filter(|nucl| {
+                self.design
+                    .get_identifier_nucl(nucl)
+                    .map(|id| self.is_drawn(id))
+                    .unwrap_or(false)
+            })
+            .filter_map(|nucl| {
+                let position = self.get_nucl_position(nucl)?;
+                let axis =
+                    self.design
+                        .get_position_of_nucl_on_helix(nucl, Referential::World, true)?;
+                Some(create_anchor_cone(axis, position))
+            })
+            .collect()
+    }
+
+    /// Return the heads and the stems of the markers of the drawn nucleotides that carry a
+    /// chemical modification
+    pub fn get_modification_markers(&self) -> (Vec<RawDnaInstance>, Vec<RawDnaInstance>) {
+        self.design
+            .get_modified_nucls()
+            .into_iter()
+            .filter(|nucl| {
+                self.design
+                    .get_identifier_nucl(nucl)
+                    .map(|id| self.is_drawn(id))
+                    .unwrap_or(false)
+            })
+            .filter_map(|nucl| {
+                let position = self.get_nucl_position(nucl)?;
+                let axis =
+                    self.design
+                        .get_position_of_nucl_on_helix(nucl, Referential::World, true)?;
+                Some(create_modification_marker(axis, position))
+            })
+            .unzip()
+    }
+
     pub fn get_surface_info_nucl(&self, nucl: Nucl) -> Option<SurfaceInfo> {
         self.design.get_surface_info_nucl(nucl)
     }
@@ -1191,8 +1952,51 @@ fn create_prime3_cone(source: Vec3, dest: Vec3, color: u32) -> RawDnaInstance {
     .to_raw_instance()
 }
 
+/// A cone pointing away from the axis of the helix, whose base is on the surface of the
+/// nucleotide.
+fn create_anchor_cone(axis: Vec3, nucl_position: Vec3) -> RawDnaInstance {
+    let direction = (nucl_position - axis).normalized();
+    ConeInstance {
+        position: nucl_position + SPHERE_RADIUS * direction,
+        length: 2. * SPHERE_RADIUS,
+        rotor: Rotor3::from_rotation_between(Vec3::unit_x(), direction),
+        color: Instance::color_from_u32(ANCHOR_COLOR),
+        id: 0,
+        radius: 1.5 * SPHERE_RADIUS,
+    }
+    .to_raw_instance()
+}
+
+/// A small lollipop pointing away from the axis of the helix, whose stem starts on the surface of
+/// the nucleotide. Return the head and the stem of the lollipop.
+fn create_modification_marker(axis: Vec3, nucl_position: Vec3) -> (RawDnaInstance, RawDnaInstance) {
+    let direction = (nucl_position - axis).normalized();
+    let color = Instance::color_from_u32(MODIFICATION_COLOR);
+    let stem_start = nucl_position + SPHERE_RADIUS * direction;
+    let head_position = nucl_position + 3.5 * SPHERE_RADIUS * direction;
+    let head = SphereInstance {
+        position: head_position,
+        radius: 0.7,
+        color,
+        id: 0,
+    }
+    .to_raw_instance();
+    let stem = TubeInstance {
+        position: (stem_start + head_position) / 2.,
+        color,
+        rotor: Rotor3::from_rotation_between(Vec3::unit_x(), direction),
+        id: 0,
+        radius: 0.4,
+        length: (head_position - stem_start).mag(),
+    }
+    .to_raw_instance();
+    (head, stem)
+}
+
 #[derive(Debug, Clone)]
 pub struct HalfHBond {
+    /// The identifier of the nucleotide
+    pub nucl_id: u32,
     pub backbone: Vec3,
     pub center_of_mass: Vec3,
     pub base: Option<char>,
@@ -1205,11 +2009,53 @@ pub struct HBond {
     pub backward: HalfHBond,
 }
 
+/// The end of a strand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrandEnd {
+    pub nucl: Nucl,
+    pub strand_id: usize,
+    /// True if `nucl` is the 3' end of the strand, false if it is its 5' end
+    pub prime3: bool,
+}
+
+impl StrandEnd {
+    /// The nucleotide on which the end of an other strand must be to be ligated with `self`.
+    pub fn ligation_neighbour(&self) -> Nucl {
+        if self.prime3 {
+            self.nucl.prime3()
+        } else {
+            self.nucl.prime5()
+        }
+    }
+
+    /// Return the identifiers `(prime5_id, prime3_id)` of the strands that are merged when
+    /// ligating `self` with `other`: the 3' end of `prime5_id` is joined to the 5' end of
+    /// `prime3_id`.
+    pub fn ligation_ids(&self, other: &StrandEnd) -> (usize, usize) {
+        if self.prime3 {
+            (self.strand_id, other.strand_id)
+        } else {
+            (other.strand_id, self.strand_id)
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
 pub(super) enum ExpandWith {
     Spheres,
     Tubes,
 }
 
+/// The objects whose instances may differ between two versions of a design.
+#[derive(Debug, Clone, Default)]
+pub struct ModifiedObjects {
+    /// The identifiers of the strands that were added, removed or modified
+    pub strands: HashSet<usize>,
+    /// The identifiers of the helices that were added, removed or modified. The instances of all
+    /// the strands that have a nucleotide on these helices must be recomputed.
+    pub helices: HashSet<usize>,
+}
+
 pub trait DesignReader: 'static + ensnano_interactor::DesignReader {
     /// Return the identifier of all the visible nucleotides
     fn get_all_visible_nucl_ids(&self) -> Vec<u32>;
@@ -1242,11 +2088,24 @@ pub trait DesignReader: 'static + ensnano_interactor::DesignReader {
     fn get_element_position(&self, e_id: u32, referential: Referential) -> Option<Vec3>;
     fn get_element_axis_position(&self, id: u32, referential: Referential) -> Option<Vec3>;
     fn get_color(&self, e_id: u32) -> Option<u32>;
+    /// Return the color of the innermost organizer group containing e_id, if this group has a
+    /// color
+    fn get_group_color(&self, e_id: u32) -> Option<u32>;
+    /// If e_id is the identifier of a nucleotide, return the difference, in degrees, between the
+    /// twist from this nucleotide to the next one on its helix and the relaxed twist given by the
+    /// DNA parameters. Positive values mean that the helix is overtwisted.
+    fn get_torsion(&self, e_id: u32) -> Option<f32>;
+    /// If e_id is the identifier of a nucleotide and a simulation computes the strain of the
+    /// nucleotides, return the elastic energy of the most strained bond of this nucleotide
+    fn get_strain(&self, e_id: u32) -> Option<f32>;
     fn get_id_of_strand_containing(&self, e_id: u32) -> Option<usize>;
+    /// Return the note attached to the strand `s_id`, if it has one
+    fn get_strand_note(&self, s_id: usize) -> Option<String>;
     fn get_id_of_helix_containing(&self, e_id: u32) -> Option<usize>;
     fn get_ids_of_elements_belonging_to_strand(&self, s_id: usize) -> Vec<u32>;
     fn get_ids_of_elements_belonging_to_helix(&self, h_id: usize) -> Vec<u32>;
     fn get_helix_basis(&self, h_id: u32) -> Option<Rotor3>;
+    fn get_helix_roll(&self, h_id: usize) -> Option<f32>;
     fn get_basis(&self) -> Rotor3;
     fn get_identifier_nucl(&self, nucl: &Nucl) -> Option<u32>;
     fn get_identifier_bound(&self, n1: Nucl, n2: Nucl) -> Option<u32>;
@@ -1260,14 +2119,26 @@ pub trait DesignReader: 'static + ensnano_interactor::DesignReader {
     fn get_helices_grid_key_coord(&self, g_id: GridId) -> Option<Vec<((isize, isize), usize)>>;
     fn get_helix_id_at_grid_coord(&self, position: GridPosition) -> Option<u32>;
     fn get_persistent_phantom_helices_id(&self) -> HashSet<u32>;
+    /// Return the number of phantom nucleotides drawn on each side of the origin of the helix
+    /// `h_id`, if the grid of the helix does not use the default length
+    fn get_helix_phantom_length(&self, h_id: usize) -> Option<usize>;
     fn get_grid_basis(&self, g_id: GridId) -> Option<Rotor3>;
     fn get_helix_grid_position(&self, h_id: u32) -> Option<HelixGridPosition>;
     fn prime5_of_which_strand(&self, nucl: Nucl) -> Option<usize>;
     fn prime3_of_which_strand(&self, nucl: Nucl) -> Option<usize>;
     fn get_all_prime3_nucl(&self) -> Vec<(Vec3, Vec3, u32)>;
+    /// Return the nucleotides that must not move during physical simulations
+    fn get_anchors(&self) -> Vec<Nucl>;
+    /// Return the nucleotides that carry a chemical modification
+    fn get_modified_nucls(&self) -> Vec<Nucl>;
+    /// Return the value of the property `coloring` for each non-scaffold strand for which it is
+    /// known
+    fn get_strand_property_values(&self, coloring: StrandColoring) -> BTreeMap<usize, f32>;
     fn get_curve_range(&self, h_id: usize) -> Option<std::ops::RangeInclusive<isize>>;
     fn get_checked_xovers_ids(&self, checked: bool) -> Vec<u32>;
     fn get_id_of_xover_involving_nucl(&self, nucl: Nucl) -> Option<usize>;
+    /// Return the strain, in nanometers, of the cross-over `xover_id`
+    fn get_xover_strain(&self, xover_id: usize) -> Option<f32>;
     fn get_grid_object(&self, position: GridPosition) -> Option<GridObject>;
     fn get_position_of_bezier_control(
         &self,
@@ -1290,15 +2161,24 @@ pub trait DesignReader: 'static + ensnano_interactor::DesignReader {
     fn get_bezier_vertex(&self, path_id: BezierPathId, vertex_id: usize) -> Option<BezierVertex>;
     fn get_corners_of_plane(&self, plane_id: BezierPlaneId) -> [Vec2; 4];
     fn get_optimal_xover_arround(&self, source: Nucl, target: Nucl) -> Option<(Nucl, Nucl)>;
+    /// Return the nucleotide that is the closest to `point`, in the model's coordinates, if it
+    /// is at distance at most `max_dist` from it.
+    fn closest_nucl(&self, point: Vec3, max_dist: f32) -> Option<Nucl>;
     fn get_bezier_grid_used_by_helix(&self, h_id: usize) -> Vec<GridId>;
     fn get_external_objects(&self) -> &External3DObjects;
     fn get_surface_info_nucl(&self, nucl: Nucl) -> Option<SurfaceInfo>;
     fn get_surface_info(&self, point: SurfacePoint) -> Option<SurfaceInfo>;
     fn get_additional_structure(&self) -> Option<&dyn AdditionalStructure>;
+    /// The name and position of all the cameras saved in the design
+    fn get_all_cameras(&self) -> Vec<(String, Camera3D)>;
+    /// Return the objects whose instances may differ between `older` and `self`, or `None` if
+    /// the instances of all the objects must be recomputed.
+    fn get_modified_objects(&self, older: &Self) -> Option<ModifiedObjects>;
 }
 
+#[derive(Default)]
 pub(super) struct HBoundsInstances {
-    pub full_h_bonds: Vec<RawDnaInstance>,
-    pub partial_h_bonds: Vec<RawDnaInstance>,
-    pub ellipsoids: Vec<RawDnaInstance>,
+    pub full_h_bonds: Rc<Vec<RawDnaInstance>>,
+    pub partial_h_bonds: Rc<Vec<RawDnaInstance>>,
+    pub ellipsoids: Rc<Vec<RawDnaInstance>>,
 }
@@ -121,6 +121,18 @@ impl<R: DesignReader> Design3D<R> {
         (sheets, spheres)
     }
 
+    /// The name of the plane of each sheet
+    pub fn get_bezier_plane_names<'a>(&self, sheets: &'a [Sheet2D]) -> Vec<(String, &'a Sheet2D)> {
+        let planes = self.design.get_bezier_planes();
+        sheets
+            .iter()
+            .filter_map(|sheet| {
+                let plane = planes.get(&sheet.plane_id)?;
+                Some((plane.name_or_default(sheet.plane_id), sheet))
+            })
+            .collect()
+    }
+
     pub fn get_bezier_vertex_position(
         &self,
         path_id: BezierPathId,
@@ -0,0 +1,273 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Keeps the instances representing the strands of a design, so that only the instances of the
+//! modified strands are recomputed when the design changes.
+//!
+//! The instances are stored strand by strand, in the order of the strands identifiers, and are
+//! followed by a tail of instances that do not belong to a strand and that are given at each
+//! update. When the layout of the instances does not change, the ranges of instances that differ
+//! from the previous update are reported so that the view can write only these ranges to the
+//! GPU.
+
+use crate::view::RawDnaInstance;
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use std::rc::Rc;
+
+/// The bits of the `id` field of an instance that hold the identifier of the element.
+const ELEMENT_ID_MASK: u32 = 0xFF_FF_FF;
+
+#[derive(Default)]
+struct StrandInstances {
+    /// The sorted identifiers of the elements of the strand
+    element_ids: Vec<u32>,
+    /// The instance of each element, if it has one
+    instances: Vec<Option<RawDnaInstance>>,
+}
+
+impl StrandInstances {
+    /// Set the identifiers of the elements of an unmodified strand whose identifiers were
+    /// shifted by the modification of other strands.
+    fn shift_ids(&mut self, element_ids: Vec<u32>) {
+        for (instance, id) in self.instances.iter_mut().zip(element_ids.iter()) {
+            if let Some(instance) = instance {
+                instance.id = (instance.id & !ELEMENT_ID_MASK) | id;
+            }
+        }
+        self.element_ids = element_ids;
+    }
+}
+
+/// The result of an update of an [InstanceCache].
+pub struct CachedInstances {
+    pub instances: Rc<Vec<RawDnaInstance>>,
+    /// The ranges of `instances` that differ from the previous update, or `None` if the layout of
+    /// the instances changed and they must all be written.
+    pub modified_ranges: Option<Vec<Range<usize>>>,
+    /// The number of instances that were recomputed for the strands
+    pub nb_regenerated: usize,
+}
+
+#[derive(Default)]
+pub struct InstanceCache {
+    strands: BTreeMap<usize, StrandInstances>,
+    /// The range of the instances of each strand at the previous update
+    layout: Vec<(usize, Range<usize>)>,
+    /// The number of instances at the previous update
+    nb_instances: usize,
+}
+
+impl InstanceCache {
+    /// Update the instances.
+    ///
+    /// `elements` maps the identifier of each strand to the identifiers of its elements that
+    /// must be drawn. The instances of the strands in `modified`, or of all the strands if
+    /// `modified` is `None`, are computed with `make_instance`. The instances of the other
+    /// strands are reused if they have the same number of elements. The instances of `tail` are
+    /// placed after the instances of the strands.
+    pub fn update<F>(
+        &mut self,
+        elements: BTreeMap<usize, Vec<u32>>,
+        modified: Option<&HashSet<usize>>,
+        tail: Vec<RawDnaInstance>,
+        mut make_instance: F,
+    ) -> CachedInstances
+    where
+        F: FnMut(u32) -> Option<RawDnaInstance>,
+    {
+        let mut old_strands = std::mem::take(&mut self.strands);
+        let mut nb_regenerated = 0;
+        // The strands whose instances may differ from the previous update
+        let mut rewritten = HashSet::new();
+        for (s_id, mut element_ids) in elements.into_iter() {
+            element_ids.sort_unstable();
+            let reusable = old_strands
+                .remove(&s_id)
+                .filter(|old| old.element_ids.len() == element_ids.len())
+                .filter(|_| modified.map(|m| !m.contains(&s_id)).unwrap_or(false));
+            let strand = if let Some(mut strand) = reusable {
+                if strand.element_ids != element_ids {
+                    strand.shift_ids(element_ids);
+                    rewritten.insert(s_id);
+                }
+                strand
+            } else {
+                let instances: Vec<_> = element_ids.iter().map(|id| make_instance(*id)).collect();
+                nb_regenerated += instances.iter().filter(|i| i.is_some()).count();
+                rewritten.insert(s_id);
+                StrandInstances {
+                    element_ids,
+                    instances,
+                }
+            };
+            self.strands.insert(s_id, strand);
+        }
+
+        let mut instances = Vec::with_capacity(self.nb_instances);
+        let mut layout = Vec::with_capacity(self.strands.len());
+        for (s_id, strand) in self.strands.iter() {
+            let start = instances.len();
+            instances.extend(strand.instances.iter().flatten());
+            layout.push((*s_id, start..instances.len()));
+        }
+        let tail_start = instances.len();
+        instances.extend(tail);
+
+        let modified_ranges = if layout == self.layout && instances.len() == self.nb_instances {
+            let mut ranges: Vec<Range<usize>> = Vec::new();
+            let strand_ranges = layout
+                .iter()
+                .filter(|(s_id, _)| rewritten.contains(s_id))
+                .map(|(_, range)| range.clone());
+            for range in strand_ranges.chain(std::iter::once(tail_start..instances.len())) {
+                match ranges.last_mut() {
+                    Some(last) if last.end == range.start => last.end = range.end,
+                    _ => ranges.push(range),
+                }
+            }
+            Some(ranges)
+        } else {
+            None
+        };
+        self.layout = layout;
+        self.nb_instances = instances.len();
+        CachedInstances {
+            instances: Rc::new(instances),
+            modified_ranges,
+            nb_regenerated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::{Instanciable, SphereInstance};
+    use ensnano_design::ultraviolet::Vec3;
+    use ensnano_utils::instance::Instance;
+
+    const NB_STRANDS: usize = 200;
+    const STRAND_LENGTH: u32 = 32;
+
+    /// The identifiers of the nucleotides of each strand, the strand `s_id` having `extra[s_id]`
+    /// more nucleotides than the others.
+    fn elements(extra: &BTreeMap<usize, u32>) -> BTreeMap<usize, Vec<u32>> {
+        let mut next_id = 0;
+        let mut ret = BTreeMap::new();
+        for s_id in 0..NB_STRANDS {
+            let length = STRAND_LENGTH + extra.get(&s_id).cloned().unwrap_or(0);
+            ret.insert(s_id, (next_id..next_id + length).collect());
+            next_id += length;
+        }
+        ret
+    }
+
+    fn sphere(id: u32, color: u32) -> RawDnaInstance {
+        SphereInstance {
+            position: Vec3::new(id as f32, 0., 0.),
+            color: Instance::color_from_u32(color),
+            id,
+            radius: 1.,
+        }
+        .to_raw_instance()
+    }
+
+    fn strand_of(id: u32, extra: &BTreeMap<usize, u32>) -> usize {
+        elements(extra)
+            .into_iter()
+            .find(|(_, ids)| ids.contains(&id))
+            .map(|(s_id, _)| s_id)
+            .unwrap()
+    }
+
+    #[test]
+    fn single_color_change_only_regenerates_one_strand() {
+        let mut cache = InstanceCache::default();
+        let no_extra = BTreeMap::new();
+        let first = cache.update(elements(&no_extra), None, vec![], |id| Some(sphere(id, 0)));
+        let nb_instances = NB_STRANDS * STRAND_LENGTH as usize;
+        assert_eq!(first.nb_regenerated, nb_instances);
+        assert_eq!(first.instances.len(), nb_instances);
+        assert!(first.modified_ranges.is_none());
+
+        // Change the color of the strand 42
+        let modified: HashSet<usize> = [42].into_iter().collect();
+        let second = cache.update(elements(&no_extra), Some(&modified), vec![], |id| {
+            let color = if strand_of(id, &no_extra) == 42 {
+                0xFF_00_00
+            } else {
+                0
+            };
+            Some(sphere(id, color))
+        });
+        assert_eq!(second.nb_regenerated, STRAND_LENGTH as usize);
+        let start = 42 * STRAND_LENGTH as usize;
+        assert_eq!(
+            second.modified_ranges,
+            Some(vec![start..start + STRAND_LENGTH as usize])
+        );
+        let red = Instance::color_from_u32(0xFF_00_00);
+        for (i, instance) in second.instances.iter().enumerate() {
+            let expected = if (start..start + STRAND_LENGTH as usize).contains(&i) {
+                red
+            } else {
+                Instance::color_from_u32(0)
+            };
+            assert_eq!(instance.color, expected);
+        }
+    }
+
+    #[test]
+    fn shifted_identifiers_are_patched_without_regeneration() {
+        let mut cache = InstanceCache::default();
+        let no_extra = BTreeMap::new();
+        cache.update(elements(&no_extra), None, vec![], |id| Some(sphere(id, 0)));
+
+        // A nucleotide is added to the strand 0, which shifts the identifiers of all the other
+        // strands
+        let extra: BTreeMap<usize, u32> = [(0, 1)].into_iter().collect();
+        let modified: HashSet<usize> = [0].into_iter().collect();
+        let update = cache.update(elements(&extra), Some(&modified), vec![], |id| {
+            Some(sphere(id, 0))
+        });
+        assert_eq!(update.nb_regenerated, STRAND_LENGTH as usize + 1);
+        assert!(update.modified_ranges.is_none());
+        let ids: Vec<u32> = update.instances.iter().map(|i| i.id).collect();
+        let expected: Vec<u32> = elements(&extra).into_values().flatten().collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn tail_is_always_rewritten() {
+        let mut cache = InstanceCache::default();
+        let no_extra = BTreeMap::new();
+        let tail = vec![sphere(u32::MAX, 0)];
+        cache.update(elements(&no_extra), None, tail.clone(), |id| {
+            Some(sphere(id, 0))
+        });
+        let update = cache.update(elements(&no_extra), Some(&HashSet::new()), tail, |id| {
+            Some(sphere(id, 0))
+        });
+        let nb_instances = NB_STRANDS * STRAND_LENGTH as usize;
+        assert_eq!(update.nb_regenerated, 0);
+        assert_eq!(
+            update.modified_ranges,
+            Some(vec![nb_instances..nb_instances + 1])
+        );
+    }
+}
@@ -0,0 +1,208 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Level of detail of the helices of large designs.
+//!
+//! When a design has more than [`LOD_NUCL_BUDGET`] nucleotides, the helices far from the camera
+//! are drawn as a few cylinders instead of one sphere per nucleotide and one tube per bond. The
+//! threshold distance is [`LOD_DISTANCE`], or less if the helices closer than that distance
+//! contain more nucleotides than the budget.
+//!
+//! Selected and candidate helices are always drawn with full detail. To avoid helices switching
+//! back and forth between the two representations when the camera moves near the threshold
+//! distance, a reduced helix is drawn with full detail again only when it becomes closer than the
+//! threshold by a margin of [`LOD_HYSTERESIS`], and conversely.
+
+use super::ultraviolet::{Rotor3, Vec3};
+use crate::view::{Instanciable, RawDnaInstance, TubeInstance};
+use ensnano_interactor::consts::{
+    BOUND_RADIUS, LOD_CAMERA_EPSILON, LOD_DISTANCE, LOD_HELIX_COLOR, LOD_HELIX_RADIUS,
+    LOD_HYSTERESIS, LOD_NUCL_BUDGET,
+};
+use ensnano_utils::instance::Instance;
+use std::collections::{BTreeMap, HashSet};
+use std::rc::Rc;
+
+/// A portion of helix that is drawn as a single cylinder when the helix is reduced.
+#[derive(Debug, Clone)]
+pub struct HelixSegment {
+    pub helix: usize,
+    /// The positions of the extremities of the segment on the axis of the helix
+    pub start: Vec3,
+    pub end: Vec3,
+    /// The identifier of a nucleotide of the segment. Picking the cylinder picks this nucleotide.
+    pub nucl_id: u32,
+    /// The number of nucleotides of the segment, on both strands
+    pub nb_nucl: usize,
+}
+
+/// A sphere containing all the segments of a helix.
+#[derive(Debug, Clone)]
+struct HelixBounds {
+    center: Vec3,
+    radius: f32,
+    nb_nucl: usize,
+}
+
+impl HelixBounds {
+    fn distance(&self, point: Vec3) -> f32 {
+        ((self.center - point).mag() - self.radius).max(0.)
+    }
+}
+
+#[derive(Default)]
+pub struct LevelOfDetail {
+    segments: Vec<HelixSegment>,
+    bounds: BTreeMap<usize, HelixBounds>,
+    nb_nucl: usize,
+    reduced_helices: Rc<HashSet<usize>>,
+    /// The helices that are always drawn with full detail
+    protected_helices: HashSet<usize>,
+    /// If true, no helix is reduced
+    full_detail: bool,
+    /// The position of the camera the last time the reduced helices were computed. If `None`,
+    /// they must be computed again.
+    camera_position: Option<Vec3>,
+}
+
+impl LevelOfDetail {
+    /// Replace the segments of the helices. This must be called when the design is modified.
+    pub fn set_segments(&mut self, segments: Vec<HelixSegment>) {
+        let mut extremities: BTreeMap<usize, (Vec<Vec3>, usize)> = BTreeMap::new();
+        for s in segments.iter() {
+            let entry = extremities.entry(s.helix).or_default();
+            entry.0.push(s.start);
+            entry.0.push(s.end);
+            entry.1 += s.nb_nucl;
+        }
+        self.bounds = extremities
+            .into_iter()
+            .map(|(h_id, (points, nb_nucl))| {
+                let center =
+                    points.iter().fold(Vec3::zero(), |acc, p| acc + *p) / points.len() as f32;
+                let radius = points
+                    .iter()
+                    .map(|p| (*p - center).mag())
+                    .fold(0., f32::max);
+                (
+                    h_id,
+                    HelixBounds {
+                        center,
+                        radius,
+                        nb_nucl,
+                    },
+                )
+            })
+            .collect();
+        self.nb_nucl = segments.iter().map(|s| s.nb_nucl).sum();
+        self.segments = segments;
+        self.camera_position = None;
+    }
+
+    pub fn set_protected_helices(&mut self, helices: HashSet<usize>) {
+        if self.protected_helices != helices {
+            self.protected_helices = helices;
+            self.camera_position = None;
+        }
+    }
+
+    pub fn set_full_detail(&mut self, full_detail: bool) {
+        if self.full_detail != full_detail {
+            self.full_detail = full_detail;
+            self.camera_position = None;
+        }
+    }
+
+    /// Compute the helices that must be reduced when the camera is at `camera_position`. Return
+    /// true if they changed.
+    pub fn update(&mut self, camera_position: Vec3) -> bool {
+        if let Some(previous) = self.camera_position {
+            if (previous - camera_position).mag() < LOD_CAMERA_EPSILON {
+                return false;
+            }
+        }
+        self.camera_position = Some(camera_position);
+        let reduced: HashSet<usize> = if self.full_detail || self.nb_nucl <= LOD_NUCL_BUDGET {
+            HashSet::new()
+        } else {
+            let mut distances: Vec<(usize, f32, usize)> = self
+                .bounds
+                .iter()
+                .map(|(h_id, b)| (*h_id, b.distance(camera_position), b.nb_nucl))
+                .collect();
+            distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+            let mut nb_nucl = 0;
+            let budget_distance = distances
+                .iter()
+                .find(|(_, _, n)| {
+                    nb_nucl += n;
+                    nb_nucl > LOD_NUCL_BUDGET
+                })
+                .map(|(_, d, _)| *d)
+                .unwrap_or(f32::INFINITY);
+            let threshold = LOD_DISTANCE.min(budget_distance);
+            distances
+                .into_iter()
+                .filter(|(h_id, d, _)| {
+                    if self.protected_helices.contains(h_id) {
+                        false
+                    } else if self.reduced_helices.contains(h_id) {
+                        *d > threshold * (1. - LOD_HYSTERESIS)
+                    } else {
+                        *d > threshold * (1. + LOD_HYSTERESIS)
+                    }
+                })
+                .map(|(h_id, _, _)| h_id)
+                .collect()
+        };
+        if reduced != *self.reduced_helices {
+            self.reduced_helices = Rc::new(reduced);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn reduced_helices(&self) -> Rc<HashSet<usize>> {
+        self.reduced_helices.clone()
+    }
+
+    pub fn is_reduced(&self, h_id: usize) -> bool {
+        self.reduced_helices.contains(&h_id)
+    }
+
+    /// The cylinders representing the reduced helices
+    pub fn cylinders(&self) -> Vec<RawDnaInstance> {
+        let color = Instance::color_from_u32(LOD_HELIX_COLOR);
+        self.segments
+            .iter()
+            .filter(|s| self.is_reduced(s.helix))
+            .map(|s| {
+                let axis = s.end - s.start;
+                TubeInstance {
+                    position: (s.start + s.end) / 2.,
+                    rotor: Rotor3::from_rotation_between(Vec3::unit_x(), axis.normalized()),
+                    color,
+                    id: s.nucl_id,
+                    radius: LOD_HELIX_RADIUS / BOUND_RADIUS,
+                    length: axis.mag(),
+                }
+                .to_raw_instance()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,270 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Measurement of distances and angles between selected nucleotides.
+//!
+//! This module also provides the label displaying the length of a domain being built, the label
+//! displaying the angle of the rotation made with the rotation widget, the labels displaying the
+//! coordinates of the grid cells near the cursor, and the labels displaying the names of the
+//! bezier planes.
+
+use super::{ultraviolet, LetterInstance};
+use crate::view::{GridInstance, Sheet2D};
+use ensnano_design::grid::{GridPosition, GridType};
+use ensnano_interactor::consts::{
+    BEZIER_PLANE_NAME_CHARS, BUILDING_LABEL_CHARS, GRID_COORDINATE_CHARS,
+    GRID_COORDINATE_LABEL_RADIUS, MEASURE_CHARS, NB_BEZIER_PLANE_NAME_CHARS,
+    NB_BUILDING_LABEL_CHARS, NB_GRID_COORDINATE_CHARS, NB_MEASURE_CHARS, NB_ROTATION_ANGLE_CHARS,
+    ROTATION_ANGLE_CHARS,
+};
+use ensnano_interactor::StrandBuildingStatus;
+use ultraviolet::{Vec3, Vec4};
+
+/// The scale of the letters of the measurement labels
+const LABEL_SCALE: f32 = 3.;
+/// The space between the start of two consecutive letters of the measurement labels
+const LABEL_ADVANCE: f32 = 0.7;
+/// The scale of the letters of the grid coordinates labels. They are smaller than the other
+/// labels so that the labels of neighbouring cells do not overlap.
+const GRID_COORDINATE_LABEL_SCALE: f32 = 1.5;
+
+/// The distances and angle between two or three nucleotides.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    positions: Vec<Vec3>,
+}
+
+impl Measurement {
+    /// Return a measurement between the given positions if there are exactly two or three of
+    /// them.
+    pub fn new(positions: Vec<Vec3>) -> Option<Self> {
+        if positions.len() == 2 || positions.len() == 3 {
+            Some(Self { positions })
+        } else {
+            None
+        }
+    }
+
+    /// The segments joining the measured positions
+    pub fn segments(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        self.positions.windows(2).map(|w| (w[0], w[1]))
+    }
+
+    fn distances(&self) -> Vec<f32> {
+        self.segments().map(|(a, b)| (b - a).mag()).collect()
+    }
+
+    /// The angle, in degrees, at the middle position if there are three of them.
+    fn angle(&self) -> Option<f32> {
+        if let [a, b, c] = self.positions[..] {
+            let u = (a - b).normalized();
+            let v = (c - b).normalized();
+            Some(u.dot(v).clamp(-1., 1.).acos().to_degrees())
+        } else {
+            None
+        }
+    }
+
+    /// A description of the measurement, to be displayed in the status bar.
+    pub fn info(&self) -> String {
+        let distances: Vec<String> = self
+            .distances()
+            .iter()
+            .map(|d| format!("{d:.2} nm"))
+            .collect();
+        if let Some(angle) = self.angle() {
+            format!("Distances: {}; Angle: {angle:.1}°", distances.join(", "))
+        } else {
+            format!("Distance: {}", distances.join(", "))
+        }
+    }
+
+    /// The letters of the labels displaying the distances in the middle of the segments, and the
+    /// angle next to the middle position.
+    pub fn letter_instances(&self, right: Vec3, up: Vec3) -> Vec<Vec<LetterInstance>> {
+        let mut instances = vec![Vec::new(); NB_MEASURE_CHARS];
+        for ((a, b), d) in self.segments().zip(self.distances()) {
+            let text = format!("{d:.2} nm");
+            push_label(
+                &text,
+                (a + b) / 2.,
+                right,
+                up,
+                MEASURE_CHARS,
+                &mut instances,
+            );
+        }
+        if let Some(angle) = self.angle() {
+            let text = format!("{angle:.1}°");
+            push_label(
+                &text,
+                self.positions[1],
+                right,
+                up,
+                MEASURE_CHARS,
+                &mut instances,
+            );
+        }
+        instances
+    }
+}
+
+/// The letters of the label displaying the length of the domain being built, next to the
+/// position of the dragged nucleotide.
+pub fn building_label_instances(
+    status: &StrandBuildingStatus,
+    position: Vec3,
+    right: Vec3,
+    up: Vec3,
+) -> Vec<Vec<LetterInstance>> {
+    let mut instances = vec![Vec::new(); NB_BUILDING_LABEL_CHARS];
+    let text = format!("{}nt/{:.1}nm", status.nt_length, status.nm_length);
+    push_label(
+        &text,
+        position,
+        right,
+        up,
+        BUILDING_LABEL_CHARS,
+        &mut instances,
+    );
+    instances
+}
+
+/// The letters of the label displaying the angle of the rotation made with the rotation widget,
+/// or the angle being typed.
+pub fn rotation_angle_label_instances(
+    text: &str,
+    position: Vec3,
+    right: Vec3,
+    up: Vec3,
+) -> Vec<Vec<LetterInstance>> {
+    let mut instances = vec![Vec::new(); NB_ROTATION_ANGLE_CHARS];
+    push_label(
+        text,
+        position,
+        right,
+        up,
+        ROTATION_ANGLE_CHARS,
+        &mut instances,
+    );
+    instances
+}
+
+/// The letters of the labels displaying the names of the bezier planes. Each name is written in
+/// the plane of its sheet, starting from the top left corner of the sheet.
+pub fn bezier_plane_name_label_instances<'a>(
+    names: impl IntoIterator<Item = (String, &'a Sheet2D)>,
+) -> Vec<Vec<LetterInstance>> {
+    let mut instances = vec![Vec::new(); NB_BEZIER_PLANE_NAME_CHARS];
+    for (name, sheet) in names {
+        let right = Vec3::unit_z().rotated_by(sheet.orientation);
+        let up = Vec3::unit_y().rotated_by(sheet.orientation);
+        let width = LABEL_ADVANCE * name.chars().count() as f32;
+        let corner = sheet.space_position_of_point2d(sheet.corners()[2]);
+        push_label(
+            &name,
+            corner + width / 2. * right,
+            right,
+            up,
+            BEZIER_PLANE_NAME_CHARS,
+            &mut instances,
+        );
+    }
+    instances
+}
+
+/// The letters of the labels displaying the coordinates of the cells of `grid` that are near
+/// `pointed`, the cell under the cursor.
+///
+/// The coordinates are written `(x,y)` with the same convention as the one used by
+/// `HelixGridPosition`. The labels are written below the center of the cells so that they do not
+/// hide the identifier of the helices.
+pub fn grid_coordinate_label_instances(
+    grid: &GridInstance,
+    pointed: GridPosition,
+    right: Vec3,
+    up: Vec3,
+) -> Vec<Vec<LetterInstance>> {
+    let mut instances = vec![Vec::new(); NB_GRID_COORDINATE_CHARS];
+    let cells: Vec<(isize, isize)> = if let GridType::Hyperboloid(_) = grid.grid.grid_type {
+        // The cells of an hyperboloid grid are not arranged on a lattice
+        vec![(pointed.x, pointed.y)]
+    } else {
+        let (min_x, max_x) = (grid.min_x as isize, grid.max_x as isize);
+        let (min_y, max_y) = (grid.min_y as isize, grid.max_y as isize);
+        let xs = (pointed.x - GRID_COORDINATE_LABEL_RADIUS).max(min_x)
+            ..=(pointed.x + GRID_COORDINATE_LABEL_RADIUS).min(max_x);
+        xs.flat_map(|x| {
+            let ys = (pointed.y - GRID_COORDINATE_LABEL_RADIUS).max(min_y)
+                ..=(pointed.y + GRID_COORDINATE_LABEL_RADIUS).min(max_y);
+            ys.map(move |y| (x, y))
+        })
+        .collect()
+    };
+    for (x, y) in cells {
+        let text = format!("({x},{y})");
+        let position = grid.grid.position_helix(x, y) - 1.2 * up;
+        push_scaled_label(
+            &text,
+            position,
+            right,
+            up,
+            GRID_COORDINATE_LABEL_SCALE,
+            GRID_COORDINATE_CHARS,
+            &mut instances,
+        );
+    }
+    instances
+}
+
+/// Add the letters of `text` to `instances`, where `instances[i]` holds the letters `chars[i]`.
+fn push_label(
+    text: &str,
+    position: Vec3,
+    right: Vec3,
+    up: Vec3,
+    chars: &[char],
+    instances: &mut [Vec<LetterInstance>],
+) {
+    push_scaled_label(text, position, right, up, LABEL_SCALE, chars, instances)
+}
+
+/// Same as [push_label], with letters of size `scale`.
+pub(super) fn push_scaled_label(
+    text: &str,
+    position: Vec3,
+    right: Vec3,
+    up: Vec3,
+    scale: f32,
+    chars: &[char],
+    instances: &mut [Vec<LetterInstance>],
+) {
+    let advance = LABEL_ADVANCE * scale / LABEL_SCALE;
+    let nb_chars = text.chars().count();
+    let shift = 0.5 * up - advance / 2. * nb_chars as f32 * right;
+    for (c_idx, c) in text.chars().enumerate() {
+        if let Some(idx) = chars.iter().position(|x| *x == c) {
+            instances[idx].push(LetterInstance {
+                position: position + advance * c_idx as f32 * right + shift,
+                color: Vec4::new(0., 0., 0., 1.),
+                design_id: 0,
+                scale,
+                shift: Vec3::zero(),
+            })
+        }
+    }
+}
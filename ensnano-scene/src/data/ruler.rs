@@ -0,0 +1,191 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A ruler showing the indices of the nucleotides along helices.
+//!
+//! A tick is drawn every [`RULER_TICK_PERIOD`] nucleotides and the index of the nucleotide is
+//! written every [`RULER_LABEL_PERIOD`] nucleotides. The ticks are placed at the positions of the
+//! nucleotides on the axis of the helix, so that they follow curved helices. The labels that are
+//! far from the camera are written less often so that they do not overlap.
+
+use super::measurement::push_scaled_label;
+use super::{ultraviolet, LetterInstance};
+use ensnano_interactor::consts::{
+    RULER_CHARS, RULER_LABEL_MIN_SPACING, RULER_LABEL_PERIOD, RULER_TICK_HALF_LENGTH,
+    RULER_TICK_PERIOD,
+};
+use std::ops::RangeInclusive;
+use ultraviolet::Vec3;
+
+/// The scale of the letters of the labels
+const LABEL_SCALE: f32 = 2.;
+/// The maximal number of nucleotides between two consecutive labels, whatever the distance to the
+/// camera
+const MAX_LABEL_PERIOD: isize = 64 * RULER_LABEL_PERIOD;
+
+/// A tick of the ruler
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Graduation {
+    /// The index of the nucleotide
+    pub index: isize,
+    /// The position of the nucleotide on the axis of the helix
+    pub position: Vec3,
+    /// The direction of the axis of the helix at `position`
+    pub tangent: Vec3,
+}
+
+impl Graduation {
+    fn is_labelled(&self) -> bool {
+        self.index.rem_euclid(RULER_LABEL_PERIOD) == 0
+    }
+
+    /// The direction in which the tick is drawn. It is orthogonal to the axis of the helix and to
+    /// the direction in which the tick is seen from the camera, and points upward on the screen.
+    fn tick_direction(&self, camera_position: Vec3, up: Vec3) -> Vec3 {
+        let side = self.tangent.cross(camera_position - self.position);
+        let side = if side.mag() > 1e-3 {
+            side.normalized()
+        } else {
+            // The axis of the helix points toward the camera
+            up
+        };
+        if side.dot(up) < 0. {
+            -side
+        } else {
+            side
+        }
+    }
+}
+
+/// Return the graduations of the ruler of a helix for the nucleotide indices in `range`.
+///
+/// `axis_position(n)` is the position of the nucleotide `n` on the axis of the helix. The
+/// direction of the axis at a graduation is given by the positions of the neighbouring
+/// nucleotides, so that it follows the discretized curve of curved helices.
+pub fn graduations<F>(range: RangeInclusive<isize>, axis_position: F) -> Vec<Graduation>
+where
+    F: Fn(isize) -> Option<Vec3>,
+{
+    let first = range.start() + (-range.start()).rem_euclid(RULER_TICK_PERIOD);
+    (first..=*range.end())
+        .step_by(RULER_TICK_PERIOD as usize)
+        .filter_map(|index| {
+            let position = axis_position(index)?;
+            let before = axis_position(index - 1).unwrap_or(position);
+            let after = axis_position(index + 1).unwrap_or(position);
+            let tangent = after - before;
+            (tangent.mag() > 0.).then(|| Graduation {
+                index,
+                position,
+                tangent: tangent.normalized(),
+            })
+        })
+        .collect()
+}
+
+/// The number of nucleotides between two consecutive labels at distance `distance` of the
+/// camera, when consecutive nucleotides are separated by `z_step` nanometers.
+///
+/// The period is a power of two multiple of [`RULER_LABEL_PERIOD`] chosen so that two
+/// consecutive labels are separated by at least `RULER_LABEL_MIN_SPACING * distance`.
+pub fn label_period(distance: f32, z_step: f32) -> isize {
+    let mut period = RULER_LABEL_PERIOD;
+    while period < MAX_LABEL_PERIOD && (period as f32) * z_step < RULER_LABEL_MIN_SPACING * distance
+    {
+        period *= 2;
+    }
+    period
+}
+
+/// The tubes and the letters of the ruler seen from a camera at `camera_position`.
+pub struct RulerInstances {
+    /// The extremities of the ticks
+    pub ticks: Vec<(Vec3, Vec3)>,
+    /// The letters of the labels, where `letters[i]` holds the letters `RULER_CHARS[i]`
+    pub letters: Vec<Vec<LetterInstance>>,
+}
+
+impl RulerInstances {
+    pub fn new(
+        graduations: &[Graduation],
+        camera_position: Vec3,
+        right: Vec3,
+        up: Vec3,
+        z_step: f32,
+    ) -> Self {
+        let mut ticks = Vec::with_capacity(graduations.len());
+        let mut letters = vec![Vec::new(); RULER_CHARS.len()];
+        for graduation in graduations.iter() {
+            let direction = graduation.tick_direction(camera_position, up);
+            let half_length = if graduation.is_labelled() {
+                2. * RULER_TICK_HALF_LENGTH
+            } else {
+                RULER_TICK_HALF_LENGTH
+            };
+            let end = graduation.position + half_length * direction;
+            ticks.push((graduation.position - half_length * direction, end));
+
+            let distance = (graduation.position - camera_position).mag();
+            if graduation.index.rem_euclid(label_period(distance, z_step)) == 0 {
+                push_scaled_label(
+                    &graduation.index.to_string(),
+                    end,
+                    right,
+                    up,
+                    LABEL_SCALE,
+                    RULER_CHARS,
+                    &mut letters,
+                );
+            }
+        }
+        Self { ticks, letters }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graduations_follow_the_curve() {
+        // A quarter of circle of radius 10 discretized in 64 nucleotides
+        let axis_position = |n: isize| {
+            (0..=64).contains(&n).then(|| {
+                let theta = n as f32 / 64. * std::f32::consts::FRAC_PI_2;
+                Vec3::new(10. * theta.cos(), 10. * theta.sin(), 0.)
+            })
+        };
+        let graduations = graduations(-3..=64, axis_position);
+        let indices: Vec<isize> = graduations.iter().map(|g| g.index).collect();
+        assert_eq!(indices, vec![0, 8, 16, 24, 32, 40, 48, 56, 64]);
+        for g in graduations.iter() {
+            assert!((g.position.mag() - 10.).abs() < 1e-4);
+            // The tangent of a circle is orthogonal to its radius
+            assert!(g.tangent.dot(g.position).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn labels_are_sparser_far_from_the_camera() {
+        let z_step = 0.332;
+        assert_eq!(label_period(10., z_step), RULER_LABEL_PERIOD);
+        let far = label_period(1000., z_step);
+        assert!(far > RULER_LABEL_PERIOD);
+        assert_eq!(far % RULER_LABEL_PERIOD, 0);
+        assert!(label_period(f32::INFINITY, z_step) <= MAX_LABEL_PERIOD);
+    }
+}
@@ -19,7 +19,7 @@ use std::rc::Rc;
 
 use super::{Device, DrawArea, DrawType, Queue, ViewPtr};
 use ensnano_design::grid::{GridId, GridPosition};
-use ensnano_design::{BezierPathId, BezierPlaneId, BezierVertexId};
+use ensnano_design::{BezierPathId, BezierPlaneId, BezierVertexId, External3DObjectId};
 use ensnano_interactor::{phantom_helix_decoder, BezierControlPoint, PhantomElement};
 use ensnano_utils as utils;
 use futures::executor;
@@ -80,42 +80,73 @@ impl ElementSelector {
         &mut self,
         clicked_pixel: PhysicalPosition<f64>,
     ) -> Option<SceneElement> {
+        self.update_pixels_if_needed();
+        self.get_highest_priority_element(clicked_pixel)
+    }
+
+    /// Return all the distinct elements drawn in a neighbourhood of `clicked_pixel`.
+    ///
+    /// The first element of the returned vector is the one that `set_selected_id` would return,
+    /// the other ones are sorted by increasing distance to `clicked_pixel`.
+    pub fn get_candidates(&mut self, clicked_pixel: PhysicalPosition<f64>) -> Vec<SceneElement> {
+        self.update_pixels_if_needed();
+        let mut ret = Vec::new();
+        for byte0 in self.neighbourhood(clicked_pixel) {
+            for reader in self.readers.iter() {
+                if let Some(element) = reader.read_pixel(byte0) {
+                    if !ret.contains(&element) {
+                        ret.push(element);
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    fn update_pixels_if_needed(&mut self) {
         if self.readers[0].pixels.is_none() || self.view.borrow().need_redraw_fake() {
             for i in 0..self.readers.len() {
                 let pixels = self.update_fake_pixels(self.readers[i].draw_type, self.stereographic);
                 self.readers[i].pixels = Some(pixels)
             }
         }
-
-        self.get_highest_priority_element(clicked_pixel)
     }
 
     fn get_highest_priority_element(
         &self,
         clicked_pixel: PhysicalPosition<f64>,
     ) -> Option<SceneElement> {
+        for byte0 in self.neighbourhood(clicked_pixel) {
+            for reader in self.readers.iter() {
+                if let Some(element) = reader.read_pixel(byte0) {
+                    return Some(element);
+                }
+            }
+        }
+        None
+    }
+
+    /// Iterate over the position in the pixel buffers of the pixels in squares of increasing size
+    /// centered on `clicked_pixel`.
+    fn neighbourhood(
+        &self,
+        clicked_pixel: PhysicalPosition<f64>,
+    ) -> impl Iterator<Item = usize> + '_ {
         let pixel = (
             clicked_pixel.cast::<u32>().x.min(self.area.size.width - 1) + self.area.position.x,
             clicked_pixel.cast::<u32>().y.min(self.area.size.height - 1) + self.area.position.y,
         );
-        for max_delta in 0..=5 {
+        let width = self.window_size.width;
+        let height = self.window_size.height;
+        (0..=5).flat_map(move |max_delta| {
             let min_x = pixel.0.max(max_delta) - max_delta;
-            let max_x = (pixel.0 + max_delta).min(self.window_size.width - 1);
+            let max_x = (pixel.0 + max_delta).min(width - 1);
             let min_y = pixel.1.max(max_delta) - max_delta;
-            let max_y = (pixel.1 + max_delta).min(self.window_size.height - 1);
-            for x in min_x..=max_x {
-                for y in min_y..=max_y {
-                    let byte0 =
-                        (y * self.window_size.width + x) as usize * std::mem::size_of::<u32>();
-                    for reader in self.readers.iter() {
-                        if let Some(element) = reader.read_pixel(byte0) {
-                            return Some(element);
-                        }
-                    }
-                }
-            }
-        }
-        None
+            let max_y = (pixel.1 + max_delta).min(height - 1);
+            (min_x..=max_x).flat_map(move |x| {
+                (min_y..=max_y).map(move |y| (y * width + x) as usize * std::mem::size_of::<u32>())
+            })
+        })
     }
 
     fn update_fake_pixels(&self, draw_type: DrawType, stereographic: bool) -> Vec<u8> {
@@ -258,6 +289,12 @@ pub enum SceneElement {
         plane_id: BezierPlaneId,
         corner_type: CornerType,
     },
+    /// The handle that is drawn on an end of a selected strand
+    StrandEndHandle {
+        strand_id: usize,
+        prime3: bool,
+    },
+    External3DObject(External3DObjectId),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -309,6 +346,8 @@ impl SceneElement {
             SceneElement::BezierVertex { .. } => Some(0),
             SceneElement::PlaneCorner { .. } => Some(0),
             SceneElement::BezierTengent { .. } => Some(0),
+            SceneElement::StrandEndHandle { .. } => Some(0),
+            SceneElement::External3DObject(_) => Some(0),
         }
     }
 
@@ -366,8 +405,15 @@ enum ObjType {
     BezierPlaneCorner = 0xFD,
     BezierTengentIn = 0xFC,
     BezierTengentOut = 0xFB,
+    StrandEndHandle = 0xFA,
+    /// The fragment shader of external objects draws the colors with this alpha value without
+    /// lighting them, so that their identifier is preserved in the fake texture.
+    External3DObject = 0xBF,
 }
 
+/// The bit of the identifier of a strand end handle that is set for the handles of 3' ends.
+const PRIME3_HANDLE_BIT: u32 = 1 << 23;
+
 impl SceneReader {
     pub fn new(draw_type: DrawType) -> Self {
         Self {
@@ -428,6 +474,15 @@ impl SceneReader {
                             vertex_id: (g + b) as usize,
                             tengent_in: false,
                         })
+                    } else if a == u32::from(ObjType::StrandEndHandle) {
+                        Some(SceneElement::StrandEndHandle {
+                            strand_id: (color & !PRIME3_HANDLE_BIT) as usize,
+                            prime3: color & PRIME3_HANDLE_BIT > 0,
+                        })
+                    } else if a == u32::from(ObjType::External3DObject) {
+                        Some(SceneElement::External3DObject(External3DObjectId(
+                            color as usize,
+                        )))
                     } else {
                         Some(SceneElement::DesignElement(a, color))
                     }
@@ -457,3 +512,12 @@ pub fn bezier_tengent_id(path_id: BezierPathId, vertex_id: usize, tengent_in: bo
     };
     (front << 24) | ((path_id.0) << 16) | (vertex_id as u32)
 }
+
+pub fn strand_end_handle_id(strand_id: usize, prime3: bool) -> u32 {
+    let prime3_bit = if prime3 { PRIME3_HANDLE_BIT } else { 0 };
+    (u32::from(ObjType::StrandEndHandle) << 24) | prime3_bit | (strand_id as u32)
+}
+
+pub fn external_3d_object_id(object_id: External3DObjectId) -> u32 {
+    (u32::from(ObjType::External3DObject) << 24) | (object_id.0 as u32)
+}
@@ -15,32 +15,39 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     You should have received a copy of the GNU General Public License
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use ensnano_design::{grid::HelixGridPosition, ultraviolet, BezierVertexId};
+use ensnano_design::{
+    grid::HelixGridPosition, ultraviolet, BezierVertexId, Collection, External3DObject,
+    External3DObjectId,
+};
 use ensnano_interactor::{
-    graphics::RenderingMode, NewBezierTengentVector, UnrootedRevolutionSurfaceDescriptor,
+    graphics::RenderingMode,
+    keymap::Keymap,
+    snapping::{self, TranslationStep},
+    toasts::{self, OverlayMessage, Toasts},
+    NewBezierTengentVector, UnrootedRevolutionSurfaceDescriptor,
 };
 use ensnano_utils::{wgpu, winit};
 use std::cell::RefCell;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use ultraviolet::{Mat4, Rotor3, Vec3};
+use std::time::{Duration, Instant};
+use ultraviolet::{Bivec3, Mat4, Rotor3, Vec3};
 
 use camera::FiniteVec3;
 use ensnano_design::{grid::GridPosition, group_attributes::GroupPivot, Nucl};
 use ensnano_interactor::{
-    application::{AppId, Application, Camera3D, Notification},
-    graphics::DrawArea,
+    application::{AppId, Application, Camera3D, Notification, PivotPlacement},
+    graphics::{DesignChanges, DrawArea, FogCenter, PngExportOptions, ReferenceDesign},
     operation::*,
-    ActionMode, CenterOfSelection, CheckXoversParameter, DesignOperation, Selection, SelectionMode,
-    StrandBuilder, WidgetBasis,
+    ActionMode, CandidatesFilter, CenterOfSelection, CheckXoversParameter, DesignOperation,
+    Selection, SelectionMode, SimulationState, StrandBuilder, StrandBuildingStatus, WidgetBasis,
 };
 use ensnano_utils::{instance, PhySize};
 use instance::Instance;
 use wgpu::{Device, Queue};
 use winit::dpi::PhysicalPosition;
-use winit::event::WindowEvent;
+use winit::event::{ElementState, WindowEvent};
 
 /// Computation of the view and projection matrix.
 mod camera;
@@ -55,11 +62,13 @@ use view::{
 /// Handling of inputs and notifications
 mod controller;
 use controller::{Consequence, Controller, WidgetTarget};
+mod cameras_export;
 /// Handling of designs and internal data
 mod data;
+use cameras_export::CamerasExport;
 pub use controller::ClickMode;
 use data::Data;
-pub use data::{DesignReader, HBond, HalfHBond, SurfaceInfo, SurfacePoint};
+pub use data::{DesignReader, HBond, HalfHBond, ModifiedObjects, SurfaceInfo, SurfacePoint};
 mod element_selector;
 use element_selector::{ElementSelector, SceneElement};
 mod maths_3d;
@@ -69,6 +78,14 @@ type DataPtr<R> = Rc<RefCell<Data<R>>>;
 use std::convert::TryInto;
 
 const PNG_SIZE: u32 = 256 * 10;
+/// The distance used to set the height of the orthographic view when there is no pivot
+const DEFAULT_ORTHOGRAPHIC_DIST: f32 = 10.;
+/// The distance between the camera and the pivot set when leaving fly mode, if there is no
+/// element in front of the camera
+const DEFAULT_FLY_PIVOT_DIST: f32 = 10.;
+/// The distance, in nanometers, by which a translation handle must be dragged to double the size
+/// of an external 3D object
+const EXTERNAL_OBJECT_DOUBLING_LENGTH: f32 = 10.;
 
 /// A structure responsible of the 3D display of the designs
 pub struct Scene<S: AppState> {
@@ -87,6 +104,28 @@ pub struct Scene<S: AppState> {
     requests: Arc<Mutex<dyn Requests>>,
     scene_kind: SceneKind,
     current_camera: Arc<(Camera3D, f32)>,
+    /// The export of the pictures taken from the saved cameras, if one is in progress
+    cameras_export: Option<CamerasExport>,
+    /// The position on which the group pivot that is being dragged snapped, if any
+    snapped_pivot: Option<GroupPivot>,
+    /// The external 3D object that is being moved with the widgets, as it was when the movement
+    /// started
+    moved_external_object: Option<(External3DObjectId, External3DObject)>,
+    /// Avoid requesting the same candidates at each mouse movement
+    candidates_filter: CandidatesFilter,
+    /// The last position of the cursor in the drawing area
+    cursor_position: PhysicalPosition<f64>,
+    /// The viewport under the cursor, if the drawing area is split into two viewports
+    split_viewport: Option<Viewport>,
+    /// The number of mouse buttons being held. The cursor stays attached to its viewport until
+    /// they are all released.
+    pressed_mouse_buttons: usize,
+    /// The messages explaining why the last actions of the user were not performed
+    toasts: Toasts,
+    /// The messages that are currently drawn on top of the view
+    overlay_messages: Vec<OverlayMessage>,
+    /// The step of the last keyboard nudge. The step is shown when it changes.
+    nudge_step: TranslationStep,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -95,6 +134,22 @@ pub enum SceneKind {
     Stereographic,
 }
 
+/// One of the two viewports of a split 3D view. Each viewport has its own camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Viewport {
+    Left,
+    Right,
+}
+
+impl Viewport {
+    fn other(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
 impl<S: AppState> Scene<S> {
     /// Create a new scene.
     /// # Argument
@@ -115,6 +170,7 @@ impl<S: AppState> Scene<S> {
         encoder: &mut wgpu::CommandEncoder,
         inital_state: S,
         scene_kind: SceneKind,
+        keymap: Arc<Keymap>,
     ) -> Self {
         let update = SceneUpdate::default();
         let view: ViewPtr = Rc::new(RefCell::new(View::new(
@@ -129,7 +185,7 @@ impl<S: AppState> Scene<S> {
             view.clone(),
         )));
         let controller: Controller<S> =
-            Controller::new(view.clone(), data.clone(), window_size, area.size);
+            Controller::new(view.clone(), data.clone(), window_size, area.size, keymap);
         let element_selector = ElementSelector::new(
             device,
             queue,
@@ -151,6 +207,16 @@ impl<S: AppState> Scene<S> {
                 Default::default(),
                 area.size.width as f32 / area.size.height as f32,
             )),
+            cameras_export: None,
+            snapped_pivot: None,
+            moved_external_object: None,
+            candidates_filter: Default::default(),
+            cursor_position: PhysicalPosition::new(-1., -1.),
+            split_viewport: None,
+            pressed_mouse_buttons: 0,
+            toasts: Default::default(),
+            overlay_messages: Vec::new(),
+            nudge_step: Default::default(),
         }
     }
 
@@ -171,6 +237,15 @@ impl<S: AppState> Scene<S> {
         cursor_position: PhysicalPosition<f64>,
         app_state: &S,
     ) -> Option<ensnano_interactor::CursorIcon> {
+        if let WindowEvent::CursorMoved { .. } = event {
+            self.cursor_position = cursor_position;
+        }
+        let cursor_position = self.viewport_cursor_position(event, cursor_position);
+        if let WindowEvent::CursorMoved { .. } = event {
+            if let SceneKind::Cartesian = self.scene_kind {
+                self.update_pointed_grid_position(cursor_position);
+            }
+        }
         let consequence = self.controller.input(
             event,
             cursor_position,
@@ -178,7 +253,128 @@ impl<S: AppState> Scene<S> {
             app_state,
         );
         self.read_consequence(consequence, app_state);
-        self.controller.get_icon()
+        self.controller.get_icon(app_state.get_action_mode().0)
+    }
+
+    /// The area of a viewport of the split view, relative to the drawing area.
+    fn viewport_area(&self, viewport: Viewport) -> DrawArea {
+        let left_width = self.area.size.width / 2;
+        let height = self.area.size.height;
+        match viewport {
+            Viewport::Left => DrawArea {
+                position: PhysicalPosition::new(0, 0),
+                size: PhySize::new(left_width, height),
+            },
+            Viewport::Right => DrawArea {
+                position: PhysicalPosition::new(left_width, 0),
+                size: PhySize::new(self.area.size.width - left_width, height),
+            },
+        }
+    }
+
+    /// The area of the viewport under the cursor, relative to the drawing area. This is the
+    /// whole drawing area if the view is not split.
+    fn active_area(&self) -> DrawArea {
+        if let Some(viewport) = self.split_viewport {
+            self.viewport_area(viewport)
+        } else {
+            DrawArea {
+                position: PhysicalPosition::new(0, 0),
+                size: self.area.size,
+            }
+        }
+    }
+
+    /// Split the drawing area into two viewports with independent cameras, or go back to a single
+    /// viewport.
+    fn toggle_split(&mut self) {
+        if self.is_stereographic() {
+            return;
+        }
+        if self.split_viewport.take().is_some() {
+            self.view.borrow_mut().merge_viewports(self.area.size);
+            self.controller.merge_cameras();
+        } else {
+            self.split_viewport = Some(Viewport::Left);
+            self.view.borrow_mut().split_viewports(
+                self.viewport_area(Viewport::Left),
+                self.viewport_area(Viewport::Right),
+            );
+            let inactive_camera = self.view.borrow().get_inactive_camera();
+            if let Some((camera, projection)) = inactive_camera {
+                self.controller.split_camera(camera, projection);
+            }
+        }
+        self.resize(self.controller.get_window_size());
+        self.notify(SceneNotification::CameraMoved);
+    }
+
+    /// If the view is split, give the control to the camera of the viewport under the cursor and
+    /// return the position of the cursor in that viewport.
+    fn viewport_cursor_position(
+        &mut self,
+        event: &WindowEvent,
+        cursor_position: PhysicalPosition<f64>,
+    ) -> PhysicalPosition<f64> {
+        let active = if let Some(viewport) = self.split_viewport {
+            viewport
+        } else {
+            return cursor_position;
+        };
+        let pointed = if cursor_position.x < self.viewport_area(Viewport::Right).position.x as f64 {
+            Viewport::Left
+        } else {
+            Viewport::Right
+        };
+        let may_change_viewport = self.pressed_mouse_buttons == 0
+            && matches!(
+                event,
+                WindowEvent::CursorMoved { .. } | WindowEvent::MouseWheel { .. }
+            );
+        if pointed != active && may_change_viewport {
+            self.view.borrow_mut().swap_viewports();
+            self.controller.swap_cameras();
+            self.split_viewport = Some(pointed);
+            self.resize(self.controller.get_window_size());
+            self.notify(SceneNotification::CameraMoved);
+        }
+        match event {
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                ..
+            } => self.pressed_mouse_buttons += 1,
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                ..
+            } => self.pressed_mouse_buttons = self.pressed_mouse_buttons.saturating_sub(1),
+            WindowEvent::Focused(false) => self.pressed_mouse_buttons = 0,
+            _ => (),
+        }
+        let area = self.active_area();
+        PhysicalPosition::new(
+            cursor_position.x - area.position.x as f64,
+            cursor_position.y - area.position.y as f64,
+        )
+    }
+
+    /// Update the labels of the grid cells near the cursor, and notify the application of the
+    /// grid position under the cursor.
+    fn update_pointed_grid_position(&mut self, cursor_position: PhysicalPosition<f64>) {
+        let size = self.active_area().size;
+        let position = self
+            .view
+            .borrow()
+            .grid_intersection(
+                (cursor_position.x / size.width as f64) as f32,
+                (cursor_position.y / size.height as f64) as f32,
+            )
+            .map(|intersection| intersection.grid_position());
+        if self.data.borrow_mut().set_pointed_grid_position(position) {
+            self.requests
+                .lock()
+                .unwrap()
+                .set_pointed_grid_position(position);
+        }
     }
 
     fn check_timers(&mut self, app_state: &S) {
@@ -190,6 +386,20 @@ impl<S: AppState> Scene<S> {
         if !matches!(consequence, Consequence::Nothing) {
             log::info!("Consequence {:?}", consequence);
         }
+        if app_state.get_simulation_state().is_runing() {
+            if let Some(edit) = consequence.design_edit() {
+                // The design cannot be modified during simulations, the request is not sent
+                self.toasts
+                    .push(toasts::blocked_during_simulation(edit), Instant::now());
+                if matches!(
+                    consequence,
+                    Consequence::XoverAtempt(..) | Consequence::EndFreeXover
+                ) {
+                    self.data.borrow_mut().end_free_xover();
+                }
+                return;
+            }
+        }
         match consequence {
             Consequence::Nothing => (),
             Consequence::CameraMoved => self.notify(SceneNotification::CameraMoved),
@@ -246,16 +456,32 @@ impl<S: AppState> Scene<S> {
                 );
                 if let Some(t) = translation {
                     match target {
+                        WidgetTarget::Object
+                            if self.moved_external_object.is_some()
+                                && self.controller.is_scaling() =>
+                        {
+                            self.scale_external_object(t, dir)
+                        }
                         WidgetTarget::Object => {
-                            self.translate_selected_design(t, app_state);
+                            if self.moved_external_object.is_some() {
+                                self.translate_external_object(t)
+                            } else {
+                                self.translate_selected_design(t, app_state);
+                            }
                             if app_state.get_current_group_id().is_none() {
                                 self.translate_group_pivot(t)
                             }
                         }
-                        WidgetTarget::Pivot => self.translate_group_pivot(t),
+                        WidgetTarget::Pivot => {
+                            let t = self.snap_pivot_translation(t, x_coord, y_coord, app_state);
+                            self.translate_group_pivot(t)
+                        }
                     }
                 }
             }
+            Consequence::Nudge { direction, step } => {
+                self.nudge_selection(direction, step, app_state)
+            }
             Consequence::ObjectTranslated { object, grid, x, y } => {
                 log::info!("Moving helix {:?} to grid {:?} ({} {})", object, grid, x, y);
                 self.requests
@@ -264,9 +490,15 @@ impl<S: AppState> Scene<S> {
                     .apply_design_operation(DesignOperation::AttachObject { object, grid, x, y });
             }
             Consequence::MovementEnded => {
+                self.moved_external_object = None;
+                self.data.borrow_mut().update_rotation_angle_label(None);
                 self.requests.lock().unwrap().suspend_op();
                 self.data.borrow_mut().notify_handle_movement();
                 self.view.borrow_mut().end_movement();
+                if let Some(pivot) = self.snapped_pivot.take() {
+                    self.request_candidates(vec![], app_state);
+                    self.requests.lock().unwrap().place_group_pivot(pivot);
+                }
             }
             Consequence::HelixSelected(helix_id) => self.requests.lock().unwrap().set_selection(
                 vec![Selection::Helix {
@@ -277,6 +509,7 @@ impl<S: AppState> Scene<S> {
                 None,
             ),
             Consequence::InitRotation(mode, x, y, target) => {
+                self.moved_external_object = Self::selected_external_object(app_state);
                 self.view
                     .borrow_mut()
                     .init_rotation(mode, x as f32, y as f32);
@@ -290,18 +523,56 @@ impl<S: AppState> Scene<S> {
                 }
             }
             Consequence::InitTranslation(x, y, _target) => {
+                self.moved_external_object = Self::selected_external_object(app_state);
                 self.view.borrow_mut().init_translation(x as f32, y as f32);
                 if let Some(pivot) = self.view.borrow().get_group_pivot() {
                     self.requests.lock().unwrap().set_current_group_pivot(pivot)
                 }
             }
-            Consequence::Rotation(x, y, target) => {
+            Consequence::InitRoll(x, y) => {
+                let roll = self
+                    .view
+                    .borrow()
+                    .get_roll_widget_helix()
+                    .and_then(|h_id| app_state.get_design_reader().get_helix_roll(h_id));
+                if let Some(roll) = roll {
+                    self.view.borrow_mut().init_roll(x as f32, y as f32, roll);
+                }
+            }
+            Consequence::Roll { x, y, snap } => {
+                let step = snap.then(|| {
+                    std::f32::consts::TAU
+                        / app_state
+                            .get_design_reader()
+                            .get_parameters()
+                            .bases_per_turn
+                });
+                let roll = self.view.borrow().compute_roll(x as f32, y as f32, step);
+                if let Some((helix_id, roll)) = roll {
+                    self.requests.lock().unwrap().apply_design_operation(
+                        DesignOperation::SetRollHelices {
+                            helices: vec![helix_id],
+                            roll,
+                        },
+                    );
+                }
+            }
+            Consequence::Rotation(x, y, target, step) => {
                 let rotation = self.view.borrow().compute_rotation(x as f32, y as f32);
                 if let Some((rotation, origin, positive)) = rotation {
                     if rotation.bv.mag() > 1e-3 {
+                        let (mut angle, plane) = snapping::signed_angle_plane(rotation, positive);
+                        if let Some(step) = step {
+                            angle = step.snap(angle);
+                        }
+                        let rotation = Rotor3::from_angle_plane(angle, plane);
                         match target {
                             WidgetTarget::Object => {
-                                self.rotate_selected_desgin(rotation, origin, positive, app_state);
+                                if self.moved_external_object.is_some() {
+                                    self.rotate_external_object(angle, plane, origin)
+                                } else {
+                                    self.rotate_selected_desgin(angle, plane, origin, app_state);
+                                }
                                 if app_state.get_current_group_id().is_none() {
                                     self.requests.lock().unwrap().rotate_group_pivot(rotation)
                                 }
@@ -310,12 +581,38 @@ impl<S: AppState> Scene<S> {
                                 self.requests.lock().unwrap().rotate_group_pivot(rotation)
                             }
                         }
+                        let label = format!("{:.1}°", angle.to_degrees());
+                        self.data
+                            .borrow_mut()
+                            .update_rotation_angle_label(Some(&label));
                     }
                     self.data.borrow_mut().notify_handle_movement();
                 } else {
                     log::warn!("Warning rotiation was None")
                 }
             }
+            Consequence::AngleEntry(text) => {
+                self.data
+                    .borrow_mut()
+                    .update_rotation_angle_label(Some(&format!("{text}°")));
+            }
+            Consequence::TypedRotation(text) => {
+                self.data.borrow_mut().update_rotation_angle_label(None);
+                match text.parse::<f32>() {
+                    Ok(degrees) if degrees.is_finite() => {
+                        self.rotate_by_typed_angle(degrees.to_radians(), app_state)
+                    }
+                    _ => self
+                        .toasts
+                        .push(format!("\"{text}\" is not a valid angle"), Instant::now()),
+                }
+            }
+            Consequence::Swing(x, y) if self.controller.is_fly_mode() => {
+                // In fly mode, the camera looks around itself
+                self.controller.set_pivot_point(None);
+                self.controller.swing(-x, -y);
+                self.notify(SceneNotification::CameraMoved);
+            }
             Consequence::Swing(x, y) => {
                 let mut pivot: Option<FiniteVec3> = self
                     .data
@@ -334,6 +631,9 @@ impl<S: AppState> Scene<S> {
                 self.controller.swing(-x, -y);
                 self.notify(SceneNotification::CameraMoved);
             }
+            Consequence::Tilt(_, _) if self.controller.is_turntable() => {
+                // The camera never rolls in turntable mode
+            }
             Consequence::Tilt(x, _) => {
                 let mut pivot: Option<FiniteVec3> = self
                     .data
@@ -357,6 +657,17 @@ impl<S: AppState> Scene<S> {
                 self.requests.lock().unwrap().toggle_widget_basis();
             }
             Consequence::BuildEnded => self.requests.lock().unwrap().suspend_op(),
+            Consequence::StrandEndReleased => {
+                let strand_to_close = self.data.borrow().get_strand_to_close(app_state);
+                let mut requests = self.requests.lock().unwrap();
+                if let Some(s_id) = strand_to_close {
+                    requests.apply_design_operation(DesignOperation::Xover {
+                        prime5_id: s_id,
+                        prime3_id: s_id,
+                    });
+                }
+                requests.suspend_op();
+            }
             Consequence::Undo => self.requests.lock().unwrap().undo(),
             Consequence::Redo => self.requests.lock().unwrap().redo(),
             Consequence::Building(position) => {
@@ -366,10 +677,42 @@ impl<S: AppState> Scene<S> {
                     .update_builder_position(position);
             }
             Consequence::Candidate(element) => self.set_candidate(element, app_state),
+            Consequence::LigationCandidate { element, partner } => {
+                let mut candidates: Vec<Selection> = self
+                    .data
+                    .borrow_mut()
+                    .set_candidate(element, app_state)
+                    .into_iter()
+                    .collect();
+                candidates.extend(partner.map(|nucl| Selection::Nucleotide(0, nucl)));
+                self.view.borrow_mut().set_widget_candidate(None);
+                self.request_candidates(candidates, app_state);
+            }
+            Consequence::Nick { nucl, strand_id } => self
+                .requests
+                .lock()
+                .unwrap()
+                .apply_design_operation(DesignOperation::Cut {
+                    nucl,
+                    s_id: strand_id,
+                }),
+            Consequence::Ligate {
+                prime5_id,
+                prime3_id,
+            } => self
+                .requests
+                .lock()
+                .unwrap()
+                .apply_design_operation(DesignOperation::Xover {
+                    prime5_id,
+                    prime3_id,
+                }),
             Consequence::PivotElement(element) => {
                 self.data.borrow_mut().set_pivot_element(element, app_state);
-                let pivot = self.data.borrow().get_pivot_position();
-                self.view.borrow_mut().update(ViewUpdate::FogCenter(pivot));
+                if !self.controller.is_fly_mode() {
+                    let pivot = self.data.borrow().get_pivot_position();
+                    self.set_pivot_fog_center(pivot);
+                }
             }
             Consequence::ElementSelected(element, adding) => {
                 if adding {
@@ -382,7 +725,14 @@ impl<S: AppState> Scene<S> {
                 .data
                 .borrow_mut()
                 .update_free_xover_target(element, position),
-            Consequence::EndFreeXover => self.data.borrow_mut().end_free_xover(),
+            Consequence::EndFreeXover => {
+                // The target of the free xover may have been snapped to a nucleotide
+                let snapped_xover = self.data.borrow().get_free_xover_nucls();
+                if let Some((source, target, d_id)) = snapped_xover {
+                    self.attempt_xover(source, target, d_id, false);
+                }
+                self.data.borrow_mut().end_free_xover()
+            }
             Consequence::BuildHelix {
                 grid_id,
                 design_id,
@@ -391,6 +741,19 @@ impl<S: AppState> Scene<S> {
                 x,
                 y,
             } => {
+                // When the position is occupied by an helix, the duplex is added on it
+                let duplex_helix = self
+                    .data
+                    .borrow()
+                    .get_helix_at_grid_position(
+                        design_id,
+                        GridPosition {
+                            grid: grid_id,
+                            x,
+                            y,
+                        },
+                    )
+                    .filter(|_| length > 0);
                 if self.controller.is_building_bezier_curve() {
                     let point = HelixGridPosition::from_grid_id_x_y(grid_id, x, y);
                     if let Some((start, end)) = self.controller.add_bezier_point(point) {
@@ -412,6 +775,14 @@ impl<S: AppState> Scene<S> {
                             app_state,
                         )
                     }
+                } else if let Some(helix) = duplex_helix {
+                    self.requests.lock().unwrap().apply_design_operation(
+                        DesignOperation::MakeDuplex {
+                            helix,
+                            start: position,
+                            length,
+                        },
+                    );
                 } else {
                     // build regular grid helix
                     self.requests
@@ -431,12 +802,24 @@ impl<S: AppState> Scene<S> {
             Consequence::PasteCandidate(element) => self.pasting_candidate(element),
             Consequence::Paste(element) => self.attempt_paste(element),
             Consequence::DoubleClick(element) => {
-                let selection = self.data.borrow().to_selection(element, app_state);
+                // Nucleotides and cross-overs are sent as such regardless of the selection mode
+                // so that the 2D view can center on them.
+                let nucl_selection = element
+                    .as_ref()
+                    .map(|e| {
+                        self.data
+                            .borrow()
+                            .element_to_selection(e, SelectionMode::Nucleotide)
+                    })
+                    .filter(|s| *s != Selection::Nothing);
+                let selection =
+                    nucl_selection.or_else(|| self.data.borrow().to_selection(element, app_state));
                 if let Some(selection) = selection {
                     self.requests
                         .lock()
                         .unwrap()
                         .request_center_selection(selection, AppId::Scene);
+                    self.center_camera_on_selection(selection, app_state);
                 }
             }
             Consequence::InitBuild(nucls) => {
@@ -457,9 +840,7 @@ impl<S: AppState> Scene<S> {
             }
             Consequence::PivotCenter => {
                 self.data.borrow_mut().set_pivot_position(Vec3::zero());
-                self.view
-                    .borrow_mut()
-                    .update(ViewUpdate::FogCenter(Some(Vec3::zero())));
+                self.set_pivot_fog_center(Some(Vec3::zero()));
             }
             Consequence::CheckXovers => {
                 let xovers = ensnano_interactor::list_of_xover_ids(
@@ -495,6 +876,17 @@ impl<S: AppState> Scene<S> {
                     )
                 }
             }
+            Consequence::InsertBezierVertex {
+                path_id,
+                after_vertex,
+                position,
+            } => self.requests.lock().unwrap().apply_design_operation(
+                DesignOperation::InsertVertexInPath {
+                    path_id,
+                    after_vertex,
+                    position,
+                },
+            ),
             Consequence::MoveBezierVertex {
                 x,
                 y,
@@ -555,12 +947,65 @@ impl<S: AppState> Scene<S> {
                 self.controller.reverse_surface_direction();
                 self.notify(SceneNotification::CameraMoved);
             }
+            Consequence::ReflectSelection => {
+                let selection = app_state.get_selection();
+                let helices = ensnano_interactor::list_of_helices(selection).map(|(_, h)| h);
+                let grids = ensnano_interactor::list_of_grids(selection).map(|(_, g)| g);
+                let frame = self.data.borrow().get_widget_frame(app_state);
+                if let (Some(plane_origin), Some(orientation)) = frame {
+                    if helices.is_some() || grids.is_some() {
+                        self.requests.lock().unwrap().apply_design_operation(
+                            DesignOperation::ReflectSelection {
+                                helices: helices.unwrap_or_default(),
+                                grids: grids.unwrap_or_default(),
+                                plane_origin,
+                                plane_normal: Vec3::unit_x().rotated_by(orientation),
+                            },
+                        )
+                    }
+                }
+            }
+            Consequence::PlacePivot => {
+                let placement = if let Some(nucl) = self.data.borrow().get_candidate_nucl() {
+                    PivotPlacement::Nucleotide(nucl)
+                } else {
+                    PivotPlacement::SelectionCentroid
+                };
+                self.place_group_pivot(placement, app_state)
+            }
+            Consequence::FocusFog => self
+                .requests
+                .lock()
+                .unwrap()
+                .apply_fog_preset(FogParameters::focus()),
+            Consequence::RemoveFog => {
+                let fog = self.view.borrow().get_fog_parameters().off();
+                self.requests.lock().unwrap().apply_fog_preset(fog)
+            }
             Consequence::SetRevolutionAxisPosition(r) => {
                 self.requests
                     .lock()
                     .unwrap()
                     .set_revolution_axis_position(r);
             }
+            Consequence::DrawingRectangleSelection(c1, c2) => self
+                .view
+                .borrow_mut()
+                .update(ViewUpdate::SelectionRectangle(Some([c1, c2]))),
+            Consequence::ReleasedRectangleSelection(c1, c2) => {
+                self.view
+                    .borrow_mut()
+                    .update(ViewUpdate::SelectionRectangle(None));
+                if let SceneKind::Cartesian = self.scene_kind {
+                    let selection = self.data.borrow().add_rectangle_to_selection(
+                        c1,
+                        c2,
+                        app_state.get_selection(),
+                        app_state,
+                    );
+                    self.requests.lock().unwrap().set_selection(selection, None);
+                }
+            }
         };
     }
 
@@ -582,10 +1027,8 @@ impl<S: AppState> Scene<S> {
     }
 
     fn element_center(&mut self, _app_state: &S) -> Option<SceneElement> {
-        let clicked_pixel = PhysicalPosition::new(
-            self.area.size.width as f64 / 2.,
-            self.area.size.height as f64 / 2.,
-        );
+        let size = self.active_area().size;
+        let clicked_pixel = PhysicalPosition::new(size.width as f64 / 2., size.height as f64 / 2.);
         let grid = self
             .view
             .borrow()
@@ -658,12 +1101,21 @@ impl<S: AppState> Scene<S> {
             None
         };
         self.view.borrow_mut().set_widget_candidate(widget);
-        let selection = if let Some(c) = new_candidates {
-            vec![c]
-        } else {
-            vec![]
-        };
-        self.requests.lock().unwrap().set_candidate(selection);
+        let note = self.data.borrow().get_candidate_note();
+        self.view.borrow_mut().update(ViewUpdate::Tooltip(
+            note.map(|note| (note, self.cursor_position)),
+        ));
+        self.request_candidates(new_candidates.into_iter().collect(), app_state);
+    }
+
+    /// Request the candidates to be set, unless they are already the current candidates.
+    fn request_candidates(&mut self, candidates: Vec<Selection>, app_state: &S) {
+        if let Some(candidates) = self
+            .candidates_filter
+            .filter(candidates, app_state.get_candidates())
+        {
+            self.requests.lock().unwrap().set_candidate(candidates);
+        }
     }
 
     fn translate_selected_design(&mut self, translation: Vec3, app_state: &S) {
@@ -742,6 +1194,125 @@ impl<S: AppState> Scene<S> {
             .update_opperation(translation_op);
     }
 
+    /// Move the selected helices or grids by `step` in `direction`, given in the basis of the
+    /// widget.
+    ///
+    /// Each key press is a complete movement that can be undone on its own: repeated key presses
+    /// are not merged into a single undoable step, so that undo always reverts the last nudge.
+    fn nudge_selection(&mut self, direction: Vec3, step: TranslationStep, app_state: &S) {
+        let rotor = if let Some(rotor) = self.data.borrow().get_widget_basis(app_state) {
+            rotor
+        } else {
+            return;
+        };
+        if step != self.nudge_step {
+            self.nudge_step = step;
+            self.toasts
+                .push(format!("Nudge step: {step}"), Instant::now());
+        }
+        let translation = direction.rotated_by(rotor) * step.length();
+        if let Some(pivot) = self.view.borrow().get_group_pivot() {
+            self.requests.lock().unwrap().set_current_group_pivot(pivot)
+        }
+        self.translate_selected_design(translation, app_state);
+        if app_state.get_current_group_id().is_none() {
+            self.translate_group_pivot(translation)
+        }
+        self.requests.lock().unwrap().suspend_op();
+        self.data.borrow_mut().notify_handle_movement();
+        self.view.borrow_mut().end_movement();
+    }
+
+    /// Rotate the selection by `angle` radians around the highlighted axis of the rotation
+    /// widget. The rotation is a single undoable step.
+    fn rotate_by_typed_angle(&mut self, angle: f32, app_state: &S) {
+        let axis = self.view.borrow().get_rotation_widget_axis();
+        let (origin, normal) = if let Some(axis) = axis {
+            axis
+        } else {
+            self.toasts.push(
+                "Point an axis of the rotation widget to rotate by a typed angle".to_string(),
+                Instant::now(),
+            );
+            return;
+        };
+        let (angle, plane) = snapping::rotation_around(normal, angle);
+        self.moved_external_object = Self::selected_external_object(app_state);
+        if let Some(pivot) = self.view.borrow().get_group_pivot() {
+            self.requests.lock().unwrap().set_current_group_pivot(pivot)
+        }
+        if self.moved_external_object.is_some() {
+            self.rotate_external_object(angle, plane, origin)
+        } else {
+            self.rotate_selected_desgin(angle, plane, origin, app_state);
+        }
+        if app_state.get_current_group_id().is_none() {
+            self.requests
+                .lock()
+                .unwrap()
+                .rotate_group_pivot(Rotor3::from_angle_plane(angle, plane))
+        }
+        self.moved_external_object = None;
+        self.requests.lock().unwrap().suspend_op();
+        self.data.borrow_mut().notify_handle_movement();
+    }
+
+    /// If a nucleotide is drawn near the cursor, highlight it and return the translation that
+    /// brings the pivot on it. Otherwise return `translation`.
+    fn snap_pivot_translation(&mut self, translation: Vec3, x: f64, y: f64, app_state: &S) -> Vec3 {
+        let origin = self.view.borrow().get_group_pivot();
+        let size = self.active_area().size;
+        let snapped = self.data.borrow().get_nucl_near_cursor(
+            PhysicalPosition::new(x, y),
+            size.width,
+            size.height,
+        );
+        let candidates = snapped
+            .iter()
+            .map(|(d_id, nucl, _)| Selection::Nucleotide(*d_id, *nucl))
+            .collect();
+        self.request_candidates(candidates, app_state);
+        match (origin, snapped) {
+            (Some(origin), Some((_, _, position))) => {
+                let orientation = app_state
+                    .get_current_group_pivot()
+                    .map(|pivot| pivot.orientation)
+                    .unwrap_or(origin.orientation);
+                self.snapped_pivot = Some(GroupPivot {
+                    position,
+                    orientation,
+                });
+                position - origin.position
+            }
+            _ => {
+                self.snapped_pivot = None;
+                translation
+            }
+        }
+    }
+
+    /// Move the pivot of the current group to a nucleotide or to the center of the selection.
+    fn place_group_pivot(&mut self, placement: PivotPlacement, app_state: &S) {
+        let position = match placement {
+            PivotPlacement::Nucleotide(nucl) => self.data.borrow().get_nucl_position(nucl, 0),
+            PivotPlacement::SelectionCentroid => {
+                self.data.borrow().get_selection_centroid(app_state)
+            }
+        };
+        if let Some(position) = position {
+            let orientation = app_state
+                .get_current_group_pivot()
+                .or_else(|| self.view.borrow().get_group_pivot())
+                .map(|pivot| pivot.orientation)
+                .unwrap_or_else(Rotor3::identity);
+            self.requests.lock().unwrap().place_group_pivot(GroupPivot {
+                position,
+                orientation,
+            });
+            self.data.borrow_mut().notify_handle_movement();
+        }
+    }
+
     fn translate_group_pivot(&mut self, translation: Vec3) {
         self.view.borrow_mut().translate_widgets(translation);
         self.requests
@@ -750,23 +1321,10 @@ impl<S: AppState> Scene<S> {
             .translate_group_pivot(translation);
     }
 
-    fn rotate_selected_desgin(
-        &mut self,
-        rotation: Rotor3,
-        origin: Vec3,
-        positive: bool,
-        app_state: &S,
-    ) {
-        log::debug!(
-            "Rotation {:?}, positive {}",
-            rotation.into_angle_plane(),
-            positive
-        );
-        let (mut angle, mut plane) = rotation.into_angle_plane();
-        if !positive {
-            angle *= -1.;
-            plane *= -1.;
-        }
+    /// Rotate the selected helices or grids by `angle` in `plane`. The angle and the plane
+    /// follow the convention of [`snapping::signed_angle_plane`].
+    fn rotate_selected_desgin(&mut self, angle: f32, plane: Bivec3, origin: Vec3, app_state: &S) {
+        log::debug!("Rotation {:?}", (angle, plane));
         let grids = ensnano_interactor::set_of_grids_containing_selection(
             app_state.get_selection(),
             &app_state.get_design_reader(),
@@ -818,19 +1376,152 @@ impl<S: AppState> Scene<S> {
         self.requests.lock().unwrap().update_opperation(rotation);
     }
 
+    /// The external 3D object that is selected, if it is the only selected element
+    fn selected_external_object(app_state: &S) -> Option<(External3DObjectId, External3DObject)> {
+        let object_id = ensnano_interactor::extract_external_3d_object(app_state.get_selection())?;
+        app_state
+            .get_design_reader()
+            .get_external_objects()
+            .get(&object_id)
+            .map(|object| (object_id, object.clone()))
+    }
+
+    fn translate_external_object(&mut self, translation: Vec3) {
+        if let Some((object_id, object)) = self.moved_external_object.as_ref() {
+            self.view.borrow_mut().translate_widgets(translation);
+            self.requests.lock().unwrap().update_opperation(Arc::new(
+                External3DObjectTransformation {
+                    object_id: *object_id,
+                    position: object.position() + translation,
+                    orientation: object.orientation(),
+                    scale: object.scale(),
+                },
+            ));
+        }
+    }
+
+    /// Scale the external 3D object that is being moved. Dragging a handle forward by
+    /// `EXTERNAL_OBJECT_DOUBLING_LENGTH` doubles the size of the object, dragging it backward
+    /// halves it.
+    fn scale_external_object(&mut self, translation: Vec3, dir: HandleDir) {
+        let handle_direction = self.view.borrow().get_handle_direction(dir);
+        if let Some(((object_id, object), handle_direction)) =
+            self.moved_external_object.as_ref().zip(handle_direction)
+        {
+            let length = translation.dot(handle_direction.normalized());
+            let factor = 2f32.powf(length / EXTERNAL_OBJECT_DOUBLING_LENGTH);
+            self.view.borrow_mut().translate_widgets(Vec3::zero());
+            self.requests.lock().unwrap().update_opperation(Arc::new(
+                External3DObjectTransformation {
+                    object_id: *object_id,
+                    position: object.position(),
+                    orientation: object.orientation(),
+                    scale: object.scale() * factor,
+                },
+            ));
+        }
+    }
+
+    fn rotate_external_object(&mut self, angle: f32, plane: Bivec3, origin: Vec3) {
+        if let Some((object_id, object)) = self.moved_external_object.as_ref() {
+            let rotation = Rotor3::from_angle_plane(angle, plane);
+            self.requests.lock().unwrap().update_opperation(Arc::new(
+                External3DObjectTransformation {
+                    object_id: *object_id,
+                    position: origin + (object.position() - origin).rotated_by(rotation),
+                    orientation: (rotation * object.orientation()).normalized(),
+                    scale: object.scale(),
+                },
+            ));
+        }
+    }
+
     /// Adapt the camera, position, orientation and pivot point to a design so that the design fits
     /// the scene, and the pivot point of the camera is the center of the design.
     fn fit_design(&mut self) {
         let camera_position = self.data.borrow().get_fitting_camera_position();
         if let Some(position) = camera_position {
             let pivot_point = self.data.borrow().get_middle_point(0);
+            let projection = self.view.borrow().get_projection();
+            if projection.borrow().is_orthographic() {
+                // Adapt the height of the view to the distance at which the design fits
+                projection
+                    .borrow_mut()
+                    .set_orthographic(true, (pivot_point - position).mag());
+            }
             self.notify(SceneNotification::NewCameraPosition(position));
             self.controller.set_pivot_point(pivot_point.try_into().ok());
         }
     }
 
+    fn set_fly_mode(&mut self, fly_mode: bool, app_state: &S) {
+        if fly_mode == self.controller.is_fly_mode() {
+            return;
+        }
+        self.controller.set_fly_mode(fly_mode);
+        if fly_mode {
+            // While flying, there is no pivot and the fog is centered on the camera
+            self.set_pivot_fog_center(None);
+        } else {
+            // Pivot around the element in front of the camera so that the orbit controls behave
+            // well after flying
+            let element = self.element_center(app_state);
+            let found_element = element.is_some();
+            self.data.borrow_mut().set_pivot_element(element, app_state);
+            let camera = self.view.borrow().get_camera();
+            let pivot = self
+                .data
+                .borrow()
+                .get_pivot_position()
+                .filter(|_| found_element)
+                .unwrap_or_else(|| {
+                    camera.borrow().position + DEFAULT_FLY_PIVOT_DIST * camera.borrow().direction()
+                });
+            self.data.borrow_mut().set_pivot_position(pivot);
+            self.controller.set_pivot_point(pivot.try_into().ok());
+            self.set_pivot_fog_center(Some(pivot));
+        }
+    }
+
+    /// Move the center of the fog to the pivot, unless the fog follows the selection.
+    fn set_pivot_fog_center(&mut self, pivot: Option<Vec3>) {
+        if self.view.borrow().get_fog_parameters().center != FogCenter::Selection {
+            self.view.borrow_mut().update(ViewUpdate::FogCenter(pivot));
+        }
+    }
+
+    /// If the fog follows the selection, move its center to the centroid of the selection. The
+    /// fog stays where it was when the selection becomes empty.
+    fn update_selection_fog_center(&mut self, app_state: &S) {
+        if self.view.borrow().get_fog_parameters().center == FogCenter::Selection {
+            let centroid = self.data.borrow().get_selection_centroid(app_state);
+            if centroid.is_some() {
+                self.view
+                    .borrow_mut()
+                    .update(ViewUpdate::FogCenter(centroid));
+            }
+        }
+    }
+
+    fn toggle_orthographic_projection(&mut self) {
+        let camera = self.view.borrow().get_camera();
+        let projection = self.view.borrow().get_projection();
+        let orthographic = !projection.borrow().is_orthographic();
+        // The height of the orthographic view is chosen so that the pivot keeps the same size on
+        // the screen
+        let dist = self
+            .data
+            .borrow()
+            .get_pivot_position()
+            .map(|pivot| (pivot - camera.borrow().position).dot(camera.borrow().direction()))
+            .filter(|dist| *dist > 0.)
+            .unwrap_or(DEFAULT_ORTHOGRAPHIC_DIST);
+        projection.borrow_mut().set_orthographic(orthographic, dist);
+    }
+
     fn need_redraw(&mut self, dt: Duration, new_state: S) -> bool {
         self.check_timers(&new_state);
+        self.export_next_camera();
         if self.controller.camera_is_moving() {
             self.notify(SceneNotification::CameraMoved);
         }
@@ -844,6 +1535,33 @@ impl<S: AppState> Scene<S> {
         self.data
             .borrow_mut()
             .update_view(&new_state, &self.older_state);
+        if let SceneKind::Cartesian = self.scene_kind {
+            if let Some(info) = self.data.borrow_mut().take_measurement_info_update() {
+                self.requests.lock().unwrap().set_measurement_info(info);
+            }
+        }
+        if new_state.design_was_modified(&self.older_state) {
+            self.controller.reset_pick_cycle();
+        }
+        if new_state.selection_was_updated(&self.older_state) {
+            self.update_selection_fog_center(&new_state);
+        }
+        if !snapping::can_enter_rotation_angle(
+            new_state.get_selection(),
+            new_state.get_action_mode().0,
+        ) && self.controller.cancel_angle_entry()
+        {
+            self.data.borrow_mut().update_rotation_angle_label(None);
+        }
+        let overlay_messages = self
+            .toasts
+            .overlay_messages(new_state.get_simulation_state().is_runing(), Instant::now());
+        if overlay_messages != self.overlay_messages {
+            self.overlay_messages = overlay_messages.clone();
+            self.view
+                .borrow_mut()
+                .update(ViewUpdate::OverlayMessages(overlay_messages));
+        }
         let mut ret = new_state.draw_options_were_updated(&self.older_state);
         self.older_state = new_state;
         ret |= self.view.borrow().need_redraw();
@@ -896,6 +1614,26 @@ impl<S: AppState> Scene<S> {
         ret
     }
 
+    /// Move the camera toward the element corresponding to `selection`.
+    fn center_camera_on_selection(&mut self, selection: Selection, app_state: &S) {
+        let surface_info = if let Selection::Nucleotide(_, nt) = selection {
+            self.data.borrow().get_surface_info_nucl(nt)
+        } else {
+            None
+        };
+        let duration = app_state.get_camera_animation_duration();
+        if let Some(surface_info) = surface_info {
+            self.controller.set_surface_point(surface_info, duration);
+        } else if let Some(position) = self.data.borrow().get_selection_position(selection) {
+            self.controller.center_camera(position, duration);
+        }
+        let pivot_element = self.data.borrow().selection_to_element(selection);
+        self.data
+            .borrow_mut()
+            .set_pivot_element(pivot_element, app_state);
+        self.notify(SceneNotification::CameraMoved);
+    }
+
     fn set_camera_target(&mut self, target: Vec3, up: Vec3, app_state: &S) {
         let pivot = self
             .data
@@ -969,28 +1707,136 @@ impl<S: AppState> Scene<S> {
         (texture, view)
     }
 
-    fn export_png(&self) {
+    fn export_png(&self, options: PngExportOptions) {
         use chrono::Utc;
-        let png_name = Utc::now()
-            .format("export_3d_%Y_%m_%d_%H_%M_%S.png")
-            .to_string();
+        let png_name = if self.is_stereographic() {
+            Utc::now().format("export_stereo_%Y_%m_%d_%H_%M_%S.png")
+        } else {
+            Utc::now().format("export_3d_%Y_%m_%d_%H_%M_%S.png")
+        }
+        .to_string();
+        let path = PathBuf::from(png_name);
+        let result = self.render_png(&path, options).map(|()| path);
+        self.requests.lock().unwrap().png_export_finished(result);
+    }
+
+    /// Start the export of one picture per saved camera of the design.
+    fn start_cameras_export(&mut self, directory: PathBuf) {
+        let cameras = self.older_state.get_design_reader().get_all_cameras();
+        let initial_camera = Camera3D {
+            pivot_position: self.data.borrow().get_explicit_pivot_position(),
+            ..self.get_camera()
+        };
+        self.cameras_export = Some(CamerasExport::new(directory, cameras, initial_camera));
+        self.requests
+            .lock()
+            .unwrap()
+            .set_cameras_export_progress(Some(0.));
+    }
+
+    /// Render the picture taken from the next camera of the ongoing cameras export. Once all the
+    /// pictures have been rendered, restore the camera and pivot of the scene.
+    fn export_next_camera(&mut self) {
+        let next_picture = match self.cameras_export.as_mut() {
+            Some(export) => export.next_picture(),
+            None => return,
+        };
+        if let Some((camera, path)) = next_picture {
+            self.set_camera_and_pivot(&camera, true);
+            if let Err(err) = self.render_png(&path, Default::default()) {
+                self.requests.lock().unwrap().png_export_finished(Err(err));
+                // Abort the export
+                if let Some(export) = self.cameras_export.as_mut() {
+                    export.abort();
+                }
+            }
+            let progress = self
+                .cameras_export
+                .as_ref()
+                .map(CamerasExport::progress)
+                .unwrap_or(1.);
+            self.requests
+                .lock()
+                .unwrap()
+                .set_cameras_export_progress(Some(progress));
+        } else if let Some(export) = self.cameras_export.take() {
+            self.set_camera_and_pivot(&export.initial_camera, false);
+            self.notify(SceneNotification::CameraMoved);
+            self.requests
+                .lock()
+                .unwrap()
+                .set_cameras_export_progress(None);
+            if !export.was_aborted() {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .png_export_finished(Ok(export.directory().to_path_buf()));
+            }
+        }
+    }
+
+    /// Move the camera without animation and update the view accordingly.
+    ///
+    /// If `keep_pivot` is true and `camera` has no pivot, the current pivot is kept.
+    fn set_camera_and_pivot(&mut self, camera: &Camera3D, keep_pivot: bool) {
+        self.controller
+            .teleport_camera(camera.position, camera.orientation);
+        if camera.pivot_position.is_some() || !keep_pivot {
+            self.data
+                .borrow_mut()
+                .restore_pivot_position(camera.pivot_position);
+        }
+        self.view.borrow_mut().update(ViewUpdate::Camera);
+    }
+
+    /// Render the scene in a png file.
+    fn render_png(&self, png_name: &Path, options: PngExportOptions) -> Result<(), String> {
+        let stereographic = self.is_stereographic();
         let device = self.element_selector.device.as_ref();
         let queue = self.element_selector.queue.as_ref();
-        println!("export to {png_name}");
-        use ensnano_utils::BufferDimensions;
+        log::info!("export to {}", png_name.to_string_lossy());
         use std::io::Write;
 
-        let ratio = self.view.borrow().get_projection().borrow().get_ratio();
-        let width = if ratio < 1. {
-            (ratio * PNG_SIZE as f32).floor() as u32
-        } else {
-            PNG_SIZE
-        };
-        let height = if ratio < 1. {
-            PNG_SIZE
+        let projection = self.view.borrow().get_projection();
+        let ratio = projection.borrow().get_ratio();
+        let (width, height) = if let Some(resolution) = options.resolution {
+            resolution
+        } else if stereographic {
+            // The stereographic projection of the whole space is a disc, so the exported image is
+            // a square
+            (PNG_SIZE, PNG_SIZE)
+        } else if ratio < 1. {
+            ((ratio * PNG_SIZE as f32).floor() as u32, PNG_SIZE)
         } else {
-            (PNG_SIZE as f32 / ratio).floor() as u32
+            (PNG_SIZE, (PNG_SIZE as f32 / ratio).floor() as u32)
         };
+        let max_size = device.limits().max_texture_dimension_2d;
+        if width == 0 || height == 0 {
+            return Err(String::from(
+                "The width and height of the image must be positive",
+            ));
+        }
+        if width > max_size || height > max_size {
+            return Err(format!(
+                "The image is too large, its width and height must be at most {max_size} pixels"
+            ));
+        }
+        let out_file = std::fs::File::create(png_name)
+            .map_err(|e| format!("Could not create {}: {e}", png_name.to_string_lossy()))?;
+
+        // Give the projection the aspect ratio of the exported image
+        projection.borrow_mut().resize(width, height);
+        let fog = self.view.borrow().get_fog_parameters();
+        if options.transparent_background {
+            // The fog would blend the far away elements with an opaque color
+            self.view
+                .borrow_mut()
+                .update(ViewUpdate::Fog(FogParameters {
+                    fog_kind: ensnano_interactor::graphics::fog_kind::NO_FOG,
+                    ..fog.clone()
+                }));
+        }
+        self.view.borrow_mut().update(ViewUpdate::Camera);
         let size = wgpu::Extent3d {
             width,
             height,
@@ -1004,7 +1850,10 @@ impl<S: AppState> Scene<S> {
         });
 
         let draw_options = DrawOptions {
-            rendering_mode: RenderingMode::Cartoon,
+            rendering_mode: options
+                .rendering_mode
+                .unwrap_or_else(|| self.older_state.get_draw_options().rendering_mode),
+            transparent_background: options.transparent_background,
             ..Default::default()
         };
 
@@ -1016,91 +1865,36 @@ impl<S: AppState> Scene<S> {
                 position: PhysicalPosition { x: 0, y: 0 },
                 size: PhySize { width, height },
             },
-            self.is_stereographic(),
+            stereographic,
             draw_options,
         );
 
-        // create a buffer and fill it with the texture
-        let extent = wgpu::Extent3d {
-            width: size.width,
-            height: size.height,
-            depth_or_array_layers: 1,
-        };
-        let buffer_dimensions =
-            BufferDimensions::new(extent.width as usize, extent.height as usize);
-        let buf_size = buffer_dimensions.padded_bytes_per_row * buffer_dimensions.height;
-        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            size: buf_size as u64,
-            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-            label: Some("staging_buffer"),
-        });
-        let buffer_copy_view = wgpu::ImageCopyBuffer {
-            buffer: &staging_buffer,
-            layout: wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: (buffer_dimensions.padded_bytes_per_row as u32)
-                    .try_into()
-                    .ok(),
-                rows_per_image: None,
-            },
-        };
-        let origin = wgpu::Origin3d { x: 0, y: 0, z: 0 };
-        let texture_copy_view = wgpu::ImageCopyTexture {
-            texture: &texture,
-            mip_level: 0,
-            origin,
-            aspect: Default::default(),
-        };
+        let pixels =
+            ensnano_utils::readback::read_texture_as_rgba(device, queue, encoder, &texture, size);
+        // Restore the aspect ratio of the drawing area and the fog
+        let size = self.active_area().size;
+        projection.borrow_mut().resize(size.width, size.height);
+        if options.transparent_background {
+            self.view.borrow_mut().update(ViewUpdate::Fog(fog));
+        }
+        self.view.borrow_mut().update(ViewUpdate::Camera);
 
-        encoder.copy_texture_to_buffer(texture_copy_view, buffer_copy_view, extent);
-        queue.submit(Some(encoder.finish()));
-
-        let buffer_slice = staging_buffer.slice(..);
-        let buffer_future = buffer_slice.map_async(wgpu::MapMode::Read);
-        device.poll(wgpu::Maintain::Wait);
-
-        let pixels = async {
-            if let Ok(()) = buffer_future.await {
-                let pixels_slice = buffer_slice.get_mapped_range();
-                let mut pixels = Vec::with_capacity((size.height * size.width) as usize);
-                for chunck in pixels_slice.chunks(buffer_dimensions.padded_bytes_per_row) {
-                    for chunk in chunck.chunks(4) {
-                        // convert Bgra to Rgba
-                        pixels.push(chunk[2]);
-                        pixels.push(chunk[1]);
-                        pixels.push(chunk[0]);
-                        pixels.push(chunk[3]);
-                    }
-                }
-                drop(pixels_slice);
-                staging_buffer.unmap();
-                pixels
-            } else {
-                panic!("could not read fake texture");
-            }
-        };
-        let pixels = futures::executor::block_on(pixels);
-        let mut png_encoder = png::Encoder::new(
-            std::fs::File::create(png_name).unwrap(),
-            buffer_dimensions.width as u32,
-            buffer_dimensions.height as u32,
-        );
+        let write_error =
+            |e: png::EncodingError| format!("Could not write {}: {e}", png_name.to_string_lossy());
+        let mut png_encoder = png::Encoder::new(out_file, width, height);
         png_encoder.set_depth(png::BitDepth::Eight);
         png_encoder.set_color(png::ColorType::Rgba);
 
         let mut png_writer = png_encoder
             .write_header()
-            .unwrap()
-            .into_stream_writer_with_size(buffer_dimensions.unpadded_bytes_per_row)
-            .unwrap();
-
-        for chunk in pixels.chunks(buffer_dimensions.padded_bytes_per_row) {
-            png_writer
-                .write_all(&chunk[..buffer_dimensions.unpadded_bytes_per_row])
-                .unwrap();
-        }
-        png_writer.finish().unwrap();
+            .map_err(write_error)?
+            .into_stream_writer_with_size(4 * width as usize)
+            .map_err(write_error)?;
+
+        png_writer
+            .write_all(pixels.as_slice())
+            .map_err(|e| format!("Could not write {}: {e}", png_name.to_string_lossy()))?;
+        png_writer.finish().map_err(write_error)
     }
 }
 
@@ -1156,15 +1950,44 @@ impl<S: AppState> Scene<S> {
 
     fn resize(&mut self, window_size: PhySize) {
         self.view.borrow_mut().update(ViewUpdate::Size(window_size));
-        self.controller.resize(window_size, self.area.size);
+        let active_area = self.active_area();
+        self.controller.resize(window_size, active_area.size);
+        if let Some(viewport) = self.split_viewport {
+            self.view
+                .borrow_mut()
+                .set_viewport_areas(active_area, self.viewport_area(viewport.other()));
+        }
         self.update.camera_update = true;
+        // The element selector reads the pixels of the viewport under the cursor, in window
+        // coordinates
+        let selector_area = DrawArea {
+            position: PhysicalPosition::new(
+                self.area.position.x + active_area.position.x,
+                self.area.position.y + active_area.position.y,
+            ),
+            size: active_area.size,
+        };
         self.element_selector
-            .resize(self.controller.get_window_size(), self.area);
+            .resize(self.controller.get_window_size(), selector_area);
     }
 
     pub fn fog_request(&mut self, fog: FogParameters) {
         if !self.is_stereographic() {
-            self.view.borrow_mut().update(ViewUpdate::Fog(fog))
+            let center = match fog.center {
+                FogCenter::Camera => None,
+                FogCenter::Pivot => self.data.borrow().get_pivot_position(),
+                FogCenter::Selection => {
+                    self.data.borrow().get_selection_centroid(&self.older_state)
+                }
+            };
+            // A center given with the parameters, such as the one saved with the design, is
+            // kept until the pivot or the selection changes.
+            self.view
+                .borrow_mut()
+                .update(ViewUpdate::Fog(FogParameters {
+                    alt_fog_center: fog.alt_fog_center.or(center),
+                    ..fog
+                }))
         }
     }
 }
@@ -1190,13 +2013,25 @@ impl<S: AppState> Application for Scene<S> {
                 }
                 self.notify(SceneNotification::CameraMoved);
             }
+            Notification::SmoothTeleportCamera(camera) => {
+                self.controller.smooth_teleport_camera(
+                    camera.position,
+                    camera.orientation,
+                    older_state.get_camera_animation_duration(),
+                );
+                if let Some(pivot) = camera.pivot_position {
+                    self.data.borrow_mut().set_pivot_position(pivot);
+                }
+                self.notify(SceneNotification::CameraMoved);
+            }
             Notification::CameraRotation(xz, yz, xy) => {
                 self.request_camera_rotation(xz, yz, xy, &older_state);
                 self.notify(SceneNotification::CameraMoved);
             }
             Notification::Centering(nucl, design_id) => {
                 if let Some(position) = self.data.borrow().get_nucl_position(nucl, design_id) {
-                    self.controller.center_camera(position);
+                    self.controller
+                        .center_camera(position, older_state.get_camera_animation_duration());
                 }
                 self.notify(SceneNotification::CameraMoved);
             }
@@ -1210,10 +2045,11 @@ impl<S: AppState> Application for Scene<S> {
                     } else {
                         None
                     };
+                    let duration = older_state.get_camera_animation_duration();
                     if let Some(surface_info) = surface_info {
-                        self.controller.set_surface_point(surface_info);
+                        self.controller.set_surface_point(surface_info, duration);
                     } else if let Some(position) = self.data.borrow().get_selected_position() {
-                        self.controller.center_camera(position);
+                        self.controller.center_camera(position, duration);
                     }
                     let pivot_element = self.data.borrow().selection_to_element(selection);
                     self.data
@@ -1222,10 +2058,14 @@ impl<S: AppState> Application for Scene<S> {
                     self.notify(SceneNotification::CameraMoved);
                 }
             }
-            Notification::ShowTorsion(_) => (),
+            Notification::ShowTorsion(b) => self.data.borrow_mut().set_show_torsion(b),
+            Notification::ShowStrain(b) => self.data.borrow_mut().set_show_strain(b),
+            Notification::ShowDomainLengths(_) => (),
+            Notification::TorsionRange(range) => self.data.borrow_mut().set_torsion_range(range),
             Notification::ModifersChanged(modifiers) => self.controller.update_modifiers(modifiers),
             Notification::Split2d => (),
             Notification::Redim2dHelices(_) => (),
+            Notification::Compact2dLayout => (),
             Notification::Fog(fog) => self.fog_request(fog),
             Notification::WindowFocusLost => self.controller.stop_camera_movement(),
             Notification::NewStereographicCamera(camera_ptr) => {
@@ -1240,15 +2080,35 @@ impl<S: AppState> Application for Scene<S> {
                 }
             }
             Notification::FlipSplitViews => (),
+            Notification::ToggleSplitScene3D => self.toggle_split(),
             Notification::HorizonAligned => {
                 self.controller.align_horizon();
                 self.notify(SceneNotification::CameraMoved);
             }
-            Notification::ScreenShot3D => {
+            Notification::ScreenShot3D(options) => self.export_png(options),
+            Notification::ExportAllCameras(directory) => {
                 if !self.is_stereographic() {
-                    self.export_png();
+                    self.start_cameras_export(directory)
                 }
             }
+            Notification::ToggleOrthographicProjection => {
+                self.toggle_orthographic_projection();
+                self.notify(SceneNotification::CameraMoved);
+            }
+            Notification::CameraModeChanged(camera_mode) => {
+                self.controller.set_camera_mode(camera_mode);
+                self.notify(SceneNotification::CameraMoved);
+            }
+            Notification::FlyMode(fly_mode) => {
+                self.set_fly_mode(fly_mode, &older_state);
+                self.notify(SceneNotification::CameraMoved);
+            }
+            Notification::TeleportCameras2D(_) => (),
+            Notification::PlaceGroupPivot(placement) => {
+                self.place_group_pivot(placement, &older_state)
+            }
+            // The isolated elements are read from the draw options of the app state
+            Notification::IsolateSelection(_) => (),
         }
     }
 
@@ -1303,8 +2163,12 @@ impl<S: AppState> Application for Scene<S> {
         self.view.borrow().get_current_pivot()
     }
 
+    fn get_fog_parameters(&self) -> Option<FogParameters> {
+        Some(self.view.borrow().get_fog_parameters())
+    }
+
     fn is_splited(&self) -> bool {
-        false
+        self.split_viewport.is_some()
     }
 }
 
@@ -1320,6 +2184,8 @@ pub trait AppState: Clone + 'static {
     fn get_action_mode(&self) -> (ActionMode, WidgetBasis);
     fn get_design_reader(&self) -> Self::DesignReader;
     fn get_strand_builders(&self) -> &[StrandBuilder];
+    /// The length of the domain being built, if a strand is being built
+    fn get_building_state(&self) -> Option<StrandBuildingStatus>;
     fn get_widget_basis(&self) -> WidgetBasis;
     fn is_changing_color(&self) -> bool;
     fn is_pasting(&self) -> bool;
@@ -1332,7 +2198,10 @@ pub trait AppState: Clone + 'static {
     fn get_draw_options(&self) -> DrawOptions;
     fn draw_options_were_updated(&self, other: &Self) -> bool;
     fn get_scroll_sensitivity(&self) -> f32;
+    /// The duration of the animated camera movements
+    fn get_camera_animation_duration(&self) -> Duration;
     fn show_insertion_representents(&self) -> bool;
+    fn get_simulation_state(&self) -> SimulationState;
 
     fn insertion_bond_display_was_modified(&self, other: &Self) -> bool {
         self.show_insertion_representents() != other.show_insertion_representents()
@@ -1349,6 +2218,20 @@ pub trait AppState: Clone + 'static {
     fn get_revolution_axis_position(&self) -> Option<f64>;
     fn revolution_bezier_updated(&self, other: &Self) -> bool;
     fn get_current_unrooted_surface(&self) -> Option<UnrootedRevolutionSurfaceDescriptor>;
+
+    /// The design loaded next to the edited one for comparison, if any
+    fn get_reference_design(&self) -> Option<&ReferenceDesign>;
+
+    fn reference_design_was_updated(&self, other: &Self) -> bool {
+        self.get_reference_design() != other.get_reference_design()
+    }
+
+    /// The changes made to the design since it was last saved, if they must be highlighted
+    fn get_design_changes(&self) -> Option<&DesignChanges>;
+
+    fn design_changes_were_updated(&self, other: &Self) -> bool {
+        self.get_design_changes() != other.get_design_changes()
+    }
 }
 
 pub trait Requests {
@@ -1373,6 +2256,22 @@ pub trait Requests {
     fn toggle_widget_basis(&mut self);
     fn set_current_group_pivot(&mut self, pivot: GroupPivot);
     fn translate_group_pivot(&mut self, translation: Vec3);
+    /// Set the pivot of the current group, replacing the one it already has.
+    fn place_group_pivot(&mut self, pivot: GroupPivot);
     fn rotate_group_pivot(&mut self, rotation: Rotor3);
     fn set_revolution_axis_position(&mut self, position: f32);
+    /// Replace the fog of the 3D view by a preset, and show its parameters in the left panel.
+    fn apply_fog_preset(&mut self, fog: FogParameters);
+    /// Display the distances and angle between the selected nucleotides in the status bar, or
+    /// clear them if `info` is `None`.
+    fn set_measurement_info(&mut self, info: Option<String>);
+    /// Display the grid position under the cursor in the status bar, or clear it if `position`
+    /// is `None`.
+    fn set_pointed_grid_position(&mut self, position: Option<GridPosition>);
+    /// Display the progress of the export of the pictures taken from the saved cameras, or
+    /// notify that the export is finished if `progress` is `None`.
+    fn set_cameras_export_progress(&mut self, progress: Option<f32>);
+    /// Notify that png pictures of the scene were written at the given path, or that the export
+    /// failed.
+    fn png_export_finished(&mut self, result: Result<PathBuf, String>);
 }
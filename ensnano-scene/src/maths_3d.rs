@@ -51,8 +51,7 @@ pub fn unproject_point_on_line(
     y_ndc: f32,
     stereography: Option<&Stereography>,
 ) -> Option<Vec3> {
-    let p1 = camera.borrow().position;
-    let p2 = ndc_to_world(x_ndc, y_ndc, camera, projection, stereography);
+    let (p1, p2) = ndc_to_world(x_ndc, y_ndc, camera, projection, stereography);
 
     let p3 = objective_origin;
     let p4 = objective_origin + objective_direction;
@@ -78,8 +77,7 @@ pub fn distance_to_cursor_with_penalty(
     initial_position: Option<Vec3>,
     stereography: Option<&Stereography>,
 ) -> Option<f32> {
-    let p1 = camera.borrow().position;
-    let p2 = ndc_to_world(x_ndc, y_ndc, camera, projection, stereography);
+    let (p1, p2) = ndc_to_world(x_ndc, y_ndc, camera, projection, stereography);
 
     let direction = (p2 - p1).normalized();
 
@@ -113,8 +111,7 @@ pub fn unproject_point_on_plane(
     y_ndc: f32,
     stereography: Option<&Stereography>,
 ) -> Option<Vec3> {
-    let p1 = camera.borrow().position;
-    let p2 = ndc_to_world(x_ndc, y_ndc, camera, projection, stereography);
+    let (p1, p2) = ndc_to_world(x_ndc, y_ndc, camera, projection, stereography);
 
     let dir = p2 - p1;
 
@@ -127,14 +124,18 @@ pub fn unproject_point_on_plane(
     }
 }
 
-/// Convert a point on the screen into a point in the world. Usefull for casting rays
+/// Convert a point on the screen into two points in the world. Usefull for casting rays
+///
+/// The first point is the origin of the ray going through the point of the screen, and the
+/// second one is at distance 1 of the origin on that ray. With the perspective projection, the
+/// origin of the ray is the position of the camera.
 fn ndc_to_world(
     x_ndc: f32,
     y_ndc: f32,
     camera: CameraPtr,
     projection: ProjectionPtr,
     stereography: Option<&Stereography>,
-) -> Vec3 {
+) -> (Vec3, Vec3) {
     let x_screen = 2. * x_ndc - 1.;
     let y_screen = 1. - 2. * y_ndc;
 
@@ -145,7 +146,14 @@ fn ndc_to_world(
         let x = 2. * khi / (1. + eta * eta + khi * khi);
         let y = 2. * eta / (1. + eta * eta + khi * khi);
         let z = (-1. + eta * eta + khi * khi) / (1. + khi * khi + eta * eta);
-        p1 + camera.borrow().rotor.reversed() * Vec3 { x, y, z }
+        (p1, p1 + camera.borrow().rotor.reversed() * Vec3 { x, y, z })
+    } else if projection.borrow().is_orthographic() {
+        // All the rays are parallel to the direction of the camera
+        let half_height = projection.borrow().half_height_at(1.);
+        let right = camera.borrow().right_vec() * half_height;
+        let up = camera.borrow().up_vec() * half_height;
+        let origin = p1 + right * x_screen * projection.borrow().get_ratio() + up * y_screen;
+        (origin, origin + camera.borrow().direction())
     } else {
         let p2 = {
             let correction = (projection.borrow().get_fovy() / 2.).tan();
@@ -154,7 +162,7 @@ fn ndc_to_world(
             let direction = camera.borrow().direction();
             p1 + right * x_screen * projection.borrow().get_ratio() + up * y_screen + direction
         };
-        p2
+        (p1, p2)
     }
 }
 
@@ -165,8 +173,8 @@ pub fn cast_ray(
     projection: ProjectionPtr,
     stereography: Option<&Stereography>,
 ) -> (Vec3, Vec3) {
-    let target = ndc_to_world(x_ndc, y_ndc, camera.clone(), projection, stereography);
-    (camera.borrow().position, target - camera.borrow().position)
+    let (origin, target) = ndc_to_world(x_ndc, y_ndc, camera, projection, stereography);
+    (origin, target - origin)
 }
 
 pub struct UnalignedBoundaries {
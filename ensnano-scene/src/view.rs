@@ -21,16 +21,17 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use self::gltf_drawer::Object3DDrawer;
 
 use super::camera;
-use crate::{DrawArea, PhySize};
+use crate::{DrawArea, PhySize, PhysicalPosition};
 use camera::{Camera, CameraPtr, Projection, ProjectionPtr};
 use ensnano_design::group_attributes::GroupPivot;
 use ensnano_design::ultraviolet;
-use ensnano_design::{grid::GridId, Axis};
-use ensnano_interactor::{consts::*, UnrootedRevolutionSurfaceDescriptor};
+use ensnano_design::{grid::GridId, Axis, External3DObjectId};
+use ensnano_interactor::{consts::*, toasts::OverlayMessage, UnrootedRevolutionSurfaceDescriptor};
 use ensnano_utils::wgpu;
-use ensnano_utils::{bindgroup_manager, text, texture};
+use ensnano_utils::{bindgroup_manager, text, texture, Ndc};
 use std::cell::RefCell;
 use std::collections::BTreeMap;
+use std::ops::Range;
 use std::rc::Rc;
 use std::usize;
 use texture::Texture;
@@ -52,10 +53,16 @@ mod grid_disc;
 /// A HandleDrawer draws the widget for translating objects
 mod handle_drawer;
 mod instances_drawer;
+mod legend;
 mod letter;
+mod overlay_messages;
+mod rectangle;
+/// A RollWidget draws the widget for modifying the roll of a helix
+mod roll_widget;
 /// A RotationWidget draws the widget for rotating objects
 mod rotation_widget;
 mod sheet_2d;
+mod tooltip;
 
 use super::maths_3d::{self, distance_to_cursor_with_penalty};
 use bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
@@ -72,14 +79,20 @@ use handle_drawer::HandlesDrawer;
 pub use handle_drawer::{HandleColors, HandleDir, HandleOrientation, HandlesDescriptor};
 pub use instances_drawer::Instanciable;
 use instances_drawer::{InstanceDrawer, RawDrawer};
+use legend::Legend;
 pub use letter::LetterInstance;
 use maths_3d::unproject_point_on_line;
+use overlay_messages::OverlayMessagesDrawer;
+use rectangle::Rectangle;
+use roll_widget::RollWidget;
+pub use roll_widget::RollWidgetDescriptor;
 use rotation_widget::RotationWidget;
 pub use rotation_widget::{
     AvailableRotationAxes, RotationMode, RotationWidgetDescriptor, RotationWidgetOrientation,
 };
 pub use sheet_2d::Sheet2D;
 use text::Letter;
+use tooltip::Tooltip;
 //use plane_drawer::PlaneDrawer;
 //pub use plane_drawer::Plane;
 
@@ -94,7 +107,10 @@ static MODEL_BG_ENTRY: &[wgpu::BindGroupLayoutEntry] = &[wgpu::BindGroupLayoutEn
     count: None,
 }];
 
-use ensnano_interactor::graphics::{Background3D, HBoundDisplay, RenderingMode};
+use ensnano_interactor::graphics::{
+    Background3D, Colormap, HBondFilter, HBoundDisplay, IsolateSelection, RenderingMode,
+    StrandColoring, StrandColors,
+};
 
 /// An object that handles the communication with the GPU to draw the scene.
 pub struct View {
@@ -110,18 +126,45 @@ pub struct View {
     handle_drawers: HandlesDrawer,
     /// The rotation widget draw the widget to rotate the elements
     rotation_widget: RotationWidget,
+    /// The roll widget draws the widget to modify the roll of the selected helix
+    roll_widget: RollWidget,
     /// A possible update of the size of the drawing area, must be taken into account before
     /// drawing the next frame
     new_size: Option<PhySize>,
     /// The pipilines that draw the basis symbols
     letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
     helix_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
+    /// The pipelines that draw the labels of the measurements
+    measure_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
+    /// The labels of the nucleotide ruler
+    ruler_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
+    /// The pipelines that draw the length of the domain being built
+    building_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
+    /// The pipelines that draw the coordinates of the grid cells near the cursor
+    grid_coordinate_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
+    /// The pipelines that draw the angle of the rotation made with the rotation widget
+    rotation_angle_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
+    /// The pipelines that draw the names of the bezier planes
+    bezier_plane_name_letter_drawer: Vec<InstanceDrawer<LetterInstance>>,
+    /// The rectangle drawn during rubber band selections
+    selection_rectangle: Rectangle,
+    /// The legend of the colormap used to color the strands according to a property
+    legend: Legend,
+    /// The tooltip displaying the note of the strand under the cursor
+    tooltip: Tooltip,
+    /// The banner shown during simulations and the messages explaining why actions were blocked
+    overlay_messages: OverlayMessagesDrawer,
     device: Rc<Device>,
     /// A bind group associated to the uniform buffer containing the view and projection matrices.
     //TODO this is currently only passed to the widgets, it could be passed to the mesh pipeline as
     //well.
     viewer: UniformBindGroup,
     stereographic_viewer: UniformBindGroup,
+    /// The uniforms used to draw the direction cube, which is always seen in perspective
+    direction_cube_viewer: UniformBindGroup,
+    /// The uniforms used to draw the external objects in the fake texture, in which their
+    /// identifier must not be mixed with the color of the fog
+    fogless_viewer: UniformBindGroup,
     models: DynamicBindGroup,
     redraw_twice: bool,
     need_redraw: bool,
@@ -137,6 +180,25 @@ pub struct View {
     external_objects_drawer: Object3DDrawer,
     stereography: Stereography,
     sheets_drawer: InstanceDrawer<Sheet2D>,
+    /// The uniforms of the viewport that is not under the cursor when the view is split
+    split_viewer: UniformBindGroup,
+    /// The second viewport, if the view is split
+    split: Option<SplitViewports>,
+}
+
+/// The two viewports of a split view.
+///
+/// The camera of the viewport under the cursor is the camera of the `View`, the other one is
+/// stored here.
+struct SplitViewports {
+    /// The area of the viewport under the cursor, relative to the drawing area
+    active_area: DrawArea,
+    /// The area of the other viewport, relative to the drawing area
+    inactive_area: DrawArea,
+    inactive_camera: CameraPtr,
+    inactive_projection: ProjectionPtr,
+    /// The center of the fog of the other viewport
+    inactive_fog_center: Option<Vec3>,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -146,7 +208,28 @@ pub struct DrawOptions {
     pub show_stereographic_camera: bool,
     pub thick_helices: bool,
     pub h_bonds: HBoundDisplay,
+    /// Which hydrogen bonds are drawn when `h_bonds` is not `HBoundDisplay::No`
+    pub h_bond_display: HBondFilter,
     pub show_bezier_planes: bool,
+    pub isolate_selection: IsolateSelection,
+    /// Replace the design by its additional structure when it has one
+    pub hide_idealized_design: bool,
+    /// Clear the background with a fully transparent color
+    pub transparent_background: bool,
+    /// Draw a marker on the nucleotides that must not move during physical simulations
+    pub show_anchors: bool,
+    /// Draw the helices far from the camera with full detail instead of cylinders, e.g. for
+    /// screenshots
+    pub full_detail: bool,
+    /// Draw the phantom helices only on the grid that is under the cursor or selected
+    pub local_phantoms: bool,
+    /// Draw the indices of the nucleotides along the selected helices and the helix under the
+    /// cursor
+    pub show_nucleotide_ruler: bool,
+    /// The property according to which the non-scaffold strands are colored
+    pub strand_coloring: StrandColoring,
+    /// The colormap used when `strand_coloring` is not `StrandColoring::Stored`
+    pub strand_colormap: Colormap,
 }
 
 impl View {
@@ -185,6 +268,25 @@ impl View {
             &Uniforms::from_view_proj(camera.clone(), projection.clone(), Some(&stereography)),
             "stereographic viewer",
         );
+        let direction_cube_viewer = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            &Uniforms::from_view_proj(camera.clone(), projection.clone(), None)
+                .with_perspective(projection.clone()),
+            "direction cube viewer",
+        );
+        let fogless_viewer = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            &Uniforms::from_view_proj(camera.clone(), projection.clone(), None),
+            "fogless 3d viewer",
+        );
+        let split_viewer = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            &Uniforms::from_view_proj(camera.clone(), projection.clone(), None),
+            "split 3d viewer",
+        );
         let model_bg_desc = wgpu::BindGroupLayoutDescriptor {
             entries: MODEL_BG_ENTRY,
             label: None,
@@ -221,6 +323,101 @@ impl View {
                 )
             })
             .collect();
+        log::info!("Create measure letter drawer");
+        let measure_letter_drawer = ensnano_interactor::consts::MEASURE_CHARS
+            .iter()
+            .map(|c| {
+                let letter = Letter::new(*c, device.clone(), queue.clone());
+                InstanceDrawer::new(
+                    device.clone(),
+                    queue.clone(),
+                    &viewer.get_layout_desc(),
+                    &model_bg_desc,
+                    letter,
+                    false,
+                    format!("measure letter {c}"),
+                )
+            })
+            .collect();
+        let ruler_letter_drawer = ensnano_interactor::consts::RULER_CHARS
+            .iter()
+            .map(|c| {
+                let letter = Letter::new(*c, device.clone(), queue.clone());
+                InstanceDrawer::new(
+                    device.clone(),
+                    queue.clone(),
+                    &viewer.get_layout_desc(),
+                    &model_bg_desc,
+                    letter,
+                    false,
+                    format!("ruler letter {c}"),
+                )
+            })
+            .collect();
+        let building_letter_drawer = ensnano_interactor::consts::BUILDING_LABEL_CHARS
+            .iter()
+            .map(|c| {
+                let letter = Letter::new(*c, device.clone(), queue.clone());
+                InstanceDrawer::new(
+                    device.clone(),
+                    queue.clone(),
+                    &viewer.get_layout_desc(),
+                    &model_bg_desc,
+                    letter,
+                    false,
+                    format!("building letter {c}"),
+                )
+            })
+            .collect();
+        let grid_coordinate_letter_drawer = ensnano_interactor::consts::GRID_COORDINATE_CHARS
+            .iter()
+            .map(|c| {
+                let letter = Letter::new(*c, device.clone(), queue.clone());
+                InstanceDrawer::new(
+                    device.clone(),
+                    queue.clone(),
+                    &viewer.get_layout_desc(),
+                    &model_bg_desc,
+                    letter,
+                    false,
+                    format!("grid coordinate letter {c}"),
+                )
+            })
+            .collect();
+        let rotation_angle_letter_drawer = ensnano_interactor::consts::ROTATION_ANGLE_CHARS
+            .iter()
+            .map(|c| {
+                let letter = Letter::new(*c, device.clone(), queue.clone());
+                InstanceDrawer::new(
+                    device.clone(),
+                    queue.clone(),
+                    &viewer.get_layout_desc(),
+                    &model_bg_desc,
+                    letter,
+                    false,
+                    format!("rotation angle letter {c}"),
+                )
+            })
+            .collect();
+        let bezier_plane_name_letter_drawer = ensnano_interactor::consts::BEZIER_PLANE_NAME_CHARS
+            .iter()
+            .map(|c| {
+                let letter = Letter::new(*c, device.clone(), queue.clone());
+                InstanceDrawer::new(
+                    device.clone(),
+                    queue.clone(),
+                    &viewer.get_layout_desc(),
+                    &model_bg_desc,
+                    letter,
+                    false,
+                    format!("bezier plane name letter {c}"),
+                )
+            })
+            .collect();
+        let selection_rectangle = Rectangle::new(device.as_ref(), queue.clone());
+        let legend = Legend::new(device.clone(), queue.clone());
+        let tooltip = Tooltip::new(device.clone(), queue.clone());
+        let overlay_messages = OverlayMessagesDrawer::new(device.clone(), queue.clone());
 
         let depth_texture =
             texture::Texture::create_depth_texture(device.as_ref(), &area_size, SAMPLE_COUNT);
@@ -327,11 +524,24 @@ impl View {
             device: device.clone(),
             viewer,
             stereographic_viewer,
+            direction_cube_viewer,
+            fogless_viewer,
             models,
             handle_drawers: HandlesDrawer::new(device.clone()),
-            rotation_widget: RotationWidget::new(device),
+            rotation_widget: RotationWidget::new(device.clone()),
+            roll_widget: RollWidget::new(device),
             letter_drawer,
             helix_letter_drawer,
+            measure_letter_drawer,
+            ruler_letter_drawer,
+            building_letter_drawer,
+            grid_coordinate_letter_drawer,
+            rotation_angle_letter_drawer,
+            bezier_plane_name_letter_drawer,
+            selection_rectangle,
+            legend,
+            tooltip,
+            overlay_messages,
             redraw_twice: false,
             need_redraw: true,
             need_redraw_fake: true,
@@ -346,6 +556,8 @@ impl View {
             external_objects_drawer,
             stereography,
             sheets_drawer,
+            split_viewer,
+            split: None,
         }
     }
 
@@ -363,6 +575,95 @@ impl View {
                 &self.fog_parameters,
                 Some(&self.stereography),
             ));
+        self.direction_cube_viewer.update(
+            &Uniforms::from_view_proj(self.camera.clone(), self.projection.clone(), None)
+                .with_perspective(self.projection.clone()),
+        );
+        self.fogless_viewer.update(&Uniforms::from_view_proj(
+            self.camera.clone(),
+            self.projection.clone(),
+            None,
+        ));
+        if let Some(split) = self.split.as_ref() {
+            let fog = FogParameters {
+                alt_fog_center: split.inactive_fog_center,
+                ..self.fog_parameters.clone()
+            };
+            self.split_viewer.update(&Uniforms::from_view_proj_fog(
+                split.inactive_camera.clone(),
+                split.inactive_projection.clone(),
+                &fog,
+                None,
+            ));
+        }
+    }
+
+    /// Split the view into two viewports. The current camera is used by the viewport at
+    /// `active_area` and a copy of it is used by the viewport at `inactive_area`.
+    pub fn split_viewports(&mut self, active_area: DrawArea, inactive_area: DrawArea) {
+        let inactive_camera = Rc::new(RefCell::new(self.camera.borrow().clone()));
+        let inactive_projection = Rc::new(RefCell::new(self.projection.borrow().clone()));
+        self.split = Some(SplitViewports {
+            active_area,
+            inactive_area,
+            inactive_camera,
+            inactive_projection,
+            inactive_fog_center: self.fog_parameters.alt_fog_center,
+        });
+        self.set_viewport_areas(active_area, inactive_area);
+    }
+
+    /// Go back to a single viewport, using the camera of the viewport under the cursor.
+    pub fn merge_viewports(&mut self, area_size: PhySize) {
+        self.split = None;
+        self.projection
+            .borrow_mut()
+            .resize(area_size.width, area_size.height);
+        self.update(ViewUpdate::Camera);
+    }
+
+    pub fn set_viewport_areas(&mut self, active_area: DrawArea, inactive_area: DrawArea) {
+        if let Some(split) = self.split.as_mut() {
+            split.active_area = active_area;
+            split.inactive_area = inactive_area;
+            split
+                .inactive_projection
+                .borrow_mut()
+                .resize(inactive_area.size.width, inactive_area.size.height);
+        }
+        self.projection
+            .borrow_mut()
+            .resize(active_area.size.width, active_area.size.height);
+        self.need_redraw_fake = true;
+        self.update(ViewUpdate::Camera);
+    }
+
+    /// Exchange the cameras of the two viewports, so that the camera of the view becomes the one
+    /// of the viewport under the cursor.
+    pub fn swap_viewports(&mut self) {
+        if let Some(split) = self.split.as_mut() {
+            std::mem::swap(&mut self.camera, &mut split.inactive_camera);
+            std::mem::swap(&mut self.projection, &mut split.inactive_projection);
+            std::mem::swap(
+                &mut self.fog_parameters.alt_fog_center,
+                &mut split.inactive_fog_center,
+            );
+            std::mem::swap(&mut split.active_area, &mut split.inactive_area);
+            std::mem::swap(&mut self.viewer, &mut self.split_viewer);
+            self.need_redraw_fake = true;
+            self.update(ViewUpdate::Camera);
+        }
+    }
+
+    /// The camera and projection of the viewport that is not under the cursor, if the view is
+    /// split
+    pub fn get_inactive_camera(&self) -> Option<(CameraPtr, ProjectionPtr)> {
+        self.split.as_ref().map(|split| {
+            (
+                split.inactive_camera.clone(),
+                split.inactive_projection.clone(),
+            )
+        })
     }
 
     /// Notify the view of an update. According to the nature of this update, the view decides if
@@ -386,7 +687,7 @@ impl View {
                     .new_instances(vec![DirectionCube::new(dist)]);
             }
             ViewUpdate::Fog(fog) => {
-                let fog_center = self.fog_parameters.alt_fog_center;
+                let fog_center = fog.alt_fog_center.or(self.fog_parameters.alt_fog_center);
                 self.fog_parameters = fog;
                 self.fog_parameters.alt_fog_center = fog_center;
                 self.update_viewers();
@@ -406,6 +707,7 @@ impl View {
                     self.projection.clone(),
                 );
             }
+            ViewUpdate::RollWidget(descr) => self.roll_widget.update_decriptor(descr),
             ViewUpdate::ModelMatrices(ref matrices) => {
                 self.models.update(matrices.clone().as_slice());
             }
@@ -419,6 +721,49 @@ impl View {
                     self.helix_letter_drawer[i].new_instances(instance);
                 }
             }
+            ViewUpdate::MeasureLetter(letter) => {
+                for (i, instance) in letter.into_iter().enumerate() {
+                    self.measure_letter_drawer[i].new_instances(instance);
+                }
+            }
+            ViewUpdate::RulerLetter(letter) => {
+                for (i, instance) in letter.into_iter().enumerate() {
+                    self.ruler_letter_drawer[i].new_instances(instance);
+                }
+            }
+            ViewUpdate::BuildingLetter(letter) => {
+                for (i, instance) in letter.into_iter().enumerate() {
+                    self.building_letter_drawer[i].new_instances(instance);
+                }
+            }
+            ViewUpdate::GridCoordinateLetter(letter) => {
+                for (i, instance) in letter.into_iter().enumerate() {
+                    self.grid_coordinate_letter_drawer[i].new_instances(instance);
+                }
+            }
+            ViewUpdate::RotationAngleLetter(letter) => {
+                for (i, instance) in letter.into_iter().enumerate() {
+                    self.rotation_angle_letter_drawer[i].new_instances(instance);
+                }
+            }
+            ViewUpdate::BezierPlaneNameLetter(letter) => {
+                for (i, instance) in letter.into_iter().enumerate() {
+                    self.bezier_plane_name_letter_drawer[i].new_instances(instance);
+                }
+            }
+            ViewUpdate::SelectionRectangle(corners) => {
+                let unit_size = ensnano_utils::winit::dpi::PhysicalSize::new(1., 1.);
+                self.selection_rectangle
+                    .update_corners(corners.map(|[c1, c2]| {
+                        [
+                            Ndc::from_physical(c1, unit_size),
+                            Ndc::from_physical(c2, unit_size),
+                        ]
+                    }));
+            }
+            ViewUpdate::StrandColorsLegend(strand_colors) => self.legend.update(strand_colors),
+            ViewUpdate::Tooltip(content) => self.tooltip.update(content),
+            ViewUpdate::OverlayMessages(messages) => self.overlay_messages.update(messages),
             ViewUpdate::Grids(grid) => self.grid_manager.new_instances(grid),
             ViewUpdate::GridDiscs(instances) => self.disc_drawer.new_instances(instances),
             ViewUpdate::RawDna(mesh, instances) => {
@@ -426,15 +771,9 @@ impl View {
                     .get_mut(mesh)
                     .new_instances_raw(instances.as_ref());
                 if let Some(mesh) = mesh.to_fake() {
-                    let mut instances = instances.as_ref().clone();
-                    for i in instances.iter_mut() {
-                        if i.scale.z <= 1. {
-                            i.scale *= ensnano_interactor::consts::SELECT_SCALE_FACTOR;
-                        }
-                    }
                     self.dna_drawers
                         .get_mut(mesh)
-                        .new_instances_raw(instances.as_ref());
+                        .new_instances_raw(&fake_instances(instances.as_ref()));
                 }
                 if let Some(mesh) = mesh.to_outline() {
                     self.dna_drawers
@@ -442,6 +781,21 @@ impl View {
                         .new_instances_raw(instances.as_ref());
                 }
             }
+            ViewUpdate::RawDnaRanges(mesh, instances, ranges) => {
+                self.dna_drawers
+                    .get_mut(mesh)
+                    .update_instances_raw(instances.as_ref(), &ranges);
+                if let Some(mesh) = mesh.to_fake() {
+                    self.dna_drawers
+                        .get_mut(mesh)
+                        .update_instances_raw(&fake_instances(instances.as_ref()), &ranges);
+                }
+                if let Some(mesh) = mesh.to_outline() {
+                    self.dna_drawers
+                        .get_mut(mesh)
+                        .update_instances_raw(instances.as_ref(), &ranges);
+                }
+            }
             ViewUpdate::FogCenter(center) => {
                 self.fog_parameters.alt_fog_center = center;
                 self.update_viewers();
@@ -449,9 +803,11 @@ impl View {
             ViewUpdate::BezierSheets(sheets) => {
                 self.sheets_drawer.new_instances(sheets);
             }
-            ViewUpdate::External3DObjects(objects) => self
-                .external_objects_drawer
-                .update_objects(objects, &self.viewer.get_layout_desc()),
+            ViewUpdate::External3DObjects(objects) => {
+                self.external_objects_drawer
+                    .update_objects(objects, &self.viewer.get_layout_desc());
+                self.need_redraw_fake = true;
+            }
             ViewUpdate::UnrootedSurface(surface) => {
                 let is_update = self
                     .external_objects_drawer
@@ -501,7 +857,14 @@ impl View {
                 None
             };
         }
-        let clear_color = if fake_color || draw_options.background3d == Background3D::White {
+        let clear_color = if draw_options.transparent_background && !fake_color {
+            wgpu::Color {
+                r: 1.,
+                g: 1.,
+                b: 1.,
+                a: 0.,
+            }
+        } else if fake_color || draw_options.background3d == Background3D::White {
             // 0xFF_FF_FF_FF is the "default" color for the fake texture
             wgpu::Color {
                 r: 1.,
@@ -527,7 +890,6 @@ impl View {
         };
 
         let viewer_bind_group = viewer.get_bindgroup();
-        let viewer_bind_group_layout = viewer.get_layout();
 
         let mut png_msaa = None;
         let attachment = if !fake_color && draw_type == DrawType::Scene {
@@ -578,14 +940,30 @@ impl View {
             &self.fake_depth_texture
         };
 
-        {
+        // When the view is split, the viewport that is not under the cursor is drawn first, with
+        // its own camera.
+        let passes = match self.split.as_ref() {
+            Some(split) if draw_type == DrawType::Scene && !stereographic => vec![
+                (&self.split_viewer, Some(split.inactive_area)),
+                (viewer, Some(split.active_area)),
+            ],
+            _ => vec![(viewer, None)],
+        };
+        for (pass_idx, (viewer, viewport)) in passes.into_iter().enumerate() {
+            let viewer_bind_group = viewer.get_bindgroup();
+            let viewer_bind_group_layout = viewer.get_layout();
+            let color_load = if pass_idx == 0 {
+                wgpu::LoadOp::Clear(clear_color)
+            } else {
+                wgpu::LoadOp::Load
+            };
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[wgpu::RenderPassColorAttachment {
                     view: attachment,
                     resolve_target,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(clear_color),
+                        load: color_load,
                         store: true,
                     },
                 }],
@@ -601,21 +979,10 @@ impl View {
                     }),
                 }),
             });
-            if draw_type != DrawType::Scene {
-                render_pass.set_viewport(
-                    area.position.x as f32,
-                    area.position.y as f32,
-                    area.size.width as f32,
-                    area.size.height as f32,
-                    0.0,
-                    1.0,
-                );
-                render_pass.set_scissor_rect(
-                    area.position.x,
-                    area.position.y,
-                    area.size.width,
-                    area.size.height,
-                );
+            if let Some(viewport) = viewport.filter(|_| draw_type == DrawType::Scene) {
+                set_viewport_and_scissor(&mut render_pass, viewport);
+            } else if draw_type != DrawType::Scene {
+                set_viewport_and_scissor(&mut render_pass, area);
             }
 
             if draw_type == DrawType::Design {
@@ -626,6 +993,13 @@ impl View {
                         self.models.get_bindgroup(),
                     )
                 }
+                if !stereographic {
+                    self.external_objects_drawer.draw(
+                        &mut render_pass,
+                        self.fogless_viewer.get_bindgroup(),
+                        true,
+                    );
+                }
             } else if draw_type == DrawType::Scene {
                 log::trace!("Draw sky..");
                 if draw_options.background3d == Background3D::Sky {
@@ -705,6 +1079,20 @@ impl View {
                         self.models.get_bindgroup(),
                     )
                 }
+                for drawer in self.measure_letter_drawer.iter_mut() {
+                    drawer.draw(
+                        &mut render_pass,
+                        viewer_bind_group,
+                        self.models.get_bindgroup(),
+                    )
+                }
+                for drawer in self.ruler_letter_drawer.iter_mut() {
+                    drawer.draw(
+                        &mut render_pass,
+                        viewer_bind_group,
+                        self.models.get_bindgroup(),
+                    )
+                }
                 self.sheets_drawer.draw(
                     &mut render_pass,
                     viewer_bind_group,
@@ -712,6 +1100,37 @@ impl View {
                 )
             }
 
+            if !fake_color && !stereographic && !matches!(draw_type, DrawType::Png { .. }) {
+                for drawer in self.building_letter_drawer.iter_mut() {
+                    drawer.draw(
+                        &mut render_pass,
+                        viewer_bind_group,
+                        self.models.get_bindgroup(),
+                    )
+                }
+                for drawer in self.grid_coordinate_letter_drawer.iter_mut() {
+                    drawer.draw(
+                        &mut render_pass,
+                        viewer_bind_group,
+                        self.models.get_bindgroup(),
+                    )
+                }
+                for drawer in self.rotation_angle_letter_drawer.iter_mut() {
+                    drawer.draw(
+                        &mut render_pass,
+                        viewer_bind_group,
+                        self.models.get_bindgroup(),
+                    )
+                }
+                for drawer in self.bezier_plane_name_letter_drawer.iter_mut() {
+                    drawer.draw(
+                        &mut render_pass,
+                        viewer_bind_group,
+                        self.models.get_bindgroup(),
+                    )
+                }
+            }
+
             if draw_type.wants_widget() && !stereographic {
                 log::trace!("draw handles...");
                 self.handle_drawers.draw(
@@ -729,25 +1148,30 @@ impl View {
                     viewer_bind_group_layout,
                     fake_color,
                 );
+                self.roll_widget.draw(
+                    &mut render_pass,
+                    viewer_bind_group,
+                    viewer_bind_group_layout,
+                    fake_color,
+                );
                 log::trace!("..Done");
             }
 
             if !fake_color {
                 log::trace!("draw external objects...");
                 self.external_objects_drawer
-                    .draw(&mut render_pass, viewer_bind_group);
+                    .draw(&mut render_pass, viewer_bind_group, false);
                 log::trace!("..Done");
             }
-
-            if fake_color {
-                self.need_redraw_fake = false;
-            } else if self.redraw_twice {
-                self.redraw_twice = false;
-                self.need_redraw = true;
-            } else {
-                self.need_redraw = false;
-                self.need_redraw_fake = true;
-            }
+        }
+        if fake_color {
+            self.need_redraw_fake = false;
+        } else if self.redraw_twice {
+            self.redraw_twice = false;
+            self.need_redraw = true;
+        } else {
+            self.need_redraw = false;
+            self.need_redraw_fake = true;
         }
         if !fake_color && draw_type == DrawType::Scene {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -772,22 +1196,56 @@ impl View {
                     }),
                 }),
             });
+            // The selection rectangle and the direction cube belong to the viewport under the
+            // cursor
+            let active_area = self
+                .split
+                .as_ref()
+                .filter(|_| !stereographic)
+                .map(|split| split.active_area);
+            if let Some(active_area) = active_area {
+                set_viewport_and_scissor(&mut render_pass, active_area);
+            }
+            self.selection_rectangle.draw(&mut render_pass);
+            if active_area.is_some() {
+                render_pass.set_scissor_rect(0, 0, area.size.width, area.size.height);
+            }
+            render_pass.set_viewport(
+                area.position.x as f32,
+                area.position.y as f32,
+                area.size.width as f32,
+                area.size.height as f32,
+                0.0,
+                1.0,
+            );
+            self.legend.draw(&mut render_pass, area.size);
+            self.tooltip.draw(&mut render_pass, area.size);
+            self.overlay_messages.draw(&mut render_pass, area.size);
+            let cube_area = active_area.unwrap_or(DrawArea {
+                position: PhysicalPosition::new(0, 0),
+                size: area.size,
+            });
             render_pass.set_viewport(
-                area.size.width as f32 / 20.,
+                cube_area.position.x as f32 + cube_area.size.width as f32 / 20.,
                 0.,
-                (area.size.width as f32 / 10. * 1.5)
+                (cube_area.size.width as f32 / 10. * 1.5)
                     .max(100.)
-                    .min(area.size.width as f32),
-                (area.size.height as f32 / 10. * 1.5)
-                    .max((100. * area.size.height as f32 / area.size.width as f32) as f32)
-                    .min(area.size.height as f32),
+                    .min(cube_area.size.width as f32),
+                (cube_area.size.height as f32 / 10. * 1.5)
+                    .max(100. * cube_area.size.height as f32 / cube_area.size.width as f32)
+                    .min(cube_area.size.height as f32),
                 0.0,
                 1.0,
             );
             log::trace!("draw direction cube...");
+            let direction_cube_bind_group = if stereographic {
+                viewer_bind_group
+            } else {
+                self.direction_cube_viewer.get_bindgroup()
+            };
             self.direction_cube.draw(
                 &mut render_pass,
-                viewer_bind_group,
+                direction_cube_bind_group,
                 self.models.get_bindgroup(),
             );
             log::trace!("..Done");
@@ -864,6 +1322,10 @@ impl View {
         self.handle_drawers.end_movement()
     }
 
+    pub fn get_fog_parameters(&self) -> FogParameters {
+        self.fog_parameters.clone()
+    }
+
     pub fn get_stereography(&self) -> Stereography {
         self.stereography.clone()
     }
@@ -899,6 +1361,13 @@ impl View {
         Some(p2 - p1)
     }
 
+    /// The direction of one of the translation handles
+    pub fn get_handle_direction(&self, direction: HandleDir) -> Option<Vec3> {
+        self.handle_drawers
+            .get_handle(direction)
+            .map(|(_, dir)| dir)
+    }
+
     /// Translate the widgets when the associated objects are translated.
     pub fn translate_widgets(&mut self, translation: Vec3) {
         self.need_redraw = true;
@@ -925,8 +1394,39 @@ impl View {
             .compute_rotation(x, y, self.camera.clone(), self.projection.clone())
     }
 
+    /// The center and the normal of the circle of the rotation widget that is highlighted.
+    pub fn get_rotation_widget_axis(&self) -> Option<(Vec3, Vec3)> {
+        self.rotation_widget.get_highlighted_axis()
+    }
+
+    /// The position of the label displaying the angle of the rotation made with the rotation
+    /// widget.
+    pub fn get_rotation_label_position(&self) -> Option<Vec3> {
+        let right = self.camera.borrow().right_vec();
+        self.rotation_widget.get_label_position(right)
+    }
+
+    /// Initialise the modification of the roll of the helix on which the roll widget is.
+    pub fn init_roll(&mut self, x: f32, y: f32, initial_roll: f32) {
+        self.need_redraw = true;
+        self.roll_widget.init_roll(x, y, initial_roll)
+    }
+
+    /// The identifier of the helix on which the roll widget is.
+    pub fn get_roll_widget_helix(&self) -> Option<usize> {
+        self.roll_widget.get_helix_id()
+    }
+
+    /// Compute the roll that must be given to the helix on which the roll widget is. If `step` is
+    /// some value, the variation of the roll is rounded to a multiple of `step`.
+    pub fn compute_roll(&self, x: f32, y: f32, step: Option<f32>) -> Option<(usize, f32)> {
+        self.roll_widget
+            .compute_roll(x, y, step, self.camera.clone(), self.projection.clone())
+    }
+
     pub fn set_widget_candidate(&mut self, selected_id: Option<u32>) {
         self.redraw_twice |= self.rotation_widget.set_selected(selected_id);
+        self.redraw_twice |= self.roll_widget.set_selected(selected_id);
         self.redraw_twice |= self.handle_drawers.set_selected(selected_id);
     }
 
@@ -1019,6 +1519,16 @@ impl View {
         self.grid_manager.set_selected_grid(grids)
     }
 
+    pub fn set_selected_external_objects(&mut self, objects: Vec<External3DObjectId>) {
+        self.external_objects_drawer.set_selected_objects(objects);
+        self.need_redraw = true;
+    }
+
+    pub fn set_candidate_external_objects(&mut self, objects: Vec<External3DObjectId>) {
+        self.external_objects_drawer.set_candidate_objects(objects);
+        self.need_redraw = true;
+    }
+
     pub fn get_group_pivot(&self) -> Option<GroupPivot> {
         self.handle_drawers
             .get_pivot_position()
@@ -1030,6 +1540,35 @@ impl View {
     }
 }
 
+/// Return the instances drawn in the picking textures, that are larger than the visible ones so
+/// that thin objects are easier to pick.
+fn fake_instances(instances: &[RawDnaInstance]) -> Vec<RawDnaInstance> {
+    let mut instances = instances.to_vec();
+    for i in instances.iter_mut() {
+        if i.scale.z <= 1. {
+            i.scale *= ensnano_interactor::consts::SELECT_SCALE_FACTOR;
+        }
+    }
+    instances
+}
+
+fn set_viewport_and_scissor(render_pass: &mut wgpu::RenderPass, area: DrawArea) {
+    render_pass.set_viewport(
+        area.position.x as f32,
+        area.position.y as f32,
+        area.size.width as f32,
+        area.size.height as f32,
+        0.0,
+        1.0,
+    );
+    render_pass.set_scissor_rect(
+        area.position.x,
+        area.position.y,
+        area.size.width,
+        area.size.height,
+    );
+}
+
 /// An notification to be given to the view
 #[derive(Debug)]
 pub enum ViewUpdate {
@@ -1042,11 +1581,38 @@ pub enum ViewUpdate {
     /// The set of phantom instances has been modified
     Handles(Option<HandlesDescriptor>),
     RotationWidget(Option<RotationWidgetDescriptor>),
+    /// The widget used to modify the roll of the selected helix
+    RollWidget(Option<RollWidgetDescriptor>),
     Letter(Vec<Vec<LetterInstance>>),
     GridLetter(Vec<Vec<LetterInstance>>),
+    /// The letters of the labels of the measurements
+    MeasureLetter(Vec<Vec<LetterInstance>>),
+    /// The letters of the labels of the nucleotide ruler
+    RulerLetter(Vec<Vec<LetterInstance>>),
+    /// The letters of the length of the domain being built
+    BuildingLetter(Vec<Vec<LetterInstance>>),
+    /// The letters of the coordinates of the grid cells near the cursor
+    GridCoordinateLetter(Vec<Vec<LetterInstance>>),
+    /// The letters of the angle of the rotation made with the rotation widget
+    RotationAngleLetter(Vec<Vec<LetterInstance>>),
+    /// The letters of the names of the bezier planes
+    BezierPlaneNameLetter(Vec<Vec<LetterInstance>>),
+    /// The corners, in normalized coordinates, of the rectangle of a rubber band selection
+    SelectionRectangle(Option<[PhysicalPosition<f64>; 2]>),
+    /// The colors of the strands whose legend must be drawn, if they are colored according to a
+    /// property
+    StrandColorsLegend(Option<StrandColors>),
+    /// The text of the tooltip and the position of the cursor in the drawing area, or `None` if
+    /// no tooltip must be drawn
+    Tooltip(Option<(String, PhysicalPosition<f64>)>),
+    /// The messages drawn on top of the view, from top to bottom
+    OverlayMessages(Vec<OverlayMessage>),
     Grids(BTreeMap<GridId, GridInstance>),
     GridDiscs(Vec<GridDisc>),
     RawDna(Mesh, Rc<Vec<RawDnaInstance>>),
+    /// The instances of a mesh were only modified in the given ranges. If their number changed,
+    /// this is equivalent to `RawDna`.
+    RawDnaRanges(Mesh, Rc<Vec<RawDnaInstance>>, Vec<Range<usize>>),
     Fog(FogParameters),
     FogCenter(Option<Vec3>),
     BezierSheets(Vec<Sheet2D>),
@@ -1077,6 +1643,9 @@ pub enum Mesh {
     PivotSphere,
     XoverSphere,
     XoverTube,
+    MeasureTube,
+    /// The ticks of the nucleotide ruler
+    RulerTick,
     Prime3Cone,
     Prime3ConeOutline,
     BezierControll,
@@ -1087,6 +1656,28 @@ pub enum Mesh {
     EllipsoidOutline,
     HBond,
     HBondOutline,
+    /// The nucleotides of the reference design
+    ReferenceSphere,
+    /// The bonds of the reference design
+    ReferenceTube,
+    /// The highlighted nucleotides of the elements that changed since the design was last saved
+    DesignChangesSphere,
+    /// The highlighted bonds of the elements that changed since the design was last saved
+    DesignChangesTube,
+    /// The preview of the nucleotides that would pair with the domain being built, and the
+    /// highlighted existing nucleotides that pair with it
+    BuildingComplementSphere,
+    /// The preview of the bonds of the strand complementary to the domain being built
+    BuildingComplementTube,
+    /// The markers of the nucleotides that must not move during physical simulations
+    AnchorCone,
+    /// The heads of the markers of the nucleotides that carry a chemical modification
+    ModificationSphere,
+    /// The stems of the markers of the nucleotides that carry a chemical modification
+    ModificationTube,
+    /// The handles on the ends of the selected strands
+    StrandEndHandle,
+    FakeStrandEndHandle,
 }
 
 impl Mesh {
@@ -1097,6 +1688,7 @@ impl Mesh {
             Self::PhantomSphere => Some(Self::FakePhantomSphere),
             Self::PhantomTube => Some(Self::FakePhantomTube),
             Self::BezierControll => Some(Self::FakeBezierControl),
+            Self::StrandEndHandle => Some(Self::FakeStrandEndHandle),
             _ => None,
         }
     }
@@ -1135,6 +1727,19 @@ struct DnaDrawers {
     pivot_sphere: InstanceDrawer<SphereInstance>,
     xover_sphere: InstanceDrawer<SphereInstance>,
     xover_tube: InstanceDrawer<TubeInstance>,
+    measure_tube: InstanceDrawer<TubeInstance>,
+    ruler_ticks: InstanceDrawer<TubeInstance>,
+    reference_sphere: InstanceDrawer<SphereInstance>,
+    reference_tube: InstanceDrawer<TubeInstance>,
+    design_changes_sphere: InstanceDrawer<SphereInstance>,
+    design_changes_tube: InstanceDrawer<TubeInstance>,
+    building_complement_sphere: InstanceDrawer<SphereInstance>,
+    building_complement_tube: InstanceDrawer<TubeInstance>,
+    anchor_cones: InstanceDrawer<dna_obj::ConeInstance>,
+    modification_spheres: InstanceDrawer<SphereInstance>,
+    modification_tubes: InstanceDrawer<TubeInstance>,
+    strand_end_handles: InstanceDrawer<dna_obj::ConeInstance>,
+    fake_strand_end_handles: InstanceDrawer<dna_obj::ConeInstance>,
     prime3_cones: InstanceDrawer<dna_obj::ConeInstance>,
     outline_prime3_cones: InstanceDrawer<dna_obj::ConeInstance>,
     bezier_controll_points: InstanceDrawer<dna_obj::SphereInstance>,
@@ -1171,6 +1776,19 @@ impl DnaDrawers {
             Mesh::PivotSphere => &mut self.pivot_sphere,
             Mesh::XoverSphere => &mut self.xover_sphere,
             Mesh::XoverTube => &mut self.xover_tube,
+            Mesh::MeasureTube => &mut self.measure_tube,
+            Mesh::RulerTick => &mut self.ruler_ticks,
+            Mesh::ReferenceSphere => &mut self.reference_sphere,
+            Mesh::ReferenceTube => &mut self.reference_tube,
+            Mesh::DesignChangesSphere => &mut self.design_changes_sphere,
+            Mesh::DesignChangesTube => &mut self.design_changes_tube,
+            Mesh::BuildingComplementSphere => &mut self.building_complement_sphere,
+            Mesh::BuildingComplementTube => &mut self.building_complement_tube,
+            Mesh::AnchorCone => &mut self.anchor_cones,
+            Mesh::ModificationSphere => &mut self.modification_spheres,
+            Mesh::ModificationTube => &mut self.modification_tubes,
+            Mesh::StrandEndHandle => &mut self.strand_end_handles,
+            Mesh::FakeStrandEndHandle => &mut self.fake_strand_end_handles,
             Mesh::Prime3Cone => &mut self.prime3_cones,
             Mesh::Prime3ConeOutline => &mut self.outline_prime3_cones,
             Mesh::BezierControll => &mut self.bezier_controll_points,
@@ -1205,8 +1823,20 @@ impl DnaDrawers {
             &mut self.pivot_sphere,
             &mut self.xover_sphere,
             &mut self.xover_tube,
+            &mut self.measure_tube,
+            &mut self.ruler_ticks,
             &mut self.bezier_squelton,
             &mut self.bezier_controll_points,
+            &mut self.reference_sphere,
+            &mut self.reference_tube,
+            &mut self.design_changes_sphere,
+            &mut self.design_changes_tube,
+            &mut self.building_complement_sphere,
+            &mut self.building_complement_tube,
+            &mut self.anchor_cones,
+            &mut self.modification_spheres,
+            &mut self.modification_tubes,
+            &mut self.strand_end_handles,
         ];
         let mut last_solid_item = 2;
         match draw_options.h_bonds {
@@ -1243,7 +1873,11 @@ impl DnaDrawers {
     }
 
     pub fn fakes(&mut self) -> Vec<&mut dyn RawDrawer<RawInstance = RawDnaInstance>> {
-        vec![&mut self.fake_sphere, &mut self.fake_tube]
+        vec![
+            &mut self.fake_sphere,
+            &mut self.fake_tube,
+            &mut self.fake_strand_end_handles,
+        ]
     }
 
     pub fn phantoms(&mut self) -> Vec<&mut dyn RawDrawer<RawInstance = RawDnaInstance>> {
@@ -1254,6 +1888,7 @@ impl DnaDrawers {
         vec![
             &mut self.fake_sphere,
             &mut self.fake_tube,
+            &mut self.fake_strand_end_handles,
             &mut self.fake_phantom_sphere,
             &mut self.fake_phantom_tube,
         ]
@@ -1405,6 +2040,123 @@ impl DnaDrawers {
                 false,
                 "xover tube",
             ),
+            measure_tube: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "measure tube",
+            ),
+            ruler_ticks: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "ruler ticks",
+            ),
+            reference_sphere: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "reference sphere",
+            ),
+            reference_tube: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "reference tube",
+            ),
+            design_changes_sphere: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "design changes sphere",
+            ),
+            design_changes_tube: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "design changes tube",
+            ),
+            building_complement_sphere: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "building complement sphere",
+            ),
+            building_complement_tube: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "building complement tube",
+            ),
+            anchor_cones: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "anchor cones",
+            ),
+            modification_spheres: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "modification spheres",
+            ),
+            modification_tubes: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "modification tubes",
+            ),
+            strand_end_handles: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                false,
+                "strand end handles",
+            ),
+            fake_strand_end_handles: InstanceDrawer::new(
+                device.clone(),
+                queue.clone(),
+                viewer_desc,
+                model_desc,
+                (),
+                true,
+                "fake strand end handles",
+            ),
             pasted_sphere: InstanceDrawer::new(
                 device.clone(),
                 queue.clone(),
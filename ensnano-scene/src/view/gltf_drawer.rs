@@ -19,11 +19,13 @@ use super::wgpu;
 use ensnano_design::{External3DObject, External3DObjectId, PointOnSurface};
 use ensnano_interactor::consts;
 use ensnano_interactor::UnrootedRevolutionSurfaceDescriptor;
+use ensnano_utils::instance::Instance;
 use ensnano_utils::{create_buffer_with_data, obj_loader::*, texture::Texture, TEXTURE_FORMAT};
+use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::path::PathBuf;
 use std::rc::Rc;
-use std::{collections::BTreeMap, path::Path};
+use ultraviolet::{Vec3, Vec4};
 use wgpu::{BindGroupLayoutDescriptor, Device};
 
 struct DesiredRevolutionShapeDrawer {
@@ -32,19 +34,21 @@ struct DesiredRevolutionShapeDrawer {
 }
 
 pub struct Object3DDrawer {
-    gltf_drawers: BTreeMap<External3DObjectId, GltfDrawer>,
-    stl_drawers: BTreeMap<External3DObjectId, StlDrawer>,
+    objects: BTreeMap<External3DObjectId, DrawnObject>,
     device: Rc<Device>,
     desired_revolution_shape_drawer: Option<DesiredRevolutionShapeDrawer>,
+    selected: Vec<External3DObjectId>,
+    candidates: Vec<External3DObjectId>,
 }
 
 impl Object3DDrawer {
     pub fn new(device: Rc<Device>) -> Self {
         Self {
-            gltf_drawers: Default::default(),
-            stl_drawers: Default::default(),
+            objects: Default::default(),
             device,
             desired_revolution_shape_drawer: None,
+            selected: vec![],
+            candidates: vec![],
         }
     }
 }
@@ -56,19 +60,27 @@ pub struct ExternalObjects {
 }
 
 impl Object3DDrawer {
+    /// Draw the visible objects. When `fake` is true, the objects are drawn with their identifier
+    /// as color, for the element selector.
     pub fn draw<'a>(
         &'a mut self,
         render_pass: &mut wgpu::RenderPass<'a>,
         viewer_bind_group: &'a wgpu::BindGroup,
+        fake: bool,
     ) {
-        for d in self.gltf_drawers.values_mut() {
-            d.draw(render_pass, viewer_bind_group)
-        }
-        for d in self.stl_drawers.values_mut() {
-            d.draw(render_pass, viewer_bind_group)
+        for o in self.objects.values_mut() {
+            if o.object.is_visible() {
+                if fake {
+                    o.fake.draw(render_pass, viewer_bind_group)
+                } else {
+                    o.real.draw(render_pass, viewer_bind_group)
+                }
+            }
         }
-        if let Some(ref mut d) = self.desired_revolution_shape_drawer {
-            d.drawer.draw(render_pass, viewer_bind_group)
+        if !fake {
+            if let Some(ref mut d) = self.desired_revolution_shape_drawer {
+                d.drawer.draw(render_pass, viewer_bind_group)
+            }
         }
     }
 
@@ -77,35 +89,74 @@ impl Object3DDrawer {
         objects: ExternalObjects,
         bg_desc: &BindGroupLayoutDescriptor,
     ) {
+        self.objects
+            .retain(|id, _| objects.objects.iter().any(|(obj_id, _)| obj_id == id));
         for (obj_id, object) in objects.objects.into_iter() {
-            if !self.stl_drawers.contains_key(&obj_id) && !self.gltf_drawers.contains_key(&obj_id) {
-                self.add_object(obj_id, object, &objects.path_base, bg_desc);
+            let path = object.get_path_to_source_file(&objects.path_base);
+            let highlight = self.highlight(obj_id);
+            if let Some(drawn) = self
+                .objects
+                .get_mut(&obj_id)
+                .filter(|drawn| drawn.source_path == path)
+            {
+                if drawn.object != object {
+                    drawn.object = object;
+                    drawn.update_vertices(self.device.as_ref(), obj_id, highlight);
+                }
+            } else if let Some(drawn) = DrawnObject::new(
+                self.device.as_ref(),
+                obj_id,
+                object,
+                path,
+                bg_desc,
+                highlight,
+            ) {
+                self.objects.insert(obj_id, drawn);
+            } else {
+                self.objects.remove(&obj_id);
             }
         }
     }
 
-    #[allow(dead_code)]
-    fn update_object(&mut self, _id: External3DObjectId, _object: External3DObject) {
-        todo!("update object's attributes")
+    pub fn set_selected_objects(&mut self, selected: Vec<External3DObjectId>) {
+        if self.selected != selected {
+            let old = std::mem::replace(&mut self.selected, selected);
+            self.update_highlight(old);
+        }
     }
 
-    fn add_object(
-        &mut self,
-        id: External3DObjectId,
-        object: External3DObject,
-        base_path: &PathBuf,
-        bg_desc: &BindGroupLayoutDescriptor,
-    ) {
-        let path = object.get_path_to_source_file(base_path);
-        println!("{:?}", path);
-        if path.extension() == Some(OsStr::new("stl")) {
-            let mut drawer = StlDrawer::new(self.device.as_ref(), bg_desc);
-            drawer.add_stl(self.device.as_ref(), path);
-            self.stl_drawers.insert(id, drawer);
-        } else if path.extension() == Some(OsStr::new("gltf")) {
-            let mut drawer = GltfDrawer::new(self.device.as_ref(), bg_desc);
-            drawer.add_gltf(self.device.as_ref(), path);
-            self.gltf_drawers.insert(id, drawer);
+    pub fn set_candidate_objects(&mut self, candidates: Vec<External3DObjectId>) {
+        if self.candidates != candidates {
+            let old = std::mem::replace(&mut self.candidates, candidates);
+            self.update_highlight(old);
+        }
+    }
+
+    /// Recompute the vertices of the objects that were highlighted before the update or that are
+    /// highlighted after it.
+    fn update_highlight(&mut self, previously_highlighted: Vec<External3DObjectId>) {
+        let ids: Vec<_> = previously_highlighted
+            .into_iter()
+            .chain(self.selected.iter().cloned())
+            .chain(self.candidates.iter().cloned())
+            .collect();
+        for id in ids {
+            let highlight = self.highlight(id);
+            if let Some(drawn) = self.objects.get_mut(&id) {
+                if drawn.highlight != highlight {
+                    drawn.update_vertices(self.device.as_ref(), id, highlight);
+                }
+            }
+        }
+    }
+
+    fn highlight(&self, id: External3DObjectId) -> Option<u32> {
+        if self.selected.contains(&id) {
+            Some(consts::SELECTED_COLOR)
+        } else if self.candidates.contains(&id) {
+            Some(consts::CANDIDATE_COLOR)
+        } else {
+            None
         }
     }
 
@@ -124,7 +175,7 @@ impl Object3DDrawer {
             false
         } else {
             let new_drawer = shape.map(|shape| {
-                let mut drawer = GltfDrawer::new(device, view_bg_layout_desc);
+                let mut drawer = GltfDrawer::new(device, view_bg_layout_desc, false);
                 let meshes = shape.meshes();
                 drawer.set_meshes(device, meshes);
                 DesiredRevolutionShapeDrawer { shape, drawer }
@@ -135,12 +186,144 @@ impl Object3DDrawer {
     }
 
     pub fn clear(&mut self) {
-        self.gltf_drawers = Default::default();
-        self.stl_drawers = Default::default();
+        self.objects = Default::default();
+        self.selected.clear();
+        self.candidates.clear();
         self.desired_revolution_shape_drawer = None;
     }
 }
 
+/// The meshes read from the source file of an external object, in the coordinates of that file.
+enum SourceMeshes {
+    Gltf(Vec<GltfMesh>),
+    Stl(Vec<ModelVertex>),
+}
+
+enum MeshDrawer {
+    Gltf(GltfDrawer),
+    Stl(StlDrawer),
+}
+
+impl MeshDrawer {
+    fn draw<'a>(
+        &'a mut self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        viewer_bind_group: &'a wgpu::BindGroup,
+    ) {
+        match self {
+            Self::Gltf(d) => d.draw(render_pass, viewer_bind_group),
+            Self::Stl(d) => d.draw(render_pass, viewer_bind_group),
+        }
+    }
+}
+
+/// An external object, with the drawers of its real and fake representations
+struct DrawnObject {
+    object: External3DObject,
+    source_path: PathBuf,
+    source: SourceMeshes,
+    real: MeshDrawer,
+    fake: MeshDrawer,
+    highlight: Option<u32>,
+}
+
+impl DrawnObject {
+    fn new(
+        device: &Device,
+        id: External3DObjectId,
+        object: External3DObject,
+        source_path: PathBuf,
+        bg_desc: &BindGroupLayoutDescriptor,
+        highlight: Option<u32>,
+    ) -> Option<Self> {
+        let (source, real, fake) = if source_path.extension() == Some(OsStr::new("stl")) {
+            let vertices = load_stl(&source_path)
+                .map_err(|err| log::error!("Could not read stl file: {:?}", err))
+                .ok()?
+                .vertices;
+            (
+                SourceMeshes::Stl(vertices),
+                MeshDrawer::Stl(StlDrawer::new(device, bg_desc, false)),
+                MeshDrawer::Stl(StlDrawer::new(device, bg_desc, true)),
+            )
+        } else if source_path.extension() == Some(OsStr::new("gltf")) {
+            let meshes = load_gltf(&source_path)
+                .map_err(|err| log::error!("Could not read gltf file: {:?}", err))
+                .ok()?
+                .meshes;
+            (
+                SourceMeshes::Gltf(meshes),
+                MeshDrawer::Gltf(GltfDrawer::new(device, bg_desc, false)),
+                MeshDrawer::Gltf(GltfDrawer::new(device, bg_desc, true)),
+            )
+        } else {
+            log::error!("Unsupported 3D object file {:?}", source_path);
+            return None;
+        };
+        let mut ret = Self {
+            object,
+            source_path,
+            source,
+            real,
+            fake,
+            highlight,
+        };
+        ret.update_vertices(device, id, highlight);
+        Some(ret)
+    }
+
+    /// Place the vertices of the source file in the world coordinates and send them to the
+    /// drawers.
+    fn update_vertices(&mut self, device: &Device, id: External3DObjectId, highlight: Option<u32>) {
+        self.highlight = highlight;
+        let model_matrix = self.object.model_matrix();
+        let orientation = self.object.orientation();
+        let highlight = highlight.map(Instance::color_from_u32);
+        let real_vertex = |v: &ModelVertex| {
+            let color = Vec4::from(v.color);
+            let color = if let Some(highlight) = highlight {
+                let mixed = (color + highlight) / 2.;
+                Vec4::new(mixed.x, mixed.y, mixed.z, color.w)
+            } else {
+                color
+            };
+            ModelVertex {
+                position: model_matrix.transform_point3(Vec3::from(v.position)).into(),
+                normal: (orientation * Vec3::from(v.normal)).into(),
+                color: color.into(),
+            }
+        };
+        let fake_color: [f32; 4] =
+            Instance::color_from_au32(crate::element_selector::external_3d_object_id(id)).into();
+        let fake_vertex = |v: &ModelVertex| ModelVertex {
+            color: fake_color,
+            ..real_vertex(v)
+        };
+        match (&self.source, &mut self.real, &mut self.fake) {
+            (SourceMeshes::Gltf(meshes), MeshDrawer::Gltf(real), MeshDrawer::Gltf(fake)) => {
+                let transformed = |f: &dyn Fn(&ModelVertex) -> ModelVertex| {
+                    meshes
+                        .iter()
+                        .map(|m| GltfMesh {
+                            vertices: m.vertices.iter().map(f).collect(),
+                            indices: m.indices.clone(),
+                        })
+                        .collect()
+                };
+                real.set_meshes(device, transformed(&real_vertex));
+                fake.set_meshes(device, transformed(&fake_vertex));
+            }
+            (SourceMeshes::Stl(vertices), MeshDrawer::Stl(real), MeshDrawer::Stl(fake)) => {
+                let real_vertices: Vec<_> = vertices.iter().map(real_vertex).collect();
+                let fake_vertices: Vec<_> = vertices.iter().map(fake_vertex).collect();
+                real.set_vertices(device, &real_vertices);
+                fake.set_vertices(device, &fake_vertices);
+            }
+            _ => unreachable!("The drawers are created from the source meshes"),
+        }
+    }
+}
+
 trait MeshGenerator {
     fn meshes(&self) -> Vec<GltfMesh>;
 }
@@ -215,10 +398,11 @@ impl GltfDrawer {
     pub fn new(
         device: &wgpu::Device,
         view_bg_layout_desc: &wgpu::BindGroupLayoutDescriptor,
+        fake: bool,
     ) -> Self {
         let primitive_topology = wgpu::PrimitiveTopology::TriangleStrip;
         let render_pipeline =
-            build_render_pipeline(device, view_bg_layout_desc, primitive_topology);
+            build_render_pipeline(device, view_bg_layout_desc, primitive_topology, fake);
 
         Self {
             render_pipeline,
@@ -242,17 +426,6 @@ impl GltfDrawer {
         }
     }
 
-    pub fn add_gltf<P: AsRef<Path>>(&mut self, device: &wgpu::Device, path: P) {
-        match load_gltf(path) {
-            Ok(file) => {
-                self.set_meshes(device, file.meshes);
-            }
-            Err(err) => {
-                log::error!("Could not read gltf file: {:?}", err);
-            }
-        }
-    }
-
     pub fn set_meshes(&mut self, device: &wgpu::Device, meshes: Vec<GltfMesh>) {
         self.nb_idx.clear();
         self.vbos.clear();
@@ -285,10 +458,11 @@ impl StlDrawer {
     pub fn new(
         device: &wgpu::Device,
         view_bg_layout_desc: &wgpu::BindGroupLayoutDescriptor,
+        fake: bool,
     ) -> Self {
         let primitive_topology = wgpu::PrimitiveTopology::TriangleList;
         let render_pipeline =
-            build_render_pipeline(device, view_bg_layout_desc, primitive_topology);
+            build_render_pipeline(device, view_bg_layout_desc, primitive_topology, fake);
 
         Self {
             render_pipeline,
@@ -310,21 +484,14 @@ impl StlDrawer {
         }
     }
 
-    pub fn add_stl<P: AsRef<Path>>(&mut self, device: &wgpu::Device, path: P) {
-        match load_stl(path) {
-            Ok(mesh) => {
-                self.nb_idx.push(mesh.vertices.len() as u32);
-                self.vbos.push(create_buffer_with_data(
-                    device,
-                    bytemuck::cast_slice(mesh.vertices.as_slice()),
-                    wgpu::BufferUsages::VERTEX,
-                    "std vertex",
-                ));
-            }
-            Err(err) => {
-                log::error!("Could not read stl file: {:?}", err);
-            }
-        }
+    pub fn set_vertices(&mut self, device: &wgpu::Device, vertices: &[ModelVertex]) {
+        self.nb_idx = vec![vertices.len() as u32];
+        self.vbos = vec![create_buffer_with_data(
+            device,
+            bytemuck::cast_slice(vertices),
+            wgpu::BufferUsages::VERTEX,
+            "std vertex",
+        )];
     }
 }
 
@@ -332,14 +499,26 @@ fn build_render_pipeline(
     device: &wgpu::Device,
     view_bg_layout_desc: &wgpu::BindGroupLayoutDescriptor,
     primitive_topology: wgpu::PrimitiveTopology,
+    fake: bool,
 ) -> wgpu::RenderPipeline {
     let viewer_bg_layout = device.create_bind_group_layout(view_bg_layout_desc);
 
     let vertex_module = device.create_shader_module(&wgpu::include_spirv!("gltf_obj.vert.spv"));
     let fragment_module = device.create_shader_module(&wgpu::include_spirv!("gltf_obj.frag.spv"));
-    let format = TEXTURE_FORMAT;
-    let blend_state = wgpu::BlendState::ALPHA_BLENDING;
-    let sample_count = consts::SAMPLE_COUNT;
+    // texture used for object identification must be in linear format and is not blended
+    let (format, blend_state, sample_count) = if fake {
+        (
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::BlendState::REPLACE,
+            1,
+        )
+    } else {
+        (
+            TEXTURE_FORMAT,
+            wgpu::BlendState::ALPHA_BLENDING,
+            consts::SAMPLE_COUNT,
+        )
+    };
 
     let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Gltf Drawer"),
@@ -393,7 +572,7 @@ fn build_render_pipeline(
         multisample: wgpu::MultisampleState {
             count: sample_count,
             mask: !0,
-            alpha_to_coverage_enabled: true,
+            alpha_to_coverage_enabled: !fake,
         },
         label: Some("Gltf drawer pipeline"),
         multiview: None,
@@ -54,7 +54,7 @@ impl HandlesDescriptor {
     pub fn make_handles(&self, camera: CameraPtr, projection: ProjectionPtr) -> [Handle; 3] {
         let dist = (camera.borrow().position - self.origin).mag();
         let (right, up, dir) = self.make_axis();
-        let length = self.size * dist * (projection.borrow().get_fovy() / 2.).tan();
+        let length = self.size * projection.borrow().half_height_at(dist);
         let colors = match self.colors {
             HandleColors::Cym => ensnano_interactor::consts::CYM_HANDLE_COLORS,
             HandleColors::Rgb => ensnano_interactor::consts::RGB_HANDLE_COLORS,
@@ -20,10 +20,10 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! [InstanceDrawer](InstanceDrawer).
 
 use ensnano_interactor::consts::*;
-use ensnano_utils::bindgroup_manager::DynamicBindGroup;
-use ensnano_utils::create_buffer_with_data;
+use ensnano_utils::bindgroup_manager::{DynamicBindGroup, DynamicBuffer};
 use ensnano_utils::texture::Texture;
 use ensnano_utils::wgpu;
+use std::ops::Range;
 use std::rc::Rc;
 use wgpu::{
     BindGroupLayoutDescriptor, Device, PrimitiveTopology, Queue, RenderPass, RenderPipeline,
@@ -200,9 +200,9 @@ pub struct InstanceDrawer<D: Instanciable + ?Sized> {
     /// The pipeline that will render the mesh
     pipeline: RenderPipeline,
     /// The vertex buffer used to draw the mesh
-    vertex_buffer: wgpu::Buffer,
+    vertex_buffer: DynamicBuffer,
     /// The index buffer used to draw the mesh
-    index_buffer: wgpu::Buffer,
+    index_buffer: DynamicBuffer,
     /// The bind group containing the instances data
     instances: DynamicBindGroup,
     /// The bind group containing the additional ressources need to draw the mesh
@@ -212,7 +212,6 @@ pub struct InstanceDrawer<D: Instanciable + ?Sized> {
     /// The number of vertex indices
     nb_indices: u32,
     ressource: D::Ressource,
-    device: Rc<Device>,
     label: String,
 }
 
@@ -293,18 +292,20 @@ impl<D: Instanciable> InstanceDrawer<D> {
         outliner: bool,
         label: S,
     ) -> Self {
-        let index_buffer = create_buffer_with_data(
-            device.as_ref(),
-            bytemuck::cast_slice(D::indices().as_slice()),
+        let mut index_buffer = DynamicBuffer::new(
+            device.clone(),
+            queue.clone(),
             wgpu::BufferUsages::INDEX,
             format!("{} index buffer", label.as_ref()).as_str(),
         );
-        let vertex_buffer = create_buffer_with_data(
-            device.as_ref(),
-            bytemuck::cast_slice(D::raw_vertices().as_slice()),
+        index_buffer.update(D::indices().as_slice());
+        let mut vertex_buffer = DynamicBuffer::new(
+            device.clone(),
+            queue.clone(),
             wgpu::BufferUsages::VERTEX,
             format!("{} vertex buffer", label.as_ref()).as_str(),
         );
+        vertex_buffer.update(D::raw_vertices().as_slice());
 
         let vertex_module = if fake {
             D::fake_vertex_module(&device).unwrap_or_else(|| D::vertex_module(&device))
@@ -376,7 +377,6 @@ impl<D: Instanciable> InstanceDrawer<D> {
             nb_indices: D::indices().len() as u32,
             additional_bind_group,
             ressource,
-            device,
             label: label_string,
         }
     }
@@ -388,20 +388,10 @@ impl<D: Instanciable> InstanceDrawer<D> {
         self.nb_instances = instances.len() as u32;
         if let Some(indices) = instances.get(0).and_then(D::custom_indices) {
             self.nb_indices = indices.len() as u32;
-            self.index_buffer = create_buffer_with_data(
-                self.device.as_ref(),
-                bytemuck::cast_slice(indices.as_slice()),
-                wgpu::BufferUsages::INDEX,
-                format!("{} index buffer", self.label).as_str(),
-            );
+            self.index_buffer.update(indices.as_slice());
         }
         if let Some(vertices) = instances.get(0).and_then(D::custom_raw_vertices) {
-            self.vertex_buffer = create_buffer_with_data(
-                self.device.as_ref(),
-                bytemuck::cast_slice(vertices.as_slice()),
-                wgpu::BufferUsages::VERTEX,
-                format!("{} vertex buffer", self.label).as_str(),
-            );
+            self.vertex_buffer.update(vertices.as_slice());
         }
     }
 
@@ -558,6 +548,16 @@ pub trait RawDrawer {
     );
 
     fn new_instances_raw(&mut self, instances_raw: &Vec<Self::RawInstance>);
+
+    /// Write the instances of `instances_raw` that are in `ranges` to the instance buffer.
+    ///
+    /// The other instances must be the same as the ones given at the previous update. If the
+    /// number of instances changed, all the instances are written.
+    fn update_instances_raw(
+        &mut self,
+        instances_raw: &[Self::RawInstance],
+        ranges: &[Range<usize>],
+    );
 }
 
 impl<D: Instanciable> RawDrawer for InstanceDrawer<D> {
@@ -568,6 +568,21 @@ impl<D: Instanciable> RawDrawer for InstanceDrawer<D> {
         self.instances.update(instances_raw.as_slice());
     }
 
+    fn update_instances_raw(&mut self, instances_raw: &[D::RawInstance], ranges: &[Range<usize>]) {
+        if self.nb_instances as usize != instances_raw.len() {
+            self.nb_instances = instances_raw.len() as u32;
+            self.instances.update(instances_raw);
+            return;
+        }
+        let instance_size = std::mem::size_of::<D::RawInstance>();
+        for range in ranges.iter().filter(|r| !r.is_empty()) {
+            self.instances.update_offset(
+                range.start * instance_size,
+                bytemuck::cast_slice(&instances_raw[range.clone()]),
+            );
+        }
+    }
+
     fn draw<'a>(
         &'a mut self,
         render_pass: &mut RenderPass<'a>,
@@ -580,13 +595,13 @@ impl<D: Instanciable> RawDrawer for InstanceDrawer<D> {
             let vbo = if let Some(ref vbo) = self.ressource.vertex_buffer() {
                 vbo.slice(..)
             } else {
-                self.vertex_buffer.slice(..)
+                self.vertex_buffer.get_slice()
             };
             render_pass.set_vertex_buffer(0, vbo);
             let ibo = if let Some(ref ibo) = self.ressource.index_buffer() {
                 ibo.slice(..)
             } else {
-                self.index_buffer.slice(..)
+                self.index_buffer.get_slice()
             };
             render_pass.set_index_buffer(ibo, wgpu::IndexFormat::Uint16);
             render_pass.set_bind_group(0, viewer_bind_group, &[]);
@@ -0,0 +1,215 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Draws the legend of the colormap used to color the strands according to a property, in the
+//! bottom right corner of the 3D view.
+//!
+//! The legend is made of a vertical gradient bar and of the labels of the smallest and largest
+//! values of the property. It is drawn with its own uniforms, that map coordinates expressed in
+//! pixels to the drawing area, so that its size does not depend on the camera.
+
+use super::bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
+use super::instances_drawer::{InstanceDrawer, RawDrawer};
+use super::rectangle::{create_pipeline, Vertex};
+use super::{text::Letter, wgpu, LetterInstance, Rc, Uniforms, MODEL_BG_ENTRY};
+use crate::PhySize;
+use ensnano_design::ultraviolet::{Mat4, Vec3, Vec4};
+use ensnano_interactor::consts::{LEGEND_CHARS, NB_LEGEND_CHARS};
+use ensnano_interactor::graphics::StrandColors;
+use ensnano_utils::instance::Instance;
+
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue, RenderPipeline};
+
+/// The number of segments of the gradient bar
+const NB_SEGMENTS: usize = 32;
+const NB_VERTICES: usize = 2 * (NB_SEGMENTS + 1);
+/// The size, in pixels, of the gradient bar
+const BAR_WIDTH: f32 = 16.;
+const BAR_HEIGHT: f32 = 160.;
+/// The distance, in pixels, between the gradient bar and the borders of the drawing area
+const MARGIN: f32 = 24.;
+/// The distance, in pixels, between the labels and the gradient bar
+const LABEL_GAP: f32 = 6.;
+const LABEL_SCALE: f32 = 40.;
+/// The space, in pixels, between the start of two consecutive letters of the labels
+const LABEL_ADVANCE: f32 = 9.;
+
+pub struct Legend {
+    strand_colors: Option<StrandColors>,
+    /// The size of the drawing area for which the legend was last laid out
+    area_size: Option<PhySize>,
+    need_update: bool,
+    pipeline: RenderPipeline,
+    vbo: wgpu::Buffer,
+    ibo: wgpu::Buffer,
+    letter_drawers: Vec<InstanceDrawer<LetterInstance>>,
+    viewer: UniformBindGroup,
+    models: DynamicBindGroup,
+    queue: Rc<Queue>,
+}
+
+impl Legend {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        let pipeline = create_pipeline(device.as_ref(), "Legend gradient pipeline");
+
+        let vertices = [Vertex::default(); NB_VERTICES];
+        let vbo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Legend vertex buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+        let indices: Vec<u16> = (0..NB_VERTICES as u16).collect();
+        let ibo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Legend index buffer"),
+            contents: bytemuck::cast_slice(indices.as_slice()),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let viewer = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            &Uniforms::screen_space(1., 1.),
+            "legend viewer",
+        );
+        let mut models = DynamicBindGroup::new(device.clone(), queue.clone(), "legend models");
+        models.update(&[Mat4::identity()]);
+        let model_bg_desc = wgpu::BindGroupLayoutDescriptor {
+            entries: MODEL_BG_ENTRY,
+            label: None,
+        };
+        let letter_drawers = LEGEND_CHARS
+            .iter()
+            .map(|c| {
+                let letter = Letter::new(*c, device.clone(), queue.clone());
+                InstanceDrawer::new(
+                    device.clone(),
+                    queue.clone(),
+                    &viewer.get_layout_desc(),
+                    &model_bg_desc,
+                    letter,
+                    false,
+                    format!("legend letter {c}"),
+                )
+            })
+            .collect();
+
+        Self {
+            strand_colors: None,
+            area_size: None,
+            need_update: false,
+            pipeline,
+            vbo,
+            ibo,
+            letter_drawers,
+            viewer,
+            models,
+            queue,
+        }
+    }
+
+    /// Set the colormap whose legend is drawn. If `strand_colors` is `None`, nothing is drawn.
+    pub fn update(&mut self, strand_colors: Option<StrandColors>) {
+        if self.strand_colors != strand_colors {
+            self.strand_colors = strand_colors;
+            self.need_update = true;
+        }
+    }
+
+    /// Draw the legend in the bottom right corner of a drawing area of size `area_size`.
+    ///
+    /// The viewport of `render_pass` must be set to the drawing area.
+    pub fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>, area_size: PhySize) {
+        if self.strand_colors.is_none() {
+            return;
+        }
+        if self.need_update || self.area_size != Some(area_size) {
+            self.layout(area_size);
+            self.need_update = false;
+            self.area_size = Some(area_size);
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_index_buffer(self.ibo.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_vertex_buffer(0, self.vbo.slice(..));
+        render_pass.draw_indexed(0..NB_VERTICES as u32, 0, 0..1);
+        for drawer in self.letter_drawers.iter_mut() {
+            drawer.draw(
+                render_pass,
+                self.viewer.get_bindgroup(),
+                self.models.get_bindgroup(),
+            )
+        }
+    }
+
+    /// Compute the vertices of the gradient bar and the letters of the labels.
+    fn layout(&mut self, area_size: PhySize) {
+        let strand_colors = if let Some(colors) = self.strand_colors.as_ref() {
+            colors
+        } else {
+            return;
+        };
+        let (width, height) = (area_size.width as f32, area_size.height as f32);
+        self.viewer.update(&Uniforms::screen_space(width, height));
+
+        // Coordinates in pixels, with the origin at the center of the drawing area
+        let right = width / 2. - MARGIN;
+        let left = right - BAR_WIDTH;
+        let bottom = -height / 2. + MARGIN;
+        let top = bottom + BAR_HEIGHT;
+
+        let mut vertices = Vec::with_capacity(NB_VERTICES);
+        for i in 0..=NB_SEGMENTS {
+            let t = i as f32 / NB_SEGMENTS as f32;
+            let y = bottom + t * BAR_HEIGHT;
+            let color = Instance::color_from_u32(strand_colors.colormap.color(t)).into();
+            for x in [left, right] {
+                vertices.push(Vertex {
+                    position: [2. * x / width, 2. * y / height],
+                    color,
+                });
+            }
+        }
+        self.queue
+            .write_buffer(&self.vbo, 0, bytemuck::cast_slice(vertices.as_slice()));
+
+        let mut letters = vec![Vec::new(); NB_LEGEND_CHARS];
+        let coloring = strand_colors.coloring;
+        for (value, y) in [(strand_colors.min, bottom), (strand_colors.max, top)] {
+            let text = coloring.format_value(value);
+            let nb_chars = text.chars().count();
+            let start = left - LABEL_GAP - LABEL_ADVANCE * nb_chars as f32;
+            for (c_idx, c) in text.chars().enumerate() {
+                if let Some(idx) = LEGEND_CHARS.iter().position(|x| *x == c) {
+                    letters[idx].push(LetterInstance {
+                        position: Vec3::new(
+                            start + LABEL_ADVANCE * c_idx as f32,
+                            y + LABEL_ADVANCE,
+                            0.,
+                        ),
+                        color: Vec4::new(0., 0., 0., 1.),
+                        design_id: 0,
+                        scale: LABEL_SCALE,
+                        shift: Vec3::zero(),
+                    })
+                }
+            }
+        }
+        for (drawer, instances) in self.letter_drawers.iter_mut().zip(letters) {
+            drawer.new_instances(instances);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Draws the messages shown on top of the view: the banner displayed while a simulation is
+//! running and the transient messages explaining why an action was blocked.
+//!
+//! The messages are stacked at the top of the drawing area, centered horizontally. Like the
+//! tooltip, they are drawn with their own uniforms, that map coordinates expressed in pixels to
+//! the drawing area.
+
+use super::bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
+use super::instances_drawer::{InstanceDrawer, RawDrawer};
+use super::rectangle::{create_pipeline, Vertex};
+use super::{text::Letter, wgpu, LetterInstance, Rc, Uniforms, MODEL_BG_ENTRY};
+use crate::PhySize;
+use ensnano_design::ultraviolet::{Mat4, Vec3, Vec4};
+use ensnano_interactor::consts::{MAX_TOASTS, SIMULATION_BANNER_COLOR, TOAST_BACKGROUND_COLOR};
+use ensnano_interactor::toasts::{OverlayMessage, OverlayMessageKind};
+use std::collections::BTreeMap;
+
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue, RenderPipeline};
+
+/// The distance, in pixels, between the top of the drawing area and the first message
+const TOP_MARGIN: f32 = 10.;
+/// The vertical space, in pixels, between two messages
+const SPACING: f32 = 6.;
+/// The distance, in pixels, between the text and the borders of a message
+const PADDING: f32 = 6.;
+const LETTER_SCALE: f32 = 40.;
+/// The space, in pixels, between the start of two consecutive letters
+const LETTER_ADVANCE: f32 = 9.;
+const TEXT_HEIGHT: f32 = 16.;
+/// The maximum number of messages drawn at the same time: the banner and the toasts
+const MAX_MESSAGES: usize = MAX_TOASTS + 1;
+
+pub struct OverlayMessagesDrawer {
+    messages: Vec<OverlayMessage>,
+    /// The size of the drawing area for which the messages were last laid out
+    area_size: Option<PhySize>,
+    need_update: bool,
+    pipeline: RenderPipeline,
+    vbo: wgpu::Buffer,
+    ibo: wgpu::Buffer,
+    letter_drawers: BTreeMap<char, InstanceDrawer<LetterInstance>>,
+    viewer: UniformBindGroup,
+    models: DynamicBindGroup,
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+}
+
+impl OverlayMessagesDrawer {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        let pipeline = create_pipeline(device.as_ref(), "Overlay messages background pipeline");
+
+        let vertices = [Vertex::default(); 4 * MAX_MESSAGES];
+        let vbo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Overlay messages vertex buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+        let indices = [0u16, 1, 2, 3];
+        let ibo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Overlay messages index buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let viewer = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            &Uniforms::screen_space(1., 1.),
+            "overlay messages viewer",
+        );
+        let mut models =
+            DynamicBindGroup::new(device.clone(), queue.clone(), "overlay messages models");
+        models.update(&[Mat4::identity()]);
+
+        Self {
+            messages: Vec::new(),
+            area_size: None,
+            need_update: false,
+            pipeline,
+            vbo,
+            ibo,
+            letter_drawers: BTreeMap::new(),
+            viewer,
+            models,
+            device,
+            queue,
+        }
+    }
+
+    /// Set the messages to be drawn, from top to bottom.
+    pub fn update(&mut self, mut messages: Vec<OverlayMessage>) {
+        messages.truncate(MAX_MESSAGES);
+        if self.messages != messages {
+            self.messages = messages;
+            self.need_update = true;
+        }
+    }
+
+    /// Draw the messages in a drawing area of size `area_size`.
+    ///
+    /// The viewport of `render_pass` must be set to the drawing area.
+    pub fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>, area_size: PhySize) {
+        if self.messages.is_empty() {
+            return;
+        }
+        if self.need_update || self.area_size != Some(area_size) {
+            self.layout(area_size);
+            self.need_update = false;
+            self.area_size = Some(area_size);
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_index_buffer(self.ibo.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_vertex_buffer(0, self.vbo.slice(..));
+        for i in 0..self.messages.len() {
+            render_pass.draw_indexed(0..4, 4 * i as i32, 0..1);
+        }
+        for drawer in self.letter_drawers.values_mut() {
+            drawer.draw(
+                render_pass,
+                self.viewer.get_bindgroup(),
+                self.models.get_bindgroup(),
+            )
+        }
+    }
+
+    /// Compute the vertices of the backgrounds and the letters of the messages.
+    fn layout(&mut self, area_size: PhySize) {
+        let (width, height) = (area_size.width as f32, area_size.height as f32);
+        self.viewer.update(&Uniforms::screen_space(width, height));
+
+        let mut vertices = Vec::with_capacity(4 * self.messages.len());
+        let mut letters: BTreeMap<char, Vec<LetterInstance>> = BTreeMap::new();
+        // Coordinates in pixels, with the origin at the center of the drawing area
+        let mut top = height / 2. - TOP_MARGIN;
+        for message in self.messages.iter() {
+            let (background, text_color) = match message.kind {
+                OverlayMessageKind::Banner => (SIMULATION_BANNER_COLOR, Vec4::new(0., 0., 0., 1.)),
+                OverlayMessageKind::Toast => (TOAST_BACKGROUND_COLOR, Vec4::new(1., 1., 1., 1.)),
+            };
+            let background = Vec4::from(background);
+            let opacity = message.opacity.max(0.).min(1.);
+            let color = [
+                background.x,
+                background.y,
+                background.z,
+                background.w * opacity,
+            ];
+            // The letters are opaque, they fade out by taking the color of the background
+            let text_color = background * (1. - opacity) + text_color * opacity;
+
+            let nb_chars = message.text.chars().count() as f32;
+            let box_width = 2. * PADDING + LETTER_ADVANCE * nb_chars;
+            let box_height = 2. * PADDING + TEXT_HEIGHT;
+            let (left, right) = (-box_width / 2., box_width / 2.);
+            let bottom = top - box_height;
+            vertices.extend(
+                [(left, bottom), (left, top), (right, bottom), (right, top)]
+                    .iter()
+                    .map(|(x, y)| Vertex {
+                        position: [2. * x / width, 2. * y / height],
+                        color,
+                    }),
+            );
+            for (c_idx, c) in message.text.chars().enumerate() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                letters.entry(c).or_default().push(LetterInstance {
+                    position: Vec3::new(
+                        left + PADDING + LETTER_ADVANCE * c_idx as f32,
+                        top - PADDING,
+                        0.,
+                    ),
+                    color: Vec4::new(text_color.x, text_color.y, text_color.z, 1.),
+                    design_id: 0,
+                    scale: LETTER_SCALE,
+                    shift: Vec3::zero(),
+                })
+            }
+            top = bottom - SPACING;
+        }
+        self.queue
+            .write_buffer(&self.vbo, 0, bytemuck::cast_slice(vertices.as_slice()));
+
+        for c in letters.keys() {
+            if !self.letter_drawers.contains_key(c) {
+                let drawer = self.new_letter_drawer(*c);
+                self.letter_drawers.insert(*c, drawer);
+            }
+        }
+        for (c, drawer) in self.letter_drawers.iter_mut() {
+            drawer.new_instances(letters.remove(c).unwrap_or_default());
+        }
+    }
+
+    fn new_letter_drawer(&self, c: char) -> InstanceDrawer<LetterInstance> {
+        let model_bg_desc = wgpu::BindGroupLayoutDescriptor {
+            entries: MODEL_BG_ENTRY,
+            label: None,
+        };
+        let letter = Letter::new(c, self.device.clone(), self.queue.clone());
+        InstanceDrawer::new(
+            self.device.clone(),
+            self.queue.clone(),
+            &self.viewer.get_layout_desc(),
+            &model_bg_desc,
+            letter,
+            false,
+            format!("overlay message letter {c}"),
+        )
+    }
+}
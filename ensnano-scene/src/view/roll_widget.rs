@@ -0,0 +1,171 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use super::{maths_3d, rotation_widget::Circle, CameraPtr, Drawer, ProjectionPtr};
+
+use ensnano_design::ultraviolet::Vec3;
+use ensnano_interactor::consts::*;
+use ensnano_utils::wgpu;
+use std::rc::Rc;
+use wgpu::Device;
+
+/// A circle around the axis of a helix, used to modify the roll of the helix.
+pub struct RollWidget {
+    descriptor: Option<RollWidgetDescriptor>,
+    circle: Option<Circle>,
+    circle_drawer: Drawer<Circle>,
+    big_circle_drawer: Drawer<Circle>,
+    selected: bool,
+    clicked_origin: Option<(f32, f32)>,
+    initial_roll: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollWidgetDescriptor {
+    pub helix_id: usize,
+    /// A point on the axis of the helix, at the center of the widget
+    pub origin: Vec3,
+    /// The direction of the axis of the helix
+    pub axis: Vec3,
+    pub radius: f32,
+}
+
+impl RollWidgetDescriptor {
+    fn make_circle(&self) -> Circle {
+        let axis = self.axis.normalized();
+        let not_colinear = if axis.x.abs() < 0.9 {
+            Vec3::unit_x()
+        } else {
+            Vec3::unit_y()
+        };
+        let right = axis.cross(not_colinear).normalized();
+        // right.cross(up) is equal to axis so that the normal of the circle is the axis
+        let up = axis.cross(right);
+        Circle::new(
+            self.origin,
+            self.radius,
+            right,
+            up,
+            ROLL_CIRCLE_COLOR,
+            ROLL_CIRCLE_ID,
+        )
+    }
+}
+
+impl RollWidget {
+    pub fn new(device: Rc<Device>) -> Self {
+        Self {
+            descriptor: None,
+            circle: None,
+            circle_drawer: Drawer::new(device.clone()),
+            big_circle_drawer: Drawer::new(device),
+            selected: false,
+            clicked_origin: None,
+            initial_roll: 0.,
+        }
+    }
+
+    pub fn update_decriptor(&mut self, descriptor: Option<RollWidgetDescriptor>) {
+        self.descriptor = descriptor;
+        self.circle = descriptor.as_ref().map(RollWidgetDescriptor::make_circle);
+        self.circle_drawer.new_object(self.circle);
+        self.select_circle(self.selected);
+    }
+
+    pub fn set_selected(&mut self, selected_id: Option<u32>) -> bool {
+        let new_selection = selected_id == Some(ROLL_CIRCLE_ID);
+        let ret = new_selection != self.selected;
+        self.select_circle(new_selection);
+        ret
+    }
+
+    fn select_circle(&mut self, selected: bool) {
+        self.selected = selected;
+        let big_circle = self.circle.filter(|_| selected).map(|c| c.bigger_version());
+        self.big_circle_drawer.new_object(big_circle);
+    }
+
+    pub fn draw<'a>(
+        &'a mut self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        viewer_bind_group: &'a wgpu::BindGroup,
+        viewer_bind_group_layout: &'a wgpu::BindGroupLayout,
+        fake: bool,
+    ) {
+        self.circle_drawer.draw(
+            render_pass,
+            viewer_bind_group,
+            viewer_bind_group_layout,
+            fake,
+        );
+        if !fake {
+            self.big_circle_drawer.draw(
+                render_pass,
+                viewer_bind_group,
+                viewer_bind_group_layout,
+                fake,
+            )
+        }
+    }
+
+    pub fn init_roll(&mut self, x: f32, y: f32, initial_roll: f32) {
+        self.clicked_origin = Some((x, y));
+        self.initial_roll = initial_roll;
+    }
+
+    /// Return the identifier of the helix on which the widget is and the roll that this helix
+    /// must have so that it follows the cursor.
+    ///
+    /// If `step` is some value, the variation of the roll is rounded to a multiple of `step`.
+    pub fn compute_roll(
+        &self,
+        x: f32,
+        y: f32,
+        step: Option<f32>,
+        camera: CameraPtr,
+        projection: ProjectionPtr,
+    ) -> Option<(usize, f32)> {
+        let (x_init, y_init) = self.clicked_origin?;
+        let descriptor = self.descriptor.as_ref()?;
+        let origin = descriptor.origin;
+        let axis = descriptor.axis.normalized();
+        let point_clicked = maths_3d::unproject_point_on_plane(
+            origin,
+            axis,
+            camera.clone(),
+            projection.clone(),
+            x_init,
+            y_init,
+            None,
+        )?;
+        let point_moved =
+            maths_3d::unproject_point_on_plane(origin, axis, camera, projection, x, y, None)?;
+        let from = point_clicked - origin;
+        let to = point_moved - origin;
+        let angle = axis.dot(from.cross(to)).atan2(from.dot(to));
+        // A positive roll turns the nucleotides clockwise around the axis of the helix
+        let mut delta = -angle;
+        if let Some(step) = step.filter(|s| *s > 0.) {
+            delta = (delta / step).round() * step;
+        }
+        Some((descriptor.helix_id, self.initial_roll + delta))
+    }
+
+    pub fn get_helix_id(&self) -> Option<usize> {
+        self.descriptor.as_ref().map(|d| d.helix_id)
+    }
+}
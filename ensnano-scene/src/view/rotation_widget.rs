@@ -214,11 +214,25 @@ impl RotationWidget {
             (point_clicked - origin).normalized(),
             (point_moved - origin).normalized(),
         );
-        let axis = {
-            let plane = rotation.bv.normalized();
-            Vec3::new(plane.yz, plane.xz, plane.xy)
-        };
-        Some((rotation, origin, normal.dot(axis) >= 0.))
+        Some((
+            rotation,
+            origin,
+            ensnano_interactor::snapping::is_positive_rotation(rotation, normal),
+        ))
+    }
+
+    /// The center and the normal of the circle that is highlighted, if any.
+    pub fn get_highlighted_axis(&self) -> Option<(Vec3, Vec3)> {
+        let circle = self.circles.as_ref()?.get(self.selected?)?;
+        Some((circle.origin, circle.normal()))
+    }
+
+    /// The position of the label displaying the angle of the rotation, on the right of the
+    /// widget as seen from the camera.
+    pub fn get_label_position(&self, right: Vec3) -> Option<Vec3> {
+        let circles = self.circles.as_ref()?;
+        let radius = circles.iter().map(|c| c.radius).fold(0., f32::max);
+        Some(circles[0].origin + right * 1.2 * radius)
     }
 
     pub fn translate(&mut self, translation: Vec3) {
@@ -274,7 +288,7 @@ impl RotationWidgetDescriptor {
     fn make_circles(&self, camera: CameraPtr, projection: ProjectionPtr) -> [Circle; 3] {
         let dist = (camera.borrow().position - self.origin).mag();
         let (right, up, dir) = self.make_axis();
-        let length = self.size * dist * (projection.borrow().get_fovy() / 2.).tan() * 1.1;
+        let length = self.size * projection.borrow().half_height_at(dist) * 1.1;
         let (xy_filter, z_filter) = match self.available_rotation_axes {
             AvailableRotationAxes::All => (1., 1.),
             AvailableRotationAxes::NoZ => (1., 0.),
@@ -314,7 +328,7 @@ impl RotationWidgetDescriptor {
 
     fn make_sphere(&self, camera: CameraPtr, projection: ProjectionPtr) -> Sphere {
         let dist = (camera.borrow().position - self.origin).mag();
-        let length = self.size * dist * (projection.borrow().get_fovy() / 2.).tan();
+        let length = self.size * projection.borrow().half_height_at(dist);
         Sphere::new(self.origin, length, 0xA0_54_54_44, SPHERE_WIDGET_ID)
     }
 
@@ -0,0 +1,233 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Draws a tooltip next to the cursor, displaying a text attached to the element under the
+//! cursor.
+//!
+//! Like the legend, the tooltip is drawn with its own uniforms, that map coordinates expressed in
+//! pixels to the drawing area. The text can contain any character, so the drawer of the letters
+//! of a character is only created the first time that this character is displayed.
+
+use super::bindgroup_manager::{DynamicBindGroup, UniformBindGroup};
+use super::instances_drawer::{InstanceDrawer, RawDrawer};
+use super::rectangle::{create_pipeline, Vertex};
+use super::{text::Letter, wgpu, LetterInstance, Rc, Uniforms, MODEL_BG_ENTRY};
+use crate::{PhySize, PhysicalPosition};
+use ensnano_design::ultraviolet::{Mat4, Vec3, Vec4};
+use ensnano_interactor::consts::{TOOLTIP_BACKGROUND_COLOR, TOOLTIP_MAX_CHARS};
+use std::collections::BTreeMap;
+
+use wgpu::util::DeviceExt;
+use wgpu::{Device, Queue, RenderPipeline};
+
+/// The distance, in pixels, between the cursor and the tooltip
+const CURSOR_OFFSET: f32 = 16.;
+/// The distance, in pixels, between the text and the borders of the tooltip
+const PADDING: f32 = 6.;
+const LETTER_SCALE: f32 = 40.;
+/// The space, in pixels, between the start of two consecutive letters
+const LETTER_ADVANCE: f32 = 9.;
+const TEXT_HEIGHT: f32 = 16.;
+
+pub struct Tooltip {
+    /// The text of the tooltip and the position of the cursor, in pixels, in the drawing area
+    content: Option<(String, PhysicalPosition<f64>)>,
+    /// The size of the drawing area for which the tooltip was last laid out
+    area_size: Option<PhySize>,
+    need_update: bool,
+    pipeline: RenderPipeline,
+    vbo: wgpu::Buffer,
+    ibo: wgpu::Buffer,
+    letter_drawers: BTreeMap<char, InstanceDrawer<LetterInstance>>,
+    viewer: UniformBindGroup,
+    models: DynamicBindGroup,
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+}
+
+impl Tooltip {
+    pub fn new(device: Rc<Device>, queue: Rc<Queue>) -> Self {
+        let pipeline = create_pipeline(device.as_ref(), "Tooltip background pipeline");
+
+        let vertices = [Vertex::default(); 4];
+        let vbo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Tooltip vertex buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+        let indices = [0u16, 1, 2, 3];
+        let ibo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Tooltip index buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let viewer = UniformBindGroup::new(
+            device.clone(),
+            queue.clone(),
+            &Uniforms::screen_space(1., 1.),
+            "tooltip viewer",
+        );
+        let mut models = DynamicBindGroup::new(device.clone(), queue.clone(), "tooltip models");
+        models.update(&[Mat4::identity()]);
+
+        Self {
+            content: None,
+            area_size: None,
+            need_update: false,
+            pipeline,
+            vbo,
+            ibo,
+            letter_drawers: BTreeMap::new(),
+            viewer,
+            models,
+            device,
+            queue,
+        }
+    }
+
+    /// Set the text of the tooltip and the position of the cursor next to which it is drawn. If
+    /// `content` is `None`, nothing is drawn.
+    pub fn update(&mut self, content: Option<(String, PhysicalPosition<f64>)>) {
+        if self.content != content {
+            self.content = content;
+            self.need_update = true;
+        }
+    }
+
+    /// Draw the tooltip in a drawing area of size `area_size`.
+    ///
+    /// The viewport of `render_pass` must be set to the drawing area.
+    pub fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>, area_size: PhySize) {
+        if self.content.is_none() {
+            return;
+        }
+        if self.need_update || self.area_size != Some(area_size) {
+            self.layout(area_size);
+            self.need_update = false;
+            self.area_size = Some(area_size);
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_index_buffer(self.ibo.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_vertex_buffer(0, self.vbo.slice(..));
+        render_pass.draw_indexed(0..4, 0, 0..1);
+        for drawer in self.letter_drawers.values_mut() {
+            drawer.draw(
+                render_pass,
+                self.viewer.get_bindgroup(),
+                self.models.get_bindgroup(),
+            )
+        }
+    }
+
+    /// Compute the vertices of the background and the letters of the text.
+    fn layout(&mut self, area_size: PhySize) {
+        let (text, cursor) = if let Some((text, cursor)) = self.content.as_ref() {
+            (displayed_text(text), *cursor)
+        } else {
+            return;
+        };
+        let (width, height) = (area_size.width as f32, area_size.height as f32);
+        self.viewer.update(&Uniforms::screen_space(width, height));
+
+        // Coordinates in pixels, with the origin at the center of the drawing area
+        let cursor_x = cursor.x as f32 - width / 2.;
+        let cursor_y = height / 2. - cursor.y as f32;
+        let box_width = 2. * PADDING + LETTER_ADVANCE * text.chars().count() as f32;
+        let box_height = 2. * PADDING + TEXT_HEIGHT;
+        // The tooltip is drawn below and to the right of the cursor, unless it would not fit in
+        // the drawing area
+        let left = if cursor_x + CURSOR_OFFSET + box_width > width / 2. {
+            cursor_x - CURSOR_OFFSET - box_width
+        } else {
+            cursor_x + CURSOR_OFFSET
+        };
+        let top = if cursor_y - CURSOR_OFFSET - box_height < -height / 2. {
+            cursor_y + CURSOR_OFFSET + box_height
+        } else {
+            cursor_y - CURSOR_OFFSET
+        };
+        let (right, bottom) = (left + box_width, top - box_height);
+
+        let color = TOOLTIP_BACKGROUND_COLOR;
+        let vertices: Vec<Vertex> = [(left, bottom), (left, top), (right, bottom), (right, top)]
+            .iter()
+            .map(|(x, y)| Vertex {
+                position: [2. * x / width, 2. * y / height],
+                color,
+            })
+            .collect();
+        self.queue
+            .write_buffer(&self.vbo, 0, bytemuck::cast_slice(vertices.as_slice()));
+
+        let mut letters: BTreeMap<char, Vec<LetterInstance>> = BTreeMap::new();
+        for (c_idx, c) in text.chars().enumerate() {
+            if c.is_whitespace() {
+                continue;
+            }
+            letters.entry(c).or_default().push(LetterInstance {
+                position: Vec3::new(
+                    left + PADDING + LETTER_ADVANCE * c_idx as f32,
+                    top - PADDING,
+                    0.,
+                ),
+                color: Vec4::new(0., 0., 0., 1.),
+                design_id: 0,
+                scale: LETTER_SCALE,
+                shift: Vec3::zero(),
+            })
+        }
+        for c in letters.keys() {
+            if !self.letter_drawers.contains_key(c) {
+                let drawer = self.new_letter_drawer(*c);
+                self.letter_drawers.insert(*c, drawer);
+            }
+        }
+        for (c, drawer) in self.letter_drawers.iter_mut() {
+            drawer.new_instances(letters.remove(c).unwrap_or_default());
+        }
+    }
+
+    fn new_letter_drawer(&self, c: char) -> InstanceDrawer<LetterInstance> {
+        let model_bg_desc = wgpu::BindGroupLayoutDescriptor {
+            entries: MODEL_BG_ENTRY,
+            label: None,
+        };
+        let letter = Letter::new(c, self.device.clone(), self.queue.clone());
+        InstanceDrawer::new(
+            self.device.clone(),
+            self.queue.clone(),
+            &self.viewer.get_layout_desc(),
+            &model_bg_desc,
+            letter,
+            false,
+            format!("tooltip letter {c}"),
+        )
+    }
+}
+
+/// The text displayed in the tooltip: the first line of `text`, truncated if it is too long.
+fn displayed_text(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > TOOLTIP_MAX_CHARS {
+        let mut ret: String = line.chars().take(TOOLTIP_MAX_CHARS - 3).collect();
+        ret.push_str("...");
+        ret
+    } else {
+        line.to_string()
+    }
+}
@@ -17,6 +17,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 */
 use super::camera::{CameraPtr, ProjectionPtr};
 use ensnano_design::ultraviolet::{Mat4, Rotor3, Vec3, Vec4};
+use ensnano_interactor::graphics::FogCenter;
 pub use ensnano_interactor::graphics::FogParameters;
 
 #[repr(C)] // We need this for Rust to store our data correctly for the shaders
@@ -102,6 +103,14 @@ impl Uniforms {
         }
     }
 
+    /// Use the perspective projection matrix even if the projection is orthographic.
+    pub fn with_perspective(self, projection: ProjectionPtr) -> Self {
+        Self {
+            proj: projection.borrow().perspective_matrix(),
+            ..self
+        }
+    }
+
     pub fn from_view_proj_fog(
         camera: CameraPtr,
         projection: ProjectionPtr,
@@ -115,8 +124,9 @@ impl Uniforms {
             Mat4::identity()
         };
         let stereography_radius = stereography.as_ref().map(|s| s.radius).unwrap_or(0.0);
+        let from_camera = fog.center == FogCenter::Camera;
         let mut make_fog = fog.fog_kind;
-        if !fog.from_camera && fog.alt_fog_center.is_none() {
+        if !from_camera && fog.alt_fog_center.is_none() {
             make_fog = ensnano_interactor::graphics::fog_kind::NO_FOG;
         }
         Self {
@@ -127,7 +137,7 @@ impl Uniforms {
             fog_length: fog.length,
             fog_radius: fog.radius,
             make_fog,
-            fog_from_camera: fog.from_camera as u32,
+            fog_from_camera: from_camera as u32,
             fog_alt_center: fog.alt_fog_center.unwrap_or(Vec3::zero()),
             stereography_view,
             stereography_radius,
@@ -136,4 +146,31 @@ impl Uniforms {
             _padding: Default::default(),
         }
     }
+
+    /// Uniforms mapping coordinates expressed in pixels, with the origin at the center of a
+    /// drawing area of size `width` x `height`, to normalized device coordinates.
+    pub fn screen_space(width: f32, height: f32) -> Self {
+        let proj = Mat4::new(
+            Vec4::new(2. / width.max(1.), 0., 0., 0.),
+            Vec4::new(0., 2. / height.max(1.), 0., 0.),
+            Vec4::new(0., 0., 1., 0.),
+            Vec4::new(0., 0., 0., 1.),
+        );
+        Self {
+            camera_position: Vec4::new(0., 0., 0., 1.),
+            view: Mat4::identity(),
+            proj,
+            inversed_view: Mat4::identity(),
+            fog_radius: 0.,
+            fog_length: 0.,
+            make_fog: false as u32,
+            fog_from_camera: false as u32,
+            fog_alt_center: Vec3::zero(),
+            stereography_radius: 0.,
+            stereography_view: Mat4::identity(),
+            aspect_ratio: width / height.max(1.),
+            stereography_zoom: 1.,
+            _padding: Default::default(),
+        }
+    }
 }
@@ -22,11 +22,154 @@ use crate::create_buffer_with_data;
 use crate::wgpu;
 use wgpu::{BindGroup, BindGroupLayout, Buffer, BufferDescriptor, Device, Queue};
 
+/// The number of consecutive updates during which a buffer must be under-used before it is
+/// shrunk.
+pub const DEFAULT_SHRINK_DELAY: usize = 600;
+
+/// Decides when a buffer whose content varies in size must be reallocated.
+///
+/// The buffer is over-allocated so that it can grow without being reallocated at each update, and
+/// it is shrunk when it has been using less than a quarter of its capacity for `shrink_delay`
+/// consecutive updates.
+#[derive(Debug, Clone)]
+pub struct BufferCapacity {
+    capacity: u64,
+    min_capacity: u64,
+    shrink_delay: usize,
+    /// The number of consecutive updates during which less than a quarter of the capacity was
+    /// used
+    nb_idle_updates: usize,
+}
+
+impl BufferCapacity {
+    pub fn new(min_capacity: u64, shrink_delay: usize) -> Self {
+        let min_capacity = aligned_size(min_capacity);
+        Self {
+            capacity: min_capacity,
+            min_capacity,
+            shrink_delay,
+            nb_idle_updates: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    pub fn set_shrink_delay(&mut self, shrink_delay: usize) {
+        self.shrink_delay = shrink_delay;
+    }
+
+    /// Make room for `size` bytes and return the new capacity if the buffer must be
+    /// reallocated.
+    pub fn reserve(&mut self, size: u64) -> Option<u64> {
+        let new_capacity = aligned_size(2 * size).max(self.min_capacity);
+        if size > self.capacity {
+            self.nb_idle_updates = 0;
+            self.capacity = new_capacity;
+            return Some(new_capacity);
+        }
+        if 4 * size <= self.capacity && self.capacity > self.min_capacity {
+            self.nb_idle_updates += 1;
+        } else {
+            self.nb_idle_updates = 0;
+        }
+        if self.nb_idle_updates > self.shrink_delay {
+            self.nb_idle_updates = 0;
+            self.capacity = new_capacity;
+            Some(new_capacity)
+        } else {
+            None
+        }
+    }
+}
+
+/// Round `size` up to a multiple of `wgpu::COPY_BUFFER_ALIGNMENT`.
+fn aligned_size(size: u64) -> u64 {
+    let alignment = wgpu::COPY_BUFFER_ALIGNMENT;
+    (size + alignment - 1) / alignment * alignment
+}
+
+/// A buffer whose content may vary in size.
+///
+/// The buffer is only reallocated when its capacity is exceeded or when it has been under-used
+/// for a long time, see [`BufferCapacity`].
+pub struct DynamicBuffer {
+    buffer: Buffer,
+    capacity: BufferCapacity,
+    length: u64,
+    device: Rc<Device>,
+    queue: Rc<Queue>,
+    usage: wgpu::BufferUsages,
+    label: String,
+}
+
+impl DynamicBuffer {
+    pub fn new(
+        device: Rc<Device>,
+        queue: Rc<Queue>,
+        usage: wgpu::BufferUsages,
+        label: &str,
+    ) -> Self {
+        let capacity = BufferCapacity::new(0, DEFAULT_SHRINK_DELAY);
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some(label),
+            size: capacity.capacity(),
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            buffer,
+            capacity,
+            length: 0,
+            device,
+            queue,
+            usage,
+            label: label.to_string(),
+        }
+    }
+
+    /// Replace the data of the buffer.
+    pub fn update<I: bytemuck::Pod>(&mut self, data: &[I]) {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        self.length = bytes.len() as u64;
+        if let Some(capacity) = self.capacity.reserve(self.length) {
+            self.buffer = self.device.create_buffer(&BufferDescriptor {
+                label: Some(&self.label),
+                size: capacity,
+                usage: self.usage | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        }
+        write_padded(self.queue.as_ref(), &self.buffer, bytes);
+    }
+
+    /// The part of the buffer that holds the data of the last update.
+    pub fn get_slice(&self) -> wgpu::BufferSlice {
+        self.buffer.slice(..self.length)
+    }
+}
+
+/// Write `bytes` at the start of `buffer`, padding them with zeros if their length is not a
+/// multiple of `wgpu::COPY_BUFFER_ALIGNMENT`.
+fn write_padded(queue: &Queue, buffer: &Buffer, bytes: &[u8]) {
+    if bytes.len() as u64 % wgpu::COPY_BUFFER_ALIGNMENT == 0 {
+        queue.write_buffer(buffer, 0, bytes);
+    } else {
+        let mut padded = bytes.to_vec();
+        padded.resize(aligned_size(bytes.len() as u64) as usize, 0);
+        queue.write_buffer(buffer, 0, padded.as_slice());
+    }
+}
+
 /// A bind group with an associated buffer whose size may varry
+///
+/// The bind group is bound to the whole buffer, so that it is only rebuilt when the buffer is
+/// reallocated.
 pub struct DynamicBindGroup {
     layout: BindGroupLayout,
     buffer: Buffer,
-    capacity: usize,
+    capacity: BufferCapacity,
     length: u64,
     bind_group: BindGroup,
     device: Rc<Device>,
@@ -37,13 +180,13 @@ const INITIAL_CAPACITY: u64 = 1024;
 
 impl DynamicBindGroup {
     pub fn new(device: Rc<Device>, queue: Rc<Queue>, label: &str) -> Self {
+        let capacity = BufferCapacity::new(INITIAL_CAPACITY, DEFAULT_SHRINK_DELAY);
         let buffer = device.create_buffer(&BufferDescriptor {
             label: Some(label),
-            size: INITIAL_CAPACITY,
+            size: capacity.capacity(),
             usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
-        let capacity = INITIAL_CAPACITY as usize;
         let length = 0;
 
         let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -88,47 +231,37 @@ impl DynamicBindGroup {
 
     /// Replace the data of the associated buffer.
     pub fn update<I: bytemuck::Pod>(&mut self, data: &[I]) {
-        let bytes = bytemuck::cast_slice(data);
-        if self.capacity < bytes.len() {
-            self.length = bytes.len() as u64;
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        self.length = bytes.len() as u64;
+        if let Some(capacity) = self.capacity.reserve(self.length) {
             self.buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some(&format!("capacity = {}", 2 * bytes.len())),
-                size: 2 * bytes.len() as u64,
+                label: Some(&format!("capacity = {}", capacity)),
+                size: capacity,
                 usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
                 mapped_at_creation: false,
             });
-            self.capacity = 2 * bytes.len();
-            self.bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: &self.layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                        buffer: &self.buffer,
-                        size: wgpu::BufferSize::new(self.length),
-                        offset: 0,
-                    }),
-                }],
-                label: None,
-            });
-        } else if self.length != bytes.len() as u64 {
-            self.length = bytes.len() as u64;
             self.bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
                 layout: &self.layout,
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
                     resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
                         buffer: &self.buffer,
-                        size: wgpu::BufferSize::new(self.length),
+                        size: None,
                         offset: 0,
                     }),
                 }],
                 label: None,
             });
         }
-        self.queue.write_buffer(&self.buffer, 0, bytes);
+        write_padded(self.queue.as_ref(), &self.buffer, bytes);
+    }
+
+    /// Set the number of consecutive updates during which the buffer must be under-used before
+    /// it is shrunk.
+    pub fn set_shrink_delay(&mut self, shrink_delay: usize) {
+        self.capacity.set_shrink_delay(shrink_delay);
     }
 
-    #[allow(dead_code)]
     /// Write in the self.buffer with an offset
     pub fn update_offset(&mut self, offset: usize, bytes: &[u8]) {
         debug_assert!(self.length as usize >= offset + bytes.len());
@@ -227,3 +360,61 @@ impl UniformBindGroup {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_grows_geometrically() {
+        let mut capacity = BufferCapacity::new(16, 10);
+        assert_eq!(capacity.reserve(10), None);
+        assert_eq!(capacity.reserve(16), None);
+        assert_eq!(capacity.reserve(17), Some(36));
+        assert_eq!(capacity.capacity(), 36);
+        // Growing one element at a time does not reallocate at each update
+        let nb_reallocations = (18..=1000)
+            .filter_map(|size| capacity.reserve(size))
+            .count();
+        assert!(nb_reallocations <= 5);
+        assert!(capacity.capacity() >= 1000);
+    }
+
+    #[test]
+    fn capacity_is_aligned() {
+        let mut capacity = BufferCapacity::new(5, 10);
+        assert_eq!(capacity.capacity(), 8);
+        let new_capacity = capacity.reserve(13).unwrap();
+        assert_eq!(new_capacity % wgpu::COPY_BUFFER_ALIGNMENT, 0);
+        assert!(new_capacity >= 13);
+    }
+
+    #[test]
+    fn buffer_shrinks_after_being_idle() {
+        let mut capacity = BufferCapacity::new(16, 3);
+        capacity.reserve(1000);
+        assert_eq!(capacity.capacity(), 2000);
+        for _ in 0..3 {
+            assert_eq!(capacity.reserve(100), None);
+        }
+        assert_eq!(capacity.reserve(100), Some(200));
+        // Using more than a quarter of the capacity resets the count of idle updates
+        let mut capacity = BufferCapacity::new(16, 3);
+        capacity.reserve(1000);
+        for _ in 0..3 {
+            assert_eq!(capacity.reserve(100), None);
+        }
+        assert_eq!(capacity.reserve(1000), None);
+        for _ in 0..3 {
+            assert_eq!(capacity.reserve(100), None);
+        }
+    }
+
+    #[test]
+    fn buffer_does_not_shrink_below_min_capacity() {
+        let mut capacity = BufferCapacity::new(64, 0);
+        assert_eq!(capacity.reserve(0), None);
+        assert_eq!(capacity.reserve(0), None);
+        assert_eq!(capacity.capacity(), 64);
+    }
+}
@@ -22,7 +22,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! coordinate conversion.
 
 use ensnano_design::{Rotor2, Vec2};
-use ensnano_interactor::consts::*;
+use ensnano_interactor::{application::Camera2D, consts::*};
 use iced_winit::winit;
 use winit::{dpi::PhysicalPosition, event::MouseScrollDelta};
 pub struct Camera {
@@ -305,6 +305,25 @@ impl Camera {
         }
     }
 
+    /// Return the position of the camera.
+    pub fn get_camera_2d(&self) -> Camera2D {
+        Camera2D {
+            scroll_offset: self.globals.scroll_offset,
+            zoom: self.globals.zoom,
+            tilt: self.globals.tilt,
+            symetry: [self.globals.symetry.x, self.globals.symetry.y],
+        }
+    }
+
+    /// Move the camera to a given position. The resolution of the camera is not modified.
+    pub fn teleport(&mut self, camera: &Camera2D) {
+        self.globals.scroll_offset = camera.scroll_offset;
+        self.globals.zoom = camera.zoom;
+        self.globals.tilt = camera.tilt;
+        self.globals.symetry = camera.symetry.into();
+        self.end_movement();
+    }
+
     pub fn swap(&mut self, other: &mut Self) {
         std::mem::swap(&mut self.globals, &mut other.globals);
         self.was_updated = true;
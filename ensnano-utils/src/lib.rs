@@ -32,6 +32,7 @@ pub mod instance;
 pub mod light;
 pub mod mesh;
 pub mod obj_loader;
+pub mod readback;
 pub mod text;
 pub mod texture;
 
@@ -92,6 +93,52 @@ pub fn new_color(color_idx: &mut usize) -> u32 {
     color
 }
 
+/// The number of colors of the palette of [`new_color`] among which [`distinct_color`] chooses
+const NB_DISTINCT_COLOR_CANDIDATES: usize = 16;
+
+/// The perceptual distance between two colors according to the CIEDE2000 formula. The alpha
+/// channel is ignored.
+pub fn color_distance(color_1: u32, color_2: u32) -> f64 {
+    use color_space::CompareCie2000;
+    to_rgb(color_1).compare_cie2000(&to_rgb(color_2))
+}
+
+fn to_rgb(color: u32) -> color_space::Rgb {
+    color_space::Rgb::new(
+        ((color >> 16) & 0xFF) as f64,
+        ((color >> 8) & 0xFF) as f64,
+        (color & 0xFF) as f64,
+    )
+}
+
+/// Return a color of the palette of [`new_color`] that is as different as possible from the
+/// colors in `neighbours`.
+///
+/// The next [`NB_DISTINCT_COLOR_CANDIDATES`] colors of the palette are considered, and the one
+/// whose smallest distance to the neighbours is the largest is chosen. `color_idx` is moved past
+/// the chosen color.
+pub fn distinct_color(color_idx: &mut usize, neighbours: &[u32]) -> u32 {
+    if neighbours.is_empty() {
+        return new_color(color_idx);
+    }
+    let mut best: Option<(usize, u32, f64)> = None;
+    for i in 0..NB_DISTINCT_COLOR_CANDIDATES {
+        let mut next_idx = *color_idx + i;
+        let color = new_color(&mut next_idx);
+        let distance = neighbours
+            .iter()
+            .map(|n| color_distance(color, *n))
+            .fold(f64::INFINITY, f64::min);
+        if best.map(|(_, _, d)| distance > d).unwrap_or(true) {
+            best = Some((next_idx, color, distance));
+        }
+    }
+    // There is at least one candidate
+    let (next_idx, color, _) = best.unwrap();
+    *color_idx = next_idx;
+    color
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Ndc {
@@ -112,3 +159,47 @@ impl Ndc {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The distance above which two colors are considered clearly distinct
+    const DISTINCT_THRESHOLD: f64 = 10.;
+
+    #[test]
+    fn without_neighbours_the_palette_is_followed() {
+        let mut idx_1 = 3;
+        let mut idx_2 = 3;
+        assert_eq!(distinct_color(&mut idx_1, &[]), new_color(&mut idx_2));
+        assert_eq!(idx_1, idx_2);
+    }
+
+    #[test]
+    fn chosen_color_differs_from_neighbours() {
+        let mut idx = 0;
+        // The neighbours use the colors that would have been chosen next
+        let neighbours: Vec<u32> = (0..4).map(|_| new_color(&mut idx)).collect();
+        let mut color_idx = 0;
+        let color = distinct_color(&mut color_idx, &neighbours);
+        for n in neighbours.iter() {
+            assert!(color_distance(color, *n) > DISTINCT_THRESHOLD);
+        }
+        assert!(color_idx > 4);
+    }
+
+    #[test]
+    fn close_neighbour_is_avoided() {
+        let mut idx = 7;
+        let neighbour = new_color(&mut idx) ^ 0x01_01_01;
+        let mut color_idx = 7;
+        let color = distinct_color(&mut color_idx, &[neighbour]);
+        assert!(color_distance(color, neighbour) > DISTINCT_THRESHOLD);
+    }
+
+    #[test]
+    fn distance_ignores_alpha() {
+        assert_eq!(color_distance(0xFF_12_34_56, 0x80_12_34_56), 0.);
+        assert!(color_distance(0xFF_FF_00_00, 0xFF_00_00_FF) > DISTINCT_THRESHOLD);
+    }
+}
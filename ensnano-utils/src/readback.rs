@@ -0,0 +1,92 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Read back the content of a texture rendered on the GPU, to export it as an image.
+
+use super::BufferDimensions;
+use std::convert::TryInto;
+use wgpu::{CommandEncoder, Device, Queue, Texture};
+
+/// Copy `texture`, whose format must be `Bgra8`, into a buffer, submit `encoder` and return the
+/// pixels of the texture in the Rgba format.
+///
+/// The rows of the returned image are not padded, i.e. each row is made of `4 * size.width`
+/// bytes.
+pub fn read_texture_as_rgba(
+    device: &Device,
+    queue: &Queue,
+    mut encoder: CommandEncoder,
+    texture: &Texture,
+    size: wgpu::Extent3d,
+) -> Vec<u8> {
+    let buffer_dimensions = BufferDimensions::new(size.width as usize, size.height as usize);
+    let buf_size = buffer_dimensions.padded_bytes_per_row * buffer_dimensions.height;
+    let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        size: buf_size as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+        label: Some("staging_buffer"),
+    });
+    let buffer_copy_view = wgpu::ImageCopyBuffer {
+        buffer: &staging_buffer,
+        layout: wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: (buffer_dimensions.padded_bytes_per_row as u32)
+                .try_into()
+                .ok(),
+            rows_per_image: None,
+        },
+    };
+    let origin = wgpu::Origin3d { x: 0, y: 0, z: 0 };
+    let texture_copy_view = wgpu::ImageCopyTexture {
+        texture,
+        mip_level: 0,
+        origin,
+        aspect: Default::default(),
+    };
+
+    encoder.copy_texture_to_buffer(texture_copy_view, buffer_copy_view, size);
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = staging_buffer.slice(..);
+    let buffer_future = buffer_slice.map_async(wgpu::MapMode::Read);
+    device.poll(wgpu::Maintain::Wait);
+
+    let pixels = async {
+        if let Ok(()) = buffer_future.await {
+            let pixels_slice = buffer_slice.get_mapped_range();
+            let mut pixels = Vec::with_capacity(
+                buffer_dimensions.unpadded_bytes_per_row * buffer_dimensions.height,
+            );
+            for row in pixels_slice.chunks(buffer_dimensions.padded_bytes_per_row) {
+                for chunk in row[..buffer_dimensions.unpadded_bytes_per_row].chunks(4) {
+                    // convert Bgra to Rgba
+                    pixels.push(chunk[2]);
+                    pixels.push(chunk[1]);
+                    pixels.push(chunk[0]);
+                    pixels.push(chunk[3]);
+                }
+            }
+            drop(pixels_slice);
+            staging_buffer.unmap();
+            pixels
+        } else {
+            panic!("could not read texture");
+        }
+    };
+    futures::executor::block_on(pixels)
+}
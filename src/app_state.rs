@@ -25,10 +25,13 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! Each component of ENSnano has specific needs and express them via its own `AppState` trait.
 
 use ensnano_design::{group_attributes::GroupPivot, BezierPathId};
-use ensnano_exports::{ExportResult, ExportType};
+use ensnano_exports::{fasta::SequencesText, ExportResult, ExportType};
 use ensnano_gui::UiSize;
 use ensnano_interactor::{
-    graphics::{Background3D, HBoundDisplay, RenderingMode},
+    graphics::{
+        Background3D, CameraMode, Colormap, DesignChanges, HBondFilter, HBoundDisplay,
+        IsolateSelection, ReferenceDesign, RenderingMode, StrandColoring,
+    },
     UnrootedRevolutionSurfaceDescriptor,
 };
 use ensnano_interactor::{
@@ -36,13 +39,14 @@ use ensnano_interactor::{
     SelectionMode, WidgetBasis,
 };
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 mod address_pointer;
 mod design_interactor;
 mod transitions;
 use crate::apply_update;
-use crate::controller::{LoadDesignError, SaveDesignError, SimulationRequest};
+use crate::controller::{LoadDesignError, SimulationRequest};
 use address_pointer::AddressPointer;
 use ensnano_design::{Design, SavingInformation};
 use ensnano_interactor::consts::APP_NAME;
@@ -51,9 +55,9 @@ use ensnano_organizer::GroupId;
 
 pub use design_interactor::controller::ErrOperation;
 pub use design_interactor::{
-    CopyOperation, DesignReader, InteractorNotification, PastePosition, PastingStatus,
-    ShiftOptimizationResult, ShiftOptimizerReader, SimulationInterface, SimulationReader,
-    SimulationTarget, SimulationUpdate,
+    CopyOperation, DesignReader, InteractorNotification, JsonDiagnostic, PastePosition,
+    PastingStatus, ShiftOptimizationResult, ShiftOptimizerReader, SimulationInterface,
+    SimulationReader, SimulationTarget, SimulationUpdate,
 };
 use design_interactor::{DesignInteractor, InteractorResult};
 
@@ -245,23 +249,40 @@ impl AppState {
         {
             path.set_extension(crate::consts::ENS_EXTENSION);
         }
-        Ok(Self(AddressPointer::new(AppState_ {
+        Ok(Self::with_imported_design(design_interactor, Some(path)))
+    }
+
+    /// Load the design stored in `backup`, as the new version of the design stored at
+    /// `design_path`.
+    pub fn import_backup(
+        backup: &PathBuf,
+        design_path: Option<PathBuf>,
+    ) -> Result<Self, LoadDesignError> {
+        let design_interactor = DesignInteractor::new_with_path(backup)?;
+        Ok(Self::with_imported_design(design_interactor, design_path))
+    }
+
+    fn with_imported_design(
+        design_interactor: DesignInteractor,
+        path_to_current_design: Option<PathBuf>,
+    ) -> Self {
+        Self(AddressPointer::new(AppState_ {
             design: AddressPointer::new(design_interactor),
             parameters: confy::load(APP_NAME, APP_NAME).unwrap_or_default(),
-            path_to_current_design: Some(path.clone()),
+            path_to_current_design,
             ..Default::default()
         }))
-        .updated())
+        .updated()
     }
 
-    pub fn save_design(
-        &mut self,
-        path: &PathBuf,
-        saving_info: SavingInformation,
-    ) -> Result<(), SaveDesignError> {
-        self.get_design_reader().save_design(path, saving_info)?;
-        self.0.make_mut().path_to_current_design = Some(path.clone());
-        Ok(())
+    /// Set the path to which the current design was saved.
+    pub fn set_path_to_current_design(&mut self, path: PathBuf) {
+        self.0.make_mut().path_to_current_design = Some(path);
+    }
+
+    /// Return a copy of the current design, ready to be saved
+    pub fn design_to_save(&self, saving_info: SavingInformation) -> Design {
+        self.get_design_reader().design_to_save(saving_info)
     }
 
     pub fn path_to_current_design(&self) -> Option<&PathBuf> {
@@ -415,8 +436,51 @@ impl AppState {
         self.0.design.get_design_reader()
     }
 
-    pub fn export(&self, export_path: &PathBuf, export_type: ExportType) -> ExportResult {
-        self.get_design_reader().export(export_path, export_type)
+    /// Export the design. If `selection_only` is true, only the strands of the current selection
+    /// are exported.
+    pub fn export(
+        &self,
+        export_path: &PathBuf,
+        export_type: ExportType,
+        selection_only: bool,
+    ) -> ExportResult {
+        let reader = self.get_design_reader();
+        let strands: Option<HashSet<usize>> = if selection_only {
+            Some(
+                ensnano_interactor::strands_of_selection(self.get_selection().as_ref(), &reader)
+                    .into_iter()
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        reader.export(export_path, export_type, strands.as_ref())
+    }
+
+    /// The sequences of the strands of the current selection, formatted to be copied to the
+    /// clipboard. Return `None` if none of these strands has a sequence.
+    pub fn selection_sequences(&self) -> Option<SequencesText> {
+        let reader = self.get_design_reader();
+        let strands =
+            ensnano_interactor::strands_of_selection(self.get_selection().as_ref(), &reader);
+        reader.sequences_text(&strands)
+    }
+
+    /// True if the arrows and PageUp/PageDown keys move the selected helices or grids in the 3D
+    /// view.
+    pub fn can_nudge_selection(&self) -> bool {
+        ensnano_interactor::snapping::can_nudge_selection(
+            self.get_selection().as_ref(),
+            self.0.action_mode,
+        )
+    }
+
+    /// True if the digits and Enter keys type a rotation angle in the 3D view.
+    pub fn can_enter_rotation_angle(&self) -> bool {
+        ensnano_interactor::snapping::can_enter_rotation_angle(
+            self.get_selection().as_ref(),
+            self.0.action_mode,
+        )
     }
 
     pub fn get_selection(&self) -> impl AsRef<[Selection]> {
@@ -434,6 +498,11 @@ impl AppState {
         *self = self.with_suggestion_parameters(source.0.parameters.suggestion_parameters.clone());
         *self = self.with_check_xovers_parameters(source.0.parameters.check_xover_paramters);
         *self = self.with_updated_parameters(|p| *p = source.0.parameters.clone());
+        // The reference design is not part of the history of the edited design
+        let mut new_state = (*self.0).clone();
+        new_state.reference_design = source.0.reference_design.clone();
+        new_state.design_changes = source.0.design_changes.clone();
+        *self = Self(AddressPointer::new(new_state));
     }
 
     pub fn with_check_xovers_parameters(
@@ -455,6 +524,38 @@ impl AppState {
         self.with_updated_parameters(|p| p.show_h_bonds = show)
     }
 
+    pub fn with_h_bond_filter(&self, filter: HBondFilter) -> Self {
+        self.with_updated_parameters(|p| p.h_bond_filter = filter)
+    }
+
+    pub fn with_show_2d_minimap(&self, show: bool) -> Self {
+        self.with_updated_parameters(|p| p.show_2d_minimap = show)
+    }
+
+    pub fn with_show_anchors(&self, show: bool) -> Self {
+        self.with_updated_parameters(|p| p.show_anchors = show)
+    }
+
+    pub fn with_local_phantoms(&self, local_phantoms: bool) -> Self {
+        self.with_updated_parameters(|p| p.local_phantoms = local_phantoms)
+    }
+
+    pub fn with_show_nucleotide_ruler(&self, show: bool) -> Self {
+        self.with_updated_parameters(|p| p.show_nucleotide_ruler = show)
+    }
+
+    pub fn with_full_detail(&self, full_detail: bool) -> Self {
+        self.with_updated_parameters(|p| p.full_detail = full_detail)
+    }
+
+    pub fn with_strand_coloring(&self, coloring: StrandColoring) -> Self {
+        self.with_updated_parameters(|p| p.strand_coloring = coloring)
+    }
+
+    pub fn with_strand_colormap(&self, colormap: Colormap) -> Self {
+        self.with_updated_parameters(|p| p.strand_colormap = colormap)
+    }
+
     pub fn with_show_bezier_paths(&self, show: bool) -> Self {
         self.with_updated_parameters(|p| p.show_bezier_paths = show)
     }
@@ -514,6 +615,32 @@ impl AppState {
         self.with_updated_parameters(|p| p.inverted_y_scroll = inverted)
     }
 
+    pub fn with_camera_animation_duration(&self, duration: f32) -> Self {
+        self.with_updated_parameters(|p| p.camera_animation_duration = duration)
+    }
+
+    pub fn with_autosave_interval(&self, interval: f32) -> Self {
+        self.with_updated_parameters(|p| p.autosave_interval = interval)
+    }
+
+    pub fn with_undo_history_length(&self, length: usize) -> Self {
+        self.with_updated_parameters(|p| p.undo_history_length = length)
+    }
+
+    pub fn with_remember_workspace(&self, remember: bool) -> Self {
+        self.with_updated_parameters(|p| p.remember_workspace = remember)
+    }
+
+    /// Return true if the layout of the application must be saved and restored with the design
+    pub fn remembers_workspace(&self) -> bool {
+        self.0.parameters.remember_workspace
+    }
+
+    /// The time between two backups of the design
+    pub fn get_autosave_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f32(60. * self.0.parameters.autosave_interval.max(0.))
+    }
+
     fn with_updated_parameters<F>(&self, update: F) -> Self
     where
         F: Fn(&mut AppStateParameters),
@@ -537,8 +664,9 @@ impl AppState {
     pub(super) fn optimize_shift(
         &mut self,
         reader: &mut dyn ShiftOptimizerReader,
+        scaffold_id: usize,
     ) -> Result<OkOperation, ErrOperation> {
-        let result = self.0.design.optimize_shift(reader);
+        let result = self.0.design.optimize_shift(reader, scaffold_id);
         self.handle_operation_result(result)
     }
 
@@ -609,6 +737,12 @@ impl AppState {
         }
     }
 
+    /// Set the pivot of the current selection, even if it already has one.
+    pub fn replace_current_group_pivot(&mut self, pivot: GroupPivot) {
+        *self.0.selection.pivot.write().unwrap() = Some(pivot);
+        *self.0.selection.old_pivot.write().unwrap() = Some(pivot);
+    }
+
     pub fn translate_group_pivot(&mut self, translation: ultraviolet::Vec3) {
         log::debug!("old pivot {:p}", Arc::as_ptr(&self.0.selection.old_pivot));
         log::info!("is {:?}", self.0.selection.old_pivot.read().unwrap());
@@ -657,6 +791,109 @@ impl AppState {
         Self(AddressPointer::new(ret))
     }
 
+    pub fn with_isolate_selection(self, isolate_selection: IsolateSelection) -> Self {
+        let mut ret = (*self.0).clone();
+        ret.isolate_selection = isolate_selection;
+        Self(AddressPointer::new(ret))
+    }
+
+    pub fn with_hide_idealized_design(self, hide: bool) -> Self {
+        let mut ret = (*self.0).clone();
+        ret.hide_idealized_design = hide;
+        Self(AddressPointer::new(ret))
+    }
+
+    /// Read the design stored at `path` so that it can be used as a reference design.
+    pub fn read_reference_design(path: &PathBuf) -> Result<ReferenceDesign, LoadDesignError> {
+        let design_interactor = DesignInteractor::new_with_path(path)?;
+        Ok(design_interactor.get_reference_design())
+    }
+
+    /// Set the reference design. If there already was a reference design, its isometry and
+    /// visibility are kept.
+    pub fn with_reference_design(self, reference_design: Option<ReferenceDesign>) -> Self {
+        let mut ret = (*self.0).clone();
+        let old_reference = ret.reference_design.take();
+        ret.reference_design = reference_design.map(|mut reference| {
+            if let Some(old) = old_reference {
+                reference.isometry = old.isometry;
+                reference.visible = old.visible;
+            }
+            reference
+        });
+        Self(AddressPointer::new(ret))
+    }
+
+    pub fn with_reference_design_visibility(self, visible: bool) -> Self {
+        let mut ret = (*self.0).clone();
+        if let Some(reference) = ret.reference_design.as_mut() {
+            reference.visible = visible;
+        }
+        Self(AddressPointer::new(ret))
+    }
+
+    pub fn with_reference_design_isometry(self, isometry: ultraviolet::Isometry3) -> Self {
+        let mut ret = (*self.0).clone();
+        if let Some(reference) = ret.reference_design.as_mut() {
+            reference.isometry = isometry;
+        }
+        Self(AddressPointer::new(ret))
+    }
+
+    /// Show or hide the changes made to the design since it was last saved.
+    pub fn with_design_changes_shown(self, shown: bool) -> Self {
+        let mut ret = (*self.0).clone();
+        ret.design_changes = if shown {
+            ret.design_changes.or_else(|| Some(Default::default()))
+        } else {
+            None
+        };
+        Self(AddressPointer::new(ret))
+    }
+
+    pub fn is_showing_design_changes(&self) -> bool {
+        self.0.design_changes.is_some()
+    }
+
+    pub fn get_design_changes(&self) -> Option<&DesignChanges> {
+        self.0.design_changes.as_ref().map(|c| &c.changes)
+    }
+
+    /// Return true if the changes are shown and were not computed between the current design and
+    /// the design of `saved_state`.
+    pub fn design_changes_are_outdated(&self, saved_state: &Self) -> bool {
+        match self.0.design_changes.as_ref() {
+            None => false,
+            Some(ShownDesignChanges {
+                source: Some((saved, current)),
+                ..
+            }) => {
+                saved.has_different_design_than(&saved_state.0.design)
+                    || current.has_different_design_than(&self.0.design)
+            }
+            Some(_) => true,
+        }
+    }
+
+    /// Recompute the changes between the design of `saved_state` and the current design.
+    pub fn with_updated_design_changes(self, saved_state: &Self) -> Self {
+        if !self.is_showing_design_changes() {
+            return self;
+        }
+        let mut ret = (*self.0).clone();
+        ret.design_changes = Some(ShownDesignChanges {
+            changes: self.0.design.get_changes_since(&saved_state.0.design),
+            source: Some((saved_state.0.design.clone(), self.0.design.clone())),
+        });
+        Self(AddressPointer::new(ret))
+    }
+
+    pub fn with_camera_mode(self, camera_mode: CameraMode) -> Self {
+        let mut ret = (*self.0).clone();
+        ret.camera_mode = camera_mode;
+        Self(AddressPointer::new(ret))
+    }
+
     pub(super) fn get_new_selection(&self) -> Option<Vec<Selection>> {
         self.0.design.get_new_selection()
     }
@@ -675,7 +912,35 @@ pub struct AppStateParameters {
     thick_helices: bool,
     scroll_sensitivity: f32,
     inverted_y_scroll: bool,
+    /// The duration, in seconds, of the animated camera movements
+    camera_animation_duration: f32,
+    /// The number of minutes between two backups of the design
+    autosave_interval: f32,
+    /// The maximum number of operations listed in the history panel on each side of the current
+    /// state
+    undo_history_length: usize,
+    /// Whether the layout of the application is saved next to the design and restored when the
+    /// design is opened
+    remember_workspace: bool,
     show_h_bonds: HBoundDisplay,
+    /// Which hydrogen bonds are drawn when `show_h_bonds` is not `HBoundDisplay::No`
+    h_bond_filter: HBondFilter,
+    /// Whether an overview of the whole design is drawn in a corner of the 2D view
+    show_2d_minimap: bool,
+    /// Whether the anchored nucleotides are marked in the 3D view
+    show_anchors: bool,
+    /// Whether the helices far from the camera are drawn with full detail in the 3D view
+    full_detail: bool,
+    /// Whether the phantom helices are only drawn on the grid that is under the cursor or
+    /// selected in the 3D view
+    local_phantoms: bool,
+    /// Whether the indices of the nucleotides are drawn along the selected helices in the 3D
+    /// view
+    show_nucleotide_ruler: bool,
+    /// The property according to which the non-scaffold strands are colored
+    strand_coloring: StrandColoring,
+    /// The colormap used when the strands are colored according to a property
+    strand_colormap: Colormap,
     show_bezier_paths: bool,
     pub ui_size: ensnano_gui::UiSize,
 }
@@ -692,7 +957,19 @@ impl Default for AppStateParameters {
             thick_helices: true,
             scroll_sensitivity: 0.0,
             inverted_y_scroll: false,
+            camera_animation_duration: crate::consts::DEFAULT_CAMERA_ANIMATION_DURATION,
+            autosave_interval: crate::consts::DEFAULT_AUTOSAVE_INTERVAL,
+            undo_history_length: crate::consts::DEFAULT_UNDO_HISTORY_LENGTH,
+            remember_workspace: true,
             show_h_bonds: HBoundDisplay::No,
+            h_bond_filter: HBondFilter::All,
+            show_2d_minimap: false,
+            show_anchors: true,
+            full_detail: false,
+            local_phantoms: false,
+            show_nucleotide_ruler: false,
+            strand_coloring: Default::default(),
+            strand_colormap: Default::default(),
             show_bezier_paths: false,
             ui_size: ensnano_gui::UiSize::default(),
         }
@@ -717,11 +994,31 @@ struct AppState_ {
     updated_once: bool,
     parameters: AppStateParameters,
     show_insertion_representents: bool,
+    /// Which elements are drawn in the 3D view
+    isolate_selection: IsolateSelection,
+    /// Only draw the additional structure of the design, if any, in the 3D view
+    hide_idealized_design: bool,
+    /// A design drawn next to the edited one for comparison
+    reference_design: Option<ReferenceDesign>,
+    /// The changes made to the design since it was last saved, if they are shown
+    design_changes: Option<ShownDesignChanges>,
+    /// How mouse drags rotate the 3D camera
+    camera_mode: CameraMode,
     exporting: bool,
     path_to_current_design: Option<PathBuf>,
     unrooted_surface: CurrentUnrootedSurface,
 }
 
+#[derive(Clone, Default)]
+struct ShownDesignChanges {
+    changes: DesignChanges,
+    /// The saved and edited designs from which `changes` were computed
+    source: Option<(
+        AddressPointer<DesignInteractor>,
+        AddressPointer<DesignInteractor>,
+    )>,
+}
+
 #[derive(Clone, Default)]
 struct CurrentUnrootedSurface {
     descriptor: Option<UnrootedRevolutionSurfaceDescriptor>,
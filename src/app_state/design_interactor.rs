@@ -21,7 +21,7 @@ use ensnano_design::{
     grid::GridId, group_attributes::GroupAttribute, BezierPathId, BezierPlaneDescriptor, Design,
     HelixCollection, InstanciatedPiecewiseBezier, Parameters,
 };
-use ensnano_exports::{ExportResult, ExportType};
+use ensnano_exports::{fasta::SequencesText, ExportResult, ExportType};
 use ensnano_interactor::{
     operation::Operation, DesignOperation, RevolutionSurfaceSystemDescriptor, RigidBodyConstants,
     Selection, SimulationState, StrandBuilder, SuggestionParameters,
@@ -39,11 +39,12 @@ pub use controller::{
 };
 
 use crate::{controller::SimulationRequest, gui::CurentOpState};
-pub(super) use controller::ErrOperation;
+pub(super) use controller::{ErrOperation, SequenceMismatch};
 use controller::{GridPresenter, HelixPresenter, OkOperation, RollPresenter, TwistPresenter};
 
 use std::sync::Arc;
 mod file_parsing;
+pub use file_parsing::JsonDiagnostic;
 
 /// The `DesignInteractor` handles all read/write operations on the design. It is a stateful struct
 /// so it is meant to be unexpansive to clone.
@@ -72,11 +73,12 @@ impl DesignInteractor {
     pub(super) fn optimize_shift(
         &self,
         reader: &mut dyn ShiftOptimizerReader,
+        scaffold_id: usize,
     ) -> Result<InteractorResult, ErrOperation> {
         let nucl_map = self.presenter.get_owned_nucl_collection();
         let result = self
             .controller
-            .optimize_shift(reader, nucl_map, &self.design);
+            .optimize_shift(reader, nucl_map, &self.design, scaffold_id);
         self.handle_operation_result(result)
     }
 
@@ -84,6 +86,17 @@ impl DesignInteractor {
         &self,
         operation: DesignOperation,
     ) -> Result<InteractorResult, ErrOperation> {
+        if let DesignOperation::ChangeSequence {
+            sequence,
+            strands,
+            force: false,
+        } = &operation
+        {
+            let mismatches = self.presenter.get_sequence_mismatches(strands, sequence);
+            if !mismatches.is_empty() {
+                return Err(ErrOperation::SequenceMismatches(mismatches));
+            }
+        }
         let result = self
             .controller
             .apply_operation(self.design.as_ref(), operation);
@@ -179,6 +192,10 @@ impl DesignInteractor {
             SimulationRequest::FinishRelaxation => {
                 controller::SimulationOperation::FinishRelaxation
             }
+            SimulationRequest::Step => controller::SimulationOperation::Step,
+            SimulationRequest::SetPinnedGroups(group_ids) => {
+                controller::SimulationOperation::SetPinnedGroups(group_ids)
+            }
         };
         let result = self
             .controller
@@ -317,6 +334,22 @@ impl DesignInteractor {
         self.presenter.current_design.parameters.unwrap_or_default()
     }
 
+    /// The geometry of the design, to draw it as a reference design
+    pub(super) fn get_reference_design(&self) -> ensnano_interactor::graphics::ReferenceDesign {
+        ensnano_interactor::graphics::ReferenceDesign::new(&self.presenter.current_design)
+    }
+
+    /// The changes made to the design of `saved` to obtain the current design
+    pub(super) fn get_changes_since(
+        &self,
+        saved: &Self,
+    ) -> ensnano_interactor::graphics::DesignChanges {
+        ensnano_interactor::graphics::DesignChanges::new(
+            &saved.presenter.current_design,
+            &self.presenter.current_design,
+        )
+    }
+
     pub(super) fn is_changing_color(&self) -> bool {
         self.controller.is_changing_color()
     }
@@ -397,31 +430,104 @@ pub struct DesignReader {
     controller: AddressPointer<Controller>,
 }
 
-use crate::controller::SaveDesignError;
+use std::collections::HashSet;
 use std::path::PathBuf;
 impl DesignReader {
-    pub(super) fn save_design(
-        &self,
-        path: &PathBuf,
-        saving_info: ensnano_design::SavingInformation,
-    ) -> Result<(), SaveDesignError> {
-        use std::io::Write;
+    pub(super) fn design_to_save(&self, saving_info: ensnano_design::SavingInformation) -> Design {
         let mut design = self.presenter.current_design.clone_inner();
         design.prepare_for_save(saving_info);
-        let json_content = serde_json::to_string_pretty(&design)?;
-        let mut f = std::fs::File::create(path)?;
-        f.write_all(json_content.as_bytes())?;
-        Ok(())
+        design
+    }
+
+    pub fn export(
+        &self,
+        export_path: &PathBuf,
+        export_type: ExportType,
+        strands: Option<&HashSet<usize>>,
+    ) -> ExportResult {
+        self.presenter.export(export_path, export_type, strands)
+    }
+
+    pub fn sequences_text(&self, strands: &[usize]) -> Option<SequencesText> {
+        self.presenter.sequences_text(strands)
     }
 
-    pub fn export(&self, export_path: &PathBuf, export_type: ExportType) -> ExportResult {
-        self.presenter.export(export_path, export_type)
+    pub fn xovers_between_helices(
+        &self,
+        helix_1: usize,
+        helix_2: usize,
+        parameters: &ensnano_design::XoverPlacementParameters,
+    ) -> Vec<(ensnano_design::Nucl, ensnano_design::Nucl)> {
+        self.presenter
+            .current_design
+            .xovers_between_helices(helix_1, helix_2, parameters)
+    }
+
+    /// Return the nucleotide that is the closest to `point`, in the model's coordinates, if it is
+    /// at distance at most `max_dist` from it.
+    pub fn closest_nucl(
+        &self,
+        point: ultraviolet::Vec3,
+        max_dist: f32,
+    ) -> Option<ensnano_design::Nucl> {
+        self.presenter
+            .get_spatial_index()
+            .closest_nucl(point, max_dist)
+    }
+
+    /// Return the nucleotides that are at distance at most `radius` from `point`, in the model's
+    /// coordinates, sorted by increasing distance.
+    pub fn nucls_within(&self, point: ultraviolet::Vec3, radius: f32) -> Vec<ensnano_design::Nucl> {
+        self.presenter
+            .get_spatial_index()
+            .nucls_within(point, radius)
     }
 
     pub fn get_strand_domain(&self, s_id: usize, d_id: usize) -> Option<&ensnano_design::Domain> {
         self.presenter.get_strand_domain(s_id, d_id)
     }
 
+    /// Return the nucleotides of the strands `strands` and the nucleotides lying on the helices
+    /// `helices`. If `ends_only` is true, only the 5' and 3' ends of the strands are returned.
+    pub fn get_nucls_of_strands_and_helices(
+        &self,
+        strands: &[usize],
+        helices: &[usize],
+        ends_only: bool,
+    ) -> Vec<ensnano_design::Nucl> {
+        let mut ret = Vec::new();
+        for (s_id, strand) in self.presenter.current_design.strands.iter() {
+            let nucls = if ends_only {
+                strand
+                    .get_5prime()
+                    .into_iter()
+                    .chain(strand.get_3prime())
+                    .collect()
+            } else {
+                strand.nucls()
+            };
+            if strands.contains(s_id) {
+                ret.extend(nucls);
+            } else {
+                ret.extend(nucls.into_iter().filter(|n| helices.contains(&n.helix)));
+            }
+        }
+        ret
+    }
+
+    /// Return the elements of the named selection `idx` that still exist in the design, and the
+    /// number of elements that were dropped because they do not exist anymore.
+    pub fn get_named_selection(&self, idx: usize) -> Option<(Vec<Selection>, usize)> {
+        self.presenter.get_named_selection(idx)
+    }
+
+    pub fn get_strained_xovers_leaving_grid(
+        &self,
+        grid_id: GridId,
+    ) -> Vec<ensnano_interactor::XoverStrain> {
+        self.presenter.get_strained_xovers_leaving_grid(grid_id)
+    }
+
     pub fn get_group_attributes(&self, group_id: GroupId) -> Option<&GroupAttribute> {
         self.presenter
             .current_design
@@ -473,10 +579,12 @@ mod tests {
     use ensnano_design::grid::HelixGridPosition;
     use ensnano_design::HelixCollection;
     use ensnano_design::{grid::GridDescriptor, Collection, DomainJunction, Nucl, Strand};
+    use ensnano_design::{BezierPlaneDescriptor, BezierPlaneId, BezierVertex};
     use ensnano_interactor::operation::GridHelixCreation;
     use ensnano_interactor::DesignReader;
+    use ensnano_interactor::{DesignTranslation, IsometryTarget, Referential};
     use std::path::PathBuf;
-    use ultraviolet::{Rotor3, Vec3};
+    use ultraviolet::{Rotor3, Vec2, Vec3};
 
     fn test_path(design_name: &'static str) -> PathBuf {
         let mut ret = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
@@ -1241,6 +1349,8 @@ mod tests {
                 grid_type: ensnano_design::grid::GridTypeDescr::Square { twist: None },
                 invisible: false,
                 bezier_vertex: None,
+                bounds: None,
+                phantom_length: None,
             }))
             .unwrap();
         app_state.update();
@@ -1260,6 +1370,8 @@ mod tests {
                 grid_type: ensnano_design::grid::GridTypeDescr::Square { twist: None },
                 invisible: false,
                 bezier_vertex: None,
+                bounds: None,
+                phantom_length: None,
             }))
             .unwrap();
         app_state.update();
@@ -1287,6 +1399,8 @@ mod tests {
                 grid_type: ensnano_design::grid::GridTypeDescr::Square { twist: None },
                 invisible: false,
                 bezier_vertex: None,
+                bounds: None,
+                phantom_length: None,
             }))
             .unwrap();
         app_state.update();
@@ -1301,6 +1415,305 @@ mod tests {
         assert_eq!(app_state.0.design.presenter.current_design.helices.len(), 1)
     }
 
+    /// A design with a square grid and one empty helix on it
+    fn one_empty_grid_helix() -> AppState {
+        let mut app_state = AppState::default();
+        app_state
+            .apply_design_op(DesignOperation::AddGrid(GridDescriptor {
+                position: Vec3::zero(),
+                orientation: Rotor3::identity(),
+                grid_type: ensnano_design::grid::GridTypeDescr::Square { twist: None },
+                invisible: false,
+                bezier_vertex: None,
+                bounds: None,
+                phantom_length: None,
+            }))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::AddGridHelix {
+                position: HelixGridPosition::from_grid_id_x_y(GridId::FreeGrid(0), 0, 0),
+                start: 0,
+                length: 0,
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+    }
+
+    #[test]
+    fn solo_grid_hides_the_other_grids_and_their_phantoms() {
+        let mut app_state = one_empty_grid_helix();
+        app_state
+            .apply_design_op(DesignOperation::AddGrid(GridDescriptor {
+                position: Vec3::unit_x() * 10.,
+                orientation: Rotor3::identity(),
+                grid_type: ensnano_design::grid::GridTypeDescr::Square { twist: None },
+                invisible: false,
+                bezier_vertex: None,
+                bounds: None,
+                phantom_length: None,
+            }))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::AddGridHelix {
+                position: HelixGridPosition::from_grid_id_x_y(GridId::FreeGrid(1), 0, 0),
+                start: 0,
+                length: 0,
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::SoloGrids {
+                grid_ids: vec![GridId::FreeGrid(1)],
+            })
+            .unwrap();
+        app_state.update();
+        let reader = app_state.get_design_reader();
+        let grids = Reader3d::get_grid_instances(&reader);
+        assert!(!grids.contains_key(&GridId::FreeGrid(0)));
+        assert!(grids.contains_key(&GridId::FreeGrid(1)));
+        assert_eq!(
+            reader.get_persistent_phantom_helices_id(),
+            HashSet::from([1])
+        );
+        assert_eq!(app_state.0.design.design.helices.len(), 2);
+
+        app_state
+            .apply_design_op(DesignOperation::SetGridVisibility {
+                grid_ids: vec![GridId::FreeGrid(0)],
+                visible: true,
+            })
+            .unwrap();
+        app_state.update();
+        let reader = app_state.get_design_reader();
+        assert_eq!(Reader3d::get_grid_instances(&reader).len(), 2);
+        assert!(app_state
+            .apply_design_op(DesignOperation::SetGridVisibility {
+                grid_ids: vec![GridId::FreeGrid(2)],
+                visible: false,
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn make_duplex_adds_two_antiparallel_strands() {
+        let mut app_state = one_empty_grid_helix();
+        assert!(matches!(
+            app_state
+                .apply_design_op(DesignOperation::MakeDuplex {
+                    helix: 0,
+                    start: 5,
+                    length: 10,
+                })
+                .unwrap(),
+            TopOkOperation::Undoable { .. }
+        ));
+        app_state.update();
+        let strands = &app_state.0.design.design.strands;
+        assert_eq!(strands.len(), 2);
+        let forward: Vec<bool> = strands
+            .values()
+            .map(|s| {
+                assert_eq!(s.length(), 10);
+                s.get_5prime().unwrap().forward
+            })
+            .collect();
+        assert!(forward.contains(&true) && forward.contains(&false));
+        let colors: Vec<u32> = strands.values().map(|s| s.color).collect();
+        assert_ne!(colors[0], colors[1]);
+    }
+
+    #[test]
+    fn strands_sharing_a_helix_get_distinct_colors() {
+        let mut app_state = one_empty_grid_helix();
+        for start in [0, 20] {
+            app_state
+                .apply_design_op(DesignOperation::MakeDuplex {
+                    helix: 0,
+                    start,
+                    length: 10,
+                })
+                .unwrap();
+            app_state.update();
+        }
+        let colors: Vec<u32> = app_state
+            .0
+            .design
+            .design
+            .strands
+            .values()
+            .map(|s| s.color)
+            .collect();
+        assert_eq!(colors.len(), 4);
+        for (i, c1) in colors.iter().enumerate() {
+            for c2 in colors[i + 1..].iter() {
+                assert!(crate::utils::color_distance(*c1, *c2) > 10.);
+            }
+        }
+    }
+
+    #[test]
+    fn make_duplex_refuses_occupied_positions() {
+        let mut app_state = one_empty_grid_helix();
+        app_state
+            .apply_design_op(DesignOperation::MakeDuplex {
+                helix: 0,
+                start: 5,
+                length: 10,
+            })
+            .unwrap();
+        app_state.update();
+        assert!(app_state
+            .apply_design_op(DesignOperation::MakeDuplex {
+                helix: 0,
+                start: 0,
+                length: 6,
+            })
+            .is_err());
+        app_state
+            .apply_design_op(DesignOperation::MakeDuplex {
+                helix: 0,
+                start: 15,
+                length: 5,
+            })
+            .unwrap();
+        app_state.update();
+        assert_eq!(app_state.0.design.design.strands.len(), 4);
+    }
+
+    #[test]
+    fn strands_that_keep_their_color_are_not_recolored() {
+        let mut app_state = pastable_design();
+        let color = app_state.0.design.design.strands.get(&0).unwrap().color;
+        app_state
+            .apply_design_op(DesignOperation::SetStrandKeepColor {
+                s_id: 0,
+                keep_color: true,
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::RecolorStaples(Default::default()))
+            .unwrap();
+        app_state.update();
+        let strand = app_state.0.design.design.strands.get(&0).unwrap();
+        assert!(strand.keep_color);
+        assert_eq!(strand.color, color);
+    }
+
+    #[test]
+    fn cutting_a_strand_copies_its_note() {
+        let mut app_state = pastable_design();
+        app_state
+            .apply_design_op(DesignOperation::SetStrandNote {
+                s_id: 0,
+                note: Some("biotin-modified".into()),
+            })
+            .unwrap();
+        app_state.update();
+        app_state.finish_operation();
+        let strand = app_state.0.design.design.strands.get(&0).unwrap().clone();
+        let nucl = strand
+            .domains
+            .iter()
+            .find_map(|d| match d {
+                ensnano_design::Domain::HelixDomain(dom) if dom.end - dom.start > 1 => Some(Nucl {
+                    helix: dom.helix,
+                    position: (dom.start + dom.end) / 2,
+                    forward: dom.forward,
+                }),
+                _ => None,
+            })
+            .unwrap();
+        app_state
+            .apply_design_op(DesignOperation::Cut { nucl, s_id: 0 })
+            .unwrap();
+        app_state.update();
+        let strands = &app_state.0.design.design.strands;
+        assert_eq!(strands.len(), 2);
+        for strand in strands.values() {
+            assert_eq!(strand.note.as_deref(), Some("biotin-modified"));
+        }
+    }
+
+    fn hyperboloid_request(radius: usize, shift: f32) -> ensnano_interactor::HyperboloidRequest {
+        ensnano_interactor::HyperboloidRequest {
+            radius,
+            length: 30.,
+            shift,
+            radius_shift: 0.2,
+            nb_turn: 0.,
+        }
+    }
+
+    /// A design with a finalized hyperboloid grid of 10 helices
+    fn finalized_hyperboloid() -> AppState {
+        use ensnano_interactor::HyperboloidOperation;
+        let mut app_state = AppState::default();
+        app_state
+            .apply_design_op(DesignOperation::HyperboloidOperation(
+                HyperboloidOperation::New {
+                    request: hyperboloid_request(10, 0.),
+                    position: Vec3::zero(),
+                    orientation: Rotor3::identity(),
+                },
+            ))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::HyperboloidOperation(
+                HyperboloidOperation::Finalize,
+            ))
+            .unwrap();
+        app_state.update();
+        app_state
+    }
+
+    fn strands_nucls(design: &Design) -> Vec<(usize, Vec<Nucl>)> {
+        design
+            .strands
+            .iter()
+            .map(|(s_id, s)| (*s_id, s.nucls()))
+            .collect()
+    }
+
+    #[test]
+    fn finalized_hyperboloid_can_be_edited() {
+        let mut app_state = finalized_hyperboloid();
+        let strands_before = strands_nucls(&app_state.0.design.presenter.current_design);
+        app_state
+            .apply_design_op(DesignOperation::UpdateHyperboloid {
+                grid_id: GridId::FreeGrid(0),
+                request: hyperboloid_request(12, 0.5),
+            })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        assert_eq!(design.helices.len(), 10);
+        assert_eq!(strands_nucls(design), strands_before);
+        let grid = design
+            .free_grids
+            .get_from_g_id(&GridId::FreeGrid(0))
+            .unwrap();
+        assert!(matches!(
+            grid.grid_type,
+            ensnano_design::grid::GridTypeDescr::Hyperboloid { radius: 12, .. }
+        ));
+    }
+
+    #[test]
+    fn shrinking_hyperboloid_onto_existing_helices_fails() {
+        let mut app_state = finalized_hyperboloid();
+        assert!(app_state
+            .apply_design_op(DesignOperation::UpdateHyperboloid {
+                grid_id: GridId::FreeGrid(0),
+                request: hyperboloid_request(5, 0.),
+            })
+            .is_err());
+    }
+
     #[test]
     fn copy_creates_clipboard() {
         let mut app_state = pastable_design();
@@ -1659,10 +2072,6 @@ mod tests {
     fn correct_staples_no_scaffold_shift() {
         let mut app_state = design_for_sequence_testing();
         let sequence = std::fs::read_to_string(test_path("seq_test.txt")).unwrap();
-        app_state
-            .apply_design_op(DesignOperation::SetScaffoldSequence { sequence, shift: 0 })
-            .unwrap();
-        app_state.update();
         let s_id = app_state
             .get_design_reader()
             .get_id_of_strand_containing_nucl(&Nucl {
@@ -1672,7 +2081,15 @@ mod tests {
             })
             .unwrap();
         app_state
-            .apply_design_op(DesignOperation::SetScaffoldId(Some(s_id)))
+            .apply_design_op(DesignOperation::SetScaffoldIds(vec![s_id]))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldSequence {
+                scaffold_id: s_id,
+                sequence,
+                shift: 0,
+            })
             .unwrap();
         app_state.update();
         let stapples = app_state.get_design_reader().presenter.get_staples();
@@ -1691,10 +2108,6 @@ mod tests {
     fn correct_staples_scaffold_shift() {
         let mut app_state = design_for_sequence_testing();
         let sequence = std::fs::read_to_string(test_path("seq_test.txt")).unwrap();
-        app_state
-            .apply_design_op(DesignOperation::SetScaffoldSequence { sequence, shift: 3 })
-            .unwrap();
-        app_state.update();
         let s_id = app_state
             .get_design_reader()
             .get_id_of_strand_containing_nucl(&Nucl {
@@ -1704,7 +2117,15 @@ mod tests {
             })
             .unwrap();
         app_state
-            .apply_design_op(DesignOperation::SetScaffoldId(Some(s_id)))
+            .apply_design_op(DesignOperation::SetScaffoldIds(vec![s_id]))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldSequence {
+                scaffold_id: s_id,
+                sequence,
+                shift: 3,
+            })
             .unwrap();
         app_state.update();
         let stapples = app_state.get_design_reader().presenter.get_staples();
@@ -1719,6 +2140,156 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sequences_are_set_per_scaffold() {
+        let mut app_state = design_for_sequence_testing();
+        let sequence = std::fs::read_to_string(test_path("seq_test.txt")).unwrap();
+        let reader = app_state.get_design_reader();
+        let first_scaffold = reader
+            .get_id_of_strand_containing_nucl(&Nucl {
+                helix: 1,
+                position: 0,
+                forward: true,
+            })
+            .unwrap();
+        let second_scaffold = reader
+            .get_id_of_strand_containing_nucl(&Nucl {
+                helix: 1,
+                position: 7,
+                forward: false,
+            })
+            .unwrap();
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldIds(vec![
+                first_scaffold,
+                second_scaffold,
+            ]))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldSequence {
+                scaffold_id: first_scaffold,
+                sequence,
+                shift: 0,
+            })
+            .unwrap();
+        app_state.update();
+        assert!(app_state
+            .apply_design_op(DesignOperation::SetScaffoldShift {
+                scaffold_id: second_scaffold,
+                shift: 1,
+            })
+            .is_err());
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldShift {
+                scaffold_id: first_scaffold,
+                shift: 3,
+            })
+            .unwrap();
+        app_state.update();
+        let infos = app_state.get_design_reader().get_scaffolds_info();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].id, first_scaffold);
+        assert_eq!(infos[0].shift, Some(3));
+        assert_eq!(infos[1].shift, None);
+
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldIds(vec![second_scaffold]))
+            .unwrap();
+        app_state.update();
+        let design = app_state
+            .get_design_reader()
+            .presenter
+            .current_design
+            .clone();
+        assert_eq!(design.scaffold_ids, vec![second_scaffold]);
+        assert!(design.scaffold_sequences.is_empty());
+    }
+
+    #[test]
+    fn staple_sequence_is_checked_against_scaffold() {
+        let mut app_state = design_for_sequence_testing();
+        let sequence = std::fs::read_to_string(test_path("seq_test.txt")).unwrap();
+        let reader = app_state.get_design_reader();
+        let scaffold_id = reader
+            .get_id_of_strand_containing_nucl(&Nucl {
+                helix: 1,
+                position: 0,
+                forward: true,
+            })
+            .unwrap();
+        let staple_id = reader
+            .get_id_of_strand_containing_nucl(&Nucl {
+                helix: 1,
+                position: 7,
+                forward: false,
+            })
+            .unwrap();
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldIds(vec![scaffold_id]))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::SetScaffoldSequence {
+                scaffold_id,
+                sequence,
+                shift: 0,
+            })
+            .unwrap();
+        app_state.update();
+
+        let staple_sequence = |app_state: &AppState| {
+            app_state
+                .get_design_reader()
+                .presenter
+                .get_staples()
+                .into_iter()
+                .find(|s| s.name.contains("5':h1:nt7"))
+                .unwrap()
+                .sequence
+        };
+        let change_sequence = |sequence: &str, force: bool| DesignOperation::ChangeSequence {
+            sequence: sequence.to_owned(),
+            strands: vec![staple_id],
+            force,
+        };
+
+        app_state
+            .apply_design_op(change_sequence("CCAATTTT", false))
+            .unwrap();
+        app_state.update();
+        assert_eq!(staple_sequence(&app_state), "CCAA TTTT");
+
+        let err = app_state
+            .apply_design_op(change_sequence("GCAATTTA", false))
+            .err()
+            .unwrap();
+        if let ErrOperation::SequenceMismatches(mismatches) = err {
+            let positions: Vec<(usize, char, char)> = mismatches
+                .iter()
+                .map(|m| (m.position, m.expected, m.found))
+                .collect();
+            assert_eq!(positions, vec![(0, 'C', 'G'), (7, 'T', 'A')]);
+        } else {
+            panic!("Expected sequence mismatches, got {:?}", err);
+        }
+
+        // Explicit sequences take precedence over the basis derived from the scaffold
+        app_state
+            .apply_design_op(change_sequence("GCAATTTA", true))
+            .unwrap();
+        app_state.update();
+        assert_eq!(staple_sequence(&app_state), "GCAA TTTA");
+
+        app_state
+            .apply_design_op(DesignOperation::ClearSequence {
+                strands: vec![staple_id],
+            })
+            .unwrap();
+        app_state.update();
+        assert_eq!(staple_sequence(&app_state), "CCAA TTTT");
+    }
+
     /// A design with two strands h1: 0 -> 5 and h1: 6 -> 10
     fn two_neighbour_one_helix() -> AppState {
         let path = test_path("two_neighbour_strands.ens");
@@ -1891,6 +2462,329 @@ mod tests {
 
         assert_good_strand(strand, "[H1: 0 -> 10] [@20] [H2: 0 <- 10]");
     }
+
+    #[test]
+    fn operations_on_members_of_locked_groups_are_rejected() {
+        // A design with one strand h1: 0 -> 5 ; h2: 0 <- 5
+        let mut app_state = one_xover();
+        let s_id = *app_state
+            .0
+            .design
+            .presenter
+            .current_design
+            .strands
+            .keys()
+            .next()
+            .unwrap();
+        let group_id: ensnano_organizer::GroupId = rand::random();
+        let tree = ensnano_design::OrganizerTree::Node {
+            name: String::from("root"),
+            childrens: vec![ensnano_design::OrganizerTree::Node {
+                name: String::from("Locked staples"),
+                childrens: vec![ensnano_design::OrganizerTree::Leaf(
+                    ensnano_design::elements::DnaElementKey::Strand(s_id),
+                )],
+                expanded: true,
+                id: Some(group_id),
+            }],
+            expanded: true,
+            id: None,
+        };
+        app_state
+            .apply_design_op(DesignOperation::SetOrganizerTree(tree))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::SetGroupLocked {
+                group_id,
+                locked: true,
+            })
+            .unwrap();
+        app_state.update();
+
+        let cut = DesignOperation::Cut {
+            nucl: Nucl {
+                helix: 1,
+                position: 3,
+                forward: true,
+            },
+            s_id,
+        };
+        match app_state.apply_design_op(cut.clone()) {
+            Err(ErrOperation::GroupIsLocked(name)) => assert_eq!(name, "Locked staples"),
+            result => panic!("Expected the group to be locked, got {:?}", result.err()),
+        }
+        // Operations that do not modify the members of the group are still possible
+        app_state
+            .apply_design_op(DesignOperation::SetRainbowScaffold(true))
+            .unwrap();
+
+        app_state
+            .apply_design_op(DesignOperation::SetGroupLocked {
+                group_id,
+                locked: false,
+            })
+            .unwrap();
+        app_state.update();
+        app_state.apply_design_op(cut).unwrap();
+    }
+
+    #[test]
+    fn nucleotide_positions_follow_the_roll_of_helices() {
+        // A design with one strand h1: 0 -> 5 ; h2: 0 <- 5
+        let mut app_state = one_xover();
+        let nucl = Nucl {
+            helix: 1,
+            position: 3,
+            forward: true,
+        };
+        let position_before = app_state
+            .get_design_reader()
+            .get_position_of_nucl_on_helix(nucl, Referential::Model, false)
+            .unwrap();
+        let roll = app_state
+            .0
+            .design
+            .presenter
+            .current_design
+            .helices
+            .get(&1)
+            .unwrap()
+            .roll
+            + std::f32::consts::FRAC_PI_2;
+        app_state
+            .apply_design_op(DesignOperation::SetRollHelices {
+                helices: vec![1],
+                roll,
+            })
+            .unwrap();
+        app_state.update();
+
+        // The nucleotide has made a quarter turn around the axis of the helix
+        let reader = app_state.get_design_reader();
+        let position_after = reader
+            .get_position_of_nucl_on_helix(nucl, Referential::Model, false)
+            .unwrap();
+        let axis = reader
+            .get_position_of_nucl_on_helix(nucl, Referential::Model, true)
+            .unwrap();
+        let (radial_before, radial_after) = (position_before - axis, position_after - axis);
+        assert!(radial_before.dot(radial_after).abs() < 1e-3);
+        assert!((radial_before.mag() - radial_after.mag()).abs() < 1e-4);
+    }
+
+    /// Put `keys` in a new locked group named "Locked group"
+    fn lock_in_new_group(
+        app_state: &mut AppState,
+        keys: Vec<ensnano_design::elements::DnaElementKey>,
+    ) {
+        let group_id: ensnano_organizer::GroupId = rand::random();
+        let tree = ensnano_design::OrganizerTree::Node {
+            name: String::from("root"),
+            childrens: vec![ensnano_design::OrganizerTree::Node {
+                name: String::from("Locked group"),
+                childrens: keys
+                    .into_iter()
+                    .map(ensnano_design::OrganizerTree::Leaf)
+                    .collect(),
+                expanded: true,
+                id: Some(group_id),
+            }],
+            expanded: true,
+            id: None,
+        };
+        app_state
+            .apply_design_op(DesignOperation::SetOrganizerTree(tree))
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::SetGroupLocked {
+                group_id,
+                locked: true,
+            })
+            .unwrap();
+        app_state.update();
+    }
+
+    #[test]
+    fn cut_on_a_locked_helix_is_rejected() {
+        // A design with one strand h1: 0 -> 5 ; h2: 0 <- 5
+        let mut app_state = one_xover();
+        let s_id = *app_state
+            .0
+            .design
+            .presenter
+            .current_design
+            .strands
+            .keys()
+            .next()
+            .unwrap();
+        // Only the helix is locked, not the strand
+        lock_in_new_group(
+            &mut app_state,
+            vec![ensnano_design::elements::DnaElementKey::Helix(1)],
+        );
+        let cut = DesignOperation::Cut {
+            nucl: Nucl {
+                helix: 1,
+                position: 3,
+                forward: true,
+            },
+            s_id,
+        };
+        match app_state.apply_design_op(cut) {
+            Err(ErrOperation::GroupIsLocked(name)) => assert_eq!(name, "Locked group"),
+            result => panic!("Expected the group to be locked, got {:?}", result.err()),
+        }
+    }
+
+    #[test]
+    fn translation_of_a_locked_grid_is_rejected() {
+        let translate_grid = || {
+            DesignOperation::Translation(DesignTranslation {
+                translation: Vec3::unit_x(),
+                target: IsometryTarget::Grids(vec![GridId::FreeGrid(0)]),
+                group_id: None,
+            })
+        };
+
+        let mut app_state = one_empty_grid_helix();
+        lock_in_new_group(
+            &mut app_state,
+            vec![ensnano_design::elements::DnaElementKey::Grid(0)],
+        );
+        match app_state.apply_design_op(translate_grid()) {
+            Err(ErrOperation::GroupIsLocked(name)) => assert_eq!(name, "Locked group"),
+            result => panic!("Expected the group to be locked, got {:?}", result.err()),
+        }
+
+        // Moving the grid moves its helices, so it is also rejected if only a helix of the grid
+        // is locked
+        let mut app_state = one_empty_grid_helix();
+        let h_id = *app_state
+            .0
+            .design
+            .presenter
+            .current_design
+            .helices
+            .keys()
+            .next()
+            .unwrap();
+        lock_in_new_group(
+            &mut app_state,
+            vec![ensnano_design::elements::DnaElementKey::Helix(h_id)],
+        );
+        match app_state.apply_design_op(translate_grid()) {
+            Err(ErrOperation::GroupIsLocked(name)) => assert_eq!(name, "Locked group"),
+            result => panic!("Expected the group to be locked, got {:?}", result.err()),
+        }
+    }
+
+    /// A design with two bezier planes and a path with two vertices on the first plane
+    fn design_with_bezier_planes() -> AppState {
+        let mut app_state = AppState::default();
+        for _ in 0..2 {
+            app_state
+                .apply_design_op(DesignOperation::AddBezierPlane {
+                    desc: BezierPlaneDescriptor::default(),
+                })
+                .unwrap();
+            app_state.update();
+        }
+        app_state
+            .apply_design_op(DesignOperation::CreateBezierPath {
+                first_vertex: BezierVertex::new(BezierPlaneId(0), Vec2::zero()),
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+            .apply_design_op(DesignOperation::AppendVertexToPath {
+                path_id: ensnano_design::BezierPathId(0),
+                vertex: BezierVertex::new(BezierPlaneId(0), Vec2::new(10., 0.)),
+            })
+            .unwrap();
+        app_state.update();
+        app_state
+    }
+
+    #[test]
+    fn rename_bezier_plane() {
+        let mut app_state = design_with_bezier_planes();
+        app_state
+            .apply_design_op(DesignOperation::RenameBezierPlane {
+                plane_id: BezierPlaneId(1),
+                name: "Top".into(),
+            })
+            .unwrap();
+        app_state.update();
+        let planes = &app_state.0.design.presenter.current_design.bezier_planes;
+        assert_eq!(planes.get(&BezierPlaneId(1)).unwrap().name, "Top");
+        assert_eq!(
+            planes
+                .get(&BezierPlaneId(0))
+                .unwrap()
+                .name_or_default(BezierPlaneId(0)),
+            "Plane 0"
+        );
+    }
+
+    #[test]
+    fn cannot_delete_used_bezier_plane() {
+        let mut app_state = design_with_bezier_planes();
+        match app_state.apply_design_op(DesignOperation::DeleteBezierPlane {
+            plane_id: BezierPlaneId(0),
+        }) {
+            Err(ErrOperation::BezierPlaneIsUsed { plane_id, vertices }) => {
+                assert_eq!(plane_id, BezierPlaneId(0));
+                assert_eq!(vertices.len(), 2);
+            }
+            result => panic!("Expected the plane to be used, got {:?}", result.err()),
+        }
+        app_state
+            .apply_design_op(DesignOperation::DeleteBezierPlane {
+                plane_id: BezierPlaneId(1),
+            })
+            .unwrap();
+        app_state.update();
+        let planes = &app_state.0.design.presenter.current_design.bezier_planes;
+        assert_eq!(planes.len(), 1);
+        assert!(planes.contains_key(&BezierPlaneId(0)));
+    }
+
+    #[test]
+    fn duplicate_bezier_plane_with_its_paths() {
+        let mut app_state = design_with_bezier_planes();
+        let offset = Vec3::new(0., 0., 20.);
+        app_state
+            .apply_design_op(DesignOperation::DuplicateBezierPlane {
+                plane_id: BezierPlaneId(0),
+                offset,
+                copy_paths: true,
+            })
+            .unwrap();
+        app_state.update();
+        let design = &app_state.0.design.presenter.current_design;
+        let copy = design
+            .bezier_planes
+            .get(&BezierPlaneId(2))
+            .expect("No copy of the plane");
+        assert!((copy.position - offset).mag() < 1e-6);
+        assert_eq!(design.bezier_paths.len(), 2);
+        assert_eq!(
+            design
+                .bezier_paths
+                .vertices_on_plane(BezierPlaneId(2))
+                .len(),
+            2
+        );
+        assert_eq!(
+            design
+                .bezier_paths
+                .vertices_on_plane(BezierPlaneId(0))
+                .len(),
+            2
+        );
+    }
 }
 
 #[allow(clippy::large_enum_variant)] // We don't create many instances of this type
@@ -19,28 +19,35 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::{NuclCollection, SimulationUpdate};
 use crate::app_state::AddressPointer;
 use ensnano_design::{
+    design_operations::{
+        strands_of_group, DuplicationPattern, RecolorParameters, RecolorScope, ScaffoldRoute,
+        ScaffoldRoutingParameters, StapleFillingParameters,
+    },
     elements::{DnaAttribute, DnaElementKey},
     grid::{
-        Edge, FreeGridId, GridDescriptor, GridId, GridObject, GridPosition, GridTypeDescr,
-        HelixGridPosition, Hyperboloid,
+        Edge, FreeGridId, GridBounds, GridDescriptor, GridId, GridObject, GridPosition,
+        GridTypeDescr, HelixGridPosition, Hyperboloid,
     },
     group_attributes::GroupPivot,
-    mutate_in_arc, BezierEnd, BezierPathId, BezierPlaneDescriptor, BezierVertex, BezierVertexId,
-    CameraId, Collection, CurveDescriptor, Design, Domain, DomainJunction, Helices, Helix,
-    HelixCollection, Nucl, Strand, Strands, UpToDateDesign,
+    modifications::Modification,
+    mutate_in_arc, BezierEnd, BezierPath, BezierPathId, BezierPlaneDescriptor, BezierPlaneId,
+    BezierVertex, BezierVertexId, CameraId, Collection, CurveDescriptor, Design, Domain,
+    DomainJunction, External3DObjectAnchor, External3DObjectId, Helices, Helix, HelixCollection,
+    Nucl, ScaffoldSequence, Strand, Strands, UpToDateDesign,
 };
 use ensnano_gui::ClipboardContent;
 pub use ensnano_interactor::PastingStatus;
 use ensnano_interactor::{
     operation::{Operation, TranslateBezierPathVertex},
-    BezierControlPoint, HyperboloidOperation, NewBezierTengentVector, SimulationState,
+    BezierControlPoint, HyperboloidOperation, HyperboloidRequest, NewBezierTengentVector,
+    SimulationState,
 };
 use ensnano_interactor::{
     BezierPlaneHomothethy, DesignOperation, DesignRotation, DesignTranslation, DomainIdentifier,
     IsometryTarget, NeighbourDescriptor, NeighbourDescriptorGiver, Selection, StrandBuilder,
 };
-use ensnano_organizer::GroupId;
-use std::collections::BTreeMap;
+use ensnano_organizer::{GroupId, OrganizerTree};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::{borrow::Cow, path::PathBuf};
 
@@ -91,6 +98,22 @@ impl Controller {
         color
     }
 
+    /// A color for a new strand with domains on `helices`, as different as possible from the
+    /// colors of the strands that already have a domain on one of these helices.
+    fn new_strand_color(color_idx: &mut usize, design: &Design, helices: &BTreeSet<usize>) -> u32 {
+        let neighbours: Vec<u32> = design
+            .strands
+            .values()
+            .filter(|s| {
+                s.domains
+                    .iter()
+                    .any(|d| matches!(d, Domain::HelixDomain(dom) if helices.contains(&dom.helix)))
+            })
+            .map(|s| s.color)
+            .collect();
+        crate::utils::distinct_color(color_idx, &neighbours)
+    }
+
     /// Apply an operation to the design. This will either produce a modified copy of the design,
     /// or result in an error that could be shown to the user to explain why the requested
     /// operation could no be applied.
@@ -109,16 +132,25 @@ impl Controller {
             OperationCompatibility::FinishFirst => return Err(ErrOperation::FinishFirst),
             OperationCompatibility::Compatible => (),
         }
+        if let Some(group_name) = locked_group_modified_by(design, &operation) {
+            return Err(ErrOperation::GroupIsLocked(group_name));
+        }
         log::debug!("applicable");
         let label = operation.label();
         let mut ret = match operation {
-            DesignOperation::RecolorStaples => Ok(self.ok_apply(Self::recolor_stapples, design)),
-            DesignOperation::SetScaffoldSequence { sequence, shift } => Ok(self.ok_apply(
-                |ctrl, design| ctrl.set_scaffold_sequence(design, sequence, shift),
+            DesignOperation::RecolorStaples(parameters) => {
+                self.apply(|c, d| c.recolor_stapples(d, parameters), design)
+            }
+            DesignOperation::SetScaffoldSequence {
+                scaffold_id,
+                sequence,
+                shift,
+            } => self.apply(
+                |ctrl, design| ctrl.set_scaffold_sequence(design, scaffold_id, sequence, shift),
                 design,
-            )),
-            DesignOperation::SetScaffoldShift(shift) => {
-                Ok(self.ok_apply(|c, d| c.set_scaffold_shift(d, shift), design))
+            ),
+            DesignOperation::SetScaffoldShift { scaffold_id, shift } => {
+                self.apply(|c, d| c.set_scaffold_shift(d, scaffold_id, shift), design)
             }
             DesignOperation::HelicesToGrid(selection) => {
                 self.apply(|c, d| c.turn_selection_into_grid(d, selection), design)
@@ -139,6 +171,12 @@ impl Controller {
             DesignOperation::SetSmallSpheres { grid_ids, small } => {
                 Ok(self.ok_apply(|c, d| c.set_small_spheres(d, grid_ids, small), design))
             }
+            DesignOperation::SetGridVisibility { grid_ids, visible } => {
+                self.apply(|c, d| c.set_grid_visibility(d, grid_ids, visible), design)
+            }
+            DesignOperation::SoloGrids { grid_ids } => {
+                Ok(self.ok_apply(|c, d| c.solo_grids(d, grid_ids), design))
+            }
             DesignOperation::SnapHelices {
                 pivots,
                 translation,
@@ -179,6 +217,11 @@ impl Controller {
                 length,
                 start,
             } => self.apply(|c, d| c.add_grid_helix(d, position, start, length), design),
+            DesignOperation::MakeDuplex {
+                helix,
+                start,
+                length,
+            } => self.apply(|c, d| c.make_duplex(d, helix, start, length), design),
             DesignOperation::AddTwoPointsBezier { start, end } => {
                 self.apply(|c, d| c.add_two_points_bezier(d, start, end), design)
             }
@@ -207,22 +250,28 @@ impl Controller {
             DesignOperation::RmXovers { xovers } => {
                 self.apply(|c, d| c.delete_xovers(d, &xovers), design)
             }
-            DesignOperation::SetScaffoldId(s_id) => Ok(self.ok_apply(
-                |_, mut d| {
-                    d.scaffold_id = s_id;
-                    d
-                },
-                design,
-            )),
+            DesignOperation::SetScaffoldIds(s_ids) => {
+                Ok(self.ok_apply(|c, d| c.set_scaffold_ids(d, s_ids), design))
+            }
             DesignOperation::HyperboloidOperation(op) => {
                 self.apply(|c, d| c.apply_hyperbolid_operation(d, op), design)
             }
+            DesignOperation::UpdateHyperboloid { grid_id, request } => {
+                self.apply(|c, d| c.update_hyperboloid(d, grid_id, request), design)
+            }
             DesignOperation::SetRollHelices { helices, roll } => {
                 self.apply(|c, d| c.set_roll_helices(d, helices, roll), design)
             }
             DesignOperation::SetVisibilityHelix { helix, visible } => {
                 self.apply(|c, d| c.set_visiblity_helix(d, helix, visible), design)
             }
+            DesignOperation::SetVisibilityStrands {
+                strand_ids,
+                visible,
+            } => self.apply(
+                |c, d| c.set_visibility_strands(d, strand_ids, visible),
+                design,
+            ),
             DesignOperation::FlipHelixGroup { helix } => {
                 self.apply(|c, d| c.flip_helix_group(d, helix), design)
             }
@@ -233,8 +282,25 @@ impl Controller {
             DesignOperation::FlipAnchors { nucls } => {
                 self.apply(|c, d| c.flip_anchors(d, nucls), design)
             }
+            DesignOperation::FlipDeletions { nucls } => {
+                self.apply(|c, d| c.flip_deletions(d, nucls), design)
+            }
+            DesignOperation::AddModification { nucl, modification } => {
+                self.apply(|c, d| c.add_modification(d, nucl, modification), design)
+            }
+            DesignOperation::RmModifications { nucls } => {
+                self.apply(|c, d| c.rm_modifications(d, nucls), design)
+            }
             DesignOperation::RmGrid(_) => Err(ErrOperation::NotImplemented), // TODO
-            DesignOperation::ChangeSequence { .. } => Err(ErrOperation::NotImplemented), // TODO
+            DesignOperation::ChangeSequence {
+                sequence, strands, ..
+            } => self.apply(
+                |c, d| c.change_sequence_strands(d, sequence, strands),
+                design,
+            ),
+            DesignOperation::ClearSequence { strands } => {
+                self.apply(|c, d| c.clear_sequence_strands(d, strands), design)
+            }
             DesignOperation::CleanDesign => Err(ErrOperation::NotImplemented), // TODO
             DesignOperation::AttachObject { object, grid, x, y } => {
                 self.apply(|c, d| c.attach_object(d, object, grid, x, y), design)
@@ -249,8 +315,25 @@ impl Controller {
             DesignOperation::SetStrandName { s_id, name } => {
                 self.apply(|c, d| c.change_strand_name(d, s_id, name), design)
             }
-            DesignOperation::SetGroupPivot { group_id, pivot } => {
-                self.apply(|c, d| c.set_group_pivot(d, group_id, pivot), design)
+            DesignOperation::SetStrandNote { s_id, note } => {
+                self.apply(|c, d| c.change_strand_note(d, s_id, note), design)
+            }
+            DesignOperation::SetStrandKeepColor { s_id, keep_color } => {
+                self.apply(|c, d| c.set_strand_keep_color(d, s_id, keep_color), design)
+            }
+            DesignOperation::SetGroupPivot {
+                group_id,
+                pivot,
+                replace,
+            } => self.apply(
+                |c, d| c.set_group_pivot(d, group_id, pivot, replace),
+                design,
+            ),
+            DesignOperation::SetGroupColor { group_id, color } => {
+                self.apply(|c, d| c.set_group_color(d, group_id, color), design)
+            }
+            DesignOperation::SetGroupLocked { group_id, locked } => {
+                self.apply(|c, d| c.set_group_locked(d, group_id, locked), design)
             }
             DesignOperation::CreateNewCamera {
                 position,
@@ -277,6 +360,15 @@ impl Controller {
             DesignOperation::SetCameraName { camera_id, name } => {
                 self.apply(|c, d| c.set_camera_name(d, camera_id, name), design)
             }
+            DesignOperation::CreateNamedSelection { name, elements } => {
+                Ok(self.ok_apply(|c, d| c.create_named_selection(d, name, elements), design))
+            }
+            DesignOperation::RenameNamedSelection { idx, name } => {
+                self.apply(|c, d| c.rename_named_selection(d, idx, name), design)
+            }
+            DesignOperation::DeleteNamedSelection(idx) => {
+                self.apply(|c, d| c.delete_named_selection(d, idx), design)
+            }
             DesignOperation::SetGridPosition { grid_id, position } => {
                 self.apply(|c, d| c.set_grid_position(d, grid_id, position), design)
             }
@@ -291,6 +383,27 @@ impl Controller {
                 |c, d| c.set_grid_nb_turn(d, grid_id, nb_turn as f64),
                 design,
             ),
+            DesignOperation::SetGridType { grid_id, grid_type } => {
+                self.apply(|c, d| c.set_grid_type(d, grid_id, grid_type), design)
+            }
+            DesignOperation::SetGridBounds {
+                grid_id,
+                min_x,
+                max_x,
+                min_y,
+                max_y,
+            } => {
+                let bounds = GridBounds {
+                    min_x: min_x.min(max_x),
+                    max_x: min_x.max(max_x),
+                    min_y: min_y.min(max_y),
+                    max_y: min_y.max(max_y),
+                };
+                self.apply(|c, d| c.set_grid_bounds(d, grid_id, bounds), design)
+            }
+            DesignOperation::SetGridPhantomLength { grid_id, length } => {
+                self.apply(|c, d| c.set_grid_phantom_length(d, grid_id, length), design)
+            }
             DesignOperation::MakeSeveralXovers { xovers, doubled } => {
                 self.apply(|c, d| c.apply_several_xovers(d, xovers, doubled), design)
             }
@@ -329,6 +442,14 @@ impl Controller {
                 |c, d| c.append_vertex_to_bezier_path(d, path_id, vertex),
                 design,
             ),
+            DesignOperation::InsertVertexInPath {
+                path_id,
+                after_vertex,
+                position,
+            } => self.apply(
+                |c, d| c.insert_vertex_in_bezier_path(d, path_id, after_vertex, position),
+                design,
+            ),
             DesignOperation::MoveBezierVertex { vertices, position } => {
                 self.apply(|c, d| c.move_bezier_vertices(d, vertices, position), design)
             }
@@ -363,9 +484,78 @@ impl Controller {
                 file_path,
                 design_path,
             } => self.apply(|c, d| c.add_3d_object(d, file_path, design_path), design),
-            DesignOperation::ImportSvgPath { path } => {
-                self.apply(|c, d| c.import_svg_path(d, path), design)
+            DesignOperation::SetExternal3DObjectTransform {
+                object_id,
+                position,
+                orientation,
+                scale,
+            } => self.apply(
+                |c, d| {
+                    c.set_external_3d_object_transform(d, object_id, position, orientation, scale)
+                },
+                design,
+            ),
+            DesignOperation::SetExternal3DObjectVisibility { object_id, visible } => self.apply(
+                |c, d| c.set_external_3d_object_visibility(d, object_id, visible),
+                design,
+            ),
+            DesignOperation::AttachExternal3DObject { object_id, anchor } => self.apply(
+                |c, d| c.attach_external_3d_object(d, object_id, anchor),
+                design,
+            ),
+            DesignOperation::RenameBezierPlane { plane_id, name } => {
+                self.apply(|c, d| c.rename_bezier_plane(d, plane_id, name), design)
             }
+            DesignOperation::DeleteBezierPlane { plane_id } => {
+                self.apply(|c, d| c.delete_bezier_plane(d, plane_id), design)
+            }
+            DesignOperation::DuplicateBezierPlane {
+                plane_id,
+                offset,
+                copy_paths,
+            } => self.apply(
+                |c, d| c.duplicate_bezier_plane(d, plane_id, offset, copy_paths),
+                design,
+            ),
+            DesignOperation::ImportSvgPath { path, parameters } => {
+                self.apply(|c, d| c.import_svg_path(d, path, parameters), design)
+            }
+            DesignOperation::ImportOxDnaConfiguration { path } => {
+                self.apply(|c, d| c.import_oxdna_configuration(d, path), design)
+            }
+            DesignOperation::ReflectSelection {
+                helices,
+                grids,
+                plane_origin,
+                plane_normal,
+            } => self.apply(
+                |c, d| c.reflect_selection(d, helices, grids, plane_origin, plane_normal),
+                design,
+            ),
+            DesignOperation::DuplicateInPattern {
+                helices,
+                strands,
+                pattern,
+            } => self.apply(
+                |c, d| c.duplicate_in_pattern(d, helices, strands, pattern),
+                design,
+            ),
+            DesignOperation::AlignHelices { helices, reference } => {
+                self.apply(|c, d| c.align_helices(d, helices, reference), design)
+            }
+            DesignOperation::RouteScaffold { route, parameters } => {
+                self.apply(|c, d| c.route_scaffold(d, route, parameters), design)
+            }
+            DesignOperation::FillStaples(parameters) => {
+                self.apply(|c, d| c.fill_staples(d, parameters), design)
+            }
+            DesignOperation::SetHelixParameters {
+                helices,
+                parameters,
+            } => self.apply(
+                |c, d| c.set_helices_parameters(d, helices, parameters),
+                design,
+            ),
         };
 
         if let Ok(ret) = &mut ret {
@@ -585,9 +775,15 @@ impl Controller {
                 }
             }
             SimulationOperation::Stop => {
-                if let ControllerState::Simulating { initial_design, .. } = &ret.state {
+                if let ControllerState::Simulating {
+                    initial_design,
+                    interface,
+                } = &ret.state
+                {
+                    interface.lock().unwrap().paused = true;
                     ret.state = ControllerState::WithPausedSimulation {
                         initial_design: initial_design.clone(),
+                        interface: interface.clone(),
                     };
                 } else if let ControllerState::SimulatingGrids { .. } = &ret.state {
                     ret.state = ControllerState::Normal;
@@ -602,7 +798,7 @@ impl Controller {
                 }
             }
             SimulationOperation::Reset => {
-                if let ControllerState::WithPausedSimulation { initial_design } = &ret.state {
+                if let ControllerState::WithPausedSimulation { initial_design, .. } = &ret.state {
                     let returned_design = initial_design.clone_inner();
                     ret.state = ControllerState::Normal;
                     return Ok((
@@ -619,6 +815,27 @@ impl Controller {
                     interface.lock().unwrap().finish();
                 }
             }
+            SimulationOperation::Step => {
+                if let ControllerState::WithPausedSimulation { interface, .. } = &ret.state {
+                    interface.lock().unwrap().step_requested = true;
+                } else {
+                    return Err(ErrOperation::IncompatibleState(
+                        "No paused simulation".into(),
+                    ));
+                }
+            }
+            SimulationOperation::SetPinnedGroups(group_ids) => {
+                let pinned_helices = simulations::helices_of_groups(&design, &group_ids);
+                if let ControllerState::Simulating { interface, .. }
+                | ControllerState::WithPausedSimulation { interface, .. } = &ret.state
+                {
+                    interface.lock().unwrap().pinned_helices = Some(pinned_helices);
+                } else {
+                    return Err(ErrOperation::IncompatibleState(
+                        "No simulation running".into(),
+                    ));
+                }
+            }
         }
         Ok((self.return_design(design, "Simulation".into()), ret))
     }
@@ -638,6 +855,35 @@ impl Controller {
         Ok(design)
     }
 
+    fn change_strand_note(
+        &mut self,
+        mut design: Design,
+        s_id: usize,
+        note: Option<String>,
+    ) -> Result<Design, ErrOperation> {
+        let strand = design
+            .strands
+            .get_mut(&s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+        self.state = ControllerState::ChangingStrandNote { strand_id: s_id };
+        strand.note = note.filter(|n| !n.is_empty());
+        Ok(design)
+    }
+
+    fn set_strand_keep_color(
+        &mut self,
+        mut design: Design,
+        s_id: usize,
+        keep_color: bool,
+    ) -> Result<Design, ErrOperation> {
+        let strand = design
+            .strands
+            .get_mut(&s_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+        strand.keep_color = keep_color;
+        Ok(design)
+    }
+
     fn add_hyperboloid_helices(
         &mut self,
         design: &mut Design,
@@ -726,6 +972,22 @@ impl Controller {
         Ok(design)
     }
 
+    fn set_visibility_strands(
+        &mut self,
+        mut design: Design,
+        strand_ids: Vec<usize>,
+        visible: bool,
+    ) -> Result<Design, ErrOperation> {
+        for s_id in strand_ids {
+            let strand = design
+                .strands
+                .get_mut(&s_id)
+                .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+            strand.invisible = !visible;
+        }
+        Ok(design)
+    }
+
     fn flip_helix_group(
         &mut self,
         mut design: Design,
@@ -751,14 +1013,35 @@ impl Controller {
         mut design: Design,
         group_id: GroupId,
         pivot: GroupPivot,
+        replace: bool,
     ) -> Result<Design, ErrOperation> {
         let attributes = design.group_attributes.entry(group_id).or_default();
-        if attributes.pivot.is_none() {
+        if replace || attributes.pivot.is_none() {
             attributes.pivot = Some(pivot);
         }
         Ok(design)
     }
 
+    fn set_group_color(
+        &mut self,
+        mut design: Design,
+        group_id: GroupId,
+        color: Option<u32>,
+    ) -> Result<Design, ErrOperation> {
+        design.group_attributes.entry(group_id).or_default().color = color;
+        Ok(design)
+    }
+
+    fn set_group_locked(
+        &mut self,
+        mut design: Design,
+        group_id: GroupId,
+        locked: bool,
+    ) -> Result<Design, ErrOperation> {
+        design.group_attributes.entry(group_id).or_default().locked = locked;
+        Ok(design)
+    }
+
     fn update_attribute(
         &mut self,
         mut design: Design,
@@ -773,6 +1056,9 @@ impl Controller {
                 DnaAttribute::LockedForSimulations(locked) => {
                     self.set_lock_during_simulation(&mut design, elt, locked)?
                 }
+                DnaAttribute::Anchored(anchored) => {
+                    self.set_anchored(&mut design, elt, anchored)?
+                }
             }
         }
         Ok(design)
@@ -796,6 +1082,50 @@ impl Controller {
         Ok(design)
     }
 
+    /// Toggle the deletion marks of `nucls`. The strands are left untouched, see
+    /// [Design::deletions].
+    fn flip_deletions(
+        &mut self,
+        mut design: Design,
+        nucls: Vec<Nucl>,
+    ) -> Result<Design, ErrOperation> {
+        let new_deletion_status = !nucls.iter().all(|n| design.deletions.contains(n));
+        if new_deletion_status {
+            for n in nucls.into_iter() {
+                design.deletions.insert(n);
+            }
+        } else {
+            for n in nucls.iter() {
+                design.deletions.remove(n);
+            }
+        }
+        Ok(design)
+    }
+
+    fn add_modification(
+        &mut self,
+        mut design: Design,
+        nucl: Nucl,
+        modification: Modification,
+    ) -> Result<Design, ErrOperation> {
+        if design.strands.get_strand_nucl(&nucl).is_none() {
+            return Err(ErrOperation::NuclDoesNotExist(nucl));
+        }
+        design.modifications.insert(nucl, modification);
+        Ok(design)
+    }
+
+    fn rm_modifications(
+        &mut self,
+        mut design: Design,
+        nucls: Vec<Nucl>,
+    ) -> Result<Design, ErrOperation> {
+        for n in nucls.iter() {
+            design.modifications.remove(n);
+        }
+        Ok(design)
+    }
+
     fn make_element_visible(
         &self,
         design: &mut Design,
@@ -813,14 +1143,44 @@ impl Controller {
                 let grid = grids_mut
                     .get_mut(&g_id)
                     .ok_or_else(|| ErrOperation::GridDoesNotExist(g_id.to_grid_id()))?;
-                grid.invisible = !grid.invisible;
+                grid.invisible = !visible;
                 drop(grids_mut);
             }
+            DnaElementKey::Strand(s_id) => {
+                let strand = design
+                    .strands
+                    .get_mut(s_id)
+                    .ok_or(ErrOperation::StrandDoesNotExist(*s_id))?;
+                strand.invisible = !visible;
+            }
             _ => (),
         }
         Ok(())
     }
 
+    fn set_anchored(
+        &self,
+        design: &mut Design,
+        element: &DnaElementKey,
+        anchored: bool,
+    ) -> Result<(), ErrOperation> {
+        if let DnaElementKey::Strand(s_id) = element {
+            let nucls = design
+                .strands
+                .get(s_id)
+                .ok_or(ErrOperation::StrandDoesNotExist(*s_id))?
+                .nucls();
+            for n in nucls {
+                if anchored {
+                    design.anchors.insert(n);
+                } else {
+                    design.anchors.remove(&n);
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn set_xover_group_of_elt(
         &self,
         design: &mut Design,
@@ -926,6 +1286,67 @@ impl Controller {
         }
     }
 
+    /// Change the parameters of the hyperboloid grid `grid_id`.
+    ///
+    /// While the parameters are being edited, the modifications are always applied on the design
+    /// as it was before the edition started, so that they can be driven by a slider.
+    fn update_hyperboloid(
+        &mut self,
+        design: Design,
+        grid_id: GridId,
+        request: HyperboloidRequest,
+    ) -> Result<Design, ErrOperation> {
+        let initial_design = match &self.state {
+            ControllerState::UpdatingHyperboloid {
+                grid_id: edited_grid,
+                initial_design,
+            } if *edited_grid == grid_id => initial_design.clone(),
+            _ => AddressPointer::new(design),
+        };
+        let mut design = initial_design.clone_inner();
+        let free_grid_id = if let GridId::FreeGrid(id) = grid_id {
+            ensnano_design::grid::FreeGridId(id)
+        } else {
+            log::error!("Editing bezier path grids is not yet implemented");
+            return Err(ErrOperation::NotImplemented);
+        };
+        let hyperboloid = request.to_grid();
+        let radius = hyperboloid.radius as isize;
+
+        let mut new_grids = design.free_grids.make_mut();
+        let grid = new_grids
+            .get_mut(&free_grid_id)
+            .ok_or(ErrOperation::GridDoesNotExist(grid_id))?;
+        if !matches!(grid.grid_type, GridTypeDescr::Hyperboloid { .. }) {
+            return Err(ErrOperation::GridIsNotHyperboloid(grid_id));
+        }
+        grid.grid_type = hyperboloid.desc();
+        drop(new_grids);
+
+        // Helices whose position is outside of the new grid are wrapped around it, which fails
+        // if an other helix is already there.
+        let mut used_positions = HashSet::new();
+        let mut helices_mut = design.helices.make_mut();
+        for (h_id, h) in helices_mut.iter_mut() {
+            if let Some(grid_position) = h.grid_position.as_mut() {
+                if grid_position.grid == grid_id {
+                    grid_position.x = grid_position.x.rem_euclid(radius);
+                    if !used_positions.insert((grid_position.x, grid_position.y)) {
+                        log::warn!("Helix {} collides with an other helix of the grid", h_id);
+                        return Err(ErrOperation::GridPositionAlreadyUsed);
+                    }
+                }
+            }
+        }
+        drop(helices_mut);
+
+        self.state = ControllerState::UpdatingHyperboloid {
+            grid_id,
+            initial_design,
+        };
+        Ok(design)
+    }
+
     pub(super) fn is_building_hyperboloid(&self) -> bool {
         matches!(&self.state, ControllerState::MakingHyperboloid { .. })
     }
@@ -948,16 +1369,20 @@ impl Controller {
         chanel_reader: &mut dyn ShiftOptimizerReader,
         nucl_collection: Arc<Nc>,
         design: &Design,
+        scaffold_id: usize,
     ) -> Result<(OkOperation, Self), ErrOperation> {
         if let OperationCompatibility::Incompatible =
-            self.check_compatibilty(&DesignOperation::SetScaffoldShift(0))
+            self.check_compatibilty(&DesignOperation::SetScaffoldShift {
+                scaffold_id,
+                shift: 0,
+            })
         {
             return Err(ErrOperation::IncompatibleState(
                 self.state.state_name().to_string(),
             ));
         }
         Ok(self.ok_no_op(
-            |c, d| c.start_shift_optimization(d, chanel_reader, nucl_collection),
+            |c, d| c.start_shift_optimization(d, scaffold_id, chanel_reader, nucl_collection),
             design,
         ))
     }
@@ -965,12 +1390,14 @@ impl Controller {
     fn start_shift_optimization<Nc: NuclCollection>(
         &mut self,
         design: &Design,
+        scaffold_id: usize,
         chanel_reader: &mut dyn ShiftOptimizerReader,
         nucl_collection: Arc<Nc>,
     ) {
         self.state = ControllerState::OptimizingScaffoldPosition;
         shift_optimization::optimize_shift(
             Arc::new(design.clone()),
+            scaffold_id,
             nucl_collection,
             chanel_reader,
         );
@@ -1050,18 +1477,29 @@ impl Controller {
                     OperationCompatibility::FinishFirst
                 }
             }
+            ControllerState::UpdatingHyperboloid {
+                grid_id: edited_grid,
+                ..
+            } => match operation {
+                DesignOperation::UpdateHyperboloid { grid_id, .. } if *grid_id == edited_grid => {
+                    OperationCompatibility::Compatible
+                }
+                _ => OperationCompatibility::FinishFirst,
+            },
             ControllerState::ApplyingOperation { .. } => OperationCompatibility::Compatible,
             ControllerState::BuildingStrand { initializing, .. } => {
                 if let DesignOperation::MoveBuilders(_) = operation {
                     OperationCompatibility::Compatible
-                } else if initializing {
+                } else if initializing || matches!(operation, DesignOperation::Xover { .. }) {
+                    // Linking the ends of the strand being built closes it once the building is
+                    // finished
                     OperationCompatibility::FinishFirst
                 } else {
                     OperationCompatibility::Incompatible
                 }
             }
             ControllerState::OptimizingScaffoldPosition => {
-                if let DesignOperation::SetScaffoldShift(_) = operation {
+                if let DesignOperation::SetScaffoldShift { .. } = operation {
                     OperationCompatibility::Compatible
                 } else {
                     OperationCompatibility::Incompatible
@@ -1080,6 +1518,19 @@ impl Controller {
                     OperationCompatibility::FinishFirst
                 }
             }
+            ControllerState::ChangingStrandNote {
+                strand_id: current_s_id,
+            } => {
+                if let DesignOperation::SetStrandNote { s_id, .. } = operation {
+                    if current_s_id == *s_id {
+                        OperationCompatibility::Compatible
+                    } else {
+                        OperationCompatibility::FinishFirst
+                    }
+                } else {
+                    OperationCompatibility::FinishFirst
+                }
+            }
             ControllerState::WithPausedSimulation { .. } => OperationCompatibility::FinishFirst,
             _ => OperationCompatibility::Incompatible,
         }
@@ -1111,7 +1562,14 @@ impl Controller {
         }
     }
 
-    fn return_design(&self, design: Design, label: std::borrow::Cow<'static, str>) -> OkOperation {
+    fn return_design(
+        &self,
+        mut design: Design,
+        label: std::borrow::Cow<'static, str>,
+    ) -> OkOperation {
+        // The modifications of the nucleotides removed by the operation are removed with it, so
+        // that undoing the operation restores them.
+        design.remove_orphan_modifications();
         if self.is_in_persistant_state().is_persistant() {
             OkOperation::Push { design, label }
         } else {
@@ -1159,7 +1617,9 @@ impl Controller {
             ControllerState::WithPendingHelicesDuplication { .. } => StatePersitance::Persistant,
             ControllerState::WithPausedSimulation { .. } => StatePersitance::NeedFinish,
             ControllerState::SettingRollHelices { .. } => StatePersitance::NeedFinish,
+            ControllerState::UpdatingHyperboloid { .. } => StatePersitance::NeedFinish,
             ControllerState::ChangingStrandName { .. } => StatePersitance::NeedFinish,
+            ControllerState::ChangingStrandNote { .. } => StatePersitance::NeedFinish,
             _ => StatePersitance::Transitory,
         }
     }
@@ -1306,6 +1766,23 @@ impl Controller {
         Ok(design)
     }
 
+    fn insert_vertex_in_bezier_path(
+        &mut self,
+        mut design: Design,
+        path_id: BezierPathId,
+        after_vertex: usize,
+        position: Vec2,
+    ) -> Result<Design, ErrOperation> {
+        let vertex_id = ensnano_design::design_operations::insert_vertex_in_bezier_path(
+            &mut design,
+            path_id,
+            after_vertex,
+            position,
+        )?;
+        self.next_selection = Some(vec![Selection::BezierVertex(vertex_id)]);
+        Ok(design)
+    }
+
     fn rm_bezier_vertices(
         &mut self,
         mut design: Design,
@@ -1602,6 +2079,41 @@ impl Controller {
         }
     }
 
+    fn create_named_selection(
+        &mut self,
+        mut design: Design,
+        name: String,
+        elements: Vec<DnaElementKey>,
+    ) -> Design {
+        design.add_named_selection(name, elements);
+        design
+    }
+
+    fn rename_named_selection(
+        &mut self,
+        mut design: Design,
+        idx: usize,
+        name: String,
+    ) -> Result<Design, ErrOperation> {
+        if design.rename_named_selection(idx, name) {
+            Ok(design)
+        } else {
+            Err(ErrOperation::NamedSelectionDoesNotExist(idx))
+        }
+    }
+
+    fn delete_named_selection(
+        &mut self,
+        mut design: Design,
+        idx: usize,
+    ) -> Result<Design, ErrOperation> {
+        if design.rm_named_selection(idx) {
+            Ok(design)
+        } else {
+            Err(ErrOperation::NamedSelectionDoesNotExist(idx))
+        }
+    }
+
     pub(super) fn is_changing_color(&self) -> bool {
         matches!(self.state, ControllerState::ChangingColor)
     }
@@ -1921,6 +2433,8 @@ pub enum ErrOperation {
     CutInexistingStrand,
     GridDoesNotExist(GridId),
     GridPositionAlreadyUsed,
+    /// The nucleotide is already part of a strand
+    NuclAlreadyUsed(Nucl),
     StrandDoesNotExist(usize),
     HelixDoesNotExists(usize),
     HelixHasNoGridPosition(usize),
@@ -1940,16 +2454,206 @@ pub enum ErrOperation {
     NoGrids,
     FinishFirst,
     CameraDoesNotExist(CameraId),
+    NamedSelectionDoesNotExist(usize),
     GridIsNotHyperboloid(GridId),
     DesignOperationError(ensnano_design::design_operations::ErrOperation),
     NotPiecewiseBezier(usize),
     GridCopyError(ensnano_design::grid::GridCopyError),
     CouldNotGetPrime3of(usize),
     PathDoesNotExist(BezierPathId),
+    BezierPlaneDoesNotExist(BezierPlaneId),
+    /// The plane cannot be deleted because the given vertices are positioned on it
+    BezierPlaneIsUsed {
+        plane_id: BezierPlaneId,
+        vertices: Vec<BezierVertexId>,
+    },
     VertexDoesNotExist(BezierPathId, usize),
     GridIsNotEmpty(GridId),
     CouldNotMake3DObject,
+    External3DObjectDoesNotExist(External3DObjectId),
+    External3DObjectAnchorDoesNotExist(External3DObjectAnchor),
     SvgImportError(ensnano_design::SvgImportError),
+    OxDnaImportError(ensnano_exports::oxdna::import::OxDnaImportError),
+    /// The sequence given to some staples does not match the complement of the scaffold
+    SequenceMismatches(Vec<SequenceMismatch>),
+    /// The operation would modify a member of the locked group with the given name
+    GroupIsLocked(String),
+}
+
+/// Return the name of a locked group of the organizer containing an element modified by
+/// `operation`, if there is one.
+///
+/// Operations on nucleotides modify the strands containing them, and moving a grid moves the
+/// helices attached to it, so these strands and helices must not be in a locked group either.
+fn locked_group_modified_by(design: &Design, operation: &DesignOperation) -> Option<String> {
+    if !design.group_attributes.values().any(|a| a.locked) {
+        return None;
+    }
+    let mut keys: BTreeSet<DnaElementKey> = operation.affected_elements().into_iter().collect();
+    keys.extend(design_dependent_affected_elements(design, operation));
+    let moved_grids: Vec<GridId> = keys
+        .iter()
+        .filter_map(|key| match key {
+            DnaElementKey::Grid(g_id) => Some(GridId::FreeGrid(*g_id)),
+            _ => None,
+        })
+        .collect();
+    keys.extend(helices_on_grids(design, |grid| moved_grids.contains(grid)));
+    let strands: Vec<DnaElementKey> = keys
+        .iter()
+        .filter_map(|key| match key {
+            DnaElementKey::Nucleotide {
+                helix,
+                position,
+                forward,
+            } => design.strands.get_strand_nucl(&Nucl {
+                helix: *helix,
+                position: *position,
+                forward: *forward,
+            }),
+            _ => None,
+        })
+        .map(DnaElementKey::Strand)
+        .collect();
+    keys.extend(strands);
+    if keys.is_empty() {
+        return None;
+    }
+    let tree = design.organizer_tree.as_ref()?;
+    locked_group_containing(tree, design, &keys, None)
+}
+
+/// The elements modified by `operation` that can only be found by looking at `design`.
+fn design_dependent_affected_elements(
+    design: &Design,
+    operation: &DesignOperation,
+) -> Vec<DnaElementKey> {
+    let paths: Vec<BezierPathId> = match operation {
+        DesignOperation::Xover {
+            prime5_id,
+            prime3_id,
+        } => {
+            let prime5_end = design.strands.get(prime5_id).and_then(Strand::get_3prime);
+            let prime3_end = design.strands.get(prime3_id).and_then(Strand::get_5prime);
+            return prime5_end
+                .into_iter()
+                .chain(prime3_end)
+                .map(|nucl| DnaElementKey::Helix(nucl.helix))
+                .collect();
+        }
+        DesignOperation::RecolorStaples(RecolorParameters { scope, .. }) => {
+            let strands: Vec<usize> = match scope {
+                RecolorScope::AllStaples => design
+                    .strands
+                    .keys()
+                    .cloned()
+                    .filter(|s_id| !design.is_scaffold(*s_id))
+                    .collect(),
+                RecolorScope::Group(group_id) => design
+                    .organizer_tree
+                    .as_ref()
+                    .and_then(|tree| strands_of_group(tree, *group_id))
+                    .map(|strands| strands.into_iter().collect())
+                    .unwrap_or_default(),
+                RecolorScope::Strands(_) => vec![],
+            };
+            return strands.into_iter().map(DnaElementKey::Strand).collect();
+        }
+        DesignOperation::RouteScaffold {
+            route: ScaffoldRoute::Grid { grid_id, .. },
+            parameters,
+        } if parameters.clear_existing => {
+            return helices_on_grids(design, |grid| grid == grid_id).collect();
+        }
+        DesignOperation::AppendVertexToPath { path_id, .. }
+        | DesignOperation::InsertVertexInPath { path_id, .. }
+        | DesignOperation::TurnPathVerticesIntoGrid { path_id, .. }
+        | DesignOperation::MakeBezierPathCyclic { path_id, .. } => vec![*path_id],
+        DesignOperation::MoveBezierVertex { vertices, .. }
+        | DesignOperation::RmBezierVertices { vertices } => {
+            vertices.iter().map(|v| v.path_id).collect()
+        }
+        DesignOperation::SetBezierVertexPosition { vertex_id, .. } => vec![vertex_id.path_id],
+        DesignOperation::SetVectorOfBezierTengent(NewBezierTengentVector { vertex_id, .. }) => {
+            vec![vertex_id.path_id]
+        }
+        DesignOperation::ApplyHomothethyOnBezierPlane { homothethy } => design
+            .bezier_paths
+            .vertices_on_plane(homothethy.plane_id)
+            .into_iter()
+            .map(|v| v.path_id)
+            .collect(),
+        _ => return vec![],
+    };
+    helices_on_grids(design, |grid| {
+        if let GridId::BezierPathGrid(vertex) = grid {
+            paths.contains(&vertex.path_id)
+        } else {
+            false
+        }
+    })
+    .collect()
+}
+
+/// The keys of the helices attached to a grid satisfying `predicate`.
+fn helices_on_grids<'a>(
+    design: &'a Design,
+    predicate: impl Fn(&GridId) -> bool + 'a,
+) -> impl Iterator<Item = DnaElementKey> + 'a {
+    design.helices.iter().filter_map(move |(h_id, h)| {
+        h.grid_position
+            .as_ref()
+            .filter(|position| predicate(&position.grid))
+            .map(|_| DnaElementKey::Helix(*h_id))
+    })
+}
+
+/// Return the name of the innermost locked group containing one of `keys`. `locked_ancestor` is
+/// the name of the innermost locked group containing `tree`.
+fn locked_group_containing(
+    tree: &OrganizerTree<DnaElementKey>,
+    design: &Design,
+    keys: &BTreeSet<DnaElementKey>,
+    locked_ancestor: Option<&str>,
+) -> Option<String> {
+    match tree {
+        OrganizerTree::Leaf(key) => locked_ancestor
+            .filter(|_| keys.contains(key))
+            .map(String::from),
+        OrganizerTree::Node {
+            name,
+            childrens,
+            id,
+            ..
+        } => {
+            let locked = id
+                .and_then(|id| design.group_attributes.get(&id))
+                .map(|attributes| attributes.locked)
+                .unwrap_or(false);
+            let locked_ancestor = if locked {
+                Some(name.as_str())
+            } else {
+                locked_ancestor
+            };
+            childrens
+                .iter()
+                .find_map(|c| locked_group_containing(c, design, keys, locked_ancestor))
+        }
+    }
+}
+
+/// A nucleotide of a staple whose basis in a new sequence is not complementary to the basis of
+/// the scaffold nucleotide it is paired with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceMismatch {
+    pub strand_id: usize,
+    /// The position of the nucleotide in the strand, starting from its 5' end
+    pub position: usize,
+    pub nucl: Nucl,
+    /// The complement of the scaffold basis
+    pub expected: char,
+    /// The basis given by the new sequence
+    pub found: char,
 }
 
 impl From<ensnano_design::design_operations::ErrOperation> for ErrOperation {
@@ -1964,34 +2668,97 @@ impl From<ensnano_design::SvgImportError> for ErrOperation {
     }
 }
 
+impl From<ensnano_exports::oxdna::import::OxDnaImportError> for ErrOperation {
+    fn from(e: ensnano_exports::oxdna::import::OxDnaImportError) -> Self {
+        Self::OxDnaImportError(e)
+    }
+}
+
 impl Controller {
-    fn recolor_stapples(&mut self, mut design: Design) -> Design {
-        for (s_id, strand) in design.strands.iter_mut() {
-            if Some(*s_id) != design.scaffold_id {
-                let color = crate::utils::new_color(&mut self.color_idx);
-                strand.color = color;
-            }
-        }
+    fn recolor_stapples(
+        &mut self,
+        mut design: Design,
+        parameters: RecolorParameters,
+    ) -> Result<Design, ErrOperation> {
+        ensnano_design::design_operations::recolor_strands(&mut design, &parameters)?;
+        Ok(design)
+    }
+
+    fn set_scaffold_ids(&mut self, mut design: Design, mut s_ids: Vec<usize>) -> Design {
+        let mut seen = HashSet::new();
+        s_ids.retain(|s_id| seen.insert(*s_id));
+        design
+            .scaffold_sequences
+            .retain(|s_id, _| s_ids.contains(s_id));
+        design.scaffold_ids = s_ids;
         design
     }
 
     fn set_scaffold_sequence(
         &mut self,
         mut design: Design,
+        scaffold_id: usize,
         sequence: String,
         shift: usize,
-    ) -> Design {
-        design.scaffold_sequence = Some(sequence);
-        design.scaffold_shift = Some(shift);
+    ) -> Result<Design, ErrOperation> {
+        if !design.is_scaffold(scaffold_id) {
+            return Err(ErrOperation::NoScaffoldSet);
+        }
         design
+            .scaffold_sequences
+            .insert(scaffold_id, ScaffoldSequence { sequence, shift });
+        Ok(design)
     }
 
-    fn set_scaffold_shift(&mut self, mut design: Design, shift: usize) -> Design {
+    fn set_scaffold_shift(
+        &mut self,
+        mut design: Design,
+        scaffold_id: usize,
+        shift: usize,
+    ) -> Result<Design, ErrOperation> {
         if let ControllerState::OptimizingScaffoldPosition = self.state {
             self.state = ControllerState::Normal;
         }
-        design.scaffold_shift = Some(shift);
-        design
+        let sequence = design
+            .scaffold_sequences
+            .get_mut(&scaffold_id)
+            .ok_or(ErrOperation::EmptyScaffoldSequence)?;
+        sequence.shift = shift;
+        Ok(design)
+    }
+
+    fn change_sequence_strands(
+        &mut self,
+        mut design: Design,
+        sequence: String,
+        strands: Vec<usize>,
+    ) -> Result<Design, ErrOperation> {
+        for s_id in strands.iter() {
+            let strand = design
+                .strands
+                .get_mut(s_id)
+                .ok_or(ErrOperation::StrandDoesNotExist(*s_id))?;
+            // The sequences of the domains would take precedence over the one of the strand
+            clear_domain_sequences(strand);
+            strand.sequence = Some(Cow::Owned(sequence.clone()));
+        }
+        Ok(design)
+    }
+
+    fn clear_sequence_strands(
+        &mut self,
+        mut design: Design,
+        strands: Vec<usize>,
+    ) -> Result<Design, ErrOperation> {
+        for s_id in strands.iter() {
+            let strand = design
+                .strands
+                .get_mut(s_id)
+                .ok_or(ErrOperation::StrandDoesNotExist(*s_id))?;
+            clear_domain_sequences(strand);
+            strand.sequence = None;
+        }
+        Ok(design)
     }
 
     fn change_color_strands(
@@ -2041,6 +2808,39 @@ impl Controller {
         design
     }
 
+    fn set_grid_visibility(
+        &mut self,
+        mut design: Design,
+        grid_ids: Vec<GridId>,
+        visible: bool,
+    ) -> Result<Design, ErrOperation> {
+        let mut new_grids = design.free_grids.make_mut();
+        for g_id in grid_ids.into_iter() {
+            if let Some(desc) = new_grids.get_mut_g_id(&g_id) {
+                desc.invisible = !visible;
+            } else if let GridId::FreeGrid(_) = g_id {
+                return Err(ErrOperation::GridDoesNotExist(g_id));
+            } else {
+                log::error!("Hiding bezier path grids is not yet implemented");
+                return Err(ErrOperation::NotImplemented);
+            }
+        }
+        drop(new_grids);
+        Ok(design)
+    }
+
+    fn solo_grids(&mut self, mut design: Design, grid_ids: Vec<GridId>) -> Design {
+        let all_grids: Vec<FreeGridId> = design.free_grids.keys().cloned().collect();
+        let mut new_grids = design.free_grids.make_mut();
+        for g_id in all_grids.into_iter() {
+            if let Some(desc) = new_grids.get_mut(&g_id) {
+                desc.invisible = !grid_ids.contains(&g_id.to_grid_id());
+            }
+        }
+        drop(new_grids);
+        design
+    }
+
     fn snap_helices(
         &mut self,
         mut design: Design,
@@ -2281,7 +3081,9 @@ impl Controller {
 
     fn init_strand(&mut self, design: &mut Design, nucl: Nucl) -> usize {
         let s_id = design.strands.keys().max().map(|n| n + 1).unwrap_or(0);
-        let color = crate::utils::new_color(&mut self.color_idx);
+        // The strand being built stays on the helix on which it was initialized
+        let helices = BTreeSet::from([nucl.helix]);
+        let color = Self::new_strand_color(&mut self.color_idx, design, &helices);
         design.strands.insert(
             s_id,
             Strand::init(nucl.helix, nucl.position, nucl.forward, color),
@@ -2301,7 +3103,8 @@ impl Controller {
         } else {
             0
         };
-        let color = crate::utils::new_color(&mut self.color_idx);
+        let helices = BTreeSet::from([helix]);
+        let color = Self::new_strand_color(&mut self.color_idx, design, &helices);
         design
             .strands
             .insert(new_key, Strand::init(helix, position, forward, color));
@@ -2522,6 +3325,9 @@ impl Controller {
             cyclic: false,
             sequence: seq_prim5,
             name: name.clone(),
+            invisible: strand.invisible,
+            note: strand.note.clone(),
+            keep_color: strand.keep_color,
         };
 
         let mut strand_3prime = Strand {
@@ -2531,6 +3337,9 @@ impl Controller {
             junctions: prime3_junctions,
             sequence: seq_prim3,
             name,
+            invisible: strand.invisible,
+            note: strand.note.clone(),
+            keep_color: strand.keep_color,
         };
         let new_id = (*strands.keys().max().unwrap_or(&0)).max(id) + 1;
         log::info!("new id {}, ; id {}", new_id, id);
@@ -2647,19 +3456,58 @@ impl Controller {
         let mut new_helices = design.helices.make_mut();
         let helix_id = new_helices.push_helix(helix);
         drop(new_helices);
-        if length > 0 {
-            for b in [false, true].iter() {
-                let new_key = self.add_strand(&mut design, helix_id, start, *b);
-                if let Domain::HelixDomain(ref mut dom) =
-                    design.strands.get_mut(&new_key).unwrap().domains[0]
-                {
-                    dom.end = dom.start + length as isize;
+        self.add_duplex_strands(&mut design, helix_id, start, length);
+        Ok(design)
+    }
+
+    fn make_duplex(
+        &mut self,
+        mut design: Design,
+        helix: usize,
+        start: isize,
+        length: usize,
+    ) -> Result<Design, ErrOperation> {
+        if !design.helices.contains_key(&helix) {
+            return Err(ErrOperation::HelixDoesNotExists(helix));
+        }
+        for position in start..(start + length as isize) {
+            for forward in [false, true] {
+                let nucl = Nucl {
+                    helix,
+                    position,
+                    forward,
+                };
+                if design.strands.get_strand_nucl(&nucl).is_some() {
+                    return Err(ErrOperation::NuclAlreadyUsed(nucl));
                 }
             }
         }
+        self.add_duplex_strands(&mut design, helix, start, length);
         Ok(design)
     }
 
+    /// Add a forward and a backward strand covering the positions `start..start + length` of
+    /// `helix`. Nothing is added if `length` is 0.
+    fn add_duplex_strands(
+        &mut self,
+        design: &mut Design,
+        helix: usize,
+        start: isize,
+        length: usize,
+    ) {
+        if length == 0 {
+            return;
+        }
+        for b in [false, true] {
+            let new_key = self.add_strand(design, helix, start, b);
+            if let Domain::HelixDomain(ref mut dom) =
+                design.strands.get_mut(&new_key).unwrap().domains[0]
+            {
+                dom.end = dom.start + length as isize;
+            }
+        }
+    }
+
     fn add_two_points_bezier(
         &mut self,
         mut design: Design,
@@ -2757,6 +3605,11 @@ impl Controller {
                 .remove(&prime3)
                 .ok_or(ErrOperation::StrandDoesNotExist(prime3))?;
             let name = strand5prime.name.or(strand3prime.name);
+            // Keep the notes of both strands
+            let note = match (strand5prime.note, strand3prime.note) {
+                (Some(note5), Some(note3)) if note5 != note3 => Some(format!("{note5}; {note3}")),
+                (note5, note3) => note5.or(note3),
+            };
             let len = strand5prime.domains.len() + strand3prime.domains.len();
             let mut domains = Vec::with_capacity(len);
             let mut junctions = Vec::with_capacity(len);
@@ -2842,6 +3695,9 @@ impl Controller {
                 junctions,
                 cyclic: false,
                 name,
+                invisible: strand5prime.invisible && strand3prime.invisible,
+                note,
+                keep_color: strand5prime.keep_color,
             };
             new_strand.merge_consecutive_domains();
             strands.insert(prime5, new_strand);
@@ -3375,6 +4231,56 @@ impl Controller {
         }
     }
 
+    fn set_grid_type(
+        &mut self,
+        mut design: Design,
+        grid_id: GridId,
+        grid_type: GridTypeDescr,
+    ) -> Result<Design, ErrOperation> {
+        ensnano_design::design_operations::set_grid_type(&mut design, grid_id, grid_type)?;
+        Ok(design)
+    }
+
+    fn set_grid_bounds(
+        &mut self,
+        mut design: Design,
+        grid_id: GridId,
+        bounds: GridBounds,
+    ) -> Result<Design, ErrOperation> {
+        if let GridId::FreeGrid(id) = grid_id {
+            let mut new_grids = design.free_grids.make_mut();
+            let grid = new_grids
+                .get_mut(&ensnano_design::grid::FreeGridId(id))
+                .ok_or(ErrOperation::GridDoesNotExist(grid_id))?;
+            grid.bounds = Some(bounds);
+            drop(new_grids);
+            Ok(design)
+        } else {
+            log::error!("Setting bounds of bezier path grids is not yet implemented");
+            Err(ErrOperation::NotImplemented)
+        }
+    }
+
+    fn set_grid_phantom_length(
+        &mut self,
+        mut design: Design,
+        grid_id: GridId,
+        length: Option<usize>,
+    ) -> Result<Design, ErrOperation> {
+        if let GridId::FreeGrid(id) = grid_id {
+            let mut new_grids = design.free_grids.make_mut();
+            let grid = new_grids
+                .get_mut(&ensnano_design::grid::FreeGridId(id))
+                .ok_or(ErrOperation::GridDoesNotExist(grid_id))?;
+            grid.phantom_length = length;
+            drop(new_grids);
+            Ok(design)
+        } else {
+            log::error!("Setting phantom length of bezier path grids is not yet implemented");
+            Err(ErrOperation::NotImplemented)
+        }
+    }
+
     fn add_3d_object(
         &mut self,
         mut design: Design,
@@ -3393,26 +4299,371 @@ impl Controller {
         Ok(design)
     }
 
+    fn set_external_3d_object_transform(
+        &mut self,
+        mut design: Design,
+        object_id: External3DObjectId,
+        position: Vec3,
+        orientation: Rotor3,
+        scale: f32,
+    ) -> Result<Design, ErrOperation> {
+        // The frame is computed first because updating the grid data moves the attached objects
+        let anchor_frame = design
+            .external_3d_objects
+            .get(&object_id)
+            .and_then(|object| object.anchor())
+            .and_then(|anchor| {
+                design
+                    .get_updated_grid_data()
+                    .external_object_anchor_frame(anchor)
+            });
+        let object = design
+            .external_3d_objects
+            .get_mut(&object_id)
+            .ok_or(ErrOperation::External3DObjectDoesNotExist(object_id))?;
+        object.set_transform(position, orientation, scale);
+        // An attached object keeps following its anchor from its new position
+        if let Some(frame) = anchor_frame {
+            object.update_local_offset(frame);
+        }
+        Ok(design)
+    }
+
+    fn attach_external_3d_object(
+        &mut self,
+        mut design: Design,
+        object_id: External3DObjectId,
+        anchor: Option<External3DObjectAnchor>,
+    ) -> Result<Design, ErrOperation> {
+        let frame = if let Some(anchor) = anchor {
+            Some(
+                design
+                    .get_updated_grid_data()
+                    .external_object_anchor_frame(anchor)
+                    .ok_or(ErrOperation::External3DObjectAnchorDoesNotExist(anchor))?,
+            )
+        } else {
+            None
+        };
+        let object = design
+            .external_3d_objects
+            .get_mut(&object_id)
+            .ok_or(ErrOperation::External3DObjectDoesNotExist(object_id))?;
+        if let Some((anchor, frame)) = anchor.zip(frame) {
+            object.attach(anchor, frame);
+        } else {
+            object.detach();
+        }
+        Ok(design)
+    }
+
+    fn set_external_3d_object_visibility(
+        &mut self,
+        mut design: Design,
+        object_id: External3DObjectId,
+        visible: bool,
+    ) -> Result<Design, ErrOperation> {
+        design
+            .external_3d_objects
+            .get_mut(&object_id)
+            .ok_or(ErrOperation::External3DObjectDoesNotExist(object_id))?
+            .set_visible(visible);
+        Ok(design)
+    }
+
+    /// Create a bezier path for each subpath of the svg file `path`, on the plane given by
+    /// `parameters`.
     fn import_svg_path(
         &mut self,
         mut design: Design,
         path: PathBuf,
+        parameters: ensnano_design::SvgImportParameters,
+    ) -> Result<Design, ErrOperation> {
+        use ensnano_design::SvgImportPlane;
+
+        let mut paths = ensnano_design::read_svg_paths(&path)?;
+        let plane_id = match &parameters.plane {
+            SvgImportPlane::Existing(plane_id) => {
+                if !design.bezier_planes.contains_key(plane_id) {
+                    return Err(ErrOperation::BezierPlaneDoesNotExist(*plane_id));
+                }
+                *plane_id
+            }
+            SvgImportPlane::New(descriptor) => {
+                design.bezier_planes.make_mut().push(descriptor.clone())
+            }
+        };
+        parameters.place_paths(&mut paths, plane_id);
+
+        let mut new_paths = design.bezier_paths.make_mut();
+        let mut selection = Vec::new();
+        for path in paths {
+            let mut vertices = path.vertices().iter();
+            if let Some(first_vertex) = vertices.next() {
+                let path_id = new_paths.create_path(*first_vertex);
+                let new_path = new_paths
+                    .get_mut(&path_id)
+                    .ok_or(ErrOperation::PathDoesNotExist(path_id))?;
+                for vertex in vertices {
+                    new_path.add_vertex(*vertex);
+                }
+                new_path.cyclic = path.cyclic;
+                selection.push(Selection::BezierVertex(BezierVertexId {
+                    path_id,
+                    vertex_id: 0,
+                }));
+            }
+        }
+        drop(new_paths);
+        self.next_selection = Some(selection);
+
+        Ok(design)
+    }
+
+    fn rename_bezier_plane(
+        &mut self,
+        mut design: Design,
+        plane_id: BezierPlaneId,
+        name: String,
+    ) -> Result<Design, ErrOperation> {
+        let mut planes = design.bezier_planes.make_mut();
+        let plane = planes
+            .get_mut(&plane_id)
+            .ok_or(ErrOperation::BezierPlaneDoesNotExist(plane_id))?;
+        plane.name = name;
+        drop(planes);
+        Ok(design)
+    }
+
+    /// Remove a bezier plane, unless some vertices of bezier paths are positioned on it.
+    fn delete_bezier_plane(
+        &mut self,
+        mut design: Design,
+        plane_id: BezierPlaneId,
+    ) -> Result<Design, ErrOperation> {
+        if !design.bezier_planes.contains_key(&plane_id) {
+            return Err(ErrOperation::BezierPlaneDoesNotExist(plane_id));
+        }
+        let vertices = design.bezier_paths.vertices_on_plane(plane_id);
+        if !vertices.is_empty() {
+            return Err(ErrOperation::BezierPlaneIsUsed { plane_id, vertices });
+        }
+        design
+            .bezier_planes
+            .make_mut()
+            .remove(&plane_id)
+            .ok_or(ErrOperation::BezierPlaneDoesNotExist(plane_id))?;
+        Ok(design)
+    }
+
+    /// Create a copy of a bezier plane translated by `offset`. If `copy_paths` is true, the paths
+    /// whose vertices are all on the plane are copied on the new plane.
+    fn duplicate_bezier_plane(
+        &mut self,
+        mut design: Design,
+        plane_id: BezierPlaneId,
+        offset: Vec3,
+        copy_paths: bool,
+    ) -> Result<Design, ErrOperation> {
+        let mut copy = design
+            .bezier_planes
+            .get(&plane_id)
+            .ok_or(ErrOperation::BezierPlaneDoesNotExist(plane_id))?
+            .clone();
+        copy.position += offset;
+        if !copy.name.is_empty() {
+            copy.name = format!("{} copy", copy.name);
+        }
+        let new_plane_id = design.bezier_planes.make_mut().push(copy);
+
+        if copy_paths {
+            let copied_paths: Vec<BezierPath> = design
+                .bezier_paths
+                .values()
+                .filter(|path| {
+                    !path.vertices().is_empty()
+                        && path.vertices().iter().all(|v| v.plane_id == plane_id)
+                })
+                .map(|path| {
+                    let mut path = path.clone();
+                    for vertex in path.vertices_mut() {
+                        vertex.plane_id = new_plane_id;
+                    }
+                    path
+                })
+                .collect();
+            let mut paths = design.bezier_paths.make_mut();
+            for path in copied_paths {
+                paths.push(path);
+            }
+        }
+        Ok(design)
+    }
+
+    fn import_oxdna_configuration(
+        &mut self,
+        mut design: Design,
+        path: PathBuf,
+    ) -> Result<Design, ErrOperation> {
+        let structure = ensnano_exports::oxdna::import::import_oxdna(&design, &path)?;
+        design.additional_structure = Some(Arc::new(structure));
+        Ok(design)
+    }
+
+    fn reflect_selection(
+        &mut self,
+        mut design: Design,
+        helices: Vec<usize>,
+        grids: Vec<GridId>,
+        plane_origin: Vec3,
+        plane_normal: Vec3,
     ) -> Result<Design, ErrOperation> {
-        use ensnano_design::BezierPlaneId;
+        self.update_state_and_design(&mut design);
+        ensnano_design::design_operations::reflect_helices(
+            &mut design,
+            helices,
+            grids,
+            plane_origin,
+            plane_normal,
+        )?;
+        Ok(design)
+    }
 
-        // The imported bezier path will be attached to plane 0 so we need to ensure that it exists
-        if design.bezier_planes.get(&BezierPlaneId(0)).is_none() {
-            design = self.add_bezier_plane(design, Default::default());
+    fn duplicate_in_pattern(
+        &mut self,
+        mut design: Design,
+        helices: Vec<usize>,
+        strands: Vec<usize>,
+        pattern: DuplicationPattern,
+    ) -> Result<Design, ErrOperation> {
+        self.update_state_and_design(&mut design);
+        let copies = ensnano_design::design_operations::duplicate_in_pattern(
+            &mut design,
+            helices,
+            strands,
+            &pattern,
+        )?;
+        if let Some(distance) = pattern.connection_distance {
+            let mut connections = Vec::new();
+            for consecutive_copies in copies.windows(2) {
+                connections.extend(Self::close_strand_ends(
+                    &design,
+                    &consecutive_copies[0],
+                    &consecutive_copies[1],
+                    distance,
+                ));
+            }
+            for (prime3, prime5) in connections {
+                self.general_cross_over(&mut design.strands, prime3, prime5)?;
+            }
         }
+        Ok(design)
+    }
+
+    fn align_helices(
+        &mut self,
+        mut design: Design,
+        helices: Vec<usize>,
+        reference: usize,
+    ) -> Result<Design, ErrOperation> {
+        self.update_state_and_design(&mut design);
+        ensnano_design::design_operations::align_helices(&mut design, helices, reference)?;
+        Ok(design)
+    }
 
-        let mut paths = design.bezier_paths.make_mut();
-        let path = ensnano_design::read_first_svg_path(&path)?;
-        paths.push(path);
+    fn route_scaffold(
+        &mut self,
+        mut design: Design,
+        route: ScaffoldRoute,
+        parameters: ScaffoldRoutingParameters,
+    ) -> Result<Design, ErrOperation> {
+        self.update_state_and_design(&mut design);
+        ensnano_design::design_operations::route_scaffold(
+            &mut design,
+            &route,
+            &parameters,
+            ensnano_interactor::consts::SCAFFOLD_COLOR,
+        )?;
+        Ok(design)
+    }
 
-        drop(paths);
+    fn fill_staples(
+        &mut self,
+        mut design: Design,
+        parameters: StapleFillingParameters,
+    ) -> Result<Design, ErrOperation> {
+        self.update_state_and_design(&mut design);
+        let color_idx = &mut self.color_idx;
+        ensnano_design::design_operations::fill_staples(&mut design, &parameters, || {
+            crate::utils::new_color(color_idx)
+        })?;
+        Ok(design)
+    }
 
+    fn set_helices_parameters(
+        &mut self,
+        mut design: Design,
+        helices: Vec<usize>,
+        parameters: Option<ensnano_design::Parameters>,
+    ) -> Result<Design, ErrOperation> {
+        self.update_state_and_design(&mut design);
+        ensnano_design::design_operations::set_helices_parameters(
+            &mut design,
+            &helices,
+            parameters,
+        )?;
         Ok(design)
     }
+
+    /// Return the pairs (3' end, 5' end) of strand ends that are at distance at most `distance`,
+    /// with one end on a strand of `strands_1` and the other one on a strand of `strands_2`. Each
+    /// strand end appears in at most one pair.
+    fn close_strand_ends(
+        design: &Design,
+        strands_1: &[usize],
+        strands_2: &[usize],
+        distance: f32,
+    ) -> Vec<(Nucl, Nucl)> {
+        let parameters = design.parameters.unwrap_or_default();
+        let mut ends = Vec::new();
+        let mut end_info = HashMap::new();
+        for (copy, strands) in [strands_1, strands_2].iter().enumerate() {
+            for s_id in strands.iter() {
+                if let Some(strand) = design.strands.get(s_id).filter(|s| !s.cyclic) {
+                    for (nucl, prime3) in
+                        [(strand.get_5prime(), false), (strand.get_3prime(), true)]
+                    {
+                        if let Some(nucl) = nucl {
+                            if let Some(helix) = design.helices.get(&nucl.helix) {
+                                ends.push((
+                                    nucl,
+                                    helix.space_pos(&parameters, nucl.position, nucl.forward),
+                                ));
+                                end_info.insert(nucl, (copy, prime3));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let mut used = HashSet::new();
+        let mut ret = Vec::new();
+        for (n1, n2, _) in ensnano_design::pairs_of_close_nucleotides(&ends, distance) {
+            if used.contains(&n1) || used.contains(&n2) {
+                continue;
+            }
+            if let Some(((copy_1, prime3_1), (copy_2, prime3_2))) =
+                end_info.get(&n1).zip(end_info.get(&n2))
+            {
+                if copy_1 != copy_2 && prime3_1 != prime3_2 {
+                    used.insert(n1);
+                    used.insert(n2);
+                    ret.push(if *prime3_1 { (n1, n2) } else { (n2, n1) });
+                }
+            }
+        }
+        ret
+    }
 }
 
 fn nucl_pos_2d(helices: &Helices, nucl: &Nucl, segment: usize) -> Option<Vec2> {
@@ -3451,6 +4702,11 @@ enum ControllerState {
     },
     ChangingColor,
     SettingRollHelices,
+    UpdatingHyperboloid {
+        grid_id: GridId,
+        /// The design before the edition of the hyperboloid started
+        initial_design: AddressPointer<Design>,
+    },
     WithPendingOp {
         operation: Arc<dyn Operation>,
         design: AddressPointer<Design>,
@@ -3519,6 +4775,9 @@ enum ControllerState {
     },
     WithPausedSimulation {
         initial_design: AddressPointer<Design>,
+        /// The interface of the paused simulation, kept alive so that the simulation can be
+        /// advanced step by step
+        interface: Arc<Mutex<HelixSystemInterface>>,
     },
     Rolling {
         _interface: Arc<Mutex<RollInterface>>,
@@ -3532,6 +4791,9 @@ enum ControllerState {
     ChangingStrandName {
         strand_id: usize,
     },
+    ChangingStrandNote {
+        strand_id: usize,
+    },
 }
 
 impl Default for ControllerState {
@@ -3562,7 +4824,9 @@ impl ControllerState {
             Self::WithPausedSimulation { .. } => "WithPausedSimulation",
             Self::Rolling { .. } => "Rolling",
             Self::SettingRollHelices => "SettingRollHelices",
+            Self::UpdatingHyperboloid { .. } => "UpdatingHyperboloid",
             Self::ChangingStrandName { .. } => "ChangingStrandName",
+            Self::ChangingStrandNote { .. } => "ChangingStrandNote",
             Self::Twisting { .. } => "Twisting",
             Self::PositioningHelicesPastingPoint { .. } => "Positioning strand pasting point",
             Self::WithPendingHelicesDuplication { .. } => "With pending helices duplication",
@@ -3714,8 +4978,10 @@ impl ControllerState {
             Self::WithPausedSimulation { .. } => Self::Normal,
             Self::Rolling { .. } => Self::Normal,
             Self::SettingRollHelices => Self::Normal,
+            Self::UpdatingHyperboloid { .. } => Self::Normal,
             Self::Twisting { .. } => Self::Normal,
             Self::ChangingStrandName { .. } => Self::Normal,
+            Self::ChangingStrandNote { .. } => Self::Normal,
             Self::PositioningHelicesPastingPoint { .. } => self.clone(),
             Self::PositioningHelicesDuplicationPoint { .. } => self.clone(),
             Self::WithPendingHelicesDuplication { .. } => self.clone(),
@@ -3783,3 +5049,11 @@ impl StatePersitance {
         matches!(self, StatePersitance::Transitory)
     }
 }
+
+fn clear_domain_sequences(strand: &mut Strand) {
+    for domain in strand.domains.iter_mut() {
+        if let Domain::HelixDomain(interval) = domain {
+            interval.sequence = None;
+        }
+    }
+}
@@ -588,8 +588,16 @@ impl Controller {
             return Err(ErrOperation::CannotPasteHere);
         }
         for pasted_strand in pasted_strands.iter() {
-            let color = Self::new_color(color_idx);
             if pasted_strand.pastable {
+                let helices = pasted_strand
+                    .domains
+                    .iter()
+                    .filter_map(|d| match d {
+                        Domain::HelixDomain(dom) => Some(dom.helix),
+                        _ => None,
+                    })
+                    .collect();
+                let color = Self::new_strand_color(color_idx, design, &helices);
                 let junctions =
                     ensnano_design::read_junctions(pasted_strand.domains.as_slice(), false);
                 let strand = Strand {
@@ -599,6 +607,9 @@ impl Controller {
                     sequence: None,
                     cyclic: false,
                     name: None,
+                    invisible: false,
+                    note: None,
+                    keep_color: false,
                 };
                 let strand_id = if let Some(n) = design.strands.keys().max() {
                     n + 1
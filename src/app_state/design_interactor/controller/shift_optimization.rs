@@ -31,28 +31,31 @@ use std::sync::mpsc;
 
 fn read_scaffold_seq(
     design: &Design,
+    scaffold_id: usize,
     nucl_collection: &dyn NuclCollection,
     shift: usize,
 ) -> Result<BTreeMap<Nucl, char>, ErrOperation> {
-    let nb_skip = if let Some(sequence) = design.scaffold_sequence.as_ref() {
-        if sequence.len() == 0 {
+    let nb_skip = if let Some(sequence) = design.scaffold_sequences.get(&scaffold_id) {
+        if sequence.sequence.len() == 0 {
             return Err(ErrOperation::EmptyScaffoldSequence);
         }
-        sequence.len() - (shift % sequence.len())
+        sequence.sequence.len() - (shift % sequence.sequence.len())
     } else {
         return Err(ErrOperation::EmptyScaffoldSequence);
     };
     if let Some(mut sequence) = design
-        .scaffold_sequence
-        .as_ref()
-        .map(|s| s.chars().cycle().skip(nb_skip))
+        .scaffold_sequences
+        .get(&scaffold_id)
+        .map(|s| s.sequence.chars().cycle().skip(nb_skip))
     {
         let mut basis_map = BTreeMap::new();
-        let s_id = design.scaffold_id.ok_or(ErrOperation::NoScaffoldSet)?;
+        if !design.is_scaffold(scaffold_id) {
+            return Err(ErrOperation::NoScaffoldSet);
+        }
         let strand = design
             .strands
-            .get(&s_id)
-            .ok_or(ErrOperation::StrandDoesNotExist(s_id))?;
+            .get(&scaffold_id)
+            .ok_or(ErrOperation::StrandDoesNotExist(scaffold_id))?;
         for domain in &strand.domains {
             if let Domain::HelixDomain(dom) = domain {
                 for nucl_position in dom.iter() {
@@ -90,9 +93,12 @@ fn read_scaffold_seq(
     }
 }
 
-/// Shift the scaffold at an optimized poisition and return the corresponding score
+/// Shift the scaffold `scaffold_id` at an optimized poisition and return the corresponding score.
+///
+/// Only the staples that are paired with this scaffold are taken into account.
 pub fn optimize_shift<Nc: NuclCollection>(
     design: Arc<Design>,
+    scaffold_id: usize,
     nucl_collection: Arc<Nc>,
     chanel_reader: &mut dyn ShiftOptimizerReader,
 ) {
@@ -101,14 +107,19 @@ pub fn optimize_shift<Nc: NuclCollection>(
     chanel_reader.attach_result_chanel(result_rcv);
     chanel_reader.attach_progress_chanel(progress_rcv);
     std::thread::spawn(move || {
-        let result =
-            get_shift_optimization_result(design.as_ref(), progress_snd, nucl_collection.as_ref());
+        let result = get_shift_optimization_result(
+            design.as_ref(),
+            scaffold_id,
+            progress_snd,
+            nucl_collection.as_ref(),
+        );
         log_err!(result_snd.send(result));
     });
 }
 
 fn get_shift_optimization_result(
     design: &Design,
+    scaffold_id: usize,
     progress_channel: std::sync::mpsc::Sender<f32>,
     nucl_collection: &dyn NuclCollection,
 ) -> ShiftOptimizationResult {
@@ -116,15 +127,15 @@ fn get_shift_optimization_result(
     let mut best_shfit = 0;
     let mut best_result = String::new();
     let len = design
-        .scaffold_sequence
-        .as_ref()
-        .map(|s| s.len())
-        .ok_or(ErrOperation::NoScaffoldSet)?;
+        .scaffold_sequences
+        .get(&scaffold_id)
+        .map(|s| s.sequence.len())
+        .ok_or(ErrOperation::EmptyScaffoldSequence)?;
     for shift in 0..len {
         if shift % 100 == 0 {
             log_err!(progress_channel.send(shift as f32 / len as f32))
         }
-        let char_map = read_scaffold_seq(design, nucl_collection, shift)?;
+        let char_map = read_scaffold_seq(design, scaffold_id, nucl_collection, shift)?;
         let (score, result) = evaluate_shift(design, &char_map);
         if score < best_score {
             println!("shift {} score {}", shift, score);
@@ -137,6 +148,7 @@ fn get_shift_optimization_result(
         }
     }
     Ok(ShiftOptimizationOk {
+        scaffold_id,
         position: best_shfit,
         score: best_result,
     })
@@ -152,7 +164,7 @@ fn evaluate_shift(design: &Design, basis_map: &BTreeMap<Nucl, char>) -> (usize,
     let ultimatelybad = regex::Regex::new(r"G{5,}|C{5,}").unwrap();
     let ultimatelybad2 = regex::Regex::new(r"G{6,}|C{6,}").unwrap();
     for (s_id, strand) in design.strands.iter() {
-        if strand.length() == 0 || design.scaffold_id == Some(*s_id) {
+        if strand.length() == 0 || design.is_scaffold(*s_id) {
             continue;
         }
         let mut sequence = String::with_capacity(10000);
@@ -235,6 +247,7 @@ fn compl(c: Option<char>) -> Option<char> {
 }
 
 pub struct ShiftOptimizationOk {
+    pub scaffold_id: usize,
     pub position: usize,
     pub score: String,
 }
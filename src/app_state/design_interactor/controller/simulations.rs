@@ -22,7 +22,7 @@ pub use revolutions::*;
 
 use super::*;
 
-use ensnano_design::{grid::Grid, Parameters};
+use ensnano_design::{grid::Grid, OrganizerTree, Parameters};
 use ensnano_interactor::{RevolutionSurfaceSystemDescriptor, RigidBodyConstants};
 use mathru::algebra::linear::vector::vector::Vector;
 use mathru::analysis::differential_equation::ordinary::{
@@ -34,8 +34,9 @@ use rand::Rng;
 use rand_distr::{Exp, StandardNormal};
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 use ultraviolet::{Bivec3, Mat3};
 
 mod roller;
@@ -46,6 +47,16 @@ mod revolutions;
 
 const MAX_DERIVATIVE_NORM: f32 = 1e4;
 
+/// The delay between two reads of the interface of a paused simulation
+const PAUSED_POLLING_DELAY: Duration = Duration::from_millis(10);
+
+/// The minimum delay between two computations of the strain of the nucleotides, so that the
+/// strain is updated at most 10 times per second
+const STRAIN_UPDATE_PERIOD: Duration = Duration::from_millis(100);
+
+/// The rest length of the springs, in nanometers
+const L0: f32 = 0.7;
+
 macro_rules! bound_derivative {
     ($obj:ident) => {
         if $obj.mag() > MAX_DERIVATIVE_NORM {
@@ -60,6 +71,9 @@ struct HelixSystem {
     springs: Vec<(RigidNucl, RigidNucl)>,
     free_springs: Vec<(usize, usize)>,
     mixed_springs: Vec<(RigidNucl, usize)>,
+    /// The nucleotides linked by each spring, in the order of `springs`, then `mixed_springs`,
+    /// then `free_springs`
+    spring_nucls: Vec<(Nucl, Nucl)>,
     free_nucls: Vec<FreeNucl>,
     free_nucl_position: Vec<Vec3>,
     helices: Vec<RigidHelix>,
@@ -123,6 +137,8 @@ struct RigidHelix {
     pub center_to_origin: Vec3,
     pub mass: f32,
     pub locked: bool,
+    /// True if the helix belongs to a pinned group
+    pub pinned: bool,
     interval: (isize, isize),
 }
 
@@ -287,19 +303,11 @@ impl HelixSystem {
         let mut forces = vec![Vec3::zero(); nb_element];
         let mut torques = vec![Vec3::zero(); nb_element];
 
-        const L0: f32 = 0.7;
         const C_VOLUME: f32 = 2f32;
         let k_anchor = 1000. * self.rigid_parameters.k_spring;
 
-        let point_conversion = |nucl: &RigidNucl| {
-            let position = positions[nucl.helix]
-                + self.helices[nucl.helix]
-                    .center_to_origin
-                    .rotated_by(orientations[nucl.helix]);
-            let mut helix = Helix::new(position, orientations[nucl.helix]);
-            helix.roll(self.helices[nucl.helix].roll);
-            helix.space_pos(&self.parameters, nucl.position, nucl.forward)
-        };
+        let point_conversion =
+            |nucl: &RigidNucl| self.rigid_nucl_position(nucl, positions, orientations);
         let free_nucl_pos = |n: &usize| positions[*n + self.helices.len()];
 
         for spring in self.springs.iter() {
@@ -430,7 +438,7 @@ impl HelixSystem {
         }
 
         for (h_id, h) in self.helices.iter().enumerate() {
-            if h.locked {
+            if h.is_immobile() {
                 forces[h_id] = Vec3::zero();
                 torques[h_id] = Vec3::zero();
             }
@@ -441,6 +449,60 @@ impl HelixSystem {
 }
 
 impl HelixSystem {
+    fn rigid_nucl_position(
+        &self,
+        nucl: &RigidNucl,
+        positions: &[Vec3],
+        orientations: &[Rotor3],
+    ) -> Vec3 {
+        let position = positions[nucl.helix]
+            + self.helices[nucl.helix]
+                .center_to_origin
+                .rotated_by(orientations[nucl.helix]);
+        let mut helix = Helix::new(position, orientations[nucl.helix]);
+        helix.roll(self.helices[nucl.helix].roll);
+        helix.space_pos(&self.parameters, nucl.position, nucl.forward)
+    }
+
+    /// Return the elastic energy stored in each spring of the current state, in the order of
+    /// `self.spring_nucls`.
+    fn spring_energies(&self) -> Vec<f32> {
+        let (positions, orientations, _, _) = self.read_state(&self.init_cond());
+        let free_nucl_pos = |n: &usize| positions[*n + self.helices.len()];
+        let rigid_pos =
+            |nucl: &RigidNucl| self.rigid_nucl_position(nucl, &positions, &orientations);
+        let lengths = self
+            .springs
+            .iter()
+            .map(|(n1, n2)| (rigid_pos(n2) - rigid_pos(n1)).mag())
+            .chain(
+                self.mixed_springs
+                    .iter()
+                    .map(|(n1, n2)| (free_nucl_pos(n2) - rigid_pos(n1)).mag()),
+            )
+            .chain(
+                self.free_springs
+                    .iter()
+                    .map(|(n1, n2)| (free_nucl_pos(n2) - free_nucl_pos(n1)).mag()),
+            );
+        lengths
+            .map(|len| 0.5 * self.rigid_parameters.k_spring * (len - L0).powi(2))
+            .collect()
+    }
+
+    /// Return the strain of the nucleotides that are linked by a spring, which is the largest
+    /// elastic energy of the springs attached to them.
+    fn nucl_strain(&self) -> HashMap<Nucl, f32> {
+        let mut ret: HashMap<Nucl, f32> = HashMap::with_capacity(2 * self.spring_nucls.len());
+        for ((n1, n2), energy) in self.spring_nucls.iter().zip(self.spring_energies()) {
+            for nucl in [n1, n2] {
+                let strain = ret.entry(*nucl).or_insert(0.);
+                *strain = strain.max(energy);
+            }
+        }
+        ret
+    }
+
     fn read_state(&self, x: &Vector<f32>) -> (Vec<Vec3>, Vec<Rotor3>, Vec<Vec3>, Vec<Vec3>) {
         let mut positions = Vec::with_capacity(self.helices.len() + self.free_nucls.len());
         let mut rotations = Vec::with_capacity(self.helices.len() + self.free_nucls.len());
@@ -481,6 +543,29 @@ impl HelixSystem {
         (positions, rotations, linear_momentums, angular_momentums)
     }
 
+    /// Pin the helices whose identifier in the design is in `pinned_helices` and release the
+    /// others.
+    ///
+    /// `helix_map` maps the indices of `self.helices` to the identifiers of the helices in the
+    /// design. The momentums of the newly pinned helices are cancelled so that they stop
+    /// immediately.
+    fn set_pinned_helices(&mut self, helix_map: &[usize], pinned_helices: &HashSet<usize>) {
+        let mut state = self.init_cond();
+        for (i, h) in self.helices.iter_mut().enumerate() {
+            let pinned = helix_map
+                .get(i)
+                .map(|h_id| pinned_helices.contains(h_id))
+                .unwrap_or(false);
+            if pinned && !h.pinned {
+                for entry in (13 * i + 7)..(13 * i + 13) {
+                    state[entry] = 0.;
+                }
+            }
+            h.pinned = pinned;
+        }
+        self.last_state = Some(state);
+    }
+
     fn next_time(&mut self) {
         self.current_time = self.next_time;
         if let Some((t, _)) = self.brownian_heap.peek() {
@@ -590,6 +675,7 @@ impl RigidHelix {
             inertia_inverse: inertia_helix(x_max - x_min, 1.).inversed(),
             interval,
             locked: false,
+            pinned: false,
         }
     }
 
@@ -612,6 +698,7 @@ impl RigidHelix {
             inertia_inverse: inertia_helix(mass, 1.).inversed(),
             interval,
             locked: false,
+            pinned: false,
         }
     }
 
@@ -619,6 +706,11 @@ impl RigidHelix {
         self.center_of_mass
     }
 
+    /// Return true if the helix must be treated as a body of infinite mass
+    fn is_immobile(&self) -> bool {
+        self.locked || self.pinned
+    }
+
     fn height(&self) -> f32 {
         self.mass
     }
@@ -834,6 +926,11 @@ pub(super) struct HelixSystemThread {
     /// immeadiatly stop when the listener is dropped.
     interface: Weak<Mutex<HelixSystemInterface>>,
     constants: Arc<RigidHelixConstants>,
+    /// The identifiers in the design of the simulated helices
+    helix_map: Vec<usize>,
+    /// The strain of the nucleotides, computed at most once per `STRAIN_UPDATE_PERIOD`
+    strain: Arc<HashMap<Nucl, f32>>,
+    last_strain_update: Option<Instant>,
 }
 
 #[derive(Default)]
@@ -841,6 +938,11 @@ pub struct HelixSystemInterface {
     pub new_state: Option<RigidHelixState>,
     pub(super) nucl_shake: Option<ShakeTarget>,
     pub(super) parameters_update: Option<RigidBodyConstants>,
+    /// The identifiers of the helices that must be immobile
+    pub(super) pinned_helices: Option<HashSet<usize>>,
+    /// While the simulation is paused, the system only moves when a step is requested
+    pub(super) paused: bool,
+    pub(super) step_requested: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -849,6 +951,7 @@ pub struct RigidHelixState {
     orientations: Vec<Rotor3>,
     center_of_mass_from_helix: Vec<Vec3>,
     constants: Arc<RigidHelixConstants>,
+    strain: Arc<HashMap<Nucl, f32>>,
 }
 
 #[derive(Debug)]
@@ -882,11 +985,26 @@ impl HelixSystemThread {
         interface: &Arc<Mutex<HelixSystemInterface>>,
         interval_result: IntervalResult,
     ) -> Self {
+        let helix_map = interval_result.helix_map.clone();
         let constants = helix_system.get_constants(interval_result);
         Self {
             helix_system,
             interface: Arc::downgrade(interface),
             constants: Arc::new(constants),
+            helix_map,
+            strain: Default::default(),
+            last_strain_update: None,
+        }
+    }
+
+    fn update_strain_if_needed(&mut self) {
+        let outdated = self
+            .last_strain_update
+            .map(|t| t.elapsed() >= STRAIN_UPDATE_PERIOD)
+            .unwrap_or(true);
+        if outdated {
+            self.strain = Arc::new(self.helix_system.nucl_strain());
+            self.last_strain_update = Some(Instant::now());
         }
     }
 
@@ -898,6 +1016,18 @@ impl HelixSystemThread {
                 if let Some(parameters) = interface.parameters_update.take() {
                     self.helix_system.update_parameters(parameters)
                 }
+                if let Some(pinned_helices) = interface.pinned_helices.take() {
+                    self.helix_system
+                        .set_pinned_helices(&self.helix_map, &pinned_helices)
+                }
+                if interface.paused && !interface.step_requested {
+                    drop(interface);
+                    drop(interface_ptr);
+                    std::thread::sleep(PAUSED_POLLING_DELAY);
+                    continue;
+                }
+                let stepping = std::mem::take(&mut interface.step_requested);
+                self.update_strain_if_needed();
                 interface.new_state = Some(self.get_state());
                 drop(interface);
                 self.helix_system.next_time();
@@ -914,6 +1044,13 @@ impl HelixSystemThread {
                 if let Ok((_, y)) = solver.solve(&self.helix_system, &method) {
                     self.helix_system.last_state = y.last().cloned();
                 }
+                if stepping {
+                    // The loop will not read the state again until the next step, so the result
+                    // of the step is sent now.
+                    self.last_strain_update = None;
+                    self.update_strain_if_needed();
+                    interface_ptr.lock().unwrap().new_state = Some(self.get_state());
+                }
             }
         });
     }
@@ -932,6 +1069,7 @@ impl HelixSystemThread {
             orientations,
             center_of_mass_from_helix,
             constants: self.constants.clone(),
+            strain: self.strain.clone(),
         }
     }
 }
@@ -1042,6 +1180,9 @@ fn make_flexible_helices_system(
     let mut springs = Vec::with_capacity(xovers.len());
     let mut mixed_springs = Vec::with_capacity(xovers.len());
     let mut free_springs = Vec::with_capacity(xovers.len());
+    let mut spring_nucls = Vec::with_capacity(xovers.len());
+    let mut mixed_spring_nucls = Vec::new();
+    let mut free_spring_nucls = Vec::new();
     for (n1, n2) in xovers {
         log::debug!("xover {:?}", (n1, n2));
         let free_nucl1 = interval_results.nucl_map[&n1];
@@ -1058,6 +1199,7 @@ fn make_flexible_helices_system(
                 forward: n2.forward,
             };
             springs.push((rigid_1, rigid_2));
+            spring_nucls.push((n1, n2));
         }
     }
     for (n1, n2) in presenter.get_all_bounds() {
@@ -1074,6 +1216,7 @@ fn make_flexible_helices_system(
             };
             let free_id = interval_results.free_nucl_ids[&free_nucl2];
             mixed_springs.push((rigid_1, free_id));
+            mixed_spring_nucls.push((n1, n2));
         } else if let Some(h2) = free_nucl2.helix {
             let rigid_2 = RigidNucl {
                 helix: h2,
@@ -1082,12 +1225,16 @@ fn make_flexible_helices_system(
             };
             let free_id = interval_results.free_nucl_ids[&free_nucl1];
             mixed_springs.push((rigid_2, free_id));
+            mixed_spring_nucls.push((n2, n1));
         } else {
             let free_id_1 = interval_results.free_nucl_ids[&free_nucl1];
             let free_id_2 = interval_results.free_nucl_ids[&free_nucl2];
             free_springs.push((free_id_1, free_id_2));
+            free_spring_nucls.push((n1, n2));
         }
     }
+    spring_nucls.extend(mixed_spring_nucls);
+    spring_nucls.extend(free_spring_nucls);
     let mut anchors = vec![];
     let mut free_anchors = vec![];
     for anchor in presenter.get_design().anchors.iter() {
@@ -1120,6 +1267,7 @@ fn make_flexible_helices_system(
         springs,
         mixed_springs,
         free_springs,
+        spring_nucls,
         free_nucls: interval_results.free_nucls.clone(),
         free_nucl_position: interval_results.free_nucl_position.clone(),
         last_state: None,
@@ -1346,6 +1494,63 @@ pub enum SimulationOperation<'pres, 'reader> {
         reader: &'reader mut dyn SimulationReader,
     },
     FinishRelaxation,
+    /// Advance a paused simulation by one time step
+    Step,
+    SetPinnedGroups(Vec<GroupId>),
+}
+
+/// The identifiers of the helices that are members of the groups `group_ids`.
+///
+/// A group contains the helices that are directly in it, the helices of its nucleotides and
+/// grids, and the members of its sub-groups.
+pub(super) fn helices_of_groups(design: &Design, group_ids: &[GroupId]) -> HashSet<usize> {
+    let mut keys = Vec::new();
+    if let Some(tree) = design.organizer_tree.as_ref() {
+        collect_group_members(tree, group_ids, false, &mut keys);
+    }
+    let mut ret = HashSet::new();
+    for key in keys {
+        match key {
+            DnaElementKey::Helix(h_id) => {
+                ret.insert(*h_id);
+            }
+            DnaElementKey::Nucleotide { helix, .. } => {
+                ret.insert(*helix);
+            }
+            DnaElementKey::Grid(g_id) => {
+                let grid_id = FreeGridId(*g_id).to_grid_id();
+                ret.extend(design.helices.iter().filter_map(|(h_id, h)| {
+                    h.grid_position
+                        .as_ref()
+                        .filter(|pos| pos.grid == grid_id)
+                        .map(|_| *h_id)
+                }));
+            }
+            DnaElementKey::Strand(_) | DnaElementKey::CrossOver { .. } => (),
+        }
+    }
+    ret
+}
+
+fn collect_group_members<'a>(
+    tree: &'a OrganizerTree<DnaElementKey>,
+    group_ids: &[GroupId],
+    in_group: bool,
+    keys: &mut Vec<&'a DnaElementKey>,
+) {
+    match tree {
+        OrganizerTree::Leaf(key) => {
+            if in_group {
+                keys.push(key)
+            }
+        }
+        OrganizerTree::Node { childrens, id, .. } => {
+            let in_group = in_group || id.map(|id| group_ids.contains(&id)).unwrap_or(false);
+            for c in childrens.iter() {
+                collect_group_members(c, group_ids, in_group, keys);
+            }
+        }
+    }
 }
 
 pub trait SimulationReader {
@@ -1375,6 +1580,10 @@ impl SimulationUpdate for RigidHelixState {
         // since update positions is implemented, we do not need to move the helices.
     }
 
+    fn get_nucl_strain(&self) -> Option<Arc<HashMap<Nucl, f32>>> {
+        Some(self.strain.clone())
+    }
+
     fn update_positions(
         &self,
         identifier_nucl: &dyn NuclCollection,
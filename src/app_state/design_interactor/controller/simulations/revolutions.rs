@@ -696,6 +696,9 @@ impl SimulationUpdate for HelicesRouting {
                     name: None,
                     cyclic: false,
                     sequence: None,
+                    invisible: false,
+                    note: None,
+                    keep_color: false,
                 });
             }
         }
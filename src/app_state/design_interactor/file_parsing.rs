@@ -19,10 +19,11 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::*;
 use crate::controller::LoadDesignError;
 use crate::utils::id_generator::IdGenerator;
-use ensnano_design::{codenano, scadnano, Nucl};
+use ensnano_design::{cadnano, codenano, scadnano, Nucl};
 use std::path::{Path, PathBuf};
 
-mod cadnano;
+mod diagnostics;
+pub use diagnostics::JsonDiagnostic;
 mod junctions;
 pub(super) use junctions::StrandJunction;
 
@@ -30,6 +31,8 @@ impl DesignInteractor {
     /// Create a new data by reading a file. At the moment, the supported format are
     /// * codenano
     /// * icednano
+    /// * scadnano
+    /// * cadnano
     pub fn new_with_path(json_path: &PathBuf) -> Result<Self, LoadDesignError> {
         let mut xover_ids: IdGenerator<(Nucl, Nucl)> = Default::default();
         let mut design = read_file(json_path)?;
@@ -54,12 +57,18 @@ impl DesignInteractor {
 }
 
 /// Create a design by parsing a file
-use cadnano::{Cadnano, FromCadnano};
 fn read_file<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<Design, LoadDesignError> {
-    let json_str =
-        std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("File not found {:?}", path));
+    let json_str = std::fs::read_to_string(&path).map_err(LoadDesignError::CannotReadFile)?;
+    let extension = path
+        .as_ref()
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    parse_design(&json_str, extension.as_deref())
+}
 
-    let design: Result<Design, _> = serde_json::from_str(&json_str);
+/// Create a design from the content of a file whose extension is `extension`
+fn parse_design(json_str: &str, extension: Option<&str>) -> Result<Design, LoadDesignError> {
+    let design: Result<Design, _> = serde_json::from_str(json_str);
     // First try to read icednano format
     match design {
         Ok(mut design) => {
@@ -78,27 +87,32 @@ fn read_file<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<Design, LoadDe
         }
         Err(e) => {
             // If the file is not in icednano format, try the other supported format
-            let cdn_design: Result<codenano::Design<(), ()>, _> = serde_json::from_str(&json_str);
+            let is_json = extension == Some("json");
+            let cadnano_design: Option<cadnano::CadnanoDesign> = if is_json {
+                serde_json::from_str(json_str).ok()
+            } else {
+                None
+            };
+
+            let cdn_design: Result<codenano::Design<(), ()>, _> = serde_json::from_str(json_str);
 
             let scadnano_design: Result<scadnano::ScadnanoDesign, _> =
-                serde_json::from_str(&json_str);
+                serde_json::from_str(json_str);
 
-            // Try codenano format
-            if let Ok(scadnano) = scadnano_design {
+            if let Some(cadnano) = cadnano_design {
+                log::info!("ok cadnano");
+                Design::from_cadnano(&cadnano).map_err(LoadDesignError::CadnanoImportError)
+            } else if let Ok(scadnano) = scadnano_design {
                 Design::from_scadnano(&scadnano)
                     .map_err(|e| LoadDesignError::ScadnanoImportError(e))
             } else if let Ok(design) = cdn_design {
                 log::error!("{:?}", scadnano_design.err());
                 log::info!("ok codenano");
                 Ok(Design::from_codenano(&design))
-            } else if let Ok(cadnano) = Cadnano::from_file(path) {
-                log::info!("ok cadnano");
-                Ok(Design::from_cadnano(cadnano))
             } else {
                 log::error!("{:?}", e);
                 // The file is not in any supported format
-                //message("Unrecognized file format".into(), rfd::MessageLevel::Error);
-                Err(LoadDesignError::JsonError(e))
+                Err(diagnostics::diagnose_failure(json_str, extension, e))
             }
         }
     }
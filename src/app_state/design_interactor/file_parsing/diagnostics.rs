@@ -0,0 +1,417 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Diagnostics of the design files that could not be read.
+//!
+//! serde_json only reports the line and column at which the deserialization failed. To help the
+//! user locate the problem, the json text is scanned up to that position to retrieve the path of
+//! the value that was being read, e.g. `strands.3.domains[0].HelixDomain.end`.
+
+use crate::controller::LoadDesignError;
+use ensnano_design::{cadnano, codenano, scadnano};
+use serde_json::{error::Category, Value};
+
+/// The formats of the files that ENSnano can open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Ensnano,
+    Scadnano,
+    Cadnano,
+    Codenano,
+}
+
+impl std::fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Ensnano => "ENSnano",
+            Self::Scadnano => "scadnano",
+            Self::Cadnano => "cadnano",
+            Self::Codenano => "codenano",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Where and why the deserialization of a design file failed
+#[derive(Debug, Clone)]
+pub struct JsonDiagnostic {
+    /// The format that the file was expected to be in
+    pub format: FileFormat,
+    /// The path of the value that could not be read. Empty if the error is at the root of the
+    /// file.
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    /// The error reported by serde_json, without its position
+    pub message: String,
+    pub category: Category,
+    /// A suggestion on how to fix the problem
+    pub hint: Option<String>,
+}
+
+impl JsonDiagnostic {
+    fn new(format: FileFormat, json_str: &str, error: serde_json::Error) -> Self {
+        let position = format!(" at line {} column {}", error.line(), error.column());
+        let full_message = error.to_string();
+        let message = full_message
+            .strip_suffix(&position)
+            .unwrap_or(&full_message)
+            .to_string();
+        let mut path = json_path_at(json_str, error.line(), error.column());
+        if message.starts_with("missing field") {
+            // The error is reported at the end of the object that misses the field, after its
+            // last key.
+            if let Some(PathSegment::Key(_)) = path.last() {
+                path.pop();
+            }
+        }
+        let hint = match error.classify() {
+            Category::Eof => Some(TRUNCATED_FILE_HINT.to_string()),
+            Category::Syntax => Some(SYNTAX_ERROR_HINT.to_string()),
+            Category::Data | Category::Io => None,
+        };
+        Self {
+            format,
+            path: path_to_string(&path),
+            line: error.line(),
+            column: error.column(),
+            message,
+            category: error.classify(),
+            hint,
+        }
+    }
+}
+
+impl std::fmt::Display for JsonDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Could not read this {} file: {}",
+            self.format, self.message
+        )?;
+        let location = if self.path.is_empty() {
+            "the root of the file".to_string()
+        } else {
+            format!("`{}`", self.path)
+        };
+        write!(
+            f,
+            "The error occurred while reading {location} (line {}, column {}).",
+            self.line, self.column
+        )?;
+        if let Some(hint) = self.hint.as_ref() {
+            write!(f, "\n\n{hint}")?;
+        }
+        Ok(())
+    }
+}
+
+const TRUNCATED_FILE_HINT: &str = "The file ends unexpectedly. It may have been truncated, \
+    for example if ENSnano was closed while the design was being saved.";
+const SYNTAX_ERROR_HINT: &str = "The file is not valid json. It may have been modified by an \
+    other program.";
+const LEGACY_FILE_HINT: &str = "This file has no version number, it was probably created \
+    with a version of ENSnano older than 0.2.0. The DNA parameters of these designs are \
+    converted when they are loaded, but this file also contains values that can no longer be \
+    read.";
+
+/// Explain why `json_str` could not be read as a design in any of the supported formats.
+///
+/// `ensnano_error` is the error that was produced when trying to read `json_str` as an ENSnano
+/// design.
+pub(super) fn diagnose_failure(
+    json_str: &str,
+    extension: Option<&str>,
+    ensnano_error: serde_json::Error,
+) -> LoadDesignError {
+    let value: Value = match serde_json::from_str(json_str) {
+        Ok(value) => value,
+        Err(e) => {
+            // The file is not valid json, its format can only be guessed from its extension.
+            let format = if extension == Some("sc") {
+                FileFormat::Scadnano
+            } else {
+                FileFormat::Ensnano
+            };
+            return LoadDesignError::JsonError(JsonDiagnostic::new(format, json_str, e));
+        }
+    };
+
+    let format = guess_format(&value, extension);
+    if format == FileFormat::Ensnano {
+        match value.get("ensnano_version").and_then(Value::as_str) {
+            Some(required) if is_newer_than_current(required) => {
+                // The fields that could not be read were most likely introduced in the version
+                // that created the file.
+                return LoadDesignError::IncompatibleVersion {
+                    current: ensnano_design::ensnano_version(),
+                    required: required.to_string(),
+                };
+            }
+            Some(_) => (),
+            None => {
+                let mut diagnostic = JsonDiagnostic::new(format, json_str, ensnano_error);
+                diagnostic.hint = Some(LEGACY_FILE_HINT.to_string());
+                return LoadDesignError::JsonError(diagnostic);
+            }
+        }
+    }
+
+    let error = match format {
+        FileFormat::Ensnano => None,
+        FileFormat::Scadnano => serde_json::from_str::<scadnano::ScadnanoDesign>(json_str).err(),
+        FileFormat::Cadnano => serde_json::from_str::<cadnano::CadnanoDesign>(json_str).err(),
+        FileFormat::Codenano => serde_json::from_str::<codenano::Design<(), ()>>(json_str).err(),
+    };
+    let diagnostic = if let Some(error) = error {
+        JsonDiagnostic::new(format, json_str, error)
+    } else {
+        JsonDiagnostic::new(FileFormat::Ensnano, json_str, ensnano_error)
+    };
+    LoadDesignError::JsonError(diagnostic)
+}
+
+/// Return true if `version` is more recent than the running version of ENSnano.
+fn is_newer_than_current(version: &str) -> bool {
+    version_compare::compare(version, &ensnano_design::ensnano_version())
+        == Ok(version_compare::Cmp::Gt)
+}
+
+/// Guess the format of a json file from its content and extension.
+fn guess_format(value: &Value, extension: Option<&str>) -> FileFormat {
+    if value.get("ensnano_version").is_some() {
+        FileFormat::Ensnano
+    } else if value.get("vstrands").is_some() {
+        FileFormat::Cadnano
+    } else if value.get("version").is_some() {
+        if extension == Some("sc") || value.get("grid").is_some() {
+            FileFormat::Scadnano
+        } else {
+            FileFormat::Codenano
+        }
+    } else if extension == Some("sc") {
+        FileFormat::Scadnano
+    } else {
+        FileFormat::Ensnano
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+fn path_to_string(path: &[PathSegment]) -> String {
+    let mut ret = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) => {
+                if !ret.is_empty() {
+                    ret.push('.');
+                }
+                ret.push_str(key);
+            }
+            PathSegment::Index(idx) => ret.push_str(&format!("[{idx}]")),
+        }
+    }
+    ret
+}
+
+/// A container that is being read by [`json_path_at`]
+enum Frame {
+    Object {
+        key: Option<String>,
+        expecting_key: bool,
+    },
+    Array {
+        index: usize,
+    },
+}
+
+/// Return the path of the value being read at `line` and `column` in `json_str`.
+///
+/// Lines and columns are 1-based and columns are counted in bytes, as in the errors of
+/// serde_json. The character at the given position is not read.
+fn json_path_at(json_str: &str, line: usize, column: usize) -> Vec<PathSegment> {
+    let bytes = json_str.as_bytes();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut current_line = 1;
+    let mut current_column = 0;
+    let mut i = 0;
+    // Advance to the next byte and return false if the position of the error has been reached.
+    let mut advance = |byte: u8| {
+        if byte == b'\n' {
+            current_line += 1;
+            current_column = 0;
+        } else {
+            current_column += 1;
+        }
+        current_line < line || (current_line == line && current_column < column)
+    };
+    'scan: while i < bytes.len() {
+        if !advance(bytes[i]) {
+            break;
+        }
+        match bytes[i] {
+            b'{' => stack.push(Frame::Object {
+                key: None,
+                expecting_key: true,
+            }),
+            b'[' => stack.push(Frame::Array { index: 0 }),
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b':' => {
+                if let Some(Frame::Object { expecting_key, .. }) = stack.last_mut() {
+                    *expecting_key = false;
+                }
+            }
+            b',' => match stack.last_mut() {
+                Some(Frame::Object { key, expecting_key }) => {
+                    *key = None;
+                    *expecting_key = true;
+                }
+                Some(Frame::Array { index }) => *index += 1,
+                None => (),
+            },
+            b'"' => {
+                let start = i + 1;
+                let mut escaped = false;
+                loop {
+                    i += 1;
+                    if i >= bytes.len() || !advance(bytes[i]) {
+                        break 'scan;
+                    }
+                    match bytes[i] {
+                        b'\\' if !escaped => escaped = true,
+                        b'"' if !escaped => break,
+                        _ => escaped = false,
+                    }
+                }
+                if let Some(Frame::Object {
+                    key,
+                    expecting_key: true,
+                }) = stack.last_mut()
+                {
+                    *key = Some(String::from_utf8_lossy(&bytes[start..i]).to_string());
+                }
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    stack
+        .into_iter()
+        .filter_map(|frame| match frame {
+            Frame::Object { key, .. } => key.map(PathSegment::Key),
+            Frame::Array { index } => Some(PathSegment::Index(index)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_design;
+    use super::*;
+
+    fn one_helix_str() -> String {
+        let mut path = std::path::PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
+        path.push("tests");
+        path.push("one_helix.json");
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    fn diagnostic(json_str: &str) -> JsonDiagnostic {
+        match parse_design(json_str, Some("json")) {
+            Err(LoadDesignError::JsonError(diagnostic)) => diagnostic,
+            Err(e) => panic!("Unexpected error {e}"),
+            Ok(_) => panic!("The design should not be readable"),
+        }
+    }
+
+    #[test]
+    fn path_of_nested_values() {
+        let json = r#"{"a": {"b": [1, 2, {"c": tru"#;
+        let path = json_path_at(json, 1, json.len());
+        assert_eq!(path_to_string(&path), "a.b[2].c");
+        let path = json_path_at(json, 1, 14);
+        assert_eq!(path_to_string(&path), "a.b[0]");
+    }
+
+    #[test]
+    fn path_ignores_brackets_in_strings() {
+        let json = "{\n  \"a\": \"[{\\\"\",\n  \"b\": [\n    0,\n    x";
+        let path = json_path_at(json, 5, 5);
+        assert_eq!(path_to_string(&path), "b[1]");
+    }
+
+    #[test]
+    fn diagnose_wrong_type() {
+        let json = one_helix_str().replacen("\"y\": 5.0", "\"y\": \"five\"", 1);
+        let diagnostic = diagnostic(&json);
+        assert_eq!(diagnostic.format, FileFormat::Ensnano);
+        assert_eq!(diagnostic.category, Category::Data);
+        assert_eq!(diagnostic.path, "helices.1.position.y");
+        assert_eq!(diagnostic.line, 6);
+        assert!(diagnostic.message.starts_with("invalid type"));
+    }
+
+    #[test]
+    fn diagnose_malformed_file() {
+        let json = one_helix_str().replacen("\"visible\": true,", "\"visible\": true,,", 1);
+        let diagnostic = diagnostic(&json);
+        assert_eq!(diagnostic.category, Category::Syntax);
+        assert_eq!(diagnostic.path, "helices.1");
+        assert_eq!(diagnostic.line, 17);
+        assert_eq!(diagnostic.hint.as_deref(), Some(SYNTAX_ERROR_HINT));
+    }
+
+    #[test]
+    fn diagnose_truncated_file() {
+        let json = one_helix_str();
+        let diagnostic = diagnostic(&json[..json.len() / 2]);
+        assert_eq!(diagnostic.category, Category::Eof);
+        assert!(!diagnostic.path.is_empty());
+        assert_eq!(diagnostic.hint.as_deref(), Some(TRUNCATED_FILE_HINT));
+    }
+
+    #[test]
+    fn diagnose_file_without_version() {
+        let mut value: Value = serde_json::from_str(&one_helix_str()).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("ensnano_version");
+        value["helices"]["1"]["position"]["y"] = Value::from("five");
+        let diagnostic = diagnostic(&serde_json::to_string_pretty(&value).unwrap());
+        assert_eq!(diagnostic.path, "helices.1.position.y");
+        assert_eq!(diagnostic.hint.as_deref(), Some(LEGACY_FILE_HINT));
+    }
+
+    #[test]
+    fn unreadable_file_from_newer_version() {
+        let json = one_helix_str()
+            .replacen("\"0.2.1\"", "\"999.0.0\"", 1)
+            .replacen("\"y\": 5.0", "\"y\": \"five\"", 1);
+        match parse_design(&json, Some("json")) {
+            Err(LoadDesignError::IncompatibleVersion { required, .. }) => {
+                assert_eq!(required, "999.0.0")
+            }
+            _ => panic!("Expected an incompatible version error"),
+        }
+    }
+}
@@ -24,8 +24,10 @@ use ensnano_design::{
     BezierPathId, Extremity, HelixCollection, InstanciatedPiecewiseBezier, Nucl, VirtualNucl,
 };
 use ensnano_interactor::{
-    application::Camera3D, NeighbourDescriptor, NeighbourDescriptorGiver, ScaffoldInfo, Selection,
-    SuggestionParameters,
+    application::Camera3D,
+    design_summary::{DesignSummary, DesignSummaryCache},
+    NeighbourDescriptor, NeighbourDescriptorGiver, ScaffoldInfo, Selection, SuggestionParameters,
+    XoverStrain,
 };
 use ultraviolet::Mat4;
 
@@ -36,9 +38,24 @@ mod impl_main_reader;
 mod impl_reader2d;
 mod impl_reader3d;
 mod impl_readergui;
+mod spatial_index;
 use crate::scene::{HBond, HalfHBond};
 use design_content::DesignContent;
+use spatial_index::SpatialIndex;
 use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// A spatial index of the nucleotides, with the content it was built from.
+type SpatialIndexCache = Option<(AddressPointer<DesignContent>, Arc<SpatialIndex>)>;
+
+/// The summary of the last design for which it was requested, with the contributions of its
+/// strands.
+#[derive(Default)]
+struct SummaryCache {
+    design: Option<AddressPointer<Design>>,
+    strands: DesignSummaryCache,
+    summary: DesignSummary,
+}
 
 #[derive(Clone)]
 /// The structure that handles "read" operations on designs.
@@ -59,6 +76,11 @@ pub(super) struct Presenter {
     visibility_sive: Option<VisibilitySieve>,
     invisible_nucls: HashSet<Nucl>,
     bonds: AddressPointer<Vec<HBond>>,
+    /// Built on the first nearest-nucleotide query after a change of `content`
+    spatial_index: Arc<Mutex<SpatialIndexCache>>,
+    /// Shared by the successive versions of the presenter so that only the strands that changed
+    /// are read again when the summary is updated
+    summary: Arc<Mutex<SummaryCache>>,
 }
 
 impl Default for Presenter {
@@ -72,6 +94,8 @@ impl Default for Presenter {
             visibility_sive: None,
             invisible_nucls: Default::default(),
             bonds: Default::default(),
+            spatial_index: Default::default(),
+            summary: Default::default(),
         }
     }
 }
@@ -82,6 +106,121 @@ impl Presenter {
         self.content.get_staples(&self.current_design, self)
     }
 
+    /// Return a spatial index of the nucleotides of the current design, in the model's
+    /// coordinates.
+    pub(super) fn get_spatial_index(&self) -> Arc<SpatialIndex> {
+        let mut cache = self.spatial_index.lock().unwrap();
+        match cache.as_ref() {
+            Some((content, index)) if *content == self.content => index.clone(),
+            _ => {
+                let positions = self.content.nucleotide.iter().filter_map(|(id, nucl)| {
+                    let position = self.content.space_position.get(id)?;
+                    Some((*nucl, Vec3::from(*position)))
+                });
+                let index = Arc::new(SpatialIndex::new(positions));
+                *cache = Some((self.content.clone(), index.clone()));
+                index
+            }
+        }
+    }
+
+    /// Return the summary of the current design. Only the strands that changed since the last
+    /// summary was computed are read again.
+    pub(super) fn get_design_summary(&self) -> DesignSummary {
+        let mut cache = self.summary.lock().unwrap();
+        if cache.design.as_ref() != Some(&self.current_design) {
+            if let Some(old_design) = cache.design.take() {
+                let changed = ensnano_design::design_diff::changed_strand_ids(
+                    &old_design,
+                    &self.current_design,
+                );
+                cache.strands.update_strands(&self.current_design, changed);
+            } else {
+                cache.strands.reset(&self.current_design);
+            }
+            cache.summary = cache.strands.summary(&self.current_design);
+            cache.design = Some(self.current_design.clone());
+        }
+        cache.summary.clone()
+    }
+
+    /// Return the nucleotides of the staples in `strands` whose basis in `sequence` is not the
+    /// complement of the scaffold nucleotide they are paired with.
+    pub(super) fn get_sequence_mismatches(
+        &self,
+        strands: &[usize],
+        sequence: &str,
+    ) -> Vec<SequenceMismatch> {
+        let mut ret = Vec::new();
+        for s_id in strands
+            .iter()
+            .filter(|s_id| !self.current_design.is_scaffold(**s_id))
+        {
+            let strand = if let Some(strand) = self.current_design.strands.get(s_id) {
+                strand
+            } else {
+                continue;
+            };
+            let mut bases = sequence.chars().map(|c| c.to_ascii_uppercase());
+            let mut position = 0;
+            for domain in strand.domains.iter() {
+                match domain {
+                    ensnano_design::Domain::HelixDomain(interval) => {
+                        for nucl_position in interval.iter() {
+                            let nucl = Nucl {
+                                helix: interval.helix,
+                                position: nucl_position,
+                                forward: interval.forward,
+                            };
+                            let found = if let Some(found) = bases.next() {
+                                found
+                            } else {
+                                break;
+                            };
+                            let expected = self.get_scaffold_partner(&nucl).and_then(|partner| {
+                                compl(self.content.basis_map.get(&partner).cloned())
+                            });
+                            let found_canonical = if found == 'U' { 'T' } else { found };
+                            if let Some(expected) = expected {
+                                if "ATGC".contains(found_canonical) && found_canonical != expected {
+                                    ret.push(SequenceMismatch {
+                                        strand_id: *s_id,
+                                        position,
+                                        nucl,
+                                        expected,
+                                        found,
+                                    });
+                                }
+                            }
+                            position += 1;
+                        }
+                    }
+                    ensnano_design::Domain::Insertion { nb_nucl, .. } => {
+                        for _ in 0..*nb_nucl {
+                            bases.next();
+                            position += 1;
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Return the nucleotide of a scaffold that is paired with `nucl`, if any.
+    fn get_scaffold_partner(&self, nucl: &Nucl) -> Option<Nucl> {
+        let virtual_compl = Nucl::map_to_virtual_nucl(nucl.compl(), &self.current_design.helices)?;
+        let partner = self
+            .content
+            .nucl_collection
+            .virtual_to_real(&virtual_compl)?;
+        let partner_id = self.content.nucl_collection.get_identifier(partner)?;
+        let partner_strand = self.content.strand_map.get(partner_id)?;
+        self.current_design
+            .is_scaffold(*partner_strand)
+            .then(|| *partner)
+    }
+
     pub fn can_start_builder_at(&self, nucl: Nucl) -> bool {
         let left = self.current_design.get_neighbour_nucl(nucl.left());
         let right = self.current_design.get_neighbour_nucl(nucl.right());
@@ -135,6 +274,8 @@ impl Presenter {
             visibility_sive: None,
             invisible_nucls: Default::default(),
             bonds: Default::default(),
+            spatial_index: Default::default(),
+            summary: Default::default(),
         };
         ret.read_scaffold_seq();
         ret.collect_h_bonds();
@@ -173,78 +314,88 @@ impl Presenter {
     }
 
     fn read_scaffold_seq(&mut self) {
-        let sequence = self.current_design.scaffold_sequence.as_ref();
-        if sequence.is_none() {
+        if self.current_design.scaffold_sequences.is_empty() {
             return;
         }
-        let sequence: String = sequence
-            .unwrap()
-            .chars()
-            .filter(|c| c.is_alphabetic())
-            .collect();
-        let nb_skip = {
-            let shift = self.current_design.scaffold_shift.unwrap_or(0);
-            sequence.len() - (shift % sequence.len())
-        };
-        if let Some(mut sequence) = self.current_design.scaffold_sequence.as_ref().map(|s| {
-            let length = s.chars().filter(|c| c.is_alphabetic()).count();
-            s.chars()
+        // At this point, the basis map only contains the basis given by explicit sequences.
+        // The basis of the scaffolds are given by their sequences, and the basis derived
+        // from the scaffold sequences are only given to the nucleotides that do not have an
+        // explicit one.
+        let explicit_basis = self.content.basis_map.clone();
+        let mut basis_map = HashMap::clone(explicit_basis.as_ref());
+        for (s_id, scaffold_sequence) in self.current_design.scaffold_sequences.iter() {
+            let strand = if let Some(strand) = self
+                .current_design
+                .strands
+                .get(s_id)
+                .filter(|_| self.current_design.is_scaffold(*s_id))
+            {
+                strand
+            } else {
+                continue;
+            };
+            let length = scaffold_sequence.len();
+            if length == 0 {
+                continue;
+            }
+            let nb_skip = length - (scaffold_sequence.shift % length);
+            let mut sequence = scaffold_sequence
+                .sequence
+                .chars()
                 .filter(|c| c.is_alphabetic())
                 .cycle()
                 .skip(nb_skip)
-                .take(length)
-        }) {
-            let mut basis_map = HashMap::clone(self.content.basis_map.as_ref());
+                .take(length);
             let mut ran_out = false;
-            if let Some(strand) = self
-                .current_design
-                .scaffold_id
-                .as_ref()
-                .and_then(|s_id| self.current_design.strands.get(s_id))
-            {
-                for domain in &strand.domains {
-                    if let ensnano_design::Domain::HelixDomain(dom) = domain {
-                        for nucl_position in dom.iter() {
-                            let nucl = Nucl {
-                                helix: dom.helix,
-                                position: nucl_position,
-                                forward: dom.forward,
-                            };
-                            let basis = sequence.next();
-                            let basis_compl = compl(basis);
-                            log::debug!("basis {:?}, basis_compl {:?}", basis, basis_compl);
-                            if let Some((basis, basis_compl)) = basis.zip(basis_compl) {
-                                basis_map.insert(nucl, basis);
-                                if let Some(virtual_compl) = Nucl::map_to_virtual_nucl(
-                                    nucl.compl(),
-                                    &self.current_design.helices,
-                                ) {
-                                    if let Some(real_compl) =
-                                        self.content.nucl_collection.virtual_to_real(&virtual_compl)
-                                    {
-                                        basis_map.insert(*real_compl, basis_compl);
-                                    }
+            for domain in &strand.domains {
+                if let ensnano_design::Domain::HelixDomain(dom) = domain {
+                    for nucl_position in dom.iter() {
+                        let nucl = Nucl {
+                            helix: dom.helix,
+                            position: nucl_position,
+                            forward: dom.forward,
+                        };
+                        let basis = sequence.next();
+                        let basis_compl = compl(basis);
+                        log::debug!("basis {:?}, basis_compl {:?}", basis, basis_compl);
+                        if let Some((basis, basis_compl)) = basis.zip(basis_compl) {
+                            basis_map.insert(nucl, basis);
+                            if let Some(virtual_compl) = Nucl::map_to_virtual_nucl(
+                                nucl.compl(),
+                                &self.current_design.helices,
+                            ) {
+                                if let Some(real_compl) = self
+                                    .content
+                                    .nucl_collection
+                                    .virtual_to_real(&virtual_compl)
+                                    .filter(|n| !explicit_basis.contains_key(n))
+                                {
+                                    basis_map.insert(*real_compl, basis_compl);
                                 }
-                            } else if basis.is_none() {
-                                if !ran_out {
-                                    log::error!("Ran out of base for nucleotide {:?}. Scaffold sequence is too short", nucl);
-                                    ran_out = true;
-                                }
-                            } else {
-                                log::error!("Could not get virtual mapping of {:?}", nucl.compl())
                             }
+                        } else if basis.is_none() {
+                            if !ran_out {
+                                log::error!(
+                                    "Ran out of base for nucleotide {:?}. Sequence of scaffold {} is too short",
+                                    nucl,
+                                    s_id
+                                );
+                                ran_out = true;
+                            }
+                        } else {
+                            log::error!("Could not get virtual mapping of {:?}", nucl.compl())
                         }
-                    } else if let ensnano_design::Domain::Insertion { nb_nucl, .. } = domain {
-                        for _ in 0..*nb_nucl {
-                            sequence.next();
-                        }
+                    }
+                } else if let ensnano_design::Domain::Insertion { nb_nucl, .. } = domain {
+                    for _ in 0..*nb_nucl {
+                        sequence.next();
                     }
                 }
             }
-            let mut new_content = self.content.clone_inner();
-            new_content.basis_map = Arc::new(basis_map);
-            self.content = AddressPointer::new(new_content);
         }
+        let mut new_content = self.content.clone_inner();
+        new_content.basis_map = Arc::new(basis_map);
+        self.content = AddressPointer::new(new_content);
     }
 
     fn collect_h_bonds(&mut self) {
@@ -298,6 +449,7 @@ impl Presenter {
             .into();
         let a1 = (pos_backward - pos_forward).normalized();
         let forward_half = HalfHBond {
+            nucl_id: forward_id,
             backbone: pos_forward,
             center_of_mass: pos_forward + 2. * a1 * ensnano_exports::oxdna::BACKBONE_TO_CM,
             base: self.content.basis_map.get(&forward_nucl).cloned(),
@@ -305,6 +457,7 @@ impl Presenter {
         };
 
         let backward_half = HalfHBond {
+            nucl_id: backward_id,
             backbone: pos_backward,
             center_of_mass: pos_backward - 2. * a1 * ensnano_exports::oxdna::BACKBONE_TO_CM,
             base: self.content.basis_map.get(&backward_nucl).cloned(),
@@ -375,6 +528,7 @@ impl Presenter {
                     Selection::BezierControlPoint { .. } => false,
                     Selection::BezierTengent { .. } => false,
                     Selection::BezierVertex(_) => false,
+                    Selection::External3DObject(_) => false,
                 };
         }
         ret
@@ -412,6 +566,45 @@ impl Presenter {
             .and_then(|s| s.domains.get(d_id))
     }
 
+    /// Return the elements of the named selection `idx` that still exist in the design, and the
+    /// number of elements that do not exist anymore.
+    pub fn get_named_selection(&self, idx: usize) -> Option<(Vec<Selection>, usize)> {
+        use ensnano_design::elements::DnaElementKey;
+        use ensnano_interactor::SelectionConversion;
+        let named_selection = self.current_design.named_selections.get(idx)?;
+        let mut selection = Vec::with_capacity(named_selection.elements.len());
+        let mut nb_dropped = 0;
+        for element in named_selection.elements.iter() {
+            let exists = match element {
+                DnaElementKey::Grid(g_id) => self
+                    .current_design
+                    .free_grids
+                    .get_from_g_id(&GridId::FreeGrid(*g_id))
+                    .is_some(),
+                DnaElementKey::Strand(s_id) => self.current_design.strands.contains_key(s_id),
+                DnaElementKey::Helix(h_id) => self.current_design.helices.contains_key(h_id),
+                DnaElementKey::Nucleotide {
+                    helix,
+                    position,
+                    forward,
+                } => self.content.nucl_collection.contains_nucl(&Nucl {
+                    helix: *helix,
+                    position: *position,
+                    forward: *forward,
+                }),
+                DnaElementKey::CrossOver { xover_id } => {
+                    self.junctions_ids.get_element(*xover_id).is_some()
+                }
+            };
+            if exists {
+                selection.push(element.to_selection(0));
+            } else {
+                nb_dropped += 1;
+            }
+        }
+        Some((selection, nb_dropped))
+    }
+
     pub(super) fn get_owned_nucl_collection(&self) -> Arc<impl NuclCollection> {
         self.content.nucl_collection.clone()
     }
@@ -492,6 +685,80 @@ impl Presenter {
         Some((Vec3::from(pos1) - Vec3::from(pos2)).mag())
     }
 
+    pub fn get_xover_strain(&self, xover_id: usize) -> Option<f32> {
+        let (n1, n2) = self.junctions_ids.get_element(xover_id)?;
+        ensnano_design::design_check::xover_strain(&self.current_design, n1, n2)
+    }
+
+    /// Return the strain of all the cross-overs of the design, from the most strained to the
+    /// least strained one.
+    pub fn get_xovers_by_strain(&self) -> Vec<XoverStrain> {
+        let mut ret: Vec<XoverStrain> = self
+            .junctions_ids
+            .get_all_elements()
+            .into_iter()
+            .filter_map(|(xover_id, xover)| {
+                Some(XoverStrain {
+                    xover_id,
+                    xover,
+                    strain: self.get_xover_strain(xover_id)?,
+                    checked: self.current_design.checked_xovers.contains(&xover_id),
+                })
+            })
+            .collect();
+        ret.sort_by(|a, b| b.strain.total_cmp(&a.strain));
+        ret
+    }
+
+    /// Return the strained cross-overs between a helix of the grid `grid_id` and a helix that is
+    /// not on that grid, from the most strained to the least strained one.
+    pub fn get_strained_xovers_leaving_grid(&self, grid_id: GridId) -> Vec<XoverStrain> {
+        let is_on_grid = |h_id: usize| {
+            self.current_design
+                .helices
+                .get(&h_id)
+                .and_then(|h| h.grid_position)
+                .map(|position| position.grid == grid_id)
+                .unwrap_or(false)
+        };
+        self.get_xovers_by_strain()
+            .into_iter()
+            .filter(|xover| {
+                xover.strain > ensnano_design::design_check::STRAINED_XOVER_THRESHOLD
+                    && is_on_grid(xover.xover.0.helix) != is_on_grid(xover.xover.1.helix)
+            })
+            .collect()
+    }
+
+    /// Return the value of the property `coloring` for each non-scaffold strand of the design
+    /// for which it is known.
+    pub fn get_strand_property_values(
+        &self,
+        coloring: ensnano_interactor::graphics::StrandColoring,
+    ) -> BTreeMap<usize, f32> {
+        use ensnano_interactor::graphics::StrandColoring;
+        if coloring == StrandColoring::Stored {
+            return BTreeMap::new();
+        }
+        let basis_map = self.content.basis_map.as_ref();
+        ensnano_design::staple_report::staple_stats(
+            self.current_design.as_ref(),
+            |nucl| basis_map.get(nucl).cloned(),
+            &Default::default(),
+        )
+        .into_iter()
+        .filter_map(|stats| {
+            let value = match coloring {
+                StrandColoring::Stored => None,
+                StrandColoring::Length => Some(stats.length as f32),
+                StrandColoring::NbDomains => Some(stats.nb_domains as f32),
+                StrandColoring::MeltingTemperature => stats.melting_temperature,
+            };
+            value.map(|v| (stats.strand_id, v))
+        })
+        .collect()
+    }
+
     pub fn get_id_of_xover_involving_nucl(&self, nucl: Nucl) -> Option<usize> {
         self.junctions_ids
             .get_all_elements()
@@ -500,12 +767,30 @@ impl Presenter {
             .map(|t| t.0)
     }
 
-    pub fn export(&self, export_path: &PathBuf, export_type: ExportType) -> ExportResult {
+    pub fn export(
+        &self,
+        export_path: &PathBuf,
+        export_type: ExportType,
+        strands: Option<&HashSet<usize>>,
+    ) -> ExportResult {
         ensnano_exports::export(
             &self.current_design,
             export_type,
             Some(self.content.basis_map.as_ref()),
             export_path,
+            strands,
+        )
+    }
+
+    /// The sequences of `strands`, formatted to be copied to the clipboard.
+    pub fn sequences_text(
+        &self,
+        strands: &[usize],
+    ) -> Option<ensnano_exports::fasta::SequencesText> {
+        ensnano_exports::fasta::sequences_text(
+            &self.current_design,
+            strands,
+            Some(self.content.basis_map.as_ref()),
         )
     }
 
@@ -669,10 +954,19 @@ impl DesignReader {
             .map(|s| s.length())
     }
 
-    pub fn get_scaffold_info(&self) -> Option<ScaffoldInfo> {
-        let id = self.presenter.current_design.scaffold_id?;
+    /// Return the informations about the scaffold `id`, or `None` if `id` is not the identifier of a
+    /// scaffold.
+    pub fn get_scaffold_info(&self, id: usize) -> Option<ScaffoldInfo> {
+        if !self.presenter.current_design.is_scaffold(id) {
+            return None;
+        }
         let length = self.get_strand_length(id)?;
-        let shift = self.presenter.current_design.scaffold_shift;
+        let shift = self
+            .presenter
+            .current_design
+            .scaffold_sequences
+            .get(&id)
+            .map(|seq| seq.shift);
         let starting_nucl = self
             .presenter
             .current_design
@@ -687,6 +981,15 @@ impl DesignReader {
         })
     }
 
+    pub fn get_scaffolds_info(&self) -> Vec<ScaffoldInfo> {
+        self.presenter
+            .current_design
+            .scaffold_ids
+            .iter()
+            .filter_map(|id| self.get_scaffold_info(*id))
+            .collect()
+    }
+
     pub fn get_camera_with_id(&self, cam_id: ensnano_design::CameraId) -> Option<Camera3D> {
         self.presenter
             .current_design
@@ -699,6 +1002,15 @@ impl DesignReader {
             })
     }
 
+    /// The identifiers of the saved cameras, in increasing order.
+    pub fn get_camera_ids(&self) -> Vec<ensnano_design::CameraId> {
+        self.presenter
+            .current_design
+            .get_cameras()
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
     pub fn get_nth_camera(&self, n: u32) -> Option<Camera3D> {
         self.presenter
             .current_design
@@ -717,6 +1029,14 @@ impl DesignReader {
             .get_favourite_camera()
             .map(|c| (c.position, c.orientation))
     }
+
+    pub fn get_fog_settings(&self) -> ensnano_design::FogSettings {
+        self.presenter.current_design.fog.clone()
+    }
+
+    pub fn get_design_summary(&self) -> DesignSummary {
+        self.presenter.get_design_summary()
+    }
 }
 
 impl HelixPresenter for Presenter {
@@ -796,6 +1116,12 @@ pub trait SimulationUpdate: Send + Sync {
     }
 
     fn update_design(&self, design: &mut Design);
+
+    /// Return the strain of the nucleotides, if the simulation computes it. Nucleotides that are
+    /// not in the returned map are not strained.
+    fn get_nucl_strain(&self) -> Option<Arc<HashMap<Nucl, f32>>> {
+        None
+    }
 }
 
 pub trait NuclCollection: Send + Sync + 'static {
@@ -19,7 +19,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 use super::*;
 use crate::scene::GridInstance;
 use ahash::RandomState;
-use ensnano_design::elements::DnaElement;
+use ensnano_design::elements::{DnaElement, DnaElementKey};
 use ensnano_design::grid::{GridObject, GridPosition, HelixGridPosition};
 use ensnano_design::*;
 use ensnano_interactor::{
@@ -103,6 +103,9 @@ pub(super) struct DesignContent {
     pub helix_map: HashMap<u32, usize, RandomState>,
     /// Maps the identifier of an element to its color
     pub color: HashMap<u32, u32, RandomState>,
+    /// Maps the identifier of an element to the color of the innermost organizer group that
+    /// contains it, if this group has a color
+    pub group_color: HashMap<u32, u32, RandomState>,
     pub basis_map: Arc<HashMap<Nucl, char, RandomState>>,
     pub prime3_set: Vec<Prime3End>,
     pub elements: Vec<DnaElement>,
@@ -112,6 +115,8 @@ pub(super) struct DesignContent {
     pub loopout_bonds: Vec<LoopoutBond>,
     /// Maps bonds identifier to the length of the corresponding insertion.
     pub insertion_length: HashMap<u32, usize, RandomState>,
+    /// The strain of the nucleotides given by the last update of the running simulation
+    pub nucl_strain: Option<Arc<HashMap<Nucl, f32>>>,
 }
 
 impl DesignContent {
@@ -173,10 +178,26 @@ impl DesignContent {
         self.grid_manager.small_spheres.contains(&g_id)
     }
 
+    pub(super) fn grid_is_visible(&self, g_id: GridId) -> bool {
+        self.grid_manager.get_visibility(g_id)
+    }
+
     pub(super) fn grid_has_persistent_phantom(&self, g_id: GridId) -> bool {
         !self.grid_manager.no_phantoms.contains(&g_id)
     }
 
+    pub(super) fn get_grid_phantom_length(&self, g_id: GridId) -> Option<usize> {
+        self.grid_manager
+            .grids
+            .get(&g_id)
+            .and_then(|g| g.phantom_length)
+    }
+
+    pub(super) fn get_helix_phantom_length(&self, h_id: usize) -> Option<usize> {
+        let position = self.grid_manager.get_helix_grid_position(h_id)?;
+        self.get_grid_phantom_length(position.grid)
+    }
+
     pub(super) fn get_grid_nb_turn(&self, g_id: GridId) -> Option<f32> {
         self.grid_manager
             .grids
@@ -222,7 +243,7 @@ impl DesignContent {
             Default::default();
         let basis_map = self.basis_map.as_ref();
         for (s_id, strand) in design.strands.iter() {
-            if strand.length() == 0 || design.scaffold_id == Some(*s_id) {
+            if strand.length() == 0 || design.is_scaffold(*s_id) {
                 continue;
             }
             let mut sequence = String::new();
@@ -230,11 +251,11 @@ impl DesignContent {
             let mut previous_char_is_basis = None;
             let mut intervals = StapleIntervals {
                 staple_id: *s_id,
+                scaffold_id: None,
                 intervals: Vec::new(),
             };
             for domain in &strand.domains {
                 let mut staple_domain = None;
-                let scaffold = design.scaffold_id.and_then(|id| design.strands.get(&id));
                 if !first {
                     sequence.push(' ');
                 }
@@ -263,15 +284,28 @@ impl DesignContent {
                             previous_char_is_basis = Some(false);
                         }
                         if let Some(virtual_nucl) = Nucl::map_to_virtual_nucl(nucl, helices) {
-                            if let Some(scaffold) = scaffold {
-                                let result = scaffold
-                                    .locate_virtual_nucl(&virtual_nucl.compl(), helices)
-                                    .map(|v| ScaffoldPosition {
-                                        domain_id: v.domain_id,
-                                        scaffold_position: (v.pos_on_strand + scaffold.length()
-                                            - design.scaffold_shift.unwrap_or(0))
-                                            % scaffold.length(),
+                            if !design.scaffold_ids.is_empty() {
+                                let result =
+                                    design.scaffolds().find_map(|(scaffold_id, scaffold)| {
+                                        let shift = design
+                                            .scaffold_sequences
+                                            .get(&scaffold_id)
+                                            .map(|seq| seq.shift)
+                                            .unwrap_or(0);
+                                        scaffold
+                                            .locate_virtual_nucl(&virtual_nucl.compl(), helices)
+                                            .map(|v| ScaffoldPosition {
+                                                scaffold_id,
+                                                domain_id: v.domain_id,
+                                                scaffold_position: (v.pos_on_strand
+                                                    + scaffold.length()
+                                                    - shift % scaffold.length())
+                                                    % scaffold.length(),
+                                            })
                                     });
+                                if intervals.scaffold_id.is_none() {
+                                    intervals.scaffold_id = result.map(|pos| pos.scaffold_id);
+                                }
                                 if staple_domain.is_none() {
                                     staple_domain = Some(StapleDomain::init(result));
                                 }
@@ -317,6 +351,7 @@ impl DesignContent {
                     s_id: *s_id,
                     sequence,
                     strand_name: strand.name.clone(),
+                    note: strand.note.clone(),
                     domain_decomposition: presenter.decompose_length(*s_id),
                     length: strand.length(),
                     color: strand.color & 0xFFFFFF,
@@ -359,18 +394,29 @@ impl DesignContent {
                     .map(|split| split.1.to_string())
                     .unwrap_or(staple_info.domain_decomposition.clone()),
                 intervals: staple_info.intervals.clone(),
+                note: staple_info.note.clone().unwrap_or_default(),
             });
         }
         ret
     }
 
+    /// Return false iff the element `id` belongs to a hidden strand.
+    fn strand_is_visible(&self, design: &Design, id: u32) -> bool {
+        self.strand_map
+            .get(&id)
+            .and_then(|s_id| design.strands.get(s_id))
+            .map(|s| !s.invisible)
+            .unwrap_or(true)
+    }
+
     pub fn get_all_visible_nucl_ids(
         &self,
         design: &Design,
         invisible_nucls: &HashSet<Nucl>,
     ) -> Vec<u32> {
-        let check_visiblity = |&(_, v): &(&u32, &Nucl)| {
+        let check_visiblity = |&(id, v): &(&u32, &Nucl)| {
             !invisible_nucls.contains(v)
+                && self.strand_is_visible(design, *id)
                 && design
                     .helices
                     .get(&v.helix)
@@ -389,8 +435,9 @@ impl DesignContent {
         design: &Design,
         invisible_nucls: &HashSet<Nucl>,
     ) -> Vec<u32> {
-        let check_visiblity = |&(_, bound): &(&u32, &(Nucl, Nucl))| {
+        let check_visiblity = |&(id, bound): &(&u32, &(Nucl, Nucl))| {
             !(invisible_nucls.contains(&bound.0) && invisible_nucls.contains(&bound.1))
+                && self.strand_is_visible(design, *id)
                 && (design
                     .helices
                     .get(&bound.0.helix)
@@ -421,11 +468,15 @@ pub struct Staple {
     pub domain_decomposition: String,
     pub length_str: String,
     pub intervals: StapleIntervals,
+    pub note: String,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct StapleIntervals {
     pub staple_id: usize,
+    /// The scaffold that the first paired domain of the staple is paired with
+    #[serde(skip)]
+    pub scaffold_id: Option<usize>,
     pub intervals: Vec<(isize, isize)>,
 }
 
@@ -433,6 +484,7 @@ struct StapleInfo {
     s_id: usize,
     sequence: String,
     strand_name: Option<Cow<'static, str>>,
+    note: Option<String>,
     color: u32,
     group_names: Vec<String>,
     domain_decomposition: String,
@@ -476,7 +528,11 @@ impl DesignContent {
         let mut suggestion_maker = XoverSuggestions::default();
         let mut insertion_length = HashMap::default();
         xover_ids.agree_on_next_id(&mut new_junctions);
-        let rainbow_strand = design.scaffold_id.filter(|_| design.rainbow_scaffold);
+        let rainbow_strands: Vec<usize> = if design.rainbow_scaffold {
+            design.scaffold_ids.clone()
+        } else {
+            vec![]
+        };
         let grid_manager = design.get_updated_grid_data().clone();
 
         for (s_id, strand) in design.strands.iter_mut() {
@@ -484,14 +540,20 @@ impl DesignContent {
                 id: *s_id,
                 length: strand.length(),
                 domain_lengths: strand.domain_lengths(),
+                name: strand.name.as_ref().map(|n| n.to_string()),
+                visible: !strand.invisible,
+                anchored: !design.anchors.is_empty()
+                    && strand.nucls().iter().all(|n| design.anchors.contains(n)),
+                color: strand.color,
             });
             let parameters = design.parameters.unwrap_or_default();
             strand.update_insertions(&design.helices, &parameters);
             let mut strand_position = 0;
+            let nb_loopouts = (loopout_nucls.len(), loopout_bonds.len());
             let strand_seq = strand.sequence.as_ref().filter(|s| s.is_ascii());
             let color = strand.color;
             let mut last_xover_junction: Option<&mut DomainJunction> = None;
-            let rainbow_len = if Some(*s_id) == rainbow_strand {
+            let rainbow_len = if rainbow_strands.contains(s_id) {
                 strand.length()
             } else {
                 0
@@ -712,11 +774,16 @@ impl DesignContent {
                         }
                     }
                 }
-                if let Some(nucl) = old_nucl {
+                if let Some(nucl) = old_nucl.filter(|_| !strand.invisible) {
                     let color = strand.color;
                     prime3_set.push(Prime3End { nucl, color });
                 }
             }
+            if strand.invisible {
+                // The insertions of hidden strands are not drawn
+                loopout_nucls.truncate(nb_loopouts.0);
+                loopout_bonds.truncate(nb_loopouts.1);
+            }
             old_nucl = None;
             old_nucl_id = None;
         }
@@ -745,6 +812,7 @@ impl DesignContent {
             strand_map,
             space_position,
             color: color_map,
+            group_color: Default::default(),
             helix_map,
             basis_map: Arc::new(basis_map),
             prime3_set,
@@ -754,18 +822,16 @@ impl DesignContent {
             loopout_bonds,
             loopout_nucls,
             insertion_length,
+            nucl_strain: None,
         };
         let suggestions = suggestion_maker.get_suggestions(&design, suggestion_parameters);
         ret.suggestions = suggestions;
+        ret.read_group_colors(&design, &new_junctions);
 
         drop(groups);
 
         if log::log_enabled!(log::Level::Warn) {
-            if let Some(s) = design
-                .scaffold_id
-                .as_ref()
-                .and_then(|s_id| design.strands.get(s_id))
-            {
+            for (_, s) in design.scaffolds() {
                 for d in s.domains.iter() {
                     if let Domain::HelixDomain(interval) = d {
                         for n in interval.iter() {
@@ -790,6 +856,60 @@ impl DesignContent {
         (ret, design, new_junctions)
     }
 
+    /// Fill the `group_color` map using the organizer tree and the group attributes of the
+    /// design.
+    fn read_group_colors(&mut self, design: &Design, junctions: &JunctionsIds) {
+        let tree = if let Some(tree) = design.organizer_tree.as_ref() {
+            tree
+        } else {
+            return;
+        };
+        let mut key_colors = BTreeMap::new();
+        collect_group_colors(tree, design, None, 0, &mut key_colors);
+        if key_colors.is_empty() {
+            return;
+        }
+
+        let mut strand_elements: HashMap<usize, Vec<u32>> = HashMap::new();
+        for (e_id, s_id) in self.strand_map.iter() {
+            strand_elements.entry(*s_id).or_default().push(*e_id);
+        }
+        // For each element, the depth of the group whose color is used
+        let mut depths: HashMap<u32, usize> = HashMap::new();
+        for (key, (depth, color)) in key_colors.into_iter() {
+            let ids = match key {
+                DnaElementKey::Strand(s_id) => strand_elements.remove(&s_id).unwrap_or_default(),
+                DnaElementKey::Nucleotide {
+                    helix,
+                    position,
+                    forward,
+                } => self
+                    .nucl_collection
+                    .get_identifier(&Nucl {
+                        helix,
+                        position,
+                        forward,
+                    })
+                    .cloned()
+                    .into_iter()
+                    .collect(),
+                DnaElementKey::CrossOver { xover_id } => junctions
+                    .get_element(xover_id)
+                    .and_then(|bond| self.identifier_bound.get(&bond))
+                    .cloned()
+                    .into_iter()
+                    .collect(),
+                DnaElementKey::Helix(_) | DnaElementKey::Grid(_) => vec![],
+            };
+            for id in ids {
+                if depths.get(&id).map(|d| *d <= depth).unwrap_or(true) {
+                    depths.insert(id, depth);
+                    self.group_color.insert(id, color);
+                }
+            }
+        }
+    }
+
     fn update_junction(
         new_xover_ids: &mut JunctionsIds,
         junction: &mut DomainJunction,
@@ -826,7 +946,8 @@ impl DesignContent {
     }
 
     pub fn read_simualtion_update(&mut self, update: &dyn SimulationUpdate) {
-        update.update_positions(self.nucl_collection.as_ref(), &mut self.space_position)
+        update.update_positions(self.nucl_collection.as_ref(), &mut self.space_position);
+        self.nucl_strain = update.get_nucl_strain();
     }
 }
 
@@ -924,7 +1045,8 @@ trait GridInstancesMaker {
 impl GridInstancesMaker for GridData {
     fn grid_instances(&self, design_id: usize) -> BTreeMap<GridId, GridInstance> {
         let mut ret = BTreeMap::new();
-        for (g_id, g) in self.grids.iter() {
+        // Hidden grids are neither drawn nor pickable in the 3D view
+        for (g_id, g) in self.grids.iter().filter(|(_, g)| !g.invisible) {
             let grid = GridInstance {
                 grid: g.clone(),
                 min_x: -2,
@@ -937,10 +1059,24 @@ impl GridInstancesMaker for GridData {
                 fake: false,
                 visible: !g.invisible,
             };
+            let grid = if let Some(bounds) = g.bounds {
+                GridInstance {
+                    min_x: bounds.min_x as i32,
+                    max_x: bounds.max_x as i32,
+                    min_y: bounds.min_y as i32,
+                    max_y: bounds.max_y as i32,
+                    ..grid
+                }
+            } else {
+                grid
+            };
             ret.insert(*g_id, grid);
         }
         for grid_position in self.get_all_used_grid_positions() {
-            if let Some(grid) = ret.get_mut(&grid_position.grid) {
+            if let Some(grid) = ret
+                .get_mut(&grid_position.grid)
+                .filter(|g| g.grid.bounds.is_none())
+            {
                 grid.min_x = grid.min_x.min(grid_position.x as i32 - 2);
                 grid.max_x = grid.max_x.max(grid_position.x as i32 + 2);
                 grid.min_y = grid.min_y.min(grid_position.y as i32 - 2);
@@ -953,6 +1089,7 @@ impl GridInstancesMaker for GridData {
 
 enum StapleDomain {
     ScaffoldDomain {
+        scaffold_id: usize,
         domain_id: usize,
         first_scaffold_position: usize,
         last_scaffold_position: usize,
@@ -964,6 +1101,7 @@ enum StapleDomain {
 
 #[derive(Clone, Copy)]
 struct ScaffoldPosition {
+    scaffold_id: usize,
     domain_id: usize,
     scaffold_position: usize,
 }
@@ -972,6 +1110,7 @@ impl StapleDomain {
     fn init(scaffold_position: Option<ScaffoldPosition>) -> Self {
         if let Some(pos) = scaffold_position {
             Self::ScaffoldDomain {
+                scaffold_id: pos.scaffold_id,
                 domain_id: pos.domain_id,
                 first_scaffold_position: pos.scaffold_position,
                 last_scaffold_position: pos.scaffold_position,
@@ -984,6 +1123,7 @@ impl StapleDomain {
     fn reset(scaffold_position: Option<ScaffoldPosition>) -> Self {
         if let Some(pos) = scaffold_position {
             Self::ScaffoldDomain {
+                scaffold_id: pos.scaffold_id,
                 domain_id: pos.domain_id,
                 first_scaffold_position: pos.scaffold_position,
                 last_scaffold_position: pos.scaffold_position,
@@ -1021,11 +1161,14 @@ impl StapleDomain {
                 }
             }
             Self::ScaffoldDomain {
+                scaffold_id,
                 domain_id,
                 last_scaffold_position,
                 ..
             } => {
-                if let Some(pos) = position.filter(|p| p.domain_id == *domain_id) {
+                if let Some(pos) =
+                    position.filter(|p| p.scaffold_id == *scaffold_id && p.domain_id == *domain_id)
+                {
                     *last_scaffold_position = pos.scaffold_position;
                     ReadResult::Continue(self)
                 } else {
@@ -1046,3 +1189,36 @@ enum ReadResult {
         new_reader: StapleDomain,
     },
 }
+
+/// Map the keys of the leaves of `tree` to the depth and color of the innermost group with a
+/// color containing them.
+fn collect_group_colors(
+    tree: &OrganizerTree<DnaElementKey>,
+    design: &Design,
+    color: Option<(usize, u32)>,
+    depth: usize,
+    key_colors: &mut BTreeMap<DnaElementKey, (usize, u32)>,
+) {
+    match tree {
+        OrganizerTree::Leaf(key) => {
+            if let Some((group_depth, color)) = color {
+                let entry = key_colors
+                    .entry(key.clone())
+                    .or_insert((group_depth, color));
+                if entry.0 < group_depth {
+                    *entry = (group_depth, color);
+                }
+            }
+        }
+        OrganizerTree::Node { childrens, id, .. } => {
+            let color = id
+                .and_then(|id| design.group_attributes.get(&id))
+                .and_then(|attributes| attributes.color)
+                .map(|c| (depth, c))
+                .or(color);
+            for c in childrens.iter() {
+                collect_group_colors(c, design, color, depth + 1, key_colors);
+            }
+        }
+    }
+}
@@ -168,11 +168,15 @@ impl XoverSuggestions {
                                             .sqrt();
                                         if dist < LEN_CRIT
                                             && (suggestion_parameters.include_scaffold
-                                                || design.strands.get_strand_nucl(nucl)
-                                                    != design.scaffold_id)
+                                                || design
+                                                    .strands
+                                                    .get_strand_nucl(nucl)
+                                                    .map_or(true, |s_id| !design.is_scaffold(s_id)))
                                             && (suggestion_parameters.include_scaffold
-                                                || design.strands.get_strand_nucl(red_nucl)
-                                                    != design.scaffold_id)
+                                                || design
+                                                    .strands
+                                                    .get_strand_nucl(red_nucl)
+                                                    .map_or(true, |s_id| !design.is_scaffold(s_id)))
                                             && (suggestion_parameters.include_intra_strand
                                                 || design.strands.get_strand_nucl(nucl)
                                                     != design.strands.get_strand_nucl(red_nucl))
@@ -217,11 +221,15 @@ impl XoverSuggestions {
                                         .sqrt();
                                     if dist < LEN_CRIT
                                         && (suggestion_parameters.include_scaffold
-                                            || design.strands.get_strand_nucl(nucl)
-                                                != design.scaffold_id)
+                                            || design
+                                                .strands
+                                                .get_strand_nucl(nucl)
+                                                .map_or(true, |s_id| !design.is_scaffold(s_id)))
                                         && (suggestion_parameters.include_scaffold
-                                            || design.strands.get_strand_nucl(red_nucl)
-                                                != design.scaffold_id)
+                                            || design
+                                                .strands
+                                                .get_strand_nucl(red_nucl)
+                                                .map_or(true, |s_id| !design.is_scaffold(s_id)))
                                         && (suggestion_parameters.include_intra_strand
                                             || design.strands.get_strand_nucl(nucl)
                                                 != design.strands.get_strand_nucl(red_nucl))
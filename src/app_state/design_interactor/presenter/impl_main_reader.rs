@@ -25,43 +25,32 @@ use std::path::PathBuf;
 impl StaplesDownloader for DesignReader {
     fn download_staples(&self) -> Result<DownloadStappleOk, DownloadStappleError> {
         let mut warnings = Vec::new();
-        if self.presenter.current_design.scaffold_id.is_none() {
+        let design = self.presenter.current_design.as_ref();
+        if design.scaffold_ids.is_empty() {
             return Err(DownloadStappleError::NoScaffoldSet);
         }
-        if self.presenter.current_design.scaffold_sequence.is_none() {
+        if design
+            .scaffold_ids
+            .iter()
+            .any(|s_id| !design.scaffold_sequences.contains_key(s_id))
+        {
             return Err(DownloadStappleError::ScaffoldSequenceNotSet);
         }
 
-        if let Some(nucl) = self
-            .presenter
-            .content
-            .get_stapple_mismatch(self.presenter.current_design.as_ref())
-        {
+        if let Some(nucl) = self.presenter.content.get_stapple_mismatch(design) {
             warnings.push(warn_all_staples_not_paired(nucl));
         }
 
-        let scaffold_length = self
-            .presenter
-            .current_design
-            .scaffold_id
-            .as_ref()
-            .and_then(|s_id| {
-                self.presenter
-                    .current_design
-                    .strands
-                    .get(s_id)
-                    .map(|s| s.length())
-            })
-            .unwrap();
-        let sequence_length = self
-            .presenter
-            .current_design
-            .scaffold_sequence
-            .as_ref()
-            .map(|s| s.len())
-            .unwrap();
-        if scaffold_length != sequence_length {
-            warnings.push(warn_scaffold_seq_mismatch(scaffold_length, sequence_length));
+        for (s_id, scaffold) in design.scaffolds() {
+            let scaffold_length = scaffold.length();
+            let sequence_length = design
+                .scaffold_sequences
+                .get(&s_id)
+                .map(|s| s.sequence.len())
+                .unwrap();
+            if scaffold_length != sequence_length {
+                warnings.push(warn_scaffold_seq_mismatch(scaffold_length, sequence_length));
+            }
         }
         Ok(DownloadStappleOk { warnings })
     }
@@ -96,6 +85,7 @@ impl StaplesDownloader for DesignReader {
                     "Domain Length",
                     "Groups",
                     "Color",
+                    "Note",
                 ]]
             });
             sheet.push(vec![
@@ -107,6 +97,7 @@ impl StaplesDownloader for DesignReader {
                 &stapple.domain_decomposition,
                 &stapple.groups_name_str,
                 &stapple.color_str,
+                &stapple.note,
             ])
         }
 
@@ -116,11 +107,11 @@ impl StaplesDownloader for DesignReader {
                 for row in rows {
                     if let Ok(length) = row[3].parse::<f64>() {
                         sw.append_row(row![
-                            row[0], row[1], row[2], length, row[4], row[5], row[6]
+                            row[0], row[1], row[2], length, row[4], row[5], row[6], row[7], row[8]
                         ])?;
                     } else {
                         sw.append_row(row![
-                            row[0], row[1], row[2], row[3], row[4], row[5], row[6]
+                            row[0], row[1], row[2], row[3], row[4], row[5], row[6], row[7], row[8]
                         ])?;
                     }
                 }
@@ -136,20 +127,28 @@ impl StaplesDownloader for DesignReader {
             .presenter
             .content
             .get_staples(&self.presenter.current_design, &self.presenter);
-        let origami = Origami {
-            scaffold_sequence: self
-                .presenter
-                .current_design
-                .scaffold_sequence
-                .clone()
-                .unwrap_or("NO SEQUENCE".to_string()),
-            intervals: stapples
-                .iter()
-                .map(|s| (s.intervals.staple_id, s.intervals.intervals.clone()))
-                .collect(),
-        };
+        let design = self.presenter.current_design.as_ref();
         let mut origamis = Origamis(BTreeMap::new());
-        origamis.0.insert(1, origami);
+        // There is one origami per scaffold. The staples that are not paired with any scaffold
+        // are put in the first one.
+        for (n, s_id) in design.scaffold_ids.iter().enumerate() {
+            let origami = Origami {
+                scaffold_sequence: design
+                    .scaffold_sequences
+                    .get(s_id)
+                    .map(|s| s.sequence.clone())
+                    .unwrap_or("NO SEQUENCE".to_string()),
+                intervals: stapples
+                    .iter()
+                    .filter(|s| {
+                        s.intervals.scaffold_id == Some(*s_id)
+                            || (n == 0 && s.intervals.scaffold_id.is_none())
+                    })
+                    .map(|s| (s.intervals.staple_id, s.intervals.intervals.clone()))
+                    .collect(),
+            };
+            origamis.0.insert(n + 1, origami);
+        }
         if let Ok(json_content) = serde_json::to_string_pretty(&origamis) {
             if let Ok(mut f) = std::fs::File::create(origami_path) {
                 if let Err(e) = f.write_all(json_content.as_bytes()) {
@@ -163,8 +162,12 @@ impl StaplesDownloader for DesignReader {
         }
     }
 
-    fn default_shift(&self) -> Option<usize> {
-        self.presenter.current_design.scaffold_shift
+    fn default_shift(&self, scaffold_id: usize) -> Option<usize> {
+        self.presenter
+            .current_design
+            .scaffold_sequences
+            .get(&scaffold_id)
+            .map(|s| s.shift)
     }
 }
 
@@ -223,6 +226,15 @@ impl MainReader for DesignReader {
             .get(&s_id)
             .map(|s| s.domain_ends())
     }
+
+    fn get_all_strand_ids(&self) -> Vec<usize> {
+        self.presenter
+            .current_design
+            .strands
+            .keys()
+            .cloned()
+            .collect()
+    }
 }
 
 use std::collections::BTreeMap;
@@ -18,7 +18,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use super::*;
 
-use crate::flatscene::DesignReader as Reader2D;
+use crate::flatscene::{DesignReader as Reader2D, ScaffoldSequenceInfo};
 use ahash::RandomState;
 use ensnano_design::{Domain, Extremity, Helix, HelixInterval, Strand};
 use ensnano_interactor::{torsion::Torsion, Referential};
@@ -98,6 +98,13 @@ impl Reader2D for DesignReader {
             .map(|s| s.color)
     }
 
+    fn get_strand_property_values(
+        &self,
+        coloring: ensnano_interactor::graphics::StrandColoring,
+    ) -> BTreeMap<usize, f32> {
+        self.presenter.get_strand_property_values(coloring)
+    }
+
     fn get_torsions(&self) -> HashMap<(Nucl, Nucl), Torsion> {
         HashMap::new()
     }
@@ -127,6 +134,93 @@ impl Reader2D for DesignReader {
             .map(|s| s.get_insertions())
     }
 
+    fn get_insertion_length_after(&self, nucl: &Nucl) -> usize {
+        let strand = self
+            .get_id_of_strand_containing_nucl(nucl)
+            .and_then(|s_id| self.presenter.current_design.strands.get(&s_id));
+        let strand = if let Some(strand) = strand {
+            strand
+        } else {
+            return 0;
+        };
+        let next_domains = strand
+            .domains
+            .iter()
+            .skip(1)
+            .chain(strand.domains.first().filter(|_| strand.cyclic));
+        strand
+            .domains
+            .iter()
+            .zip(next_domains)
+            .find(|(d, _)| d.prime3_end() == Some(*nucl))
+            .and_then(|(_, next)| {
+                if let Domain::Insertion { nb_nucl, .. } = next {
+                    Some(*nb_nucl)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(0)
+    }
+
+    fn get_scaffold_sequence_info(&self, scaffold_id: usize) -> Option<ScaffoldSequenceInfo> {
+        let design = &self.presenter.current_design;
+        if !design.is_scaffold(scaffold_id) {
+            return None;
+        }
+        let scaffold_sequence = design.scaffold_sequences.get(&scaffold_id)?;
+        let sequence_length = scaffold_sequence.len();
+        if sequence_length == 0 {
+            return None;
+        }
+        let shift = scaffold_sequence.shift % sequence_length;
+        // The bases of the sequence are assigned to the nucleotides of the helix domains of the
+        // scaffold, insertions are skipped.
+        let seam = design.strands.get(&scaffold_id).and_then(|s| {
+            s.domains
+                .iter()
+                .filter_map(|d| {
+                    if let Domain::HelixDomain(interval) = d {
+                        Some(interval)
+                    } else {
+                        None
+                    }
+                })
+                .flat_map(|interval| {
+                    interval.iter().map(move |position| Nucl {
+                        helix: interval.helix,
+                        position,
+                        forward: interval.forward,
+                    })
+                })
+                .nth(shift)
+        });
+        Some(ScaffoldSequenceInfo {
+            scaffold_id,
+            shift,
+            sequence_length,
+            seam,
+        })
+    }
+
+    fn get_deletions(&self) -> Vec<Nucl> {
+        self.presenter
+            .current_design
+            .deletions
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    fn get_modified_nucls(&self) -> Vec<Nucl> {
+        self.presenter
+            .current_design
+            .modifications
+            .keys()
+            .cloned()
+            .collect()
+    }
+
     fn get_raw_strand(&self, s_id: usize) -> Option<Strand> {
         self.presenter.current_design.strands.get(&s_id).cloned()
     }
@@ -159,6 +253,14 @@ impl Reader2D for DesignReader {
             .map(|h| h.visible)
     }
 
+    fn get_visibility_strand(&self, s_id: usize) -> Option<bool> {
+        self.presenter
+            .current_design
+            .strands
+            .get(&s_id)
+            .map(|s| !s.invisible)
+    }
+
     fn get_xovers_list_with_id(&self) -> Vec<(usize, (Nucl, Nucl))> {
         self.presenter.junctions_ids.get_all_elements()
     }
@@ -25,16 +25,44 @@ use ensnano_interactor::{
     graphics::{LoopoutBond, LoopoutNucl},
     BezierControlPoint, ObjectType, Referential,
 };
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use ultraviolet::{Mat4, Rotor3, Vec2, Vec3};
 
-use crate::scene::{DesignReader as Reader3D, GridInstance, SurfaceInfo};
+use crate::scene::{DesignReader as Reader3D, GridInstance, ModifiedObjects, SurfaceInfo};
 
 impl Reader3D for DesignReader {
     fn get_color(&self, e_id: u32) -> Option<u32> {
         self.presenter.content.color.get(&e_id).cloned()
     }
 
+    fn get_group_color(&self, e_id: u32) -> Option<u32> {
+        self.presenter.content.group_color.get(&e_id).cloned()
+    }
+
+    fn get_torsion(&self, e_id: u32) -> Option<f32> {
+        let nucl = self.presenter.content.nucleotide.get(&e_id)?;
+        let helix = self.presenter.current_design.helices.get(&nucl.helix)?;
+        let parameters = self.presenter.current_design.parameters.unwrap_or_default();
+        let relaxed_twist =
+            2. * std::f32::consts::PI / helix.parameters(&parameters).bases_per_turn;
+        let twist = helix.twist_at(&parameters, nucl.position, nucl.forward);
+        Some((twist - relaxed_twist).to_degrees())
+    }
+
+    fn get_strain(&self, e_id: u32) -> Option<f32> {
+        let strain = self.presenter.content.nucl_strain.as_ref()?;
+        let nucl = self.presenter.content.nucleotide.get(&e_id)?;
+        Some(strain.get(nucl).cloned().unwrap_or(0.))
+    }
+
+    fn get_strand_note(&self, s_id: usize) -> Option<String> {
+        self.presenter
+            .current_design
+            .strands
+            .get(&s_id)
+            .and_then(|s| s.note.clone())
+    }
+
     fn get_basis(&self) -> Rotor3 {
         self.presenter.model_matrix.extract_rotation()
     }
@@ -79,6 +107,14 @@ impl Reader3D for DesignReader {
             .map(|h| h.orientation)
     }
 
+    fn get_helix_roll(&self, h_id: usize) -> Option<f32> {
+        self.presenter
+            .current_design
+            .helices
+            .get(&h_id)
+            .map(|h| h.roll)
+    }
+
     fn get_all_nucl_ids(&self) -> Vec<u32> {
         self.presenter.content.nucleotide.keys().cloned().collect()
     }
@@ -150,6 +186,31 @@ impl Reader3D for DesignReader {
         self.presenter.content.get_helices_on_grid(g_id)
     }
 
+    fn get_anchors(&self) -> Vec<Nucl> {
+        self.presenter
+            .current_design
+            .anchors
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    fn get_modified_nucls(&self) -> Vec<Nucl> {
+        self.presenter
+            .current_design
+            .modifications
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    fn get_strand_property_values(
+        &self,
+        coloring: ensnano_interactor::graphics::StrandColoring,
+    ) -> BTreeMap<usize, f32> {
+        self.presenter.get_strand_property_values(coloring)
+    }
+
     fn get_all_prime3_nucl(&self) -> Vec<(Vec3, Vec3, u32)> {
         let locate_nucl = |nucl| {
             let pos_start_opt = self
@@ -266,6 +327,10 @@ impl Reader3D for DesignReader {
         self.presenter.content.get_persistent_phantom_helices_id()
     }
 
+    fn get_helix_phantom_length(&self, h_id: usize) -> Option<usize> {
+        self.presenter.content.get_helix_phantom_length(h_id)
+    }
+
     fn get_ids_of_elements_belonging_to_helix(&self, h_id: usize) -> Vec<u32> {
         let nucls = self
             .presenter
@@ -395,6 +460,10 @@ impl Reader3D for DesignReader {
         self.presenter.get_id_of_xover_involving_nucl(nucl)
     }
 
+    fn get_xover_strain(&self, xover_id: usize) -> Option<f32> {
+        self.presenter.get_xover_strain(xover_id)
+    }
+
     fn get_grid_object(&self, position: GridPosition) -> Option<GridObject> {
         self.presenter.content.get_grid_object(position)
     }
@@ -482,44 +551,57 @@ impl Reader3D for DesignReader {
         let source_id = self.get_id_of_strand_containing_nucl(&source)?;
         let target_id = self.get_id_of_strand_containing_nucl(&target)?;
         let mut opt_pair = (source, target);
-        let helix_source = self.presenter.current_design.helices.get(&source.helix)?;
-        let helix_target = self.presenter.current_design.helices.get(&target.helix)?;
-        let parameters = self.presenter.current_design.parameters.unwrap_or_default();
-        let mut opt_dist = std::f32::INFINITY;
+        let content = &self.presenter.content;
+        let space_position = |nucl: &Nucl| -> Option<Vec3> {
+            let id = content.nucl_collection.get_identifier(nucl)?;
+            content.space_position.get(id).map(|p| p.into())
+        };
+        let (source_pos, target_pos) =
+            if let Some(positions) = space_position(&source).zip(space_position(&target)) {
+                positions
+            } else {
+                return Some(opt_pair);
+            };
+        // Only the candidates that are closer than the given pair are looked for
+        let mut opt_dist = (source_pos - target_pos).mag();
+        let spatial_index = self.presenter.get_spatial_index();
         for i in -2..2 {
             let source_candidate = Nucl {
                 position: source.position + i,
                 ..source
             };
-            if self.get_id_of_strand_containing_nucl(&source_candidate) == Some(source_id) {
-                for j in -2..2 {
-                    let target_candidate = Nucl {
-                        position: target.position + j,
-                        ..target
-                    };
-                    if self.get_id_of_strand_containing_nucl(&target_candidate) == Some(target_id) {
-                        let source_pos = helix_source.space_pos(
-                            &parameters,
-                            source_candidate.position,
-                            source_candidate.forward,
-                        );
-                        let target_pos = helix_target.space_pos(
-                            &parameters,
-                            target_candidate.position,
-                            target_candidate.forward,
-                        );
-                        let dist = (source_pos - target_pos).mag();
-                        if dist < opt_dist {
-                            opt_dist = dist;
-                            opt_pair = (source_candidate, target_candidate);
-                        }
-                    }
-                }
+            if self.get_id_of_strand_containing_nucl(&source_candidate) != Some(source_id) {
+                continue;
+            }
+            let source_pos = if let Some(pos) = space_position(&source_candidate) {
+                pos
+            } else {
+                continue;
+            };
+            let target_candidate = spatial_index
+                .nucls_within(source_pos, opt_dist)
+                .into_iter()
+                .find(|candidate| {
+                    candidate.helix == target.helix
+                        && candidate.forward == target.forward
+                        && (-2..2).contains(&(candidate.position - target.position))
+                        && self.get_id_of_strand_containing_nucl(candidate) == Some(target_id)
+                });
+            if let Some(target_candidate) = target_candidate {
+                let target_pos = space_position(&target_candidate)?;
+                opt_dist = (source_pos - target_pos).mag();
+                opt_pair = (source_candidate, target_candidate);
             }
         }
         Some(opt_pair)
     }
 
+    fn closest_nucl(&self, point: Vec3, max_dist: f32) -> Option<Nucl> {
+        self.presenter
+            .get_spatial_index()
+            .closest_nucl(point, max_dist)
+    }
+
     fn get_bezier_grid_used_by_helix(&self, h_id: usize) -> Vec<GridId> {
         let helix = self.presenter.current_design.helices.get(&h_id);
         if let Some(CurveDescriptor::TranslatedPath { path_id, .. }) =
@@ -563,6 +645,57 @@ impl Reader3D for DesignReader {
             .as_ref()
             .map(Arc::as_ref)
     }
+
+    fn get_all_cameras(&self) -> Vec<(String, ensnano_interactor::application::Camera3D)> {
+        self.presenter
+            .current_design
+            .get_cameras()
+            .map(|(_, c)| {
+                (
+                    c.name.clone(),
+                    ensnano_interactor::application::Camera3D {
+                        position: c.position,
+                        orientation: c.orientation,
+                        pivot_position: c.pivot_position,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn get_modified_objects(&self, older: &Self) -> Option<ModifiedObjects> {
+        if self.presenter == older.presenter {
+            return Some(Default::default());
+        }
+        let (new, old) = (&self.presenter, &older.presenter);
+        if new.model_matrix != old.model_matrix
+            || new.invisible_nucls != old.invisible_nucls
+            || new.content.group_color != old.content.group_color
+        {
+            return None;
+        }
+        let (new_design, old_design) = (&new.current_design, &old.current_design);
+        if new_design != old_design {
+            // Attributes that can modify the instances of any strand
+            if new_design.parameters != old_design.parameters
+                || new_design.scaffold_ids != old_design.scaffold_ids
+                || new_design.rainbow_scaffold != old_design.rainbow_scaffold
+                || new_design.small_spheres != old_design.small_spheres
+                || new_design.deletions != old_design.deletions
+                || !ensnano_design::design_diff::same_helix_supports(old_design, new_design)
+            {
+                return None;
+            }
+        }
+        Some(ModifiedObjects {
+            strands: ensnano_design::design_diff::changed_strand_ids(old_design, new_design)
+                .into_iter()
+                .collect(),
+            helices: ensnano_design::design_diff::changed_helix_ids(old_design, new_design)
+                .into_iter()
+                .collect(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -16,11 +16,13 @@ ENSnano, a 3d graphical application for DNA nanostructures.
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use ensnano_design::{elements::DnaElement, CameraId, Collection};
+use ensnano_design::{
+    elements::DnaElement, grid::GridTypeDescr, modifications::Modification, CameraId, Collection,
+};
 
 use super::*;
-use crate::gui::DesignReader as ReaderGui;
-use ensnano_interactor::InsertionPoint;
+use crate::gui::{BezierPlaneEntry, DesignReader as ReaderGui, External3DObjectEntry};
+use ensnano_interactor::{HyperboloidRequest, InsertionPoint};
 use ultraviolet::Rotor3;
 
 impl ReaderGui for DesignReader {
@@ -28,6 +30,10 @@ impl ReaderGui for DesignReader {
         self.presenter.content.grid_has_small_spheres(g_id)
     }
 
+    fn grid_is_visible(&self, g_id: GridId) -> bool {
+        self.presenter.content.grid_is_visible(g_id)
+    }
+
     fn grid_has_persistent_phantom(&self, g_id: GridId) -> bool {
         self.presenter.content.grid_has_persistent_phantom(g_id)
     }
@@ -40,6 +46,37 @@ impl ReaderGui for DesignReader {
         self.presenter.content.get_grid_nb_turn(g_id)
     }
 
+    fn get_grid_phantom_length(&self, g_id: GridId) -> Option<usize> {
+        self.presenter.content.get_grid_phantom_length(g_id)
+    }
+
+    fn get_hyperboloid_parameters(&self, g_id: GridId) -> Option<HyperboloidRequest> {
+        let grid = self
+            .presenter
+            .current_design
+            .free_grids
+            .get_from_g_id(&g_id)?;
+        if let GridTypeDescr::Hyperboloid {
+            radius,
+            shift,
+            length,
+            radius_shift,
+            nb_turn_per_100_nt,
+            ..
+        } = grid.grid_type
+        {
+            Some(HyperboloidRequest {
+                radius,
+                length,
+                shift,
+                radius_shift,
+                nb_turn: nb_turn_per_100_nt,
+            })
+        } else {
+            None
+        }
+    }
+
     fn get_strand_length(&self, s_id: usize) -> Option<usize> {
         self.presenter
             .current_design
@@ -49,13 +86,35 @@ impl ReaderGui for DesignReader {
     }
 
     fn is_id_of_scaffold(&self, s_id: usize) -> bool {
-        self.presenter.current_design.scaffold_id == Some(s_id)
+        self.presenter.current_design.is_scaffold(s_id)
     }
 
     fn nucl_is_anchor(&self, nucl: Nucl) -> bool {
         self.presenter.current_design.anchors.contains(&nucl)
     }
 
+    fn nucl_modification(&self, nucl: Nucl) -> Option<Modification> {
+        self.presenter
+            .current_design
+            .modifications
+            .get(&nucl)
+            .cloned()
+    }
+
+    fn get_external_3d_objects(&self) -> Vec<External3DObjectEntry> {
+        self.presenter
+            .current_design
+            .external_3d_objects
+            .iter()
+            .map(|(id, object)| External3DObjectEntry {
+                id: *id,
+                name: object.name(),
+                visible: object.is_visible(),
+                attached: object.anchor().is_some(),
+            })
+            .collect()
+    }
+
     fn length_decomposition(&self, s_id: usize) -> String {
         self.presenter.decompose_length(s_id)
     }
@@ -70,6 +129,23 @@ impl ReaderGui for DesignReader {
             .clone()
     }
 
+    fn get_group_colors(&self) -> HashMap<ensnano_design::GroupId, u32> {
+        RollPresenter::get_design(self.presenter.as_ref())
+            .group_attributes
+            .iter()
+            .filter_map(|(g_id, attributes)| attributes.color.map(|c| (*g_id, c)))
+            .collect()
+    }
+
+    fn get_locked_groups(&self) -> HashSet<ensnano_design::GroupId> {
+        RollPresenter::get_design(self.presenter.as_ref())
+            .group_attributes
+            .iter()
+            .filter(|(_, attributes)| attributes.locked)
+            .map(|(g_id, _)| *g_id)
+            .collect()
+    }
+
     fn strand_name(&self, s_id: usize) -> String {
         self.presenter
             .current_design
@@ -79,6 +155,23 @@ impl ReaderGui for DesignReader {
             .unwrap_or_else(|| String::from("Unamed strand"))
     }
 
+    fn strand_note(&self, s_id: usize) -> Option<String> {
+        self.presenter
+            .current_design
+            .strands
+            .get(&s_id)
+            .and_then(|s| s.note.clone())
+    }
+
+    fn strand_keeps_color(&self, s_id: usize) -> bool {
+        self.presenter
+            .current_design
+            .strands
+            .get(&s_id)
+            .map(|s| s.keep_color)
+            .unwrap_or(false)
+    }
+
     fn get_all_cameras(&self) -> Vec<(CameraId, &str)> {
         //TODO this obviously needs to be updated to relate to the real content
         self.presenter
@@ -93,6 +186,15 @@ impl ReaderGui for DesignReader {
         self.presenter.current_design.get_favourite_camera_id()
     }
 
+    fn get_named_selections(&self) -> Vec<&str> {
+        self.presenter
+            .current_design
+            .named_selections
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect()
+    }
+
     fn get_grid_position_and_orientation(&self, g_id: GridId) -> Option<(Vec3, Rotor3)> {
         self.presenter
             .current_design
@@ -230,6 +332,19 @@ impl ReaderGui for DesignReader {
             .map(|p| p.cyclic)
     }
 
+    fn get_bezier_plane_entries(&self) -> Vec<BezierPlaneEntry> {
+        self.presenter
+            .current_design
+            .bezier_planes
+            .iter()
+            .map(|(id, plane)| BezierPlaneEntry {
+                id: *id,
+                name: plane.name_or_default(*id),
+                normal: Vec3::unit_x().rotated_by(plane.orientation),
+            })
+            .collect()
+    }
+
     fn get_bezier_vertex_position(
         &self,
         vertex_id: ensnano_design::BezierVertexId,
@@ -242,8 +357,12 @@ impl ReaderGui for DesignReader {
         path.vertices().get(vertex_id.vertex_id).map(|v| v.position)
     }
 
-    fn get_scaffold_sequence(&self) -> Option<&str> {
-        self.presenter.current_design.scaffold_sequence.as_deref()
+    fn get_scaffold_sequence(&self, scaffold_id: usize) -> Option<&str> {
+        self.presenter
+            .current_design
+            .scaffold_sequences
+            .get(&scaffold_id)
+            .map(|s| s.sequence.as_str())
     }
 
     fn get_current_length_of_relaxed_shape(&self) -> Option<usize> {
@@ -253,4 +372,34 @@ impl ReaderGui for DesignReader {
             .as_ref()
             .and_then(|s| s.current_length())
     }
+
+    fn get_staple_stats(
+        &self,
+        bounds: &ensnano_design::staple_report::StapleBounds,
+    ) -> Vec<ensnano_design::staple_report::StapleStats> {
+        let basis_map = self.presenter.content.basis_map.as_ref();
+        ensnano_design::staple_report::staple_stats(
+            self.presenter.current_design.as_ref(),
+            |nucl| basis_map.get(nucl).cloned(),
+            bounds,
+        )
+    }
+
+    fn get_xovers_by_strain(&self) -> Vec<ensnano_interactor::XoverStrain> {
+        self.presenter.get_xovers_by_strain()
+    }
+
+    fn get_design_summary(&self) -> ensnano_interactor::design_summary::DesignSummary {
+        self.presenter.get_design_summary()
+    }
+
+    fn check_design(
+        &self,
+        parameters: &ensnano_design::design_check::CheckParameters,
+    ) -> Vec<ensnano_design::design_check::Diagnostic> {
+        ensnano_design::design_check::check_design(
+            self.presenter.current_design.as_ref(),
+            parameters,
+        )
+    }
 }
@@ -0,0 +1,325 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! A spatial index over the positions of the nucleotides of a design, used to answer "nearest
+//! nucleotide" queries without scanning all the nucleotides.
+//!
+//! The space is divided in cubic cells of side [`CELL_SIZE`] and each nucleotide is stored in the
+//! cell that contains it.
+
+use ensnano_design::Nucl;
+use std::collections::HashMap;
+use ultraviolet::Vec3;
+
+/// The side, in nanometers, of the cells of the index.
+const CELL_SIZE: f32 = 2.;
+
+type Cell = [i64; 3];
+
+#[derive(Default)]
+pub struct SpatialIndex {
+    cells: HashMap<Cell, Vec<(Nucl, Vec3)>>,
+    /// The smallest and largest coordinates of the non-empty cells
+    bounds: Option<(Cell, Cell)>,
+}
+
+impl SpatialIndex {
+    pub fn new(nucls: impl IntoIterator<Item = (Nucl, Vec3)>) -> Self {
+        let mut ret = Self::default();
+        for (nucl, position) in nucls {
+            if !(position.x.is_finite() && position.y.is_finite() && position.z.is_finite()) {
+                continue;
+            }
+            let cell = cell_of(position);
+            ret.bounds = Some(match ret.bounds {
+                Some((min, max)) => (
+                    [0, 1, 2].map(|i| min[i].min(cell[i])),
+                    [0, 1, 2].map(|i| max[i].max(cell[i])),
+                ),
+                None => (cell, cell),
+            });
+            ret.cells.entry(cell).or_default().push((nucl, position));
+        }
+        ret
+    }
+
+    /// Return the nucleotide that is the closest to `point`, if it is at distance at most
+    /// `max_dist` from it.
+    pub fn closest_nucl(&self, point: Vec3, max_dist: f32) -> Option<Nucl> {
+        let (min, max) = self.bounds?;
+        if max_dist.is_nan() || max_dist < 0. {
+            return None;
+        }
+        // Clamping the cell of a point outside of the bounds does not reduce its distance to the
+        // cells that are further away, so the search below remains valid.
+        let center = {
+            let cell = cell_of(point);
+            [0, 1, 2].map(|i| cell[i].clamp(min[i].saturating_sub(1), max[i].saturating_add(1)))
+        };
+        // The number of rings of cells that must be visited to cover the whole index
+        let last_ring_in_bounds = (0..3)
+            .map(|i| {
+                center[i]
+                    .saturating_sub(min[i])
+                    .max(max[i].saturating_sub(center[i]))
+            })
+            .max()
+            .unwrap_or(0);
+        // The points of the cells of the ring `r` are at distance at least `(r - 1) * CELL_SIZE`
+        // from `point`
+        let last_ring_in_range = (max_dist / CELL_SIZE).min(i64::MAX as f32 / 2.) as i64 + 1;
+        let mut best: Option<(Nucl, f32)> = None;
+        for ring in 0..=last_ring_in_bounds.min(last_ring_in_range) {
+            if let Some((_, dist)) = best {
+                if dist <= (ring - 1) as f32 * CELL_SIZE {
+                    break;
+                }
+            }
+            for cell in ring_cells(center, ring, (min, max)) {
+                for (nucl, position) in self.cells.get(&cell).into_iter().flatten() {
+                    let dist = (*position - point).mag();
+                    if dist <= max_dist && best.map(|(_, d)| dist < d).unwrap_or(true) {
+                        best = Some((*nucl, dist));
+                    }
+                }
+            }
+        }
+        best.map(|(nucl, _)| nucl)
+    }
+
+    /// Return the nucleotides that are at distance at most `radius` from `point`, sorted by
+    /// increasing distance.
+    pub fn nucls_within(&self, point: Vec3, radius: f32) -> Vec<Nucl> {
+        let (min, max) = if let Some(bounds) = self.bounds {
+            bounds
+        } else {
+            return vec![];
+        };
+        if radius.is_nan() || radius < 0. {
+            return vec![];
+        }
+        let radius_vec = Vec3::broadcast(radius.min(f32::MAX / 4.));
+        let low = cell_of(point - radius_vec);
+        let high = cell_of(point + radius_vec);
+        let range = |i: usize| low[i].max(min[i])..=high[i].min(max[i]);
+        let mut ret = Vec::new();
+        for x in range(0) {
+            for y in range(1) {
+                for z in range(2) {
+                    for (nucl, position) in self.cells.get(&[x, y, z]).into_iter().flatten() {
+                        let dist = (*position - point).mag();
+                        if dist <= radius {
+                            ret.push((*nucl, dist));
+                        }
+                    }
+                }
+            }
+        }
+        ret.sort_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+        ret.into_iter().map(|(nucl, _)| nucl).collect()
+    }
+}
+
+fn cell_of(position: Vec3) -> Cell {
+    // Float to integer casts saturate, so points far away are mapped to the extreme cells
+    [
+        (position.x / CELL_SIZE).floor() as i64,
+        (position.y / CELL_SIZE).floor() as i64,
+        (position.z / CELL_SIZE).floor() as i64,
+    ]
+}
+
+/// Return the cells that are inside `bounds` and at distance exactly `ring` from `center` for the
+/// infinity norm.
+fn ring_cells(center: Cell, ring: i64, bounds: (Cell, Cell)) -> impl Iterator<Item = Cell> {
+    let (min, max) = bounds;
+    let range = move |i: usize| {
+        center[i].saturating_sub(ring).max(min[i])..=center[i].saturating_add(ring).min(max[i])
+    };
+    range(0).flat_map(move |x| {
+        range(1).flat_map(move |y| {
+            let on_ring = (x - center[0]).abs() == ring || (y - center[1]).abs() == ring;
+            let zs: Vec<i64> = if on_ring {
+                range(2).collect()
+            } else {
+                let mut zs = vec![center[2].saturating_sub(ring)];
+                if ring > 0 {
+                    zs.push(center[2].saturating_add(ring));
+                }
+                zs.retain(|z| range(2).contains(z));
+                zs
+            };
+            zs.into_iter().map(move |z| [x, y, z])
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    fn random_nucls(nb_nucls: usize, side: f32) -> Vec<(Nucl, Vec3)> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        (0..nb_nucls)
+            .map(|i| {
+                let nucl = Nucl {
+                    helix: i / 100,
+                    position: (i % 100) as isize,
+                    forward: true,
+                };
+                let position = Vec3::new(
+                    rng.gen_range(-side..side),
+                    rng.gen_range(-side..side),
+                    rng.gen_range(-side..side),
+                );
+                (nucl, position)
+            })
+            .collect()
+    }
+
+    fn brute_force_closest(nucls: &[(Nucl, Vec3)], point: Vec3, max_dist: f32) -> Option<Nucl> {
+        nucls
+            .iter()
+            .map(|(nucl, position)| (*nucl, (*position - point).mag()))
+            .filter(|(_, dist)| *dist <= max_dist)
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(nucl, _)| nucl)
+    }
+
+    #[test]
+    fn closest_nucl_matches_brute_force() {
+        let nucls = random_nucls(2_000, 20.);
+        let index = SpatialIndex::new(nucls.clone());
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        for _ in 0..200 {
+            let point = Vec3::new(
+                rng.gen_range(-30. ..30.),
+                rng.gen_range(-30. ..30.),
+                rng.gen_range(-30. ..30.),
+            );
+            for max_dist in [0.5, 3., 100., f32::INFINITY] {
+                assert_eq!(
+                    index.closest_nucl(point, max_dist),
+                    brute_force_closest(&nucls, point, max_dist)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nucls_within_matches_brute_force() {
+        let nucls = random_nucls(2_000, 20.);
+        let index = SpatialIndex::new(nucls.clone());
+        let point = Vec3::new(1., -2., 3.);
+        for radius in [0., 1., 5., 1000.] {
+            let mut expected: Vec<_> = nucls
+                .iter()
+                .filter(|(_, position)| (*position - point).mag() <= radius)
+                .map(|(nucl, _)| *nucl)
+                .collect();
+            let mut found = index.nucls_within(point, radius);
+            let distances: Vec<f32> = found
+                .iter()
+                .map(|n| (nucls.iter().find(|(m, _)| m == n).unwrap().1 - point).mag())
+                .collect();
+            assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn empty_index_and_far_points() {
+        let index = SpatialIndex::new(vec![]);
+        assert_eq!(index.closest_nucl(Vec3::zero(), f32::INFINITY), None);
+        assert!(index.nucls_within(Vec3::zero(), 10.).is_empty());
+
+        let nucls = random_nucls(10, 1.);
+        let index = SpatialIndex::new(nucls.clone());
+        let far = Vec3::new(1e30, -1e30, 0.);
+        assert_eq!(index.closest_nucl(far, 10.), None);
+        assert_eq!(
+            index.closest_nucl(far, f32::INFINITY),
+            brute_force_closest(&nucls, far, f32::INFINITY)
+        );
+    }
+
+    /// Queries on a design of 50,000 nucleotides.
+    ///
+    /// Run with `cargo test --release -- --ignored spatial_index_queries_are_fast --nocapture`.
+    #[test]
+    #[ignore]
+    fn spatial_index_queries_are_fast() {
+        use ensnano_design::{Helix, Parameters};
+        use std::time::Instant;
+        use ultraviolet::Rotor3;
+
+        const NB_HELICES: usize = 100;
+        const HELIX_LENGTH: isize = 250;
+        let parameters = Parameters::GEARY_2014_DNA;
+        let mut nucls = Vec::new();
+        for h_id in 0..NB_HELICES {
+            let origin = Vec3::new((h_id % 10) as f32 * 2.65, (h_id / 10) as f32 * 2.65, 0.);
+            let helix = Helix::new(origin, Rotor3::identity());
+            for position in 0..HELIX_LENGTH {
+                for forward in [true, false] {
+                    let nucl = Nucl {
+                        helix: h_id,
+                        position,
+                        forward,
+                    };
+                    nucls.push((nucl, helix.space_pos(&parameters, position, forward)));
+                }
+            }
+        }
+        assert_eq!(nucls.len(), 50_000);
+
+        let start = Instant::now();
+        let index = SpatialIndex::new(nucls.clone());
+        println!("Index built in {:?}", start.elapsed());
+
+        const NB_QUERIES: u32 = 10_000;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+        let points: Vec<Vec3> = (0..NB_QUERIES)
+            .map(|_| {
+                Vec3::new(
+                    rng.gen_range(-5. ..30.),
+                    rng.gen_range(-5. ..30.),
+                    rng.gen_range(-5. ..90.),
+                )
+            })
+            .collect();
+
+        let start = Instant::now();
+        for point in points.iter() {
+            index.closest_nucl(*point, 5.);
+        }
+        let closest_time = start.elapsed() / NB_QUERIES;
+        let start = Instant::now();
+        for point in points.iter() {
+            index.nucls_within(*point, 2.);
+        }
+        let within_time = start.elapsed() / NB_QUERIES;
+        println!(
+            "closest_nucl: {closest_time:?} per query, nucls_within: {within_time:?} per query"
+        );
+        assert!(closest_time.as_secs_f64() < 1e-3);
+        assert!(within_time.as_secs_f64() < 1e-3);
+    }
+}
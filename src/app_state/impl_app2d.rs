@@ -65,6 +65,26 @@ impl App2D for AppState {
     fn get_building_state(&self) -> Option<ensnano_interactor::StrandBuildingStatus> {
         self.get_strand_building_state()
     }
+
+    fn show_minimap(&self) -> bool {
+        self.0.parameters.show_2d_minimap
+    }
+
+    fn get_strand_coloring(&self) -> ensnano_interactor::graphics::StrandColoring {
+        self.0.parameters.strand_coloring
+    }
+
+    fn get_strand_colormap(&self) -> ensnano_interactor::graphics::Colormap {
+        self.0.parameters.strand_colormap
+    }
+
+    fn get_design_changes(&self) -> Option<&ensnano_interactor::graphics::DesignChanges> {
+        self.get_design_changes()
+    }
+
+    fn get_simulation_state(&self) -> ensnano_interactor::SimulationState {
+        self.0.design.get_simulation_state()
+    }
 }
 
 #[cfg(test)]
@@ -68,6 +68,10 @@ impl App3D for AppState {
         self.0.design.get_strand_builders()
     }
 
+    fn get_building_state(&self) -> Option<ensnano_interactor::StrandBuildingStatus> {
+        self.get_strand_building_state()
+    }
+
     fn get_widget_basis(&self) -> WidgetBasis {
         // When the selected object is a grid associated to a bezier vertex, we always want to
         // return WidgetBasis::Object. We do so to enforce that all rotation applied to that grid
@@ -124,7 +128,17 @@ impl App3D for AppState {
             show_stereographic_camera: self.0.parameters.show_stereography,
             thick_helices: self.0.parameters.thick_helices,
             h_bonds: self.0.parameters.show_h_bonds,
+            h_bond_display: self.0.parameters.h_bond_filter,
             show_bezier_planes: self.0.parameters.show_bezier_paths,
+            isolate_selection: self.0.isolate_selection,
+            hide_idealized_design: self.0.hide_idealized_design,
+            transparent_background: false,
+            show_anchors: self.0.parameters.show_anchors,
+            full_detail: self.0.parameters.full_detail,
+            local_phantoms: self.0.parameters.local_phantoms,
+            show_nucleotide_ruler: self.0.parameters.show_nucleotide_ruler,
+            strand_coloring: self.0.parameters.strand_coloring,
+            strand_colormap: self.0.parameters.strand_colormap,
         }
     }
 
@@ -141,10 +155,18 @@ impl App3D for AppState {
         sign * crate::consts::scroll_sensitivity_convertion(self.0.parameters.scroll_sensitivity)
     }
 
+    fn get_camera_animation_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f32(self.0.parameters.camera_animation_duration.max(0.))
+    }
+
     fn show_insertion_representents(&self) -> bool {
         self.0.show_insertion_representents
     }
 
+    fn get_simulation_state(&self) -> ensnano_interactor::SimulationState {
+        self.0.design.get_simulation_state()
+    }
+
     fn show_bezier_paths(&self) -> bool {
         self.0.parameters.show_bezier_paths
     }
@@ -176,6 +198,14 @@ impl App3D for AppState {
         self.0.unrooted_surface.descriptor.clone()
     }
 
+    fn get_reference_design(&self) -> Option<&ensnano_interactor::graphics::ReferenceDesign> {
+        self.0.reference_design.as_ref()
+    }
+
+    fn get_design_changes(&self) -> Option<&ensnano_interactor::graphics::DesignChanges> {
+        self.0.design_changes.as_ref().map(|c| &c.changes)
+    }
+
     fn get_revolution_axis_position(&self) -> Option<f64> {
         Some(
             self.0
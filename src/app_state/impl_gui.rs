@@ -64,8 +64,8 @@ impl GuiState for AppState {
         self.0.design.is_building_hyperboloid()
     }
 
-    fn get_scaffold_info(&self) -> Option<ScaffoldInfo> {
-        self.get_design_reader().get_scaffold_info()
+    fn get_scaffolds_info(&self) -> Vec<ScaffoldInfo> {
+        self.get_design_reader().get_scaffolds_info()
     }
 
     fn can_make_grid(&self) -> bool {
@@ -138,6 +138,38 @@ impl GuiState for AppState {
         self.0.parameters.show_h_bonds
     }
 
+    fn get_h_bond_filter(&self) -> HBondFilter {
+        self.0.parameters.h_bond_filter
+    }
+
+    fn show_2d_minimap(&self) -> bool {
+        self.0.parameters.show_2d_minimap
+    }
+
+    fn show_anchors(&self) -> bool {
+        self.0.parameters.show_anchors
+    }
+
+    fn local_phantoms(&self) -> bool {
+        self.0.parameters.local_phantoms
+    }
+
+    fn show_nucleotide_ruler(&self) -> bool {
+        self.0.parameters.show_nucleotide_ruler
+    }
+
+    fn full_detail(&self) -> bool {
+        self.0.parameters.full_detail
+    }
+
+    fn get_strand_coloring(&self) -> ensnano_interactor::graphics::StrandColoring {
+        self.0.parameters.strand_coloring
+    }
+
+    fn get_strand_colormap(&self) -> ensnano_interactor::graphics::Colormap {
+        self.0.parameters.strand_colormap
+    }
+
     fn get_scroll_sensitivity(&self) -> f32 {
         self.0.parameters.scroll_sensitivity
     }
@@ -146,6 +178,22 @@ impl GuiState for AppState {
         self.0.parameters.inverted_y_scroll
     }
 
+    fn get_camera_animation_duration(&self) -> f32 {
+        self.0.parameters.camera_animation_duration
+    }
+
+    fn get_autosave_interval(&self) -> f32 {
+        self.0.parameters.autosave_interval
+    }
+
+    fn get_undo_history_length(&self) -> usize {
+        self.0.parameters.undo_history_length
+    }
+
+    fn get_remember_workspace(&self) -> bool {
+        self.remembers_workspace()
+    }
+
     fn want_thick_helices(&self) -> bool {
         self.0.parameters.thick_helices
     }
@@ -154,6 +202,26 @@ impl GuiState for AppState {
         !self.0.show_insertion_representents
     }
 
+    fn get_isolate_selection(&self) -> IsolateSelection {
+        self.0.isolate_selection
+    }
+
+    fn is_showing_design_changes(&self) -> bool {
+        self.0.design_changes.is_some()
+    }
+
+    fn hide_idealized_design(&self) -> bool {
+        self.0.hide_idealized_design
+    }
+
+    fn get_reference_design_visibility(&self) -> Option<bool> {
+        self.0.reference_design.as_ref().map(|r| r.visible)
+    }
+
+    fn get_camera_mode(&self) -> CameraMode {
+        self.0.camera_mode
+    }
+
     fn get_show_bezier_paths(&self) -> bool {
         self.0.parameters.show_bezier_paths
     }
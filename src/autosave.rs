@@ -0,0 +1,164 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Periodic backups of the current design.
+//!
+//! The backups of a design are written next to the design file, or in the platform data
+//! directory if the design has not been saved yet. The [`NB_BACKUPS`] most recent backups are
+//! kept: `<name>.ensbackup` is the most recent one and `<name>_1.ensbackup`,
+//! `<name>_2.ensbackup`, ... are older ones.
+
+use crate::consts::{APP_NAME, ENS_BACKUP_EXTENSION, ENS_UNNAMED_FILE_NAME};
+use crate::controller::SaveDesignError;
+use ensnano_design::Design;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+/// The number of backups that are kept for each design
+pub const NB_BACKUPS: usize = 3;
+
+/// Return the paths of the backups of the design stored at `design_path`, from the most recent
+/// to the oldest one.
+///
+/// If `design_path` is `None`, return the paths of the backups of unnamed designs.
+pub fn backup_paths(design_path: Option<&Path>) -> Option<Vec<PathBuf>> {
+    let (directory, name) = if let Some(path) = design_path {
+        (
+            path.parent()?.to_path_buf(),
+            path.file_stem()?.to_string_lossy().to_string(),
+        )
+    } else {
+        (
+            dirs::data_dir()?.join(APP_NAME),
+            ENS_UNNAMED_FILE_NAME.to_string(),
+        )
+    };
+    Some(
+        (0..NB_BACKUPS)
+            .map(|i| {
+                let file_name = if i == 0 {
+                    name.clone()
+                } else {
+                    format!("{name}_{i}")
+                };
+                let mut path = directory.join(file_name);
+                path.set_extension(ENS_BACKUP_EXTENSION);
+                path
+            })
+            .collect(),
+    )
+}
+
+/// Return the most recent backup of the design stored at `design_path` if it was written after
+/// the last time the design was saved.
+pub fn backup_to_restore(design_path: Option<&Path>) -> Option<PathBuf> {
+    let backup = backup_paths(design_path)?.into_iter().next()?;
+    let backup_date = modification_date(&backup)?;
+    match design_path.and_then(modification_date) {
+        Some(design_date) => (backup_date > design_date).then_some(backup),
+        None => Some(backup),
+    }
+}
+
+/// Return the most recent backup of the design stored at `design_path` that exists on the disk,
+/// regardless of the date at which the design was saved.
+pub fn latest_backup(design_path: &Path) -> Option<PathBuf> {
+    backup_paths(Some(design_path))?
+        .into_iter()
+        .find(|backup| backup.exists() && backup != design_path)
+}
+
+fn modification_date(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Delete all the backups of the design stored at `design_path`.
+pub fn remove_backups(design_path: Option<&Path>) {
+    for path in backup_paths(design_path).into_iter().flatten() {
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::error!("Could not remove backup {}: {e}", path.to_string_lossy());
+            }
+        }
+    }
+}
+
+/// Writes backups on a worker thread so that the render loop is not blocked while large designs
+/// are serialized.
+#[derive(Default)]
+pub struct Autosaver {
+    worker: Option<JoinHandle<Result<PathBuf, SaveDesignError>>>,
+}
+
+impl Autosaver {
+    /// Return true if a backup is currently being written.
+    pub fn is_busy(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    /// If the backup that was being written is done, report its result.
+    pub fn poll(&mut self) {
+        if self
+            .worker
+            .as_ref()
+            .map(|w| w.is_finished())
+            .unwrap_or(false)
+        {
+            self.join();
+        }
+    }
+
+    /// Wait for the backup that is being written, if any.
+    pub fn join(&mut self) {
+        match self.worker.take().map(JoinHandle::join) {
+            Some(Ok(Ok(path))) => println!("Saved backup to {}", path.to_string_lossy()),
+            Some(Ok(Err(e))) => log::error!("Could not save backup {:?}", e),
+            Some(Err(_)) => log::error!("Backup thread panicked"),
+            None => (),
+        }
+    }
+
+    /// Start writing `design` as the most recent backup of the design stored at `design_path`.
+    pub fn save(
+        &mut self,
+        design: Design,
+        design_path: Option<&Path>,
+    ) -> Result<(), SaveDesignError> {
+        let paths =
+            backup_paths(design_path).ok_or_else(SaveDesignError::cannot_open_default_dir)?;
+        self.join();
+        self.worker = Some(std::thread::spawn(move || write_backup(design, paths)));
+        Ok(())
+    }
+}
+
+/// Shift the existing backups and write `design` to the first path of `paths`.
+fn write_backup(design: Design, paths: Vec<PathBuf>) -> Result<PathBuf, SaveDesignError> {
+    let json_content = serde_json::to_string_pretty(&design)?;
+    if let Some(directory) = paths[0].parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    for shift in paths.windows(2).rev() {
+        if shift[0].exists() {
+            std::fs::rename(&shift[0], &shift[1])?;
+        }
+    }
+    std::fs::write(&paths[0], json_content)?;
+    Ok(paths[0].clone())
+}
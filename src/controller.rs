@@ -23,9 +23,12 @@ mod download_intervals;
 mod download_staples;
 use download_staples::*;
 pub use download_staples::{DownloadStappleError, DownloadStappleOk, StaplesDownloader};
+mod dropped_files;
 mod quit;
+mod recovery;
 use ensnano_design::grid::GridId;
 use ensnano_design::group_attributes::GroupPivot;
+use ensnano_design::GroupId;
 use ensnano_exports::{ExportResult, ExportType};
 use ensnano_interactor::{
     application::Notification, DesignOperation, RevolutionSurfaceSystemDescriptor,
@@ -192,16 +195,37 @@ pub(crate) trait MainState: ScaffoldSetter {
     fn exit_control_flow(&mut self);
     fn new_design(&mut self);
     fn load_design(&mut self, path: PathBuf) -> Result<(), LoadDesignError>;
-    fn save_design(&mut self, path: &PathBuf) -> Result<(), SaveDesignError>;
+    /// Remove `path` from the list of recently opened designs.
+    fn forget_recent_file(&mut self, path: &Path);
+    /// Load the design stored in `backup` as the new version of the design stored at
+    /// `design_path`.
+    fn restore_backup(
+        &mut self,
+        backup: PathBuf,
+        design_path: Option<PathBuf>,
+    ) -> Result<(), LoadDesignError>;
+    /// Start writing the current design at `path` and return the identifier of the request.
+    fn save_design(&mut self, path: &PathBuf) -> Result<u64, SaveDesignError>;
+    /// Return the result of the save request `id` once the design has been written. The result
+    /// is returned only once.
+    fn take_design_save_result(&mut self, id: u64) -> Option<Result<(), SaveDesignError>>;
+    /// Return true if a design is being written.
+    fn is_saving_design(&self) -> bool;
     fn save_backup(&mut self) -> Result<(), SaveDesignError>;
     fn get_chanel_reader(&mut self) -> &mut ChanelReader;
     fn apply_operation(&mut self, operation: DesignOperation);
     fn apply_silent_operation(&mut self, operation: DesignOperation);
     fn undo(&mut self);
     fn redo(&mut self);
+    fn jump_in_history(&mut self, steps: isize);
     fn get_staple_downloader(&self) -> Box<dyn StaplesDownloader>;
     fn toggle_split_mode(&mut self, mode: SplitMode);
-    fn export(&mut self, path: &PathBuf, export_type: ExportType) -> ExportResult;
+    fn export(
+        &mut self,
+        path: &PathBuf,
+        export_type: ExportType,
+        selection_only: bool,
+    ) -> ExportResult;
     fn change_ui_size(&mut self, ui_size: UiSize);
     fn notify_apps(&mut self, notificiation: Notification);
     fn get_selection(&mut self) -> Box<dyn AsRef<[Selection]>>;
@@ -210,12 +234,17 @@ pub(crate) trait MainState: ScaffoldSetter {
     fn get_bezier_sheet_creation_position(&self) -> Option<(Vec3, Rotor3)>;
     fn finish_operation(&mut self);
     fn request_copy(&mut self);
+    /// Copy the sequences of the selected strands to the system clipboard.
+    fn copy_sequences(&mut self);
+    /// Copy the summary of the design to the system clipboard.
+    fn copy_design_summary(&mut self);
     fn request_pasting_candidate(&mut self, candidate: Option<PastePosition>);
     fn init_paste(&mut self);
     fn apply_paste(&mut self);
     fn duplicate(&mut self);
     fn delete_selection(&mut self);
     fn scaffold_to_selection(&mut self);
+    fn set_scaffold_role(&mut self, s_id: usize, is_scaffold: bool);
     fn start_helix_simulation(&mut self, parameters: RigidBodyConstants);
     fn start_grid_simulation(&mut self, parameters: RigidBodyConstants);
     fn start_revolution_simulation(&mut self, desc: RevolutionSurfaceSystemDescriptor);
@@ -223,40 +252,80 @@ pub(crate) trait MainState: ScaffoldSetter {
     fn update_simulation(&mut self, request: SimulationRequest);
     fn set_roll_of_selected_helices(&mut self, roll: f32);
     fn turn_selection_into_anchor(&mut self);
+    fn anchor_selected_strands(&mut self, ends_only: bool);
     fn set_visibility_sieve(&mut self, compl: bool);
     fn clear_visibility_sieve(&mut self);
     fn need_save(&self) -> Option<Option<PathBuf>>;
     fn get_current_design_directory(&self) -> Option<&Path>;
     fn get_current_file_name(&self) -> Option<&Path>;
     fn set_current_group_pivot(&mut self, pivot: GroupPivot);
+    /// Move the pivot of the current group, even if it already has one.
+    fn place_group_pivot(&mut self, pivot: GroupPivot);
     fn translate_group_pivot(&mut self, translation: Vec3);
     fn rotate_group_pivot(&mut self, rotation: Rotor3);
     fn create_new_camera(&mut self);
     fn select_camera(&mut self, camera_id: ensnano_design::CameraId);
     fn select_favorite_camera(&mut self, n_camera: u32);
+    /// Move the camera to the saved camera following (or preceding if `forward` is false) the
+    /// last selected one.
+    fn select_next_camera(&mut self, forward: bool);
+    /// Move the camera smoothly to the `n_camera`-th saved camera.
+    fn go_to_nth_camera(&mut self, n_camera: u32);
     fn update_camera(&mut self, camera_id: ensnano_design::CameraId);
+    /// Stop editing the parameters of the hyperboloid grid `grid_id` and report the strained
+    /// cross-overs between this grid and the rest of the design.
+    fn finish_hyperboloid_update(&mut self, grid_id: GridId);
+    /// Save the current selection in the design under the name `name`.
+    fn save_named_selection(&mut self, name: String);
+    /// Select the elements of the `idx`-th named selection that still exist in the design.
+    fn recall_named_selection(&mut self, idx: usize);
     fn toggle_2d(&mut self);
     fn make_all_suggested_xover(&mut self, doubled: bool);
+    /// Place cross-overs between the two selected helices.
+    fn place_xovers_between_selected_helices(
+        &mut self,
+        parameters: ensnano_design::XoverPlacementParameters,
+    );
     fn need_backup(&self) -> bool;
     fn check_backup(&mut self);
     fn flip_split_views(&mut self);
     fn start_twist(&mut self, g_id: GridId);
     fn set_expand_insertions(&mut self, expand: bool);
+    fn set_isolate_selection(
+        &mut self,
+        isolate_selection: ensnano_interactor::graphics::IsolateSelection,
+    );
+    fn set_camera_mode(&mut self, camera_mode: ensnano_interactor::graphics::CameraMode);
+    fn toggle_fly_mode(&mut self);
     fn set_exporting(&mut self, exporting: bool);
     fn load_3d_object(&mut self, path: PathBuf);
     fn load_svg(&mut self, path: PathBuf);
+    fn load_oxdna_configuration(
+        &mut self,
+        path: PathBuf,
+    ) -> Result<(), ensnano_exports::oxdna::import::OxDnaImportError>;
+    fn set_hide_idealized_design(&mut self, hide: bool);
+    /// Load the design stored at `path` as the reference design drawn next to the edited one.
+    fn load_reference_design(&mut self, path: PathBuf) -> Result<(), LoadDesignError>;
+    fn remove_reference_design(&mut self);
+    fn set_reference_design_visibility(&mut self, visible: bool);
+    fn set_reference_design_isometry(&mut self, isometry: ultraviolet::Isometry3);
+    fn set_show_design_changes(&mut self, show: bool);
 }
 
 pub enum LoadDesignError {
-    JsonError(serde_json::Error),
+    CannotReadFile(std::io::Error),
+    JsonError(crate::app_state::JsonDiagnostic),
     ScadnanoImportError(ensnano_design::scadnano::ScadnanoImportError),
+    CadnanoImportError(ensnano_design::cadnano::CadnanoImportError),
     IncompatibleVersion { current: String, required: String },
 }
 
 impl std::fmt::Display for LoadDesignError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::JsonError(e) => write!(f, "Json error: {e}"),
+            Self::CannotReadFile(e) => write!(f, "Could not read file: {e}"),
+            Self::JsonError(diagnostic) => write!(f, "{diagnostic}"),
             Self::ScadnanoImportError(e) => {
                 write!(
                     f,
@@ -265,19 +334,28 @@ impl std::fmt::Display for LoadDesignError {
                     e
                 )
             }
+            Self::CadnanoImportError(e) => {
+                write!(
+                    f,
+                    "Cadnano file detected but the following error was encountered:
+                {:?}",
+                    e
+                )
+            }
             Self::IncompatibleVersion { current, required } => {
                 write!(
                     f,
                     "Your ENSnano version is too old to load this design.
                 Your version: {current},
-                Requiered version: {required}"
+                Requiered version: {required}
+                Please install a more recent version of ENSnano to open it."
                 )
             }
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SaveDesignError(String);
 
 impl<E: std::error::Error> From<E> for SaveDesignError {
@@ -290,6 +368,10 @@ impl SaveDesignError {
     pub fn cannot_open_default_dir() -> Self {
         Self(crate::consts::CANNOT_OPEN_DEFAULT_DIR.to_string())
     }
+
+    pub fn worker_stopped() -> Self {
+        Self(crate::consts::SAVING_THREAD_STOPPED.to_string())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -298,4 +380,8 @@ pub enum SimulationRequest {
     UpdateParameters(RigidBodyConstants),
     FinishRelaxation,
     Reset,
+    /// Advance a paused rigid helices simulation by one time step
+    Step,
+    /// Make the helices of the given groups immobile in the rigid helices simulation
+    SetPinnedGroups(Vec<GroupId>),
 }
@@ -26,11 +26,13 @@ use crate::app_state::{
     ShiftOptimizationResult, ShiftOptimizerReader, SimulationInterface, SimulationReader,
     SimulationUpdate,
 };
+use crate::design_saver::DesignSaveResult;
 #[derive(Default)]
 pub struct ChanelReader {
     scaffold_shift_optimization_progress: Option<mpsc::Receiver<f32>>,
     scaffold_shift_optimization_result: Option<mpsc::Receiver<ShiftOptimizationResult>>,
     simulation_interface: Option<Weak<Mutex<dyn SimulationInterface>>>,
+    design_save_results: Option<mpsc::Receiver<DesignSaveResult>>,
 }
 
 pub enum ChanelReaderUpdate {
@@ -40,6 +42,8 @@ pub enum ChanelReaderUpdate {
     ScaffoldShiftOptimizationResult(ShiftOptimizationResult),
     SimulationUpdate(Box<dyn SimulationUpdate>),
     SimulationExpired,
+    /// The worker thread finished writing a design
+    DesignSaved(DesignSaveResult),
 }
 
 impl ChanelReader {
@@ -70,9 +74,16 @@ impl ChanelReader {
         if invalidated {
             self.simulation_interface = None;
         }
+        if let Some(chanel) = self.design_save_results.as_ref() {
+            updates.extend(chanel.try_iter().map(ChanelReaderUpdate::DesignSaved));
+        }
         updates
     }
 
+    pub fn attach_design_save_chanel(&mut self, chanel: mpsc::Receiver<DesignSaveResult>) {
+        self.design_save_results = Some(chanel);
+    }
+
     fn get_scaffold_shift_optimization_progress(&self) -> Option<f32> {
         self.scaffold_shift_optimization_progress
             .as_ref()
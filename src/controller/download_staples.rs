@@ -173,7 +173,7 @@ pub trait StaplesDownloader {
     fn download_staples(&self) -> Result<DownloadStappleOk, DownloadStappleError>;
     fn write_staples_xlsx(&self, xlsx_path: &PathBuf);
     fn write_intervals(&self, origami_path: &PathBuf);
-    fn default_shift(&self) -> Option<usize>;
+    fn default_shift(&self, scaffold_id: usize) -> Option<usize>;
 }
 
 pub enum DownloadStappleError {
@@ -182,7 +182,7 @@ pub enum DownloadStappleError {
     SeveralDesignNoneSelected,
     /// No strand is set as the scaffold
     NoScaffoldSet,
-    /// There is no sequence set for the scaffold
+    /// There is no sequence set for one of the scaffolds
     ScaffoldSequenceNotSet,
 }
 
@@ -0,0 +1,129 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Open the files that were dropped onto the window, choosing how to open them from their
+//! extension.
+
+use super::{messages, Load, LoadType, MainState, NormalState, State, TransitionMessage};
+use crate::dialog::Filters;
+use std::path::{Path, PathBuf};
+
+/// Open the files dropped onto the window.
+///
+/// When several files are dropped at once, only one of them is opened. Designs are opened in
+/// priority, then oxDNA configurations (which may be dropped together with their topology),
+/// 3D objects and finally svg paths.
+pub(super) fn open_dropped_files(
+    paths: Vec<PathBuf>,
+    main_state: &mut dyn MainState,
+) -> Box<dyn State> {
+    let (path, load_type) = if let Some(choice) = choose_file(&paths) {
+        choice
+    } else {
+        return TransitionMessage::new(
+            messages::unsupported_dropped_files(&paths),
+            rfd::MessageLevel::Error,
+            Box::new(NormalState),
+        );
+    };
+    if paths.len() > 1 {
+        log::info!("Several files dropped, opening {}", path.to_string_lossy());
+    }
+    match load_type {
+        LoadType::Design => Load::known_path_after_save(main_state.need_save(), path),
+        LoadType::Object3D if main_state.get_current_design_directory().is_none() => {
+            TransitionMessage::new(
+                messages::SET_DESIGN_DIRECTORY_FIRST,
+                rfd::MessageLevel::Error,
+                Box::new(NormalState),
+            )
+        }
+        load_type => Box::new(Load::known_path_of_type(path, load_type)),
+    }
+}
+
+fn choose_file(paths: &[PathBuf]) -> Option<(PathBuf, LoadType)> {
+    let find = |filters: Filters| paths.iter().find(|p| has_extension_in(p, filters)).cloned();
+    if let Some(design) = find(messages::DESIGN_LOAD_FILTER) {
+        Some((design, LoadType::Design))
+    } else if let Some(configuration) =
+        find(messages::OXDNA_CONFIGURATION_LOAD_FILTERS).or_else(|| {
+            paths
+                .iter()
+                .filter(|p| has_extension(p, messages::OXDNA_TOPOLOGY_EXTENSION))
+                .find_map(|p| configuration_of_topology(p))
+        })
+    {
+        Some((configuration, LoadType::OxDnaConfiguration))
+    } else if let Some(object) = find(messages::OBJECT3D_FILTERS) {
+        Some((object, LoadType::Object3D))
+    } else {
+        find(messages::SVG_FILTERS).map(|svg| (svg, LoadType::SvgPath))
+    }
+}
+
+/// The configuration file next to an oxDNA topology file.
+fn configuration_of_topology(topology: &Path) -> Option<PathBuf> {
+    messages::OXDNA_CONFIGURATION_LOAD_FILTERS
+        .iter()
+        .flat_map(|(_, extensions)| extensions.iter())
+        .map(|ext| topology.with_extension(ext))
+        .find(|configuration| configuration.exists())
+}
+
+fn has_extension_in(path: &Path, filters: Filters) -> bool {
+    filters
+        .iter()
+        .flat_map(|(_, extensions)| extensions.iter())
+        .any(|ext| has_extension(path, ext))
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().eq_ignore_ascii_case(extension))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chosen(paths: &[&str]) -> Option<(PathBuf, LoadType)> {
+        let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        choose_file(&paths)
+    }
+
+    #[test]
+    fn designs_are_opened_in_priority() {
+        let (path, load_type) = chosen(&["object.stl", "origami.sc", "design.ENS"]).unwrap();
+        assert_eq!(path, PathBuf::from("origami.sc"));
+        assert!(matches!(load_type, LoadType::Design));
+    }
+
+    #[test]
+    fn oxdna_configuration_dropped_with_its_topology() {
+        let (path, load_type) = chosen(&["export.top", "export.dat"]).unwrap();
+        assert_eq!(path, PathBuf::from("export.dat"));
+        assert!(matches!(load_type, LoadType::OxDnaConfiguration));
+    }
+
+    #[test]
+    fn unsupported_files_are_not_opened() {
+        assert!(chosen(&["notes.txt", "README"]).is_none());
+    }
+}
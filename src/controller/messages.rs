@@ -31,8 +31,9 @@ pub const NO_SCAFFOLD_SET: &'static str = "No scaffold set. \n
                     Chose a strand and set it as the scaffold by checking the scaffold checkbox\
                     in the status bar";
 
-pub const NO_SCAFFOLD_SEQUENCE_SET: &'static str = "No sequence uploaded for scaffold. \n
-                Upload a sequence for the scaffold by pressing the \"Load scaffold\" button";
+pub const NO_SCAFFOLD_SEQUENCE_SET: &'static str =
+    "No sequence uploaded for one of the scaffolds. \n
+                Upload a sequence for each scaffold by pressing the \"Load scaffold\" button";
 
 pub const NO_DESIGN_SELECTED: &'static str =
     "No design selected, select a design by selecting one of its elements";
@@ -49,6 +50,39 @@ pub const SAVE_DESIGN_FAILED: &'static str = "Could not save design";
 pub const SAVE_BEFORE_EXIT: &'static str = "Do you want to save your design before exiting?";
 pub const SAVE_BEFORE_LOAD: &'static str =
     "Do you want to save your design before loading an other one?";
+pub fn restore_backup(backup: &std::path::Path) -> String {
+    format!(
+        "ENSnano was not closed properly and a more recent backup of your design was found at\n{}\n\
+        Do you want to restore it?",
+        backup.to_string_lossy()
+    )
+}
+
+pub fn open_backup_after_error(error: &str, backup: &std::path::Path) -> String {
+    format!(
+        "Error when loading design:\n{error}\n\n\
+        A backup of this design was found at\n{}\n\
+        Do you want to open it instead?",
+        backup.to_string_lossy()
+    )
+}
+
+pub fn missing_design_file(path: &std::path::Path) -> String {
+    format!(
+        "The file {} does not exist anymore.\n\
+        It was removed from the list of recent files.",
+        path.to_string_lossy()
+    )
+}
+
+pub fn unsupported_dropped_files(paths: &[std::path::PathBuf]) -> String {
+    let names: Vec<_> = paths.iter().map(|p| p.to_string_lossy()).collect();
+    format!(
+        "ENSnano cannot open the dropped file(s):\n{}",
+        names.join("\n")
+    )
+}
+
 pub const SAVE_BEFORE_RELOAD: &'static str =
     "Do you want to save your changes in an other file before reloading?";
 pub const SAVE_BEFORE_NEW: &'static str =
@@ -100,14 +134,24 @@ pub const CHANGING_DNA_PARAMETERS_WARNING: &'static str =
 
 pub const OXDNA_CONFIG_EXTENSTION: &str = "oxdna";
 
+pub const OXDNA_TOPOLOGY_EXTENSION: &str = "top";
+
 pub const OXDNA_CONFIG_FILTERS: Filters = &[("Oxdna config files", &[OXDNA_CONFIG_EXTENSTION])];
 
+pub const OXDNA_CONFIGURATION_LOAD_FILTERS: Filters = &[(
+    "Oxdna configuration files",
+    &[OXDNA_CONFIG_EXTENSTION, "dat", "conf"],
+)];
+
 pub const STAPLES_FILTER: Filters = &[("Excel files", &["xlsx"])];
 
 pub const ORIGAMI_FLTER: Filters = &[("Origami files", &[crate::consts::ORIGAMI_EXTENSION])];
 
 pub const PDB_FILTER: Filters = &[("Pdb files", &["pdb"])];
 pub const CADNANO_FILTER: Filters = &[("Cadnano files", &["json"])];
+pub const CSV_FILTER: Filters = &[("Csv files", &["csv"])];
+
+pub const OBJ_FILTER: Filters = &[("Wavefront obj files", &["obj"])];
 
 pub const OBJECT3D_FILTERS: Filters = &[
     ("All supported files", &["gltf", "stl"]),
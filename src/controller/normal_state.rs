@@ -24,7 +24,8 @@ use super::*;
 use ensnano_design::group_attributes::GroupPivot;
 use ensnano_design::{grid::GridId, Parameters};
 use ensnano_interactor::{
-    graphics::FogParameters, HyperboloidOperation, RevolutionSurfaceSystemDescriptor,
+    graphics::{CameraMode, FogParameters},
+    HyperboloidOperation, RevolutionSurfaceSystemDescriptor,
 };
 
 /// User is interacting with graphical components.
@@ -48,13 +49,16 @@ impl State for NormalState {
                 }
                 Action::DownloadStaplesRequest => Box::new(DownloadStaples::default()),
                 Action::DownloadOrigamiRequest => Box::new(DownloadIntervals::default()),
-                Action::SetScaffoldSequence { shift } => Box::new(SetScaffoldSequence::init(shift)),
+                Action::SetScaffoldSequence { scaffold_id, shift } => {
+                    Box::new(SetScaffoldSequence::init(scaffold_id, shift))
+                }
                 Action::Exit => Quit::quit(main_state.need_save()),
                 Action::ToggleSplit(mode) => {
                     main_state.toggle_split_mode(mode);
                     self
                 }
-                Action::Export(export_type) => export(export_type),
+                Action::Export(export_type) => export(export_type, false),
+                Action::ExportSelection(export_type) => export(export_type, true),
                 Action::CloseOverlay(_) | Action::OpenOverlay(_) => {
                     println!("unexpected action");
                     self
@@ -82,16 +86,24 @@ impl State for NormalState {
                     main_state.redo();
                     self
                 }
+                Action::JumpInHistory(steps) => {
+                    main_state.jump_in_history(steps);
+                    self
+                }
+                Action::NotifyApps(Notification::IsolateSelection(isolate_selection)) => {
+                    main_state.set_isolate_selection(isolate_selection);
+                    self
+                }
                 Action::NotifyApps(notificiation) => {
                     main_state.notify_apps(notificiation);
                     self
                 }
                 Action::TurnSelectionIntoGrid => self.turn_selection_into_grid(main_state),
                 Action::AddGrid(descr) => self.add_grid(main_state, descr),
-                Action::ChangeSequence(_) => {
-                    println!("Sequence input is not yet implemented");
-                    self
+                Action::ChangeSequence { sequence, force } => {
+                    self.change_sequence(main_state, sequence, force)
                 }
+                Action::ClearSequence => self.clear_sequence(main_state),
                 Action::ChangeColorStrand(color) => self.change_color(main_state, color),
                 Action::FinishChangingColor => {
                     main_state.finish_operation();
@@ -101,7 +113,24 @@ impl State for NormalState {
                     self.toggle_helices_persistance(main_state, persistant)
                 }
                 Action::ToggleSmallSphere(small) => self.toggle_small_spheres(main_state, small),
-                Action::LoadDesign(Some(path)) => Box::new(Load::known_path(path)),
+                Action::SetGridVisibility(visible) => self.set_grid_visibility(main_state, visible),
+                Action::SoloGrids => self.solo_grids(main_state),
+                Action::LoadDesign(Some(path)) => {
+                    if path.exists() {
+                        Load::known_path_after_save(main_state.need_save(), path)
+                    } else {
+                        main_state.forget_recent_file(&path);
+                        TransitionMessage::new(
+                            messages::missing_design_file(&path),
+                            rfd::MessageLevel::Error,
+                            Box::new(NormalState),
+                        )
+                    }
+                }
+                Action::StartupLoad(path) => super::recovery::startup_load(path),
+                Action::OpenDroppedFiles(paths) => {
+                    super::dropped_files::open_dropped_files(paths, main_state)
+                }
                 Action::LoadDesign(None) => Load::load(main_state.need_save(), LoadType::Design),
                 Action::Import3DObject => {
                     if main_state.get_current_design_directory().is_some() {
@@ -115,6 +144,10 @@ impl State for NormalState {
                     }
                 }
                 Action::ImportSvg => Load::load(None, LoadType::SvgPath),
+                Action::ImportOxDnaConfiguration => Load::load(None, LoadType::OxDnaConfiguration),
+                Action::ImportReferenceDesign => Load::load(None, LoadType::ReferenceDesign),
+                Action::LoadReferenceDesign(path) => load_reference_design(path, main_state),
+                Action::ExportAllCameras => Box::new(ExportingAllCameras::new()),
                 Action::SuspendOp => {
                     log::info!("Suspending operation");
                     main_state.finish_operation();
@@ -124,6 +157,14 @@ impl State for NormalState {
                     main_state.request_copy();
                     self
                 }
+                Action::CopySequences => {
+                    main_state.copy_sequences();
+                    self
+                }
+                Action::CopyDesignSummary => {
+                    main_state.copy_design_summary();
+                    self
+                }
                 Action::InitPaste => {
                     main_state.init_paste();
                     self
@@ -149,6 +190,10 @@ impl State for NormalState {
                     main_state.scaffold_to_selection();
                     self
                 }
+                Action::SetScaffoldRole { s_id, is_scaffold } => {
+                    main_state.set_scaffold_role(s_id, is_scaffold);
+                    self
+                }
                 Action::NewHyperboloid(request) => {
                     if let Some((position, orientation)) = main_state.get_grid_creation_position() {
                         main_state.apply_operation(DesignOperation::HyperboloidOperation(
@@ -169,6 +214,7 @@ impl State for NormalState {
                             desc: ensnano_design::BezierPlaneDescriptor {
                                 position,
                                 orientation,
+                                ..Default::default()
                             },
                         })
                     }
@@ -202,6 +248,14 @@ impl State for NormalState {
                     main_state.update_simulation(SimulationRequest::Reset);
                     self
                 }
+                Action::StepSimulation => {
+                    main_state.update_simulation(SimulationRequest::Step);
+                    self
+                }
+                Action::SetPinnedGroups(group_ids) => {
+                    main_state.update_simulation(SimulationRequest::SetPinnedGroups(group_ids));
+                    self
+                }
                 Action::RigidParametersUpdate(parameters) => {
                     main_state.update_simulation(SimulationRequest::UpdateParameters(parameters));
                     self
@@ -218,10 +272,18 @@ impl State for NormalState {
                     main_state.notify_apps(Notification::Split2d);
                     self
                 }
+                Action::Split3D => {
+                    main_state.notify_apps(Notification::ToggleSplitScene3D);
+                    self
+                }
                 Action::TurnIntoAnchor => {
                     main_state.turn_selection_into_anchor();
                     self
                 }
+                Action::AnchorSelectedStrands { ends_only } => {
+                    main_state.anchor_selected_strands(ends_only);
+                    self
+                }
                 Action::SetVisiblitySieve { compl } => {
                     main_state.set_visibility_sieve(compl);
                     self
@@ -237,6 +299,10 @@ impl State for NormalState {
                         self
                     }
                 }
+                Action::PlaceGroupPivot(pivot) => {
+                    main_state.place_group_pivot(pivot);
+                    self
+                }
                 Action::SetGroupPivot(pivot) => {
                     main_state.set_current_group_pivot(pivot);
                     self
@@ -262,10 +328,30 @@ impl State for NormalState {
                     main_state.select_favorite_camera(n);
                     self
                 }
+                Action::SelectNextCamera { forward } => {
+                    main_state.select_next_camera(forward);
+                    self
+                }
+                Action::GoToNthCamera(n) => {
+                    main_state.go_to_nth_camera(n);
+                    self
+                }
                 Action::UpdateCamera(camera_id) => {
                     main_state.update_camera(camera_id);
                     self
                 }
+                Action::FinishHyperboloidUpdate(grid_id) => {
+                    main_state.finish_hyperboloid_update(grid_id);
+                    self
+                }
+                Action::SaveNamedSelection(name) => {
+                    main_state.save_named_selection(name);
+                    self
+                }
+                Action::RecallNamedSelection(idx) => {
+                    main_state.recall_named_selection(idx);
+                    self
+                }
                 Action::Toggle2D => {
                     main_state.toggle_2d();
                     self
@@ -275,6 +361,10 @@ impl State for NormalState {
                     main_state.make_all_suggested_xover(doubled);
                     self
                 }
+                Action::PlaceXoversBetweenHelices(parameters) => {
+                    main_state.place_xovers_between_selected_helices(parameters);
+                    self
+                }
 
                 Action::FlipSplitViews => {
                     main_state.flip_split_views();
@@ -293,11 +383,41 @@ impl State for NormalState {
                     main_state.set_expand_insertions(b);
                     self
                 }
+                Action::SetHideIdealizedDesign(hide) => {
+                    main_state.set_hide_idealized_design(hide);
+                    self
+                }
+                Action::RemoveReferenceDesign => {
+                    main_state.remove_reference_design();
+                    self
+                }
+                Action::SetReferenceDesignVisibility(visible) => {
+                    main_state.set_reference_design_visibility(visible);
+                    self
+                }
+                Action::SetReferenceDesignIsometry(isometry) => {
+                    main_state.set_reference_design_isometry(isometry);
+                    self
+                }
+                Action::ShowDesignChanges(show) => {
+                    main_state.set_show_design_changes(show);
+                    self
+                }
+                Action::ToggleFlyMode => {
+                    main_state.toggle_fly_mode();
+                    self
+                }
+                Action::SetCameraMode(camera_mode) => {
+                    main_state.set_camera_mode(camera_mode);
+                    self
+                }
                 Action::SetExporting(exporting) => {
                     main_state.set_exporting(exporting);
                     self
                 }
-                Action::OptimizeShift => Box::new(SetScaffoldSequence::optimize_shift()),
+                Action::OptimizeShift { scaffold_id } => {
+                    Box::new(SetScaffoldSequence::optimize_shift(scaffold_id))
+                }
                 action => {
                     println!("Not implemented {:?}", action);
                     self
@@ -343,6 +463,8 @@ impl NormalState {
                 orientation,
                 invisible: false,
                 bezier_vertex: None,
+                bounds: None,
+                phantom_length: None,
             }))
         } else {
             println!("Could not get position and orientation for new grid");
@@ -358,6 +480,35 @@ impl NormalState {
         self
     }
 
+    fn change_sequence(
+        self: Box<Self>,
+        main_state: &mut dyn MainState,
+        sequence: String,
+        force: bool,
+    ) -> Box<Self> {
+        let strands = ensnano_interactor::extract_strands_from_selection(
+            main_state.get_selection().as_ref().as_ref(),
+        );
+        if !strands.is_empty() {
+            main_state.apply_operation(DesignOperation::ChangeSequence {
+                sequence,
+                strands,
+                force,
+            });
+        }
+        self
+    }
+
+    fn clear_sequence(self: Box<Self>, main_state: &mut dyn MainState) -> Box<Self> {
+        let strands = ensnano_interactor::extract_strands_from_selection(
+            main_state.get_selection().as_ref().as_ref(),
+        );
+        if !strands.is_empty() {
+            main_state.apply_operation(DesignOperation::ClearSequence { strands });
+        }
+        self
+    }
+
     fn toggle_small_spheres(
         self: Box<Self>,
         main_state: &mut dyn MainState,
@@ -386,6 +537,29 @@ impl NormalState {
         }
         self
     }
+
+    fn set_grid_visibility(
+        self: Box<Self>,
+        main_state: &mut dyn MainState,
+        visible: bool,
+    ) -> Box<Self> {
+        let grid_ids =
+            ensnano_interactor::extract_grids(main_state.get_selection().as_ref().as_ref());
+        if !grid_ids.is_empty() {
+            main_state.apply_operation(DesignOperation::SetGridVisibility { grid_ids, visible });
+        }
+        self
+    }
+
+    /// Hide all the grids except the selected ones.
+    fn solo_grids(self: Box<Self>, main_state: &mut dyn MainState) -> Box<Self> {
+        let grid_ids =
+            ensnano_interactor::extract_grids(main_state.get_selection().as_ref().as_ref());
+        if !grid_ids.is_empty() {
+            main_state.apply_operation(DesignOperation::SoloGrids { grid_ids });
+        }
+        self
+    }
 }
 
 fn save_as() -> Box<dyn State> {
@@ -410,14 +584,19 @@ fn quicksave<P: AsRef<Path>>(starting_path: P) -> Box<dyn State> {
     })
 }
 
-fn export(export_type: ExportType) -> Box<dyn State> {
+fn export(export_type: ExportType, selection_only: bool) -> Box<dyn State> {
     let on_success = Box::new(NormalState);
     let on_error = TransitionMessage::new(
         messages::OXDNA_EXPORT_FAILED,
         rfd::MessageLevel::Error,
         Box::new(NormalState),
     );
-    Box::new(Exporting::new(on_success, on_error, export_type))
+    Box::new(Exporting::new(
+        on_success,
+        on_error,
+        export_type,
+        selection_only,
+    ))
 }
 
 use ensnano_design::grid::{GridDescriptor, GridTypeDescr};
@@ -431,18 +610,26 @@ use ensnano_interactor::{
 #[derive(Debug, Clone)]
 pub enum Action {
     LoadDesign(Option<PathBuf>),
+    /// Load the design given as a command line argument, or offer to restore its backup if it is
+    /// more recent than the design file.
+    StartupLoad(Option<PathBuf>),
+    /// Open the files dropped onto the window
+    OpenDroppedFiles(Vec<PathBuf>),
     NewDesign,
     SaveAs,
     QuickSave,
     DownloadStaplesRequest,
     DownloadOrigamiRequest,
-    /// Trigger the sequence of action that will set the scaffold of the sequence.
+    /// Trigger the sequence of action that will set the sequence of a scaffold.
     SetScaffoldSequence {
+        scaffold_id: usize,
         shift: usize,
     },
     Exit,
     ToggleSplit(SplitMode),
     Export(ExportType),
+    /// Export only the strands of the current selection
+    ExportSelection(ExportType),
     CloseOverlay(OverlayType),
     OpenOverlay(OverlayType),
     ChangeUiSize(UiSize),
@@ -452,20 +639,35 @@ pub enum Action {
     SilentDesignOperation(DesignOperation),
     Undo,
     Redo,
+    /// Undo `-n` operations if `n` is negative, or redo `n` operations otherwise
+    JumpInHistory(isize),
     NotifyApps(Notification),
     TurnSelectionIntoGrid,
     AddGrid(GridTypeDescr),
     /// Set the sequence of all the selected strands
-    ChangeSequence(String),
+    ChangeSequence {
+        sequence: String,
+        /// Apply the sequence even if it does not match the scaffold
+        force: bool,
+    },
+    /// Remove the sequence of all the selected strands
+    ClearSequence,
     /// Change the color of all the selected strands
     ChangeColorStrand(u32),
     FinishChangingColor,
     ToggleHelicesPersistance(bool),
     ToggleSmallSphere(bool),
+    SetGridVisibility(bool),
+    /// Hide all the grids except the selected ones
+    SoloGrids,
     RollRequest(RollRequest),
     StopSimulation,
     RollHelices(f32),
     Copy,
+    /// Copy the sequences of the selected strands to the system clipboard
+    CopySequences,
+    /// Copy the summary of the design shown in the status bar to the system clipboard
+    CopyDesignSummary,
     PasteCandidate(Option<PastePosition>),
     InitPaste,
     ApplyPaste,
@@ -481,40 +683,91 @@ pub enum Action {
         parameters: RigidBodyConstants,
     },
     ResetSimulation,
+    StepSimulation,
+    SetPinnedGroups(Vec<GroupId>),
     RigidParametersUpdate(RigidBodyConstants),
     TurnIntoAnchor,
+    /// Flip the anchor status of the nucleotides of the selected strands and helices
+    AnchorSelectedStrands {
+        /// Only flip the 5' and 3' ends of the strands
+        ends_only: bool,
+    },
     NewHyperboloid(HyperboloidRequest),
     UpdateHyperboloidShift(f32),
+    /// Stop editing the parameters of an existing hyperboloid grid and report the strained
+    /// cross-overs that leave it
+    FinishHyperboloidUpdate(GridId),
     SetVisiblitySieve {
         compl: bool,
     },
     DeleteSelection,
     ScaffoldToSelection,
+    /// Add the strand `s_id` to the scaffolds of the design or remove it from them
+    SetScaffoldRole {
+        s_id: usize,
+        is_scaffold: bool,
+    },
     /// Remove empty domains and merge consecutive domains
     CleanDesign,
     SuspendOp,
     Fog(FogParameters),
     Split2D,
+    Split3D,
     ReloadFile,
     ClearVisibilitySieve,
     SetGroupPivot(GroupPivot),
+    /// Move the pivot of the current group, even if it already has one
+    PlaceGroupPivot(GroupPivot),
     TranslateGroupPivot(Vec3),
     RotateGroupPivot(Rotor3),
     NewCamera,
     SelectCamera(ensnano_design::CameraId),
     SelectFavoriteCamera(u32),
+    /// Cycle through the saved cameras
+    SelectNextCamera {
+        forward: bool,
+    },
+    /// Move the camera smoothly to the nth saved camera
+    GoToNthCamera(u32),
     UpdateCamera(ensnano_design::CameraId),
+    /// Save the current selection in the design under the given name
+    SaveNamedSelection(String),
+    /// Select the elements of the nth named selection of the design
+    RecallNamedSelection(usize),
     Toggle2D,
     MakeAllSuggestedXover {
         doubled: bool,
     },
+    /// Place cross-overs between the two selected helices
+    PlaceXoversBetweenHelices(ensnano_design::XoverPlacementParameters),
     FlipSplitViews,
     Twist(GridId),
     SetDnaParameters(Parameters),
     SetExpandInsertions(bool),
+    /// Only draw the oxDNA configuration shown on top of the design in the 3D view
+    SetHideIdealizedDesign(bool),
+    SetCameraMode(CameraMode),
+    /// Enter or leave the fly-through navigation mode of the 3D view
+    ToggleFlyMode,
     AddBezierPlane,
     SetExporting(bool),
     Import3DObject,
     ImportSvg,
-    OptimizeShift,
+    ImportOxDnaConfiguration,
+    /// Ask for a design file and load it as the reference design
+    ImportReferenceDesign,
+    /// Load a design, next to the edited one, that is drawn in the 3D view for comparison but
+    /// cannot be modified
+    LoadReferenceDesign(PathBuf),
+    RemoveReferenceDesign,
+    SetReferenceDesignVisibility(bool),
+    /// Set the isometry applied to the reference design to align it with the edited design
+    SetReferenceDesignIsometry(ultraviolet::Isometry3),
+    /// Highlight the changes made to the design since it was last saved
+    ShowDesignChanges(bool),
+    /// Render one picture per saved camera of the design
+    ExportAllCameras,
+    OptimizeShift {
+        scaffold_id: usize,
+    },
 }
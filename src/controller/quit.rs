@@ -23,6 +23,7 @@ use super::{dialog, messages, MainState, State, TransitionMessage, YesNo};
 
 use dialog::PathInput;
 use ensnano_exports::ExportType;
+use ensnano_interactor::application::Notification;
 use std::path::Path;
 
 pub(super) struct Quit {
@@ -58,6 +59,10 @@ impl State for Quit {
         match self.step {
             QuitStep::Init { need_save } => init_quit(need_save),
             QuitStep::Quitting => {
+                if pending_action.is_saving_design() {
+                    // Wait for the design to be written before exiting
+                    return Box::new(Quit::quitting());
+                }
                 pending_action.exit_control_flow();
                 Box::new(super::NormalState)
             }
@@ -99,9 +104,27 @@ pub(super) struct Load {
 
 impl Load {
     pub(super) fn known_path(path: PathBuf) -> Self {
+        Self::known_path_of_type(path, LoadType::Design)
+    }
+
+    pub(super) fn known_path_of_type(path: PathBuf, load_type: LoadType) -> Self {
         Self {
             step: LoadStep::GotPath(path),
-            load_type: LoadType::Design,
+            load_type,
+        }
+    }
+
+    /// Load the design stored at `path`, after offering to save the current design if needed.
+    pub(super) fn known_path_after_save(
+        need_save: Option<Option<PathBuf>>,
+        path: PathBuf,
+    ) -> Box<dyn State> {
+        if let Some(save_path) = need_save {
+            let yes = save_before_known_path(save_path, path.clone());
+            let no = Box::new(Load::known_path(path));
+            Box::new(YesNo::new(messages::SAVE_BEFORE_LOAD, yes, no))
+        } else {
+            Box::new(Load::known_path(path))
         }
     }
 
@@ -131,6 +154,8 @@ pub(super) enum LoadType {
     Design,
     Object3D,
     SvgPath,
+    OxDnaConfiguration,
+    ReferenceDesign,
 }
 
 impl Load {
@@ -162,6 +187,8 @@ impl State for Load {
                 LoadType::Design => load_design(path, state),
                 LoadType::Object3D => load_3d_object(path, state),
                 LoadType::SvgPath => load_svg(path, state),
+                LoadType::OxDnaConfiguration => load_oxdna_configuration(path, state),
+                LoadType::ReferenceDesign => load_reference_design(path, state),
             },
         }
     }
@@ -235,8 +262,9 @@ fn ask_path<P: AsRef<Path>>(
     } else {
         let filters = match load_type {
             LoadType::Object3D => messages::OBJECT3D_FILTERS,
-            LoadType::Design => messages::DESIGN_LOAD_FILTER,
+            LoadType::Design | LoadType::ReferenceDesign => messages::DESIGN_LOAD_FILTER,
             LoadType::SvgPath => messages::SVG_FILTERS,
+            LoadType::OxDnaConfiguration => messages::OXDNA_CONFIGURATION_LOAD_FILTERS,
         };
         let path_input = dialog::load(starting_directory, filters);
         Box::new(Load {
@@ -249,7 +277,10 @@ fn ask_path<P: AsRef<Path>>(
 }
 
 fn load_design(path: PathBuf, state: &mut dyn MainState) -> Box<dyn State> {
-    if let Err(err) = state.load_design(path) {
+    if let Err(err) = state.load_design(path.clone()) {
+        if let Some(backup) = crate::autosave::latest_backup(&path) {
+            return super::recovery::offer_backup_after_error(err.to_string(), backup, path);
+        }
         TransitionMessage::new(
             format!("Error when loading design:\n{err}"),
             rfd::MessageLevel::Error,
@@ -260,6 +291,18 @@ fn load_design(path: PathBuf, state: &mut dyn MainState) -> Box<dyn State> {
     }
 }
 
+pub(super) fn load_reference_design(path: PathBuf, state: &mut dyn MainState) -> Box<dyn State> {
+    if let Err(err) = state.load_reference_design(path) {
+        TransitionMessage::new(
+            format!("Error when loading reference design:\n{err}"),
+            rfd::MessageLevel::Error,
+            Box::new(super::NormalState),
+        )
+    } else {
+        Box::new(super::NormalState)
+    }
+}
+
 fn load_3d_object(path: PathBuf, state: &mut dyn MainState) -> Box<dyn State> {
     state.load_3d_object(path);
     Box::new(super::NormalState)
@@ -270,6 +313,18 @@ fn load_svg(path: PathBuf, state: &mut dyn MainState) -> Box<dyn State> {
     Box::new(super::NormalState)
 }
 
+fn load_oxdna_configuration(path: PathBuf, state: &mut dyn MainState) -> Box<dyn State> {
+    if let Err(err) = state.load_oxdna_configuration(path) {
+        TransitionMessage::new(
+            format!("Error when importing oxDNA configuration:\n{err}"),
+            rfd::MessageLevel::Error,
+            Box::new(super::NormalState),
+        )
+    } else {
+        Box::new(super::NormalState)
+    }
+}
+
 pub(super) struct NewDesign {
     step: NewStep,
 }
@@ -354,18 +409,17 @@ impl State for SaveAs {
         if let Some(ref getter) = self.file_getter {
             if let Some(path_opt) = getter.get() {
                 if let Some(ref path) = path_opt {
-                    if let Err(err) = main_state.save_design(path) {
-                        TransitionMessage::new(
+                    match main_state.save_design(path) {
+                        Ok(request_id) => Box::new(WaitingForSave {
+                            request_id,
+                            on_success: self.on_success,
+                            on_error: self.on_error,
+                        }),
+                        Err(err) => TransitionMessage::new(
                             format!("Failed to save: {:?}", err.0),
                             rfd::MessageLevel::Error,
                             self.on_error,
-                        )
-                    } else {
-                        TransitionMessage::new(
-                            "Saved successfully".to_string(),
-                            rfd::MessageLevel::Info,
-                            self.on_success,
-                        )
+                        ),
                     }
                 } else {
                     TransitionMessage::new(
@@ -397,18 +451,43 @@ pub(super) struct SaveWithPath {
 
 impl State for SaveWithPath {
     fn make_progress(self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
-        if let Err(err) = main_state.save_design(&self.path) {
-            TransitionMessage::new(
+        match main_state.save_design(&self.path) {
+            Ok(request_id) => Box::new(WaitingForSave {
+                request_id,
+                on_success: self.on_success,
+                on_error: self.on_error,
+            }),
+            Err(err) => TransitionMessage::new(
                 format!("Failed to save: {:?}", err.0),
                 rfd::MessageLevel::Error,
                 self.on_error,
-            )
-        } else {
-            TransitionMessage::new(
+            ),
+        }
+    }
+}
+
+/// Waits for the design to be written by the worker thread.
+struct WaitingForSave {
+    /// The identifier returned by `save_design`, so that the results of other saves are ignored
+    request_id: u64,
+    on_error: Box<dyn State>,
+    on_success: Box<dyn State>,
+}
+
+impl State for WaitingForSave {
+    fn make_progress(self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        match main_state.take_design_save_result(self.request_id) {
+            Some(Ok(())) => TransitionMessage::new(
                 "Saved successfully".to_string(),
                 rfd::MessageLevel::Info,
                 self.on_success,
-            )
+            ),
+            Some(Err(err)) => TransitionMessage::new(
+                format!("Failed to save: {:?}", err.0),
+                rfd::MessageLevel::Error,
+                self.on_error,
+            ),
+            None => self,
         }
     }
 }
@@ -418,6 +497,8 @@ pub(super) struct Exporting {
     on_success: Box<dyn State>,
     on_error: Box<dyn State>,
     export_type: ExportType,
+    /// Only export the strands of the current selection
+    selection_only: bool,
 }
 
 impl Exporting {
@@ -425,12 +506,14 @@ impl Exporting {
         on_success: Box<dyn State>,
         on_error: Box<dyn State>,
         export_type: ExportType,
+        selection_only: bool,
     ) -> Self {
         Self {
             file_getter: None,
             on_success,
             on_error,
             export_type,
+            selection_only,
         }
     }
 }
@@ -440,7 +523,7 @@ impl State for Exporting {
         if let Some(ref getter) = self.file_getter {
             if let Some(path_opt) = getter.get() {
                 if let Some(ref path) = path_opt {
-                    match main_state.export(path, self.export_type) {
+                    match main_state.export(path, self.export_type, self.selection_only) {
                         Err(err) => TransitionMessage::new(
                             messages::failed_to_save_msg(&err),
                             rfd::MessageLevel::Error,
@@ -479,12 +562,43 @@ impl State for Exporting {
     }
 }
 
+/// Ask for a directory and render one picture per saved camera of the design in it.
+pub(super) struct ExportingAllCameras {
+    dir_getter: Option<PathInput>,
+}
+
+impl ExportingAllCameras {
+    pub(super) fn new() -> Self {
+        Self { dir_getter: None }
+    }
+}
+
+impl State for ExportingAllCameras {
+    fn make_progress(mut self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Some(ref getter) = self.dir_getter {
+            match getter.get() {
+                Some(Some(directory)) => {
+                    main_state.notify_apps(Notification::ExportAllCameras(directory));
+                    Box::new(NormalState)
+                }
+                Some(None) => Box::new(NormalState),
+                None => self,
+            }
+        } else {
+            self.dir_getter = Some(dialog::get_dir());
+            self
+        }
+    }
+}
+
 fn export_extenstion(export_type: ExportType) -> &'static str {
     match export_type {
         ExportType::Oxdna => messages::OXDNA_CONFIG_EXTENSTION,
         ExportType::Pdb => "pdb",
         ExportType::Cadnano => "json",
         ExportType::Cando => "cndo",
+        ExportType::Csv => "csv",
+        ExportType::Obj(_) => "obj",
     }
 }
 
@@ -494,5 +608,7 @@ fn export_filters(export_type: ExportType) -> &'static Filters {
         ExportType::Pdb => &messages::PDB_FILTER,
         ExportType::Cadnano => &messages::CADNANO_FILTER,
         ExportType::Cando => todo!(),
+        ExportType::Csv => &messages::CSV_FILTER,
+        ExportType::Obj(_) => &messages::OBJ_FILTER,
     }
 }
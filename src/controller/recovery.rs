@@ -0,0 +1,76 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! States used to restore the backups left by a session of ENSnano that was not closed properly.
+
+use super::{messages, Load, MainState, NormalState, State, TransitionMessage, YesNo};
+use std::path::PathBuf;
+
+/// Load the design stored at `design_path`, after asking the user if they want to restore its
+/// backup when it is more recent than the design file.
+pub(super) fn startup_load(design_path: Option<PathBuf>) -> Box<dyn State> {
+    let load: Box<dyn State> = if let Some(path) = design_path.clone() {
+        Box::new(Load::known_path(path))
+    } else {
+        Box::new(NormalState)
+    };
+    if let Some(backup) = crate::autosave::backup_to_restore(design_path.as_deref()) {
+        let question = messages::restore_backup(&backup);
+        let yes = Box::new(RestoreBackup {
+            backup,
+            design_path,
+        });
+        Box::new(YesNo::new(question, yes, load))
+    } else {
+        load
+    }
+}
+
+/// Report an error that occurred when loading the design stored at `design_path`, and offer to
+/// open `backup` instead.
+pub(super) fn offer_backup_after_error(
+    error: String,
+    backup: PathBuf,
+    design_path: PathBuf,
+) -> Box<dyn State> {
+    let question = messages::open_backup_after_error(&error, &backup);
+    let yes = Box::new(RestoreBackup {
+        backup,
+        design_path: Some(design_path.with_extension(crate::consts::ENS_EXTENSION)),
+    });
+    Box::new(YesNo::new(question, yes, Box::new(NormalState)))
+}
+
+struct RestoreBackup {
+    backup: PathBuf,
+    design_path: Option<PathBuf>,
+}
+
+impl State for RestoreBackup {
+    fn make_progress(self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
+        if let Err(err) = main_state.restore_backup(self.backup, self.design_path) {
+            TransitionMessage::new(
+                format!("Error when restoring backup:\n{err}"),
+                rfd::MessageLevel::Error,
+                Box::new(NormalState),
+            )
+        } else {
+            Box::new(NormalState)
+        }
+    }
+}
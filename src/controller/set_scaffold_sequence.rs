@@ -22,22 +22,25 @@ use ensnano_interactor::StandardSequence;
 use dialog::PathInput;
 use std::path::Path;
 
-/// User is in the process of setting the sequence of the scaffold
+/// User is in the process of setting the sequence of a scaffold
 pub(super) struct SetScaffoldSequence {
     step: Step,
+    scaffold_id: usize,
     shift: usize,
 }
 
 impl SetScaffoldSequence {
-    pub(super) fn init(shift: usize) -> Self {
+    pub(super) fn init(scaffold_id: usize, shift: usize) -> Self {
         Self {
+            scaffold_id,
             shift,
             step: Default::default(),
         }
     }
 
-    pub(super) fn optimize_shift() -> Self {
+    pub(super) fn optimize_shift(scaffold_id: usize) -> Self {
         Self {
+            scaffold_id,
             shift: 0,
             step: Step::OptimizeScaffoldPosition { design_id: 0 },
         }
@@ -51,17 +54,19 @@ impl Default for Step {
 }
 
 impl SetScaffoldSequence {
-    fn use_default(shift: usize, sequence: StandardSequence) -> Self {
+    fn use_default(scaffold_id: usize, shift: usize, sequence: StandardSequence) -> Self {
         let sequence = sequence.sequence().to_string();
         Self {
             step: Step::SetSequence(sequence),
+            scaffold_id,
             shift,
         }
     }
 
-    fn ask_path(shift: usize) -> Self {
+    fn ask_path(scaffold_id: usize, shift: usize) -> Self {
         Self {
             step: Step::AskPath { path_input: None },
+            scaffold_id,
             shift,
         }
     }
@@ -86,22 +91,33 @@ enum Step {
 impl State for SetScaffoldSequence {
     fn make_progress(self: Box<Self>, main_state: &mut dyn MainState) -> Box<dyn State> {
         match self.step {
-            Step::Init => init_set_scaffold_sequence(self.shift, main_state.get_scaffold_length()),
+            Step::Init => init_set_scaffold_sequence(
+                self.scaffold_id,
+                self.shift,
+                main_state.get_scaffold_length(self.scaffold_id),
+            ),
             Step::AskPath { path_input } => ask_path(
                 path_input,
+                self.scaffold_id,
                 self.shift,
                 main_state.get_current_design_directory(),
             ),
-            Step::GotPath(path) => got_path(path, self.shift),
-            Step::SetSequence(sequence) => set_sequence(sequence, self.shift, main_state),
+            Step::GotPath(path) => got_path(path, self.scaffold_id, self.shift),
+            Step::SetSequence(sequence) => {
+                set_sequence(sequence, self.scaffold_id, self.shift, main_state)
+            }
             Step::OptimizeScaffoldPosition { design_id } => {
-                optimize_scaffold_position(design_id, main_state)
+                optimize_scaffold_position(design_id, self.scaffold_id, main_state)
             }
         }
     }
 }
 
-fn init_set_scaffold_sequence(shift: usize, scaffold_length: Option<usize>) -> Box<dyn State> {
+fn init_set_scaffold_sequence(
+    scaffold_id: usize,
+    shift: usize,
+    scaffold_length: Option<usize>,
+) -> Box<dyn State> {
     let suggested_sequence = scaffold_length
         .map(StandardSequence::from_length)
         .unwrap_or_default();
@@ -111,14 +127,19 @@ fn init_set_scaffold_sequence(shift: usize, scaffold_length: Option<usize>) -> B
     If you chose no, you will be ask to chose a file containing the scaffold sequence."
     );
 
-    let yes = Box::new(SetScaffoldSequence::use_default(shift, suggested_sequence));
-    let no = Box::new(SetScaffoldSequence::ask_path(shift));
+    let yes = Box::new(SetScaffoldSequence::use_default(
+        scaffold_id,
+        shift,
+        suggested_sequence,
+    ));
+    let no = Box::new(SetScaffoldSequence::ask_path(scaffold_id, shift));
 
     Box::new(YesNo::new(message, yes, no))
 }
 
 fn ask_path<P: AsRef<Path>>(
     path_input: Option<PathInput>,
+    scaffold_id: usize,
     shift: usize,
     starting_directory: Option<P>,
 ) -> Box<dyn State> {
@@ -127,6 +148,7 @@ fn ask_path<P: AsRef<Path>>(
             if let Some(path) = result {
                 Box::new(SetScaffoldSequence {
                     step: Step::GotPath(path),
+                    scaffold_id,
                     shift,
                 })
             } else {
@@ -141,6 +163,7 @@ fn ask_path<P: AsRef<Path>>(
                 step: Step::AskPath {
                     path_input: Some(path_input),
                 },
+                scaffold_id,
                 shift,
             })
         }
@@ -150,12 +173,13 @@ fn ask_path<P: AsRef<Path>>(
             step: Step::AskPath {
                 path_input: Some(path_input),
             },
+            scaffold_id,
             shift,
         })
     }
 }
 
-fn got_path(path: PathBuf, shift: usize) -> Box<dyn State> {
+fn got_path(path: PathBuf, scaffold_id: usize, shift: usize) -> Box<dyn State> {
     let mut content = std::fs::read_to_string(path).unwrap();
     content.make_ascii_uppercase();
     if let Some(n) =
@@ -166,6 +190,7 @@ fn got_path(path: PathBuf, shift: usize) -> Box<dyn State> {
     } else {
         Box::new(SetScaffoldSequence {
             step: Step::SetSequence(content),
+            scaffold_id,
             shift,
         })
     }
@@ -173,10 +198,11 @@ fn got_path(path: PathBuf, shift: usize) -> Box<dyn State> {
 
 fn set_sequence(
     sequence: String,
+    scaffold_id: usize,
     shift: usize,
     scaffold_setter: &mut dyn MainState,
 ) -> Box<dyn State> {
-    let result = scaffold_setter.set_scaffold_sequence(sequence, shift);
+    let result = scaffold_setter.set_scaffold_sequence(scaffold_id, sequence, shift);
     match result {
         Ok(SetScaffoldSequenceOk {
             default_shift,
@@ -186,6 +212,7 @@ fn set_sequence(
                 let message = messages::optimize_scaffold_position_msg(default_shift.unwrap_or(0));
                 let yes = Box::new(SetScaffoldSequence {
                     step: Step::OptimizeScaffoldPosition { design_id: 0 },
+                    scaffold_id,
                     shift,
                 });
                 let no = Box::new(super::NormalState);
@@ -212,19 +239,24 @@ fn set_sequence(
     }
 }
 
-fn optimize_scaffold_position(_design_id: usize, main_state: &mut dyn MainState) -> Box<dyn State> {
-    main_state.optimize_shift();
+fn optimize_scaffold_position(
+    _design_id: usize,
+    scaffold_id: usize,
+    main_state: &mut dyn MainState,
+) -> Box<dyn State> {
+    main_state.optimize_shift(scaffold_id);
     Box::new(super::NormalState)
 }
 
 pub trait ScaffoldSetter {
-    fn get_scaffold_length(&self) -> Option<usize>;
+    fn get_scaffold_length(&self, scaffold_id: usize) -> Option<usize>;
     fn set_scaffold_sequence(
         &mut self,
+        scaffold_id: usize,
         sequence: String,
         shift: usize,
     ) -> Result<SetScaffoldSequenceOk, SetScaffoldSequenceError>;
-    fn optimize_shift(&mut self);
+    fn optimize_shift(&mut self, scaffold_id: usize);
 }
 
 pub struct SetScaffoldSequenceOk {
@@ -0,0 +1,235 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+//! Writes the design files on a worker thread, so that the render loop is not blocked while large
+//! designs are serialized.
+//!
+//! A design is first written to a temporary file that is then renamed over the destination, so
+//! that the destination is never left half written. When several designs are waiting to be
+//! written at the same path, only the most recent one is written and the others are reported as
+//! superseded.
+
+use crate::controller::SaveDesignError;
+use ensnano_design::Design;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+struct SaveRequest {
+    id: u64,
+    design: Design,
+    path: PathBuf,
+}
+
+/// The outcome of a request made to a [`DesignSaver`].
+pub struct DesignSaveResult {
+    /// The identifier that [`DesignSaver::save`] returned for the request
+    pub id: u64,
+    pub path: PathBuf,
+    pub outcome: SaveOutcome,
+}
+
+pub enum SaveOutcome {
+    /// The design was written, or could not be written
+    Written(Result<(), SaveDesignError>),
+    /// The design was not written because a more recent request to write a design at the same
+    /// path replaced it. The outcome of that write is reported with the identifier `by`.
+    Superseded { by: u64 },
+}
+
+pub struct DesignSaver {
+    requests: mpsc::Sender<SaveRequest>,
+    next_id: u64,
+}
+
+impl DesignSaver {
+    /// Start the worker thread. The results of the requests are sent to the returned receiver.
+    ///
+    /// The worker thread stops when the `DesignSaver` is dropped.
+    pub fn new() -> (Self, mpsc::Receiver<DesignSaveResult>) {
+        let (request_sender, request_receiver) = mpsc::channel();
+        let (result_sender, result_receiver) = mpsc::channel();
+        std::thread::spawn(move || run_worker(request_receiver, result_sender));
+        (
+            Self {
+                requests: request_sender,
+                next_id: 0,
+            },
+            result_receiver,
+        )
+    }
+
+    /// Request `design` to be written at `path` and return the identifier of the request.
+    ///
+    /// If requests made earlier to write a design at the same path are still waiting, they are
+    /// not written and are reported as superseded by this one.
+    pub fn save(&mut self, design: Design, path: PathBuf) -> Result<u64, SaveDesignError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.requests
+            .send(SaveRequest { id, design, path })
+            .map_err(|_| SaveDesignError::worker_stopped())?;
+        Ok(id)
+    }
+}
+
+fn run_worker(requests: mpsc::Receiver<SaveRequest>, results: mpsc::Sender<DesignSaveResult>) {
+    while let Ok(request) = requests.recv() {
+        let mut waiting = vec![request];
+        waiting.extend(requests.try_iter());
+        for (request, superseded) in merge_requests(waiting) {
+            for id in superseded {
+                let dropped = DesignSaveResult {
+                    id,
+                    path: request.path.clone(),
+                    outcome: SaveOutcome::Superseded { by: request.id },
+                };
+                if results.send(dropped).is_err() {
+                    return;
+                }
+            }
+            let result = write_design(&request.design, &request.path);
+            let saved = DesignSaveResult {
+                id: request.id,
+                path: request.path,
+                outcome: SaveOutcome::Written(result),
+            };
+            if results.send(saved).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Keep only the most recent of the waiting requests made for each path, together with the
+/// identifiers of the older requests made for the same path.
+///
+/// The requests are returned in the order in which they were made, so that the last design
+/// written is the last one that was requested.
+fn merge_requests(waiting: Vec<SaveRequest>) -> Vec<(SaveRequest, Vec<u64>)> {
+    let mut merged: Vec<(SaveRequest, Vec<u64>)> = Vec::new();
+    for request in waiting {
+        if let Some(idx) = merged.iter().position(|(r, _)| r.path == request.path) {
+            let (older, mut superseded) = merged.remove(idx);
+            superseded.push(older.id);
+            merged.push((request, superseded));
+        } else {
+            merged.push((request, Vec::new()));
+        }
+    }
+    merged
+}
+
+/// Write `design` to a temporary file and rename it to `path`.
+fn write_design(design: &Design, path: &Path) -> Result<(), SaveDesignError> {
+    let json_content = serde_json::to_string_pretty(design)?;
+    let tmp = tmp_path(path);
+    let result = std::fs::write(&tmp, json_content).and_then(|_| std::fs::rename(&tmp, path));
+    if result.is_err() && tmp.exists() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result.map_err(SaveDesignError::from)
+}
+
+/// The path of the temporary file in which the design stored at `path` is written.
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ensnano_design_saver_{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn design_is_written_and_reported() {
+        let dir = test_dir("written");
+        let path = dir.join("design.ens");
+        let (mut saver, results) = DesignSaver::new();
+        let id = saver.save(Design::new(), path.clone()).unwrap();
+        let saved = results.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(saved.id, id);
+        assert_eq!(saved.path, path);
+        assert!(matches!(saved.outcome, SaveOutcome::Written(Ok(()))));
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(serde_json::from_str::<Design>(&content).is_ok());
+        assert!(!tmp_path(&path).exists());
+    }
+
+    #[test]
+    fn failed_write_is_reported() {
+        let dir = test_dir("failed");
+        let path = dir.join("missing_directory").join("design.ens");
+        let (mut saver, results) = DesignSaver::new();
+        saver.save(Design::new(), path.clone()).unwrap();
+        let saved = results.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(matches!(saved.outcome, SaveOutcome::Written(Err(_))));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn last_request_is_always_written() {
+        let dir = test_dir("latest");
+        let path = dir.join("design.ens");
+        let (mut saver, results) = DesignSaver::new();
+        let mut last_id = 0;
+        for _ in 0..10 {
+            last_id = saver.save(Design::new(), path.clone()).unwrap();
+        }
+        let mut reported = Vec::new();
+        while reported.last() != Some(&last_id) {
+            let saved = results.recv_timeout(Duration::from_secs(10)).unwrap();
+            match saved.outcome {
+                SaveOutcome::Written(result) => assert!(result.is_ok()),
+                SaveOutcome::Superseded { by } => assert!(by > saved.id),
+            }
+            reported.push(saved.id);
+        }
+        // Every request is reported exactly once
+        assert_eq!(reported, (0..=last_id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn only_requests_for_the_same_path_are_merged() {
+        let path_a = PathBuf::from("a.ens");
+        let path_b = PathBuf::from("b.ens");
+        let request = |id: u64, path: &PathBuf| SaveRequest {
+            id,
+            design: Design::new(),
+            path: path.clone(),
+        };
+        let merged = merge_requests(vec![
+            request(0, &path_a),
+            request(1, &path_b),
+            request(2, &path_a),
+        ]);
+        let merged: Vec<_> = merged
+            .into_iter()
+            .map(|(r, superseded)| (r.id, r.path, superseded))
+            .collect();
+        assert_eq!(merged, vec![(1, path_b, vec![]), (2, path_a, vec![0])]);
+    }
+}
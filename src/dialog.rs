@@ -170,7 +170,6 @@ pub fn get_file_to_write<P1: AsRef<Path>, P2: AsRef<Path>>(
     PathInput(rcv)
 }
 
-#[allow(dead_code)]
 pub fn get_dir() -> PathInput {
     let dialog = rfd::AsyncFileDialog::new().pick_folder();
     let (snd, rcv) = mpsc::channel();
@@ -0,0 +1,60 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Loading of the keyboard shortcuts.
+//!
+//! The keymap is read from the `keymap` file of the user configuration directory. It selects one
+//! of the built-in presets and may override the bindings of some actions, e.g.
+//!
+//! ```yaml
+//! preset: cadnano
+//! bindings:
+//!   toggle_split: ["Ctrl+Tab"]
+//!   undo: ["Ctrl+Z", "Back"]
+//! ```
+
+use crate::consts::APP_NAME;
+use ensnano_interactor::keymap::{Keymap, KeymapConfig};
+
+const KEYMAP_CONFIG_NAME: &str = "keymap";
+
+/// Load the keymap of the user configuration, falling back to the default keymap if the
+/// configuration cannot be read.
+///
+/// Return the keymap and a message describing the problems found in the configuration, if any.
+pub fn load_keymap() -> (Keymap, Option<String>) {
+    let config: KeymapConfig = match confy::load(APP_NAME, KEYMAP_CONFIG_NAME) {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!("Could not read keymap configuration: {e}");
+            return (
+                Keymap::default(),
+                Some("Could not read keymap configuration, using default keymap".to_string()),
+            );
+        }
+    };
+    let (keymap, warnings) = Keymap::from_config(&config);
+    if warnings.is_empty() {
+        (keymap, None)
+    } else {
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        log::warn!("Problems in keymap configuration:\n{}", warnings.join("\n"));
+        let message = format!("Problems in keyboard shortcuts: {}", warnings.join("; "));
+        (keymap, Some(message))
+    }
+}
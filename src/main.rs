@@ -74,7 +74,7 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //!  permitted by the current state of the program. For example an error is returned if the user
 //!  try to modify the design durring a simulation.
 //!
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::env;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
@@ -128,7 +128,7 @@ use ensnano_interactor::consts;
 mod multiplexer;
 use ensnano_flatscene as flatscene;
 use ensnano_interactor::{
-    graphics::{ElementType, SplitMode},
+    graphics::{ElementType, FogParameters, SplitMode},
     operation::Operation,
     ActionMode, CheckXoversParameter, Selection, SelectionMode,
 };
@@ -154,13 +154,19 @@ use controller::Controller;
 mod requests;
 pub use requests::Requests;
 
+mod autosave;
+mod design_saver;
 mod dialog;
+mod keymap;
+mod recent_files;
+mod workspace;
 
 use flatscene::FlatScene;
 use gui::{ColorOverlay, Gui, IcedMessages, OverlayType, UiSize};
 use multiplexer::{Multiplexer, Overlay};
 use scene::Scene;
 use utils::{PhySize, TEXTURE_FORMAT};
+use workspace::Workspace;
 
 fn convert_size(size: PhySize) -> Size<f32> {
     Size::new(size.width as f32, size.height as f32)
@@ -241,6 +247,9 @@ fn main() {
     let mut windows_title = String::from("ENSnano");
     window.set_title("ENSnano");
     window.set_min_inner_size(Some(PhySize::new(100, 100)));
+    // Kept alive for the whole session so that the copied text stays available to other
+    // applications.
+    let mut clipboard = iced_winit::Clipboard::connect(&window);
 
     log::info!("scale factor {}", window.scale_factor());
 
@@ -316,6 +325,11 @@ fn main() {
     let requests = Arc::new(Mutex::new(Requests::default()));
     let messages = Arc::new(Mutex::new(IcedMessages::new()));
     let mut scheduler = Scheduler::new();
+    let (keymap, keymap_warnings) = keymap::load_keymap();
+    let keymap = Arc::new(keymap);
+    if let Some(warnings) = keymap_warnings {
+        messages.lock().unwrap().push_message(warnings);
+    }
 
     // Initialize the layout
     let mut multiplexer = Multiplexer::new(
@@ -324,6 +338,7 @@ fn main() {
         device.clone(),
         requests.clone(),
         ui_size,
+        keymap.clone(),
     );
     multiplexer.change_split(SplitMode::Both);
 
@@ -340,6 +355,7 @@ fn main() {
         &mut encoder,
         Default::default(),
         scene::SceneKind::Cartesian,
+        keymap.clone(),
     )));
     let stereographic_scene = Arc::new(Mutex::new(Scene::new(
         device.clone(),
@@ -350,6 +366,7 @@ fn main() {
         &mut encoder,
         Default::default(),
         scene::SceneKind::Stereographic,
+        keymap.clone(),
     )));
 
     queue.submit(Some(encoder.finish()));
@@ -363,6 +380,7 @@ fn main() {
         scene_area,
         requests.clone(),
         Default::default(),
+        keymap,
     )));
     scheduler.add_application(flat_scene.clone(), ElementType::FlatScene);
 
@@ -398,10 +416,9 @@ fn main() {
         .applications
         .insert(ElementType::StereographicScene, stereographic_scene);
 
-    // Add a design to the scene if one was given as a command line arguement
-    if path.is_some() {
-        main_state.push_action(Action::LoadDesign(path))
-    }
+    // Add a design to the scene if one was given as a command line arguement, or restore its
+    // backup if ENSnano was not closed properly.
+    main_state.push_action(Action::StartupLoad(path));
     main_state.update();
     main_state.last_saved_state = main_state.app_state.clone();
 
@@ -413,6 +430,8 @@ fn main() {
     }
 
     let mut first_iteration = true;
+    // Files dropped onto the window since the last iteration, opened together
+    let mut dropped_files: Vec<PathBuf> = Vec::new();
 
     let mut last_gui_state = (
         main_state.app_state.clone(),
@@ -434,6 +453,7 @@ fn main() {
             gui: &mut gui,
             scheduler: &mut scheduler,
             window: &window,
+            clipboard: &mut clipboard,
             resized: false,
         };
 
@@ -449,6 +469,28 @@ fn main() {
                 event: WindowEvent::Focused(false),
                 ..
             } => main_state_view.notify_apps(Notification::WindowFocusLost),
+            Event::WindowEvent {
+                event: WindowEvent::HoveredFile(_),
+                ..
+            } => {
+                main_state_view.multiplexer.set_hovering_file(true);
+                window.request_redraw();
+            }
+            Event::WindowEvent {
+                event: WindowEvent::HoveredFileCancelled,
+                ..
+            } => {
+                main_state_view.multiplexer.set_hovering_file(false);
+                window.request_redraw();
+            }
+            Event::WindowEvent {
+                event: WindowEvent::DroppedFile(path),
+                ..
+            } => {
+                main_state_view.multiplexer.set_hovering_file(false);
+                dropped_files.push(path);
+                window.request_redraw();
+            }
             Event::WindowEvent {
                 event: WindowEvent::ModifiersChanged(modifiers),
                 ..
@@ -489,6 +531,9 @@ fn main() {
             Event::WindowEvent { event, .. } => {
                 //let modifiers = multiplexer.modifiers();
                 if let Some(event) = event.to_static() {
+                    multiplexer.set_nudge_enabled(main_state.app_state.can_nudge_selection());
+                    multiplexer
+                        .set_angle_entry_enabled(main_state.app_state.can_enter_rotation_angle());
                     // Feed the event to the multiplexer
                     let event = multiplexer.event(event, &mut resized, &mut scale_factor_changed);
 
@@ -547,6 +592,12 @@ fn main() {
                 redraw |= main_state.update_cursor(&multiplexer);
                 redraw |= gui.fetch_change(&window, &multiplexer);
 
+                if !dropped_files.is_empty() {
+                    main_state
+                        .pending_actions
+                        .push_back(Action::OpenDroppedFiles(std::mem::take(&mut dropped_files)));
+                }
+
                 // When there is no more event to deal with
                 requests::poll_all(requests.lock().unwrap(), &mut main_state);
 
@@ -557,6 +608,7 @@ fn main() {
                     gui: &mut gui,
                     scheduler: &mut scheduler,
                     window: &window,
+                    clipboard: &mut clipboard,
                     resized: false,
                 };
 
@@ -581,9 +633,10 @@ fn main() {
                     {
                         main_state.messages.lock().unwrap().finish_progess();
                         if let Ok(result) = result {
-                            main_state.apply_operation(DesignOperation::SetScaffoldShift(
-                                result.position,
-                            ));
+                            main_state.apply_operation(DesignOperation::SetScaffoldShift {
+                                scaffold_id: result.scaffold_id,
+                                shift: result.position,
+                            });
                             let msg = format!(
                                 "Scaffold position set to {}\n {}",
                                 result.position, result.score
@@ -597,6 +650,8 @@ fn main() {
                         main_state.app_state.apply_simulation_update(update)
                     } else if let ChanelReaderUpdate::SimulationExpired = update {
                         main_state.update_simulation(SimulationRequest::Stop)
+                    } else if let ChanelReaderUpdate::DesignSaved(saved) = update {
+                        main_state.design_saved(saved)
                     }
                 }
 
@@ -741,6 +796,7 @@ fn main() {
                     staging_belt.finish();
                     queue.submit(Some(encoder.finish()));
                     frame.present();
+                    log::trace!("frame drawn in {:?}", now.elapsed());
 
                     // And update the mouse cursor
                     main_state.gui_cursor =
@@ -981,16 +1037,47 @@ pub(crate) struct MainState {
     wants_fit: bool,
     last_backup_date: Instant,
     last_backed_up_state: AppState,
+    autosaver: autosave::Autosaver,
+    design_saver: design_saver::DesignSaver,
+    /// The requests made to `design_saver` that have not been reported yet
+    pending_saves: BTreeMap<u64, PendingSave>,
+    /// The results of the saves, by request identifier, until they are read by the controller
+    save_results: BTreeMap<u64, Result<(), SaveDesignError>>,
+    /// The requests that were not written because a more recent request replaced them, with the
+    /// identifier of that request
+    superseded_saves: BTreeMap<u64, u64>,
     simulation_cursor: Option<CursorIcon>,
     applications_cursor: Option<CursorIcon>,
     gui_cursor: CursorIcon,
     cursor: CursorIcon,
+    /// The last saved camera that was selected, used to cycle through the saved cameras.
+    current_camera: Option<ensnano_design::CameraId>,
+    /// The designs that were recently opened or saved, the most recent one first
+    recent_files: Vec<gui::RecentFile>,
+}
+
+/// The entries of the list of recent designs shown in the GUI
+fn recent_file_entries(paths: Vec<PathBuf>) -> Vec<gui::RecentFile> {
+    paths
+        .into_iter()
+        .map(|path| gui::RecentFile {
+            exists: path.exists(),
+            path,
+        })
+        .collect()
 }
 
 struct MainStateConstructor {
     messages: Arc<Mutex<IcedMessages<AppState>>>,
 }
 
+/// A design that is being written by the design saver.
+struct PendingSave {
+    /// The state whose design is being written
+    state: AppState,
+    was_unnamed: bool,
+}
+
 use controller::SaveDesignError;
 impl MainState {
     fn new(constructor: MainStateConstructor) -> Self {
@@ -1001,12 +1088,15 @@ impl MainState {
                 Default::default()
             }
         };
+        let (design_saver, save_results) = design_saver::DesignSaver::new();
+        let mut chanel_reader = ChanelReader::default();
+        chanel_reader.attach_design_save_chanel(save_results);
         Self {
             app_state: app_state.clone(),
             pending_actions: VecDeque::new(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
-            chanel_reader: Default::default(),
+            chanel_reader,
             messages: constructor.messages,
             applications: Default::default(),
             focussed_element: None,
@@ -1015,10 +1105,17 @@ impl MainState {
             wants_fit: false,
             last_backup_date: Instant::now(),
             last_backed_up_state: app_state,
+            autosaver: Default::default(),
+            design_saver,
+            pending_saves: Default::default(),
+            save_results: Default::default(),
+            superseded_saves: Default::default(),
             simulation_cursor: None,
             applications_cursor: None,
             gui_cursor: Default::default(),
             cursor: Default::default(),
+            current_camera: None,
+            recent_files: recent_file_entries(recent_files::read()),
         }
     }
 
@@ -1091,7 +1188,16 @@ impl MainState {
                 .unwrap()
                 .on_notify(Notification::NewStereographicCamera(camera_ptr));
         }
-        self.app_state.update()
+        self.app_state.update();
+        if self
+            .app_state
+            .design_changes_are_outdated(&self.last_saved_state)
+        {
+            self.app_state = self
+                .app_state
+                .clone()
+                .with_updated_design_changes(&self.last_saved_state);
+        }
     }
 
     fn update_candidates(&mut self, candidates: Vec<Selection>) {
@@ -1105,7 +1211,11 @@ impl MainState {
             .get(&ElementType::Scene)
             .and_then(|app| app.lock().unwrap().get_current_selection_pivot());
         if let Some(pivot) = self.app_state.get_current_group_pivot().or(scene_pivot) {
-            self.apply_operation(DesignOperation::SetGroupPivot { group_id, pivot })
+            self.apply_operation(DesignOperation::SetGroupPivot {
+                group_id,
+                pivot,
+                replace: false,
+            })
         }
     }
 
@@ -1139,6 +1249,27 @@ impl MainState {
             );
             self.apply_operation(operation);
         } else {
+            if let DesignOperation::ChangeSequence { .. } = operation {
+                let nb_mismatches = match &result {
+                    Err(ErrOperation::SequenceMismatches(mismatches)) => {
+                        Some(Some(mismatches.len()))
+                    }
+                    Ok(_) => Some(None),
+                    Err(_) => None,
+                };
+                if let Some(nb_mismatches) = nb_mismatches {
+                    self.messages
+                        .lock()
+                        .unwrap()
+                        .push_sequence_check(nb_mismatches);
+                }
+            }
+            if let Err(ErrOperation::BezierPlaneIsUsed { plane_id, vertices }) = &result {
+                self.messages
+                    .lock()
+                    .unwrap()
+                    .push_message(used_bezier_plane_message(*plane_id, vertices));
+            }
             self.apply_operation_result(result);
         }
     }
@@ -1226,39 +1357,77 @@ impl MainState {
     }
 
     fn undo(&mut self) {
-        if let Some(mut transition) = self.undo_stack.pop() {
-            transition.state.prepare_for_replacement(&self.app_state);
-            let mut redo_state = std::mem::replace(&mut self.app_state, transition.state);
-            redo_state = redo_state.notified(app_state::InteractorNotification::FinishOperation);
-            self.set_camera_3d(transition.camera_3d.clone());
-            self.messages
-                .lock()
-                .unwrap()
-                .push_message(format!("UNDO: {}", transition.label.as_ref()));
-            if redo_state.is_in_stable_state() {
-                self.redo_stack.push(AppStateTransition {
-                    state: redo_state,
-                    label: transition.label,
-                    camera_3d: transition.camera_3d,
+        self.jump_in_history(-1)
+    }
+
+    fn redo(&mut self) {
+        self.jump_in_history(1)
+    }
+
+    /// Undo `-steps` operations if `steps` is negative, or redo `steps` operations otherwise.
+    ///
+    /// The intermediate states are not applied, but they are moved to the other stack so that
+    /// the history stays the same as if the operations had been undone or redone one by one.
+    fn jump_in_history(&mut self, steps: isize) {
+        let undo = steps < 0;
+        let nb_steps = steps.unsigned_abs();
+        let (source, target) = if undo {
+            (&mut self.undo_stack, &mut self.redo_stack)
+        } else {
+            (&mut self.redo_stack, &mut self.undo_stack)
+        };
+        if nb_steps == 0 || nb_steps > source.len() {
+            return;
+        }
+        let mut state = if undo {
+            self.app_state
+                .clone()
+                .notified(app_state::InteractorNotification::FinishOperation)
+        } else {
+            self.app_state.clone()
+        };
+        let mut last_transition = None;
+        for i in 0..nb_steps {
+            // Unwrap: there are at least `nb_steps` transitions in `source`
+            let transition = source.pop().unwrap();
+            let replaced_state = std::mem::replace(&mut state, transition.state);
+            // When undoing, the current state may be a transitory state that cannot be restored
+            if !undo || i > 0 || replaced_state.is_in_stable_state() {
+                target.push(AppStateTransition {
+                    state: replaced_state,
+                    label: transition.label.clone(),
+                    camera_3d: transition.camera_3d.clone(),
                 });
             }
+            last_transition = Some((transition.label, transition.camera_3d));
+        }
+        state.prepare_for_replacement(&self.app_state);
+        self.app_state = state;
+        if let Some((label, camera_3d)) = last_transition {
+            self.set_camera_3d(camera_3d);
+            let prefix = if undo { "UNDO" } else { "REDO" };
+            let message = if nb_steps == 1 {
+                format!("{prefix}: {}", label.as_ref())
+            } else {
+                format!("{prefix} {nb_steps} operations: {}", label.as_ref())
+            };
+            self.messages.lock().unwrap().push_message(message);
         }
     }
 
-    fn redo(&mut self) {
-        if let Some(mut transition) = self.redo_stack.pop() {
-            transition.state.prepare_for_replacement(&self.app_state);
-            let undo_state = std::mem::replace(&mut self.app_state, transition.state);
-            self.set_camera_3d(transition.camera_3d.clone());
-            self.messages
-                .lock()
-                .unwrap()
-                .push_message(format!("REDO: {}", transition.label.as_ref()));
-            self.undo_stack.push(AppStateTransition {
-                state: undo_state,
-                camera_3d: transition.camera_3d,
-                label: transition.label,
-            });
+    fn undo_history(&self) -> gui::UndoHistory {
+        gui::UndoHistory {
+            undo_labels: self
+                .undo_stack
+                .iter()
+                .map(|t| t.label.as_ref().to_string())
+                .collect(),
+            redo_labels: self
+                .redo_stack
+                .iter()
+                .rev()
+                .map(|t| t.label.as_ref().to_string())
+                .collect(),
         }
     }
 
@@ -1294,9 +1463,9 @@ impl MainState {
         self.apply_operation_result(result);
     }
 
-    fn optimize_shift(&mut self) {
+    fn optimize_shift(&mut self, scaffold_id: usize) {
         let reader = &mut self.chanel_reader;
-        let result = self.app_state.optimize_shift(reader);
+        let result = self.app_state.optimize_shift(reader, scaffold_id);
         self.apply_operation_result(result);
     }
 
@@ -1359,7 +1528,7 @@ impl MainState {
         }
     }
 
-    fn save_design(&mut self, path: &PathBuf) -> Result<(), SaveDesignError> {
+    fn saving_information(&self) -> ensnano_design::SavingInformation {
         let camera = self
             .applications
             .get(&ElementType::Scene)
@@ -1371,48 +1540,90 @@ impl MainState {
                 orientation: camera.0.orientation,
                 pivot_position: camera.0.pivot_position,
             });
-        let save_info = ensnano_design::SavingInformation { camera };
-        self.app_state.save_design(path, save_info)?;
+        let fog = self
+            .applications
+            .get(&ElementType::Scene)
+            .and_then(|s| s.lock().unwrap().get_fog_parameters())
+            .map(|fog| fog.to_settings());
+        ensnano_design::SavingInformation { camera, fog }
+    }
+
+    /// Start writing the current design at `path` and return the identifier of the request. The
+    /// design is considered saved once the design saver reports that it was written.
+    fn save_design(&mut self, path: &PathBuf) -> Result<u64, SaveDesignError> {
+        let design = self.app_state.design_to_save(self.saving_information());
+        let id = self.design_saver.save(design, path.clone())?;
+        self.pending_saves.insert(
+            id,
+            PendingSave {
+                state: self.app_state.clone(),
+                was_unnamed: self.app_state.path_to_current_design().is_none(),
+            },
+        );
+        Ok(id)
+    }
+
+    fn design_saved(&mut self, saved: design_saver::DesignSaveResult) {
+        let pending = self.pending_saves.remove(&saved.id);
+        let result = match saved.outcome {
+            design_saver::SaveOutcome::Written(result) => result,
+            design_saver::SaveOutcome::Superseded { by } => {
+                // The design of the request `by` is more recent, its outcome will be reported
+                // with its own identifier.
+                log::info!("Save {} superseded by save {}", saved.id, by);
+                self.superseded_saves.insert(saved.id, by);
+                return;
+            }
+        };
+        match (&result, pending) {
+            (Ok(()), Some(pending)) => {
+                self.recent_files = recent_file_entries(recent_files::add(&saved.path));
+                self.app_state.set_path_to_current_design(saved.path);
+                if pending.was_unnamed {
+                    // The backups of the unnamed design are not needed anymore
+                    autosave::remove_backups(None);
+                }
 
-        if self.app_state.is_in_stable_state() {
-            self.last_saved_state = self.app_state.clone();
+                // Saving the design clears the highlighted changes
+                self.modify_state(|app| app.with_design_changes_shown(false), None);
+                if pending.state.is_in_stable_state() {
+                    self.last_saved_state = pending.state;
+                }
+                self.update_current_file_name();
+            }
+            (Ok(()), None) => (),
+            (Err(e), _) => log::error!("Could not save design: {:?}", e),
         }
-        self.update_current_file_name();
-        Ok(())
+        // The requests superseded by this one are reported before it, and share its result
+        let superseded: Vec<u64> = self
+            .superseded_saves
+            .iter()
+            .filter(|(_, by)| **by == saved.id)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in superseded {
+            self.superseded_saves.remove(&id);
+            self.save_results.insert(id, result.clone());
+        }
+        self.save_results.insert(saved.id, result);
+    }
+
+    fn is_saving_design(&self) -> bool {
+        !self.pending_saves.is_empty()
     }
 
     fn save_backup(&mut self) -> Result<(), SaveDesignError> {
-        let camera = self
-            .applications
-            .get(&ElementType::Scene)
-            .and_then(|s| s.lock().unwrap().get_camera())
-            .map(|camera| Camera {
-                id: Default::default(),
-                name: String::from("Saved Camera"),
-                position: camera.0.position,
-                orientation: camera.0.orientation,
-                pivot_position: camera.0.pivot_position,
-            });
-        let save_info = ensnano_design::SavingInformation { camera };
-        let path = if let Some(mut path) = self.app_state.path_to_current_design().cloned() {
-            path.set_extension(crate::consts::ENS_BACKUP_EXTENSION);
-            path
-        } else {
-            let mut ret = dirs::document_dir()
-                .or_else(dirs::home_dir)
-                .ok_or_else(|| {
+        if self.app_state.is_in_stable_state() {
+            let design = self.app_state.design_to_save(self.saving_information());
+            let design_path = self.app_state.path_to_current_design().cloned();
+            self.autosaver
+                .save(design, design_path.as_deref())
+                .map_err(|e| {
                     self.last_backup_date =
                         Instant::now() + Duration::from_secs(crate::consts::SEC_PER_YEAR);
-                    SaveDesignError::cannot_open_default_dir()
+                    e
                 })?;
-            ret.push(crate::consts::ENS_UNNAMED_FILE_NAME);
-            ret.set_extension(crate::consts::ENS_BACKUP_EXTENSION);
-            ret
-        };
-        if self.app_state.is_in_stable_state() {
-            self.app_state.save_design(&path, save_info)?;
             self.last_backed_up_state = self.app_state.clone();
-            println!("Saved backup to {}", path.to_string_lossy());
         } else {
             // Do nothing. We do not want to save backup in transitory states.
         }
@@ -1478,6 +1689,38 @@ impl MainState {
         self.modify_state(|s| s.with_show_h_bonds(show), None)
     }
 
+    fn set_h_bond_filter(&mut self, filter: ensnano_interactor::graphics::HBondFilter) {
+        self.modify_state(|s| s.with_h_bond_filter(filter), None)
+    }
+
+    fn set_show_2d_minimap(&mut self, show: bool) {
+        self.modify_state(|s| s.with_show_2d_minimap(show), None)
+    }
+
+    fn set_show_anchors(&mut self, show: bool) {
+        self.modify_state(|s| s.with_show_anchors(show), None)
+    }
+
+    fn set_local_phantoms(&mut self, local_phantoms: bool) {
+        self.modify_state(|s| s.with_local_phantoms(local_phantoms), None)
+    }
+
+    fn set_show_nucleotide_ruler(&mut self, show: bool) {
+        self.modify_state(|s| s.with_show_nucleotide_ruler(show), None)
+    }
+
+    fn set_full_detail(&mut self, full_detail: bool) {
+        self.modify_state(|s| s.with_full_detail(full_detail), None)
+    }
+
+    fn set_strand_coloring(&mut self, coloring: ensnano_interactor::graphics::StrandColoring) {
+        self.modify_state(|s| s.with_strand_coloring(coloring), None)
+    }
+
+    fn set_strand_colormap(&mut self, colormap: ensnano_interactor::graphics::Colormap) {
+        self.modify_state(|s| s.with_strand_colormap(colormap), None)
+    }
+
     fn set_show_bezier_paths(&mut self, show: bool) {
         self.modify_state(|s| s.with_show_bezier_paths(show), None)
     }
@@ -1507,6 +1750,7 @@ impl MainState {
                     desc: ensnano_design::BezierPlaneDescriptor {
                         position,
                         orientation,
+                        ..Default::default()
                     },
                 })
             }
@@ -1553,10 +1797,52 @@ impl MainState {
         self.modify_state(|s| s.with_inverted_y_scroll(inverted), None)
     }
 
+    fn set_camera_animation_duration(&mut self, duration: f32) {
+        self.modify_state(|s| s.with_camera_animation_duration(duration), None)
+    }
+
+    fn set_autosave_interval(&mut self, interval: f32) {
+        self.modify_state(|s| s.with_autosave_interval(interval), None)
+    }
+
+    fn set_undo_history_length(&mut self, length: usize) {
+        self.modify_state(|s| s.with_undo_history_length(length), None)
+    }
+
+    fn set_remember_workspace(&mut self, remember: bool) {
+        self.modify_state(|s| s.with_remember_workspace(remember), None)
+    }
+
+    fn set_cameras_export_progress(&mut self, progress: Option<f32>) {
+        let mut messages = self.messages.lock().unwrap();
+        if let Some(progress) = progress {
+            messages.push_progress("Exporting cameras: ".to_string(), progress)
+        } else {
+            messages.finish_progess()
+        }
+    }
+
+    fn set_measurement_info(&mut self, info: Option<String>) {
+        let mut messages = self.messages.lock().unwrap();
+        if let Some(info) = info {
+            messages.push_message(info)
+        } else {
+            messages.clear_message()
+        }
+    }
+
+    fn set_pointed_grid_position(&mut self, position: Option<ensnano_design::grid::GridPosition>) {
+        self.messages
+            .lock()
+            .unwrap()
+            .push_pointed_grid_position(position)
+    }
+
     fn gui_state(&self, multiplexer: &Multiplexer) -> gui::MainState {
         gui::MainState {
             can_undo: !self.undo_stack.is_empty(),
             can_redo: !self.redo_stack.is_empty(),
+            history: self.undo_history(),
             need_save: self.need_save(),
             can_reload: self.get_current_file_name().is_some(),
             can_split2d: multiplexer.is_showing(&ElementType::FlatScene),
@@ -1567,6 +1853,13 @@ impl MainState {
                 .unwrap_or(false),
             can_toggle_2d: multiplexer.is_showing(&ElementType::FlatScene)
                 || multiplexer.is_showing(&ElementType::StereographicScene),
+            can_split3d: multiplexer.is_showing(&ElementType::Scene),
+            splited_3d: self
+                .applications
+                .get(&ElementType::Scene)
+                .map(|app| app.lock().unwrap().is_splited())
+                .unwrap_or(false),
+            recent_files: self.recent_files.clone(),
         }
     }
 
@@ -1601,10 +1894,128 @@ struct MainStateView<'a> {
     scheduler: &'a mut Scheduler,
     gui: &'a mut Gui<Requests, AppState>,
     window: &'a Window,
+    clipboard: &'a mut iced_winit::Clipboard,
     resized: bool,
 }
 
 use controller::{LoadDesignError, MainState as MainStateInteface, StaplesDownloader};
+impl<'a> MainStateView<'a> {
+    /// Replace the current state by a state in which a design was just loaded.
+    fn set_loaded_state(&mut self, state: AppState) {
+        self.notify_apps(Notification::ClearDesigns);
+        self.main_state.clear_app_state(state);
+        if let Some((position, orientation)) = self
+            .main_state
+            .app_state
+            .get_design_reader()
+            .get_favourite_camera()
+        {
+            self.notify_apps(Notification::TeleportCamera(
+                ensnano_interactor::application::Camera3D {
+                    position,
+                    orientation,
+                    pivot_position: None,
+                },
+            ));
+        } else {
+            self.main_state.wants_fit = true;
+        }
+        let fog = FogParameters::from_settings(
+            &self
+                .main_state
+                .app_state
+                .get_design_reader()
+                .get_fog_settings(),
+        );
+        self.main_state
+            .messages
+            .lock()
+            .unwrap()
+            .push_fog_parameters(fog.clone());
+        self.notify_apps(Notification::Fog(fog));
+        self.main_state.update_current_file_name();
+    }
+
+    /// The current layout of the application, if the current design is stored in a file.
+    fn current_workspace(&self) -> Option<Workspace> {
+        let app_state = &self.main_state.app_state;
+        let design_path = app_state.path_to_current_design()?.clone();
+        let flatscene = self
+            .main_state
+            .applications
+            .get(&ElementType::FlatScene)
+            .map(|app| app.lock().unwrap());
+        Some(Workspace {
+            version: workspace::WORKSPACE_VERSION,
+            design_path,
+            split_mode: self.multiplexer.get_split_mode(),
+            stereographic_2d: self.multiplexer.is_showing_stereographic_2d(),
+            split_2d: flatscene
+                .as_ref()
+                .map(|app| app.is_splited())
+                .unwrap_or(false),
+            cameras_2d: flatscene.as_ref().and_then(|app| app.get_cameras_2d()),
+            camera_3d: self.main_state.get_camera_3d(),
+            left_panel_proportion: self.multiplexer.get_left_panel_proportion(),
+            selection_mode: gui::AppState::get_selection_mode(app_state),
+            action_mode: gui::AppState::get_action_mode(app_state),
+        })
+    }
+
+    fn save_workspace(&self) {
+        if !self.main_state.app_state.remembers_workspace() {
+            return;
+        }
+        if let Some(workspace) = self.current_workspace() {
+            if let Err(e) = workspace.save() {
+                log::error!("Could not save workspace: {e}");
+            }
+        }
+    }
+
+    /// Restore the layout of the application that was saved with the current design, if any.
+    fn restore_workspace(&mut self) {
+        if !self.main_state.app_state.remembers_workspace() {
+            return;
+        }
+        let workspace = if let Some(workspace) = self
+            .main_state
+            .app_state
+            .path_to_current_design()
+            .and_then(|path| Workspace::load(path))
+        {
+            workspace
+        } else {
+            return;
+        };
+
+        if let Some(proportion) = workspace.left_panel_proportion {
+            self.multiplexer.set_left_panel_proportion(proportion);
+        }
+        self.toggle_split_mode(workspace.split_mode);
+        if workspace.stereographic_2d != self.multiplexer.is_showing_stereographic_2d() {
+            self.toggle_2d();
+        }
+        let splited_2d = self
+            .main_state
+            .applications
+            .get(&ElementType::FlatScene)
+            .map(|app| app.lock().unwrap().is_splited())
+            .unwrap_or(false);
+        if workspace.split_2d != splited_2d {
+            self.notify_apps(Notification::Split2d);
+        }
+        if let Some(cameras) = workspace.cameras_2d {
+            self.notify_apps(Notification::TeleportCameras2D(cameras));
+        }
+        self.notify_apps(Notification::TeleportCamera(workspace.camera_3d));
+        self.main_state.wants_fit = false;
+        self.main_state
+            .change_selection_mode(workspace.selection_mode);
+        self.main_state.change_action_mode(workspace.action_mode);
+    }
+}
+
 impl<'a> MainStateInteface for MainStateView<'a> {
     fn pop_action(&mut self) -> Option<Action> {
         if !self.main_state.pending_actions.is_empty() {
@@ -1614,6 +2025,7 @@ impl<'a> MainStateInteface for MainStateView<'a> {
     }
 
     fn check_backup(&mut self) {
+        self.main_state.autosaver.poll();
         if !self
             .main_state
             .last_backed_up_state
@@ -1628,11 +2040,23 @@ impl<'a> MainStateInteface for MainStateView<'a> {
     }
 
     fn need_backup(&self) -> bool {
+        // Backups are not written during simulations to avoid saving transient states.
         Instant::now() - self.main_state.last_backup_date
-            > Duration::from_secs(crate::consts::SEC_BETWEEN_BACKUPS)
+            > self.main_state.app_state.get_autosave_interval()
+            && !self.main_state.autosaver.is_busy()
+            && !self.main_state.app_state.get_simulation_state().is_runing()
     }
 
     fn exit_control_flow(&mut self) {
+        self.save_workspace();
+        // The application is closed normally so there is nothing to recover at the next startup.
+        self.main_state.autosaver.join();
+        autosave::remove_backups(
+            self.main_state
+                .app_state
+                .path_to_current_design()
+                .map(PathBuf::as_path),
+        );
         *self.control_flow = ControlFlow::Exit
     }
 
@@ -1641,33 +2065,41 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.main_state.new_design()
     }
 
-    fn export(&mut self, path: &PathBuf, export_type: ExportType) -> ExportResult {
-        let ret = self.main_state.app_state.export(path, export_type);
+    fn export(
+        &mut self,
+        path: &PathBuf,
+        export_type: ExportType,
+        selection_only: bool,
+    ) -> ExportResult {
+        let ret = self
+            .main_state
+            .app_state
+            .export(path, export_type, selection_only);
         self.set_exporting(false);
         ret
     }
 
     fn load_design(&mut self, path: PathBuf) -> Result<(), LoadDesignError> {
-        let state = AppState::import_design(path)?;
-        self.notify_apps(Notification::ClearDesigns);
-        self.main_state.clear_app_state(state);
-        if let Some((position, orientation)) = self
-            .main_state
-            .app_state
-            .get_design_reader()
-            .get_favourite_camera()
-        {
-            self.notify_apps(Notification::TeleportCamera(
-                ensnano_interactor::application::Camera3D {
-                    position,
-                    orientation,
-                    pivot_position: None,
-                },
-            ));
-        } else {
-            self.main_state.wants_fit = true;
-        }
-        self.main_state.update_current_file_name();
+        let state = AppState::import_design(path.clone())?;
+        self.main_state.recent_files = recent_file_entries(recent_files::add(&path));
+        self.set_loaded_state(state);
+        self.restore_workspace();
+        Ok(())
+    }
+
+    fn forget_recent_file(&mut self, path: &Path) {
+        self.main_state.recent_files = recent_file_entries(recent_files::remove(path));
+    }
+
+    fn restore_backup(
+        &mut self,
+        backup: PathBuf,
+        design_path: Option<PathBuf>,
+    ) -> Result<(), LoadDesignError> {
+        let state = AppState::import_backup(&backup, design_path)?;
+        self.set_loaded_state(state);
+        // The restored design has not been written to the design file yet.
+        self.main_state.last_saved_state = Default::default();
         Ok(())
     }
 
@@ -1691,14 +2123,32 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.main_state.redo();
     }
 
+    fn jump_in_history(&mut self, steps: isize) {
+        self.main_state.jump_in_history(steps);
+    }
+
     fn get_staple_downloader(&self) -> Box<dyn StaplesDownloader> {
         Box::new(self.main_state.app_state.get_design_reader())
     }
 
-    fn save_design(&mut self, path: &PathBuf) -> Result<(), SaveDesignError> {
-        self.main_state.save_design(path)?;
+    fn save_design(&mut self, path: &PathBuf) -> Result<u64, SaveDesignError> {
+        let id = self.main_state.save_design(path)?;
         self.main_state.last_backup_date = Instant::now();
-        Ok(())
+        Ok(id)
+    }
+
+    fn take_design_save_result(&mut self, id: u64) -> Option<Result<(), SaveDesignError>> {
+        let result = self.main_state.save_results.remove(&id);
+        if let Some(Ok(())) = result {
+            // The workspace is written next to the design, whose path is only known once the
+            // design has been written
+            self.save_workspace();
+        }
+        result
+    }
+
+    fn is_saving_design(&self) -> bool {
+        self.main_state.is_saving_design()
     }
 
     fn save_backup(&mut self) -> Result<(), SaveDesignError> {
@@ -1769,6 +2219,40 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.main_state.request_copy()
     }
 
+    fn copy_sequences(&mut self) {
+        use iced_native::clipboard::Clipboard;
+        let message = if let Some(sequences) = self.main_state.app_state.selection_sequences() {
+            self.clipboard.write(sequences.text);
+            if sequences.nb_sequences > 1 {
+                format!("Copied {} sequences", sequences.nb_sequences)
+            } else {
+                String::from("Copied 1 sequence")
+            }
+        } else {
+            String::from("The selection contains no strand with a sequence")
+        };
+        self.main_state
+            .messages
+            .lock()
+            .unwrap()
+            .push_message(message);
+    }
+
+    fn copy_design_summary(&mut self) {
+        use iced_native::clipboard::Clipboard;
+        let summary = self
+            .main_state
+            .app_state
+            .get_design_reader()
+            .get_design_summary();
+        self.clipboard.write(summary.to_text());
+        self.main_state
+            .messages
+            .lock()
+            .unwrap()
+            .push_message(String::from("Copied the summary of the design"));
+    }
+
     fn init_paste(&mut self) {
         self.main_state
             .apply_copy_operation(CopyOperation::PositionPastingPoint(None));
@@ -1824,18 +2308,36 @@ impl<'a> MainStateInteface for MainStateView<'a> {
     }
 
     fn scaffold_to_selection(&mut self) {
-        let scaffold_id = self
+        let scaffolds: Vec<Selection> = self
             .main_state
             .get_app_state()
             .get_design_reader()
-            .get_scaffold_info()
-            .map(|info| info.id);
-        if let Some(s_id) = scaffold_id {
-            self.main_state
-                .update_selection(vec![Selection::Strand(0, s_id as u32)], None)
+            .get_scaffolds_info()
+            .iter()
+            .map(|info| Selection::Strand(0, info.id as u32))
+            .collect();
+        if !scaffolds.is_empty() {
+            self.main_state.update_selection(scaffolds, None)
         }
     }
 
+    fn set_scaffold_role(&mut self, s_id: usize, is_scaffold: bool) {
+        let mut scaffold_ids: Vec<usize> = self
+            .main_state
+            .get_app_state()
+            .get_design_reader()
+            .get_scaffolds_info()
+            .iter()
+            .map(|info| info.id)
+            .filter(|id| *id != s_id)
+            .collect();
+        if is_scaffold {
+            scaffold_ids.push(s_id);
+        }
+        self.main_state
+            .apply_operation(DesignOperation::SetScaffoldIds(scaffold_ids))
+    }
+
     fn start_helix_simulation(&mut self, parameters: RigidBodyConstants) {
         self.main_state.start_helix_simulation(parameters);
     }
@@ -1868,6 +2370,22 @@ impl<'a> MainStateInteface for MainStateView<'a> {
             .apply_operation(DesignOperation::FlipAnchors { nucls });
     }
 
+    fn anchor_selected_strands(&mut self, ends_only: bool) {
+        let selection = self.get_selection();
+        let strands =
+            ensnano_interactor::extract_strands_from_selection(selection.as_ref().as_ref());
+        let helices = ensnano_interactor::extract_helices(selection.as_ref().as_ref());
+        let nucls = self
+            .main_state
+            .app_state
+            .get_design_reader()
+            .get_nucls_of_strands_and_helices(&strands, &helices, ends_only);
+        if !nucls.is_empty() {
+            self.main_state
+                .apply_operation(DesignOperation::FlipAnchors { nucls });
+        }
+    }
+
     fn set_visibility_sieve(&mut self, compl: bool) {
         let selection = self.get_selection().as_ref().as_ref().to_vec();
         self.main_state.set_visibility_sieve(selection, compl);
@@ -1911,12 +2429,28 @@ impl<'a> MainStateInteface for MainStateView<'a> {
 
     fn set_current_group_pivot(&mut self, pivot: ensnano_design::group_attributes::GroupPivot) {
         if let Some(group_id) = self.main_state.app_state.get_current_group_id() {
-            self.apply_operation(DesignOperation::SetGroupPivot { group_id, pivot })
+            self.apply_operation(DesignOperation::SetGroupPivot {
+                group_id,
+                pivot,
+                replace: false,
+            })
         } else {
             self.main_state.app_state.set_current_group_pivot(pivot);
         }
     }
 
+    fn place_group_pivot(&mut self, pivot: ensnano_design::group_attributes::GroupPivot) {
+        if let Some(group_id) = self.main_state.app_state.get_current_group_id() {
+            self.apply_operation(DesignOperation::SetGroupPivot {
+                group_id,
+                pivot,
+                replace: true,
+            })
+        } else {
+            self.main_state.app_state.replace_current_group_pivot(pivot);
+        }
+    }
+
     fn translate_group_pivot(&mut self, translation: Vec3) {
         use ensnano_interactor::{DesignTranslation, IsometryTarget};
         if let Some(group_id) = self.main_state.app_state.get_current_group_id() {
@@ -1965,12 +2499,47 @@ impl<'a> MainStateInteface for MainStateView<'a> {
     fn select_camera(&mut self, camera_id: ensnano_design::CameraId) {
         let reader = self.main_state.app_state.get_design_reader();
         if let Some(camera) = reader.get_camera_with_id(camera_id) {
-            self.notify_apps(Notification::TeleportCamera(camera))
+            self.main_state.current_camera = Some(camera_id);
+            self.notify_apps(Notification::SmoothTeleportCamera(camera))
         } else {
             log::error!("Could not get camera {:?}", camera_id)
         }
     }
 
+    fn select_next_camera(&mut self, forward: bool) {
+        let reader = self.main_state.app_state.get_design_reader();
+        let ids = reader.get_camera_ids();
+        let current_idx = self
+            .main_state
+            .current_camera
+            .and_then(|id| ids.iter().position(|x| *x == id));
+        let next_idx = match current_idx {
+            Some(idx) if forward => (idx + 1) % ids.len(),
+            Some(idx) => (idx + ids.len() - 1) % ids.len(),
+            None if forward => 0,
+            None => ids.len().saturating_sub(1),
+        };
+        if let Some(camera_id) = ids.get(next_idx).cloned() {
+            if let Some(camera) = reader.get_camera_with_id(camera_id) {
+                self.main_state.current_camera = Some(camera_id);
+                self.notify_apps(Notification::SmoothTeleportCamera(camera))
+            }
+        } else {
+            log::info!("Design has no saved camera");
+        }
+    }
+
+    fn go_to_nth_camera(&mut self, n_camera: u32) {
+        let reader = self.main_state.app_state.get_design_reader();
+        let camera_id = reader.get_camera_ids().get(n_camera as usize).cloned();
+        if let Some(camera) = camera_id.and_then(|id| reader.get_camera_with_id(id)) {
+            self.main_state.current_camera = camera_id;
+            self.notify_apps(Notification::SmoothTeleportCamera(camera))
+        } else {
+            log::error!("Design has less than {} cameras", n_camera + 1);
+        }
+    }
+
     fn update_camera(&mut self, camera_id: ensnano_design::CameraId) {
         if let Some(camera) = self
             .main_state
@@ -1989,9 +2558,69 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         }
     }
 
+    fn finish_hyperboloid_update(&mut self, grid_id: GridId) {
+        self.finish_operation();
+        let strained_xovers = self
+            .main_state
+            .app_state
+            .get_design_reader()
+            .get_strained_xovers_leaving_grid(grid_id);
+        if !strained_xovers.is_empty() {
+            self.main_state
+                .messages
+                .lock()
+                .unwrap()
+                .push_message(format!(
+                    "{} strained cross-overs leave the edited nanotube",
+                    strained_xovers.len()
+                ));
+        }
+    }
+
+    fn save_named_selection(&mut self, name: String) {
+        use ensnano_interactor::SelectionConversion;
+        let selection = self.get_selection();
+        let elements: Vec<_> = selection
+            .as_ref()
+            .as_ref()
+            .iter()
+            .filter_map(|s| ensnano_design::elements::DnaElementKey::from_selection(s, 0))
+            .collect();
+        if elements.is_empty() {
+            self.main_state
+                .messages
+                .lock()
+                .unwrap()
+                .push_message("Cannot save an empty selection".to_string());
+        } else {
+            self.main_state
+                .apply_operation(DesignOperation::CreateNamedSelection { name, elements })
+        }
+    }
+
+    fn recall_named_selection(&mut self, idx: usize) {
+        let reader = self.main_state.app_state.get_design_reader();
+        if let Some((selection, nb_dropped)) = reader.get_named_selection(idx) {
+            self.main_state.update_selection(selection, None);
+            if nb_dropped > 0 {
+                self.main_state
+                    .messages
+                    .lock()
+                    .unwrap()
+                    .push_message(format!(
+                        "{nb_dropped} elements of the saved selection do not exist anymore"
+                    ));
+            }
+        } else {
+            log::error!("Could not get named selection {idx}")
+        }
+    }
+
     fn select_favorite_camera(&mut self, n_camera: u32) {
         let reader = self.main_state.app_state.get_design_reader();
         if let Some(camera) = reader.get_nth_camera(n_camera) {
+            self.main_state.current_camera =
+                reader.get_camera_ids().get(n_camera as usize).cloned();
             self.notify_apps(Notification::TeleportCamera(camera))
         } else {
             log::error!("Design has less than {} cameras", n_camera + 1);
@@ -2011,6 +2640,27 @@ impl<'a> MainStateInteface for MainStateView<'a> {
         self.apply_operation(DesignOperation::MakeSeveralXovers { xovers, doubled })
     }
 
+    fn place_xovers_between_selected_helices(
+        &mut self,
+        parameters: ensnano_design::XoverPlacementParameters,
+    ) {
+        let helices =
+            ensnano_interactor::extract_helices(self.main_state.app_state.get_selection().as_ref());
+        if let [helix_1, helix_2] = helices[..] {
+            let xovers = self
+                .main_state
+                .app_state
+                .get_design_reader()
+                .xovers_between_helices(helix_1, helix_2, &parameters);
+            self.apply_operation(DesignOperation::MakeSeveralXovers {
+                xovers,
+                doubled: false,
+            })
+        } else {
+            log::warn!("Exactly two helices must be selected to place cross-overs");
+        }
+    }
+
     fn flip_split_views(&mut self) {
         self.notify_apps(Notification::FlipSplitViews)
     }
@@ -2024,6 +2674,26 @@ impl<'a> MainStateInteface for MainStateView<'a> {
             .modify_state(|app| app.with_expand_insertion_set(expand), None);
     }
 
+    fn set_isolate_selection(
+        &mut self,
+        isolate_selection: ensnano_interactor::graphics::IsolateSelection,
+    ) {
+        self.main_state
+            .modify_state(|app| app.with_isolate_selection(isolate_selection), None);
+        self.notify_apps(Notification::IsolateSelection(isolate_selection))
+    }
+
+    fn set_camera_mode(&mut self, camera_mode: ensnano_interactor::graphics::CameraMode) {
+        self.main_state
+            .modify_state(|app| app.with_camera_mode(camera_mode), None);
+        self.notify_apps(Notification::CameraModeChanged(camera_mode))
+    }
+
+    fn toggle_fly_mode(&mut self) {
+        let fly_mode = self.multiplexer.toggle_fly_mode();
+        self.notify_apps(Notification::FlyMode(fly_mode))
+    }
+
     fn set_exporting(&mut self, exporting: bool) {
         self.main_state
             .modify_state(|app| app.exporting(exporting), None)
@@ -2042,7 +2712,85 @@ impl<'a> MainStateInteface for MainStateView<'a> {
     }
 
     fn load_svg(&mut self, path: PathBuf) {
-        self.apply_operation(DesignOperation::ImportSvgPath { path });
+        use ensnano_design::{BezierPlaneDescriptor, SvgImportParameters, SvgImportPlane};
+        use ensnano_interactor::operation::SvgPathImport;
+        use ensnano_scene::DesignReader;
+        let available_planes = self
+            .main_state
+            .app_state
+            .get_design_reader()
+            .get_bezier_planes()
+            .keys()
+            .cloned()
+            .collect();
+        // By default, the paths are imported on a new plane facing the camera
+        let new_plane = self
+            .get_bezier_sheet_creation_position()
+            .map(|(position, orientation)| BezierPlaneDescriptor {
+                position,
+                orientation,
+                ..Default::default()
+            })
+            .unwrap_or_default();
+        self.main_state
+            .update_pending_operation(Arc::new(SvgPathImport {
+                file_path: path,
+                parameters: SvgImportParameters {
+                    plane: SvgImportPlane::New(new_plane.clone()),
+                    ..Default::default()
+                },
+                available_planes,
+                new_plane,
+                replace: false,
+            }));
+    }
+
+    fn load_oxdna_configuration(
+        &mut self,
+        path: PathBuf,
+    ) -> Result<(), ensnano_exports::oxdna::import::OxDnaImportError> {
+        let result = self
+            .main_state
+            .app_state
+            .apply_design_op(DesignOperation::ImportOxDnaConfiguration { path });
+        if let Err(ErrOperation::OxDnaImportError(err)) = result {
+            Err(err)
+        } else {
+            self.main_state.apply_operation_result(result);
+            Ok(())
+        }
+    }
+
+    fn set_hide_idealized_design(&mut self, hide: bool) {
+        self.main_state
+            .modify_state(|app| app.with_hide_idealized_design(hide), None)
+    }
+
+    fn load_reference_design(&mut self, path: PathBuf) -> Result<(), LoadDesignError> {
+        let reference = AppState::read_reference_design(&path)?;
+        self.main_state
+            .modify_state(|app| app.with_reference_design(Some(reference)), None);
+        Ok(())
+    }
+
+    fn remove_reference_design(&mut self) {
+        self.main_state
+            .modify_state(|app| app.with_reference_design(None), None)
+    }
+
+    fn set_reference_design_visibility(&mut self, visible: bool) {
+        self.main_state
+            .modify_state(|app| app.with_reference_design_visibility(visible), None)
+    }
+
+    fn set_reference_design_isometry(&mut self, isometry: ultraviolet::Isometry3) {
+        self.main_state
+            .modify_state(|app| app.with_reference_design_isometry(isometry), None)
+    }
+
+    fn set_show_design_changes(&mut self, show: bool) {
+        self.main_state
+            .modify_state(|app| app.with_design_changes_shown(show), None)
     }
 }
 
@@ -2052,6 +2800,7 @@ use crate::controller::TargetScaffoldLength;
 impl<'a> controller::ScaffoldSetter for MainStateView<'a> {
     fn set_scaffold_sequence(
         &mut self,
+        scaffold_id: usize,
         sequence: String,
         shift: usize,
     ) -> Result<SetScaffoldSequenceOk, SetScaffoldSequenceError> {
@@ -2059,16 +2808,19 @@ impl<'a> controller::ScaffoldSetter for MainStateView<'a> {
         match self
             .main_state
             .app_state
-            .apply_design_op(DesignOperation::SetScaffoldSequence { sequence, shift })
-        {
+            .apply_design_op(DesignOperation::SetScaffoldSequence {
+                scaffold_id,
+                sequence,
+                shift,
+            }) {
             Ok(OkOperation::Undoable { state, label }) => {
                 self.main_state.save_old_state(state, label)
             }
             Ok(OkOperation::NotUndoable) => (),
             Err(e) => return Err(SetScaffoldSequenceError(format!("{:?}", e))),
         };
-        let default_shift = self.get_staple_downloader().default_shift();
-        let scaffold_length = self.get_scaffold_length().unwrap_or(0);
+        let default_shift = self.get_staple_downloader().default_shift(scaffold_id);
+        let scaffold_length = self.get_scaffold_length(scaffold_id).unwrap_or(0);
         let target_scaffold_length = if len == scaffold_length {
             TargetScaffoldLength::Ok
         } else {
@@ -2083,15 +2835,15 @@ impl<'a> controller::ScaffoldSetter for MainStateView<'a> {
         })
     }
 
-    fn optimize_shift(&mut self) {
-        self.main_state.optimize_shift();
+    fn optimize_shift(&mut self, scaffold_id: usize) {
+        self.main_state.optimize_shift(scaffold_id);
     }
 
-    fn get_scaffold_length(&self) -> Option<usize> {
-        use gui::AppState;
+    fn get_scaffold_length(&self, scaffold_id: usize) -> Option<usize> {
         self.main_state
             .app_state
-            .get_scaffold_info()
+            .get_design_reader()
+            .get_scaffold_info(scaffold_id)
             .map(|info| info.length)
     }
 }
@@ -2103,3 +2855,33 @@ where
     let tmp = obj.clone();
     *obj = update_func(tmp);
 }
+
+/// The message explaining that a bezier plane cannot be deleted because of the paths that have
+/// vertices on it.
+fn used_bezier_plane_message(
+    plane_id: ensnano_design::BezierPlaneId,
+    vertices: &[ensnano_design::BezierVertexId],
+) -> String {
+    let mut nb_vertices_per_path = std::collections::BTreeMap::new();
+    for vertex in vertices {
+        *nb_vertices_per_path
+            .entry(vertex.path_id.0)
+            .or_insert(0usize) += 1;
+    }
+    let dependents: Vec<String> = nb_vertices_per_path
+        .into_iter()
+        .map(|(path_id, nb_vertices)| {
+            let vertex = if nb_vertices > 1 {
+                "vertices"
+            } else {
+                "vertex"
+            };
+            format!("path {path_id} ({nb_vertices} {vertex})")
+        })
+        .collect();
+    format!(
+        "Cannot delete plane {}, it is used by {}",
+        plane_id.0,
+        dependents.join(", ")
+    )
+}
@@ -128,7 +128,7 @@ fn empty_selections_dont_pollute_undo_stack() {
 #[test]
 fn recolor_stapple_undoable() {
     let mut state = new_state();
-    state.apply_operation(DesignOperation::RecolorStaples);
+    state.apply_operation(DesignOperation::RecolorStaples(Default::default()));
     assert!(!state.undo_stack.is_empty())
 }
 
@@ -462,3 +462,182 @@ fn no_need_to_save_after_new_design() {
     main_state.update();
     assert!(!main_state.need_save(), "Need save after update");
 }
+
+#[test]
+fn jump_in_history_moves_skipped_states() {
+    let mut state = new_state();
+    let selections = [
+        vec![Selection::Strand(0, 0)],
+        vec![Selection::Strand(0, 1)],
+        vec![Selection::Strand(0, 2)],
+    ];
+    for selection in selections.iter() {
+        state.update_selection(selection.clone(), None);
+    }
+    state.jump_in_history(-2);
+    assert_eq!(
+        state.app_state.get_selection().as_ref().clone(),
+        selections[0]
+    );
+    assert_eq!(state.undo_stack.len(), 1);
+    assert_eq!(state.redo_stack.len(), 2);
+    state.redo();
+    assert_eq!(
+        state.app_state.get_selection().as_ref().clone(),
+        selections[1]
+    );
+    state.jump_in_history(1);
+    assert_eq!(
+        state.app_state.get_selection().as_ref().clone(),
+        selections[2]
+    );
+    assert!(state.redo_stack.is_empty());
+    // Jumping further than the history does nothing
+    state.jump_in_history(-4);
+    assert_eq!(
+        state.app_state.get_selection().as_ref().clone(),
+        selections[2]
+    );
+}
+
+#[test]
+fn design_is_saved_once_written() {
+    let mut main_state = new_state();
+    main_state.clear_app_state(pastable_design());
+    main_state.apply_operation(DesignOperation::RecolorStaples(Default::default()));
+    main_state.update();
+    assert!(main_state.need_save());
+
+    let path = std::env::temp_dir().join("ensnano_design_is_saved_once_written.ens");
+    let id = main_state.save_design(&path).unwrap();
+    assert!(main_state.is_saving_design());
+    assert!(main_state.need_save(), "Saved before being written");
+
+    wait_for_saves(&mut main_state);
+    assert!(matches!(main_state.save_results.remove(&id), Some(Ok(()))));
+    assert!(main_state.save_results.remove(&id).is_none());
+    assert!(!main_state.need_save());
+    assert_eq!(main_state.app_state.path_to_current_design(), Some(&path));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn saves_to_different_paths_are_all_written() {
+    let mut main_state = new_state();
+    main_state.clear_app_state(pastable_design());
+    let dir = std::env::temp_dir().join("ensnano_saves_to_different_paths");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path_a = dir.join("a.ens");
+    let path_b = dir.join("b.ens");
+
+    // The second request is made before the worker had a chance to write the first one
+    let id_a = main_state.save_design(&path_a).unwrap();
+    let id_b = main_state.save_design(&path_b).unwrap();
+
+    wait_for_saves(&mut main_state);
+    assert!(path_a.exists());
+    assert!(path_b.exists());
+    assert!(matches!(
+        main_state.save_results.remove(&id_a),
+        Some(Ok(()))
+    ));
+    assert!(matches!(
+        main_state.save_results.remove(&id_b),
+        Some(Ok(()))
+    ));
+    assert_eq!(main_state.app_state.path_to_current_design(), Some(&path_b));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Wait until all the designs requested to `main_state` have been written.
+fn wait_for_saves(main_state: &mut MainState) {
+    let start = Instant::now();
+    while main_state.is_saving_design() {
+        assert!(start.elapsed() < Duration::from_secs(10), "Save timed out");
+        for update in main_state.chanel_reader.get_updates() {
+            if let ChanelReaderUpdate::DesignSaved(saved) = update {
+                main_state.design_saved(saved);
+            }
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn save_results_are_reported_to_their_own_request() {
+    let mut main_state = new_state();
+    main_state.clear_app_state(pastable_design());
+    let dir = std::env::temp_dir().join("ensnano_save_results_by_request");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("a.ens");
+    let unwritable = dir.join("missing_directory").join("b.ens");
+
+    let id_ok = main_state.save_design(&path).unwrap();
+    let id_err = main_state.save_design(&unwritable).unwrap();
+    wait_for_saves(&mut main_state);
+
+    // The failure of the second save must not be reported to the first one
+    assert!(matches!(
+        main_state.save_results.remove(&id_ok),
+        Some(Ok(()))
+    ));
+    assert!(matches!(
+        main_state.save_results.remove(&id_err),
+        Some(Err(_))
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn superseded_saves_report_the_result_of_the_save_replacing_them() {
+    let mut main_state = new_state();
+    main_state.clear_app_state(pastable_design());
+    let path = std::env::temp_dir().join("ensnano_superseded_saves_report_a_result.ens");
+
+    // Depending on the worker, the first request is either written or superseded by the second
+    let first = main_state.save_design(&path).unwrap();
+    let second = main_state.save_design(&path).unwrap();
+    wait_for_saves(&mut main_state);
+
+    assert!(matches!(
+        main_state.save_results.remove(&first),
+        Some(Ok(()))
+    ));
+    assert!(matches!(
+        main_state.save_results.remove(&second),
+        Some(Ok(()))
+    ));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn undoing_a_reflection_restores_the_design() {
+    let mut main_state = new_state();
+    main_state.clear_app_state(pastable_design());
+    main_state.update();
+    let saved_design = |main_state: &MainState| {
+        let design = main_state
+            .app_state
+            .design_to_save(ensnano_design::SavingInformation {
+                camera: None,
+                fog: None,
+            });
+        serde_json::to_string(&design).unwrap()
+    };
+    let original = saved_design(&main_state);
+
+    main_state.apply_operation(DesignOperation::ReflectSelection {
+        helices: vec![],
+        grids: vec![ensnano_design::grid::GridId::FreeGrid(0)],
+        plane_origin: Vec3::unit_x(),
+        plane_normal: Vec3::new(1., 2., 0.5),
+    });
+    main_state.update();
+    assert_ne!(saved_design(&main_state), original);
+
+    main_state.undo();
+    main_state.update();
+    assert_eq!(saved_design(&main_state), original);
+}
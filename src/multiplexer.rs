@@ -34,6 +34,8 @@ use super::{Action, Requests};
 use crate::gui::UiSize;
 use crate::utils::texture::SampledTexture;
 use crate::PhySize;
+use ensnano_interactor::keymap::{KeyAction, KeyScope, Keymap};
+use ensnano_interactor::snapping;
 use ensnano_interactor::{ActionMode, SelectionMode};
 use iced_wgpu::wgpu;
 use iced_winit::winit;
@@ -85,6 +87,8 @@ pub struct Multiplexer {
     top_bar_split: usize,
     /// The pointer to the node that separtate the status bar from the scene.
     status_bar_split: usize,
+    /// The pointer to the node that separate the left panel from the scene.
+    left_pannel_split: usize,
     device: Rc<Device>,
     pipeline: Option<wgpu::RenderPipeline>,
     split_mode: SplitMode,
@@ -95,12 +99,25 @@ pub struct Multiplexer {
     pub icon: Option<CursorIcon>,
     element_3d: ElementType,
     element_2d: ElementType,
+    /// True if the 3D scene is in fly mode, in which case its movement keys are not captured.
+    fly_mode: bool,
+    /// True if the selection can be nudged in the 3D scene, in which case the arrows and
+    /// PageUp/PageDown keys are not captured.
+    nudge_enabled: bool,
+    /// True if a rotation angle can be typed in the 3D scene, in which case the digits, '.', '-'
+    /// and Enter keys are not captured.
+    angle_entry_enabled: bool,
+    keymap: Arc<Keymap>,
+    /// True while a file is dragged over the window, in which case the window is dimmed.
+    hovering_file: bool,
 }
 
 /// Maximum width of the left pannel.
 const MAX_LEFT_PANNEL_WIDTH: f64 = 200.;
 /// Maximum height of the status bar.
 const MAX_STATUS_BAR_HEIGHT: f64 = 50.;
+/// Factor applied to the colors of the window while a file is dragged over it.
+const HOVERED_FILE_DIMMING: f64 = 0.5;
 
 impl Multiplexer {
     /// Create a new multiplexer for a window with size `window_size`.
@@ -110,6 +127,7 @@ impl Multiplexer {
         device: Rc<Device>,
         requests: Arc<Mutex<Requests>>,
         ui_size: UiSize,
+        keymap: Arc<Keymap>,
     ) -> Self {
         let mut layout_manager = LayoutTree::new();
         let top_pannel_prop =
@@ -121,6 +139,7 @@ impl Multiplexer {
             MAX_LEFT_PANNEL_WIDTH * scale_factor,
             window_size.width as f64,
         );
+        let left_pannel_split = scene;
         let (left_pannel, scene) = layout_manager.vsplit(scene, left_pannel_prop, true);
         let scene_height = (1. - top_pannel_prop) * window_size.height as f64;
         let status_bar_prop = exact_proportion(MAX_STATUS_BAR_HEIGHT * scale_factor, scene_height);
@@ -153,6 +172,7 @@ impl Multiplexer {
             requests,
             status_bar_split,
             top_bar_split,
+            left_pannel_split,
             state: State::Normal {
                 mouse_position: PhysicalPosition::new(-1., -1.),
             },
@@ -161,6 +181,11 @@ impl Multiplexer {
             icon: None,
             element_2d: ElementType::FlatScene,
             element_3d: ElementType::Scene,
+            fly_mode: false,
+            nudge_enabled: false,
+            angle_entry_enabled: false,
+            keymap,
+            hovering_file: false,
         };
         ret.generate_textures();
         ret
@@ -204,6 +229,11 @@ impl Multiplexer {
         self.modifiers = modifiers
     }
 
+    /// Dim the window while a file is dragged over it, to show that it can be dropped.
+    pub fn set_hovering_file(&mut self, hovering_file: bool) {
+        self.hovering_file = hovering_file
+    }
+
     pub fn draw(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
@@ -247,6 +277,17 @@ impl Multiplexer {
             }],
             depth_stencil_attachment: None,
         });
+        let brightness = if self.hovering_file {
+            HOVERED_FILE_DIMMING
+        } else {
+            1.
+        };
+        render_pass.set_blend_constant(wgpu::Color {
+            r: brightness,
+            g: brightness,
+            b: brightness,
+            a: 1.,
+        });
         if self.window_size.width > 0 && self.window_size.height > 0 {
             for element in [
                 ElementType::TopBar,
@@ -492,92 +533,35 @@ impl Multiplexer {
                     },
                 ..
             } => {
-                captured = true;
-                match *key {
-                    VirtualKeyCode::Escape => {
-                        self.requests.lock().unwrap().action_mode = Some(ActionMode::Normal)
-                    }
-                    VirtualKeyCode::X if self.modifiers.alt() => {
-                        self.requests.lock().unwrap().keep_proceed.push_back(
-                            Action::MakeAllSuggestedXover {
-                                doubled: self.modifiers.shift(),
-                            },
-                        )
-                    }
-                    VirtualKeyCode::X => {
-                        self.requests.lock().unwrap().toggle_thick_helices = Some(());
-                    }
-                    VirtualKeyCode::Z if ctrl(&self.modifiers) => {
-                        if self.modifiers.shift() {
-                            self.requests.lock().unwrap().redo = Some(())
-                        } else {
-                            self.requests.lock().unwrap().undo = Some(());
-                        }
-                    }
-                    VirtualKeyCode::R if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().redo = Some(());
-                    }
-                    VirtualKeyCode::C if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().copy = Some(());
-                    }
-                    VirtualKeyCode::V if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().paste = Some(());
-                    }
-                    VirtualKeyCode::J if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().duplication = Some(());
-                    }
-                    VirtualKeyCode::L if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().anchor = Some(());
-                    }
-                    VirtualKeyCode::R if !ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().action_mode = Some(ActionMode::Rotate)
-                    }
-                    VirtualKeyCode::T => {
-                        self.requests.lock().unwrap().action_mode = Some(ActionMode::Translate)
-                    }
-                    VirtualKeyCode::N => {
-                        self.requests.lock().unwrap().selection_mode =
-                            Some(SelectionMode::Nucleotide)
-                    }
-                    VirtualKeyCode::H => {
-                        self.requests.lock().unwrap().selection_mode = Some(SelectionMode::Helix)
-                    }
-                    VirtualKeyCode::S if ctrl(&self.modifiers) => {
-                        self.requests.lock().unwrap().save_shortcut = Some(());
-                    }
-                    VirtualKeyCode::O if ctrl(&self.modifiers) => {
-                        self.requests
-                            .lock()
-                            .unwrap()
-                            .keep_proceed
-                            .push_back(Action::LoadDesign(None));
-                    }
-                    VirtualKeyCode::Q if ctrl(&self.modifiers) && cfg!(target_os = "macos") => {
-                        self.requests
-                            .lock()
-                            .unwrap()
-                            .keep_proceed
-                            .push_back(Action::Exit);
-                    }
-                    keycode if keycode_to_num(keycode).is_some() => {
-                        let n_camera = keycode_to_num(keycode).unwrap();
-                        self.requests
-                            .lock()
-                            .unwrap()
-                            .keep_proceed
-                            .push_back(Action::SelectFavoriteCamera(n_camera));
-                    }
-                    VirtualKeyCode::S => {
-                        self.requests.lock().unwrap().selection_mode = Some(SelectionMode::Strand)
-                    }
-                    VirtualKeyCode::K => {
-                        self.requests.lock().unwrap().recolor_stapples = Some(());
-                    }
-                    VirtualKeyCode::Delete | VirtualKeyCode::Back => {
-                        self.requests.lock().unwrap().delete_selection = Some(());
-                    }
-                    _ => captured = false,
-                }
+                let fly_key = matches!(
+                    key,
+                    VirtualKeyCode::W
+                        | VirtualKeyCode::A
+                        | VirtualKeyCode::S
+                        | VirtualKeyCode::D
+                        | VirtualKeyCode::Q
+                        | VirtualKeyCode::E
+                );
+                let nudge_key = matches!(
+                    key,
+                    VirtualKeyCode::Left
+                        | VirtualKeyCode::Right
+                        | VirtualKeyCode::Up
+                        | VirtualKeyCode::Down
+                        | VirtualKeyCode::PageUp
+                        | VirtualKeyCode::PageDown
+                );
+                let angle_key = snapping::angle_entry_char(*key).is_some()
+                    || snapping::is_angle_entry_validation(*key);
+                // In fly mode, these keys move the camera of the 3D scene
+                let fly_movement = fly_key && self.fly_mode && !ctrl(&self.modifiers);
+                // When helices or grids can be nudged, these keys move them
+                let nudge = nudge_key && self.nudge_enabled;
+                // When the rotation widget is shown, these keys type a rotation angle
+                let angle_entry = angle_key && self.angle_entry_enabled && !ctrl(&self.modifiers);
+                captured = !((fly_movement || nudge || angle_entry)
+                    && self.focus == Some(ElementType::Scene))
+                    && self.process_shortcut(*key);
             }
             _ => {}
         }
@@ -628,6 +612,91 @@ impl Multiplexer {
         }
     }
 
+    /// Toggle the fly mode of the 3D scene and return the new value.
+    /// Trigger the global action bound to `key` in the keymap. Return true if the key was
+    /// captured.
+    fn process_shortcut(&mut self, key: VirtualKeyCode) -> bool {
+        let action =
+            if let Some(action) = self.keymap.action(key, &self.modifiers, KeyScope::Global) {
+                action
+            } else if let Some(n_camera) = keycode_to_num(key) {
+                let action = if ctrl(&self.modifiers) {
+                    Action::GoToNthCamera(n_camera)
+                } else {
+                    Action::SelectFavoriteCamera(n_camera)
+                };
+                self.requests.lock().unwrap().keep_proceed.push_back(action);
+                return true;
+            } else {
+                return false;
+            };
+        let mut requests = self.requests.lock().unwrap();
+        match action {
+            KeyAction::ToggleFlyMode => {
+                if self.focus == Some(ElementType::Scene) {
+                    requests.keep_proceed.push_back(Action::ToggleFlyMode)
+                } else {
+                    return false;
+                }
+            }
+            KeyAction::NormalMode => requests.action_mode = Some(ActionMode::Normal),
+            KeyAction::MakeXovers => requests
+                .keep_proceed
+                .push_back(Action::MakeAllSuggestedXover { doubled: false }),
+            KeyAction::MakeDoubledXovers => requests
+                .keep_proceed
+                .push_back(Action::MakeAllSuggestedXover { doubled: true }),
+            KeyAction::ToggleThickHelices => requests.toggle_thick_helices = Some(()),
+            KeyAction::Undo => requests.undo = Some(()),
+            KeyAction::Redo => requests.redo = Some(()),
+            KeyAction::Copy => requests.copy = Some(()),
+            KeyAction::CopySequences => requests.copy_sequences = Some(()),
+            KeyAction::Paste => requests.paste = Some(()),
+            KeyAction::Duplicate => requests.duplication = Some(()),
+            KeyAction::Anchor => requests.anchor = Some(()),
+            KeyAction::RotationMode => requests.action_mode = Some(ActionMode::Rotate),
+            KeyAction::TranslationMode => requests.action_mode = Some(ActionMode::Translate),
+            KeyAction::NickMode => requests.action_mode = Some(ActionMode::Cut),
+            KeyAction::LigateMode => requests.action_mode = Some(ActionMode::Ligate),
+            KeyAction::SelectNucleotides => {
+                requests.selection_mode = Some(SelectionMode::Nucleotide)
+            }
+            KeyAction::SelectHelices => requests.selection_mode = Some(SelectionMode::Helix),
+            KeyAction::SelectStrands => requests.selection_mode = Some(SelectionMode::Strand),
+            KeyAction::Save => requests.save_shortcut = Some(()),
+            KeyAction::Open => requests.keep_proceed.push_back(Action::LoadDesign(None)),
+            KeyAction::QuickOpen => requests.quick_open = Some(()),
+            KeyAction::Quit => requests.keep_proceed.push_back(Action::Exit),
+            KeyAction::NextCamera => requests
+                .keep_proceed
+                .push_back(Action::SelectNextCamera { forward: true }),
+            KeyAction::PreviousCamera => requests
+                .keep_proceed
+                .push_back(Action::SelectNextCamera { forward: false }),
+            KeyAction::RecolorStaples => requests.recolor_stapples = Some(()),
+            KeyAction::DeleteSelection => requests.delete_selection = Some(()),
+            KeyAction::ToggleSplit => requests.split2d = Some(()),
+            // The actions of the 3D and 2D views are handled by their controllers
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn toggle_fly_mode(&mut self) -> bool {
+        self.fly_mode ^= true;
+        self.fly_mode
+    }
+
+    /// Set whether the arrows and PageUp/PageDown keys nudge the selection in the 3D scene.
+    pub fn set_nudge_enabled(&mut self, nudge_enabled: bool) {
+        self.nudge_enabled = nudge_enabled;
+    }
+
+    /// Set whether the digits, '.', '-' and Enter keys type a rotation angle in the 3D scene.
+    pub fn set_angle_entry_enabled(&mut self, angle_entry_enabled: bool) {
+        self.angle_entry_enabled = angle_entry_enabled;
+    }
+
     pub fn toggle_2d(&mut self) {
         log::info!("Toggle 2d");
         if log::log_enabled!(log::Level::Info) {
@@ -651,6 +720,26 @@ impl Multiplexer {
         self.generate_textures();
     }
 
+    pub fn get_split_mode(&self) -> SplitMode {
+        self.split_mode
+    }
+
+    /// Return true if the stereographic view is shown instead of the 2D view.
+    pub fn is_showing_stereographic_2d(&self) -> bool {
+        self.element_2d == ElementType::StereographicScene
+    }
+
+    /// The proportion of the width of the window attributed to the left panel.
+    pub fn get_left_panel_proportion(&self) -> Option<f64> {
+        self.layout_manager.get_proportion(self.left_pannel_split)
+    }
+
+    pub fn set_left_panel_proportion(&mut self, proportion: f64) {
+        self.layout_manager
+            .resize(self.left_pannel_split, proportion.min(0.95).max(0.05));
+        self.generate_textures();
+    }
+
     pub fn change_split(&mut self, split_mode: SplitMode) {
         if split_mode != self.split_mode {
             self.change_split_(split_mode)
@@ -793,7 +882,15 @@ fn create_pipeline(device: &Device, bg_layout: &wgpu::BindGroupLayout) -> wgpu::
 
     let targets = &[wgpu::ColorTargetState {
         format: wgpu::TextureFormat::Bgra8UnormSrgb,
-        blend: Some(wgpu::BlendState::REPLACE),
+        // The colors are multiplied by the blend constant, which is used to dim the window
+        blend: Some(wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Constant,
+                dst_factor: wgpu::BlendFactor::Zero,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        }),
         write_mask: wgpu::ColorWrites::ALL,
     }];
 
@@ -0,0 +1,152 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! The list of the designs that were recently opened or saved.
+//!
+//! The list is stored in `recent_files.json` in the platform configuration directory. Several
+//! instances of ENSnano may update it at the same time: the list is read again before each
+//! update, and it is written in a temporary file that replaces the previous list once it is
+//! complete, so that the file is never left truncated.
+
+use crate::consts::APP_NAME;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// The maximum number of designs in the list
+pub const MAX_RECENT_FILES: usize = 10;
+
+const RECENT_FILES_FILE_NAME: &str = "recent_files.json";
+
+fn recent_files_path() -> Option<PathBuf> {
+    Some(
+        dirs::config_dir()?
+            .join(APP_NAME)
+            .join(RECENT_FILES_FILE_NAME),
+    )
+}
+
+/// Read the list of recent designs, the most recent one first.
+pub fn read() -> Vec<PathBuf> {
+    recent_files_path()
+        .map(|path| read_list(&path))
+        .unwrap_or_default()
+}
+
+/// Put `design_path` at the top of the list of recent designs and return the updated list.
+pub fn add(design_path: &Path) -> Vec<PathBuf> {
+    update(|list| with_recent_file(list, design_path))
+}
+
+/// Remove `design_path` from the list of recent designs and return the updated list.
+pub fn remove(design_path: &Path) -> Vec<PathBuf> {
+    update(|mut list| {
+        list.retain(|p| p != design_path);
+        list
+    })
+}
+
+fn update<F: FnOnce(Vec<PathBuf>) -> Vec<PathBuf>>(f: F) -> Vec<PathBuf> {
+    if let Some(path) = recent_files_path() {
+        let list = f(read_list(&path));
+        if let Err(e) = write_list(&list, &path) {
+            log::error!("Could not write the list of recent files: {e}");
+        }
+        list
+    } else {
+        f(Vec::new())
+    }
+}
+
+fn with_recent_file(mut list: Vec<PathBuf>, design_path: &Path) -> Vec<PathBuf> {
+    let design_path = design_path
+        .canonicalize()
+        .unwrap_or_else(|_| design_path.to_path_buf());
+    list.retain(|p| p != &design_path);
+    list.insert(0, design_path);
+    list.truncate(MAX_RECENT_FILES);
+    list
+}
+
+fn read_list(path: &Path) -> Vec<PathBuf> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| {
+            serde_json::from_str(&content)
+                .map_err(|e| log::error!("Ignoring invalid list of recent files: {e}"))
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// Write `list` to a temporary file that then replaces the file at `path`.
+fn write_list(list: &[PathBuf], path: &Path) -> std::io::Result<()> {
+    let json_content = serde_json::to_string_pretty(list)?;
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    let tmp = tmp_path(path);
+    let result = std::fs::write(&tmp, json_content).and_then(|_| std::fs::rename(&tmp, path));
+    if result.is_err() && tmp.exists() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// The path of the temporary file in which the list is written. It depends on the process so
+/// that two instances of ENSnano never write in the same temporary file.
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_files_are_moved_to_the_top() {
+        let list = vec![PathBuf::from("/a.ens"), PathBuf::from("/b.ens")];
+        let list = with_recent_file(list, Path::new("/b.ens"));
+        assert_eq!(list, vec![PathBuf::from("/b.ens"), PathBuf::from("/a.ens")]);
+    }
+
+    #[test]
+    fn recent_files_are_limited() {
+        let mut list = Vec::new();
+        for i in 0..(2 * MAX_RECENT_FILES) {
+            list = with_recent_file(list, &PathBuf::from(format!("/{i}.ens")));
+        }
+        assert_eq!(list.len(), MAX_RECENT_FILES);
+        assert_eq!(
+            list[0],
+            PathBuf::from(format!("/{}.ens", 2 * MAX_RECENT_FILES - 1))
+        );
+    }
+
+    #[test]
+    fn list_is_written_and_read() {
+        let dir = std::env::temp_dir().join("ensnano_recent_files");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join(RECENT_FILES_FILE_NAME);
+        let list = vec![PathBuf::from("/a.ens"), PathBuf::from("/b.ens")];
+        write_list(&list, &path).unwrap();
+        assert_eq!(read_list(&path), list);
+        assert!(!tmp_path(&path).exists());
+    }
+}
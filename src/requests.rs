@@ -28,7 +28,10 @@ mod poll;
 use super::gui::UiSize;
 use super::*;
 use ensnano_interactor::{application::AppId, RollRequest, Selection};
-use ensnano_interactor::{graphics::HBoundDisplay, UnrootedRevolutionSurfaceDescriptor};
+use ensnano_interactor::{
+    graphics::{Colormap, HBondFilter, HBoundDisplay, StrandColoring},
+    UnrootedRevolutionSurfaceDescriptor,
+};
 use ensnano_interactor::{CenterOfSelection, CheckXoversParameter};
 pub(crate) use poll::poll_all;
 use ultraviolet::Vec3;
@@ -64,12 +67,28 @@ pub struct Requests {
     pub file_save: Option<()>,
     /// A request to change the color of the selcted strand
     pub strand_color_change: Option<u32>,
-    /// A request to change the sequence of the selected strand
-    pub sequence_change: Option<String>,
+    /// A request to change the sequence of the selected strand, and whether the sequence must be
+    /// applied even if it does not match the scaffold
+    pub sequence_change: Option<(String, bool)>,
     /// A request to show/hide the sequences
     pub toggle_text: Option<bool>,
     /// A request to change the sensitivity of scrolling
     pub scroll_sensitivity: Option<f32>,
+    /// A request to change the duration of the animated camera movements
+    pub camera_animation_duration: Option<f32>,
+    /// A request to change the number of minutes between two backups of the design
+    pub autosave_interval: Option<f32>,
+    /// A request to change the number of operations listed in the history panel
+    pub undo_history_length: Option<usize>,
+    pub set_remember_workspace: Option<bool>,
+    /// A request to display the distances and angle between the selected nucleotides
+    pub measurement_info: Option<Option<String>>,
+    /// A request to display the grid position under the cursor
+    pub pointed_grid_position: Option<Option<GridPosition>>,
+    /// The progress of the export of the pictures taken from the saved cameras
+    pub cameras_export_progress: Option<Option<f32>>,
+    /// The path to which a png export of the 3D view was written, or the reason why it failed
+    pub png_export_result: Option<Result<PathBuf, String>>,
     pub make_grids: Option<()>,
     pub operation_update: Option<Arc<dyn Operation>>,
     pub toggle_persistent_helices: Option<bool>,
@@ -78,17 +97,28 @@ pub struct Requests {
     pub camera_rotation: Option<(f32, f32, f32)>,
     pub camera_target: Option<(Vec3, Vec3)>,
     pub small_spheres: Option<bool>,
-    pub set_scaffold_id: Option<Option<usize>>,
+    pub set_scaffold_role: Option<(usize, bool)>,
     pub recolor_stapples: Option<()>,
     pub roll_request: Option<RollRequest>,
     pub show_torsion_request: Option<bool>,
+    pub torsion_range_request: Option<Option<f32>>,
+    pub show_strain_request: Option<bool>,
+    pub show_domain_lengths_request: Option<bool>,
     pub fog: Option<FogParameters>,
+    /// A fog preset chosen with a keyboard shortcut in the 3D view
+    pub fog_preset: Option<FogParameters>,
     pub hyperboloid_update: Option<HyperboloidRequest>,
     pub new_hyperboloid: Option<HyperboloidRequest>,
     pub finalize_hyperboloid: Option<()>,
     pub cancel_hyperboloid: Option<()>,
+    /// New parameters for an existing hyperboloid grid
+    pub edited_hyperboloid: Option<(GridId, HyperboloidRequest)>,
     pub helix_roll: Option<f32>,
     pub copy: Option<()>,
+    /// A request to copy the sequences of the selected strands to the clipboard
+    pub copy_sequences: Option<()>,
+    /// A request to copy the summary of the design to the clipboard
+    pub copy_design_summary: Option<()>,
     pub paste: Option<()>,
     pub duplication: Option<()>,
     pub rigid_grid_simulation: Option<RigidBodyConstants>,
@@ -102,18 +132,24 @@ pub struct Requests {
     pub new_attribute: Option<(DnaAttribute, Vec<DnaElementKey>)>,
     pub new_tree: Option<OrganizerTree<DnaElementKey>>,
     pub split2d: Option<()>,
+    pub split3d: Option<()>,
     pub toggle_visibility: Option<bool>,
     pub all_visible: Option<()>,
     pub redim_2d_helices: Option<bool>,
+    pub compact_2d_layout: Option<()>,
+    pub place_pivot_on_selection: Option<()>,
     pub delete_selection: Option<()>,
     pub select_scaffold: Option<()>,
-    pub scaffold_shift: Option<usize>,
+    /// The identifier of a scaffold and its new shift
+    pub scaffold_shift: Option<(usize, usize)>,
     pub rendering_mode: Option<RenderingMode>,
     pub background3d: Option<Background3D>,
     pub undo: Option<()>,
     pub redo: Option<()>,
     pub save_shortcut: Option<()>,
     pub open_shortcut: Option<()>,
+    /// A request to show the list of recent designs
+    pub quick_open: Option<()>,
     pub force_help: Option<()>,
     pub show_tutorial: Option<()>,
     pub clean_requests: Option<()>,
@@ -133,6 +169,14 @@ pub struct Requests {
     pub follow_stereographic_camera: Option<bool>,
     pub set_show_stereographic_camera: Option<bool>,
     pub set_show_h_bonds: Option<HBoundDisplay>,
+    pub set_h_bond_filter: Option<HBondFilter>,
+    pub set_show_2d_minimap: Option<bool>,
+    pub set_show_anchors: Option<bool>,
+    pub set_full_detail: Option<bool>,
+    pub set_local_phantoms: Option<bool>,
+    pub set_show_nucleotide_ruler: Option<bool>,
+    pub set_strand_coloring: Option<StrandColoring>,
+    pub set_strand_colormap: Option<Colormap>,
     pub set_show_bezier_paths: Option<bool>,
     pub set_invert_y_scroll: Option<bool>,
     pub set_thick_helices: Option<bool>,
@@ -19,9 +19,21 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 //! Implements the [Requests](`crate::gui::Requests`) trait for [Requests](`super::Requests`).
 
 use crate::gui::{Requests as GuiRequests, RigidBodyParametersRequest};
-use ensnano_design::grid::GridId;
+use ensnano_design::{
+    design_operations::{
+        DuplicationPattern, RecolorPalette, RecolorParameters, RecolorScope, ScaffoldRoute,
+        ScaffoldRoutingParameters, StapleFillingParameters,
+    },
+    grid::GridId,
+    modifications::Modification,
+    External3DObjectAnchor, External3DObjectId,
+};
+use ensnano_interactor::graphics::{
+    CameraMode, Colormap, IsolateSelection, PngExportOptions, StrandColoring,
+};
 use ensnano_interactor::{InsertionPoint, RigidBodyConstants, RollRequest};
 use std::collections::BTreeSet;
+use std::path::PathBuf;
 
 use super::*;
 
@@ -64,6 +76,14 @@ impl GuiRequests for Requests {
         self.redim_2d_helices = Some(all);
     }
 
+    fn compact_2d_layout(&mut self) {
+        self.compact_2d_layout = Some(());
+    }
+
+    fn place_pivot_on_selection(&mut self) {
+        self.place_pivot_on_selection = Some(());
+    }
+
     fn make_all_elements_visible(&mut self) {
         self.all_visible = Some(());
     }
@@ -96,17 +116,37 @@ impl GuiRequests for Requests {
         self.keep_proceed.push_back(Action::DownloadStaplesRequest)
     }
 
-    fn set_selected_strand_sequence(&mut self, sequence: String) {
-        self.sequence_change = Some(sequence);
+    fn select_strand(&mut self, s_id: usize) {
+        self.new_selection = Some(vec![Selection::Strand(0, s_id as u32)]);
+    }
+
+    fn select_and_center(&mut self, selection: Selection) {
+        self.new_selection = Some(vec![selection]);
+        self.center_selection = Some((selection, AppId::Organizer));
+    }
+
+    fn check_xovers(&mut self, xovers: Vec<usize>) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::CheckXovers {
+                xovers,
+            }));
+    }
+
+    fn set_selected_strand_sequence(&mut self, sequence: String, force: bool) {
+        self.sequence_change = Some((sequence, force));
+    }
+
+    fn clear_selected_strands_sequence(&mut self) {
+        self.keep_proceed.push_back(Action::ClearSequence);
     }
 
-    fn set_scaffold_sequence(&mut self, shift: usize) {
+    fn set_scaffold_sequence(&mut self, scaffold_id: usize, shift: usize) {
         self.keep_proceed
-            .push_back(Action::SetScaffoldSequence { shift });
+            .push_back(Action::SetScaffoldSequence { scaffold_id, shift });
     }
 
-    fn set_scaffold_shift(&mut self, shift: usize) {
-        self.scaffold_shift = Some(shift);
+    fn set_scaffold_shift(&mut self, scaffold_id: usize, shift: usize) {
+        self.scaffold_shift = Some((scaffold_id, shift));
     }
 
     fn set_ui_size(&mut self, size: UiSize) {
@@ -152,6 +192,15 @@ impl GuiRequests for Requests {
         self.hyperboloid_update = Some(parameters);
     }
 
+    fn update_hyperboloid(&mut self, grid_id: GridId, parameters: HyperboloidRequest) {
+        self.edited_hyperboloid = Some((grid_id, parameters));
+    }
+
+    fn finish_hyperboloid_update(&mut self, grid_id: GridId) {
+        self.keep_proceed
+            .push_back(Action::FinishHyperboloidUpdate(grid_id));
+    }
+
     fn update_roll_of_selected_helices(&mut self, roll: f32) {
         self.helix_roll = Some(roll);
     }
@@ -160,6 +209,22 @@ impl GuiRequests for Requests {
         self.scroll_sensitivity = Some(sensitivity);
     }
 
+    fn update_camera_animation_duration(&mut self, duration: f32) {
+        self.camera_animation_duration = Some(duration);
+    }
+
+    fn update_autosave_interval(&mut self, interval: f32) {
+        self.autosave_interval = Some(interval);
+    }
+
+    fn update_undo_history_length(&mut self, length: usize) {
+        self.undo_history_length = Some(length);
+    }
+
+    fn set_remember_workspace(&mut self, remember: bool) {
+        self.set_remember_workspace = Some(remember);
+    }
+
     fn set_fog_parameters(&mut self, parameters: FogParameters) {
         self.fog = Some(parameters);
     }
@@ -168,6 +233,22 @@ impl GuiRequests for Requests {
         self.show_torsion_request = Some(visible);
     }
 
+    fn set_strain_visibility(&mut self, visible: bool) {
+        self.show_strain_request = Some(visible);
+    }
+
+    fn set_domain_lengths_visibility(&mut self, visible: bool) {
+        self.show_domain_lengths_request = Some(visible);
+    }
+
+    fn copy_design_summary(&mut self) {
+        self.copy_design_summary = Some(());
+    }
+
+    fn set_torsion_range(&mut self, range: Option<f32>) {
+        self.torsion_range_request = Some(range);
+    }
+
     fn set_camera_dir_up_vec(&mut self, direction: Vec3, up: Vec3) {
         self.camera_target = Some((direction, up));
     }
@@ -217,10 +298,19 @@ impl GuiRequests for Requests {
         self.keep_proceed.push_back(Action::Export(export_type))
     }
 
+    fn export_selection(&mut self, export_type: ExportType) {
+        self.keep_proceed
+            .push_back(Action::ExportSelection(export_type))
+    }
+
     fn toggle_2d_view_split(&mut self) {
         self.split2d = Some(());
     }
 
+    fn toggle_3d_view_split(&mut self) {
+        self.split3d = Some(());
+    }
+
     fn undo(&mut self) {
         self.undo = Some(());
     }
@@ -229,6 +319,10 @@ impl GuiRequests for Requests {
         self.redo = Some(());
     }
 
+    fn jump_in_history(&mut self, steps: isize) {
+        self.keep_proceed.push_back(Action::JumpInHistory(steps))
+    }
+
     fn force_help(&mut self) {
         self.force_help = Some(());
     }
@@ -253,6 +347,14 @@ impl GuiRequests for Requests {
         self.keep_proceed.push_back(Action::LoadDesign(None));
     }
 
+    fn toggle_quick_open(&mut self) {
+        self.quick_open = Some(());
+    }
+
+    fn open_recent_file(&mut self, path: PathBuf) {
+        self.keep_proceed.push_back(Action::LoadDesign(Some(path)));
+    }
+
     fn fit_design_in_scenes(&mut self) {
         self.fitting = Some(());
     }
@@ -270,8 +372,8 @@ impl GuiRequests for Requests {
         self.keep_proceed.push_back(Action::ErrorMsg(msg))
     }
 
-    fn set_scaffold_id(&mut self, s_id: Option<usize>) {
-        self.set_scaffold_id = Some(s_id);
+    fn set_scaffold_role(&mut self, s_id: usize, is_scaffold: bool) {
+        self.set_scaffold_role = Some((s_id, is_scaffold));
     }
 
     fn toggle_helices_persistance_of_grid(&mut self, persistant: bool) {
@@ -282,6 +384,15 @@ impl GuiRequests for Requests {
         self.small_spheres = Some(small);
     }
 
+    fn set_grid_visibility(&mut self, visible: bool) {
+        self.keep_proceed
+            .push_back(Action::SetGridVisibility(visible));
+    }
+
+    fn solo_selected_grids(&mut self) {
+        self.keep_proceed.push_back(Action::SoloGrids);
+    }
+
     fn finish_changing_color(&mut self) {
         self.keep_proceed.push_back(Action::FinishChangingColor);
     }
@@ -294,6 +405,15 @@ impl GuiRequests for Requests {
         self.keep_proceed.push_back(Action::ResetSimulation)
     }
 
+    fn step_simulation(&mut self) {
+        self.keep_proceed.push_back(Action::StepSimulation)
+    }
+
+    fn set_pinned_groups(&mut self, group_ids: Vec<ensnano_organizer::GroupId>) {
+        self.keep_proceed
+            .push_back(Action::SetPinnedGroups(group_ids))
+    }
+
     fn reload_file(&mut self) {
         self.keep_proceed.push_back(Action::ReloadFile)
     }
@@ -310,6 +430,142 @@ impl GuiRequests for Requests {
             }));
     }
 
+    fn set_strand_note(&mut self, s_id: usize, note: String) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::SetStrandNote {
+                s_id,
+                note: Some(note).filter(|n| !n.is_empty()),
+            }));
+    }
+
+    fn set_strand_keep_color(&mut self, s_id: usize, keep_color: bool) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::SetStrandKeepColor { s_id, keep_color },
+        ));
+    }
+
+    fn add_modification(&mut self, nucl: Nucl, modification: Modification) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::AddModification {
+                nucl,
+                modification,
+            }));
+    }
+
+    fn rm_modification(&mut self, nucl: Nucl) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::RmModifications {
+                nucls: vec![nucl],
+            }));
+    }
+
+    fn set_external_3d_object_visibility(&mut self, object_id: External3DObjectId, visible: bool) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::SetExternal3DObjectVisibility { object_id, visible },
+        ));
+    }
+
+    fn attach_external_3d_object(
+        &mut self,
+        object_id: External3DObjectId,
+        anchor: Option<External3DObjectAnchor>,
+    ) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::AttachExternal3DObject { object_id, anchor },
+        ));
+    }
+
+    fn duplicate_in_pattern(
+        &mut self,
+        helices: Vec<usize>,
+        strands: Vec<usize>,
+        pattern: DuplicationPattern,
+    ) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::DuplicateInPattern {
+                helices,
+                strands,
+                pattern,
+            },
+        ));
+    }
+
+    fn align_helices(&mut self, helices: Vec<usize>, reference: usize) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::AlignHelices {
+                helices,
+                reference,
+            }));
+    }
+
+    fn route_scaffold(&mut self, route: ScaffoldRoute, parameters: ScaffoldRoutingParameters) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::RouteScaffold {
+                route,
+                parameters,
+            }));
+    }
+
+    fn fill_staples(&mut self, parameters: StapleFillingParameters) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::FillStaples(
+                parameters,
+            )));
+    }
+
+    fn recolor_staples(&mut self, scope: RecolorScope, palette: RecolorPalette) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::RecolorStaples(
+                RecolorParameters {
+                    scope,
+                    palette,
+                    seed: rand::random(),
+                },
+            )));
+    }
+
+    fn set_helices_parameters(
+        &mut self,
+        helices: Vec<usize>,
+        parameters: Option<ensnano_design::Parameters>,
+    ) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::SetHelixParameters {
+                helices,
+                parameters,
+            },
+        ));
+    }
+
+    fn place_xovers_between_selected_helices(
+        &mut self,
+        parameters: ensnano_design::XoverPlacementParameters,
+    ) {
+        self.keep_proceed
+            .push_back(Action::PlaceXoversBetweenHelices(parameters));
+    }
+
+    fn anchor_selected_strands(&mut self, ends_only: bool) {
+        self.keep_proceed
+            .push_back(Action::AnchorSelectedStrands { ends_only });
+    }
+
+    fn set_group_color(&mut self, group_id: ensnano_organizer::GroupId, color: Option<u32>) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::SetGroupColor {
+                group_id,
+                color,
+            }));
+    }
+
+    fn set_group_locked(&mut self, group_id: ensnano_organizer::GroupId, locked: bool) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::SetGroupLocked {
+                group_id,
+                locked,
+            }));
+    }
+
     fn create_new_camera(&mut self) {
         self.keep_proceed.push_back(Action::NewCamera);
     }
@@ -343,6 +599,28 @@ impl GuiRequests for Requests {
             }))
     }
 
+    fn save_named_selection(&mut self, name: String) {
+        self.keep_proceed
+            .push_back(Action::SaveNamedSelection(name))
+    }
+
+    fn recall_named_selection(&mut self, idx: usize) {
+        self.keep_proceed
+            .push_back(Action::RecallNamedSelection(idx))
+    }
+
+    fn rename_named_selection(&mut self, idx: usize, name: String) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::RenameNamedSelection { idx, name },
+        ))
+    }
+
+    fn delete_named_selection(&mut self, idx: usize) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::DeleteNamedSelection(idx),
+        ))
+    }
+
     fn set_suggestion_parameters(&mut self, param: SuggestionParameters) {
         self.new_suggestion_parameters = Some(param);
     }
@@ -376,6 +654,20 @@ impl GuiRequests for Requests {
             }))
     }
 
+    fn set_grid_phantom_length(&mut self, grid_id: GridId, length: Option<usize>) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::SetGridPhantomLength { grid_id, length },
+        ))
+    }
+
+    fn set_grid_type(&mut self, grid_id: GridId, grid_type: GridTypeDescr) {
+        self.keep_proceed
+            .push_back(Action::DesignOperation(DesignOperation::SetGridType {
+                grid_id,
+                grid_type,
+            }))
+    }
+
     fn set_check_xover_parameters(&mut self, paramters: CheckXoversParameter) {
         self.check_xover_parameters = Some(paramters);
     }
@@ -402,6 +694,38 @@ impl GuiRequests for Requests {
         self.set_show_h_bonds = Some(show);
     }
 
+    fn set_h_bond_filter(&mut self, filter: HBondFilter) {
+        self.set_h_bond_filter = Some(filter);
+    }
+
+    fn set_show_2d_minimap(&mut self, show: bool) {
+        self.set_show_2d_minimap = Some(show);
+    }
+
+    fn set_show_anchors(&mut self, show: bool) {
+        self.set_show_anchors = Some(show);
+    }
+
+    fn set_local_phantoms(&mut self, local_phantoms: bool) {
+        self.set_local_phantoms = Some(local_phantoms);
+    }
+
+    fn set_show_nucleotide_ruler(&mut self, show: bool) {
+        self.set_show_nucleotide_ruler = Some(show);
+    }
+
+    fn set_full_detail(&mut self, full_detail: bool) {
+        self.set_full_detail = Some(full_detail);
+    }
+
+    fn set_strand_coloring(&mut self, coloring: StrandColoring) {
+        self.set_strand_coloring = Some(coloring);
+    }
+
+    fn set_strand_colormap(&mut self, colormap: Colormap) {
+        self.set_strand_colormap = Some(colormap);
+    }
+
     fn set_show_bezier_paths(&mut self, show: bool) {
         self.set_show_bezier_paths = Some(show);
     }
@@ -431,6 +755,18 @@ impl GuiRequests for Requests {
             .push_back(Action::SetExpandInsertions(expand))
     }
 
+    fn set_isolate_selection(&mut self, isolate_selection: IsolateSelection) {
+        self.keep_proceed
+            .push_back(Action::NotifyApps(Notification::IsolateSelection(
+                isolate_selection,
+            )))
+    }
+
+    fn set_camera_mode(&mut self, camera_mode: CameraMode) {
+        self.keep_proceed
+            .push_back(Action::SetCameraMode(camera_mode))
+    }
+
     fn set_insertion_length(&mut self, insertion_point: InsertionPoint, length: usize) {
         self.keep_proceed.push_back(Action::DesignOperation(
             DesignOperation::SetInsertionLength {
@@ -456,6 +792,33 @@ impl GuiRequests for Requests {
         ))
     }
 
+    fn rename_bezier_plane(&mut self, plane_id: ensnano_design::BezierPlaneId, name: String) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::RenameBezierPlane { plane_id, name },
+        ))
+    }
+
+    fn delete_bezier_plane(&mut self, plane_id: ensnano_design::BezierPlaneId) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::DeleteBezierPlane { plane_id },
+        ))
+    }
+
+    fn duplicate_bezier_plane(
+        &mut self,
+        plane_id: ensnano_design::BezierPlaneId,
+        offset: Vec3,
+        copy_paths: bool,
+    ) {
+        self.keep_proceed.push_back(Action::DesignOperation(
+            DesignOperation::DuplicateBezierPlane {
+                plane_id,
+                offset,
+                copy_paths,
+            },
+        ))
+    }
+
     fn set_exporting(&mut self, exporting: bool) {
         self.keep_proceed.push_back(Action::SetExporting(exporting))
     }
@@ -477,8 +840,9 @@ impl GuiRequests for Requests {
         ))
     }
 
-    fn optimize_scaffold_shift(&mut self) {
-        self.keep_proceed.push_back(Action::OptimizeShift)
+    fn optimize_scaffold_shift(&mut self, scaffold_id: usize) {
+        self.keep_proceed
+            .push_back(Action::OptimizeShift { scaffold_id })
     }
 
     fn start_revolution_relaxation(&mut self, desc: RevolutionSurfaceSystemDescriptor) {
@@ -495,6 +859,37 @@ impl GuiRequests for Requests {
         self.keep_proceed.push_back(Action::ImportSvg)
     }
 
+    fn import_oxdna_configuration(&mut self) {
+        self.keep_proceed
+            .push_back(Action::ImportOxDnaConfiguration)
+    }
+
+    fn set_hide_idealized_design(&mut self, hide: bool) {
+        self.keep_proceed
+            .push_back(Action::SetHideIdealizedDesign(hide))
+    }
+
+    fn load_reference_design(&mut self) {
+        self.keep_proceed.push_back(Action::ImportReferenceDesign)
+    }
+
+    fn remove_reference_design(&mut self) {
+        self.keep_proceed.push_back(Action::RemoveReferenceDesign)
+    }
+
+    fn set_reference_design_visibility(&mut self, visible: bool) {
+        self.keep_proceed
+            .push_back(Action::SetReferenceDesignVisibility(visible))
+    }
+
+    fn set_reference_design_isometry(&mut self, isometry: ultraviolet::Isometry3) {
+        self.keep_proceed
+            .push_back(Action::SetReferenceDesignIsometry(isometry))
+    }
+
+    fn set_show_design_changes(&mut self, show: bool) {
+        self.keep_proceed.push_back(Action::ShowDesignChanges(show))
+    }
     fn set_bezier_revolution_id(&mut self, id: Option<usize>) {
         self.new_bezier_revolution_id = Some(id);
     }
@@ -503,9 +898,19 @@ impl GuiRequests for Requests {
         self.new_bezier_revolution_radius = Some(radius);
     }
 
-    fn request_screenshot_3d(&mut self) {
+    fn request_screenshot_3d(&mut self, options: PngExportOptions) {
         self.keep_proceed
-            .push_back(Action::NotifyApps(Notification::ScreenShot3D))
+            .push_back(Action::NotifyApps(Notification::ScreenShot3D(options)))
+    }
+
+    fn request_export_all_cameras(&mut self) {
+        self.keep_proceed.push_back(Action::ExportAllCameras)
+    }
+
+    fn toggle_orthographic_projection(&mut self) {
+        self.keep_proceed.push_back(Action::NotifyApps(
+            Notification::ToggleOrthographicProjection,
+        ))
     }
 
     fn set_unrooted_surface(&mut self, surface: Option<UnrootedRevolutionSurfaceDescriptor>) {
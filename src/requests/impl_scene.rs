@@ -108,6 +108,10 @@ impl SceneRequests for Requests {
         self.keep_proceed.push_back(Action::SetGroupPivot(pivot))
     }
 
+    fn place_group_pivot(&mut self, pivot: ensnano_design::group_attributes::GroupPivot) {
+        self.keep_proceed.push_back(Action::PlaceGroupPivot(pivot))
+    }
+
     fn translate_group_pivot(&mut self, translation: Vec3) {
         if let Some(Action::TranslateGroupPivot(t)) = self.keep_proceed.iter_mut().last() {
             *t = translation
@@ -129,4 +133,24 @@ impl SceneRequests for Requests {
     fn set_revolution_axis_position(&mut self, position: f32) {
         self.new_bezier_revolution_axis_position = Some(position as f64);
     }
+
+    fn apply_fog_preset(&mut self, fog: FogParameters) {
+        self.fog_preset = Some(fog);
+    }
+
+    fn set_measurement_info(&mut self, info: Option<String>) {
+        self.measurement_info = Some(info);
+    }
+
+    fn set_pointed_grid_position(&mut self, position: Option<GridPosition>) {
+        self.pointed_grid_position = Some(position);
+    }
+
+    fn set_cameras_export_progress(&mut self, progress: Option<f32>) {
+        self.cameras_export_progress = Some(progress);
+    }
+
+    fn png_export_finished(&mut self, result: Result<PathBuf, String>) {
+        self.png_export_result = Some(result);
+    }
 }
@@ -21,8 +21,12 @@ ENSnano, a 3d graphical application for DNA nanostructures.
 
 use super::*;
 use crate::PastePosition;
+use ensnano_design::design_operations::RecolorParameters;
 
-use ensnano_interactor::{application::Notification, HyperboloidOperation, SelectionConversion};
+use ensnano_interactor::{
+    application::{Notification, PivotPlacement},
+    HyperboloidOperation, SelectionConversion,
+};
 
 use std::ops::DerefMut;
 pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
@@ -61,8 +65,8 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.change_double_strand_parameters(double_strand_parameters)
     }
 
-    if let Some(sequence) = requests.sequence_change.take() {
-        main_state.push_action(Action::ChangeSequence(sequence))
+    if let Some((sequence, force)) = requests.sequence_change.take() {
+        main_state.push_action(Action::ChangeSequence { sequence, force })
     }
 
     if let Some(color) = requests.strand_color_change.take() {
@@ -73,6 +77,45 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.set_scroll_sensitivity(sensitivity)
     }
 
+    if let Some(duration) = requests.camera_animation_duration.take() {
+        main_state.set_camera_animation_duration(duration)
+    }
+
+    if let Some(interval) = requests.autosave_interval.take() {
+        main_state.set_autosave_interval(interval)
+    }
+
+    if let Some(length) = requests.undo_history_length.take() {
+        main_state.set_undo_history_length(length)
+    }
+
+    if let Some(remember) = requests.set_remember_workspace.take() {
+        main_state.set_remember_workspace(remember)
+    }
+
+    if let Some(info) = requests.measurement_info.take() {
+        main_state.set_measurement_info(info)
+    }
+
+    if let Some(position) = requests.pointed_grid_position.take() {
+        main_state.set_pointed_grid_position(position)
+    }
+
+    if let Some(progress) = requests.cameras_export_progress.take() {
+        main_state.set_cameras_export_progress(progress)
+    }
+
+    if let Some(result) = requests.png_export_result.take() {
+        match result {
+            Ok(path) => main_state
+                .messages
+                .lock()
+                .unwrap()
+                .push_message(format!("Exported to {}", path.to_string_lossy())),
+            Err(err) => main_state.push_action(Action::ErrorMsg(err)),
+        }
+    }
+
     if let Some(op) = requests.operation_update.take() {
         main_state.update_pending_operation(op);
     }
@@ -95,14 +138,17 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         )))
     }
 
-    if let Some(scaffold_id) = requests.set_scaffold_id.take() {
-        main_state.push_action(Action::DesignOperation(DesignOperation::SetScaffoldId(
-            scaffold_id,
-        )))
+    if let Some((s_id, is_scaffold)) = requests.set_scaffold_role.take() {
+        main_state.push_action(Action::SetScaffoldRole { s_id, is_scaffold })
     }
 
     if requests.recolor_stapples.take().is_some() {
-        main_state.push_action(Action::DesignOperation(DesignOperation::RecolorStaples))
+        main_state.push_action(Action::DesignOperation(DesignOperation::RecolorStaples(
+            RecolorParameters {
+                seed: rand::random(),
+                ..Default::default()
+            },
+        )))
     }
 
     if let Some(roll_request) = requests.roll_request.take() {
@@ -113,10 +159,31 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.push_action(Action::NotifyApps(Notification::ShowTorsion(b)))
     }
 
+    if let Some(range) = requests.torsion_range_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::TorsionRange(range)))
+    }
+
+    if let Some(b) = requests.show_strain_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::ShowStrain(b)))
+    }
+
+    if let Some(b) = requests.show_domain_lengths_request.take() {
+        main_state.push_action(Action::NotifyApps(Notification::ShowDomainLengths(b)))
+    }
+
     if let Some(fog) = requests.fog.take() {
         main_state.push_action(Action::Fog(fog))
     }
 
+    if let Some(fog) = requests.fog_preset.take() {
+        main_state
+            .messages
+            .lock()
+            .unwrap()
+            .push_fog_parameters(fog.clone());
+        main_state.push_action(Action::Fog(fog))
+    }
+
     if let Some(hyperboloid) = requests.new_hyperboloid.take() {
         main_state.push_action(Action::NewHyperboloid(hyperboloid))
     }
@@ -139,6 +206,12 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         ))
     }
 
+    if let Some((grid_id, request)) = requests.edited_hyperboloid.take() {
+        main_state.push_action(Action::DesignOperation(
+            DesignOperation::UpdateHyperboloid { grid_id, request },
+        ))
+    }
+
     if let Some(roll) = requests.helix_roll.take() {
         main_state.push_action(Action::RollHelices(roll))
     }
@@ -147,6 +220,14 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.push_action(Action::Copy)
     }
 
+    if requests.copy_sequences.take().is_some() {
+        main_state.push_action(Action::CopySequences)
+    }
+
+    if requests.copy_design_summary.take().is_some() {
+        main_state.push_action(Action::CopyDesignSummary)
+    }
+
     if requests.paste.take().is_some() {
         main_state.push_action(Action::InitPaste);
         requests.duplication = None;
@@ -190,10 +271,10 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.update_selection(selection, g_id);
     }
 
-    if let Some(c) = requests.organizer_candidates.take() {
-        let candidates = c.into_iter().map(|e| e.to_selection(0)).collect();
-        main_state.update_candidates(candidates);
-    }
+    let organizer_candidates = requests
+        .organizer_candidates
+        .take()
+        .map(|c| c.into_iter().map(|e| e.to_selection(0)).collect());
 
     if let Some((attribute, elements)) = requests.new_attribute.take() {
         main_state.push_action(Action::DesignOperation(DesignOperation::UpdateAttribute {
@@ -216,6 +297,10 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.push_action(Action::Split2D)
     }
 
+    if requests.split3d.take().is_some() {
+        main_state.push_action(Action::Split3D)
+    }
+
     if requests.all_visible.take().is_some() {
         main_state.push_action(Action::ClearVisibilitySieve)
     }
@@ -237,10 +322,11 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.push_action(Action::ScaffoldToSelection)
     }
 
-    if let Some(n) = requests.scaffold_shift.take() {
-        main_state.push_action(Action::DesignOperation(DesignOperation::SetScaffoldShift(
-            n,
-        )))
+    if let Some((scaffold_id, shift)) = requests.scaffold_shift.take() {
+        main_state.push_action(Action::DesignOperation(DesignOperation::SetScaffoldShift {
+            scaffold_id,
+            shift,
+        }))
     }
 
     if let Some(mode) = requests.rendering_mode.take() {
@@ -271,7 +357,13 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.messages.lock().unwrap().show_help()
     }
 
-    if let Some(candidates) = requests.new_candidates.take() {
+    if requests.quick_open.take().is_some() {
+        main_state.messages.lock().unwrap().toggle_quick_open()
+    }
+
+    // Only one set of candidates is applied, the candidates requested by the 2D and 3D views
+    // taking precedence over the ones requested by the organizer.
+    if let Some(candidates) = requests.new_candidates.take().or(organizer_candidates) {
         main_state.update_candidates(candidates);
     }
 
@@ -308,6 +400,20 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
             )))
     }
 
+    if requests.compact_2d_layout.take().is_some() {
+        main_state
+            .pending_actions
+            .push_back(Action::NotifyApps(Notification::Compact2dLayout))
+    }
+
+    if requests.place_pivot_on_selection.take().is_some() {
+        main_state
+            .pending_actions
+            .push_back(Action::NotifyApps(Notification::PlaceGroupPivot(
+                PivotPlacement::SelectionCentroid,
+            )))
+    }
+
     if let Some((selection, app_id)) = requests.center_selection.take() {
         main_state
             .pending_actions
@@ -354,6 +460,38 @@ pub(crate) fn poll_all<R: DerefMut<Target = Requests>>(
         main_state.set_show_h_bonds(b);
     }
 
+    if let Some(filter) = requests.set_h_bond_filter.take() {
+        main_state.set_h_bond_filter(filter);
+    }
+
+    if let Some(b) = requests.set_show_2d_minimap.take() {
+        main_state.set_show_2d_minimap(b);
+    }
+
+    if let Some(b) = requests.set_show_anchors.take() {
+        main_state.set_show_anchors(b);
+    }
+
+    if let Some(b) = requests.set_local_phantoms.take() {
+        main_state.set_local_phantoms(b);
+    }
+
+    if let Some(b) = requests.set_show_nucleotide_ruler.take() {
+        main_state.set_show_nucleotide_ruler(b);
+    }
+
+    if let Some(b) = requests.set_full_detail.take() {
+        main_state.set_full_detail(b);
+    }
+
+    if let Some(coloring) = requests.set_strand_coloring.take() {
+        main_state.set_strand_coloring(coloring);
+    }
+
+    if let Some(colormap) = requests.set_strand_colormap.take() {
+        main_state.set_strand_colormap(colormap);
+    }
+
     if let Some(b) = requests.set_show_bezier_paths.take() {
         main_state.set_show_bezier_paths(b);
     }
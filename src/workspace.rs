@@ -0,0 +1,131 @@
+/*
+ENSnano, a 3d graphical application for DNA nanostructures.
+    Copyright (C) 2021  Nicolas Levy <nicolaspierrelevy@gmail.com> and Nicolas Schabanel <nicolas.schabanel@ens-lyon.fr>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Per-design workspaces.
+//!
+//! The layout of the application is written next to the design file, in
+//! `<name>.ensworkspace.json`, when the design is saved and when the application is closed. It
+//! is restored the next time the design is opened.
+
+use crate::consts::ENS_WORKSPACE_EXTENSION;
+use ensnano_interactor::application::{Camera2D, Camera3D};
+use ensnano_interactor::graphics::SplitMode;
+use ensnano_interactor::{ActionMode, SelectionMode};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The version of the workspace format. Workspaces written with an other version are ignored.
+pub const WORKSPACE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub version: u32,
+    /// The path of the design for which the workspace was written
+    pub design_path: PathBuf,
+    pub split_mode: SplitMode,
+    /// True if the stereographic view is shown instead of the 2D view
+    pub stereographic_2d: bool,
+    /// True if the 2D view is split in two
+    pub split_2d: bool,
+    /// The cameras of the top and bottom views of the 2D scene
+    pub cameras_2d: Option<[Camera2D; 2]>,
+    pub camera_3d: Camera3D,
+    /// The proportion of the width of the window attributed to the left panel
+    pub left_panel_proportion: Option<f64>,
+    pub selection_mode: SelectionMode,
+    pub action_mode: ActionMode,
+}
+
+impl Workspace {
+    /// Return the path of the workspace of the design stored at `design_path`.
+    pub fn path(design_path: &Path) -> PathBuf {
+        design_path.with_extension(ENS_WORKSPACE_EXTENSION)
+    }
+
+    /// Read the workspace of the design stored at `design_path`.
+    ///
+    /// Return `None` if there is no such workspace, or if it cannot be used for this design.
+    pub fn load(design_path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path(design_path)).ok()?;
+        Self::from_json(&content, design_path)
+    }
+
+    fn from_json(content: &str, design_path: &Path) -> Option<Self> {
+        let workspace: Self = serde_json::from_str(content)
+            .map_err(|e| log::info!("Ignoring invalid workspace: {e}"))
+            .ok()?;
+        (workspace.version == WORKSPACE_VERSION && workspace.design_path == design_path)
+            .then_some(workspace)
+    }
+
+    /// Write the workspace next to the design for which it was created.
+    pub fn save(&self) -> std::io::Result<()> {
+        let json_content = serde_json::to_string_pretty(self)?;
+        std::fs::write(Self::path(&self.design_path), json_content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(design_path: &str) -> Workspace {
+        Workspace {
+            version: WORKSPACE_VERSION,
+            design_path: design_path.into(),
+            split_mode: SplitMode::Both,
+            stereographic_2d: false,
+            split_2d: true,
+            cameras_2d: None,
+            camera_3d: Default::default(),
+            left_panel_proportion: Some(0.3),
+            selection_mode: SelectionMode::Helix,
+            action_mode: ActionMode::Cut,
+        }
+    }
+
+    #[test]
+    fn workspace_is_restored_for_its_design() {
+        let json = serde_json::to_string(&workspace("/designs/origami.ens")).unwrap();
+        let restored = Workspace::from_json(&json, Path::new("/designs/origami.ens")).unwrap();
+        assert_eq!(restored.split_mode, SplitMode::Both);
+        assert_eq!(restored.selection_mode, SelectionMode::Helix);
+        assert_eq!(restored.action_mode, ActionMode::Cut);
+        assert_eq!(restored.left_panel_proportion, Some(0.3));
+    }
+
+    #[test]
+    fn invalid_workspaces_are_ignored() {
+        let json = serde_json::to_string(&workspace("/designs/origami.ens")).unwrap();
+        assert!(Workspace::from_json(&json, Path::new("/designs/other.ens")).is_none());
+        assert!(Workspace::from_json(&json[1..], Path::new("/designs/origami.ens")).is_none());
+
+        let mut old_workspace = workspace("/designs/origami.ens");
+        old_workspace.version = WORKSPACE_VERSION + 1;
+        let json = serde_json::to_string(&old_workspace).unwrap();
+        assert!(Workspace::from_json(&json, Path::new("/designs/origami.ens")).is_none());
+    }
+
+    #[test]
+    fn workspace_is_written_next_to_the_design() {
+        assert_eq!(
+            Workspace::path(Path::new("/designs/origami.ens")),
+            PathBuf::from("/designs/origami.ensworkspace.json")
+        );
+    }
+}